@@ -0,0 +1,272 @@
+//! Opt-in gameplay analytics for playtests.
+//!
+//! Scripts record events with `analytics.track(name, data)`. Nothing is
+//! recorded unless `analytics.enabled` is set in naive.yaml, and the game can
+//! switch tracking off at runtime (for a player's privacy setting) with
+//! `analytics.set_enabled(false)`. Events are batched and appended as JSON
+//! lines to `analytics/<session>.jsonl` under the project root, or posted to
+//! `analytics.endpoint`. A batch the endpoint doesn't take is written to the
+//! local file instead, so no playtest data is lost.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest event name.
+const MAX_EVENT_NAME: usize = 64;
+
+/// Analytics settings (`analytics:` in naive.yaml).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AnalyticsConfig {
+    /// Record events at all. Off unless the project opts in.
+    #[serde(default)]
+    pub enabled: bool,
+    /// URL to POST batches to; without one they are written to files.
+    pub endpoint: Option<String>,
+    /// Folder for the local files, relative to the project root.
+    #[serde(default = "default_directory")]
+    pub directory: String,
+    /// Events held before a batch is sent.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Longest time in seconds an event waits to be sent.
+    #[serde(default = "default_flush_interval")]
+    pub flush_interval: f32,
+}
+
+fn default_directory() -> String {
+    "analytics".to_string()
+}
+
+fn default_batch_size() -> usize {
+    50
+}
+
+fn default_flush_interval() -> f32 {
+    30.0
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: None,
+            directory: default_directory(),
+            batch_size: default_batch_size(),
+            flush_interval: default_flush_interval(),
+        }
+    }
+}
+
+/// One tracked event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalyticsEvent {
+    pub event: String,
+    /// Seconds since the session started.
+    pub time: f64,
+    pub data: HashMap<String, serde_json::Value>,
+}
+
+/// Check an event name: 1 to 64 letters, digits, `_`, `.` or `-`.
+pub fn check_event_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_EVENT_NAME {
+        return Err(format!("event names are 1 to {} characters long", MAX_EVENT_NAME));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')) {
+        return Err(format!("event name '{}' may only use letters, digits, '_', '.' and '-'", name));
+    }
+    Ok(())
+}
+
+/// The session's event sink.
+pub struct Analytics {
+    config: AnalyticsConfig,
+    enabled: bool,
+    file: PathBuf,
+    game: String,
+    version: String,
+    session: String,
+    elapsed: f64,
+    since_flush: f32,
+    pending: Vec<AnalyticsEvent>,
+}
+
+impl Analytics {
+    /// Start a session for the game `game` at `version`.
+    pub fn new(config: &AnalyticsConfig, project_root: &Path, game: &str, version: &str) -> Self {
+        let session = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        if config.enabled {
+            match &config.endpoint {
+                Some(endpoint) => tracing::info!("Analytics on: session {} posting to {}", session, endpoint),
+                None => tracing::info!("Analytics on: session {} writing to {}/", session, config.directory),
+            }
+        }
+        Self {
+            file: project_root.join(&config.directory).join(format!("{}.jsonl", session)),
+            config: config.clone(),
+            enabled: config.enabled,
+            game: game.to_string(),
+            version: version.to_string(),
+            session,
+            elapsed: 0.0,
+            since_flush: 0.0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Whether events are being recorded.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Switch tracking on or off for the rest of the session. Only works in
+    /// projects that enable analytics; switching off drops unsent events.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled && self.config.enabled;
+        if !self.enabled {
+            self.pending.clear();
+        }
+    }
+
+    /// Session id, the local time the session started.
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Record an event. Returns false when tracking is off.
+    pub fn track(&mut self, name: &str, data: HashMap<String, serde_json::Value>) -> Result<bool, String> {
+        check_event_name(name)?;
+        if !self.enabled {
+            return Ok(false);
+        }
+        self.pending.push(AnalyticsEvent { event: name.to_string(), time: self.elapsed, data });
+        if self.pending.len() >= self.config.batch_size.max(1) {
+            self.flush(false);
+        }
+        Ok(true)
+    }
+
+    /// Events waiting to be sent.
+    pub fn pending(&self) -> &[AnalyticsEvent] {
+        &self.pending
+    }
+
+    /// Advance the session clock; call once a frame with real time, so
+    /// batches go out on schedule while the game is paused.
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt as f64;
+        self.since_flush += dt;
+        if self.since_flush >= self.config.flush_interval && !self.pending.is_empty() {
+            self.flush(false);
+        }
+    }
+
+    /// Send the pending events. Posts run on a thread unless `blocking`.
+    pub fn flush(&mut self, blocking: bool) {
+        self.since_flush = 0.0;
+        if self.pending.is_empty() {
+            return;
+        }
+        let events = std::mem::take(&mut self.pending);
+        let Some(endpoint) = self.config.endpoint.clone() else {
+            if let Err(e) = append_events(&self.file, &events) {
+                tracing::warn!("Analytics: {}", e);
+            }
+            return;
+        };
+
+        let batch = serde_json::json!({
+            "game": self.game,
+            "version": self.version,
+            "session": self.session,
+            "events": events,
+        });
+        let file = self.file.clone();
+        let post = move || {
+            let result = ureq::post(&endpoint)
+                .set("User-Agent", &format!("nAIVE-engine/{}", env!("CARGO_PKG_VERSION")))
+                .send_json(&batch);
+            if let Err(e) = result {
+                tracing::warn!("Analytics: posting to {} failed ({}), keeping the batch in {:?}", endpoint, e, file);
+                if let Err(e) = append_events(&file, &events) {
+                    tracing::warn!("Analytics: {}", e);
+                }
+            }
+        };
+        if blocking {
+            post();
+        } else {
+            std::thread::spawn(post);
+        }
+    }
+}
+
+impl Drop for Analytics {
+    fn drop(&mut self) {
+        self.flush(true);
+    }
+}
+
+/// Append events to a JSON lines file, creating its folder.
+fn append_events(path: &Path, events: &[AnalyticsEvent]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("couldn't create {:?}: {}", dir, e))?;
+    }
+    let mut lines = String::new();
+    for event in events {
+        let line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| format!("couldn't write {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_batch_to_a_session_file() {
+        let root = std::env::temp_dir().join(format!("naive-analytics-{}", std::process::id()));
+        let config = AnalyticsConfig { enabled: true, batch_size: 2, ..Default::default() };
+        let mut analytics = Analytics::new(&config, &root, "test", "0.1.0");
+        let path = root.join("analytics").join(format!("{}.jsonl", analytics.session()));
+
+        let data = HashMap::from([("time".to_string(), serde_json::json!(42.5))]);
+        assert_eq!(analytics.track("level_complete", data), Ok(true));
+        analytics.update(1.0);
+        assert_eq!(analytics.pending().len(), 1);
+        assert!(!path.exists());
+        // The second event fills the batch
+        assert_eq!(analytics.track("death", HashMap::new()), Ok(true));
+        assert!(analytics.pending().is_empty());
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = written.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "level_complete");
+        assert_eq!(lines[0]["data"]["time"], 42.5);
+        assert_eq!(lines[1]["time"], 1.0);
+
+        assert!(analytics.track("bad name", HashMap::new()).is_err());
+        // Switched off, nothing more is recorded
+        analytics.set_enabled(false);
+        assert_eq!(analytics.track("death", HashMap::new()), Ok(false));
+        analytics.set_enabled(true);
+        assert!(analytics.enabled());
+        drop(analytics);
+        let _ = std::fs::remove_dir_all(&root);
+
+        // Projects that don't opt in can't be switched on
+        let mut analytics = Analytics::new(&AnalyticsConfig::default(), &root, "test", "0.1.0");
+        analytics.set_enabled(true);
+        assert_eq!(analytics.track("death", HashMap::new()), Ok(false));
+    }
+}
@@ -1,11 +1,17 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use cpal::traits::{DeviceTrait, HostTrait};
 use glam::Vec3;
+use kira::manager::backend::cpal::CpalBackendSettings;
 use kira::manager::{AudioManager, AudioManagerSettings, DefaultBackend};
 use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle};
 use kira::sound::PlaybackState;
 use kira::tween::Tween;
+use serde::Deserialize;
+
+/// Seconds between checks for added and removed output devices.
+const DEVICE_POLL_INTERVAL: f32 = 2.0;
 
 /// Audio component for entities that emit spatial sound.
 #[derive(Debug, Clone)]
@@ -17,47 +23,255 @@ pub struct AudioSource {
     pub max_distance: f32,
 }
 
+/// Output device and buffering (`audio:` in naive.yaml, `audio.set_device`).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct AudioSettings {
+    /// Output device name, or a case-insensitive part of it. Unset follows
+    /// the system default device as it changes.
+    pub device: Option<String>,
+    /// Frames per buffer. Smaller buffers lower latency but may crackle on
+    /// a busy machine; unset uses the driver's default.
+    pub buffer_size: Option<u32>,
+}
+
+/// Index of the device `wanted` names: an exact match, else the first name
+/// containing it, ignoring case.
+pub fn match_device(names: &[String], wanted: &str) -> Option<usize> {
+    let wanted_lower = wanted.to_lowercase();
+    names
+        .iter()
+        .position(|name| name == wanted)
+        .or_else(|| names.iter().position(|name| name.to_lowercase().contains(&wanted_lower)))
+}
+
+/// Names of the connected output devices.
+pub fn output_devices() -> Vec<String> {
+    match cpal::default_host().output_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to list audio devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Music kept to restart it when the output moves to another device.
+#[derive(Debug, Clone)]
+struct MusicTrack {
+    path: PathBuf,
+    volume: f32,
+}
+
+/// A volume change shaped by a curve, stepped every frame.
+#[derive(Debug, Clone)]
+struct VolumeFade {
+    /// Sound id, or None for the music.
+    sound: Option<String>,
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    /// Blend from `from` to `to` over the fade, played over the curve's key
+    /// range; a straight line without one.
+    curve: Option<crate::curve::Curve>,
+}
+
+impl VolumeFade {
+    fn volume(&self) -> f32 {
+        let t = if self.duration > 0.0 { (self.elapsed / self.duration).min(1.0) } else { 1.0 };
+        let blend = self.curve.as_ref().map_or(t, |curve| curve.eval_normalized(t));
+        self.from + (self.to - self.from) * blend
+    }
+}
+
 /// Central audio system wrapping Kira.
 pub struct AudioSystem {
     manager: Option<AudioManager>,
     /// Active sound handles keyed by a string identifier.
     sounds: HashMap<String, StaticSoundHandle>,
+    /// Volume each active sound was last set to, before the master volume.
+    sound_volumes: HashMap<String, f32>,
+    /// Curve-driven fades in progress.
+    fades: Vec<VolumeFade>,
     /// Music track handle.
     music: Option<StaticSoundHandle>,
+    music_track: Option<MusicTrack>,
     /// Listener position for spatial audio.
     listener_pos: Vec3,
     /// Master volume.
     master_volume: f32,
+    settings: AudioSettings,
+    /// Device the manager plays on (None while audio is off).
+    device_name: Option<String>,
+    /// Sample rate of that device, for the latency estimate.
+    sample_rate: Option<u32>,
+    last_device_poll: instant::Instant,
 }
 
 impl AudioSystem {
     pub fn new() -> Self {
+        Self::with_settings(AudioSettings::default())
+    }
+
+    pub fn with_settings(settings: AudioSettings) -> Self {
+        let mut audio = Self {
+            manager: None,
+            sounds: HashMap::new(),
+            sound_volumes: HashMap::new(),
+            fades: Vec::new(),
+            music: None,
+            music_track: None,
+            listener_pos: Vec3::ZERO,
+            master_volume: 1.0,
+            settings,
+            device_name: None,
+            sample_rate: None,
+            last_device_poll: instant::Instant::now(),
+        };
+        audio.open();
+        if audio.manager.is_some() {
+            tracing::info!(
+                "Audio system initialized (Kira) on '{}'",
+                audio.device_name.as_deref().unwrap_or("default device")
+            );
+        }
+        audio
+    }
+
+    /// (Re)open the output with the current settings. Sounds playing on the
+    /// old device stop; the music starts over on the new one.
+    fn open(&mut self) {
+        self.sounds.clear();
+        self.music = None;
+        self.manager = None;
+
+        let host = cpal::default_host();
+        // A chosen device is opened by name; otherwise Kira follows the default
+        let chosen = self.settings.device.as_deref().and_then(|wanted| {
+            let mut devices: Vec<cpal::Device> = host.output_devices().ok()?.collect();
+            let names: Vec<String> = devices.iter().map(|d| d.name().unwrap_or_default()).collect();
+            let found = match_device(&names, wanted).map(|i| devices.swap_remove(i));
+            if found.is_none() {
+                tracing::warn!("Audio device '{}' not found; using the default output", wanted);
+            }
+            found
+        });
+        let describe = |device: &cpal::Device| {
+            let rate = device.default_output_config().ok().map(|config| config.sample_rate().0);
+            (device.name().ok(), rate)
+        };
+        let described = match &chosen {
+            Some(device) => Some(describe(device)),
+            None => host.default_output_device().as_ref().map(describe),
+        };
+        let Some((name, rate)) = described else {
+            tracing::warn!("No audio output device. Audio disabled.");
+            self.device_name = None;
+            self.sample_rate = None;
+            return;
+        };
+        self.device_name = name;
+        self.sample_rate = rate;
+
         let settings = AudioManagerSettings::<DefaultBackend> {
             capacities: kira::manager::Capacities {
                 sound_capacity: 512,
                 command_capacity: 256,
                 ..Default::default()
             },
+            backend_settings: CpalBackendSettings {
+                device: chosen,
+                buffer_size: self.settings.buffer_size.map_or(cpal::BufferSize::Default, cpal::BufferSize::Fixed),
+            },
             ..Default::default()
         };
-        let manager = AudioManager::<DefaultBackend>::new(settings)
+        self.manager = AudioManager::<DefaultBackend>::new(settings)
             .map_err(|e| {
                 tracing::warn!("Failed to initialize audio: {}. Audio disabled.", e);
                 e
             })
             .ok();
 
-        if manager.is_some() {
-            tracing::info!("Audio system initialized (Kira)");
+        if let Some(track) = self.music_track.clone() {
+            if let Err(e) = self.start_music(&track.path, track.volume, 0.0) {
+                tracing::warn!("Music not restarted: {}", e);
+            }
         }
+    }
 
-        Self {
-            manager,
-            sounds: HashMap::new(),
-            music: None,
-            listener_pos: Vec3::ZERO,
-            master_volume: 1.0,
+    /// Apply new settings, reopening the output if they change it.
+    pub fn configure(&mut self, settings: AudioSettings) {
+        if settings != self.settings {
+            self.settings = settings;
+            self.open();
+        }
+    }
+
+    pub fn settings(&self) -> &AudioSettings {
+        &self.settings
+    }
+
+    /// Name of the device sound plays on (None while audio is off).
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    /// Output latency of one buffer in milliseconds, when the buffer size is set.
+    pub fn latency_ms(&self) -> Option<f32> {
+        let (frames, rate) = (self.settings.buffer_size?, self.sample_rate?);
+        Some(frames as f32 * 1000.0 / rate as f32)
+    }
+
+    /// Follow devices being plugged in and out, at most every couple of
+    /// seconds: move off a chosen device that disappeared, back onto it when
+    /// it returns, and start audio once a device shows up. Kira itself
+    /// follows changes of the default device. Returns the new device name
+    /// when sound moved.
+    pub fn poll_devices(&mut self) -> Option<String> {
+        if self.last_device_poll.elapsed().as_secs_f32() < DEVICE_POLL_INTERVAL {
+            return None;
         }
+        self.last_device_poll = instant::Instant::now();
+
+        let host = cpal::default_host();
+        let reopen = match &self.settings.device {
+            Some(wanted) => {
+                let names = output_devices();
+                let current = self.device_name.clone().unwrap_or_default();
+                let on_chosen = match_device(std::slice::from_ref(&current), wanted).is_some();
+                let vanished = !names.contains(&current) && (self.manager.is_some() || !names.is_empty());
+                vanished || (!on_chosen && match_device(&names, wanted).is_some())
+            }
+            None => {
+                let default = host.default_output_device();
+                let default_name = default.as_ref().and_then(|d| d.name().ok());
+                if self.manager.is_some() {
+                    // Kira moved to the new default on its own
+                    if default_name.is_some() && default_name != self.device_name {
+                        self.sample_rate = default
+                            .as_ref()
+                            .and_then(|d| d.default_output_config().ok())
+                            .map(|config| config.sample_rate().0);
+                        self.device_name = default_name.clone();
+                        tracing::info!("Audio moved to '{}'", default_name.as_deref().unwrap_or_default());
+                        return default_name;
+                    }
+                    false
+                } else {
+                    default_name.is_some()
+                }
+            }
+        };
+        if !reopen {
+            return None;
+        }
+        let previous = self.device_name.clone();
+        self.open();
+        if self.device_name == previous {
+            return None;
+        }
+        tracing::info!("Audio moved to '{}'", self.device_name.as_deref().unwrap_or("no device"));
+        Some(self.device_name.clone().unwrap_or_default())
     }
 
     /// Update the listener position (typically the camera/player position).
@@ -87,6 +301,8 @@ impl AudioSystem {
             .map_err(|e| format!("Failed to play sound: {}", e))?;
 
         self.sounds.insert(id.to_string(), handle);
+        self.sound_volumes.insert(id.to_string(), volume);
+        self.fades.retain(|fade| fade.sound.as_deref() != Some(id));
         Ok(())
     }
 
@@ -98,6 +314,12 @@ impl AudioSystem {
         volume: f32,
         fade_in_secs: f32,
     ) -> Result<(), String> {
+        let full_path = project_root.join(path);
+        self.music_track = Some(MusicTrack { path: full_path.clone(), volume });
+        self.start_music(&full_path, volume, fade_in_secs)
+    }
+
+    fn start_music(&mut self, full_path: &Path, volume: f32, fade_in_secs: f32) -> Result<(), String> {
         let manager = match &mut self.manager {
             Some(m) => m,
             None => return Ok(()),
@@ -111,8 +333,7 @@ impl AudioSystem {
             });
         }
 
-        let full_path = project_root.join(path);
-        let sound_data = StaticSoundData::from_file(&full_path)
+        let sound_data = StaticSoundData::from_file(full_path)
             .map_err(|e| format!("Failed to load music {:?}: {}", full_path, e))?;
 
         let handle = manager
@@ -135,6 +356,8 @@ impl AudioSystem {
 
     /// Stop a specific sound.
     pub fn stop_sound(&mut self, id: &str, fade_out_secs: f32) {
+        self.sound_volumes.remove(id);
+        self.fades.retain(|fade| fade.sound.as_deref() != Some(id));
         if let Some(mut handle) = self.sounds.remove(id) {
             handle.stop(Tween {
                 duration: std::time::Duration::from_secs_f32(fade_out_secs),
@@ -145,6 +368,8 @@ impl AudioSystem {
 
     /// Stop music.
     pub fn stop_music(&mut self, fade_out_secs: f32) {
+        self.music_track = None;
+        self.fades.retain(|fade| fade.sound.is_some());
         if let Some(mut music) = self.music.take() {
             music.stop(Tween {
                 duration: std::time::Duration::from_secs_f32(fade_out_secs),
@@ -169,11 +394,66 @@ impl AudioSystem {
         }
     }
 
+    /// Fade a sound (or the music, with `sound` None) to `volume` over
+    /// `seconds`, shaped by `curve` when given.
+    pub fn fade(&mut self, sound: Option<&str>, volume: f32, seconds: f32, curve: Option<crate::curve::Curve>) {
+        let from = match sound {
+            Some(id) => match self.sound_volumes.get(id) {
+                Some(&v) => v,
+                None => return,
+            },
+            None => match &self.music_track {
+                Some(track) => track.volume,
+                None => return,
+            },
+        };
+        self.fades.retain(|fade| fade.sound.as_deref() != sound);
+        self.fades.push(VolumeFade {
+            sound: sound.map(str::to_string),
+            from,
+            to: volume,
+            duration: seconds.max(0.0),
+            elapsed: 0.0,
+            curve,
+        });
+    }
+
+    /// Step the fades in progress and apply their volumes.
+    pub fn update_fades(&mut self, dt: f32) {
+        if self.fades.is_empty() {
+            return;
+        }
+        let master = self.master_volume as f64;
+        for fade in &mut self.fades {
+            fade.elapsed += dt;
+            let volume = fade.volume();
+            let handle = match &fade.sound {
+                Some(id) => {
+                    self.sound_volumes.insert(id.clone(), volume);
+                    self.sounds.get_mut(id)
+                }
+                None => {
+                    if let Some(track) = &mut self.music_track {
+                        track.volume = volume;
+                    }
+                    self.music.as_mut()
+                }
+            };
+            if let Some(handle) = handle {
+                handle.set_volume(volume as f64 * master, Tween::default());
+            }
+        }
+        self.fades.retain(|fade| fade.elapsed < fade.duration);
+    }
+
     /// Clean up finished sounds.
     pub fn cleanup(&mut self) {
         self.sounds.retain(|_, handle| {
             handle.state() != PlaybackState::Stopped
         });
+        let sounds = &self.sounds;
+        self.sound_volumes.retain(|id, _| sounds.contains_key(id));
+        self.fades.retain(|fade| fade.sound.as_ref().is_none_or(|id| sounds.contains_key(id)));
     }
 }
 
@@ -181,6 +461,26 @@ impl AudioSystem {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_volume_fade_follows_curve() {
+        let mut fade = VolumeFade {
+            sound: None,
+            from: 1.0,
+            to: 0.0,
+            duration: 2.0,
+            elapsed: 1.0,
+            curve: None,
+        };
+        assert!((fade.volume() - 0.5).abs() < 1e-5);
+        fade.curve = Some(crate::curve::Curve::new(
+            vec![[0.0, 0.0], [0.5, 0.9], [1.0, 1.0]],
+            crate::curve::Interpolation::Linear,
+        ));
+        assert!((fade.volume() - 0.1).abs() < 1e-5);
+        fade.elapsed = 5.0;
+        assert!(fade.volume().abs() < 1e-5);
+    }
+
     #[test]
     fn test_audio_system_creation() {
         // Audio might fail in test environment (no audio device), that's OK
@@ -203,4 +503,25 @@ mod tests {
         let vol = audio.spatial_volume(Vec3::new(5.0, 0.0, 0.0), 10.0, 1.0);
         assert!(vol > 0.0 && vol < 1.0);
     }
+
+    #[test]
+    fn test_match_device_prefers_exact_name() {
+        let names = vec![
+            "Speakers (Realtek Audio)".to_string(),
+            "Headset (USB Audio)".to_string(),
+            "USB Audio".to_string(),
+        ];
+        assert_eq!(match_device(&names, "USB Audio"), Some(2));
+        assert_eq!(match_device(&names, "headset"), Some(1));
+        assert_eq!(match_device(&names, "usb"), Some(1));
+        assert_eq!(match_device(&names, "HDMI"), None);
+    }
+
+    #[test]
+    fn test_audio_settings_parse() {
+        let settings: AudioSettings = serde_yaml::from_str("device: headset\nbuffer_size: 256").unwrap();
+        assert_eq!(settings.device.as_deref(), Some("headset"));
+        assert_eq!(settings.buffer_size, Some(256));
+        assert_eq!(serde_yaml::from_str::<AudioSettings>("{}").unwrap(), AudioSettings::default());
+    }
 }
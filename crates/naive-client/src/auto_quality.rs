@@ -0,0 +1,334 @@
+//! Runtime quality auto-tuning (`auto_quality:` in naive.yaml).
+//!
+//! The governor averages frame times over one-second windows. When the game
+//! falls under its target frame rate it steps down a level, lowering the
+//! render scale, shadow map resolution and particle budget together; after a
+//! run of windows at the target it tries the next level up. A step up that is
+//! undone by the next window doubles the wait before trying again, so the
+//! level settles instead of flickering. Every change sends `perf.quality_changed`.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::engine_events::payload;
+use crate::pipeline::RenderDebugState;
+
+/// Seconds of frames averaged per measurement.
+const SAMPLE_WINDOW: f32 = 1.0;
+/// A window under this share of the target frame rate steps down.
+const STEP_DOWN_BELOW: f32 = 0.9;
+/// A window at or over this share of the target frame rate counts toward a step up.
+const STEADY_ABOVE: f32 = 0.97;
+/// Most windows to wait between step ups after failed tries.
+const MAX_RAISE_AFTER: u32 = 120;
+
+/// Auto-tuning settings. Each range is `[lowest, highest]`, and the levels
+/// in between are spread evenly across all of them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AutoQualityConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Frame rate to hold.
+    #[serde(default = "default_target_fps")]
+    pub target_fps: f32,
+    /// Number of levels, lowest to highest (at least 2).
+    #[serde(default = "default_levels")]
+    pub levels: u32,
+    /// Resolution of the pipeline's viewport-sized targets, as a fraction of the window's.
+    #[serde(default = "default_render_scale")]
+    pub render_scale: [f32; 2],
+    /// Size of the directional shadow map in texels.
+    #[serde(default = "default_shadow_resolution")]
+    pub shadow_resolution: [u32; 2],
+    /// Fraction of each emitter's `max_particles` (and of each burst) spawned.
+    #[serde(default = "default_particle_budget")]
+    pub particle_budget: [f32; 2],
+    /// Seconds in a row at the target frame rate before stepping up.
+    #[serde(default = "default_raise_after")]
+    pub raise_after: u32,
+}
+
+impl Default for AutoQualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_fps: default_target_fps(),
+            levels: default_levels(),
+            render_scale: default_render_scale(),
+            shadow_resolution: default_shadow_resolution(),
+            particle_budget: default_particle_budget(),
+            raise_after: default_raise_after(),
+        }
+    }
+}
+
+fn default_target_fps() -> f32 {
+    60.0
+}
+
+fn default_levels() -> u32 {
+    4
+}
+
+fn default_render_scale() -> [f32; 2] {
+    [0.5, 1.0]
+}
+
+fn default_shadow_resolution() -> [u32; 2] {
+    [512, 2048]
+}
+
+fn default_particle_budget() -> [f32; 2] {
+    [0.25, 1.0]
+}
+
+fn default_raise_after() -> u32 {
+    5
+}
+
+impl AutoQualityConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.target_fps.is_finite() && self.target_fps > 0.0) {
+            return Err(format!("target_fps must be above 0, got {}", self.target_fps));
+        }
+        if self.levels < 2 {
+            return Err(format!("levels must be at least 2, got {}", self.levels));
+        }
+        if self.raise_after == 0 {
+            return Err("raise_after must be at least 1".to_string());
+        }
+        let [low, high] = self.render_scale;
+        if !(low > 0.0 && low <= high && high <= 2.0) {
+            return Err(format!("render_scale must be [low, high] within (0, 2], got [{}, {}]", low, high));
+        }
+        let [low, high] = self.shadow_resolution;
+        if !(low > 0 && low <= high && high <= 8192) {
+            return Err(format!("shadow_resolution must be [low, high] within 1..8192, got [{}, {}]", low, high));
+        }
+        let [low, high] = self.particle_budget;
+        if !(low > 0.0 && low <= high && high <= 1.0) {
+            return Err(format!("particle_budget must be [low, high] within (0, 1], got [{}, {}]", low, high));
+        }
+        Ok(())
+    }
+
+    /// Settings of `level` (0 is the lowest).
+    pub fn level(&self, level: u32) -> QualityLevel {
+        let level = level.min(self.levels - 1);
+        let t = level as f32 / (self.levels - 1) as f32;
+        let lerp = |[low, high]: [f32; 2]| low + (high - low) * t;
+        let [low, high] = self.shadow_resolution;
+        QualityLevel {
+            level,
+            render_scale: lerp(self.render_scale),
+            shadow_resolution: lerp([low as f32, high as f32]).round() as u32,
+            particle_budget: lerp(self.particle_budget),
+        }
+    }
+}
+
+/// What one quality level renders with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityLevel {
+    pub level: u32,
+    pub render_scale: f32,
+    pub shadow_resolution: u32,
+    pub particle_budget: f32,
+}
+
+impl QualityLevel {
+    /// Set the render settings this level controls.
+    pub fn apply(self, debug: &mut RenderDebugState) {
+        debug.render_scale = self.render_scale;
+        debug.shadow_resolution = Some(self.shadow_resolution);
+        debug.particle_budget = self.particle_budget;
+    }
+}
+
+/// Undo `QualityLevel::apply`: the window's resolution, the pipeline's own
+/// shadow map size and every particle.
+pub fn clear(debug: &mut RenderDebugState) {
+    debug.render_scale = 1.0;
+    debug.shadow_resolution = None;
+    debug.particle_budget = 1.0;
+}
+
+/// A level change made by `QualityGovernor::update`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityChange {
+    pub previous: u32,
+    pub level: QualityLevel,
+    /// Frame rate of the window that caused it.
+    pub fps: f32,
+}
+
+impl QualityChange {
+    /// Payload of `perf.quality_changed`.
+    pub fn payload(&self) -> HashMap<String, Value> {
+        payload(json!({
+            "level": self.level.level,
+            "previous": self.previous,
+            "fps": self.fps,
+            "render_scale": self.level.render_scale,
+            "shadow_resolution": self.level.shadow_resolution,
+            "particle_budget": self.level.particle_budget,
+        }))
+    }
+}
+
+/// Steps the quality level to hold the target frame rate. Starts at the
+/// highest level.
+#[derive(Debug, Clone)]
+pub struct QualityGovernor {
+    config: AutoQualityConfig,
+    level: u32,
+    elapsed: f32,
+    frames: u32,
+    /// Windows in a row at the target frame rate.
+    steady: u32,
+    /// Steady windows needed for the next step up.
+    raise_after: u32,
+    /// The last change was a step up and no window has been judged since.
+    just_raised: bool,
+    /// Windows to discard before judging again.
+    settle: u32,
+}
+
+impl QualityGovernor {
+    /// A governor for `config`, or None when it is disabled or invalid (with
+    /// a warning).
+    pub fn from_config(config: &AutoQualityConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        if let Err(e) = config.validate() {
+            tracing::warn!("auto_quality disabled: {}", e);
+            return None;
+        }
+        Some(Self {
+            config: config.clone(),
+            level: config.levels - 1,
+            elapsed: 0.0,
+            frames: 0,
+            steady: 0,
+            raise_after: config.raise_after,
+            just_raised: false,
+            settle: 0,
+        })
+    }
+
+    /// Settings of the current level.
+    pub fn current(&self) -> QualityLevel {
+        self.config.level(self.level)
+    }
+
+    /// Discard the window being measured and the next one, so a hitch
+    /// (a scene load, reallocated targets) doesn't count against the level.
+    pub fn hold(&mut self) {
+        self.elapsed = 0.0;
+        self.frames = 0;
+        self.settle = 1;
+    }
+
+    /// Record one frame of `dt` seconds. Returns the change when the level
+    /// moves.
+    pub fn update(&mut self, dt: f32) -> Option<QualityChange> {
+        self.elapsed += dt;
+        self.frames += 1;
+        if self.elapsed < SAMPLE_WINDOW {
+            return None;
+        }
+        let fps = self.frames as f32 / self.elapsed;
+        self.elapsed = 0.0;
+        self.frames = 0;
+        if self.settle > 0 {
+            self.settle -= 1;
+            return None;
+        }
+
+        let raised = std::mem::take(&mut self.just_raised);
+        let target = self.config.target_fps;
+        if fps < target * STEP_DOWN_BELOW {
+            self.steady = 0;
+            if self.level == 0 {
+                return None;
+            }
+            if raised {
+                self.raise_after = (self.raise_after * 2).min(MAX_RAISE_AFTER);
+            }
+            return Some(self.step_to(self.level - 1, fps));
+        }
+
+        if fps >= target * STEADY_ABOVE {
+            self.steady += 1;
+        } else {
+            self.steady = 0;
+        }
+        if self.steady >= self.raise_after && self.level + 1 < self.config.levels {
+            self.steady = 0;
+            self.just_raised = true;
+            return Some(self.step_to(self.level + 1, fps));
+        }
+        None
+    }
+
+    fn step_to(&mut self, level: u32, fps: f32) -> QualityChange {
+        let previous = std::mem::replace(&mut self.level, level);
+        self.hold();
+        QualityChange { previous, level: self.current(), fps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `seconds` of frames at `fps`, collecting the changes.
+    fn run(governor: &mut QualityGovernor, fps: f32, seconds: f32) -> Vec<QualityChange> {
+        let frames = (fps * seconds).round() as u32;
+        (0..frames).filter_map(|_| governor.update(1.0 / fps)).collect()
+    }
+
+    #[test]
+    fn test_levels_spread_over_ranges() {
+        let config = AutoQualityConfig { enabled: true, ..Default::default() };
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.level(0),
+            QualityLevel { level: 0, render_scale: 0.5, shadow_resolution: 512, particle_budget: 0.25 }
+        );
+        assert_eq!(config.level(2).shadow_resolution, 1536);
+        assert_eq!(config.level(9), config.level(3));
+
+        assert!(AutoQualityConfig { levels: 1, ..Default::default() }.validate().is_err());
+        assert!(AutoQualityConfig { render_scale: [1.0, 0.5], ..Default::default() }.validate().is_err());
+        assert!(AutoQualityConfig { particle_budget: [0.0, 1.0], ..Default::default() }.validate().is_err());
+        assert!(QualityGovernor::from_config(&AutoQualityConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_governor_steps_down_and_backs_off() {
+        let config = AutoQualityConfig { enabled: true, raise_after: 2, ..Default::default() };
+        let mut governor = QualityGovernor::from_config(&config).unwrap();
+        assert_eq!(governor.current().level, 3);
+
+        // Slow frames step down one level, then the next window is discarded
+        let changes = run(&mut governor, 32.0, 2.0);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].previous, changes[0].level.level), (3, 2));
+        assert_eq!(changes[0].fps, 32.0);
+        assert_eq!(changes[0].payload()["shadow_resolution"], 1536);
+
+        // At the target, it tries the level above after `raise_after` windows
+        let changes = run(&mut governor, 64.0, 3.0);
+        assert_eq!(changes.iter().map(|c| c.level.level).collect::<Vec<_>>(), [3]);
+
+        // Failing there steps straight back down and doubles the wait
+        assert_eq!(run(&mut governor, 32.0, 1.0).len(), 1);
+        assert_eq!(governor.current().level, 2);
+        assert!(run(&mut governor, 64.0, 4.0).is_empty());
+        assert_eq!(run(&mut governor, 64.0, 1.0).len(), 1);
+    }
+}
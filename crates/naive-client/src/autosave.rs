@@ -0,0 +1,176 @@
+//! Auto-save of live scene edits, and recovery after a crash.
+//!
+//! Entities spawned, modified or destroyed through the command socket only
+//! exist in the running engine until `save_scene` writes them out. While such
+//! edits are unsaved, the engine snapshots the scene into `.naive/autosave/`
+//! every `autosave.interval_secs`; on the next start an autosave newer than
+//! its scene file is reported, and `--recover` loads it in place of the scene.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Autosave folder, relative to the project root.
+pub const AUTOSAVE_DIR: &str = ".naive/autosave";
+
+/// Slot of an editor scene that was never saved.
+const UNTITLED: &str = "untitled";
+
+/// Sidecar written next to each autosaved scene.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutosaveInfo {
+    /// Scene the edits were made to, relative to the project root (empty for
+    /// an editor scene that was never saved).
+    pub scene: String,
+    /// When the snapshot was taken, in seconds since the Unix epoch.
+    pub saved_at: u64,
+    /// Socket edits made since the scene was last saved.
+    pub edits: u32,
+}
+
+/// An autosave taken after its scene file was last written.
+#[derive(Debug)]
+pub struct Autosave {
+    pub info: AutosaveInfo,
+    /// The autosaved scene YAML.
+    pub path: PathBuf,
+}
+
+impl Autosave {
+    /// "3 unsaved edits from 12 min ago", for logs and notifications.
+    pub fn describe(&self) -> String {
+        let minutes = unix_now().saturating_sub(self.info.saved_at) / 60;
+        let age = match minutes {
+            0 => "under a minute ago".to_string(),
+            m => format!("{} min ago", m),
+        };
+        let plural = if self.info.edits == 1 { "" } else { "s" };
+        format!("{} unsaved edit{} from {}", self.info.edits, plural, age)
+    }
+}
+
+/// The name a scene's autosave is filed under: its path relative to the
+/// project root, or empty for an unsaved editor scene.
+pub fn scene_key(project_root: &Path, scene: Option<&Path>) -> String {
+    match scene {
+        Some(path) => path.strip_prefix(project_root).unwrap_or(path).to_string_lossy().replace('\\', "/"),
+        None => String::new(),
+    }
+}
+
+/// File stem of an autosave: `scenes/level1.yaml` -> `scenes_level1`.
+fn slot(key: &str) -> String {
+    if key.is_empty() {
+        return UNTITLED.to_string();
+    }
+    let stem = key.strip_suffix(".yaml").or_else(|| key.strip_suffix(".yml")).unwrap_or(key);
+    stem.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// The autosaved scene and its sidecar.
+fn slot_files(project_root: &Path, key: &str) -> (PathBuf, PathBuf) {
+    let dir = project_root.join(AUTOSAVE_DIR);
+    let slot = slot(key);
+    (dir.join(format!("{}.yaml", slot)), dir.join(format!("{}.json", slot)))
+}
+
+/// Snapshot `yaml`, the scene including its unsaved edits. Files are written
+/// under a temporary name and renamed, so a crash mid-write leaves the
+/// previous autosave intact.
+pub fn write(project_root: &Path, scene: Option<&Path>, edits: u32, yaml: &str) -> Result<PathBuf, String> {
+    let key = scene_key(project_root, scene);
+    let (scene_file, info_file) = slot_files(project_root, &key);
+    let info = AutosaveInfo { scene: key, saved_at: unix_now(), edits };
+    let info_json = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+
+    let dir = project_root.join(AUTOSAVE_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    write_replacing(&scene_file, yaml)?;
+    write_replacing(&info_file, &info_json)?;
+    Ok(scene_file)
+}
+
+fn write_replacing(path: &Path, contents: &str) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, contents).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// The autosave of `scene`, unless the scene file was written after it (the
+/// edits were saved, or the file was changed by hand since).
+pub fn find(project_root: &Path, scene: Option<&Path>) -> Option<Autosave> {
+    let key = scene_key(project_root, scene);
+    let (scene_file, info_file) = slot_files(project_root, &key);
+    let info: AutosaveInfo = serde_json::from_str(&std::fs::read_to_string(&info_file).ok()?).ok()?;
+    if info.scene != key || !scene_file.exists() {
+        return None;
+    }
+    let modified = scene.and_then(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok());
+    if modified.is_some_and(|time| unix_secs(time) > info.saved_at) {
+        return None;
+    }
+    Some(Autosave { info, path: scene_file })
+}
+
+/// Remove the autosave of `scene`, once its edits were saved.
+pub fn discard(project_root: &Path, scene: Option<&Path>) {
+    let (scene_file, info_file) = slot_files(project_root, &scene_key(project_root, scene));
+    let _ = std::fs::remove_file(scene_file);
+    let _ = std::fs::remove_file(info_file);
+}
+
+fn unix_now() -> u64 {
+    unix_secs(SystemTime::now())
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autosave_round_trip() {
+        let root = std::env::temp_dir().join("naive_test_autosave");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("scenes")).unwrap();
+        let scene = root.join("scenes/level 1.yaml");
+        std::fs::write(&scene, "name: Level\nentities: []\n").unwrap();
+
+        assert!(find(&root, Some(&scene)).is_none());
+        let written = write(&root, Some(&scene), 3, "name: Level\nentities: [{id: crate}]\n").unwrap();
+        assert_eq!(written, root.join(AUTOSAVE_DIR).join("scenes_level_1.yaml"));
+
+        let found = find(&root, Some(&scene)).unwrap();
+        assert_eq!(found.info.scene, "scenes/level 1.yaml");
+        assert_eq!(found.info.edits, 3);
+        assert!(std::fs::read_to_string(&found.path).unwrap().contains("crate"));
+        assert!(found.describe().starts_with("3 unsaved edits from"));
+
+        // Another scene's slot is separate, including the untitled one
+        assert!(find(&root, None).is_none());
+        write(&root, None, 1, "entities: []\n").unwrap();
+        assert_eq!(find(&root, None).unwrap().info.scene, "");
+
+        // The scene file being newer makes the autosave stale
+        let stale = AutosaveInfo { saved_at: 0, ..found.info };
+        std::fs::write(
+            root.join(AUTOSAVE_DIR).join("scenes_level_1.json"),
+            serde_json::to_string(&stale).unwrap(),
+        )
+        .unwrap();
+        assert!(find(&root, Some(&scene)).is_none());
+
+        discard(&root, None);
+        assert!(find(&root, None).is_none());
+        assert!(!root.join(AUTOSAVE_DIR).join("untitled.yaml").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -3,7 +3,8 @@
 use std::fs;
 use std::path::Path;
 
-use crate::project_config::NaiveConfig;
+use crate::project_config::{current_platform, NaiveConfig, ScriptOutput};
+use crate::script_bundle::{bundle_key, ScriptBundle, BUNDLE_FILE};
 
 /// Content directories to include in the bundle.
 const CONTENT_DIRS: &[&str] = &[
@@ -14,13 +15,20 @@ const CONTENT_DIRS: &[&str] = &[
     "pipelines",
     "input",
     "events",
+    "sequences",
+    "curves",
+    "camera_paths",
 ];
 
+/// Bundle the project into `dist/<name>-<target>`. `config` should be loaded
+/// for the target platform (`project_config::load_config_for`).
 pub fn bundle_project(
     config: &NaiveConfig,
     project_root: &Path,
     target: Option<&str>,
+    bytecode: bool,
 ) -> Result<(), String> {
+    let bytecode = bytecode || config.build.scripts == ScriptOutput::Bytecode;
     let target_name = target.unwrap_or(current_platform());
     let dist_name = format!("{}-{}", config.name, target_name);
     let dist_dir = project_root.join("dist").join(&dist_name);
@@ -54,18 +62,28 @@ pub fn bundle_project(
             .map_err(|e| format!("Failed to copy naive.yaml: {}", e))?;
     }
 
-    // Copy content directories
+    // Copy content directories (without the scripts when they are compiled)
+    let skip_ext = if bytecode { Some("lua") } else { None };
     let mut total_size: u64 = 0;
     for dir_name in CONTENT_DIRS {
         let src = project_root.join(dir_name);
         if src.is_dir() {
             let dest = dist_dir.join(dir_name);
-            let size = copy_dir_recursive(&src, &dest)?;
+            let size = copy_dir_recursive(&src, &dest, skip_ext)?;
             total_size += size;
             println!("  {} ({} files)", dir_name, count_files(&dest));
         }
     }
 
+    if bytecode {
+        let bundle = compile_scripts(project_root, !config.build.keep_debug_info)?;
+        let bytes = bundle.to_bytes();
+        fs::write(dist_dir.join(BUNDLE_FILE), &bytes)
+            .map_err(|e| format!("Failed to write {}: {}", BUNDLE_FILE, e))?;
+        total_size += bytes.len() as u64;
+        println!("  {} ({} scripts, {})", BUNDLE_FILE, bundle.chunks.len(), bundle.tag);
+    }
+
     // Add the binary size
     total_size += fs::metadata(&dest_binary)
         .map(|m| m.len())
@@ -114,17 +132,34 @@ pub fn bundle_project(
     Ok(())
 }
 
-fn current_platform() -> &'static str {
-    if cfg!(target_os = "macos") {
-        "macos"
-    } else if cfg!(target_os = "windows") {
-        "windows"
-    } else {
-        "linux"
+/// Compile every `.lua` file in the content directories into one bundle.
+fn compile_scripts(project_root: &Path, strip: bool) -> Result<ScriptBundle, String> {
+    let mut sources = Vec::new();
+    for dir_name in CONTENT_DIRS {
+        collect_scripts(project_root, &project_root.join(dir_name), &mut sources)?;
     }
+    ScriptBundle::compile(sources.iter().map(|(path, code)| (path.as_str(), code.as_str())), strip)
+}
+
+fn collect_scripts(project_root: &Path, dir: &Path, out: &mut Vec<(String, String)>) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))? {
+        let path = entry.map_err(|e| format!("Directory entry error: {}", e))?.path();
+        if path.is_dir() {
+            collect_scripts(project_root, &path, out)?;
+        } else if path.extension().is_some_and(|ext| ext == "lua") {
+            let code = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let relative = path.strip_prefix(project_root).unwrap_or(&path);
+            out.push((bundle_key(relative), code));
+        }
+    }
+    Ok(())
 }
 
-fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<u64, String> {
+fn copy_dir_recursive(src: &Path, dest: &Path, skip_ext: Option<&str>) -> Result<u64, String> {
     fs::create_dir_all(dest)
         .map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
 
@@ -135,7 +170,9 @@ fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<u64, String> {
         let dest_path = dest.join(entry.file_name());
 
         if src_path.is_dir() {
-            total += copy_dir_recursive(&src_path, &dest_path)?;
+            total += copy_dir_recursive(&src_path, &dest_path, skip_ext)?;
+        } else if skip_ext.is_some_and(|skip| src_path.extension().is_some_and(|ext| ext == skip)) {
+            continue;
         } else {
             fs::copy(&src_path, &dest_path)
                 .map_err(|e| format!("Failed to copy {}: {}", src_path.display(), e))?;
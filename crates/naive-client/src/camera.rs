@@ -14,8 +14,10 @@ pub struct CameraUniform {
     pub far_plane: f32,                 // offset 208
     pub _pad1: f32,                     // offset 212 (align viewport_size to WGSL vec2 alignment 8)
     pub viewport_size: [f32; 2],        // offset 216
-    pub _pad2: [f32; 4],               // offset 224 → 240 (align mat4 to 16)
-    pub inv_view_projection: [[f32; 4]; 4], // offset 240, 64 bytes → total 304
+    pub jitter: [f32; 2],               // offset 224, NDC offset baked into projection (TAA)
+    pub _pad2: [f32; 2],               // offset 232 → 240 (align mat4 to 16)
+    pub inv_view_projection: [[f32; 4]; 4], // offset 240
+    pub prev_view_projection: [[f32; 4]; 4], // offset 304, last frame's unjittered, 64 bytes → total 368
 }
 
 impl Default for CameraUniform {
@@ -29,18 +31,32 @@ impl Default for CameraUniform {
             far_plane: 100.0,
             _pad1: 0.0,
             viewport_size: [1280.0, 720.0],
-            _pad2: [0.0; 4],
+            jitter: [0.0; 2],
+            _pad2: [0.0; 2],
             inv_view_projection: Mat4::IDENTITY.to_cols_array_2d(),
+            prev_view_projection: Mat4::IDENTITY.to_cols_array_2d(),
         }
     }
 }
 
+/// Length of the sub-pixel jitter sequence (Halton 2,3).
+const JITTER_SAMPLES: u32 = 8;
+
 /// Manages the camera uniform buffer and bind group.
 pub struct CameraState {
     pub uniform: CameraUniform,
     pub buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
     pub bind_group_layout: wgpu::BindGroupLayout,
+    /// Offset the projection by a sub-pixel amount each frame. Set while the
+    /// pipeline has a TAA pass, which accumulates the jittered frames.
+    pub jitter_enabled: bool,
+    /// Region of the swapchain this camera draws into (split screen); None
+    /// for the whole target.
+    pub viewport: Option<crate::split_screen::Viewport>,
+    frame_index: u32,
+    /// Unjittered view-projection of the previous update, None before the first.
+    prev_view_projection: Option<Mat4>,
 }
 
 impl CameraState {
@@ -50,7 +66,10 @@ impl CameraState {
                 label: Some("Camera Bind Group Layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    // Compute too, for the light clustering step
+                    visibility: wgpu::ShaderStages::VERTEX
+                        | wgpu::ShaderStages::FRAGMENT
+                        | wgpu::ShaderStages::COMPUTE,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -81,6 +100,10 @@ impl CameraState {
             buffer,
             bind_group,
             bind_group_layout,
+            jitter_enabled: false,
+            viewport: None,
+            frame_index: 0,
+            prev_view_projection: None,
         }
     }
 
@@ -98,12 +121,77 @@ impl CameraState {
             transform.rotation * Vec3::NEG_Z,
             Vec3::Y,
         );
-        let projection = Mat4::perspective_rh(
-            camera.fov_degrees.to_radians(),
-            viewport_width as f32 / viewport_height.max(1) as f32,
+        let projection = camera.projection_matrix(viewport_width as f32 / viewport_height.max(1) as f32);
+        self.set_matrices(
+            queue,
+            view,
+            projection,
+            transform.position,
             camera.near,
             camera.far,
+            viewport_width,
+            viewport_height,
         );
+    }
+
+    /// Update the uniform for a camera drawn into `viewport` of a
+    /// `target_width` x `target_height` target: the projection takes the
+    /// viewport's aspect, while the passes still render at the target's size.
+    pub fn update_viewport(
+        &mut self,
+        queue: &wgpu::Queue,
+        camera: &Camera,
+        transform: &Transform,
+        target_width: u32,
+        target_height: u32,
+        viewport: crate::split_screen::Viewport,
+    ) {
+        let view = Mat4::look_to_rh(
+            transform.position,
+            transform.rotation * Vec3::NEG_Z,
+            Vec3::Y,
+        );
+        let projection = camera.projection_matrix(viewport.aspect());
+        self.viewport = Some(viewport);
+        self.set_matrices(
+            queue,
+            view,
+            projection,
+            transform.position,
+            camera.near,
+            camera.far,
+            target_width,
+            target_height,
+        );
+    }
+
+    /// Upload a view and (unjittered) projection, applying this frame's jitter
+    /// and recording the previous frame's view-projection for reprojection.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_matrices(
+        &mut self,
+        queue: &wgpu::Queue,
+        view: Mat4,
+        projection: Mat4,
+        position: Vec3,
+        near: f32,
+        far: f32,
+        viewport_width: u32,
+        viewport_height: u32,
+    ) {
+        let unjittered_view_projection = projection * view;
+        let prev_view_projection = self
+            .prev_view_projection
+            .replace(unjittered_view_projection)
+            .unwrap_or(unjittered_view_projection);
+
+        let jitter = if self.jitter_enabled {
+            self.frame_index = self.frame_index.wrapping_add(1);
+            jitter_ndc(self.frame_index, viewport_width, viewport_height)
+        } else {
+            [0.0; 2]
+        };
+        let projection = jitter_projection(projection, jitter);
         let view_projection = projection * view;
 
         let inv_view_projection = view_projection.inverse();
@@ -112,20 +200,117 @@ impl CameraState {
             view: view.to_cols_array_2d(),
             projection: projection.to_cols_array_2d(),
             view_projection: view_projection.to_cols_array_2d(),
-            position: transform.position.to_array(),
-            near_plane: camera.near,
-            far_plane: camera.far,
+            position: position.to_array(),
+            near_plane: near,
+            far_plane: far,
             _pad1: 0.0,
             viewport_size: [viewport_width as f32, viewport_height as f32],
-            _pad2: [0.0; 4],
+            jitter,
+            _pad2: [0.0; 2],
             inv_view_projection: inv_view_projection.to_cols_array_2d(),
+            prev_view_projection: prev_view_projection.to_cols_array_2d(),
         };
 
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
     }
 
+    /// Forget the previous frame so the next update doesn't reproject across
+    /// a cut (scene load, camera teleport).
+    pub fn reset_history(&mut self) {
+        self.prev_view_projection = None;
+    }
+
     /// Get the current view matrix.
     pub fn view_matrix(&self) -> Mat4 {
         Mat4::from_cols_array_2d(&self.uniform.view)
     }
 }
+
+/// Radical inverse of `index` in `base`, in [0, 1).
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0 / base as f32;
+    while index > 0 {
+        result += (index % base) as f32 * fraction;
+        index /= base;
+        fraction /= base as f32;
+    }
+    result
+}
+
+/// `projection` shifted by `jitter` in NDC. Each column's x/y moves against
+/// its w, so clip x/y change by jitter * w: a constant offset after the
+/// divide, for perspective (w from z) and orthographic (w = 1) alike.
+fn jitter_projection(mut projection: Mat4, jitter: [f32; 2]) -> Mat4 {
+    for column in [&mut projection.x_axis, &mut projection.y_axis, &mut projection.z_axis, &mut projection.w_axis] {
+        column.x -= jitter[0] * column.w;
+        column.y -= jitter[1] * column.w;
+    }
+    projection
+}
+
+/// Sub-pixel jitter for `frame` in NDC units: a Halton (2,3) point in
+/// [-0.5, 0.5) pixels, scaled by 2 / viewport.
+fn jitter_ndc(frame: u32, viewport_width: u32, viewport_height: u32) -> [f32; 2] {
+    let index = frame % JITTER_SAMPLES + 1;
+    let x = halton(index, 2) - 0.5;
+    let y = halton(index, 3) - 0.5;
+    [
+        2.0 * x / viewport_width.max(1) as f32,
+        2.0 * y / viewport_height.max(1) as f32,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_uniform_layout() {
+        // Matches the WGSL struct, including the TAA fields at the end
+        assert_eq!(std::mem::size_of::<CameraUniform>(), 368);
+        assert_eq!(std::mem::offset_of!(CameraUniform, jitter), 224);
+        assert_eq!(std::mem::offset_of!(CameraUniform, inv_view_projection), 240);
+        assert_eq!(std::mem::offset_of!(CameraUniform, prev_view_projection), 304);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_a_pixel() {
+        let mut seen = Vec::new();
+        for frame in 0..JITTER_SAMPLES {
+            let [x, y] = jitter_ndc(frame, 100, 50);
+            // Half a pixel is 0.01 NDC horizontally and 0.02 vertically
+            assert!(x.abs() <= 0.01 && y.abs() <= 0.02, "frame {frame}: {x}, {y}");
+            seen.push((x, y));
+        }
+        seen.dedup();
+        assert_eq!(seen.len(), JITTER_SAMPLES as usize);
+        assert_eq!(jitter_ndc(0, 100, 50), jitter_ndc(JITTER_SAMPLES, 100, 50));
+    }
+
+    #[test]
+    fn test_orthographic_projection_and_jitter() {
+        let camera = Camera {
+            near: 0.5,
+            far: 50.0,
+            projection: crate::components::Projection::Orthographic { size: 10.0 },
+            ..Default::default()
+        };
+        let projection = camera.projection_matrix(2.0);
+        // 10 units tall and 20 wide whatever the distance
+        for depth in [1.0, 40.0] {
+            let corner = projection.project_point3(Vec3::new(10.0, 5.0, -depth));
+            assert!((corner.x - 1.0).abs() < 1e-5 && (corner.y - 1.0).abs() < 1e-5);
+        }
+        assert!(projection.project_point3(Vec3::new(0.0, 0.0, -0.5)).z.abs() < 1e-5);
+        assert!((projection.project_point3(Vec3::new(0.0, 0.0, -50.0)).z - 1.0).abs() < 1e-5);
+
+        // Jitter moves both kinds of projection by the same NDC offset
+        let perspective = Camera::default().projection_matrix(2.0);
+        for matrix in [projection, perspective] {
+            let point = Vec3::new(1.0, -0.5, -5.0);
+            let shift = jitter_projection(matrix, [0.01, 0.02]).project_point3(point) - matrix.project_point3(point);
+            assert!((shift.x + 0.01).abs() < 1e-5 && (shift.y + 0.02).abs() < 1e-5, "{shift}");
+        }
+    }
+}
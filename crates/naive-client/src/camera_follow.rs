@@ -0,0 +1,132 @@
+//! Third-person camera smoothing.
+//!
+//! A `CameraFollow` remembers where a third-person camera was last frame, so
+//! the camera can ease toward its orbit position and rotation instead of
+//! snapping to them, and lead the player in the direction they move. The
+//! damping values are time constants: after that many seconds the camera has
+//! closed about two thirds of the gap, and 0 snaps. The easing is exponential
+//! in time, so it looks the same at any frame rate.
+
+use glam::{Quat, Vec3};
+
+/// A jump of the followed entity longer than this in one frame (a teleport
+/// or respawn) snaps the camera.
+const SNAP_DISTANCE: f32 = 10.0;
+/// Time constant of the look-ahead offset, so it doesn't shake with every
+/// change of speed.
+const LEAD_DAMPING: f32 = 0.3;
+
+/// How a third-person camera follows (`camera:` in the scene).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FollowSettings {
+    /// Seconds to ease toward the orbit position.
+    pub position_damping: f32,
+    /// Seconds to ease toward the rotation looking at the player.
+    pub rotation_damping: f32,
+    /// Seconds of the player's horizontal movement the orbit point leads by.
+    pub look_ahead: f32,
+    /// Farthest the orbit point leads, in meters.
+    pub look_ahead_max: f32,
+}
+
+/// Share of the remaining gap closed in `dt` seconds with time constant `damping`.
+fn ease(dt: f32, damping: f32) -> f32 {
+    if damping > 0.0 {
+        1.0 - (-dt.max(0.0) / damping).exp()
+    } else {
+        1.0
+    }
+}
+
+/// A third-person camera's pose last frame. Added to entities with
+/// `CameraMode::ThirdPerson`; each frame calls `lead`, `ease_position` and
+/// `ease_rotation` in that order.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraFollow {
+    /// False until the first frame, which snaps.
+    primed: bool,
+    /// Where the followed entity was.
+    entity: Vec3,
+    lead: Vec3,
+    position: Vec3,
+    rotation: Quat,
+}
+
+impl CameraFollow {
+    /// Offset of the orbit point ahead of the followed entity, now at
+    /// `entity`. A jump too long to be movement snaps the camera this frame.
+    pub fn lead(&mut self, entity: Vec3, dt: f32, settings: &FollowSettings) -> Vec3 {
+        if !self.primed || entity.distance(self.entity) > SNAP_DISTANCE {
+            self.primed = false;
+            self.lead = Vec3::ZERO;
+        } else if dt > 0.0 {
+            let velocity = (entity - self.entity) / dt;
+            let target = (Vec3::new(velocity.x, 0.0, velocity.z) * settings.look_ahead)
+                .clamp_length_max(settings.look_ahead_max.max(0.0));
+            self.lead = self.lead.lerp(target, ease(dt, LEAD_DAMPING));
+        }
+        self.entity = entity;
+        self.lead
+    }
+
+    /// Ease from last frame's position toward `target`.
+    pub fn ease_position(&self, target: Vec3, dt: f32, settings: &FollowSettings) -> Vec3 {
+        if !self.primed {
+            return target;
+        }
+        self.position.lerp(target, ease(dt, settings.position_damping))
+    }
+
+    /// Ease from last frame's rotation toward `target`, and remember the
+    /// camera as at `position` (after any pull in front of walls) with the
+    /// returned rotation.
+    pub fn ease_rotation(&mut self, position: Vec3, target: Quat, dt: f32, settings: &FollowSettings) -> Quat {
+        let rotation = if self.primed {
+            self.rotation.slerp(target, ease(dt, settings.rotation_damping))
+        } else {
+            target
+        };
+        self.primed = true;
+        self.position = position;
+        self.rotation = rotation;
+        rotation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> FollowSettings {
+        FollowSettings { position_damping: 0.5, rotation_damping: 0.0, look_ahead: 0.5, look_ahead_max: 1.0 }
+    }
+
+    #[test]
+    fn test_eases_and_leads() {
+        let settings = settings();
+        let mut follow = CameraFollow::default();
+
+        // The first frame snaps
+        assert_eq!(follow.lead(Vec3::ZERO, 0.1, &settings), Vec3::ZERO);
+        assert_eq!(follow.ease_position(Vec3::new(0.0, 2.0, 4.0), 0.1, &settings), Vec3::new(0.0, 2.0, 4.0));
+        follow.ease_rotation(Vec3::new(0.0, 2.0, 4.0), Quat::IDENTITY, 0.1, &settings);
+
+        // One time constant closes 1 - 1/e of the gap
+        let eased = follow.ease_position(Vec3::new(2.0, 2.0, 4.0), 0.5, &settings);
+        assert!((eased.x - 2.0 * (1.0 - (-1.0f32).exp())).abs() < 1e-5);
+        // Without rotation damping the rotation snaps
+        let turned = Quat::from_rotation_y(1.0);
+        assert_eq!(follow.ease_rotation(eased, turned, 0.5, &settings), turned);
+
+        // Running at 4 m/s along +X leads by 2 m, held to the 1 m limit
+        let mut lead = Vec3::ZERO;
+        for frame in 1..=60 {
+            lead = follow.lead(Vec3::new(frame as f32 * 0.4, 0.0, 0.0), 0.1, &settings);
+        }
+        assert!((lead - Vec3::X).length() < 1e-3);
+
+        // A teleport snaps everything back
+        assert_eq!(follow.lead(Vec3::new(100.0, 0.0, 0.0), 0.1, &settings), Vec3::ZERO);
+        assert_eq!(follow.ease_position(Vec3::splat(100.0), 0.1, &settings), Vec3::splat(100.0));
+    }
+}
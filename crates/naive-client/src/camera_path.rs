@@ -0,0 +1,304 @@
+//! Camera paths: keyframed camera moves for cutscenes and fly-throughs.
+//!
+//! A path is a list of keys in `camera_paths/<name>.yaml`, each with a time,
+//! a position, a rotation (or a point to look at) and optionally a field of
+//! view. `camera.play_path("intro", 6)` flies the main camera along one,
+//! stretched to six seconds, then hands the camera back. Unlike a sequence
+//! (see `sequence`), a path only moves the camera.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use glam::{Quat, Vec3};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::engine_events::payload;
+use crate::format::Format;
+use crate::mods::ModOverlay;
+use crate::sequence::{catmull_rom, look_rotation, CameraPose};
+
+/// Directory holding the camera paths, relative to the project root.
+pub const CAMERA_PATHS_DIR: &str = "camera_paths";
+
+/// Camera path files (`camera_paths/*.yaml`).
+pub const CAMERA_PATH_FORMAT: Format = Format { name: "camera path", current: 1, migrations: &[] };
+
+/// A camera path file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CameraPath {
+    pub keys: Vec<PathKey>,
+    /// Ignore the player's movement and look input while playing.
+    #[serde(default = "default_true")]
+    pub lock_player: bool,
+}
+
+/// Where the camera is at `time` seconds into the path.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PathKey {
+    pub time: f32,
+    pub position: [f32; 3],
+    /// Euler degrees `[pitch, yaw, roll]`, as in a transform.
+    #[serde(default)]
+    pub rotation: Option<[f32; 3]>,
+    /// Face this point instead of giving a rotation.
+    #[serde(default)]
+    pub look_at: Option<[f32; 3]>,
+    /// Field of view in degrees (the camera's own when unset).
+    #[serde(default)]
+    pub fov: Option<f32>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl PathKey {
+    fn orientation(&self) -> Quat {
+        match (self.rotation, self.look_at) {
+            (Some(euler), _) => crate::world::euler_degrees_to_quat(euler),
+            (None, Some(target)) => look_rotation(Vec3::from(target) - Vec3::from(self.position)),
+            (None, None) => Quat::IDENTITY,
+        }
+    }
+}
+
+impl CameraPath {
+    /// Parse and check a path file, sorting its keys by time.
+    pub fn parse(yaml: &str) -> Result<Self, String> {
+        let mut path: CameraPath = match CAMERA_PATH_FORMAT.migrate(yaml)? {
+            Some(doc) => serde_yaml::from_value(doc),
+            None => serde_yaml::from_str(yaml),
+        }
+        .map_err(|e| e.to_string())?;
+
+        if path.keys.is_empty() {
+            return Err("a camera path needs at least one key".to_string());
+        }
+        for key in &path.keys {
+            if !(key.time.is_finite() && key.time >= 0.0) {
+                return Err(format!("times must be zero or more seconds, got {}", key.time));
+            }
+            match (key.rotation, key.look_at) {
+                (Some(_), Some(_)) => return Err(format!("key at {}s has both rotation and look_at", key.time)),
+                (None, None) => return Err(format!("key at {}s needs a rotation or a look_at", key.time)),
+                _ => {}
+            }
+        }
+        path.keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(path)
+    }
+
+    /// Time of the last key.
+    pub fn length(&self) -> f32 {
+        self.keys.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Camera on the path at `time`: a Catmull-Rom curve through the key
+    /// positions, rotations blended along the shortest arc and the field of
+    /// view eased linearly. Held at the first and last keys outside them.
+    pub fn pose_at(&self, time: f32) -> CameraPose {
+        let keys = &self.keys;
+        let last = keys.len() - 1;
+        let i = keys.iter().rposition(|k| k.time <= time).unwrap_or(0);
+        let j = (i + 1).min(last);
+        let span = keys[j].time - keys[i].time;
+        let t = if span > 0.0 { ((time - keys[i].time) / span).clamp(0.0, 1.0) } else { 0.0 };
+
+        let p = |index: usize| Vec3::from(keys[index].position);
+        let position = catmull_rom(p(i.saturating_sub(1)), p(i), p(j), p((j + 1).min(last)), t);
+        let rotation = keys[i].orientation().slerp(keys[j].orientation(), t);
+        let fov = match (keys[i].fov, keys[j].fov) {
+            (Some(a), Some(b)) => Some(a + (b - a) * t),
+            (a, b) => a.or(b),
+        };
+        CameraPose { position, rotation, fov }
+    }
+}
+
+/// The path playing now.
+#[derive(Debug, Clone)]
+struct Playback {
+    name: String,
+    path: Rc<CameraPath>,
+    /// Seconds since it started.
+    elapsed: f32,
+    /// Seconds it plays for.
+    duration: f32,
+}
+
+impl Playback {
+    /// Time on the path, with the path stretched over `duration`.
+    fn path_time(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0) * self.path.length()
+        } else {
+            self.path.length()
+        }
+    }
+}
+
+/// Loads camera paths and plays one at a time on the main camera.
+#[derive(Debug, Default)]
+pub struct CameraPathPlayer {
+    root: PathBuf,
+    /// Mods may replace or add paths.
+    pub overlay: ModOverlay,
+    cache: HashMap<String, Rc<CameraPath>>,
+    active: Option<Playback>,
+    /// `camera_path.finished` payloads to send.
+    finished: Vec<HashMap<String, Value>>,
+}
+
+impl CameraPathPlayer {
+    pub fn new(project_root: &Path) -> Self {
+        Self { root: project_root.to_path_buf(), ..Default::default() }
+    }
+
+    /// `camera_paths/<name>.yaml`, parsed once and cached.
+    pub fn load(&mut self, name: &str) -> Result<Rc<CameraPath>, String> {
+        if let Some(path) = self.cache.get(name) {
+            return Ok(path.clone());
+        }
+        let relative = Path::new(CAMERA_PATHS_DIR).join(format!("{}.yaml", name));
+        let file = self.overlay.resolve(&self.root, &relative);
+        let contents = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let path = Rc::new(CameraPath::parse(&contents).map_err(|e| format!("{}: {}", file.display(), e))?);
+        self.cache.insert(name.to_string(), path.clone());
+        Ok(path)
+    }
+
+    /// Load and start `name` over `duration` seconds (the path's own timing
+    /// when None), replacing whatever path is playing.
+    pub fn play(&mut self, name: &str, duration: Option<f32>) -> Result<(), String> {
+        if let Some(d) = duration {
+            if !(d.is_finite() && d >= 0.0) {
+                return Err(format!("duration must be zero or more seconds, got {}", d));
+            }
+        }
+        let path = self.load(name)?;
+        self.start(name, path, duration);
+        Ok(())
+    }
+
+    /// Start an already loaded path.
+    pub fn start(&mut self, name: &str, path: Rc<CameraPath>, duration: Option<f32>) {
+        self.stop();
+        let duration = duration.unwrap_or_else(|| path.length());
+        self.active = Some(Playback { name: name.to_string(), path, elapsed: 0.0, duration });
+    }
+
+    /// Hand the camera back now.
+    pub fn stop(&mut self) {
+        if let Some(playback) = self.active.take() {
+            self.finish(playback, true);
+        }
+    }
+
+    /// Forget the playing path without an event (scene change) and drop
+    /// cached files so edits are picked up.
+    pub fn reset(&mut self) {
+        self.active = None;
+        self.finished.clear();
+        self.cache.clear();
+    }
+
+    fn finish(&mut self, playback: Playback, stopped: bool) {
+        self.finished.push(payload(json!({ "name": playback.name, "stopped": stopped })));
+    }
+
+    /// Advance by `dt` seconds. Returns the `camera_path.finished` payloads
+    /// of the paths that ended since the last update.
+    pub fn update(&mut self, dt: f32) -> Vec<HashMap<String, Value>> {
+        if let Some(playback) = &mut self.active {
+            // The last pose shows for a frame before the camera is handed back
+            if playback.elapsed >= playback.duration {
+                if let Some(playback) = self.active.take() {
+                    self.finish(playback, false);
+                }
+            } else {
+                playback.elapsed += dt.max(0.0);
+            }
+        }
+        std::mem::take(&mut self.finished)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Name of the playing path and seconds since it started.
+    pub fn current(&self) -> Option<(&str, f32)> {
+        self.active.as_ref().map(|p| (p.name.as_str(), p.elapsed))
+    }
+
+    /// Whether the player's controls are locked by the playing path.
+    pub fn locks_player(&self) -> bool {
+        self.active.as_ref().is_some_and(|p| p.path.lock_player)
+    }
+
+    pub fn camera(&self) -> Option<CameraPose> {
+        self.active.as_ref().map(|p| p.path.pose_at(p.path_time()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLYBY: &str = r#"
+keys:
+  - { time: 2, position: [10, 2, 0], rotation: [0, 90, 0], fov: 50 }
+  - { time: 0, position: [0, 2, 10], look_at: [0, 2, 0], fov: 70 }
+"#;
+
+    #[test]
+    fn test_parse_and_pose() {
+        let path = CameraPath::parse(FLYBY).unwrap();
+        assert_eq!(path.keys[0].time, 0.0);
+        assert_eq!(path.length(), 2.0);
+        assert!(path.lock_player);
+
+        let start = path.pose_at(0.0);
+        assert!((start.position - Vec3::new(0.0, 2.0, 10.0)).length() < 1e-5);
+        assert!((start.rotation * Vec3::NEG_Z - Vec3::NEG_Z).length() < 1e-4);
+        assert_eq!(start.fov, Some(70.0));
+
+        // Halfway: fov and rotation halfway too (yaw 45 looks toward -X, -Z)
+        let middle = path.pose_at(1.0);
+        assert_eq!(middle.fov, Some(60.0));
+        let forward = middle.rotation * Vec3::NEG_Z;
+        assert!((forward - Vec3::new(-1.0, 0.0, -1.0).normalize()).length() < 1e-4);
+        assert!((path.pose_at(5.0).position - Vec3::new(10.0, 2.0, 0.0)).length() < 1e-5);
+
+        assert!(CameraPath::parse("keys: []").is_err());
+        assert!(CameraPath::parse("keys: [{ time: 0, position: [0, 0, 0] }]").is_err());
+        assert!(CameraPath::parse("keys: [{ time: 0, position: [0, 0, 0], rotation: [0, 0, 0], look_at: [1, 0, 0] }]").is_err());
+    }
+
+    #[test]
+    fn test_playback_stretches_and_finishes() {
+        let mut player = CameraPathPlayer::default();
+        let path = Rc::new(CameraPath::parse(FLYBY).unwrap());
+        player.start("flyby", path.clone(), Some(4.0));
+        assert!(player.locks_player());
+        assert!(player.update(2.0).is_empty());
+        // Two seconds into four is halfway along the two-second path
+        assert_eq!(player.camera().unwrap().fov, Some(60.0));
+        assert!(player.update(2.0).is_empty());
+        assert_eq!(player.camera().unwrap().fov, Some(50.0));
+
+        let finished = player.update(0.1);
+        assert_eq!(finished.len(), 1);
+        assert_eq!((finished[0]["name"].as_str(), finished[0]["stopped"].as_bool()), (Some("flyby"), Some(false)));
+        assert!(!player.is_playing() && player.camera().is_none());
+
+        player.start("flyby", path, None);
+        assert_eq!(player.current(), Some(("flyby", 0.0)));
+        player.stop();
+        assert_eq!(player.update(0.1)[0]["stopped"], true);
+        assert!(player.play("flyby", Some(-1.0)).is_err());
+    }
+}
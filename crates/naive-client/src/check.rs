@@ -0,0 +1,759 @@
+//! `naive check` — parse every scene of a project and apply its lint rules.
+//!
+//! Rules live under `lint:` in naive.yaml. Each one reports at `warning`
+//! (the default) or `error`; any error makes `naive check` exit with status
+//! 1, so CI can hold back a scene that breaks them. A scene that fails to
+//! parse is always an error.
+//!
+//! Budget rules compare each scene against an estimate of what it costs:
+//! GPU memory for its textures, meshes and splats, draw calls, lights and
+//! scripts. The estimate reads the asset files without a GPU, so it doesn't
+//! see what the render pipeline allocates for itself.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use naive_core::scene::{ComponentMap, SceneFile};
+use serde::Deserialize;
+
+use crate::mesh::MeshFootprint;
+use crate::project_config::NaiveConfig;
+
+/// Folder the scenes are found in, relative to the project root.
+const SCENES_DIR: &str = "scenes";
+
+/// Lint rules (`lint:` in naive.yaml).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct LintConfig {
+    /// Scenes that ship, as scene files or folders relative to the project
+    /// root. Without a list every scene ships.
+    pub shipped: Option<Vec<String>>,
+    /// Most light components in one scene.
+    pub max_lights: Option<LimitRule>,
+    /// Most gaussians across a scene's splat clouds.
+    pub max_splats: Option<LimitRule>,
+    /// Most estimated GPU memory for a scene's textures, meshes and splats, in MB.
+    pub max_gpu_memory_mb: Option<LimitRule>,
+    /// Most estimated draw calls in one scene.
+    pub max_draw_calls: Option<LimitRule>,
+    /// Most scripted entities in one scene.
+    pub max_scripts: Option<LimitRule>,
+    /// Tags every entity with a given tag must also carry.
+    #[serde(default)]
+    pub required_tags: Vec<RequiredTagsRule>,
+    /// Naming convention for entity ids.
+    pub naming: Option<NamingRule>,
+    /// Components shipped scenes may not use.
+    pub forbidden_components: Option<ForbiddenComponentsRule>,
+}
+
+/// How a broken rule is reported.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    #[default]
+    Warning,
+    /// Fails `naive check`.
+    Error,
+}
+
+impl std::fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// An upper bound per scene.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LimitRule {
+    pub max: u64,
+    #[serde(default)]
+    pub level: LintLevel,
+}
+
+/// Entities tagged `tag` must also carry every tag in `require`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RequiredTagsRule {
+    pub tag: String,
+    pub require: Vec<String>,
+    #[serde(default)]
+    pub level: LintLevel,
+}
+
+/// Entity ids follow `convention`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NamingRule {
+    pub convention: NamingConvention,
+    #[serde(default)]
+    pub level: LintLevel,
+}
+
+/// An id naming convention (`naming.convention`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamingConvention {
+    /// `goblin_archer_2`
+    SnakeCase,
+    /// `goblin-archer-2`
+    KebabCase,
+    /// `goblinArcher2`
+    CamelCase,
+    /// `GoblinArcher2`
+    PascalCase,
+}
+
+impl NamingConvention {
+    /// Whether `id` follows the convention.
+    pub fn matches(self, id: &str) -> bool {
+        let Some(first) = id.chars().next() else {
+            return false;
+        };
+        let words = |separator: char| {
+            first.is_ascii_lowercase()
+                && !id.ends_with(separator)
+                && !id.contains(&format!("{0}{0}", separator))
+                && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == separator)
+        };
+        match self {
+            Self::SnakeCase => words('_'),
+            Self::KebabCase => words('-'),
+            Self::CamelCase => first.is_ascii_lowercase() && id.chars().all(|c| c.is_ascii_alphanumeric()),
+            Self::PascalCase => first.is_ascii_uppercase() && id.chars().all(|c| c.is_ascii_alphanumeric()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::SnakeCase => "snake_case",
+            Self::KebabCase => "kebab_case",
+            Self::CamelCase => "camel_case",
+            Self::PascalCase => "pascal_case",
+        }
+    }
+}
+
+/// Components no shipped scene may use, by their scene YAML key.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ForbiddenComponentsRule {
+    pub components: Vec<String>,
+    #[serde(default)]
+    pub level: LintLevel,
+}
+
+/// One broken rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub level: LintLevel,
+    /// Scene path relative to the project root.
+    pub scene: String,
+    /// The entity at fault, for rules about single entities.
+    pub entity: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.entity {
+            Some(entity) => write!(f, "{}: {}: entity '{}': {}", self.level, self.scene, entity, self.message),
+            None => write!(f, "{}: {}: {}", self.level, self.scene, self.message),
+        }
+    }
+}
+
+/// The outcome of `naive check`.
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Scenes checked.
+    pub scenes: usize,
+    pub findings: Vec<LintFinding>,
+    /// Each scene's estimate, when asked for.
+    pub budgets: Vec<(String, SceneBudget)>,
+}
+
+impl CheckReport {
+    pub fn errors(&self) -> usize {
+        self.findings.iter().filter(|f| f.level == LintLevel::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.findings.iter().filter(|f| f.level == LintLevel::Warning).count()
+    }
+}
+
+impl LintConfig {
+    /// Whether the scene at `scene_path` (relative to the project root) ships.
+    pub fn ships(&self, scene_path: &str) -> bool {
+        let Some(shipped) = &self.shipped else {
+            return true;
+        };
+        shipped.iter().any(|entry| {
+            let entry = entry.trim_end_matches('/');
+            scene_path == entry || scene_path.strip_prefix(entry).is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// The rules that measure asset files, which need `estimate_scene`.
+    fn estimate_rules(&self) -> impl Iterator<Item = &LimitRule> {
+        [&self.max_splats, &self.max_gpu_memory_mb, &self.max_draw_calls].into_iter().flatten()
+    }
+}
+
+/// Sizes of the assets a scene uses. `ProjectAssets` reads them from the
+/// project's files.
+pub trait AssetSizes {
+    fn mesh(&mut self, mesh: &str) -> Result<MeshFootprint, String>;
+    /// The texture maps a material names.
+    fn material_textures(&mut self, material: &str) -> Result<Vec<String>, String>;
+    /// Bytes of a texture on the GPU.
+    fn texture(&mut self, texture: &str) -> Result<u64, String>;
+    /// Gaussians in a splat source and their bytes on the GPU.
+    fn splats(&mut self, source: &str) -> Result<(u64, u64), String>;
+}
+
+/// Measures a project's asset files, each once across all scenes.
+pub struct ProjectAssets<'a> {
+    root: &'a Path,
+    meshes: HashMap<String, Result<MeshFootprint, String>>,
+    materials: HashMap<String, Result<Vec<String>, String>>,
+    textures: HashMap<String, Result<u64, String>>,
+    splats: HashMap<String, Result<(u64, u64), String>>,
+}
+
+impl<'a> ProjectAssets<'a> {
+    pub fn new(root: &'a Path) -> Self {
+        Self {
+            root,
+            meshes: HashMap::new(),
+            materials: HashMap::new(),
+            textures: HashMap::new(),
+            splats: HashMap::new(),
+        }
+    }
+}
+
+impl AssetSizes for ProjectAssets<'_> {
+    fn mesh(&mut self, mesh: &str) -> Result<MeshFootprint, String> {
+        let root = self.root;
+        self.meshes
+            .entry(mesh.to_string())
+            .or_insert_with(|| crate::mesh::measure_mesh(root, mesh).map_err(|e| e.to_string()))
+            .clone()
+    }
+
+    fn material_textures(&mut self, material: &str) -> Result<Vec<String>, String> {
+        let root = self.root;
+        self.materials
+            .entry(material.to_string())
+            .or_insert_with(|| crate::material::texture_maps(root, material).map_err(|e| e.to_string()))
+            .clone()
+    }
+
+    fn texture(&mut self, texture: &str) -> Result<u64, String> {
+        let root = self.root;
+        self.textures
+            .entry(texture.to_string())
+            .or_insert_with(|| crate::texture_cache::texture_bytes(&root.join(texture)))
+            .clone()
+    }
+
+    fn splats(&mut self, source: &str) -> Result<(u64, u64), String> {
+        let root = self.root;
+        self.splats
+            .entry(source.to_string())
+            .or_insert_with(|| {
+                crate::splat::measure_splats(root, source)
+                    .map(|(count, bytes)| (count as u64, bytes))
+                    .map_err(|e| e.to_string())
+            })
+            .clone()
+    }
+}
+
+/// What a scene is estimated to cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SceneBudget {
+    pub texture_bytes: u64,
+    /// Vertex and index buffers, LOD levels included.
+    pub mesh_bytes: u64,
+    pub splat_bytes: u64,
+    /// Gaussians across the splat clouds, counted per entity.
+    pub splats: u64,
+    pub lights: u64,
+    pub draw_calls: u64,
+    pub scripts: u64,
+}
+
+impl SceneBudget {
+    pub fn gpu_bytes(&self) -> u64 {
+        self.texture_bytes + self.mesh_bytes + self.splat_bytes
+    }
+}
+
+impl std::fmt::Display for SceneBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} MB GPU memory ({:.1} MB textures, {:.1} MB meshes, {:.1} MB splats), {} lights, {} draw calls, {} scripts",
+            megabytes(self.gpu_bytes()),
+            megabytes(self.texture_bytes),
+            megabytes(self.mesh_bytes),
+            megabytes(self.splat_bytes),
+            self.lights,
+            self.draw_calls,
+            self.scripts,
+        )
+    }
+}
+
+fn megabytes(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Adds up a scene's assets, counting each mesh, texture and splat cloud's
+/// memory once however many entities share it, as the engine's caches do.
+struct Estimate<'a, A> {
+    assets: &'a mut A,
+    budget: SceneBudget,
+    /// Assets already counted (or reported), by kind and path.
+    seen: HashSet<(&'static str, String)>,
+    /// Assets that couldn't be measured, with the first entity using each.
+    unmeasured: Vec<(String, String)>,
+}
+
+impl<A: AssetSizes> Estimate<'_, A> {
+    /// Count `mesh`, scaled to `ratio` of its geometry for a decimated LOD
+    /// level. Returns its draws per instance.
+    fn mesh(&mut self, entity: &str, mesh: &str, ratio: f32) -> u64 {
+        let first = self.seen.insert(("mesh", format!("{}@{}", mesh, ratio)));
+        match self.assets.mesh(mesh) {
+            Ok(footprint) => {
+                if first {
+                    self.budget.mesh_bytes += (footprint.geometry_bytes() as f64 * ratio as f64) as u64;
+                    if ratio == 1.0 {
+                        self.budget.texture_bytes += footprint.texture_bytes;
+                    }
+                }
+                footprint.draws
+            }
+            Err(e) => {
+                if first {
+                    self.unmeasured.push((entity.to_string(), e));
+                }
+                1
+            }
+        }
+    }
+
+    fn material(&mut self, entity: &str, material: &str) {
+        if !self.seen.insert(("material", material.to_string())) {
+            return;
+        }
+        match self.assets.material_textures(material) {
+            Ok(textures) => textures.iter().for_each(|texture| self.texture(entity, texture)),
+            Err(e) => self.unmeasured.push((entity.to_string(), e)),
+        }
+    }
+
+    fn texture(&mut self, entity: &str, texture: &str) {
+        if !self.seen.insert(("texture", texture.to_string())) {
+            return;
+        }
+        match self.assets.texture(texture) {
+            Ok(bytes) => self.budget.texture_bytes += bytes,
+            Err(e) => self.unmeasured.push((entity.to_string(), e)),
+        }
+    }
+
+    fn splats(&mut self, entity: &str, source: &str) {
+        let first = self.seen.insert(("splats", source.to_string()));
+        match self.assets.splats(source) {
+            Ok((count, bytes)) => {
+                self.budget.splats += count;
+                if first {
+                    self.budget.splat_bytes += bytes;
+                }
+            }
+            Err(e) if first => self.unmeasured.push((entity.to_string(), e)),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Estimate what a scene costs. Returns the estimate and the assets that
+/// couldn't be measured, each with the first entity using it.
+///
+/// Draw calls count one per submesh of each mesh renderer, water and
+/// foliage (instanced), again for mesh renderers that cast shadows when
+/// the scene has a directional light, plus one per terrain chunk, splat
+/// cloud, particle emitter and skybox. Terrain geometry isn't counted.
+pub fn estimate_scene(scene: &SceneFile, assets: &mut impl AssetSizes) -> (SceneBudget, Vec<(String, String)>) {
+    let mut estimate = Estimate {
+        assets,
+        budget: SceneBudget::default(),
+        seen: HashSet::new(),
+        unmeasured: Vec::new(),
+    };
+    let shadows = scene.entities.iter().any(|e| e.components.directional_light.is_some());
+
+    for entity in &scene.entities {
+        let (id, components) = (entity.id.as_str(), &entity.components);
+        let mut draws = 0;
+        if let Some(renderer) = &components.mesh_renderer {
+            let base = estimate.mesh(id, &renderer.mesh, 1.0);
+            draws += if renderer.cast_shadows && shadows { base * 2 } else { base };
+            estimate.material(id, &renderer.material);
+            for level in renderer.lod.iter().flat_map(|lod| &lod.levels) {
+                match (&level.mesh, level.ratio) {
+                    (Some(mesh), _) => {
+                        estimate.mesh(id, mesh, 1.0);
+                    }
+                    (None, Some(ratio)) => {
+                        estimate.mesh(id, &renderer.mesh, ratio.clamp(0.0, 1.0));
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+        if let Some(water) = &components.water {
+            draws += estimate.mesh(id, &water.mesh, 1.0);
+        }
+        if let Some(foliage) = &components.foliage {
+            draws += estimate.mesh(id, &foliage.mesh, 1.0);
+        }
+        if let Some(terrain) = &components.terrain {
+            draws += terrain.chunks as u64 * terrain.chunks as u64;
+            let layers = terrain.layers.iter().filter_map(|layer| layer.texture.as_ref());
+            for texture in terrain.splat_map.iter().chain(layers) {
+                estimate.texture(id, texture);
+            }
+        }
+        if let Some(splat) = &components.gaussian_splat {
+            estimate.splats(id, &splat.source);
+            draws += 1;
+        }
+        if let Some(skybox) = &components.skybox {
+            for texture in skybox.texture.iter().chain(skybox.cubemap.iter().flatten()) {
+                estimate.texture(id, texture);
+            }
+            draws += 1;
+        }
+        draws += components.particle_emitter.is_some() as u64;
+
+        estimate.budget.draw_calls += draws;
+        estimate.budget.lights += light_count(components);
+        estimate.budget.scripts += components.script.is_some() as u64;
+    }
+    (estimate.budget, estimate.unmeasured)
+}
+
+/// Apply the lint rules to a parsed scene. `assets` is only asked for sizes
+/// when a rule needs the estimate (`max_splats`, `max_gpu_memory_mb`,
+/// `max_draw_calls`).
+pub fn lint_scene(
+    config: &LintConfig,
+    scene_path: &str,
+    scene: &SceneFile,
+    assets: &mut impl AssetSizes,
+) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    let mut report = |level: LintLevel, entity: Option<&str>, message: String| {
+        findings.push(LintFinding {
+            level,
+            scene: scene_path.to_string(),
+            entity: entity.map(str::to_string),
+            message,
+        });
+    };
+
+    if let Some(rule) = &config.max_lights {
+        let lights: u64 = scene.entities.iter().map(|e| light_count(&e.components)).sum();
+        if lights > rule.max {
+            report(rule.level, None, format!("{} lights, more than the {} allowed", lights, rule.max));
+        }
+    }
+
+    if let Some(rule) = &config.max_scripts {
+        let scripts = scene.entities.iter().filter(|e| e.components.script.is_some()).count() as u64;
+        if scripts > rule.max {
+            report(rule.level, None, format!("{} scripts, more than the {} allowed", scripts, rule.max));
+        }
+    }
+
+    if config.estimate_rules().next().is_some() {
+        let (budget, unmeasured) = estimate_scene(scene, assets);
+        // An asset that can't be measured fails as hard as the strictest rule it could break
+        let level = if config.estimate_rules().any(|r| r.level == LintLevel::Error) {
+            LintLevel::Error
+        } else {
+            LintLevel::Warning
+        };
+        for (entity, e) in unmeasured {
+            report(level, Some(&entity), format!("couldn't measure: {}", e));
+        }
+        if let Some(rule) = config.max_splats.as_ref().filter(|r| budget.splats > r.max) {
+            report(rule.level, None, format!("{} splats, more than the {} allowed", budget.splats, rule.max));
+        }
+        if let Some(rule) = config.max_gpu_memory_mb.as_ref().filter(|r| budget.gpu_bytes() > r.max.saturating_mul(1 << 20)) {
+            report(
+                rule.level,
+                None,
+                format!("{:.1} MB of GPU memory, more than the {} MB allowed", megabytes(budget.gpu_bytes()), rule.max),
+            );
+        }
+        if let Some(rule) = config.max_draw_calls.as_ref().filter(|r| budget.draw_calls > r.max) {
+            report(rule.level, None, format!("{} draw calls, more than the {} allowed", budget.draw_calls, rule.max));
+        }
+    }
+
+    for rule in &config.required_tags {
+        for entity in scene.entities.iter().filter(|e| e.tags.contains(&rule.tag)) {
+            let missing: Vec<&str> = rule.require.iter().filter(|t| !entity.tags.contains(*t)).map(String::as_str).collect();
+            if !missing.is_empty() {
+                report(
+                    rule.level,
+                    Some(&entity.id),
+                    format!("tagged '{}' but missing {}", rule.tag, quoted_list(&missing)),
+                );
+            }
+        }
+    }
+
+    if let Some(rule) = &config.naming {
+        for entity in scene.entities.iter().filter(|e| !rule.convention.matches(&e.id)) {
+            report(rule.level, Some(&entity.id), format!("id isn't {}", rule.convention.name()));
+        }
+    }
+
+    if let Some(rule) = config.forbidden_components.as_ref().filter(|_| config.ships(scene_path)) {
+        for entity in &scene.entities {
+            let used = component_names(&entity.components);
+            let forbidden: Vec<&str> = rule.components.iter().filter(|c| used.contains(*c)).map(String::as_str).collect();
+            if !forbidden.is_empty() {
+                report(
+                    rule.level,
+                    Some(&entity.id),
+                    format!("uses {} in a shipped scene", quoted_list(&forbidden)),
+                );
+            }
+        }
+    }
+
+    findings
+}
+
+/// Check every scene under `scenes/` against the project's lint rules, and
+/// with `budgets` estimate what each one costs.
+pub fn check_project(config: &NaiveConfig, project_root: &Path, budgets: bool) -> CheckReport {
+    let mut report = CheckReport::default();
+    let mut scene_files = Vec::new();
+    collect_scenes(&project_root.join(SCENES_DIR), &mut scene_files);
+    scene_files.sort();
+
+    let mut assets = ProjectAssets::new(project_root);
+    for path in scene_files {
+        let scene_path = path
+            .strip_prefix(project_root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        report.scenes += 1;
+        let scene = match naive_core::scene::load_scene(&path) {
+            Ok(scene) => scene,
+            Err(e) => {
+                report.findings.push(LintFinding {
+                    level: LintLevel::Error,
+                    scene: scene_path,
+                    entity: None,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        report.findings.extend(lint_scene(&config.lint, &scene_path, &scene, &mut assets));
+        if budgets {
+            report.budgets.push((scene_path, estimate_scene(&scene, &mut assets).0));
+        }
+    }
+    report
+}
+
+/// Scene YAML files under `dir`, recursively.
+fn collect_scenes(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_scenes(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml") {
+            out.push(path);
+        }
+    }
+}
+
+/// Light components on an entity.
+fn light_count(components: &ComponentMap) -> u64 {
+    components.point_light.is_some() as u64
+        + components.spot_light.is_some() as u64
+        + components.directional_light.is_some() as u64
+}
+
+/// The scene YAML keys of the components an entity has, including unknown ones.
+fn component_names(components: &ComponentMap) -> Vec<String> {
+    let Ok(serde_yaml::Value::Mapping(map)) = serde_yaml::to_value(components) else {
+        return Vec::new();
+    };
+    map.into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .filter_map(|(key, _)| key.as_str().map(str::to_string))
+        .collect()
+}
+
+fn quoted_list(items: &[&str]) -> String {
+    items.iter().map(|item| format!("'{}'", item)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every mesh has two submeshes and a 1 MB image, every material one
+    /// 4 MB texture, every splat cloud 5000 gaussians.
+    struct FakeAssets;
+
+    impl AssetSizes for FakeAssets {
+        fn mesh(&mut self, mesh: &str) -> Result<MeshFootprint, String> {
+            if mesh.contains("missing") {
+                return Err(format!("{} not found", mesh));
+            }
+            Ok(MeshFootprint { vertices: 1000, indices: 3000, draws: 2, texture_bytes: 1 << 20 })
+        }
+
+        fn material_textures(&mut self, material: &str) -> Result<Vec<String>, String> {
+            Ok(vec![material.replace(".yaml", ".png")])
+        }
+
+        fn texture(&mut self, _texture: &str) -> Result<u64, String> {
+            Ok(4 << 20)
+        }
+
+        fn splats(&mut self, _source: &str) -> Result<(u64, u64), String> {
+            Ok((5000, 5000 * 80))
+        }
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        let scene = naive_core::scene::parse_scene(
+            r#"
+name: Arena
+entities:
+  - id: sun
+    components:
+      directional_light: {}
+  - id: torch_1
+    components:
+      point_light: {}
+  - id: GoblinBoss
+    tags: [enemy, hostile]
+    components:
+      gaussian_splat: { source: assets/splats/boss.ply }
+  - id: goblin_2
+    tags: [enemy]
+    components:
+      debug_label: { text: "spawn" }
+"#,
+        )
+        .unwrap();
+        let config: LintConfig = serde_yaml::from_str(
+            r#"
+shipped: [scenes/levels]
+max_lights: { max: 1, level: error }
+max_splats: { max: 1000 }
+required_tags:
+  - { tag: enemy, require: [hostile], level: error }
+naming: { convention: snake_case }
+forbidden_components: { components: [debug_label], level: error }
+"#,
+        )
+        .unwrap();
+
+        let findings = lint_scene(&config, "scenes/levels/arena.yaml", &scene, &mut FakeAssets);
+        let messages: Vec<String> = findings.iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "error: scenes/levels/arena.yaml: 2 lights, more than the 1 allowed",
+                "warning: scenes/levels/arena.yaml: 5000 splats, more than the 1000 allowed",
+                "error: scenes/levels/arena.yaml: entity 'goblin_2': tagged 'enemy' but missing 'hostile'",
+                "warning: scenes/levels/arena.yaml: entity 'GoblinBoss': id isn't snake_case",
+                "error: scenes/levels/arena.yaml: entity 'goblin_2': uses 'debug_label' in a shipped scene",
+            ]
+        );
+
+        // Forbidden components only matter in shipped scenes
+        let findings = lint_scene(&config, "scenes/dev/arena.yaml", &scene, &mut FakeAssets);
+        assert!(!findings.iter().any(|f| f.message.contains("shipped")));
+
+        assert!(NamingConvention::KebabCase.matches("goblin-archer-2"));
+        assert!(!NamingConvention::SnakeCase.matches("goblin__archer"));
+        assert!(NamingConvention::PascalCase.matches("GoblinArcher2"));
+        assert!(!NamingConvention::CamelCase.matches("GoblinArcher"));
+    }
+
+    #[test]
+    fn test_budget_rules() {
+        let scene = naive_core::scene::parse_scene(
+            r#"
+name: Quarry
+entities:
+  - id: sun
+    components:
+      directional_light: {}
+  - id: rock_1
+    components:
+      mesh_renderer: { mesh: assets/meshes/rock.glb, material: materials/stone.yaml }
+      script: { source: scripts/rock.lua }
+  - id: rock_2
+    components:
+      mesh_renderer: { mesh: assets/meshes/rock.glb, material: materials/stone.yaml, cast_shadows: false }
+      script: { source: scripts/rock.lua }
+  - id: statue
+    components:
+      mesh_renderer: { mesh: assets/meshes/missing.glb, material: materials/stone.yaml }
+"#,
+        )
+        .unwrap();
+
+        // The rock and the stone texture count once; shadow casters draw twice
+        let (budget, unmeasured) = estimate_scene(&scene, &mut FakeAssets);
+        assert_eq!(budget.texture_bytes, 5 << 20);
+        assert_eq!(budget.mesh_bytes, MeshFootprint { vertices: 1000, indices: 3000, ..Default::default() }.geometry_bytes());
+        assert_eq!((budget.lights, budget.draw_calls, budget.scripts), (1, 8, 2));
+        assert_eq!(unmeasured, [("statue".to_string(), "assets/meshes/missing.glb not found".to_string())]);
+
+        let config: LintConfig = serde_yaml::from_str(
+            r#"
+max_gpu_memory_mb: { max: 4, level: error }
+max_draw_calls: { max: 6 }
+max_scripts: { max: 1 }
+"#,
+        )
+        .unwrap();
+        let findings = lint_scene(&config, "scenes/quarry.yaml", &scene, &mut FakeAssets);
+        let messages: Vec<String> = findings.iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "warning: scenes/quarry.yaml: 2 scripts, more than the 1 allowed",
+                "error: scenes/quarry.yaml: entity 'statue': couldn't measure: assets/meshes/missing.glb not found",
+                "error: scenes/quarry.yaml: 5.1 MB of GPU memory, more than the 4 MB allowed",
+                "warning: scenes/quarry.yaml: 8 draw calls, more than the 6 allowed",
+            ]
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use clap::{Parser, Subcommand};
 
+/// Frames a headless run simulates and renders when `--frames` is not given.
+pub const DEFAULT_HEADLESS_FRAMES: u64 = 300;
+
+/// Output file for `--screenshot-after` when `--screenshot-path` is not given.
+pub const DEFAULT_SCREENSHOT_PATH: &str = "screenshot.png";
+
 #[derive(Parser, Debug)]
 #[command(name = "naive", version, about = "nAIVE - The AI-Native Game Engine")]
 pub struct CliArgs {
-    /// Subcommand (init, run, test, build, publish)
+    /// Subcommand (init, run, test, check, docs, build, publish, splat)
     #[command(subcommand)]
     pub command: Option<Command>,
 
@@ -31,6 +37,59 @@ pub struct CliArgs {
     #[arg(long, global = true)]
     pub hud: bool,
 
+    /// Render offscreen without a window (same as `--output headless`)
+    #[arg(long, global = true)]
+    pub headless: bool,
+
+    /// Frames to simulate and render before exiting (headless runs only)
+    #[arg(long, global = true, default_value_t = DEFAULT_HEADLESS_FRAMES)]
+    pub frames: u64,
+
+    /// Capture a screenshot after N frames have been rendered
+    #[arg(long, global = true)]
+    pub screenshot_after: Option<u64>,
+
+    /// Output PNG for `--screenshot-after`
+    #[arg(long, global = true, default_value = DEFAULT_SCREENSHOT_PATH)]
+    pub screenshot_path: String,
+
+    /// Lua VM memory cap in megabytes (0 = unlimited; overrides `scripting:` in naive.yaml)
+    #[arg(long, global = true)]
+    pub lua_memory_limit: Option<u64>,
+
+    /// Milliseconds per frame spent on incremental Lua garbage collection
+    /// (overrides `scripting:` in naive.yaml)
+    #[arg(long, global = true)]
+    pub lua_gc_budget: Option<f32>,
+
+    /// Draw everything at full detail, ignoring the quality preset and distance
+    #[arg(long, global = true)]
+    pub force_lod0: bool,
+
+    /// Draw everything that would be culled (by distance or visibility)
+    #[arg(long, global = true)]
+    pub no_culling: bool,
+
+    /// Override a value in the scripts' `config` table (repeatable, dotted keys for nesting)
+    #[arg(long = "config", global = true, value_name = "KEY=VALUE")]
+    pub config: Vec<String>,
+
+    /// Override a render pipeline feature flag for `enabled_if: setting.<name>` (repeatable)
+    #[arg(long = "setting", global = true, value_name = "NAME=ON|OFF")]
+    pub settings: Vec<String>,
+
+    /// Load the scene's auto-saved live edits left by a crashed session
+    #[arg(long, global = true)]
+    pub recover: bool,
+
+    /// Log sRGB/linear mismatches in the render pipeline and loaded textures
+    #[arg(long, global = true)]
+    pub color_audit: bool,
+
+    /// GPU backend, adapter, surface format and present mode
+    #[command(flatten)]
+    pub graphics: crate::gpu_options::GraphicsOptions,
+
     /// Editor mode (set internally by `naive edit`)
     #[arg(skip)]
     pub editor_mode: bool,
@@ -59,6 +118,9 @@ pub enum Command {
         /// Target platform (macos, windows, linux)
         #[arg(long)]
         target: Option<String>,
+        /// Ship scripts as precompiled bytecode (overrides build.scripts)
+        #[arg(long)]
+        bytecode: bool,
     },
     /// Publish to nAIVE world server
     Publish,
@@ -95,6 +157,67 @@ pub enum Command {
         #[arg(long)]
         mock: Option<String>,
     },
+    /// Parse every scene and apply the lint rules from naive.yaml
+    Check {
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        strict: bool,
+        /// Print each scene's estimated GPU memory, lights, draw calls and scripts
+        #[arg(long)]
+        budgets: bool,
+    },
+    /// Generate Lua API docs (Markdown, HTML) and a Lua language server stub
+    Docs {
+        /// Output folder
+        #[arg(long, default_value = "docs/api")]
+        out: String,
+    },
+    /// Report the GPU adapters and graphics settings the engine would use
+    Doctor,
+    /// Render a scene thumbnail to a PNG (no window required)
+    Preview {
+        /// Scene file to render
+        scene: String,
+        /// Output PNG path
+        #[arg(long, default_value = "preview.png")]
+        out: String,
+        /// Entity ID of the camera to render from (defaults to the main camera)
+        #[arg(long)]
+        camera: Option<String>,
+        /// Image width in pixels
+        #[arg(long, default_value_t = 1280)]
+        width: u32,
+        /// Image height in pixels
+        #[arg(long, default_value_t = 720)]
+        height: u32,
+    },
+    /// Process Gaussian splat files
+    Splat {
+        #[command(subcommand)]
+        action: SplatCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SplatCommand {
+    /// Prune, decimate and reorder splat files into engine-ready .nsplat files
+    Optimize {
+        /// Splat files to optimize (.ply, .splat, .ksplat, .spz or .nsplat)
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        /// Output path (one input only; defaults to the input with a .nsplat extension)
+        #[arg(long)]
+        out: Option<String>,
+        /// Share of splats to keep after pruning, most prominent first
+        #[arg(long, default_value_t = 1.0)]
+        decimate: f32,
+        /// Drop splats less opaque than this
+        #[arg(long, default_value_t = 0.0)]
+        prune_alpha: f32,
+        /// Order of the splats in the output
+        #[arg(long, value_enum, default_value_t = crate::splat_optimize::SplatOrder::Morton)]
+        reorder: crate::splat_optimize::SplatOrder,
+    },
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -102,3 +225,28 @@ pub enum OutputMode {
     Window,
     Headless,
 }
+
+impl CliArgs {
+    /// Whether to render offscreen instead of opening a window.
+    pub fn is_headless(&self) -> bool {
+        self.headless || matches!(self.output, OutputMode::Headless)
+    }
+
+    /// Lua limits from `--lua-memory-limit`/`--lua-gc-budget`, falling back to
+    /// `configured` (naive.yaml) for flags that were not given.
+    pub fn scripting_limits(&self, configured: &crate::project_config::ScriptingConfig) -> crate::project_config::ScriptingConfig {
+        crate::project_config::ScriptingConfig {
+            memory_limit_mb: self.lua_memory_limit.or(configured.memory_limit_mb),
+            gc_budget_ms: self.lua_gc_budget.or(configured.gc_budget_ms),
+            ..*configured
+        }
+    }
+
+    /// Level-of-detail and culling locks from `--force-lod0` and `--no-culling`.
+    pub fn lod_lock(&self) -> crate::pipeline::LodLock {
+        crate::pipeline::LodLock {
+            force_lod0: self.force_lod0,
+            disable_culling: self.no_culling,
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Color grading for the tonemap pass: exposure, contrast, saturation and an
+//! optional 3D lookup table.
+//!
+//! The pass's `grading:` block sets the pipeline default; a scene's
+//! `settings.color_grading` replaces it while that scene is loaded, and
+//! scripts can override the numeric controls with `render.set`. LUTs map
+//! sRGB-encoded color to sRGB-encoded color, so the tonemap shader encodes
+//! before the lookup and decodes after it. Both `.cube` files and strip PNGs
+//! (Unreal/Unity style, N*N by N) are supported.
+
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::{CompiledPipeline, RenderDebugState};
+use crate::scene::ColorGradingSettings;
+
+/// Largest LUT edge accepted (64^3 is already far beyond what grading needs).
+pub const MAX_LUT_SIZE: u32 = 64;
+
+/// Grading uniforms read by the tonemap pass.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ColorGradingUniforms {
+    pub exposure: f32,
+    pub contrast: f32,
+    pub saturation: f32,
+    /// LUT edge length, 0 when no LUT is bound.
+    pub lut_size: f32,
+}
+
+impl ColorGradingUniforms {
+    /// Uniforms for `settings`, with any script overrides from `debug`.
+    pub fn new(settings: &ColorGradingSettings, lut_size: u32, debug: &RenderDebugState) -> Self {
+        Self {
+            exposure: debug.exposure.unwrap_or(settings.exposure),
+            contrast: debug.contrast.unwrap_or(settings.contrast),
+            saturation: debug.saturation.unwrap_or(settings.saturation),
+            lut_size: lut_size as f32,
+        }
+    }
+}
+
+/// A 3D LUT, red varying fastest, then green, then blue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLut {
+    pub size: u32,
+    pub data: Vec<[f32; 3]>,
+}
+
+impl ColorLut {
+    /// The identity LUT of edge `size`.
+    pub fn identity(size: u32) -> Self {
+        let max = (size - 1).max(1) as f32;
+        let mut data = Vec::with_capacity((size * size * size) as usize);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push([r as f32 / max, g as f32 / max, b as f32 / max]);
+                }
+            }
+        }
+        Self { size, data }
+    }
+
+    /// Parse an Adobe/Resolve `.cube` file. Only 3D LUTs over the default
+    /// [0, 1] domain are accepted.
+    pub fn parse_cube(text: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut data = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let first = words.next().unwrap_or_default();
+            match first {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let n: u32 = words
+                        .next()
+                        .and_then(|w| w.parse().ok())
+                        .ok_or_else(|| format!("line {}: invalid LUT_3D_SIZE", line_no + 1))?;
+                    size = Some(n);
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                    let expected = if first == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                    let values: Vec<f32> = words.filter_map(|w| w.parse().ok()).collect();
+                    if values.len() != 3 || values.iter().any(|&v| v != expected) {
+                        return Err(format!("line {}: only the default {} is supported", line_no + 1, first));
+                    }
+                }
+                _ => {
+                    let values: Result<Vec<f32>, _> = line.split_whitespace().map(str::parse).collect();
+                    match values.ok().as_deref() {
+                        Some(&[r, g, b]) => data.push([r, g, b]),
+                        _ => return Err(format!("line {}: expected 'r g b', got '{}'", line_no + 1, line)),
+                    }
+                }
+            }
+        }
+        let size = size.ok_or("missing LUT_3D_SIZE")?;
+        Self::check_size(size)?;
+        let expected = (size * size * size) as usize;
+        if data.len() != expected {
+            return Err(format!("expected {} entries for size {}, found {}", expected, size, data.len()));
+        }
+        Ok(Self { size, data })
+    }
+
+    /// Read a strip LUT: N tiles of N x N side by side, red across each tile,
+    /// green down it and blue from tile to tile.
+    pub fn from_strip(image: &image::RgbImage) -> Result<Self, String> {
+        let (width, height) = image.dimensions();
+        let size = height;
+        if width != size * size {
+            return Err(format!("strip LUT must be N*N x N pixels, got {}x{}", width, height));
+        }
+        Self::check_size(size)?;
+        let mut data = Vec::with_capacity((size * size * size) as usize);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let p = image.get_pixel(b * size + r, g);
+                    data.push([p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0]);
+                }
+            }
+        }
+        Ok(Self { size, data })
+    }
+
+    /// Load a `.cube` or strip image LUT from the project.
+    pub fn load(project_root: &Path, path: &str) -> Result<Self, String> {
+        let full_path = project_root.join(path);
+        let is_cube = full_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cube"));
+        let result = if is_cube {
+            std::fs::read_to_string(&full_path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Self::parse_cube(&text))
+        } else {
+            image::open(&full_path)
+                .map_err(|e| e.to_string())
+                .and_then(|img| Self::from_strip(&img.to_rgb8()))
+        };
+        result.map_err(|e| format!("Failed to load color LUT '{}': {}", full_path.display(), e))
+    }
+
+    fn check_size(size: u32) -> Result<(), String> {
+        if !(2..=MAX_LUT_SIZE).contains(&size) {
+            return Err(format!("LUT size must be 2 to {} (got {})", MAX_LUT_SIZE, size));
+        }
+        Ok(())
+    }
+
+    /// Upload as an Rgba16Float 3D texture.
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::TextureView {
+        let extent = wgpu::Extent3d { width: self.size, height: self.size, depth_or_array_layers: self.size };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Color Grading LUT"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let mut bytes = Vec::with_capacity(self.data.len() * 8);
+        for texel in &self.data {
+            for c in [texel[0], texel[1], texel[2], 1.0] {
+                bytes.extend_from_slice(&crate::environment::f16_bits(c).to_le_bytes());
+            }
+        }
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &bytes,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(self.size * 8),
+                rows_per_image: Some(self.size),
+            },
+            extent,
+        );
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+}
+
+/// 1x1x1 stand-in bound while no LUT is active (the shader skips the lookup).
+pub fn fallback_lut_view(device: &wgpu::Device) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("Color Grading LUT Fallback"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// The tonemap pass's grading state.
+pub struct ColorGrading {
+    /// From the tonemap pass's `grading:` block.
+    pub pipeline_settings: ColorGradingSettings,
+    /// Settings in effect (the scene's, if it has any).
+    pub settings: ColorGradingSettings,
+    pub lut_view: wgpu::TextureView,
+    /// Edge length of the bound LUT, 0 for the fallback.
+    pub lut_size: u32,
+    pub buffer: wgpu::Buffer,
+    /// LUT path the bound view came from, for `ColorGradingCache::sync`.
+    pub lut_source: Option<String>,
+}
+
+/// Loaded LUT, kept across pipeline recompiles and scene reloads so only a
+/// changed path is read again.
+#[derive(Default)]
+pub struct ColorGradingCache {
+    /// Last path asked for; None means it failed to load (not retried until
+    /// the path changes).
+    loaded: Option<(PathBuf, Option<(wgpu::TextureView, u32)>)>,
+}
+
+impl ColorGradingCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind the scene's grading (or the pipeline's) to the tonemap pass.
+    /// Cheap when nothing changed since the last call.
+    pub fn sync(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        project_root: &Path,
+        scene_grading: Option<&ColorGradingSettings>,
+        compiled: &mut CompiledPipeline,
+    ) {
+        let Some(grading) = &mut compiled.color_grading else {
+            return;
+        };
+        let wanted = scene_grading.unwrap_or(&grading.pipeline_settings).clone();
+        let lut_changed = grading.lut_source != wanted.lut;
+        grading.settings = wanted;
+        if !lut_changed {
+            return;
+        }
+
+        let lut = grading.settings.lut.as_ref().and_then(|path| {
+            let full_path = project_root.join(path);
+            if self.loaded.as_ref().is_none_or(|(loaded, _)| *loaded != full_path) {
+                let lut = match ColorLut::load(project_root, path) {
+                    Ok(lut) => {
+                        tracing::info!("Color LUT loaded: {} ({}^3)", path, lut.size);
+                        Some((lut.upload(device, queue), lut.size))
+                    }
+                    Err(e) => {
+                        tracing::error!("{}", e);
+                        None
+                    }
+                };
+                self.loaded = Some((full_path, lut));
+            }
+            self.loaded.as_ref().and_then(|(_, lut)| lut.clone())
+        });
+        let (view, size) = lut.unwrap_or_else(|| (fallback_lut_view(device), 0));
+        grading.lut_view = view;
+        grading.lut_size = size;
+        grading.lut_source = grading.settings.lut.clone();
+        crate::pipeline::rebuild_bind_groups(device, compiled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cube() {
+        let text = "\
+# comment
+TITLE \"identity\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+0 0 0
+1 0 0
+0 1 0
+1 1 0
+0 0 1
+1 0 1
+0 1 1
+1 1 1
+";
+        let lut = ColorLut::parse_cube(text).unwrap();
+        assert_eq!(lut, ColorLut::identity(2));
+
+        assert!(ColorLut::parse_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(ColorLut::parse_cube("0 0 0\n").is_err());
+        assert!(ColorLut::parse_cube("LUT_3D_SIZE 2\nDOMAIN_MAX 2 2 2\n").is_err());
+        assert!(ColorLut::parse_cube("LUT_1D_SIZE 16\n").is_err());
+    }
+
+    #[test]
+    fn test_strip_layout() {
+        let size = 4u32;
+        let identity = ColorLut::identity(size);
+        let image = image::RgbImage::from_fn(size * size, size, |x, y| {
+            let (r, b, g) = (x % size, x / size, y);
+            let v = |c: u32| (c * 255 / (size - 1)) as u8;
+            image::Rgb([v(r), v(g), v(b)])
+        });
+        let lut = ColorLut::from_strip(&image).unwrap();
+        assert_eq!(lut.size, size);
+        for (a, b) in lut.data.iter().zip(&identity.data) {
+            for c in 0..3 {
+                assert!((a[c] - b[c]).abs() < 1e-6);
+            }
+        }
+        assert!(ColorLut::from_strip(&image::RgbImage::new(10, 4)).is_err());
+    }
+
+    #[test]
+    fn test_uniforms_prefer_script_overrides() {
+        let settings = ColorGradingSettings { exposure: 2.0, saturation: 0.5, ..Default::default() };
+        let debug = RenderDebugState { saturation: Some(1.5), ..Default::default() };
+        let uniforms = ColorGradingUniforms::new(&settings, 16, &debug);
+        assert_eq!(uniforms.exposure, 2.0);
+        assert_eq!(uniforms.contrast, 1.0);
+        assert_eq!(uniforms.saturation, 1.5);
+        assert_eq!(uniforms.lut_size, 16.0);
+    }
+}
@@ -0,0 +1,205 @@
+//! Color spaces of textures and pipeline resources, and the `--color-audit`
+//! checks for gamma mistakes.
+//!
+//! Shaders work in linear light. An sRGB texture format converts on the way
+//! in and out: sampling decodes, rendering encodes. A linear format stores
+//! values as written. Color images are sRGB and data maps (normals,
+//! roughness) are linear, and pipeline resources declare theirs with
+//! `color_space:`. A pass whose shader encodes sRGB itself says so with
+//! `output_color_space: srgb`.
+//!
+//! The audit flags double encoding (an encoded value written to an sRGB
+//! target), missing encoding (linear light on a linear swapchain), encoded
+//! values read back as linear, and image files sampled both as color and as
+//! data.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::PipelineFile;
+
+/// How the values of a texture relate to light.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+    /// Values are proportional to light.
+    #[default]
+    Linear,
+    /// Values are sRGB encoded (perceptual, gamma ~2.2).
+    Srgb,
+}
+
+impl ColorSpace {
+    /// The color space a format stores values in, as shaders see them.
+    pub fn of_format(format: wgpu::TextureFormat) -> Self {
+        if format.is_srgb() {
+            Self::Srgb
+        } else {
+            Self::Linear
+        }
+    }
+
+    /// `format` in this color space: the sRGB variant for `Srgb`, the plain
+    /// one for `Linear`. None when the format has no sRGB variant.
+    pub fn apply(self, format: wgpu::TextureFormat) -> Option<wgpu::TextureFormat> {
+        let converted = match self {
+            Self::Srgb => format.add_srgb_suffix(),
+            Self::Linear => format.remove_srgb_suffix(),
+        };
+        (Self::of_format(converted) == self).then_some(converted)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Srgb => "srgb",
+        }
+    }
+}
+
+/// Check a pipeline's passes against the formats of their targets.
+/// `formats` are the allocated resource formats, `surface_format` the
+/// swapchain's. Returns one line per problem.
+pub fn audit_pipeline(
+    pipeline: &PipelineFile,
+    formats: &HashMap<String, wgpu::TextureFormat>,
+    surface_format: wgpu::TextureFormat,
+) -> Vec<String> {
+    let format_of = |name: &str| match name {
+        "swapchain" => Some(surface_format),
+        _ => formats.get(name).copied(),
+    };
+    let mut issues = Vec::new();
+    // Linear-format resources holding values a pass already sRGB encoded
+    let mut encoded = HashSet::new();
+
+    for pass in &pipeline.passes {
+        let output = pass.output_color_space.unwrap_or_default();
+        let mut targets: Vec<&String> = pass.outputs.values().collect();
+        targets.sort();
+        targets.dedup();
+        for target in targets {
+            let Some(format) = format_of(target).filter(|f| !f.is_depth_stencil_format()) else { continue };
+            match (output, ColorSpace::of_format(format)) {
+                (ColorSpace::Srgb, ColorSpace::Srgb) => issues.push(format!(
+                    "pass '{}' writes sRGB-encoded values to '{}' ({:?}), which encodes them again (double gamma): \
+                     drop output_color_space: srgb or give the target a linear format",
+                    pass.name, target, format
+                )),
+                (ColorSpace::Srgb, ColorSpace::Linear) => {
+                    encoded.insert(target.as_str());
+                }
+                // A swapchain format with an sRGB variant is 8-bit, not HDR
+                (ColorSpace::Linear, ColorSpace::Linear) if target == "swapchain" && format.add_srgb_suffix() != format => {
+                    issues.push(format!(
+                        "pass '{}' writes linear light to a linear swapchain ({:?}) without sRGB encoding, so the image \
+                         is too dark: use surface_format: srgb, or encode in the shader and declare output_color_space: srgb",
+                        pass.name, format
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for pass in &pipeline.passes {
+        let mut inputs: Vec<&String> = pass.inputs.values().filter(|r| encoded.contains(r.as_str())).collect();
+        inputs.sort();
+        inputs.dedup();
+        for input in inputs {
+            issues.push(format!(
+                "pass '{}' samples '{}', which holds sRGB-encoded values in a linear format, as linear light: \
+                 declare color_space: srgb on '{}' and output_color_space: linear on the pass writing it",
+                pass.name, input, input
+            ));
+        }
+    }
+    issues
+}
+
+/// Image files loaded both as sRGB color and as linear data.
+pub fn audit_textures(keys: impl IntoIterator<Item = (PathBuf, bool)>) -> Vec<String> {
+    let mut spaces: HashMap<PathBuf, (bool, bool)> = HashMap::new();
+    for (path, srgb) in keys {
+        let entry = spaces.entry(path).or_default();
+        if srgb {
+            entry.0 = true;
+        } else {
+            entry.1 = true;
+        }
+    }
+    let mut mixed: Vec<PathBuf> = spaces.into_iter().filter(|(_, both)| both.0 && both.1).map(|(path, _)| path).collect();
+    mixed.sort();
+    mixed
+        .into_iter()
+        .map(|path| {
+            format!(
+                "texture '{}' is sampled as sRGB color in one place and as linear data in another; one of them is wrong",
+                path.display()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::TextureFormat::*;
+
+    fn pipeline(yaml: &str) -> PipelineFile {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_color_space_apply() {
+        assert_eq!(ColorSpace::Srgb.apply(Rgba8Unorm), Some(Rgba8UnormSrgb));
+        assert_eq!(ColorSpace::Linear.apply(Rgba8UnormSrgb), Some(Rgba8Unorm));
+        assert_eq!(ColorSpace::Linear.apply(Rgba16Float), Some(Rgba16Float));
+        assert_eq!(ColorSpace::Srgb.apply(Rgba16Float), None);
+    }
+
+    #[test]
+    fn test_audit_pipeline() {
+        let file = pipeline(
+            r#"
+passes:
+  - name: lighting
+    type: fullscreen
+    outputs: { color: hdr }
+  - name: grade
+    type: fullscreen
+    output_color_space: srgb
+    inputs: { input: hdr }
+    outputs: { color: ldr }
+  - name: fxaa
+    type: fullscreen
+    inputs: { input: ldr }
+    outputs: { color: swapchain }
+"#,
+        );
+        let formats = HashMap::from([("hdr".to_string(), Rgba16Float), ("ldr".to_string(), Rgba8Unorm)]);
+        assert_eq!(audit_pipeline(&file, &formats, Bgra8UnormSrgb).len(), 1);
+        let issues = audit_pipeline(&file, &formats, Bgra8Unorm);
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().any(|i| i.contains("too dark")));
+        assert!(issues.iter().any(|i| i.contains("pass 'fxaa' samples 'ldr'")));
+
+        let formats = HashMap::from([("hdr".to_string(), Rgba16Float), ("ldr".to_string(), Rgba8UnormSrgb)]);
+        let issues = audit_pipeline(&file, &formats, Bgra8UnormSrgb);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("double gamma"));
+    }
+
+    #[test]
+    fn test_audit_textures() {
+        let issues = audit_textures([
+            (PathBuf::from("textures/rock.png"), true),
+            (PathBuf::from("textures/rock_n.png"), false),
+            (PathBuf::from("textures/rock.png"), false),
+        ]);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("rock.png"));
+    }
+}
@@ -7,7 +7,7 @@ use winit::event::MouseButton;
 
 use crate::components::*;
 use crate::events::EventBus;
-use crate::input::InputState;
+use crate::input::{InputState, InputTrigger};
 use crate::world::SceneWorld;
 
 /// A command request received via the Unix socket.
@@ -196,19 +196,28 @@ pub fn handle_command_rc(
             None => CommandResponse::error("No scene loaded"),
         },
         "spawn_entity" => match scene_world {
-            Some(sw) => cmd_spawn_entity(req, sw),
+            Some(sw) => cmd_spawn_entity(req, sw, event_bus),
             None => CommandResponse::error("No scene loaded"),
         },
         "destroy_entity" => match scene_world {
-            Some(sw) => cmd_destroy_entity(req, sw),
+            Some(sw) => cmd_destroy_entity(req, sw, event_bus),
             None => CommandResponse::error("No scene loaded"),
         },
         "emit_event" => cmd_emit_event(req, event_bus),
         "query_events" => cmd_query_events(req, event_bus),
+        "get_event_schema" => cmd_get_event_schema(req, event_bus),
         "inject_input" => match input_state {
             Some(is) => cmd_inject_input(req, is),
             None => CommandResponse::error("No input state"),
         },
+        "get_bindings" => match input_state {
+            Some(is) => cmd_get_bindings(is),
+            None => CommandResponse::error("No input state"),
+        },
+        "rebind" => match input_state {
+            Some(is) => cmd_rebind(req, is),
+            None => CommandResponse::error("No input state"),
+        },
         "runtime_control" => cmd_runtime_control(req, paused),
         _ => CommandResponse::error(format!("Unknown command: {}", req.cmd)),
     }
@@ -273,6 +282,8 @@ fn cmd_query_entity(req: &CommandRequest, sw: &SceneWorld) -> CommandResponse {
             data.insert("camera".into(), json!({
                 "fov": c.fov_degrees, "near": c.near, "far": c.far,
                 "role": format!("{:?}", c.role),
+                "projection": c.projection.name(),
+                "size": match c.projection { Projection::Orthographic { size } => Some(size), _ => None },
             }));
         }
     }
@@ -281,6 +292,7 @@ fn cmd_query_entity(req: &CommandRequest, sw: &SceneWorld) -> CommandResponse {
             data.insert("point_light".into(), json!({
                 "color": [pl.color.x, pl.color.y, pl.color.z],
                 "intensity": pl.intensity, "range": pl.range,
+                "casts_shadows": pl.casts_shadows,
             }));
         }
     }
@@ -368,7 +380,7 @@ fn cmd_modify_entity(req: &CommandRequest, sw: &mut SceneWorld) -> CommandRespon
     CommandResponse::ok_empty()
 }
 
-fn cmd_spawn_entity(req: &CommandRequest, sw: &mut SceneWorld) -> CommandResponse {
+fn cmd_spawn_entity(req: &CommandRequest, sw: &mut SceneWorld, event_bus: &mut EventBus) -> CommandResponse {
     let entity_id = match get_str_param(req, "entity_id") {
         Some(id) => id.to_string(),
         None => return CommandResponse::error("Missing 'entity_id' parameter"),
@@ -410,23 +422,31 @@ fn cmd_spawn_entity(req: &CommandRequest, sw: &mut SceneWorld) -> CommandRespons
             .and_then(|a| json_array_to_vec3(a)).unwrap_or(glam::Vec3::ONE);
         let intensity = pl.get("intensity").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
         let range = pl.get("range").and_then(|v| v.as_f64()).unwrap_or(10.0) as f32;
-        sw.world.spawn((eid, tags, transform, PointLight { color, intensity, range }))
+        let casts_shadows = pl.get("casts_shadows").and_then(|v| v.as_bool()).unwrap_or(false);
+        sw.world.spawn((eid, tags, transform, PointLight { color, intensity, range, casts_shadows }))
     } else if let Some(cam) = components.and_then(|c| c.get("camera")) {
         let fov = cam.get("fov").and_then(|v| v.as_f64()).unwrap_or(75.0) as f32;
         let near = cam.get("near").and_then(|v| v.as_f64()).unwrap_or(0.1) as f32;
         let far = cam.get("far").and_then(|v| v.as_f64()).unwrap_or(100.0) as f32;
         let role_str = cam.get("role").and_then(|v| v.as_str()).unwrap_or("other");
-        let role = if role_str == "main" { CameraRole::Main } else { CameraRole::Other(role_str.into()) };
-        sw.world.spawn((eid, tags, transform, Camera { fov_degrees: fov, near, far, role, aspect_ratio: 16.0/9.0 }))
+        let role = CameraRole::from_name(role_str);
+        let size = cam.get("size").and_then(|v| v.as_f64()).unwrap_or(10.0) as f32;
+        let projection = cam.get("projection").and_then(|v| v.as_str())
+            .and_then(|name| Projection::from_name(name, size)).unwrap_or_default();
+        sw.world.spawn((eid, tags, transform, Camera { fov_degrees: fov, near, far, role, aspect_ratio: 16.0/9.0, projection }))
     } else {
         sw.world.spawn((eid, tags, transform))
     };
 
     sw.entity_registry.insert(entity_id.clone(), entity);
+    event_bus.emit(
+        crate::engine_events::ENTITY_SPAWNED,
+        crate::engine_events::payload(json!({"entity_id": entity_id})),
+    );
     CommandResponse::ok(json!({"entity_id": entity_id}))
 }
 
-fn cmd_destroy_entity(req: &CommandRequest, sw: &mut SceneWorld) -> CommandResponse {
+fn cmd_destroy_entity(req: &CommandRequest, sw: &mut SceneWorld, event_bus: &mut EventBus) -> CommandResponse {
     let entity_id = match get_str_param(req, "entity_id") {
         Some(id) => id.to_string(),
         None => return CommandResponse::error("Missing 'entity_id' parameter"),
@@ -436,6 +456,10 @@ fn cmd_destroy_entity(req: &CommandRequest, sw: &mut SceneWorld) -> CommandRespo
         None => return CommandResponse::error(format!("Entity '{}' not found", entity_id)),
     };
     let _ = sw.world.despawn(entity);
+    event_bus.emit(
+        crate::engine_events::ENTITY_DESTROYED,
+        crate::engine_events::payload(json!({"entity_id": entity_id})),
+    );
     CommandResponse::ok_empty()
 }
 
@@ -449,10 +473,28 @@ fn cmd_emit_event(req: &CommandRequest, event_bus: &mut EventBus) -> CommandResp
     let data: HashMap<String, Value> = req.params.get("data")
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default();
+    if let Err(e) = event_bus.validate(&event_type, &data) {
+        return CommandResponse::error(e);
+    }
     event_bus.emit(&event_type, data);
     CommandResponse::ok_empty()
 }
 
+fn cmd_get_event_schema(req: &CommandRequest, event_bus: &EventBus) -> CommandResponse {
+    // Built-in engine events first, so project entries with the same name win
+    let mut events = crate::engine_events::builtin_schema().events;
+    if let Some(schema) = event_bus.schema() {
+        events.extend(schema.events.clone());
+    }
+    match get_str_param(req, "event_type") {
+        Some(event_type) => match events.get(event_type) {
+            Some(entry) => CommandResponse::ok(json!({"event_type": event_type, "schema": entry})),
+            None => CommandResponse::error(format!("No schema for event '{}'", event_type)),
+        },
+        None => CommandResponse::ok(json!({"events": events})),
+    }
+}
+
 fn cmd_query_events(req: &CommandRequest, event_bus: &EventBus) -> CommandResponse {
     let filter = get_str_param(req, "filter").map(String::from);
     let limit = req.params.get("limit")
@@ -522,6 +564,54 @@ fn cmd_inject_input(req: &CommandRequest, input: &mut InputState) -> CommandResp
     CommandResponse::ok_empty()
 }
 
+/// Actions sorted by name with their trigger names, the axes, and any
+/// triggers bound to more than one action.
+fn cmd_get_bindings(input: &InputState) -> CommandResponse {
+    let bindings = input.bindings();
+    let mut actions: Vec<Value> = bindings
+        .actions
+        .iter()
+        .map(|(action, triggers)| {
+            let names: Vec<&str> = triggers.iter().map(|t| t.name()).collect();
+            json!({"action": action, "triggers": names})
+        })
+        .collect();
+    actions.sort_by(|a, b| a["action"].as_str().cmp(&b["action"].as_str()));
+    CommandResponse::ok(json!({
+        "actions": actions,
+        "axes": bindings.axes,
+        "conflicts": bindings.conflicts(),
+    }))
+}
+
+/// Replace an action's triggers live. The change lasts until the bindings
+/// file is reloaded; `save_bindings` writes it back to the file.
+fn cmd_rebind(req: &CommandRequest, input: &mut InputState) -> CommandResponse {
+    let action = match get_str_param(req, "action") {
+        Some(a) => a,
+        None => return CommandResponse::error("Missing 'action' parameter"),
+    };
+    let names = match req.params.get("triggers").and_then(|v| v.as_array()) {
+        Some(names) => names,
+        None => return CommandResponse::error("Missing 'triggers' parameter (array of key or mouse button names)"),
+    };
+    let mut triggers = Vec::with_capacity(names.len());
+    for name in names {
+        match name.as_str().and_then(InputTrigger::parse) {
+            Some(trigger) => triggers.push(trigger),
+            None => return CommandResponse::error(format!("Unknown key or mouse button: {}", name)),
+        }
+    }
+    input.rebind(action, triggers);
+    let conflicts: Vec<_> = input
+        .bindings()
+        .conflicts()
+        .into_iter()
+        .filter(|c| c.actions.iter().any(|a| a == action))
+        .collect();
+    CommandResponse::ok(json!({"action": action, "conflicts": conflicts}))
+}
+
 // --- Runtime commands ---
 
 fn cmd_runtime_control(req: &CommandRequest, paused: &mut bool) -> CommandResponse {
@@ -570,6 +660,26 @@ mod tests {
         assert_eq!(resp.status, "ok");
     }
 
+    #[test]
+    fn test_emit_event_rejects_unknown_field() {
+        let dir = std::env::temp_dir().join("naive_test_event_schema");
+        std::fs::create_dir_all(dir.join("events")).unwrap();
+        std::fs::write(dir.join("events/schema.yaml"), "events:\n  door.opened:\n    fields:\n      - door_id: string\n").unwrap();
+        let mut bus = EventBus::new(10);
+        bus.load_schema(&dir);
+
+        let req: CommandRequest = serde_json::from_str(
+            r#"{"cmd": "emit_event", "event_type": "door.opened", "data": {"door": "d1"}}"#,
+        ).unwrap();
+        assert_eq!(cmd_emit_event(&req, &mut bus).status, "error");
+
+        let req: CommandRequest = serde_json::from_str(r#"{"cmd": "get_event_schema"}"#).unwrap();
+        let resp = cmd_get_event_schema(&req, &bus);
+        assert_eq!(resp.data.unwrap()["events"]["door.opened"]["fields"][0]["type"], "string");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_runtime_control() {
         let mut paused = false;
@@ -591,4 +701,22 @@ mod tests {
         assert_eq!(resp2.status, "ok");
         assert!(!paused);
     }
+
+    #[test]
+    fn test_rebind_reports_conflicts() {
+        let mut input = InputState::new(crate::input::InputBindings::default());
+        let req: CommandRequest = serde_json::from_str(
+            r#"{"cmd": "rebind", "action": "interact", "triggers": ["Space"]}"#,
+        ).unwrap();
+        let data = cmd_rebind(&req, &mut input).data.unwrap();
+        assert_eq!(data["conflicts"][0]["trigger"], "Space");
+
+        let data = cmd_get_bindings(&input).data.unwrap();
+        assert_eq!(data["conflicts"][0]["actions"], json!(["interact", "jump"]));
+
+        let req: CommandRequest = serde_json::from_str(
+            r#"{"cmd": "rebind", "action": "interact", "triggers": ["Nope"]}"#,
+        ).unwrap();
+        assert_eq!(cmd_rebind(&req, &mut input).status, "error");
+    }
 }
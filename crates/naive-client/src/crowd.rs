@@ -0,0 +1,294 @@
+//! Crowd flocking: boids steering for ambient creatures and hordes.
+//!
+//! Entities with a `flock` component steer with the other agents of their
+//! group: separation, alignment and cohesion, plus an optional goal (a point
+//! or an entity to chase). Neighbors come from a spatial grid with cells the
+//! size of the neighbor radius, and steering runs in parallel, so a horde
+//! costs about O(n) instead of a per-entity O(n²) Lua loop.
+//!
+//! Agents with a dynamic rigid body are steered through their velocity;
+//! the rest are moved by their transform.
+
+use std::collections::HashMap;
+
+use glam::{IVec3, Vec3};
+use rayon::prelude::*;
+
+use crate::scene::FlockDef;
+
+/// Flock agent component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlockAgent {
+    pub group: String,
+    /// Per-agent override of the group's `max_speed`.
+    pub max_speed: Option<f32>,
+    /// Steer in the XZ plane only.
+    pub planar: bool,
+    /// Turn the entity to face its heading.
+    pub face_velocity: bool,
+    pub velocity: Vec3,
+}
+
+impl FlockAgent {
+    pub fn from_def(def: &FlockDef) -> Self {
+        Self {
+            group: def.group.clone(),
+            max_speed: def.max_speed,
+            planar: def.planar,
+            face_velocity: def.face_velocity,
+            velocity: Vec3::ZERO,
+        }
+    }
+}
+
+/// Where a group heads, besides flocking.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlockGoal {
+    Point(Vec3),
+    Entity(String),
+}
+
+/// Steering parameters shared by a group (`crowd.set_params`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlockParams {
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    /// Agents closer than this are neighbors.
+    pub neighbor_radius: f32,
+    /// Agents closer than this push each other apart.
+    pub separation_radius: f32,
+    pub max_speed: f32,
+    /// Largest steering acceleration.
+    pub max_force: f32,
+    pub goal: Option<FlockGoal>,
+    pub goal_weight: f32,
+}
+
+impl Default for FlockParams {
+    fn default() -> Self {
+        Self {
+            separation: 1.5,
+            alignment: 1.0,
+            cohesion: 1.0,
+            neighbor_radius: 4.0,
+            separation_radius: 1.5,
+            max_speed: 3.0,
+            max_force: 8.0,
+            goal: None,
+            goal_weight: 1.0,
+        }
+    }
+}
+
+impl FlockParams {
+    /// Names accepted by `set`, for Lua error messages.
+    pub const NAMES: &[&str] = &[
+        "separation",
+        "alignment",
+        "cohesion",
+        "neighbor_radius",
+        "separation_radius",
+        "max_speed",
+        "max_force",
+        "goal_weight",
+    ];
+
+    /// Set a numeric parameter by name.
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+        let value = if value.is_finite() { value.max(0.0) } else { 0.0 };
+        match name {
+            "separation" => self.separation = value,
+            "alignment" => self.alignment = value,
+            "cohesion" => self.cohesion = value,
+            // A zero radius would make the grid cells degenerate
+            "neighbor_radius" => self.neighbor_radius = value.max(0.01),
+            "separation_radius" => self.separation_radius = value,
+            "max_speed" => self.max_speed = value,
+            "max_force" => self.max_force = value,
+            "goal_weight" => self.goal_weight = value,
+            _ => {
+                return Err(format!(
+                    "unknown parameter '{}' (expected one of: {})",
+                    name,
+                    Self::NAMES.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a numeric parameter by name.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "separation" => self.separation,
+            "alignment" => self.alignment,
+            "cohesion" => self.cohesion,
+            "neighbor_radius" => self.neighbor_radius,
+            "separation_radius" => self.separation_radius,
+            "max_speed" => self.max_speed,
+            "max_force" => self.max_force,
+            "goal_weight" => self.goal_weight,
+            _ => return None,
+        })
+    }
+}
+
+/// Per-group flock parameters, shared with Lua.
+#[derive(Debug, Clone, Default)]
+pub struct CrowdSystem {
+    pub groups: HashMap<String, FlockParams>,
+}
+
+impl CrowdSystem {
+    /// Parameters of `group`, created with defaults on first use.
+    pub fn group_mut(&mut self, group: &str) -> &mut FlockParams {
+        self.groups.entry(group.to_string()).or_default()
+    }
+
+    /// Parameters of `group`, or the defaults if it was never configured.
+    pub fn group(&self, group: &str) -> FlockParams {
+        self.groups.get(group).cloned().unwrap_or_default()
+    }
+}
+
+/// One agent's state for a steering step.
+#[derive(Debug, Clone, Copy)]
+pub struct AgentState {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Index into the per-group slices passed to `steer`.
+    pub group: usize,
+    pub max_speed: f32,
+    pub planar: bool,
+    pub dt: f32,
+}
+
+/// New velocities for every agent after one steering step. `params` and
+/// `goals` are indexed by `AgentState::group`.
+pub fn steer(agents: &[AgentState], params: &[FlockParams], goals: &[Option<Vec3>]) -> Vec<Vec3> {
+    let cell = |position: Vec3, group: usize| (position / params[group].neighbor_radius).floor().as_ivec3();
+    let mut grid: HashMap<(usize, IVec3), Vec<usize>> = HashMap::new();
+    for (i, agent) in agents.iter().enumerate() {
+        grid.entry((agent.group, cell(agent.position, agent.group))).or_default().push(i);
+    }
+
+    agents
+        .par_iter()
+        .enumerate()
+        .map(|(i, agent)| {
+            let p = &params[agent.group];
+            let home = cell(agent.position, agent.group);
+            let mut separation = Vec3::ZERO;
+            let mut velocity_sum = Vec3::ZERO;
+            let mut position_sum = Vec3::ZERO;
+            let mut neighbors = 0u32;
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(cell_agents) = grid.get(&(agent.group, home + IVec3::new(dx, dy, dz))) else {
+                            continue;
+                        };
+                        for &j in cell_agents {
+                            if j == i {
+                                continue;
+                            }
+                            let other = &agents[j];
+                            let offset = agent.position - other.position;
+                            let distance = offset.length();
+                            if distance >= p.neighbor_radius {
+                                continue;
+                            }
+                            if distance < p.separation_radius && distance > 1e-4 {
+                                // Stronger the closer they are
+                                separation += offset / (distance * distance);
+                            }
+                            velocity_sum += other.velocity;
+                            position_sum += other.position;
+                            neighbors += 1;
+                        }
+                    }
+                }
+            }
+
+            let mut steering = separation * p.separation;
+            if neighbors > 0 {
+                let n = neighbors as f32;
+                steering += (velocity_sum / n - agent.velocity) * p.alignment;
+                steering += (position_sum / n - agent.position) * p.cohesion;
+            }
+            if let Some(goal) = goals[agent.group] {
+                let desired = (goal - agent.position).normalize_or_zero() * agent.max_speed;
+                steering += (desired - agent.velocity) * p.goal_weight;
+            }
+
+            let mut velocity = agent.velocity + steering.clamp_length_max(p.max_force) * agent.dt;
+            if agent.planar {
+                velocity.y = 0.0;
+            }
+            velocity.clamp_length_max(agent.max_speed)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(x: f32, z: f32, velocity: Vec3) -> AgentState {
+        AgentState {
+            position: Vec3::new(x, 0.0, z),
+            velocity,
+            group: 0,
+            max_speed: 3.0,
+            planar: true,
+            dt: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_separation_pushes_apart() {
+        let params = [FlockParams { alignment: 0.0, cohesion: 0.0, ..Default::default() }];
+        let velocities = steer(&[agent(0.0, 0.0, Vec3::ZERO), agent(0.5, 0.0, Vec3::ZERO)], &params, &[None]);
+        assert!(velocities[0].x < 0.0);
+        assert!(velocities[1].x > 0.0);
+    }
+
+    #[test]
+    fn test_alignment_and_speed_limit() {
+        let params = [FlockParams { separation: 0.0, cohesion: 0.0, max_force: 100.0, ..Default::default() }];
+        let agents = [agent(0.0, 0.0, Vec3::ZERO), agent(2.0, 0.0, Vec3::new(0.0, 5.0, 3.0))];
+        let velocities = steer(&agents, &params, &[None]);
+        // The still agent picks up its neighbor's heading, flattened and capped
+        assert!(velocities[0].z > 0.0);
+        assert_eq!(velocities[0].y, 0.0);
+        assert!(velocities[1].length() <= 3.0 + 1e-4);
+    }
+
+    #[test]
+    fn test_goal_and_groups() {
+        let params = [FlockParams::default(), FlockParams::default()];
+        let mut lone = agent(0.5, 0.0, Vec3::ZERO);
+        lone.group = 1;
+        // Different groups ignore each other; group 0 heads for its goal
+        let velocities = steer(
+            &[agent(0.0, 0.0, Vec3::ZERO), lone],
+            &params,
+            &[Some(Vec3::new(0.0, 0.0, -10.0)), None],
+        );
+        assert!(velocities[0].z < 0.0);
+        assert!(velocities[0].x.abs() < 1e-6);
+        assert_eq!(velocities[1], Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_params_by_name() {
+        let mut params = FlockParams::default();
+        params.set("cohesion", 2.5).unwrap();
+        assert_eq!(params.get("cohesion"), Some(2.5));
+        params.set("neighbor_radius", 0.0).unwrap();
+        assert!(params.neighbor_radius > 0.0);
+        assert!(params.set("speed", 1.0).is_err());
+        assert_eq!(params.get("speed"), None);
+    }
+}
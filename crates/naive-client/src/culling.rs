@@ -0,0 +1,140 @@
+//! CPU frustum culling for mesh draws.
+//!
+//! Every mesh gets a local-space bounding box when it is loaded. Each frame
+//! the rasterize and shadow passes move those boxes into world space and skip
+//! the meshes whose box lies outside the camera's (or light's) frustum.
+//! `--no-culling` draws everything. Pipelines with `settings.gpu_culling`
+//! run the camera test on the GPU instead (see `pipeline::gpu_culling`).
+
+use glam::{Mat4, Vec3, Vec4};
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Bounds of `points`; an empty set gives a point box at the origin.
+    pub fn from_points(points: impl IntoIterator<Item = [f32; 3]>) -> Self {
+        let mut points = points.into_iter().map(Vec3::from);
+        let Some(first) = points.next() else {
+            return Self { min: Vec3::ZERO, max: Vec3::ZERO };
+        };
+        points.fold(Self { min: first, max: first }, |aabb, p| Self {
+            min: aabb.min.min(p),
+            max: aabb.max.max(p),
+        })
+    }
+
+    /// The box enclosing this one after `matrix` (which may rotate it).
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let center = matrix.transform_point3((self.min + self.max) * 0.5);
+        let half = (self.max - self.min) * 0.5;
+        let extent = matrix.x_axis.truncate().abs() * half.x
+            + matrix.y_axis.truncate().abs() * half.y
+            + matrix.z_axis.truncate().abs() * half.z;
+        Self {
+            min: center - extent,
+            max: center + extent,
+        }
+    }
+}
+
+/// The six planes of a view-projection volume, pointing inward.
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Frustum of a view-projection matrix with wgpu's 0..1 clip depth.
+    /// Works for perspective and orthographic projections.
+    pub fn from_view_projection(view_projection: &Mat4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_projection.row(i));
+        Self {
+            planes: [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2],
+        }
+    }
+
+    /// Left, right, bottom, top, near and far planes as `(normal, distance)`.
+    pub fn planes(&self) -> [Vec4; 6] {
+        self.planes
+    }
+
+    /// Whether any part of `aabb` may be inside. Boxes near a frustum corner
+    /// can pass without being visible, which only costs a wasted draw.
+    pub fn intersects(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // The box corner furthest along the plane normal
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+/// Mesh draws of the last frame, for the debug HUD.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    /// Meshes drawn and culled by rasterize passes.
+    pub drawn: u32,
+    pub culled: u32,
+    /// Meshes drawn and culled by shadow passes, summed over shadow maps.
+    pub shadow_drawn: u32,
+    pub shadow_culled: u32,
+    /// Pipeline, bind group and vertex/index buffer changes made by
+    /// rasterize passes.
+    pub state_changes: u32,
+    /// Rasterize passes culled on the GPU; `drawn` then counts the meshes
+    /// submitted and `culled` stays 0.
+    pub gpu_culling: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box_at(center: Vec3) -> Aabb {
+        Aabb { min: center - Vec3::splat(0.5), max: center + Vec3::splat(0.5) }
+    }
+
+    #[test]
+    fn test_perspective_frustum() {
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(60f32.to_radians(), 1.0, 0.1, 100.0);
+        let frustum = Frustum::from_view_projection(&(proj * view));
+
+        assert!(frustum.intersects(&unit_box_at(Vec3::new(0.0, 0.0, -10.0))));
+        // Behind the camera, past the far plane, off to the side
+        assert!(!frustum.intersects(&unit_box_at(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects(&unit_box_at(Vec3::new(0.0, 0.0, -102.0))));
+        assert!(!frustum.intersects(&unit_box_at(Vec3::new(20.0, 0.0, -10.0))));
+        // Straddling the left edge still counts
+        assert!(frustum.intersects(&unit_box_at(Vec3::new(-5.9, 0.0, -10.0))));
+    }
+
+    #[test]
+    fn test_orthographic_frustum() {
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 30.0, 0.0), Vec3::ZERO, Vec3::Z);
+        let proj = Mat4::orthographic_rh(-10.0, 10.0, -10.0, 10.0, 0.1, 60.0);
+        let frustum = Frustum::from_view_projection(&(proj * view));
+        assert!(frustum.intersects(&unit_box_at(Vec3::new(9.0, 0.0, -9.0))));
+        assert!(!frustum.intersects(&unit_box_at(Vec3::new(12.0, 0.0, 0.0))));
+    }
+
+    #[test]
+    fn test_transformed_bounds() {
+        let aabb = Aabb::from_points([[-1.0, 0.0, -0.5], [1.0, 2.0, 0.5]]);
+        assert_eq!(aabb.min, Vec3::new(-1.0, 0.0, -0.5));
+
+        let moved = aabb.transformed(&Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)));
+        assert_eq!(moved.min, Vec3::new(9.0, 0.0, -0.5));
+
+        // A quarter turn about Y swaps the X and Z extents
+        let turned = aabb.transformed(&Mat4::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        assert!((turned.max - Vec3::new(0.5, 2.0, 1.0)).abs().max_element() < 1e-5);
+        assert_eq!(Aabb::from_points([]), Aabb { min: Vec3::ZERO, max: Vec3::ZERO });
+    }
+}
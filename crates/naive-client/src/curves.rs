@@ -0,0 +1,141 @@
+//! Curve assets in the project's `curves/` folder.
+//!
+//! Scene fields that take a curve accept either the name of a file here or an
+//! inline curve (see [`crate::curve::CurveDef`]). Scripts read the same files
+//! through `curve.eval(name, t)`; the library caches them and drops its cache
+//! when a file in `curves/` changes.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::components::{LightFlicker, PointLight, TimeScale};
+use crate::curve::{Curve, CurveDef, Wrap};
+
+/// Folder of the curve assets, relative to the project root.
+pub const CURVES_DIR: &str = "curves";
+
+/// Load `curves/<name>.yaml`.
+pub fn load_curve(project_root: &Path, name: &str) -> Result<Curve, String> {
+    let path = project_root.join(CURVES_DIR).join(format!("{}.yaml", name));
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read curve '{}': {}", name, e))?;
+    Curve::from_yaml(&contents).map_err(|e| format!("Failed to parse curve '{}': {}", name, e))
+}
+
+/// The curve a scene field refers to: loaded from `curves/` when named,
+/// checked and sorted when inline.
+pub fn resolve(project_root: &Path, def: &CurveDef) -> Result<Curve, String> {
+    match def {
+        CurveDef::Named(name) => load_curve(project_root, name),
+        CurveDef::Inline(curve) => {
+            curve.validate()?;
+            Ok(Curve::new(curve.keys.clone(), curve.interpolation).with_wrap(curve.wrap))
+        }
+    }
+}
+
+/// Resolve an optional curve field, warning and dropping it when it fails.
+pub fn resolve_field(project_root: &Path, def: Option<&CurveDef>, what: &str) -> Option<Curve> {
+    let def = def?;
+    resolve(project_root, def)
+        .map_err(|e| tracing::warn!("{}: {}", what, e))
+        .ok()
+}
+
+/// Curves used by scripts, by name: loaded from `curves/` on first use or
+/// defined at runtime with `curve.define`.
+#[derive(Debug, Default)]
+pub struct CurveLibrary {
+    curves: HashMap<String, Curve>,
+}
+
+impl CurveLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The named curve, loading it from `curves/` if it isn't cached.
+    pub fn get(&mut self, project_root: &Path, name: &str) -> Result<&Curve, String> {
+        if !self.curves.contains_key(name) {
+            let curve = load_curve(project_root, name)?;
+            self.curves.insert(name.to_string(), curve);
+        }
+        Ok(&self.curves[name])
+    }
+
+    /// Define (or replace) a curve by name.
+    pub fn define(&mut self, name: &str, curve: Curve) {
+        self.curves.insert(name.to_string(), curve);
+    }
+
+    /// Forget cached curves so the next use reloads them from disk.
+    pub fn invalidate(&mut self) {
+        self.curves.clear();
+    }
+}
+
+/// A curve for something that runs indefinitely (light flicker): a clamped
+/// curve loops instead of holding its last value.
+pub fn looping(curve: Curve) -> Curve {
+    match curve.wrap {
+        Wrap::Clamp => curve.with_wrap(Wrap::Loop),
+        _ => curve,
+    }
+}
+
+/// Advance flickering lights and set their intensity from their curve.
+pub fn update_light_flicker(world: &mut hecs::World, dt: f32) {
+    for (_entity, (flicker, light, scale)) in
+        world.query_mut::<(&mut LightFlicker, &mut PointLight, Option<&TimeScale>)>()
+    {
+        let dt = scale.map_or(dt, |s| dt * s.0);
+        flicker.time += dt * flicker.speed;
+        light.intensity = flicker.base_intensity * flicker.curve.eval(flicker.time).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_loads_and_invalidates() {
+        let root = std::env::temp_dir().join(format!("naive_curves_{}", std::process::id()));
+        let dir = root.join(CURVES_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("recoil.yaml"), "keys: [[0, 0], [1, 2]]\n").unwrap();
+
+        let mut library = CurveLibrary::new();
+        assert!((library.get(&root, "recoil").unwrap().eval(0.5) - 1.0).abs() < 1e-5);
+        std::fs::write(dir.join("recoil.yaml"), "keys: [[0, 0], [1, 4]]\n").unwrap();
+        assert!((library.get(&root, "recoil").unwrap().eval(0.5) - 1.0).abs() < 1e-5);
+        library.invalidate();
+        assert!((library.get(&root, "recoil").unwrap().eval(0.5) - 2.0).abs() < 1e-5);
+        assert!(library.get(&root, "missing").is_err());
+
+        let inline = CurveDef::Inline(Curve::new(vec![[0.0, 1.0]], Default::default()));
+        assert!(resolve(&root, &inline).is_ok());
+        assert!(resolve(&root, &CurveDef::Named("recoil".into())).is_ok());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_light_flicker_scales_intensity() {
+        let mut world = hecs::World::new();
+        let curve = Curve::new(vec![[0.0, 1.0], [1.0, 0.5]], Default::default())
+            .with_wrap(Wrap::Loop);
+        let light = PointLight { color: glam::Vec3::ONE, intensity: 2.0, range: 5.0, casts_shadows: false };
+        let flicker = LightFlicker {
+            curve,
+            source: CurveDef::Named("torch".into()),
+            speed: 2.0,
+            base_intensity: 2.0,
+            time: 0.0,
+        };
+        let entity = world.spawn((light, flicker));
+        update_light_flicker(&mut world, 0.25);
+        assert!((world.get::<&PointLight>(entity).unwrap().intensity - 1.5).abs() < 1e-5);
+        update_light_flicker(&mut world, 0.5);
+        assert!((world.get::<&PointLight>(entity).unwrap().intensity - 1.5).abs() < 1e-5);
+    }
+}
@@ -278,6 +278,18 @@ fn draw_shape(
                 }
             }
         }
+        ShapeType::HeightField => {
+            if let Some(heightfield) = shape.as_heightfield() {
+                for tri in heightfield.triangles() {
+                    let a = transform(Vec3::new(tri.a.x, tri.a.y, tri.a.z));
+                    let b = transform(Vec3::new(tri.b.x, tri.b.y, tri.b.z));
+                    let c = transform(Vec3::new(tri.c.x, tri.c.y, tri.c.z));
+                    push_line(verts, a, b, COLOR_TRIMESH);
+                    push_line(verts, b, c, COLOR_TRIMESH);
+                    push_line(verts, c, a, COLOR_TRIMESH);
+                }
+            }
+        }
         _ => {
             // Unsupported shape — skip
         }
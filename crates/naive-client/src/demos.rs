@@ -446,7 +446,20 @@ pub fn run_demo(selector: Option<&str>) -> Option<CliArgs> {
         project: project_root.to_string_lossy().to_string(),
         socket: "/tmp/naive-runtime.sock".to_string(),
         hud: false,
+        headless: false,
+        frames: crate::cli::DEFAULT_HEADLESS_FRAMES,
+        screenshot_after: None,
+        screenshot_path: crate::cli::DEFAULT_SCREENSHOT_PATH.to_string(),
+        lua_memory_limit: None,
+        lua_gc_budget: None,
+        force_lod0: false,
+        no_culling: false,
+        config: Vec::new(),
+        settings: Vec::new(),
+        graphics: Default::default(),
         editor_mode: false,
+        recover: false,
+        color_audit: false,
     })
 }
 
@@ -0,0 +1,136 @@
+//! Recovery from a lost GPU device.
+//!
+//! A driver reset (a TDR on Windows, a hung GPU, an unplugged eGPU) loses the
+//! wgpu device and every object made from it. `DeviceLossWatch` hears about
+//! it from wgpu's device-lost callback. The engine then snapshots the live
+//! scene into `.naive/device_lost.yaml`, drops all GPU state and rebuilds it
+//! on a new device: pipelines are recompiled and meshes, textures and splats
+//! are read again from their files. `RecoveryRetry` spaces out attempts while
+//! the driver has no adapter to offer yet.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use winit::window::Window;
+
+/// Scene snapshot taken when the device was lost, relative to the project root.
+pub const SNAPSHOT_FILE: &str = ".naive/device_lost.yaml";
+
+/// Wait before each reinitialization attempt; the last one repeats.
+const RETRY_DELAYS_MS: [u64; 5] = [0, 250, 1000, 2000, 5000];
+
+/// Receives the device-lost callback of the current device.
+#[derive(Debug, Default)]
+pub struct DeviceLossWatch {
+    lost: Arc<Mutex<Option<String>>>,
+}
+
+impl DeviceLossWatch {
+    /// Watch `device`. Callbacks of previously watched devices, such as the
+    /// one dropped during recovery, are no longer heard.
+    pub fn watch(&mut self, device: &wgpu::Device) {
+        self.lost = Arc::new(Mutex::new(None));
+        let lost = Arc::clone(&self.lost);
+        device.set_device_lost_callback(move |reason, message| {
+            if let Ok(mut lost) = lost.lock() {
+                *lost = Some(format!("{:?}: {}", reason, message));
+            }
+        });
+    }
+
+    /// The reason the device was lost, once.
+    pub fn take(&self) -> Option<String> {
+        self.lost.lock().ok()?.take()
+    }
+}
+
+/// Schedules reinitialization attempts.
+#[derive(Debug)]
+pub struct RecoveryRetry {
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+impl RecoveryRetry {
+    pub fn new(now: Instant) -> Self {
+        Self { attempts: 0, next_attempt: now }
+    }
+
+    /// Attempts made so far.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// When the next attempt should run.
+    pub fn next_attempt(&self) -> Instant {
+        self.next_attempt
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        now >= self.next_attempt
+    }
+
+    /// An attempt failed at `now`. Returns the wait before the next one.
+    pub fn failed(&mut self, now: Instant) -> Duration {
+        self.attempts += 1;
+        let index = (self.attempts as usize).min(RETRY_DELAYS_MS.len() - 1);
+        let delay = Duration::from_millis(RETRY_DELAYS_MS[index]);
+        self.next_attempt = now + delay;
+        delay
+    }
+}
+
+/// A lost device waiting to be replaced.
+pub struct PendingRecovery {
+    /// The game window; its old surface went with the device.
+    pub window: Arc<Window>,
+    /// Why the device was lost, as wgpu reported it.
+    pub reason: String,
+    /// The live scene at the time of the loss (None if it couldn't be written).
+    pub snapshot: Option<PathBuf>,
+    /// Tool windows that were open, reopened once the device is back.
+    pub tool_windows: Vec<crate::tool_window::ToolWindowKind>,
+    pub retry: RecoveryRetry,
+}
+
+/// Write the scene snapshot the session resumes from.
+pub fn write_snapshot(project_root: &Path, yaml: &str) -> Result<PathBuf, String> {
+    let path = project_root.join(SNAPSHOT_FILE);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, yaml).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_retry_backs_off_to_the_last_delay() {
+        let start = Instant::now();
+        let mut retry = RecoveryRetry::new(start);
+        assert!(retry.due(start));
+
+        assert_eq!(retry.failed(start), Duration::from_millis(250));
+        assert!(!retry.due(start));
+        assert!(retry.due(start + Duration::from_millis(250)));
+
+        assert_eq!(retry.failed(start), Duration::from_millis(1000));
+        assert_eq!(retry.failed(start), Duration::from_millis(2000));
+        assert_eq!(retry.failed(start), Duration::from_millis(5000));
+        assert_eq!(retry.failed(start), Duration::from_millis(5000));
+        assert_eq!(retry.attempts(), 5);
+    }
+
+    #[test]
+    fn test_write_snapshot() {
+        let dir = std::env::temp_dir().join(format!("naive_device_lost_{}", std::process::id()));
+        let path = write_snapshot(&dir, "name: Test\nentities: []\n").unwrap();
+        assert_eq!(path, dir.join(SNAPSHOT_FILE));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "name: Test\nentities: []\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
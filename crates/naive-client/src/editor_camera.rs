@@ -1,7 +1,7 @@
 use glam::{Mat4, Quat, Vec3};
 use winit::keyboard::KeyCode;
 
-use crate::camera::CameraState;
+use crate::camera::{CameraState, CameraUniform};
 use crate::input::InputState;
 
 /// Free fly camera for editor mode.
@@ -36,6 +36,24 @@ impl EditorCamera {
         Self::new(Vec3::new(0.0, 5.0, 10.0), 0.0, -0.3)
     }
 
+    /// A camera where a camera uniform looks from, with its field of view
+    /// (when perspective) and clip planes: the debug camera detaches here.
+    pub fn from_uniform(uniform: &CameraUniform) -> Self {
+        let view = Mat4::from_cols_array_2d(&uniform.view);
+        let forward = -view.inverse().z_axis.truncate().normalize();
+        let limit = std::f32::consts::FRAC_PI_2 - 0.01;
+        let yaw = f32::atan2(-forward.x, -forward.z);
+        let pitch = forward.y.clamp(-1.0, 1.0).asin().clamp(-limit, limit);
+        let mut camera = Self::new(Vec3::from(uniform.position), yaw, pitch);
+        let projection = Mat4::from_cols_array_2d(&uniform.projection);
+        if projection.w_axis.w == 0.0 {
+            camera.fov_degrees = (2.0 * (1.0 / projection.y_axis.y).atan()).to_degrees();
+        }
+        camera.near = uniform.near_plane;
+        camera.far = uniform.far_plane;
+        camera
+    }
+
     /// Update camera from input. Mouse look is always active in editor mode.
     pub fn update(&mut self, input: &InputState, dt: f32) {
         // Mouse look: always active (no button needed — works on Mac trackpads)
@@ -104,27 +122,7 @@ impl EditorCamera {
             self.near,
             self.far,
         );
-        let view_projection = projection * view;
-        let inv_view_projection = view_projection.inverse();
-
-        camera_state.uniform = crate::camera::CameraUniform {
-            view: view.to_cols_array_2d(),
-            projection: projection.to_cols_array_2d(),
-            view_projection: view_projection.to_cols_array_2d(),
-            position: self.position.to_array(),
-            near_plane: self.near,
-            far_plane: self.far,
-            _pad1: 0.0,
-            viewport_size: [width as f32, height as f32],
-            _pad2: [0.0; 4],
-            inv_view_projection: inv_view_projection.to_cols_array_2d(),
-        };
-
-        queue.write_buffer(
-            &camera_state.buffer,
-            0,
-            bytemuck::cast_slice(&[camera_state.uniform]),
-        );
+        camera_state.set_matrices(queue, view, projection, self.position, self.near, self.far, width, height);
     }
 
     fn forward(&self) -> Vec3 {
@@ -142,3 +140,25 @@ impl EditorCamera {
         Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_uniform_keeps_pose() {
+        let camera = EditorCamera::new(Vec3::new(1.0, 2.0, 3.0), 0.7, -0.4);
+        let uniform = CameraUniform {
+            view: Mat4::look_to_rh(camera.position, camera.forward(), Vec3::Y).to_cols_array_2d(),
+            projection: Mat4::perspective_rh(60f32.to_radians(), 1.5, 0.1, 250.0).to_cols_array_2d(),
+            position: camera.position.to_array(),
+            far_plane: 250.0,
+            ..bytemuck::Zeroable::zeroed()
+        };
+        let detached = EditorCamera::from_uniform(&uniform);
+        assert!((detached.yaw - 0.7).abs() < 1e-4 && (detached.pitch + 0.4).abs() < 1e-4);
+        assert!((detached.fov_degrees - 60.0).abs() < 1e-3);
+        assert!((detached.position - camera.position).length() < 1e-5);
+        assert_eq!(detached.far, 250.0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::mpsc;
@@ -13,9 +13,10 @@ use winit::window::{Window, WindowId};
 
 use crate::audio::AudioSystem;
 use crate::camera::CameraState;
+use crate::camera_follow::{CameraFollow, FollowSettings};
 use crate::cli::CliArgs;
 use crate::command::CommandServer;
-use crate::components::{Camera, CameraMode, CameraRole, CollisionDamage, GaussianSplat, Health, MeshRenderer, Player, Projectile, Transform};
+use crate::components::{Camera, CameraMode, CameraRole, CollisionDamage, Foliage, GaussianSplat, Health, MeshRenderer, Player, Portal, Projectile, Terrain, Transform};
 use crate::editor_camera::EditorCamera;
 use crate::events::EventBus;
 use crate::font::BitmapFont;
@@ -41,7 +42,15 @@ pub struct Engine {
     pub gpu: Option<GpuState>,
     pub project_root: PathBuf,
     _watcher: Option<RecommendedWatcher>,
+    // Directories of shader includes outside the project's watched directories
+    include_watch_dirs: std::collections::HashSet<PathBuf>,
     watch_rx: Option<mpsc::Receiver<WatchEvent>>,
+    // naive.yaml as last loaded, diffed on hot-reload (None if the project has none)
+    project_config: Option<crate::project_config::NaiveConfig>,
+    watch_config: crate::project_config::WatchConfig,
+    // Asset changes held back until the watcher's debounce window has passed
+    pending_watch_events: Vec<WatchEvent>,
+    last_watch_event: Option<instant::Instant>,
 
     // Phase 2: scene rendering state
     pub scene_world: Option<Rc<RefCell<SceneWorld>>>,
@@ -52,6 +61,10 @@ pub struct Engine {
     pub draw_pool: Option<DrawUniformPool>,
     pub forward_pipeline: Option<wgpu::RenderPipeline>,
     scene_path: Option<PathBuf>,
+    // Socket edits since the scene was last saved, and when the oldest one
+    // not yet in an autosave was made
+    unsaved_edits: u32,
+    autosave_pending: Option<instant::Instant>,
 
     // Phase 3: compiled render pipeline
     pub compiled_pipeline: Option<CompiledPipeline>,
@@ -78,6 +91,10 @@ pub struct Engine {
     // UI overlay
     pub bitmap_font: Option<Rc<RefCell<BitmapFont>>>,
     pub ui_renderer: Option<Rc<RefCell<UiRenderer>>>,
+    // Inspector/profiler windows, and ones requested over the socket that are
+    // created on the next event loop turn
+    tool_windows: Vec<crate::tool_window::ToolWindow>,
+    pending_tool_windows: Vec<crate::tool_window::ToolWindowKind>,
 
     // Entity command queue (deferred Lua commands)
     pub entity_commands: Rc<RefCell<crate::world::EntityCommandQueue>>,
@@ -92,21 +109,83 @@ pub struct Engine {
     pub lua_event_listeners: Rc<RefCell<HashMap<String, Vec<mlua::RegistryKey>>>>,
     pub next_lua_listener_id: Rc<RefCell<u64>>,
     pub lua_listener_id_map: Rc<RefCell<HashMap<u64, (String, usize)>>>,
+    // Directed entity messages (msg.send / msg.request)
+    pub message_queue: Rc<RefCell<crate::messaging::MessageQueue>>,
+    // Global time scale (time.set_scale) and this frame's scaled dt
+    pub game_clock: crate::scripting::SharedGameClock,
+    // Screenshot paths queued by Lua, the socket and --screenshot-after
+    pub screenshot_queue: crate::scripting::SharedScreenshotQueue,
+    // Frames presented to the window (drives --screenshot-after)
+    frames_presented: u64,
+    // Whether Lua memory was above the high-water mark last frame
+    lua_memory_high: bool,
+    // Built-in engine events: low-FPS warnings
+    low_fps_monitor: crate::engine_events::LowFpsMonitor,
+    // Steps render scale, shadow map size and particle budget to hold the frame rate (auto_quality)
+    quality_governor: Option<crate::auto_quality::QualityGovernor>,
+    // Minimized/hidden/focus state, and whether rendering is suspended because of it
+    window_activity: crate::window_state::WindowActivity,
+    render_suspended: bool,
+    // Next timer tick while suspended (the event loop waits instead of polling)
+    background_tick: Option<std::time::Instant>,
+    // Consecutive swapchain failures, for surface reconfigure/recreate
+    surface_recovery: crate::window_state::SurfaceRecovery,
+    // Device-lost callback of the current device, the recovery waiting for a
+    // new device, and the scene snapshot the next scene load resumes from
+    device_loss: crate::device_recovery::DeviceLossWatch,
+    device_recovery: Option<crate::device_recovery::PendingRecovery>,
+    restore_snapshot: Option<PathBuf>,
+    // Per-pass GPU timings (None if the adapter lacks timestamp queries)
+    gpu_profiler: Option<crate::pipeline::GpuPassProfiler>,
+    // Meshes drawn and frustum-culled last frame (debug HUD)
+    draw_stats: crate::culling::DrawStats,
 
     // Render debug: interactive pass toggles (number keys)
     pub render_debug: crate::pipeline::RenderDebugState,
+    // Render parameters set by Lua (render.set), applied before the next frame
+    pub render_tweaks: crate::scripting::SharedRenderTweaks,
+    // Post-process pass parameters set by Lua (render.set_param)
+    pub pass_params: crate::scripting::SharedPassParams,
+    // Running pipeline file and switches requested by Lua (pipeline.switch)
+    pub pipeline_switch: crate::scripting::SharedPipelineSwitch,
+    // Curve assets shared by Lua (curve.eval, audio.fade)
+    pub curves: crate::scripting::SharedCurves,
+    // Split-screen views, one per player camera while there are two or more
+    pub player_views: Vec<crate::split_screen::PlayerView>,
+    // Flock group parameters and goals set by Lua (crowd.set_params)
+    pub crowd: crate::scripting::SharedCrowd,
+    // Gameplay noises AI can hear (noise.emit, footsteps, impacts)
+    pub noise: crate::scripting::SharedNoise,
+    // Mods under mods/, mounted over the project at startup
+    pub mods: crate::scripting::SharedMods,
+    // Cutscene playback (sequence.play), owns the camera while playing
+    pub sequences: crate::scripting::SharedSequences,
+    // Camera paths (camera.play_path), own the main camera while playing
+    pub camera_paths: crate::scripting::SharedCameraPaths,
+    // Steamworks connection (steam.app_id, builds with the steam feature)
+    pub steam: crate::scripting::SharedSteam,
+    // Playtest analytics (analytics.enabled)
+    pub analytics: crate::scripting::SharedAnalytics,
+    // Plugin callbacks run before/after named pipeline passes
+    pub render_hooks: crate::pipeline::RenderHooks,
+    // Pipeline asks for shared textures but was compiled without them because hooks were registered
+    pub aliasing_suppressed: bool,
 
     // Debug wireframe renderer for collider visualization
     pub debug_draw: Option<crate::debug_draw::DebugDrawRenderer>,
 
     // Hot-reload notifications (message, timestamp, color)
     pub reload_notifications: Vec<(String, instant::Instant, [f32; 4])>,
+    // Last pipeline/shader compile error, shown on screen until a compile succeeds
+    pub pipeline_error: Option<String>,
 
     // Camera shake state
     pub camera_shake: Rc<RefCell<CameraShakeState>>,
 
     // Editor mode
     pub editor_camera: Option<EditorCamera>,
+    // Free-fly debug camera ([7] with the HUD up); the player stays where it was
+    debug_camera: Option<EditorCamera>,
     pub editor_command_log: Vec<(String, instant::Instant)>,
     pub editor_scene_path: Option<PathBuf>,
 
@@ -118,6 +197,18 @@ pub struct Engine {
 
     // Texture cache for material-level textures (albedo_map, normal_map)
     pub texture_cache: crate::texture_cache::TextureCache,
+    /// Prefiltered maps of the scene's skybox.
+    environment_cache: crate::environment::EnvironmentCache,
+    color_grading_cache: crate::color_grading::ColorGradingCache,
+    /// Portal views, composited by a hook after the lighting pass (None without portals).
+    portal_renderer: Option<Rc<RefCell<crate::portal::PortalRenderer>>>,
+    portal_hook: Option<crate::pipeline::HookId>,
+    /// Foliage scatters, drawn by a hook after the geometry pass (None without foliage).
+    foliage_renderer: Option<Rc<RefCell<crate::foliage::FoliageRenderer>>>,
+    foliage_hook: Option<crate::pipeline::HookId>,
+    /// Terrains, drawn by a hook after the geometry pass (None without terrain).
+    terrain_renderer: Option<Rc<RefCell<crate::terrain::TerrainRenderer>>>,
+    terrain_hook: Option<crate::pipeline::HookId>,
 
     // Skeletal animation system
     pub animation_system: crate::anim_system::AnimationSystem,
@@ -130,12 +221,43 @@ impl Engine {
     pub fn new(args: CliArgs) -> Self {
         let project_root = PathBuf::from(&args.project);
         let show_hud = args.hud;
-        Self {
+        let project_config = crate::project_config::load_config(&project_root.join("naive.yaml")).ok();
+        let mut render_debug = crate::pipeline::RenderDebugState {
+            show_hud,
+            ..Default::default()
+        };
+        if let Some(quality) = project_config.as_ref().and_then(|c| c.quality) {
+            quality.apply(&mut render_debug);
+        }
+        render_debug.lod_lock = args.lod_lock();
+        let render_settings = project_config.as_ref().map(|c| c.render_settings.clone()).unwrap_or_default();
+        render_debug.override_settings(&render_settings, &args.settings);
+        let watch_config = project_config.as_ref().map(|c| c.watch.clone()).unwrap_or_default();
+        let audio_settings = project_config.as_ref().map(|c| c.audio.clone()).unwrap_or_default();
+        let steam = crate::steam::SteamSession::connect(&project_config.as_ref().map(|c| c.steam.clone()).unwrap_or_default());
+        let analytics = match &project_config {
+            Some(c) => crate::analytics::Analytics::new(&c.analytics, &project_root, &c.name, &c.version),
+            None => crate::analytics::Analytics::new(&Default::default(), &project_root, "", ""),
+        };
+        let mods = crate::mods::ModSet::load(&project_root).unwrap_or_else(|e| {
+            tracing::error!("Mods not loaded: {}", e);
+            Default::default()
+        });
+        mods.report();
+        let overlay = mods.overlay.clone();
+        let sequences = crate::sequence::SequencePlayer::new(&project_root);
+        let camera_paths = crate::camera_path::CameraPathPlayer::new(&project_root);
+        let mut engine = Self {
             args,
             gpu: None,
             project_root,
             _watcher: None,
+            include_watch_dirs: Default::default(),
             watch_rx: None,
+            project_config,
+            watch_config,
+            pending_watch_events: Vec::new(),
+            last_watch_event: None,
             scene_world: None,
             mesh_cache: MeshCache::new(),
             material_cache: MaterialCache::new(),
@@ -144,6 +266,8 @@ impl Engine {
             draw_pool: None,
             forward_pipeline: None,
             scene_path: None,
+            unsaved_edits: 0,
+            autosave_pending: None,
             compiled_pipeline: None,
             pipeline_path: None,
             input_state: None,
@@ -152,34 +276,86 @@ impl Engine {
             delta_time: 1.0 / 60.0,
             script_runtime: None,
             event_bus: Rc::new(RefCell::new(EventBus::new(1000))),
-            audio_system: Rc::new(RefCell::new(AudioSystem::new())),
+            audio_system: Rc::new(RefCell::new(AudioSystem::with_settings(audio_settings))),
             tween_system: TweenSystem::new(),
             command_server: None,
             paused: false,
             bitmap_font: None,
             ui_renderer: None,
+            tool_windows: Vec::new(),
+            pending_tool_windows: Vec::new(),
             entity_commands: Rc::new(RefCell::new(crate::world::EntityCommandQueue::new())),
             pool_manager: Rc::new(RefCell::new(crate::world::EntityPoolManager::new())),
             particle_system: Rc::new(RefCell::new(crate::particles::ParticleSystem::new())),
             lua_event_listeners: Rc::new(RefCell::new(HashMap::new())),
             next_lua_listener_id: Rc::new(RefCell::new(0)),
             lua_listener_id_map: Rc::new(RefCell::new(HashMap::new())),
-            render_debug: crate::pipeline::RenderDebugState {
-                show_hud,
-                ..Default::default()
-            },
+            message_queue: Rc::new(RefCell::new(crate::messaging::MessageQueue::new())),
+            game_clock: Rc::new(RefCell::new(crate::time_scale::GameClock::new())),
+            screenshot_queue: Rc::new(RefCell::new(Vec::new())),
+            frames_presented: 0,
+            lua_memory_high: false,
+            low_fps_monitor: crate::engine_events::LowFpsMonitor::new(crate::engine_events::DEFAULT_LOW_FPS_THRESHOLD),
+            quality_governor: None,
+            window_activity: Default::default(),
+            render_suspended: false,
+            background_tick: None,
+            surface_recovery: Default::default(),
+            device_loss: Default::default(),
+            device_recovery: None,
+            restore_snapshot: None,
+            gpu_profiler: None,
+            draw_stats: Default::default(),
+            render_debug,
+            render_tweaks: Rc::new(RefCell::new(Vec::new())),
+            pass_params: Rc::new(RefCell::new(Default::default())),
+            pipeline_switch: Rc::new(RefCell::new(Default::default())),
+            curves: Rc::new(RefCell::new(crate::curves::CurveLibrary::new())),
+            player_views: Vec::new(),
+            crowd: Rc::new(RefCell::new(Default::default())),
+            noise: Rc::new(RefCell::new(Default::default())),
+            mods: Rc::new(RefCell::new(mods)),
+            steam: Rc::new(RefCell::new(steam)),
+            analytics: Rc::new(RefCell::new(analytics)),
+            sequences: Rc::new(RefCell::new(sequences)),
+            camera_paths: Rc::new(RefCell::new(camera_paths)),
+            render_hooks: crate::pipeline::RenderHooks::new(),
+            aliasing_suppressed: false,
             debug_draw: None,
             reload_notifications: Vec::new(),
+            pipeline_error: None,
             camera_shake: Rc::new(RefCell::new(CameraShakeState::new())),
             editor_camera: None,
+            debug_camera: None,
             editor_command_log: Vec::new(),
             editor_scene_path: None,
             shared_surface_config: None,
             texture_resources: None,
             texture_cache: crate::texture_cache::TextureCache::new(),
+            environment_cache: crate::environment::EnvironmentCache::new(),
+            color_grading_cache: crate::color_grading::ColorGradingCache::new(),
+            portal_renderer: None,
+            portal_hook: None,
+            foliage_renderer: None,
+            foliage_hook: None,
+            terrain_renderer: None,
+            terrain_hook: None,
             animation_system: crate::anim_system::AnimationSystem::new(),
             bone_palettes: HashMap::new(),
-        }
+        };
+        engine.mesh_cache.overlay = overlay.clone();
+        engine.material_cache.overlay = overlay.clone();
+        engine.texture_cache.overlay = overlay.clone();
+        engine.sequences.borrow_mut().overlay = overlay.clone();
+        engine.camera_paths.borrow_mut().overlay = overlay;
+        engine.splat_cache.arenas = engine.mesh_cache.arenas.clone();
+        engine.configure_quality_governor();
+        engine
+    }
+
+    /// Full path of a project-relative scene, from the mods if one has it.
+    fn scene_path(&self, relative: &str) -> PathBuf {
+        self.mods.borrow().overlay.resolve(&self.project_root, relative)
     }
 
     /// Get the initial WGSL shader source for the triangle, trying SLANG first.
@@ -196,10 +372,9 @@ impl Engine {
 
     /// Initialize the forward pipeline and load the scene.
     fn load_scene(&mut self) {
-        let gpu = match &self.gpu {
-            Some(gpu) => gpu,
-            None => return,
-        };
+        if self.gpu.is_none() {
+            return;
+        }
 
         // Generate default audio files if they don't exist
         crate::audio_gen::generate_default_sounds(&self.project_root);
@@ -209,12 +384,23 @@ impl Engine {
             None => return,
         };
 
-        // Resolve scene path relative to project root
-        let scene_path = self.project_root.join(&scene_arg);
+        // Resolve scene path relative to project root (or a mod)
+        let scene_path = self.scene_path(&scene_arg);
         if !scene_path.exists() {
             tracing::error!("Scene file not found: {:?}", scene_path);
             return;
         }
+        // The snapshot of a lost device, or live edits a crashed session
+        // left in .naive/autosave/
+        let recovered = match self.restore_snapshot.take() {
+            Some(snapshot) => Some(snapshot),
+            None => self.recovered_autosave(Some(&scene_path)),
+        };
+
+        let gpu = match &self.gpu {
+            Some(gpu) => gpu,
+            None => return,
+        };
 
         // Create camera state and draw uniform pool
         let camera_state = CameraState::new(&gpu.device);
@@ -252,7 +438,7 @@ impl Engine {
         );
 
         // Load the scene YAML
-        let scene = match crate::scene::load_scene(&scene_path) {
+        let scene = match crate::scene::load_scene(recovered.as_deref().unwrap_or(&scene_path)) {
             Ok(s) => s,
             Err(e) => {
                 tracing::error!("Failed to load scene: {}", e);
@@ -280,6 +466,7 @@ impl Engine {
 
         self.scene_world = Some(Rc::new(RefCell::new(scene_world)));
         self.camera_state = Some(Rc::new(RefCell::new(camera_state)));
+        self.player_views.clear();
         self.draw_pool = Some(draw_pool);
         self.forward_pipeline = Some(forward_pipeline);
         self.scene_path = Some(scene_path);
@@ -287,30 +474,35 @@ impl Engine {
         tracing::info!("Scene loaded and forward pipeline created");
 
         // UI overlay: bitmap font atlas + 2D renderer
-        let font = crate::font::create_bitmap_font(&gpu.device, &gpu.queue);
+        let font_cache = self.project_root.join(crate::font::FONT_CACHE_DIR);
+        let font = crate::font::create_bitmap_font(&gpu.device, &gpu.queue, self.text_rendering(), Some(&font_cache));
         let ui = UiRenderer::new(&gpu.device, gpu.config.format, &font);
         self.bitmap_font = Some(Rc::new(RefCell::new(font)));
         self.ui_renderer = Some(Rc::new(RefCell::new(ui)));
 
         // Register skeletal animation data from loaded meshes
         self.register_skeletons();
+        if self.args.color_audit {
+            self.report_color_audit("textures", crate::color_space::audit_textures(self.texture_cache.keys()));
+        }
 
         // Phase 5: Initialize input system
-        let bindings = crate::input::load_bindings(&self.project_root);
+        let bindings = crate::input::load_bindings(&self.bindings_path());
         self.input_state = Some(Rc::new(RefCell::new(InputState::new(bindings))));
 
         // Phase 5: Initialize physics world
-        let gravity = if let Some(sw) = &self.scene_world {
+        let (gravity, physics_rate) = if let Some(sw) = &self.scene_world {
             let sw = sw.borrow();
             if let Some(scene) = &sw.current_scene {
-                glam::Vec3::from(scene.settings.gravity)
+                (glam::Vec3::from(scene.settings.gravity), scene.settings.physics_rate)
             } else {
-                glam::Vec3::new(0.0, -9.81, 0.0)
+                (glam::Vec3::new(0.0, -9.81, 0.0), 0.0)
             }
         } else {
-            glam::Vec3::new(0.0, -9.81, 0.0)
+            (glam::Vec3::new(0.0, -9.81, 0.0), 0.0)
         };
         let mut physics_world = PhysicsWorld::new(gravity);
+        physics_world.timestep = crate::physics::FixedTimestep::new(physics_rate);
 
         // Spawn physics components for entities that have them
         if let Some(sw) = &self.scene_world {
@@ -404,8 +596,15 @@ impl Engine {
                                         .as_ref()
                                         .map(|rb| rb.ccd)
                                         .unwrap_or(false);
+                                    let interpolate = entity_def
+                                        .components
+                                        .rigid_body
+                                        .as_ref()
+                                        .map(|rb| rb.interpolate)
+                                        .unwrap_or(true);
                                     let (rb_handle, col_handle) = physics_world
                                         .add_dynamic_body(entity, pos, rot, shape.clone(), mass, restitution, friction, ccd);
+                                    physics_world.set_interpolated(rb_handle, interpolate);
                                     let rb_comp = crate::physics::RigidBody {
                                         handle: rb_handle,
                                         body_type: crate::physics::PhysicsBodyType::Dynamic,
@@ -420,6 +619,13 @@ impl Engine {
                                 "kinematic" => {
                                     let (rb_handle, col_handle) = physics_world
                                         .add_kinematic_body(entity, pos, rot, shape.clone(), is_trigger, restitution, friction);
+                                    let interpolate = entity_def
+                                        .components
+                                        .rigid_body
+                                        .as_ref()
+                                        .map(|rb| rb.interpolate)
+                                        .unwrap_or(true);
+                                    physics_world.set_interpolated(rb_handle, interpolate);
                                     let rb_comp = crate::physics::RigidBody {
                                         handle: rb_handle,
                                         body_type: crate::physics::PhysicsBodyType::Kinematic,
@@ -457,6 +663,13 @@ impl Engine {
 
         // Phase 6: Initialize scripting runtime
         let mut script_runtime = ScriptRuntime::new();
+        if let Err(e) = script_runtime.configure_memory(self.args.lua_memory_limit, self.args.lua_gc_budget) {
+            tracing::warn!("Lua memory limit unavailable: {}", e);
+        }
+        if let Err(e) = script_runtime.load_bundle(&self.project_root) {
+            tracing::error!("Failed to load script bundle: {}", e);
+        }
+        script_runtime.overlay = self.mods.borrow().overlay.clone();
         if let Err(e) = script_runtime.register_api() {
             tracing::error!("Failed to register script API: {}", e);
         }
@@ -511,6 +724,9 @@ impl Engine {
             if let Err(e) = script_runtime.register_camera_shake_api(self.camera_shake.clone()) {
                 tracing::error!("Failed to register camera shake API: {}", e);
             }
+            if let Err(e) = script_runtime.register_camera_path_api(self.camera_paths.clone()) {
+                tracing::error!("Failed to register camera path API: {}", e);
+            }
         }
 
         // Register event bus API (with Lua listener support)
@@ -518,18 +734,51 @@ impl Engine {
             if let Err(e) = script_runtime.register_event_api(self.event_bus.clone(), self.lua_event_listeners.clone(), self.next_lua_listener_id.clone(), self.lua_listener_id_map.clone()) {
                 tracing::error!("Failed to register event API: {}", e);
             }
+            if let Err(e) = script_runtime.register_message_api(self.message_queue.clone()) {
+                tracing::error!("Failed to register message API: {}", e);
+            }
         }
 
         // Register audio API
         {
-            if let Err(e) = script_runtime.register_audio_api(self.audio_system.clone(), self.project_root.clone()) {
+            if let Err(e) = script_runtime.register_audio_api(self.audio_system.clone(), self.project_root.clone(), self.noise.clone(), self.curves.clone()) {
                 tracing::error!("Failed to register audio API: {}", e);
             }
+            if let Err(e) = script_runtime.register_screenshot_api(self.screenshot_queue.clone(), self.project_root.clone()) {
+                tracing::error!("Failed to register screenshot API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_render_api(self.render_tweaks.clone(), self.pass_params.clone()) {
+                tracing::error!("Failed to register render API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_pipeline_api(self.pipeline_switch.clone(), self.project_root.clone()) {
+                tracing::error!("Failed to register pipeline API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_curve_api(self.curves.clone(), self.project_root.clone()) {
+                tracing::error!("Failed to register curve API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_crowd_api(self.crowd.clone()) {
+                tracing::error!("Failed to register crowd API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_noise_api(self.noise.clone()) {
+                tracing::error!("Failed to register noise API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_mods_api(self.mods.clone()) {
+                tracing::error!("Failed to register mods API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_steam_api(self.steam.clone()) {
+                tracing::error!("Failed to register steam API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_analytics_api(self.analytics.clone()) {
+                tracing::error!("Failed to register analytics API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_sequence_api(self.sequences.clone()) {
+                tracing::error!("Failed to register sequence API: {}", e);
+            }
         }
 
         // Register particle API
         if let Some(sw) = &self.scene_world {
-            if let Err(e) = script_runtime.register_particle_api(sw.clone(), self.particle_system.clone()) {
+            if let Err(e) = script_runtime.register_particle_api(sw.clone(), self.particle_system.clone(), self.project_root.clone()) {
                 tracing::error!("Failed to register particle API: {}", e);
             }
         }
@@ -542,7 +791,19 @@ impl Engine {
             if let Err(e) = script_runtime.register_mesh_api(self.entity_commands.clone()) {
                 tracing::error!("Failed to register mesh API: {}", e);
             }
+            if let Err(e) = script_runtime.register_time_api(self.game_clock.clone(), sw.clone()) {
+                tracing::error!("Failed to register time API: {}", e);
+            }
+        }
+        if let Err(e) = script_runtime.register_config_api(&self.script_config()) {
+            tracing::error!("Failed to register config API: {}", e);
         }
+        if let Err(e) = script_runtime.register_api_shims(self.script_api_level(), crate::scripting::LUA_DEPRECATIONS) {
+            tracing::error!("Failed to register script API shims: {}", e);
+        }
+
+        // Phase 7: Load the event schema before scripts run so emits are validated from init()
+        self.event_bus.borrow_mut().load_schema(&self.project_root);
 
         // Load scripts for entities that have them
         if let Some(sw) = &self.scene_world {
@@ -600,13 +861,21 @@ impl Engine {
         self.script_runtime = Some(script_runtime);
         tracing::info!("Script runtime initialized");
 
-        // Phase 7: Initialize event bus schema and audio
-        self.event_bus.borrow_mut().load_schema(&self.project_root);
+        let scene = self.scene_path.as_deref().map(|p| self.project_relative(p)).unwrap_or_default();
+        self.emit_engine_event(
+            crate::engine_events::SCENE_LOADED,
+            crate::engine_events::payload(serde_json::json!({"scene": scene})),
+        );
 
         // Phase 3: try to compile the render pipeline if --pipeline was given
-        self.try_load_pipeline();
+        if let Err(e) = self.try_load_pipeline() {
+            tracing::error!("{}", e);
+        }
 
-        // Phase 8: Start command socket server
+        // Phase 8: Start command socket server (kept across device recovery)
+        if self.command_server.is_some() {
+            return;
+        }
         match CommandServer::start(&self.args.socket) {
             Ok(server) => {
                 tracing::info!("Command socket: {}", server.socket_path);
@@ -620,6 +889,14 @@ impl Engine {
 
     /// Initialize editor mode: load or create scene, init free camera, start command socket.
     fn init_editor_mode(&mut self) {
+        // The snapshot of a lost device, or live edits a crashed session
+        // left in .naive/autosave/
+        let existing_scene = self.args.scene.as_ref().map(|s| self.scene_path(s)).filter(|p| p.exists());
+        let recovered = match self.restore_snapshot.take() {
+            Some(snapshot) => Some(snapshot),
+            None => self.recovered_autosave(existing_scene.as_deref()),
+        };
+
         let gpu = match &self.gpu {
             Some(gpu) => gpu,
             None => return,
@@ -631,7 +908,9 @@ impl Engine {
         } else {
             "nAIVE Editor".to_string()
         };
-        gpu.window.set_title(&title);
+        if let Some(window) = &gpu.window {
+            window.set_title(&title);
+        }
 
         // Generate default audio files
         crate::audio_gen::generate_default_sounds(&self.project_root);
@@ -663,9 +942,9 @@ impl Engine {
 
         // Load existing scene or create a default editor scene
         let (scene, scene_path) = if let Some(scene_arg) = &self.args.scene {
-            let path = self.project_root.join(scene_arg);
+            let path = self.scene_path(scene_arg);
             if path.exists() {
-                match crate::scene::load_scene(&path) {
+                match crate::scene::load_scene(recovered.as_deref().unwrap_or(&path)) {
                     Ok(s) => (s, Some(path)),
                     Err(e) => {
                         tracing::error!("Failed to load scene: {}", e);
@@ -676,6 +955,14 @@ impl Engine {
                 tracing::warn!("Scene file not found: {:?}, creating default", path);
                 (Self::default_editor_scene(), None)
             }
+        } else if let Some(autosave) = &recovered {
+            match crate::scene::load_scene(autosave) {
+                Ok(s) => (s, None),
+                Err(e) => {
+                    tracing::error!("Failed to load autosave: {}", e);
+                    (Self::default_editor_scene(), None)
+                }
+            }
         } else {
             (Self::default_editor_scene(), None)
         };
@@ -702,6 +989,7 @@ impl Engine {
         scene_world.current_scene = Some(scene.clone());
         self.scene_world = Some(Rc::new(RefCell::new(scene_world)));
         self.camera_state = Some(Rc::new(RefCell::new(camera_state)));
+        self.player_views.clear();
         self.draw_pool = Some(draw_pool);
         self.forward_pipeline = Some(forward_pipeline);
         self.scene_path = scene_path.clone();
@@ -710,6 +998,7 @@ impl Engine {
         // Initialize physics world (same as normal game mode)
         let gravity = glam::Vec3::from(scene.settings.gravity);
         let mut physics_world = PhysicsWorld::new(gravity);
+        physics_world.timestep = crate::physics::FixedTimestep::new(scene.settings.physics_rate);
         if let Some(sw) = &self.scene_world {
             let mut sw = sw.borrow_mut();
             for entity_def in &scene.entities {
@@ -754,6 +1043,8 @@ impl Engine {
                                     .map(|rb| rb.ccd).unwrap_or(false);
                                 let (rb_handle, col_handle) = physics_world
                                     .add_dynamic_body(entity, pos, rot, shape.clone(), mass, restitution, friction, ccd);
+                                physics_world.set_interpolated(rb_handle, entity_def.components.rigid_body.as_ref()
+                                    .map(|rb| rb.interpolate).unwrap_or(true));
                                 let _ = sw.world.insert(entity, (
                                     crate::physics::RigidBody { handle: rb_handle, body_type: crate::physics::PhysicsBodyType::Dynamic },
                                     crate::physics::Collider { handle: col_handle, shape, is_trigger },
@@ -762,6 +1053,8 @@ impl Engine {
                             "kinematic" => {
                                 let (rb_handle, col_handle) = physics_world
                                     .add_kinematic_body(entity, pos, rot, shape.clone(), is_trigger, restitution, friction);
+                                physics_world.set_interpolated(rb_handle, entity_def.components.rigid_body.as_ref()
+                                    .map(|rb| rb.interpolate).unwrap_or(true));
                                 let _ = sw.world.insert(entity, (
                                     crate::physics::RigidBody { handle: rb_handle, body_type: crate::physics::PhysicsBodyType::Kinematic },
                                     crate::physics::Collider { handle: col_handle, shape, is_trigger },
@@ -784,7 +1077,8 @@ impl Engine {
         tracing::info!("Physics world initialized");
 
         // UI overlay (must be initialized before Lua API registration)
-        let font = crate::font::create_bitmap_font(&gpu.device, &gpu.queue);
+        let font_cache = self.project_root.join(crate::font::FONT_CACHE_DIR);
+        let font = crate::font::create_bitmap_font(&gpu.device, &gpu.queue, self.text_rendering(), Some(&font_cache));
         let ui = UiRenderer::new(&gpu.device, gpu.config.format, &font);
         self.bitmap_font = Some(Rc::new(RefCell::new(font)));
         self.ui_renderer = Some(Rc::new(RefCell::new(ui)));
@@ -793,11 +1087,18 @@ impl Engine {
         self.register_skeletons();
 
         // Input system (must be initialized before Lua API registration)
-        let bindings = crate::input::load_bindings(&self.project_root);
+        let bindings = crate::input::load_bindings(&self.bindings_path());
         self.input_state = Some(Rc::new(RefCell::new(InputState::new(bindings))));
 
         // Initialize scripting runtime with full API suite (same as load_scene)
-        let script_runtime = ScriptRuntime::new();
+        let mut script_runtime = ScriptRuntime::new();
+        if let Err(e) = script_runtime.configure_memory(self.args.lua_memory_limit, self.args.lua_gc_budget) {
+            tracing::warn!("Lua memory limit unavailable: {}", e);
+        }
+        if let Err(e) = script_runtime.load_bundle(&self.project_root) {
+            tracing::error!("Failed to load script bundle: {}", e);
+        }
+        script_runtime.overlay = self.mods.borrow().overlay.clone();
         if let Err(e) = script_runtime.register_api() {
             tracing::error!("Failed to register script API: {}", e);
         }
@@ -852,6 +1153,9 @@ impl Engine {
             if let Err(e) = script_runtime.register_camera_shake_api(self.camera_shake.clone()) {
                 tracing::error!("Failed to register camera shake API: {}", e);
             }
+            if let Err(e) = script_runtime.register_camera_path_api(self.camera_paths.clone()) {
+                tracing::error!("Failed to register camera path API: {}", e);
+            }
         }
 
         // Register event bus API
@@ -859,18 +1163,51 @@ impl Engine {
             if let Err(e) = script_runtime.register_event_api(self.event_bus.clone(), self.lua_event_listeners.clone(), self.next_lua_listener_id.clone(), self.lua_listener_id_map.clone()) {
                 tracing::error!("Failed to register event API: {}", e);
             }
+            if let Err(e) = script_runtime.register_message_api(self.message_queue.clone()) {
+                tracing::error!("Failed to register message API: {}", e);
+            }
         }
 
         // Register audio API
         {
-            if let Err(e) = script_runtime.register_audio_api(self.audio_system.clone(), self.project_root.clone()) {
+            if let Err(e) = script_runtime.register_audio_api(self.audio_system.clone(), self.project_root.clone(), self.noise.clone(), self.curves.clone()) {
                 tracing::error!("Failed to register audio API: {}", e);
             }
+            if let Err(e) = script_runtime.register_screenshot_api(self.screenshot_queue.clone(), self.project_root.clone()) {
+                tracing::error!("Failed to register screenshot API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_render_api(self.render_tweaks.clone(), self.pass_params.clone()) {
+                tracing::error!("Failed to register render API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_pipeline_api(self.pipeline_switch.clone(), self.project_root.clone()) {
+                tracing::error!("Failed to register pipeline API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_curve_api(self.curves.clone(), self.project_root.clone()) {
+                tracing::error!("Failed to register curve API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_crowd_api(self.crowd.clone()) {
+                tracing::error!("Failed to register crowd API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_noise_api(self.noise.clone()) {
+                tracing::error!("Failed to register noise API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_mods_api(self.mods.clone()) {
+                tracing::error!("Failed to register mods API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_steam_api(self.steam.clone()) {
+                tracing::error!("Failed to register steam API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_analytics_api(self.analytics.clone()) {
+                tracing::error!("Failed to register analytics API: {}", e);
+            }
+            if let Err(e) = script_runtime.register_sequence_api(self.sequences.clone()) {
+                tracing::error!("Failed to register sequence API: {}", e);
+            }
         }
 
         // Register particle API
         if let Some(sw) = &self.scene_world {
-            if let Err(e) = script_runtime.register_particle_api(sw.clone(), self.particle_system.clone()) {
+            if let Err(e) = script_runtime.register_particle_api(sw.clone(), self.particle_system.clone(), self.project_root.clone()) {
                 tracing::error!("Failed to register particle API: {}", e);
             }
         }
@@ -883,6 +1220,15 @@ impl Engine {
             if let Err(e) = script_runtime.register_mesh_api(self.entity_commands.clone()) {
                 tracing::error!("Failed to register mesh API: {}", e);
             }
+            if let Err(e) = script_runtime.register_time_api(self.game_clock.clone(), sw.clone()) {
+                tracing::error!("Failed to register time API: {}", e);
+            }
+        }
+        if let Err(e) = script_runtime.register_config_api(&self.script_config()) {
+            tracing::error!("Failed to register config API: {}", e);
+        }
+        if let Err(e) = script_runtime.register_api_shims(self.script_api_level(), crate::scripting::LUA_DEPRECATIONS) {
+            tracing::error!("Failed to register script API shims: {}", e);
         }
 
         self.script_runtime = Some(script_runtime);
@@ -903,16 +1249,20 @@ impl Engine {
         self.editor_camera = Some(EditorCamera::new(cam_pos, 0.0, -0.3));
 
         // Try to load render pipeline
-        self.try_load_pipeline();
+        if let Err(e) = self.try_load_pipeline() {
+            tracing::error!("{}", e);
+        }
 
-        // Start command socket
-        match CommandServer::start(&self.args.socket) {
-            Ok(server) => {
-                tracing::info!("Editor command socket: {}", server.socket_path);
-                self.command_server = Some(server);
-            }
-            Err(e) => {
-                tracing::warn!("Failed to start command server: {}", e);
+        // Start command socket (kept across device recovery)
+        if self.command_server.is_none() {
+            match CommandServer::start(&self.args.socket) {
+                Ok(server) => {
+                    tracing::info!("Editor command socket: {}", server.socket_path);
+                    self.command_server = Some(server);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to start command server: {}", e);
+                }
             }
         }
 
@@ -923,11 +1273,15 @@ impl Engine {
     fn default_editor_scene() -> crate::scene::SceneFile {
         use crate::scene::*;
         SceneFile {
+            version: crate::format::SCENE_FORMAT.current,
             name: "Editor Scene".to_string(),
             settings: SceneSettings {
                 ambient_light: [0.15, 0.15, 0.2],
                 fog: None,
                 gravity: [0.0, -9.81, 0.0],
+                physics_rate: 0.0,
+                config: Default::default(),
+                color_grading: None,
             },
             entities: vec![
                 // Ground plane (with static collider so things bounce off it)
@@ -946,6 +1300,7 @@ impl Engine {
                             material: "procedural:default".to_string(),
                             cast_shadows: true,
                             receive_shadows: true,
+                            lod: None,
                         }),
                         collider: Some(ColliderDef {
                             shape: "box".to_string(),
@@ -975,6 +1330,7 @@ impl Engine {
                             material: "procedural:default".to_string(),
                             cast_shadows: true,
                             receive_shadows: true,
+                            lod: None,
                         }),
                         ..Default::default()
                     },
@@ -1014,6 +1370,8 @@ impl Engine {
                             color: [1.0, 0.9, 0.8],
                             intensity: 50.0,
                             range: 30.0,
+                            casts_shadows: false,
+                            flicker: None,
                         }),
                         ..Default::default()
                     },
@@ -1033,6 +1391,8 @@ impl Engine {
                             color: [0.6, 0.7, 1.0],
                             intensity: 30.0,
                             range: 25.0,
+                            casts_shadows: false,
+                            flicker: None,
                         }),
                         ..Default::default()
                     },
@@ -1057,6 +1417,12 @@ impl Engine {
                             distance: 4.0,
                             height_offset: 1.5,
                             pitch_limits: None,
+                            position_damping: 0.0,
+                            rotation_damping: 0.0,
+                            look_ahead: 0.0,
+                            look_ahead_max: 2.0,
+                            projection: "perspective".to_string(),
+                            size: 10.0,
                         }),
                         ..Default::default()
                     },
@@ -1066,8 +1432,10 @@ impl Engine {
     }
 
     /// Attempt to load and compile the render pipeline from YAML.
-    fn try_load_pipeline(&mut self) {
-        let pipeline_arg = match &self.args.pipeline {
+    /// Returns Ok without compiling when no pipeline is configured or the GPU isn't ready.
+    fn try_load_pipeline(&mut self) -> Result<(), String> {
+        let selected = self.pipeline_switch.borrow().selected.clone();
+        let pipeline_arg = match selected.as_ref().or(self.args.pipeline.as_ref()) {
             Some(p) => p.clone(),
             None => {
                 // Auto-detect: use pipelines/render.yaml if it exists
@@ -1075,61 +1443,306 @@ impl Engine {
                 if default_path.exists() {
                     "pipelines/render.yaml".to_string()
                 } else {
-                    return;
+                    return Ok(());
                 }
             }
         };
 
         let gpu = match &self.gpu {
             Some(gpu) => gpu,
-            None => return,
+            None => return Ok(()),
         };
         let camera_state_rc = match &self.camera_state {
             Some(cs) => cs,
-            None => return,
+            None => return Ok(()),
         };
         let camera_state = camera_state_rc.borrow();
         let draw_pool = match &self.draw_pool {
             Some(dp) => dp,
-            None => return,
+            None => return Ok(()),
         };
 
         let pipeline_path = self.project_root.join(&pipeline_arg);
         if !pipeline_path.exists() {
-            tracing::error!("Pipeline file not found: {:?}", pipeline_path);
-            return;
+            return Err(format!("Pipeline file not found: {:?}", pipeline_path));
         }
 
         match crate::pipeline::load_pipeline(&pipeline_path) {
-            Ok(pipeline_file) => {
+            Ok(mut pipeline_file) => {
+                // Hooks can read any resource, so they get textures of their own
+                let suppress_aliasing = pipeline_file.settings.alias_resources && !self.render_hooks.is_empty();
+                pipeline_file.settings.alias_resources &= !suppress_aliasing;
                 let tex_layout = self.texture_resources.as_ref().map(|tr| &tr.bind_group_layout);
+                let (render_width, render_height) = self.render_debug.render_size(gpu.config.width, gpu.config.height);
                 match crate::pipeline::compile_pipeline(
                     &gpu.device,
+                    &gpu.adapter,
                     &pipeline_file,
                     &self.project_root,
                     &*camera_state,
                     draw_pool,
                     gpu.config.format,
-                    gpu.config.width,
-                    gpu.config.height,
+                    render_width,
+                    render_height,
                     tex_layout,
                 ) {
-                    Ok(compiled) => {
+                    Ok(mut compiled) => {
+                        // auto_quality's shadow map size carries over a recompile
+                        if self.render_debug.shadow_resolution.is_some() {
+                            let size = self.render_debug.shadow_resolution;
+                            crate::pipeline::resize_fixed_resource(&gpu.device, &mut compiled.resources, "shadow_map", size);
+                            crate::pipeline::rebuild_bind_groups(&gpu.device, &mut compiled);
+                        }
+                        // Jitter the projection only while a TAA pass accumulates it
+                        drop(camera_state);
+                        let mut camera_state = camera_state_rc.borrow_mut();
+                        camera_state.jitter_enabled = compiled.taa.is_some();
+                        camera_state.reset_history();
+                        drop(camera_state);
+                        // Materials may name the pipeline's samplers
+                        if let Some(tex_res) = &mut self.texture_resources {
+                            tex_res.set_named_samplers(compiled.samplers.clone());
+                            self.material_cache.rebuild_sampled_bind_groups(&gpu.device, &self.texture_cache, tex_res);
+                        }
+                        // Scripts' post-process parameters carry over a recompile
+                        self.pass_params.borrow_mut().declare(&compiled);
+                        if self.args.color_audit {
+                            let formats = compiled.resources.iter().map(|(name, r)| (name.clone(), r.format)).collect();
+                            let issues = crate::color_space::audit_pipeline(&pipeline_file, &formats, gpu.config.format);
+                            self.report_color_audit("pipeline", issues);
+                        }
                         self.compiled_pipeline = Some(compiled);
+                        self.aliasing_suppressed = suppress_aliasing;
                         self.pipeline_path = Some(pipeline_path);
+                        self.pipeline_switch.borrow_mut().current = Some(pipeline_arg);
+                        self.pipeline_error = None;
+                        self.watch_shader_includes();
                         tracing::info!("Render pipeline compiled successfully");
+                        Ok(())
                     }
                     Err(e) => {
-                        tracing::error!("Pipeline compilation failed: {}", e);
+                        let message = format!("Pipeline compilation failed: {}", e);
+                        self.pipeline_error = Some(message.clone());
+                        Err(message)
                     }
                 }
             }
             Err(e) => {
-                tracing::error!("Failed to load pipeline: {}", e);
+                let message = format!("Failed to load pipeline: {}", e);
+                self.pipeline_error = Some(message.clone());
+                Err(message)
             }
         }
     }
 
+    /// Drop and recompile the render pipeline, reporting the result on the event bus.
+    fn recompile_pipeline(&mut self) -> Result<(), String> {
+        self.compiled_pipeline = None;
+        if let Some(camera_state) = &self.camera_state {
+            camera_state.borrow_mut().jitter_enabled = false;
+        }
+        let result = self.try_load_pipeline();
+        if let Err(e) = &result {
+            tracing::error!("{}", e);
+        }
+        let path = self.pipeline_path.as_deref().map(|p| self.project_relative(p)).unwrap_or_default();
+        self.emit_engine_event(
+            crate::engine_events::PIPELINE_RECOMPILED,
+            crate::engine_events::payload(serde_json::json!({
+                "path": path,
+                "ok": result.is_ok(),
+                "error": result.as_ref().err().cloned().unwrap_or_default(),
+            })),
+        );
+        result
+    }
+
+    /// Compile another of the project's pipeline files and swap it in for the
+    /// running one (`pipeline.switch`, `switch_pipeline`). On failure the
+    /// running pipeline stays. Returns the file switched to.
+    fn switch_pipeline(&mut self, name: &str) -> Result<String, String> {
+        let file = crate::pipeline::switch::pipeline_file(name);
+        let result = if self.project_root.join(&file).is_file() {
+            let previous = self.pipeline_switch.borrow_mut().selected.replace(file.clone());
+            let previous_error = self.pipeline_error.clone();
+            let result = self.try_load_pipeline();
+            if result.is_err() {
+                self.pipeline_switch.borrow_mut().selected = previous;
+                self.pipeline_error = previous_error;
+            }
+            result
+        } else {
+            Err(format!(
+                "No pipeline '{}' ({} not found; available: {})",
+                name,
+                file,
+                crate::pipeline::switch::list_pipelines(&self.project_root).join(", ")
+            ))
+        };
+
+        match &result {
+            Ok(()) => {
+                tracing::info!("Switched render pipeline to {}", file);
+                self.reload_notifications.push((
+                    format!("Pipeline: {}", crate::pipeline::switch::pipeline_name(&file)),
+                    instant::Instant::now(),
+                    [0.3, 1.0, 0.3, 1.0],
+                ));
+            }
+            Err(e) => {
+                tracing::error!("Pipeline switch to '{}' failed: {}", name, e);
+                self.reload_notifications.push((
+                    format!("Pipeline switch failed: {}", name),
+                    instant::Instant::now(),
+                    [1.0, 0.3, 0.3, 1.0],
+                ));
+            }
+        }
+        self.emit_engine_event(
+            crate::engine_events::PIPELINE_RECOMPILED,
+            crate::engine_events::payload(serde_json::json!({
+                "path": file,
+                "ok": result.is_ok(),
+                "error": result.as_ref().err().cloned().unwrap_or_default(),
+            })),
+        );
+        result.map(|()| file)
+    }
+
+    /// Make the pipeline switch a script asked for (deferred from `pipeline.switch`).
+    fn process_pending_pipeline_switch(&mut self) {
+        let Some(file) = self.pipeline_switch.borrow_mut().pending.take() else { return };
+        let _ = self.switch_pipeline(&file);
+    }
+
+    /// Handle switch_pipeline: compile and swap in `name` (see `switch_pipeline`).
+    fn handle_switch_pipeline(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+
+        let Some(name) = req.params.get("name").and_then(|v| v.as_str()) else {
+            return CommandResponse::error("Missing 'name' parameter");
+        };
+        match self.switch_pipeline(name) {
+            Ok(file) => CommandResponse::ok(serde_json::json!({
+                "name": crate::pipeline::switch::pipeline_name(&file),
+                "path": file,
+                "available": crate::pipeline::switch::list_pipelines(&self.project_root),
+            })),
+            Err(e) => CommandResponse::error(e),
+        }
+    }
+
+    /// Queue a built-in engine event (see `engine_events`).
+    fn emit_engine_event(&self, event_type: &str, data: HashMap<String, serde_json::Value>) {
+        self.event_bus.borrow_mut().emit(event_type, data);
+    }
+
+    /// Report scripts the watchdog aborted: log, on-screen notice and a
+    /// `script.budget_exceeded` event.
+    fn process_watchdog_events(&mut self) {
+        let events = match &self.script_runtime {
+            Some(sr) => sr.watchdog.borrow_mut().take_events(),
+            None => return,
+        };
+        for event in events {
+            let entity_id = event.entity
+                .and_then(|e| {
+                    let sw = self.scene_world.as_ref()?.borrow();
+                    sw.entity_registry.iter().find(|(_, &v)| v == e).map(|(id, _)| id.clone())
+                })
+                .unwrap_or_default();
+            let label = if entity_id.is_empty() { event.hook.clone() } else { format!("{}.{}", entity_id, event.hook) };
+            let message = if event.disabled {
+                format!("Script disabled: {} exceeded its {} budget {} times", label, event.kind.as_str(), event.violations)
+            } else {
+                format!("Script aborted: {} exceeded its {} budget", label, event.kind.as_str())
+            };
+            tracing::warn!("{}", message);
+            self.reload_notifications.push((message, instant::Instant::now(), [1.0, 0.5, 0.2, 1.0]));
+            self.emit_engine_event(
+                crate::engine_events::SCRIPT_BUDGET_EXCEEDED,
+                crate::engine_events::payload(serde_json::json!({
+                    "entity_id": entity_id,
+                    "hook": event.hook,
+                    "budget": event.kind.as_str(),
+                    "violations": event.violations,
+                    "disabled": event.disabled,
+                })),
+            );
+        }
+    }
+
+    /// Run the end-of-frame Lua GC and emit `script.memory_high` when the VM
+    /// crosses the high-water mark of its memory cap.
+    fn process_script_memory(&mut self) {
+        let script_runtime = match &self.script_runtime {
+            Some(sr) => sr,
+            None => return,
+        };
+        let report = script_runtime.collect_garbage();
+        if report.full_collect {
+            tracing::warn!("Lua memory near its cap: ran a full collection ({:.2} ms)", report.ms);
+        }
+        let used = script_runtime.lua.used_memory();
+        let (high, limit, live, top) = {
+            let watchdog = script_runtime.watchdog.borrow();
+            let memory = &watchdog.memory;
+            let top = memory.entity_stats()
+                .max_by(|a, b| a.1.bytes_per_frame.total_cmp(&b.1.bytes_per_frame))
+                .map(|(e, stats)| (e, stats.bytes_per_frame));
+            (memory.is_high(used), memory.limit, memory.live_bytes, top)
+        };
+        if high && !self.lua_memory_high {
+            let (top_id, top_rate) = match top {
+                Some((entity, rate)) => {
+                    let id = self.scene_world.as_ref()
+                        .and_then(|sw| sw.borrow().entity_registry.iter().find(|(_, &v)| v == entity).map(|(id, _)| id.clone()))
+                        .unwrap_or_else(|| format!("{:?}", entity));
+                    (id, rate)
+                }
+                None => (String::new(), 0.0),
+            };
+            let message = format!(
+                "Lua memory high: {:.1} / {:.0} MB (top allocator: {} at {:.0} KB/frame)",
+                used as f64 / (1024.0 * 1024.0),
+                limit as f64 / (1024.0 * 1024.0),
+                if top_id.is_empty() { "none" } else { &top_id },
+                top_rate / 1024.0,
+            );
+            tracing::warn!("{}", message);
+            self.reload_notifications.push((message, instant::Instant::now(), [1.0, 0.5, 0.2, 1.0]));
+            self.emit_engine_event(
+                crate::engine_events::SCRIPT_MEMORY_HIGH,
+                crate::engine_events::payload(serde_json::json!({
+                    "used_bytes": used,
+                    "limit_bytes": limit,
+                    "live_bytes": live,
+                    "top_entity_id": top_id,
+                    "top_bytes_per_frame": top_rate,
+                })),
+            );
+        }
+        self.lua_memory_high = high;
+    }
+
+    /// Path relative to the project root, for event payloads.
+    fn project_relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.project_root).unwrap_or(path).to_string_lossy().to_string()
+    }
+
+    /// Watch the compiled pipeline's shader includes that lie outside the
+    /// project's watched directories.
+    fn watch_shader_includes(&mut self) {
+        if let (Some(watcher), Some(compiled)) = (&mut self._watcher, &self.compiled_pipeline) {
+            crate::watcher::watch_includes(
+                watcher,
+                &self.project_root,
+                compiled.passes.iter().flat_map(|p| &p.includes),
+                &mut self.include_watch_dirs,
+            );
+        }
+    }
+
     /// Start the file watcher on the project directory.
     fn start_watcher(&mut self) {
         match crate::watcher::start_watching_all(&self.project_root) {
@@ -1137,6 +1750,7 @@ impl Engine {
                 self._watcher = Some(watcher);
                 self.watch_rx = Some(rx);
                 tracing::info!("File watching enabled");
+                self.watch_shader_includes();
             }
             Err(e) => {
                 tracing::warn!("Failed to start file watcher: {:?}", e);
@@ -1145,24 +1759,21 @@ impl Engine {
     }
 
     /// Handle a shader file change by recompiling and recreating the pipeline.
-    fn handle_shader_reload(&mut self, changed_path: &Path) {
+    fn handle_shader_reload(&mut self, changed_path: &Path) -> Result<(), String> {
+        // If we have a compiled pipeline and the shader belongs to it, recompile the pipeline
+        if self.compiled_pipeline.is_some() {
+            tracing::info!("Hot-reloading shader: {:?}", changed_path);
+            tracing::info!("Recompiling render pipeline after shader change");
+            return self.recompile_pipeline();
+        }
+
         let gpu = match &mut self.gpu {
             Some(gpu) => gpu,
-            None => return,
+            None => return Ok(()),
         };
 
         tracing::info!("Hot-reloading shader: {:?}", changed_path);
 
-        // If we have a compiled pipeline and the shader belongs to it, recompile the pipeline
-        if self.compiled_pipeline.is_some() {
-            tracing::info!("Recompiling render pipeline after shader change");
-            // Drop the old pipeline first
-            self.compiled_pipeline = None;
-            // Recompile
-            self.try_load_pipeline();
-            return;
-        }
-
         // Phase 2 shader reload path
         let is_forward = self.forward_pipeline.is_some();
 
@@ -1171,7 +1782,7 @@ impl Engine {
                 Ok(wgsl) => wgsl,
                 Err(e) => {
                     tracing::error!("Shader reload failed: {}, keeping old pipeline", e);
-                    return;
+                    return Err(e.to_string());
                 }
             };
 
@@ -1194,7 +1805,7 @@ impl Engine {
             let error = pollster::block_on(gpu.device.pop_error_scope());
             if let Some(err) = error {
                 tracing::error!("Shader validation error: {:?}, keeping old pipeline", err);
-                return;
+                return Err(err.to_string());
             }
 
             self.forward_pipeline = Some(new_pipeline);
@@ -1204,7 +1815,7 @@ impl Engine {
                 Ok(wgsl) => wgsl,
                 Err(e) => {
                     tracing::error!("Shader reload failed: {}, keeping old pipeline", e);
-                    return;
+                    return Err(e.to_string());
                 }
             };
 
@@ -1217,24 +1828,25 @@ impl Engine {
             let error = pollster::block_on(gpu.device.pop_error_scope());
             if let Some(err) = error {
                 tracing::error!("Shader validation error: {:?}, keeping old pipeline", err);
-                return;
+                return Err(err.to_string());
             }
 
             gpu.render_pipeline = Some(new_pipeline);
             tracing::info!("Triangle shader hot-reload complete");
         }
+        Ok(())
     }
 
     /// Handle a scene file change.
-    fn handle_scene_reload(&mut self, changed_path: &Path) {
+    fn handle_scene_reload(&mut self, changed_path: &Path) -> Result<(), String> {
         let gpu = match &self.gpu {
             Some(gpu) => gpu,
-            None => return,
+            None => return Ok(()),
         };
 
         let scene_world = match &self.scene_world {
     Some(scene_world) => scene_world,
-    None => return,
+    None => return Ok(()),
 };
 let mut scene_world = scene_world.borrow_mut();
 
@@ -1246,7 +1858,7 @@ let mut scene_world = scene_world.borrow_mut();
                 let file_name = changed_path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
                 self.reload_notifications.push((format!("Scene failed: {} - {}", file_name, e), instant::Instant::now(), [1.0, 0.3, 0.3, 1.0]));
                 tracing::error!("Scene reload failed: {}, keeping old scene", e);
-                return;
+                return Err(e.to_string());
             }
         };
 
@@ -1321,6 +1933,7 @@ let mut scene_world = scene_world.borrow_mut();
                                                 pw.add_static_body(entity, pos, rot, new_shape.clone(), is_trigger, restitution, friction)
                                             }
                                         };
+                                        pw.set_interpolated(rb_handle, entity_def.components.rigid_body.as_ref().map(|rb| rb.interpolate).unwrap_or(true));
                                         let _ = scene_world.world.insert(entity, (
                                             crate::physics::RigidBody { handle: rb_handle, body_type: body_type_enum },
                                             crate::physics::Collider { handle: col_handle, shape: new_shape, is_trigger },
@@ -1334,13 +1947,44 @@ let mut scene_world = scene_world.borrow_mut();
             }
         }
 
+        // The scene's `settings.config` may have changed
+        drop(pw_borrow);
+        drop(scene_world);
+        self.refresh_script_config();
+
         let file_name = changed_path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
         self.reload_notifications.push((format!("Scene reloaded: {}", file_name), instant::Instant::now(), [0.3, 1.0, 0.3, 1.0]));
         tracing::info!("Scene hot-reload complete");
+        self.emit_engine_event(
+            crate::engine_events::SCENE_RELOADED,
+            crate::engine_events::payload(serde_json::json!({"scene": self.project_relative(changed_path)})),
+        );
+        Ok(())
+    }
+
+    /// Handle a splat (.ply, .splat, .ksplat, .spz, .nsplat) file change by invalidating the cache and reloading.
+    /// Reload a curve asset: scripts see it on their next `curve.eval`, and
+    /// lights flickering with it pick it up now.
+    fn handle_curve_reload(&mut self, changed_path: &Path) -> Result<(), String> {
+        self.curves.borrow_mut().invalidate();
+        let name = changed_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("Not a curve file: {:?}", changed_path))?;
+        let curve = crate::curves::load_curve(&self.project_root, &name)?;
+        if let Some(scene_world) = &self.scene_world {
+            let mut sw = scene_world.borrow_mut();
+            for (_entity, flicker) in sw.world.query_mut::<&mut crate::components::LightFlicker>() {
+                if flicker.source == crate::curve::CurveDef::Named(name.clone()) {
+                    flicker.curve = crate::curves::looping(curve.clone());
+                }
+            }
+        }
+        tracing::info!("Curve reloaded: {}", name);
+        Ok(())
     }
 
-    /// Handle a splat (.ply) file change by invalidating the cache and reloading.
-    fn handle_splat_reload(&mut self, changed_path: &Path) {
+    fn handle_splat_reload(&mut self, changed_path: &Path) -> Result<(), String> {
         tracing::info!("Hot-reloading splat: {:?}", changed_path);
 
         // Try to extract relative path from project root
@@ -1358,84 +2002,795 @@ let mut scene_world = scene_world.borrow_mut();
         // Reloading will happen automatically on next frame when get_or_load is called
         // Force pipeline recompilation to pick up new splat data
         if self.compiled_pipeline.is_some() {
-            self.compiled_pipeline = None;
-            self.try_load_pipeline();
+            return self.recompile_pipeline();
         }
+        Ok(())
     }
 
     /// Handle a pipeline YAML file change.
-    fn handle_pipeline_reload(&mut self, _changed_path: &Path) {
+    fn handle_pipeline_reload(&mut self, _changed_path: &Path) -> Result<(), String> {
         tracing::info!("Hot-reloading render pipeline");
-        self.compiled_pipeline = None;
-        self.try_load_pipeline();
+        self.recompile_pipeline()
     }
 
-    /// Poll for file change events (non-blocking).
-    fn poll_changes(&mut self) {
-        let events: Vec<WatchEvent> = if let Some(rx) = &self.watch_rx {
-            let mut events = Vec::new();
-            while let Ok(event) = rx.try_recv() {
-                events.push(event);
-            }
-            events
-        } else {
-            return;
-        };
+    /// The scripts' `config` table: naive.yaml's `config:`, then the current
+    /// scene's `settings.config:`, then `--config` overrides.
+    fn script_config(&self) -> serde_yaml::Mapping {
+        let project = self.project_config.as_ref().map(|c| &c.config);
+        let scene = self.scene_world.as_ref().and_then(|sw| {
+            sw.borrow().current_scene.as_ref().map(|s| s.settings.config.clone())
+        });
+        crate::script_config::resolve(project, scene.as_ref(), &self.args.config).unwrap_or_else(|e| {
+            tracing::error!("{}; ignoring --config overrides", e);
+            crate::script_config::resolve(project, scene.as_ref(), &[]).unwrap_or_default()
+        })
+    }
 
-        let mut shader_paths = std::collections::HashSet::new();
-        let mut scene_paths = std::collections::HashSet::new();
-        let mut splat_paths = std::collections::HashSet::new();
-        let mut script_paths = std::collections::HashSet::new();
-        let mut pipeline_changed = false;
+    /// The project's input bindings file, relative to its root.
+    fn bindings_relative_path(&self) -> &str {
+        match &self.project_config {
+            Some(config) => config.input.bindings_path(),
+            None => crate::input::BINDINGS_PATH,
+        }
+    }
 
-        for event in events {
-            match event {
-                WatchEvent::ShaderChanged(path) => {
-                    shader_paths.insert(path);
-                }
-                WatchEvent::SceneChanged(path) => {
-                    scene_paths.insert(path);
-                }
-                WatchEvent::MaterialChanged(_path) => {
-                    tracing::info!("Material changed (reload not yet implemented)");
-                }
-                WatchEvent::PipelineChanged(path) => {
-                    tracing::info!("Pipeline file changed: {:?}", path);
-                    pipeline_changed = true;
-                }
-                WatchEvent::SplatChanged(path) => {
-                    splat_paths.insert(path);
-                }
-                WatchEvent::ScriptChanged(path) => {
-                    script_paths.insert(path);
-                }
-            }
+    fn bindings_path(&self) -> PathBuf {
+        self.project_root.join(self.bindings_relative_path())
+    }
+
+    /// Lua API level of the project's scripts: `scripting.api_level`, or the
+    /// first level when a project doesn't declare one.
+    fn script_api_level(&self) -> u32 {
+        match &self.project_config {
+            Some(config) => config.scripting.api_level.unwrap_or(1),
+            None => crate::scripting::LUA_API_LEVEL,
         }
+    }
 
-        for path in shader_paths {
-            self.handle_shader_reload(&path);
+    /// How UI text is drawn (`ui.text` in naive.yaml).
+    fn text_rendering(&self) -> crate::font::TextRendering {
+        self.project_config.as_ref().map(|c| c.ui.text).unwrap_or_default()
+    }
+
+    /// `autosave:` from naive.yaml, or the defaults without a project.
+    fn autosave_config(&self) -> crate::project_config::AutosaveConfig {
+        self.project_config.as_ref().map(|c| c.autosave.clone()).unwrap_or_default()
+    }
+
+    /// Count a socket edit of the scene towards the next autosave.
+    fn mark_scene_edited(&mut self) {
+        self.unsaved_edits += 1;
+        self.autosave_pending.get_or_insert_with(instant::Instant::now);
+    }
+
+    /// Snapshot the scene once its oldest unsaved edit is
+    /// `autosave.interval_secs` old.
+    fn update_autosave(&mut self) {
+        let interval = self.autosave_config().interval_secs;
+        if self.autosave_pending.is_some_and(|since| since.elapsed().as_secs() >= interval) {
+            self.write_autosave();
         }
+    }
 
-        for path in scene_paths {
-            self.handle_scene_reload(&path);
+    /// Write the scene's unsaved edits to `.naive/autosave/` now, if there
+    /// are any the last autosave doesn't cover.
+    fn write_autosave(&mut self) {
+        if self.autosave_pending.take().is_none() || !self.autosave_config().enabled {
+            return;
+        }
+        let Some(yaml) = self.serialize_scene_to_yaml() else {
+            return;
+        };
+        match crate::autosave::write(&self.project_root, self.scene_path.as_deref(), self.unsaved_edits, &yaml) {
+            Ok(path) => tracing::info!("Autosaved {} unsaved edit(s) to {:?}", self.unsaved_edits, path),
+            Err(e) => tracing::error!("Autosave failed: {}", e),
         }
+    }
 
-        for path in &splat_paths {
-            self.handle_splat_reload(path);
+    /// The autosave to load in place of `scene_path` (None for an unsaved
+    /// editor scene) under `--recover`. Without the flag a waiting autosave
+    /// is only reported.
+    fn recovered_autosave(&mut self, scene_path: Option<&Path>) -> Option<PathBuf> {
+        let autosave = crate::autosave::find(&self.project_root, scene_path)?;
+        let scene = match autosave.info.scene.as_str() {
+            "" => "Untitled scene",
+            scene => scene,
+        };
+        if self.args.recover {
+            tracing::info!("Recovering {} of {}", autosave.describe(), scene);
+            self.unsaved_edits = autosave.info.edits;
+            self.reload_notifications.push((
+                format!("Recovered {}", autosave.describe()),
+                instant::Instant::now(),
+                [0.3, 1.0, 0.3, 1.0],
+            ));
+            Some(autosave.path)
+        } else {
+            tracing::warn!(
+                "{} has {} in {}; run with --recover to load them",
+                scene,
+                autosave.describe(),
+                crate::autosave::AUTOSAVE_DIR,
+            );
+            self.reload_notifications.push((
+                format!("{} has {}: restart with --recover", scene, autosave.describe()),
+                instant::Instant::now(),
+                [1.0, 0.8, 0.2, 1.0],
+            ));
+            None
         }
+    }
 
-        if pipeline_changed {
-            if let Some(path) = self.pipeline_path.clone() {
-                self.handle_pipeline_reload(&path);
+    /// Rebuild the scripts' `config` table after naive.yaml or the scene changed.
+    fn refresh_script_config(&self) {
+        if let Some(script_runtime) = &self.script_runtime {
+            if let Err(e) = script_runtime.register_config_api(&self.script_config()) {
+                tracing::error!("Failed to update script config: {}", e);
             }
         }
+    }
 
-        // Hot-reload scripts
-        if !script_paths.is_empty() {
-            if let (Some(scene_world), Some(script_runtime)) =
-                (&self.scene_world, &mut self.script_runtime)
-            {
-                let scene_world_guard = scene_world.borrow();
+    /// Title for the game window: `window.title` from naive.yaml, or the engine name.
+    fn window_title(&self) -> String {
+        self.project_config
+            .as_ref()
+            .and_then(|c| c.window.title.clone())
+            .unwrap_or_else(|| "nAIVE Engine".to_string())
+    }
+
+    /// Why rendering is suspended right now (window state plus
+    /// `window.suspend_unfocused`), or None if it isn't.
+    fn suspend_reason(&self) -> Option<&'static str> {
+        let suspend_unfocused = self.project_config.as_ref().is_some_and(|c| c.window.suspend_unfocused);
+        self.window_activity.suspend_reason(suspend_unfocused)
+    }
+
+    /// `window.background` from naive.yaml.
+    fn background_mode(&self) -> crate::project_config::BackgroundMode {
+        self.project_config.as_ref().map(|c| c.window.background).unwrap_or_default()
+    }
+
+    /// Enter or leave the suspended state, emitting `window.suspended` on change.
+    fn update_suspended(&mut self) {
+        let reason = self.suspend_reason();
+        if reason.is_some() == self.render_suspended {
+            return;
+        }
+        self.render_suspended = reason.is_some();
+        match reason {
+            Some(reason) => tracing::info!("Rendering suspended ({}), background: {:?}", reason, self.background_mode()),
+            None => {
+                tracing::info!("Rendering resumed");
+                // Don't count the time spent in the background as one long frame
+                self.last_frame_time = None;
+            }
+        }
+        self.emit_engine_event(
+            crate::engine_events::WINDOW_SUSPENDED,
+            crate::engine_events::payload(serde_json::json!({
+                "suspended": self.render_suspended,
+                "reason": reason.unwrap_or(""),
+            })),
+        );
+    }
+
+    /// React to a failed swapchain acquisition.
+    fn recover_surface(&mut self, action: crate::window_state::SurfaceAction) {
+        let Some(gpu) = &mut self.gpu else { return };
+        match action {
+            crate::window_state::SurfaceAction::Skip => {}
+            crate::window_state::SurfaceAction::Reconfigure => gpu.configure_surface(),
+            crate::window_state::SurfaceAction::Recreate => {
+                if let Err(e) = crate::renderer::recreate_surface(gpu) {
+                    tracing::error!("{}", e);
+                }
+            }
+        }
+    }
+
+    /// Take a new device into use: per-pass timings, GPU splat sorting and
+    /// the device-lost callback.
+    fn install_gpu(&mut self, gpu_state: GpuState) {
+        self.gpu_profiler = crate::pipeline::GpuPassProfiler::new(&gpu_state.device, &gpu_state.queue);
+        if self.gpu_profiler.is_none() {
+            tracing::info!("GPU timestamp queries unsupported; per-pass GPU timings disabled");
+        }
+        self.splat_cache.enable_gpu_sort(&gpu_state.device);
+        self.device_loss.watch(&gpu_state.device);
+        self.gpu = Some(gpu_state);
+    }
+
+    /// The device was lost: snapshot the scene, drop every GPU object, and
+    /// reinitialize from `about_to_wait` (see `device_recovery`).
+    fn begin_device_recovery(&mut self, reason: String) {
+        let Some(window) = self.gpu.as_ref().and_then(|gpu| gpu.window.clone()) else { return };
+        tracing::error!("GPU device lost ({}); reinitializing", reason);
+
+        let snapshot = self.serialize_scene_to_yaml().and_then(|yaml| {
+            crate::device_recovery::write_snapshot(&self.project_root, &yaml)
+                .map_err(|e| tracing::error!("Scene snapshot failed, the scene restarts from its file: {}", e))
+                .ok()
+        });
+        let tool_windows = self.tool_windows.iter().map(|tool| tool.kind).collect();
+        self.release_gpu_state();
+        self.device_recovery = Some(crate::device_recovery::PendingRecovery {
+            window,
+            reason,
+            snapshot,
+            tool_windows,
+            retry: crate::device_recovery::RecoveryRetry::new(std::time::Instant::now()),
+        });
+    }
+
+    /// Drop the GPU state and everything made from it: caches, renderers,
+    /// pipelines, and the scene, physics and scripts that hold their handles.
+    /// The command socket, audio and settings stay.
+    fn release_gpu_state(&mut self) {
+        if let (Some(sw), Some(sr)) = (&self.scene_world, &self.script_runtime) {
+            let sw = sw.borrow();
+            let scripted: Vec<hecs::Entity> = sw.world.query::<&Script>().iter().map(|(e, _)| e).collect();
+            for entity in scripted {
+                sr.call_on_destroy(entity);
+            }
+        }
+        self.script_runtime = None;
+        self.scene_world = None;
+        self.physics_world = None;
+        self.input_state = None;
+        self.camera_state = None;
+        self.draw_pool = None;
+        self.forward_pipeline = None;
+        self.compiled_pipeline = None;
+        self.debug_draw = None;
+        self.texture_resources = None;
+        self.bitmap_font = None;
+        self.ui_renderer = None;
+        self.shared_surface_config = None;
+        self.tool_windows.clear();
+        self.portal_renderer = None;
+        self.portal_hook = None;
+        self.foliage_renderer = None;
+        self.foliage_hook = None;
+        self.terrain_renderer = None;
+        self.terrain_hook = None;
+        self.render_hooks = crate::pipeline::RenderHooks::new();
+        self.gpu_profiler = None;
+
+        let overlay = self.mods.borrow().overlay.clone();
+        self.mesh_cache = MeshCache::new();
+        self.material_cache = MaterialCache::new();
+        self.splat_cache = SplatCache::new();
+        self.texture_cache = crate::texture_cache::TextureCache::new();
+        self.mesh_cache.overlay = overlay.clone();
+        self.material_cache.overlay = overlay.clone();
+        self.texture_cache.overlay = overlay;
+        self.splat_cache.arenas = self.mesh_cache.arenas.clone();
+        self.environment_cache = crate::environment::EnvironmentCache::new();
+        self.color_grading_cache = crate::color_grading::ColorGradingCache::new();
+        self.animation_system = crate::anim_system::AnimationSystem::new();
+        self.bone_palettes.clear();
+
+        // Same as a scene change: nothing may point at the dropped entities
+        *self.entity_commands.borrow_mut() = crate::world::EntityCommandQueue::new();
+        *self.pool_manager.borrow_mut() = crate::world::EntityPoolManager::new();
+        *self.particle_system.borrow_mut() = crate::particles::ParticleSystem::new();
+        self.lua_event_listeners.borrow_mut().clear();
+        *self.next_lua_listener_id.borrow_mut() = 0;
+        self.lua_listener_id_map.borrow_mut().clear();
+        self.message_queue.borrow_mut().clear();
+        self.crowd.borrow_mut().groups.clear();
+        self.noise.borrow_mut().clear();
+        self.sequences.borrow_mut().reset();
+        self.camera_paths.borrow_mut().reset();
+
+        self.gpu = None;
+    }
+
+    /// Try to create the new device once the retry delay has passed, then
+    /// reload the scene from its snapshot and tell the HUD and scripts.
+    fn retry_device_recovery(&mut self) {
+        let now = std::time::Instant::now();
+        let Some(window) = self.device_recovery.as_ref().filter(|p| p.retry.due(now)).map(|p| Arc::clone(&p.window)) else {
+            return;
+        };
+        let initial_wgsl = self.get_initial_shader();
+        let gpu_state = match pollster::block_on(crate::renderer::init_gpu(window, &initial_wgsl, &self.args.graphics)) {
+            Ok(gpu_state) => gpu_state,
+            Err(e) => {
+                if let Some(pending) = &mut self.device_recovery {
+                    let delay = pending.retry.failed(now);
+                    tracing::warn!(
+                        "GPU reinitialization failed (attempt {}): {}; retrying in {:.1}s",
+                        pending.retry.attempts(),
+                        e,
+                        delay.as_secs_f32()
+                    );
+                }
+                return;
+            }
+        };
+        let Some(pending) = self.device_recovery.take() else { return };
+        self.install_gpu(gpu_state);
+
+        self.restore_snapshot = pending.snapshot;
+        if self.args.editor_mode {
+            self.init_editor_mode();
+        } else {
+            self.load_scene();
+        }
+        self.restore_snapshot = None;
+        self.pending_tool_windows.extend(pending.tool_windows);
+        self.last_frame_time = None;
+
+        let attempts = pending.retry.attempts() + 1;
+        tracing::info!("GPU device recovered after {} attempt(s)", attempts);
+        self.reload_notifications.push((
+            "GPU device lost: recovered, scene restored from snapshot".to_string(),
+            instant::Instant::now(),
+            [1.0, 0.8, 0.2, 1.0],
+        ));
+        self.emit_engine_event(
+            crate::engine_events::GPU_DEVICE_RECOVERED,
+            crate::engine_events::payload(serde_json::json!({
+                "reason": pending.reason,
+                "attempts": attempts,
+            })),
+        );
+    }
+
+    /// Create the portal renderer and its composite hook while the scene has
+    /// portals and the pipeline has a lighting pass; drop them otherwise.
+    fn sync_portals(&mut self) {
+        let target = match (&self.scene_world, &self.compiled_pipeline) {
+            (Some(sw), Some(compiled)) => {
+                let has_portals = sw.borrow().world.query::<&Portal>().iter().next().is_some();
+                has_portals
+                    .then(|| crate::portal::composite_pass(compiled))
+                    .flatten()
+                    .map(|(pass, format)| (pass.to_string(), format))
+            }
+            _ => None,
+        };
+        if let (Some(renderer), Some((pass, format))) = (&self.portal_renderer, &target) {
+            if renderer.borrow().matches(pass, *format) {
+                return;
+            }
+        }
+        if let Some(id) = self.portal_hook.take() {
+            self.render_hooks.remove(id);
+        }
+        self.portal_renderer = None;
+
+        let (Some((pass, format)), Some(gpu), Some(camera_state), Some(draw_pool)) =
+            (target, &self.gpu, &self.camera_state, &self.draw_pool)
+        else {
+            return;
+        };
+        let renderer = Rc::new(RefCell::new(crate::portal::PortalRenderer::new(
+            &gpu.device,
+            &pass,
+            format,
+            &camera_state.borrow().bind_group_layout,
+            draw_pool,
+            self.texture_resources.as_ref(),
+        )));
+        let hook_renderer = renderer.clone();
+        self.portal_hook = Some(self.render_hooks.add(
+            &pass,
+            crate::pipeline::HookStage::After,
+            Box::new(move |ctx| hook_renderer.borrow_mut().composite(ctx)),
+        ));
+        self.portal_renderer = Some(renderer);
+    }
+
+    /// Recompile the pipeline when render hooks come or go. Hooks can read any
+    /// resource, so pipeline resources only share textures while none are
+    /// registered.
+    fn sync_resource_aliasing(&mut self) {
+        let Some(compiled) = &self.compiled_pipeline else {
+            return;
+        };
+        let stale = if self.render_hooks.is_empty() {
+            self.aliasing_suppressed
+        } else {
+            compiled.resources.values().any(|r| r.alias_of.is_some())
+        };
+        if !stale {
+            return;
+        }
+        tracing::info!(
+            "Render hooks {}: recompiling pipeline {} shared textures",
+            if self.render_hooks.is_empty() { "removed" } else { "registered" },
+            if self.render_hooks.is_empty() { "with" } else { "without" },
+        );
+        let _ = self.recompile_pipeline();
+    }
+
+    /// Create the terrain renderer and its draw hook while the scene has
+    /// terrain and the pipeline has a geometry pass, drop them otherwise, and
+    /// build the chunks of new or changed terrains.
+    fn sync_terrain(&mut self) {
+        let target = match (&self.scene_world, &self.compiled_pipeline) {
+            (Some(sw), Some(compiled)) => {
+                let has_terrain = sw.borrow().world.query::<&Terrain>().iter().next().is_some();
+                has_terrain
+                    .then(|| crate::foliage::geometry_pass(compiled))
+                    .flatten()
+                    .map(|(pass, formats, depth)| (pass.to_string(), formats, depth))
+            }
+            _ => None,
+        };
+        let current = match (&self.terrain_renderer, &target) {
+            (Some(renderer), Some((pass, formats, depth))) => renderer.borrow().matches(pass, formats, *depth),
+            _ => false,
+        };
+        if !current {
+            if let Some(id) = self.terrain_hook.take() {
+                self.render_hooks.remove(id);
+            }
+            self.terrain_renderer = None;
+
+            let (Some((pass, formats, depth)), Some(gpu), Some(camera_state)) =
+                (target, &self.gpu, &self.camera_state)
+            else {
+                return;
+            };
+            let renderer = Rc::new(RefCell::new(crate::terrain::TerrainRenderer::new(
+                &gpu.device,
+                &gpu.queue,
+                &pass,
+                &formats,
+                depth,
+                &camera_state.borrow().bind_group_layout,
+            )));
+            let hook_renderer = renderer.clone();
+            self.terrain_hook = Some(self.render_hooks.add(
+                &pass,
+                crate::pipeline::HookStage::After,
+                Box::new(move |ctx| hook_renderer.borrow_mut().draw(ctx)),
+            ));
+            self.terrain_renderer = Some(renderer);
+        }
+
+        let (Some(renderer), Some(sw), Some(gpu)) = (&self.terrain_renderer, &self.scene_world, &self.gpu) else {
+            return;
+        };
+        let mut renderer = renderer.borrow_mut();
+        renderer.set_culling(!self.render_debug.lod_lock.disable_culling);
+        renderer.set_force_lod0(self.render_debug.lod_lock.force_lod0);
+        renderer.sync(&gpu.device, &gpu.queue, &self.project_root, &sw.borrow().world);
+    }
+
+    /// Create the foliage renderer and its draw hook while the scene has
+    /// foliage and the pipeline has a geometry pass, drop them otherwise, and
+    /// scatter new or changed foliage onto the physics colliders.
+    fn sync_foliage(&mut self) {
+        let target = match (&self.scene_world, &self.compiled_pipeline) {
+            (Some(sw), Some(compiled)) => {
+                let has_foliage = sw.borrow().world.query::<&Foliage>().iter().next().is_some();
+                has_foliage
+                    .then(|| crate::foliage::geometry_pass(compiled))
+                    .flatten()
+                    .map(|(pass, formats, depth)| (pass.to_string(), formats, depth))
+            }
+            _ => None,
+        };
+        let current = match (&self.foliage_renderer, &target) {
+            (Some(renderer), Some((pass, formats, depth))) => renderer.borrow().matches(pass, formats, *depth),
+            _ => false,
+        };
+        if !current {
+            if let Some(id) = self.foliage_hook.take() {
+                self.render_hooks.remove(id);
+            }
+            self.foliage_renderer = None;
+
+            let (Some((pass, formats, depth)), Some(gpu), Some(camera_state)) =
+                (target, &self.gpu, &self.camera_state)
+            else {
+                return;
+            };
+            let renderer = Rc::new(RefCell::new(crate::foliage::FoliageRenderer::new(
+                &gpu.device,
+                &pass,
+                &formats,
+                depth,
+                &camera_state.borrow().bind_group_layout,
+            )));
+            let hook_renderer = renderer.clone();
+            self.foliage_hook = Some(self.render_hooks.add(
+                &pass,
+                crate::pipeline::HookStage::After,
+                Box::new(move |ctx| hook_renderer.borrow_mut().draw(ctx)),
+            ));
+            self.foliage_renderer = Some(renderer);
+        }
+
+        let (Some(renderer), Some(sw), Some(gpu)) = (&self.foliage_renderer, &self.scene_world, &self.gpu) else {
+            return;
+        };
+        let mut renderer = renderer.borrow_mut();
+        renderer.set_density_scale(self.render_debug.foliage_fraction());
+        renderer.set_culling(!self.render_debug.lod_lock.disable_culling);
+        let sw = sw.borrow();
+        if !renderer.has_pending(&sw.world) {
+            return;
+        }
+        // Colliders spawned since the last physics step aren't queryable yet
+        if let Some(physics) = &self.physics_world {
+            let pw = &mut *physics.borrow_mut();
+            pw.query_pipeline.update(&pw.collider_set);
+        }
+        let physics = self.physics_world.as_ref().map(|p| p.borrow());
+        renderer.sync(&gpu.device, &self.project_root, &sw.world, |surface, origin| {
+            let Some(pw) = &physics else {
+                return Some(origin);
+            };
+            let filter = crate::physics::EntityQueryFilter {
+                include_only: surface.map(|id| sw.entity_registry.get(id).copied().into_iter().collect()),
+                exclude_triggers: true,
+                ..Default::default()
+            };
+            pw.raycast_detailed(origin, glam::Vec3::NEG_Y, crate::foliage::GROUND_PROBE_DISTANCE, &filter)
+                .map(|(_, _, point, _)| point)
+        });
+    }
+
+    /// Handle an input bindings file change: swap the new bindings into the
+    /// input state. A file that fails to parse keeps the current bindings.
+    fn handle_bindings_reload(&mut self, changed_path: &Path) -> Result<(), String> {
+        let bindings = crate::input::read_bindings(changed_path)?;
+        if let Some(input) = &self.input_state {
+            input.borrow_mut().set_bindings(bindings);
+        }
+        tracing::info!("Hot-reloaded input bindings");
+        self.reload_notifications.push((
+            "Input bindings reloaded".to_string(),
+            instant::Instant::now(),
+            [0.3, 1.0, 0.3, 1.0],
+        ));
+        Ok(())
+    }
+
+    /// (Re)create the quality governor from naive.yaml and apply its starting
+    /// level, or full quality when `auto_quality` is off. `--force-lod0`
+    /// keeps full quality.
+    fn configure_quality_governor(&mut self) {
+        let config = self.project_config.as_ref().map(|c| c.auto_quality.clone()).unwrap_or_default();
+        self.quality_governor = if self.render_debug.lod_lock.force_lod0 {
+            None
+        } else {
+            crate::auto_quality::QualityGovernor::from_config(&config)
+        };
+        let level = self.quality_governor.as_ref().map(|g| g.current());
+        self.apply_quality_level(level);
+    }
+
+    /// Feed the frame time to the quality governor and apply the level it
+    /// steps to.
+    fn update_quality_governor(&mut self) {
+        let Some(change) = self.quality_governor.as_mut().and_then(|g| g.update(self.delta_time)) else {
+            return;
+        };
+        tracing::info!(
+            "auto_quality: level {} -> {} at {:.1} FPS",
+            change.previous,
+            change.level.level,
+            change.fps
+        );
+        self.apply_quality_level(Some(change.level));
+        self.emit_engine_event(crate::engine_events::QUALITY_CHANGED, change.payload());
+    }
+
+    /// Set a quality level's render scale, shadow map size and particle
+    /// budget (full quality for None), reallocating the targets it resizes.
+    fn apply_quality_level(&mut self, level: Option<crate::auto_quality::QualityLevel>) {
+        let before = (self.render_debug.render_scale, self.render_debug.shadow_resolution);
+        match level {
+            Some(level) => level.apply(&mut self.render_debug),
+            None => crate::auto_quality::clear(&mut self.render_debug),
+        }
+        let scale_changed = self.render_debug.render_scale != before.0;
+        let shadow_changed = self.render_debug.shadow_resolution != before.1;
+        if !scale_changed && !shadow_changed {
+            return;
+        }
+        let (Some(gpu), Some(compiled)) = (&self.gpu, &mut self.compiled_pipeline) else {
+            return;
+        };
+        if scale_changed {
+            let (width, height) = self.render_debug.render_size(gpu.config.width, gpu.config.height);
+            crate::pipeline::resize_resources(&gpu.device, &mut compiled.resources, width, height);
+        }
+        if shadow_changed {
+            let size = self.render_debug.shadow_resolution;
+            crate::pipeline::resize_fixed_resource(&gpu.device, &mut compiled.resources, "shadow_map", size);
+        }
+        crate::pipeline::rebuild_bind_groups(&gpu.device, compiled);
+    }
+
+    /// Handle a naive.yaml change: apply the settings that can change live
+    /// (window title, quality preset, watcher settings, script config, bindings
+    /// file) and log the ones that need a restart.
+    fn handle_project_config_reload(&mut self, changed_path: &Path) -> Result<(), String> {
+        let config = crate::project_config::load_config(changed_path).map_err(|e| e.to_string())?;
+        let changes = match &self.project_config {
+            Some(old) => crate::project_config::diff_config(old, &config),
+            None => crate::project_config::ConfigChanges {
+                live: vec!["window.title", "quality", "auto_quality", "render_settings", "watch", "audio", "config", "input", "autosave", "split_screen"],
+                restart: Vec::new(),
+            },
+        };
+
+        let quality = config.quality.unwrap_or_default();
+        let render_settings = config.render_settings.clone();
+        let audio = config.audio.clone();
+        self.watch_config = config.watch.clone();
+        if !self.watch_config.enabled {
+            self.pending_watch_events.clear();
+        }
+        self.project_config = Some(config);
+        for setting in &changes.live {
+            match *setting {
+                "window.title" if !self.args.editor_mode => {
+                    if let Some(window) = self.gpu.as_ref().and_then(|gpu| gpu.window.as_ref()) {
+                        window.set_title(&self.window_title());
+                    }
+                }
+                "quality" => quality.apply(&mut self.render_debug),
+                "auto_quality" => self.configure_quality_governor(),
+                "render_settings" => self.render_debug.override_settings(&render_settings, &self.args.settings),
+                "audio" => self.audio_system.borrow_mut().configure(audio.clone()),
+                "config" => self.refresh_script_config(),
+                "input" => {
+                    let bindings = crate::input::load_bindings(&self.bindings_path());
+                    if let Some(input) = &self.input_state {
+                        input.borrow_mut().set_bindings(bindings);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !changes.live.is_empty() {
+            tracing::info!("naive.yaml reloaded: applied {}", changes.live.join(", "));
+            self.reload_notifications.push((
+                format!("Config reloaded: {}", changes.live.join(", ")),
+                instant::Instant::now(),
+                [0.3, 1.0, 0.3, 1.0],
+            ));
+        }
+        if !changes.restart.is_empty() {
+            tracing::warn!("naive.yaml: {} changed; restart to apply", changes.restart.join(", "));
+            self.reload_notifications.push((
+                format!("Restart to apply: {}", changes.restart.join(", ")),
+                instant::Instant::now(),
+                [1.0, 0.8, 0.2, 1.0],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Poll for file change events (non-blocking).
+    fn poll_changes(&mut self) {
+        let received: Vec<WatchEvent> = if let Some(rx) = &self.watch_rx {
+            let mut events = Vec::new();
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+            events
+        } else {
+            return;
+        };
+
+        // Each reload reports its result as an `asset.reloaded` event
+        let mut reloaded: Vec<(String, &str, Result<(), String>)> = Vec::new();
+
+        // Config and bindings apply immediately, even with asset watching off,
+        // so `watch.enabled` can be switched back on without a restart
+        let mut config_path = None;
+        let mut bindings_path = None;
+        for event in received {
+            match event {
+                WatchEvent::ProjectConfigChanged(path) => config_path = Some(path),
+                // Other YAML files in input/ are not the bindings in use
+                WatchEvent::BindingsChanged(path) if path.ends_with(self.bindings_relative_path()) => {
+                    bindings_path = Some(path);
+                }
+                WatchEvent::BindingsChanged(_) => {}
+                event if self.watch_config.enabled => {
+                    self.pending_watch_events.push(event);
+                    self.last_watch_event = Some(instant::Instant::now());
+                }
+                _ => {}
+            }
+        }
+        if let Some(path) = bindings_path {
+            let result = self.handle_bindings_reload(&path);
+            reloaded.push((self.project_relative(&path), "bindings", result));
+        }
+        if let Some(path) = config_path {
+            let result = self.handle_project_config_reload(&path);
+            reloaded.push((self.project_relative(&path), "config", result));
+        }
+
+        let debounce = std::time::Duration::from_millis(self.watch_config.debounce_ms);
+        let settled = self.last_watch_event.is_some_and(|t| t.elapsed() >= debounce);
+        let events = if settled && self.watch_config.enabled {
+            self.last_watch_event = None;
+            std::mem::take(&mut self.pending_watch_events)
+        } else {
+            Vec::new()
+        };
+
+        let mut shader_paths = std::collections::HashSet::new();
+        let mut scene_paths = std::collections::HashSet::new();
+        let mut splat_paths = std::collections::HashSet::new();
+        let mut script_paths = std::collections::HashSet::new();
+        let mut curve_paths = std::collections::HashSet::new();
+        let mut pipeline_changed = false;
+
+        for event in events {
+            match event {
+                WatchEvent::ShaderChanged(path) => {
+                    shader_paths.insert(path);
+                }
+                WatchEvent::SceneChanged(path) => {
+                    scene_paths.insert(path);
+                }
+                WatchEvent::MaterialChanged(_path) => {
+                    tracing::info!("Material changed (reload not yet implemented)");
+                }
+                WatchEvent::PipelineChanged(path) => {
+                    tracing::info!("Pipeline file changed: {:?}", path);
+                    pipeline_changed = true;
+                }
+                WatchEvent::SplatChanged(path) => {
+                    splat_paths.insert(path);
+                }
+                WatchEvent::ScriptChanged(path) => {
+                    script_paths.insert(path);
+                }
+                WatchEvent::CurveChanged(path) => {
+                    curve_paths.insert(path);
+                }
+                WatchEvent::ProjectConfigChanged(_) | WatchEvent::BindingsChanged(_) => {}
+            }
+        }
+
+        for path in shader_paths {
+            let result = self.handle_shader_reload(&path);
+            reloaded.push((self.project_relative(&path), "shader", result));
+        }
+
+        for path in scene_paths {
+            let result = self.handle_scene_reload(&path);
+            reloaded.push((self.project_relative(&path), "scene", result));
+        }
+
+        for path in &splat_paths {
+            let result = self.handle_splat_reload(path);
+            reloaded.push((self.project_relative(path), "splat", result));
+        }
+
+        for path in curve_paths {
+            let result = self.handle_curve_reload(&path);
+            reloaded.push((self.project_relative(&path), "curve", result));
+        }
+
+        if pipeline_changed {
+            if let Some(path) = self.pipeline_path.clone() {
+                let result = self.handle_pipeline_reload(&path);
+                reloaded.push((self.project_relative(&path), "pipeline", result));
+            }
+        }
+
+        // Hot-reload scripts
+        if !script_paths.is_empty() {
+            if let (Some(scene_world), Some(script_runtime)) =
+                (&self.scene_world, &mut self.script_runtime)
+            {
+                let scene_world_guard = scene_world.borrow();
                 let reload_candidates: Vec<(hecs::Entity, std::path::PathBuf)> = scene_world_guard.world
                     .query::<&Script>().iter()
                     .filter_map(|(entity, script)| {
@@ -1452,14 +2807,28 @@ let mut scene_world = scene_world.borrow_mut();
                     })
                     .collect();
                 for (entity, source) in reload_candidates {
+                    let source_str = source.to_string_lossy().to_string();
                     match script_runtime.hot_reload_script(entity, &self.project_root, &source) {
-                        Ok(true) => tracing::info!("Script hot-reloaded: {:?}", source),
+                        Ok(true) => {
+                            tracing::info!("Script hot-reloaded: {:?}", source);
+                            reloaded.push((source_str, "script", Ok(())));
+                        }
                         Ok(false) => {}
-                        Err(e) => tracing::error!("Script hot-reload failed: {}", e),
+                        Err(e) => {
+                            tracing::error!("Script hot-reload failed: {}", e);
+                            reloaded.push((source_str, "script", Err(e)));
+                        }
                     }
                 }
             }
         }
+
+        for (path, kind, result) in &reloaded {
+            self.emit_engine_event(
+                crate::engine_events::ASSET_RELOADED,
+                crate::engine_events::asset_reloaded(path, kind, result),
+            );
+        }
     }
 
     /// Update the FPS camera controller: mouse look + WASD movement + physics.
@@ -1480,10 +2849,10 @@ let scene_world = scene_world.borrow_mut();
 };
 let mut physics_world = physics_world.borrow_mut();
 
-        let dt = self.delta_time;
+        let dt = self.game_clock.borrow().dt;
 
         // Collect player entity data
-        let mut player_updates: Vec<(hecs::Entity, glam::Vec3, f32, f32, rapier3d::prelude::RigidBodyHandle, rapier3d::prelude::ColliderHandle, f32, f32, f32, bool, glam::Vec3)> = Vec::new();
+        let mut player_updates: Vec<(hecs::Entity, glam::Vec3, f32, f32, rapier3d::prelude::RigidBodyHandle, rapier3d::prelude::ColliderHandle, f32, f32, f32, bool, glam::Vec3, crate::physics::CharacterPush, f32)> = Vec::new();
 
         for (entity, (player, cc, rb, col)) in scene_world
             .world
@@ -1513,6 +2882,8 @@ let mut physics_world = physics_world.borrow_mut();
             } else {
                 cc.move_speed
             };
+            // Sprinting footsteps carry further
+            let footstep_loudness = crate::noise::FOOTSTEP_LOUDNESS * speed / cc.move_speed.max(1e-3);
 
             // Calculate movement direction relative to yaw
             let forward = glam::Vec3::new(-new_yaw.sin(), 0.0, -new_yaw.cos());
@@ -1543,12 +2914,24 @@ let mut physics_world = physics_world.borrow_mut();
                 player.radius,
                 cc.grounded,
                 cc.velocity,
+                cc.push(),
+                footstep_loudness,
             ));
         }
 
         // Apply updates
-        for (entity, desired, new_yaw, new_pitch, rb_handle, col_handle, vel_y, _height, _radius, _was_grounded, _old_vel) in player_updates {
-            let (_effective, grounded) = physics_world.move_character(rb_handle, col_handle, desired, dt);
+        for (entity, desired, new_yaw, new_pitch, rb_handle, col_handle, vel_y, _height, _radius, _was_grounded, _old_vel, push, footstep_loudness) in player_updates {
+            let (effective, grounded) = physics_world.move_character(rb_handle, col_handle, desired, dt, push);
+
+            // A footstep noise every stride walked on the ground
+            if grounded && self.noise.borrow_mut().step(entity, effective.x.hypot(effective.z)) {
+                if let Ok(transform) = scene_world.world.get::<&Transform>(entity) {
+                    let source = scene_world.entity_registry.iter().find(|(_, &e)| e == entity).map(|(id, _)| id.clone());
+                    self.noise.borrow_mut().emit(
+                        crate::noise::NoiseEvent::new(transform.position, footstep_loudness, "footstep").with_source(source),
+                    );
+                }
+            }
 
             // Update ECS components
             if let Ok(mut player) = scene_world.world.get::<&mut Player>(entity) {
@@ -1662,12 +3045,12 @@ let mut physics_world = physics_world.borrow_mut();
             None => return,
         };
         let scene_world = scene_world_rc.borrow_mut();
-        let dt = self.delta_time;
+        let dt = self.game_clock.borrow().dt;
 
         let mut expired = Vec::new();
 
         for (entity, projectile) in scene_world.world.query::<&mut Projectile>().iter() {
-            projectile.age += dt;
+            projectile.age += crate::time_scale::entity_dt(&scene_world.world, entity, dt);
             if projectile.age >= projectile.lifetime {
                 expired.push(entity);
             }
@@ -1687,6 +3070,214 @@ let mut physics_world = physics_world.borrow_mut();
         }
     }
 
+    /// Despawn entities whose `lifetime` rules fired this frame.
+    fn update_lifetimes(&mut self) {
+        let scene_world_rc = match &self.scene_world {
+            Some(sw) => sw,
+            None => return,
+        };
+        let scene_world = scene_world_rc.borrow();
+        let dt = self.game_clock.borrow().dt;
+
+        let expired = crate::lifetime::update_lifetimes(&scene_world.world, dt);
+        if expired.is_empty() {
+            return;
+        }
+
+        let mut commands = self.entity_commands.borrow_mut();
+        for entity in expired {
+            if let Ok(id) = scene_world.world.get::<&crate::components::EntityId>(entity) {
+                commands.destroys.push(id.0.clone());
+            }
+        }
+    }
+
+    /// Emit an impact noise for each collision that started this step, louder
+    /// the faster the bodies met. The faster body is the noise's source.
+    fn emit_impact_noises(&mut self) {
+        let (Some(scene_world), Some(physics_world)) = (&self.scene_world, &self.physics_world) else {
+            return;
+        };
+        let sw = scene_world.borrow();
+        let pw = physics_world.borrow();
+        let velocity_of = |entity: hecs::Entity| {
+            sw.world
+                .get::<&crate::physics::RigidBody>(entity)
+                .ok()
+                .and_then(|rb| pw.get_linvel(rb.handle))
+                .unwrap_or(glam::Vec3::ZERO)
+        };
+        let position_of = |entity: hecs::Entity| sw.world.get::<&Transform>(entity).ok().map(|t| t.position);
+
+        let mut noise = self.noise.borrow_mut();
+        for event in pw.collision_events.iter().filter(|e| e.started) {
+            let (va, vb) = (velocity_of(event.entity_a), velocity_of(event.entity_b));
+            let Some(loudness) = crate::noise::impact_loudness((va - vb).length()) else {
+                continue;
+            };
+            let position = match (position_of(event.entity_a), position_of(event.entity_b)) {
+                (Some(a), Some(b)) => (a + b) * 0.5,
+                (Some(p), None) | (None, Some(p)) => p,
+                (None, None) => continue,
+            };
+            let loudest = if va.length_squared() >= vb.length_squared() { event.entity_a } else { event.entity_b };
+            let source = sw.entity_registry.iter().find(|(_, &e)| e == loudest).map(|(id, _)| id.clone());
+            noise.emit(crate::noise::NoiseEvent::new(position, loudness, "impact").with_source(source));
+        }
+    }
+
+    /// Publish noises made since the last frame as `sound.noise` events and
+    /// forget the ones older than `noise::NOISE_MEMORY`.
+    fn publish_noises(&mut self, dt: f32) {
+        let events = {
+            let mut noise = self.noise.borrow_mut();
+            noise.update(dt);
+            noise.take_unpublished()
+        };
+        for event in events {
+            self.emit_engine_event(crate::engine_events::SOUND_NOISE, event.payload());
+        }
+    }
+
+    /// Advance the cutscene sequencer and apply the cues it passed: animation
+    /// states, sounds and events. Its camera is applied in `update_camera`.
+    fn update_sequence(&mut self, dt: f32) {
+        let actions = self.sequences.borrow_mut().update(dt);
+        for action in actions {
+            match action {
+                crate::sequence::SequenceAction::Animate { entity, state } => {
+                    let Some(scene_world) = &self.scene_world else { continue };
+                    let sw = scene_world.borrow();
+                    let animator = sw.entity_registry.get(&entity)
+                        .and_then(|&e| sw.world.get::<&mut crate::components::Animator>(e).ok());
+                    match animator {
+                        Some(mut animator) => {
+                            animator.controller.play(naive_core::animation::AnimState::from_str(&state));
+                        }
+                        None => tracing::warn!("Sequence cue for '{}': no animated entity with that id", entity),
+                    }
+                }
+                crate::sequence::SequenceAction::Sound { id, path, volume } => {
+                    let root = self.sequences.borrow().overlay.root_for(&self.project_root, &path);
+                    if let Err(e) = self.audio_system.borrow_mut().play_sfx(&id, &root, &path, volume) {
+                        tracing::error!("Sequence sound: {}", e);
+                    }
+                }
+                crate::sequence::SequenceAction::Music { path, volume, fade } => {
+                    let root = self.sequences.borrow().overlay.root_for(&self.project_root, &path);
+                    if let Err(e) = self.audio_system.borrow_mut().play_music(&root, &path, volume, fade) {
+                        tracing::error!("Sequence music: {}", e);
+                    }
+                }
+                crate::sequence::SequenceAction::StopSound { id } => {
+                    self.audio_system.borrow_mut().stop_sound(&id, 0.1);
+                }
+                crate::sequence::SequenceAction::Event { name, data } => {
+                    self.emit_engine_event(&name, data);
+                }
+            }
+        }
+    }
+
+    /// Advance the playing camera path and announce the ones that ended. Its
+    /// camera is applied in `update_camera`.
+    fn update_camera_path(&mut self, dt: f32) {
+        let finished = self.camera_paths.borrow_mut().update(dt);
+        for data in finished {
+            self.emit_engine_event(crate::engine_events::CAMERA_PATH_FINISHED, data);
+        }
+    }
+
+    /// Steer flock agents: dynamic bodies get a new velocity, everything
+    /// else moves by its transform.
+    fn update_crowds(&mut self) {
+        let Some(scene_world_rc) = &self.scene_world else {
+            return;
+        };
+        let dt = self.game_clock.borrow().dt;
+        let scene_world = scene_world_rc.borrow();
+        let crowd = self.crowd.borrow();
+
+        // Group params and goals, indexed by group
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        let mut params = Vec::new();
+        let mut agents = Vec::new();
+        let mut entities = Vec::new();
+        for (entity, (agent, transform)) in scene_world
+            .world
+            .query::<(&crate::crowd::FlockAgent, &Transform)>()
+            .iter()
+        {
+            let group = *group_index.entry(agent.group.clone()).or_insert_with(|| {
+                params.push(crowd.group(&agent.group));
+                params.len() - 1
+            });
+            let dt = crate::time_scale::entity_dt(&scene_world.world, entity, dt);
+            agents.push(crate::crowd::AgentState {
+                position: transform.position,
+                velocity: agent.velocity,
+                group,
+                max_speed: agent.max_speed.unwrap_or(params[group].max_speed),
+                planar: agent.planar,
+                dt,
+            });
+            entities.push(entity);
+        }
+        if agents.is_empty() {
+            return;
+        }
+        let goals: Vec<Option<glam::Vec3>> = params
+            .iter()
+            .map(|p| match &p.goal {
+                Some(crate::crowd::FlockGoal::Point(point)) => Some(*point),
+                Some(crate::crowd::FlockGoal::Entity(id)) => scene_world
+                    .entity_registry
+                    .get(id)
+                    .and_then(|&e| scene_world.world.get::<&Transform>(e).ok().map(|t| t.position)),
+                None => None,
+            })
+            .collect();
+
+        let velocities = crate::crowd::steer(&agents, &params, &goals);
+
+        let mut physics = self.physics_world.as_ref().map(|pw| pw.borrow_mut());
+        for ((entity, state), velocity) in entities.into_iter().zip(&agents).zip(velocities) {
+            let body = scene_world
+                .world
+                .get::<&crate::physics::RigidBody>(entity)
+                .ok()
+                .filter(|rb| rb.body_type == crate::physics::PhysicsBodyType::Dynamic)
+                .map(|rb| rb.handle);
+            let face_velocity = match scene_world.world.get::<&mut crate::crowd::FlockAgent>(entity) {
+                Ok(mut agent) => {
+                    agent.velocity = velocity;
+                    agent.face_velocity
+                }
+                Err(_) => continue,
+            };
+            if let Ok(mut transform) = scene_world.world.get::<&mut Transform>(entity) {
+                match (body, physics.as_mut()) {
+                    (Some(handle), Some(pw)) => {
+                        // Keep the body's own vertical speed so gravity still applies
+                        let mut linvel = velocity;
+                        if state.planar {
+                            linvel.y = pw.get_linvel(handle).map_or(0.0, |v| v.y);
+                        }
+                        pw.set_linvel(handle, linvel, false);
+                    }
+                    _ => {
+                        transform.position += velocity * state.dt;
+                        transform.dirty = true;
+                    }
+                }
+                if face_velocity && velocity.x.hypot(velocity.z) > 1e-3 {
+                    transform.rotation = glam::Quat::from_rotation_y(velocity.x.atan2(velocity.z));
+                    transform.dirty = true;
+                }
+            }
+        }
+    }
+
     /// Register skeletons from newly loaded meshes and attach Animator components
     /// to entities whose meshes have skin data.
     fn register_skeletons(&mut self) {
@@ -1741,7 +3332,7 @@ let mut scene_world = scene_world.borrow_mut();
             None => return,
         };
         let scene_world = scene_world_rc.borrow_mut();
-        let dt = self.delta_time;
+        let dt = self.game_clock.borrow().dt;
 
         // Collect entities with animators
         let animated: Vec<hecs::Entity> = scene_world
@@ -1755,7 +3346,8 @@ let mut scene_world = scene_world.borrow_mut();
 
         for entity in animated {
             if let Ok(mut animator) = scene_world.world.get::<&mut crate::components::Animator>(entity) {
-                let palette = self.animation_system.tick_entity(&mut animator, dt);
+                let entity_dt = crate::time_scale::entity_dt(&scene_world.world, entity, dt);
+                let palette = self.animation_system.tick_entity(&mut animator, entity_dt);
                 self.bone_palettes.insert(entity, palette);
             }
         }
@@ -1794,6 +3386,10 @@ let mut scene_world = scene_world.borrow_mut();
             None => return,
         };
 
+        // Entity ids reported as lifecycle events once all commands are applied
+        let mut destroyed: Vec<String> = Vec::new();
+        let mut spawned: Vec<String> = Vec::new();
+
         // Tier 2: Process destroys FIRST (fixes destroy+spawn same-frame bug)
         let destroys: Vec<_> = self.entity_commands.borrow_mut().destroys.drain(..).collect();
         for id in destroys {
@@ -1814,7 +3410,9 @@ let mut scene_world = scene_world.borrow_mut();
                         }
                     }
                 }
-                crate::world::destroy_runtime_entity(&mut *scene_world, &id);
+                if crate::world::destroy_runtime_entity(&mut *scene_world, &id) {
+                    destroyed.push(id);
+                }
             }
         }
 
@@ -1840,6 +3438,7 @@ let mut scene_world = scene_world.borrow_mut();
 
                 // Attach collider + rigid body if specified
                 if ok {
+                    spawned.push(cmd.id.clone());
                     if let Some(&entity) = scene_world.entity_registry.get(&cmd.id) {
                         let position = glam::Vec3::from(cmd.position);
                         let rotation = glam::Quat::IDENTITY;
@@ -1916,7 +3515,7 @@ let mut scene_world = scene_world.borrow_mut();
             if let (Some(scene_world), Some(physics_world)) = (&self.scene_world, &self.physics_world) {
                 let mut scene_world = scene_world.borrow_mut();
                 let mut physics_world = physics_world.borrow_mut();
-                crate::world::spawn_projectile_entity(
+                if crate::world::spawn_projectile_entity(
                     &mut *scene_world,
                     cmd,
                     &gpu.device,
@@ -1926,7 +3525,9 @@ let mut scene_world = scene_world.borrow_mut();
                     &mut self.material_cache,
                     &mut *physics_world,
                     self.texture_resources.as_ref(),
-                );
+                ) {
+                    spawned.push(cmd.id.clone());
+                }
             }
         }
 
@@ -1936,7 +3537,7 @@ let mut scene_world = scene_world.borrow_mut();
             if let (Some(scene_world), Some(physics_world)) = (&self.scene_world, &self.physics_world) {
                 let mut scene_world = scene_world.borrow_mut();
                 let mut physics_world = physics_world.borrow_mut();
-                crate::world::spawn_dynamic_entity(
+                if crate::world::spawn_dynamic_entity(
                     &mut *scene_world,
                     cmd,
                     &gpu.device,
@@ -1946,7 +3547,9 @@ let mut scene_world = scene_world.borrow_mut();
                     &mut self.material_cache,
                     &mut *physics_world,
                     self.texture_resources.as_ref(),
-                );
+                ) {
+                    spawned.push(cmd.id.clone());
+                }
             }
         }
 
@@ -2005,9 +3608,17 @@ let mut scene_world = scene_world.borrow_mut();
         let tex_swaps: Vec<_> = self.entity_commands.borrow_mut().texture_swaps.drain(..).collect();
         if !tex_swaps.is_empty() {
             if let Some(tex_res) = &self.texture_resources {
-                let layout = &tex_res.bind_group_layout;
                 for swap in tex_swaps {
-                    match self.texture_cache.get_or_load(&gpu.device, &gpu.queue, layout, &self.project_root, &swap.texture_path) {
+                    // Color maps are sampled as sRGB, data maps as linear
+                    let srgb = match swap.slot.as_str() {
+                        "albedo" | "emissive" => true,
+                        "normal" | "metallic_roughness" => false,
+                        _ => {
+                            tracing::warn!("entity.set_texture: unknown slot '{}'", swap.slot);
+                            continue;
+                        }
+                    };
+                    match self.texture_cache.get_or_load(&gpu.device, &gpu.queue, &self.project_root, &swap.texture_path, srgb) {
                         Ok(tex_handle) => {
                             if let Some(scene_world) = &self.scene_world {
                                 let sw = scene_world.borrow();
@@ -2017,8 +3628,10 @@ let mut scene_world = scene_world.borrow_mut();
                                         match swap.slot.as_str() {
                                             "albedo" => mat.albedo_texture = Some(tex_handle),
                                             "normal" => mat.normal_texture = Some(tex_handle),
-                                            _ => tracing::warn!("entity.set_texture: unknown slot '{}'", swap.slot),
+                                            "metallic_roughness" => mat.metallic_roughness_texture = Some(tex_handle),
+                                            _ => mat.emissive_texture = Some(tex_handle),
                                         }
+                                        mat.rebuild_bind_group(&gpu.device, &self.texture_cache, tex_res);
                                     }
                                 }
                             }
@@ -2028,25 +3641,86 @@ let mut scene_world = scene_world.borrow_mut();
                 }
             }
         }
-
-        // Process runtime mesh creates
-        let mesh_creates: Vec<_> = self.entity_commands.borrow_mut().mesh_creates.drain(..).collect();
-        for cmd in mesh_creates {
-            self.mesh_cache.insert_runtime_mesh(
-                &gpu.device,
-                &cmd.name,
-                &cmd.positions,
-                &cmd.normals,
-                &cmd.uvs,
-                &cmd.indices,
-            );
-        }
-
-        // Process spawn commands that have colliders/scripts
-        // (already handled by spawn_runtime_entity above, but we need to attach
-        // colliders and scripts post-spawn for the commands that specified them)
-        // Note: this is done as a second pass since spawn_runtime_entity doesn't
-        // support these yet — we attach them after the entity exists.
+
+        // Process runtime mesh creates and updates
+        let mesh_creates: Vec<_> = self.entity_commands.borrow_mut().mesh_creates.drain(..).collect();
+        for cmd in mesh_creates {
+            self.mesh_cache.insert_runtime_mesh(&gpu.device, &gpu.queue, &cmd.name, &cmd.data);
+        }
+
+        // Process full-definition spawns (after mesh creates, so they can use runtime meshes)
+        let entity_spawns: Vec<_> = self.entity_commands.borrow_mut().entity_spawns.drain(..).collect();
+        for entity_def in &entity_spawns {
+            match self.spawn_entity_def(entity_def) {
+                Ok(_) => spawned.push(entity_def.id.clone()),
+                Err(e) => tracing::error!("entity.spawn_ex: {}", e),
+            }
+        }
+
+        // Process collider rebuilds (after spawns, so new entities can be resized at once)
+        let collider_updates: Vec<_> = self.entity_commands.borrow_mut().collider_updates.drain(..).collect();
+        for cmd in &collider_updates {
+            if let (Some(scene_world), Some(physics_world)) = (&self.scene_world, &self.physics_world) {
+                let mut scene_world = scene_world.borrow_mut();
+                let mut physics_world = physics_world.borrow_mut();
+                if let Err(e) = crate::world::set_entity_collider(&mut scene_world, cmd, &self.mesh_cache, &mut physics_world) {
+                    tracing::warn!("physics.set_collider: {}", e);
+                }
+            }
+        }
+
+        for id in destroyed {
+            self.emit_engine_event(
+                crate::engine_events::ENTITY_DESTROYED,
+                crate::engine_events::payload(serde_json::json!({"entity_id": id})),
+            );
+        }
+        for id in spawned {
+            self.emit_engine_event(
+                crate::engine_events::ENTITY_SPAWNED,
+                crate::engine_events::payload(serde_json::json!({"entity_id": id})),
+            );
+        }
+    }
+
+    /// Spawn a runtime entity from a full scene-format definition, with physics,
+    /// then load and initialize its script.
+    fn spawn_entity_def(&mut self, entity_def: &crate::scene::EntityDef) -> Result<hecs::Entity, String> {
+        let gpu = self.gpu.as_ref().ok_or("GPU not initialized")?;
+        let scene_world = self.scene_world.as_ref().ok_or("No scene loaded")?;
+        let (entity, script_source) = {
+            let mut sw = scene_world.borrow_mut();
+            let mut pw = self.physics_world.as_ref().map(|pw| pw.borrow_mut());
+            let entity = crate::world::spawn_entity_from_def(
+                &mut sw,
+                entity_def,
+                &gpu.device,
+                &gpu.queue,
+                &self.project_root,
+                &mut self.mesh_cache,
+                &mut self.material_cache,
+                &mut self.splat_cache,
+                pw.as_deref_mut(),
+                self.texture_resources.as_ref(),
+                Some(&mut self.texture_cache),
+            )?;
+            let source = sw.world.get::<&Script>(entity).ok().map(|s| s.source.clone());
+            (entity, source)
+        };
+
+        if let (Some(source), Some(script_runtime)) = (script_source, &mut self.script_runtime) {
+            match script_runtime.load_script(entity, &self.project_root, &source) {
+                Ok(()) => {
+                    let _ = script_runtime.set_entity_string_id(entity, &entity_def.id);
+                    script_runtime.call_init(entity);
+                    if let Ok(mut script) = scene_world.borrow().world.get::<&mut Script>(entity) {
+                        script.initialized = true;
+                    }
+                }
+                Err(e) => tracing::error!("Failed to load script for '{}': {}", entity_def.id, e),
+            }
+        }
+        Ok(entity)
     }
 
     /// Process a pending scene load (deferred from Lua `scene.load(path)`).
@@ -2056,7 +3730,7 @@ let mut scene_world = scene_world.borrow_mut();
             None => return,
         };
 
-        let scene_path = self.project_root.join(&scene_rel);
+        let scene_path = self.scene_path(&scene_rel);
         if !scene_path.exists() {
             tracing::error!("scene.load: file not found: {:?}", scene_path);
             return;
@@ -2070,6 +3744,10 @@ let mut scene_world = scene_world.borrow_mut();
             }
         };
 
+        // Keep the outgoing scene's unsaved edits
+        self.write_autosave();
+        self.unsaved_edits = 0;
+
         // 1. Call on_destroy on all scripted entities
         if let (Some(sw), Some(sr)) = (&self.scene_world, &self.script_runtime) {
             let sw = sw.borrow();
@@ -2101,6 +3779,7 @@ let mut scene_world = scene_world.borrow_mut();
         if let Some(pw) = &self.physics_world {
             let mut pw = pw.borrow_mut();
             *pw = PhysicsWorld::new(gravity);
+            pw.timestep = crate::physics::FixedTimestep::new(scene.settings.physics_rate);
         }
 
         // 5. Clear pool manager, particle system, lua event listeners, camera shake
@@ -2109,7 +3788,17 @@ let mut scene_world = scene_world.borrow_mut();
         self.lua_event_listeners.borrow_mut().clear();
         *self.next_lua_listener_id.borrow_mut() = 0;
         self.lua_listener_id_map.borrow_mut().clear();
+        self.message_queue.borrow_mut().clear();
+        *self.game_clock.borrow_mut() = crate::time_scale::GameClock::new();
         *self.camera_shake.borrow_mut() = CameraShakeState::new();
+        self.crowd.borrow_mut().groups.clear();
+        self.noise.borrow_mut().clear();
+        self.sequences.borrow_mut().reset();
+        self.camera_paths.borrow_mut().reset();
+        // The load hitches; don't let it count against the quality level
+        if let Some(governor) = &mut self.quality_governor {
+            governor.hold();
+        }
 
         let gpu = match &self.gpu {
             Some(gpu) => gpu,
@@ -2156,7 +3845,7 @@ let mut scene_world = scene_world.borrow_mut();
                             let (rb_handle, col_handle) = pw.add_character_body(entity, pos, half_height.max(0.1), cc_def.radius);
                             let rb_comp = crate::physics::RigidBody { handle: rb_handle, body_type: crate::physics::PhysicsBodyType::Kinematic };
                             let col_comp = crate::physics::Collider { handle: col_handle, shape: crate::physics::PhysicsShape::Capsule { half_height: half_height.max(0.1), radius: cc_def.radius }, is_trigger: false };
-                            let cc_comp = CharacterController { move_speed: cc_def.move_speed, sprint_multiplier: cc_def.sprint_multiplier, jump_impulse: cc_def.jump_impulse, step_height: cc_def.step_height, ..Default::default() };
+                            let cc_comp = CharacterController { move_speed: cc_def.move_speed, sprint_multiplier: cc_def.sprint_multiplier, jump_impulse: cc_def.jump_impulse, step_height: cc_def.step_height, push_force: cc_def.push_force, mass: cc_def.mass, ..Default::default() };
                             let player = crate::components::Player { height: cc_def.height, radius: cc_def.radius, ..Default::default() };
                             let _ = sw.world.insert(entity, (rb_comp, col_comp, cc_comp, player));
                         } else if let Some(col_def) = &entity_def.components.collider {
@@ -2209,7 +3898,8 @@ let mut scene_world = scene_world.borrow_mut();
             }
         }
 
-        // 8. Re-load scripts for the new scene
+        // 8. Re-load scripts for the new scene, with its config
+        self.refresh_script_config();
         if let Some(sw) = &self.scene_world {
             let mut sw = sw.borrow_mut();
             if let Some(sr) = &mut self.script_runtime {
@@ -2255,10 +3945,76 @@ let mut scene_world = scene_world.borrow_mut();
             }
         }
 
+        // 8b. Free the buffers of meshes and splats the new scene doesn't use
+        self.release_unused_gpu_buffers();
+
         // 9. Update scene_path for hot-reload
         self.scene_path = Some(scene_path);
 
         tracing::info!("Scene loaded via scene.load(\"{}\")", scene_rel);
+        if self.args.color_audit {
+            self.report_color_audit("textures", crate::color_space::audit_textures(self.texture_cache.keys()));
+        }
+        self.emit_engine_event(
+            crate::engine_events::SCENE_LOADED,
+            crate::engine_events::payload(serde_json::json!({"scene": scene_rel})),
+        );
+    }
+
+    /// Log the findings of a `--color-audit` check and flag them on the HUD.
+    fn report_color_audit(&mut self, what: &str, issues: Vec<String>) {
+        if issues.is_empty() {
+            tracing::info!("Color audit ({}): no issues", what);
+            return;
+        }
+        for issue in &issues {
+            tracing::warn!("Color audit ({}): {}", what, issue);
+        }
+        self.reload_notifications.push((
+            format!("Color audit: {} {} issue(s), see log", issues.len(), what),
+            instant::Instant::now(),
+            [1.0, 0.8, 0.2, 1.0],
+        ));
+    }
+
+    /// Release the meshes and splats no entity uses any more, then compact
+    /// the buffer arenas if that left them fragmented (see `gpu_memory`).
+    fn release_unused_gpu_buffers(&mut self) {
+        let (Some(gpu), Some(sw)) = (&self.gpu, &self.scene_world) else {
+            return;
+        };
+        let sw = sw.borrow();
+        let mut meshes: HashSet<crate::components::MeshHandle> = HashSet::new();
+        for (_, renderer) in sw.world.query::<&MeshRenderer>().iter() {
+            meshes.insert(renderer.mesh_handle);
+        }
+        for (_, lod) in sw.world.query::<&crate::lod::MeshLod>().iter() {
+            meshes.extend((0..=lod.levels.len()).map(|level| lod.mesh(level)));
+        }
+        for (_, water) in sw.world.query::<&crate::components::Water>().iter() {
+            meshes.extend(water.mesh);
+        }
+        for (_, foliage) in sw.world.query::<&Foliage>().iter() {
+            meshes.extend(foliage.mesh);
+        }
+        let splats: HashSet<crate::components::SplatHandle> = sw.world
+            .query::<&GaussianSplat>()
+            .iter()
+            .map(|(_, splat)| splat.splat_handle)
+            .collect();
+        drop(sw);
+
+        let released_meshes = self.mesh_cache.release_unused(&gpu.device, &meshes);
+        let released_splats = self.splat_cache.release_unused(&splats);
+        let moved = self.mesh_cache.compact(&gpu.device, &gpu.queue) + self.splat_cache.compact(&gpu.device, &gpu.queue);
+        if released_meshes + released_splats > 0 || moved > 0 {
+            tracing::info!(
+                "Released {} meshes and {} splat clouds; compaction moved {:.1} MB",
+                released_meshes,
+                released_splats,
+                moved as f64 / (1024.0 * 1024.0)
+            );
+        }
     }
 
     /// Compute camera shake offset, decaying the timer.
@@ -2290,11 +4046,16 @@ let mut scene_world = scene_world.borrow_mut();
     fn update_camera(&mut self) {
         // Compute camera shake offset before borrowing other fields
         let shake_offset = self.compute_camera_shake(self.delta_time);
+        // The debug camera was applied by update_debug_camera
+        if self.debug_camera.is_some() {
+            return;
+        }
 
         let gpu = match &self.gpu {
             Some(gpu) => gpu,
             None => return,
         };
+        let (width, height) = self.render_debug.render_size(gpu.config.width, gpu.config.height);
         let scene_world = match &self.scene_world {
             Some(sw) => sw,
             None => return,
@@ -2306,87 +4067,42 @@ let mut scene_world = scene_world.borrow_mut();
         };
         let mut camera_state = camera_state_rc.borrow_mut();
 
+        // A playing cutscene drives the main camera along its path, then a
+        // camera path started with camera.play_path
+        let pose = self.sequences.borrow().camera().or_else(|| self.camera_paths.borrow().camera());
+        if let Some(pose) = pose {
+            let main = scene_world.world.query::<(&Transform, &Camera)>().iter()
+                .find(|(_, (_, camera))| camera.role == CameraRole::Main)
+                .map(|(_, (transform, camera))| (transform.clone(), camera.clone()));
+            if let Some((transform, mut camera)) = main {
+                if let Some(fov) = pose.fov {
+                    camera.fov_degrees = fov;
+                }
+                let cam_transform = Transform {
+                    position: pose.position + shake_offset,
+                    rotation: pose.rotation,
+                    ..transform
+                };
+                camera_state.update(&gpu.queue, &camera, &cam_transform, width, height);
+                return;
+            }
+        }
+
         // Check if there's a player entity controlling the camera
         let mut player_camera_applied = false;
         for (entity, (transform, player, camera)) in
             scene_world.world.query::<(&Transform, &Player, &Camera)>().iter()
         {
             if camera.role == CameraRole::Main {
-                // Check for CameraMode component
-                let camera_mode = scene_world.world.get::<&CameraMode>(entity).ok();
-
-                let is_third_person = camera_mode.as_ref().map(|cm| matches!(**cm, CameraMode::ThirdPerson { .. })).unwrap_or(false);
-
-                if is_third_person {
-                    let (distance, height_offset) = if let Some(ref cm) = camera_mode {
-                        match **cm {
-                            CameraMode::ThirdPerson { distance, height_offset, .. } => (distance, height_offset),
-                            _ => (4.0, 1.5),
-                        }
-                    } else {
-                        (4.0, 1.5)
-                    };
-                    // Third-person camera: orbit behind player
-                    let target = transform.position + glam::Vec3::new(0.0, height_offset, 0.0);
-
-                    // Camera position orbits around target based on yaw and pitch
-                    let cam_offset = glam::Vec3::new(
-                        player.yaw.sin() * player.pitch.cos() * distance,
-                        player.pitch.sin() * distance,
-                        player.yaw.cos() * player.pitch.cos() * distance,
-                    );
-                    let mut desired_pos = target + cam_offset;
-
-                    // Wall collision: raycast from target to desired camera position
-                    if let Some(physics_world) = &self.physics_world {
-                        let physics_world = physics_world.borrow_mut();
-                        let ray_dir = (desired_pos - target).normalize_or_zero();
-                        let ray_dist = (desired_pos - target).length();
-                        if let Some((_entity, toi, _hit, _normal)) = physics_world.raycast_detailed(
-                            target, ray_dir, ray_dist, None,
-                        ) {
-                            // Pull camera closer to avoid clipping through walls
-                            desired_pos = target + ray_dir * (toi - 0.2).max(0.5);
-                        }
-                    }
-
-                    // Look rotation: camera looks from desired_pos toward target
-                    let forward = (target - desired_pos).normalize_or_zero();
-                    let look_rotation = if forward.length_squared() > 0.001 {
-                        glam::Quat::from_rotation_arc(-glam::Vec3::Z, forward)
-                    } else {
-                        glam::Quat::IDENTITY
-                    };
-
-                    let cam_transform = Transform {
-                        position: desired_pos + shake_offset,
-                        rotation: look_rotation,
-                        ..transform.clone()
-                    };
-                    camera_state.update(
-                        &gpu.queue,
-                        camera,
-                        &cam_transform,
-                        gpu.config.width,
-                        gpu.config.height,
-                    );
-                } else {
-                    // First-person camera (existing behavior)
-                    let look_rotation = glam::Quat::from_rotation_y(player.yaw)
-                        * glam::Quat::from_rotation_x(player.pitch);
-                    let cam_transform = Transform {
-                        position: transform.position + glam::Vec3::new(0.0, player.height * 0.4, 0.0) + shake_offset,
-                        rotation: look_rotation,
-                        ..transform.clone()
-                    };
-                    camera_state.update(
-                        &gpu.queue,
-                        camera,
-                        &cam_transform,
-                        gpu.config.width,
-                        gpu.config.height,
-                    );
-                }
+                let mut cam_transform = self.player_camera_transform(&scene_world.world, entity, transform, player);
+                cam_transform.position += shake_offset;
+                camera_state.update(
+                    &gpu.queue,
+                    camera,
+                    &cam_transform,
+                    width,
+                    height,
+                );
                 player_camera_applied = true;
                 break;
             }
@@ -2406,8 +4122,8 @@ let mut scene_world = scene_world.borrow_mut();
                         &gpu.queue,
                         camera,
                         &cam_transform,
-                        gpu.config.width,
-                        gpu.config.height,
+                        width,
+                        height,
                     );
                     break;
                 }
@@ -2415,6 +4131,171 @@ let mut scene_world = scene_world.borrow_mut();
         }
     }
 
+    /// Where a player-controlled camera looks from: first person at eye
+    /// height, or third person orbiting behind the player and pulled in front
+    /// of walls, eased and leading the player as its `CameraFollow` says.
+    fn player_camera_transform(
+        &self,
+        world: &hecs::World,
+        entity: hecs::Entity,
+        transform: &Transform,
+        player: &Player,
+    ) -> Transform {
+        let third_person = match world.get::<&CameraMode>(entity).ok().as_deref() {
+            Some(&CameraMode::ThirdPerson {
+                distance,
+                height_offset,
+                position_damping,
+                rotation_damping,
+                look_ahead,
+                look_ahead_max,
+                ..
+            }) => Some((
+                distance,
+                height_offset,
+                FollowSettings { position_damping, rotation_damping, look_ahead, look_ahead_max },
+            )),
+            _ => None,
+        };
+
+        let Some((distance, height_offset, settings)) = third_person else {
+            // First-person camera
+            let look_rotation = glam::Quat::from_rotation_y(player.yaw)
+                * glam::Quat::from_rotation_x(player.pitch);
+            return Transform {
+                position: transform.position + glam::Vec3::new(0.0, player.height * 0.4, 0.0),
+                rotation: look_rotation,
+                ..transform.clone()
+            };
+        };
+        let mut follow = world.get::<&mut CameraFollow>(entity).ok();
+        let dt = self.delta_time;
+
+        // Third-person camera: orbit behind player, around a point leading them
+        let target = transform.position + glam::Vec3::new(0.0, height_offset, 0.0);
+        let lead = follow.as_mut().map_or(glam::Vec3::ZERO, |f| f.lead(transform.position, dt, &settings));
+        let focus = target + lead;
+
+        // Camera position orbits around the focus based on yaw and pitch
+        let cam_offset = glam::Vec3::new(
+            player.yaw.sin() * player.pitch.cos() * distance,
+            player.pitch.sin() * distance,
+            player.yaw.cos() * player.pitch.cos() * distance,
+        );
+        let mut desired_pos = focus + cam_offset;
+        if let Some(follow) = &follow {
+            desired_pos = follow.ease_position(desired_pos, dt, &settings);
+        }
+
+        // Wall collision: raycast from target to desired camera position
+        if let Some(physics_world) = &self.physics_world {
+            let physics_world = physics_world.borrow_mut();
+            let ray_dir = (desired_pos - target).normalize_or_zero();
+            let ray_dist = (desired_pos - target).length();
+            // Ignore the player's own capsule and trigger volumes
+            let filter = crate::physics::EntityQueryFilter {
+                exclude: [entity].into_iter().collect(),
+                exclude_triggers: true,
+                ..Default::default()
+            };
+            if let Some((_entity, toi, _hit, _normal)) = physics_world.raycast_detailed(
+                target, ray_dir, ray_dist, &filter,
+            ) {
+                // Pull camera closer to avoid clipping through walls
+                desired_pos = target + ray_dir * (toi - 0.2).max(0.5);
+            }
+        }
+
+        // Look rotation: camera looks from desired_pos toward the focus
+        let forward = (focus - desired_pos).normalize_or_zero();
+        let mut look_rotation = if forward.length_squared() > 0.001 {
+            glam::Quat::from_rotation_arc(-glam::Vec3::Z, forward)
+        } else {
+            glam::Quat::IDENTITY
+        };
+        if let Some(follow) = &mut follow {
+            look_rotation = follow.ease_rotation(desired_pos, look_rotation, dt, &settings);
+        }
+
+        Transform {
+            position: desired_pos,
+            rotation: look_rotation,
+            ..transform.clone()
+        }
+    }
+
+    /// Update the split-screen views from the `player<N>` cameras. With fewer
+    /// than two of them there are no views and the main camera renders alone.
+    fn update_player_views(&mut self) {
+        let (Some(gpu), Some(scene_world)) = (&self.gpu, &self.scene_world) else {
+            return;
+        };
+        let scene_world = scene_world.borrow();
+        let mut cameras: Vec<(u32, hecs::Entity)> = scene_world
+            .world
+            .query::<&Camera>()
+            .iter()
+            .filter_map(|(entity, camera)| match camera.role {
+                CameraRole::Player(n) => Some((n, entity)),
+                _ => None,
+            })
+            .collect();
+        cameras.sort();
+        cameras.truncate(crate::split_screen::MAX_PLAYERS);
+        if cameras.len() < 2 {
+            self.player_views.clear();
+            return;
+        }
+
+        // Keep each player's camera state (and its TAA history) while it
+        // follows the same entity
+        let jitter = self.camera_state.as_ref().is_some_and(|cs| cs.borrow().jitter_enabled);
+        if jitter && self.player_views.is_empty() {
+            tracing::warn!("Split screen: the views share the TAA history and will ghost; switch to a pipeline without TAA");
+        }
+        let mut old_views = std::mem::take(&mut self.player_views);
+        for &(player, entity) in &cameras {
+            let view = match old_views.iter().position(|v| v.player == player && v.entity == entity) {
+                Some(i) => old_views.swap_remove(i),
+                None => {
+                    tracing::info!("Split screen: player {} views through its camera", player);
+                    crate::split_screen::PlayerView {
+                        player,
+                        entity,
+                        camera_state: crate::camera::CameraState::new(&gpu.device),
+                    }
+                }
+            };
+            self.player_views.push(view);
+        }
+
+        let (width, height) = (gpu.config.width, gpu.config.height);
+        let (target_width, target_height) = self.render_debug.render_size(width, height);
+        let layout = self.project_config.as_ref().map(|c| c.split_screen.layout).unwrap_or_default();
+        let viewports = crate::split_screen::layout_viewports(self.player_views.len(), width, height, layout);
+        let world = &scene_world.world;
+        let poses: Vec<Option<(Camera, Transform)>> = self
+            .player_views
+            .iter()
+            .map(|view| {
+                let transform = world.get::<&Transform>(view.entity).ok()?;
+                let camera = world.get::<&Camera>(view.entity).ok()?;
+                let cam_transform = match world.get::<&Player>(view.entity) {
+                    Ok(player) => self.player_camera_transform(world, view.entity, &transform, &player),
+                    Err(_) => (*transform).clone(),
+                };
+                Some(((*camera).clone(), cam_transform))
+            })
+            .collect();
+        for ((view, viewport), pose) in self.player_views.iter_mut().zip(viewports).zip(poses) {
+            let Some((camera, cam_transform)) = pose else {
+                continue;
+            };
+            view.camera_state.jitter_enabled = jitter;
+            view.camera_state.update_viewport(&gpu.queue, &camera, &cam_transform, target_width, target_height, viewport);
+        }
+    }
+
     /// Process commands from the command socket.
     /// Editor-enhanced commands (spawn with mesh, save_scene, etc.) are handled here
     /// at Engine level where we have access to GPU resources and caches.
@@ -2475,13 +4356,10 @@ let mut scene_world = scene_world.borrow_mut();
             }
 
             let response = match cmd {
-                // Enhanced spawn_entity: if it has mesh_renderer, handle at Engine level
+                // Full spawn_entity needs GPU resources, so it is handled at Engine level
                 "spawn_entity" => {
-                    let has_mesh = pending.request.params.get("components")
-                        .and_then(|c| c.get("mesh_renderer"))
-                        .is_some();
-                    if has_mesh {
-                        self.handle_spawn_with_mesh(&pending.request)
+                    if self.gpu.is_some() {
+                        self.handle_spawn_entity(&pending.request)
                     } else {
                         {
                             let mut sw_opt = self.scene_world.as_ref().map(|rc| rc.borrow_mut());
@@ -2498,10 +4376,19 @@ let mut scene_world = scene_world.borrow_mut();
                     }
                 }
                 "save_scene" => self.handle_save_scene(&pending.request),
+                "save_bindings" => self.handle_save_bindings(),
                 "get_scene_yaml" => self.handle_get_scene_yaml(),
                 "set_camera" => self.handle_set_camera(&pending.request),
                 "editor_status" => self.handle_editor_status(),
+                "get_gpu_timings" => self.handle_get_gpu_timings(),
+                "get_gpu_memory" => self.handle_get_gpu_memory(),
+                "capture_screenshot" => self.handle_capture_screenshot(&pending.request.params),
+                "get_script_watchdog" => self.handle_get_script_watchdog(),
+                "get_script_memory" => self.handle_get_script_memory(),
                 "run_lua" => self.handle_run_lua(&pending.request),
+                "open_window" => self.handle_open_window(&pending.request),
+                "close_window" => self.handle_close_window(&pending.request),
+                "switch_pipeline" => self.handle_switch_pipeline(&pending.request),
                 _ => {
                         let mut sw_opt = self.scene_world.as_ref().map(|rc| rc.borrow_mut());
                         let mut eb = self.event_bus.borrow_mut();
@@ -2515,204 +4402,48 @@ let mut scene_world = scene_world.borrow_mut();
                         )
                     },
             };
+            if response.status == "ok" && matches!(cmd, "spawn_entity" | "modify_entity" | "destroy_entity") {
+                self.mark_scene_edited();
+            }
             let _ = pending.responder.send(response);
         }
     }
 
-    /// Handle spawn_entity with mesh_renderer component (needs GPU resources).
-    fn handle_spawn_with_mesh(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
-        use crate::command::{CommandResponse};
+    /// Handle spawn_entity: `components` is parsed like a scene YAML entity, so
+    /// anything a scene entity can have (lights, colliders, rigid bodies,
+    /// scripts, health, ...) can be spawned at runtime. `extends` names an
+    /// entity of the current scene to inherit from.
+    fn handle_spawn_entity(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
         use serde_json::json;
 
         let entity_id = match req.params.get("entity_id").and_then(|v| v.as_str()) {
             Some(id) => id.to_string(),
             None => return CommandResponse::error("Missing 'entity_id' parameter"),
         };
-
-        let gpu = match &self.gpu {
-            Some(gpu) => gpu,
-            None => return CommandResponse::error("GPU not initialized"),
-        };
-        let device = &gpu.device;
-
-        let components = match req.params.get("components") {
-            Some(c) => c,
-            None => return CommandResponse::error("Missing 'components' parameter"),
-        };
-
-        let mr = match components.get("mesh_renderer") {
-            Some(mr) => mr,
-            None => return CommandResponse::error("Missing 'mesh_renderer' in components"),
+        let def_json = json!({
+            "id": entity_id,
+            "tags": req.params.get("tags").cloned().unwrap_or_else(|| json!([])),
+            "extends": req.params.get("extends").cloned().unwrap_or(serde_json::Value::Null),
+            "components": req.params.get("components").cloned().unwrap_or_else(|| json!({})),
+        });
+        let entity_def: crate::scene::EntityDef = match serde_json::from_value(def_json) {
+            Ok(def) => def,
+            Err(e) => return CommandResponse::error(format!("Invalid entity '{}': {}", entity_id, e)),
         };
 
-        let mesh = mr.get("mesh").and_then(|v| v.as_str()).unwrap_or("procedural:cube");
-        let material = mr.get("material").and_then(|v| v.as_str()).unwrap_or("procedural:default");
-
-        // Parse transform
-        let mut position = [0.0f32; 3];
-        let mut scale = [1.0f32; 3];
-        let mut rotation = [0.0f32; 3];
-        if let Some(t) = components.get("transform") {
-            if let Some(arr) = t.get("position").and_then(|v| v.as_array()) {
-                if arr.len() == 3 {
-                    position = [
-                        arr[0].as_f64().unwrap_or(0.0) as f32,
-                        arr[1].as_f64().unwrap_or(0.0) as f32,
-                        arr[2].as_f64().unwrap_or(0.0) as f32,
-                    ];
-                }
-            }
-            if let Some(arr) = t.get("scale").and_then(|v| v.as_array()) {
-                if arr.len() == 3 {
-                    scale = [
-                        arr[0].as_f64().unwrap_or(1.0) as f32,
-                        arr[1].as_f64().unwrap_or(1.0) as f32,
-                        arr[2].as_f64().unwrap_or(1.0) as f32,
-                    ];
-                }
-            }
-            if let Some(arr) = t.get("rotation").and_then(|v| v.as_array()) {
-                if arr.len() == 3 {
-                    rotation = [
-                        arr[0].as_f64().unwrap_or(0.0) as f32,
-                        arr[1].as_f64().unwrap_or(0.0) as f32,
-                        arr[2].as_f64().unwrap_or(0.0) as f32,
-                    ];
-                }
-            }
-        }
-
-        // Parse tags
-        let tags = req.params.get("tags")
-            .and_then(|v| v.as_array())
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect::<Vec<_>>())
-            .unwrap_or_default();
-
-        // Spawn entity, apply rotation/tags (scoped to release scene_world borrow)
-        {
-            let scene_world = match &self.scene_world {
-                Some(sw) => sw,
-                None => return CommandResponse::error("No scene loaded"),
-            };
-            let mut scene_world = scene_world.borrow_mut();
-
-            if scene_world.entity_registry.contains_key(&entity_id) {
-                return CommandResponse::error(format!("Entity '{}' already exists", entity_id));
-            }
-
-            // Use spawn_runtime_entity for mesh entities
-            let ok = crate::world::spawn_runtime_entity(
-                &mut *scene_world,
-                &entity_id,
-                mesh,
-                material,
-                position,
-                scale,
-                device,
-                &gpu.queue,
-                &self.project_root,
-                &mut self.mesh_cache,
-                &mut self.material_cache,
-                self.texture_resources.as_ref(),
-            );
-
-            if !ok {
-                return CommandResponse::error(format!("Failed to spawn entity '{}'", entity_id));
-            }
-
-            // Apply rotation if non-zero
-            if rotation != [0.0, 0.0, 0.0] {
-                if let Some(&entity) = scene_world.entity_registry.get(&entity_id) {
-                    if let Ok(mut transform) = scene_world.world.get::<&mut Transform>(entity) {
-                        transform.rotation = crate::world::euler_degrees_to_quat(rotation);
-                        transform.dirty = true;
-                    }
-                }
-            }
-
-            // Apply tags
-            if !tags.is_empty() {
-                if let Some(&entity) = scene_world.entity_registry.get(&entity_id) {
-                    if let Ok(mut entity_tags) = scene_world.world.get::<&mut crate::components::Tags>(entity) {
-                        entity_tags.0 = tags;
-                    }
-                }
-            }
-        } // scene_world borrow released here
-
-        // Add physics body if rigid_body/collider components are specified
-        if let Some(collider_json) = components.get("collider") {
-            // Parse collider shape from JSON
-            let shape_str = collider_json.get("shape").and_then(|v| v.as_str()).unwrap_or("box");
-            let shape = match shape_str {
-                "sphere" => crate::physics::PhysicsShape::Sphere {
-                    radius: collider_json.get("radius").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32,
-                },
-                "capsule" => crate::physics::PhysicsShape::Capsule {
-                    half_height: collider_json.get("half_height").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32,
-                    radius: collider_json.get("radius").and_then(|v| v.as_f64()).unwrap_or(0.3) as f32,
-                },
-                _ => {
-                    let he = if let Some(arr) = collider_json.get("half_extents").and_then(|v| v.as_array()) {
-                        if arr.len() == 3 {
-                            glam::Vec3::new(
-                                arr[0].as_f64().unwrap_or(0.5) as f32,
-                                arr[1].as_f64().unwrap_or(0.5) as f32,
-                                arr[2].as_f64().unwrap_or(0.5) as f32,
-                            )
-                        } else {
-                            glam::Vec3::splat(0.5)
-                        }
-                    } else {
-                        glam::Vec3::new(scale[0] * 0.5, scale[1] * 0.5, scale[2] * 0.5)
-                    };
-                    crate::physics::PhysicsShape::Box { half_extents: he }
-                }
-            };
-
-            let is_trigger = collider_json.get("is_trigger").and_then(|v| v.as_bool()).unwrap_or(false);
-            let restitution = collider_json.get("restitution").and_then(|v| v.as_f64()).unwrap_or(0.3) as f32;
-            let friction = collider_json.get("friction").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
-
-            let rb_json = components.get("rigid_body");
-            let body_type = rb_json.and_then(|rb| rb.get("type")).and_then(|v| v.as_str()).unwrap_or("static");
-
-            let pos = glam::Vec3::from(position);
-            let rot = crate::world::euler_degrees_to_quat(rotation);
-
-            if let (Some(pw), Some(sw)) = (&self.physics_world, &self.scene_world) {
-                let mut pw = pw.borrow_mut();
-                let mut sw = sw.borrow_mut();
-                if let Some(&entity) = sw.entity_registry.get(&entity_id) {
-                    match body_type {
-                        "dynamic" => {
-                            let mass = rb_json.and_then(|rb| rb.get("mass")).and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
-                            let ccd = rb_json.and_then(|rb| rb.get("ccd")).and_then(|v| v.as_bool()).unwrap_or(false);
-                            let (rb_handle, col_handle) = pw
-                                .add_dynamic_body(entity, pos, rot, shape.clone(), mass, restitution, friction, ccd);
-                            let _ = sw.world.insert(entity, (
-                                crate::physics::RigidBody { handle: rb_handle, body_type: crate::physics::PhysicsBodyType::Dynamic },
-                                crate::physics::Collider { handle: col_handle, shape, is_trigger },
-                            ));
-                        }
-                        _ => {
-                            let (rb_handle, col_handle) = pw
-                                .add_static_body(entity, pos, rot, shape.clone(), is_trigger, restitution, friction);
-                            let _ = sw.world.insert(entity, (
-                                crate::physics::RigidBody { handle: rb_handle, body_type: crate::physics::PhysicsBodyType::Static },
-                                crate::physics::Collider { handle: col_handle, shape, is_trigger },
-                            ));
-                        }
-                    }
-                }
-            }
+        if let Err(e) = self.spawn_entity_def(&entity_def) {
+            return CommandResponse::error(e);
         }
-
+        self.emit_engine_event(
+            crate::engine_events::ENTITY_SPAWNED,
+            crate::engine_events::payload(json!({"entity_id": entity_id})),
+        );
         CommandResponse::ok(json!({"entity_id": entity_id}))
     }
 
     /// Handle save_scene: serialize current ECS state to YAML file.
-    fn handle_save_scene(&self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
+    fn handle_save_scene(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
         use crate::command::CommandResponse;
         use serde_json::json;
 
@@ -2733,12 +4464,36 @@ let mut scene_world = scene_world.borrow_mut();
         match std::fs::write(&full_path, &yaml) {
             Ok(()) => {
                 tracing::info!("Scene saved to {:?}", full_path);
+                // The edits are on disk now; a crash no longer loses them
+                crate::autosave::discard(&self.project_root, self.scene_path.as_deref());
+                self.unsaved_edits = 0;
+                self.autosave_pending = None;
                 CommandResponse::ok(json!({"path": full_path.display().to_string(), "bytes": yaml.len()}))
             }
             Err(e) => CommandResponse::error(format!("Failed to write scene: {}", e)),
         }
     }
 
+    /// Handle save_bindings: write the live input bindings (including any
+    /// `rebind` changes) to the project's bindings file.
+    fn handle_save_bindings(&self) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let input = match &self.input_state {
+            Some(input) => input.borrow(),
+            None => return CommandResponse::error("No input state"),
+        };
+        let path = self.bindings_path();
+        match crate::input::write_bindings(&path, input.bindings()) {
+            Ok(()) => {
+                tracing::info!("Input bindings saved to {:?}", path);
+                CommandResponse::ok(json!({"path": path.display().to_string()}))
+            }
+            Err(e) => CommandResponse::error(e),
+        }
+    }
+
     /// Handle get_scene_yaml: return current scene as YAML string.
     fn handle_get_scene_yaml(&self) -> crate::command::CommandResponse {
         use crate::command::CommandResponse;
@@ -2795,6 +4550,131 @@ let mut scene_world = scene_world.borrow_mut();
         }))
     }
 
+    /// Handle get_gpu_timings: most recent per-pass GPU times from timestamp queries.
+    fn handle_get_gpu_timings(&self) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let profiler = match &self.gpu_profiler {
+            Some(p) => p,
+            None => return CommandResponse::ok(json!({ "supported": false, "passes": [], "total_ms": 0.0 })),
+        };
+        let passes: Vec<serde_json::Value> = profiler.timings().iter()
+            .map(|t| json!({ "name": t.name, "gpu_ms": t.gpu_ms }))
+            .collect();
+        CommandResponse::ok(json!({
+            "supported": true,
+            "passes": passes,
+            "total_ms": profiler.total_ms(),
+        }))
+    }
+
+    /// Handle get_gpu_memory: use and fragmentation of the buffer arenas.
+    fn handle_get_gpu_memory(&self) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let arenas: Vec<serde_json::Value> = self.mesh_cache.arenas.stats().iter()
+            .map(|a| json!({
+                "class": a.class.name(),
+                "blocks": a.blocks,
+                "capacity_bytes": a.capacity,
+                "used_bytes": a.used,
+                "allocations": a.allocations,
+                "largest_free_bytes": a.largest_free,
+                "fragmentation": a.fragmentation(),
+            }))
+            .collect();
+        let (compactions, bytes_moved) = self.mesh_cache.arenas.compaction_totals();
+        CommandResponse::ok(json!({
+            "arenas": arenas,
+            "compactions": compactions,
+            "bytes_moved": bytes_moved,
+        }))
+    }
+
+    /// Handle get_script_watchdog: budgets, violations and disabled scripts.
+    fn handle_get_script_watchdog(&self) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let script_runtime = match &self.script_runtime {
+            Some(sr) => sr,
+            None => return CommandResponse::error("Script runtime not initialized"),
+        };
+        let watchdog = script_runtime.watchdog.borrow();
+        let entity_to_id: HashMap<hecs::Entity, String> = self.scene_world.as_ref()
+            .map(|sw| sw.borrow().entity_registry.iter().map(|(id, &e)| (e, id.clone())).collect())
+            .unwrap_or_default();
+        let scripts: Vec<serde_json::Value> = watchdog.violations()
+            .map(|(entity, violations, disabled)| json!({
+                "entity_id": entity_to_id.get(&entity).cloned().unwrap_or_else(|| format!("{:?}", entity)),
+                "violations": violations,
+                "disabled": disabled,
+            }))
+            .collect();
+        CommandResponse::ok(json!({
+            "max_instructions": watchdog.max_instructions,
+            "max_time_ms": watchdog.max_time.as_secs_f32() * 1000.0,
+            "max_violations": watchdog.max_violations,
+            "scripts": scripts,
+        }))
+    }
+
+    /// Handle get_script_memory: VM usage, GC stats and per-script allocation
+    /// rates, heaviest allocators first.
+    fn handle_get_script_memory(&self) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        /// Stop counting a script's state past this many table entries.
+        const STATE_ENTRY_CAP: usize = 100_000;
+
+        let script_runtime = match &self.script_runtime {
+            Some(sr) => sr,
+            None => return CommandResponse::error("Script runtime not initialized"),
+        };
+        let watchdog = script_runtime.watchdog.borrow();
+        let memory = &watchdog.memory;
+        let entity_to_id: HashMap<hecs::Entity, String> = self.scene_world.as_ref()
+            .map(|sw| sw.borrow().entity_registry.iter().map(|(id, &e)| (e, id.clone())).collect())
+            .unwrap_or_default();
+        let mut stats: Vec<_> = memory.entity_stats().collect();
+        stats.sort_by(|a, b| b.1.bytes_per_frame.total_cmp(&a.1.bytes_per_frame));
+        let scripts: Vec<serde_json::Value> = stats.into_iter()
+            .map(|(entity, s)| json!({
+                "entity_id": entity_to_id.get(&entity).cloned().unwrap_or_else(|| format!("{:?}", entity)),
+                "bytes_per_frame": s.bytes_per_frame,
+                "total_bytes": s.total_bytes,
+                "state_entries": script_runtime.state_entries(entity, STATE_ENTRY_CAP),
+            }))
+            .collect();
+        CommandResponse::ok(json!({
+            "used_bytes": script_runtime.lua.used_memory(),
+            "limit_bytes": memory.limit,
+            "live_bytes": memory.live_bytes,
+            "gc_budget_ms": memory.gc_budget.as_secs_f32() * 1000.0,
+            "last_gc_ms": memory.last_gc_ms,
+            "gc_cycles": memory.gc_cycles,
+            "scripts": scripts,
+        }))
+    }
+
+    fn handle_capture_screenshot(&mut self, params: &HashMap<String, serde_json::Value>) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let path = params.get("path").and_then(|v| v.as_str()).unwrap_or("screenshots/screenshot.png");
+        match crate::screenshot::resolve_output_path(&self.project_root, path, true) {
+            Ok(out) => {
+                self.screenshot_queue.borrow_mut().push(out.clone());
+                // Written at the end of the next rendered frame
+                CommandResponse::ok(json!({ "queued": true, "path": out.to_string_lossy() }))
+            }
+            Err(e) => CommandResponse::error(e),
+        }
+    }
+
     /// Handle editor_status: return editor mode info.
     fn handle_editor_status(&self) -> crate::command::CommandResponse {
         use crate::command::CommandResponse;
@@ -2824,6 +4704,48 @@ let mut scene_world = scene_world.borrow_mut();
         }))
     }
 
+    /// Handle open_window: open an inspector or profiler window next to the
+    /// game window, or focus the one already open.
+    fn handle_open_window(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let name = req.params.get("kind").and_then(|v| v.as_str()).unwrap_or("inspector");
+        let kind = match crate::tool_window::ToolWindowKind::parse(name) {
+            Ok(kind) => kind,
+            Err(e) => return CommandResponse::error(e),
+        };
+        if self.gpu.is_none() || self.bitmap_font.is_none() {
+            return CommandResponse::error("No window to open it from yet");
+        }
+        if let Some(open) = self.tool_windows.iter().find(|w| w.kind == kind) {
+            open.window.focus_window();
+            return CommandResponse::ok(json!({ "kind": kind.name(), "opened": false }));
+        }
+        if !self.pending_tool_windows.contains(&kind) {
+            // Windows can only be created with the event loop at hand
+            self.pending_tool_windows.push(kind);
+        }
+        CommandResponse::ok(json!({ "kind": kind.name(), "opened": true }))
+    }
+
+    /// Handle close_window: close the inspector or profiler window.
+    fn handle_close_window(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
+        use crate::command::CommandResponse;
+        use serde_json::json;
+
+        let name = req.params.get("kind").and_then(|v| v.as_str()).unwrap_or("inspector");
+        let kind = match crate::tool_window::ToolWindowKind::parse(name) {
+            Ok(kind) => kind,
+            Err(e) => return CommandResponse::error(e),
+        };
+        let open = self.tool_windows.len() + self.pending_tool_windows.len();
+        self.tool_windows.retain(|w| w.kind != kind);
+        self.pending_tool_windows.retain(|k| *k != kind);
+        let closed = open > self.tool_windows.len() + self.pending_tool_windows.len();
+        CommandResponse::ok(json!({ "kind": kind.name(), "closed": closed }))
+    }
+
     /// Handle run_lua: execute arbitrary Lua code with access to all registered APIs.
     fn handle_run_lua(&mut self, req: &crate::command::CommandRequest) -> crate::command::CommandResponse {
         use crate::command::CommandResponse;
@@ -2862,10 +4784,12 @@ let mut scene_world = scene_world.borrow_mut();
             })?;
             env.set("print", print_fn)?;
 
-            let value = lua.load(code)
+            let chunk = lua.load(code)
                 .set_name("run_lua")
-                .set_environment(env)
-                .eval::<mlua::Value>()?;
+                .set_environment(env);
+            let value = crate::script_watchdog::guarded(&script_runtime.watchdog, None, "run_lua", || {
+                chunk.eval::<mlua::Value>()
+            })?;
 
             // Format return value
             let result_str = match &value {
@@ -2933,8 +4857,11 @@ let mut scene_world = scene_world.borrow_mut();
 
             // MeshRenderer
             if let Ok(mr) = scene_world.world.get::<&MeshRenderer>(entity) {
-                let mesh_name = self.mesh_cache.name_for_handle(mr.mesh_handle)
-                    .unwrap_or_else(|| format!("mesh:{}", mr.mesh_handle.0));
+                // The LOD level drawn now isn't the authored mesh
+                let lod = scene_world.world.get::<&crate::lod::MeshLod>(entity).ok();
+                let mesh_handle = lod.as_ref().map_or(mr.mesh_handle, |lod| lod.base);
+                let mesh_name = self.mesh_cache.name_for_handle(mesh_handle)
+                    .unwrap_or_else(|| format!("mesh:{}", mesh_handle.0));
                 let material_name = self.material_cache.name_for_handle(mr.material_handle)
                     .unwrap_or_else(|| format!("material:{}", mr.material_handle.0));
                 components.mesh_renderer = Some(MeshRendererDef {
@@ -2942,33 +4869,45 @@ let mut scene_world = scene_world.borrow_mut();
                     material: material_name,
                     cast_shadows: true,
                     receive_shadows: true,
+                    lod: lod.map(|lod| lod.def.clone()),
                 });
             }
 
             // Camera
             if let Ok(c) = scene_world.world.get::<&Camera>(entity) {
-                let role_str = match &c.role {
-                    CameraRole::Main => "main".to_string(),
-                    CameraRole::Other(s) => s.clone(),
-                };
                 components.camera = Some(CameraDef {
                     fov: c.fov_degrees,
                     near: c.near,
                     far: c.far,
-                    role: role_str,
+                    role: c.role.name(),
                     mode: "first_person".to_string(),
                     distance: 4.0,
                     height_offset: 1.5,
                     pitch_limits: None,
+                    position_damping: 0.0,
+                    rotation_damping: 0.0,
+                    look_ahead: 0.0,
+                    look_ahead_max: 2.0,
+                    projection: c.projection.name().to_string(),
+                    size: match c.projection {
+                        Projection::Orthographic { size } => size,
+                        Projection::Perspective => 10.0,
+                    },
                 });
             }
 
             // PointLight
             if let Ok(pl) = scene_world.world.get::<&PointLight>(entity) {
+                let flicker = scene_world.world.get::<&crate::components::LightFlicker>(entity).ok();
                 components.point_light = Some(PointLightDef {
                     color: pl.color.to_array(),
-                    intensity: pl.intensity,
+                    intensity: flicker.as_ref().map_or(pl.intensity, |f| f.base_intensity),
                     range: pl.range,
+                    casts_shadows: pl.casts_shadows,
+                    flicker: flicker.map(|f| crate::scene::LightFlickerDef {
+                        curve: f.source.clone(),
+                        speed: f.speed,
+                    }),
                 });
             }
 
@@ -2982,6 +4921,18 @@ let mut scene_world = scene_world.borrow_mut();
                 });
             }
 
+            // SpotLight
+            if let Ok(sl) = scene_world.world.get::<&crate::components::SpotLight>(entity) {
+                components.spot_light = Some(crate::scene::SpotLightDef {
+                    color: sl.color.to_array(),
+                    intensity: sl.intensity,
+                    range: sl.range,
+                    direction: sl.direction.to_array(),
+                    inner_angle: sl.inner_angle,
+                    outer_angle: sl.outer_angle,
+                });
+            }
+
             // Tags
             let tags = scene_world.world.get::<&Tags>(entity)
                 .map(|t| t.0.clone())
@@ -2996,6 +4947,7 @@ let mut scene_world = scene_world.borrow_mut();
         }
 
         let scene_file = SceneFile {
+            version: crate::format::SCENE_FORMAT.current,
             name: scene_name,
             settings,
             entities,
@@ -3029,8 +4981,38 @@ let input = input.borrow();
 
         if let (Some(editor_cam), Some(camera_state)) = (&self.editor_camera, &self.camera_state) {
             let mut camera_state = camera_state.borrow_mut();
-            editor_cam.apply_to_camera_state(&mut *camera_state, &gpu.queue, gpu.config.width, gpu.config.height);
+            let (width, height) = self.render_debug.render_size(gpu.config.width, gpu.config.height);
+            editor_cam.apply_to_camera_state(&mut *camera_state, &gpu.queue, width, height);
+        }
+    }
+
+    /// Detach a free-fly debug camera where the main camera is now, or hand
+    /// the view back to the main camera.
+    fn toggle_debug_camera(&mut self) {
+        if self.debug_camera.take().is_some() {
+            tracing::info!("Free camera: OFF");
+            return;
         }
+        let Some(camera_state) = &self.camera_state else {
+            return;
+        };
+        self.debug_camera = Some(EditorCamera::from_uniform(&camera_state.borrow().uniform));
+        tracing::info!("Free camera: ON");
+    }
+
+    /// Fly the debug camera from input and apply it to CameraState. It moves
+    /// by real time, so it keeps flying while the game is paused or slowed.
+    fn update_debug_camera(&mut self) {
+        let (Some(debug_cam), Some(input), Some(gpu), Some(camera_state)) =
+            (&mut self.debug_camera, &self.input_state, &self.gpu, &self.camera_state)
+        else {
+            return;
+        };
+        let mut input = input.borrow_mut();
+        input.compute_cursor_delta();
+        debug_cam.update(&input, self.delta_time);
+        let (width, height) = self.render_debug.render_size(gpu.config.width, gpu.config.height);
+        debug_cam.apply_to_camera_state(&mut camera_state.borrow_mut(), &gpu.queue, width, height);
     }
 
     /// Render a full editor frame: 3D scene + overlay.
@@ -3088,6 +5070,166 @@ let input = input.borrow();
             }
         }
     }
+
+    /// Run without a window: render `frames` frames into an offscreen target
+    /// through the same frame loop as the windowed engine. The first GPU
+    /// validation error aborts the run.
+    pub fn run_headless(&mut self, frames: u64) -> Result<crate::headless::HeadlessRunReport, String> {
+        let initial_wgsl = self.get_initial_shader();
+        let gpu_state = pollster::block_on(crate::renderer::init_headless_gpu(
+            crate::headless::DEFAULT_HEADLESS_WIDTH,
+            crate::headless::DEFAULT_HEADLESS_HEIGHT,
+            &initial_wgsl,
+            &self.args.graphics,
+        ))?;
+
+        self.gpu_profiler = crate::pipeline::GpuPassProfiler::new(&gpu_state.device, &gpu_state.queue);
+        self.gpu = Some(gpu_state);
+        tracing::info!("Headless GPU initialized");
+
+        if self.args.editor_mode {
+            self.init_editor_mode();
+        } else {
+            self.load_scene();
+        }
+
+        let start = std::time::Instant::now();
+        let mut game_time = 0.0;
+        for frame in 0..frames {
+            if let Some(gpu) = &self.gpu {
+                gpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            }
+            self.handle_window_event(None, WindowEvent::RedrawRequested);
+            game_time += self.delta_time;
+            if let Some(gpu) = &self.gpu {
+                if let Some(error) = pollster::block_on(gpu.device.pop_error_scope()) {
+                    return Err(format!("Frame {}: {}", frame, error));
+                }
+            }
+        }
+
+        let elapsed_ms = start.elapsed().as_secs_f32() * 1000.0;
+        Ok(crate::headless::HeadlessRunReport {
+            frames,
+            game_time,
+            avg_frame_ms: if frames > 0 { elapsed_ms / frames as f32 } else { 0.0 },
+        })
+    }
+
+    /// Redraw the game window, and its tool windows along with it.
+    fn request_redraws(&self) {
+        if let Some(gpu) = &self.gpu {
+            gpu.request_redraw();
+        }
+        for tool in &self.tool_windows {
+            tool.window.request_redraw();
+        }
+    }
+
+    /// Create the tool windows requested since the last event loop turn.
+    fn open_pending_tool_windows(&mut self, event_loop: &ActiveEventLoop) {
+        let (Some(gpu), Some(font_rc)) = (&self.gpu, &self.bitmap_font) else { return };
+        for kind in std::mem::take(&mut self.pending_tool_windows) {
+            let title = format!("{} - {}", kind.title(), self.window_title());
+            match crate::tool_window::ToolWindow::new(event_loop, gpu, &font_rc.borrow(), kind, &title) {
+                Ok(window) => {
+                    tracing::info!("Opened {} window", kind.name());
+                    self.tool_windows.push(window);
+                }
+                Err(e) => tracing::warn!("Failed to open {} window: {}", kind.name(), e),
+            }
+        }
+    }
+
+    /// Handle an event of a tool window. Input to tool windows never reaches
+    /// the game.
+    fn tool_window_event(&mut self, window_id: WindowId, event: WindowEvent) {
+        let Some(index) = self.tool_windows.iter().position(|w| w.window.id() == window_id) else {
+            return;
+        };
+        match event {
+            WindowEvent::CloseRequested => {
+                let closed = self.tool_windows.remove(index);
+                tracing::info!("Closed {} window", closed.kind.name());
+            }
+            WindowEvent::Resized(size) => {
+                if let Some(gpu) = &self.gpu {
+                    self.tool_windows[index].resize(&gpu.device, size.width, size.height);
+                }
+            }
+            WindowEvent::Focused(true) => {
+                // Focus moving from the game to one of its tool windows
+                // doesn't count as the game losing focus
+                self.window_activity.focused = true;
+                self.update_suspended();
+            }
+            WindowEvent::RedrawRequested => {
+                let lines = self.tool_window_lines(self.tool_windows[index].kind);
+                if let (Some(gpu), Some(font_rc)) = (&self.gpu, &self.bitmap_font) {
+                    self.tool_windows[index].render(&gpu.device, &gpu.queue, &font_rc.borrow(), &lines);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// What a tool window shows this frame.
+    fn tool_window_lines(&self, kind: crate::tool_window::ToolWindowKind) -> Vec<crate::tool_window::ToolLine> {
+        use crate::tool_window::{ToolLine, ToolWindowKind};
+
+        let hdr = [0.7, 0.7, 0.7, 1.0];
+        let val = [1.0, 0.9, 0.3, 1.0];
+        let white = [1.0, 1.0, 1.0, 0.9];
+        let mut lines = Vec::new();
+        match kind {
+            ToolWindowKind::Inspector => {
+                let Some(sw_rc) = &self.scene_world else {
+                    lines.push(ToolLine::new("No scene loaded", hdr));
+                    return lines;
+                };
+                let sw = sw_rc.borrow();
+                let scene = sw.current_scene.as_ref().map(|s| s.name.as_str()).unwrap_or("Editor Scene");
+                lines.push(ToolLine::new(format!("SCENE: {}", scene), hdr));
+                lines.push(ToolLine::new(format!("Entities: {}", sw.entity_registry.len()), val));
+                let mut ids: Vec<(&String, &hecs::Entity)> = sw.entity_registry.iter().collect();
+                ids.sort_by(|a, b| a.0.cmp(b.0));
+                for (id, &entity) in ids {
+                    let text = match sw.world.get::<&Transform>(entity) {
+                        Ok(t) => format!("{}  ({:.1}, {:.1}, {:.1})", id, t.position.x, t.position.y, t.position.z),
+                        Err(_) => id.clone(),
+                    };
+                    lines.push(ToolLine::new(text, white));
+                }
+            }
+            ToolWindowKind::Profiler => {
+                let ms = self.delta_time * 1000.0;
+                lines.push(ToolLine::new("FRAME", hdr));
+                lines.push(ToolLine::new(format!("{:.2} ms  ({:.0} fps)", ms, 1000.0 / ms.max(0.001)), val));
+                let stats = self.draw_stats;
+                lines.push(ToolLine::new(format!("Meshes: {} drawn, {} culled", stats.drawn, stats.culled), val));
+                lines.push(ToolLine::new(format!("State changes: {}", stats.state_changes), val));
+                lines.push(ToolLine::new(
+                    format!("Shadow casters: {} drawn, {} culled", stats.shadow_drawn, stats.shadow_culled),
+                    val,
+                ));
+                if let Some(script_runtime) = &self.script_runtime {
+                    let mb = script_runtime.lua.used_memory() as f64 / (1024.0 * 1024.0);
+                    lines.push(ToolLine::new(format!("Lua: {:.1} MB", mb), val));
+                }
+                lines.push(ToolLine::new("", hdr));
+                match &self.gpu_profiler {
+                    Some(profiler) => {
+                        lines.push(ToolLine::new(format!("GPU: {:.2} ms", profiler.total_ms()), hdr));
+                        for timing in profiler.timings() {
+                            lines.push(ToolLine::new(format!("  {}: {:.2} ms", timing.name, timing.gpu_ms), val));
+                        }
+                    }
+                    None => lines.push(ToolLine::new("GPU timings: unsupported", hdr)),
+                }
+            }
+        }
+        lines
+    }
 }
 
 impl ApplicationHandler for Engine {
@@ -3099,7 +5241,7 @@ impl ApplicationHandler for Engine {
         tracing::info!("Application resumed, initializing GPU");
 
         let window_attrs = Window::default_attributes()
-            .with_title("nAIVE Engine")
+            .with_title(self.window_title())
             .with_inner_size(winit::dpi::LogicalSize::new(1280, 720));
 
         let window = Arc::new(
@@ -3111,9 +5253,9 @@ impl ApplicationHandler for Engine {
         let initial_wgsl = self.get_initial_shader();
 
         let gpu_state =
-            pollster::block_on(crate::renderer::init_gpu(Arc::clone(&window), &initial_wgsl));
-
-        self.gpu = Some(gpu_state);
+            pollster::block_on(crate::renderer::init_gpu(Arc::clone(&window), &initial_wgsl, &self.args.graphics))
+                .unwrap_or_else(|e| panic!("{}", e));
+        self.install_gpu(gpu_state);
         tracing::info!("GPU initialized successfully");
 
         if self.args.editor_mode {
@@ -3130,7 +5272,72 @@ impl ApplicationHandler for Engine {
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let game_window = self.gpu.as_ref().and_then(|gpu| gpu.window.as_ref());
+        if game_window.is_some_and(|window| window.id() != window_id) {
+            self.tool_window_event(window_id, event);
+            return;
+        }
+        self.handle_window_event(Some(event_loop), event);
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: winit::event::DeviceId,
+        event: winit::event::DeviceEvent,
+    ) {
+        if let Some(input) = &self.input_state {
+            let mut input = input.borrow_mut();
+            input.handle_device_event(&event);
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Nothing draws without a device; wait for the next recovery attempt
+        if self.device_recovery.is_some() {
+            self.retry_device_recovery();
+            if let Some(pending) = &self.device_recovery {
+                event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(pending.retry.next_attempt()));
+                return;
+            }
+        }
+
+        self.open_pending_tool_windows(event_loop);
+
+        if self.render_suspended {
+            // Tick on a timer instead of spinning the event loop for a hidden window
+            let interval_ms = match self.background_mode() {
+                crate::project_config::BackgroundMode::Pause => 100,
+                crate::project_config::BackgroundMode::Simulate => 16,
+            };
+            let now = std::time::Instant::now();
+            let tick = match self.background_tick {
+                Some(tick) if tick > now => tick,
+                _ => {
+                    self.request_redraws();
+                    now + std::time::Duration::from_millis(interval_ms)
+                }
+            };
+            self.background_tick = Some(tick);
+            event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(tick));
+            return;
+        }
+
+        self.background_tick = None;
+        event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
+        self.request_redraws();
+    }
+}
+
+impl Engine {
+    /// Handle a window event. Headless runs drive frames by feeding
+    /// `RedrawRequested` without an event loop.
+    fn handle_window_event(
+        &mut self,
+        event_loop: Option<&ActiveEventLoop>,
         event: WindowEvent,
     ) {
         // Feed all events to input system
@@ -3138,18 +5345,39 @@ impl ApplicationHandler for Engine {
             let mut input = input.borrow_mut();
             input.handle_window_event(&event);
         }
+        if self.window_activity.handle_event(&event) {
+            self.update_suspended();
+        }
 
         match event {
             WindowEvent::CloseRequested => {
                 tracing::info!("Close requested, exiting");
-                event_loop.exit();
+                self.write_autosave();
+                if let Some(event_loop) = event_loop {
+                    event_loop.exit();
+                }
+            }
+            WindowEvent::Focused(focused) => {
+                self.emit_engine_event(
+                    crate::engine_events::WINDOW_FOCUS_CHANGED,
+                    crate::engine_events::payload(serde_json::json!({"focused": focused})),
+                );
             }
             WindowEvent::Resized(new_size) => {
+                if new_size.width > 0 && new_size.height > 0 {
+                    self.emit_engine_event(
+                        crate::engine_events::WINDOW_RESIZED,
+                        crate::engine_events::payload(serde_json::json!({
+                            "width": new_size.width,
+                            "height": new_size.height,
+                        })),
+                    );
+                }
                 if let Some(gpu) = &mut self.gpu {
                     if new_size.width > 0 && new_size.height > 0 {
                         gpu.config.width = new_size.width;
                         gpu.config.height = new_size.height;
-                        gpu.surface.configure(&gpu.device, &gpu.config);
+                        gpu.configure_surface();
 
                         // Recreate depth texture on resize
                         let (depth_texture, depth_view) =
@@ -3161,13 +5389,14 @@ impl ApplicationHandler for Engine {
                         gpu.depth_texture = depth_texture;
                         gpu.depth_view = depth_view;
 
-                        // Phase 3: resize pipeline resources
+                        // Phase 3: resize pipeline resources (scaled by auto_quality)
                         if let Some(compiled) = &mut self.compiled_pipeline {
+                            let (width, height) = self.render_debug.render_size(new_size.width, new_size.height);
                             crate::pipeline::resize_resources(
                                 &gpu.device,
                                 &mut compiled.resources,
-                                new_size.width,
-                                new_size.height,
+                                width,
+                                height,
                             );
                             crate::pipeline::rebuild_bind_groups(&gpu.device, compiled);
                         }
@@ -3175,19 +5404,61 @@ impl ApplicationHandler for Engine {
                 }
             }
             WindowEvent::RedrawRequested => {
+                // Rebuild everything on a new device instead of failing every frame
+                if let Some(reason) = self.device_loss.take() {
+                    self.begin_device_recovery(reason);
+                    return;
+                }
+                // The Steam overlay keeps the window drawing while it is open
+                let overlay_change = self.steam.borrow_mut().poll();
+                if let Some(active) = overlay_change {
+                    self.window_activity.overlay = active;
+                    self.emit_engine_event(
+                        crate::engine_events::STEAM_OVERLAY_CHANGED,
+                        crate::engine_events::payload(serde_json::json!({ "active": active })),
+                    );
+                }
+                // naive.yaml may have changed the suspend settings
+                self.update_suspended();
+                // Paused in the background: keep the command socket and file
+                // watcher alive, but don't advance or draw anything
+                if self.render_suspended && self.background_mode() == crate::project_config::BackgroundMode::Pause {
+                    self.process_commands();
+                    self.update_autosave();
+                    self.poll_changes();
+                    if let Some(input) = &self.input_state {
+                        input.borrow_mut().begin_frame();
+                    }
+                    return;
+                }
+
                 // Calculate delta time
                 let now = instant::Instant::now();
                 if let Some(last) = self.last_frame_time {
                     self.delta_time = now.duration_since(last).as_secs_f32().min(0.1);
                 }
                 self.last_frame_time = Some(now);
+                self.analytics.borrow_mut().update(self.delta_time);
+
+                if let Some(fps) = self.low_fps_monitor.update(self.delta_time) {
+                    tracing::warn!("Low FPS: {:.1}", fps);
+                    let threshold = self.low_fps_monitor.threshold;
+                    self.emit_engine_event(
+                        crate::engine_events::LOW_FPS,
+                        crate::engine_events::payload(serde_json::json!({"fps": fps, "threshold": threshold})),
+                    );
+                }
+                self.update_quality_governor();
 
                 // Phase 8: Process command socket before input
                 self.process_commands();
+                self.update_autosave();
 
                 // ── Editor mode: update free camera (runs full loop below) ─
                 if self.args.editor_mode {
                     self.update_editor_camera();
+                } else if self.debug_camera.is_some() {
+                    self.update_debug_camera();
                 }
 
                 // Handle Escape to toggle cursor capture (skip in editor mode)
@@ -3195,9 +5466,9 @@ impl ApplicationHandler for Engine {
                     if let Some(input_rc) = &self.input_state {
                         let mut input = input_rc.borrow_mut();
                         if input.key_held(KeyCode::Escape) {
-                            if let Some(gpu) = &self.gpu {
-                                let _ = gpu.window.set_cursor_grab(winit::window::CursorGrabMode::None);
-                                gpu.window.set_cursor_visible(true);
+                            if let Some(window) = self.gpu.as_ref().and_then(|gpu| gpu.window.as_ref()) {
+                                let _ = window.set_cursor_grab(winit::window::CursorGrabMode::None);
+                                window.set_cursor_visible(true);
                             }
                             input.cursor_captured = false;
                         }
@@ -3213,10 +5484,10 @@ impl ApplicationHandler for Engine {
                                 || input.just_pressed("interact");
                             if should_capture {
                                 tracing::info!("Capturing cursor for FPS mode");
-                                if let Some(gpu) = &self.gpu {
-                                    let _ = gpu.window.set_cursor_grab(winit::window::CursorGrabMode::Locked)
-                                        .or_else(|_| gpu.window.set_cursor_grab(winit::window::CursorGrabMode::Confined));
-                                    gpu.window.set_cursor_visible(false);
+                                if let Some(window) = self.gpu.as_ref().and_then(|gpu| gpu.window.as_ref()) {
+                                    let _ = window.set_cursor_grab(winit::window::CursorGrabMode::Locked)
+                                        .or_else(|_| window.set_cursor_grab(winit::window::CursorGrabMode::Confined));
+                                    window.set_cursor_visible(false);
                                 }
                                 input.cursor_captured = true;
                             }
@@ -3224,7 +5495,8 @@ impl ApplicationHandler for Engine {
                     }
                 }
 
-                // Render debug toggles: 0 always toggles HUD, 1-6 only when HUD is visible
+                // Render debug toggles: 0 always toggles HUD, 1-7 only when HUD is visible
+                let mut toggle_debug_camera = false;
                 if let Some(input) = &self.input_state {
                     let input = input.borrow_mut();
                     if input.just_pressed_key(KeyCode::Digit0) {
@@ -3271,27 +5543,47 @@ impl ApplicationHandler for Engine {
                             };
                             tracing::info!("Ambient override: {}", self.render_debug.ambient_override);
                         }
+                        toggle_debug_camera = input.just_pressed_key(KeyCode::Digit7) && !self.args.editor_mode;
                     }
                 }
+                if toggle_debug_camera {
+                    self.toggle_debug_camera();
+                }
 
                 // Poll for file changes (shader + scene + pipeline)
                 self.poll_changes();
 
                 if self.scene_world.is_some() {
                     if !self.paused {
+                        // Scaled game time for this frame (time.set_scale)
+                        let dt = self.game_clock.borrow_mut().advance(self.delta_time);
+                        if let Some(script_runtime) = &self.script_runtime {
+                            script_runtime.watchdog.borrow_mut().begin_frame();
+                        }
+
                         // Phase 5: FPS controller update (skip in editor mode — uses free camera)
                         if !self.args.editor_mode {
-                            if self.input_state.as_ref().map(|i| i.borrow().cursor_captured).unwrap_or(false) {
+                            let captured = self.input_state.as_ref().map(|i| i.borrow().cursor_captured).unwrap_or(false);
+                            // A cutscene or camera path that locks the player takes over their
+                            // controls, and the debug camera takes the movement keys
+                            let locked = self.sequences.borrow().locks_player()
+                                || self.camera_paths.borrow().locks_player()
+                                || self.debug_camera.is_some();
+                            if captured && !locked {
                                 self.update_fps_controller();
                             }
                         }
 
+                        // Flocking agents steer before physics moves their bodies
+                        self.update_crowds();
+
                         // Always step physics (gravity, collisions, etc.)
                         if let (Some(scene_world), Some(physics_world)) =
                             (&self.scene_world, &self.physics_world)
                         {
                             let mut pw = physics_world.borrow_mut();
-                            pw.step(self.delta_time);
+                            pw.sync_water_volumes(&scene_world.borrow().world);
+                            pw.advance(self.game_clock.borrow().physics_dt());
                             let mut sw = scene_world.borrow_mut();
                             pw.sync_to_ecs(&mut sw.world);
                         }
@@ -3316,32 +5608,67 @@ impl ApplicationHandler for Engine {
                                 script_runtime.call_on_collision(event.entity_a, id_b);
                                 script_runtime.call_on_collision(event.entity_b, id_a);
                             }
+
+                            // Trigger hooks may damage or move entities, so release borrows first
+                            let trigger_calls: Vec<_> = pw.trigger_events.iter()
+                                .flat_map(|event| {
+                                    let trigger_id = entity_to_id.get(&event.trigger_entity).copied().unwrap_or("unknown").to_string();
+                                    let other_id = entity_to_id.get(&event.other_entity).copied().unwrap_or("unknown").to_string();
+                                    [(event.trigger_entity, other_id, event.phase), (event.other_entity, trigger_id, event.phase)]
+                                })
+                                .collect();
+                            drop(pw);
+                            drop(sw);
+                            for (entity, other_id, phase) in trigger_calls {
+                                match phase {
+                                    crate::physics::TriggerPhase::Enter => script_runtime.call_on_trigger_enter(entity, &other_id),
+                                    crate::physics::TriggerPhase::Stay { elapsed } => script_runtime.call_on_trigger_stay(entity, &other_id, elapsed),
+                                    crate::physics::TriggerPhase::Exit => script_runtime.call_on_trigger_exit(entity, &other_id),
+                                }
+                            }
                         }
 
                         // Tier 1: Process collision damage (auto-damage + projectile hits)
                         self.process_collision_damage();
 
+                        // Collisions that started this step are noises AI can hear
+                        self.emit_impact_noises();
+
                         // Tier 1: Update projectiles (age tracking, lifetime expiry)
                         self.update_projectiles();
 
+                        // Tier 1: Despawn entities whose lifetime rules fired
+                        self.update_lifetimes();
+
                         // Tier 1: Process health system (on_death callbacks)
                         self.process_health_system();
 
-                        // Phase 6: Update scripts
-                        let dt = self.delta_time;
+                        // Publish this frame's noises before scripts query them
+                        self.publish_noises(dt);
+
+                        // Phase 6: Update scripts (each with its own time-scaled dt)
                         if let (Some(scene_world), Some(script_runtime)) =
                             (&self.scene_world, &self.script_runtime)
                         {
-                            let scripted: Vec<hecs::Entity> = {
+                            let scripted: Vec<(hecs::Entity, f32)> = {
                                 let sw = scene_world.borrow();
                                 let mut query = sw.world.query::<&Script>();
-                                query.iter().map(|(e, _)| e).collect()
+                                query.iter()
+                                    .map(|(e, _)| (e, crate::time_scale::entity_dt(&sw.world, e, dt)))
+                                    .collect()
                             };
-                            for entity in scripted {
-                                script_runtime.call_update(entity, dt);
+                            for (entity, entity_dt) in scripted {
+                                script_runtime.call_update(entity, entity_dt);
                             }
+
+                            // Deliver directed messages and resolve requests
+                            crate::messaging::dispatch_messages(script_runtime, &self.message_queue, scene_world, dt);
                         }
 
+                        // Advance the playing cutscene and apply its cues
+                        self.update_sequence(dt);
+                        self.update_camera_path(dt);
+
                         // Tick skeletal animations
                         self.tick_animations();
 
@@ -3351,6 +5678,9 @@ impl ApplicationHandler for Engine {
                         // Process deferred scene load (must be after entity commands)
                         self.process_pending_scene_load();
 
+                        // Swap the render pipeline a script switched to
+                        self.process_pending_pipeline_switch();
+
                         // Tier 2: Dispatch Lua event listeners
                         self.event_bus.borrow_mut().tick(dt as f64);
                         let flushed_events = self.event_bus.borrow_mut().flush();
@@ -3362,43 +5692,56 @@ impl ApplicationHandler for Engine {
                                     .unwrap_or_default();
                                 for func_result in listener_keys {
                                     if let Ok(func) = func_result {
-                                        let lua = &script_runtime.lua;
-                                        if let Ok(tbl) = lua.create_table() {
-                                            let _ = tbl.set("type", event.event_type.clone());
-                                            if let Ok(data_tbl) = lua.create_table() {
-                                                for (k, v) in &event.data {
-                                                    match v {
-                                                        serde_json::Value::Number(n) => {
-                                                            if let Some(f) = n.as_f64() {
-                                                                let _ = data_tbl.set(k.as_str(), f);
-                                                            }
-                                                        }
-                                                        serde_json::Value::String(s) => {
-                                                            let _ = data_tbl.set(k.as_str(), s.as_str());
-                                                        }
-                                                        serde_json::Value::Bool(b) => {
-                                                            let _ = data_tbl.set(k.as_str(), *b);
-                                                        }
-                                                        _ => {}
-                                                    }
+                                        // Build the table first so the bus borrow ends before the listener runs
+                                        let tbl = crate::scripting::event_to_lua_table(
+                                            &script_runtime.lua,
+                                            event,
+                                            &self.event_bus.borrow(),
+                                        );
+                                        match tbl {
+                                            Ok(tbl) => {
+                                                let call = || func.call::<()>(tbl);
+                                                if let Err(e) = crate::script_watchdog::guarded(&script_runtime.watchdog, None, "event listener", call) {
+                                                    tracing::error!("Lua event listener error: {}", e);
                                                 }
-                                                let _ = tbl.set("data", data_tbl);
-                                            }
-                                            if let Err(e) = func.call::<()>(tbl) {
-                                                tracing::error!("Lua event listener error: {}", e);
                                             }
+                                            Err(e) => tracing::error!("Failed to build event table: {}", e),
                                         }
                                     }
                                 }
                             }
                         }
-                        let _tween_results = self.tween_system.update(dt);
+                        // Surface scripts the watchdog aborted this frame
+                        self.process_watchdog_events();
+                        // Frame-budgeted Lua GC and memory warnings
+                        self.process_script_memory();
+
+                        if let Some(scene_world) = &self.scene_world {
+                            let sw = scene_world.borrow();
+                            let _tween_results = self.tween_system.update_scaled(dt, |e| {
+                                crate::time_scale::entity_dt(&sw.world, e, 1.0)
+                            });
+                        }
+                        if let Some(scene_world) = &self.scene_world {
+                            crate::curves::update_light_flicker(&mut scene_world.borrow_mut().world, dt);
+                        }
+                        self.audio_system.borrow_mut().update_fades(dt);
                         self.audio_system.borrow_mut().cleanup();
+                        let device_change = self.audio_system.borrow_mut().poll_devices();
+                        if let Some(device) = device_change {
+                            self.emit_engine_event(
+                                crate::engine_events::AUDIO_DEVICE_CHANGED,
+                                crate::engine_events::payload(serde_json::json!({ "device": device })),
+                            );
+                        }
 
                         // Tier 2: Update particle system
                         if let Some(scene_world) = &self.scene_world {
                             let scene_world = scene_world.borrow();
-                            self.particle_system.borrow_mut().update(dt, &*scene_world);
+                            let physics = self.physics_world.as_ref().map(|pw| pw.borrow());
+                            let mut particles = self.particle_system.borrow_mut();
+                            particles.budget = self.render_debug.particle_budget;
+                            particles.update(dt, &*scene_world, physics.as_deref());
                         }
 
                         // Update listener position for spatial audio
@@ -3421,39 +5764,60 @@ impl ApplicationHandler for Engine {
                     // Editor mode: camera already updated above via update_editor_camera()
                     if !self.args.editor_mode {
                         self.update_camera();
+                        self.update_player_views();
                     }
 
-                    // Tier 2: Grow GPU draw buffer if needed
-                    if let (Some(gpu), Some(scene_world), Some(draw_pool)) =
-                        (&self.gpu, &self.scene_world, &mut self.draw_pool)
-                    {
+                    // Swap LOD meshes for this frame's camera
+                    if let (Some(scene_world), Some(camera_state)) = (&self.scene_world, &self.camera_state) {
+                        crate::lod::update_lods(
+                            &scene_world.borrow().world,
+                            &camera_state.borrow().uniform,
+                            self.render_debug.lod_lock.force_lod0,
+                        );
+                    }
+
+                    // Upload splat transforms, opacity and crops for the splat passes
+                    if let (Some(gpu), Some(scene_world)) = (&self.gpu, &self.scene_world) {
                         let sw = scene_world.borrow();
-                        let mut visible_count = 0u32;
-                        for (entity, _) in sw.world.query::<&crate::components::MeshRenderer>().iter() {
-                            if sw.world.get::<&crate::components::Hidden>(entity).is_ok() {
-                                continue;
-                            }
-                            visible_count += 1;
-                        }
-                        draw_pool.ensure_capacity(&gpu.device, visible_count);
+                        self.splat_cache.prepare_frame(&sw.world, &gpu.device, &gpu.queue);
+                    }
+
+                    // Hooks first: registering one can recompile the pipeline
+                    self.sync_portals();
+                    self.sync_terrain();
+                    self.sync_foliage();
+                    self.sync_resource_aliasing();
+
+                    // Upload live particles, the alpha-blended ones sorted back-to-front
+                    if let (Some(gpu), Some(camera_state), Some(particles)) = (
+                        &self.gpu,
+                        &self.camera_state,
+                        self.compiled_pipeline.as_ref().and_then(|c| c.particles.as_ref()),
+                    ) {
+                        let camera_position = glam::Vec3::from(camera_state.borrow().uniform.position);
+                        let (instances, alpha_count) = self.particle_system.borrow().collect_instances(camera_position);
+                        particles.upload(&gpu.queue, &instances, alpha_count);
                     }
 
-                    // Sort splats for correct alpha blending (CPU back-to-front)
-                    if let (Some(gpu), Some(scene_world), Some(camera_state)) =
-                        (&self.gpu, &self.scene_world, &self.camera_state)
+                    // Bind the scene's skybox (prefiltered once per source) and color grading
+                    if let (Some(gpu), Some(scene_world), Some(compiled)) =
+                        (&self.gpu, &self.scene_world, &mut self.compiled_pipeline)
                     {
-                        let cs = camera_state.borrow();
-                        let view_matrix = cs.view_matrix();
                         let sw = scene_world.borrow();
-                        for (_entity, splat) in
-                            sw.world.query::<&GaussianSplat>().iter()
-                        {
-                            self.splat_cache.sort_splats(
-                                splat.splat_handle,
-                                &view_matrix,
-                                &gpu.queue,
-                            );
-                        }
+                        self.environment_cache.sync(
+                            &gpu.device,
+                            &gpu.queue,
+                            &self.project_root,
+                            &sw.world,
+                            compiled,
+                        );
+                        self.color_grading_cache.sync(
+                            &gpu.device,
+                            &gpu.queue,
+                            &self.project_root,
+                            sw.current_scene.as_ref().and_then(|s| s.settings.color_grading.as_ref()),
+                            compiled,
+                        );
                     }
 
                     // Queue editor overlay draw commands (before gpu borrow)
@@ -3461,25 +5825,19 @@ impl ApplicationHandler for Engine {
                         self.draw_editor_overlay();
                     }
 
-                    // Acquire swapchain and render 3D scene + UI overlay
-                    if let Some(gpu) = &self.gpu {
-                        let output = match gpu.surface.get_current_texture() {
-                            Ok(t) => t,
-                            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                                gpu.surface.configure(&gpu.device, &gpu.config);
-                                if let Some(gpu) = &self.gpu {
-                                    gpu.window.request_redraw();
-                                }
-                                if let Some(input) = &self.input_state {
-                                    let mut input = input.borrow_mut();
-                                    input.begin_frame();
-                                }
-                                return;
+                    // Acquire swapchain and render 3D scene + UI overlay (not while
+                    // suspended: some drivers block or fail on hidden swapchains)
+                    if let Some(gpu) = self.gpu.as_ref().filter(|_| !self.render_suspended) {
+                        let output = match gpu.get_current_texture() {
+                            Ok(t) => {
+                                self.surface_recovery.succeeded();
+                                t
                             }
                             Err(e) => {
-                                tracing::error!("Surface error: {:?}", e);
+                                let action = self.surface_recovery.failed(&e);
+                                self.recover_surface(action);
                                 if let Some(gpu) = &self.gpu {
-                                    gpu.window.request_redraw();
+                                    gpu.request_redraw();
                                 }
                                 if let Some(input) = &self.input_state {
                                     let mut input = input.borrow_mut();
@@ -3489,10 +5847,28 @@ impl ApplicationHandler for Engine {
                             }
                         };
 
-                        let swapchain_view = output
-                            .texture
+                        // A surface without COPY_SRC can't be read back, so frames
+                        // with a screenshot due render into a capture texture instead
+                        let screenshot_due = !self.screenshot_queue.borrow().is_empty()
+                            || self.args.screenshot_after == Some(self.frames_presented + 1);
+                        let capture_texture = (screenshot_due
+                            && !gpu.config.usage.contains(wgpu::TextureUsages::COPY_SRC))
+                            .then(|| gpu.create_capture_texture());
+                        let frame_texture = capture_texture.as_ref().unwrap_or(&output.texture);
+
+                        let swapchain_view = frame_texture
                             .create_view(&wgpu::TextureViewDescriptor::default());
 
+                        // Move the draw uniform ring on to this frame's region. It only
+                        // grows here, with every entity of the frame counted.
+                        if let (Some(scene_world), Some(draw_pool)) = (&self.scene_world, &mut self.draw_pool) {
+                            let draws = crate::renderer::collect_frame_draws(&scene_world.borrow(), &self.mesh_cache);
+                            draw_pool.begin_frame(&gpu.device, draws);
+                        }
+
+                        // 3D, debug and UI command buffers go out in one submit
+                        let mut command_buffers: Vec<wgpu::CommandBuffer> = Vec::new();
+
                         // Render 3D scene
                         if self.compiled_pipeline.is_some() {
                             if let (
@@ -3506,24 +5882,105 @@ impl ApplicationHandler for Engine {
                                 &self.draw_pool,
                                 &self.compiled_pipeline,
                             ) {
+                                for (name, value) in self.render_tweaks.borrow_mut().drain(..) {
+                                    if let Err(e) = self.render_debug.set_param(&name, value) {
+                                        tracing::warn!("render.set: {}", e);
+                                    }
+                                }
+                                self.pass_params.borrow_mut().apply(&gpu.queue, compiled);
                                 let sw = scene_world.borrow();
                                 let cs = camera_state.borrow();
-                                let encoder = crate::pipeline::execute_pipeline_to_view(
-                                    gpu,
-                                    compiled,
-                                    &*sw,
-                                    &*cs,
-                                    draw_pool,
-                                    &self.mesh_cache,
-                                    &self.material_cache,
-                                    &self.splat_cache,
-                                    &swapchain_view,
-                                    &self.render_debug,
-                                    self.texture_resources.as_ref(),
-                                    &self.bone_palettes,
-                                    Some(&self.texture_cache),
-                                );
-                                gpu.queue.submit(std::iter::once(encoder.finish()));
+                                if let Some(profiler) = self.gpu_profiler.as_mut() {
+                                    profiler.begin_frame(&gpu.device);
+                                }
+                                // Portal views read this frame's draw uniforms, which the
+                                // pipeline uploads before the shared submit.
+                                if let Some(portals) = &self.portal_renderer {
+                                    let mut encoder = gpu.device.create_command_encoder(
+                                        &wgpu::CommandEncoderDescriptor {
+                                            label: Some("Portal Views Encoder"),
+                                        },
+                                    );
+                                    portals.borrow_mut().render_views(
+                                        &gpu.device,
+                                        &gpu.queue,
+                                        &mut encoder,
+                                        &*sw,
+                                        &*cs,
+                                        draw_pool,
+                                        &self.mesh_cache,
+                                        &self.material_cache,
+                                        self.texture_resources.as_ref(),
+                                    );
+                                    command_buffers.push(encoder.finish());
+                                }
+                                if self.player_views.is_empty() {
+                                    let frame = crate::pipeline::execute_pipeline_to_view(
+                                        &gpu.device,
+                                        &gpu.queue,
+                                        compiled,
+                                        &*sw,
+                                        &*cs,
+                                        draw_pool,
+                                        &self.mesh_cache,
+                                        &self.material_cache,
+                                        &self.splat_cache,
+                                        &swapchain_view,
+                                        &self.render_debug,
+                                        self.texture_resources.as_ref(),
+                                        &self.bone_palettes,
+                                        self.gpu_profiler.as_mut(),
+                                        Some(&mut self.render_hooks),
+                                    );
+                                    self.draw_stats = frame.draw_stats;
+                                    command_buffers.extend(frame.finish());
+                                } else {
+                                    // Split screen: the pipeline runs once per player into its
+                                    // region. Each view is its own submit, because the frame's
+                                    // shared uploads (lights, culling, sorted particles and
+                                    // splats) are rewritten per view.
+                                    let mut encoder = gpu.device.create_command_encoder(
+                                        &wgpu::CommandEncoderDescriptor {
+                                            label: Some("Split Screen Clear Encoder"),
+                                        },
+                                    );
+                                    crate::split_screen::clear_target(&mut encoder, &swapchain_view);
+                                    command_buffers.push(encoder.finish());
+                                    gpu.queue.submit(command_buffers.drain(..));
+                                    for (i, view) in self.player_views.iter().enumerate() {
+                                        // Particles sort back-to-front for this player's camera;
+                                        // splats sort in the pipeline's compute encoder
+                                        if let Some(particles) = &compiled.particles {
+                                            let camera_position = glam::Vec3::from(view.camera_state.uniform.position);
+                                            let (instances, alpha_count) =
+                                                self.particle_system.borrow().collect_instances(camera_position);
+                                            particles.upload(&gpu.queue, &instances, alpha_count);
+                                        }
+                                        // Pass timings cover the first view only
+                                        let profiler = if i == 0 { self.gpu_profiler.as_mut() } else { None };
+                                        let frame = crate::pipeline::execute_pipeline_to_view(
+                                            &gpu.device,
+                                            &gpu.queue,
+                                            compiled,
+                                            &*sw,
+                                            &view.camera_state,
+                                            draw_pool,
+                                            &self.mesh_cache,
+                                            &self.material_cache,
+                                            &self.splat_cache,
+                                            &swapchain_view,
+                                            &self.render_debug,
+                                            self.texture_resources.as_ref(),
+                                            &self.bone_palettes,
+                                            profiler,
+                                            Some(&mut self.render_hooks),
+                                        );
+                                        if i == 0 {
+                                            self.draw_stats = frame.draw_stats;
+                                        }
+                                        gpu.queue.submit(frame.finish());
+                                    }
+                                }
                             }
                         } else if let (
                             Some(scene_world),
@@ -3544,7 +6001,7 @@ impl ApplicationHandler for Engine {
                             let sw = scene_world.borrow();
                             let cs = camera_state.borrow();
                             crate::renderer::render_scene_to_view(
-                                gpu,
+                                &gpu.queue,
                                 &*sw,
                                 &*cs,
                                 draw_pool,
@@ -3552,10 +6009,11 @@ impl ApplicationHandler for Engine {
                                 &self.material_cache,
                                 forward_pipeline,
                                 &swapchain_view,
+                                &gpu.depth_view,
                                 &mut encoder,
                                 self.texture_resources.as_ref(),
                             );
-                            gpu.queue.submit(std::iter::once(encoder.finish()));
+                            command_buffers.push(encoder.finish());
                         }
 
                         // Debug wireframe pass: draw collider shapes over 3D scene
@@ -3578,7 +6036,7 @@ impl ApplicationHandler for Engine {
                                     &*cs,
                                     &*pw,
                                 );
-                                gpu.queue.submit(std::iter::once(encoder.finish()));
+                                command_buffers.push(encoder.finish());
                             }
                         }
 
@@ -3590,6 +6048,29 @@ impl ApplicationHandler for Engine {
                             let mut ui = ui_rc.borrow_mut();
                             let font_guard = font_rc.borrow();
                             let font: &BitmapFont = &*font_guard;
+                            // Cutscene letterbox bars and subtitles, under the HUD
+                            {
+                                let sequences = self.sequences.borrow();
+                                let (w, h) = (gpu.config.width as f32, gpu.config.height as f32);
+                                let bar = sequences.letterbox() * h;
+                                if bar > 0.0 {
+                                    ui.draw_rect(0.0, 0.0, w, bar, [0.0, 0.0, 0.0, 1.0]);
+                                    ui.draw_rect(0.0, h - bar, w, bar, [0.0, 0.0, 0.0, 1.0]);
+                                }
+                                if let Some(subtitle) = sequences.subtitle() {
+                                    let text = match &subtitle.speaker {
+                                        Some(speaker) => format!("{}: {}", speaker, subtitle.text),
+                                        None => subtitle.text.clone(),
+                                    };
+                                    let sz = (h / 30.0).max(16.0);
+                                    let text_w = text.chars().count() as f32 * font.glyph_w * (sz / font.glyph_h);
+                                    // Centred in the bottom bar, or near the bottom edge without one
+                                    let band = bar.max(h * 0.08);
+                                    let y = h - band + (band - sz) * 0.5;
+                                    ui.draw_rect((w - text_w) * 0.5 - 8.0, y - 4.0, text_w + 16.0, sz + 8.0, [0.0, 0.0, 0.0, 0.6]);
+                                    ui.draw_text((w - text_w) * 0.5, y, &text, sz, [1.0, 1.0, 1.0, 1.0], font);
+                                }
+                            }
                             // Queue render debug HUD if enabled
                             if self.render_debug.show_hud {
                                 let on = [0.3, 1.0, 0.3, 1.0];
@@ -3611,9 +6092,43 @@ impl ApplicationHandler for Engine {
                                 let c = if self.render_debug.torch_flicker_enabled { on } else { off };
                                 ui.draw_text(x, y, &format!("[5] Torch Flicker: {}", if self.render_debug.torch_flicker_enabled { "ON" } else { "OFF" }), sz, c, font); y += sz + 2.0;
                                 ui.draw_text(x, y, &format!("[6] Ambient: {}", if self.render_debug.ambient_override < 0.1 { "scene".to_string() } else { format!("{:.1}", self.render_debug.ambient_override) }), sz, val, font); y += sz + 2.0;
+                                let c = if self.debug_camera.is_some() { on } else { off };
+                                ui.draw_text(x, y, &format!("[7] Free Camera: {}", if self.debug_camera.is_some() { "ON" } else { "OFF" }), sz, c, font); y += sz + 2.0;
                                 ui.draw_text(x, y, "[0] Toggle this HUD", sz, hdr, font); y += sz + 2.0;
                                 let c = if self.render_debug.show_colliders { on } else { off };
-                                ui.draw_text(x, y, &format!("[H] Colliders: {}", if self.render_debug.show_colliders { "ON" } else { "OFF" }), sz, c, font);
+                                ui.draw_text(x, y, &format!("[H] Colliders: {}", if self.render_debug.show_colliders { "ON" } else { "OFF" }), sz, c, font); y += sz + 2.0;
+                                let stats = self.draw_stats;
+                                let meshes = if stats.gpu_culling {
+                                    format!("Meshes: {} submitted, culled on GPU", stats.drawn)
+                                } else {
+                                    format!("Meshes: {} drawn, {} culled", stats.drawn, stats.culled)
+                                };
+                                ui.draw_text(x, y, &meshes, sz, val, font); y += sz + 2.0;
+                                ui.draw_text(x, y, &format!("State changes: {}", stats.state_changes), sz, val, font); y += sz + 2.0;
+                                ui.draw_text(x, y, &format!("Shadow casters: {} drawn, {} culled", stats.shadow_drawn, stats.shadow_culled), sz, val, font); y += sz + 2.0;
+
+                                if let Some(script_runtime) = &self.script_runtime {
+                                    let disabled = script_runtime.watchdog.borrow().disabled_count();
+                                    let c = if disabled > 0 { off } else { on };
+                                    ui.draw_text(x, y, &format!("Scripts disabled: {}", disabled), sz, c, font); y += sz + 2.0;
+                                    let used = script_runtime.lua.used_memory();
+                                    let watchdog = script_runtime.watchdog.borrow();
+                                    let c = if watchdog.memory.is_high(used) { off } else { val };
+                                    let mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+                                    ui.draw_text(x, y, &format!("Lua: {:.1}/{:.0} MB  GC {:.2} ms", mb(used), mb(watchdog.memory.limit), watchdog.memory.last_gc_ms), sz, c, font); y += sz + 2.0;
+                                }
+
+                                // Per-pass GPU timings
+                                y += 6.0;
+                                match &self.gpu_profiler {
+                                    Some(profiler) => {
+                                        ui.draw_text(x, y, &format!("GPU: {:.2} ms", profiler.total_ms()), sz, hdr, font); y += sz + 2.0;
+                                        for timing in profiler.timings() {
+                                            ui.draw_text(x, y, &format!("  {}: {:.2} ms", timing.name, timing.gpu_ms), sz, val, font); y += sz + 2.0;
+                                        }
+                                    }
+                                    None => ui.draw_text(x, y, "GPU timings: unsupported", sz, off, font),
+                                }
                             }
 
                             // Always show collider indicator when active
@@ -3635,6 +6150,17 @@ impl ApplicationHandler for Engine {
                                 ui.draw_text(x.max(10.0), y, msg, 14.0, c, font);
                             }
 
+                            // Pipeline/shader error (stays until a compile succeeds)
+                            if let Some(error) = &self.pipeline_error {
+                                let lines: Vec<&str> = error.lines().take(24).collect();
+                                let panel_h = lines.len() as f32 * 16.0 + 34.0;
+                                ui.draw_rect(0.0, 0.0, screen_w, panel_h, [0.25, 0.0, 0.0, 0.85]);
+                                ui.draw_text(10.0, 8.0, "RENDER PIPELINE ERROR (save the file to retry)", 16.0, [1.0, 0.4, 0.4, 1.0], font);
+                                for (i, line) in lines.iter().enumerate() {
+                                    ui.draw_text(10.0, 30.0 + i as f32 * 16.0, line, 14.0, [1.0, 0.9, 0.9, 1.0], font);
+                                }
+                            }
+
                             let mut ui_encoder = gpu.device.create_command_encoder(
                                 &wgpu::CommandEncoderDescriptor {
                                     label: Some("UI Encoder"),
@@ -3650,20 +6176,79 @@ impl ApplicationHandler for Engine {
                                 gpu.config.height,
                                 self.delta_time,
                             );
-                            gpu.queue.submit(std::iter::once(ui_encoder.finish()));
+                            command_buffers.push(ui_encoder.finish());
+                        }
+
+                        gpu.queue.submit(command_buffers);
+                        if let Some(profiler) = self.gpu_profiler.as_mut() {
+                            profiler.after_submit();
+                        }
+
+                        // Screenshots: copy the finished frame (3D + UI) before presenting
+                        self.frames_presented += 1;
+                        if self.args.screenshot_after == Some(self.frames_presented) {
+                            self.screenshot_queue.borrow_mut().push(PathBuf::from(&self.args.screenshot_path));
+                        }
+                        let requested = std::mem::take(&mut *self.screenshot_queue.borrow_mut());
+                        if !requested.is_empty() {
+                            let pixels = crate::screenshot::read_texture_rgba8(
+                                &gpu.device,
+                                &gpu.queue,
+                                frame_texture,
+                                gpu.config.width,
+                                gpu.config.height,
+                                gpu.config.format,
+                            );
+                            for out in requested {
+                                let result = pixels.as_ref().map_err(|e| e.clone()).and_then(|px| {
+                                    crate::screenshot::write_png(&out, px, gpu.config.width, gpu.config.height)
+                                });
+                                match result {
+                                    Ok(()) => {
+                                        tracing::info!("Screenshot saved to {}", out.display());
+                                        self.reload_notifications.push((format!("Screenshot: {}", out.display()), instant::Instant::now(), [0.3, 1.0, 0.3, 1.0]));
+                                    }
+                                    Err(e) => {
+                                        tracing::error!("Screenshot failed: {}", e);
+                                        self.reload_notifications.push((format!("Screenshot failed: {}", e), instant::Instant::now(), [1.0, 0.3, 0.3, 1.0]));
+                                    }
+                                }
+                            }
                         }
 
-                        output.present();
+                        match &capture_texture {
+                            None => output.present(),
+                            // Show the captured frame if the surface accepts copies;
+                            // otherwise drop it and keep the previous frame on screen
+                            Some(capture) if gpu.config.usage.contains(wgpu::TextureUsages::COPY_DST) => {
+                                let mut encoder = gpu.device.create_command_encoder(
+                                    &wgpu::CommandEncoderDescriptor { label: Some("Screenshot Present Encoder") },
+                                );
+                                encoder.copy_texture_to_texture(
+                                    capture.as_image_copy(),
+                                    output.texture.as_image_copy(),
+                                    wgpu::Extent3d {
+                                        width: gpu.config.width,
+                                        height: gpu.config.height,
+                                        depth_or_array_layers: 1,
+                                    },
+                                );
+                                gpu.queue.submit(std::iter::once(encoder.finish()));
+                                output.present();
+                            }
+                            Some(_) => {}
+                        }
                     }
 
-                    if let Some(gpu) = &self.gpu {
-                        gpu.window.request_redraw();
+                    // While suspended, about_to_wait schedules frames on a timer
+                    if let Some(gpu) = self.gpu.as_ref().filter(|_| !self.render_suspended) {
+                        gpu.request_redraw();
                     }
                 } else {
                     // Phase 1: triangle fallback
-                    if let Some(gpu) = &self.gpu {
+                    if let Some(gpu) = self.gpu.as_ref().filter(|_| !self.render_suspended) {
                         crate::renderer::render(gpu);
-                        gpu.window.request_redraw();
+                        gpu.request_redraw();
                     }
                 }
 
@@ -3676,22 +6261,4 @@ impl ApplicationHandler for Engine {
             _ => {}
         }
     }
-
-    fn device_event(
-        &mut self,
-        _event_loop: &ActiveEventLoop,
-        _device_id: winit::event::DeviceId,
-        event: winit::event::DeviceEvent,
-    ) {
-        if let Some(input) = &self.input_state {
-            let mut input = input.borrow_mut();
-            input.handle_device_event(&event);
-        }
-    }
-
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(gpu) = &self.gpu {
-            gpu.window.request_redraw();
-        }
-    }
 }
@@ -0,0 +1,251 @@
+//! Built-in events the engine emits on the event bus.
+//!
+//! Scripts subscribe with `events.on(...)` and tools with `query_events`, the
+//! same as game events, so nothing needs to poll engine state.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::events::{EventField, EventFieldSchema, EventFieldType, EventSchema};
+
+/// `{ focused: bool }`
+pub const WINDOW_FOCUS_CHANGED: &str = "window.focus_changed";
+/// `{ width: number, height: number }`
+pub const WINDOW_RESIZED: &str = "window.resized";
+/// `{ suspended: bool, reason: string }`
+pub const WINDOW_SUSPENDED: &str = "window.suspended";
+/// `{ scene: string }`
+pub const SCENE_LOADED: &str = "lifecycle.scene_loaded";
+/// `{ scene: string }`
+pub const SCENE_RELOADED: &str = "lifecycle.scene_reloaded";
+/// `{ entity_id: string }`
+pub const ENTITY_SPAWNED: &str = "lifecycle.entity_spawned";
+/// `{ entity_id: string }`
+pub const ENTITY_DESTROYED: &str = "lifecycle.entity_destroyed";
+/// `{ path: string, kind: string, ok: bool, error: string }`
+pub const ASSET_RELOADED: &str = "asset.reloaded";
+/// `{ path: string, ok: bool, error: string }`
+pub const PIPELINE_RECOMPILED: &str = "pipeline.recompiled";
+/// `{ fps: number, threshold: number }`
+pub const LOW_FPS: &str = "perf.low_fps";
+/// `{ entity_id: string, hook: string, budget: string, violations: number, disabled: bool }`
+pub const SCRIPT_BUDGET_EXCEEDED: &str = "script.budget_exceeded";
+/// `{ used_bytes: number, limit_bytes: number, live_bytes: number, top_entity_id: string, top_bytes_per_frame: number }`
+pub const SCRIPT_MEMORY_HIGH: &str = "script.memory_high";
+/// `{ x: number, y: number, z: number, loudness: number, tag: string, source: string }`
+pub const SOUND_NOISE: &str = "sound.noise";
+/// `{ name: string }`
+pub const SEQUENCE_STARTED: &str = "sequence.started";
+/// `{ name: string, skipped: bool }`
+pub const SEQUENCE_FINISHED: &str = "sequence.finished";
+/// `{ name: string, stopped: bool }`
+pub const CAMERA_PATH_FINISHED: &str = "camera_path.finished";
+/// `{ device: string }`
+pub const AUDIO_DEVICE_CHANGED: &str = "audio.device_changed";
+/// `{ active: bool }`
+pub const STEAM_OVERLAY_CHANGED: &str = "steam.overlay_changed";
+/// `{ level: number, previous: number, fps: number, render_scale: number, shadow_resolution: number, particle_budget: number }`
+pub const QUALITY_CHANGED: &str = "perf.quality_changed";
+/// `{ reason: string, attempts: number }`
+pub const GPU_DEVICE_RECOVERED: &str = "gpu.device_recovered";
+
+/// FPS below which `perf.low_fps` fires.
+pub const DEFAULT_LOW_FPS_THRESHOLD: f32 = 30.0;
+/// Seconds of frames averaged per FPS sample.
+const FPS_SAMPLE_WINDOW: f32 = 1.0;
+/// Minimum seconds between two low-FPS warnings.
+const LOW_FPS_COOLDOWN: f32 = 5.0;
+
+/// Built-in schema entry: event type, description, and typed fields.
+type BuiltinEvent = (&'static str, &'static str, &'static [(&'static str, EventFieldType)]);
+
+/// Convert a `json!({...})` object into an event payload.
+pub fn payload(value: Value) -> HashMap<String, Value> {
+    match value {
+        Value::Object(map) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// Payload for `asset.reloaded`; `error` is empty on success.
+pub fn asset_reloaded(path: &str, kind: &str, result: &Result<(), String>) -> HashMap<String, Value> {
+    payload(json!({
+        "path": path,
+        "kind": kind,
+        "ok": result.is_ok(),
+        "error": result.as_ref().err().cloned().unwrap_or_default(),
+    }))
+}
+
+/// Schema entries for the built-in events, for tooling and autocompletion.
+pub fn builtin_schema() -> EventSchema {
+    use EventFieldType::*;
+    let entries: [BuiltinEvent; 20] = [
+        (WINDOW_FOCUS_CHANGED, "The window gained or lost focus", &[("focused", Bool)]),
+        (WINDOW_RESIZED, "The window was resized", &[("width", Number), ("height", Number)]),
+        (
+            WINDOW_SUSPENDED,
+            "Rendering was suspended (minimized, hidden or unfocused) or resumed",
+            &[("suspended", Bool), ("reason", String)],
+        ),
+        (SCENE_LOADED, "Scene finished loading", &[("scene", String)]),
+        (SCENE_RELOADED, "Scene file was hot-reloaded", &[("scene", String)]),
+        (ENTITY_SPAWNED, "An entity was spawned at runtime", &[("entity_id", String)]),
+        (ENTITY_DESTROYED, "An entity was destroyed at runtime", &[("entity_id", String)]),
+        (
+            ASSET_RELOADED,
+            "A watched asset was hot-reloaded",
+            &[("path", String), ("kind", String), ("ok", Bool), ("error", String)],
+        ),
+        (
+            PIPELINE_RECOMPILED,
+            "The render pipeline was recompiled",
+            &[("path", String), ("ok", Bool), ("error", String)],
+        ),
+        (LOW_FPS, "Frame rate dropped below the warning threshold", &[("fps", Number), ("threshold", Number)]),
+        (
+            QUALITY_CHANGED,
+            "auto_quality stepped the quality level to hold the target frame rate",
+            &[
+                ("level", Number),
+                ("previous", Number),
+                ("fps", Number),
+                ("render_scale", Number),
+                ("shadow_resolution", Number),
+                ("particle_budget", Number),
+            ],
+        ),
+        (
+            SCRIPT_BUDGET_EXCEEDED,
+            "A script ran over its per-frame budget and was aborted",
+            &[("entity_id", String), ("hook", String), ("budget", String), ("violations", Number), ("disabled", Bool)],
+        ),
+        (
+            SCRIPT_MEMORY_HIGH,
+            "Lua memory crossed 75% of its cap",
+            &[
+                ("used_bytes", Number),
+                ("limit_bytes", Number),
+                ("live_bytes", Number),
+                ("top_entity_id", String),
+                ("top_bytes_per_frame", Number),
+            ],
+        ),
+        (
+            SOUND_NOISE,
+            "A gameplay noise AI can hear (footstep, impact, sound effect or noise.emit)",
+            &[
+                ("x", Number),
+                ("y", Number),
+                ("z", Number),
+                ("loudness", Number),
+                ("tag", String),
+                ("source", String),
+            ],
+        ),
+        (SEQUENCE_STARTED, "A cutscene sequence started playing", &[("name", String)]),
+        (
+            SEQUENCE_FINISHED,
+            "A cutscene sequence ended, played through or skipped",
+            &[("name", String), ("skipped", Bool)],
+        ),
+        (
+            CAMERA_PATH_FINISHED,
+            "A camera path ended and handed the main camera back",
+            &[("name", String), ("stopped", Bool)],
+        ),
+        (
+            AUDIO_DEVICE_CHANGED,
+            "Sound moved to another output device (empty when none is left)",
+            &[("device", String)],
+        ),
+        (
+            STEAM_OVERLAY_CHANGED,
+            "The Steam overlay opened or closed; pause the game while it is open",
+            &[("active", Bool)],
+        ),
+        (
+            GPU_DEVICE_RECOVERED,
+            "The GPU device was lost and recreated; the scene restarted from a snapshot",
+            &[("reason", String), ("attempts", Number)],
+        ),
+    ];
+
+    let events = entries
+        .iter()
+        .map(|(name, description, fields)| {
+            let fields = fields
+                .iter()
+                .map(|(field, field_type)| EventField { name: field.to_string(), field_type: *field_type })
+                .collect();
+            (name.to_string(), EventFieldSchema { fields, description: description.to_string() })
+        })
+        .collect();
+    EventSchema { events }
+}
+
+/// Averages frame times and reports when FPS stays under a threshold.
+pub struct LowFpsMonitor {
+    pub threshold: f32,
+    elapsed: f32,
+    frames: u32,
+    cooldown: f32,
+}
+
+impl LowFpsMonitor {
+    pub fn new(threshold: f32) -> Self {
+        Self { threshold, elapsed: 0.0, frames: 0, cooldown: 0.0 }
+    }
+
+    /// Record one frame. Returns the measured FPS when a warning should be emitted.
+    pub fn update(&mut self, dt: f32) -> Option<f32> {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        self.elapsed += dt;
+        self.frames += 1;
+        if self.elapsed < FPS_SAMPLE_WINDOW {
+            return None;
+        }
+
+        let fps = self.frames as f32 / self.elapsed;
+        self.elapsed = 0.0;
+        self.frames = 0;
+        if fps < self.threshold && self.cooldown <= 0.0 {
+            self.cooldown = LOW_FPS_COOLDOWN;
+            Some(fps)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_fps_monitor_fires_once_per_cooldown() {
+        let mut monitor = LowFpsMonitor::new(30.0);
+        // 60 FPS never warns
+        assert!((0..120).all(|_| monitor.update(1.0 / 60.0).is_none()));
+
+        // 10 FPS warns after one sample window, then stays quiet during cooldown
+        let warnings: Vec<f32> = (0..40).filter_map(|_| monitor.update(0.1)).collect();
+        assert_eq!(warnings.len(), 1);
+        assert!((warnings[0] - 10.0).abs() < 0.5);
+
+        let later: Vec<f32> = (0..40).filter_map(|_| monitor.update(0.1)).collect();
+        assert_eq!(later.len(), 1);
+    }
+
+    #[test]
+    fn test_builtin_schema_accepts_engine_payloads() {
+        let schema = builtin_schema();
+        let data = asset_reloaded("shaders/mesh.wgsl", "shader", &Err("bad".to_string()));
+        assert!(schema.validate(ASSET_RELOADED, &data).is_ok());
+        let data = payload(json!({"width": 800, "height": 600}));
+        assert!(schema.validate(WINDOW_RESIZED, &data).is_ok());
+        let data = payload(json!({"focused": 1}));
+        assert!(schema.validate(WINDOW_FOCUS_CHANGED, &data).is_err());
+    }
+}
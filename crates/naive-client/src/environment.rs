@@ -0,0 +1,536 @@
+//! Skybox loading and image-based lighting.
+//!
+//! A `Skybox` component's image (equirectangular or six faces) is resampled
+//! into a cubemap drawn behind the scene, then prefiltered on the CPU into a
+//! diffuse irradiance cube and a specular cube whose mips hold increasing
+//! GGX roughness. The deferred lighting pass samples both as ambient light.
+
+use std::f32::consts::PI;
+use std::path::Path;
+
+use glam::Vec3;
+
+use crate::components::{Skybox, SkyboxSource};
+use crate::pipeline::CompiledPipeline;
+
+/// Largest sky cube face drawn as the background.
+const SKY_FACE_MAX: u32 = 512;
+/// Edge length of the first specular mip; each further mip halves it.
+const SPECULAR_SIZE: u32 = 128;
+/// Specular mips, roughness 0 to 1 in equal steps.
+pub const SPECULAR_MIPS: u32 = 5;
+/// GGX samples per specular texel.
+const SPECULAR_SAMPLES: u32 = 64;
+/// Edge length of the irradiance cube (diffuse light varies slowly).
+const IRRADIANCE_SIZE: u32 = 16;
+/// Source face size the irradiance convolution integrates over.
+const IRRADIANCE_SOURCE_SIZE: u32 = 16;
+
+/// Environment uniforms shared by the skybox and lighting passes.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EnvironmentUniforms {
+    /// Multiplier on the sky and the light it casts.
+    pub intensity: f32,
+    /// Mip levels of the specular cube (roughness 1 is the last).
+    pub specular_mips: f32,
+    /// 1 when a skybox is bound; the lighting pass falls back to a flat
+    /// ambient term and the skybox pass draws nothing otherwise.
+    pub enabled: u32,
+    pub _pad: u32,
+}
+
+/// Linear RGB radiance on the six faces of a cube (+X, -X, +Y, -Y, +Z, -Z),
+/// each `size` x `size` texels, row-major.
+#[derive(Debug, Clone)]
+pub struct CubeImage {
+    pub size: u32,
+    pub faces: [Vec<Vec3>; 6],
+}
+
+/// Direction through `(u, v)` (each -1..1, v pointing down) of cube face
+/// `face`, in the wgpu/Vulkan cubemap convention.
+pub fn face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0),
+    }
+    .normalize()
+}
+
+/// The face a direction hits and its `(u, v)` there (inverse of `face_direction`).
+pub fn direction_to_face(dir: Vec3) -> (usize, f32, f32) {
+    let a = dir.abs();
+    if a.x >= a.y && a.x >= a.z {
+        if dir.x > 0.0 {
+            (0, -dir.z / a.x, -dir.y / a.x)
+        } else {
+            (1, dir.z / a.x, -dir.y / a.x)
+        }
+    } else if a.y >= a.z {
+        if dir.y > 0.0 {
+            (2, dir.x / a.y, dir.z / a.y)
+        } else {
+            (3, dir.x / a.y, -dir.z / a.y)
+        }
+    } else if dir.z > 0.0 {
+        (4, dir.x / a.z, -dir.y / a.z)
+    } else {
+        (5, -dir.x / a.z, -dir.y / a.z)
+    }
+}
+
+/// Bilinear sample at pixel coordinates `(x, y)` (texel centers at i + 0.5),
+/// wrapping horizontally for panoramas and clamping otherwise.
+fn bilinear(pixels: &[Vec3], width: u32, height: u32, x: f32, y: f32, wrap_x: bool) -> Vec3 {
+    let (w, h) = (width as i64, height as i64);
+    let (x, y) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let at = |c: i64, r: i64| {
+        let c = if wrap_x { c.rem_euclid(w) } else { c.clamp(0, w - 1) };
+        pixels[(r.clamp(0, h - 1) * w + c) as usize]
+    };
+    let top = at(x0, y0).lerp(at(x0 + 1, y0), fx);
+    let bottom = at(x0, y0 + 1).lerp(at(x0 + 1, y0 + 1), fx);
+    top.lerp(bottom, fy)
+}
+
+/// Solid angle of the texel at `(u, v)` on a face of `size` texels.
+fn texel_solid_angle(u: f32, v: f32, size: u32) -> f32 {
+    let texel = 2.0 / size as f32;
+    texel * texel / (1.0 + u * u + v * v).powf(1.5)
+}
+
+/// Texel center of `index` on a face of `size` texels, in -1..1.
+fn texel_center(index: u32, size: u32) -> f32 {
+    (index as f32 + 0.5) / size as f32 * 2.0 - 1.0
+}
+
+impl CubeImage {
+    /// A cube whose texels hold `radiance(direction)`.
+    pub fn from_fn(size: u32, radiance: impl Fn(Vec3) -> Vec3) -> Self {
+        let faces = std::array::from_fn(|face| {
+            let mut texels = Vec::with_capacity((size * size) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    texels.push(radiance(face_direction(face, texel_center(x, size), texel_center(y, size))));
+                }
+            }
+            texels
+        });
+        Self { size, faces }
+    }
+
+    /// Resample an equirectangular panorama (+Y up, -Z at its center).
+    pub fn from_equirect(pixels: &[Vec3], width: u32, height: u32, size: u32) -> Self {
+        Self::from_fn(size, |dir| {
+            let u = 0.5 + dir.x.atan2(-dir.z) / (2.0 * PI);
+            let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+            bilinear(pixels, width, height, u * width as f32, v * height as f32, true)
+        })
+    }
+
+    /// Bilinear radiance seen in direction `dir`.
+    pub fn sample(&self, dir: Vec3) -> Vec3 {
+        let (face, u, v) = direction_to_face(dir);
+        let size = self.size as f32;
+        bilinear(&self.faces[face], self.size, self.size, (u + 1.0) * 0.5 * size, (v + 1.0) * 0.5 * size, false)
+    }
+
+    /// Half-resolution copy (2x2 box filter).
+    pub fn downsample(&self) -> Self {
+        let size = (self.size / 2).max(1);
+        let faces = std::array::from_fn(|face| {
+            let src = &self.faces[face];
+            let at = |x: u32, y: u32| src[(y.min(self.size - 1) * self.size + x.min(self.size - 1)) as usize];
+            let mut texels = Vec::with_capacity((size * size) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    let (sx, sy) = (x * 2, y * 2);
+                    texels.push((at(sx, sy) + at(sx + 1, sy) + at(sx, sy + 1) + at(sx + 1, sy + 1)) * 0.25);
+                }
+            }
+            texels
+        });
+        Self { size, faces }
+    }
+
+    /// Cosine-weighted irradiance divided by pi, so that a lambertian surface
+    /// reflects `albedo * irradiance`.
+    pub fn irradiance(&self, size: u32) -> Self {
+        let mut source = self.clone();
+        while source.size > IRRADIANCE_SOURCE_SIZE {
+            source = source.downsample();
+        }
+        // Every source texel as (direction, radiance * solid angle)
+        let mut texels = Vec::with_capacity(6 * (source.size * source.size) as usize);
+        for (face, radiance) in source.faces.iter().enumerate() {
+            for y in 0..source.size {
+                for x in 0..source.size {
+                    let (u, v) = (texel_center(x, source.size), texel_center(y, source.size));
+                    let weight = texel_solid_angle(u, v, source.size);
+                    texels.push((face_direction(face, u, v), radiance[(y * source.size + x) as usize] * weight));
+                }
+            }
+        }
+        Self::from_fn(size, |normal| {
+            let mut sum = Vec3::ZERO;
+            for &(dir, radiance) in &texels {
+                sum += radiance * normal.dot(dir).max(0.0);
+            }
+            sum / PI
+        })
+    }
+
+    /// Specular mips for roughness 0, 1/(mips-1), ..., 1 (split-sum
+    /// prefiltering with the view along the normal). Samples are read from
+    /// a mip of the source matching their footprint, which keeps 64 samples
+    /// free of fireflies.
+    pub fn prefilter_specular(&self, size: u32, mips: u32) -> Vec<Self> {
+        let mut chain = vec![self.clone()];
+        while chain.last().is_some_and(|c| c.size > 1) {
+            let next = chain.last().unwrap().downsample();
+            chain.push(next);
+        }
+        (0..mips)
+            .map(|mip| {
+                let mip_size = (size >> mip).max(1);
+                if mip == 0 {
+                    return Self::from_fn(mip_size, |dir| self.sample(dir));
+                }
+                let roughness = mip as f32 / (mips - 1) as f32;
+                Self::from_fn(mip_size, |normal| prefilter_texel(&chain, normal, roughness))
+            })
+            .collect()
+    }
+}
+
+/// GGX-weighted radiance around `normal` for one specular texel.
+fn prefilter_texel(chain: &[CubeImage], normal: Vec3, roughness: f32) -> Vec3 {
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let up = if normal.y.abs() < 0.999 { Vec3::Y } else { Vec3::X };
+    let tangent = up.cross(normal).normalize();
+    let bitangent = normal.cross(tangent);
+    let source_size = chain[0].size as f32;
+    let texel_angle = 4.0 * PI / (6.0 * source_size * source_size);
+
+    let mut sum = Vec3::ZERO;
+    let mut weight = 0.0;
+    for i in 0..SPECULAR_SAMPLES {
+        // Hammersley point, importance-sampled by the GGX distribution
+        let xi_x = i as f32 / SPECULAR_SAMPLES as f32;
+        let xi_y = i.reverse_bits() as f32 * 2.328_306_4e-10;
+        let phi = 2.0 * PI * xi_x;
+        let cos_theta = ((1.0 - xi_y) / (1.0 + (alpha2 - 1.0) * xi_y)).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let half = tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + normal * cos_theta;
+        let light = half * (2.0 * normal.dot(half)) - normal;
+        let n_dot_l = normal.dot(light);
+        if n_dot_l <= 0.0 {
+            continue;
+        }
+        // With the view along the normal the sample pdf is D / 4
+        let d = cos_theta * cos_theta * (alpha2 - 1.0) + 1.0;
+        let pdf = alpha2 / (PI * d * d) / 4.0;
+        let sample_angle = 1.0 / (SPECULAR_SAMPLES as f32 * pdf + 1e-4);
+        let lod = (0.5 * (sample_angle / texel_angle).log2() + 1.0).clamp(0.0, (chain.len() - 1) as f32);
+        sum += chain[lod.round() as usize].sample(light) * n_dot_l;
+        weight += n_dot_l;
+    }
+    if weight > 0.0 {
+        sum / weight
+    } else {
+        chain[0].sample(normal)
+    }
+}
+
+/// IEEE half-float bits of `value`, saturating at the largest finite half
+/// (a sun in an HDR sky shouldn't become infinity).
+pub(crate) fn f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    if value.is_nan() {
+        return sign | 0x7e00;
+    }
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent >= 0x1f {
+        sign | 0x7bff
+    } else if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        sign | ((mantissa | 0x80_0000) >> (14 - exponent)) as u16
+    } else {
+        sign | ((exponent as u16) << 10) | (mantissa >> 13) as u16
+    }
+}
+
+/// Load an image as linear RGB. Float formats (HDR, EXR) are already linear;
+/// 8-bit images are decoded from sRGB.
+fn load_linear_image(project_root: &Path, path: &str) -> Result<(Vec<Vec3>, u32, u32), String> {
+    let full_path = project_root.join(path);
+    let img = image::open(&full_path)
+        .map_err(|e| format!("Failed to load skybox image '{}': {}", full_path.display(), e))?;
+    let is_float = matches!(img.color(), image::ColorType::Rgb32F | image::ColorType::Rgba32F);
+    let rgb = img.to_rgb32f();
+    let (width, height) = rgb.dimensions();
+    let decode = |c: f32| {
+        if is_float {
+            c.max(0.0)
+        } else if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let pixels = rgb.pixels().map(|p| Vec3::new(decode(p[0]), decode(p[1]), decode(p[2]))).collect();
+    Ok((pixels, width, height))
+}
+
+/// Read a skybox source into a sky cube.
+pub fn load_sky_cube(project_root: &Path, source: &SkyboxSource) -> Result<CubeImage, String> {
+    match source {
+        SkyboxSource::Equirect(path) => {
+            let (pixels, width, height) = load_linear_image(project_root, path)?;
+            let size = (width / 4).clamp(16, SKY_FACE_MAX);
+            Ok(CubeImage::from_equirect(&pixels, width, height, size))
+        }
+        SkyboxSource::Cubemap(paths) => {
+            let mut faces: [Vec<Vec3>; 6] = Default::default();
+            let mut size = None;
+            for (face, path) in paths.iter().enumerate() {
+                let (pixels, width, height) = load_linear_image(project_root, path)?;
+                if width != height || size.is_some_and(|s| s != width) {
+                    return Err(format!(
+                        "Skybox cubemap faces must be square and the same size ('{}' is {}x{})",
+                        path, width, height
+                    ));
+                }
+                size = Some(width);
+                faces[face] = pixels;
+            }
+            let mut cube = CubeImage { size: size.unwrap_or(1), faces };
+            while cube.size > SKY_FACE_MAX {
+                cube = cube.downsample();
+            }
+            Ok(cube)
+        }
+    }
+}
+
+/// Upload cube mips as an Rgba16Float cube texture.
+fn upload_cube(device: &wgpu::Device, queue: &wgpu::Queue, label: &str, mips: &[CubeImage]) -> wgpu::TextureView {
+    let size = mips[0].size;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d { width: size, height: size, depth_or_array_layers: 6 },
+        mip_level_count: mips.len() as u32,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba16Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    for (level, mip) in mips.iter().enumerate() {
+        let mut bytes = Vec::with_capacity(6 * (mip.size * mip.size) as usize * 8);
+        for texel in mip.faces.iter().flatten() {
+            for c in [texel.x, texel.y, texel.z, 1.0] {
+                bytes.extend_from_slice(&f16_bits(c).to_le_bytes());
+            }
+        }
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: level as u32,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &bytes,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(mip.size * 8),
+                rows_per_image: Some(mip.size),
+            },
+            wgpu::Extent3d { width: mip.size, height: mip.size, depth_or_array_layers: 6 },
+        );
+    }
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some(label),
+        dimension: Some(wgpu::TextureViewDimension::Cube),
+        ..Default::default()
+    })
+}
+
+/// The cubemaps of one skybox.
+#[derive(Clone)]
+pub struct EnvironmentMaps {
+    /// Background radiance.
+    pub sky: wgpu::TextureView,
+    /// GGX-prefiltered radiance, roughness increasing per mip.
+    pub specular: wgpu::TextureView,
+    /// Diffuse irradiance.
+    pub irradiance: wgpu::TextureView,
+    pub specular_mips: u32,
+}
+
+impl EnvironmentMaps {
+    /// Black 1x1 cubes, bound while the scene has no skybox.
+    pub fn fallback(device: &wgpu::Device) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Environment Fallback"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 6 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Environment Fallback"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        Self { sky: view.clone(), specular: view.clone(), irradiance: view, specular_mips: 1 }
+    }
+
+    /// Prefilter a sky cube and upload the three maps.
+    pub fn from_sky(device: &wgpu::Device, queue: &wgpu::Queue, sky: &CubeImage) -> Self {
+        let specular = sky.prefilter_specular(SPECULAR_SIZE.min(sky.size), SPECULAR_MIPS);
+        let irradiance = sky.irradiance(IRRADIANCE_SIZE);
+        Self {
+            sky: upload_cube(device, queue, "Skybox Cube", std::slice::from_ref(sky)),
+            specular: upload_cube(device, queue, "Specular Environment Cube", &specular),
+            irradiance: upload_cube(device, queue, "Irradiance Environment Cube", &[irradiance]),
+            specular_mips: specular.len() as u32,
+        }
+    }
+}
+
+/// Loaded skybox maps, kept across pipeline recompiles and scene reloads so
+/// only a changed source is prefiltered again.
+#[derive(Default)]
+pub struct EnvironmentCache {
+    /// Last source asked for; None maps mean it failed to load (not retried
+    /// until the source changes).
+    loaded: Option<(SkyboxSource, Option<EnvironmentMaps>)>,
+}
+
+impl EnvironmentCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn maps_for(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        project_root: &Path,
+        source: &SkyboxSource,
+    ) -> Option<&EnvironmentMaps> {
+        if self.loaded.as_ref().is_none_or(|(loaded, _)| loaded != source) {
+            let start = std::time::Instant::now();
+            let maps = match load_sky_cube(project_root, source) {
+                Ok(sky) => {
+                    let maps = EnvironmentMaps::from_sky(device, queue, &sky);
+                    tracing::info!("Skybox loaded and prefiltered in {:.0?}", start.elapsed());
+                    Some(maps)
+                }
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    None
+                }
+            };
+            self.loaded = Some((source.clone(), maps));
+        }
+        self.loaded.as_ref().and_then(|(_, maps)| maps.as_ref())
+    }
+
+    /// Bind the scene's skybox (the first `Skybox` component) to the
+    /// pipeline, or the black fallback if there is none. Cheap when nothing
+    /// changed since the last call.
+    pub fn sync(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        project_root: &Path,
+        world: &hecs::World,
+        compiled: &mut CompiledPipeline,
+    ) {
+        let skybox = world.query::<&Skybox>().iter().next().map(|(_, sky)| sky.clone());
+        let wanted = skybox.as_ref().map(|sky| (sky.source.clone(), sky.intensity));
+        if compiled.environment_source == wanted {
+            return;
+        }
+        let maps = skybox.and_then(|sky| self.maps_for(device, queue, project_root, &sky.source).cloned());
+        let intensity = wanted.as_ref().map_or(0.0, |(_, intensity)| *intensity);
+        crate::pipeline::set_environment(device, queue, compiled, maps, intensity);
+        compiled.environment_source = wanted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_face_direction_round_trips() {
+        for face in 0..6 {
+            for (u, v) in [(0.0, 0.0), (0.5, -0.25), (-0.9, 0.7)] {
+                let (f, fu, fv) = direction_to_face(face_direction(face, u, v));
+                assert_eq!(f, face);
+                assert!((fu - u).abs() < 1e-5 && (fv - v).abs() < 1e-5, "face {} ({}, {})", face, u, v);
+            }
+        }
+        // wgpu convention: +Z face center looks down +Z, +Y face's top row toward -Z
+        assert_eq!(face_direction(4, 0.0, 0.0), Vec3::Z);
+        assert!(face_direction(2, 0.0, -1.0).z < 0.0);
+    }
+
+    #[test]
+    fn test_equirect_maps_up_and_forward() {
+        // Top half bright, bottom half dark
+        let (width, height) = (16, 8);
+        let pixels: Vec<Vec3> = (0..width * height)
+            .map(|i| if i / width < height / 2 { Vec3::ONE } else { Vec3::ZERO })
+            .collect();
+        let cube = CubeImage::from_equirect(&pixels, width, height, 4);
+        assert!(cube.sample(Vec3::Y).x > 0.99);
+        assert!(cube.sample(-Vec3::Y).x < 0.01);
+    }
+
+    #[test]
+    fn test_constant_sky_prefilters_to_itself() {
+        let radiance = Vec3::new(0.5, 1.0, 2.0);
+        let sky = CubeImage::from_fn(8, |_| radiance);
+        let irradiance = sky.irradiance(4);
+        for texel in irradiance.faces.iter().flatten() {
+            assert!((*texel - radiance).abs().max_element() < 0.02, "{:?}", texel);
+        }
+        let specular = sky.prefilter_specular(8, 4);
+        assert_eq!(specular.iter().map(|m| m.size).collect::<Vec<_>>(), vec![8, 4, 2, 1]);
+        for texel in specular.iter().flat_map(|m| m.faces.iter().flatten()) {
+            assert!((*texel - radiance).abs().max_element() < 1e-3, "{:?}", texel);
+        }
+    }
+
+    #[test]
+    fn test_f16_bits() {
+        assert_eq!(f16_bits(0.0), 0);
+        assert_eq!(f16_bits(1.0), 0x3c00);
+        assert_eq!(f16_bits(0.5), 0x3800);
+        assert_eq!(f16_bits(-2.0), 0xc000);
+        assert_eq!(f16_bits(65504.0), 0x7bff);
+        assert_eq!(f16_bits(1.0e9), 0x7bff);
+        // Smallest subnormal half
+        assert_eq!(f16_bits(5.960_464_5e-8), 1);
+    }
+}
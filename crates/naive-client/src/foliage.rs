@@ -0,0 +1,669 @@
+//! Foliage: grass and other small meshes scattered over an entity's local XZ
+//! square and drawn with GPU instancing.
+//!
+//! Each `Foliage` entity is scattered once on the CPU into world-space
+//! instances (position, scale, yaw, sway phase, tint) and rescattered when its
+//! component or transform changes. Instances come out in random order, so any
+//! prefix of the list is an even thinning of it: the quality preset's density
+//! scale just draws fewer of them. The instances are drawn into the G-buffer
+//! by a render hook after the geometry pass, so the lighting pass lights them
+//! like any other surface. Wind sway and the distance fade happen in the
+//! vertex shader.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::components::{Foliage, Hidden, MeshHandle, Transform};
+use crate::mesh::Vertex3D;
+use crate::pipeline::{CompiledPipeline, PassType, RenderHookContext};
+
+/// How far below the scatter square instances look for ground.
+pub const GROUND_PROBE_DISTANCE: f32 = 1000.0;
+
+/// One drawn copy of the foliage mesh, in world space.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FoliageInstance {
+    pub position: [f32; 3],
+    pub scale: f32,
+    /// Rotation about +Y, radians.
+    pub yaw: f32,
+    /// Offset into the wind cycle, radians.
+    pub phase: f32,
+    /// Brightness variation multiplied into the color.
+    pub tint: f32,
+    pub _pad: f32,
+}
+
+impl FoliageInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+        7 => Float32x4, // position, scale
+        8 => Float32x4  // yaw, phase, tint
+    ];
+
+    /// Instance-rate layout following the mesh's `Vertex3D` attributes (0-6).
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<FoliageInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Per-entity uniforms (group 1).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct FoliageUniforms {
+    /// RGB + fade start.
+    base_color: [f32; 4],
+    /// RGB + fade end.
+    tip_color: [f32; 4],
+    /// Direction (world XZ, normalized), strength, speed.
+    wind: [f32; 4],
+    /// Time, unused x3.
+    params: [f32; 4],
+}
+
+impl FoliageUniforms {
+    /// Without `fade`, instances keep full size at any distance.
+    fn new(foliage: &Foliage, time: f32, fade: bool) -> Self {
+        let [br, bg, bb] = foliage.base_color;
+        let [tr, tg, tb] = foliage.tip_color;
+        let direction = glam::Vec2::from_array(foliage.wind_direction).normalize_or(glam::Vec2::X);
+        let (fade_start, fade_end) = if fade {
+            // Equal fade distances would make the shader's smoothstep undefined
+            (foliage.fade_start.min(foliage.fade_end - 0.01), foliage.fade_end)
+        } else {
+            (1.0e30, 2.0e30)
+        };
+        Self {
+            base_color: [br, bg, bb, fade_start],
+            tip_color: [tr, tg, tb, fade_end],
+            wind: [direction.x, direction.y, foliage.wind_strength, foliage.wind_speed],
+            params: [time, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// Grayscale density map, brightness 0..1, sampled over the scatter square.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityMap {
+    pub width: u32,
+    pub height: u32,
+    pub values: Vec<f32>,
+}
+
+impl DensityMap {
+    /// Load a project-relative image as its luminance.
+    pub fn load(project_root: &Path, path: &str) -> Result<Self, String> {
+        let image = image::open(project_root.join(path))
+            .map_err(|e| format!("density map '{}': {}", path, e))?
+            .to_luma8();
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            values: image.pixels().map(|p| p.0[0] as f32 / 255.0).collect(),
+        })
+    }
+
+    /// Nearest texel at (u, v) in 0..1; v runs along local +Z.
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let x = ((u.clamp(0.0, 1.0) * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v.clamp(0.0, 1.0) * self.height as f32) as u32).min(self.height - 1);
+        self.values[(y * self.width + x) as usize]
+    }
+}
+
+/// SplitMix64: small, seedable and plenty for scattering.
+struct ScatterRng(u64);
+
+impl ScatterRng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Scatter `foliage` over the unit square of `world`'s local XZ plane.
+/// Candidates are spread evenly at full density (capped at
+/// `max_instances`), thinned by the density map, then handed to `ground`,
+/// which returns where the instance stands or None to drop it.
+pub fn scatter(
+    foliage: &Foliage,
+    world: &Mat4,
+    density_map: Option<&DensityMap>,
+    mut ground: impl FnMut(Vec3) -> Option<Vec3>,
+) -> Vec<FoliageInstance> {
+    let area = world.x_axis.truncate().length() * world.z_axis.truncate().length();
+    let candidates = ((foliage.density * area).round() as u64).min(foliage.max_instances as u64);
+
+    let mut rng = ScatterRng(foliage.seed);
+    let mut instances = Vec::new();
+    for _ in 0..candidates {
+        let (u, v) = (rng.next_f32(), rng.next_f32());
+        let (yaw, phase, size, tint, keep) =
+            (rng.next_f32(), rng.next_f32(), rng.next_f32(), rng.next_f32(), rng.next_f32());
+        if density_map.is_some_and(|map| keep >= map.sample(u, v)) {
+            continue;
+        }
+        let Some(position) = ground(world.transform_point3(Vec3::new(u - 0.5, 0.0, v - 0.5))) else {
+            continue;
+        };
+        let [min_scale, max_scale] = foliage.scale;
+        instances.push(FoliageInstance {
+            position: position.to_array(),
+            scale: min_scale + (max_scale - min_scale) * size,
+            yaw: yaw * std::f32::consts::TAU,
+            phase: phase * std::f32::consts::TAU,
+            tint: 0.8 + 0.4 * tint,
+            _pad: 0.0,
+        });
+    }
+    instances
+}
+
+/// Instances drawn at a density scale: an even share of the full list.
+pub fn drawn_count(total: usize, density_scale: f32) -> u32 {
+    (total as f32 * density_scale.clamp(0.0, 1.0)).ceil() as u32
+}
+
+/// The geometry pass of a compiled pipeline: the first rasterize pass with
+/// a depth target and three color targets (albedo, normal, emission). Returns
+/// its name with the color and depth formats.
+pub fn geometry_pass(
+    compiled: &CompiledPipeline,
+) -> Option<(&str, Vec<wgpu::TextureFormat>, wgpu::TextureFormat)> {
+    compiled.pass_order.iter().map(|&i| &compiled.passes[i]).find_map(|pass| {
+        if pass.pass_type != PassType::Rasterize || pass.color_targets.len() != 3 {
+            return None;
+        }
+        let depth = compiled.resources.get(pass.depth_target.as_ref()?)?;
+        let formats = pass
+            .color_targets
+            .iter()
+            .map(|name| compiled.resources.get(name).map(|r| r.format))
+            .collect::<Option<Vec<_>>>()?;
+        Some((pass.name.as_str(), formats, depth.format))
+    })
+}
+
+/// One entity's scattered instances.
+struct FoliageBatch {
+    /// Component and world matrix the instances were scattered from.
+    source: (Foliage, Mat4),
+    mesh: MeshHandle,
+    instance_buffer: Option<wgpu::Buffer>,
+    count: usize,
+    /// World bounds of the instance roots, for distance culling.
+    bounds: (Vec3, Vec3),
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Scatters and draws every `Foliage` of the scene into the G-buffer.
+pub struct FoliageRenderer {
+    pass_name: String,
+    formats: Vec<wgpu::TextureFormat>,
+    depth_format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    batches: HashMap<hecs::Entity, FoliageBatch>,
+    /// Loaded density maps by path (None when loading failed).
+    density_maps: HashMap<String, Option<DensityMap>>,
+    /// Fraction of each batch drawn, from the quality preset.
+    density_scale: f32,
+    /// Skip batches past their fade end and fade instances out (off under `--no-culling`).
+    culling: bool,
+    /// Clock for the wind.
+    start: instant::Instant,
+}
+
+impl FoliageRenderer {
+    /// `pass_name` and the formats are the geometry pass's, from
+    /// [`geometry_pass`].
+    pub fn new(
+        device: &wgpu::Device,
+        pass_name: &str,
+        formats: &[wgpu::TextureFormat],
+        depth_format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Foliage Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Foliage Shader"),
+            source: wgpu::ShaderSource::Wgsl(FOLIAGE_WGSL.into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Foliage Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let targets: Vec<_> = formats
+            .iter()
+            .map(|&format| {
+                Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+            })
+            .collect();
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Foliage Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex3D::desc(), FoliageInstance::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &targets,
+                compilation_options: Default::default(),
+            }),
+            // Blades are seen from both sides
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pass_name: pass_name.to_string(),
+            formats: formats.to_vec(),
+            depth_format,
+            pipeline,
+            bind_group_layout,
+            batches: HashMap::new(),
+            density_maps: HashMap::new(),
+            density_scale: 1.0,
+            culling: true,
+            start: instant::Instant::now(),
+        }
+    }
+
+    /// Whether this renderer was built for the given geometry pass and formats.
+    pub fn matches(&self, pass_name: &str, formats: &[wgpu::TextureFormat], depth_format: wgpu::TextureFormat) -> bool {
+        self.pass_name == pass_name && self.formats == formats && self.depth_format == depth_format
+    }
+
+    pub fn set_density_scale(&mut self, scale: f32) {
+        self.density_scale = scale.clamp(0.0, 1.0);
+    }
+
+    pub fn set_culling(&mut self, culling: bool) {
+        self.culling = culling;
+    }
+
+    /// Whether some foliage entity needs (re)scattering.
+    pub fn has_pending(&self, world: &hecs::World) -> bool {
+        world.query::<(&Transform, &Foliage)>().iter().any(|(entity, (transform, foliage))| {
+            foliage.mesh.is_some()
+                && self
+                    .batches
+                    .get(&entity)
+                    .is_none_or(|b| b.source.0 != *foliage || b.source.1 != transform.world_matrix)
+        })
+    }
+
+    /// Scatter new or changed foliage and drop batches of removed entities.
+    /// `ground` finds where an instance dropped from a point lands, given the
+    /// foliage's `surface` entity id.
+    pub fn sync(
+        &mut self,
+        device: &wgpu::Device,
+        project_root: &Path,
+        world: &hecs::World,
+        mut ground: impl FnMut(Option<&str>, Vec3) -> Option<Vec3>,
+    ) {
+        self.batches.retain(|&entity, _| world.get::<&Foliage>(entity).is_ok());
+
+        for (entity, (transform, foliage)) in world.query::<(&Transform, &Foliage)>().iter() {
+            let Some(mesh) = foliage.mesh else {
+                self.batches.remove(&entity);
+                continue;
+            };
+            let source = (foliage.clone(), transform.world_matrix);
+            if self.batches.get(&entity).is_some_and(|b| b.source == source) {
+                continue;
+            }
+
+            let density_map = foliage.density_map.as_ref().and_then(|path| {
+                self.density_maps
+                    .entry(path.clone())
+                    .or_insert_with(|| {
+                        DensityMap::load(project_root, path)
+                            .inspect_err(|e| tracing::warn!("Foliage: {}", e))
+                            .ok()
+                    })
+                    .as_ref()
+            });
+            let surface = foliage.surface.as_deref();
+            let instances = scatter(foliage, &transform.world_matrix, density_map, |point| {
+                if foliage.snap_to_ground {
+                    ground(surface, point)
+                } else {
+                    Some(point)
+                }
+            });
+
+            let max_scale = foliage.scale[1];
+            let bounds = instances.iter().fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), instance| {
+                    let p = Vec3::from_array(instance.position);
+                    (min.min(p), max.max(p))
+                },
+            );
+            let bounds = (bounds.0 - Vec3::splat(max_scale), bounds.1 + Vec3::splat(max_scale));
+            let instance_buffer = (!instances.is_empty()).then(|| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Foliage Instances"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsages::VERTEX,
+                })
+            });
+            let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Foliage Uniforms"),
+                contents: bytemuck::cast_slice(&[FoliageUniforms::new(foliage, 0.0, true)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Foliage Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            });
+            tracing::debug!("Scattered {} foliage instances", instances.len());
+            self.batches.insert(
+                entity,
+                FoliageBatch {
+                    source,
+                    mesh,
+                    instance_buffer,
+                    count: instances.len(),
+                    bounds,
+                    uniform_buffer,
+                    bind_group,
+                },
+            );
+        }
+    }
+
+    /// Draw the batches into the geometry pass's targets. Runs as a hook
+    /// after that pass.
+    pub fn draw(&mut self, ctx: &mut RenderHookContext<'_>) {
+        let resources = &ctx.compiled.resources;
+        let views: Option<Vec<_>> = ctx.pass.color_targets.iter().map(|name| resources.get(name)).collect();
+        let depth = ctx.pass.depth_target.as_ref().and_then(|name| resources.get(name));
+        let (Some(views), Some(depth)) = (views, depth) else {
+            return;
+        };
+
+        let camera_position = Vec3::from_array(ctx.camera_state.uniform.position);
+        let time = self.start.elapsed().as_secs_f32();
+        let mut visible = Vec::new();
+        for (entity, batch) in &self.batches {
+            let (Some(buffer), count) = (&batch.instance_buffer, drawn_count(batch.count, self.density_scale))
+            else {
+                continue;
+            };
+            if count == 0 || ctx.scene_world.world.get::<&Hidden>(*entity).is_ok() {
+                continue;
+            }
+            let nearest = camera_position.clamp(batch.bounds.0, batch.bounds.1);
+            if self.culling && nearest.distance(camera_position) > batch.source.0.fade_end {
+                continue;
+            }
+            ctx.queue.write_buffer(
+                &batch.uniform_buffer,
+                0,
+                bytemuck::cast_slice(&[FoliageUniforms::new(&batch.source.0, time, self.culling)]),
+            );
+            visible.push((batch, buffer, count));
+        }
+        if visible.is_empty() {
+            return;
+        }
+
+        let color_attachments: Vec<_> = views
+            .iter()
+            .map(|resource| {
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &resource.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            })
+            .collect();
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("foliage"),
+            color_attachments: &color_attachments,
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &ctx.camera_state.bind_group, &[]);
+        for (batch, buffer, count) in visible {
+            let mesh = ctx.mesh_cache.get(batch.mesh);
+            render_pass.set_bind_group(1, &batch.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice());
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(mesh.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.index_count, 0, 0..count);
+        }
+    }
+}
+
+/// Instanced G-buffer shader. Sway grows with the square of the vertex's
+/// height in the mesh, so roots stay put; past the fade start instances
+/// shrink into the ground.
+const FOLIAGE_WGSL: &str = r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    jitter: vec2<f32>,
+    _pad2: vec2<f32>,
+    inv_view_projection: mat4x4<f32>,
+    prev_view_projection: mat4x4<f32>,
+};
+
+struct FoliageUniforms {
+    base_color: vec4<f32>, // rgb, fade start
+    tip_color: vec4<f32>,  // rgb, fade end
+    wind: vec4<f32>,       // direction xz, strength, speed
+    params: vec4<f32>,     // time
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> foliage: FoliageUniforms;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(3) color: vec4<f32>,
+    @location(7) instance_position: vec4<f32>, // xyz, scale
+    @location(8) instance_params: vec4<f32>,   // yaw, phase, tint
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct GBufferOutput {
+    @location(0) albedo: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+    @location(2) emission: vec4<f32>,
+};
+
+fn rotate_y(v: vec3<f32>, angle: f32) -> vec3<f32> {
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec3<f32>(c * v.x + s * v.z, v.y, -s * v.x + c * v.z);
+}
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let root = model.instance_position.xyz;
+    let yaw = model.instance_params.x;
+
+    let distance_to_camera = distance(root, camera.position);
+    let fade = 1.0 - smoothstep(foliage.base_color.w, foliage.tip_color.w, distance_to_camera);
+    let scale = model.instance_position.w * fade;
+
+    let height = max(model.position.y, 0.0);
+    let direction = vec3<f32>(foliage.wind.x, 0.0, foliage.wind.y);
+    // Gusts roll across the field along the wind direction
+    let t = foliage.params.x * foliage.wind.w + model.instance_params.y - dot(root.xz, foliage.wind.xy) * 0.35;
+    let gust = sin(t) * 0.7 + sin(t * 2.3 + 1.7) * 0.3;
+    let sway = foliage.wind.z * (gust + 0.5) * height * height;
+
+    var local = rotate_y(model.position, yaw) + direction * sway;
+    // Bend rather than stretch: lower the tip as it leans
+    local.y -= 0.5 * sway * sway / max(height, 0.001);
+    let world = root + local * scale;
+
+    out.clip_position = camera.view_projection * vec4<f32>(world, 1.0);
+    out.world_normal = normalize(rotate_y(model.normal, yaw) - direction * sway * 0.5);
+    let gradient = clamp(model.position.y, 0.0, 1.0);
+    out.color = mix(foliage.base_color.rgb, foliage.tip_color.rgb, gradient)
+        * model.color.rgb * model.instance_params.z;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) front_facing: bool) -> GBufferOutput {
+    var out: GBufferOutput;
+    let normal = select(-in.world_normal, in.world_normal, front_facing);
+    out.albedo = vec4<f32>(in.color, 0.8);
+    out.normal = vec4<f32>(normalize(normal) * 0.5 + 0.5, 0.0);
+    out.emission = vec4<f32>(0.0);
+    return out;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grass() -> Foliage {
+        Foliage {
+            mesh: Some(MeshHandle(0)),
+            density: 4.0,
+            density_map: None,
+            max_instances: 10_000,
+            seed: 7,
+            snap_to_ground: false,
+            surface: None,
+            scale: [0.5, 1.0],
+            base_color: [0.0; 3],
+            tip_color: [1.0; 3],
+            wind_strength: 0.1,
+            wind_speed: 1.0,
+            wind_direction: [1.0, 0.0],
+            fade_start: 10.0,
+            fade_end: 20.0,
+        }
+    }
+
+    #[test]
+    fn test_scatter_covers_scaled_square_deterministically() {
+        let world = Mat4::from_scale_rotation_translation(
+            Vec3::new(10.0, 1.0, 5.0),
+            glam::Quat::IDENTITY,
+            Vec3::new(100.0, 2.0, 0.0),
+        );
+        let instances = scatter(&grass(), &world, None, Some);
+        assert_eq!(instances.len(), 200);
+        for instance in &instances {
+            let [x, y, z] = instance.position;
+            assert!((95.0..=105.0).contains(&x) && (-2.5..=2.5).contains(&z));
+            assert_eq!(y, 2.0);
+            assert!((0.5..=1.0).contains(&instance.scale));
+        }
+        assert_eq!(scatter(&grass(), &world, None, Some), instances);
+    }
+
+    #[test]
+    fn test_scatter_drops_instances_without_ground_and_outside_density_map() {
+        let world = Mat4::from_scale(Vec3::new(10.0, 1.0, 10.0));
+        // Ground only under the +X half
+        let ground = |p: Vec3| (p.x > 0.0).then_some(p - Vec3::Y);
+        let instances = scatter(&grass(), &world, None, ground);
+        assert!(!instances.is_empty() && instances.len() < 400);
+        assert!(instances.iter().all(|i| i.position[0] > 0.0 && i.position[1] == -1.0));
+
+        // Left column dense, right column empty
+        let map = DensityMap { width: 2, height: 1, values: vec![1.0, 0.0] };
+        let instances = scatter(&grass(), &world, Some(&map), Some);
+        assert!(!instances.is_empty());
+        assert!(instances.iter().all(|i| i.position[0] < 0.0));
+    }
+
+    #[test]
+    fn test_drawn_count_scales_with_density() {
+        assert_eq!(drawn_count(100, 1.0), 100);
+        assert_eq!(drawn_count(100, 0.25), 25);
+        assert_eq!(drawn_count(3, 0.5), 2);
+        assert_eq!(drawn_count(100, 0.0), 0);
+        assert_eq!(drawn_count(100, 2.0), 100);
+    }
+}
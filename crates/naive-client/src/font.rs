@@ -1,5 +1,28 @@
-/// Bitmap font atlas — programmatically generated 6x8 pixel font.
-/// Covers ASCII 32–126 (95 printable characters) in a 16×6 grid.
+//! Bitmap font atlas — programmatically generated 6x8 pixel font.
+//! Covers ASCII 32–126 (95 printable characters) in a 16×6 grid.
+//!
+//! The glyphs are drawn either straight from the pixel atlas (nearest
+//! filtered) or from a signed distance field built from it, which stays
+//! sharp at any text size. Building the distance field takes a moment, so it
+//! is cached on disk under `.naive/cache/fonts/`, keyed by font and size.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// How UI text is drawn (`ui.text` in naive.yaml).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextRendering {
+    /// The pixel font, nearest filtered: crisp at multiples of 8 px.
+    #[default]
+    Bitmap,
+    /// Signed distance field: smooth edges at any size.
+    Sdf,
+    /// Distance field with LCD subpixel antialiasing (RGB stripes). Needs
+    /// dual-source blending; drawn as `sdf` where the GPU lacks it.
+    Subpixel,
+}
 
 /// GPU-resident bitmap font atlas.
 pub struct BitmapFont {
@@ -10,14 +33,40 @@ pub struct BitmapFont {
     pub bind_group: wgpu::BindGroup,
     pub glyph_w: f32,
     pub glyph_h: f32,
+    /// Atlas cell of one glyph: the glyph plus `pad` texels on each side.
+    pub cell_w: f32,
+    pub cell_h: f32,
+    pub pad: f32,
     pub cols: u32,
     pub atlas_w: f32,
     pub atlas_h: f32,
+    pub rendering: TextRendering,
 }
 
-/// Create the bitmap font, generating the atlas and uploading to GPU.
-pub fn create_bitmap_font(device: &wgpu::Device, queue: &wgpu::Queue) -> BitmapFont {
-    let (atlas_w, atlas_h, rgba_data) = generate_font_atlas();
+/// Create the font, generating the atlas (or reading the cached distance
+/// field from `cache_dir`) and uploading it to the GPU.
+pub fn create_bitmap_font(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    rendering: TextRendering,
+    cache_dir: Option<&Path>,
+) -> BitmapFont {
+    let (atlas_w, atlas_h, rgba_data, scale, pad) = match rendering {
+        TextRendering::Bitmap => {
+            let (w, h, rgba) = generate_font_atlas();
+            (w, h, rgba, 1, 0)
+        }
+        TextRendering::Sdf | TextRendering::Subpixel => {
+            let (w, h, texels) = load_or_generate_sdf_atlas(cache_dir);
+            let rgba = texels.iter().flat_map(|&d| [255, 255, 255, d]).collect();
+            (w, h, rgba, SDF_SCALE, SDF_PAD * SDF_SCALE)
+        }
+    };
+    // Distances are data, not color
+    let (format, filter) = match rendering {
+        TextRendering::Bitmap => (wgpu::TextureFormat::Rgba8UnormSrgb, wgpu::FilterMode::Nearest),
+        _ => (wgpu::TextureFormat::Rgba8Unorm, wgpu::FilterMode::Linear),
+    };
 
     let texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some("Font Atlas"),
@@ -29,7 +78,7 @@ pub fn create_bitmap_font(device: &wgpu::Device, queue: &wgpu::Queue) -> BitmapF
         mip_level_count: 1,
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        format,
         usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
         view_formats: &[],
     });
@@ -58,8 +107,8 @@ pub fn create_bitmap_font(device: &wgpu::Device, queue: &wgpu::Queue) -> BitmapF
 
     let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
         label: Some("Font Sampler"),
-        mag_filter: wgpu::FilterMode::Nearest,
-        min_filter: wgpu::FilterMode::Nearest,
+        mag_filter: filter,
+        min_filter: filter,
         ..Default::default()
     });
 
@@ -100,19 +149,28 @@ pub fn create_bitmap_font(device: &wgpu::Device, queue: &wgpu::Queue) -> BitmapF
         ],
     });
 
-    tracing::info!("Bitmap font atlas created ({}x{}, {} glyphs)", atlas_w, atlas_h, GLYPH_COUNT);
+    tracing::info!(
+        "Font atlas created ({}x{}, {} glyphs, {:?})",
+        atlas_w, atlas_h, GLYPH_COUNT, rendering
+    );
 
+    let glyph_w = (GLYPH_W * scale) as f32;
+    let glyph_h = (GLYPH_H * scale) as f32;
     BitmapFont {
         _texture: texture,
         texture_view,
         _sampler: sampler,
         bind_group_layout,
         bind_group,
-        glyph_w: GLYPH_W as f32,
-        glyph_h: GLYPH_H as f32,
+        glyph_w,
+        glyph_h,
+        cell_w: glyph_w + 2.0 * pad as f32,
+        cell_h: glyph_h + 2.0 * pad as f32,
+        pad: pad as f32,
         cols: ATLAS_COLS,
         atlas_w: atlas_w as f32,
         atlas_h: atlas_h as f32,
+        rendering,
     }
 }
 
@@ -125,8 +183,8 @@ pub fn glyph_uvs(font: &BitmapFont, ch: char) -> [f32; 4] {
     let idx = code - 32;
     let col = idx % font.cols;
     let row = idx / font.cols;
-    let u0 = (col as f32 * font.glyph_w) / font.atlas_w;
-    let v0 = (row as f32 * font.glyph_h) / font.atlas_h;
+    let u0 = (col as f32 * font.cell_w + font.pad) / font.atlas_w;
+    let v0 = (row as f32 * font.cell_h + font.pad) / font.atlas_h;
     let u1 = u0 + font.glyph_w / font.atlas_w;
     let v1 = v0 + font.glyph_h / font.atlas_h;
     [u0, v0, u1, v1]
@@ -140,6 +198,12 @@ const ATLAS_COLS: u32 = 16;
 const ATLAS_ROWS: u32 = 6;
 const GLYPH_COUNT: u32 = 95; // ASCII 32–126
 
+/// Distance field texels per font pixel.
+const SDF_SCALE: u32 = 8;
+/// Font pixels of distance field around each glyph; also the distance at
+/// which the field saturates.
+const SDF_PAD: u32 = 1;
+
 /// Generate the font atlas as RGBA pixel data.
 fn generate_font_atlas() -> (u32, u32, Vec<u8>) {
     let w = ATLAS_COLS * GLYPH_W;
@@ -154,9 +218,8 @@ fn generate_font_atlas() -> (u32, u32, Vec<u8>) {
         let y0 = row * GLYPH_H;
 
         for dy in 0..GLYPH_H {
-            let bits = bitmap[dy as usize];
             for dx in 0..GLYPH_W {
-                if bits & (1 << (7 - dx)) != 0 {
+                if pixel_lit(bitmap, dx as i32, dy as i32) {
                     let px = x0 + dx;
                     let py = y0 + dy;
                     let off = ((py * w + px) * 4) as usize;
@@ -173,6 +236,127 @@ fn generate_font_atlas() -> (u32, u32, Vec<u8>) {
     (w, h, pixels)
 }
 
+fn pixel_lit(bitmap: &[u8; 8], x: i32, y: i32) -> bool {
+    (0..GLYPH_W as i32).contains(&x) && (0..GLYPH_H as i32).contains(&y) && bitmap[y as usize] & (1 << (7 - x)) != 0
+}
+
+/// Distance in font pixels from (x, y) to the outline of a glyph's lit
+/// pixels, negative inside.
+fn signed_distance(bitmap: &[u8; 8], x: f32, y: f32) -> f32 {
+    let inside = pixel_lit(bitmap, x.floor() as i32, y.floor() as i32);
+    let reach = SDF_PAD as i32 + 1;
+    let mut nearest = SDF_PAD as f32;
+    for sy in -reach..GLYPH_H as i32 + reach {
+        for sx in -reach..GLYPH_W as i32 + reach {
+            if pixel_lit(bitmap, sx, sy) == inside {
+                continue;
+            }
+            let dx = (sx as f32 - x).max(x - (sx + 1) as f32).max(0.0);
+            let dy = (sy as f32 - y).max(y - (sy + 1) as f32).max(0.0);
+            nearest = nearest.min((dx * dx + dy * dy).sqrt());
+        }
+    }
+    if inside { -nearest } else { nearest }
+}
+
+/// Generate the distance field atlas, one byte per texel: 128 on the
+/// outline, higher inside.
+fn generate_sdf_atlas() -> (u32, u32, Vec<u8>) {
+    let cell_w = (GLYPH_W + 2 * SDF_PAD) * SDF_SCALE;
+    let cell_h = (GLYPH_H + 2 * SDF_PAD) * SDF_SCALE;
+    let w = ATLAS_COLS * cell_w;
+    let h = ATLAS_ROWS * cell_h;
+    let mut texels = vec![0u8; (w * h) as usize];
+
+    for i in 0..GLYPH_COUNT {
+        let bitmap = &FONT_DATA[i as usize];
+        let x0 = (i % ATLAS_COLS) * cell_w;
+        let y0 = (i / ATLAS_COLS) * cell_h;
+        for ty in 0..cell_h {
+            for tx in 0..cell_w {
+                // Texel center in font pixels from the glyph's top-left
+                let x = (tx as f32 + 0.5) / SDF_SCALE as f32 - SDF_PAD as f32;
+                let y = (ty as f32 + 0.5) / SDF_SCALE as f32 - SDF_PAD as f32;
+                let d = signed_distance(bitmap, x, y);
+                let value = (0.5 - d / (2.0 * SDF_PAD as f32)).clamp(0.0, 1.0);
+                texels[((y0 + ty) * w + x0 + tx) as usize] = (value * 255.0).round() as u8;
+            }
+        }
+    }
+
+    (w, h, texels)
+}
+
+// --- Disk cache ---
+
+/// Cached font atlases, relative to the project root.
+pub const FONT_CACHE_DIR: &str = ".naive/cache/fonts";
+
+const CACHE_MAGIC: &[u8; 8] = b"NAIVEFNT";
+
+/// Cache file of a font's distance field atlas at `size` px per glyph height.
+pub fn atlas_cache_path(cache_dir: &Path, font: &str, size: u32) -> PathBuf {
+    cache_dir.join(format!("{}-{}px-sdf.bin", font, size))
+}
+
+/// FNV-1a hash of what the atlas is built from, so edits to the glyphs or
+/// the field parameters invalidate the cache.
+fn atlas_source_hash() -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let params = [SDF_SCALE as u8, SDF_PAD as u8, ATLAS_COLS as u8];
+    for &byte in FONT_DATA.iter().flatten().chain(params.iter()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Read a cached atlas: None if missing, unreadable or built from another source.
+pub fn load_cached_atlas(path: &Path, source_hash: u64) -> Option<(u32, u32, Vec<u8>)> {
+    let bytes = std::fs::read(path).ok()?;
+    let header = bytes.get(..24)?;
+    if &header[..8] != CACHE_MAGIC || header[8..16] != source_hash.to_le_bytes() {
+        return None;
+    }
+    let w = u32::from_le_bytes(header[16..20].try_into().ok()?);
+    let h = u32::from_le_bytes(header[20..24].try_into().ok()?);
+    let texels = &bytes[24..];
+    (texels.len() == (w as usize) * (h as usize)).then(|| (w, h, texels.to_vec()))
+}
+
+/// Write an atlas for `load_cached_atlas`.
+pub fn save_cached_atlas(path: &Path, source_hash: u64, w: u32, h: u32, texels: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut bytes = Vec::with_capacity(24 + texels.len());
+    bytes.extend_from_slice(CACHE_MAGIC);
+    bytes.extend_from_slice(&source_hash.to_le_bytes());
+    bytes.extend_from_slice(&w.to_le_bytes());
+    bytes.extend_from_slice(&h.to_le_bytes());
+    bytes.extend_from_slice(texels);
+    std::fs::write(path, bytes)
+}
+
+/// The distance field atlas from the cache, or generated and cached.
+fn load_or_generate_sdf_atlas(cache_dir: Option<&Path>) -> (u32, u32, Vec<u8>) {
+    let hash = atlas_source_hash();
+    let path = cache_dir.map(|dir| atlas_cache_path(dir, "builtin", GLYPH_H * SDF_SCALE));
+    if let Some(atlas) = path.as_deref().and_then(|p| load_cached_atlas(p, hash)) {
+        tracing::info!("Font atlas read from cache");
+        return atlas;
+    }
+    let start = instant::Instant::now();
+    let (w, h, texels) = generate_sdf_atlas();
+    tracing::info!("Font distance field built in {:.0} ms", start.elapsed().as_secs_f64() * 1000.0);
+    if let Some(path) = &path {
+        if let Err(e) = save_cached_atlas(path, hash, w, h, &texels) {
+            tracing::warn!("Failed to cache font atlas at {}: {}", path.display(), e);
+        }
+    }
+    (w, h, texels)
+}
+
 // 6x8 bitmap font data for ASCII 32-126 (95 glyphs).
 // Each glyph is 8 bytes (8 rows), top 6 bits used per row.
 #[rustfmt::skip]
@@ -368,3 +552,32 @@ const FONT_DATA: [[u8; 8]; 95] = [
     // 126: '~'
     [0x40,0xA8,0x10,0x00,0x00,0x00,0x00,0x00],
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_distance() {
+        // '|': column 2 lit in rows 0-6
+        let bar = &FONT_DATA[('|' as u32 - 32) as usize];
+        assert!(signed_distance(bar, 2.5, 3.5) < 0.0);
+        assert!((signed_distance(bar, 2.5, 3.5) + 0.5).abs() < 1e-5);
+        assert!((signed_distance(bar, 4.0, 3.5) - 1.0).abs() < 1e-5);
+        // Saturates beyond the padding
+        assert_eq!(signed_distance(bar, 5.9, 3.5), SDF_PAD as f32);
+    }
+
+    #[test]
+    fn test_atlas_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("naive_font_cache_{}", std::process::id()));
+        let path = atlas_cache_path(&dir, "builtin", 64);
+        assert!(load_cached_atlas(&path, 7).is_none());
+
+        save_cached_atlas(&path, 7, 2, 3, &[1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(load_cached_atlas(&path, 7), Some((2, 3, vec![1, 2, 3, 4, 5, 6])));
+        // Built from other glyphs or parameters
+        assert!(load_cached_atlas(&path, 8).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
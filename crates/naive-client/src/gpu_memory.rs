@@ -0,0 +1,455 @@
+//! Sub-allocated GPU buffers.
+//!
+//! Mesh vertex and index data and splat storage live in a few large buffers
+//! per usage class instead of one `wgpu::Buffer` each, so a long session that
+//! loads many scenes doesn't leave the driver with thousands of scattered
+//! allocations. A class grows one block at a time; data bigger than a block
+//! gets a block of its own. A `SubBuffer` returns its range to the block's
+//! free list when its last clone drops, and an emptied block is released.
+//!
+//! On scene transitions the engine releases the meshes and splats no entity
+//! uses, then compacts any class whose blocks have become mostly holes: the
+//! live ranges are copied into fresh blocks on the GPU, their owners are
+//! pointed at the copies, and the old blocks go away (see `compact`).
+
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, Weak};
+
+/// What a sub-allocated buffer is bound as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BufferClass {
+    Vertex,
+    Index,
+    Storage,
+}
+
+impl BufferClass {
+    pub const ALL: [BufferClass; 3] = [BufferClass::Vertex, BufferClass::Index, BufferClass::Storage];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BufferClass::Vertex => "vertex",
+            BufferClass::Index => "index",
+            BufferClass::Storage => "storage",
+        }
+    }
+
+    fn usage(self) -> wgpu::BufferUsages {
+        let usage = match self {
+            BufferClass::Vertex => wgpu::BufferUsages::VERTEX,
+            BufferClass::Index => wgpu::BufferUsages::INDEX,
+            BufferClass::Storage => wgpu::BufferUsages::STORAGE,
+        };
+        usage | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST
+    }
+
+    /// Offset alignment. Storage ranges are bound at their offset, which must
+    /// be a multiple of `min_storage_buffer_offset_alignment` (256 at most).
+    fn alignment(self) -> u64 {
+        match self {
+            BufferClass::Vertex | BufferClass::Index => wgpu::COPY_BUFFER_ALIGNMENT,
+            BufferClass::Storage => 256,
+        }
+    }
+
+    /// Size of a shared block.
+    fn block_size(self) -> u64 {
+        match self {
+            BufferClass::Vertex => 32 << 20,
+            BufferClass::Index => 16 << 20,
+            BufferClass::Storage => 64 << 20,
+        }
+    }
+}
+
+/// Free ranges of a block, sorted by offset and never touching.
+#[derive(Debug, Clone)]
+struct FreeList {
+    size: u64,
+    free: Vec<Range<u64>>,
+}
+
+impl FreeList {
+    fn new(size: u64) -> Self {
+        Self { size, free: std::iter::once(0..size).collect() }
+    }
+
+    /// Take `size` bytes at the first `align`-aligned offset they fit.
+    fn alloc(&mut self, size: u64, align: u64) -> Option<u64> {
+        let (i, start) = self.free.iter().enumerate().find_map(|(i, range)| {
+            let start = range.start.next_multiple_of(align);
+            (start + size <= range.end).then_some((i, start))
+        })?;
+        let range = self.free[i].clone();
+        let rest = [range.start..start, start + size..range.end];
+        self.free.splice(i..=i, rest.into_iter().filter(|r| !r.is_empty()));
+        Some(start)
+    }
+
+    /// Return a range taken by `alloc`, merging it with its neighbours.
+    fn free(&mut self, offset: u64, size: u64) {
+        let at = self.free.partition_point(|r| r.start < offset);
+        self.free.insert(at, offset..offset + size);
+        if at + 1 < self.free.len() && self.free[at].end == self.free[at + 1].start {
+            self.free[at].end = self.free.remove(at + 1).end;
+        }
+        if at > 0 && self.free[at - 1].end == self.free[at].start {
+            self.free[at - 1].end = self.free.remove(at).end;
+        }
+    }
+
+    fn free_bytes(&self) -> u64 {
+        self.free.iter().map(|r| r.end - r.start).sum()
+    }
+
+    fn largest_free(&self) -> u64 {
+        self.free.iter().map(|r| r.end - r.start).max().unwrap_or(0)
+    }
+}
+
+struct Block {
+    id: u64,
+    buffer: Arc<wgpu::Buffer>,
+    free: FreeList,
+    allocations: usize,
+    /// Holds a single allocation bigger than a shared block.
+    dedicated: bool,
+    /// Being compacted away: takes no new allocations and is released once
+    /// its last range is freed.
+    retired: bool,
+}
+
+#[derive(Default)]
+struct ArenaState {
+    classes: [Vec<Block>; 3],
+    next_block: u64,
+    compactions: u32,
+    bytes_moved: u64,
+}
+
+impl ArenaState {
+    fn blocks(&mut self, class: BufferClass) -> &mut Vec<Block> {
+        &mut self.classes[class as usize]
+    }
+
+    fn release(&mut self, class: BufferClass, block: u64, offset: u64, size: u64) {
+        let blocks = self.blocks(class);
+        let Some(i) = blocks.iter().position(|b| b.id == block) else {
+            return;
+        };
+        let b = &mut blocks[i];
+        b.free.free(offset, size);
+        b.allocations -= 1;
+        if b.allocations == 0 && (b.dedicated || b.retired) {
+            blocks.remove(i);
+        }
+    }
+}
+
+/// Where a pooled range goes back to.
+struct Home {
+    arenas: Weak<Mutex<ArenaState>>,
+    class: BufferClass,
+    block: u64,
+    dedicated: bool,
+}
+
+struct Allocation {
+    buffer: Arc<wgpu::Buffer>,
+    offset: u64,
+    size: u64,
+    home: Option<Home>,
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        let Some(home) = &self.home else {
+            return;
+        };
+        if let Some(state) = home.arenas.upgrade() {
+            state.lock().unwrap().release(home.class, home.block, self.offset, self.size);
+        }
+    }
+}
+
+/// A range of a GPU buffer. Clones share the range, which goes back to its
+/// arena when the last one drops.
+#[derive(Clone)]
+pub struct SubBuffer(Arc<Allocation>);
+
+impl SubBuffer {
+    /// A whole buffer of its own, outside any arena (see `BufferArenas::adopt`).
+    pub fn dedicated(buffer: wgpu::Buffer) -> Self {
+        let size = buffer.size();
+        Self(Arc::new(Allocation { buffer: Arc::new(buffer), offset: 0, size, home: None }))
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.0.buffer
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.0.offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.0.size
+    }
+
+    /// The range, for `set_vertex_buffer` / `set_index_buffer`.
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.0.buffer.slice(self.0.offset..self.0.offset + self.0.size)
+    }
+
+    /// The range, for a bind group entry.
+    pub fn binding(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: &self.0.buffer,
+            offset: self.0.offset,
+            size: NonZeroU64::new(self.0.size),
+        })
+    }
+
+    /// Whether both are the same range (one is a clone of the other).
+    pub fn shares(&self, other: &SubBuffer) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Whether `compact` may move this range out of its block.
+    fn movable(&self, class: BufferClass) -> bool {
+        self.0.home.as_ref().is_some_and(|home| home.class == class && !home.dedicated)
+    }
+}
+
+/// Memory use of one buffer class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaStats {
+    pub class: BufferClass,
+    pub blocks: usize,
+    /// Bytes in all blocks.
+    pub capacity: u64,
+    /// Bytes handed out.
+    pub used: u64,
+    pub allocations: usize,
+    /// Biggest free range of any block.
+    pub largest_free: u64,
+}
+
+impl ArenaStats {
+    /// Share of the free bytes outside the largest free range: 0 when the
+    /// free space is one hole, near 1 when it is many small ones.
+    pub fn fragmentation(&self) -> f32 {
+        let free = self.capacity - self.used;
+        if free == 0 {
+            return 0.0;
+        }
+        1.0 - self.largest_free as f32 / free as f32
+    }
+}
+
+/// The usage-class arenas. Clones share them.
+#[derive(Clone, Default)]
+pub struct BufferArenas(Arc<Mutex<ArenaState>>);
+
+impl BufferArenas {
+    /// Reserve `size` bytes of `class`, growing the arena if no block has room.
+    pub fn alloc(&self, device: &wgpu::Device, class: BufferClass, size: u64) -> SubBuffer {
+        let size = size.max(1).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        let mut state = self.0.lock().unwrap();
+        let shared = class.block_size().min(device.limits().max_buffer_size);
+        let dedicated = size > shared;
+
+        let found = if dedicated {
+            None
+        } else {
+            state.blocks(class).iter_mut()
+                .filter(|b| !b.dedicated && !b.retired)
+                .find_map(|b| b.free.alloc(size, class.alignment()).map(|offset| (b, offset)))
+                .map(|(b, offset)| {
+                    b.allocations += 1;
+                    (b.id, b.buffer.clone(), offset)
+                })
+        };
+        let (block, buffer, offset) = found.unwrap_or_else(|| {
+            let id = state.next_block;
+            state.next_block += 1;
+            let block_size = if dedicated { size } else { shared };
+            let buffer = Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("{} arena block {}", class.name(), id)),
+                size: block_size,
+                usage: class.usage(),
+                mapped_at_creation: false,
+            }));
+            let mut free = FreeList::new(block_size);
+            let offset = free.alloc(size, class.alignment()).unwrap_or(0);
+            state.blocks(class).push(Block { id, buffer: buffer.clone(), free, allocations: 1, dedicated, retired: false });
+            (id, buffer, offset)
+        });
+
+        SubBuffer(Arc::new(Allocation {
+            buffer,
+            offset,
+            size,
+            home: Some(Home { arenas: Arc::downgrade(&self.0), class, block, dedicated }),
+        }))
+    }
+
+    /// Allocate and fill a range with `contents`.
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue, class: BufferClass, contents: &[u8]) -> SubBuffer {
+        let sub = self.alloc(device, class, contents.len() as u64);
+        if contents.len() as u64 == sub.size() {
+            queue.write_buffer(sub.buffer(), sub.offset(), contents);
+        } else {
+            let mut padded = contents.to_vec();
+            padded.resize(sub.size() as usize, 0);
+            queue.write_buffer(sub.buffer(), sub.offset(), &padded);
+        }
+        sub
+    }
+
+    /// Copy a dedicated buffer (which needs `COPY_SRC`) into the arena of
+    /// `class`. Ranges already in an arena come back as they are.
+    pub fn adopt(&self, device: &wgpu::Device, queue: &wgpu::Queue, class: BufferClass, buffer: &SubBuffer) -> SubBuffer {
+        if buffer.0.home.is_some() {
+            return buffer.clone();
+        }
+        let sub = self.alloc(device, class, buffer.size());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Arena Upload") });
+        encoder.copy_buffer_to_buffer(buffer.buffer(), buffer.offset(), sub.buffer(), sub.offset(), buffer.size());
+        queue.submit(std::iter::once(encoder.finish()));
+        sub
+    }
+
+    /// Whether `class` has more than one shared block and over a quarter of
+    /// their bytes free, so `compact` would give memory back.
+    pub fn needs_compaction(&self, class: BufferClass) -> bool {
+        let mut state = self.0.lock().unwrap();
+        let shared: Vec<&Block> = state.blocks(class).iter().filter(|b| !b.dedicated).collect();
+        let capacity: u64 = shared.iter().map(|b| b.free.size).sum();
+        let free: u64 = shared.iter().map(|b| b.free.free_bytes()).sum();
+        shared.len() > 1 && free > capacity / 4
+    }
+
+    /// Pack the live ranges of `class` into fresh blocks and repoint `live`
+    /// at the copies. `live` should hold every owner of the class's ranges: a
+    /// range left out keeps its old block alive. Ranges in dedicated blocks
+    /// stay where they are. Returns the bytes copied.
+    pub fn compact<'a>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        class: BufferClass,
+        live: impl IntoIterator<Item = &'a mut SubBuffer>,
+    ) -> u64 {
+        let live: Vec<&mut SubBuffer> = live.into_iter().filter(|b| b.movable(class)).collect();
+        {
+            let mut state = self.0.lock().unwrap();
+            let blocks = state.blocks(class);
+            blocks.retain(|b| b.allocations > 0);
+            for block in blocks.iter_mut().filter(|b| !b.dedicated) {
+                block.retired = true;
+            }
+        }
+
+        let mut copies: HashMap<*const Allocation, SubBuffer> = HashMap::new();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Arena Compaction") });
+        let mut moved = 0;
+        for old in &live {
+            let key = Arc::as_ptr(&old.0);
+            if copies.contains_key(&key) {
+                continue;
+            }
+            let new = self.alloc(device, class, old.size());
+            encoder.copy_buffer_to_buffer(old.buffer(), old.offset(), new.buffer(), new.offset(), old.size());
+            moved += old.size();
+            copies.insert(key, new);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+
+        // Dropping the old ranges (outside the lock) releases the retired blocks
+        for old in live {
+            *old = copies[&Arc::as_ptr(&old.0)].clone();
+        }
+        let mut state = self.0.lock().unwrap();
+        state.compactions += 1;
+        state.bytes_moved += moved;
+        moved
+    }
+
+    /// Per-class memory use.
+    pub fn stats(&self) -> Vec<ArenaStats> {
+        let state = self.0.lock().unwrap();
+        BufferClass::ALL
+            .iter()
+            .map(|&class| {
+                let blocks = &state.classes[class as usize];
+                let capacity = blocks.iter().map(|b| b.free.size).sum();
+                ArenaStats {
+                    class,
+                    blocks: blocks.len(),
+                    capacity,
+                    used: capacity - blocks.iter().map(|b| b.free.free_bytes()).sum::<u64>(),
+                    allocations: blocks.iter().map(|b| b.allocations).sum(),
+                    largest_free: blocks.iter().map(|b| b.free.largest_free()).max().unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Compactions run and bytes they copied, since startup.
+    pub fn compaction_totals(&self) -> (u32, u64) {
+        let state = self.0.lock().unwrap();
+        (state.compactions, state.bytes_moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_list_alignment_and_merging() {
+        let mut list = FreeList::new(1024);
+        assert_eq!(list.alloc(100, 4), Some(0));
+        // Aligned up past the first range, leaving a hole before it
+        assert_eq!(list.alloc(64, 256), Some(256));
+        assert_eq!(list.free, vec![100..256, 320..1024]);
+        assert_eq!(list.alloc(2048, 4), None);
+
+        list.free(0, 100);
+        assert_eq!(list.free, vec![0..256, 320..1024]);
+        list.free(256, 64);
+        assert_eq!(list.free, vec![0..1024]);
+        assert_eq!(list.free_bytes(), 1024);
+    }
+
+    #[test]
+    fn test_free_list_first_fit() {
+        let mut list = FreeList::new(400);
+        let offsets: Vec<u64> = (0..4).map(|_| list.alloc(100, 4).unwrap()).collect();
+        assert_eq!(offsets, vec![0, 100, 200, 300]);
+        list.free(100, 100);
+        list.free(300, 100);
+        assert_eq!(list.largest_free(), 100);
+        assert_eq!(list.alloc(150, 4), None);
+        assert_eq!(list.alloc(60, 4), Some(100));
+        assert_eq!(list.free, vec![160..200, 300..400]);
+    }
+
+    #[test]
+    fn test_fragmentation() {
+        let stats = |used, largest_free| ArenaStats {
+            class: BufferClass::Vertex,
+            blocks: 1,
+            capacity: 1000,
+            used,
+            allocations: 1,
+            largest_free,
+        };
+        assert_eq!(stats(1000, 0).fragmentation(), 0.0);
+        assert_eq!(stats(200, 800).fragmentation(), 0.0);
+        assert_eq!(stats(200, 200).fragmentation(), 0.75);
+    }
+}
@@ -0,0 +1,332 @@
+//! GPU backend, adapter, surface format and present mode selection.
+//!
+//! Options come from `graphics:` in naive.yaml and the matching CLI flags,
+//! with the flags taking priority. `naive doctor` prints what they resolve
+//! to on the current machine.
+
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+
+/// Graphics API to restrict wgpu to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuBackend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+}
+
+impl GpuBackend {
+    pub fn to_wgpu(self) -> wgpu::Backends {
+        match self {
+            GpuBackend::Vulkan => wgpu::Backends::VULKAN,
+            GpuBackend::Metal => wgpu::Backends::METAL,
+            GpuBackend::Dx12 => wgpu::Backends::DX12,
+            GpuBackend::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+/// Which adapter to pick when the machine has several.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdapterPreference {
+    /// Discrete GPU first.
+    #[default]
+    HighPerformance,
+    /// Integrated GPU first (longer battery life on laptops).
+    Integrated,
+}
+
+/// Swapchain color encoding.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SurfaceFormatChoice {
+    /// sRGB format; the display encodes shader output.
+    #[default]
+    Srgb,
+    /// Non-sRGB format; shader output is presented as-is.
+    Linear,
+}
+
+/// Presentation mode, i.e. how frames wait for the display.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PresentModeChoice {
+    /// Vsync (supported everywhere).
+    #[default]
+    Fifo,
+    /// Vsync, but late frames tear instead of waiting.
+    FifoRelaxed,
+    /// Low-latency vsync: the newest frame replaces queued ones.
+    Mailbox,
+    /// No vsync; may tear.
+    Immediate,
+    /// Best available vsync mode.
+    AutoVsync,
+    /// Best available mode without vsync.
+    AutoNoVsync,
+}
+
+impl PresentModeChoice {
+    pub fn to_wgpu(self) -> wgpu::PresentMode {
+        match self {
+            PresentModeChoice::Fifo => wgpu::PresentMode::Fifo,
+            PresentModeChoice::FifoRelaxed => wgpu::PresentMode::FifoRelaxed,
+            PresentModeChoice::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentModeChoice::Immediate => wgpu::PresentMode::Immediate,
+            PresentModeChoice::AutoVsync => wgpu::PresentMode::AutoVsync,
+            PresentModeChoice::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+        }
+    }
+}
+
+/// GPU selection options (`graphics:` in naive.yaml, or `--backend`,
+/// `--adapter`, `--surface-format` and `--present-mode`). Unset options keep
+/// the engine defaults.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Args)]
+pub struct GraphicsOptions {
+    /// Graphics API: vulkan, metal, dx12 or gl (default: any available)
+    #[arg(long, global = true)]
+    pub backend: Option<GpuBackend>,
+
+    /// Adapter preference on multi-GPU machines
+    #[arg(long, global = true)]
+    pub adapter: Option<AdapterPreference>,
+
+    /// Swapchain color encoding
+    #[arg(long, global = true)]
+    pub surface_format: Option<SurfaceFormatChoice>,
+
+    /// Presentation mode
+    #[arg(long, global = true)]
+    pub present_mode: Option<PresentModeChoice>,
+}
+
+impl GraphicsOptions {
+    /// These options, with unset ones taken from `fallback`.
+    pub fn or(&self, fallback: &GraphicsOptions) -> GraphicsOptions {
+        GraphicsOptions {
+            backend: self.backend.or(fallback.backend),
+            adapter: self.adapter.or(fallback.adapter),
+            surface_format: self.surface_format.or(fallback.surface_format),
+            present_mode: self.present_mode.or(fallback.present_mode),
+        }
+    }
+
+    pub fn backends(&self) -> wgpu::Backends {
+        self.backend.map_or(wgpu::Backends::all(), GpuBackend::to_wgpu)
+    }
+
+    pub fn power_preference(&self) -> wgpu::PowerPreference {
+        match self.adapter.unwrap_or_default() {
+            AdapterPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+            AdapterPreference::Integrated => wgpu::PowerPreference::LowPower,
+        }
+    }
+}
+
+/// Sort key for adapters under `preference` (lower is better). Drivers don't
+/// always honor `PowerPreference`, so the device type decides.
+fn adapter_rank(device_type: wgpu::DeviceType, preference: AdapterPreference) -> u8 {
+    use wgpu::DeviceType;
+    match (preference, device_type) {
+        (AdapterPreference::HighPerformance, DeviceType::DiscreteGpu)
+        | (AdapterPreference::Integrated, DeviceType::IntegratedGpu) => 0,
+        (_, DeviceType::DiscreteGpu | DeviceType::IntegratedGpu) => 1,
+        (_, DeviceType::VirtualGpu | DeviceType::Other) => 2,
+        (_, DeviceType::Cpu) => 3,
+    }
+}
+
+/// Index of the adapter to use among `infos`, or None if the list is empty.
+pub fn preferred_adapter(infos: &[wgpu::AdapterInfo], options: &GraphicsOptions) -> Option<usize> {
+    let preference = options.adapter.unwrap_or_default();
+    (0..infos.len()).min_by_key(|&i| adapter_rank(infos[i].device_type, preference))
+}
+
+/// Pick an adapter on the configured backends that can present to `surface`
+/// (any adapter when there is none), falling back to wgpu's own choice.
+pub async fn request_adapter(
+    instance: &wgpu::Instance,
+    options: &GraphicsOptions,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> Option<wgpu::Adapter> {
+    let mut adapters: Vec<wgpu::Adapter> = instance
+        .enumerate_adapters(options.backends())
+        .into_iter()
+        .filter(|a| surface.is_none_or(|s| a.is_surface_supported(s)))
+        .collect();
+    let infos: Vec<wgpu::AdapterInfo> = adapters.iter().map(|a| a.get_info()).collect();
+    if let Some(index) = preferred_adapter(&infos, options) {
+        return Some(adapters.swap_remove(index));
+    }
+    instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: options.power_preference(),
+            compatible_surface: surface,
+            force_fallback_adapter: false,
+        })
+        .await
+}
+
+/// The surface format matching `choice`, or the surface's preferred format
+/// (with a warning) if it offers none.
+pub fn pick_surface_format(formats: &[wgpu::TextureFormat], choice: SurfaceFormatChoice) -> wgpu::TextureFormat {
+    let wants_srgb = choice == SurfaceFormatChoice::Srgb;
+    formats.iter().copied().find(|f| f.is_srgb() == wants_srgb).unwrap_or_else(|| {
+        tracing::warn!("Surface has no {:?} format, using {:?}", choice, formats[0]);
+        formats[0]
+    })
+}
+
+/// `choice` if the surface supports it, else Fifo (with a warning). The
+/// Auto modes always resolve to a supported mode.
+pub fn pick_present_mode(modes: &[wgpu::PresentMode], choice: PresentModeChoice) -> wgpu::PresentMode {
+    let mode = choice.to_wgpu();
+    let auto = matches!(mode, wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync);
+    if auto || modes.contains(&mode) {
+        mode
+    } else {
+        tracing::warn!("Present mode {:?} unsupported by this surface, using Fifo", mode);
+        wgpu::PresentMode::Fifo
+    }
+}
+
+/// CLI/YAML name of an option value.
+fn value_name<T: ValueEnum>(value: Option<T>, unset: &str) -> String {
+    value
+        .and_then(|v| v.to_possible_value())
+        .map_or_else(|| unset.to_string(), |v| v.get_name().to_string())
+}
+
+/// Report for `naive doctor`: the resolved options and the adapters found,
+/// marking the one the engine would pick.
+pub fn doctor_report(options: &GraphicsOptions) -> String {
+    let mut report = String::from("Graphics configuration:\n");
+    report.push_str(&format!("  backend:        {}\n", value_name(options.backend, "any")));
+    report.push_str(&format!("  adapter:        {}\n", value_name(Some(options.adapter.unwrap_or_default()), "")));
+    report.push_str(&format!(
+        "  surface format: {}\n",
+        value_name(Some(options.surface_format.unwrap_or_default()), "")
+    ));
+    report.push_str(&format!(
+        "  present mode:   {}\n",
+        value_name(Some(options.present_mode.unwrap_or_default()), "")
+    ));
+
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: options.backends(),
+        ..Default::default()
+    });
+    let adapters = instance.enumerate_adapters(options.backends());
+    let infos: Vec<wgpu::AdapterInfo> = adapters.iter().map(|a| a.get_info()).collect();
+    if infos.is_empty() {
+        report.push_str("\nNo GPU adapters found on the selected backend.\n");
+        return report;
+    }
+    let selected = preferred_adapter(&infos, options);
+    report.push_str("\nAdapters:\n");
+    for (i, info) in infos.iter().enumerate() {
+        let marker = if Some(i) == selected { "*" } else { " " };
+        report.push_str(&format!(
+            "  {} {} ({:?}, {:?}, driver {})\n",
+            marker, info.name, info.device_type, info.backend, info.driver_info
+        ));
+    }
+    if let Some(adapter) = selected.map(|i| &adapters[i]) {
+        report.push_str(&capability_report(adapter));
+    }
+    report.push_str("\nSurface format and present mode are checked against the window at startup;\n");
+    report.push_str("unsupported choices fall back to the defaults with a warning.\n");
+    report
+}
+
+/// What the pipeline and splat loader will downgrade on a device.
+fn capability_report(adapter: &wgpu::Adapter) -> String {
+    use crate::pipeline::downgrade::{device_format_features, gpu_culling_supported, light_capacity, supported_format};
+    let features = crate::renderer::device_features(adapter);
+    let limits = &adapter.limits();
+    let format_features =
+        |format| device_format_features(format, adapter.get_texture_format_features(format), features);
+    let mut report = String::from("\nCapabilities of the selected adapter:\n");
+    let rgba32f = match supported_format(wgpu::TextureFormat::Rgba32Float, &format_features) {
+        Some(wgpu::TextureFormat::Rgba32Float) => "supported".to_string(),
+        Some(fallback) => format!("falls back to {:?}", fallback),
+        None => "unsupported".to_string(),
+    };
+    report.push_str(&format!("  rgba32f resources: {}\n", rgba32f));
+    report.push_str(&format!(
+        "  point lights:      {} of {}\n",
+        light_capacity(limits),
+        crate::pipeline::MAX_LIGHTS
+    ));
+    report.push_str(&format!(
+        "  splats per chunk:  {} ({} with SH)\n",
+        crate::splat::splats_per_chunk(limits, false),
+        crate::splat::splats_per_chunk(limits, true)
+    ));
+    let gpu_culling = if gpu_culling_supported(features, limits) { "supported" } else { "falls back to CPU" };
+    report.push_str(&format!("  gpu culling:       {}\n", gpu_culling));
+    let splat_sort = if crate::splat_sort::gpu_sort_supported(limits) { "gpu" } else { "falls back to CPU" };
+    report.push_str(&format!("  splat sorting:     {}\n", splat_sort));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(name: &str, device_type: wgpu::DeviceType) -> wgpu::AdapterInfo {
+        wgpu::AdapterInfo {
+            name: name.to_string(),
+            vendor: 0,
+            device: 0,
+            device_type,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: wgpu::Backend::Vulkan,
+        }
+    }
+
+    #[test]
+    fn test_preferred_adapter_follows_device_type() {
+        let infos = vec![
+            adapter("llvmpipe", wgpu::DeviceType::Cpu),
+            adapter("Intel Iris Xe", wgpu::DeviceType::IntegratedGpu),
+            adapter("RTX 4060", wgpu::DeviceType::DiscreteGpu),
+        ];
+        let mut options = GraphicsOptions::default();
+        assert_eq!(preferred_adapter(&infos, &options), Some(2));
+        options.adapter = Some(AdapterPreference::Integrated);
+        assert_eq!(preferred_adapter(&infos, &options), Some(1));
+        assert_eq!(preferred_adapter(&infos[..1], &options), Some(0));
+        assert_eq!(preferred_adapter(&[], &options), None);
+    }
+
+    #[test]
+    fn test_surface_choices_fall_back_when_unsupported() {
+        use wgpu::TextureFormat::{Bgra8Unorm, Bgra8UnormSrgb};
+        assert_eq!(pick_surface_format(&[Bgra8Unorm, Bgra8UnormSrgb], SurfaceFormatChoice::Srgb), Bgra8UnormSrgb);
+        assert_eq!(pick_surface_format(&[Bgra8UnormSrgb, Bgra8Unorm], SurfaceFormatChoice::Linear), Bgra8Unorm);
+        assert_eq!(pick_surface_format(&[Bgra8UnormSrgb], SurfaceFormatChoice::Linear), Bgra8UnormSrgb);
+
+        let modes = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate];
+        assert_eq!(pick_present_mode(&modes, PresentModeChoice::Immediate), wgpu::PresentMode::Immediate);
+        assert_eq!(pick_present_mode(&modes, PresentModeChoice::Mailbox), wgpu::PresentMode::Fifo);
+        assert_eq!(pick_present_mode(&modes, PresentModeChoice::AutoNoVsync), wgpu::PresentMode::AutoNoVsync);
+    }
+
+    #[test]
+    fn test_yaml_options_merge_under_cli_flags() {
+        let yaml: GraphicsOptions =
+            serde_yaml::from_str("backend: vulkan\nadapter: integrated\npresent_mode: fifo-relaxed\n").unwrap();
+        let cli = GraphicsOptions { adapter: Some(AdapterPreference::HighPerformance), ..Default::default() };
+        let merged = cli.or(&yaml);
+        assert_eq!(merged.backends(), wgpu::Backends::VULKAN);
+        assert_eq!(merged.adapter, Some(AdapterPreference::HighPerformance));
+        assert_eq!(merged.present_mode, Some(PresentModeChoice::FifoRelaxed));
+        assert_eq!(merged.surface_format, None);
+    }
+}
@@ -0,0 +1,341 @@
+//! Headless rendering: run the compiled render pipeline into an offscreen texture.
+//!
+//! No window or surface is created, so the full pipeline (shadow, geometry,
+//! splats, lighting, bloom, tonemap) can be exercised on CI machines and by
+//! `naive test --headless`. `naive preview` uses the same renderer for
+//! one-off thumbnails. `naive run --headless` instead drives the full engine
+//! into an offscreen target (see `Engine::run_headless`).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::camera::CameraState;
+use crate::components::{Camera, CameraRole, Player, Transform};
+use crate::material::MaterialCache;
+use crate::mesh::MeshCache;
+use crate::physics::PhysicsWorld;
+use crate::renderer::DrawUniformPool;
+use crate::scene::SceneFile;
+use crate::splat::SplatCache;
+use crate::world::SceneWorld;
+
+/// Color format of the offscreen target (matches the sRGB surface the window path prefers).
+pub const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Default offscreen resolution for headless runs.
+pub const DEFAULT_HEADLESS_WIDTH: u32 = 1280;
+pub const DEFAULT_HEADLESS_HEIGHT: u32 = 720;
+
+/// Renders scenes into an offscreen color target with the project's pipeline
+/// (or the forward renderer if the project has none).
+pub struct HeadlessRenderer {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub camera_state: CameraState,
+    pub draw_pool: DrawUniformPool,
+    pub texture_resources: crate::mesh::TextureResources,
+    pub texture_cache: crate::texture_cache::TextureCache,
+    pub mesh_cache: MeshCache,
+    pub material_cache: MaterialCache,
+    pub splat_cache: SplatCache,
+    environment_cache: crate::environment::EnvironmentCache,
+    color_grading_cache: crate::color_grading::ColorGradingCache,
+    compiled: Option<crate::pipeline::CompiledPipeline>,
+    forward_pipeline: Option<wgpu::RenderPipeline>,
+    target: wgpu::Texture,
+    target_view: wgpu::TextureView,
+    _depth_texture: wgpu::Texture,
+    depth_view: wgpu::TextureView,
+    pub width: u32,
+    pub height: u32,
+    project_root: PathBuf,
+    /// Frames rendered since creation.
+    pub frames_rendered: u64,
+    /// Render toggles and LOD locks frames are drawn with.
+    pub render_debug: crate::pipeline::RenderDebugState,
+}
+
+impl HeadlessRenderer {
+    /// Create an offscreen device and compile `pipeline` (relative to the
+    /// project root; defaults to pipelines/render.yaml if present).
+    pub fn new(project_root: &Path, pipeline: Option<&str>, width: u32, height: u32) -> Result<Self, String> {
+        let (width, height) = (width.max(1), height.max(1));
+        let graphics = crate::gpu_options::GraphicsOptions::default();
+        let instance = crate::renderer::create_instance(&graphics);
+        let (adapter, device, queue) =
+            pollster::block_on(crate::renderer::request_device(&instance, &graphics, None))?;
+
+        let camera_state = CameraState::new(&device);
+        let draw_pool = DrawUniformPool::new(&device);
+        let mut texture_resources = crate::mesh::TextureResources::new(&device, &queue);
+
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Target"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HEADLESS_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let (depth_texture, depth_view) = crate::renderer::create_depth_texture(&device, width, height);
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let compiled = load_compiled_pipeline(
+            &device, &adapter, project_root, pipeline, &camera_state, &draw_pool, &texture_resources, width, height,
+        )?;
+        let mut splat_cache = SplatCache::new();
+        splat_cache.enable_gpu_sort(&device);
+        let forward_pipeline = match compiled {
+            Some(_) => None,
+            None => {
+                let forward_slang = project_root.join("shaders/passes/mesh_forward.slang");
+                let forward_wgsl = crate::shader::compile_mesh_forward_shader(Some(&forward_slang))
+                    .unwrap_or_else(|_| crate::shader::get_mesh_forward_wgsl());
+                Some(crate::renderer::create_forward_pipeline(
+                    &device,
+                    &forward_wgsl,
+                    HEADLESS_FORMAT,
+                    &camera_state.bind_group_layout,
+                    &draw_pool.bind_group_layout,
+                    Some(&texture_resources.bind_group_layout),
+                ))
+            }
+        };
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(format!("Pipeline validation failed: {}", error));
+        }
+        if let Some(compiled) = &compiled {
+            texture_resources.set_named_samplers(compiled.samplers.clone());
+        }
+
+        Ok(Self {
+            device,
+            queue,
+            camera_state,
+            draw_pool,
+            texture_resources,
+            texture_cache: crate::texture_cache::TextureCache::new(),
+            mesh_cache: MeshCache::new(),
+            material_cache: MaterialCache::new(),
+            splat_cache,
+            environment_cache: crate::environment::EnvironmentCache::new(),
+            color_grading_cache: crate::color_grading::ColorGradingCache::new(),
+            compiled,
+            forward_pipeline,
+            target,
+            target_view,
+            _depth_texture: depth_texture,
+            depth_view,
+            width,
+            height,
+            project_root: project_root.to_path_buf(),
+            frames_rendered: 0,
+            render_debug: Default::default(),
+        })
+    }
+
+    /// Whether frames go through a compiled pipeline (vs. the forward fallback).
+    pub fn has_pipeline(&self) -> bool {
+        self.compiled.is_some()
+    }
+
+    /// Spawn a scene with GPU resources (meshes, materials, splats) from this renderer.
+    pub fn spawn_scene(&mut self, scene_world: &mut SceneWorld, scene: &SceneFile, physics_world: Option<&mut PhysicsWorld>) {
+        crate::world::spawn_all_entities(
+            scene_world,
+            scene,
+            &self.device,
+            &self.queue,
+            &self.project_root,
+            &mut self.mesh_cache,
+            &mut self.material_cache,
+            &mut self.splat_cache,
+            physics_world,
+            Some(&self.texture_resources),
+            Some(&mut self.texture_cache),
+        );
+        crate::transform::update_transforms(&mut scene_world.world);
+    }
+
+    /// Render one frame from the scene's own camera (player camera, else main camera).
+    pub fn render_scene(&mut self, scene_world: &SceneWorld) -> Result<(), String> {
+        let (camera, cam_transform) = frame_camera(scene_world)
+            .ok_or_else(|| "Scene has no main camera to render from".to_string())?;
+        self.render(scene_world, &camera, &cam_transform)
+    }
+
+    /// Render one frame from the given camera into the offscreen target.
+    /// GPU validation errors during the frame are returned as `Err`.
+    pub fn render(&mut self, scene_world: &SceneWorld, camera: &Camera, cam_transform: &Transform) -> Result<(), String> {
+        let draws = crate::renderer::collect_frame_draws(scene_world, &self.mesh_cache);
+        self.draw_pool.begin_frame(&self.device, draws);
+
+        self.camera_state.update(&self.queue, camera, cam_transform, self.width, self.height);
+        self.splat_cache.prepare_frame(&scene_world.world, &self.device, &self.queue);
+
+        if let Some(compiled) = &mut self.compiled {
+            self.environment_cache.sync(&self.device, &self.queue, &self.project_root, &scene_world.world, compiled);
+            let grading = scene_world.current_scene.as_ref().and_then(|s| s.settings.color_grading.as_ref());
+            self.color_grading_cache.sync(&self.device, &self.queue, &self.project_root, grading, compiled);
+        }
+
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let command_buffers = match (&self.compiled, &self.forward_pipeline) {
+            (Some(compiled), _) => Vec::from(crate::pipeline::execute_pipeline_to_view(
+                &self.device,
+                &self.queue,
+                compiled,
+                scene_world,
+                &self.camera_state,
+                &self.draw_pool,
+                &self.mesh_cache,
+                &self.material_cache,
+                &self.splat_cache,
+                &self.target_view,
+                &self.render_debug,
+                Some(&self.texture_resources),
+                &HashMap::new(),
+                None,
+                None,
+            ).finish()),
+            (None, Some(forward_pipeline)) => {
+                let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Headless Forward Encoder"),
+                });
+                crate::renderer::render_scene_to_view(
+                    &self.queue,
+                    scene_world,
+                    &self.camera_state,
+                    &self.draw_pool,
+                    &self.mesh_cache,
+                    &self.material_cache,
+                    forward_pipeline,
+                    &self.target_view,
+                    &self.depth_view,
+                    &mut encoder,
+                    Some(&self.texture_resources),
+                );
+                vec![encoder.finish()]
+            }
+            (None, None) => unreachable!("headless renderer has neither a pipeline nor a forward fallback"),
+        };
+        self.queue.submit(command_buffers);
+        if let Some(error) = pollster::block_on(self.device.pop_error_scope()) {
+            return Err(format!("GPU error during headless frame: {}", error));
+        }
+        self.frames_rendered += 1;
+        Ok(())
+    }
+
+    /// Read the last rendered frame back as tightly packed RGBA8 pixels.
+    pub fn read_pixels(&self) -> Result<Vec<u8>, String> {
+        crate::screenshot::read_texture_rgba8(&self.device, &self.queue, &self.target, self.width, self.height, HEADLESS_FORMAT)
+    }
+
+    /// Read the last rendered frame back and write it as a PNG.
+    pub fn save_png(&self, out: &Path) -> Result<(), String> {
+        let pixels = self.read_pixels()?;
+        crate::screenshot::write_png(out, &pixels, self.width, self.height)
+    }
+}
+
+/// Summary of a headless `naive run`.
+#[derive(Debug, Clone)]
+pub struct HeadlessRunReport {
+    pub frames: u64,
+    /// Simulated game time, in seconds.
+    pub game_time: f32,
+    /// Average wall-clock time per simulated and rendered frame.
+    pub avg_frame_ms: f32,
+}
+
+/// Load and compile the render pipeline, or `None` if the project has none.
+#[allow(clippy::too_many_arguments)]
+fn load_compiled_pipeline(
+    device: &wgpu::Device,
+    adapter: &wgpu::Adapter,
+    project_root: &Path,
+    pipeline: Option<&str>,
+    camera_state: &CameraState,
+    draw_pool: &DrawUniformPool,
+    tex_res: &crate::mesh::TextureResources,
+    width: u32,
+    height: u32,
+) -> Result<Option<crate::pipeline::CompiledPipeline>, String> {
+    let pipeline_path = match pipeline {
+        Some(p) => project_root.join(p),
+        None => {
+            let default_path = project_root.join("pipelines/render.yaml");
+            if !default_path.exists() {
+                return Ok(None);
+            }
+            default_path
+        }
+    };
+
+    let pipeline_file = crate::pipeline::load_pipeline(&pipeline_path)
+        .map_err(|e| format!("Failed to load pipeline: {}", e))?;
+    crate::pipeline::compile_pipeline(
+        device,
+        adapter,
+        &pipeline_file,
+        project_root,
+        camera_state,
+        draw_pool,
+        HEADLESS_FORMAT,
+        width,
+        height,
+        Some(&tex_res.bind_group_layout),
+    )
+    .map(Some)
+    .map_err(|e| format!("Pipeline compilation failed: {}", e))
+}
+
+/// Camera the game would render from: the player's first-person view if the
+/// player carries the main camera, otherwise the main camera entity.
+pub fn frame_camera(scene_world: &SceneWorld) -> Option<(Camera, Transform)> {
+    let player_view = scene_world
+        .world
+        .query::<(&Transform, &Player, &Camera)>()
+        .iter()
+        .find(|(_, (_, _, camera))| camera.role == CameraRole::Main)
+        .map(|(_, (transform, player, camera))| {
+            let rotation = glam::Quat::from_rotation_y(player.yaw) * glam::Quat::from_rotation_x(player.pitch);
+            let view = Transform {
+                position: transform.position + glam::Vec3::new(0.0, player.height * 0.4, 0.0),
+                rotation,
+                ..transform.clone()
+            };
+            (camera.clone(), view)
+        });
+    player_view.or_else(|| {
+        scene_world
+            .world
+            .query::<(&Transform, &Camera)>()
+            .iter()
+            .find(|(_, (_, camera))| camera.role == CameraRole::Main)
+            .map(|(_, (transform, camera))| (camera.clone(), transform.clone()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_camera_prefers_player_view() {
+        let mut sw = SceneWorld::new();
+        let main = Camera { role: CameraRole::Main, ..Default::default() };
+        sw.world.spawn((Transform::default(), main.clone()));
+        assert!(frame_camera(&sw).is_some());
+
+        let player = Player { yaw: 0.0, pitch: 0.0, height: 2.0, radius: 0.3 };
+        let body = Transform { position: glam::Vec3::new(5.0, 0.0, 0.0), ..Default::default() };
+        sw.world.spawn((body, player, main));
+        let (_, view) = frame_camera(&sw).unwrap();
+        assert_eq!(view.position, glam::Vec3::new(5.0, 0.8, 0.0));
+    }
+}
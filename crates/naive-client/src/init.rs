@@ -39,6 +39,7 @@ pub fn create_project(name: &str) -> Result<(), String> {
     }
 
     // naive.yaml
+    let api_level = crate::scripting::LUA_API_LEVEL;
     write_file(
         &root.join("naive.yaml"),
         &format!(
@@ -57,6 +58,9 @@ build:
     - "windows"
     - "linux"
 
+scripting:
+  api_level: {api_level}
+
 dev_log:
   enabled: true
   submit_on_complete: true
@@ -141,6 +145,8 @@ entities:
       character_controller:
         speed: 5.0
         jump_force: 8.0
+        push_force: 400.0               # max force on dynamic props walked into
+        mass: 80.0                      # heavier props slow the character more
       health:
         max: 100                        # current defaults to max
       collision_damage:
@@ -162,6 +168,10 @@ entities:
 - `procedural:cylinder` — Y-axis cylinder, radius 0.5, height 1.0 (built-in)
 - `procedural:cone` — Y-axis cone, radius 0.5, height 1.0 (built-in)
 - `procedural:torus` — torus (donut), major radius 0.3, minor radius 0.1 (built-in)
+- `procedural:capsule` — Y-axis capsule, radius 0.5, height 2.0 (built-in)
+- `procedural:icosphere` — evenly tessellated sphere, radius 0.5 (built-in)
+- `procedural:rounded_box` — 1x1x1 box with rounded edges (built-in)
+- `capsule(0.4, 1.8)` — any built-in shape with its own size and detail
 - `assets/meshes/file.gltf` — load a glTF/GLB model file
 - `assets/meshes/file.ply` — load a PLY point cloud
 - If a mesh file is missing, the engine silently falls back to a procedural cube
@@ -201,6 +211,7 @@ function fixed_update(dt)    -- called at fixed physics timestep
 function on_destroy()        -- called when entity is destroyed
 function on_collision(other_entity_id)    -- physics collision
 function on_trigger_enter(other_entity_id) -- trigger volume enter
+function on_trigger_stay(other_entity_id, elapsed) -- still inside (every 0.1s by default)
 function on_trigger_exit(other_entity_id)  -- trigger volume exit
 function on_damage(amount, source_id)     -- entity took damage (requires health component)
 function on_death()                       -- entity health reached 0 (requires health component)
@@ -350,10 +361,15 @@ camera:
   distance: 5.0               # third_person only: orbit distance behind player
   height_offset: 2.0          # third_person only: camera target height above player
   pitch_limits: [-60, 75]     # [min_degrees, max_degrees] for look up/down
+  position_damping: 0.15      # third_person only: seconds to ease toward the orbit position
+  rotation_damping: 0.1       # third_person only: seconds to ease the rotation
+  look_ahead: 0.4             # third_person only: seconds of movement to lead the player by
 ```
 
 Third-person camera automatically orbits behind the player and handles wall
-collision (pulls camera closer to avoid clipping through geometry).
+collision (pulls camera closer to avoid clipping through geometry). The
+damping and look-ahead settings default to 0, which snaps the camera to its
+orbit every frame.
 
 ### Physics API — `physics.*`
 
@@ -365,6 +381,29 @@ local hit, dist, nx, ny, nz = physics.raycast(ox, oy, oz, dx, dy, dz, max_dist)
 -- Returns: hit(bool), entity_id(string), distance, hit_x, hit_y, hit_z, normal_x, normal_y, normal_z
 local hit, eid, dist, hx, hy, hz, nx, ny, nz = physics.hitscan(ox, oy, oz, dx, dy, dz, range)
 -- Use with entity.damage(): if hit then entity.damage(eid, 25) end
+
+-- Optional last argument filters hits for both raycast and hitscan:
+-- {{ exclude = id|{{ids}}, include_tags = tag|{{tags}}, exclude_tags = tag|{{tags}}, triggers = bool }}
+local hit, eid = physics.hitscan(ox, oy, oz, dx, dy, dz, range, {{ exclude = _entity_string_id, triggers = false }})
+
+-- Entity ids currently inside a trigger volume
+for _, id in ipairs(physics.volume_contents("lava_zone")) do entity.damage(id, 5) end
+physics.set_trigger_stay_interval(0.25)  -- on_trigger_stay rate (seconds)
+```
+
+### Time API — `time.*`
+
+```lua
+time.set_scale(0.2)                  -- global slow motion (0 = frozen, 1 = normal)
+time.set_entity_scale("player", 5)   -- per-entity multiplier: update dt, tweens, animation, particles
+time.set_physics_scaled(false)       -- physics in real time (default follows the global scale)
+local real_dt = time.unscaled_dt()   -- real frame time, for UI during slow motion
+```
+
+### Screenshots
+
+```lua
+capture_screenshot("screenshots/shot.png")  -- PNG of this frame, path relative to the project
 ```
 
 ### Math Utilities
@@ -629,6 +668,8 @@ improving the nAIVE engine.
     write_file(
         &root.join(".gitignore"),
         r#"dist/
+analytics/
+.naive/
 *.log
 !dev.log
 .DS_Store
@@ -735,6 +776,7 @@ cull_mode: back
 settings:
   vsync: true
   hdr: true
+  # samples: 4   # MSAA for rasterize passes (1 = off)
 
 resources:
   - name: gbuffer_albedo
@@ -769,6 +811,16 @@ resources:
     type: texture_2d
     format: depth32f
     size: "[2048, 2048]"
+  # Ambient occlusion: declaring this resource adds the built-in SSAO pass
+  # - name: ssao
+  #   type: texture_2d
+  #   format: rgba8
+  #   size: viewport/2
+  # Temporal anti-aliasing history, read and updated by taa_pass below
+  # - name: taa_history
+  #   type: texture_2d
+  #   format: rgba8
+  #   size: viewport
 
 passes:
   - name: shadow_pass
@@ -804,6 +856,30 @@ passes:
     outputs:
       color: hdr_buffer
 
+  # Background from the scene's skybox component (draws nothing without one)
+  - name: skybox_pass
+    type: skybox
+    inputs:
+      depth: gbuffer_depth
+    outputs:
+      color: hdr_buffer
+
+  # Water components over the lit scene (draws nothing without one)
+  - name: water_pass
+    type: water
+    inputs:
+      depth: gbuffer_depth
+    outputs:
+      color: hdr_buffer
+
+  # Particle emitters, soft against the scene depth
+  - name: particle_pass
+    type: particles
+    inputs:
+      depth: gbuffer_depth
+    outputs:
+      color: hdr_buffer
+
   - name: bloom_pass
     type: fullscreen
     shader: shaders/passes/bloom.slang
@@ -811,6 +887,11 @@ passes:
       hdr: hdr_buffer
     outputs:
       color: bloom_buffer
+    bloom:
+      threshold: 0.5
+      knee: 0.3
+      intensity: 0.7
+      mips: 6
 
   - name: tonemap_pass
     type: fullscreen
@@ -828,6 +909,16 @@ passes:
       ldr: ldr_buffer
     outputs:
       color: swapchain
+
+  # TAA instead of FXAA: replace fxaa_pass with this and declare taa_history
+  # - name: taa_pass
+  #   type: taa
+  #   inputs:
+  #     color: ldr_buffer
+  #     depth: gbuffer_depth
+  #     history: taa_history
+  #   outputs:
+  #     color: swapchain
 "#,
     )?;
 
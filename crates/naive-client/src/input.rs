@@ -51,25 +51,89 @@ impl Default for InputBindings {
     }
 }
 
+impl InputTrigger {
+    /// Parse a trigger name: `Left`, `Right` and `Middle` are mouse buttons,
+    /// anything else must be a known key name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "Left" | "Right" | "Middle" => Some(InputTrigger::Mouse(name.to_string())),
+            _ => key_name_to_code(name).map(|_| InputTrigger::Key(name.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            InputTrigger::Key(name) | InputTrigger::Mouse(name) => name,
+        }
+    }
+}
+
+/// A key or mouse button bound to more than one action.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BindingConflict {
+    pub trigger: String,
+    pub actions: Vec<String>,
+}
+
+impl InputBindings {
+    /// Triggers bound to more than one action, sorted by trigger name.
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut by_trigger: HashMap<&str, Vec<String>> = HashMap::new();
+        for (action, triggers) in &self.actions {
+            for trigger in triggers {
+                let actions = by_trigger.entry(trigger.name()).or_default();
+                if !actions.contains(action) {
+                    actions.push(action.clone());
+                }
+            }
+        }
+        let mut conflicts: Vec<BindingConflict> = by_trigger
+            .into_iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .map(|(trigger, mut actions)| {
+                actions.sort();
+                BindingConflict { trigger: trigger.to_string(), actions }
+            })
+            .collect();
+        conflicts.sort_by(|a, b| a.trigger.cmp(&b.trigger));
+        conflicts
+    }
+}
+
+/// Default bindings file, relative to the project root (`input.bindings`
+/// in naive.yaml picks another).
+pub const BINDINGS_PATH: &str = "input/bindings.yaml";
+
 /// Load input bindings from a YAML file, with defaults as fallback.
-pub fn load_bindings(project_root: &Path) -> InputBindings {
-    let path = project_root.join("input/bindings.yaml");
+pub fn load_bindings(path: &Path) -> InputBindings {
     if path.exists() {
-        match std::fs::read_to_string(&path) {
-            Ok(contents) => match serde_yaml::from_str(&contents) {
-                Ok(bindings) => {
-                    tracing::info!("Loaded input bindings from {:?}", path);
-                    return bindings;
-                }
-                Err(e) => tracing::warn!("Failed to parse bindings.yaml: {}", e),
-            },
-            Err(e) => tracing::warn!("Failed to read bindings.yaml: {}", e),
+        match read_bindings(path) {
+            Ok(bindings) => {
+                tracing::info!("Loaded input bindings from {:?}", path);
+                return bindings;
+            }
+            Err(e) => tracing::warn!("{}", e),
         }
     }
     tracing::info!("Using default input bindings");
     InputBindings::default()
 }
 
+/// Read and parse a bindings file, without falling back to defaults.
+pub fn read_bindings(path: &Path) -> Result<InputBindings, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read bindings.yaml: {}", e))?;
+    serde_yaml::from_str(&contents).map_err(|e| format!("Failed to parse bindings.yaml: {}", e))
+}
+
+/// Write bindings back to a bindings file, e.g. after rebinding at runtime.
+pub fn write_bindings(path: &Path, bindings: &InputBindings) -> Result<(), String> {
+    let yaml = serde_yaml::to_string(bindings).map_err(|e| format!("Failed to serialize bindings: {}", e))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    std::fs::write(path, yaml).map_err(|e| format!("Failed to write bindings.yaml: {}", e))
+}
+
 /// Maps key name strings to winit KeyCode.
 fn key_name_to_code(name: &str) -> Option<KeyCode> {
     match name {
@@ -169,6 +233,24 @@ impl InputState {
         }
     }
 
+    pub fn bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    /// Replace one action's triggers; an empty list unbinds it.
+    pub fn rebind(&mut self, action: &str, triggers: Vec<InputTrigger>) {
+        if triggers.is_empty() {
+            self.bindings.actions.remove(action);
+        } else {
+            self.bindings.actions.insert(action.to_string(), triggers);
+        }
+    }
+
+    /// Swap in new bindings (hot-reload). Held keys and buttons are kept.
+    pub fn set_bindings(&mut self, bindings: InputBindings) {
+        self.bindings = bindings;
+    }
+
     /// Call at the start of each frame to clear transient state.
     pub fn begin_frame(&mut self) {
         self.keys_just_pressed.clear();
@@ -490,6 +572,26 @@ mod tests {
         assert!(state.pressed("move_forward"));
     }
 
+    #[test]
+    fn test_rebind_and_conflicts() {
+        let mut state = InputState::new(InputBindings::default());
+        assert!(state.bindings().conflicts().is_empty());
+
+        state.rebind("jump", vec![InputTrigger::parse("W").unwrap()]);
+        let conflicts = state.bindings().conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].trigger, "W");
+        assert_eq!(conflicts[0].actions, vec!["jump".to_string(), "move_forward".to_string()]);
+
+        state.keys_held.insert(KeyCode::KeyW);
+        assert!(state.pressed("jump"));
+
+        state.rebind("jump", Vec::new());
+        assert!(!state.bindings().actions.contains_key("jump"));
+        assert!(InputTrigger::parse("NotAKey").is_none());
+        assert!(matches!(InputTrigger::parse("Right"), Some(InputTrigger::Mouse(_))));
+    }
+
     #[test]
     fn test_axis_2d() {
         let bindings = InputBindings::default();
@@ -1,41 +1,86 @@
 // Re-export naive-core modules so client files can use `crate::components`, `crate::scene`, etc.
 pub use naive_core::animation;
 pub use naive_core::components;
+pub use naive_core::curve;
 pub use naive_core::events;
+pub use naive_core::format;
 pub use naive_core::scene;
 pub use naive_core::transform;
 pub use naive_core::tween;
 
+pub mod analytics;
 pub mod anim_system;
 pub mod audio;
 pub mod beautify;
 pub mod audio_gen;
+pub mod auto_quality;
+pub mod autosave;
 pub mod build;
 pub mod camera;
+pub mod camera_follow;
+pub mod camera_path;
+pub mod check;
 pub mod debug_draw;
 pub mod cli;
+pub mod color_grading;
+pub mod color_space;
 pub mod command;
+pub mod crowd;
+pub mod curves;
+pub mod culling;
 pub mod demos;
+pub mod device_recovery;
 pub mod dev_log;
 pub mod editor_camera;
 pub mod engine;
+pub mod engine_events;
+pub mod environment;
+pub mod foliage;
 pub mod font;
+pub mod gpu_memory;
+pub mod gpu_options;
+pub mod headless;
 pub mod init;
 pub mod input;
+pub mod lifetime;
+pub mod lod;
+pub mod lua_docs;
 pub mod material;
 pub mod mesh;
+pub mod messaging;
+pub mod mods;
+pub mod noise;
+pub mod obj;
 pub mod particles;
 pub mod physics;
 pub mod pipeline;
+pub mod portal;
+pub mod preview;
 pub mod project_config;
 pub mod publish;
 pub mod reflect;
 pub mod renderer;
+pub mod screenshot;
+pub mod script_bundle;
+pub mod script_config;
+pub mod script_memory;
+pub mod script_watchdog;
 pub mod scripting;
+pub mod sequence;
 pub mod shader;
+pub mod shader_diagnostic;
 pub mod splat;
+pub mod splat_formats;
+pub mod splat_optimize;
+pub mod splat_sort;
+pub mod split_screen;
+pub mod steam;
+pub mod terrain;
 pub mod test_runner;
 pub mod texture_cache;
+pub mod time_scale;
+pub mod tool_window;
 pub mod ui;
 pub mod watcher;
+pub mod window_state;
 pub mod world;
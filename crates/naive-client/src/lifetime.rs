@@ -0,0 +1,274 @@
+//! Entity lifetimes and auto-despawn rules.
+//!
+//! A `lifetime` component despawns its entity once any of its rules fires:
+//! an age in seconds (entity time scale applies), a condition expression, or
+//! a distance from the nearest player. The engine checks every entity each
+//! frame, so debris and dropped items clean themselves up without a script.
+//!
+//! Conditions compare a variable to a number, joined with `and` / `or`
+//! (`and` binds tighter): `position.y < -50 or health <= 0`. Variables are
+//! `age`, `position.x`, `position.y`, `position.z`, `health` and
+//! `player_distance`. A comparison on a variable the entity doesn't have
+//! (no `health` component, no player in the scene) is false.
+
+use hecs::{Entity, World};
+
+use crate::components::{Health, Player, Transform};
+use crate::scene::LifetimeDef;
+
+/// Runtime lifetime component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lifetime {
+    pub seconds: Option<f32>,
+    pub condition: Option<Condition>,
+    pub despawn_distance: Option<f32>,
+    /// Game time lived so far, scaled by the entity's time scale.
+    pub age: f32,
+}
+
+impl Lifetime {
+    /// Build from a scene definition; fails on a malformed condition.
+    pub fn from_def(def: &LifetimeDef) -> Result<Self, String> {
+        let condition = def
+            .condition
+            .as_deref()
+            .map(Condition::parse)
+            .transpose()
+            .map_err(|e| format!("lifetime condition: {}", e))?;
+        Ok(Self {
+            seconds: def.seconds,
+            condition,
+            despawn_distance: def.despawn_distance,
+            age: 0.0,
+        })
+    }
+}
+
+/// A value a condition can test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Var {
+    Age,
+    PositionX,
+    PositionY,
+    PositionZ,
+    Health,
+    PlayerDistance,
+}
+
+impl Var {
+    fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "age" => Var::Age,
+            "position.x" => Var::PositionX,
+            "position.y" => Var::PositionY,
+            "position.z" => Var::PositionZ,
+            "health" => Var::Health,
+            "player_distance" => Var::PlayerDistance,
+            _ => {
+                return Err(format!(
+                    "unknown variable '{}' (expected age, position.x/y/z, health or player_distance)",
+                    name
+                ))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn apply(self, a: f32, b: f32) -> bool {
+        match self {
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+        }
+    }
+}
+
+/// A parsed condition: true if any `and` group has all its comparisons true.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    any: Vec<Vec<(Var, Op, f32)>>,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let any = split_words(source, "or")
+            .into_iter()
+            .map(|group| {
+                split_words(group, "and")
+                    .into_iter()
+                    .map(parse_comparison)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { any })
+    }
+
+    /// Evaluate with `lookup` supplying variable values (`None` = absent).
+    pub fn eval(&self, lookup: &mut dyn FnMut(Var) -> Option<f32>) -> bool {
+        self.any.iter().any(|all| {
+            all.iter()
+                .all(|&(var, op, value)| lookup(var).is_some_and(|v| op.apply(v, value)))
+        })
+    }
+}
+
+/// Split on a keyword surrounded by whitespace.
+fn split_words<'a>(source: &'a str, keyword: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut rest = source;
+    let pattern = format!(" {} ", keyword);
+    while let Some(i) = rest.find(&pattern) {
+        parts.push(&rest[..i]);
+        rest = &rest[i + pattern.len()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+fn parse_comparison(source: &str) -> Result<(Var, Op, f32), String> {
+    const OPS: [(&str, Op); 6] = [
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ];
+    let (at, symbol, op) = OPS
+        .iter()
+        .find_map(|&(symbol, op)| source.find(symbol).map(|at| (at, symbol, op)))
+        .ok_or_else(|| format!("expected a comparison, got '{}'", source.trim()))?;
+    let var = Var::parse(source[..at].trim())?;
+    let value = source[at + symbol.len()..].trim();
+    let value = value
+        .parse::<f32>()
+        .map_err(|_| format!("expected a number after '{}', got '{}'", symbol, value))?;
+    Ok((var, op, value))
+}
+
+/// Age every lifetime by its entity's dt and return the entities due to despawn.
+pub fn update_lifetimes(world: &World, dt: f32) -> Vec<Entity> {
+    let players: Vec<glam::Vec3> = world
+        .query::<(&Player, &Transform)>()
+        .iter()
+        .map(|(_, (_, t))| world_position(t))
+        .collect();
+
+    let mut expired = Vec::new();
+    for (entity, lifetime) in world.query::<&mut Lifetime>().iter() {
+        lifetime.age += crate::time_scale::entity_dt(world, entity, dt);
+        let position = world.get::<&Transform>(entity).ok().map(|t| world_position(&t));
+        let player_distance = position.and_then(|p| {
+            players.iter().map(|q| p.distance(*q)).min_by(f32::total_cmp)
+        });
+
+        let timed_out = lifetime.seconds.is_some_and(|s| lifetime.age >= s);
+        let too_far = lifetime
+            .despawn_distance
+            .zip(player_distance)
+            .is_some_and(|(max, d)| d > max);
+        let condition_met = lifetime.condition.as_ref().is_some_and(|c| {
+            c.eval(&mut |var| match var {
+                Var::Age => Some(lifetime.age),
+                Var::PositionX => position.map(|p| p.x),
+                Var::PositionY => position.map(|p| p.y),
+                Var::PositionZ => position.map(|p| p.z),
+                Var::Health => world.get::<&Health>(entity).ok().map(|h| h.current),
+                Var::PlayerDistance => player_distance,
+            })
+        });
+        if timed_out || too_far || condition_met {
+            expired.push(entity);
+        }
+    }
+    expired
+}
+
+/// Root entities use `position` directly, so an entity spawned this frame
+/// isn't seen at the origin before its world matrix is first computed.
+fn world_position(transform: &Transform) -> glam::Vec3 {
+    match transform.parent {
+        Some(_) => transform.world_matrix.w_axis.truncate(),
+        None => transform.position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, vars: &[(Var, f32)]) -> bool {
+        Condition::parse(source)
+            .unwrap()
+            .eval(&mut |var| vars.iter().find(|(v, _)| *v == var).map(|(_, x)| *x))
+    }
+
+    #[test]
+    fn test_condition_parse_and_eval() {
+        assert!(eval("position.y < -50", &[(Var::PositionY, -60.0)]));
+        assert!(!eval("position.y < -50", &[(Var::PositionY, 0.0)]));
+        assert!(eval("health <= 0 or age > 10", &[(Var::Health, 5.0), (Var::Age, 11.0)]));
+        assert!(!eval("age > 2 and player_distance >= 30", &[(Var::Age, 3.0), (Var::PlayerDistance, 10.0)]));
+        assert!(eval("age > 2 and player_distance >= 30 or health == 0", &[(Var::Age, 3.0), (Var::Health, 0.0)]));
+        // Missing variables make the comparison false
+        assert!(!eval("health <= 0", &[]));
+        assert!(eval("health != 1 or age >= 0", &[(Var::Age, 0.0)]));
+    }
+
+    #[test]
+    fn test_condition_parse_errors() {
+        assert!(Condition::parse("speed > 3").unwrap_err().contains("unknown variable"));
+        assert!(Condition::parse("age > soon").is_err());
+        assert!(Condition::parse("age").is_err());
+    }
+
+    #[test]
+    fn test_update_lifetimes() {
+        let mut world = World::new();
+        let spawn_at = |world: &mut World, position: glam::Vec3, def: LifetimeDef| {
+            let transform = Transform { position, ..Default::default() };
+            world.spawn((transform, Lifetime::from_def(&def).unwrap()))
+        };
+        let timed = spawn_at(&mut world, glam::Vec3::ZERO, LifetimeDef { seconds: Some(1.0), ..Default::default() });
+        let fallen = spawn_at(
+            &mut world,
+            glam::Vec3::new(0.0, -100.0, 0.0),
+            LifetimeDef { condition: Some("position.y < -50".into()), ..Default::default() },
+        );
+        let far = spawn_at(
+            &mut world,
+            glam::Vec3::new(80.0, 0.0, 0.0),
+            LifetimeDef { despawn_distance: Some(50.0), ..Default::default() },
+        );
+        let near = spawn_at(
+            &mut world,
+            glam::Vec3::new(20.0, 0.0, 0.0),
+            LifetimeDef { despawn_distance: Some(50.0), ..Default::default() },
+        );
+
+        // No player yet: distance rules don't fire
+        assert_eq!(update_lifetimes(&world, 0.5), vec![fallen]);
+
+        world.spawn((Player::default(), Transform::default()));
+        let mut expired = update_lifetimes(&world, 0.5);
+        expired.sort();
+        let mut expected = vec![timed, fallen, far];
+        expected.sort();
+        assert_eq!(expired, expected);
+        assert!(!expired.contains(&near));
+    }
+}
@@ -0,0 +1,314 @@
+//! Mesh level of detail.
+//!
+//! A mesh renderer with `lod:` levels gets a `MeshLod` component holding the
+//! mesh of every level. Each frame `update_lods` picks the level for the
+//! entity's distance to the camera, or the share of the screen it covers, and
+//! swaps `MeshRenderer::mesh_handle`, so every pass (shadows included) draws
+//! the chosen mesh. Levels with a `ratio:` are made at load by `decimate`.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use glam::{Mat4, Vec3};
+
+use crate::camera::CameraUniform;
+use crate::components::{MeshHandle, MeshRenderer, Transform};
+use crate::scene::{LodDef, LodLevelDef};
+
+/// Finest grid `decimate` tries, per axis.
+const MAX_GRID_RESOLUTION: u32 = 1024;
+
+/// When a lower-detail level takes over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LodSwitch {
+    /// Beyond this many meters from the camera.
+    Distance(f32),
+    /// Below this fraction of the screen height.
+    ScreenSize(f32),
+}
+
+impl LodSwitch {
+    /// Whether the threshold is passed, with it moved `margin` (a fraction)
+    /// toward the entity so the level is kept a little longer.
+    fn passed(self, distance: f32, screen_size: f32, margin: f32) -> bool {
+        match self {
+            LodSwitch::Distance(d) => distance > d * (1.0 - margin),
+            LodSwitch::ScreenSize(s) => screen_size < s * (1.0 + margin),
+        }
+    }
+}
+
+/// A lower-detail level of a mesh renderer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LodLevel {
+    pub mesh: MeshHandle,
+    pub switch: LodSwitch,
+}
+
+/// Level-of-detail meshes of an entity's `MeshRenderer`.
+#[derive(Debug, Clone)]
+pub struct MeshLod {
+    /// The full-detail mesh (level 0).
+    pub base: MeshHandle,
+    /// Levels 1 and up.
+    pub levels: Vec<LodLevel>,
+    pub hysteresis: f32,
+    /// Level drawn now.
+    pub current: usize,
+    /// Bounding sphere of the base mesh, in local space.
+    pub center: Vec3,
+    pub radius: f32,
+    /// The scene definition, written back on scene export.
+    pub def: LodDef,
+}
+
+impl MeshLod {
+    /// Check `def` and load each level's mesh with `load` (authored mesh
+    /// path or decimation ratio).
+    pub fn from_def(
+        def: &LodDef,
+        base: MeshHandle,
+        bounds: crate::culling::Aabb,
+        mut load: impl FnMut(&LodLevelDef) -> Result<MeshHandle, String>,
+    ) -> Result<Self, String> {
+        if !(0.0..1.0).contains(&def.hysteresis) {
+            return Err(format!("lod hysteresis must be in 0..1, got {}", def.hysteresis));
+        }
+        let mut levels = Vec::with_capacity(def.levels.len());
+        for (i, level) in def.levels.iter().enumerate() {
+            let switch = match (level.distance, level.screen_size) {
+                (Some(d), None) if d > 0.0 => LodSwitch::Distance(d),
+                (None, Some(s)) if s > 0.0 => LodSwitch::ScreenSize(s),
+                _ => return Err(format!("lod level {} needs one positive distance or screen_size", i + 1)),
+            };
+            match (&level.mesh, level.ratio) {
+                (Some(_), None) => {}
+                (None, Some(r)) if r > 0.0 && r < 1.0 => {}
+                _ => return Err(format!("lod level {} needs a mesh or a ratio between 0 and 1", i + 1)),
+            }
+            levels.push(LodLevel { mesh: load(level)?, switch });
+        }
+        Ok(Self {
+            base,
+            levels,
+            hysteresis: def.hysteresis,
+            current: 0,
+            center: (bounds.min + bounds.max) * 0.5,
+            radius: (bounds.max - bounds.min).length() * 0.5,
+            def: def.clone(),
+        })
+    }
+
+    /// Pick the level for an entity `distance` meters from the camera that
+    /// covers `screen_size` of the screen height, and return its mesh. A
+    /// coarser level is taken as soon as its threshold is passed, but a finer
+    /// one only returns once the entity is `hysteresis` back past it.
+    pub fn select(&mut self, distance: f32, screen_size: f32) -> MeshHandle {
+        let level_for = |margin: f32| {
+            self.levels
+                .iter()
+                .rposition(|level| level.switch.passed(distance, screen_size, margin))
+                .map_or(0, |i| i + 1)
+        };
+        let (wanted, kept) = (level_for(0.0), level_for(self.hysteresis));
+        self.current = self.current.clamp(wanted, kept.max(wanted));
+        self.mesh(self.current)
+    }
+
+    /// Mesh of `level` (0 = base).
+    pub fn mesh(&self, level: usize) -> MeshHandle {
+        match level {
+            0 => self.base,
+            n => self.levels[n - 1].mesh,
+        }
+    }
+}
+
+/// Swap every LOD entity's mesh for the level that fits the camera.
+/// `force_lod0` (`--force-lod0`) pins everything to full detail.
+pub fn update_lods(world: &hecs::World, camera: &CameraUniform, force_lod0: bool) {
+    let eye = Vec3::from(camera.position);
+    let projection = Mat4::from_cols_array_2d(&camera.projection);
+    // Screen height covered per meter of radius (at 1 m for perspective)
+    let scale = projection.y_axis.y;
+    let orthographic = projection.w_axis.w == 1.0;
+
+    for (_, (lod, renderer, transform)) in world.query::<(&mut MeshLod, &mut MeshRenderer, &Transform)>().iter() {
+        if force_lod0 {
+            lod.current = 0;
+            renderer.mesh_handle = lod.base;
+            continue;
+        }
+        let matrix = transform.world_matrix;
+        let center = matrix.transform_point3(lod.center);
+        let max_scale = matrix.x_axis.truncate().length()
+            .max(matrix.y_axis.truncate().length())
+            .max(matrix.z_axis.truncate().length());
+        let distance = center.distance(eye);
+        let screen_size = if orthographic {
+            lod.radius * max_scale * scale
+        } else {
+            lod.radius * max_scale * scale / distance.max(1e-3)
+        };
+        renderer.mesh_handle = lod.select(distance, screen_size);
+    }
+}
+
+/// Simplify a triangle mesh to about `ratio` of its triangles by merging the
+/// vertices that share a cell of a grid over its bounds, with the grid as
+/// fine as the triangle budget allows. Each cell keeps the existing vertex
+/// nearest its centroid, so the result indexes the original vertex buffer.
+/// `runs` are triangle ranges drawn with their own material; they stay
+/// separate and their new index ranges are returned alongside the indices.
+pub fn decimate(positions: &[[f32; 3]], triangles: &[[u32; 3]], runs: &[Range<usize>], ratio: f32) -> (Vec<u32>, Vec<Range<u32>>) {
+    let target = ((triangles.len() as f32 * ratio).ceil() as usize).max(1);
+    let bounds = crate::culling::Aabb::from_points(positions.iter().copied());
+    let extent = (bounds.max - bounds.min).max(Vec3::splat(1e-6));
+    let cell_of = |resolution: u32, p: [f32; 3]| -> u64 {
+        let cell = ((Vec3::from(p) - bounds.min) / extent * resolution as f32)
+            .floor()
+            .min(Vec3::splat((resolution - 1) as f32))
+            .max(Vec3::ZERO);
+        let r = resolution as u64;
+        cell.x as u64 + cell.y as u64 * r + cell.z as u64 * r * r
+    };
+    // Triangles left with every vertex merged into its grid cell
+    let collapse = |resolution: u32| -> Vec<Vec<[u64; 3]>> {
+        let cells: Vec<u64> = positions.iter().map(|&p| cell_of(resolution, p)).collect();
+        runs.iter()
+            .map(|run| {
+                let mut seen = HashSet::new();
+                triangles[run.clone()]
+                    .iter()
+                    .map(|t| t.map(|v| cells[v as usize]))
+                    .filter(|[a, b, c]| a != b && b != c && a != c)
+                    .filter(|t| {
+                        let mut key = *t;
+                        key.sort_unstable();
+                        seen.insert(key)
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+    let count = |kept: &[Vec<[u64; 3]>]| kept.iter().map(Vec::len).sum::<usize>();
+
+    // Finest grid that stays within the budget
+    let (mut low, mut high) = (1, MAX_GRID_RESOLUTION);
+    let mut best = collapse(low);
+    while low < high {
+        let middle = (low + high).div_ceil(2);
+        let kept = collapse(middle);
+        if count(&kept) <= target {
+            low = middle;
+            best = kept;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    // The vertex nearest each cell's centroid stands for the cell
+    let mut sums: HashMap<u64, (Vec3, u32)> = HashMap::new();
+    for &p in positions {
+        let entry = sums.entry(cell_of(low, p)).or_insert((Vec3::ZERO, 0));
+        entry.0 += Vec3::from(p);
+        entry.1 += 1;
+    }
+    let mut representative: HashMap<u64, (u32, f32)> = HashMap::new();
+    for (i, &p) in positions.iter().enumerate() {
+        let cell = cell_of(low, p);
+        let (sum, n) = sums[&cell];
+        let d = Vec3::from(p).distance_squared(sum / n as f32);
+        let entry = representative.entry(cell).or_insert((i as u32, d));
+        if d < entry.1 {
+            *entry = (i as u32, d);
+        }
+    }
+
+    let mut indices = Vec::with_capacity(count(&best) * 3);
+    let mut ranges = Vec::with_capacity(runs.len());
+    for run in best {
+        let start = indices.len() as u32;
+        indices.extend(run.iter().flat_map(|t| t.map(|cell| representative[&cell].0)));
+        ranges.push(start..indices.len() as u32);
+    }
+    (indices, ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An `n` x `n` quad grid on the XZ plane.
+    fn grid(n: u32) -> (Vec<[f32; 3]>, Vec<[u32; 3]>) {
+        let positions = (0..=n)
+            .flat_map(|z| (0..=n).map(move |x| [x as f32, 0.0, z as f32]))
+            .collect();
+        let v = |x: u32, z: u32| z * (n + 1) + x;
+        let triangles = (0..n)
+            .flat_map(|z| (0..n).flat_map(move |x| [[v(x, z), v(x, z + 1), v(x + 1, z)], [v(x + 1, z), v(x, z + 1), v(x + 1, z + 1)]]))
+            .collect();
+        (positions, triangles)
+    }
+
+    #[test]
+    fn test_decimate_meets_budget() {
+        let (positions, triangles) = grid(32);
+        let (indices, ranges) = decimate(&positions, &triangles, &[0..1024, 1024..2048], 0.25);
+        let kept = indices.len() / 3;
+        assert!((64..=512).contains(&kept), "{} triangles", kept);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[1].end as usize, indices.len());
+        for t in indices.chunks_exact(3) {
+            assert!(t.iter().all(|&i| (i as usize) < positions.len()));
+            assert!(t[0] != t[1] && t[1] != t[2] && t[0] != t[2]);
+        }
+    }
+
+    fn lod(hysteresis: f32) -> MeshLod {
+        let def = LodDef {
+            levels: vec![
+                LodLevelDef { mesh: Some("near.glb".into()), ratio: None, distance: Some(10.0), screen_size: None },
+                LodLevelDef { mesh: None, ratio: Some(0.1), distance: None, screen_size: Some(0.05) },
+            ],
+            hysteresis,
+        };
+        let bounds = crate::culling::Aabb { min: Vec3::splat(-1.0), max: Vec3::splat(1.0) };
+        let mut next = 0;
+        MeshLod::from_def(&def, MeshHandle(0), bounds, |_| {
+            next += 1;
+            Ok(MeshHandle(next))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_select_with_hysteresis() {
+        let mut lod = lod(0.2);
+        assert_eq!(lod.select(5.0, 0.3), MeshHandle(0));
+        assert_eq!(lod.select(11.0, 0.2), MeshHandle(1));
+        // Back inside the threshold, but not by 20%
+        assert_eq!(lod.select(9.0, 0.2), MeshHandle(1));
+        assert_eq!(lod.select(7.9, 0.2), MeshHandle(0));
+        // Small on screen wins over the nearer distance level
+        assert_eq!(lod.select(30.0, 0.04), MeshHandle(2));
+        assert_eq!(lod.select(30.0, 0.055), MeshHandle(2));
+        assert_eq!(lod.select(30.0, 0.07), MeshHandle(1));
+    }
+
+    #[test]
+    fn test_from_def_rejects_ambiguous_levels() {
+        let bounds = crate::culling::Aabb { min: Vec3::ZERO, max: Vec3::ONE };
+        let both = LodDef {
+            levels: vec![LodLevelDef { mesh: None, ratio: Some(0.5), distance: Some(5.0), screen_size: Some(0.1) }],
+            hysteresis: 0.1,
+        };
+        assert!(MeshLod::from_def(&both, MeshHandle(0), bounds, |_| Ok(MeshHandle(1))).is_err());
+        let neither = LodDef {
+            levels: vec![LodLevelDef { mesh: None, ratio: None, distance: Some(5.0), screen_size: None }],
+            hysteresis: 0.1,
+        };
+        assert!(MeshLod::from_def(&neither, MeshHandle(0), bounds, |_| Ok(MeshHandle(1))).is_err());
+        assert_eq!(lod(0.1).radius, 3f32.sqrt());
+    }
+}
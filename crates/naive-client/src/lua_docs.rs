@@ -0,0 +1,172 @@
+//! `naive docs` — reference pages and a language server stub for the Lua API.
+//!
+//! Everything is generated from `scripting::LUA_API`, the declaration table
+//! that a test keeps in step with the registered bindings, so the docs and
+//! editor autocompletion can't drift from what scripts can actually call.
+
+use std::path::{Path, PathBuf};
+
+use crate::scripting::{LuaApiFn, LUA_API, LUA_DEPRECATIONS};
+
+/// Files written by `naive docs`, inside the output folder.
+pub const MARKDOWN_FILE: &str = "lua_api.md";
+pub const HTML_FILE: &str = "lua_api.html";
+/// Lua language server definitions; add the folder to `workspace.library`
+/// in `.luarc.json` for autocompletion.
+pub const STUB_FILE: &str = "naive_api.lua";
+
+/// Standard library tables the API extends; the stub must not redeclare them.
+const STD_TABLES: &[&str] = &["math", "string", "table"];
+
+/// Write the Markdown, HTML and stub files into `out_dir`.
+pub fn write_docs(out_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+    let mut written = Vec::new();
+    for (file, contents) in [(MARKDOWN_FILE, markdown()), (HTML_FILE, html()), (STUB_FILE, lua_stub())] {
+        let path = out_dir.join(file);
+        std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// The API as Markdown, one section per table.
+pub fn markdown() -> String {
+    let mut out = String::from("# Lua API\n\nGenerated by `naive docs`; do not edit.\n");
+    for (table, functions) in grouped() {
+        out.push_str(&format!("\n## {}\n", section_title(table)));
+        for f in functions {
+            out.push_str(&format!("\n### {}\n\n```lua\n{}\n```\n\n{}\n", f.path(), signature(f), f.doc));
+        }
+    }
+    if !LUA_DEPRECATIONS.is_empty() {
+        out.push_str("\n## Deprecated\n\nStill callable from projects below the given `scripting.api_level`.\n\n");
+        for d in LUA_DEPRECATIONS {
+            out.push_str(&format!("- `{}` (removed in level {}): {}\n", d.path(), d.level, d.hint));
+        }
+    }
+    out
+}
+
+/// The API as a standalone HTML page.
+pub fn html() -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Lua API</title>\n</head>\n<body>\n<h1>Lua API</h1>\n",
+    );
+    for (table, functions) in grouped() {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&section_title(table))));
+        for f in functions {
+            out.push_str(&format!(
+                "<h3 id=\"{}\">{}</h3>\n<pre><code>{}</code></pre>\n<p>{}</p>\n",
+                f.path(),
+                f.path(),
+                escape_html(&signature(f)),
+                inline_code(&escape_html(f.doc)),
+            ));
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Lua language server (LuaLS) definitions for every function.
+pub fn lua_stub() -> String {
+    let mut out = String::from("---@meta\n-- Generated by `naive docs`; do not edit.\n");
+    for (table, functions) in grouped() {
+        out.push('\n');
+        if !table.is_empty() && !STD_TABLES.contains(&table) {
+            out.push_str(&format!("{} = {{}}\n\n", table));
+        }
+        for f in functions {
+            out.push_str(&format!("---{}\n", f.doc));
+            let mut args = Vec::new();
+            for (name, ty) in f.params {
+                out.push_str(&format!("---@param {} {}\n", name, ty));
+                args.push(name.trim_end_matches('?'));
+            }
+            for (name, ty) in f.returns {
+                out.push_str(&format!("---@return {} {}\n", ty, name));
+            }
+            out.push_str(&format!("function {}({}) end\n\n", f.path(), args.join(", ")));
+        }
+    }
+    for d in LUA_DEPRECATIONS {
+        out.push_str(&format!("---{}\n---@deprecated\nfunction {}(...) end\n\n", d.hint, d.path()));
+    }
+    out
+}
+
+/// Declarations grouped by table, tables in first-declared order.
+fn grouped() -> Vec<(&'static str, Vec<&'static LuaApiFn>)> {
+    let mut groups: Vec<(&'static str, Vec<&'static LuaApiFn>)> = Vec::new();
+    for f in LUA_API {
+        match groups.iter_mut().find(|(table, _)| *table == f.table) {
+            Some((_, functions)) => functions.push(f),
+            None => groups.push((f.table, vec![f])),
+        }
+    }
+    groups
+}
+
+fn section_title(table: &str) -> String {
+    if table.is_empty() {
+        "Globals".to_string()
+    } else {
+        format!("`{}`", table)
+    }
+}
+
+/// `entity.get_position(id: string) -> x: number, y: number, z: number`
+fn signature(f: &LuaApiFn) -> String {
+    let params: Vec<String> = f.params.iter().map(|(name, ty)| format!("{}: {}", name, ty)).collect();
+    let mut sig = format!("{}({})", f.path(), params.join(", "));
+    if !f.returns.is_empty() {
+        let returns: Vec<String> = f.returns.iter().map(|(name, ty)| format!("{}: {}", name, ty)).collect();
+        sig.push_str(" -> ");
+        sig.push_str(&returns.join(", "));
+    }
+    sig
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Turn `backtick` spans into `<code>` elements.
+fn inline_code(text: &str) -> String {
+    let mut out = String::new();
+    for (i, part) in text.split('`').enumerate() {
+        if i % 2 == 1 {
+            out.push_str("<code>");
+            out.push_str(part);
+            out.push_str("</code>");
+        } else {
+            out.push_str(part);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_docs() {
+        let stub = lua_stub();
+        assert!(stub.starts_with("---@meta"));
+        assert!(stub.contains("entity = {}\n"));
+        assert!(!stub.contains("math = {}"));
+        assert!(stub.contains("---@param filter? table\n"));
+        assert!(stub.contains("function physics.raycast(ox, oy, oz, dx, dy, dz, max_dist, filter) end\n"));
+        assert!(stub.contains("---@return number x\n"));
+
+        let md = markdown();
+        assert!(md.contains("\n## Globals\n"));
+        assert!(md.contains("entity.get_position(id: string) -> x: number, y: number, z: number"));
+
+        let html = html();
+        assert!(html.contains("<h3 id=\"input.pressed\">"));
+        assert!(html.contains("-&gt; held: boolean"));
+    }
+}
@@ -2,7 +2,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use crate::color_space::ColorSpace;
 use crate::components::MaterialHandle;
+use crate::mesh::{MaterialTextures, TextureResources};
 use crate::texture_cache::{TextureCache, TextureHandle};
 
 #[derive(Debug)]
@@ -31,6 +33,14 @@ pub struct MaterialFile {
     pub blend_mode: String,
     #[serde(default = "default_back")]
     pub cull_mode: String,
+    /// Named sampler from the render pipeline's `samplers:` section used for
+    /// the texture maps (default: linear, repeating).
+    #[serde(default)]
+    pub sampler: Option<String>,
+    /// Color space per texture map field, overriding the default (sRGB for
+    /// albedo and emissive, linear for normal and metallic-roughness).
+    #[serde(default)]
+    pub color_space: HashMap<String, ColorSpace>,
 }
 
 fn default_opaque() -> String {
@@ -50,11 +60,19 @@ pub struct MaterialProperties {
     pub metallic: f32,
     #[serde(default)]
     pub emission: [f32; 3],
-    // Texture paths are recorded but not loaded until Phase 3
+    /// Base color map (sRGB), multiplied by `base_color`.
     #[serde(default)]
     pub albedo_map: Option<String>,
+    /// Tangent-space normal map.
     #[serde(default)]
     pub normal_map: Option<String>,
+    /// glTF-style packed map: G = roughness, B = metallic, each multiplied
+    /// by the scalar value.
+    #[serde(default)]
+    pub metallic_roughness_map: Option<String>,
+    /// Emission map (sRGB), multiplied by `emission` (white if unset).
+    #[serde(default)]
+    pub emissive_map: Option<String>,
 }
 
 impl Default for MaterialProperties {
@@ -66,6 +84,8 @@ impl Default for MaterialProperties {
             emission: [0.0; 3],
             albedo_map: None,
             normal_map: None,
+            metallic_roughness_map: None,
+            emissive_map: None,
         }
     }
 }
@@ -77,6 +97,88 @@ fn default_roughness() -> f32 {
     0.5
 }
 
+/// Resolve a Wavefront material reference: `path/to/file.mtl#Name`, or
+/// `path/to/file.mtl` for the library's first material. Returns `Ok(None)`
+/// for anything else. A missing library or name falls back to defaults with a
+/// warning, like a missing YAML material.
+fn load_mtl_material(project_root: &Path, material_path: &str) -> Result<Option<MaterialFile>, MaterialError> {
+    let (file, name) = match material_path.split_once('#') {
+        Some((file, name)) => (file, Some(name)),
+        None => (material_path, None),
+    };
+    if !file.to_lowercase().ends_with(".mtl") {
+        return Ok(None);
+    }
+
+    let mut mat_file = MaterialFile {
+        shader: String::new(),
+        properties: MaterialProperties::default(),
+        blend_mode: default_opaque(),
+        cull_mode: default_back(),
+        sampler: None,
+        color_space: HashMap::new(),
+    };
+    let full_path = project_root.join(file);
+    if !full_path.exists() {
+        tracing::warn!("Material library not found: {:?}, using defaults", full_path);
+        return Ok(Some(mat_file));
+    }
+    let contents = std::fs::read_to_string(&full_path).map_err(MaterialError::IoError)?;
+    let materials = crate::obj::parse_mtl(&contents);
+    let found = match name {
+        Some(name) => materials.iter().find(|m| m.name == name),
+        None => materials.first(),
+    };
+    match found {
+        Some(material) => {
+            let mtl_dir = Path::new(file).parent().unwrap_or(Path::new(""));
+            mat_file.properties = material.to_properties(mtl_dir);
+            if material.dissolve < 1.0 {
+                mat_file.blend_mode = "transparent".to_string();
+            }
+        }
+        None => tracing::warn!("Material '{}' not found in {:?}, using defaults", name.unwrap_or(""), full_path),
+    }
+    Ok(Some(mat_file))
+}
+
+/// Read a material from a YAML file or an MTL library entry, with the PBR
+/// factors it sets explicitly. A missing file gives the defaults with a warning.
+fn read_material_file(material_root: &Path, material_path: &str) -> Result<(MaterialFile, ExplicitFactors), MaterialError> {
+    if let Some(mat_file) = load_mtl_material(material_root, material_path)? {
+        // An MTL material defines every factor
+        return Ok((mat_file, ExplicitFactors { roughness: true, metallic: true, emission: true }));
+    }
+    let full_path = material_root.join(material_path);
+    if full_path.exists() {
+        let contents = std::fs::read_to_string(&full_path).map_err(MaterialError::IoError)?;
+        let mat_file = serde_yaml::from_str::<MaterialFile>(&contents).map_err(MaterialError::ParseError)?;
+        return Ok((mat_file, ExplicitFactors::from_yaml(&contents)));
+    }
+    tracing::warn!(
+        "Material file not found: {:?}, using defaults",
+        full_path
+    );
+    let mat_file = MaterialFile {
+        shader: String::new(),
+        properties: MaterialProperties::default(),
+        blend_mode: default_opaque(),
+        cull_mode: default_back(),
+        sampler: None,
+        color_space: HashMap::new(),
+    };
+    Ok((mat_file, ExplicitFactors::default()))
+}
+
+/// The texture map paths a material names, without loading them.
+pub fn texture_maps(project_root: &Path, material_path: &str) -> Result<Vec<String>, MaterialError> {
+    let props = read_material_file(project_root, material_path)?.0.properties;
+    Ok([props.albedo_map, props.normal_map, props.metallic_roughness_map, props.emissive_map]
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
 /// GPU-side material uniform data.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -100,13 +202,105 @@ impl MaterialUniform {
     }
 }
 
+/// Which factors a material file sets itself. glTF submesh factors fill in
+/// the ones it leaves out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExplicitFactors {
+    pub roughness: bool,
+    pub metallic: bool,
+    pub emission: bool,
+}
+
+impl ExplicitFactors {
+    /// Factors named under `properties:` in a material file's YAML source.
+    pub fn from_yaml(source: &str) -> Self {
+        let properties = serde_yaml::from_str::<serde_yaml::Value>(source)
+            .ok()
+            .and_then(|doc| doc.get("properties").cloned());
+        let has = |key: &str| properties.as_ref().is_some_and(|p| p.get(key).is_some());
+        Self {
+            roughness: has("roughness"),
+            metallic: has("metallic"),
+            emission: has("emission"),
+        }
+    }
+}
+
 /// A loaded GPU material.
 pub struct GpuMaterial {
     pub uniform: MaterialUniform,
+    /// Factors set in the material file, which take priority over glTF ones.
+    pub explicit: ExplicitFactors,
     /// Albedo texture loaded from material's `albedo_map` field.
     pub albedo_texture: Option<TextureHandle>,
     /// Normal map loaded from material's `normal_map` field.
     pub normal_texture: Option<TextureHandle>,
+    /// Loaded from material's `metallic_roughness_map` field.
+    pub metallic_roughness_texture: Option<TextureHandle>,
+    /// Loaded from material's `emissive_map` field.
+    pub emissive_texture: Option<TextureHandle>,
+    /// Named pipeline sampler from the material's `sampler` field.
+    pub sampler: Option<String>,
+    /// Group 2 bind group of the texture maps. None for materials without
+    /// maps, which use the mesh's own textures instead.
+    pub bind_group: Option<wgpu::BindGroup>,
+}
+
+impl GpuMaterial {
+    fn untextured(uniform: MaterialUniform, explicit: ExplicitFactors) -> Self {
+        Self {
+            uniform,
+            explicit,
+            albedo_texture: None,
+            normal_texture: None,
+            metallic_roughness_texture: None,
+            emissive_texture: None,
+            sampler: None,
+            bind_group: None,
+        }
+    }
+
+    /// (Re)build the bind group after a texture map changed.
+    pub fn rebuild_bind_group(&mut self, device: &wgpu::Device, texture_cache: &TextureCache, texture_resources: &TextureResources) {
+        let maps = [self.albedo_texture, self.normal_texture, self.metallic_roughness_texture, self.emissive_texture];
+        if maps.iter().all(Option::is_none) {
+            self.bind_group = None;
+            return;
+        }
+        let view = |handle: Option<TextureHandle>| handle.map(|h| texture_cache.get(h));
+        self.bind_group = Some(texture_resources.create_bind_group(device, "Material Textures", MaterialTextures {
+            albedo: view(self.albedo_texture),
+            normal: view(self.normal_texture),
+            metallic_roughness: view(self.metallic_roughness_texture),
+            emissive: view(self.emissive_texture),
+            sampler: self.sampler.as_deref(),
+        }));
+    }
+}
+
+/// Load one texture map of a material, logging failures.
+#[allow(clippy::too_many_arguments)]
+fn load_texture_map(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    project_root: &Path,
+    texture_cache: &mut TextureCache,
+    material_path: &str,
+    field: &str,
+    texture_path: Option<&String>,
+    srgb: bool,
+) -> Option<TextureHandle> {
+    let texture_path = texture_path?;
+    match texture_cache.get_or_load(device, queue, project_root, texture_path, srgb) {
+        Ok(handle) => {
+            tracing::info!("Material '{}' loaded {}: {}", material_path, field, texture_path);
+            Some(handle)
+        }
+        Err(e) => {
+            tracing::warn!("Material '{}' failed to load {} '{}': {}", material_path, field, texture_path, e);
+            None
+        }
+    }
 }
 
 /// Cache of loaded materials.
@@ -114,6 +308,8 @@ pub struct MaterialCache {
     materials: Vec<GpuMaterial>,
     path_to_handle: HashMap<PathBuf, MaterialHandle>,
     default_handle: Option<MaterialHandle>,
+    /// Mod folders searched before the project for material files.
+    pub overlay: crate::mods::ModOverlay,
 }
 
 impl MaterialCache {
@@ -122,6 +318,7 @@ impl MaterialCache {
             materials: Vec::new(),
             path_to_handle: HashMap::new(),
             default_handle: None,
+            overlay: Default::default(),
         }
     }
 
@@ -131,31 +328,17 @@ impl MaterialCache {
         queue: &wgpu::Queue,
         project_root: &Path,
         material_path: &str,
-        mut texture_cache: Option<&mut TextureCache>,
-        texture_layout: Option<&wgpu::BindGroupLayout>,
+        texture_cache: Option<&mut TextureCache>,
+        texture_resources: Option<&TextureResources>,
     ) -> Result<MaterialHandle, MaterialError> {
         let key = PathBuf::from(material_path);
         if let Some(&handle) = self.path_to_handle.get(&key) {
             return Ok(handle);
         }
 
-        let full_path = project_root.join(material_path);
-
-        let mat_file = if full_path.exists() {
-            let contents = std::fs::read_to_string(&full_path).map_err(MaterialError::IoError)?;
-            serde_yaml::from_str::<MaterialFile>(&contents).map_err(MaterialError::ParseError)?
-        } else {
-            tracing::warn!(
-                "Material file not found: {:?}, using defaults",
-                full_path
-            );
-            MaterialFile {
-                shader: String::new(),
-                properties: MaterialProperties::default(),
-                blend_mode: default_opaque(),
-                cull_mode: default_back(),
-            }
-        };
+        let file = material_path.split_once('#').map_or(material_path, |(file, _)| file);
+        let material_root = self.overlay.root_for(project_root, file);
+        let (mat_file, explicit) = read_material_file(&material_root, material_path)?;
 
         let uniform = MaterialUniform::from_properties(&mat_file.properties);
         tracing::warn!(
@@ -165,42 +348,27 @@ impl MaterialCache {
             uniform.roughness
         );
 
-        // Load textures if referenced
-        let albedo_texture = if let (Some(albedo_path), Some(tex_cache), Some(tex_layout)) =
-            (&mat_file.properties.albedo_map, texture_cache.as_deref_mut(), texture_layout)
-        {
-            match tex_cache.get_or_load(device, queue, tex_layout, project_root, albedo_path) {
-                Ok(handle) => {
-                    tracing::info!("Material '{}' loaded albedo_map: {}", material_path, albedo_path);
-                    Some(handle)
-                }
-                Err(e) => {
-                    tracing::warn!("Material '{}' failed to load albedo_map '{}': {}", material_path, albedo_path, e);
-                    None
-                }
+        // Load texture maps if referenced (needs the texture cache and resources)
+        let props = &mat_file.properties;
+        let mut gpu_material = GpuMaterial::untextured(uniform, explicit);
+        gpu_material.sampler = mat_file.sampler.clone();
+        if let (Some(tex_cache), Some(tex_res)) = (texture_cache, texture_resources) {
+            let maps = [
+                (&mut gpu_material.albedo_texture, "albedo_map", props.albedo_map.as_ref(), true),
+                (&mut gpu_material.normal_texture, "normal_map", props.normal_map.as_ref(), false),
+                (&mut gpu_material.metallic_roughness_texture, "metallic_roughness_map", props.metallic_roughness_map.as_ref(), false),
+                (&mut gpu_material.emissive_texture, "emissive_map", props.emissive_map.as_ref(), true),
+            ];
+            for (slot, field, path, srgb) in maps {
+                let srgb = mat_file.color_space.get(field).map_or(srgb, |space| *space == ColorSpace::Srgb);
+                *slot = load_texture_map(device, queue, project_root, tex_cache, material_path, field, path, srgb);
             }
-        } else {
-            None
-        };
-
-        let normal_texture = if let (Some(normal_path), Some(tex_cache), Some(tex_layout)) =
-            (&mat_file.properties.normal_map, texture_cache, texture_layout)
-        {
-            match tex_cache.get_or_load(device, queue, tex_layout, project_root, normal_path) {
-                Ok(handle) => {
-                    tracing::info!("Material '{}' loaded normal_map: {}", material_path, normal_path);
-                    Some(handle)
-                }
-                Err(e) => {
-                    tracing::warn!("Material '{}' failed to load normal_map '{}': {}", material_path, normal_path, e);
-                    None
-                }
+            // An emissive map with no emission color set glows at full strength
+            if gpu_material.emissive_texture.is_some() && props.emission == [0.0; 3] {
+                gpu_material.uniform.emission = [1.0, 1.0, 1.0, 0.0];
             }
-        } else {
-            None
-        };
-
-        let gpu_material = GpuMaterial { uniform, albedo_texture, normal_texture };
+            gpu_material.rebuild_bind_group(device, tex_cache, tex_res);
+        }
 
         let handle = MaterialHandle(self.materials.len());
         self.materials.push(gpu_material);
@@ -209,6 +377,14 @@ impl MaterialCache {
         Ok(handle)
     }
 
+    /// Rebuild the bind groups of materials that name a sampler, after the
+    /// pipeline's samplers changed.
+    pub fn rebuild_sampled_bind_groups(&mut self, device: &wgpu::Device, texture_cache: &TextureCache, texture_resources: &TextureResources) {
+        for material in self.materials.iter_mut().filter(|m| m.sampler.is_some()) {
+            material.rebuild_bind_group(device, texture_cache, texture_resources);
+        }
+    }
+
     pub fn get(&self, handle: MaterialHandle) -> &GpuMaterial {
         &self.materials[handle.0]
     }
@@ -238,10 +414,38 @@ impl MaterialCache {
                 // Create a hardcoded default
                 let uniform = MaterialUniform::from_properties(&MaterialProperties::default());
                 let h = MaterialHandle(self.materials.len());
-                self.materials.push(GpuMaterial { uniform, albedo_texture: None, normal_texture: None });
+                self.materials.push(GpuMaterial::untextured(uniform, ExplicitFactors::default()));
                 h
             });
         self.default_handle = Some(handle);
         handle
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_factors_from_yaml() {
+        let explicit = ExplicitFactors::from_yaml("properties:\n  roughness: 0.9\n  emission: [1, 0, 0]\n");
+        assert_eq!(explicit, ExplicitFactors { roughness: true, metallic: false, emission: true });
+        assert_eq!(ExplicitFactors::from_yaml("shader: pbr\n"), ExplicitFactors::default());
+    }
+
+    #[test]
+    fn test_material_yaml_texture_maps() {
+        let yaml = "properties:\n  roughness: 1.0\n  albedo_map: textures/a.png\n  metallic_roughness_map: textures/mr.png\n  emissive_map: textures/e.png\n";
+        let file: MaterialFile = serde_yaml::from_str(yaml).unwrap();
+        let props = &file.properties;
+        assert_eq!(props.albedo_map.as_deref(), Some("textures/a.png"));
+        assert_eq!(props.metallic_roughness_map.as_deref(), Some("textures/mr.png"));
+        assert_eq!(props.emissive_map.as_deref(), Some("textures/e.png"));
+        assert!(props.normal_map.is_none());
+        assert_eq!(file.blend_mode, "opaque");
+        assert!(file.color_space.is_empty());
+
+        let file: MaterialFile = serde_yaml::from_str("color_space:\n  emissive_map: linear\n").unwrap();
+        assert_eq!(file.color_space.get("emissive_map"), Some(&ColorSpace::Linear));
+    }
+}
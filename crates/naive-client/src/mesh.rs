@@ -1,18 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use wgpu::util::DeviceExt;
 
 use crate::components::MeshHandle;
+use crate::gpu_memory::{BufferArenas, BufferClass, SubBuffer};
 
 #[derive(Debug)]
 pub enum MeshError {
     IoError(String),
     GltfError(gltf::Error),
     StlError(String),
+    ObjError(String),
     NoMeshes,
     NoPrimitives,
     NoPositions,
+    Primitive(String),
 }
 
 impl std::fmt::Display for MeshError {
@@ -21,9 +25,11 @@ impl std::fmt::Display for MeshError {
             Self::IoError(msg) => write!(f, "Mesh IO error: {}", msg),
             Self::GltfError(e) => write!(f, "glTF error: {}", e),
             Self::StlError(msg) => write!(f, "STL error: {}", msg),
+            Self::ObjError(msg) => write!(f, "OBJ error: {}", msg),
             Self::NoMeshes => write!(f, "glTF file contains no meshes"),
             Self::NoPrimitives => write!(f, "glTF mesh has no primitives"),
             Self::NoPositions => write!(f, "glTF primitive has no position data"),
+            Self::Primitive(msg) => write!(f, "Procedural mesh: {}", msg),
         }
     }
 }
@@ -48,16 +54,22 @@ pub struct Vertex3D {
     /// Joint weights for skeletal animation (up to 4 influences).
     /// For non-skinned meshes, set to [1.0, 0.0, 0.0, 0.0].
     pub joint_weights: [f32; 4],
+    /// Tangent direction (xyz) and bitangent sign (w) for normal mapping.
+    pub tangent: [f32; 4],
 }
 
+/// Tangent for vertices without UV-derived tangents (+X, right-handed).
+pub const DEFAULT_TANGENT: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
 impl Vertex3D {
-    const ATTRIBS: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+    const ATTRIBS: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
         0 => Float32x3,  // position
         1 => Float32x3,  // normal
         2 => Float32x2,  // tex_coords
         3 => Float32x4,  // color
         4 => Uint32x4,   // joint_indices
-        5 => Float32x4   // joint_weights
+        5 => Float32x4,  // joint_weights
+        6 => Float32x4   // tangent
     ];
 
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
@@ -77,8 +89,9 @@ pub struct SkinData {
 
 /// A loaded GPU mesh.
 pub struct GpuMesh {
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
+    /// Shared with the mesh's decimated LOD levels.
+    pub vertex_buffer: SubBuffer,
+    pub index_buffer: SubBuffer,
     pub index_count: u32,
     /// Texture bind group for GLB albedo texture (None = no texture).
     pub texture_bind_group: Option<wgpu::BindGroup>,
@@ -88,147 +101,524 @@ pub struct GpuMesh {
     pub physics_vertices: Option<Vec<[f32; 3]>>,
     /// CPU-side triangle indices for physics trimesh colliders.
     pub physics_indices: Option<Vec<[u32; 3]>>,
+    /// Index ranges drawn with their own material (one per glTF material).
+    /// Empty means the whole mesh is drawn once with the entity's material.
+    pub submeshes: Vec<SubMesh>,
+    /// Local-space bounds of the vertices, for frustum culling.
+    pub bounds: crate::culling::Aabb,
+}
+
+/// Material factors of a glTF primitive or an OBJ `usemtl` group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubMeshMaterial {
+    pub name: Option<String>,
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emission: [f32; 3],
+}
+
+impl SubMeshMaterial {
+    fn from_gltf(material: &gltf::Material) -> Self {
+        let pbr = material.pbr_metallic_roughness();
+        Self {
+            name: material.name().map(str::to_string),
+            base_color: pbr.base_color_factor(),
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            emission: material.emissive_factor(),
+        }
+    }
+
+    fn from_mtl(material: &crate::obj::MtlMaterial) -> Self {
+        let [r, g, b] = material.diffuse;
+        Self {
+            name: Some(material.name.clone()),
+            base_color: [r, g, b, material.dissolve],
+            metallic: material.metallic.unwrap_or(0.0),
+            roughness: material.pbr_roughness(),
+            emission: material.emission,
+        }
+    }
+}
+
+/// A range of a mesh's index buffer drawn with one material.
+pub struct SubMesh {
+    pub first_index: u32,
+    pub index_count: u32,
+    /// None for geometry without a material of its own (the entity's material applies).
+    pub material: Option<SubMeshMaterial>,
+    /// Texture maps of the submesh's material (group 2).
+    pub texture_bind_group: Option<Arc<wgpu::BindGroup>>,
+}
+
+/// What one draw of a mesh uses: an index range, its material and texture.
+pub struct DrawSlot<'a> {
+    pub indices: std::ops::Range<u32>,
+    pub material: Option<&'a SubMeshMaterial>,
+    pub texture_bind_group: Option<&'a wgpu::BindGroup>,
+}
+
+impl GpuMesh {
+    /// Draws (and per-draw uniform slots) this mesh needs.
+    pub fn draw_slots(&self) -> u32 {
+        self.submeshes.len().max(1) as u32
+    }
+
+    /// Index range, material and texture of draw `slot` (`0..draw_slots()`).
+    pub fn draw_slot(&self, slot: u32) -> DrawSlot<'_> {
+        match self.submeshes.get(slot as usize) {
+            Some(sub) => DrawSlot {
+                indices: sub.first_index..sub.first_index + sub.index_count,
+                material: sub.material.as_ref(),
+                texture_bind_group: sub.texture_bind_group.as_deref().or(self.texture_bind_group.as_ref()),
+            },
+            None => DrawSlot {
+                indices: 0..self.index_count,
+                material: None,
+                texture_bind_group: self.texture_bind_group.as_ref(),
+            },
+        }
+    }
+
+    /// This mesh simplified to about `ratio` of its triangles (see
+    /// `lod::decimate`), sharing its vertex buffer, materials and textures.
+    /// None without CPU-side geometry (procedural shapes).
+    pub fn decimated(&self, device: &wgpu::Device, ratio: f32, label: &str) -> Option<GpuMesh> {
+        let positions = self.physics_vertices.as_ref()?;
+        let triangles = self.physics_indices.as_ref()?;
+        let runs: Vec<std::ops::Range<usize>> = if self.submeshes.is_empty() {
+            std::iter::once(0..triangles.len()).collect()
+        } else {
+            self.submeshes.iter()
+                .map(|sub| (sub.first_index / 3) as usize..((sub.first_index + sub.index_count) / 3) as usize)
+                .collect()
+        };
+        let (indices, ranges) = crate::lod::decimate(positions, triangles, &runs, ratio);
+        if indices.is_empty() {
+            return None;
+        }
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("Mesh IB: {} (lod {})", label, ratio)),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_SRC,
+        });
+        let submeshes = self.submeshes.iter()
+            .zip(&ranges)
+            .map(|(sub, range)| SubMesh {
+                first_index: range.start,
+                index_count: range.end - range.start,
+                material: sub.material.clone(),
+                texture_bind_group: sub.texture_bind_group.clone(),
+            })
+            .collect();
+        Some(GpuMesh {
+            vertex_buffer: self.vertex_buffer.clone(),
+            index_buffer: SubBuffer::dedicated(index_buffer),
+            index_count: indices.len() as u32,
+            texture_bind_group: None,
+            skin_data: None,
+            // Colliders keep the full-detail shape
+            physics_vertices: self.physics_vertices.clone(),
+            physics_indices: self.physics_indices.clone(),
+            submeshes,
+            bounds: self.bounds,
+        })
+    }
+}
+
+/// Texture views for a material's group 2 bind group. Unset maps use the
+/// `TextureResources` fallbacks, which leave the uniform factors unchanged.
+#[derive(Default, Clone, Copy)]
+pub struct MaterialTextures<'a> {
+    /// Base color (sRGB).
+    pub albedo: Option<&'a wgpu::TextureView>,
+    /// Tangent-space normal map (linear).
+    pub normal: Option<&'a wgpu::TextureView>,
+    /// glTF layout: G = roughness, B = metallic (linear).
+    pub metallic_roughness: Option<&'a wgpu::TextureView>,
+    /// Emission color (sRGB), scaled by the material's emission.
+    pub emissive: Option<&'a wgpu::TextureView>,
+    /// Named pipeline sampler; the default sampler if unset or unknown.
+    pub sampler: Option<&'a str>,
 }
 
-/// Shared texture resources: bind group layout and 1x1 white fallback.
+/// Shared texture resources: the material texture bind group layout
+/// (group 2: albedo, sampler, normal, metallic-roughness, emissive) and 1x1
+/// fallbacks for maps a material doesn't set.
 pub struct TextureResources {
     pub bind_group_layout: wgpu::BindGroupLayout,
+    /// All fallbacks: white albedo, flat normal, white metallic-roughness and emissive.
     pub default_bind_group: wgpu::BindGroup,
+    /// Linear, repeating sampler shared by all material textures.
+    pub sampler: wgpu::Sampler,
+    /// Samplers of the compiled pipeline's `samplers:` section, by name.
+    named_samplers: HashMap<String, wgpu::Sampler>,
+    white_view: wgpu::TextureView,
+    flat_normal_view: wgpu::TextureView,
 }
 
 impl TextureResources {
     pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let texture_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        // Bindings 0 and 1 keep the original albedo + sampler slots, so shaders
+        // that only sample albedo still match this layout
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Texture Bind Group Layout"),
+            label: Some("Material Texture Bind Group Layout"),
             entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
+                texture_entry(0),
                 wgpu::BindGroupLayoutEntry {
                     binding: 1,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                texture_entry(2),
+                texture_entry(3),
+                texture_entry(4),
             ],
         });
 
-        // Create 1x1 white fallback texture
-        let white_pixel: [u8; 4] = [255, 255, 255, 255];
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("White 1x1 Fallback"),
-            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture, mip_level: 0,
-                origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All,
-            },
-            &white_pixel,
-            wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(4), rows_per_image: Some(1) },
-            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        let white_view = crate::texture_cache::create_texture_view_from_rgba(
+            device, queue, &[255, 255, 255, 255], 1, 1, true, "White 1x1 Fallback",
+        );
+        let flat_normal_view = crate::texture_cache::create_texture_view_from_rgba(
+            device, queue, &[128, 128, 255, 255], 1, 1, false, "Flat Normal 1x1 Fallback",
         );
-        let view = texture.create_view(&Default::default());
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Material Texture Sampler"),
             mag_filter: wgpu::FilterMode::Linear,
             min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
             ..Default::default()
         });
-        let default_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Default White Texture BG"),
-            layout: &bind_group_layout,
+        let default_bind_group = Self::bind_group(
+            device, &bind_group_layout, &sampler, &white_view, &flat_normal_view,
+            "Default Material Texture BG", MaterialTextures::default(),
+        );
+
+        Self {
+            bind_group_layout, default_bind_group, sampler, named_samplers: HashMap::new(), white_view, flat_normal_view,
+        }
+    }
+
+    /// Replace the named samplers materials can pick (after a pipeline compile).
+    pub fn set_named_samplers(&mut self, samplers: HashMap<String, wgpu::Sampler>) {
+        self.named_samplers = samplers;
+    }
+
+    /// Create a material texture bind group, filling unset maps with fallbacks.
+    pub fn create_bind_group(&self, device: &wgpu::Device, label: &str, textures: MaterialTextures<'_>) -> wgpu::BindGroup {
+        let sampler = match textures.sampler {
+            Some(name) => self.named_samplers.get(name).unwrap_or_else(|| {
+                tracing::warn!("{}: unknown sampler '{}', using the default", label, name);
+                &self.sampler
+            }),
+            None => &self.sampler,
+        };
+        Self::bind_group(
+            device, &self.bind_group_layout, sampler, &self.white_view, &self.flat_normal_view,
+            label, textures,
+        )
+    }
+
+    fn bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        white: &wgpu::TextureView,
+        flat_normal: &wgpu::TextureView,
+        label: &str,
+        textures: MaterialTextures<'_>,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout,
             entries: &[
-                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
-                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(textures.albedo.unwrap_or(white)) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(textures.normal.unwrap_or(flat_normal)) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(textures.metallic_roughness.unwrap_or(white)) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::TextureView(textures.emissive.unwrap_or(white)) },
             ],
-        });
-
-        Self { bind_group_layout, default_bind_group }
+        })
     }
 }
 
-fn create_texture_bind_group(
+/// Upload a glTF image as an RGBA8 texture.
+fn create_gltf_texture_view(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     image_data: &gltf::image::Data,
-    layout: &wgpu::BindGroupLayout,
-) -> wgpu::BindGroup {
+    srgb: bool,
+) -> wgpu::TextureView {
     // Ensure RGBA8 format
-    let (rgba_pixels, width, height) = match image_data.format {
-        gltf::image::Format::R8G8B8A8 => {
-            (image_data.pixels.clone(), image_data.width, image_data.height)
+    let rgba_pixels = match image_data.format {
+        gltf::image::Format::R8G8B8 => image_data.pixels.chunks(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        // RGBA8, or a fallback: try to use as RGBA
+        _ => image_data.pixels.clone(),
+    };
+    crate::texture_cache::create_texture_view_from_rgba(
+        device, queue, &rgba_pixels, image_data.width, image_data.height, srgb, "GLB Texture",
+    )
+}
+
+/// Geometry of a runtime mesh, built by a script (`mesh.create`) or a Rust
+/// system and uploaded with `MeshCache::insert_runtime_mesh`.
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    /// Per-vertex normals; empty to compute smooth normals from the triangles.
+    pub normals: Vec<[f32; 3]>,
+    /// Per-vertex UVs; vertices past the end get (0, 0).
+    pub uvs: Vec<[f32; 2]>,
+    /// Triangle list.
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    /// Check that the indices form triangles of existing vertices and that
+    /// normals, when given, match the vertices.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(format!("{} indices is not a whole number of triangles", self.indices.len()));
         }
-        gltf::image::Format::R8G8B8 => {
-            let rgba: Vec<u8> = image_data.pixels.chunks(3)
-                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
-                .collect();
-            (rgba, image_data.width, image_data.height)
+        if let Some(&index) = self.indices.iter().find(|&&i| i as usize >= self.positions.len()) {
+            return Err(format!("index {} is out of range for {} vertices", index, self.positions.len()));
         }
-        _ => {
-            // Fallback: try to use as RGBA
-            (image_data.pixels.clone(), image_data.width, image_data.height)
+        if !self.normals.is_empty() && self.normals.len() != self.positions.len() {
+            return Err(format!("{} normals for {} vertices", self.normals.len(), self.positions.len()));
         }
-    };
+        Ok(())
+    }
 
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("GLB Albedo Texture"),
-        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        view_formats: &[],
-    });
+    /// GPU vertices, with the missing normals, UVs and tangents filled in.
+    pub fn vertices(&self) -> Vec<Vertex3D> {
+        let normals = if self.normals.is_empty() {
+            generate_smooth_normals(&self.positions, &self.indices)
+        } else {
+            self.normals.clone()
+        };
+        let tangents = generate_tangents(&self.positions, &normals, &self.uvs, &self.indices);
+        self.positions.iter().enumerate().map(|(i, pos)| {
+            Vertex3D {
+                position: *pos,
+                normal: normals[i],
+                tex_coords: self.uvs.get(i).copied().unwrap_or([0.0, 0.0]),
+                color: [1.0, 1.0, 1.0, 1.0],
+                joint_indices: [0, 0, 0, 0],
+                joint_weights: [1.0, 0.0, 0.0, 0.0],
+                tangent: tangents[i],
+            }
+        }).collect()
+    }
 
-    queue.write_texture(
-        wgpu::TexelCopyTextureInfo {
-            texture: &texture, mip_level: 0,
-            origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All,
-        },
-        &rgba_pixels,
-        wgpu::TexelCopyBufferLayout {
-            offset: 0,
-            bytes_per_row: Some(4 * width),
-            rows_per_image: Some(height),
-        },
-        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-    );
+    fn triangles(&self) -> Vec<[u32; 3]> {
+        self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect()
+    }
+}
 
-    let view = texture.create_view(&Default::default());
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
-    });
+/// Most segments, rings or sectors a built-in shape can ask for.
+const MAX_PRIMITIVE_SEGMENTS: u32 = 256;
+
+/// Built-in shapes and their parameters, in argument order.
+pub const PRIMITIVE_PARAMS: &[(&str, &[&str])] = &[
+    ("cube", &[]),
+    ("sphere", &["radius", "rings", "sectors"]),
+    ("plane", &["width", "depth", "subdivisions"]),
+    ("cylinder", &["radius", "height", "segments"]),
+    ("cone", &["radius", "height", "segments"]),
+    ("torus", &["major_radius", "minor_radius", "major_segments", "minor_segments"]),
+    ("grass", &["width", "height", "segments"]),
+    ("capsule", &["radius", "height", "segments", "rings"]),
+    ("icosphere", &["radius", "subdivisions"]),
+    ("rounded_box", &["width", "height", "depth", "radius", "segments"]),
+];
+
+/// A built-in shape, referenced as `procedural:<shape>` or with arguments
+/// as `capsule(0.5, 2.0)` (the `procedural:` prefix is optional then).
+/// Omitted trailing arguments take the defaults. All shapes are centered
+/// on the origin, with their axis along Y.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    /// Unit cube.
+    Cube,
+    Sphere { radius: f32, rings: u32, sectors: u32 },
+    /// XZ plane facing +Y.
+    Plane { width: f32, depth: f32, subdivisions: u32 },
+    Cylinder { radius: f32, height: f32, segments: u32 },
+    Cone { radius: f32, height: f32, segments: u32 },
+    /// In the XZ plane.
+    Torus { major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32 },
+    /// A tapered grass blade, rooted at the origin.
+    Grass { width: f32, height: f32, segments: u32 },
+    /// `height` is the full height, caps included; `rings` per cap.
+    Capsule { radius: f32, height: f32, segments: u32, rings: u32 },
+    /// Subdivided icosahedron: evenly sized triangles, no poles.
+    Icosphere { radius: f32, subdivisions: u32 },
+    /// Box with edges and corners rounded to `radius`.
+    RoundedBox { width: f32, height: f32, depth: f32, radius: f32, segments: u32 },
+}
 
-    device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("GLB Albedo Texture BG"),
-        layout,
-        entries: &[
-            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
-            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
-        ],
-    })
+impl Primitive {
+    /// Parse a mesh reference. None when it names a file rather than a
+    /// built-in shape.
+    pub fn parse(spec: &str) -> Option<Result<Primitive, String>> {
+        let (prefixed, rest) = match spec.strip_prefix("procedural:") {
+            Some(rest) => (true, rest.trim()),
+            None => (false, spec.trim()),
+        };
+        let (name, args) = match rest.split_once('(') {
+            Some((name, args)) => (name.trim(), Some(args)),
+            None => (rest, None),
+        };
+        let params = PRIMITIVE_PARAMS.iter().find(|(n, _)| *n == name).map(|(_, params)| *params);
+        if !prefixed && (args.is_none() || params.is_none()) {
+            return None;
+        }
+        Some(Self::parse_args(name, params, args))
+    }
+
+    fn parse_args(name: &str, params: Option<&'static [&'static str]>, args: Option<&str>) -> Result<Primitive, String> {
+        let Some(params) = params else {
+            let names: Vec<&str> = PRIMITIVE_PARAMS.iter().map(|(n, _)| *n).collect();
+            return Err(format!("unknown shape '{}' (expected one of: {})", name, names.join(", ")));
+        };
+        let mut values = Vec::new();
+        if let Some(args) = args {
+            let inner = args.trim_end().strip_suffix(')').ok_or_else(|| format!("{}: missing ')'", name))?;
+            if !inner.trim().is_empty() {
+                for arg in inner.split(',') {
+                    let arg = arg.trim();
+                    values.push(arg.parse::<f32>().map_err(|_| format!("{}: '{}' is not a number", name, arg))?);
+                }
+            }
+        }
+        if values.len() > params.len() {
+            return Err(format!(
+                "{} takes at most {} arguments ({}), got {}",
+                name,
+                params.len(),
+                params.join(", "),
+                values.len()
+            ));
+        }
+
+        let a = PrimitiveArgs { shape: name, params, values };
+        let max = MAX_PRIMITIVE_SEGMENTS;
+        Ok(match name {
+            "cube" => Primitive::Cube,
+            "sphere" => Primitive::Sphere { radius: a.size(0, 0.5)?, rings: a.count(1, 32, 2, max)?, sectors: a.count(2, 32, 3, max)? },
+            "plane" => Primitive::Plane { width: a.size(0, 1.0)?, depth: a.size(1, 1.0)?, subdivisions: a.count(2, 1, 1, max)? },
+            "cylinder" => Primitive::Cylinder { radius: a.size(0, 0.5)?, height: a.size(1, 1.0)?, segments: a.count(2, 32, 3, max)? },
+            "cone" => Primitive::Cone { radius: a.size(0, 0.5)?, height: a.size(1, 1.0)?, segments: a.count(2, 32, 3, max)? },
+            "torus" => Primitive::Torus {
+                major_radius: a.size(0, 0.3)?,
+                minor_radius: a.size(1, 0.1)?,
+                major_segments: a.count(2, 32, 3, max)?,
+                minor_segments: a.count(3, 16, 3, max)?,
+            },
+            "grass" => Primitive::Grass { width: a.size(0, 0.08)?, height: a.size(1, 1.0)?, segments: a.count(2, 4, 1, max)? },
+            "capsule" => Primitive::Capsule {
+                radius: a.size(0, 0.5)?,
+                height: a.size(1, 2.0)?,
+                segments: a.count(2, 32, 3, max)?,
+                rings: a.count(3, 8, 1, max)?,
+            },
+            // Each subdivision quadruples the triangles (20 * 4^n)
+            "icosphere" => Primitive::Icosphere { radius: a.size(0, 0.5)?, subdivisions: a.count(1, 2, 0, 6)? },
+            "rounded_box" => Primitive::RoundedBox {
+                width: a.size(0, 1.0)?,
+                height: a.size(1, 1.0)?,
+                depth: a.size(2, 1.0)?,
+                radius: a.size(3, 0.1)?,
+                segments: a.count(4, 4, 1, max)?,
+            },
+            _ => unreachable!("shape listed in PRIMITIVE_PARAMS"),
+        })
+    }
+
+    /// The shape's vertices and triangle indices.
+    pub fn geometry(&self) -> (Vec<Vertex3D>, Vec<u32>) {
+        match *self {
+            Primitive::Cube => cube_geometry(),
+            Primitive::Sphere { radius, rings, sectors } => sphere_geometry(radius, rings, sectors),
+            Primitive::Plane { width, depth, subdivisions } => plane_geometry(width, depth, subdivisions),
+            Primitive::Cylinder { radius, height, segments } => cylinder_geometry(radius, height, segments),
+            Primitive::Cone { radius, height, segments } => cone_geometry(radius, height, segments),
+            Primitive::Torus { major_radius, minor_radius, major_segments, minor_segments } => {
+                torus_geometry(major_radius, minor_radius, major_segments, minor_segments)
+            }
+            Primitive::Grass { width, height, segments } => grass_blade_geometry(width, height, segments),
+            Primitive::Capsule { radius, height, segments, rings } => capsule_geometry(radius, height, segments, rings),
+            Primitive::Icosphere { radius, subdivisions } => icosphere_geometry(radius, subdivisions),
+            Primitive::RoundedBox { width, height, depth, radius, segments } => {
+                rounded_box_geometry([width, height, depth], radius, segments)
+            }
+        }
+    }
+
+    /// Generate the shape's mesh.
+    pub fn build(&self, device: &wgpu::Device) -> GpuMesh {
+        let (vertices, indices) = self.geometry();
+        build_procedural_gpu_mesh(device, &vertices, &indices, &format!("Procedural {:?}", self))
+    }
+}
+
+/// Arguments of a primitive reference; missing ones take defaults.
+struct PrimitiveArgs<'a> {
+    shape: &'a str,
+    params: &'static [&'static str],
+    values: Vec<f32>,
+}
+
+impl PrimitiveArgs<'_> {
+    fn size(&self, i: usize, default: f32) -> Result<f32, String> {
+        let value = self.values.get(i).copied().unwrap_or(default);
+        if value > 0.0 && value.is_finite() {
+            Ok(value)
+        } else {
+            Err(format!("{}: {} must be positive, got {}", self.shape, self.params[i], value))
+        }
+    }
+
+    fn count(&self, i: usize, default: u32, min: u32, max: u32) -> Result<u32, String> {
+        let Some(&value) = self.values.get(i) else {
+            return Ok(default);
+        };
+        if value.fract() != 0.0 || value < min as f32 || value > max as f32 {
+            return Err(format!(
+                "{}: {} must be a whole number from {} to {}, got {}",
+                self.shape, self.params[i], min, max, value
+            ));
+        }
+        Ok(value as u32)
+    }
 }
 
 /// Cache of loaded meshes, keyed by file path.
 pub struct MeshCache {
     meshes: Vec<GpuMesh>,
     path_to_handle: HashMap<PathBuf, MeshHandle>,
+    /// Mod folders searched before the project for mesh files.
+    pub overlay: crate::mods::ModOverlay,
+    /// Where vertex and index data live (see `gpu_memory`).
+    pub arenas: BufferArenas,
+    /// Stands in for the buffers of released meshes.
+    released: Option<SubBuffer>,
 }
 
 impl MeshCache {
@@ -236,6 +626,9 @@ impl MeshCache {
         Self {
             meshes: Vec::new(),
             path_to_handle: HashMap::new(),
+            overlay: Default::default(),
+            arenas: BufferArenas::default(),
+            released: None,
         }
     }
 
@@ -252,44 +645,28 @@ impl MeshCache {
             return Ok(handle);
         }
 
-        // Support procedural mesh specifiers: "procedural:sphere", "procedural:cube"
-        let gpu_mesh = if let Some(shape) = mesh_path.strip_prefix("procedural:") {
-            match shape {
-                "sphere" => {
-                    tracing::info!("Generating procedural sphere");
-                    create_procedural_sphere(device, 0.5, 32, 32)
-                }
-                "cube" => {
-                    tracing::info!("Generating procedural cube");
-                    create_procedural_cube(device)
-                }
-                "plane" => {
-                    tracing::info!("Generating procedural plane");
-                    create_procedural_plane(device, 1.0, 1.0, 1)
-                }
-                "cylinder" => {
-                    tracing::info!("Generating procedural cylinder");
-                    create_procedural_cylinder(device, 0.5, 1.0, 32)
+        // Built-in shapes: "procedural:sphere", "capsule(0.5, 2.0)", ...
+        let gpu_mesh = if let Some(primitive) = Primitive::parse(mesh_path) {
+            let primitive = match primitive {
+                Ok(primitive) => primitive,
+                // A bare unknown name keeps the old cube fallback
+                Err(e) if !mesh_path.contains('(') => {
+                    tracing::warn!("{}, using cube", e);
+                    Primitive::Cube
                 }
-                "cone" => {
-                    tracing::info!("Generating procedural cone");
-                    create_procedural_cone(device, 0.5, 1.0, 32)
-                }
-                "torus" => {
-                    tracing::info!("Generating procedural torus");
-                    create_procedural_torus(device, 0.3, 0.1, 32, 16)
-                }
-                _ => {
-                    tracing::warn!("Unknown procedural shape '{}', using cube", shape);
-                    create_procedural_cube(device)
-                }
-            }
+                Err(e) => return Err(MeshError::Primitive(e)),
+            };
+            tracing::info!("Generating procedural {:?}", primitive);
+            primitive.build(device)
         } else if mesh_path.to_lowercase().ends_with(".stl") {
-            load_stl(device, project_root, mesh_path)?
+            load_stl(device, &self.overlay.root_for(project_root, mesh_path), mesh_path)?
+        } else if mesh_path.to_lowercase().ends_with(".obj") {
+            load_obj(device, queue, &self.overlay.root_for(project_root, mesh_path), mesh_path, texture_resources)?
         } else {
-            load_gltf(device, queue, project_root, mesh_path, texture_resources)?
+            load_gltf(device, queue, &self.overlay.root_for(project_root, mesh_path), mesh_path, texture_resources)?
         };
 
+        let gpu_mesh = self.adopt_into_arenas(device, queue, gpu_mesh);
         let handle = MeshHandle(self.meshes.len());
         self.meshes.push(gpu_mesh);
         self.path_to_handle.insert(key, handle);
@@ -297,46 +674,152 @@ impl MeshCache {
         Ok(handle)
     }
 
+    /// `mesh_path` decimated to about `ratio` of its triangles, for a LOD
+    /// level. Meshes that can't be decimated come back at full detail.
+    pub fn get_or_load_decimated(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        project_root: &Path,
+        mesh_path: &str,
+        ratio: f32,
+        texture_resources: Option<&TextureResources>,
+    ) -> Result<MeshHandle, MeshError> {
+        let key = PathBuf::from(format!("{}#lod{}", mesh_path, ratio));
+        if let Some(&handle) = self.path_to_handle.get(&key) {
+            return Ok(handle);
+        }
+        let base = self.get_or_load(device, queue, project_root, mesh_path, texture_resources)?;
+        // Runtime meshes are rewritten in place, which a LOD sharing their
+        // vertices wouldn't follow
+        if mesh_path.starts_with("runtime:") {
+            return Ok(base);
+        }
+        let Some(gpu_mesh) = self.meshes[base.0].decimated(device, ratio, mesh_path) else {
+            tracing::warn!("Mesh '{}' has no geometry to decimate, its LOD uses the full mesh", mesh_path);
+            return Ok(base);
+        };
+        tracing::info!(
+            "Decimated mesh '{}' to {:.0}%: {} -> {} indices",
+            mesh_path,
+            ratio * 100.0,
+            self.meshes[base.0].index_count,
+            gpu_mesh.index_count
+        );
+        let gpu_mesh = self.adopt_into_arenas(device, queue, gpu_mesh);
+        let handle = MeshHandle(self.meshes.len());
+        self.meshes.push(gpu_mesh);
+        self.path_to_handle.insert(key, handle);
+        Ok(handle)
+    }
+
     pub fn get(&self, handle: MeshHandle) -> &GpuMesh {
         &self.meshes[handle.0]
     }
 
-    /// Insert a runtime mesh built from raw vertex data (from Lua mesh.create).
+    /// Add a runtime mesh, used by entities as `runtime:<name>`. A name
+    /// that already exists gets the new geometry: written over the old
+    /// buffers when it fits in them, uploaded afresh otherwise. `data` must
+    /// pass `MeshData::validate`.
     pub fn insert_runtime_mesh(
         &mut self,
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         name: &str,
-        positions: &[[f32; 3]],
-        normals: &[[f32; 3]],
-        uvs: &[[f32; 2]],
-        indices: &[u32],
+        data: &MeshData,
     ) -> MeshHandle {
         let key = PathBuf::from(format!("runtime:{}", name));
+        let vertices = data.vertices();
         if let Some(&handle) = self.path_to_handle.get(&key) {
+            let arenas = self.arenas.clone();
+            let mesh = &mut self.meshes[handle.0];
+            mesh.vertex_buffer = rewrite(&arenas, device, queue, BufferClass::Vertex, &mesh.vertex_buffer, bytemuck::cast_slice(&vertices));
+            mesh.index_buffer = rewrite(&arenas, device, queue, BufferClass::Index, &mesh.index_buffer, bytemuck::cast_slice(&data.indices));
+            mesh.index_count = data.indices.len() as u32;
+            mesh.bounds = crate::culling::Aabb::from_points(data.positions.iter().copied());
+            mesh.physics_vertices = Some(data.positions.clone());
+            mesh.physics_indices = Some(data.triangles());
+            tracing::debug!("Updated runtime mesh '{}': {} vertices, {} indices", name, vertices.len(), data.indices.len());
             return handle;
         }
 
-        let j = [0u32, 0, 0, 0];
-        let w = [1.0f32, 0.0, 0.0, 0.0];
-        let vertices: Vec<Vertex3D> = positions.iter().enumerate().map(|(i, pos)| {
-            Vertex3D {
-                position: *pos,
-                normal: normals.get(i).copied().unwrap_or([0.0, 1.0, 0.0]),
-                tex_coords: uvs.get(i).copied().unwrap_or([0.0, 0.0]),
-                color: [1.0, 1.0, 1.0, 1.0],
-                joint_indices: j,
-                joint_weights: w,
-            }
-        }).collect();
-
-        let gpu_mesh = build_procedural_gpu_mesh(device, &vertices, indices, &format!("Runtime: {}", name));
+        let mut gpu_mesh = build_procedural_gpu_mesh(device, &vertices, &data.indices, &format!("Runtime: {}", name));
+        // Kept for trimesh colliders
+        gpu_mesh.physics_vertices = Some(data.positions.clone());
+        gpu_mesh.physics_indices = Some(data.triangles());
+        let gpu_mesh = self.adopt_into_arenas(device, queue, gpu_mesh);
         let handle = MeshHandle(self.meshes.len());
         self.meshes.push(gpu_mesh);
         self.path_to_handle.insert(key, handle);
-        tracing::info!("Created runtime mesh '{}': {} vertices, {} indices", name, positions.len(), indices.len());
+        tracing::info!("Created runtime mesh '{}': {} vertices, {} indices", name, vertices.len(), data.indices.len());
         handle
     }
 
+    /// Move a freshly built mesh's buffers into the arenas.
+    fn adopt_into_arenas(&self, device: &wgpu::Device, queue: &wgpu::Queue, mut gpu_mesh: GpuMesh) -> GpuMesh {
+        gpu_mesh.vertex_buffer = self.arenas.adopt(device, queue, BufferClass::Vertex, &gpu_mesh.vertex_buffer);
+        gpu_mesh.index_buffer = self.arenas.adopt(device, queue, BufferClass::Index, &gpu_mesh.index_buffer);
+        gpu_mesh
+    }
+
+    /// Free the GPU buffers of loaded meshes outside `keep`, on a scene
+    /// change. A released handle draws nothing and its file loads afresh on
+    /// the next `get_or_load`; runtime meshes (Lua `mesh.create`) are kept.
+    /// Returns how many meshes were released.
+    pub fn release_unused(&mut self, device: &wgpu::Device, keep: &HashSet<MeshHandle>) -> usize {
+        let mut runtime = HashSet::new();
+        self.path_to_handle.retain(|path, handle| {
+            if path.to_string_lossy().starts_with("runtime:") {
+                runtime.insert(*handle);
+            }
+            runtime.contains(handle) || keep.contains(handle)
+        });
+        let empty = self.released.get_or_insert_with(|| {
+            SubBuffer::dedicated(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Released Mesh"),
+                size: wgpu::COPY_BUFFER_ALIGNMENT,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::INDEX,
+                mapped_at_creation: false,
+            }))
+        });
+
+        let mut released = 0;
+        for (i, mesh) in self.meshes.iter_mut().enumerate() {
+            let handle = MeshHandle(i);
+            if keep.contains(&handle) || runtime.contains(&handle) || mesh.vertex_buffer.shares(empty) {
+                continue;
+            }
+            *mesh = GpuMesh {
+                vertex_buffer: empty.clone(),
+                index_buffer: empty.clone(),
+                index_count: 0,
+                texture_bind_group: None,
+                skin_data: None,
+                physics_vertices: None,
+                physics_indices: None,
+                submeshes: Vec::new(),
+                bounds: crate::culling::Aabb::from_points([]),
+            };
+            released += 1;
+        }
+        released
+    }
+
+    /// Compact the vertex and index arenas if they have become fragmented.
+    /// Returns the bytes copied.
+    pub fn compact(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> u64 {
+        let mut moved = 0;
+        if self.arenas.needs_compaction(BufferClass::Vertex) {
+            let live = self.meshes.iter_mut().map(|m| &mut m.vertex_buffer);
+            moved += self.arenas.compact(device, queue, BufferClass::Vertex, live);
+        }
+        if self.arenas.needs_compaction(BufferClass::Index) {
+            let live = self.meshes.iter_mut().map(|m| &mut m.index_buffer);
+            moved += self.arenas.compact(device, queue, BufferClass::Index, live);
+        }
+        moved
+    }
+
     /// Check if a mesh has skin data.
     pub fn has_skin(&self, handle: MeshHandle) -> bool {
         self.meshes.get(handle.0).map(|m| m.skin_data.is_some()).unwrap_or(false)
@@ -376,6 +859,97 @@ impl MeshCache {
     }
 }
 
+/// What a mesh takes on the GPU, measured without a device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MeshFootprint {
+    pub vertices: u64,
+    pub indices: u64,
+    /// Draws per instance (see `GpuMesh::draw_slots`).
+    pub draws: u64,
+    /// Images the mesh file brings (glTF images, OBJ texture maps).
+    pub texture_bytes: u64,
+}
+
+impl MeshFootprint {
+    /// Bytes of the vertex and index buffers.
+    pub fn geometry_bytes(&self) -> u64 {
+        self.vertices * std::mem::size_of::<Vertex3D>() as u64 + self.indices * 4
+    }
+}
+
+/// Measure `mesh_path` as `MeshCache::get_or_load` would load it. Unlike
+/// loading, a missing file is an error rather than the cube stand-in, and
+/// so is an unknown built-in shape.
+pub fn measure_mesh(project_root: &Path, mesh_path: &str) -> Result<MeshFootprint, MeshError> {
+    if let Some(primitive) = Primitive::parse(mesh_path) {
+        let (vertices, indices) = primitive.map_err(MeshError::Primitive)?.geometry();
+        return Ok(MeshFootprint {
+            vertices: vertices.len() as u64,
+            indices: indices.len() as u64,
+            draws: 1,
+            texture_bytes: 0,
+        });
+    }
+    let full_path = project_root.join(mesh_path);
+    if !full_path.exists() {
+        return Err(MeshError::IoError(format!("{} not found", mesh_path)));
+    }
+
+    let lower = mesh_path.to_lowercase();
+    if lower.ends_with(".stl") {
+        let mut file = std::fs::File::open(&full_path)
+            .map_err(|e| MeshError::StlError(format!("Failed to open {:?}: {}", full_path, e)))?;
+        let stl = stl_io::read_stl(&mut file)
+            .map_err(|e| MeshError::StlError(format!("Failed to parse {:?}: {}", full_path, e)))?;
+        Ok(MeshFootprint {
+            vertices: stl.vertices.len() as u64,
+            indices: stl.faces.len() as u64 * 3,
+            draws: 1,
+            texture_bytes: 0,
+        })
+    } else if lower.ends_with(".obj") {
+        let source = std::fs::read_to_string(&full_path)
+            .map_err(|e| MeshError::IoError(format!("Failed to read {:?}: {}", full_path, e)))?;
+        let geometry = crate::obj::parse_obj(&source)
+            .map_err(|e| MeshError::ObjError(format!("{}: {}", mesh_path, e)))?;
+
+        // Diffuse and normal maps of the materials the groups use, once each
+        let used: HashSet<&str> = geometry.groups.iter().filter_map(|g| g.material.as_deref()).collect();
+        let obj_dir = Path::new(mesh_path).parent().unwrap_or(Path::new(""));
+        let mut maps = HashSet::new();
+        for lib in &geometry.material_libs {
+            let mtl_path = obj_dir.join(lib);
+            let Ok(mtl) = std::fs::read_to_string(project_root.join(&mtl_path)) else {
+                continue;
+            };
+            let mtl_dir = mtl_path.parent().unwrap_or(Path::new(""));
+            for material in crate::obj::parse_mtl(&mtl).iter().filter(|m| used.contains(m.name.as_str())) {
+                for map in [&material.diffuse_map, &material.normal_map].into_iter().flatten() {
+                    maps.insert(project_root.join(mtl_dir).join(map));
+                }
+            }
+        }
+        Ok(MeshFootprint {
+            vertices: geometry.positions.len() as u64,
+            indices: geometry.groups.iter().map(|g| g.indices.len() as u64).sum(),
+            draws: geometry.groups.len().max(1) as u64,
+            texture_bytes: maps.iter().filter_map(|map| crate::texture_cache::texture_bytes(map).ok()).sum(),
+        })
+    } else {
+        let (document, buffers, images) = gltf::import(&full_path)?;
+        let geometry = build_gltf_geometry(&document, &buffers);
+        if geometry.vertices.is_empty() {
+            return Err(MeshError::NoMeshes);
+        }
+        Ok(MeshFootprint {
+            vertices: geometry.vertices.len() as u64,
+            indices: geometry.indices.len() as u64,
+            draws: geometry.submeshes.len().max(1) as u64,
+            texture_bytes: images.iter().map(|image| image.width as u64 * image.height as u64 * 4).sum(),
+        })
+    }
+}
+
 /// Load an STL file (ASCII or binary) and create GPU buffers.
 /// STL files contain triangles with per-face normals but no UVs or textures.
 fn load_stl(
@@ -404,6 +978,7 @@ fn load_stl(
 
     let j = [0u32, 0, 0, 0];
     let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
 
     // Build per-vertex data from indexed STL
     let mut vertices: Vec<Vertex3D> = Vec::with_capacity(stl.vertices.len());
@@ -440,6 +1015,7 @@ fn load_stl(
             color: [1.0, 1.0, 1.0, 1.0],
             joint_indices: j,
             joint_weights: w,
+            tangent: t,
         });
     }
 
@@ -467,9 +1043,153 @@ fn load_stl(
     Ok(gpu_mesh)
 }
 
-/// Load a glTF file and create GPU buffers.
-/// Merges all nodes/meshes/primitives into a single draw call,
-/// applying each node's world transform to positions and normals.
+/// Load a Wavefront OBJ file and create GPU buffers. Faces are grouped by
+/// `usemtl` into submeshes drawn with the factors and diffuse texture of the
+/// matching `newmtl` in the file's `mtllib`s. Missing normals are generated
+/// smooth; tangents come from the UVs.
+fn load_obj(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    project_root: &Path,
+    mesh_path: &str,
+    texture_resources: Option<&TextureResources>,
+) -> Result<GpuMesh, MeshError> {
+    let full_path = project_root.join(mesh_path);
+
+    if !full_path.exists() {
+        tracing::warn!("OBJ file not found: {:?}, using procedural cube", full_path);
+        return Ok(create_procedural_cube(device));
+    }
+
+    let source = std::fs::read_to_string(&full_path)
+        .map_err(|e| MeshError::IoError(format!("Failed to read {:?}: {}", full_path, e)))?;
+    let geometry = crate::obj::parse_obj(&source)
+        .map_err(|e| MeshError::ObjError(format!("{}: {}", mesh_path, e)))?;
+    if geometry.groups.iter().all(|g| g.indices.is_empty()) {
+        return Err(MeshError::ObjError(format!("{}: no faces", mesh_path)));
+    }
+
+    // Materials from every mtllib, keyed by name; a missing library only
+    // costs the colors, so it is a warning
+    let obj_dir = Path::new(mesh_path).parent().unwrap_or(Path::new(""));
+    let mut materials: HashMap<String, (crate::obj::MtlMaterial, PathBuf)> = HashMap::new();
+    for lib in &geometry.material_libs {
+        let mtl_path = obj_dir.join(lib);
+        match std::fs::read_to_string(project_root.join(&mtl_path)) {
+            Ok(mtl) => {
+                let mtl_dir = mtl_path.parent().map(Path::to_path_buf).unwrap_or_default();
+                for material in crate::obj::parse_mtl(&mtl) {
+                    materials.insert(material.name.clone(), (material, mtl_dir.clone()));
+                }
+            }
+            Err(e) => tracing::warn!("OBJ '{}': failed to read mtllib {:?}: {}", mesh_path, mtl_path, e),
+        }
+    }
+
+    let mut indices: Vec<u32> = Vec::new();
+    for group in &geometry.groups {
+        indices.extend_from_slice(&group.indices);
+    }
+    let normals = geometry
+        .normals
+        .clone()
+        .unwrap_or_else(|| generate_smooth_normals(&geometry.positions, &indices));
+    let tangents = generate_tangents(&geometry.positions, &normals, &geometry.uvs, &indices);
+    let vertices: Vec<Vertex3D> = (0..geometry.positions.len())
+        .map(|i| Vertex3D {
+            position: geometry.positions[i],
+            normal: normals[i],
+            tex_coords: geometry.uvs[i],
+            color: [1.0, 1.0, 1.0, 1.0],
+            joint_indices: [0, 0, 0, 0],
+            joint_weights: [1.0, 0.0, 0.0, 0.0],
+            tangent: tangents[i],
+        })
+        .collect();
+
+    // One submesh per material group; each image is uploaded once and shared
+    let mut image_views: HashMap<(PathBuf, bool), wgpu::TextureView> = HashMap::new();
+    let mut submeshes = Vec::new();
+    let mut first_index = 0u32;
+    for group in &geometry.groups {
+        let found = group.material.as_ref().and_then(|name| {
+            let found = materials.get(name);
+            if found.is_none() {
+                tracing::warn!("OBJ '{}': material '{}' not found in mtllib", mesh_path, name);
+            }
+            found
+        });
+        let texture_bind_group = match (texture_resources, found) {
+            (Some(tex_res), Some((material, mtl_dir))) => {
+                // Diffuse (sRGB) and normal (linear) maps
+                let mut keys: [Option<(PathBuf, bool)>; 2] = [None, None];
+                for (key_slot, (map, srgb)) in keys.iter_mut().zip([(&material.diffuse_map, true), (&material.normal_map, false)]) {
+                    let Some(map) = map else { continue };
+                    let key = (project_root.join(mtl_dir).join(map), srgb);
+                    if !image_views.contains_key(&key) {
+                        match image::open(&key.0) {
+                            Ok(img) => {
+                                let img = img.to_rgba8();
+                                let (width, height) = img.dimensions();
+                                let view = crate::texture_cache::create_texture_view_from_rgba(
+                                    device, queue, &img, width, height, srgb, &format!("OBJ Texture: {}", map),
+                                );
+                                image_views.insert(key.clone(), view);
+                            }
+                            Err(e) => {
+                                tracing::warn!("OBJ '{}': failed to load texture {:?}: {}", mesh_path, key.0, e);
+                                continue;
+                            }
+                        }
+                    }
+                    *key_slot = Some(key);
+                }
+                let view = |key: &Option<(PathBuf, bool)>| key.as_ref().and_then(|k| image_views.get(k));
+                keys.iter().any(Option::is_some).then(|| {
+                    Arc::new(tex_res.create_bind_group(device, &format!("OBJ Material: {}", material.name), MaterialTextures {
+                        albedo: view(&keys[0]),
+                        normal: view(&keys[1]),
+                        ..Default::default()
+                    }))
+                })
+            }
+            _ => None,
+        };
+        submeshes.push(SubMesh {
+            first_index,
+            index_count: group.indices.len() as u32,
+            material: found.map(|(material, _)| SubMeshMaterial::from_mtl(material)),
+            texture_bind_group,
+        });
+        first_index += group.indices.len() as u32;
+    }
+
+    tracing::info!(
+        "OBJ '{}': {} submeshes, {} verts, {} indices",
+        mesh_path,
+        submeshes.len(),
+        vertices.len(),
+        indices.len()
+    );
+
+    let physics_vertices: Vec<[f32; 3]> = geometry.positions.clone();
+    let physics_indices: Vec<[u32; 3]> = indices.chunks_exact(3)
+        .map(|c| [c[0], c[1], c[2]])
+        .collect();
+
+    let mut gpu_mesh = build_procedural_gpu_mesh(device, &vertices, &indices, &format!("OBJ: {}", mesh_path));
+    gpu_mesh.physics_vertices = Some(physics_vertices);
+    gpu_mesh.physics_indices = Some(physics_indices);
+    gpu_mesh.submeshes = submeshes;
+
+    Ok(gpu_mesh)
+}
+
+/// Load a glTF file (.gltf or .glb) and create GPU buffers.
+/// All nodes/meshes/primitives share one vertex and index buffer, with each
+/// node's world transform applied to positions, normals and tangents. Indices
+/// are grouped by glTF material into submeshes, each drawn with that
+/// material's factors and base color texture.
 fn load_gltf(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
@@ -489,94 +1209,157 @@ fn load_gltf(
     }
 
     let (document, buffers, images) = gltf::import(&full_path)?;
+    let geometry = build_gltf_geometry(&document, &buffers);
 
-    let mut all_vertices: Vec<Vertex3D> = Vec::new();
-    let mut all_indices: Vec<u32> = Vec::new();
-    let mut total_primitives = 0u32;
-
-    // Walk every node in every scene, applying world transforms
-    for scene in document.scenes() {
-        for node in scene.nodes() {
-            collect_node_meshes(
-                &node,
-                glam::Mat4::IDENTITY,
-                &buffers,
-                &mut all_vertices,
-                &mut all_indices,
-                &mut total_primitives,
-            );
-        }
-    }
-
-    if all_vertices.is_empty() {
+    if geometry.vertices.is_empty() {
         return Err(MeshError::NoMeshes);
     }
 
     tracing::info!(
-        "glTF '{}': merged {} primitives, {} verts, {} indices",
+        "glTF '{}': {} primitives in {} submeshes, {} verts, {} indices",
         mesh_path,
-        total_primitives,
-        all_vertices.len(),
-        all_indices.len()
+        geometry.primitive_count,
+        geometry.submeshes.len(),
+        geometry.vertices.len(),
+        geometry.indices.len()
     );
 
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some(&format!("Mesh VB: {}", mesh_path)),
-        contents: bytemuck::cast_slice(&all_vertices),
-        usage: wgpu::BufferUsages::VERTEX,
+        contents: bytemuck::cast_slice(&geometry.vertices),
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
     });
 
     let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some(&format!("Mesh IB: {}", mesh_path)),
-        contents: bytemuck::cast_slice(&all_indices),
-        usage: wgpu::BufferUsages::INDEX,
+        contents: bytemuck::cast_slice(&geometry.indices),
+        usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_SRC,
     });
 
-    // Extract GLB texture: find the first base_color texture
-    let texture_bind_group = if let Some(tex_res) = texture_resources {
-        let mut texture_image_index: Option<usize> = None;
-        'outer: for mesh in document.meshes() {
-            for prim in mesh.primitives() {
-                if let Some(info) = prim.material().pbr_metallic_roughness().base_color_texture() {
-                    texture_image_index = Some(info.texture().source().index());
-                    break 'outer;
+    // One submesh per material, each with a bind group of its texture maps
+    // (images are uploaded once per color space and shared between materials)
+    let mut image_views: HashMap<(usize, bool), wgpu::TextureView> = HashMap::new();
+    let submeshes: Vec<SubMesh> = geometry.submeshes.iter()
+        .map(|range| {
+            let material = range.material.and_then(|i| document.materials().nth(i));
+            let texture_bind_group = match (texture_resources, &material) {
+                (Some(tex_res), Some(material)) => {
+                    let pbr = material.pbr_metallic_roughness();
+                    let sources = [
+                        (pbr.base_color_texture().map(|t| t.texture()), true),
+                        (material.normal_texture().map(|t| t.texture()), false),
+                        (pbr.metallic_roughness_texture().map(|t| t.texture()), false),
+                        (material.emissive_texture().map(|t| t.texture()), true),
+                    ];
+                    let keys = sources.map(|(texture, srgb)| {
+                        let idx = texture?.source().index();
+                        if idx >= images.len() {
+                            return None;
+                        }
+                        image_views.entry((idx, srgb)).or_insert_with(|| {
+                            tracing::info!("glTF '{}': loading texture {} ({}x{})", mesh_path, idx, images[idx].width, images[idx].height);
+                            create_gltf_texture_view(device, queue, &images[idx], srgb)
+                        });
+                        Some((idx, srgb))
+                    });
+                    let view = |key: Option<(usize, bool)>| key.and_then(|k| image_views.get(&k));
+                    keys.iter().any(Option::is_some).then(|| {
+                        Arc::new(tex_res.create_bind_group(device, "GLB Material Textures", MaterialTextures {
+                            albedo: view(keys[0]),
+                            normal: view(keys[1]),
+                            metallic_roughness: view(keys[2]),
+                            emissive: view(keys[3]),
+                            ..Default::default()
+                        }))
+                    })
                 }
+                _ => None,
+            };
+            SubMesh {
+                first_index: range.first_index,
+                index_count: range.index_count,
+                material: material.as_ref().map(SubMeshMaterial::from_gltf),
+                texture_bind_group,
             }
-        }
-        if let Some(idx) = texture_image_index {
-            if idx < images.len() {
-                tracing::info!("GLB '{}': loading albedo texture ({}x{})", mesh_path, images[idx].width, images[idx].height);
-                Some(create_texture_bind_group(device, queue, &images[idx], &tex_res.bind_group_layout))
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+        })
+        .collect();
 
     // Extract skin and animation data
     let skin_data = extract_skin_data(&document, &buffers);
 
     // Keep CPU-side data for physics trimesh colliders
-    let physics_vertices: Vec<[f32; 3]> = all_vertices.iter().map(|v| v.position).collect();
-    let physics_indices: Vec<[u32; 3]> = all_indices.chunks_exact(3)
+    let physics_vertices: Vec<[f32; 3]> = geometry.vertices.iter().map(|v| v.position).collect();
+    let physics_indices: Vec<[u32; 3]> = geometry.indices.chunks_exact(3)
         .map(|c| [c[0], c[1], c[2]])
         .collect();
 
     Ok(GpuMesh {
-        vertex_buffer,
-        index_buffer,
-        index_count: all_indices.len() as u32,
-        texture_bind_group,
+        vertex_buffer: SubBuffer::dedicated(vertex_buffer),
+        index_buffer: SubBuffer::dedicated(index_buffer),
+        index_count: geometry.indices.len() as u32,
+        texture_bind_group: None,
         skin_data,
         physics_vertices: Some(physics_vertices),
         physics_indices: Some(physics_indices),
+        submeshes,
+        bounds: crate::culling::Aabb::from_points(geometry.vertices.iter().map(|v| v.position)),
     })
 }
 
+/// A run of a glTF mesh's index buffer that uses one material.
+#[derive(Debug, Clone, PartialEq)]
+struct SubMeshRange {
+    first_index: u32,
+    index_count: u32,
+    /// glTF material index (None = primitive without a material).
+    material: Option<usize>,
+}
+
+/// CPU-side geometry of a whole glTF document.
+struct GltfGeometry {
+    vertices: Vec<Vertex3D>,
+    indices: Vec<u32>,
+    submeshes: Vec<SubMeshRange>,
+    primitive_count: u32,
+}
+
+/// Walk every node in every scene and merge all primitives, grouping their
+/// indices by material so each material is one contiguous submesh.
+fn build_gltf_geometry(document: &gltf::Document, buffers: &[gltf::buffer::Data]) -> GltfGeometry {
+    let mut vertices: Vec<Vertex3D> = Vec::new();
+    let mut by_material: std::collections::BTreeMap<Option<usize>, Vec<u32>> = std::collections::BTreeMap::new();
+    let mut primitive_count = 0u32;
+
+    for scene in document.scenes() {
+        for node in scene.nodes() {
+            collect_node_meshes(
+                &node,
+                glam::Mat4::IDENTITY,
+                buffers,
+                &mut vertices,
+                &mut by_material,
+                &mut primitive_count,
+            );
+        }
+    }
+
+    let mut indices = Vec::new();
+    let mut submeshes = Vec::new();
+    for (material, group) in by_material {
+        if group.is_empty() {
+            continue;
+        }
+        submeshes.push(SubMeshRange {
+            first_index: indices.len() as u32,
+            index_count: group.len() as u32,
+            material,
+        });
+        indices.extend(group);
+    }
+
+    GltfGeometry { vertices, indices, submeshes, primitive_count }
+}
+
 /// Extract skeleton and animation clip data from a glTF document.
 fn extract_skin_data(
     document: &gltf::Document,
@@ -752,13 +1535,13 @@ fn extract_skin_data(
 }
 
 /// Recursively walk a glTF node tree, collecting mesh primitives with
-/// accumulated world transforms.
+/// accumulated world transforms. Indices go to the group of the primitive's material.
 fn collect_node_meshes(
     node: &gltf::Node,
     parent_transform: glam::Mat4,
     buffers: &[gltf::buffer::Data],
     vertices: &mut Vec<Vertex3D>,
-    indices: &mut Vec<u32>,
+    indices_by_material: &mut std::collections::BTreeMap<Option<usize>, Vec<u32>>,
     prim_count: &mut u32,
 ) {
     let local = glam::Mat4::from_cols_array_2d(&node.transform().matrix());
@@ -775,8 +1558,16 @@ fn collect_node_meshes(
                 None => continue,
             };
 
+            // UVs from the set the base color texture samples (TEXCOORD_0 by default)
+            let material = primitive.material();
+            let uv_set = material
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .map(|info| info.tex_coord())
+                .unwrap_or(0);
             let tex_coords: Vec<[f32; 2]> = reader
-                .read_tex_coords(0)
+                .read_tex_coords(uv_set)
+                .or_else(|| reader.read_tex_coords(0))
                 .map(|t| t.into_f32().collect())
                 .unwrap_or_else(|| vec![[0.0, 0.0]; positions.len()]);
 
@@ -803,6 +1594,11 @@ fn collect_node_meshes(
                     }
                 });
 
+            let tangents: Vec<[f32; 4]> = reader
+                .read_tangents()
+                .map(|t| t.collect())
+                .unwrap_or_else(|| generate_tangents(&positions, &normals, &tex_coords, &prim_indices));
+
             let base_vertex = vertices.len() as u32;
 
             // Read joint indices and weights for skinned meshes
@@ -818,27 +1614,19 @@ fn collect_node_meshes(
 
             // For skinned meshes, don't bake world transforms (skeleton handles that).
             // For non-skinned meshes, bake transforms as before.
-            let is_skinned = reader.read_joints(0).is_some();
-            // Re-read since we consumed the iterator above
-            let _ = is_skinned; // Use the flag from joints vec being non-default
             let has_joints = joints.iter().any(|j| j != &[0u32, 0, 0, 0]);
+            let (point_mat, dir_mat, norm_mat) = if has_joints {
+                // Skinned: local-space positions (skeleton transforms at runtime)
+                (local, glam::Mat3::from_mat4(local), glam::Mat3::from_mat4(local).inverse().transpose())
+            } else {
+                (world, glam::Mat3::from_mat4(world), normal_mat)
+            };
 
             for (i, pos) in positions.iter().enumerate() {
-                let (p, n) = if has_joints {
-                    // Skinned: store local-space positions (skeleton transforms at runtime)
-                    let local_p = local.transform_point3(glam::Vec3::from(*pos));
-                    let local_n = glam::Mat3::from_mat4(local)
-                        .inverse()
-                        .transpose()
-                        * glam::Vec3::from(normals.get(i).copied().unwrap_or([0.0, 1.0, 0.0]));
-                    (local_p, local_n)
-                } else {
-                    // Non-skinned: bake world transform
-                    let p = world.transform_point3(glam::Vec3::from(*pos));
-                    let n = normal_mat
-                        * glam::Vec3::from(normals.get(i).copied().unwrap_or([0.0, 1.0, 0.0]));
-                    (p, n)
-                };
+                let p = point_mat.transform_point3(glam::Vec3::from(*pos));
+                let n = norm_mat * glam::Vec3::from(normals.get(i).copied().unwrap_or([0.0, 1.0, 0.0]));
+                let t = tangents.get(i).copied().unwrap_or(DEFAULT_TANGENT);
+                let t_dir = (dir_mat * glam::Vec3::new(t[0], t[1], t[2])).normalize_or_zero();
                 vertices.push(Vertex3D {
                     position: p.to_array(),
                     normal: n.normalize_or_zero().to_array(),
@@ -846,12 +1634,14 @@ fn collect_node_meshes(
                     color: colors.get(i).copied().unwrap_or([1.0, 1.0, 1.0, 1.0]),
                     joint_indices: joints.get(i).copied().unwrap_or([0, 0, 0, 0]),
                     joint_weights: weights.get(i).copied().unwrap_or([1.0, 0.0, 0.0, 0.0]),
+                    tangent: [t_dir.x, t_dir.y, t_dir.z, if t[3] < 0.0 { -1.0 } else { 1.0 }],
                 });
             }
 
-            for idx in &prim_indices {
-                indices.push(base_vertex + idx);
-            }
+            indices_by_material
+                .entry(material.index())
+                .or_default()
+                .extend(prim_indices.iter().map(|idx| base_vertex + idx));
 
             *prim_count += 1;
         }
@@ -859,7 +1649,65 @@ fn collect_node_meshes(
 
     // Recurse into children
     for child in node.children() {
-        collect_node_meshes(&child, world, buffers, vertices, indices, prim_count);
+        collect_node_meshes(&child, world, buffers, vertices, indices_by_material, prim_count);
+    }
+}
+
+/// Generate per-vertex tangents from UV gradients, accumulated per triangle
+/// and orthogonalized against the normal. `w` is the bitangent sign.
+fn generate_tangents(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    uvs: &[[f32; 2]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    let mut tan = vec![glam::Vec3::ZERO; positions.len()];
+    let mut bitan = vec![glam::Vec3::ZERO; positions.len()];
+
+    for tri in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+        if i0 >= positions.len() || i1 >= positions.len() || i2 >= positions.len() {
+            continue;
+        }
+        let uv = |i: usize| glam::Vec2::from(uvs.get(i).copied().unwrap_or([0.0, 0.0]));
+        let e1 = glam::Vec3::from(positions[i1]) - glam::Vec3::from(positions[i0]);
+        let e2 = glam::Vec3::from(positions[i2]) - glam::Vec3::from(positions[i0]);
+        let d1 = uv(i1) - uv(i0);
+        let d2 = uv(i2) - uv(i0);
+        let det = d1.x * d2.y - d2.x * d1.y;
+        if det.abs() < 1e-12 {
+            continue;
+        }
+        let r = 1.0 / det;
+        let t = (e1 * d2.y - e2 * d1.y) * r;
+        let b = (e2 * d1.x - e1 * d2.x) * r;
+        for i in [i0, i1, i2] {
+            tan[i] += t;
+            bitan[i] += b;
+        }
+    }
+
+    (0..positions.len())
+        .map(|i| {
+            let n = glam::Vec3::from(normals.get(i).copied().unwrap_or([0.0, 1.0, 0.0]));
+            // Gram-Schmidt against the normal
+            let t = (tan[i] - n * n.dot(tan[i])).normalize_or_zero();
+            if t == glam::Vec3::ZERO {
+                return DEFAULT_TANGENT;
+            }
+            let w = if n.cross(t).dot(bitan[i]) < 0.0 { -1.0 } else { 1.0 };
+            [t.x, t.y, t.z, w]
+        })
+        .collect()
+}
+
+/// Replace placeholder tangents with ones derived from the vertex UVs.
+fn fill_tangents(vertices: &mut [Vertex3D], indices: &[u32]) {
+    let positions: Vec<[f32; 3]> = vertices.iter().map(|v| v.position).collect();
+    let normals: Vec<[f32; 3]> = vertices.iter().map(|v| v.normal).collect();
+    let uvs: Vec<[f32; 2]> = vertices.iter().map(|v| v.tex_coords).collect();
+    for (vertex, tangent) in vertices.iter_mut().zip(generate_tangents(&positions, &normals, &uvs, indices)) {
+        vertex.tangent = tangent;
     }
 }
 
@@ -917,7 +1765,7 @@ fn generate_flat_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
 }
 
 /// Create a procedural UV sphere.
-fn create_procedural_sphere(device: &wgpu::Device, radius: f32, rings: u32, sectors: u32) -> GpuMesh {
+fn sphere_geometry(radius: f32, rings: u32, sectors: u32) -> (Vec<Vertex3D>, Vec<u32>) {
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
 
@@ -942,6 +1790,8 @@ fn create_procedural_sphere(device: &wgpu::Device, radius: f32, rings: u32, sect
                 color: [1.0, 1.0, 1.0, 1.0],
                 joint_indices: [0, 0, 0, 0],
                 joint_weights: [1.0, 0.0, 0.0, 0.0],
+                // d(position)/du along the sector direction; bitangent follows +v
+                tangent: [-sin_phi, 0.0, cos_phi, 1.0],
             });
         }
     }
@@ -962,65 +1812,52 @@ fn create_procedural_sphere(device: &wgpu::Device, radius: f32, rings: u32, sect
         }
     }
 
-    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Procedural Sphere VB"),
-        contents: bytemuck::cast_slice(&vertices),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
-
-    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Procedural Sphere IB"),
-        contents: bytemuck::cast_slice(&indices),
-        usage: wgpu::BufferUsages::INDEX,
-    });
-
-    GpuMesh {
-        vertex_buffer,
-        index_buffer,
-        index_count: indices.len() as u32,
-        texture_bind_group: None,
-        skin_data: None,
-        physics_vertices: None,
-        physics_indices: None,
-    }
+    (vertices, indices)
 }
 
 /// Create a procedural unit cube as fallback.
 fn create_procedural_cube(device: &wgpu::Device) -> GpuMesh {
+    let (vertices, indices) = cube_geometry();
+    build_procedural_gpu_mesh(device, &vertices, &indices, "Procedural Cube")
+}
+
+/// Unit cube, four vertices per face.
+fn cube_geometry() -> (Vec<Vertex3D>, Vec<u32>) {
     let j = [0u32, 0, 0, 0];
     let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
     #[rustfmt::skip]
-    let vertices: Vec<Vertex3D> = vec![
+    let mut vertices: Vec<Vertex3D> = vec![
         // Front face (z = 0.5)
-        Vertex3D { position: [-0.5, -0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5, -0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5,  0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5,  0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
+        Vertex3D { position: [-0.5, -0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5, -0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5,  0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5,  0.5,  0.5], normal: [ 0.0,  0.0,  1.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
         // Back face (z = -0.5)
-        Vertex3D { position: [ 0.5, -0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5, -0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5,  0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5,  0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
+        Vertex3D { position: [ 0.5, -0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5, -0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5,  0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5,  0.5, -0.5], normal: [ 0.0,  0.0, -1.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
         // Top face (y = 0.5)
-        Vertex3D { position: [-0.5,  0.5,  0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5,  0.5,  0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5,  0.5, -0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5,  0.5, -0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
+        Vertex3D { position: [-0.5,  0.5,  0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5,  0.5,  0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5,  0.5, -0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5,  0.5, -0.5], normal: [ 0.0,  1.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
         // Bottom face (y = -0.5)
-        Vertex3D { position: [-0.5, -0.5, -0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5, -0.5, -0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5, -0.5,  0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5, -0.5,  0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
+        Vertex3D { position: [-0.5, -0.5, -0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5, -0.5, -0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5, -0.5,  0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5, -0.5,  0.5], normal: [ 0.0, -1.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
         // Right face (x = 0.5)
-        Vertex3D { position: [ 0.5, -0.5,  0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5, -0.5, -0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5,  0.5, -0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [ 0.5,  0.5,  0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
+        Vertex3D { position: [ 0.5, -0.5,  0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5, -0.5, -0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5,  0.5, -0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [ 0.5,  0.5,  0.5], normal: [ 1.0,  0.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
         // Left face (x = -0.5)
-        Vertex3D { position: [-0.5, -0.5, -0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5, -0.5,  0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5,  0.5,  0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
-        Vertex3D { position: [-0.5,  0.5, -0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w },
+        Vertex3D { position: [-0.5, -0.5, -0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [0.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5, -0.5,  0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [1.0, 1.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5,  0.5,  0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [1.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
+        Vertex3D { position: [-0.5,  0.5, -0.5], normal: [-1.0,  0.0,  0.0], tex_coords: [0.0, 0.0], color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t },
     ];
 
     #[rustfmt::skip]
@@ -1033,13 +1870,15 @@ fn create_procedural_cube(device: &wgpu::Device) -> GpuMesh {
         20, 22, 21, 20, 23, 22,  // left
     ];
 
-    build_procedural_gpu_mesh(device, &vertices, &indices, "Procedural Cube")
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
 }
 
 /// Create a procedural XZ plane centered at the origin.
-fn create_procedural_plane(device: &wgpu::Device, width: f32, depth: f32, subdivisions: u32) -> GpuMesh {
+fn plane_geometry(width: f32, depth: f32, subdivisions: u32) -> (Vec<Vertex3D>, Vec<u32>) {
     let j = [0u32, 0, 0, 0];
     let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
     let segs = subdivisions + 1;
     let hw = width / 2.0;
     let hd = depth / 2.0;
@@ -1058,6 +1897,7 @@ fn create_procedural_plane(device: &wgpu::Device, width: f32, depth: f32, subdiv
                 color: [1.0, 1.0, 1.0, 1.0],
                 joint_indices: j,
                 joint_weights: w,
+                tangent: t,
             });
         }
     }
@@ -1072,13 +1912,59 @@ fn create_procedural_plane(device: &wgpu::Device, width: f32, depth: f32, subdiv
         }
     }
 
-    build_procedural_gpu_mesh(device, &vertices, &indices, "Procedural Plane")
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
+}
+
+/// Create a single grass blade: a strip from the origin up to `height`,
+/// tapering from `width` to a point and curving toward +Z. Meant to be drawn
+/// two-sided.
+fn grass_blade_geometry(width: f32, height: f32, segments: u32) -> (Vec<Vertex3D>, Vec<u32>) {
+    let j = [0u32, 0, 0, 0];
+    let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
+    let bend = 0.15 * height;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for i in 0..=segments {
+        let v = i as f32 / segments as f32;
+        let half = 0.5 * width * (1.0 - v);
+        let y = v * height;
+        let z = bend * v * v;
+        // Normal of the curve (0, y, z(y)) in the YZ plane, facing +Z
+        let normal = glam::Vec3::new(0.0, -2.0 * bend * v / height, 1.0).normalize();
+        for (side, u) in [(-1.0f32, 0.0f32), (1.0, 1.0)] {
+            vertices.push(Vertex3D {
+                position: [side * half, y, z],
+                normal: normal.into(),
+                tex_coords: [u, 1.0 - v],
+                color: [1.0, 1.0, 1.0, 1.0],
+                joint_indices: j,
+                joint_weights: w,
+                tangent: t,
+            });
+        }
+    }
+
+    for i in 0..segments {
+        let bl = i * 2;
+        let br = bl + 1;
+        let tl = bl + 2;
+        let tr = bl + 3;
+        indices.extend_from_slice(&[bl, br, tr, bl, tr, tl]);
+    }
+
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
 }
 
 /// Create a procedural Y-axis aligned cylinder with caps.
-fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, segments: u32) -> GpuMesh {
+fn cylinder_geometry(radius: f32, height: f32, segments: u32) -> (Vec<Vertex3D>, Vec<u32>) {
     let j = [0u32, 0, 0, 0];
     let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
     let half_h = height / 2.0;
 
     let mut vertices = Vec::new();
@@ -1096,7 +1982,7 @@ fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, s
             normal: [cos_a, 0.0, sin_a],
             tex_coords: [u, 1.0],
             color: [1.0, 1.0, 1.0, 1.0],
-            joint_indices: j, joint_weights: w,
+            joint_indices: j, joint_weights: w, tangent: t,
         });
         // Top ring
         vertices.push(Vertex3D {
@@ -1104,7 +1990,7 @@ fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, s
             normal: [cos_a, 0.0, sin_a],
             tex_coords: [u, 0.0],
             color: [1.0, 1.0, 1.0, 1.0],
-            joint_indices: j, joint_weights: w,
+            joint_indices: j, joint_weights: w, tangent: t,
         });
     }
 
@@ -1122,7 +2008,7 @@ fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, s
     vertices.push(Vertex3D {
         position: [0.0, half_h, 0.0], normal: [0.0, 1.0, 0.0],
         tex_coords: [0.5, 0.5], color: [1.0, 1.0, 1.0, 1.0],
-        joint_indices: j, joint_weights: w,
+        joint_indices: j, joint_weights: w, tangent: t,
     });
     for i in 0..=segments {
         let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
@@ -1130,7 +2016,7 @@ fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, s
         vertices.push(Vertex3D {
             position: [radius * cos_a, half_h, radius * sin_a], normal: [0.0, 1.0, 0.0],
             tex_coords: [0.5 + 0.5 * cos_a, 0.5 + 0.5 * sin_a],
-            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w,
+            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t,
         });
     }
     for i in 0..segments {
@@ -1142,7 +2028,7 @@ fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, s
     vertices.push(Vertex3D {
         position: [0.0, -half_h, 0.0], normal: [0.0, -1.0, 0.0],
         tex_coords: [0.5, 0.5], color: [1.0, 1.0, 1.0, 1.0],
-        joint_indices: j, joint_weights: w,
+        joint_indices: j, joint_weights: w, tangent: t,
     });
     for i in 0..=segments {
         let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
@@ -1150,20 +2036,22 @@ fn create_procedural_cylinder(device: &wgpu::Device, radius: f32, height: f32, s
         vertices.push(Vertex3D {
             position: [radius * cos_a, -half_h, radius * sin_a], normal: [0.0, -1.0, 0.0],
             tex_coords: [0.5 + 0.5 * cos_a, 0.5 - 0.5 * sin_a],
-            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w,
+            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t,
         });
     }
     for i in 0..segments {
         indices.extend_from_slice(&[bot_center, bot_center + 2 + i, bot_center + 1 + i]);
     }
 
-    build_procedural_gpu_mesh(device, &vertices, &indices, "Procedural Cylinder")
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
 }
 
 /// Create a procedural Y-axis cone with a base cap.
-fn create_procedural_cone(device: &wgpu::Device, radius: f32, height: f32, segments: u32) -> GpuMesh {
+fn cone_geometry(radius: f32, height: f32, segments: u32) -> (Vec<Vertex3D>, Vec<u32>) {
     let j = [0u32, 0, 0, 0];
     let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
     let half_h = height / 2.0;
     let slope = radius / height;
 
@@ -1175,7 +2063,7 @@ fn create_procedural_cone(device: &wgpu::Device, radius: f32, height: f32, segme
     vertices.push(Vertex3D {
         position: [0.0, half_h, 0.0], normal: [0.0, 1.0, 0.0],
         tex_coords: [0.5, 0.0], color: [1.0, 1.0, 1.0, 1.0],
-        joint_indices: j, joint_weights: w,
+        joint_indices: j, joint_weights: w, tangent: t,
     });
 
     // Base ring vertices for sides
@@ -1188,7 +2076,7 @@ fn create_procedural_cone(device: &wgpu::Device, radius: f32, height: f32, segme
             position: [radius * cos_a, -half_h, radius * sin_a],
             normal: [cos_a / len, ny / len, sin_a / len],
             tex_coords: [i as f32 / segments as f32, 1.0],
-            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w,
+            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t,
         });
     }
 
@@ -1202,7 +2090,7 @@ fn create_procedural_cone(device: &wgpu::Device, radius: f32, height: f32, segme
     vertices.push(Vertex3D {
         position: [0.0, -half_h, 0.0], normal: [0.0, -1.0, 0.0],
         tex_coords: [0.5, 0.5], color: [1.0, 1.0, 1.0, 1.0],
-        joint_indices: j, joint_weights: w,
+        joint_indices: j, joint_weights: w, tangent: t,
     });
     for i in 0..=segments {
         let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
@@ -1210,26 +2098,27 @@ fn create_procedural_cone(device: &wgpu::Device, radius: f32, height: f32, segme
         vertices.push(Vertex3D {
             position: [radius * cos_a, -half_h, radius * sin_a], normal: [0.0, -1.0, 0.0],
             tex_coords: [0.5 + 0.5 * cos_a, 0.5 - 0.5 * sin_a],
-            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w,
+            color: [1.0, 1.0, 1.0, 1.0], joint_indices: j, joint_weights: w, tangent: t,
         });
     }
     for i in 0..segments {
         indices.extend_from_slice(&[bot_center, bot_center + 2 + i, bot_center + 1 + i]);
     }
 
-    build_procedural_gpu_mesh(device, &vertices, &indices, "Procedural Cone")
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
 }
 
 /// Create a procedural torus (donut) centered at the origin in the XZ plane.
-fn create_procedural_torus(
-    device: &wgpu::Device,
+fn torus_geometry(
     major_radius: f32,
     minor_radius: f32,
     major_segments: u32,
     minor_segments: u32,
-) -> GpuMesh {
+) -> (Vec<Vertex3D>, Vec<u32>) {
     let j = [0u32, 0, 0, 0];
     let w = [1.0f32, 0.0, 0.0, 0.0];
+    let t = DEFAULT_TANGENT;
     let pi2 = 2.0 * std::f32::consts::PI;
 
     let mut vertices = Vec::new();
@@ -1252,7 +2141,7 @@ fn create_procedural_torus(
                 normal: [cos_p * cos_t, sin_p, cos_p * sin_t],
                 tex_coords: [i as f32 / major_segments as f32, k as f32 / minor_segments as f32],
                 color: [1.0, 1.0, 1.0, 1.0],
-                joint_indices: j, joint_weights: w,
+                joint_indices: j, joint_weights: w, tangent: t,
             });
         }
     }
@@ -1268,7 +2157,196 @@ fn create_procedural_torus(
         }
     }
 
-    build_procedural_gpu_mesh(device, &vertices, &indices, "Procedural Torus")
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
+}
+
+/// Vertex of a procedural shape; tangents are filled in afterwards.
+fn procedural_vertex(position: glam::Vec3, normal: glam::Vec3, uv: [f32; 2]) -> Vertex3D {
+    Vertex3D {
+        position: position.to_array(),
+        normal: normal.to_array(),
+        tex_coords: uv,
+        color: [1.0, 1.0, 1.0, 1.0],
+        joint_indices: [0, 0, 0, 0],
+        joint_weights: [1.0, 0.0, 0.0, 0.0],
+        tangent: DEFAULT_TANGENT,
+    }
+}
+
+/// Capsule along Y: a cylinder of `height - 2 * radius` between two
+/// hemispheres, built as latitude rows like the UV sphere.
+fn capsule_geometry(radius: f32, height: f32, segments: u32, rings: u32) -> (Vec<Vertex3D>, Vec<u32>) {
+    let half_length = (height / 2.0 - radius).max(0.0);
+    let total = 2.0 * (half_length + radius);
+    let mut vertices = Vec::new();
+    // Top cap from the pole to the equator, then the bottom cap from the
+    // equator down; the rows between the two equators form the side
+    for cap in 0..2 {
+        let center_y = if cap == 0 { half_length } else { -half_length };
+        for ring in 0..=rings {
+            let theta = std::f32::consts::FRAC_PI_2 * (ring as f32 / rings as f32 + cap as f32);
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            let y = center_y + radius * cos_theta;
+            for sector in 0..=segments {
+                let phi = std::f32::consts::TAU * sector as f32 / segments as f32;
+                let normal = glam::Vec3::new(sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin());
+                let position = glam::Vec3::new(radius * normal.x, y, radius * normal.z);
+                let uv = [sector as f32 / segments as f32, (half_length + radius - y) / total];
+                vertices.push(procedural_vertex(position, normal, uv));
+            }
+        }
+    }
+
+    let mut indices = Vec::new();
+    let rows = 2 * (rings + 1);
+    for row in 0..rows - 1 {
+        let curr_row = row * (segments + 1);
+        let next_row = (row + 1) * (segments + 1);
+        for sector in 0..segments {
+            indices.extend_from_slice(&[
+                curr_row + sector, next_row + sector + 1, next_row + sector,
+                curr_row + sector, curr_row + sector + 1, next_row + sector + 1,
+            ]);
+        }
+    }
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
+}
+
+/// Icosahedron with each triangle split into four `subdivisions` times,
+/// projected onto the sphere.
+fn icosphere_geometry(radius: f32, subdivisions: u32) -> (Vec<Vertex3D>, Vec<u32>) {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut points: Vec<glam::Vec3> = [
+        [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+        [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+        [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|p| glam::Vec3::from(*p).normalize())
+    .collect();
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edge midpoints are shared by the two faces of the edge
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push(((points[a as usize] + points[b as usize]) / 2.0).normalize());
+                points.len() as u32 - 1
+            })
+        };
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut vertices: Vec<Vertex3D> = points
+        .iter()
+        .map(|&n| {
+            let uv = [0.5 + n.z.atan2(n.x) / std::f32::consts::TAU, n.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI];
+            procedural_vertex(n * radius, n, uv)
+        })
+        .collect();
+    let indices: Vec<u32> = faces.into_iter().flatten().collect();
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
+}
+
+/// Box of `size` with rounded edges: each face is a grid whose outer
+/// `segments` rows wrap around a quarter circle of `radius`.
+fn rounded_box_geometry(size: [f32; 3], radius: f32, segments: u32) -> (Vec<Vertex3D>, Vec<u32>) {
+    let half = glam::Vec3::from(size) / 2.0;
+    let radius = radius.min(half.min_element());
+    let inner = half - glam::Vec3::splat(radius);
+    // Grid coordinates along an axis: the rounded band at each end, and the
+    // flat part between them (none when the radius takes the whole side)
+    let stops = |extent: f32| {
+        let mut stops: Vec<f32> = Vec::new();
+        let ends = (0..=segments).map(|i| -extent + radius * i as f32 / segments as f32)
+            .chain((0..=segments).map(|i| extent - radius + radius * i as f32 / segments as f32));
+        for stop in ends {
+            if stops.last().is_none_or(|&last| stop - last > 1e-6) {
+                stops.push(stop);
+            }
+        }
+        stops
+    };
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for axis in 0..3 {
+        for sign in [1.0f32, -1.0] {
+            // u x v points out of the face
+            let (mut u, mut v) = ((axis + 1) % 3, (axis + 2) % 3);
+            if sign < 0.0 {
+                std::mem::swap(&mut u, &mut v);
+            }
+            let mut face_normal = glam::Vec3::ZERO;
+            face_normal[axis] = sign;
+            let (stops_u, stops_v) = (stops(half[u]), stops(half[v]));
+            let base = vertices.len() as u32;
+            for &sv in &stops_v {
+                for &su in &stops_u {
+                    let mut p = face_normal * half[axis];
+                    p[u] = su;
+                    p[v] = sv;
+                    let center = p.clamp(-inner, inner);
+                    let normal = (p - center).normalize_or(face_normal);
+                    let uv = [(su + half[u]) / (2.0 * half[u]), 1.0 - (sv + half[v]) / (2.0 * half[v])];
+                    vertices.push(procedural_vertex(center + normal * radius, normal, uv));
+                }
+            }
+            let row = stops_u.len() as u32;
+            for k in 0..stops_v.len() as u32 - 1 {
+                for i in 0..row - 1 {
+                    let a = base + k * row + i;
+                    let c = a + row;
+                    indices.extend_from_slice(&[a, a + 1, c + 1, a, c + 1, c]);
+                }
+            }
+        }
+    }
+    fill_tangents(&mut vertices, &indices);
+    (vertices, indices)
+}
+
+/// Write `contents` over `current` when it fits there, otherwise into a new
+/// range of the arena (the old one is freed once nothing draws from it).
+fn rewrite(
+    arenas: &BufferArenas,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    class: BufferClass,
+    current: &SubBuffer,
+    contents: &[u8],
+) -> SubBuffer {
+    // Empty writes aren't allowed; an empty mesh just draws nothing
+    if contents.is_empty() {
+        return current.clone();
+    }
+    let size = (contents.len() as u64).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+    if size > current.size() {
+        return arenas.upload(device, queue, class, contents);
+    }
+    if size == contents.len() as u64 {
+        queue.write_buffer(current.buffer(), current.offset(), contents);
+    } else {
+        let mut padded = contents.to_vec();
+        padded.resize(size as usize, 0);
+        queue.write_buffer(current.buffer(), current.offset(), &padded);
+    }
+    current.clone()
 }
 
 /// Helper to build a GpuMesh from vertex and index data.
@@ -1281,22 +2359,190 @@ fn build_procedural_gpu_mesh(
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some(&format!("{} VB", label)),
         contents: bytemuck::cast_slice(vertices),
-        usage: wgpu::BufferUsages::VERTEX,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
     });
 
     let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some(&format!("{} IB", label)),
         contents: bytemuck::cast_slice(indices),
-        usage: wgpu::BufferUsages::INDEX,
+        usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_SRC,
     });
 
     GpuMesh {
-        vertex_buffer,
-        index_buffer,
+        vertex_buffer: SubBuffer::dedicated(vertex_buffer),
+        index_buffer: SubBuffer::dedicated(index_buffer),
         index_count: indices.len() as u32,
         texture_bind_group: None,
         skin_data: None,
         physics_vertices: None,
         physics_indices: None,
+        submeshes: Vec::new(),
+        bounds: crate::culling::Aabb::from_points(vertices.iter().map(|v| v.position)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two triangles in one mesh: the first (with UVs) uses material 1, the
+    /// second uses material 0.
+    const TWO_MATERIAL_GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "materials": [
+            { "name": "red", "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "roughnessFactor": 0.25 } },
+            { "name": "blue", "pbrMetallicRoughness": { "baseColorFactor": [0, 0, 1, 1], "metallicFactor": 0 } }
+        ],
+        "meshes": [{ "primitives": [
+            { "attributes": { "POSITION": 0, "TEXCOORD_0": 1 }, "material": 1 },
+            { "attributes": { "POSITION": 2 }, "material": 0 }
+        ] }],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] },
+            { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" },
+            { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 1], "max": [1, 1, 1] }
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 60, "byteLength": 36 }
+        ],
+        "buffers": [{ "byteLength": 96, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AACAPwAAAAAAAIA/AAAAAAAAgD8AAIA/" }]
+    }"#;
+
+    #[test]
+    fn test_gltf_primitives_are_split_by_material() {
+        let (document, buffers, _) = gltf::import_slice(TWO_MATERIAL_GLTF.as_bytes()).unwrap();
+        let geometry = build_gltf_geometry(&document, &buffers);
+
+        assert_eq!(geometry.primitive_count, 2);
+        assert_eq!(geometry.vertices.len(), 6);
+        assert_eq!(
+            geometry.submeshes,
+            vec![
+                SubMeshRange { first_index: 0, index_count: 3, material: Some(0) },
+                SubMeshRange { first_index: 3, index_count: 3, material: Some(1) },
+            ]
+        );
+        // Material 0's triangle is the second primitive (vertices 3..6)
+        assert_eq!(&geometry.indices[..3], &[3, 4, 5]);
+
+        let red = SubMeshMaterial::from_gltf(&document.materials().next().unwrap());
+        assert_eq!(red.name.as_deref(), Some("red"));
+        assert_eq!(red.base_color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(red.roughness, 0.25);
+        assert_eq!(red.metallic, 1.0);
+    }
+
+    #[test]
+    fn test_gltf_normals_and_tangents_are_generated() {
+        let (document, buffers, _) = gltf::import_slice(TWO_MATERIAL_GLTF.as_bytes()).unwrap();
+        let geometry = build_gltf_geometry(&document, &buffers);
+
+        // The UV-mapped triangle faces +Z with U running along +X
+        let v = &geometry.vertices[0];
+        assert!((glam::Vec3::from(v.normal) - glam::Vec3::Z).length() < 1e-5);
+        assert!((glam::Vec3::from_slice(&v.tangent[..3]) - glam::Vec3::X).length() < 1e-5);
+        assert_eq!(v.tangent[3], 1.0);
+        assert_eq!(geometry.vertices[1].tex_coords, [1.0, 0.0]);
+
+        // Without UVs there is no gradient to follow: fall back to the default
+        assert_eq!(geometry.vertices[3].tangent, DEFAULT_TANGENT);
+    }
+
+    #[test]
+    fn test_fill_tangents_follows_uv_handedness() {
+        // XZ plane facing +Y with V running along +Z, like the procedural plane
+        let vertex = |x: f32, z: f32| Vertex3D {
+            position: [x, 0.0, z],
+            normal: [0.0, 1.0, 0.0],
+            tex_coords: [x, z],
+            color: [1.0; 4],
+            joint_indices: [0; 4],
+            joint_weights: [1.0, 0.0, 0.0, 0.0],
+            tangent: DEFAULT_TANGENT,
+        };
+        let mut vertices = vec![vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0), vertex(1.0, 1.0)];
+        fill_tangents(&mut vertices, &[0, 3, 1, 0, 2, 3]);
+
+        for v in &vertices {
+            assert!((glam::Vec3::from_slice(&v.tangent[..3]) - glam::Vec3::X).length() < 1e-5);
+            // N x T = -Z while the bitangent follows +V (+Z): mirrored
+            assert_eq!(v.tangent[3], -1.0);
+        }
+    }
+
+    #[test]
+    fn test_runtime_mesh_data() {
+        let mut data = MeshData {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 1.0]],
+            normals: Vec::new(),
+            uvs: vec![[0.0, 0.0]],
+            indices: vec![0, 3, 1, 0, 2, 3],
+        };
+        assert_eq!(data.validate(), Ok(()));
+        let vertices = data.vertices();
+        assert_eq!(vertices.len(), 4);
+        assert!(vertices.iter().all(|v| v.normal == [0.0, 1.0, 0.0]));
+        assert_eq!(vertices[3].tex_coords, [0.0, 0.0]);
+        assert_eq!(data.triangles(), vec![[0, 3, 1], [0, 2, 3]]);
+
+        data.indices.push(4);
+        assert!(data.validate().is_err());
+        data.indices.extend([4, 4]);
+        assert_eq!(data.validate(), Err("index 4 is out of range for 4 vertices".to_string()));
+        data.indices.truncate(6);
+        data.normals = vec![[0.0, 1.0, 0.0]];
+        assert!(data.validate().is_err());
+    }
+
+    #[test]
+    fn test_primitive_specs() {
+        assert_eq!(Primitive::parse("assets/meshes/rock.glb"), None);
+        assert_eq!(Primitive::parse("procedural:cube"), Some(Ok(Primitive::Cube)));
+        assert_eq!(
+            Primitive::parse("capsule(0.5, 2.0)"),
+            Some(Ok(Primitive::Capsule { radius: 0.5, height: 2.0, segments: 32, rings: 8 }))
+        );
+        assert_eq!(
+            Primitive::parse("procedural:torus(1, 0.25, 48)"),
+            Some(Ok(Primitive::Torus { major_radius: 1.0, minor_radius: 0.25, major_segments: 48, minor_segments: 16 }))
+        );
+        assert!(matches!(Primitive::parse("procedural:blob"), Some(Err(_))));
+        assert!(matches!(Primitive::parse("icosphere(0.5, 9)"), Some(Err(e)) if e.contains("subdivisions")));
+        assert!(matches!(Primitive::parse("cone(0.5, -1)"), Some(Err(e)) if e.contains("height")));
+        assert!(matches!(Primitive::parse("cube(1)"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_new_primitives_face_outward() {
+        let shapes = [
+            ("capsule", capsule_geometry(0.5, 2.0, 16, 4)),
+            ("icosphere", icosphere_geometry(0.5, 2)),
+            ("rounded_box", rounded_box_geometry([2.0, 1.0, 0.5], 0.25, 3)),
+        ];
+        for (name, (vertices, indices)) in &shapes {
+            assert!(indices.iter().all(|&i| (i as usize) < vertices.len()), "{}", name);
+            for tri in indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|k| &vertices[tri[k] as usize]);
+                let face = (glam::Vec3::from(b.position) - glam::Vec3::from(a.position))
+                    .cross(glam::Vec3::from(c.position) - glam::Vec3::from(a.position));
+                // Zero-area triangles only at the capsule's poles
+                if face.length_squared() < 1e-12 {
+                    continue;
+                }
+                let normal = glam::Vec3::from(a.normal) + glam::Vec3::from(b.normal) + glam::Vec3::from(c.normal);
+                assert!(face.dot(normal) > 0.0, "{} has an inward triangle", name);
+            }
+        }
+        let (_, (_, icosphere_indices)) = &shapes[1];
+        assert_eq!(icosphere_indices.len(), 20 * 16 * 3);
+
+        let (_, (vertices, _)) = &shapes[0];
+        let top = vertices.iter().map(|v| v.position[1]).fold(f32::MIN, f32::max);
+        assert!((top - 1.0).abs() < 1e-5);
     }
 }
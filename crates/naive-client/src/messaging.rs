@@ -0,0 +1,271 @@
+//! Directed entity-to-entity messages with optional request/response.
+//!
+//! `msg.send(target, name, data)` queues a message for one entity's `on_message`
+//! hook instead of broadcasting on the event bus. `msg.request` additionally
+//! registers a callback that receives the handler's reply, or `nil, "timeout"`
+//! if nothing answers in time.
+
+use std::collections::HashMap;
+
+use mlua::prelude::*;
+
+use crate::scripting::{ScriptRuntime, SharedMessageQueue, SharedSceneWorld};
+
+/// Default time a request waits for a reply, in seconds.
+pub const DEFAULT_REQUEST_TIMEOUT: f32 = 1.0;
+
+/// A queued message addressed to a single entity.
+pub struct Message {
+    pub target: String,
+    pub sender: Option<String>,
+    pub name: String,
+    /// Payload as a Lua value, kept in the registry until delivery.
+    pub data: Option<LuaRegistryKey>,
+    /// Set when the sender is waiting for a reply.
+    pub request_id: Option<u64>,
+}
+
+/// A request waiting for its reply.
+pub struct PendingRequest {
+    pub sender: Option<String>,
+    pub target: String,
+    pub name: String,
+    pub callback: LuaRegistryKey,
+    /// Seconds left before the request times out.
+    pub remaining: f32,
+}
+
+/// Outcome handed to a request callback.
+pub enum Reply {
+    Value(Option<LuaRegistryKey>),
+    Error(String),
+}
+
+/// Per-frame message outbox plus the table of open requests.
+pub struct MessageQueue {
+    outbox: Vec<Message>,
+    pending: HashMap<u64, PendingRequest>,
+    resolved: Vec<(PendingRequest, Reply)>,
+    next_request_id: u64,
+}
+
+impl MessageQueue {
+    pub fn new() -> Self {
+        Self {
+            outbox: Vec::new(),
+            pending: HashMap::new(),
+            resolved: Vec::new(),
+            next_request_id: 1,
+        }
+    }
+
+    /// Queue a message for delivery on the next dispatch.
+    pub fn send(&mut self, message: Message) {
+        self.outbox.push(message);
+    }
+
+    /// Register a pending request and return its id.
+    pub fn open_request(&mut self, request: PendingRequest) -> u64 {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+        self.pending.insert(id, request);
+        id
+    }
+
+    /// Answer a pending request. Returns false if it was already answered or timed out.
+    pub fn reply(&mut self, request_id: u64, reply: Reply) -> bool {
+        match self.pending.remove(&request_id) {
+            Some(request) => {
+                self.resolved.push((request, reply));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Age pending requests by `dt` and resolve the ones that ran out of time.
+    pub fn expire(&mut self, dt: f32) {
+        let mut expired = Vec::new();
+        for (id, request) in self.pending.iter_mut() {
+            request.remaining -= dt;
+            if request.remaining <= 0.0 {
+                expired.push(*id);
+            }
+        }
+        for id in expired {
+            self.reply(id, Reply::Error("timeout".to_string()));
+        }
+    }
+
+    pub fn take_outbox(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    pub fn take_resolved(&mut self) -> Vec<(PendingRequest, Reply)> {
+        std::mem::take(&mut self.resolved)
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Drop all queued messages and open requests (scene change).
+    pub fn clear(&mut self) {
+        self.outbox.clear();
+        self.pending.clear();
+        self.resolved.clear();
+    }
+}
+
+impl Default for MessageQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deliver queued messages to `on_message` hooks, then run callbacks for
+/// requests that were answered or timed out. Messages sent from inside a
+/// handler are delivered on the next dispatch.
+pub fn dispatch_messages(
+    runtime: &ScriptRuntime,
+    queue: &SharedMessageQueue,
+    scene_world: &SharedSceneWorld,
+    dt: f32,
+) {
+    let outbox = queue.borrow_mut().take_outbox();
+    for message in outbox {
+        let entity = scene_world.borrow().entity_registry.get(&message.target).copied();
+        let result = match entity {
+            Some(entity) => runtime.call_on_message(entity, &message),
+            None => Err(format!("no entity with id '{}'", message.target)),
+        };
+
+        let request_id = match message.request_id {
+            Some(id) => id,
+            None => {
+                if let Err(e) = result {
+                    tracing::warn!("Message '{}' to '{}' dropped: {}", message.name, message.target, e);
+                }
+                continue;
+            }
+        };
+        let reply = match result {
+            // Handler may answer later with msg.reply(request_id, data)
+            Ok(LuaValue::Nil) => continue,
+            Ok(value) => match runtime.lua.create_registry_value(value) {
+                Ok(key) => Reply::Value(Some(key)),
+                Err(e) => Reply::Error(e.to_string()),
+            },
+            Err(e) => Reply::Error(e),
+        };
+        queue.borrow_mut().reply(request_id, reply);
+    }
+
+    queue.borrow_mut().expire(dt);
+    let resolved = queue.borrow_mut().take_resolved();
+    for (request, reply) in resolved {
+        runtime.call_request_callback(&request, reply);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(lua: &Lua, timeout: f32) -> PendingRequest {
+        let callback = lua.create_function(|_, ()| Ok(())).unwrap();
+        PendingRequest {
+            sender: Some("a".to_string()),
+            target: "b".to_string(),
+            name: "ping".to_string(),
+            callback: lua.create_registry_value(callback).unwrap(),
+            remaining: timeout,
+        }
+    }
+
+    #[test]
+    fn test_request_times_out() {
+        let lua = Lua::new();
+        let mut queue = MessageQueue::new();
+        let id = queue.open_request(pending(&lua, 0.5));
+
+        queue.expire(0.3);
+        assert!(queue.take_resolved().is_empty());
+        queue.expire(0.3);
+        let resolved = queue.take_resolved();
+        assert_eq!(resolved.len(), 1);
+        assert!(matches!(&resolved[0].1, Reply::Error(e) if e == "timeout"));
+
+        // Late replies are ignored
+        assert!(!queue.reply(id, Reply::Value(None)));
+        assert_eq!(queue.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let mut runtime = ScriptRuntime::new();
+        runtime.register_api().unwrap();
+        let queue: SharedMessageQueue = std::rc::Rc::new(std::cell::RefCell::new(MessageQueue::new()));
+        runtime.register_message_api(queue.clone()).unwrap();
+
+        let dir = std::env::temp_dir().join("naive_test_messaging");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("door.lua"), r#"
+            function on_message(name, data, from)
+                if name == "open" then
+                    return { opened = data.key == "gold", by = from }
+                end
+            end
+        "#).unwrap();
+        std::fs::write(dir.join("player.lua"), r#"
+            function init()
+                msg.request("door_1", "open", { key = "gold" }, function(reply, err)
+                    self.opened = reply and reply.opened
+                    self.by = reply and reply.by
+                    self.err = err
+                end)
+                msg.request("door_1", "knock", nil, function(reply, err)
+                    self.knock_err = err
+                end, 0.01)
+            end
+        "#).unwrap();
+
+        let scene_world: SharedSceneWorld = std::rc::Rc::new(std::cell::RefCell::new(crate::world::SceneWorld::new()));
+        let (door, player) = {
+            let mut sw = scene_world.borrow_mut();
+            let door = sw.world.spawn(());
+            let player = sw.world.spawn(());
+            sw.entity_registry.insert("door_1".to_string(), door);
+            sw.entity_registry.insert("player".to_string(), player);
+            (door, player)
+        };
+        runtime.load_script(door, &dir, std::path::Path::new("door.lua")).unwrap();
+        runtime.load_script(player, &dir, std::path::Path::new("player.lua")).unwrap();
+        runtime.set_entity_string_id(door, "door_1").unwrap();
+        runtime.set_entity_string_id(player, "player").unwrap();
+        runtime.call_init(player);
+
+        dispatch_messages(&runtime, &queue, &scene_world, 0.016);
+
+        let env: LuaTable = runtime.lua.registry_value(runtime.entity_envs.get(&player).unwrap()).unwrap();
+        let state: LuaTable = env.get("self").unwrap();
+        assert!(state.get::<bool>("opened").unwrap());
+        assert_eq!(state.get::<String>("by").unwrap(), "player");
+        assert!(state.get::<Option<String>>("err").unwrap().is_none());
+        assert_eq!(state.get::<String>("knock_err").unwrap(), "timeout");
+        assert_eq!(queue.borrow().pending_count(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reply_resolves_once() {
+        let lua = Lua::new();
+        let mut queue = MessageQueue::new();
+        let id = queue.open_request(pending(&lua, 1.0));
+
+        assert!(queue.reply(id, Reply::Value(None)));
+        assert!(!queue.reply(id, Reply::Value(None)));
+        assert_eq!(queue.take_resolved().len(), 1);
+    }
+}
@@ -0,0 +1,301 @@
+//! Mods: folders under `mods/` laid over the project directory.
+//!
+//! Each `mods/<id>/` mirrors the project layout. A file there replaces the
+//! project file at the same relative path, and new files are simply added,
+//! so a mod can change a scene, a script or a material, or bring its own.
+//! Enabled mods are mounted at startup, highest `priority` first (ties by
+//! id); when several mounted mods ship the same file the first one wins and
+//! the clash is reported. Enabling or disabling a mod is saved to
+//! `mods/mods.yaml` and takes effect on the next launch.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Directory holding the mods, relative to the project root.
+pub const MODS_DIR: &str = "mods";
+/// Which mods are switched off, relative to the project root.
+pub const MODS_STATE_FILE: &str = "mods/mods.yaml";
+/// Optional per-mod manifest inside its folder.
+pub const MOD_MANIFEST: &str = "mod.yaml";
+
+/// `mod.yaml`; every field is optional.
+#[derive(Debug, Default, Deserialize)]
+struct ModManifest {
+    name: Option<String>,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    description: String,
+    /// Higher priorities are mounted first and win clashes.
+    #[serde(default)]
+    priority: i32,
+}
+
+/// `mods/mods.yaml`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ModsState {
+    #[serde(default)]
+    disabled: Vec<String>,
+}
+
+/// A mod found under `mods/`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModInfo {
+    /// Folder name.
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub priority: i32,
+    /// Saved choice, applied on the next launch.
+    pub enabled: bool,
+    /// Whether its files are laid over the project in this session.
+    pub mounted: bool,
+    pub path: PathBuf,
+}
+
+/// A file shipped by more than one mounted mod.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModConflict {
+    /// Project-relative path, with forward slashes.
+    pub path: String,
+    /// The mod whose file is used.
+    pub winner: String,
+    /// Mods whose copy is ignored, in mount order.
+    pub shadowed: Vec<String>,
+}
+
+/// Mounted mod folders, in the order files are looked up. Cheap to clone
+/// into the caches that load project files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModOverlay {
+    mounts: Vec<PathBuf>,
+}
+
+impl ModOverlay {
+    /// Directory that provides `relative`: the first mounted mod that has
+    /// it, else the project root.
+    pub fn root_for(&self, project_root: &Path, relative: impl AsRef<Path>) -> PathBuf {
+        let relative = relative.as_ref();
+        self.mounts
+            .iter()
+            .find(|mount| mount.join(relative).is_file())
+            .cloned()
+            .unwrap_or_else(|| project_root.to_path_buf())
+    }
+
+    /// Full path of `relative`, looking in the mods before the project.
+    pub fn resolve(&self, project_root: &Path, relative: impl AsRef<Path>) -> PathBuf {
+        let relative = relative.as_ref();
+        self.root_for(project_root, relative).join(relative)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mounts.is_empty()
+    }
+}
+
+/// Every mod of a project and the overlay of the mounted ones.
+#[derive(Debug, Clone, Default)]
+pub struct ModSet {
+    root: PathBuf,
+    /// In mount order.
+    pub mods: Vec<ModInfo>,
+    pub overlay: ModOverlay,
+    pub conflicts: Vec<ModConflict>,
+}
+
+impl ModSet {
+    /// Find the project's mods and mount the enabled ones. A project
+    /// without `mods/` has none.
+    pub fn load(project_root: &Path) -> Result<Self, String> {
+        let mut set = Self { root: project_root.to_path_buf(), ..Default::default() };
+        let mods_dir = project_root.join(MODS_DIR);
+        if !mods_dir.is_dir() {
+            return Ok(set);
+        }
+
+        let state = read_state(project_root)?;
+        let entries = std::fs::read_dir(&mods_dir)
+            .map_err(|e| format!("Failed to read {}: {}", mods_dir.display(), e))?;
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            let manifest_path = path.join(MOD_MANIFEST);
+            let manifest: ModManifest = if manifest_path.is_file() {
+                let contents = std::fs::read_to_string(&manifest_path)
+                    .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+                serde_yaml::from_str(&contents)
+                    .map_err(|e| format!("Invalid {}: {}", manifest_path.display(), e))?
+            } else {
+                ModManifest::default()
+            };
+            let enabled = !state.disabled.contains(&id);
+            set.mods.push(ModInfo {
+                name: manifest.name.unwrap_or_else(|| id.clone()),
+                version: manifest.version,
+                description: manifest.description,
+                priority: manifest.priority,
+                enabled,
+                mounted: enabled,
+                path,
+                id,
+            });
+        }
+        set.mods.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+        set.overlay = ModOverlay {
+            mounts: set.mods.iter().filter(|m| m.mounted).map(|m| m.path.clone()).collect(),
+        };
+        set.conflicts = find_conflicts(&set.mods);
+        Ok(set)
+    }
+
+    /// Log what the mounted mods change and where they clash.
+    pub fn report(&self) {
+        for info in self.mods.iter().filter(|m| m.mounted) {
+            tracing::info!("Mounted mod '{}' {} (priority {})", info.id, info.version, info.priority);
+        }
+        for conflict in &self.conflicts {
+            tracing::warn!(
+                "Mod conflict: {} is in {} and {}; using '{}'",
+                conflict.path,
+                conflict.winner,
+                conflict.shadowed.join(", "),
+                conflict.winner
+            );
+        }
+    }
+
+    /// Switch a mod on or off for the next launch and save the choice.
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> Result<(), String> {
+        let info = self
+            .mods
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or_else(|| format!("unknown mod '{}'", id))?;
+        info.enabled = enabled;
+
+        let state = ModsState {
+            disabled: self.mods.iter().filter(|m| !m.enabled).map(|m| m.id.clone()).collect(),
+        };
+        let yaml = serde_yaml::to_string(&state).map_err(|e| e.to_string())?;
+        let path = self.root.join(MODS_STATE_FILE);
+        std::fs::write(&path, yaml).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+fn read_state(project_root: &Path) -> Result<ModsState, String> {
+    let path = project_root.join(MODS_STATE_FILE);
+    if !path.is_file() {
+        return Ok(ModsState::default());
+    }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&contents).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Files shipped by more than one mounted mod.
+fn find_conflicts(mods: &[ModInfo]) -> Vec<ModConflict> {
+    let mut providers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for info in mods.iter().filter(|m| m.mounted) {
+        let mut files = Vec::new();
+        collect_files(&info.path, &info.path, &mut files);
+        for file in files {
+            providers.entry(file).or_default().push(info.id.clone());
+        }
+    }
+    providers
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(path, mut ids)| {
+            let winner = ids.remove(0);
+            ModConflict { path, winner, shadowed: ids }
+        })
+        .collect()
+}
+
+/// Mod-relative paths of the files under `dir`, except the manifest.
+fn collect_files(base: &Path, dir: &Path, out: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+        if path.is_dir() {
+            collect_files(base, &path, out);
+        } else if let Ok(relative) = path.strip_prefix(base) {
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if relative != MOD_MANIFEST {
+                out.push(relative);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn project(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        write(&dir.join("scenes/main.yaml"), "base");
+        write(&dir.join("logic/player.lua"), "base");
+        write(&dir.join("mods/hd_textures/mod.yaml"), "name: HD Textures\nversion: 1.2.0\npriority: 10\n");
+        write(&dir.join("mods/hd_textures/scenes/main.yaml"), "hd");
+        write(&dir.join("mods/hard_mode/scenes/main.yaml"), "hard");
+        write(&dir.join("mods/hard_mode/logic/boss.lua"), "hard");
+        dir
+    }
+
+    #[test]
+    fn test_overlay_order_and_conflicts() {
+        let root = project("naive_test_mods_overlay");
+        let set = ModSet::load(&root).unwrap();
+        let ids: Vec<&str> = set.mods.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["hd_textures", "hard_mode"]);
+        assert_eq!(set.mods[0].name, "HD Textures");
+        assert_eq!(set.mods[1].name, "hard_mode");
+
+        // Overridden by the higher priority mod, added by a mod, untouched
+        let read = |rel: &str| std::fs::read_to_string(set.overlay.resolve(&root, rel)).unwrap();
+        assert_eq!(read("scenes/main.yaml"), "hd");
+        assert_eq!(read("logic/boss.lua"), "hard");
+        assert_eq!(read("logic/player.lua"), "base");
+
+        assert_eq!(
+            set.conflicts,
+            [ModConflict {
+                path: "scenes/main.yaml".into(),
+                winner: "hd_textures".into(),
+                shadowed: vec!["hard_mode".into()],
+            }]
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_disabled_mods_apply_next_launch() {
+        let root = project("naive_test_mods_disable");
+        let mut set = ModSet::load(&root).unwrap();
+        set.set_enabled("hd_textures", false).unwrap();
+        assert!(set.set_enabled("missing", true).is_err());
+        // Still mounted for this session
+        assert!(set.mods[0].mounted && !set.mods[0].enabled);
+
+        let set = ModSet::load(&root).unwrap();
+        assert!(!set.mods[0].mounted);
+        assert!(set.conflicts.is_empty());
+        assert_eq!(std::fs::read_to_string(set.overlay.resolve(&root, "scenes/main.yaml")).unwrap(), "hard");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
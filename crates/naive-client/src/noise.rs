@@ -0,0 +1,198 @@
+//! Gameplay noise: one channel for the sounds AI should react to.
+//!
+//! Player footsteps, physics impacts, `audio.play_sfx` calls given a noise
+//! and `noise.emit` all produce a `NoiseEvent`. The engine publishes each
+//! one on the event bus as `sound.noise` for Lua listeners, and keeps it for
+//! `NOISE_MEMORY` seconds so perception code can ask what a listener at a
+//! point has heard (`noise.heard`).
+//!
+//! Loudness is the distance in meters at which a listener with normal
+//! hearing (1.0) still hears the sound.
+
+use std::collections::HashMap;
+
+use glam::Vec3;
+use serde_json::{json, Value};
+
+/// Seconds a noise stays audible to `heard` queries.
+pub const NOISE_MEMORY: f32 = 1.0;
+/// Meters walked between two footstep noises.
+pub const FOOTSTEP_STRIDE: f32 = 1.8;
+/// Loudness of a footstep at walking speed; sprinting scales it up.
+pub const FOOTSTEP_LOUDNESS: f32 = 6.0;
+/// Impacts slower than this (m/s) are resting contacts, not noise.
+pub const IMPACT_MIN_SPEED: f32 = 1.5;
+/// Impact loudness per m/s of relative speed, up to `IMPACT_MAX_LOUDNESS`.
+pub const IMPACT_LOUDNESS_PER_SPEED: f32 = 2.0;
+pub const IMPACT_MAX_LOUDNESS: f32 = 30.0;
+
+/// A gameplay-relevant sound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseEvent {
+    pub position: Vec3,
+    pub loudness: f32,
+    /// What made it: "footstep", "impact", "gunshot", ...
+    pub tag: String,
+    /// Entity id of the noise maker, if known.
+    pub source: Option<String>,
+}
+
+impl NoiseEvent {
+    pub fn new(position: Vec3, loudness: f32, tag: impl Into<String>) -> Self {
+        Self { position, loudness, tag: tag.into(), source: None }
+    }
+
+    pub fn with_source(mut self, source: Option<String>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Payload of the `sound.noise` event; `source` is empty when unknown.
+    pub fn payload(&self) -> HashMap<String, Value> {
+        crate::engine_events::payload(json!({
+            "x": self.position.x,
+            "y": self.position.y,
+            "z": self.position.z,
+            "loudness": self.loudness,
+            "tag": self.tag,
+            "source": self.source.clone().unwrap_or_default(),
+        }))
+    }
+}
+
+/// A noise a listener heard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeardNoise<'a> {
+    pub event: &'a NoiseEvent,
+    pub distance: f32,
+    /// Seconds since it was made.
+    pub age: f32,
+}
+
+/// Recent noises and per-entity footstep progress.
+#[derive(Debug, Clone, Default)]
+pub struct NoiseChannel {
+    /// Noises with their age, oldest first.
+    events: Vec<(NoiseEvent, f32)>,
+    /// Trailing entries of `events` not yet published on the event bus.
+    unpublished: usize,
+    /// Meters walked since each entity's last footstep.
+    strides: HashMap<hecs::Entity, f32>,
+}
+
+impl NoiseChannel {
+    /// Record a noise; silent or malformed ones are dropped.
+    pub fn emit(&mut self, event: NoiseEvent) {
+        if !(event.loudness.is_finite() && event.loudness > 0.0 && event.position.is_finite()) {
+            return;
+        }
+        self.events.push((event, 0.0));
+        self.unpublished += 1;
+    }
+
+    /// Noises emitted since the last call, for the event bus.
+    pub fn take_unpublished(&mut self) -> Vec<NoiseEvent> {
+        let start = self.events.len() - self.unpublished;
+        self.unpublished = 0;
+        self.events[start..].iter().map(|(event, _)| event.clone()).collect()
+    }
+
+    /// Age noises by `dt` and forget those older than `NOISE_MEMORY`.
+    pub fn update(&mut self, dt: f32) {
+        for (_, age) in &mut self.events {
+            *age += dt;
+        }
+        let expired = self.events.iter().take_while(|(_, age)| *age > NOISE_MEMORY).count();
+        self.events.drain(..expired);
+        self.unpublished = self.unpublished.min(self.events.len());
+    }
+
+    /// Noises a listener at `position` hears, nearest first. `hearing`
+    /// scales every noise's loudness (2 = hears twice as far).
+    pub fn heard(&self, position: Vec3, hearing: f32) -> Vec<HeardNoise<'_>> {
+        let mut heard: Vec<HeardNoise> = self
+            .events
+            .iter()
+            .filter_map(|(event, age)| {
+                let distance = event.position.distance(position);
+                (distance <= event.loudness * hearing).then_some(HeardNoise { event, distance, age: *age })
+            })
+            .collect();
+        heard.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        heard
+    }
+
+    /// Advance `entity`'s stride by `distance` walked; true when a footstep
+    /// lands.
+    pub fn step(&mut self, entity: hecs::Entity, distance: f32) -> bool {
+        let walked = self.strides.entry(entity).or_insert(0.0);
+        *walked += distance;
+        if *walked >= FOOTSTEP_STRIDE {
+            *walked %= FOOTSTEP_STRIDE;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Loudness of an impact at `speed` m/s, or None for a resting contact.
+pub fn impact_loudness(speed: f32) -> Option<f32> {
+    (speed >= IMPACT_MIN_SPEED).then(|| (speed * IMPACT_LOUDNESS_PER_SPEED).min(IMPACT_MAX_LOUDNESS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heard_by_distance_and_hearing() {
+        let mut channel = NoiseChannel::default();
+        channel.emit(NoiseEvent::new(Vec3::new(10.0, 0.0, 0.0), 12.0, "gunshot").with_source(Some("guard".into())));
+        channel.emit(NoiseEvent::new(Vec3::new(3.0, 0.0, 0.0), 4.0, "footstep"));
+        channel.emit(NoiseEvent::new(Vec3::ZERO, 0.0, "silent"));
+
+        let heard = channel.heard(Vec3::ZERO, 1.0);
+        let tags: Vec<&str> = heard.iter().map(|h| h.event.tag.as_str()).collect();
+        assert_eq!(tags, ["footstep", "gunshot"]);
+        assert_eq!(heard[1].distance, 10.0);
+
+        // Hard of hearing: only the nearby footstep is still in range
+        let heard = channel.heard(Vec3::new(-2.0, 0.0, 0.0), 0.5);
+        assert!(heard.is_empty());
+        assert_eq!(channel.heard(Vec3::new(2.0, 0.0, 0.0), 0.5).len(), 1);
+    }
+
+    #[test]
+    fn test_publish_once_and_forget() {
+        let mut channel = NoiseChannel::default();
+        channel.emit(NoiseEvent::new(Vec3::ZERO, 5.0, "impact"));
+        let published = channel.take_unpublished();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].payload()["source"], "");
+        assert!(channel.take_unpublished().is_empty());
+
+        channel.update(0.6);
+        channel.emit(NoiseEvent::new(Vec3::ZERO, 5.0, "footstep"));
+        channel.update(0.6);
+        // The impact is forgotten, the footstep is still queued and audible
+        assert_eq!(channel.heard(Vec3::ZERO, 1.0).len(), 1);
+        assert_eq!(channel.take_unpublished()[0].tag, "footstep");
+    }
+
+    #[test]
+    fn test_footstep_strides_and_impacts() {
+        let mut channel = NoiseChannel::default();
+        let entity = hecs::World::new().spawn(());
+        let steps = (0..10).filter(|_| channel.step(entity, 0.5)).count();
+        // 5 meters at 1.8 meters per stride
+        assert_eq!(steps, 2);
+        assert_eq!(impact_loudness(0.5), None);
+        assert_eq!(impact_loudness(5.0), Some(10.0));
+        assert_eq!(impact_loudness(100.0), Some(IMPACT_MAX_LOUDNESS));
+    }
+}
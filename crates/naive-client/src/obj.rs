@@ -0,0 +1,278 @@
+//! Wavefront OBJ and MTL parsing.
+//!
+//! `parse_obj` reads positions, UVs, normals and faces (triangulated as fans)
+//! and groups face indices by `usemtl` material. `parse_mtl` reads the
+//! accompanying material library; `MtlMaterial` maps its Phong-style values
+//! onto the engine's PBR material properties. Building GPU meshes from the
+//! result lives in `mesh.rs`; `.mtl` material entries are resolved by
+//! `MaterialCache`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::material::MaterialProperties;
+
+/// Indices of one material's faces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjGroup {
+    /// `usemtl` name (None for faces before any `usemtl`).
+    pub material: Option<String>,
+    pub indices: Vec<u32>,
+}
+
+/// Geometry of an OBJ file, de-indexed into one vertex per unique
+/// position/UV/normal combination.
+#[derive(Debug, Clone, Default)]
+pub struct ObjGeometry {
+    pub positions: Vec<[f32; 3]>,
+    /// Flipped to top-left origin (OBJ UVs start at the bottom).
+    pub uvs: Vec<[f32; 2]>,
+    /// None if any face vertex had no normal; callers generate them.
+    pub normals: Option<Vec<[f32; 3]>>,
+    /// Groups in order of first use, one per material.
+    pub groups: Vec<ObjGroup>,
+    /// `mtllib` paths, relative to the OBJ file.
+    pub material_libs: Vec<String>,
+}
+
+/// One `newmtl` entry of an MTL file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtlMaterial {
+    pub name: String,
+    /// `Kd`
+    pub diffuse: [f32; 3],
+    /// `d`, or 1 - `Tr`
+    pub dissolve: f32,
+    /// `Ns`
+    pub specular_exponent: Option<f32>,
+    /// `Pr` (PBR extension)
+    pub roughness: Option<f32>,
+    /// `Pm` (PBR extension)
+    pub metallic: Option<f32>,
+    /// `Ke`
+    pub emission: [f32; 3],
+    /// `map_Kd`, relative to the MTL file.
+    pub diffuse_map: Option<String>,
+    /// `norm`, `map_Bump` or `bump`, relative to the MTL file.
+    pub normal_map: Option<String>,
+}
+
+impl MtlMaterial {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            diffuse: [0.8, 0.8, 0.8],
+            dissolve: 1.0,
+            specular_exponent: None,
+            roughness: None,
+            metallic: None,
+            emission: [0.0; 3],
+            diffuse_map: None,
+            normal_map: None,
+        }
+    }
+
+    /// PBR roughness: `Pr` when present, otherwise approximated from the
+    /// Phong exponent with the Blinn-Phong to Beckmann mapping
+    /// `sqrt(2 / (Ns + 2))`. Materials with neither get 0.5.
+    pub fn pbr_roughness(&self) -> f32 {
+        match (self.roughness, self.specular_exponent) {
+            (Some(r), _) => r.clamp(0.0, 1.0),
+            (None, Some(ns)) => (2.0 / (ns.max(0.0) + 2.0)).sqrt(),
+            (None, None) => 0.5,
+        }
+    }
+
+    /// Engine material properties, with texture paths made relative to the
+    /// project via `mtl_dir` (the MTL file's directory, project-relative).
+    pub fn to_properties(&self, mtl_dir: &Path) -> MaterialProperties {
+        let resolve = |p: &String| mtl_dir.join(p).to_string_lossy().replace('\\', "/");
+        MaterialProperties {
+            base_color: self.diffuse,
+            roughness: self.pbr_roughness(),
+            metallic: self.metallic.unwrap_or(0.0).clamp(0.0, 1.0),
+            emission: self.emission,
+            albedo_map: self.diffuse_map.as_ref().map(resolve),
+            normal_map: self.normal_map.as_ref().map(resolve),
+            metallic_roughness_map: None,
+            emissive_map: None,
+        }
+    }
+}
+
+/// Parse an MTL file. Unknown statements are ignored.
+pub fn parse_mtl(source: &str) -> Vec<MtlMaterial> {
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+    for line in source.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(keyword) = parts.next() else { continue };
+        let rest: Vec<&str> = parts.collect();
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial::new(&rest.join(" ")));
+            continue;
+        }
+        let Some(current) = materials.last_mut() else { continue };
+        let float = |i: usize| rest.get(i).and_then(|s| s.parse::<f32>().ok());
+        let color = || Some([float(0)?, float(1)?, float(2)?]);
+        match keyword {
+            "Kd" => current.diffuse = color().unwrap_or(current.diffuse),
+            "Ke" => current.emission = color().unwrap_or(current.emission),
+            "d" => current.dissolve = float(0).unwrap_or(1.0),
+            "Tr" => current.dissolve = 1.0 - float(0).unwrap_or(0.0),
+            "Ns" => current.specular_exponent = float(0),
+            "Pr" => current.roughness = float(0),
+            "Pm" => current.metallic = float(0),
+            // Texture statements may carry options (-bm 1.0 ...); the path is last
+            "map_Kd" => current.diffuse_map = rest.last().map(|s| s.to_string()),
+            "norm" | "map_Bump" | "map_bump" | "bump" => current.normal_map = rest.last().map(|s| s.to_string()),
+            _ => {}
+        }
+    }
+    materials
+}
+
+/// Resolve a 1-based (or negative, relative) OBJ index against `len` elements.
+fn resolve_index(token: &str, len: usize, line: usize) -> Result<usize, String> {
+    let i: i64 = token.parse().map_err(|_| format!("line {}: bad index '{}'", line, token))?;
+    let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("line {}: index {} out of range", line, i));
+    }
+    Ok(resolved as usize)
+}
+
+/// Parse an OBJ file. Polygons are triangulated as fans; lines, points and
+/// smoothing groups are ignored.
+pub fn parse_obj(source: &str) -> Result<ObjGeometry, String> {
+    let mut v: Vec<[f32; 3]> = Vec::new();
+    let mut vt: Vec<[f32; 2]> = Vec::new();
+    let mut vn: Vec<[f32; 3]> = Vec::new();
+
+    let mut geometry = ObjGeometry::default();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut missing_normals = false;
+    let mut unique: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    let mut group_of: HashMap<Option<String>, usize> = HashMap::new();
+    let mut current_material: Option<String> = None;
+
+    for (n, line) in source.lines().enumerate() {
+        let n = n + 1;
+        let mut parts = line.split_whitespace();
+        let Some(keyword) = parts.next() else { continue };
+        let floats = |parts: std::str::SplitWhitespace| -> Result<Vec<f32>, String> {
+            parts
+                .map(|s| s.parse::<f32>().map_err(|_| format!("line {}: bad number '{}'", n, s)))
+                .collect()
+        };
+        match keyword {
+            "v" => {
+                let c = floats(parts)?;
+                if c.len() < 3 {
+                    return Err(format!("line {}: vertex needs 3 coordinates", n));
+                }
+                v.push([c[0], c[1], c[2]]);
+            }
+            "vt" => {
+                let c = floats(parts)?;
+                vt.push([c.first().copied().unwrap_or(0.0), 1.0 - c.get(1).copied().unwrap_or(0.0)]);
+            }
+            "vn" => {
+                let c = floats(parts)?;
+                if c.len() < 3 {
+                    return Err(format!("line {}: normal needs 3 components", n));
+                }
+                vn.push([c[0], c[1], c[2]]);
+            }
+            "usemtl" => current_material = Some(parts.collect::<Vec<_>>().join(" ")),
+            "mtllib" => geometry.material_libs.extend(parts.map(str::to_string)),
+            "f" => {
+                let mut face: Vec<u32> = Vec::new();
+                for corner in parts {
+                    let mut refs = corner.split('/');
+                    let vi = resolve_index(refs.next().unwrap_or(""), v.len(), n)?;
+                    let ti = match refs.next() {
+                        Some(t) if !t.is_empty() => Some(resolve_index(t, vt.len(), n)?),
+                        _ => None,
+                    };
+                    let ni = match refs.next() {
+                        Some(t) if !t.is_empty() => Some(resolve_index(t, vn.len(), n)?),
+                        _ => None,
+                    };
+                    missing_normals |= ni.is_none();
+                    let index = *unique.entry((vi, ti, ni)).or_insert_with(|| {
+                        geometry.positions.push(v[vi]);
+                        geometry.uvs.push(ti.map(|t| vt[t]).unwrap_or([0.0, 0.0]));
+                        normals.push(ni.map(|t| vn[t]).unwrap_or([0.0, 1.0, 0.0]));
+                        (geometry.positions.len() - 1) as u32
+                    });
+                    face.push(index);
+                }
+                if face.len() < 3 {
+                    return Err(format!("line {}: face needs at least 3 vertices", n));
+                }
+                let group = *group_of.entry(current_material.clone()).or_insert_with(|| {
+                    geometry.groups.push(ObjGroup { material: current_material.clone(), indices: Vec::new() });
+                    geometry.groups.len() - 1
+                });
+                let indices = &mut geometry.groups[group].indices;
+                for i in 1..face.len() - 1 {
+                    indices.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !missing_normals {
+        geometry.normals = Some(normals);
+    }
+    Ok(geometry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_obj_groups_faces_by_material() {
+        let obj = "mtllib box.mtl\n\
+                   v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                   vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+                   vn 0 0 1\n\
+                   usemtl red\n\
+                   f 1/1/1 2/2/1 3/3/1 4/4/1\n\
+                   usemtl blue\n\
+                   f -4/1/1 -2/3/1 -1/4/1\n";
+        let geometry = parse_obj(obj).unwrap();
+        assert_eq!(geometry.material_libs, vec!["box.mtl"]);
+        // The quad is a two-triangle fan; the blue triangle reuses three corners
+        assert_eq!(geometry.positions.len(), 4);
+        assert_eq!(geometry.groups.len(), 2);
+        assert_eq!(geometry.groups[0].material.as_deref(), Some("red"));
+        assert_eq!(geometry.groups[0].indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(geometry.groups[1].indices, vec![0, 2, 3]);
+        assert_eq!(geometry.uvs[3], [0.0, 0.0]);
+        assert!(geometry.normals.is_some());
+
+        assert!(parse_obj("v 0 0 0\nf 1 2 3\n").is_err());
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap().normals.is_none());
+    }
+
+    #[test]
+    fn test_parse_mtl_maps_to_pbr_properties() {
+        let mtl = "newmtl Brick\nKd 0.6 0.3 0.2\nNs 98\nmap_Kd -bm 1 textures/brick.png\n\
+                   newmtl Chrome\nKd 0.9 0.9 0.9\nPr 0.1\nPm 1\nKe 0 0 0\n";
+        let materials = parse_mtl(mtl);
+        assert_eq!(materials.len(), 2);
+
+        let brick = materials[0].to_properties(Path::new("models"));
+        assert_eq!(brick.base_color, [0.6, 0.3, 0.2]);
+        assert!((brick.roughness - (2.0f32 / 100.0).sqrt()).abs() < 1e-6);
+        assert_eq!(brick.albedo_map.as_deref(), Some("models/textures/brick.png"));
+
+        let chrome = materials[1].to_properties(Path::new("models"));
+        assert_eq!(chrome.roughness, 0.1);
+        assert_eq!(chrome.metallic, 1.0);
+        assert!(chrome.albedo_map.is_none());
+    }
+}
@@ -1,7 +1,25 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use glam::{Vec3, Vec4};
-use crate::components::{ParticleConfig, ParticleEmitter, Transform};
+use crate::components::{ParticleBlend, ParticleCollision, ParticleConfig, ParticleEmitter, Transform};
+use crate::physics::{EntityQueryFilter, PhysicsWorld};
 use crate::world::SceneWorld;
 
+/// Folder of the particle presets, relative to the project root.
+pub const EFFECTS_DIR: &str = "effects";
+
+/// Load the preset `effects/<name>.yaml`, which holds the fields of a
+/// `particle_emitter` component.
+pub fn load_preset(project_root: &Path, name: &str) -> Result<ParticleConfig, String> {
+    let path = project_root.join(EFFECTS_DIR).join(format!("{}.yaml", name));
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read particle preset '{}': {}", name, e))?;
+    let def: crate::scene::ParticleEmitterDef = serde_yaml::from_str(&contents)
+        .map_err(|e| format!("Failed to parse particle preset '{}': {}", name, e))?;
+    Ok(crate::world::particle_config_from_def(project_root, &def))
+}
+
 /// A single particle in the simulation.
 struct Particle {
     position: Vec3,
@@ -10,6 +28,8 @@ struct Particle {
     size: f32,
     lifetime: f32,
     age: f32,
+    blend: ParticleBlend,
+    softness: f32,
 }
 
 /// A runtime emitter instance tied to an ECS entity.
@@ -20,21 +40,50 @@ struct EmitterInstance {
     spawn_accumulator: f32,
 }
 
+/// A one-shot burst not tied to any entity.
+struct Burst {
+    config: ParticleConfig,
+    particles: Vec<Particle>,
+}
+
 /// CPU-side particle simulation system.
 pub struct ParticleSystem {
     emitters: Vec<EmitterInstance>,
-    /// One-shot bursts not tied to any entity.
-    orphan_particles: Vec<Particle>,
+    bursts: Vec<Burst>,
+    /// Presets loaded for `particles.spawn_burst`, by name.
+    presets: HashMap<String, ParticleConfig>,
+    /// Fraction of each emitter's `max_particles` and of each burst spawned
+    /// (the render debug state's `particle_budget`).
+    pub budget: f32,
+}
+
+/// Particles an emitter may hold under `budget`: at least one unless it
+/// allows none.
+fn budgeted(max_particles: u32, budget: f32) -> usize {
+    let cap = (max_particles as f32 * budget).ceil() as usize;
+    cap.max(1).min(max_particles as usize)
 }
 
 impl ParticleSystem {
     pub fn new() -> Self {
         Self {
             emitters: Vec::new(),
-            orphan_particles: Vec::new(),
+            bursts: Vec::new(),
+            presets: HashMap::new(),
+            budget: 1.0,
         }
     }
 
+    /// A preset from `effects/`, loaded on first use.
+    pub fn preset(&mut self, project_root: &Path, name: &str) -> Result<ParticleConfig, String> {
+        if let Some(config) = self.presets.get(name) {
+            return Ok(config.clone());
+        }
+        let config = load_preset(project_root, name)?;
+        self.presets.insert(name.to_string(), config.clone());
+        Ok(config)
+    }
+
     /// Synchronize emitters with the ECS world. Adds new emitters, removes stale ones.
     pub fn sync_emitters(&mut self, scene_world: &SceneWorld) {
         // Collect entity IDs that currently have ParticleEmitter
@@ -56,11 +105,14 @@ impl ParticleSystem {
         self.emitters.retain(|e| active_entities.contains(&e.owner_entity));
     }
 
-    /// Update all particles: spawn new, age existing, kill expired.
-    pub fn update(&mut self, dt: f32, scene_world: &SceneWorld) {
+    /// Update all particles: spawn new, age existing, kill expired. Particles
+    /// with a collision mode collide with `physics` when it is given.
+    pub fn update(&mut self, dt: f32, scene_world: &SceneWorld, physics: Option<&PhysicsWorld>) {
         self.sync_emitters(scene_world);
 
         for emitter in &mut self.emitters {
+            let dt = crate::time_scale::entity_dt(&scene_world.world, emitter.owner_entity, dt);
+
             // Get owner position
             let owner_pos = scene_world.world.get::<&Transform>(emitter.owner_entity)
                 .map(|t| t.position)
@@ -78,49 +130,34 @@ impl ParticleSystem {
                 emitter.spawn_accumulator -= to_spawn as f32;
 
                 for _ in 0..to_spawn {
-                    if emitter.particles.len() >= emitter.config.max_particles as usize {
+                    if emitter.particles.len() >= budgeted(emitter.config.max_particles, self.budget) {
                         break;
                     }
                     emitter.particles.push(spawn_particle(&emitter.config, owner_pos));
                 }
             }
 
-            // Update existing particles
-            let gravity = Vec3::new(0.0, -9.81 * emitter.config.gravity_scale, 0.0);
-            for particle in &mut emitter.particles {
-                particle.velocity += gravity * dt;
-                particle.position += particle.velocity * dt;
-                particle.age += dt;
-
-                // Interpolate color and size
-                let t = (particle.age / particle.lifetime).clamp(0.0, 1.0);
-                let start = Vec4::from(emitter.config.color_start);
-                let end = Vec4::from(emitter.config.color_end);
-                particle.color = start.lerp(end, t);
-                let [size_start, size_end] = emitter.config.size;
-                particle.size = size_start + (size_end - size_start) * t;
-            }
-
-            // Remove expired
-            emitter.particles.retain(|p| p.age < p.lifetime);
+            // Particles start inside the emitter's own collider
+            let filter = EntityQueryFilter {
+                exclude: std::iter::once(emitter.owner_entity).collect(),
+                exclude_triggers: true,
+                ..Default::default()
+            };
+            simulate(&emitter.config, &mut emitter.particles, dt, physics.map(|pw| (pw, &filter)));
         }
 
-        // Update orphan particles
-        for particle in &mut self.orphan_particles {
-            particle.velocity += Vec3::new(0.0, -9.81, 0.0) * dt;
-            particle.position += particle.velocity * dt;
-            particle.age += dt;
-            let t = (particle.age / particle.lifetime).clamp(0.0, 1.0);
-            particle.color.w = 1.0 - t; // fade out
+        let filter = EntityQueryFilter { exclude_triggers: true, ..Default::default() };
+        for burst in &mut self.bursts {
+            simulate(&burst.config, &mut burst.particles, dt, physics.map(|pw| (pw, &filter)));
         }
-        self.orphan_particles.retain(|p| p.age < p.lifetime);
+        self.bursts.retain(|b| !b.particles.is_empty());
     }
 
     /// Spawn a burst of particles at a world position (no entity).
     pub fn spawn_burst(&mut self, position: Vec3, count: u32, config: &ParticleConfig) {
-        for _ in 0..count {
-            self.orphan_particles.push(spawn_particle(config, position));
-        }
+        let count = budgeted(count, self.budget);
+        let particles = (0..count).map(|_| spawn_particle(config, position)).collect();
+        self.bursts.push(Burst { config: config.clone(), particles });
     }
 
     /// Spawn a burst on an existing emitter entity.
@@ -134,70 +171,121 @@ impl ParticleSystem {
             .unwrap_or(Vec3::ZERO);
 
         for _ in 0..count {
-            if emitter.particles.len() >= emitter.config.max_particles as usize {
+            if emitter.particles.len() >= budgeted(emitter.config.max_particles, self.budget) {
                 break;
             }
             emitter.particles.push(spawn_particle(&emitter.config, owner_pos));
         }
     }
 
-    /// Collect all live particle data for rendering (position, size, color).
-    /// Returns a flat Vec of billboard vertex data.
-    pub fn collect_billboard_data(&self, camera_right: Vec3, camera_up: Vec3) -> Vec<ParticleBillboardVertex> {
-        let mut vertices = Vec::new();
-
+    /// Collect the live particles as instances for the particle pass:
+    /// alpha-blended ones sorted back-to-front from `camera_position`, then
+    /// additive ones. Returns the instances and how many are alpha-blended.
+    pub fn collect_instances(&self, camera_position: Vec3) -> (Vec<ParticleInstance>, usize) {
         let all_particles = self.emitters.iter()
             .flat_map(|e| e.particles.iter())
-            .chain(self.orphan_particles.iter());
+            .chain(self.bursts.iter().flat_map(|b| b.particles.iter()));
 
+        let mut alpha = Vec::new();
+        let mut additive = Vec::new();
         for particle in all_particles {
-            let half = particle.size * 0.5;
-            let right = camera_right * half;
-            let up = camera_up * half;
-
-            let p = particle.position;
-            let color = [particle.color.x, particle.color.y, particle.color.z, particle.color.w];
-
-            // Quad: two triangles
-            vertices.push(ParticleBillboardVertex { position: (p - right - up).to_array(), color });
-            vertices.push(ParticleBillboardVertex { position: (p + right - up).to_array(), color });
-            vertices.push(ParticleBillboardVertex { position: (p + right + up).to_array(), color });
-
-            vertices.push(ParticleBillboardVertex { position: (p - right - up).to_array(), color });
-            vertices.push(ParticleBillboardVertex { position: (p + right + up).to_array(), color });
-            vertices.push(ParticleBillboardVertex { position: (p - right + up).to_array(), color });
+            let instance = ParticleInstance {
+                position: particle.position.to_array(),
+                size: particle.size,
+                color: particle.color.to_array(),
+                softness: particle.softness,
+                _pad: [0.0; 3],
+            };
+            match particle.blend {
+                ParticleBlend::Alpha => alpha.push((particle.position.distance_squared(camera_position), instance)),
+                ParticleBlend::Additive => additive.push(instance),
+            }
         }
 
-        vertices
+        alpha.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let alpha_count = alpha.len();
+        let mut instances: Vec<ParticleInstance> = alpha.into_iter().map(|(_, instance)| instance).collect();
+        instances.extend(additive);
+        (instances, alpha_count)
     }
 
     /// Get total live particle count (for diagnostics).
     pub fn particle_count(&self) -> usize {
         self.emitters.iter().map(|e| e.particles.len()).sum::<usize>()
-            + self.orphan_particles.len()
+            + self.bursts.iter().map(|b| b.particles.len()).sum::<usize>()
     }
 }
 
-/// Vertex data for a particle billboard quad.
+/// One particle as drawn by the particle pass: a camera-facing quad per
+/// instance.
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct ParticleBillboardVertex {
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ParticleInstance {
     pub position: [f32; 3],
+    pub size: f32,
     pub color: [f32; 4],
+    pub softness: f32,
+    pub _pad: [f32; 3],
 }
 
-impl ParticleBillboardVertex {
+impl ParticleInstance {
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
-        const ATTRIBS: [wgpu::VertexAttribute; 2] =
-            wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+        const ATTRIBS: [wgpu::VertexAttribute; 4] =
+            wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32, 2 => Float32x4, 3 => Float32];
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<ParticleBillboardVertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
+            array_stride: std::mem::size_of::<ParticleInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
             attributes: &ATTRIBS,
         }
     }
 }
 
+/// Age and move particles, apply the config's color and size over life, and
+/// drop the expired ones. With `physics`, each moving particle casts a ray
+/// along its step and bounces or dies where it hits.
+fn simulate(
+    config: &ParticleConfig,
+    particles: &mut Vec<Particle>,
+    dt: f32,
+    physics: Option<(&PhysicsWorld, &EntityQueryFilter)>,
+) {
+    /// Gap kept between a bounced particle and the surface.
+    const SURFACE_OFFSET: f32 = 0.01;
+
+    let gravity = Vec3::new(0.0, -9.81 * config.gravity_scale, 0.0);
+    let physics = physics.filter(|_| config.collision != ParticleCollision::None);
+    for particle in particles.iter_mut() {
+        particle.velocity += gravity * dt;
+        let step = particle.velocity * dt;
+        let hit = physics.and_then(|(pw, filter)| {
+            let distance = step.length();
+            if distance <= f32::EPSILON {
+                return None;
+            }
+            pw.raycast_detailed(particle.position, step, distance, filter)
+                // A zero normal means the ray started inside a collider
+                .filter(|(_, _, _, normal)| *normal != Vec3::ZERO)
+        });
+        match hit {
+            Some((_, _, point, normal)) => match config.collision {
+                ParticleCollision::Kill => particle.age = particle.lifetime,
+                _ => {
+                    let into = particle.velocity.dot(normal).min(0.0);
+                    particle.velocity -= normal * into * (1.0 + config.bounce);
+                    particle.position = point + normal * SURFACE_OFFSET;
+                }
+            },
+            None => particle.position += step,
+        }
+        particle.age += dt;
+
+        let t = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+        particle.color = Vec4::from(config.color_at(t));
+        particle.size = config.size_at(t);
+    }
+    particles.retain(|p| p.age < p.lifetime);
+}
+
 /// Spawn a single particle using the emitter config and random variation.
 fn spawn_particle(config: &ParticleConfig, origin: Vec3) -> Particle {
     // Simple deterministic-ish variation using a basic hash
@@ -240,9 +328,88 @@ fn spawn_particle(config: &ParticleConfig, origin: Vec3) -> Particle {
     Particle {
         position: origin,
         velocity,
-        color: Vec4::from(config.color_start),
-        size: config.size[0],
+        color: Vec4::from(config.color_at(0.0)),
+        size: config.size_at(0.0),
         lifetime,
         age: 0.0,
+        blend: config.blend,
+        softness: config.softness,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_curves() {
+        let dir = std::env::temp_dir().join("naive_test_particle_presets");
+        std::fs::create_dir_all(dir.join(EFFECTS_DIR)).unwrap();
+        std::fs::write(
+            dir.join(EFFECTS_DIR).join("sparks.yaml"),
+            "rate: 50\ncolor_over_life:\n  - [1.0, 1, 0, 0, 0]\n  - [0.0, 1, 1, 1, 1]\n  - [0.5, 1, 1, 0, 1]\nsize_over_life: [[0, 0.1], [1, 0.3]]\nblend: additive\n",
+        ).unwrap();
+
+        let mut system = ParticleSystem::new();
+        let config = system.preset(&dir, "sparks").unwrap();
+        assert_eq!(config.spawn_rate, 50.0);
+        assert_eq!(config.blend, ParticleBlend::Additive);
+        assert_eq!(config.color_at(0.0), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(config.color_at(0.75), [1.0, 0.5, 0.0, 0.5]);
+        assert_eq!(config.color_at(2.0), [1.0, 0.0, 0.0, 0.0]);
+        assert!((config.size_at(0.5) - 0.2).abs() < 1e-6);
+        assert!(system.preset(&dir, "missing").is_err());
+
+        system.spawn_burst(Vec3::ZERO, 8, &config);
+        assert_eq!(system.particle_count(), 8);
+        // A quarter budget spawns a quarter of each burst, but never nothing
+        system.budget = 0.25;
+        system.spawn_burst(Vec3::ZERO, 8, &config);
+        assert_eq!(system.particle_count(), 10);
+        assert_eq!((budgeted(3, 0.1), budgeted(0, 0.5)), (1, 0));
+
+        let _ =std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_particles_land_on_colliders() {
+        let mut world = hecs::World::new();
+        let floor = world.spawn(());
+        let mut pw = PhysicsWorld::new(Vec3::new(0.0, -9.81, 0.0));
+        pw.add_static_body(
+            floor,
+            Vec3::new(0.0, -0.5, 0.0),
+            glam::Quat::IDENTITY,
+            crate::physics::PhysicsShape::Box { half_extents: Vec3::new(10.0, 0.5, 10.0) },
+            false,
+            0.0,
+            0.5,
+        );
+        pw.query_pipeline.update(&pw.collider_set);
+        let scene_world = SceneWorld::new();
+
+        let config = ParticleConfig {
+            lifetime: [10.0, 10.0],
+            initial_speed: [0.0, 0.0],
+            gravity_scale: 1.0,
+            collision: ParticleCollision::Bounce,
+            ..Default::default()
+        };
+        let mut system = ParticleSystem::new();
+        system.spawn_burst(Vec3::new(0.0, 2.0, 0.0), 4, &config);
+        for _ in 0..120 {
+            system.update(1.0 / 60.0, &scene_world, Some(&pw));
+        }
+        let (instances, _) = system.collect_instances(Vec3::ZERO);
+        assert_eq!(instances.len(), 4);
+        assert!(instances.iter().all(|p| p.position[1] > -0.01 && p.position[1] < 0.5));
+
+        let kill = ParticleConfig { collision: ParticleCollision::Kill, ..config };
+        let mut system = ParticleSystem::new();
+        system.spawn_burst(Vec3::new(0.0, 2.0, 0.0), 4, &kill);
+        for _ in 0..120 {
+            system.update(1.0 / 60.0, &scene_world, Some(&pw));
+        }
+        assert_eq!(system.particle_count(), 0);
     }
 }
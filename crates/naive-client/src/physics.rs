@@ -31,6 +31,35 @@ pub struct CharacterController {
     pub step_height: f32,
     pub max_slope_angle: f32,
     pub velocity: Vec3,
+    /// Maximum force (N) the character exerts on dynamic bodies it walks into.
+    pub push_force: f32,
+    /// Character mass (kg), used for the push mass ratio.
+    pub mass: f32,
+}
+
+impl CharacterController {
+    pub fn push(&self) -> CharacterPush {
+        CharacterPush { force: self.push_force, mass: self.mass }
+    }
+}
+
+/// How a character interacts with dynamic bodies it runs into.
+#[derive(Debug, Clone, Copy)]
+pub struct CharacterPush {
+    pub force: f32,
+    pub mass: f32,
+}
+
+impl CharacterPush {
+    /// Fraction of the character's speed a pushed body of `body_mass` is
+    /// driven to: light props move with the character, heavy ones barely budge,
+    /// and the blocked character is held back to the same speed.
+    pub fn speed_share(&self, body_mass: f32) -> f32 {
+        if self.mass <= 0.0 {
+            return 0.0;
+        }
+        self.mass / (self.mass + body_mass.max(0.0))
+    }
 }
 
 impl Default for CharacterController {
@@ -43,6 +72,8 @@ impl Default for CharacterController {
             step_height: 0.3,
             max_slope_angle: 45.0_f32.to_radians(),
             velocity: Vec3::ZERO,
+            push_force: 400.0,
+            mass: 80.0,
         }
     }
 }
@@ -64,6 +95,14 @@ pub enum PhysicsShape {
         vertices: Vec<rapier3d::na::Point3<f32>>,
         indices: Vec<[u32; 3]>,
     },
+    /// Grid of heights, `rows` along Z by `cols` along X (row-major), spread
+    /// over `scale.x` by `scale.z` centered on the body and multiplied by `scale.y`.
+    Heightfield {
+        heights: Vec<f32>,
+        rows: usize,
+        cols: usize,
+        scale: Vec3,
+    },
 }
 
 /// Collision event emitted when two colliders touch.
@@ -74,12 +113,118 @@ pub struct CollisionEvent {
     pub started: bool,
 }
 
-/// Trigger event emitted when an entity enters/exits a trigger volume.
+/// Seconds between `on_trigger_stay` dispatches for entities inside a trigger.
+pub const DEFAULT_TRIGGER_STAY_INTERVAL: f32 = 0.1;
+
+/// Which part of a trigger overlap a `TriggerEvent` reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerPhase {
+    Enter,
+    /// Still inside; emitted at most once per `trigger_stay_interval` for each
+    /// pair, with the seconds since the pair's enter or previous stay event.
+    Stay { elapsed: f32 },
+    Exit,
+}
+
+/// Trigger event emitted when an entity enters, stays in, or exits a trigger volume.
 #[derive(Debug, Clone)]
 pub struct TriggerEvent {
     pub trigger_entity: hecs::Entity,
     pub other_entity: hecs::Entity,
-    pub entered: bool,
+    pub phase: TriggerPhase,
+}
+
+/// Entity filter for physics queries (raycast, hitscan).
+/// Callers resolve ids and tags to entities; the filter is applied per collider.
+#[derive(Debug, Clone, Default)]
+pub struct EntityQueryFilter {
+    /// Entities that are never reported as hits (e.g. the shooter).
+    pub exclude: HashSet<hecs::Entity>,
+    /// When set, only these entities can be hit.
+    pub include_only: Option<HashSet<hecs::Entity>>,
+    /// Skip trigger volumes.
+    pub exclude_triggers: bool,
+}
+
+impl EntityQueryFilter {
+    /// Whether a hit on this entity passes the filter.
+    pub fn allows(&self, entity: hecs::Entity) -> bool {
+        !self.exclude.contains(&entity)
+            && self.include_only.as_ref().is_none_or(|only| only.contains(&entity))
+    }
+}
+
+/// Most fixed steps taken in one frame. After a longer hitch the rest of the
+/// backlog is dropped, so a slow frame can't make the next one slower still.
+pub const MAX_STEPS_PER_FRAME: u32 = 8;
+
+/// Accumulates frame time into fixed physics steps (`physics_rate` in the
+/// scene settings).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FixedTimestep {
+    /// Seconds per step, or 0 to step once per frame by the frame's time.
+    pub step: f32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    /// Steps `rate` times per second; 0 steps once per frame.
+    pub fn new(rate: f32) -> Self {
+        let step = if rate > 0.0 { 1.0 / rate } else { 0.0 };
+        Self { step, accumulator: 0.0 }
+    }
+
+    /// Add `dt` seconds and return how many steps came due.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        if self.step <= 0.0 {
+            return 0;
+        }
+        self.accumulator += dt.max(0.0);
+        let due = (self.accumulator / self.step) as u32;
+        self.accumulator = (self.accumulator - due as f32 * self.step).max(0.0);
+        due.min(MAX_STEPS_PER_FRAME)
+    }
+
+    /// How far time has run into the next step (0..1), or 1 when stepping
+    /// once per frame.
+    pub fn alpha(&self) -> f32 {
+        if self.step > 0.0 {
+            (self.accumulator / self.step).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A body of water that floats dynamic bodies: a box hanging below a level
+/// surface, turned by `rotation` about the vertical.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterVolume {
+    /// Center of the surface.
+    pub center: Vec3,
+    pub rotation: Quat,
+    /// Half extents of the surface along the local X and Z axes.
+    pub half_extents: [f32; 2],
+    pub depth: f32,
+    /// Upward force on a fully submerged body, as a multiple of its weight.
+    pub buoyancy: f32,
+    /// Fraction of velocity lost per second while fully submerged.
+    pub drag: f32,
+}
+
+impl WaterVolume {
+    /// Fraction (0..1) of a vertical span `[bottom, top]` at `point` (world
+    /// space) that is under the surface and inside the volume.
+    pub fn submerged_fraction(&self, point: Vec3, bottom: f32, top: f32) -> f32 {
+        let local = self.rotation.inverse() * (point - self.center);
+        if local.x.abs() > self.half_extents[0] || local.z.abs() > self.half_extents[1] {
+            return 0.0;
+        }
+        let height = (top - bottom).max(1e-4);
+        let surface = self.center.y;
+        let covered = (surface.min(top) - bottom.max(surface - self.depth)).max(0.0);
+        (covered / height).min(1.0)
+    }
 }
 
 /// Central physics world state.
@@ -108,8 +253,25 @@ pub struct PhysicsWorld {
     // Track active contact pairs from previous frame to detect new-only contacts
     active_contact_pairs: HashSet<[ColliderHandle; 2]>,
 
+    // Overlapping (trigger, other) collider pairs, kept across frames
+    active_intersections: HashSet<[ColliderHandle; 2]>,
+    /// Seconds between `TriggerPhase::Stay` events of each overlapping pair.
+    pub trigger_stay_interval: f32,
+    // Seconds since each overlapping pair's enter or last stay event
+    trigger_stay_elapsed: HashMap<[ColliderHandle; 2], f32>,
+
     // Character controller
     pub character_controller: KinematicCharacterController,
+
+    /// Water volumes by owning entity, applied to dynamic bodies each step.
+    pub water_volumes: HashMap<hecs::Entity, WaterVolume>,
+
+    /// Fixed step `advance` simulates in.
+    pub timestep: FixedTimestep,
+    // Pose of each moving body before the last fixed step, for interpolation
+    previous_poses: HashMap<RigidBodyHandle, Isometry<Real>>,
+    // Bodies whose transforms snap to the latest step
+    snapped: HashSet<RigidBodyHandle>,
 }
 
 impl PhysicsWorld {
@@ -142,7 +304,14 @@ impl PhysicsWorld {
             collision_events: Vec::new(),
             trigger_events: Vec::new(),
             active_contact_pairs: HashSet::new(),
+            active_intersections: HashSet::new(),
+            trigger_stay_interval: DEFAULT_TRIGGER_STAY_INTERVAL,
+            trigger_stay_elapsed: HashMap::new(),
             character_controller,
+            water_volumes: HashMap::new(),
+            timestep: FixedTimestep::default(),
+            previous_poses: HashMap::new(),
+            snapped: HashSet::new(),
         }
     }
 
@@ -167,7 +336,11 @@ impl PhysicsWorld {
             .restitution(restitution)
             .friction(friction);
         let collider = if is_trigger {
-            collider_builder.sensor(true).build()
+            // Sensors on fixed/kinematic bodies must still detect the kinematic player
+            collider_builder
+                .sensor(true)
+                .active_collision_types(ActiveCollisionTypes::all())
+                .build()
         } else {
             collider_builder.build()
         };
@@ -202,7 +375,11 @@ impl PhysicsWorld {
             .restitution(restitution)
             .friction(friction);
         let collider = if is_trigger {
-            collider_builder.sensor(true).build()
+            // Sensors on fixed/kinematic bodies must still detect the kinematic player
+            collider_builder
+                .sensor(true)
+                .active_collision_types(ActiveCollisionTypes::all())
+                .build()
         } else {
             collider_builder.build()
         };
@@ -277,9 +454,61 @@ impl PhysicsWorld {
         (rb_handle, col_handle)
     }
 
+    /// Choose whether the body's transform is interpolated between its last
+    /// two fixed steps (the default) or snaps to the latest one.
+    pub fn set_interpolated(&mut self, rb_handle: RigidBodyHandle, interpolated: bool) {
+        if interpolated {
+            self.snapped.remove(&rb_handle);
+        } else {
+            self.snapped.insert(rb_handle);
+            self.previous_poses.remove(&rb_handle);
+        }
+    }
+
+    /// Advance the simulation by `dt` seconds: the fixed steps that came due,
+    /// or one step of `dt` without a fixed timestep. Events from every step
+    /// are kept until the next call.
+    pub fn advance(&mut self, dt: f32) {
+        if self.timestep.step <= 0.0 {
+            self.step(dt);
+            return;
+        }
+        self.collision_events.clear();
+        self.trigger_events.clear();
+        let steps = self.timestep.advance(dt);
+        for i in 0..steps {
+            // Only the last step's starting pose is interpolated from
+            if i + 1 == steps {
+                self.record_poses();
+            }
+            self.simulate(self.timestep.step);
+        }
+    }
+
     /// Step the physics simulation.
     pub fn step(&mut self, dt: f32) {
+        self.collision_events.clear();
+        self.trigger_events.clear();
+        // Frozen time (time scale 0): nothing moves and no new events fire
+        if dt <= 0.0 {
+            return;
+        }
+        self.simulate(dt);
+    }
+
+    fn record_poses(&mut self) {
+        self.previous_poses.clear();
+        for (handle, body) in self.rigid_body_set.iter() {
+            if !body.is_fixed() && !self.snapped.contains(&handle) {
+                self.previous_poses.insert(handle, *body.position());
+            }
+        }
+    }
+
+    /// Run one step of `dt` seconds, appending its events.
+    fn simulate(&mut self, dt: f32) {
         self.integration_params.dt = dt;
+        self.apply_buoyancy(dt);
         let gravity = vector![self.gravity.x, self.gravity.y, self.gravity.z];
 
         self.physics_pipeline.step(
@@ -299,9 +528,6 @@ impl PhysicsWorld {
         );
 
         // Collect collision events from narrow phase — only newly started contacts
-        self.collision_events.clear();
-        self.trigger_events.clear();
-
         let mut current_pairs = HashSet::new();
         for pair in self.narrow_phase.contact_pairs() {
             if pair.has_any_active_contact {
@@ -323,40 +549,174 @@ impl PhysicsWorld {
             }
         }
         self.active_contact_pairs = current_pairs;
+
+        self.collect_trigger_events(dt);
+    }
+
+    /// Push submerged dynamic bodies up and damp their motion. The submerged
+    /// fraction is measured on the vertical extent of each body's colliders.
+    fn apply_buoyancy(&mut self, dt: f32) {
+        if self.water_volumes.is_empty() {
+            return;
+        }
+        for (_, body) in self.rigid_body_set.iter_mut() {
+            if !body.is_dynamic() || !body.is_enabled() {
+                continue;
+            }
+            let mut bounds: Option<Aabb> = None;
+            for handle in body.colliders() {
+                if let Some(collider) = self.collider_set.get(*handle) {
+                    if collider.is_sensor() {
+                        continue;
+                    }
+                    let aabb = collider.compute_aabb();
+                    bounds = Some(bounds.map_or(aabb, |b| b.merged(&aabb)));
+                }
+            }
+            let Some(bounds) = bounds else { continue };
+            let center = bounds.center();
+            let center = Vec3::new(center.x, center.y, center.z);
+            let (bottom, top) = (bounds.mins.y, bounds.maxs.y);
+
+            let mut submerged = 0.0f32;
+            let mut buoyancy = 0.0f32;
+            let mut drag = 0.0f32;
+            for volume in self.water_volumes.values() {
+                let fraction = volume.submerged_fraction(center, bottom, top);
+                if fraction > submerged {
+                    submerged = fraction;
+                    buoyancy = volume.buoyancy;
+                    drag = volume.drag;
+                }
+            }
+            if submerged <= 0.0 {
+                continue;
+            }
+
+            let mass = body.mass();
+            let lift = -self.gravity * mass * buoyancy * submerged * dt;
+            body.apply_impulse(vector![lift.x, lift.y, lift.z], true);
+            let damping = (1.0 - drag * submerged * dt).clamp(0.0, 1.0);
+            let linvel = *body.linvel() * damping;
+            let angvel = *body.angvel() * damping;
+            body.set_linvel(linvel, true);
+            body.set_angvel(angvel, true);
+        }
+    }
+
+    /// Diff sensor intersections against last frame into enter/exit events,
+    /// plus rate-limited stay events for everything still inside.
+    fn collect_trigger_events(&mut self, dt: f32) {
+        let mut current = HashSet::new();
+        for (collider1, collider2, intersecting) in self.narrow_phase.intersection_pairs() {
+            if !intersecting {
+                continue;
+            }
+            for (trigger, other) in [(collider1, collider2), (collider2, collider1)] {
+                if self.collider_set.get(trigger).is_some_and(|c| c.is_sensor()) {
+                    current.insert([trigger, other]);
+                }
+            }
+        }
+
+        // Each pair keeps its own stay timer, started by its enter
+        let mut stay_elapsed = HashMap::new();
+        let mut stayed = Vec::new();
+        for pair in current.intersection(&self.active_intersections) {
+            let elapsed = self.trigger_stay_elapsed.get(pair).copied().unwrap_or(0.0) + dt;
+            if elapsed >= self.trigger_stay_interval {
+                stayed.push((pair, TriggerPhase::Stay { elapsed }));
+                stay_elapsed.insert(*pair, 0.0);
+            } else {
+                stay_elapsed.insert(*pair, elapsed);
+            }
+        }
+
+        let entered: Vec<_> = current.difference(&self.active_intersections).map(|p| (p, TriggerPhase::Enter)).collect();
+        for (pair, _) in &entered {
+            stay_elapsed.insert(**pair, dt);
+        }
+        let exited = self.active_intersections.difference(&current).map(|p| (p, TriggerPhase::Exit));
+        let events: Vec<TriggerEvent> = entered
+            .into_iter()
+            .chain(stayed)
+            .chain(exited)
+            .filter_map(|([trigger, other], phase)| {
+                Some(TriggerEvent {
+                    trigger_entity: *self.collider_to_entity.get(trigger)?,
+                    other_entity: *self.collider_to_entity.get(other)?,
+                    phase,
+                })
+            })
+            .collect();
+        self.trigger_events = events;
+        self.trigger_stay_elapsed = stay_elapsed;
+        self.active_intersections = current;
+    }
+
+    /// Entities currently overlapping the given trigger entity's volume.
+    pub fn volume_contents(&self, trigger_entity: hecs::Entity) -> Vec<hecs::Entity> {
+        let mut contents: Vec<hecs::Entity> = self
+            .active_intersections
+            .iter()
+            .filter(|[trigger, _]| self.collider_to_entity.get(trigger) == Some(&trigger_entity))
+            .filter_map(|[_, other]| self.collider_to_entity.get(other).copied())
+            .collect();
+        contents.sort_by_key(|e| e.id());
+        contents.dedup();
+        contents
     }
 
     /// Move a character controller and return the effective movement.
+    /// Dynamic bodies in the way are pushed according to `push`, and hold the
+    /// character back to the speed they are driven at.
     pub fn move_character(
         &mut self,
         rb_handle: RigidBodyHandle,
         col_handle: ColliderHandle,
         desired_movement: Vec3,
         dt: f32,
+        push: CharacterPush,
     ) -> (Vec3, bool) {
         let body = &self.rigid_body_set[rb_handle];
         let collider = &self.collider_set[col_handle];
 
+        let mut collisions = Vec::new();
         let movement = self.character_controller.move_shape(
             dt,
             &self.rigid_body_set,
             &self.collider_set,
             &self.query_pipeline,
             collider.shape(),
-            body.position(),
+            // A move from a frame without a physics step is still pending
+            body.next_position(),
             vector![desired_movement.x, desired_movement.y, desired_movement.z],
             QueryFilter::default().exclude_rigid_body(rb_handle),
-            |_| {},
+            |collision| collisions.push(collision),
         );
 
         let grounded = movement.grounded;
-        let effective = Vec3::new(
+        let mut effective = Vec3::new(
             movement.translation.x,
             movement.translation.y,
             movement.translation.z,
         );
 
+        // Pushback: the character advances into the heaviest pushed body only
+        // as fast as that body is driven away
+        if let Some(share) = self.push_dynamic_bodies(&collisions, desired_movement, dt, push) {
+            let horizontal = Vec3::new(desired_movement.x, 0.0, desired_movement.z);
+            let dir = horizontal.normalize();
+            let limit = horizontal.length() * share;
+            let along = effective.dot(dir);
+            if along > limit {
+                effective -= dir * (along - limit);
+            }
+        }
+        let body = &self.rigid_body_set[rb_handle];
+
         // Apply the movement to the rigid body
-        let current_pos = body.position().translation;
+        let current_pos = body.next_position().translation;
         let new_pos = vector![
             current_pos.x + effective.x,
             current_pos.y + effective.y,
@@ -364,7 +724,7 @@ impl PhysicsWorld {
         ];
 
         if let Some(body) = self.rigid_body_set.get_mut(rb_handle) {
-            let mut new_iso = *body.position();
+            let mut new_iso = *body.next_position();
             new_iso.translation = new_pos.into();
             body.set_next_kinematic_position(new_iso);
         }
@@ -372,61 +732,130 @@ impl PhysicsWorld {
         (effective, grounded)
     }
 
-    /// Sync physics body positions back to ECS transforms.
+    /// Drive dynamic bodies the character walked into along its horizontal
+    /// movement, up to `push.force`. The body is accelerated towards the
+    /// character's speed scaled by the mass ratio. Returns the smallest speed
+    /// share among the pushed bodies, or None when nothing was pushed.
+    fn push_dynamic_bodies(
+        &mut self,
+        collisions: &[rapier3d::control::CharacterCollision],
+        desired_movement: Vec3,
+        dt: f32,
+        push: CharacterPush,
+    ) -> Option<f32> {
+        let horizontal = Vec3::new(desired_movement.x, 0.0, desired_movement.z);
+        if push.force <= 0.0 || dt <= 0.0 || horizontal.length_squared() < 1e-8 {
+            return None;
+        }
+        let dir = horizontal.normalize();
+        let speed = horizontal.length() / dt;
+        let max_impulse = push.force * dt;
+
+        let mut pushed = HashSet::new();
+        let mut slowest: Option<f32> = None;
+        for collision in collisions {
+            // Standing on or bumping the underside of a body is not a push
+            if collision.hit.normal1.y.abs() > 0.7 {
+                continue;
+            }
+            let Some(parent) = self.collider_set.get(collision.handle).and_then(|c| c.parent()) else {
+                continue;
+            };
+            if !pushed.insert(parent) {
+                continue;
+            }
+            let Some(body) = self.rigid_body_set.get_mut(parent) else {
+                continue;
+            };
+            if !body.is_dynamic() {
+                continue;
+            }
+
+            let body_mass = body.mass();
+            let share = push.speed_share(body_mass);
+            slowest = Some(slowest.map_or(share, |s| s.min(share)));
+            let target_speed = speed * share;
+            let linvel = body.linvel();
+            let current_speed = linvel.x * dir.x + linvel.z * dir.z;
+            let impulse = (body_mass * (target_speed - current_speed)).clamp(0.0, max_impulse);
+            if impulse > 0.0 {
+                body.apply_impulse(vector![dir.x * impulse, 0.0, dir.z * impulse], true);
+            }
+        }
+        slowest
+    }
+
+    /// Rebuild the water volumes from the ECS `Water` components. The surface
+    /// is a unit plane, so the volume spans half the transform's scale.
+    pub fn sync_water_volumes(&mut self, world: &hecs::World) {
+        self.water_volumes.clear();
+        for (entity, (transform, water)) in world.query::<(&Transform, &crate::components::Water)>().iter() {
+            let (scale, rotation, center) = transform.world_matrix.to_scale_rotation_translation();
+            self.water_volumes.insert(
+                entity,
+                WaterVolume {
+                    center,
+                    rotation,
+                    half_extents: [scale.x.abs() * 0.5, scale.z.abs() * 0.5],
+                    depth: water.depth,
+                    buoyancy: water.buoyancy,
+                    drag: water.drag,
+                },
+            );
+        }
+    }
+
+    /// Sync physics body positions back to ECS transforms. With a fixed
+    /// timestep, moving bodies are placed between their last two steps by how
+    /// far time has run into the next one, unless they snap.
     pub fn sync_to_ecs(&self, world: &mut hecs::World) {
+        let alpha = self.timestep.alpha();
         for (rb_handle, &entity) in &self.body_to_entity {
             if let Some(body) = self.rigid_body_set.get(*rb_handle) {
                 if let Ok(mut transform) = world.get::<&mut Transform>(entity) {
-                    let pos = body.position().translation;
-                    let rot = body.position().rotation;
-                    transform.position = Vec3::new(pos.x, pos.y, pos.z);
-                    transform.rotation = Quat::from_xyzw(rot.i, rot.j, rot.k, rot.w);
+                    let (mut position, mut rotation) = pose_to_glam(body.position());
+                    if let Some(previous) = self.previous_poses.get(rb_handle).filter(|_| alpha < 1.0) {
+                        let (from_position, from_rotation) = pose_to_glam(previous);
+                        position = from_position.lerp(position, alpha);
+                        rotation = from_rotation.slerp(rotation, alpha);
+                    }
+                    transform.position = position;
+                    transform.rotation = rotation;
                     transform.dirty = true;
                 }
             }
         }
     }
 
-    /// Cast a ray and return the first hit.
+    /// Cast a ray and return the first hit that passes the filter.
     pub fn raycast(
         &self,
         origin: Vec3,
         direction: Vec3,
         max_distance: f32,
+        filter: &EntityQueryFilter,
     ) -> Option<(hecs::Entity, f32, Vec3)> {
         let ray = Ray::new(
             point![origin.x, origin.y, origin.z],
             vector![direction.x, direction.y, direction.z],
         );
 
-        if let Some((handle, intersection)) = self.query_pipeline.cast_ray_and_get_normal(
-            &self.rigid_body_set,
-            &self.collider_set,
-            &ray,
-            max_distance,
-            true,
-            QueryFilter::default(),
-        ) {
-            if let Some(&entity) = self.collider_to_entity.get(&handle) {
-                let normal = Vec3::new(
-                    intersection.normal.x,
-                    intersection.normal.y,
-                    intersection.normal.z,
-                );
-                return Some((entity, intersection.time_of_impact, normal));
-            }
-        }
-        None
+        let (entity, intersection) = self.cast_ray_filtered(&ray, max_distance, filter)?;
+        let normal = Vec3::new(
+            intersection.normal.x,
+            intersection.normal.y,
+            intersection.normal.z,
+        );
+        Some((entity, intersection.time_of_impact, normal))
     }
 
     /// Cast a ray and return the first hit with detailed info including entity, distance, hit point, and normal.
-    /// Optionally excludes a specific entity from the results.
     pub fn raycast_detailed(
         &self,
         origin: Vec3,
         direction: Vec3,
         max_distance: f32,
-        exclude_entity: Option<hecs::Entity>,
+        filter: &EntityQueryFilter,
     ) -> Option<(hecs::Entity, f32, Vec3, Vec3)> {
         let dir = direction.normalize_or_zero();
         let ray = Ray::new(
@@ -434,57 +863,44 @@ impl PhysicsWorld {
             vector![dir.x, dir.y, dir.z],
         );
 
-        // Find the collider handles to exclude based on entity
-        let exclude_colliders: Vec<ColliderHandle> = if let Some(excl) = exclude_entity {
-            self.collider_to_entity
-                .iter()
-                .filter(|(_, &e)| e == excl)
-                .map(|(&h, _)| h)
-                .collect()
-        } else {
-            Vec::new()
-        };
+        let (entity, intersection) = self.cast_ray_filtered(&ray, max_distance, filter)?;
+        let toi = intersection.time_of_impact;
+        let hit_point = origin + dir * toi;
+        let normal = Vec3::new(
+            intersection.normal.x,
+            intersection.normal.y,
+            intersection.normal.z,
+        );
+        Some((entity, toi, hit_point, normal))
+    }
 
-        // Use cast_ray_and_get_normal with a filter
-        let filter = if let Some(excl) = exclude_entity {
-            // Find the rigid body handle for the excluded entity
-            let excl_rb: Option<RigidBodyHandle> = self.body_to_entity
-                .iter()
-                .find(|(_, &e)| e == excl)
-                .map(|(&h, _)| h);
-            if let Some(rb) = excl_rb {
-                QueryFilter::default().exclude_rigid_body(rb)
-            } else {
-                QueryFilter::default()
-            }
-        } else {
-            QueryFilter::default()
+    /// Shared ray query: translates an `EntityQueryFilter` into a rapier `QueryFilter`.
+    fn cast_ray_filtered(
+        &self,
+        ray: &Ray,
+        max_distance: f32,
+        filter: &EntityQueryFilter,
+    ) -> Option<(hecs::Entity, RayIntersection)> {
+        let predicate = |handle: ColliderHandle, _: &rapier3d::geometry::Collider| {
+            self.collider_to_entity
+                .get(&handle)
+                .is_some_and(|&entity| filter.allows(entity))
         };
+        let mut query_filter = QueryFilter::default().predicate(&predicate);
+        if filter.exclude_triggers {
+            query_filter = query_filter.exclude_sensors();
+        }
 
-        if let Some((handle, intersection)) = self.query_pipeline.cast_ray_and_get_normal(
+        let (handle, intersection) = self.query_pipeline.cast_ray_and_get_normal(
             &self.rigid_body_set,
             &self.collider_set,
-            &ray,
+            ray,
             max_distance,
             true,
-            filter,
-        ) {
-            // Double check collider isn't in exclude list
-            if exclude_colliders.contains(&handle) {
-                return None;
-            }
-            if let Some(&entity) = self.collider_to_entity.get(&handle) {
-                let toi = intersection.time_of_impact;
-                let hit_point = origin + dir * toi;
-                let normal = Vec3::new(
-                    intersection.normal.x,
-                    intersection.normal.y,
-                    intersection.normal.z,
-                );
-                return Some((entity, toi, hit_point, normal));
-            }
-        }
-        None
+            query_filter,
+        )?;
+        let entity = *self.collider_to_entity.get(&handle)?;
+        Some((entity, intersection))
     }
 
     /// Set the linear velocity of a rigid body and optionally disable gravity.
@@ -543,6 +959,60 @@ impl PhysicsWorld {
         }
     }
 
+    /// Replace a collider with one of a new shape on the same body. Trigger
+    /// flag, restitution and friction keep their current values unless given;
+    /// the body's mass is preserved. Returns the new handle, or None if the
+    /// old collider doesn't exist or has no parent body.
+    pub fn replace_collider(
+        &mut self,
+        old: ColliderHandle,
+        shape: &PhysicsShape,
+        is_trigger: Option<bool>,
+        restitution: Option<f32>,
+        friction: Option<f32>,
+    ) -> Option<ColliderHandle> {
+        let old_collider = self.collider_set.get(old)?;
+        let rb_handle = old_collider.parent()?;
+        let is_trigger = is_trigger.unwrap_or(old_collider.is_sensor());
+        let mut builder = shape_to_collider(shape)
+            .sensor(is_trigger)
+            .restitution(restitution.unwrap_or(old_collider.restitution()))
+            .friction(friction.unwrap_or(old_collider.friction()))
+            .mass(old_collider.mass());
+        if is_trigger {
+            builder = builder.active_collision_types(ActiveCollisionTypes::all());
+        } else {
+            builder = builder.active_collision_types(old_collider.active_collision_types());
+        }
+
+        let entity = self.collider_to_entity.remove(&old);
+        self.collider_set
+            .remove(old, &mut self.island_manager, &mut self.rigid_body_set, true);
+        let new = self
+            .collider_set
+            .insert_with_parent(builder.build(), rb_handle, &mut self.rigid_body_set);
+        if let Some(entity) = entity {
+            self.collider_to_entity.insert(new, entity);
+        }
+
+        // Carry ongoing contacts and stay timers over so the swap doesn't fire fresh Enter events
+        let remap = |pairs: &HashSet<[ColliderHandle; 2]>| -> HashSet<[ColliderHandle; 2]> {
+            pairs
+                .iter()
+                .map(|pair| pair.map(|h| if h == old { new } else { h }))
+                .collect()
+        };
+        self.active_contact_pairs = remap(&self.active_contact_pairs);
+        self.active_intersections = remap(&self.active_intersections);
+        self.trigger_stay_elapsed = self
+            .trigger_stay_elapsed
+            .iter()
+            .map(|(pair, &elapsed)| (pair.map(|h| if h == old { new } else { h }), elapsed))
+            .collect();
+
+        Some(new)
+    }
+
     /// Remove a body and its colliders.
     pub fn remove_body(&mut self, rb_handle: RigidBodyHandle) {
         self.rigid_body_set.remove(
@@ -554,9 +1024,16 @@ impl PhysicsWorld {
             true,
         );
         self.body_to_entity.remove(&rb_handle);
+        self.previous_poses.remove(&rb_handle);
+        self.snapped.remove(&rb_handle);
     }
 }
 
+fn pose_to_glam(pose: &Isometry<Real>) -> (Vec3, Quat) {
+    let (pos, rot) = (pose.translation, pose.rotation);
+    (Vec3::new(pos.x, pos.y, pos.z), Quat::from_xyzw(rot.i, rot.j, rot.k, rot.w))
+}
+
 fn shape_to_collider(shape: &PhysicsShape) -> ColliderBuilder {
     match shape {
         PhysicsShape::Box { half_extents } => {
@@ -577,6 +1054,10 @@ fn shape_to_collider(shape: &PhysicsShape) -> ColliderBuilder {
             };
             ColliderBuilder::convex_decomposition_with_params(vertices, indices, &params)
         }
+        PhysicsShape::Heightfield { heights, rows, cols, scale } => {
+            let heights = rapier3d::na::DMatrix::from_fn(*rows, *cols, |row, col| heights[row * *cols + col]);
+            ColliderBuilder::heightfield(heights, vector![scale.x, scale.y, scale.z])
+        }
     }
 }
 
@@ -596,6 +1077,136 @@ mod tests {
         assert_eq!(pw.collider_set.len(), 0);
     }
 
+    #[test]
+    fn test_water_volume_submerged_fraction() {
+        let volume = WaterVolume {
+            center: Vec3::new(0.0, 1.0, 0.0),
+            rotation: Quat::IDENTITY,
+            half_extents: [5.0, 5.0],
+            depth: 3.0,
+            buoyancy: 1.5,
+            drag: 1.0,
+        };
+        assert_eq!(volume.submerged_fraction(Vec3::new(0.0, 1.0, 0.0), 0.5, 1.5), 0.5);
+        assert_eq!(volume.submerged_fraction(Vec3::new(0.0, 0.0, 0.0), -0.5, 0.5), 1.0);
+        assert_eq!(volume.submerged_fraction(Vec3::new(0.0, 3.0, 0.0), 2.5, 3.5), 0.0);
+        // Outside the surface's extent, or below the volume
+        assert_eq!(volume.submerged_fraction(Vec3::new(6.0, 0.0, 0.0), -0.5, 0.5), 0.0);
+        assert_eq!(volume.submerged_fraction(Vec3::new(0.0, -5.0, 0.0), -5.5, -4.5), 0.0);
+    }
+
+    #[test]
+    fn test_buoyancy_floats_dynamic_body() {
+        let mut world = hecs::World::new();
+        let entity = world.spawn(());
+        let water = world.spawn(());
+        let mut pw = PhysicsWorld::new(Vec3::new(0.0, -9.81, 0.0));
+        let (rb, _) = pw.add_dynamic_body(
+            entity,
+            Vec3::new(0.0, -1.0, 0.0),
+            Quat::IDENTITY,
+            PhysicsShape::Sphere { radius: 0.5 },
+            1.0,
+            0.0,
+            0.5,
+            false,
+        );
+        pw.water_volumes.insert(
+            water,
+            WaterVolume {
+                center: Vec3::ZERO,
+                rotation: Quat::IDENTITY,
+                half_extents: [10.0, 10.0],
+                depth: 10.0,
+                buoyancy: 2.0,
+                drag: 0.5,
+            },
+        );
+        for _ in 0..10 {
+            pw.step(1.0 / 60.0);
+        }
+        assert!(pw.get_linvel(rb).unwrap().y > 0.0, "submerged body should rise");
+    }
+
+    #[test]
+    fn test_fixed_timestep_advance() {
+        let mut timestep = FixedTimestep::new(4.0);
+        assert_eq!(timestep.advance(0.375), 1);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(0.125), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+        // A long hitch is capped and its backlog dropped
+        assert_eq!(timestep.advance(10.0), MAX_STEPS_PER_FRAME);
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(FixedTimestep::new(0.0).alpha(), 1.0);
+    }
+
+    #[test]
+    fn test_fixed_timestep_interpolates_transforms() {
+        let mut world = hecs::World::new();
+        let ball = world.spawn((Transform::default(),));
+        let snapped = world.spawn((Transform::default(),));
+        let mut pw = PhysicsWorld::new(Vec3::ZERO);
+        pw.timestep = FixedTimestep::new(10.0);
+        let sphere = PhysicsShape::Sphere { radius: 0.5 };
+        let (ball_rb, _) = pw.add_dynamic_body(ball, Vec3::ZERO, Quat::IDENTITY, sphere.clone(), 1.0, 0.0, 0.5, false);
+        let (snapped_rb, _) = pw.add_dynamic_body(snapped, Vec3::new(0.0, 5.0, 0.0), Quat::IDENTITY, sphere, 1.0, 0.0, 0.5, false);
+        pw.set_interpolated(snapped_rb, false);
+        pw.set_linvel(ball_rb, Vec3::new(10.0, 0.0, 0.0), false);
+        pw.set_linvel(snapped_rb, Vec3::new(10.0, 0.0, 0.0), false);
+
+        // 0.15 s is one 0.1 s step plus half of the next
+        pw.advance(0.15);
+        pw.sync_to_ecs(&mut world);
+        let ball_x = world.get::<&Transform>(ball).unwrap().position.x;
+        let snapped_x = world.get::<&Transform>(snapped).unwrap().position.x;
+        assert!((snapped_x - 1.0).abs() < 1e-3, "snapped body at the latest step, got {}", snapped_x);
+        assert!((ball_x - 0.5).abs() < 1e-3, "ball halfway between its steps, got {}", ball_x);
+
+        // A frame without a step still moves it along
+        pw.advance(0.04);
+        pw.sync_to_ecs(&mut world);
+        assert!((world.get::<&Transform>(ball).unwrap().position.x - 0.9).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_headless_spawn_keeps_interpolate_flag() {
+        let yaml = r#"
+name: "Interpolate"
+entities:
+  - id: platform
+    components:
+      transform:
+        position: [0, 1, 0]
+      rigid_body:
+        body_type: kinematic
+        interpolate: false
+      collider:
+        shape: box
+  - id: crate
+    components:
+      rigid_body:
+        body_type: dynamic
+        interpolate: false
+      collider:
+        shape: sphere
+        radius: 0.5
+"#;
+        let scene: crate::scene::SceneFile = serde_yaml::from_str(yaml).unwrap();
+        let mut scene_world = crate::world::SceneWorld::new();
+        let mut pw = PhysicsWorld::new(Vec3::ZERO);
+        crate::world::spawn_all_entities_headless(&mut scene_world, &scene, std::path::Path::new("."), &mut pw);
+
+        for id in ["platform", "crate"] {
+            let entity = scene_world.entity_registry[id];
+            let body = scene_world.world.get::<&RigidBody>(entity).unwrap();
+            assert!(pw.snapped.contains(&body.handle), "{} should not be interpolated", id);
+        }
+        let platform = scene_world.entity_registry["platform"];
+        let body = scene_world.world.get::<&RigidBody>(platform).unwrap();
+        assert_eq!(body.body_type, PhysicsBodyType::Kinematic);
+    }
+
     #[test]
     fn test_add_static_body() {
         let mut world = hecs::World::new();
@@ -643,10 +1254,171 @@ mod tests {
         pw.query_pipeline.update(&pw.collider_set);
 
         // Raycast down
-        let result = pw.raycast(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 100.0);
+        let result = pw.raycast(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 100.0, &EntityQueryFilter::default());
         assert!(result.is_some());
         let (hit_entity, distance, _normal) = result.unwrap();
         assert_eq!(hit_entity, entity);
         assert!(distance > 0.0);
     }
+
+    #[test]
+    fn test_raycast_filters() {
+        let mut world = hecs::World::new();
+        let floor = world.spawn(());
+        let shooter = world.spawn(());
+        let zone = world.spawn(());
+        let mut pw = PhysicsWorld::new(Vec3::new(0.0, -9.81, 0.0));
+
+        let box_shape = |y: f32| PhysicsShape::Box { half_extents: Vec3::new(1.0, y, 1.0) };
+        pw.add_static_body(floor, Vec3::new(0.0, -1.0, 0.0), Quat::IDENTITY, box_shape(0.5), false, 0.0, 0.5);
+        pw.add_static_body(zone, Vec3::new(0.0, 1.0, 0.0), Quat::IDENTITY, box_shape(0.5), true, 0.0, 0.5);
+        pw.add_static_body(shooter, Vec3::new(0.0, 4.0, 0.0), Quat::IDENTITY, box_shape(0.5), false, 0.0, 0.5);
+        pw.query_pipeline.update(&pw.collider_set);
+
+        let origin = Vec3::new(0.0, 4.0, 0.0);
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let hit = |filter: &EntityQueryFilter| pw.raycast(origin, down, 100.0, filter).map(|(e, _, _)| e);
+
+        // Ray starts inside the shooter
+        assert_eq!(hit(&EntityQueryFilter::default()), Some(shooter));
+
+        let mut filter = EntityQueryFilter::default();
+        filter.exclude.insert(shooter);
+        assert_eq!(hit(&filter), Some(zone));
+
+        filter.exclude_triggers = true;
+        assert_eq!(hit(&filter), Some(floor));
+
+        let only_zone = EntityQueryFilter {
+            include_only: Some([zone].into_iter().collect()),
+            ..Default::default()
+        };
+        assert_eq!(hit(&only_zone), Some(zone));
+    }
+
+    #[test]
+    fn test_character_pushes_by_mass_ratio() {
+        let mut world = hecs::World::new();
+        let player = world.spawn(());
+        let light = world.spawn(());
+        let heavy = world.spawn(());
+        let push = CharacterPush { force: 400.0, mass: 80.0 };
+        let crate_shape = PhysicsShape::Box { half_extents: Vec3::splat(0.5) };
+
+        // Walk a character into a crate for one second and return how far the
+        // crate and the character moved
+        let push_crate = |entity: hecs::Entity, mass: f32| {
+            let mut pw = PhysicsWorld::new(Vec3::ZERO);
+            let (rb, col) = pw.add_character_body(player, Vec3::new(0.0, 0.0, 0.0), 0.5, 0.3);
+            let (crate_rb, _) = pw.add_dynamic_body(
+                entity, Vec3::new(1.0, 0.0, 0.0), Quat::IDENTITY, crate_shape.clone(), mass, 0.0, 0.0, false,
+            );
+            for _ in 0..60 {
+                pw.query_pipeline.update(&pw.collider_set);
+                pw.move_character(rb, col, Vec3::new(0.1, 0.0, 0.0), 1.0 / 60.0, push);
+                pw.step(1.0 / 60.0);
+            }
+            (pw.rigid_body_set[crate_rb].translation().x - 1.0, pw.rigid_body_set[rb].translation().x)
+        };
+
+        let (light_moved, _) = push_crate(light, 10.0);
+        let (heavy_moved, walked) = push_crate(heavy, 1000.0);
+        assert!(light_moved > 1.0, "light crate moved {light_moved}");
+        assert!(heavy_moved < light_moved * 0.2, "heavy crate moved {heavy_moved}");
+        // The heavy crate holds the character back to its own pace
+        assert!(walked < 6.0 * push.speed_share(1000.0) + 0.3, "character walked {walked}");
+        assert!((push.speed_share(80.0) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_trigger_enter_stay_exit() {
+        let mut world = hecs::World::new();
+        let zone = world.spawn(());
+        let player = world.spawn(());
+        let mut pw = PhysicsWorld::new(Vec3::ZERO);
+
+        let shape = PhysicsShape::Box { half_extents: Vec3::splat(2.0) };
+        pw.add_static_body(zone, Vec3::ZERO, Quat::IDENTITY, shape, true, 0.0, 0.5);
+        let (player_rb, _) = pw.add_character_body(player, Vec3::ZERO, 0.5, 0.3);
+        let phases = |pw: &PhysicsWorld| pw.trigger_events.iter().map(|e| e.phase).collect::<Vec<_>>();
+
+        pw.step(0.04);
+        assert_eq!(phases(&pw), vec![TriggerPhase::Enter]);
+        assert_eq!(pw.trigger_events[0].trigger_entity, zone);
+        assert_eq!(pw.volume_contents(zone), vec![player]);
+
+        // Stay is rate-limited to trigger_stay_interval
+        pw.step(0.04);
+        assert!(phases(&pw).is_empty());
+        pw.step(0.04);
+        assert!(matches!(phases(&pw)[..], [TriggerPhase::Stay { elapsed }] if (elapsed - 0.12).abs() < 1e-4));
+
+        pw.rigid_body_set[player_rb].set_next_kinematic_translation(vector![10.0, 0.0, 0.0]);
+        pw.step(0.04);
+        assert_eq!(phases(&pw), vec![TriggerPhase::Exit]);
+        assert!(pw.volume_contents(zone).is_empty());
+    }
+
+    #[test]
+    fn test_trigger_stay_timed_per_pair() {
+        let mut world = hecs::World::new();
+        let zone = world.spawn(());
+        let first = world.spawn(());
+        let second = world.spawn(());
+        let mut pw = PhysicsWorld::new(Vec3::ZERO);
+
+        let shape = PhysicsShape::Box { half_extents: Vec3::splat(2.0) };
+        pw.add_static_body(zone, Vec3::ZERO, Quat::IDENTITY, shape, true, 0.0, 0.5);
+        pw.add_character_body(first, Vec3::ZERO, 0.5, 0.3);
+        let (second_rb, _) = pw.add_character_body(second, Vec3::new(10.0, 0.0, 0.0), 0.5, 0.3);
+        let stays = |pw: &PhysicsWorld| {
+            pw.trigger_events
+                .iter()
+                .filter(|e| matches!(e.phase, TriggerPhase::Stay { .. }))
+                .map(|e| e.other_entity)
+                .collect::<Vec<_>>()
+        };
+
+        pw.step(0.04);
+        pw.rigid_body_set[second_rb].set_next_kinematic_translation(vector![0.5, 0.0, 0.0]);
+        pw.step(0.04);
+        // Each pair stays on its own schedule, counted from its enter
+        pw.step(0.04);
+        assert_eq!(stays(&pw), vec![first]);
+        pw.step(0.04);
+        assert_eq!(stays(&pw), vec![second]);
+        pw.step(0.04);
+        assert!(stays(&pw).is_empty());
+        pw.step(0.04);
+        assert_eq!(stays(&pw), vec![first]);
+    }
+
+    #[test]
+    fn test_replace_collider_keeps_body_and_overlaps() {
+        let mut world = hecs::World::new();
+        let zone = world.spawn(());
+        let player = world.spawn(());
+        let mut pw = PhysicsWorld::new(Vec3::ZERO);
+
+        let shape = PhysicsShape::Box { half_extents: Vec3::splat(2.0) };
+        pw.add_static_body(zone, Vec3::ZERO, Quat::IDENTITY, shape, true, 0.0, 0.5);
+        let (player_rb, old) = pw.add_character_body(player, Vec3::ZERO, 0.5, 0.3);
+        pw.step(0.04);
+        assert_eq!(pw.trigger_events.len(), 1);
+
+        // Crouch: shorter capsule on the same body, still inside the zone
+        let crouched = PhysicsShape::Capsule { half_height: 0.2, radius: 0.3 };
+        let new = pw.replace_collider(old, &crouched, None, None, Some(0.9)).unwrap();
+        assert!(pw.collider_set.get(old).is_none());
+        assert_eq!(pw.collider_set[new].parent(), Some(player_rb));
+        assert_eq!(pw.collider_set[new].friction(), 0.9);
+        assert!(!pw.collider_set[new].is_sensor());
+        assert_eq!(pw.collider_to_entity.get(&new), Some(&player));
+        assert!(!pw.collider_to_entity.contains_key(&old));
+
+        pw.step(0.04);
+        assert!(pw.trigger_events.iter().all(|e| e.phase != TriggerPhase::Enter));
+        assert_eq!(pw.volume_contents(zone), vec![player]);
+        assert!(pw.replace_collider(old, &crouched, None, None, None).is_none());
+    }
 }
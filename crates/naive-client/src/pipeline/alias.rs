@@ -0,0 +1,169 @@
+//! Transient resource aliasing. Each resource lives from the first pass that
+//! uses it to the last, in execution order; resources whose lifetimes don't
+//! overlap and that share format, size and sample count are given the same
+//! texture, so a pipeline needs about as many textures as it has resources
+//! alive at once.
+//!
+//! A resource keeps a texture of its own when its contents must survive
+//! between frames or past the passes that name it:
+//! - it is read (or drawn over without a clear) before any pass clears it,
+//!   like a TAA history or a target only skybox or water passes write;
+//! - no pass uses it.
+
+use std::collections::HashMap;
+
+use super::def::{LoadKind, PassDef, PipelineError, PipelineFile};
+use super::resource::{parse_resource_size, ResourceSize};
+
+/// Pass types that draw over their targets unless the YAML clears them.
+const DRAWS_OVER: &[&str] = &["skybox", "water", "particles", "compute"];
+
+/// Span of passes, by execution position, that use one resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Lifetime {
+    first: usize,
+    last: usize,
+    /// The first use reads contents left from before the pass.
+    reads_first: bool,
+}
+
+/// Pass indices in the order their commands run: shadow passes are recorded
+/// into the prepass encoder, which is submitted before the rest.
+pub fn execution_order(passes: &[PassDef], pass_order: &[usize]) -> Vec<usize> {
+    let (shadow, main): (Vec<usize>, Vec<usize>) =
+        pass_order.iter().partition(|&&i| passes[i].pass_type == "shadow");
+    shadow.into_iter().chain(main).collect()
+}
+
+/// Whether `pass` clears the target in output `slot` before drawing.
+fn clears(pass: &PassDef, slot: &str) -> bool {
+    match pass.targets.get(slot) {
+        Some(ops) => ops.load == LoadKind::Clear,
+        None => !DRAWS_OVER.contains(&pass.pass_type.as_str()),
+    }
+}
+
+fn lifetimes(passes: &[PassDef], order: &[usize]) -> HashMap<String, Lifetime> {
+    let mut lifetimes: HashMap<String, Lifetime> = HashMap::new();
+    for (position, &index) in order.iter().enumerate() {
+        let pass = &passes[index];
+        let reads = pass.inputs.values().map(|name| (name, true));
+        let writes = pass.outputs.iter().map(|(slot, name)| (name, !clears(pass, slot)));
+        for (name, reads_contents) in reads.chain(writes) {
+            if name == "auto" || name == "swapchain" {
+                continue;
+            }
+            lifetimes
+                .entry(name.clone())
+                .and_modify(|lifetime| {
+                    if lifetime.first == position {
+                        lifetime.reads_first |= reads_contents;
+                    }
+                    lifetime.last = position;
+                })
+                .or_insert(Lifetime { first: position, last: position, reads_first: reads_contents });
+        }
+    }
+    lifetimes
+}
+
+/// Pick which resources share a texture: maps each aliased resource to the
+/// resource whose texture it uses. `sample_counts` and `format_overrides` are
+/// those the resources are allocated with.
+pub fn plan_aliases(
+    pipeline_file: &PipelineFile,
+    pass_order: &[usize],
+    sample_counts: &HashMap<String, u32>,
+    format_overrides: &HashMap<String, wgpu::TextureFormat>,
+) -> Result<HashMap<String, String>, PipelineError> {
+    let order = execution_order(&pipeline_file.passes, pass_order);
+    let lifetimes = lifetimes(&pipeline_file.passes, &order);
+
+    let mut candidates = Vec::new();
+    for def in &pipeline_file.resources {
+        let Some(&lifetime) = lifetimes.get(&def.name) else { continue };
+        if lifetime.reads_first {
+            continue;
+        }
+        let format = match format_overrides.get(&def.name) {
+            Some(&format) => format,
+            None => def.texture_format()?,
+        };
+        let size = match parse_resource_size(&def.size) {
+            ResourceSize::Viewport => (0, 0, 0),
+            ResourceSize::ViewportDiv(d) => (1, d, 0),
+            ResourceSize::Fixed(w, h) => (2, w, h),
+        };
+        let samples = sample_counts.get(&def.name).copied().unwrap_or(1);
+        candidates.push((&def.name, lifetime, (format, size, samples)));
+    }
+    candidates.sort_by_key(|(name, lifetime, _)| (lifetime.first, *name));
+
+    // Textures handed out so far: owner, what fits it, and its last use
+    let mut textures: Vec<(&String, _, usize)> = Vec::new();
+    let mut aliases = HashMap::new();
+    for (name, lifetime, key) in candidates {
+        match textures.iter_mut().find(|(_, k, last)| *k == key && *last < lifetime.first) {
+            Some((owner, _, last)) => {
+                aliases.insert(name.clone(), (*owner).clone());
+                *last = lifetime.last;
+            }
+            None => textures.push((name, key, lifetime.last)),
+        }
+    }
+    Ok(aliases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(yaml: &str) -> PipelineFile {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_plan_aliases() {
+        let file = pipeline(
+            r#"
+version: 1
+resources:
+  - { name: shadow_map, type: texture_2d, format: depth32f, size: "[1024, 1024]" }
+  - { name: light_map, type: texture_2d, format: depth32f, size: "[1024, 1024]" }
+  - { name: albedo, type: texture_2d, format: rgba8, size: viewport }
+  - { name: lit, type: texture_2d, format: rgba16f, size: viewport }
+  - { name: ldr, type: texture_2d, format: rgba8, size: viewport }
+  - { name: blurred, type: texture_2d, format: rgba8, size: viewport }
+  - { name: history, type: texture_2d, format: rgba8, size: viewport }
+  - { name: unused, type: texture_2d, format: rgba8, size: viewport }
+passes:
+  - { name: geometry, type: rasterize, shader: g.wgsl, outputs: { color: albedo } }
+  - { name: shadow, type: shadow, shader: s.wgsl, outputs: { depth: shadow_map } }
+  - { name: lighting, type: fullscreen, shader: l.wgsl, inputs: { albedo: albedo, shadow: shadow_map }, outputs: { color: lit } }
+  - { name: bake, type: fullscreen, shader: b.wgsl, inputs: { lit: lit }, outputs: { depth: light_map } }
+  - { name: tonemap, type: fullscreen, shader: t.wgsl, inputs: { hdr: lit, light: light_map }, outputs: { color: ldr } }
+  - { name: blur, type: fullscreen, shader: b.wgsl, inputs: { ldr: ldr, history: history }, outputs: { color: blurred } }
+  - { name: sky, type: skybox, inputs: {}, outputs: { color: blurred } }
+  - { name: present, type: fullscreen, shader: p.wgsl, inputs: { color: blurred }, outputs: { color: swapchain } }
+"#,
+        );
+        let order = super::super::def::build_dag(&file.passes).unwrap();
+        let shadow = file.passes.iter().position(|p| p.name == "shadow").unwrap();
+        assert_eq!(execution_order(&file.passes, &order)[0], shadow);
+
+        let aliases = plan_aliases(&file, &order, &HashMap::new(), &HashMap::new()).unwrap();
+        // The shadow map is done with after lighting; ldr starts after albedo's last read
+        assert_eq!(aliases.get("light_map").map(String::as_str), Some("shadow_map"));
+        assert_eq!(aliases.get("ldr").map(String::as_str), Some("albedo"));
+        // blurred is written while ldr is read; history is read before any write
+        assert!(!aliases.contains_key("blurred"));
+        assert!(!aliases.contains_key("history") && !aliases.values().any(|v| v == "history"));
+        assert!(!aliases.contains_key("unused"));
+        assert_eq!(aliases.len(), 2);
+
+        // A different sample count keeps ldr apart from albedo
+        let samples = HashMap::from([("albedo".to_string(), 4)]);
+        let aliases = plan_aliases(&file, &order, &samples, &HashMap::new()).unwrap();
+        assert!(!aliases.contains_key("ldr"));
+    }
+}
@@ -0,0 +1,316 @@
+//! Bloom mip chain (Jimenez 2014, as in Call of Duty: Advanced Warfare).
+//!
+//! The bloom pass's own shader thresholds the HDR buffer into its target.
+//! The chain then downsamples that target level by level with a 13-tap
+//! filter and walks back up with a tent filter, adding each level onto the
+//! next larger one. The last step lands on the pass target scaled by the
+//! intensity, so the tonemap pass reads the finished bloom.
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use super::def::BloomSettings;
+use super::resource::GpuResource;
+
+/// Uniforms of the bloom extraction shader (binding 2 of its group 0).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct BloomParams {
+    pub threshold: f32,
+    pub knee: f32,
+    pub intensity: f32,
+    pub _pad: f32,
+}
+
+impl BloomParams {
+    pub fn new(settings: &BloomSettings) -> Self {
+        Self {
+            threshold: settings.threshold.max(0.0),
+            knee: settings.knee.max(0.0),
+            intensity: settings.intensity.max(0.0),
+            _pad: 0.0,
+        }
+    }
+}
+
+/// Sizes of the chain's levels below a `width` x `height` target: each half
+/// the previous, `mips - 1` of them (the target is the first level), stopping
+/// before a side would drop below one pixel. Always at least one level, so
+/// the intensity is applied.
+pub fn mip_sizes(width: u32, height: u32, mips: u32) -> Vec<(u32, u32)> {
+    let mut sizes = Vec::new();
+    let (mut w, mut h) = (width, height);
+    while sizes.len() + 1 < mips.max(2) as usize && (w > 1 || h > 1) {
+        w = (w / 2).max(1);
+        h = (h / 2).max(1);
+        sizes.push((w, h));
+    }
+    if sizes.is_empty() {
+        sizes.push((1, 1));
+    }
+    sizes
+}
+
+/// One level below the target.
+struct BloomMip {
+    view: wgpu::TextureView,
+    /// Samples this level.
+    bind_group: wgpu::BindGroup,
+}
+
+/// The chain's pipelines and levels. Levels follow the target's size and are
+/// rebuilt by `resize`.
+pub struct BloomChain {
+    pub settings: BloomSettings,
+    /// `BloomParams` for the extraction shader.
+    pub params_buffer: wgpu::Buffer,
+    /// Resource the bloom pass writes (first level of the chain).
+    pub target: String,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    downsample_pipeline: wgpu::RenderPipeline,
+    /// Adds onto a smaller level's parent.
+    upsample_pipeline: wgpu::RenderPipeline,
+    /// Final step onto the target: (target + upsampled) * intensity.
+    composite_pipeline: wgpu::RenderPipeline,
+    target_bind_group: Option<wgpu::BindGroup>,
+    mips: Vec<BloomMip>,
+}
+
+impl BloomChain {
+    pub fn new(
+        device: &wgpu::Device,
+        settings: BloomSettings,
+        target: &str,
+        resources: &HashMap<String, GpuResource>,
+    ) -> Self {
+        let format = resources.get(target).map_or(wgpu::TextureFormat::Rgba16Float, |r| r.format);
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Bloom Params"),
+            contents: bytemuck::cast_slice(&[BloomParams::new(&settings)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom Chain Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Chain Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Chain Shader"),
+            source: wgpu::ShaderSource::Wgsl(crate::shader::get_bloom_chain_wgsl().into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Chain Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label: &str, entry_point: &str, blend: wgpu::BlendState| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let blend = |factor| wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: factor,
+                dst_factor: factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let downsample_pipeline =
+            pipeline("Bloom Downsample Pipeline", "fs_downsample", wgpu::BlendState::REPLACE);
+        let upsample_pipeline =
+            pipeline("Bloom Upsample Pipeline", "fs_upsample", blend(wgpu::BlendFactor::One));
+        let composite_pipeline =
+            pipeline("Bloom Composite Pipeline", "fs_upsample", blend(wgpu::BlendFactor::Constant));
+
+        let mut chain = Self {
+            settings,
+            params_buffer,
+            target: target.to_string(),
+            bind_group_layout,
+            sampler,
+            downsample_pipeline,
+            upsample_pipeline,
+            composite_pipeline,
+            target_bind_group: None,
+            mips: Vec::new(),
+        };
+        chain.resize(device, resources);
+        chain
+    }
+
+    /// Recreate the levels for the target's current size.
+    pub fn resize(&mut self, device: &wgpu::Device, resources: &HashMap<String, GpuResource>) {
+        let Some(target) = resources.get(&self.target) else {
+            self.target_bind_group = None;
+            self.mips.clear();
+            return;
+        };
+        self.target_bind_group = Some(self.sampling_bind_group(device, &target.view));
+        let size = target.texture.size();
+        self.mips = mip_sizes(size.width, size.height, self.settings.mips)
+            .into_iter()
+            .map(|(width, height)| {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Bloom Mip"),
+                    size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: target.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                let bind_group = self.sampling_bind_group(device, &view);
+                BloomMip { view, bind_group }
+            })
+            .collect();
+    }
+
+    fn sampling_bind_group(&self, device: &wgpu::Device, view: &wgpu::TextureView) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Bloom Chain Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+            ],
+        })
+    }
+
+    /// Blur the extracted bloom in `target` down and back up the chain. Runs
+    /// right after the bloom pass.
+    pub fn record(&self, encoder: &mut wgpu::CommandEncoder, resources: &HashMap<String, GpuResource>) {
+        let (Some(target), Some(target_bind_group)) = (resources.get(&self.target), &self.target_bind_group)
+        else {
+            return;
+        };
+
+        // Down: target -> mips[0] -> mips[1] -> ...
+        let mut source = target_bind_group;
+        for mip in &self.mips {
+            blit(encoder, "bloom_downsample", &mip.view, &self.downsample_pipeline, source, None);
+            source = &mip.bind_group;
+        }
+
+        // Up: add each level onto its parent; the last step lands on the target.
+        for pair in self.mips.windows(2).rev() {
+            blit(encoder, "bloom_upsample", &pair[0].view, &self.upsample_pipeline, &pair[1].bind_group, None);
+        }
+        let intensity = BloomParams::new(&self.settings).intensity as f64;
+        blit(
+            encoder,
+            "bloom_composite",
+            &target.view,
+            &self.composite_pipeline,
+            &self.mips[0].bind_group,
+            Some(wgpu::Color { r: intensity, g: intensity, b: intensity, a: 1.0 }),
+        );
+    }
+}
+
+/// Draw a fullscreen triangle into `view`, keeping its contents for blending.
+fn blit(
+    encoder: &mut wgpu::CommandEncoder,
+    label: &str,
+    view: &wgpu::TextureView,
+    pipeline: &wgpu::RenderPipeline,
+    source: &wgpu::BindGroup,
+    blend_constant: Option<wgpu::Color>,
+) {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(0, source, &[]);
+    if let Some(color) = blend_constant {
+        render_pass.set_blend_constant(color);
+    }
+    render_pass.draw(0..3, 0..1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mip_sizes_halve_and_stop_at_one_pixel() {
+        assert_eq!(mip_sizes(640, 360, 4), vec![(320, 180), (160, 90), (80, 45)]);
+        assert_eq!(mip_sizes(4, 2, 10), vec![(2, 1), (1, 1)]);
+        // One level is kept so the intensity still applies.
+        assert_eq!(mip_sizes(640, 360, 0), vec![(320, 180)]);
+        assert_eq!(mip_sizes(1, 1, 6), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_bloom_params_clamp_negative_values() {
+        let params = BloomParams::new(&BloomSettings { threshold: -1.0, knee: 0.2, intensity: -0.5, mips: 6 });
+        assert_eq!(params.threshold, 0.0);
+        assert_eq!(params.knee, 0.2);
+        assert_eq!(params.intensity, 0.0);
+    }
+}
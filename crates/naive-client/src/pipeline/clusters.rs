@@ -0,0 +1,214 @@
+//! Clustered point lighting.
+//!
+//! The view frustum is split into `CLUSTER_GRID` clusters: screen tiles cut
+//! into depth slices spaced exponentially between the near and far planes.
+//! Before the lighting pass a compute step lists, per cluster, the point
+//! lights whose range reaches it, and the lighting shader only shades the
+//! lights of its pixel's cluster. Hundreds of torches then cost about as much
+//! as the handful that overlap any one pixel.
+
+use super::resource::{PointLightUniform, CLUSTER_GRID, CLUSTER_STRIDE};
+
+/// Clusters in the grid.
+pub const CLUSTER_COUNT: u32 = CLUSTER_GRID[0] * CLUSTER_GRID[1] * CLUSTER_GRID[2];
+
+/// Invocations per workgroup of the clustering shader.
+const CLUSTER_WORKGROUP_SIZE: u32 = 64;
+
+/// Point light storage, per-cluster light lists and the compute step that
+/// fills them.
+pub struct LightClusters {
+    /// Point lights, `max_lights` of them; the lighting header's
+    /// `light_count` says how many are live this frame.
+    pub light_buffer: wgpu::Buffer,
+    /// `CLUSTER_STRIDE` words per cluster: a light count, then light indices.
+    pub cluster_buffer: wgpu::Buffer,
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl LightClusters {
+    /// `header_buffer` holds the `LightingUniforms` the lighting pass binds;
+    /// the clustering step reads the light count from it.
+    pub fn new(
+        device: &wgpu::Device,
+        header_buffer: &wgpu::Buffer,
+        max_lights: usize,
+        camera_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let light_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Light Storage Buffer"),
+            size: (max_lights.max(1) * std::mem::size_of::<PointLightUniform>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let cluster_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Cluster Buffer"),
+            size: (CLUSTER_COUNT * CLUSTER_STRIDE) as u64 * 4,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Light Cluster Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Cluster Bind Group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: header_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: light_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: cluster_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Light Cluster Shader"),
+            source: wgpu::ShaderSource::Wgsl(crate::shader::get_light_cluster_wgsl().into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Light Cluster Pipeline Layout"),
+            bind_group_layouts: &[camera_layout, &layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Light Cluster Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            light_buffer,
+            cluster_buffer,
+            pipeline,
+            bind_group,
+        }
+    }
+
+    /// Upload this frame's point lights (at most `max_lights`).
+    pub fn write_lights(&self, queue: &wgpu::Queue, lights: &[PointLightUniform]) {
+        if !lights.is_empty() {
+            queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(lights));
+        }
+    }
+
+    /// Rebuild the cluster light lists; record before the lighting pass.
+    pub fn record(&self, encoder: &mut wgpu::CommandEncoder, camera_bind_group: &wgpu::BindGroup) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("light_clusters"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, camera_bind_group, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.dispatch_workgroups(CLUSTER_COUNT.div_ceil(CLUSTER_WORKGROUP_SIZE), 1, 1);
+    }
+}
+
+/// Depth slice of a point `depth` in front of the camera, as the lighting
+/// shader computes it.
+pub fn depth_slice(depth: f32, near: f32, far: f32) -> u32 {
+    let slices = CLUSTER_GRID[2] as f32;
+    let slice = (depth.max(near) / near).ln() / (far / near).ln() * slices;
+    slice.clamp(0.0, slices - 1.0) as u32
+}
+
+/// View depth where depth slice `slice` starts, as the clustering shader
+/// computes it.
+pub fn slice_depth(slice: u32, near: f32, far: f32) -> f32 {
+    near * (far / near).powf(slice as f32 / CLUSTER_GRID[2] as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_depth_slices_cover_the_depth_range() {
+        let (near, far) = (0.1, 100.0);
+        assert_eq!(depth_slice(0.01, near, far), 0);
+        assert_eq!(depth_slice(near, near, far), 0);
+        assert_eq!(depth_slice(500.0, near, far), CLUSTER_GRID[2] - 1);
+        // A point in a slice falls between that slice's bounds
+        for slice in 0..CLUSTER_GRID[2] {
+            let start = slice_depth(slice, near, far);
+            let end = slice_depth(slice + 1, near, far);
+            assert_eq!(depth_slice((start + end) * 0.5, near, far), slice);
+        }
+        assert!((slice_depth(CLUSTER_GRID[2], near, far) - far).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_spot_cone_factor() {
+        // The lighting shader's cone term, for a light shining down -Y
+        let cone = |light: &PointLightUniform, to_surface: glam::Vec3| {
+            let cos = to_surface.normalize().dot(glam::Vec3::from(light.direction));
+            (cos * light.spot_scale + light.spot_offset).clamp(0.0, 1.0)
+        };
+        let spot = PointLightUniform::spot(glam::Vec3::ZERO, 10.0, glam::Vec3::ONE, 1.0, glam::Vec3::NEG_Y, 20.0, 30.0);
+        let at_angle = |deg: f32| {
+            let rad = deg.to_radians();
+            glam::Vec3::new(rad.sin(), -rad.cos(), 0.0)
+        };
+        assert_eq!(cone(&spot, at_angle(0.0)), 1.0);
+        assert!((cone(&spot, at_angle(19.0)) - 1.0).abs() < 1e-4);
+        let half = cone(&spot, at_angle(25.0));
+        assert!(half > 0.0 && half < 1.0);
+        assert_eq!(cone(&spot, at_angle(31.0)), 0.0);
+
+        // Point lights shine everywhere
+        let point = PointLightUniform::point(glam::Vec3::ZERO, 10.0, glam::Vec3::ONE, 1.0);
+        assert_eq!(cone(&point, glam::Vec3::Y), 1.0);
+        assert_eq!(std::mem::size_of::<PointLightUniform>(), 64);
+    }
+
+    #[test]
+    fn test_cluster_shaders_validate() {
+        for (name, source) in [
+            ("light_clusters.wgsl", crate::shader::get_light_cluster_wgsl()),
+            ("deferred_light.wgsl", crate::shader::get_deferred_light_wgsl()),
+            ("deferred_light_splats.wgsl", crate::shader::get_deferred_light_with_splats_wgsl()),
+        ] {
+            if let Err(e) = crate::shader_diagnostic::check_wgsl(&source, Path::new(name), &[]) {
+                panic!("{}", e);
+            }
+        }
+    }
+}
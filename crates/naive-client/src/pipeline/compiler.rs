@@ -1,18 +1,27 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use wgpu::util::DeviceExt;
 
 use crate::camera::CameraState;
+use crate::color_grading::{ColorGrading, ColorGradingUniforms};
+use crate::components::ParticleBlend;
+use crate::environment::{EnvironmentMaps, EnvironmentUniforms};
 use crate::mesh::Vertex3D;
 use crate::renderer::DrawUniformPool;
 
-use super::def::{PipelineError, PipelineFile};
+use super::clusters::LightClusters;
+use super::gpu_culling::GpuCulling;
+use super::def::{PassDef, PipelineError, PipelineFile, ResourceDef};
 use super::resource::{
-    allocate_resources, GpuResource, LightingUniforms, PassType,
-    ShadowUniforms,
+    allocate_resources, create_samplers, GpuResource, LightingUniforms, PassType,
+    PointShadowUniforms, ShadowUniforms, MAX_POINT_SHADOWS, POINT_SHADOW_FACE_STRIDE,
+    POINT_SHADOW_SIZE,
 };
-use super::{CompiledPass, CompiledPipeline};
+use super::particles::{create_particle_pipeline, ParticleInputs, ParticlePass};
+use super::post::{create_post_pipeline, PostProcessPass};
+use super::water::{create_water_pipeline, WaterInputs, WaterPass};
+use super::{BloomChain, CompiledPass, CompiledPipeline, DepthResolve, PointShadows, TaaInputs, TaaPass};
 
 // ---------------------------------------------------------------------------
 // Pipeline compiler
@@ -22,6 +31,7 @@ use super::{CompiledPass, CompiledPipeline};
 #[allow(clippy::too_many_arguments)]
 pub fn compile_pipeline(
     device: &wgpu::Device,
+    adapter: &wgpu::Adapter,
     pipeline_file: &PipelineFile,
     project_root: &Path,
     camera_state: &CameraState,
@@ -34,25 +44,99 @@ pub fn compile_pipeline(
     // 1. Build DAG and get execution order
     let pass_order = super::def::build_dag(&pipeline_file.passes)?;
 
-    // 2. Allocate GPU resources
-    let resources = allocate_resources(device, &pipeline_file.resources, viewport_width, viewport_height)?;
+    // 2. Allocate GPU resources, with MSAA targets for multisampled rasterize passes
+    let mut pass_samples = Vec::with_capacity(pipeline_file.passes.len());
+    let mut sample_counts: HashMap<String, u32> = HashMap::new();
+    for pass_def in &pipeline_file.passes {
+        let samples = super::def::pass_sample_count(pipeline_file, pass_def)?;
+        if pass_def.pass_type == "rasterize" {
+            for resource_name in pass_def.outputs.values() {
+                let existing = *sample_counts.entry(resource_name.clone()).or_insert(samples);
+                if existing != samples {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Resource '{}' is written with {} and {} samples",
+                        resource_name, existing, samples
+                    )));
+                }
+            }
+        }
+        pass_samples.push(samples);
+    }
+    // Substitute what the device can't do rather than failing below
+    let format_features = |format| {
+        super::downgrade::device_format_features(format, adapter.get_texture_format_features(format), device.features())
+    };
+    let downgrades = super::downgrade::plan_downgrades(pipeline_file, device.features(), &format_features, &device.limits())?;
+    for line in &downgrades.report {
+        tracing::warn!("Pipeline downgrade: {}", line);
+    }
 
-    // 3. Create light uniform buffer
+    // Resources alive in disjoint stretches of the frame share textures
+    let aliases = if pipeline_file.settings.alias_resources {
+        super::alias::plan_aliases(pipeline_file, &pass_order, &sample_counts, &downgrades.formats)?
+    } else {
+        HashMap::new()
+    };
+    let mut resources = allocate_resources(
+        device,
+        &pipeline_file.resources,
+        &sample_counts,
+        &downgrades.formats,
+        &aliases,
+        viewport_width,
+        viewport_height,
+    )?;
+
+    // 3. Create light uniform buffer, point light storage and the clustering step
     let light_uniform = LightingUniforms::default();
     let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Lighting Uniform Buffer"),
         contents: bytemuck::cast_slice(&[light_uniform]),
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
     });
+    let light_clusters = LightClusters::new(
+        device,
+        &light_buffer,
+        downgrades.max_lights,
+        &camera_state.bind_group_layout,
+    );
 
-    // Create shadow comparison sampler for lighting pass
-    let shadow_cmp_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        label: Some("Shadow Comparison Sampler"),
-        compare: Some(wgpu::CompareFunction::LessEqual),
+    // Skybox maps for ambient light and the background, black until the
+    // engine binds the scene's skybox
+    let environment = EnvironmentMaps::fallback(device);
+    let environment_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Environment Sampler"),
         mag_filter: wgpu::FilterMode::Linear,
         min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Linear,
         ..Default::default()
     });
+    let environment_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Environment Uniform Buffer"),
+        contents: bytemuck::cast_slice(&[EnvironmentUniforms::default()]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
+    // Named samplers; passes that don't pick one keep the built-in defaults
+    let samplers = create_samplers(device, &pipeline_file.samplers)?;
+    let mut pass_samplers = Vec::with_capacity(pipeline_file.passes.len());
+    for pass_def in &pipeline_file.passes {
+        let input = super::def::pass_sampler(pipeline_file, pass_def, "input")?;
+        let shadow = super::def::pass_sampler(pipeline_file, pass_def, "shadow")?;
+        pass_samplers.push((input, shadow));
+    }
+
+    // Create shadow comparison sampler for lighting pass
+    let shadow_cmp_sampler = match pass_samplers.iter().find_map(|(_, shadow)| *shadow) {
+        Some(def) => samplers[&def.name].clone(),
+        None => device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Comparison Sampler"),
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        }),
+    };
 
     // Create a 1x1 dummy depth texture for when shadow_map is not present
     let shadow_dummy_tex = device.create_texture(&wgpu::TextureDescriptor {
@@ -70,6 +154,13 @@ pub fn compile_pipeline(
         .map(|r| &r.view)
         .unwrap_or(&shadow_dummy_view);
 
+    // Placeholder for the lighting pass's AO input when there is no ssao resource
+    let ao_fallback_view = create_ao_fallback(device);
+
+    // Point light cube shadows are drawn by the shadow pass, if there is one
+    let has_shadow_pass = pipeline_file.passes.iter().any(|p| p.pass_type == "shadow");
+    let (mut point_shadows, point_face_layout) = create_point_shadows(device, has_shadow_pass);
+
     let light_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Lighting Bind Group Layout"),
@@ -100,30 +191,65 @@ pub fn compile_pipeline(
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                cube_texture_layout_entry(5),
+                cube_texture_layout_entry(6),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 7,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                light_storage_layout_entry(9),
+                light_storage_layout_entry(10),
             ],
         });
 
-    let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("Lighting Bind Group"),
-        layout: &light_bind_group_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: light_buffer.as_entire_binding(),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::TextureView(shadow_map_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 2,
-                resource: wgpu::BindingResource::Sampler(&shadow_cmp_sampler),
-            },
-        ],
-    });
+    let light_bind_group = create_light_bind_group(
+        device,
+        "Lighting Bind Group",
+        &light_bind_group_layout,
+        &light_buffer,
+        &light_clusters,
+        shadow_map_view,
+        &shadow_cmp_sampler,
+        &point_shadows,
+        &environment,
+        &environment_sampler,
+        &environment_buffer,
+    );
 
     // 4. Create shared sampler for G-buffer reads
-    let gbuffer_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+    let mut gbuffer_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
         label: Some("GBuffer Sampler"),
         mag_filter: wgpu::FilterMode::Nearest,
         min_filter: wgpu::FilterMode::Nearest,
@@ -166,19 +292,35 @@ pub fn compile_pipeline(
     let mut gbuffer_bind_group = None;
     let mut tonemap_bind_group_layout = None;
     let mut tonemap_bind_group = None;
+    let mut color_grading = None;
     let mut bloom_bind_group_layout = None;
     let mut bloom_bind_group = None;
+    let mut bloom_chain = None;
     let mut splat_data_bind_group_layout = None;
     let mut splat_composite_bind_group_layout = None;
     let mut splat_composite_bind_group = None;
     let mut fxaa_bind_group_layout = None;
     let mut fxaa_bind_group = None;
+    let mut ssao_bind_group_layout = None;
+    let mut ssao_bind_group = None;
+    let mut bloom_sampler = None;
+    let mut tonemap_sampler = None;
+    let mut fxaa_sampler = None;
+    let mut taa = None;
+    let mut skybox_bind_group_layout = None;
+    let mut skybox_bind_group = None;
+    let mut water = None;
+    let mut particles = None;
+    let mut post_passes = HashMap::new();
     let mut shadow_uniform_buffer = None;
     let mut shadow_bind_group_layout = None;
     let mut shadow_bind_group = None;
     let shadow_sampler = Some(shadow_cmp_sampler);
 
-    for pass_def in &pipeline_file.passes {
+    for ((pass_def, &sample_count), &(input_sampler_def, _)) in
+        pipeline_file.passes.iter().zip(&pass_samples).zip(&pass_samplers)
+    {
+        let input_sampler = input_sampler_def.map(|def| samplers[&def.name].clone());
         let pass_type = PassType::from_str(&pass_def.pass_type).ok_or_else(|| {
             PipelineError::InvalidFormat(format!("Unknown pass type: '{}'", pass_def.pass_type))
         })?;
@@ -213,7 +355,24 @@ pub fn compile_pipeline(
 
         // Compile the shader (try SLANG first, then fallback)
         let shader_path = project_root.join(&pass_def.shader);
-        let wgsl_source = compile_pass_shader(&shader_path, &pass_def.name)?;
+        let include_dirs = [project_root.join("shaders")];
+        let mut includes = Vec::new();
+        let wgsl_source = if pass_type == PassType::Ssao && pass_def.shader.is_empty() {
+            crate::shader::get_ssao_wgsl()
+        } else if pass_type == PassType::Taa && pass_def.shader.is_empty() {
+            crate::shader::get_taa_wgsl()
+        } else if pass_type == PassType::Skybox && pass_def.shader.is_empty() {
+            crate::shader::get_skybox_wgsl()
+        } else if pass_type == PassType::Water && pass_def.shader.is_empty() {
+            crate::shader::get_water_wgsl()
+        } else if pass_type == PassType::Particles && pass_def.shader.is_empty() {
+            crate::shader::get_particle_wgsl()
+        } else {
+            let defines = super::def::define_pairs(&pass_def.defines);
+            let (wgsl, files) = compile_pass_shader(&shader_path, &pass_def.name, &include_dirs, &defines)?;
+            includes = files;
+            wgsl
+        };
 
         // Create the render pipeline for this pass
         let pipeline = match pass_type {
@@ -228,6 +387,8 @@ pub fn compile_pipeline(
                     &draw_pool.bind_group_layout,
                     texture_bind_group_layout,
                     Some(&skin_bind_group_layout),
+                    sample_count,
+                    surface_format,
                 )
             }
             PassType::Fullscreen => {
@@ -237,42 +398,71 @@ pub fn compile_pipeline(
                         .get("ldr_buffer")
                         .map(|r| r.format)
                         .unwrap_or(surface_format);
+                    let sampler = input_sampler.unwrap_or_else(|| linear_clamp_sampler(device, "HDR Sampler"));
+                    let grading = new_color_grading(device, pass_def.grading.clone().unwrap_or_default());
                     let (layout, bg, pipeline) = create_tonemap_pipeline(
                         device,
                         &wgsl_source,
                         &color_targets,
                         &resources,
-                        &gbuffer_sampler,
+                        &sampler,
+                        &grading,
                         tonemap_output_format,
                     );
                     tonemap_bind_group_layout = Some(layout);
                     tonemap_bind_group = Some(bg);
+                    tonemap_sampler = Some(sampler);
+                    color_grading = Some(grading);
                     pipeline
                 } else if pass_def.name.contains("fxaa") {
                     // FXAA pass: reads LDR buffer, writes to swapchain
+                    let sampler = input_sampler.unwrap_or_else(|| linear_clamp_sampler(device, "FXAA LDR Sampler"));
                     let (layout, bg, pipeline) = create_fxaa_pipeline(
                         device,
                         &wgsl_source,
                         &resources,
+                        &sampler,
                         surface_format,
                     );
                     fxaa_bind_group_layout = Some(layout);
                     fxaa_bind_group = Some(bg);
+                    fxaa_sampler = Some(sampler);
                     pipeline
                 } else if pass_def.name.contains("bloom") {
                     // Bloom pass: reads HDR buffer, outputs to bloom_buffer
+                    let sampler = input_sampler.unwrap_or_else(|| linear_clamp_sampler(device, "Bloom HDR Sampler"));
+                    let chain = BloomChain::new(
+                        device,
+                        pass_def.bloom.unwrap_or_default(),
+                        color_targets.first().map_or("bloom_buffer", String::as_str),
+                        &resources,
+                    );
                     let (layout, bg, pipeline) = create_bloom_pipeline(
                         device,
                         &wgsl_source,
                         &color_targets,
                         &resources,
-                        &gbuffer_sampler,
+                        &sampler,
+                        &chain.params_buffer,
                     );
                     bloom_bind_group_layout = Some(layout);
                     bloom_bind_group = Some(bg);
+                    bloom_sampler = Some(sampler);
+                    bloom_chain = Some(chain);
                     pipeline
                 } else {
-                    // Lighting pass: inputs from G-buffer
+                    // Lighting pass: inputs from G-buffer, bound as a non-filtering sampler
+                    if let Some(def) = input_sampler_def {
+                        if def.filter != super::def::FilterKind::Nearest || def.anisotropy > 1 {
+                            return Err(PipelineError::InvalidFormat(format!(
+                                "Pass '{}': G-buffer sampler '{}' must use nearest filtering",
+                                pass_def.name, def.name
+                            )));
+                        }
+                    }
+                    if let Some(sampler) = input_sampler {
+                        gbuffer_sampler = sampler;
+                    }
                     let has_splat_resources = resources.contains_key("splat_color")
                         && resources.contains_key("splat_depth");
 
@@ -292,6 +482,7 @@ pub fn compile_pipeline(
                                 &resources,
                                 &camera_state.bind_group_layout,
                                 &gbuffer_sampler,
+                                &ao_fallback_view,
                                 &light_bind_group_layout,
                             );
                         gbuffer_bind_group_layout = Some(gb_layout);
@@ -302,11 +493,12 @@ pub fn compile_pipeline(
                     } else {
                         let (layout, bg, pipeline) = create_lighting_pipeline(
                             device,
-                            &wgsl_source,
+                            &lighting_wgsl,
                             &color_targets,
                             &resources,
                             &camera_state.bind_group_layout,
                             &gbuffer_sampler,
+                            &ao_fallback_view,
                             &light_bind_group_layout,
                         );
                         gbuffer_bind_group_layout = Some(layout);
@@ -373,12 +565,217 @@ pub fn compile_pipeline(
                     &skin_bind_group_layout,
                 );
 
+                // Same shader for the point light cube faces, one matrix per face
+                point_shadows.pipeline = Some(create_shadow_pipeline(
+                    device,
+                    &wgsl_source,
+                    None,
+                    &resources,
+                    &point_face_layout,
+                    &draw_pool.bind_group_layout,
+                    &skin_bind_group_layout,
+                ));
+
                 shadow_uniform_buffer = Some(shadow_buf);
                 shadow_bind_group_layout = Some(shadow_bg_layout);
                 shadow_bind_group = Some(shadow_bg);
 
                 pipeline
             }
+            PassType::Ssao => {
+                if !resources.contains_key("gbuffer_normal") || !resources.contains_key("gbuffer_depth") {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Pass '{}': SSAO needs gbuffer_normal and gbuffer_depth resources",
+                        pass_def.name
+                    )));
+                }
+                let (layout, bg, pipeline) = create_ssao_pipeline(
+                    device,
+                    &wgsl_source,
+                    &color_targets,
+                    &resources,
+                    &camera_state.bind_group_layout,
+                );
+                ssao_bind_group_layout = Some(layout);
+                ssao_bind_group = Some(bg);
+                pipeline
+            }
+            PassType::Taa => {
+                let inputs = allocate_taa_resolve(
+                    device,
+                    pipeline_file,
+                    pass_def,
+                    &mut resources,
+                    viewport_width,
+                    viewport_height,
+                )?;
+                if color_targets.len() != 1 {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Pass '{}': TAA needs exactly one color output",
+                        pass_def.name
+                    )));
+                }
+                color_targets.push(inputs.resolve.clone());
+                let sampler = linear_clamp_sampler(device, "TAA History Sampler");
+                let (layout, bg, pipeline) = create_taa_pipeline(
+                    device,
+                    &wgsl_source,
+                    &color_targets,
+                    &resources,
+                    &inputs,
+                    &sampler,
+                    &camera_state.bind_group_layout,
+                    surface_format,
+                );
+                taa = Some(TaaPass {
+                    inputs,
+                    sampler,
+                    bind_group_layout: layout,
+                    bind_group: bg,
+                });
+                pipeline
+            }
+            PassType::Skybox => {
+                // The depth input is attached read-only: the sky only lands
+                // where nothing was drawn
+                let depth = pass_def
+                    .inputs
+                    .get("depth")
+                    .filter(|name| resources.get(*name).is_some_and(|r| r.format.is_depth_stencil_format()))
+                    .ok_or_else(|| {
+                        PipelineError::InvalidFormat(format!(
+                            "Pass '{}': skybox needs a depth texture as its `depth` input",
+                            pass_def.name
+                        ))
+                    })?;
+                if color_targets.len() != 1 || depth_target.is_some() {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Pass '{}': skybox needs exactly one color output",
+                        pass_def.name
+                    )));
+                }
+                depth_target = Some(depth.clone());
+                let (layout, pipeline) = create_skybox_pipeline(
+                    device,
+                    &wgsl_source,
+                    &color_targets[0],
+                    depth,
+                    &resources,
+                    &camera_state.bind_group_layout,
+                );
+                skybox_bind_group = Some(create_skybox_bind_group(
+                    device,
+                    &layout,
+                    &environment,
+                    &environment_sampler,
+                    &environment_buffer,
+                ));
+                skybox_bind_group_layout = Some(layout);
+                pipeline
+            }
+            PassType::Water => {
+                // Depth is read as a texture and tested in the shader, so it
+                // isn't attached
+                let depth = pass_def
+                    .inputs
+                    .get("depth")
+                    .filter(|name| resources.get(*name).is_some_and(|r| r.format.is_depth_stencil_format()))
+                    .ok_or_else(|| {
+                        PipelineError::InvalidFormat(format!(
+                            "Pass '{}': water needs a depth texture as its `depth` input",
+                            pass_def.name
+                        ))
+                    })?;
+                if color_targets.len() != 1 || depth_target.is_some() {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Pass '{}': water needs exactly one color output",
+                        pass_def.name
+                    )));
+                }
+                let format = resources[&color_targets[0]].format;
+                let mut pass = WaterPass::new(
+                    device,
+                    WaterInputs {
+                        color: color_targets[0].clone(),
+                        depth: depth.clone(),
+                    },
+                );
+                pass.rebuild(device, &resources, &environment, &environment_sampler, &environment_buffer);
+                let pipeline = create_water_pipeline(
+                    device,
+                    &wgsl_source,
+                    format,
+                    &camera_state.bind_group_layout,
+                    &pass,
+                );
+                water = Some(pass);
+                pipeline
+            }
+            PassType::Particles => {
+                // Depth is read as a texture for soft particles and tested in
+                // the shader, so it isn't attached
+                let depth = pass_def
+                    .inputs
+                    .get("depth")
+                    .filter(|name| resources.get(*name).is_some_and(|r| r.format.is_depth_stencil_format()))
+                    .ok_or_else(|| {
+                        PipelineError::InvalidFormat(format!(
+                            "Pass '{}': particles need a depth texture as their `depth` input",
+                            pass_def.name
+                        ))
+                    })?;
+                if color_targets.len() != 1 || depth_target.is_some() {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Pass '{}': particles need exactly one color output",
+                        pass_def.name
+                    )));
+                }
+                let format = resources[&color_targets[0]].format;
+                let mut pass = ParticlePass::new(
+                    device,
+                    ParticleInputs {
+                        color: color_targets[0].clone(),
+                        depth: depth.clone(),
+                    },
+                );
+                pass.rebuild(device, &resources);
+                let camera_layout = &camera_state.bind_group_layout;
+                let pipeline =
+                    create_particle_pipeline(device, &wgsl_source, format, camera_layout, &pass, ParticleBlend::Alpha);
+                pass.additive_pipeline = Some(create_particle_pipeline(
+                    device,
+                    &wgsl_source,
+                    format,
+                    camera_layout,
+                    &pass,
+                    ParticleBlend::Additive,
+                ));
+                particles = Some(pass);
+                pipeline
+            }
+            PassType::PostProcess => {
+                if color_targets.is_empty() || depth_target.is_some() {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "Pass '{}': postprocess passes need color outputs and no depth output",
+                        pass_def.name
+                    )));
+                }
+                let post = PostProcessPass::new(
+                    device,
+                    pass_def,
+                    &wgsl_source,
+                    &resources,
+                    input_sampler_def.map(|def| (def, &samplers[&def.name])),
+                    &camera_state.buffer,
+                )?;
+                let formats: Vec<wgpu::TextureFormat> = color_targets
+                    .iter()
+                    .map(|name| resources.get(name).map_or(surface_format, |r| r.format))
+                    .collect();
+                let pipeline = create_post_pipeline(device, &pass_def.name, &wgsl_source, &post, &formats);
+                post_passes.insert(pass_def.name.clone(), post);
+                pipeline
+            }
             PassType::Compute => {
                 // Compute passes not yet implemented
                 return Err(PipelineError::InvalidFormat(
@@ -387,6 +784,29 @@ pub fn compile_pipeline(
             }
         };
 
+        // Load ops are written per output slot; key them by resource like the targets
+        let mut target_ops = HashMap::new();
+        for (slot, ops) in &pass_def.targets {
+            let resource_name = pass_def.outputs.get(slot).ok_or_else(|| {
+                PipelineError::InvalidFormat(format!(
+                    "Pass '{}': targets.{} is not one of its outputs",
+                    pass_def.name, slot
+                ))
+            })?;
+            target_ops.insert(resource_name.clone(), ops.clone());
+        }
+
+        let enabled_if = match &pass_def.enabled_if {
+            Some(conditions) => super::condition::parse_conditions(&conditions.sources(), &pipeline_file.settings.flags)
+                .map_err(|e| PipelineError::InvalidFormat(format!("Pass '{}': enabled_if: {}", pass_def.name, e)))?,
+            None => Vec::new(),
+        };
+
+        let depth_resolve = match (sample_count, depth_target.as_ref().and_then(|name| resources.get(name))) {
+            (2.., Some(depth)) => Some(create_depth_resolve(device, depth)),
+            _ => None,
+        };
+
         compiled_passes.push(CompiledPass {
             name: pass_def.name.clone(),
             pass_type,
@@ -395,6 +815,11 @@ pub fn compile_pipeline(
             depth_target,
             wgsl_source,
             shader_path,
+            includes,
+            sample_count,
+            depth_resolve,
+            target_ops,
+            enabled_if,
         });
     }
 
@@ -409,95 +834,565 @@ pub fn compile_pipeline(
         passes: compiled_passes,
         pass_order,
         light_buffer,
+        light_clusters,
         light_bind_group_layout,
         light_bind_group,
         gbuffer_sampler,
+        bloom_sampler: bloom_sampler.unwrap_or_else(|| linear_clamp_sampler(device, "Bloom HDR Sampler")),
+        tonemap_sampler: tonemap_sampler.unwrap_or_else(|| linear_clamp_sampler(device, "HDR Sampler")),
+        fxaa_sampler: fxaa_sampler.unwrap_or_else(|| linear_clamp_sampler(device, "FXAA LDR Sampler")),
+        samplers,
         gbuffer_bind_group_layout,
         gbuffer_bind_group,
         tonemap_bind_group_layout,
         tonemap_bind_group,
+        color_grading,
         bloom_bind_group_layout,
         bloom_bind_group,
+        bloom_chain,
         splat_data_bind_group_layout,
         splat_composite_bind_group_layout,
         splat_composite_bind_group,
         fxaa_bind_group_layout,
         fxaa_bind_group,
+        ssao_bind_group_layout,
+        ssao_bind_group,
+        ao_fallback_view,
+        taa,
+        gpu_culling: downgrades.gpu_culling.then(|| GpuCulling::new(device)),
+        downgrades,
+        environment,
+        environment_sampler,
+        environment_buffer,
+        environment_source: None,
+        skybox_bind_group_layout,
+        skybox_bind_group,
+        water,
+        particles,
+        post_passes,
         shadow_uniform_buffer,
         shadow_bind_group_layout,
         shadow_bind_group,
         shadow_sampler,
+        point_shadows,
         skin_buffer: Some(skin_buffer),
         skin_bind_group_layout: Some(skin_bind_group_layout),
         skin_bind_group: Some(skin_bind_group),
     })
 }
 
-/// Compile a pass shader: try SLANG, fallback to WGSL.
-fn compile_pass_shader(shader_path: &Path, pass_name: &str) -> Result<String, PipelineError> {
-    // Skip SLANG for geometry pass -- SLANG-compiled WGSL doesn't support dynamic-offset UBOs correctly
-    let skip_slang = pass_name.contains("geometry") || pass_name.contains("gbuffer");
-    if shader_path.exists() && !skip_slang {
-        match crate::shader::compile_slang_to_wgsl_public(shader_path) {
-            Ok(wgsl) => {
-                tracing::info!("SLANG compiled for pass '{}': {:?}", pass_name, shader_path);
-                return Ok(wgsl);
-            }
-            Err(e) => {
-                tracing::warn!(
-                    "SLANG compilation failed for pass '{}': {}, using WGSL fallback",
-                    pass_name,
-                    e
-                );
-            }
-        }
+/// Lighting pass group 2: light uniforms, directional shadow map, comparison
+/// sampler, the point light shadow array, the skybox's specular and
+/// irradiance cubes, and the point lights with their cluster lists.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn create_light_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    light_buffer: &wgpu::Buffer,
+    clusters: &LightClusters,
+    shadow_map_view: &wgpu::TextureView,
+    shadow_sampler: &wgpu::Sampler,
+    point_shadows: &PointShadows,
+    environment: &EnvironmentMaps,
+    environment_sampler: &wgpu::Sampler,
+    environment_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: light_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(shadow_map_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(shadow_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&point_shadows.array_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: point_shadows.uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(&environment.specular),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: wgpu::BindingResource::TextureView(&environment.irradiance),
+            },
+            wgpu::BindGroupEntry {
+                binding: 7,
+                resource: wgpu::BindingResource::Sampler(environment_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 8,
+                resource: environment_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 9,
+                resource: clusters.light_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 10,
+                resource: clusters.cluster_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// Fragment-visible read-only storage buffer at `binding` (point lights and
+/// cluster light lists).
+fn light_storage_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
     }
+}
 
-    // Use fallback WGSL
-    let wgsl = match pass_name {
-        name if name.contains("geometry") || name.contains("gbuffer") => {
-            crate::shader::get_gbuffer_wgsl()
-        }
-        name if name.contains("splat") && !name.contains("light") => {
-            crate::shader::get_splat_render_wgsl()
-        }
-        name if name.contains("light") => crate::shader::get_deferred_light_wgsl(),
-        name if name.contains("bloom") => crate::shader::get_bloom_wgsl(),
-        name if name.contains("tonemap") => crate::shader::get_tonemap_wgsl(),
-        name if name.contains("fxaa") => crate::shader::get_fxaa_wgsl(),
-        name if name.contains("shadow") => crate::shader::get_shadow_depth_wgsl(),
-        _ => {
-            return Err(PipelineError::ShaderError(format!(
-                "No fallback WGSL for pass '{}'",
-                pass_name
-            )));
-        }
-    };
-    Ok(wgsl)
+/// Fragment-visible filterable float cube texture at `binding`.
+pub(super) fn cube_texture_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::Cube,
+            multisampled: false,
+        },
+        count: None,
+    }
 }
 
-/// Create a rasterize (geometry) pipeline with MRT outputs.
-fn create_rasterize_pipeline(
+/// Skybox pass group 1: sky cube, its sampler and the environment uniforms.
+pub(super) fn create_skybox_bind_group(
     device: &wgpu::Device,
-    wgsl_source: &str,
-    color_targets: &[String],
-    depth_target: Option<&str>,
-    resources: &HashMap<String, GpuResource>,
-    camera_bind_group_layout: &wgpu::BindGroupLayout,
-    draw_bind_group_layout: &wgpu::BindGroupLayout,
-    texture_bind_group_layout: Option<&wgpu::BindGroupLayout>,
-    skin_bind_group_layout: Option<&wgpu::BindGroupLayout>,
-) -> wgpu::RenderPipeline {
-    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("GBuffer Shader"),
-        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    layout: &wgpu::BindGroupLayout,
+    environment: &EnvironmentMaps,
+    environment_sampler: &wgpu::Sampler,
+    environment_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Skybox Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&environment.sky),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(environment_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: environment_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// Allocate the point light shadow array (six layers per shadow slot) and its
+/// per-face uniforms. The returned layout is group 0 of the point shadow
+/// pipeline: one face matrix, selected by dynamic offset.
+fn create_point_shadows(device: &wgpu::Device, enabled: bool) -> (PointShadows, wgpu::BindGroupLayout) {
+    let (size, layers) = if enabled {
+        (POINT_SHADOW_SIZE, MAX_POINT_SHADOWS as u32 * 6)
+    } else {
+        (1, 1)
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Point Shadow Maps"),
+        size: wgpu::Extent3d { width: size, height: size, depth_or_array_layers: layers },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let array_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("Point Shadow Array View"),
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..Default::default()
     });
+    let face_views = (0..layers)
+        .map(|layer| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Point Shadow Face View"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
 
-    let mut layouts: Vec<&wgpu::BindGroupLayout> = vec![camera_bind_group_layout, draw_bind_group_layout];
-    if let Some(tex_layout) = texture_bind_group_layout {
-        layouts.push(tex_layout);
-    }
-    if let Some(skin_layout) = skin_bind_group_layout {
+    let face_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Point Shadow Face Buffer"),
+        size: POINT_SHADOW_FACE_STRIDE * layers as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let face_size = std::mem::size_of::<ShadowUniforms>() as u64;
+    let face_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Point Shadow Face Bind Group Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: wgpu::BufferSize::new(face_size),
+            },
+            count: None,
+        }],
+    });
+    let face_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Point Shadow Face Bind Group"),
+        layout: &face_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &face_buffer,
+                offset: 0,
+                size: wgpu::BufferSize::new(face_size),
+            }),
+        }],
+    });
+
+    let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Point Shadow Uniform Buffer"),
+        contents: bytemuck::cast_slice(&[PointShadowUniforms::default()]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
+    let point_shadows = PointShadows {
+        texture,
+        array_view,
+        face_views,
+        face_buffer,
+        face_bind_group,
+        uniform_buffer,
+        pipeline: None,
+    };
+    (point_shadows, face_layout)
+}
+
+/// 1x1 texture bound as the lighting pass's AO input when the pipeline has no
+/// ssao resource; the shader treats a 1x1 AO texture as unoccluded.
+fn create_ao_fallback(device: &wgpu::Device) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("AO Placeholder"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&Default::default())
+}
+
+/// Lighting pass group 1: G-buffer textures, sampler, emission and ambient
+/// occlusion. None if a G-buffer resource is missing.
+pub(super) fn create_gbuffer_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    resources: &HashMap<String, GpuResource>,
+    gbuffer_sampler: &wgpu::Sampler,
+    ao_fallback_view: &wgpu::TextureView,
+) -> Option<wgpu::BindGroup> {
+    let albedo_view = &resources.get("gbuffer_albedo")?.view;
+    let normal_view = &resources.get("gbuffer_normal")?.view;
+    let depth_view = &resources.get("gbuffer_depth")?.view;
+    let emission_view = resources
+        .get("gbuffer_emission")
+        .map(|r| &r.view)
+        .unwrap_or(albedo_view); // fallback to albedo if emission not present
+    let ao_view = resources
+        .get(super::def::SSAO_RESOURCE)
+        .map(|r| &r.view)
+        .unwrap_or(ao_fallback_view);
+
+    Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(albedo_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(normal_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(depth_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(gbuffer_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::TextureView(emission_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::TextureView(ao_view),
+            },
+        ],
+    }))
+}
+
+/// SSAO pass group 1: G-buffer normals and depth, read with `textureLoad`.
+pub(super) fn create_ssao_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    resources: &HashMap<String, GpuResource>,
+) -> Option<wgpu::BindGroup> {
+    let normal_view = &resources.get("gbuffer_normal")?.view;
+    let depth_view = &resources.get("gbuffer_depth")?.view;
+    Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(normal_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(depth_view),
+            },
+        ],
+    }))
+}
+
+/// Check a TAA pass's `color`, `depth` and `history` inputs and allocate its
+/// resolve target: a copy of the history resource's format and size, named
+/// `<history>.resolve`, so it is resized along with the other resources.
+fn allocate_taa_resolve(
+    device: &wgpu::Device,
+    pipeline_file: &PipelineFile,
+    pass_def: &PassDef,
+    resources: &mut HashMap<String, GpuResource>,
+    viewport_width: u32,
+    viewport_height: u32,
+) -> Result<TaaInputs, PipelineError> {
+    let input = |slot: &str| {
+        pass_def
+            .inputs
+            .get(slot)
+            .filter(|name| resources.contains_key(name.as_str()))
+            .cloned()
+            .ok_or_else(|| {
+                PipelineError::InvalidFormat(format!(
+                    "Pass '{}': TAA needs a '{}' input naming a pipeline resource",
+                    pass_def.name, slot
+                ))
+            })
+    };
+    let (color, depth, history) = (input("color")?, input("depth")?, input("history")?);
+    if !resources[&depth].format.is_depth_stencil_format() {
+        return Err(PipelineError::InvalidFormat(format!(
+            "Pass '{}': TAA depth input '{}' is not a depth texture",
+            pass_def.name, depth
+        )));
+    }
+    let history_def = pipeline_file
+        .resources
+        .iter()
+        .find(|r| r.name == history)
+        .expect("history resource was allocated from its definition");
+    if resources[&history].format.is_depth_stencil_format() {
+        return Err(PipelineError::InvalidFormat(format!(
+            "Pass '{}': TAA history '{}' must be a color texture",
+            pass_def.name, history
+        )));
+    }
+
+    let resolve = format!("{}.resolve", history);
+    let resolve_def = ResourceDef {
+        name: resolve.clone(),
+        resource_type: history_def.resource_type.clone(),
+        format: history_def.format.clone(),
+        size: history_def.size.clone(),
+        samples: None,
+        color_space: history_def.color_space,
+    };
+    let resolve_format = HashMap::from([(resolve.clone(), resources[&history].format)]);
+    resources.extend(allocate_resources(
+        device,
+        &[resolve_def],
+        &HashMap::new(),
+        &resolve_format,
+        &HashMap::new(),
+        viewport_width,
+        viewport_height,
+    )?);
+    Ok(TaaInputs { color, depth, history, resolve })
+}
+
+/// TAA pass group 1: current color, depth, history and the history sampler.
+/// None if one of the resources is missing.
+pub(super) fn create_taa_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    resources: &HashMap<String, GpuResource>,
+    inputs: &TaaInputs,
+    sampler: &wgpu::Sampler,
+) -> Option<wgpu::BindGroup> {
+    let color_view = &resources.get(&inputs.color)?.view;
+    let depth_view = &resources.get(&inputs.depth)?.view;
+    let history_view = &resources.get(&inputs.history)?.view;
+    Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(color_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(depth_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(history_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    }))
+}
+
+/// Default sampler for post-processing passes that read a full-screen buffer.
+fn linear_clamp_sampler(device: &wgpu::Device, label: &str) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some(label),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    })
+}
+
+/// Compile a pass shader: `.wgsl` files are preprocessed (`#include`,
+/// `#define`, `#if`), otherwise try SLANG, then fall back to the built-in WGSL
+/// for the pass name. Both compile with the pass's `defines`. Also returns the
+/// files the shader includes.
+///
+/// A shader that fails to compile is an error pointing at its source line;
+/// the built-in WGSL only stands in when the file is missing or SLANG support
+/// isn't compiled in.
+fn compile_pass_shader(
+    shader_path: &Path,
+    pass_name: &str,
+    include_dirs: &[PathBuf],
+    defines: &[(String, String)],
+) -> Result<(String, Vec<PathBuf>), PipelineError> {
+    let shader_error = |e: String| PipelineError::ShaderError(format!("Pass '{}': {}", pass_name, e));
+    if shader_path.extension().is_some_and(|ext| ext == "wgsl") {
+        let preprocessed = super::preprocess::preprocess_file(shader_path, include_dirs, defines)
+            .map_err(shader_error)?;
+        crate::shader_diagnostic::check_wgsl(&preprocessed.source, shader_path, &preprocessed.origins)
+            .map_err(|d| shader_error(d.to_string()))?;
+        return Ok((preprocessed.source, preprocessed.includes));
+    }
+    // Skip SLANG for geometry pass -- SLANG-compiled WGSL doesn't support dynamic-offset UBOs correctly
+    let skip_slang = pass_name.contains("geometry") || pass_name.contains("gbuffer");
+    if shader_path.exists() && !skip_slang {
+        match crate::shader::compile_slang_to_wgsl_with_defines(shader_path, defines) {
+            Ok(wgsl) => {
+                // Errors here are in SLANG's output, so they point at the generated WGSL
+                let generated = PathBuf::from(format!("{} (generated WGSL)", shader_path.display()));
+                crate::shader_diagnostic::check_wgsl(&wgsl, &generated, &[]).map_err(|d| shader_error(d.to_string()))?;
+                tracing::info!("SLANG compiled for pass '{}': {:?}", pass_name, shader_path);
+                return Ok((wgsl, super::preprocess::collect_includes(shader_path, include_dirs)));
+            }
+            Err(e) if !cfg!(feature = "slang") => {
+                tracing::warn!("{} for pass '{}', using WGSL fallback", e, pass_name);
+            }
+            Err(e) => return Err(shader_error(e.to_string())),
+        }
+    }
+
+    // Use fallback WGSL
+    let wgsl = match pass_name {
+        name if name.contains("geometry") || name.contains("gbuffer") => {
+            crate::shader::get_gbuffer_wgsl()
+        }
+        name if name.contains("splat") && !name.contains("light") => {
+            crate::shader::get_splat_render_wgsl()
+        }
+        name if name.contains("light") => crate::shader::get_deferred_light_wgsl(),
+        name if name.contains("bloom") => crate::shader::get_bloom_wgsl(),
+        name if name.contains("tonemap") => crate::shader::get_tonemap_wgsl(),
+        name if name.contains("fxaa") => crate::shader::get_fxaa_wgsl(),
+        name if name.contains("ssao") => crate::shader::get_ssao_wgsl(),
+        name if name.contains("taa") => crate::shader::get_taa_wgsl(),
+        name if name.contains("sky") => crate::shader::get_skybox_wgsl(),
+        name if name.contains("shadow") => crate::shader::get_shadow_depth_wgsl(),
+        _ => {
+            return Err(PipelineError::ShaderError(format!(
+                "No fallback WGSL for pass '{}'",
+                pass_name
+            )));
+        }
+    };
+    Ok((wgsl, Vec::new()))
+}
+
+/// Create a rasterize (geometry) pipeline with MRT outputs.
+#[allow(clippy::too_many_arguments)]
+fn create_rasterize_pipeline(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    color_targets: &[String],
+    depth_target: Option<&str>,
+    resources: &HashMap<String, GpuResource>,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+    draw_bind_group_layout: &wgpu::BindGroupLayout,
+    texture_bind_group_layout: Option<&wgpu::BindGroupLayout>,
+    skin_bind_group_layout: Option<&wgpu::BindGroupLayout>,
+    sample_count: u32,
+    surface_format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("GBuffer Shader"),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+
+    let mut layouts: Vec<&wgpu::BindGroupLayout> = vec![camera_bind_group_layout, draw_bind_group_layout];
+    if let Some(tex_layout) = texture_bind_group_layout {
+        layouts.push(tex_layout);
+    }
+    if let Some(skin_layout) = skin_bind_group_layout {
         layouts.push(skin_layout);
     }
 
@@ -507,14 +1402,14 @@ fn create_rasterize_pipeline(
         push_constant_ranges: &[],
     });
 
-    // Build color targets from resource formats
+    // Build color targets from resource formats; a target that is no resource is the swapchain
     let color_target_states: Vec<Option<wgpu::ColorTargetState>> = color_targets
         .iter()
         .map(|name| {
             let format = resources
                 .get(name)
                 .map(|r| r.format)
-                .unwrap_or(wgpu::TextureFormat::Rgba8UnormSrgb);
+                .unwrap_or(surface_format);
             Some(wgpu::ColorTargetState {
                 format,
                 blend: Some(wgpu::BlendState::REPLACE),
@@ -556,9 +1451,87 @@ fn create_rasterize_pipeline(
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
         }),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// Create the depth resolve pass for a multisampled depth resource.
+fn create_depth_resolve(device: &wgpu::Device, depth: &GpuResource) -> DepthResolve {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Depth Resolve Shader"),
+        source: wgpu::ShaderSource::Wgsl(crate::shader::get_depth_resolve_wgsl().into()),
+    });
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Depth Resolve Bind Group Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Depth,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: true,
+            },
+            count: None,
+        }],
+    });
+    let bind_group = create_depth_resolve_bind_group(device, &bind_group_layout, depth.render_view());
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Depth Resolve Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Depth Resolve Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &[],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: depth.format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
         multisample: wgpu::MultisampleState::default(),
         multiview: None,
         cache: None,
+    });
+
+    DepthResolve { pipeline, bind_group_layout, bind_group }
+}
+
+/// Bind a multisampled depth view for the depth resolve pass.
+pub(super) fn create_depth_resolve_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    msaa_depth_view: &wgpu::TextureView,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Depth Resolve Bind Group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(msaa_depth_view),
+        }],
     })
 }
 
@@ -577,32 +1550,9 @@ fn create_splat_pipeline(
         source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
     });
 
-    // Group 1: splat data + sorted indices (created per-entity at render time)
-    let splat_data_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("Splat Data Bind Group Layout"),
-        entries: &[
-            wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            },
-        ],
-    });
+    // Group 1: splat data, sorted indices, SH and the entity's params at a
+    // dynamic offset (bound per chunk from the splat cache)
+    let splat_data_layout = crate::splat::create_splat_data_layout(device);
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Splat Pipeline Layout"),
@@ -680,6 +1630,7 @@ fn create_splat_pipeline(
 }
 
 /// Create the deferred lighting fullscreen pipeline.
+#[allow(clippy::too_many_arguments)]
 fn create_lighting_pipeline(
     device: &wgpu::Device,
     wgsl_source: &str,
@@ -687,6 +1638,7 @@ fn create_lighting_pipeline(
     resources: &HashMap<String, GpuResource>,
     camera_bind_group_layout: &wgpu::BindGroupLayout,
     gbuffer_sampler: &wgpu::Sampler,
+    ao_fallback_view: &wgpu::TextureView,
     light_bind_group_layout: &wgpu::BindGroupLayout,
 ) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::RenderPipeline) {
     let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -744,54 +1696,29 @@ fn create_lighting_pipeline(
                 },
                 count: None,
             },
-        ],
-    });
-
-    // Build the bind group referencing the G-buffer textures
-    let albedo_view = resources
-        .get("gbuffer_albedo")
-        .map(|r| &r.view)
-        .expect("gbuffer_albedo resource missing");
-    let normal_view = resources
-        .get("gbuffer_normal")
-        .map(|r| &r.view)
-        .expect("gbuffer_normal resource missing");
-    let depth_view = resources
-        .get("gbuffer_depth")
-        .map(|r| &r.view)
-        .expect("gbuffer_depth resource missing");
-    let emission_view = resources
-        .get("gbuffer_emission")
-        .map(|r| &r.view)
-        .unwrap_or(albedo_view); // fallback to albedo if emission not present
-
-    let gbuffer_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("GBuffer Input Bind Group"),
-        layout: &gbuffer_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(albedo_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::TextureView(normal_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 2,
-                resource: wgpu::BindingResource::TextureView(depth_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 3,
-                resource: wgpu::BindingResource::Sampler(gbuffer_sampler),
-            },
-            wgpu::BindGroupEntry {
-                binding: 4,
-                resource: wgpu::BindingResource::TextureView(emission_view),
+            wgpu::BindGroupLayoutEntry {
+                binding: 5,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
             },
         ],
     });
 
+    let gbuffer_bind_group = create_gbuffer_bind_group(
+        device,
+        "GBuffer Input Bind Group",
+        &gbuffer_layout,
+        resources,
+        gbuffer_sampler,
+        ao_fallback_view,
+    )
+    .expect("G-buffer resources missing");
+
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Deferred Lighting Pipeline Layout"),
         bind_group_layouts: &[
@@ -842,6 +1769,7 @@ fn create_lighting_pipeline(
 
 /// Create the deferred lighting pipeline with splat compositing.
 /// Returns (gbuffer_layout, gbuffer_bg, splat_composite_layout, splat_composite_bg, pipeline).
+#[allow(clippy::too_many_arguments)]
 fn create_lighting_pipeline_with_splats(
     device: &wgpu::Device,
     wgsl_source: &str,
@@ -849,6 +1777,7 @@ fn create_lighting_pipeline_with_splats(
     resources: &HashMap<String, GpuResource>,
     camera_bind_group_layout: &wgpu::BindGroupLayout,
     gbuffer_sampler: &wgpu::Sampler,
+    ao_fallback_view: &wgpu::TextureView,
     light_bind_group_layout: &wgpu::BindGroupLayout,
 ) -> (
     wgpu::BindGroupLayout,
@@ -912,108 +1841,279 @@ fn create_lighting_pipeline_with_splats(
                 },
                 count: None,
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 5,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
         ],
     });
 
-    let albedo_view = &resources.get("gbuffer_albedo").expect("gbuffer_albedo missing").view;
-    let normal_view = &resources.get("gbuffer_normal").expect("gbuffer_normal missing").view;
-    let gbuffer_depth_view = &resources.get("gbuffer_depth").expect("gbuffer_depth missing").view;
-    let emission_view = resources
-        .get("gbuffer_emission")
-        .map(|r| &r.view)
-        .unwrap_or(albedo_view);
+    let gbuffer_bind_group = create_gbuffer_bind_group(
+        device,
+        "GBuffer Input Bind Group",
+        &gbuffer_layout,
+        resources,
+        gbuffer_sampler,
+        ao_fallback_view,
+    )
+    .expect("G-buffer resources missing");
 
-    let gbuffer_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("GBuffer Input Bind Group"),
-        layout: &gbuffer_layout,
+    // Group 3: splat composite textures (splat_color + splat_depth)
+    let splat_composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Splat Composite Layout"),
         entries: &[
-            wgpu::BindGroupEntry {
+            wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::TextureView(albedo_view),
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
             },
-            wgpu::BindGroupEntry {
+            wgpu::BindGroupLayoutEntry {
                 binding: 1,
-                resource: wgpu::BindingResource::TextureView(normal_view),
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
             },
+        ],
+    });
+
+    let splat_color_view = &resources.get("splat_color").expect("splat_color missing").view;
+    let splat_depth_view = &resources.get("splat_depth").expect("splat_depth missing").view;
+
+    let splat_composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Splat Composite Bind Group"),
+        layout: &splat_composite_layout,
+        entries: &[
             wgpu::BindGroupEntry {
-                binding: 2,
-                resource: wgpu::BindingResource::TextureView(gbuffer_depth_view),
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(splat_color_view),
             },
             wgpu::BindGroupEntry {
-                binding: 3,
-                resource: wgpu::BindingResource::Sampler(gbuffer_sampler),
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(splat_depth_view),
             },
-            wgpu::BindGroupEntry {
-                binding: 4,
-                resource: wgpu::BindingResource::TextureView(emission_view),
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Deferred Lighting + Splat Pipeline Layout"),
+        bind_group_layouts: &[
+            camera_bind_group_layout,
+            &gbuffer_layout,
+            light_bind_group_layout,
+            &splat_composite_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+
+    let output_format = color_targets
+        .first()
+        .and_then(|name| resources.get(name))
+        .map(|r| r.format)
+        .unwrap_or(wgpu::TextureFormat::Rgba16Float);
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Deferred Lighting + Splat Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: output_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+
+    (
+        gbuffer_layout,
+        gbuffer_bind_group,
+        splat_composite_layout,
+        splat_composite_bind_group,
+        pipeline,
+    )
+}
+
+/// Create the SSAO pipeline: camera at group 0, G-buffer normal + depth at group 1.
+fn create_ssao_pipeline(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    color_targets: &[String],
+    resources: &HashMap<String, GpuResource>,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::RenderPipeline) {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("SSAO Shader"),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+
+    let ssao_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("SSAO Input Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
             },
         ],
     });
 
-    // Group 3: splat composite textures (splat_color + splat_depth)
-    let splat_composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("Splat Composite Layout"),
+    let ssao_bind_group = create_ssao_bind_group(device, "SSAO Input Bind Group", &ssao_layout, resources)
+        .expect("G-buffer resources missing for SSAO");
+
+    let output_format = color_targets
+        .first()
+        .and_then(|name| resources.get(name))
+        .map(|r| r.format)
+        .unwrap_or(wgpu::TextureFormat::R8Unorm);
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("SSAO Pipeline Layout"),
+        bind_group_layouts: &[camera_bind_group_layout, &ssao_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("SSAO Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: output_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+
+    (ssao_layout, ssao_bind_group, pipeline)
+}
+
+/// Blend that draws behind the destination's coverage (alpha).
+const UNDER_BLEND: wgpu::BlendComponent = wgpu::BlendComponent {
+    src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+    dst_factor: wgpu::BlendFactor::One,
+    operation: wgpu::BlendOperation::Add,
+};
+
+/// Create the skybox pipeline: a fullscreen triangle at the far plane,
+/// depth-tested (read-only) against `depth` so it only covers empty pixels,
+/// and blended under the target's alpha so splats composited there by the
+/// lighting pass stay in front.
+fn create_skybox_pipeline(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    color_target: &str,
+    depth: &str,
+    resources: &HashMap<String, GpuResource>,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+) -> (wgpu::BindGroupLayout, wgpu::RenderPipeline) {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Skybox Shader"),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+
+    let skybox_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Skybox Layout"),
         entries: &[
+            cube_texture_layout_entry(0),
             wgpu::BindGroupLayoutEntry {
-                binding: 0,
+                binding: 1,
                 visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    multisampled: false,
-                },
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                 count: None,
             },
             wgpu::BindGroupLayoutEntry {
-                binding: 1,
+                binding: 2,
                 visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    sample_type: wgpu::TextureSampleType::Depth,
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    multisampled: false,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
                 },
                 count: None,
             },
         ],
     });
 
-    let splat_color_view = &resources.get("splat_color").expect("splat_color missing").view;
-    let splat_depth_view = &resources.get("splat_depth").expect("splat_depth missing").view;
-
-    let splat_composite_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("Splat Composite Bind Group"),
-        layout: &splat_composite_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(splat_color_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::TextureView(splat_depth_view),
-            },
-        ],
-    });
+    let output_format = resources
+        .get(color_target)
+        .map(|r| r.format)
+        .unwrap_or(wgpu::TextureFormat::Rgba16Float);
+    let depth_format = resources
+        .get(depth)
+        .map(|r| r.format)
+        .unwrap_or(wgpu::TextureFormat::Depth32Float);
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Deferred Lighting + Splat Pipeline Layout"),
-        bind_group_layouts: &[
-            camera_bind_group_layout,
-            &gbuffer_layout,
-            light_bind_group_layout,
-            &splat_composite_layout,
-        ],
+        label: Some("Skybox Pipeline Layout"),
+        bind_group_layouts: &[camera_bind_group_layout, &skybox_layout],
         push_constant_ranges: &[],
     });
 
-    let output_format = color_targets
-        .first()
-        .and_then(|name| resources.get(name))
-        .map(|r| r.format)
-        .unwrap_or(wgpu::TextureFormat::Rgba16Float);
-
     let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Deferred Lighting + Splat Pipeline"),
+        label: Some("Skybox Pipeline"),
         layout: Some(&pipeline_layout),
         vertex: wgpu::VertexState {
             module: &shader_module,
@@ -1026,7 +2126,10 @@ fn create_lighting_pipeline_with_splats(
             entry_point: Some("fs_main"),
             targets: &[Some(wgpu::ColorTargetState {
                 format: output_format,
-                blend: Some(wgpu::BlendState::REPLACE),
+                blend: Some(wgpu::BlendState {
+                    color: UNDER_BLEND,
+                    alpha: UNDER_BLEND,
+                }),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: Default::default(),
@@ -1035,19 +2138,19 @@ fn create_lighting_pipeline_with_splats(
             topology: wgpu::PrimitiveTopology::TriangleList,
             ..Default::default()
         },
-        depth_stencil: None,
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
         multisample: wgpu::MultisampleState::default(),
         multiview: None,
         cache: None,
     });
 
-    (
-        gbuffer_layout,
-        gbuffer_bind_group,
-        splat_composite_layout,
-        splat_composite_bind_group,
-        pipeline,
-    )
+    (skybox_layout, pipeline)
 }
 
 /// Create a bloom extraction pipeline: reads HDR buffer, outputs to half-res bloom buffer.
@@ -1056,20 +2159,14 @@ fn create_bloom_pipeline(
     wgsl_source: &str,
     color_targets: &[String],
     resources: &HashMap<String, GpuResource>,
-    _gbuffer_sampler: &wgpu::Sampler,
+    hdr_sampler: &wgpu::Sampler,
+    params_buffer: &wgpu::Buffer,
 ) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::RenderPipeline) {
     let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Bloom Shader"),
         source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
     });
 
-    let hdr_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        label: Some("Bloom HDR Sampler"),
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
-    });
-
     let bloom_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("Bloom Input Layout"),
         entries: &[
@@ -1089,6 +2186,16 @@ fn create_bloom_pipeline(
                 ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                 count: None,
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
         ],
     });
 
@@ -1097,20 +2204,14 @@ fn create_bloom_pipeline(
         .map(|r| &r.view)
         .expect("hdr_buffer resource missing for bloom");
 
-    let bloom_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("Bloom Input Bind Group"),
-        layout: &bloom_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(hdr_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::Sampler(&hdr_sampler),
-            },
-        ],
-    });
+    let bloom_bind_group = create_bloom_bind_group(
+        device,
+        "Bloom Input Bind Group",
+        &bloom_layout,
+        hdr_view,
+        hdr_sampler,
+        params_buffer,
+    );
 
     // Output format from the bloom_buffer resource
     let output_format = color_targets
@@ -1157,12 +2258,42 @@ fn create_bloom_pipeline(
     (bloom_layout, bloom_bind_group, pipeline)
 }
 
+/// Bloom bind group: HDR + sampler + bloom params.
+pub(crate) fn create_bloom_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    hdr_view: &wgpu::TextureView,
+    hdr_sampler: &wgpu::Sampler,
+    params_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(hdr_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(hdr_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: params_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
 fn create_tonemap_pipeline(
     device: &wgpu::Device,
     wgsl_source: &str,
     _color_targets: &[String],
     resources: &HashMap<String, GpuResource>,
-    _gbuffer_sampler: &wgpu::Sampler,
+    hdr_sampler: &wgpu::Sampler,
+    grading: &ColorGrading,
     surface_format: wgpu::TextureFormat,
 ) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::RenderPipeline) {
     let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -1170,15 +2301,7 @@ fn create_tonemap_pipeline(
         source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
     });
 
-    // Create a filtering sampler for tonemap (reads HDR buffer with filtering)
-    let hdr_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        label: Some("HDR Sampler"),
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
-    });
-
-    // Group 0: HDR texture + sampler + bloom texture
+    // Group 0: HDR texture + sampler + bloom texture + grading LUT + grading uniforms
     let tonemap_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("Tonemap Input Layout"),
         entries: &[
@@ -1208,39 +2331,39 @@ fn create_tonemap_pipeline(
                 },
                 count: None,
             },
-        ],
-    });
-
-    let hdr_view = resources
-        .get("hdr_buffer")
-        .map(|r| &r.view)
-        .expect("hdr_buffer resource missing");
-
-    // Bloom buffer (fall back to HDR view if bloom not present yet)
-    let bloom_view = resources
-        .get("bloom_buffer")
-        .map(|r| &r.view)
-        .unwrap_or(hdr_view);
-
-    let tonemap_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("Tonemap Input Bind Group"),
-        layout: &tonemap_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(hdr_view),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::Sampler(&hdr_sampler),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D3,
+                    multisampled: false,
+                },
+                count: None,
             },
-            wgpu::BindGroupEntry {
-                binding: 2,
-                resource: wgpu::BindingResource::TextureView(bloom_view),
+            wgpu::BindGroupLayoutEntry {
+                binding: 4,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
             },
         ],
     });
 
+    let tonemap_bind_group = create_tonemap_bind_group(
+        device,
+        "Tonemap Input Bind Group",
+        &tonemap_layout,
+        resources,
+        hdr_sampler,
+        grading,
+    )
+    .expect("hdr_buffer resource missing");
+
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Tonemap Pipeline Layout"),
         bind_group_layouts: &[&tonemap_layout],
@@ -1279,11 +2402,71 @@ fn create_tonemap_pipeline(
     (tonemap_layout, tonemap_bind_group, pipeline)
 }
 
+/// Grading state for a tonemap pass, with the fallback LUT bound until
+/// `ColorGradingCache::sync` loads the configured one.
+fn new_color_grading(device: &wgpu::Device, settings: crate::scene::ColorGradingSettings) -> ColorGrading {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Color Grading Uniforms"),
+        size: std::mem::size_of::<ColorGradingUniforms>() as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    ColorGrading {
+        pipeline_settings: settings.clone(),
+        settings,
+        lut_view: crate::color_grading::fallback_lut_view(device),
+        lut_size: 0,
+        buffer,
+        lut_source: None,
+    }
+}
+
+/// Tonemap bind group: HDR + sampler + bloom (HDR again without one) + grading.
+/// None if there is no `hdr_buffer`.
+pub(crate) fn create_tonemap_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    resources: &HashMap<String, GpuResource>,
+    hdr_sampler: &wgpu::Sampler,
+    grading: &ColorGrading,
+) -> Option<wgpu::BindGroup> {
+    let hdr_view = resources.get("hdr_buffer").map(|r| &r.view)?;
+    let bloom_view = resources.get("bloom_buffer").map(|r| &r.view).unwrap_or(hdr_view);
+    Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(hdr_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(hdr_sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(bloom_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&grading.lut_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: grading.buffer.as_entire_binding(),
+            },
+        ],
+    }))
+}
+
 /// Create the FXAA post-processing pipeline (reads LDR buffer, writes to swapchain).
 fn create_fxaa_pipeline(
     device: &wgpu::Device,
     wgsl_source: &str,
     resources: &HashMap<String, GpuResource>,
+    ldr_sampler: &wgpu::Sampler,
     surface_format: wgpu::TextureFormat,
 ) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::RenderPipeline) {
     let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -1291,13 +2474,6 @@ fn create_fxaa_pipeline(
         source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
     });
 
-    let ldr_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        label: Some("FXAA LDR Sampler"),
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        ..Default::default()
-    });
-
     let fxaa_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("FXAA Input Layout"),
         entries: &[
@@ -1335,7 +2511,7 @@ fn create_fxaa_pipeline(
             },
             wgpu::BindGroupEntry {
                 binding: 1,
-                resource: wgpu::BindingResource::Sampler(&ldr_sampler),
+                resource: wgpu::BindingResource::Sampler(ldr_sampler),
             },
         ],
     });
@@ -1378,6 +2554,97 @@ fn create_fxaa_pipeline(
     (fxaa_layout, fxaa_bind_group, pipeline)
 }
 
+/// Create the TAA resolve pipeline: camera at group 0, inputs at group 1,
+/// writing the pass output (swapchain or resource) and the history resolve.
+#[allow(clippy::too_many_arguments)]
+fn create_taa_pipeline(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    color_targets: &[String],
+    resources: &HashMap<String, GpuResource>,
+    inputs: &TaaInputs,
+    sampler: &wgpu::Sampler,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+    surface_format: wgpu::TextureFormat,
+) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::RenderPipeline) {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("TAA Shader"),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+
+    let texture_entry = |binding: u32, sample_type: wgpu::TextureSampleType| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+    let taa_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("TAA Input Layout"),
+        entries: &[
+            texture_entry(0, wgpu::TextureSampleType::Float { filterable: false }),
+            texture_entry(1, wgpu::TextureSampleType::Depth),
+            texture_entry(2, wgpu::TextureSampleType::Float { filterable: true }),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    });
+    let taa_bind_group = create_taa_bind_group(device, "TAA Input Bind Group", &taa_layout, resources, inputs, sampler)
+        .expect("TAA inputs were checked when allocating the resolve target");
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("TAA Pipeline Layout"),
+        bind_group_layouts: &[camera_bind_group_layout, &taa_layout],
+        push_constant_ranges: &[],
+    });
+
+    let targets: Vec<Option<wgpu::ColorTargetState>> = color_targets
+        .iter()
+        .map(|name| {
+            let format = resources.get(name).map(|r| r.format).unwrap_or(surface_format);
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+        .collect();
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("TAA Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &targets,
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+
+    (taa_layout, taa_bind_group, pipeline)
+}
+
 /// Create a shadow depth rendering pipeline.
 fn create_shadow_pipeline(
     device: &wgpu::Device,
@@ -0,0 +1,233 @@
+//! `enabled_if:` conditions of pipeline passes, checked every frame so one
+//! pipeline file can serve scenes with and without splats, bloom or water.
+//!
+//! A condition is one of:
+//! - a render toggle (`bloom`, `point_lights`, `emission`, `torch_flicker`),
+//!   as switched by the debug keys and `render.set`;
+//! - `has <component>`: some entity of the scene has the component, named as
+//!   in scene YAML (`has gaussian_splat`);
+//! - `quality <op> <preset>`: compares the quality preset, ordered
+//!   `low` < `medium` < `high`, with `==`, `!=`, `<`, `<=`, `>` or `>=`;
+//! - `setting.<name>`: a feature flag the pipeline declares under
+//!   `settings.flags`, overridable from naive.yaml (`render_settings`), the
+//!   command line (`--setting name=off`) and Lua (`render.set("setting.name", false)`).
+//!
+//! A leading `!` negates any of them.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::components::{
+    Animator, DirectionalLight, Foliage, GaussianSplat, ParticleEmitter, PointLight, Portal, Skybox, SpotLight,
+    Terrain, Water,
+};
+use crate::project_config::QualityPreset;
+
+use super::RenderDebugState;
+
+/// Components `has` can test for, by their scene YAML name.
+pub const HAS_COMPONENTS: &[&str] = &[
+    "gaussian_splat",
+    "particle_emitter",
+    "point_light",
+    "spot_light",
+    "directional_light",
+    "skybox",
+    "water",
+    "terrain",
+    "foliage",
+    "portal",
+    "animator",
+];
+
+/// One parsed `enabled_if` condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassCondition {
+    negated: bool,
+    test: ConditionTest,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ConditionTest {
+    Toggle(&'static str),
+    Has(&'static str),
+    /// Holds when the preset compares to the given one with any of the orderings.
+    Quality(&'static [Ordering], QualityPreset),
+    /// A pipeline feature flag and the pipeline's default for it.
+    Setting(String, bool),
+}
+
+const TOGGLES: &[&str] = &["bloom", "point_lights", "emission", "torch_flicker"];
+
+/// Comparison operators, the two-character ones first so they match whole.
+const OPERATORS: &[(&str, &[Ordering])] = &[
+    ("==", &[Ordering::Equal]),
+    ("!=", &[Ordering::Less, Ordering::Greater]),
+    ("<=", &[Ordering::Less, Ordering::Equal]),
+    (">=", &[Ordering::Greater, Ordering::Equal]),
+    ("<", &[Ordering::Less]),
+    (">", &[Ordering::Greater]),
+];
+
+impl PassCondition {
+    /// Parse a condition; `flags` are the pipeline's `settings.flags`.
+    pub fn parse(source: &str, flags: &HashMap<String, bool>) -> Result<Self, String> {
+        let source = source.trim();
+        let (negated, rest) = match source.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, source),
+        };
+
+        let test = if let Some(name) = rest.strip_prefix("setting.") {
+            let name = name.trim();
+            let default = *flags.get(name).ok_or_else(|| {
+                format!("'{}': the pipeline declares no flag '{}' under settings.flags", source, name)
+            })?;
+            ConditionTest::Setting(name.to_string(), default)
+        } else if let Some(component) = rest.strip_prefix("has ") {
+            let component = component.trim();
+            let name = HAS_COMPONENTS.iter().copied().find(|c| *c == component).ok_or_else(|| {
+                format!("unknown component '{}' (expected one of: {})", component, HAS_COMPONENTS.join(", "))
+            })?;
+            ConditionTest::Has(name)
+        } else if let Some(comparison) = rest.strip_prefix("quality") {
+            let comparison = comparison.trim_start();
+            let &(op, orderings) = OPERATORS
+                .iter()
+                .find(|(op, _)| comparison.starts_with(*op))
+                .ok_or_else(|| format!("'{}': expected `quality <op> <preset>` with ==, !=, <, <=, > or >=", source))?;
+            let preset = match comparison[op.len()..].trim() {
+                "low" => QualityPreset::Low,
+                "medium" => QualityPreset::Medium,
+                "high" => QualityPreset::High,
+                other => return Err(format!("unknown quality preset '{}' (expected low, medium or high)", other)),
+            };
+            ConditionTest::Quality(orderings, preset)
+        } else {
+            let name = TOGGLES.iter().copied().find(|t| *t == rest).ok_or_else(|| {
+                format!(
+                    "unknown condition '{}' (expected a toggle ({}), `has <component>`, `quality <op> <preset>` or `setting.<flag>`)",
+                    source,
+                    TOGGLES.join(", ")
+                )
+            })?;
+            ConditionTest::Toggle(name)
+        };
+        Ok(Self { negated, test })
+    }
+
+    /// Whether the condition holds for this frame's render settings and scene.
+    pub fn holds(&self, debug: &RenderDebugState, world: &hecs::World) -> bool {
+        let result = match &self.test {
+            ConditionTest::Toggle(name) => match *name {
+                "bloom" => debug.bloom_enabled,
+                "point_lights" => debug.point_lights_enabled,
+                "emission" => debug.emission_enabled,
+                _ => debug.torch_flicker_enabled,
+            },
+            ConditionTest::Has(component) => scene_has(world, component),
+            ConditionTest::Quality(orderings, preset) => orderings.contains(&debug.quality.cmp(preset)),
+            ConditionTest::Setting(name, default) => debug.settings.get(name).copied().unwrap_or(*default),
+        };
+        result != self.negated
+    }
+}
+
+/// Parse a pass's `enabled_if` conditions (all must hold).
+pub fn parse_conditions(sources: &[&str], flags: &HashMap<String, bool>) -> Result<Vec<PassCondition>, String> {
+    sources.iter().map(|source| PassCondition::parse(source, flags)).collect()
+}
+
+/// Parse a `name=value` flag override from `--setting`. Accepts
+/// true/false, on/off, yes/no and 1/0.
+pub fn parse_setting_override(arg: &str) -> Result<(String, bool), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("--setting '{}': expected name=value", arg))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("--setting '{}': missing flag name", arg));
+    }
+    let value = match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "on" | "yes" | "1" => true,
+        "false" | "off" | "no" | "0" => false,
+        other => return Err(format!("--setting '{}': expected on or off, got '{}'", arg, other)),
+    };
+    Ok((name.to_string(), value))
+}
+
+fn scene_has(world: &hecs::World, component: &str) -> bool {
+    fn any<T: hecs::Component>(world: &hecs::World) -> bool {
+        world.query::<&T>().iter().next().is_some()
+    }
+    match component {
+        "gaussian_splat" => any::<GaussianSplat>(world),
+        "particle_emitter" => any::<ParticleEmitter>(world),
+        "point_light" => any::<PointLight>(world),
+        "spot_light" => any::<SpotLight>(world),
+        "directional_light" => any::<DirectionalLight>(world),
+        "skybox" => any::<Skybox>(world),
+        "water" => any::<Water>(world),
+        "terrain" => any::<Terrain>(world),
+        "foliage" => any::<Foliage>(world),
+        "portal" => any::<Portal>(world),
+        "animator" => any::<Animator>(world),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pass_conditions() {
+        let mut world = hecs::World::new();
+        let mut debug = RenderDebugState::default();
+        let flags = HashMap::new();
+        let holds = |source: &str, debug: &RenderDebugState, world: &hecs::World| {
+            PassCondition::parse(source, &flags).unwrap().holds(debug, world)
+        };
+
+        assert!(holds("bloom", &debug, &world));
+        assert!(!holds("!bloom", &debug, &world));
+        assert!(!holds("has gaussian_splat", &debug, &world));
+        world.spawn((PointLight { color: glam::Vec3::ONE, intensity: 1.0, range: 5.0, casts_shadows: false },));
+        assert!(holds("has point_light", &debug, &world));
+        assert!(!holds("! has point_light", &debug, &world));
+
+        QualityPreset::Medium.apply(&mut debug);
+        assert!(holds("quality >= medium", &debug, &world));
+        assert!(holds("quality<high", &debug, &world));
+        assert!(!holds("quality == low", &debug, &world));
+        assert!(holds("quality != low", &debug, &world));
+
+        assert!(PassCondition::parse("has mesh", &flags).unwrap_err().contains("unknown component"));
+        assert!(PassCondition::parse("quality ~ low", &flags).is_err());
+        assert!(PassCondition::parse("quality >= ultra", &flags).unwrap_err().contains("ultra"));
+        assert!(PassCondition::parse("splats", &flags).unwrap_err().contains("unknown condition"));
+        assert_eq!(parse_conditions(&["bloom", "quality > low"], &flags).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_setting_conditions() {
+        let world = hecs::World::new();
+        let mut debug = RenderDebugState::default();
+        let flags = HashMap::from([("ssao".to_string(), true), ("volumetrics".to_string(), false)]);
+        let ssao = PassCondition::parse("setting.ssao", &flags).unwrap();
+        let volumetrics = PassCondition::parse("!setting.volumetrics", &flags).unwrap();
+        assert!(ssao.holds(&debug, &world));
+        assert!(volumetrics.holds(&debug, &world));
+
+        debug.set_param("setting.ssao", 0.0).unwrap();
+        debug.set_param("setting.volumetrics", 1.0).unwrap();
+        assert!(!ssao.holds(&debug, &world));
+        assert!(!volumetrics.holds(&debug, &world));
+
+        assert!(PassCondition::parse("setting.fog", &flags).unwrap_err().contains("no flag 'fog'"));
+        assert_eq!(parse_setting_override("ssao=off").unwrap(), ("ssao".to_string(), false));
+        assert_eq!(parse_setting_override(" fog = TRUE").unwrap(), ("fog".to_string(), true));
+        assert!(parse_setting_override("ssao").is_err());
+        assert!(parse_setting_override("ssao=maybe").is_err());
+    }
+}
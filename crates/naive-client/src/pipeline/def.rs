@@ -3,6 +3,10 @@ use std::path::Path;
 
 use serde::Deserialize;
 
+use crate::color_space::ColorSpace;
+use crate::format::Format;
+use crate::scene::ColorGradingSettings;
+
 // ---------------------------------------------------------------------------
 // Pipeline YAML serde types
 // ---------------------------------------------------------------------------
@@ -10,11 +14,15 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct PipelineFile {
+    /// Format version (`PIPELINE_FORMAT`); older pipelines are migrated on load.
+    #[serde(default = "current_pipeline_version")]
     pub version: u32,
     #[serde(default)]
     pub settings: PipelineSettings,
     #[serde(default)]
     pub resources: Vec<ResourceDef>,
+    #[serde(default)]
+    pub samplers: Vec<SamplerDef>,
     pub passes: Vec<PassDef>,
 }
 
@@ -29,6 +37,20 @@ pub struct PipelineSettings {
     pub max_fps: u32,
     #[serde(default)]
     pub hdr: bool,
+    /// MSAA sample count for rasterize passes (1 = off). 4 is supported on
+    /// every backend; 2 and 8 depend on the GPU and target formats.
+    #[serde(default = "default_samples")]
+    pub samples: u32,
+    /// Cull rasterize passes in a compute step that writes indirect draws,
+    /// where the device supports it (CPU culling otherwise).
+    #[serde(default)]
+    pub gpu_culling: bool,
+    /// Let resources whose passes don't overlap share one texture.
+    #[serde(default = "default_true")]
+    pub alias_resources: bool,
+    /// Feature flags for `enabled_if: setting.<name>`, with their defaults.
+    #[serde(default)]
+    pub flags: HashMap<String, bool>,
 }
 
 impl Default for PipelineSettings {
@@ -38,6 +60,10 @@ impl Default for PipelineSettings {
             vsync: true,
             max_fps: 60,
             hdr: false,
+            samples: 1,
+            gpu_culling: false,
+            alias_resources: true,
+            flags: HashMap::new(),
         }
     }
 }
@@ -51,6 +77,9 @@ fn default_true() -> bool {
 fn default_60() -> u32 {
     60
 }
+fn default_samples() -> u32 {
+    1
+}
 
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -61,6 +90,31 @@ pub struct ResourceDef {
     pub format: String,
     #[serde(default = "default_viewport")]
     pub size: String,
+    /// Overrides `settings.samples` for the rasterize passes writing this resource.
+    #[serde(default)]
+    pub samples: Option<u32>,
+    /// `srgb` stores 8-bit color sRGB encoded: writes encode and reads decode,
+    /// so shaders still see linear light. Default: the format's own.
+    #[serde(default)]
+    pub color_space: Option<ColorSpace>,
+}
+
+impl ResourceDef {
+    /// The texture format of `format`, in the declared color space.
+    pub fn texture_format(&self) -> Result<wgpu::TextureFormat, PipelineError> {
+        let format = super::resource::format_from_string(&self.format)?;
+        match self.color_space {
+            None => Ok(format),
+            Some(space) => space.apply(format).ok_or_else(|| {
+                PipelineError::InvalidFormat(format!(
+                    "Resource '{}': '{}' has no {} variant (float formats always hold linear values)",
+                    self.name,
+                    self.format,
+                    space.name()
+                ))
+            }),
+        }
+    }
 }
 
 fn default_viewport() -> String {
@@ -73,6 +127,9 @@ pub struct PassDef {
     pub name: String,
     #[serde(rename = "type")]
     pub pass_type: String,
+    /// Shader path, relative to the project. Optional for `ssao` and `taa`
+    /// passes, which fall back to the built-in shader.
+    #[serde(default)]
     pub shader: String,
     #[serde(default)]
     pub inputs: HashMap<String, String>,
@@ -84,6 +141,280 @@ pub struct PassDef {
     pub cull: Option<String>,
     #[serde(default)]
     pub dispatch: Option<String>,
+    /// Load/clear behavior per output slot (same keys as `outputs`).
+    #[serde(default)]
+    pub targets: HashMap<String, TargetOpsDef>,
+    /// Named samplers per binding slot: `input` for the pass's texture reads,
+    /// `shadow` for the lighting pass's shadow map comparison.
+    #[serde(default)]
+    pub samplers: HashMap<String, String>,
+    /// Exposure, contrast, saturation and LUT for the tonemap pass.
+    #[serde(default)]
+    pub grading: Option<ColorGradingSettings>,
+    /// Threshold, knee, intensity and mip count for the bloom pass.
+    #[serde(default)]
+    pub bloom: Option<BloomSettings>,
+    /// Starting values of a `postprocess` pass's uniform parameters, by name.
+    #[serde(default)]
+    pub params: HashMap<String, ParamValue>,
+    /// Preprocessor defines the shader is compiled with, so one shader file
+    /// can serve several variants (`NORMAL_MAP: true`, `SAMPLES: 16`).
+    #[serde(default)]
+    pub defines: HashMap<String, DefineValue>,
+    /// What the shader writes: linear light (default), or `srgb` for values
+    /// it encodes itself. Only `--color-audit` reads it.
+    #[serde(default)]
+    pub output_color_space: Option<ColorSpace>,
+    /// Conditions the pass only runs under, checked every frame (see
+    /// `condition`); while they don't hold, the targets it would clear are
+    /// cleared and the ones it draws over are left as they are.
+    #[serde(default)]
+    pub enabled_if: Option<ConditionList>,
+}
+
+/// `enabled_if:` of a pass: one condition, or a list that must all hold.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ConditionList {
+    One(String),
+    All(Vec<String>),
+}
+
+impl ConditionList {
+    pub fn sources(&self) -> Vec<&str> {
+        match self {
+            ConditionList::One(condition) => vec![condition.as_str()],
+            ConditionList::All(conditions) => conditions.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// A uniform parameter value: a number or a vector of up to four.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Scalar(f32),
+    Vector(Vec<f32>),
+}
+
+impl ParamValue {
+    pub fn components(&self) -> Vec<f32> {
+        match self {
+            ParamValue::Scalar(v) => vec![*v],
+            ParamValue::Vector(v) => v.clone(),
+        }
+    }
+}
+
+/// A shader define's value. Booleans become `1` / `0`, so they work with
+/// both `#if` and `#ifdef`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum DefineValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl DefineValue {
+    /// The value as shader source text.
+    pub fn to_source(&self) -> String {
+        match self {
+            DefineValue::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+            DefineValue::Int(i) => i.to_string(),
+            DefineValue::Float(f) => format!("{:?}", f),
+            DefineValue::Text(t) => t.clone(),
+        }
+    }
+}
+
+/// A pass's defines as `(name, value)` source pairs, sorted by name.
+pub fn define_pairs(defines: &HashMap<String, DefineValue>) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = defines.iter().map(|(k, v)| (k.clone(), v.to_source())).collect();
+    pairs.sort();
+    pairs
+}
+
+/// Whether a pass clears a target or keeps its previous contents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadKind {
+    #[default]
+    Clear,
+    Load,
+}
+
+/// Load behavior of one pass output (`targets.<slot>` in pass YAML).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TargetOpsDef {
+    #[serde(default)]
+    pub load: LoadKind,
+    /// RGBA clear color for color targets; defaults to the pass type's usual clear.
+    #[serde(default)]
+    pub clear_color: Option<[f64; 4]>,
+    /// Clear value for depth targets (default 1.0).
+    #[serde(default)]
+    pub clear_depth: Option<f32>,
+}
+
+impl TargetOpsDef {
+    pub fn color_load_op(&self, default: wgpu::Color) -> wgpu::LoadOp<wgpu::Color> {
+        match (self.load, self.clear_color) {
+            (LoadKind::Load, _) => wgpu::LoadOp::Load,
+            (LoadKind::Clear, Some([r, g, b, a])) => wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }),
+            (LoadKind::Clear, None) => wgpu::LoadOp::Clear(default),
+        }
+    }
+
+    pub fn depth_load_op(&self) -> wgpu::LoadOp<f32> {
+        match self.load {
+            LoadKind::Load => wgpu::LoadOp::Load,
+            LoadKind::Clear => wgpu::LoadOp::Clear(self.clear_depth.unwrap_or(1.0)),
+        }
+    }
+}
+
+/// Bloom parameters (`bloom:` on the bloom pass). The pass's shader extracts
+/// pixels brighter than `threshold` (softened over `knee`) into its target,
+/// then the built-in chain blurs it over `mips` levels, each half the size
+/// of the last, and scales the result by `intensity`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct BloomSettings {
+    #[serde(default = "default_bloom_threshold")]
+    pub threshold: f32,
+    #[serde(default = "default_bloom_knee")]
+    pub knee: f32,
+    #[serde(default = "default_bloom_intensity")]
+    pub intensity: f32,
+    #[serde(default = "default_bloom_mips")]
+    pub mips: u32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: default_bloom_threshold(),
+            knee: default_bloom_knee(),
+            intensity: default_bloom_intensity(),
+            mips: default_bloom_mips(),
+        }
+    }
+}
+
+fn default_bloom_threshold() -> f32 {
+    0.5
+}
+fn default_bloom_knee() -> f32 {
+    0.3
+}
+fn default_bloom_intensity() -> f32 {
+    0.7
+}
+fn default_bloom_mips() -> u32 {
+    6
+}
+
+/// Texture filtering of a sampler (magnification, minification and mips).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterKind {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+/// Addressing of UVs outside [0, 1], applied to all axes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressKind {
+    #[default]
+    Clamp,
+    Repeat,
+    Mirror,
+}
+
+/// Depth comparison of a comparison sampler (shadow maps).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareKind {
+    Never,
+    Less,
+    LessEqual,
+    Equal,
+    GreaterEqual,
+    Greater,
+    NotEqual,
+    Always,
+}
+
+/// A named sampler (`samplers:` in pipeline YAML), referenced by passes
+/// through their `samplers` slots and by materials through `sampler`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SamplerDef {
+    pub name: String,
+    #[serde(default)]
+    pub filter: FilterKind,
+    #[serde(default)]
+    pub address_mode: AddressKind,
+    /// Max anisotropy, 1 (off) to 16. Needs linear filtering.
+    #[serde(default = "default_anisotropy")]
+    pub anisotropy: u16,
+    #[serde(default)]
+    pub compare: Option<CompareKind>,
+}
+
+fn default_anisotropy() -> u16 {
+    1
+}
+
+impl SamplerDef {
+    /// The wgpu descriptor, or an error for settings wgpu would reject.
+    pub fn descriptor(&self) -> Result<wgpu::SamplerDescriptor<'_>, PipelineError> {
+        if !(1..=16).contains(&self.anisotropy) {
+            return Err(PipelineError::InvalidFormat(format!(
+                "Sampler '{}': anisotropy must be 1 to 16 (got {})",
+                self.name, self.anisotropy
+            )));
+        }
+        if self.anisotropy > 1 && self.filter != FilterKind::Linear {
+            return Err(PipelineError::InvalidFormat(format!(
+                "Sampler '{}': anisotropy needs linear filtering",
+                self.name
+            )));
+        }
+        let filter = match self.filter {
+            FilterKind::Nearest => wgpu::FilterMode::Nearest,
+            FilterKind::Linear => wgpu::FilterMode::Linear,
+        };
+        let address_mode = match self.address_mode {
+            AddressKind::Clamp => wgpu::AddressMode::ClampToEdge,
+            AddressKind::Repeat => wgpu::AddressMode::Repeat,
+            AddressKind::Mirror => wgpu::AddressMode::MirrorRepeat,
+        };
+        let compare = self.compare.map(|c| match c {
+            CompareKind::Never => wgpu::CompareFunction::Never,
+            CompareKind::Less => wgpu::CompareFunction::Less,
+            CompareKind::LessEqual => wgpu::CompareFunction::LessEqual,
+            CompareKind::Equal => wgpu::CompareFunction::Equal,
+            CompareKind::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+            CompareKind::Greater => wgpu::CompareFunction::Greater,
+            CompareKind::NotEqual => wgpu::CompareFunction::NotEqual,
+            CompareKind::Always => wgpu::CompareFunction::Always,
+        });
+        Ok(wgpu::SamplerDescriptor {
+            label: Some(&self.name),
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            anisotropy_clamp: self.anisotropy,
+            compare,
+            ..Default::default()
+        })
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -115,10 +446,23 @@ impl std::fmt::Display for PipelineError {
 // Pipeline YAML loading
 // ---------------------------------------------------------------------------
 
+/// Pipeline files (`pipelines/*.yaml`).
+pub const PIPELINE_FORMAT: Format = Format { name: "pipeline", current: 1, migrations: &[] };
+
+fn current_pipeline_version() -> u32 {
+    PIPELINE_FORMAT.current
+}
+
 pub fn load_pipeline(path: &Path) -> Result<PipelineFile, PipelineError> {
     let contents = std::fs::read_to_string(path).map_err(PipelineError::IoError)?;
-    let pipeline: PipelineFile =
-        serde_yaml::from_str(&contents).map_err(PipelineError::ParseError)?;
+    let migrated = PIPELINE_FORMAT
+        .migrate(&contents)
+        .map_err(|e| PipelineError::InvalidFormat(format!("{}: {}", path.display(), e)))?;
+    let mut pipeline: PipelineFile = match migrated {
+        Some(doc) => serde_yaml::from_value(doc).map_err(PipelineError::ParseError)?,
+        None => serde_yaml::from_str(&contents).map_err(PipelineError::ParseError)?,
+    };
+    wire_ssao(&mut pipeline);
     tracing::info!(
         "Loaded pipeline v{} with {} passes and {} resources",
         pipeline.version,
@@ -128,6 +472,136 @@ pub fn load_pipeline(path: &Path) -> Result<PipelineFile, PipelineError> {
     Ok(pipeline)
 }
 
+// ---------------------------------------------------------------------------
+// SSAO auto-wiring
+// ---------------------------------------------------------------------------
+
+/// Resource the lighting pass reads ambient occlusion from.
+pub const SSAO_RESOURCE: &str = "ssao";
+
+/// Wire up ambient occlusion when the pipeline declares an `ssao` resource:
+/// add a built-in `ssao` pass if no pass writes it, and list it as an input
+/// of the fullscreen passes reading `gbuffer_depth` (the lighting pass) so
+/// the DAG runs SSAO first.
+pub fn wire_ssao(pipeline: &mut PipelineFile) {
+    if !pipeline.resources.iter().any(|r| r.name == SSAO_RESOURCE) {
+        return;
+    }
+    let written = pipeline
+        .passes
+        .iter()
+        .any(|p| p.outputs.values().any(|r| r == SSAO_RESOURCE));
+    if !written {
+        pipeline.passes.push(PassDef {
+            name: "ssao_pass".to_string(),
+            pass_type: "ssao".to_string(),
+            shader: String::new(),
+            inputs: HashMap::from([
+                ("normal".to_string(), "gbuffer_normal".to_string()),
+                ("depth".to_string(), "gbuffer_depth".to_string()),
+            ]),
+            outputs: HashMap::from([("color".to_string(), SSAO_RESOURCE.to_string())]),
+            sort: None,
+            cull: None,
+            dispatch: None,
+            targets: HashMap::new(),
+            samplers: HashMap::new(),
+            grading: None,
+            bloom: None,
+            params: HashMap::new(),
+            defines: HashMap::new(),
+            output_color_space: None,
+            enabled_if: None,
+        });
+    }
+    for pass in &mut pipeline.passes {
+        let reads_gbuffer = pass.inputs.values().any(|r| r == "gbuffer_depth");
+        if pass.pass_type == "fullscreen" && reads_gbuffer {
+            pass.inputs
+                .entry(SSAO_RESOURCE.to_string())
+                .or_insert_with(|| SSAO_RESOURCE.to_string());
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MSAA sample counts
+// ---------------------------------------------------------------------------
+
+/// MSAA sample count of a rasterize pass: the `samples` of its output
+/// resources if they set one (all must agree), else `settings.samples`.
+/// Other pass types always render single-sampled.
+pub fn pass_sample_count(pipeline: &PipelineFile, pass: &PassDef) -> Result<u32, PipelineError> {
+    if pass.pass_type != "rasterize" {
+        return Ok(1);
+    }
+    let mut count: Option<u32> = None;
+    for resource_name in pass.outputs.values() {
+        let samples = pipeline
+            .resources
+            .iter()
+            .find(|r| &r.name == resource_name)
+            .and_then(|r| r.samples);
+        match (count, samples) {
+            (Some(a), Some(b)) if a != b => {
+                return Err(PipelineError::InvalidFormat(format!(
+                    "Pass '{}': outputs disagree on samples ({} vs {})",
+                    pass.name, a, b
+                )));
+            }
+            (None, Some(b)) => count = Some(b),
+            _ => {}
+        }
+    }
+    let count = count.unwrap_or(pipeline.settings.samples);
+    if !matches!(count, 1 | 2 | 4 | 8) {
+        return Err(PipelineError::InvalidFormat(format!(
+            "Pass '{}': samples must be 1, 2, 4 or 8 (got {})",
+            pass.name, count
+        )));
+    }
+    Ok(count)
+}
+
+// ---------------------------------------------------------------------------
+// Named samplers
+// ---------------------------------------------------------------------------
+
+/// Look up the sampler a pass binds at `slot`, if it names one. Only
+/// fullscreen passes have sampler slots: `input` takes a regular sampler and
+/// `shadow` a comparison sampler.
+pub fn pass_sampler<'a>(
+    pipeline: &'a PipelineFile,
+    pass: &PassDef,
+    slot: &str,
+) -> Result<Option<&'a SamplerDef>, PipelineError> {
+    for key in pass.samplers.keys() {
+        let known = pass.pass_type == "fullscreen" && matches!(key.as_str(), "input" | "shadow");
+        if !known {
+            return Err(PipelineError::InvalidFormat(format!(
+                "Pass '{}': no sampler slot '{}' on {} passes",
+                pass.name, key, pass.pass_type
+            )));
+        }
+    }
+    let Some(name) = pass.samplers.get(slot) else {
+        return Ok(None);
+    };
+    let def = pipeline.samplers.iter().find(|s| &s.name == name).ok_or_else(|| {
+        PipelineError::InvalidFormat(format!("Pass '{}': unknown sampler '{}'", pass.name, name))
+    })?;
+    if (slot == "shadow") != def.compare.is_some() {
+        return Err(PipelineError::InvalidFormat(format!(
+            "Pass '{}': sampler '{}' {} a compare function for slot '{}'",
+            pass.name,
+            name,
+            if def.compare.is_some() { "must not set" } else { "needs" },
+            slot
+        )));
+    }
+    Ok(Some(def))
+}
+
 // ---------------------------------------------------------------------------
 // DAG builder -- topological sort via Kahn's algorithm
 // ---------------------------------------------------------------------------
@@ -138,15 +612,22 @@ pub fn load_pipeline(path: &Path) -> Result<PipelineFile, PipelineError> {
 /// whose value matches the name of a resource that pass A writes to, then
 /// A must execute before B. Special values "auto" and "swapchain" are not
 /// considered resources produced by other passes.
+///
+/// A resource written by several passes (e.g. a skybox drawn into the lit
+/// HDR buffer) is written in file order; a pass reading it between two
+/// writers sees the earlier write, any other reader sees the last one.
 pub fn build_dag(passes: &[PassDef]) -> Result<Vec<usize>, PipelineError> {
     let n = passes.len();
 
-    // Map: resource_name -> index of the pass that produces it
-    let mut producer: HashMap<&str, usize> = HashMap::new();
+    // Map: resource_name -> indices of the passes that write it, in file order
+    let mut writers: HashMap<&str, Vec<usize>> = HashMap::new();
     for (i, pass) in passes.iter().enumerate() {
         for resource_name in pass.outputs.values() {
             if resource_name != "swapchain" {
-                producer.insert(resource_name.as_str(), i);
+                let list = writers.entry(resource_name.as_str()).or_default();
+                if list.last() != Some(&i) {
+                    list.push(i);
+                }
             }
         }
     }
@@ -154,17 +635,33 @@ pub fn build_dag(passes: &[PassDef]) -> Result<Vec<usize>, PipelineError> {
     // Build adjacency list and in-degree counts
     let mut adj: Vec<Vec<usize>> = vec![vec![]; n];
     let mut in_degree: Vec<usize> = vec![0; n];
+    let mut add_edge = |from: usize, to: usize| {
+        if from != to && !adj[from].contains(&to) {
+            adj[from].push(to);
+            in_degree[to] += 1;
+        }
+    };
+
+    for list in writers.values() {
+        for pair in list.windows(2) {
+            add_edge(pair[0], pair[1]);
+        }
+    }
 
     for (i, pass) in passes.iter().enumerate() {
         for input_resource in pass.inputs.values() {
             if input_resource == "auto" {
                 continue;
             }
-            if let Some(&producer_idx) = producer.get(input_resource.as_str()) {
-                if producer_idx != i {
-                    adj[producer_idx].push(i);
-                    in_degree[i] += 1;
+            let Some(list) = writers.get(input_resource.as_str()) else {
+                continue;
+            };
+            match list.iter().rposition(|&w| w < i) {
+                Some(prev) if prev + 1 < list.len() => {
+                    add_edge(list[prev], i);
+                    add_edge(i, list[prev + 1]);
                 }
+                _ => add_edge(*list.last().unwrap(), i),
             }
         }
     }
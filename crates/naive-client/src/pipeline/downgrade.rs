@@ -0,0 +1,272 @@
+//! Capability checks run before a pipeline is compiled. Where the device
+//! can't run the pipeline as written, a fallback is picked instead of letting
+//! compilation fail, and every fallback is described in `Downgrades::report`.
+
+use std::collections::HashMap;
+
+use super::def::{PipelineError, PipelineFile};
+use super::gpu_culling::GPU_CULL_WORKGROUP_SIZE;
+use super::resource::{PointLightUniform, MAX_LIGHTS};
+
+/// Fallbacks applied to one pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Downgrades {
+    /// Resources allocated in another format than the YAML asks for.
+    pub formats: HashMap<String, wgpu::TextureFormat>,
+    /// Point lights the lighting pass holds (`MAX_LIGHTS` unless the storage
+    /// buffer binding limit is lower).
+    pub max_lights: usize,
+    /// Whether rasterize passes cull on the GPU (`settings.gpu_culling` on a
+    /// device that supports it).
+    pub gpu_culling: bool,
+    /// One line per fallback, for logs and `naive doctor`.
+    pub report: Vec<String>,
+}
+
+/// What `format` supports on a device opened with `features`, given the
+/// adapter's capabilities for it. Mirrors wgpu's validation: the adapter's
+/// capabilities apply only with `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`
+/// (otherwise just the WebGPU guarantees do), and 32-bit float formats filter
+/// only with `FLOAT32_FILTERABLE`.
+pub fn device_format_features(
+    format: wgpu::TextureFormat,
+    adapter_features: wgpu::TextureFormatFeatures,
+    features: wgpu::Features,
+) -> wgpu::TextureFormatFeatures {
+    if !features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+        return format.guaranteed_format_features(features);
+    }
+    let mut caps = adapter_features;
+    let float32 = matches!(
+        format,
+        wgpu::TextureFormat::R32Float | wgpu::TextureFormat::Rg32Float | wgpu::TextureFormat::Rgba32Float
+    );
+    if float32 && !features.contains(wgpu::Features::FLOAT32_FILTERABLE) {
+        caps.flags.remove(wgpu::TextureFormatFeatureFlags::FILTERABLE);
+    }
+    caps
+}
+
+/// Color resources are rendered to and read back with filtering samplers,
+/// and render pipelines enable (replace) blending on their targets.
+fn color_format_supported(caps: wgpu::TextureFormatFeatures) -> bool {
+    caps.allowed_usages
+        .contains(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING)
+        && caps
+            .flags
+            .contains(wgpu::TextureFormatFeatureFlags::FILTERABLE | wgpu::TextureFormatFeatureFlags::BLENDABLE)
+}
+
+/// Next format to try when `format` is unsupported.
+fn fallback_format(format: wgpu::TextureFormat) -> Option<wgpu::TextureFormat> {
+    match format {
+        wgpu::TextureFormat::Rgba32Float => Some(wgpu::TextureFormat::Rgba16Float),
+        wgpu::TextureFormat::Rgba16Float | wgpu::TextureFormat::Rg16Float | wgpu::TextureFormat::R16Float => {
+            Some(wgpu::TextureFormat::Rgba8Unorm)
+        }
+        _ => None,
+    }
+}
+
+/// The format a resource gets on this device: `format` if supported, else the
+/// first supported fallback. `format_features` reports what the device
+/// supports for a format (see `device_format_features`).
+pub fn supported_format(
+    format: wgpu::TextureFormat,
+    format_features: &dyn Fn(wgpu::TextureFormat) -> wgpu::TextureFormatFeatures,
+) -> Option<wgpu::TextureFormat> {
+    if format.is_depth_stencil_format() {
+        return Some(format);
+    }
+    let mut candidate = format;
+    while !color_format_supported(format_features(candidate)) {
+        candidate = fallback_format(candidate)?;
+    }
+    Some(candidate)
+}
+
+/// Storage buffers the lighting pass reads per stage: the point lights and
+/// the cluster light lists.
+pub const LIGHTING_STORAGE_BUFFERS: u32 = 2;
+
+/// Point lights that fit in one storage buffer binding.
+pub fn light_capacity(limits: &wgpu::Limits) -> usize {
+    let max_bytes = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+    (max_bytes / std::mem::size_of::<PointLightUniform>() as u64).min(MAX_LIGHTS as u64) as usize
+}
+
+/// GPU culling dispatches a compute shader that writes draw commands into a
+/// storage buffer and draws them with `draw_indexed_indirect`. The GL
+/// backend can't run indirect draws fully, and is recognized by lacking
+/// `INDIRECT_FIRST_INSTANCE`, which every native backend offers.
+pub fn gpu_culling_supported(features: wgpu::Features, limits: &wgpu::Limits) -> bool {
+    features.contains(wgpu::Features::INDIRECT_FIRST_INSTANCE)
+        && limits.max_compute_workgroup_size_x >= GPU_CULL_WORKGROUP_SIZE
+        && limits.max_compute_invocations_per_workgroup >= GPU_CULL_WORKGROUP_SIZE
+        && limits.max_storage_buffers_per_shader_stage >= 2
+}
+
+/// Check a pipeline against the device's features, format capabilities and limits.
+pub fn plan_downgrades(
+    pipeline: &PipelineFile,
+    features: wgpu::Features,
+    format_features: &dyn Fn(wgpu::TextureFormat) -> wgpu::TextureFormatFeatures,
+    limits: &wgpu::Limits,
+) -> Result<Downgrades, PipelineError> {
+    let mut formats = HashMap::new();
+    let mut report = Vec::new();
+    for def in &pipeline.resources {
+        let requested = def.texture_format()?;
+        let format = supported_format(requested, format_features).ok_or_else(|| {
+            PipelineError::InvalidFormat(format!(
+                "Resource '{}': format '{}' can't be rendered and filtered on this device",
+                def.name, def.format
+            ))
+        })?;
+        if format != requested {
+            report.push(format!(
+                "resource '{}': {} -> {:?} ('{}' isn't renderable, filterable and blendable here)",
+                def.name, def.format, format, def.format
+            ));
+            formats.insert(def.name.clone(), format);
+        }
+    }
+
+    if limits.max_storage_buffers_per_shader_stage < LIGHTING_STORAGE_BUFFERS {
+        return Err(PipelineError::InvalidFormat(format!(
+            "Clustered lighting needs {} storage buffers per shader stage, the device allows {}",
+            LIGHTING_STORAGE_BUFFERS, limits.max_storage_buffers_per_shader_stage
+        )));
+    }
+    let max_lights = light_capacity(limits);
+    if max_lights == 0 {
+        return Err(PipelineError::InvalidFormat(format!(
+            "Storage buffers are limited to {} bytes, too small for a point light",
+            limits.max_storage_buffer_binding_size
+        )));
+    }
+    if max_lights < MAX_LIGHTS {
+        report.push(format!(
+            "point lights: {} -> {} (storage buffer bindings are limited to {} bytes)",
+            MAX_LIGHTS, max_lights, limits.max_storage_buffer_binding_size
+        ));
+    }
+
+    let gpu_culling = pipeline.settings.gpu_culling && gpu_culling_supported(features, limits);
+    if pipeline.settings.gpu_culling && !gpu_culling {
+        report.push("gpu culling: GPU -> CPU (the device can't run indirect draws from a compute shader)".to_string());
+    }
+
+    Ok(Downgrades { formats, max_lights, gpu_culling, report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Format capabilities of a device without adapter-specific features.
+    fn guaranteed(format: wgpu::TextureFormat) -> wgpu::TextureFormatFeatures {
+        format.guaranteed_format_features(wgpu::Features::empty())
+    }
+
+    /// An adapter that can render, filter and blend every format.
+    fn full_adapter(features: wgpu::Features) -> impl Fn(wgpu::TextureFormat) -> wgpu::TextureFormatFeatures {
+        move |format| {
+            let adapter = wgpu::TextureFormatFeatures {
+                allowed_usages: wgpu::TextureUsages::all(),
+                flags: wgpu::TextureFormatFeatureFlags::all(),
+            };
+            device_format_features(format, adapter, features)
+        }
+    }
+
+    #[test]
+    fn test_rgba32f_falls_back_without_float32_blending() {
+        assert_eq!(
+            supported_format(wgpu::TextureFormat::Rgba32Float, &guaranteed),
+            Some(wgpu::TextureFormat::Rgba16Float)
+        );
+        assert_eq!(
+            supported_format(wgpu::TextureFormat::Rgba8Unorm, &guaranteed),
+            Some(wgpu::TextureFormat::Rgba8Unorm)
+        );
+        assert_eq!(
+            supported_format(wgpu::TextureFormat::Depth32Float, &guaranteed),
+            Some(wgpu::TextureFormat::Depth32Float)
+        );
+    }
+
+    #[test]
+    fn test_rgba32f_kept_when_adapter_supports_it() {
+        let adapter_specific = wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        let supported = full_adapter(adapter_specific | wgpu::Features::FLOAT32_FILTERABLE);
+        assert_eq!(
+            supported_format(wgpu::TextureFormat::Rgba32Float, &supported),
+            Some(wgpu::TextureFormat::Rgba32Float)
+        );
+
+        // Without FLOAT32_FILTERABLE the device can't filter it, whatever the adapter says
+        let unfilterable = full_adapter(adapter_specific);
+        assert_eq!(
+            supported_format(wgpu::TextureFormat::Rgba32Float, &unfilterable),
+            Some(wgpu::TextureFormat::Rgba16Float)
+        );
+
+        // Without adapter-specific features only the WebGPU guarantees apply
+        let guaranteed_only = full_adapter(wgpu::Features::FLOAT32_FILTERABLE);
+        assert_eq!(
+            supported_format(wgpu::TextureFormat::Rgba32Float, &guaranteed_only),
+            Some(wgpu::TextureFormat::Rgba16Float)
+        );
+    }
+
+    #[test]
+    fn test_plan_downgrades_reports_substitutions_and_lights() {
+        let yaml = r#"
+version: 1
+resources:
+  - name: gbuffer_normal
+    type: texture_2d
+    format: rgba32f
+  - name: hdr_buffer
+    type: texture_2d
+    format: rgba16f
+passes: []
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let plan = plan_downgrades(&pipeline, wgpu::Features::empty(), &guaranteed, &wgpu::Limits::default()).unwrap();
+        assert_eq!(plan.formats.len(), 1);
+        assert_eq!(plan.formats["gbuffer_normal"], wgpu::TextureFormat::Rgba16Float);
+        assert_eq!(plan.max_lights, MAX_LIGHTS);
+        assert_eq!(plan.report.len(), 1);
+
+        let limits = wgpu::Limits { max_storage_buffer_binding_size: 4096, ..Default::default() };
+        let plan = plan_downgrades(&pipeline, wgpu::Features::empty(), &guaranteed, &limits).unwrap();
+        // 64 bytes per light
+        assert_eq!(plan.max_lights, 64);
+        assert_eq!(plan.report.len(), 2);
+
+        let limits = wgpu::Limits { max_storage_buffers_per_shader_stage: 1, ..Default::default() };
+        assert!(plan_downgrades(&pipeline, wgpu::Features::empty(), &guaranteed, &limits).is_err());
+    }
+
+    #[test]
+    fn test_gpu_culling_falls_back_to_cpu() {
+        let mut pipeline: PipelineFile = serde_yaml::from_str("version: 1\nsettings:\n  gpu_culling: true\npasses: []\n").unwrap();
+        let native = wgpu::Features::INDIRECT_FIRST_INSTANCE;
+        let plan = plan_downgrades(&pipeline, native, &guaranteed, &wgpu::Limits::default()).unwrap();
+        assert!(plan.gpu_culling);
+        assert!(plan.report.is_empty());
+
+        // GL, or no compute shaders at all
+        let plan = plan_downgrades(&pipeline, wgpu::Features::empty(), &guaranteed, &wgpu::Limits::default()).unwrap();
+        assert!(!plan.gpu_culling);
+        assert_eq!(plan.report.len(), 1);
+        let limits = wgpu::Limits::downlevel_webgl2_defaults();
+        assert!(!gpu_culling_supported(native, &limits));
+
+        pipeline.settings.gpu_culling = false;
+        let plan = plan_downgrades(&pipeline, native, &guaranteed, &wgpu::Limits::default()).unwrap();
+        assert!(!plan.gpu_culling && plan.report.is_empty());
+    }
+}
@@ -1,20 +1,67 @@
 use std::collections::HashMap;
 
 use crate::camera::CameraState;
-use crate::components::{DirectionalLight, GaussianSplat, Hidden, MaterialOverride, MeshRenderer, PointLight, Transform};
+use crate::color_grading::ColorGradingUniforms;
+use crate::culling::{DrawStats, Frustum};
+use crate::environment::{EnvironmentMaps, EnvironmentUniforms};
+use crate::components::{DirectionalLight, GaussianSplat, MaterialOverride, PointLight, SpotLight, Transform};
 use crate::material::MaterialCache;
 use crate::mesh::MeshCache;
-use crate::renderer::{DrawUniformPool, DrawUniforms, GpuState, DRAW_UNIFORM_SIZE};
-use crate::splat::SplatCache;
+use crate::renderer::{BoundDrawState, DrawUniformPool, DrawUniforms, GpuState};
+use crate::splat::{SplatCache, SplatView};
 use crate::world::SceneWorld;
 
-use super::resource::{LightingUniforms, PointLightUniform, ShadowUniforms, MAX_LIGHTS, PassType};
+use super::resource::{
+    point_shadow_face_view_proj, LightingUniforms, PointLightUniform, PointShadowUniforms, ShadowUniforms,
+    MAX_POINT_SHADOWS, POINT_SHADOW_FACE_STRIDE, PassType,
+};
+use super::gpu_culling::{CullItem, GpuCulling, INDIRECT_COMMAND_SIZE};
+use super::hooks::{HookStage, RenderHookContext, RenderHooks};
+use super::profiler::GpuPassProfiler;
 use super::{CompiledPass, CompiledPipeline, RenderDebugState};
 
 // ---------------------------------------------------------------------------
 // Pipeline executor
 // ---------------------------------------------------------------------------
 
+/// Command encoders for one frame, submitted together in order. Compute and
+/// shadow passes depend only on this frame's uploads, so they record into
+/// their own command buffers ahead of the passes that read their output.
+/// Callers append overlays and UI to `main` or add their own buffers after it
+/// and submit the whole frame at once.
+pub struct FrameEncoders {
+    /// GPU culling and splat sort dispatches.
+    pub compute: wgpu::CommandEncoder,
+    /// Shadow map passes.
+    pub prepass: wgpu::CommandEncoder,
+    /// Geometry, splat, lighting and post-processing passes.
+    pub main: wgpu::CommandEncoder,
+    /// Meshes drawn and culled while recording.
+    pub draw_stats: DrawStats,
+}
+
+impl FrameEncoders {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self {
+            compute: device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Pipeline Compute Encoder"),
+            }),
+            prepass: device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Pipeline Prepass Encoder"),
+            }),
+            main: device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Pipeline Render Encoder"),
+            }),
+            draw_stats: DrawStats::default(),
+        }
+    }
+
+    /// Finish all encoders in submission order.
+    pub fn finish(self) -> [wgpu::CommandBuffer; 3] {
+        [self.compute.finish(), self.prepass.finish(), self.main.finish()]
+    }
+}
+
 /// Execute the compiled pipeline for one frame.
 #[allow(clippy::too_many_arguments)]
 pub fn execute_pipeline(
@@ -29,12 +76,11 @@ pub fn execute_pipeline(
     debug: &RenderDebugState,
     texture_resources: Option<&crate::mesh::TextureResources>,
     bone_palettes: &HashMap<hecs::Entity, crate::anim_system::BoneMatrixPalette>,
-    texture_cache: Option<&crate::texture_cache::TextureCache>,
 ) {
-    let output = match gpu.surface.get_current_texture() {
+    let output = match gpu.get_current_texture() {
         Ok(t) => t,
         Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-            gpu.surface.configure(&gpu.device, &gpu.config);
+            gpu.configure_surface();
             return;
         }
         Err(e) => {
@@ -47,19 +93,24 @@ pub fn execute_pipeline(
         .texture
         .create_view(&wgpu::TextureViewDescriptor::default());
 
-    let encoder = execute_pipeline_to_view(
-        gpu, compiled, scene_world, camera_state, draw_pool,
+    let frame = execute_pipeline_to_view(
+        &gpu.device, &gpu.queue, compiled, scene_world, camera_state, draw_pool,
         mesh_cache, material_cache, splat_cache, &swapchain_view, debug,
-        texture_resources, bone_palettes, texture_cache,
+        texture_resources, bone_palettes, None, None,
     );
 
-    gpu.queue.submit(std::iter::once(encoder.finish()));
+    gpu.queue.submit(frame.finish());
     output.present();
 }
 
-/// Execute the compiled multi-pass pipeline, returning the encoder for further passes.
+/// Execute the compiled multi-pass pipeline, returning the frame's encoders for further passes.
+/// Only needs the device and queue, so it works for windowed and offscreen targets alike.
+/// With a profiler, each pass is timed and the queries are resolved into the main encoder.
+/// Render hooks attached to a pass run in the same encoder right before and after it.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_pipeline_to_view(
-    gpu: &GpuState,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
     compiled: &CompiledPipeline,
     scene_world: &SceneWorld,
     camera_state: &CameraState,
@@ -71,18 +122,20 @@ pub fn execute_pipeline_to_view(
     debug: &RenderDebugState,
     texture_resources: Option<&crate::mesh::TextureResources>,
     bone_palettes: &HashMap<hecs::Entity, crate::anim_system::BoneMatrixPalette>,
-    texture_cache: Option<&crate::texture_cache::TextureCache>,
-) -> wgpu::CommandEncoder {
-
-    // Upload per-entity draw uniforms (skip hidden entities before incrementing draw_index)
-    let mut draw_index = 0u32;
-    for (entity, (transform, mesh_renderer)) in
-        scene_world.world.query::<(&Transform, &MeshRenderer)>().iter()
-    {
-        if scene_world.world.get::<&Hidden>(entity).is_ok() {
+    mut profiler: Option<&mut GpuPassProfiler>,
+    mut hooks: Option<&mut RenderHooks>,
+) -> FrameEncoders {
+
+    // Upload the draw uniforms of the frame's visible meshes (see `FrameDraw`).
+    // With GPU culling, each draw slot's world bounds go up alongside.
+    let gpu_culling = compiled.gpu_culling.as_ref().filter(|_| !debug.lod_lock.disable_culling);
+    let mut cull_items = Vec::new();
+    for item in draw_pool.draws() {
+        let entity = item.entity;
+        let Ok(transform) = scene_world.world.get::<&Transform>(entity) else {
             continue;
-        }
-        let material = material_cache.get(mesh_renderer.material_handle);
+        };
+        let material = material_cache.get(item.material);
         let model_matrix = transform.world_matrix;
         let normal_matrix = model_matrix.inverse().transpose();
 
@@ -112,56 +165,110 @@ pub fn execute_pipeline_to_view(
             .map(|c| [c[0], c[1], c[2], material.uniform.base_color[3]])
             .unwrap_or(material.uniform.base_color);
 
-        let gpu_mesh = mesh_cache.get(mesh_renderer.mesh_handle);
-        let has_texture = if material.albedo_texture.is_some() || gpu_mesh.texture_bind_group.is_some() { 1.0f32 } else { 0.0f32 };
+        let gpu_mesh = mesh_cache.get(item.mesh);
         // Check if entity has skeletal animation
         let entity_has_skin = scene_world.world
             .get::<&crate::components::Animator>(entity)
             .is_ok();
 
-        let draw_uniform = DrawUniforms {
-            model_matrix: model_matrix.to_cols_array_2d(),
-            normal_matrix: normal_matrix.to_cols_array_2d(),
-            base_color,
-            roughness,
-            metallic,
-            has_texture,
-            has_skin: if entity_has_skin { 1.0 } else { 0.0 },
-            emission,
-            _padding: [0.0; 20],
-        };
-
-        gpu.queue.write_buffer(
-            &draw_pool.buffer,
-            draw_index as u64 * DRAW_UNIFORM_SIZE,
-            bytemuck::cast_slice(&[draw_uniform]),
-        );
-        draw_index += 1;
+        // One uniform per submesh: glTF material factors fill in whatever the
+        // entity material file and runtime overrides leave unset, and tint its base color
+        let keep_roughness = material.explicit.roughness || mat_override.as_ref().is_some_and(|o| o.roughness.is_some());
+        let keep_metallic = material.explicit.metallic || mat_override.as_ref().is_some_and(|o| o.metallic.is_some());
+        let keep_emission = material.explicit.emission
+            || mat_override.as_ref().is_some_and(|o| o.emission.is_some())
+            || !debug.emission_enabled;
+        let world_bounds = gpu_culling.map(|_| gpu_mesh.bounds.transformed(&model_matrix));
+        for slot in 0..gpu_mesh.draw_slots() {
+            let draw = gpu_mesh.draw_slot(slot);
+            if let Some(bounds) = &world_bounds {
+                cull_items.push(CullItem::new(bounds, draw.indices.clone(), bone_palettes.contains_key(&entity)));
+            }
+            let has_texture = if material.albedo_texture.is_some() || draw.texture_bind_group.is_some() { 1.0f32 } else { 0.0f32 };
+            let (roughness, metallic, emission, tint) = match draw.material {
+                Some(m) => (
+                    if keep_roughness { roughness } else { m.roughness },
+                    if keep_metallic { metallic } else { m.metallic },
+                    if keep_emission { emission } else { [m.emission[0], m.emission[1], m.emission[2], 0.0] },
+                    m.base_color,
+                ),
+                None => (roughness, metallic, emission, [1.0; 4]),
+            };
+
+            let draw_uniform = DrawUniforms {
+                model_matrix: model_matrix.to_cols_array_2d(),
+                normal_matrix: normal_matrix.to_cols_array_2d(),
+                base_color: std::array::from_fn(|i| base_color[i] * tint[i]),
+                roughness,
+                metallic,
+                has_texture,
+                has_skin: if entity_has_skin { 1.0 } else { 0.0 },
+                emission,
+                _padding: [0.0; 20],
+            };
+
+            draw_pool.write(item.first_slot + slot, &draw_uniform);
+        }
     }
+    draw_pool.upload(queue);
 
     // Upload light uniforms (point lights + directional light)
     let mut light_data = LightingUniforms::default();
+    let mut lights = Vec::new();
+    let mut point_shadow_data = PointShadowUniforms::default();
+    let mut point_shadow_count = 0usize;
     if debug.point_lights_enabled {
         for (_entity, (transform, light)) in
             scene_world.world.query::<(&Transform, &PointLight)>().iter()
         {
-            if (light_data.light_count as usize) < MAX_LIGHTS {
-                let idx = light_data.light_count as usize;
+            if lights.len() < compiled.downgrades.max_lights {
+                let idx = lights.len();
                 let base_intensity = if debug.torch_flicker_enabled {
                     light.intensity
                 } else {
                     1.5 // base_intensity, ignoring flicker script
                 };
-                light_data.lights[idx] = PointLightUniform {
-                    position: transform.position.to_array(),
-                    range: light.range,
-                    color: light.color.to_array(),
-                    intensity: base_intensity * debug.light_intensity_mult,
-                };
-                light_data.light_count += 1;
+                lights.push(PointLightUniform::point(
+                    transform.position,
+                    light.range,
+                    light.color,
+                    base_intensity * debug.light_intensity_mult,
+                ));
+                // The first few shadow casters get a cube shadow slot
+                let has_slot = point_shadow_count < MAX_POINT_SHADOWS && compiled.point_shadows.pipeline.is_some();
+                if light.casts_shadows && has_slot {
+                    let faces = point_shadow_face_view_proj(transform.position, light.range);
+                    for (face, view_proj) in faces.iter().enumerate() {
+                        point_shadow_data.face_view_proj[point_shadow_count * 6 + face] = view_proj.to_cols_array_2d();
+                    }
+                    point_shadow_data.light_slots[idx / 4][idx % 4] = point_shadow_count as i32;
+                    point_shadow_count += 1;
+                }
             }
         }
     }
+    // Spot lights follow the point lights in the same list (no shadows)
+    if debug.spot_lights_enabled {
+        for (_entity, (transform, light)) in
+            scene_world.world.query::<(&Transform, &SpotLight)>().iter()
+        {
+            if lights.len() >= compiled.downgrades.max_lights {
+                break;
+            }
+            let direction = (transform.rotation * light.direction).normalize_or(glam::Vec3::NEG_Y);
+            lights.push(PointLightUniform::spot(
+                transform.position,
+                light.range,
+                light.color,
+                light.intensity * debug.light_intensity_mult,
+                direction,
+                light.inner_angle,
+                light.outer_angle,
+            ));
+        }
+    }
+    light_data.light_count = lights.len() as u32;
+    compiled.light_clusters.write_lights(queue, &lights);
 
     // Query directional light and compute shadow VP matrix
     let mut light_vp = glam::Mat4::IDENTITY;
@@ -193,34 +300,85 @@ pub fn execute_pipeline_to_view(
         light_data.dir_light_color = [1.0, 1.0, 1.0];
     }
 
-    gpu.queue.write_buffer(
+    queue.write_buffer(
         &compiled.light_buffer,
         0,
         bytemuck::cast_slice(&[light_data]),
     );
 
+    // Point shadow slots for lighting, and one face matrix per dynamic offset for the shadow pass
+    queue.write_buffer(
+        &compiled.point_shadows.uniform_buffer,
+        0,
+        bytemuck::cast_slice(&[point_shadow_data]),
+    );
+    if point_shadow_count > 0 {
+        let face_count = point_shadow_count * 6;
+        let mut face_data = vec![0u8; face_count * POINT_SHADOW_FACE_STRIDE as usize];
+        for (layer, view_proj) in point_shadow_data.face_view_proj[..face_count].iter().enumerate() {
+            let start = layer * POINT_SHADOW_FACE_STRIDE as usize;
+            let uniforms = ShadowUniforms { light_view_projection: *view_proj };
+            face_data[start..start + std::mem::size_of::<ShadowUniforms>()]
+                .copy_from_slice(bytemuck::bytes_of(&uniforms));
+        }
+        queue.write_buffer(&compiled.point_shadows.face_buffer, 0, &face_data);
+    }
+
     // Upload shadow uniform buffer (light VP matrix for shadow pass)
     if let Some(shadow_buf) = &compiled.shadow_uniform_buffer {
         let shadow_data = ShadowUniforms {
             light_view_projection: light_vp.to_cols_array_2d(),
         };
-        gpu.queue.write_buffer(
+        queue.write_buffer(
             shadow_buf,
             0,
             bytemuck::cast_slice(&[shadow_data]),
         );
     }
 
-    // Create command encoder
-    let mut encoder = gpu
-        .device
-        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Pipeline Render Encoder"),
-        });
+    // Tonemap grading, with script overrides
+    if let Some(grading) = &compiled.color_grading {
+        let uniforms = ColorGradingUniforms::new(&grading.settings, grading.lut_size, debug);
+        queue.write_buffer(&grading.buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    // Compute and shadow work get their own buffers, everything else keeps DAG order in main
+    let mut frame = FrameEncoders::new(device);
+
+    // Cull, subsample and sort splats back-to-front for this camera, ahead of the splat pass
+    let splat_view = crate::splat::SplatView::new(&camera_state.uniform, debug.lod_lock);
+    for (_entity, (splat, transform)) in scene_world.world.query::<(&GaussianSplat, &Transform)>().iter() {
+        let local = splat_view.local(&transform.world_matrix);
+        splat_cache.sort_splats(splat, &local, device, queue, &mut frame.compute);
+    }
+
+    // Frustums to cull mesh draws against (none under --no-culling)
+    let culling = !debug.lod_lock.disable_culling;
+    let camera_frustum = culling.then(|| {
+        Frustum::from_view_projection(&glam::Mat4::from_cols_array_2d(&camera_state.uniform.view_projection))
+    });
+    let shadow_frustums: Vec<Option<Frustum>> = std::iter::once(light_vp.to_cols_array_2d())
+        .chain(point_shadow_data.face_view_proj[..point_shadow_count * 6].iter().copied())
+        .map(|view_proj| culling.then(|| Frustum::from_view_projection(&glam::Mat4::from_cols_array_2d(&view_proj))))
+        .collect();
+
+    // GPU culling writes the rasterize passes' indirect draws in the compute buffer, ahead of them
+    if let (Some(gpu_culling), Some(frustum)) = (gpu_culling, &camera_frustum) {
+        gpu_culling.write(device, queue, frustum, &cull_items);
+        gpu_culling.record(&mut frame.compute, cull_items.len() as u32);
+        frame.draw_stats.gpu_culling = true;
+    }
+    let indirect = gpu_culling.and_then(GpuCulling::commands);
+    let raster_frustum = camera_frustum.as_ref().filter(|_| indirect.is_none());
 
     // Execute passes in topological order (skip passes disabled by debug state)
     for &pass_idx in &compiled.pass_order {
         let pass = &compiled.passes[pass_idx];
+        let stats = &mut frame.draw_stats;
+        let encoder = match pass.pass_type {
+            PassType::Shadow => &mut frame.prepass,
+            _ => &mut frame.main,
+        };
 
         // Skip passes disabled via debug toggles
         if !debug.bloom_enabled && pass.name == "bloom_pass" {
@@ -244,11 +402,26 @@ pub fn execute_pipeline_to_view(
             }
             continue;
         }
+        if !pass.enabled(debug, &scene_world.world) {
+            clear_skipped_pass(encoder, pass, compiled);
+            continue;
+        }
+        let hooked = hooks.as_deref().is_some_and(|h| h.has_hooks(&pass.name));
+        if hooked {
+            if let Some(hooks) = hooks.as_deref_mut() {
+                let mut ctx = RenderHookContext {
+                    device, queue, encoder: &mut *encoder, compiled, camera_state, swapchain_view, pass,
+                    scene_world, mesh_cache,
+                };
+                hooks.run(HookStage::Before, &mut ctx);
+            }
+        }
+        let timestamp_writes = profiler.as_deref_mut().and_then(|p| p.pass_timestamps(&pass.name));
         match pass.pass_type {
             PassType::Rasterize => {
                 execute_rasterize_pass(
-                    &mut encoder,
-                    gpu,
+                    encoder,
+                    queue,
                     pass,
                     compiled,
                     scene_world,
@@ -258,60 +431,135 @@ pub fn execute_pipeline_to_view(
                     material_cache,
                     texture_resources,
                     bone_palettes,
-                    texture_cache,
+                    raster_frustum,
+                    indirect.as_deref(),
+                    stats,
+                    timestamp_writes,
                 );
             }
             PassType::Fullscreen => {
+                if is_lighting_pass(&pass.name) {
+                    compiled.light_clusters.record(encoder, &camera_state.bind_group);
+                }
                 execute_fullscreen_pass(
-                    &mut encoder,
+                    encoder,
                     pass,
                     compiled,
                     camera_state,
                     &swapchain_view,
+                    timestamp_writes,
                 );
+                if pass.name.contains("bloom") {
+                    if let Some(chain) = &compiled.bloom_chain {
+                        chain.record(encoder, &compiled.resources);
+                    }
+                }
             }
             PassType::Splat => {
                 execute_splat_pass(
-                    &mut encoder,
+                    encoder,
                     pass,
                     compiled,
-                    &gpu.device,
                     scene_world,
                     camera_state,
                     splat_cache,
+                    debug.lod_lock,
+                    timestamp_writes,
                 );
             }
             PassType::Shadow => {
                 execute_shadow_pass(
-                    &mut encoder,
-                    gpu,
+                    encoder,
+                    queue,
                     pass,
                     compiled,
                     scene_world,
                     draw_pool,
                     mesh_cache,
                     bone_palettes,
+                    &shadow_frustums,
+                    stats,
+                    timestamp_writes,
                 );
             }
+            PassType::Ssao => {
+                execute_ssao_pass(encoder, pass, compiled, camera_state, timestamp_writes);
+            }
+            PassType::Taa => {
+                execute_taa_pass(encoder, pass, compiled, camera_state, swapchain_view, timestamp_writes);
+            }
+            PassType::Skybox => {
+                execute_skybox_pass(encoder, pass, compiled, camera_state, timestamp_writes);
+            }
+            PassType::Water => {
+                if let Some(water) = &compiled.water {
+                    water.record(
+                        encoder,
+                        queue,
+                        &pass.pipeline,
+                        &pass.name,
+                        &compiled.resources,
+                        &camera_state.bind_group,
+                        scene_world,
+                        mesh_cache,
+                        timestamp_writes,
+                    );
+                }
+            }
+            PassType::Particles => {
+                if let Some(particles) = &compiled.particles {
+                    particles.record(
+                        encoder,
+                        &pass.pipeline,
+                        &pass.name,
+                        &compiled.resources,
+                        &camera_state.bind_group,
+                        timestamp_writes,
+                    );
+                }
+            }
+            PassType::PostProcess => {
+                execute_post_pass(encoder, pass, compiled, camera_state, swapchain_view, timestamp_writes);
+            }
             PassType::Compute => {
                 // Not implemented yet
             }
         }
+        if hooked {
+            if let Some(hooks) = hooks.as_deref_mut() {
+                let mut ctx = RenderHookContext {
+                    device, queue, encoder: &mut *encoder, compiled, camera_state, swapchain_view, pass,
+                    scene_world, mesh_cache,
+                };
+                hooks.run(HookStage::After, &mut ctx);
+            }
+        }
     }
 
-    encoder
+    if let Some(profiler) = profiler {
+        profiler.resolve(&mut frame.main);
+    }
+
+    frame
 }
 
-/// Execute a shadow depth pass (renders all geometry from light's perspective).
+/// Execute a shadow depth pass (renders all geometry from light's perspective),
+/// then the cube faces of the point shadow slots in use. `frustums` holds the
+/// directional light's frustum followed by one per cube face (None entries
+/// draw unculled).
+#[allow(clippy::too_many_arguments)]
 fn execute_shadow_pass(
     encoder: &mut wgpu::CommandEncoder,
-    gpu: &GpuState,
+    queue: &wgpu::Queue,
     pass: &CompiledPass,
     compiled: &CompiledPipeline,
     scene_world: &SceneWorld,
     draw_pool: &DrawUniformPool,
     mesh_cache: &MeshCache,
     bone_palettes: &HashMap<hecs::Entity, crate::anim_system::BoneMatrixPalette>,
+    frustums: &[Option<Frustum>],
+    stats: &mut DrawStats,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
 ) {
     let depth_view = pass
         .depth_target
@@ -327,12 +575,12 @@ fn execute_shadow_pass(
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: depth_view,
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: pass.depth_load_op(pass.depth_target.as_deref().unwrap_or_default()),
                     store: wgpu::StoreOp::Store,
                 }),
                 stencil_ops: None,
             }),
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
 
@@ -343,45 +591,95 @@ fn execute_shadow_pass(
             render_pass.set_bind_group(0, bg, &[]);
         }
 
-        // Draw all mesh entities (skip hidden before incrementing draw_index)
-        let mut draw_index = 0u32;
-        for (entity, (_, mesh_renderer)) in
-            scene_world.world.query::<(&Transform, &MeshRenderer)>().iter()
-        {
-            if scene_world.world.get::<&Hidden>(entity).is_ok() {
-                continue;
-            }
-            let gpu_mesh = mesh_cache.get(mesh_renderer.mesh_handle);
-            let dynamic_offset = draw_index * DRAW_UNIFORM_SIZE as u32;
+        draw_shadow_casters(&mut render_pass, queue, compiled, scene_world, draw_pool, mesh_cache, bone_palettes, frustums[0].as_ref(), stats);
+    }
+
+    // Point light cube faces: same casters, one face matrix per dynamic offset
+    let point_shadows = &compiled.point_shadows;
+    let Some(point_pipeline) = &point_shadows.pipeline else {
+        return;
+    };
+    for (layer, (face_view, frustum)) in point_shadows.face_views.iter().zip(&frustums[1..]).enumerate() {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("point_shadow_face"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: face_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(point_pipeline);
+        let face_offset = (layer as u64 * POINT_SHADOW_FACE_STRIDE) as u32;
+        render_pass.set_bind_group(0, &point_shadows.face_bind_group, &[face_offset]);
+        draw_shadow_casters(&mut render_pass, queue, compiled, scene_world, draw_pool, mesh_cache, bone_palettes, frustum.as_ref(), stats);
+    }
+}
 
-            render_pass.set_bind_group(1, &draw_pool.bind_group, &[dynamic_offset]);
+/// Draw every visible mesh depth-only (groups 1 and 2 of the shadow shader).
+#[allow(clippy::too_many_arguments)]
+fn draw_shadow_casters(
+    render_pass: &mut wgpu::RenderPass<'_>,
+    queue: &wgpu::Queue,
+    compiled: &CompiledPipeline,
+    scene_world: &SceneWorld,
+    draw_pool: &DrawUniformPool,
+    mesh_cache: &MeshCache,
+    bone_palettes: &HashMap<hecs::Entity, crate::anim_system::BoneMatrixPalette>,
+    frustum: Option<&Frustum>,
+    stats: &mut DrawStats,
+) {
+    // Draw the frame's visible meshes
+    for item in draw_pool.draws() {
+        let entity = item.entity;
+        let Ok(transform) = scene_world.world.get::<&Transform>(entity) else {
+            continue;
+        };
+        let gpu_mesh = mesh_cache.get(item.mesh);
+        if !in_frustum(frustum, &transform, gpu_mesh, bone_palettes.contains_key(&entity)) {
+            stats.shadow_culled += 1;
+            continue;
+        }
+        let Some(dynamic_offset) = draw_pool.dynamic_offset(item.first_slot) else {
+            continue;
+        };
+        stats.shadow_drawn += 1;
 
-            // Upload bone matrices for skinned entities (group 2 in shadow shader)
-            if let (Some(skin_buffer), Some(skin_bg)) = (&compiled.skin_buffer, &compiled.skin_bind_group) {
-                if let Some(palette) = bone_palettes.get(&entity) {
-                    gpu.queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[*palette]));
-                } else {
-                    let identity = crate::anim_system::BoneMatrixPalette::default();
-                    gpu.queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[identity]));
-                }
-                render_pass.set_bind_group(2, skin_bg, &[]);
-            }
+        render_pass.set_bind_group(1, &draw_pool.bind_group, &[dynamic_offset]);
 
-            render_pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(
-                gpu_mesh.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint32,
-            );
-            render_pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
-            draw_index += 1;
+        // Upload bone matrices for skinned entities (group 2 in shadow shader)
+        if let (Some(skin_buffer), Some(skin_bg)) = (&compiled.skin_buffer, &compiled.skin_bind_group) {
+            if let Some(palette) = bone_palettes.get(&entity) {
+                queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[*palette]));
+            } else {
+                let identity = crate::anim_system::BoneMatrixPalette::default();
+                queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[identity]));
+            }
+            render_pass.set_bind_group(2, skin_bg, &[]);
         }
+
+        // Depth only: the whole mesh in one draw, using its first slot's transform
+        render_pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice());
+        render_pass.set_index_buffer(
+            gpu_mesh.index_buffer.slice(),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
     }
 }
 
-/// Execute a rasterize pass (G-buffer geometry pass).
+/// Execute a rasterize pass (G-buffer geometry pass), skipping meshes
+/// outside `frustum`. With `indirect` commands from GPU culling, every slot
+/// is submitted and the GPU skips the culled ones.
+#[allow(clippy::too_many_arguments)]
 fn execute_rasterize_pass(
     encoder: &mut wgpu::CommandEncoder,
-    gpu: &GpuState,
+    queue: &wgpu::Queue,
     pass: &CompiledPass,
     compiled: &CompiledPipeline,
     scene_world: &SceneWorld,
@@ -391,44 +689,47 @@ fn execute_rasterize_pass(
     material_cache: &MaterialCache,
     texture_resources: Option<&crate::mesh::TextureResources>,
     bone_palettes: &HashMap<hecs::Entity, crate::anim_system::BoneMatrixPalette>,
-    texture_cache: Option<&crate::texture_cache::TextureCache>,
+    frustum: Option<&Frustum>,
+    indirect: Option<&wgpu::Buffer>,
+    stats: &mut DrawStats,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
 ) {
-    // Build color attachments from pass targets
-    let color_views: Vec<&wgpu::TextureView> = pass
+    // Build color attachments from pass targets. With MSAA, render into the
+    // multisampled targets and resolve into the resources.
+    let color_resources: Vec<&super::GpuResource> = pass
         .color_targets
         .iter()
-        .filter_map(|name| compiled.resources.get(name).map(|r| &r.view))
+        .filter_map(|name| compiled.resources.get(name))
         .collect();
 
-    let color_attachments: Vec<Option<wgpu::RenderPassColorAttachment>> = color_views
+    let color_attachments: Vec<Option<wgpu::RenderPassColorAttachment>> = color_resources
         .iter()
-        .map(|view| {
+        .map(|resource| {
+            let multisampled = resource.msaa_view.is_some();
+            let load = pass.color_load_op(&resource.name, wgpu::Color::TRANSPARENT);
+            // Multisampled samples only need keeping if the next frame loads them
+            let store = if multisampled && load != wgpu::LoadOp::Load {
+                wgpu::StoreOp::Discard
+            } else {
+                wgpu::StoreOp::Store
+            };
             Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 0.0,
-                    }),
-                    store: wgpu::StoreOp::Store,
-                },
+                view: resource.render_view(),
+                resolve_target: multisampled.then_some(&resource.view),
+                ops: wgpu::Operations { load, store },
             })
         })
         .collect();
 
-    let depth_view = pass
+    let depth_resource = pass
         .depth_target
         .as_ref()
-        .and_then(|name| compiled.resources.get(name))
-        .map(|r| &r.view);
+        .and_then(|name| compiled.resources.get(name));
 
-    let depth_attachment = depth_view.map(|view| wgpu::RenderPassDepthStencilAttachment {
-        view,
+    let depth_attachment = depth_resource.map(|r| wgpu::RenderPassDepthStencilAttachment {
+        view: r.render_view(),
         depth_ops: Some(wgpu::Operations {
-            load: wgpu::LoadOp::Clear(1.0),
+            load: pass.depth_load_op(&r.name),
             store: wgpu::StoreOp::Store,
         }),
         stencil_ops: None,
@@ -439,162 +740,267 @@ fn execute_rasterize_pass(
             label: Some(&pass.name),
             color_attachments: &color_attachments,
             depth_stencil_attachment: depth_attachment,
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
 
         render_pass.set_pipeline(&pass.pipeline);
         render_pass.set_bind_group(0, &camera_state.bind_group, &[]);
+        // Draws come sorted by material and mesh, so buffers and textures
+        // are only set when they change
+        let mut bound = BoundDrawState::default();
+        bound.changes += 2;
+        let skin = compiled.skin_buffer.as_ref().zip(compiled.skin_bind_group.as_ref());
+        if let Some((_, skin_bg)) = skin {
+            render_pass.set_bind_group(3, skin_bg, &[]);
+            bound.changes += 1;
+        }
 
-        let mut draw_index = 0u32;
-        for (entity, (_, mesh_renderer)) in
-            scene_world.world.query::<(&Transform, &MeshRenderer)>().iter()
-        {
-            if scene_world.world.get::<&Hidden>(entity).is_ok() {
+        let mut culled = 0u32;
+        for item in draw_pool.draws() {
+            let entity = item.entity;
+            let Ok(transform) = scene_world.world.get::<&Transform>(entity) else {
+                continue;
+            };
+            let gpu_mesh = mesh_cache.get(item.mesh);
+            if !in_frustum(frustum, &transform, gpu_mesh, bone_palettes.contains_key(&entity)) {
+                culled += 1;
                 continue;
             }
-            let gpu_mesh = mesh_cache.get(mesh_renderer.mesh_handle);
-            let dynamic_offset = draw_index * DRAW_UNIFORM_SIZE as u32;
-
-            render_pass.set_bind_group(1, &draw_pool.bind_group, &[dynamic_offset]);
-
-            // Bind texture at group(2): material texture > mesh texture > white fallback
-            if let Some(tex_res) = texture_resources {
-                let material = material_cache.get(mesh_renderer.material_handle);
-                let tex_bg = if let (Some(albedo_handle), Some(tc)) = (material.albedo_texture, texture_cache) {
-                    tc.get(albedo_handle)
-                } else if let Some(mesh_tex) = gpu_mesh.texture_bind_group.as_ref() {
-                    mesh_tex
-                } else {
-                    &tex_res.default_bind_group
-                };
-                render_pass.set_bind_group(2, tex_bg, &[]);
-            }
+            stats.drawn += 1;
 
             // Upload bone matrices for skinned entities (group 3)
-            if let (Some(skin_buffer), Some(skin_bg)) = (&compiled.skin_buffer, &compiled.skin_bind_group) {
+            if let Some((skin_buffer, _)) = skin {
                 if let Some(palette) = bone_palettes.get(&entity) {
-                    gpu.queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[*palette]));
+                    queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[*palette]));
                 } else {
                     // Non-skinned: upload identity palette (has_skin=0)
                     let identity = crate::anim_system::BoneMatrixPalette::default();
-                    gpu.queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[identity]));
+                    queue.write_buffer(skin_buffer, 0, bytemuck::cast_slice(&[identity]));
                 }
-                render_pass.set_bind_group(3, skin_bg, &[]);
             }
 
-            render_pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(
-                gpu_mesh.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint32,
-            );
-            render_pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
-            draw_index += 1;
+            bound.bind_mesh(&mut render_pass, item.mesh, gpu_mesh);
+            let material = material_cache.get(item.material);
+
+            // One draw per submesh, each with its own uniform slot and texture
+            for slot in 0..gpu_mesh.draw_slots() {
+                let draw = gpu_mesh.draw_slot(slot);
+                let draw_index = item.first_slot + slot;
+                let Some(dynamic_offset) = draw_pool.dynamic_offset(draw_index) else {
+                    continue;
+                };
+                render_pass.set_bind_group(1, &draw_pool.bind_group, &[dynamic_offset]);
+                bound.changes += 1;
+
+                // Bind texture maps at group(2): material maps > submesh/mesh maps > fallbacks
+                if let Some(tex_res) = texture_resources {
+                    let tex_bg = material.bind_group.as_ref()
+                        .or(draw.texture_bind_group)
+                        .unwrap_or(&tex_res.default_bind_group);
+                    bound.bind_textures(&mut render_pass, 2, tex_bg);
+                }
+
+                // GPU-culled slots draw whatever the culling step wrote for them
+                match indirect {
+                    Some(commands) => {
+                        render_pass.draw_indexed_indirect(commands, draw_index as u64 * INDIRECT_COMMAND_SIZE)
+                    }
+                    None => render_pass.draw_indexed(draw.indices, 0, 0..1),
+                }
+            }
         }
-        let draw_count = draw_index;
+        stats.state_changes += bound.changes;
+        stats.culled += culled;
+        let draw_count = draw_pool.slots();
         if draw_count == 0 {
             tracing::warn!("Rasterize pass '{}': ZERO entities drawn!", pass.name);
         } else {
-            tracing::debug!("Rasterize pass '{}': {} entities drawn", pass.name, draw_count);
+            tracing::debug!("Rasterize pass '{}': {} draw slots, {} entities culled", pass.name, draw_count, culled);
+        }
+    }
+
+    // Copy the multisampled depth into the resource for later passes
+    if let (Some(resolve), Some(depth)) = (&pass.depth_resolve, depth_resource) {
+        let mut resolve_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("depth_resolve"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        resolve_pass.set_pipeline(&resolve.pipeline);
+        resolve_pass.set_bind_group(0, &resolve.bind_group, &[]);
+        resolve_pass.draw(0..3, 0..1);
+    }
+}
+
+/// What a pass clears its color targets to unless its YAML says otherwise,
+/// or None for pass types that draw over their targets.
+fn default_clear_color(pass: &CompiledPass) -> Option<wgpu::Color> {
+    match pass.pass_type {
+        PassType::Skybox | PassType::Water | PassType::Particles | PassType::Compute => None,
+        // No occlusion
+        PassType::Ssao => Some(wgpu::Color::WHITE),
+        PassType::Taa | PassType::PostProcess => Some(wgpu::Color::BLACK),
+        // Lighting leaves empty pixels transparent for the skybox pass to fill
+        PassType::Fullscreen if ["tonemap", "bloom", "fxaa"].iter().any(|n| pass.name.contains(n)) => {
+            Some(wgpu::Color::BLACK)
+        }
+        PassType::Fullscreen | PassType::Rasterize | PassType::Splat | PassType::Shadow => {
+            Some(wgpu::Color::TRANSPARENT)
         }
     }
 }
 
+/// Stand in for a pass whose `enabled_if` doesn't hold this frame: clear the
+/// targets it would have cleared, so later passes read nothing from it
+/// rather than a stale frame. Targets it draws over keep their contents.
+fn clear_skipped_pass(encoder: &mut wgpu::CommandEncoder, pass: &CompiledPass, compiled: &CompiledPipeline) {
+    let Some(clear_color) = default_clear_color(pass) else {
+        return;
+    };
+    // One pass per target, as they needn't share a size
+    for resource in pass.color_targets.iter().filter_map(|name| compiled.resources.get(name)) {
+        let load = pass.color_load_op(&resource.name, clear_color);
+        if load == wgpu::LoadOp::Load {
+            continue;
+        }
+        let _clear = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&pass.name),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &resource.view,
+                resolve_target: None,
+                ops: wgpu::Operations { load, store: wgpu::StoreOp::Store },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+    if let Some(depth) = pass.depth_target.as_ref().and_then(|name| compiled.resources.get(name)) {
+        let load = pass.depth_load_op(&depth.name);
+        if load != wgpu::LoadOp::Load {
+            let _clear = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&pass.name),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth.view,
+                    depth_ops: Some(wgpu::Operations { load, store: wgpu::StoreOp::Store }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+    }
+}
+
+/// Whether a mesh's world bounds reach into `frustum` (always with no
+/// frustum). Skinned meshes move outside their bind-pose bounds, so they are
+/// never culled.
+fn in_frustum(frustum: Option<&Frustum>, transform: &Transform, mesh: &crate::mesh::GpuMesh, skinned: bool) -> bool {
+    match frustum {
+        Some(frustum) if !skinned => frustum.intersects(&mesh.bounds.transformed(&transform.world_matrix)),
+        _ => true,
+    }
+}
+
 /// Execute a Gaussian splat rendering pass.
+#[allow(clippy::too_many_arguments)]
 fn execute_splat_pass(
     encoder: &mut wgpu::CommandEncoder,
     pass: &CompiledPass,
     compiled: &CompiledPipeline,
-    device: &wgpu::Device,
     scene_world: &SceneWorld,
     camera_state: &CameraState,
     splat_cache: &SplatCache,
+    lod_lock: super::LodLock,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
 ) {
     // Build color attachments
-    let color_views: Vec<&wgpu::TextureView> = pass
+    let color_resources: Vec<&super::GpuResource> = pass
         .color_targets
         .iter()
-        .filter_map(|name| compiled.resources.get(name).map(|r| &r.view))
+        .filter_map(|name| compiled.resources.get(name))
         .collect();
 
-    let color_attachments: Vec<Option<wgpu::RenderPassColorAttachment>> = color_views
+    let color_attachments: Vec<Option<wgpu::RenderPassColorAttachment>> = color_resources
         .iter()
-        .map(|view| {
+        .map(|resource| {
             Some(wgpu::RenderPassColorAttachment {
-                view,
+                view: &resource.view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 0.0,
-                    }),
+                    load: pass.color_load_op(&resource.name, wgpu::Color::TRANSPARENT),
                     store: wgpu::StoreOp::Store,
                 },
             })
         })
         .collect();
 
-    let depth_view = pass
+    let depth_resource = pass
         .depth_target
         .as_ref()
-        .and_then(|name| compiled.resources.get(name))
-        .map(|r| &r.view);
+        .and_then(|name| compiled.resources.get(name));
 
-    let depth_attachment = depth_view.map(|view| wgpu::RenderPassDepthStencilAttachment {
-        view,
+    let depth_attachment = depth_resource.map(|r| wgpu::RenderPassDepthStencilAttachment {
+        view: &r.view,
         depth_ops: Some(wgpu::Operations {
-            load: wgpu::LoadOp::Clear(1.0),
+            load: pass.depth_load_op(&r.name),
             store: wgpu::StoreOp::Store,
         }),
         stencil_ops: None,
     });
 
-    // Get the splat data bind group layout
-    let splat_layout = match &compiled.splat_data_bind_group_layout {
-        Some(layout) => layout,
-        None => return,
-    };
+    // The pipeline reads the cache's chunk bind groups
+    if compiled.splat_data_bind_group_layout.is_none() {
+        return;
+    }
 
     {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some(&pass.name),
             color_attachments: &color_attachments,
             depth_stencil_attachment: depth_attachment,
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
 
         render_pass.set_pipeline(&pass.pipeline);
         render_pass.set_bind_group(0, &camera_state.bind_group, &[]);
+        let view = SplatView::new(&camera_state.uniform, lod_lock);
 
-        // For each entity with a GaussianSplat component, create a bind group and draw
-        for (_entity, splat) in scene_world.world.query::<&GaussianSplat>().iter() {
+        // For each entity with a GaussianSplat component, draw its visible cells
+        for (entity, (splat, transform)) in scene_world.world.query::<(&GaussianSplat, &Transform)>().iter() {
             let gpu_splat = splat_cache.get(splat.splat_handle);
+            let Some(params_offset) = splat_cache.params_offset(entity) else {
+                continue;
+            };
             if gpu_splat.splat_count == 0 {
                 continue;
             }
 
-            // Create bind group for this splat's data
-            let splat_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Splat Data Bind Group"),
-                layout: splat_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: gpu_splat.splat_buffer.as_entire_binding(),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: gpu_splat.sorted_index_buffer.as_entire_binding(),
-                    },
-                ],
-            });
+            for draw in gpu_splat.plan(&view.local(&transform.world_matrix), splat) {
+                let cell = &gpu_splat.cells[draw.cell];
+                let chunk = &gpu_splat.chunks[cell.chunk];
+                let Some(splat_bind_group) = &chunk.bind_group else {
+                    continue;
+                };
 
-            render_pass.set_bind_group(1, &splat_bind_group, &[]);
-            // 6 vertices per quad, N instances (one per splat)
-            render_pass.draw(0..6, 0..gpu_splat.splat_count);
+                render_pass.set_bind_group(1, splat_bind_group, &[params_offset]);
+                // 6 vertices per quad, one instance per splat of the cell's
+                // sorted segment
+                let first = cell.segment * chunk.segment_capacity;
+                render_pass.draw(0..6, first..first + draw.count);
+            }
         }
     }
 }
@@ -606,26 +1012,35 @@ fn execute_fullscreen_pass(
     compiled: &CompiledPipeline,
     camera_state: &CameraState,
     swapchain_view: &wgpu::TextureView,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
 ) {
     let is_tonemap = pass.name.contains("tonemap");
     let is_bloom = pass.name.contains("bloom");
     let is_fxaa = pass.name.contains("fxaa");
+    let clear_color = default_clear_color(pass).unwrap_or(wgpu::Color::TRANSPARENT);
     let writes_to_swapchain = pass
         .color_targets
         .iter()
         .any(|t| t == "swapchain");
 
     // Determine the output view
-    let output_view = if writes_to_swapchain {
-        swapchain_view
+    let (output_name, output_view) = if writes_to_swapchain {
+        ("swapchain", swapchain_view)
     } else {
         pass.color_targets
             .first()
             .and_then(|name| compiled.resources.get(name))
-            .map(|r| &r.view)
+            .map(|r| (r.name.as_str(), &r.view))
             .expect("Fullscreen pass has no output target")
     };
 
+    // A split-screen view draws only its region and keeps the others'
+    let viewport = camera_state.viewport.filter(|_| writes_to_swapchain);
+    let load = match viewport {
+        Some(_) => wgpu::LoadOp::Load,
+        None => pass.color_load_op(output_name, clear_color),
+    };
+
     {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some(&pass.name),
@@ -633,21 +1048,19 @@ fn execute_fullscreen_pass(
                 view: output_view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 1.0,
-                    }),
+                    load,
                     store: wgpu::StoreOp::Store,
                 },
             })],
             depth_stencil_attachment: None,
-            timestamp_writes: None,
+            timestamp_writes,
             occlusion_query_set: None,
         });
 
         render_pass.set_pipeline(&pass.pipeline);
+        if let Some(viewport) = viewport {
+            crate::split_screen::apply_viewport(&mut render_pass, viewport);
+        }
 
         if is_fxaa {
             // FXAA: group 0 = LDR texture + sampler
@@ -682,6 +1095,225 @@ fn execute_fullscreen_pass(
     }
 }
 
+/// Fullscreen passes other than bloom, tonemap and FXAA are lighting passes.
+fn is_lighting_pass(name: &str) -> bool {
+    !(name.contains("tonemap") || name.contains("bloom") || name.contains("fxaa"))
+}
+
+/// Execute a user post-process pass into all of its color targets.
+fn execute_post_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    pass: &CompiledPass,
+    compiled: &CompiledPipeline,
+    camera_state: &CameraState,
+    swapchain_view: &wgpu::TextureView,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+) {
+    let Some(post) = compiled.post_passes.get(&pass.name) else {
+        return;
+    };
+    // Only a pass into the swapchain alone is limited to a split-screen
+    // view's region; its other targets are full-size resources
+    let to_swapchain = |name: &String| !compiled.resources.contains_key(name);
+    let viewport = camera_state
+        .viewport
+        .filter(|_| !pass.color_targets.is_empty() && pass.color_targets.iter().all(to_swapchain));
+    let color_attachments: Vec<Option<wgpu::RenderPassColorAttachment>> = pass
+        .color_targets
+        .iter()
+        .map(|name| {
+            Some(wgpu::RenderPassColorAttachment {
+                view: compiled.resources.get(name).map_or(swapchain_view, |r| &r.view),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: match viewport {
+                        Some(_) => wgpu::LoadOp::Load,
+                        None => pass.color_load_op(name, wgpu::Color::BLACK),
+                    },
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        })
+        .collect();
+
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(&pass.name),
+        color_attachments: &color_attachments,
+        depth_stencil_attachment: None,
+        timestamp_writes,
+        occlusion_query_set: None,
+    });
+    render_pass.set_pipeline(&pass.pipeline);
+    if let Some(viewport) = viewport {
+        crate::split_screen::apply_viewport(&mut render_pass, viewport);
+    }
+    post.bind(&mut render_pass);
+    render_pass.draw(0..3, 0..1);
+}
+
+/// Execute the built-in SSAO pass into its AO target.
+fn execute_ssao_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    pass: &CompiledPass,
+    compiled: &CompiledPipeline,
+    camera_state: &CameraState,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+) {
+    let Some(target) = pass.color_targets.first().and_then(|name| compiled.resources.get(name)) else {
+        tracing::warn!("SSAO pass '{}' has no output target", pass.name);
+        return;
+    };
+
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(&pass.name),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &target.view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: pass.color_load_op(&target.name, wgpu::Color::WHITE),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes,
+        occlusion_query_set: None,
+    });
+    render_pass.set_pipeline(&pass.pipeline);
+    render_pass.set_bind_group(0, &camera_state.bind_group, &[]);
+    if let Some(bg) = &compiled.ssao_bind_group {
+        render_pass.set_bind_group(1, bg, &[]);
+    }
+    render_pass.draw(0..3, 0..1);
+}
+
+/// Execute the TAA resolve, then copy this frame's result into the history
+/// resource for the next frame.
+fn execute_taa_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    pass: &CompiledPass,
+    compiled: &CompiledPipeline,
+    camera_state: &CameraState,
+    swapchain_view: &wgpu::TextureView,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+) {
+    let Some(taa) = &compiled.taa else {
+        return;
+    };
+    let (Some(history), Some(resolve)) = (
+        compiled.resources.get(&taa.inputs.history),
+        compiled.resources.get(&taa.inputs.resolve),
+    ) else {
+        return;
+    };
+    let Some(output_name) = pass.color_targets.first() else {
+        tracing::warn!("TAA pass '{}' has no output target", pass.name);
+        return;
+    };
+    let output_view = match compiled.resources.get(output_name) {
+        Some(resource) => &resource.view,
+        None => swapchain_view,
+    };
+
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&pass.name),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: output_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: pass.color_load_op(output_name, wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &resolve.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&pass.pipeline);
+        render_pass.set_bind_group(0, &camera_state.bind_group, &[]);
+        render_pass.set_bind_group(1, &taa.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    encoder.copy_texture_to_texture(
+        resolve.texture.as_image_copy(),
+        history.texture.as_image_copy(),
+        history.texture.size(),
+    );
+}
+
+/// Draw the skybox behind everything already in the pass's color target:
+/// pixels the depth input leaves empty, under whatever coverage (alpha) the
+/// target holds there. The target is kept unless the pass YAML clears it.
+fn execute_skybox_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    pass: &CompiledPass,
+    compiled: &CompiledPipeline,
+    camera_state: &CameraState,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+) {
+    let target = pass.color_targets.first().and_then(|name| compiled.resources.get(name));
+    let depth = pass.depth_target.as_ref().and_then(|name| compiled.resources.get(name));
+    let (Some(target), Some(depth), Some(bind_group)) = (target, depth, &compiled.skybox_bind_group) else {
+        tracing::warn!("Skybox pass '{}' is missing its target or depth", pass.name);
+        return;
+    };
+    let load = pass
+        .target_ops
+        .get(&target.name)
+        .map_or(wgpu::LoadOp::Load, |ops| ops.color_load_op(wgpu::Color::BLACK));
+
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(&pass.name),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &target.view,
+            resolve_target: None,
+            ops: wgpu::Operations { load, store: wgpu::StoreOp::Store },
+        })],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: &depth.view,
+            depth_ops: None,
+            stencil_ops: None,
+        }),
+        timestamp_writes,
+        occlusion_query_set: None,
+    });
+    render_pass.set_pipeline(&pass.pipeline);
+    render_pass.set_bind_group(0, &camera_state.bind_group, &[]);
+    render_pass.set_bind_group(1, bind_group, &[]);
+    render_pass.draw(0..3, 0..1);
+}
+
+/// Bind skybox maps (or the black fallback) to the lighting and skybox
+/// passes and upload the environment uniforms.
+pub fn set_environment(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    compiled: &mut CompiledPipeline,
+    maps: Option<EnvironmentMaps>,
+    intensity: f32,
+) {
+    let uniforms = EnvironmentUniforms {
+        intensity,
+        specular_mips: maps.as_ref().map_or(1, |m| m.specular_mips) as f32,
+        enabled: maps.is_some() as u32,
+        _pad: 0,
+    };
+    queue.write_buffer(&compiled.environment_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    compiled.environment = maps.unwrap_or_else(|| EnvironmentMaps::fallback(device));
+    rebuild_bind_groups(device, compiled);
+}
+
 /// Rebuild bind groups after resources are resized.
 /// Call this after `resize_resources()` to update texture view references.
 pub fn rebuild_bind_groups(
@@ -690,130 +1322,79 @@ pub fn rebuild_bind_groups(
 ) {
     // Rebuild G-buffer bind group
     if let Some(layout) = &compiled.gbuffer_bind_group_layout {
-        let albedo_view = compiled
-            .resources
-            .get("gbuffer_albedo")
-            .map(|r| &r.view);
-        let normal_view = compiled
-            .resources
-            .get("gbuffer_normal")
-            .map(|r| &r.view);
-        let depth_view = compiled
-            .resources
-            .get("gbuffer_depth")
-            .map(|r| &r.view);
-        let emission_view = compiled
-            .resources
-            .get("gbuffer_emission")
-            .map(|r| &r.view);
-
-        if let (Some(albedo), Some(normal), Some(depth)) = (albedo_view, normal_view, depth_view) {
-            let emission = emission_view.unwrap_or(albedo);
-            compiled.gbuffer_bind_group = Some(device.create_bind_group(
-                &wgpu::BindGroupDescriptor {
-                    label: Some("GBuffer Input Bind Group (resized)"),
-                    layout,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(albedo),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::TextureView(normal),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 2,
-                            resource: wgpu::BindingResource::TextureView(depth),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 3,
-                            resource: wgpu::BindingResource::Sampler(&compiled.gbuffer_sampler),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 4,
-                            resource: wgpu::BindingResource::TextureView(emission),
-                        },
-                    ],
-                },
-            ));
+        if let Some(bg) = super::compiler::create_gbuffer_bind_group(
+            device,
+            "GBuffer Input Bind Group (resized)",
+            layout,
+            &compiled.resources,
+            &compiled.gbuffer_sampler,
+            &compiled.ao_fallback_view,
+        ) {
+            compiled.gbuffer_bind_group = Some(bg);
         }
     }
 
-    // Rebuild bloom bind group
-    if let Some(layout) = &compiled.bloom_bind_group_layout {
-        if let Some(hdr_view) = compiled.resources.get("hdr_buffer").map(|r| &r.view) {
-            let hdr_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-                label: Some("Bloom HDR Sampler (resized)"),
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Linear,
-                ..Default::default()
-            });
-            compiled.bloom_bind_group = Some(device.create_bind_group(
-                &wgpu::BindGroupDescriptor {
-                    label: Some("Bloom Input Bind Group (resized)"),
-                    layout,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(hdr_view),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&hdr_sampler),
-                        },
-                    ],
-                },
-            ));
+    // Rebuild SSAO bind group
+    if let Some(layout) = &compiled.ssao_bind_group_layout {
+        if let Some(bg) = super::compiler::create_ssao_bind_group(
+            device,
+            "SSAO Input Bind Group (resized)",
+            layout,
+            &compiled.resources,
+        ) {
+            compiled.ssao_bind_group = Some(bg);
         }
     }
 
-    // Rebuild tonemap bind group (HDR + bloom)
-    if let Some(layout) = &compiled.tonemap_bind_group_layout {
-        if let Some(hdr_view) = compiled.resources.get("hdr_buffer").map(|r| &r.view) {
-            let hdr_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-                label: Some("HDR Sampler (resized)"),
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Linear,
-                ..Default::default()
-            });
-
-            let bloom_view = compiled.resources.get("bloom_buffer")
-                .map(|r| &r.view)
-                .unwrap_or(hdr_view);
+    // Rebuild TAA bind group
+    if let Some(taa) = &mut compiled.taa {
+        if let Some(bg) = super::compiler::create_taa_bind_group(
+            device,
+            "TAA Input Bind Group (resized)",
+            &taa.bind_group_layout,
+            &compiled.resources,
+            &taa.inputs,
+            &taa.sampler,
+        ) {
+            taa.bind_group = bg;
+        }
+    }
 
-            compiled.tonemap_bind_group = Some(device.create_bind_group(
-                &wgpu::BindGroupDescriptor {
-                    label: Some("Tonemap Input Bind Group (resized)"),
-                    layout,
-                    entries: &[
-                        wgpu::BindGroupEntry {
-                            binding: 0,
-                            resource: wgpu::BindingResource::TextureView(hdr_view),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&hdr_sampler),
-                        },
-                        wgpu::BindGroupEntry {
-                            binding: 2,
-                            resource: wgpu::BindingResource::TextureView(bloom_view),
-                        },
-                    ],
-                },
+    // Rebuild bloom bind group and mip chain
+    if let Some(chain) = &mut compiled.bloom_chain {
+        chain.resize(device, &compiled.resources);
+        if let (Some(layout), Some(hdr_view)) = (
+            &compiled.bloom_bind_group_layout,
+            compiled.resources.get("hdr_buffer").map(|r| &r.view),
+        ) {
+            compiled.bloom_bind_group = Some(super::compiler::create_bloom_bind_group(
+                device,
+                "Bloom Input Bind Group (resized)",
+                layout,
+                hdr_view,
+                &compiled.bloom_sampler,
+                &chain.params_buffer,
             ));
         }
     }
 
+    // Rebuild tonemap bind group (HDR + bloom + grading)
+    if let (Some(layout), Some(grading)) = (&compiled.tonemap_bind_group_layout, &compiled.color_grading) {
+        if let Some(bg) = super::compiler::create_tonemap_bind_group(
+            device,
+            "Tonemap Input Bind Group (resized)",
+            layout,
+            &compiled.resources,
+            &compiled.tonemap_sampler,
+            grading,
+        ) {
+            compiled.tonemap_bind_group = Some(bg);
+        }
+    }
+
     // Rebuild FXAA bind group
     if let Some(layout) = &compiled.fxaa_bind_group_layout {
         if let Some(ldr_view) = compiled.resources.get("ldr_buffer").map(|r| &r.view) {
-            let ldr_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-                label: Some("FXAA LDR Sampler (resized)"),
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Linear,
-                ..Default::default()
-            });
             compiled.fxaa_bind_group = Some(device.create_bind_group(
                 &wgpu::BindGroupDescriptor {
                     label: Some("FXAA Input Bind Group (resized)"),
@@ -825,7 +1406,7 @@ pub fn rebuild_bind_groups(
                         },
                         wgpu::BindGroupEntry {
                             binding: 1,
-                            resource: wgpu::BindingResource::Sampler(&ldr_sampler),
+                            resource: wgpu::BindingResource::Sampler(&compiled.fxaa_sampler),
                         },
                     ],
                 },
@@ -858,6 +1439,51 @@ pub fn rebuild_bind_groups(
         }
     }
 
+    // Rebuild depth resolve bind groups (MSAA depth targets were recreated)
+    for pass in &mut compiled.passes {
+        let msaa_depth = pass
+            .depth_target
+            .as_ref()
+            .and_then(|name| compiled.resources.get(name))
+            .and_then(|r| r.msaa_view.as_ref());
+        if let (Some(resolve), Some(view)) = (&mut pass.depth_resolve, msaa_depth) {
+            resolve.bind_group =
+                super::compiler::create_depth_resolve_bind_group(device, &resolve.bind_group_layout, view);
+        }
+    }
+
+    // Rebuild skybox bind group (the environment maps may have changed)
+    if let Some(layout) = &compiled.skybox_bind_group_layout {
+        compiled.skybox_bind_group = Some(super::compiler::create_skybox_bind_group(
+            device,
+            layout,
+            &compiled.environment,
+            &compiled.environment_sampler,
+            &compiled.environment_buffer,
+        ));
+    }
+
+    // Rebuild the water pass's scene copy (resized) and environment bindings
+    if let Some(water) = &mut compiled.water {
+        water.rebuild(
+            device,
+            &compiled.resources,
+            &compiled.environment,
+            &compiled.environment_sampler,
+            &compiled.environment_buffer,
+        );
+    }
+
+    // Rebind the particle pass's depth input
+    if let Some(particles) = &mut compiled.particles {
+        particles.rebuild(device, &compiled.resources);
+    }
+
+    // Rebuild post-process bindings against the resized inputs
+    for post in compiled.post_passes.values_mut() {
+        post.rebuild(device, &compiled.resources);
+    }
+
     // Rebuild lighting bind group (shadow map may have been resized)
     if let Some(sampler) = &compiled.shadow_sampler {
         // Create dummy shadow map fallback
@@ -876,23 +1502,18 @@ pub fn rebuild_bind_groups(
             .map(|r| &r.view)
             .unwrap_or(&shadow_dummy_view);
 
-        compiled.light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Lighting Bind Group (resized)"),
-            layout: &compiled.light_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: compiled.light_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(shadow_map_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::Sampler(sampler),
-                },
-            ],
-        });
+        compiled.light_bind_group = super::compiler::create_light_bind_group(
+            device,
+            "Lighting Bind Group (resized)",
+            &compiled.light_bind_group_layout,
+            &compiled.light_buffer,
+            &compiled.light_clusters,
+            shadow_map_view,
+            sampler,
+            &compiled.point_shadows,
+            &compiled.environment,
+            &compiled.environment_sampler,
+            &compiled.environment_buffer,
+        );
     }
 }
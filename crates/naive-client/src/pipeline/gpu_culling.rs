@@ -0,0 +1,236 @@
+//! GPU-driven culling for rasterize passes (`settings.gpu_culling: true`).
+//!
+//! Each frame the CPU uploads one `CullItem` per draw slot, holding the
+//! world-space bounds of its mesh and its index range, without testing
+//! anything. A compute step checks the items against the camera frustum and
+//! writes one `DrawIndexedIndirect` command per slot, with an instance count
+//! of 0 for the culled ones. Rasterize passes then issue those commands as
+//! they are, so visibility never comes back to the CPU. Devices that can't
+//! run indirect draws keep culling on the CPU (see `downgrade`).
+
+use std::cell::{Ref, RefCell};
+
+use crate::culling::{Aabb, Frustum};
+
+/// Invocations per workgroup of the culling shader.
+pub const GPU_CULL_WORKGROUP_SIZE: u32 = 64;
+
+/// Bytes of one `DrawIndexedIndirect` command (five u32s).
+pub const INDIRECT_COMMAND_SIZE: u64 = 20;
+
+/// Draw slots the buffers hold before they first grow.
+const INITIAL_CAPACITY: u32 = 256;
+
+/// One draw slot as the culling shader sees it.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CullItem {
+    pub min: [f32; 3],
+    pub index_count: u32,
+    pub max: [f32; 3],
+    pub first_index: u32,
+    /// Non-zero for slots that are drawn wherever their bounds are
+    /// (skinned meshes).
+    pub always_visible: u32,
+    pub _pad: [u32; 3],
+}
+
+impl CullItem {
+    pub fn new(bounds: &Aabb, indices: std::ops::Range<u32>, always_visible: bool) -> Self {
+        Self {
+            min: bounds.min.to_array(),
+            index_count: indices.end - indices.start,
+            max: bounds.max.to_array(),
+            first_index: indices.start,
+            always_visible: always_visible as u32,
+            _pad: [0; 3],
+        }
+    }
+}
+
+/// Frustum planes and the number of live items.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullParams {
+    planes: [[f32; 4]; 6],
+    count: u32,
+    _pad: [u32; 3],
+}
+
+/// Item and command storage, sized for `capacity` draw slots.
+struct CullBuffers {
+    capacity: u32,
+    items: wgpu::Buffer,
+    commands: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// The culling compute step and the indirect commands it writes.
+pub struct GpuCulling {
+    pipeline: wgpu::ComputePipeline,
+    layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    /// Grown while recording a frame, hence the cell.
+    buffers: RefCell<Option<CullBuffers>>,
+}
+
+impl GpuCulling {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Cull Params Buffer"),
+            size: std::mem::size_of::<CullParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("GPU Cull Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("GPU Cull Shader"),
+            source: wgpu::ShaderSource::Wgsl(crate::shader::get_gpu_cull_wgsl().into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("GPU Cull Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("GPU Cull Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            layout,
+            params_buffer,
+            buffers: RefCell::new(None),
+        }
+    }
+
+    /// Upload this frame's frustum and items, growing the buffers to fit.
+    pub fn write(&self, device: &wgpu::Device, queue: &wgpu::Queue, frustum: &Frustum, items: &[CullItem]) {
+        let needed = items.len() as u32;
+        let mut buffers = self.buffers.borrow_mut();
+        if buffers.as_ref().is_none_or(|b| b.capacity < needed) {
+            let capacity = needed.max(INITIAL_CAPACITY).next_power_of_two();
+            *buffers = Some(self.create_buffers(device, capacity));
+        }
+
+        let params = CullParams {
+            planes: frustum.planes().map(|plane| plane.to_array()),
+            count: needed,
+            _pad: [0; 3],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+        if let Some(buffers) = buffers.as_ref().filter(|_| !items.is_empty()) {
+            queue.write_buffer(&buffers.items, 0, bytemuck::cast_slice(items));
+        }
+    }
+
+    fn create_buffers(&self, device: &wgpu::Device, capacity: u32) -> CullBuffers {
+        tracing::debug!("GPU culling buffers sized for {} draws", capacity);
+        let items = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Cull Item Buffer"),
+            size: capacity as u64 * std::mem::size_of::<CullItem>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let commands = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Cull Indirect Buffer"),
+            size: capacity as u64 * INDIRECT_COMMAND_SIZE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("GPU Cull Bind Group"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: items.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: commands.as_entire_binding(),
+                },
+            ],
+        });
+        CullBuffers { capacity, items, commands, bind_group }
+    }
+
+    /// Write the indirect commands for `count` items; record before the
+    /// rasterize passes that draw them.
+    pub fn record(&self, encoder: &mut wgpu::CommandEncoder, count: u32) {
+        let buffers = self.buffers.borrow();
+        let Some(buffers) = buffers.as_ref().filter(|_| count > 0) else {
+            return;
+        };
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("gpu_cull"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &buffers.bind_group, &[]);
+        pass.dispatch_workgroups(count.div_ceil(GPU_CULL_WORKGROUP_SIZE), 1, 1);
+    }
+
+    /// Indirect commands, `INDIRECT_COMMAND_SIZE` bytes per draw slot (None
+    /// before the first `write`).
+    pub fn commands(&self) -> Option<Ref<'_, wgpu::Buffer>> {
+        Ref::filter_map(self.buffers.borrow(), |b| b.as_ref().map(|b| &b.commands)).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_cull_item_matches_shader_layout() {
+        assert_eq!(std::mem::size_of::<CullItem>(), 48);
+        assert_eq!(std::mem::size_of::<CullParams>(), 112);
+        let bounds = Aabb { min: glam::Vec3::splat(-1.0), max: glam::Vec3::ONE };
+        let item = CullItem::new(&bounds, 30..66, true);
+        assert_eq!((item.first_index, item.index_count, item.always_visible), (30, 36, 1));
+
+        let source = crate::shader::get_gpu_cull_wgsl();
+        if let Err(e) = crate::shader_diagnostic::check_wgsl(&source, Path::new("gpu_cull.wgsl"), &[]) {
+            panic!("{}", e);
+        }
+    }
+}
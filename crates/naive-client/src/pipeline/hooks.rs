@@ -0,0 +1,130 @@
+//! Render hooks: callbacks that run before or after a named pipeline pass.
+//!
+//! Hooks record into the same command encoder as the pass they are attached
+//! to, so they can draw debug geometry, portals or other custom content into
+//! the pipeline's resources without editing the executor. Hooks of passes that
+//! don't execute this frame (missing from the pipeline, or skipped by a debug
+//! toggle) don't run.
+
+use crate::camera::CameraState;
+use crate::mesh::MeshCache;
+use crate::world::SceneWorld;
+
+use super::{CompiledPass, CompiledPipeline};
+
+/// When a hook runs relative to its pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    Before,
+    After,
+}
+
+/// Handle returned by `RenderHooks::add`, used to remove the hook again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+/// Everything a hook may record with. `pass` is the pass the hook is attached to.
+pub struct RenderHookContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub compiled: &'a CompiledPipeline,
+    pub camera_state: &'a CameraState,
+    pub swapchain_view: &'a wgpu::TextureView,
+    pub pass: &'a CompiledPass,
+    pub scene_world: &'a SceneWorld,
+    pub mesh_cache: &'a MeshCache,
+}
+
+impl<'a> RenderHookContext<'a> {
+    /// View of a pipeline resource by name; `"swapchain"` is the frame's output view.
+    pub fn view(&self, name: &str) -> Option<&'a wgpu::TextureView> {
+        if name == "swapchain" {
+            return Some(self.swapchain_view);
+        }
+        self.compiled.resources.get(name).map(|r| &r.view)
+    }
+}
+
+/// A hook callback.
+pub type RenderHookFn = Box<dyn FnMut(&mut RenderHookContext<'_>)>;
+
+struct HookEntry {
+    id: HookId,
+    pass: String,
+    stage: HookStage,
+    hook: RenderHookFn,
+}
+
+/// Registered hooks, run in registration order.
+#[derive(Default)]
+pub struct RenderHooks {
+    entries: Vec<HookEntry>,
+    next_id: u64,
+}
+
+impl RenderHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `hook` before or after every execution of the pass named `pass`.
+    pub fn add(&mut self, pass: &str, stage: HookStage, hook: RenderHookFn) -> HookId {
+        let id = HookId(self.next_id);
+        self.next_id += 1;
+        self.entries.push(HookEntry { id, pass: pass.to_string(), stage, hook });
+        id
+    }
+
+    /// Remove a hook. Returns false if it was already removed.
+    pub fn remove(&mut self, id: HookId) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether any hook is attached to `pass`.
+    pub fn has_hooks(&self, pass: &str) -> bool {
+        self.entries.iter().any(|e| e.pass == pass)
+    }
+
+    /// Run the hooks attached to `ctx.pass` for `stage`.
+    pub fn run(&mut self, stage: HookStage, ctx: &mut RenderHookContext<'_>) {
+        for entry in &mut self.entries {
+            if entry.stage == stage && entry.pass == ctx.pass.name {
+                (entry.hook)(ctx);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove_hooks() {
+        let mut hooks = RenderHooks::new();
+        let a = hooks.add("lighting_pass", HookStage::Before, Box::new(|_| {}));
+        let b = hooks.add("tonemap_pass", HookStage::After, Box::new(|_| {}));
+        assert_ne!(a, b);
+        assert!(hooks.has_hooks("lighting_pass"));
+        assert!(hooks.has_hooks("tonemap_pass"));
+        assert!(!hooks.has_hooks("bloom_pass"));
+
+        assert!(hooks.remove(a));
+        assert!(!hooks.remove(a));
+        assert!(!hooks.has_hooks("lighting_pass"));
+
+        hooks.clear();
+        assert!(hooks.is_empty());
+    }
+}
@@ -1,16 +1,44 @@
+pub mod alias;
+pub mod bloom;
+pub mod clusters;
+pub mod condition;
 pub mod def;
 pub mod resource;
 pub mod compiler;
+pub mod downgrade;
 pub mod executor;
+pub mod gpu_culling;
+pub mod hooks;
+pub mod particles;
+pub mod post;
+pub mod preprocess;
+pub mod profiler;
+pub mod switch;
+pub mod water;
 
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::color_grading::ColorGrading;
+use crate::components::SkyboxSource;
+use crate::environment::EnvironmentMaps;
+
 // Re-export all public items so external code using `crate::pipeline::*` continues to work.
+pub use bloom::BloomChain;
+pub use clusters::LightClusters;
+pub use condition::PassCondition;
 pub use def::*;
 pub use resource::*;
 pub use compiler::compile_pipeline;
-pub use executor::{execute_pipeline, execute_pipeline_to_view, rebuild_bind_groups};
+pub use downgrade::{plan_downgrades, Downgrades};
+pub use gpu_culling::GpuCulling;
+pub use executor::{execute_pipeline, execute_pipeline_to_view, rebuild_bind_groups, set_environment, FrameEncoders};
+pub use hooks::{HookId, HookStage, RenderHookContext, RenderHookFn, RenderHooks};
+pub use particles::ParticlePass;
+pub use post::{PassParams, PostProcessPass};
+pub use profiler::{GpuPassProfiler, PassTiming};
+pub use switch::PipelineSwitch;
+pub use water::WaterPass;
 
 // ---------------------------------------------------------------------------
 // Runtime render debug state (toggled interactively via number keys)
@@ -21,6 +49,7 @@ pub use executor::{execute_pipeline, execute_pipeline_to_view, rebuild_bind_grou
 pub struct RenderDebugState {
     pub bloom_enabled: bool,
     pub point_lights_enabled: bool,
+    pub spot_lights_enabled: bool,
     pub emission_enabled: bool,
     pub torch_flicker_enabled: bool,
     pub show_hud: bool,
@@ -30,6 +59,37 @@ pub struct RenderDebugState {
     pub light_intensity_mult: f32,
     /// Override ambient light level (0.0 = use scene default)
     pub ambient_override: f32,
+    /// Script overrides of the tonemap grading (None = pipeline/scene value).
+    pub exposure: Option<f32>,
+    pub contrast: Option<f32>,
+    pub saturation: Option<f32>,
+    /// Fraction of each foliage scatter drawn (set by the quality preset).
+    pub foliage_density: f32,
+    /// Size of the viewport-sized pipeline targets relative to the window
+    /// (set by `auto_quality`).
+    pub render_scale: f32,
+    /// Shadow map size in texels in place of the pipeline's (set by
+    /// `auto_quality`).
+    pub shadow_resolution: Option<u32>,
+    /// Fraction of the particles emitters and bursts spawn (set by `auto_quality`).
+    pub particle_budget: f32,
+    /// Level-of-detail and culling decisions locked from the command line.
+    pub lod_lock: LodLock,
+    /// Quality preset the toggles above were set from, for `enabled_if`.
+    pub quality: crate::project_config::QualityPreset,
+    /// Overrides of the pipeline's feature flags (`enabled_if: setting.<name>`)
+    /// from naive.yaml, `--setting` and `render.set("setting.<name>", ...)`.
+    pub settings: HashMap<String, bool>,
+}
+
+/// Locks on level-of-detail and culling decisions, so benchmarks and test
+/// renders draw the same work on every machine (`--force-lod0`, `--no-culling`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LodLock {
+    /// Full detail everywhere, whatever the quality preset or distance.
+    pub force_lod0: bool,
+    /// Draw what would be culled by distance or visibility.
+    pub disable_culling: bool,
 }
 
 impl Default for RenderDebugState {
@@ -37,16 +97,110 @@ impl Default for RenderDebugState {
         Self {
             bloom_enabled: true,
             point_lights_enabled: true,
+            spot_lights_enabled: true,
             emission_enabled: true,
             torch_flicker_enabled: true,
             show_hud: false,
             show_colliders: false,
             light_intensity_mult: 1.0,
             ambient_override: 0.0,
+            exposure: None,
+            contrast: None,
+            saturation: None,
+            foliage_density: 1.0,
+            render_scale: 1.0,
+            shadow_resolution: None,
+            particle_budget: 1.0,
+            lod_lock: LodLock::default(),
+            quality: Default::default(),
+            settings: HashMap::new(),
         }
     }
 }
 
+impl RenderDebugState {
+    /// Parameters scripts may set through `render.set(name, value)`.
+    pub const SCRIPT_PARAMS: &'static [&'static str] = &[
+        "bloom",
+        "point_lights",
+        "spot_lights",
+        "emission",
+        "torch_flicker",
+        "light_intensity",
+        "ambient",
+        "exposure",
+        "contrast",
+        "saturation",
+        "foliage_density",
+    ];
+
+    /// Size the viewport-sized targets render at in a `width` x `height` window.
+    pub fn render_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = |n: u32| ((n as f32 * self.render_scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+
+    /// Fraction of each foliage scatter to draw: all of it under `force_lod0`.
+    pub fn foliage_fraction(&self) -> f32 {
+        if self.lod_lock.force_lod0 {
+            1.0
+        } else {
+            self.foliage_density
+        }
+    }
+
+    /// Whether `render.set` accepts `name`: one of `SCRIPT_PARAMS`, or a
+    /// pipeline feature flag as `setting.<name>`.
+    pub fn is_script_param(name: &str) -> bool {
+        Self::SCRIPT_PARAMS.contains(&name) || name.strip_prefix("setting.").is_some_and(|flag| !flag.is_empty())
+    }
+
+    /// Override pipeline feature flags from naive.yaml's `render_settings`,
+    /// then from `--setting` arguments, which win. `render.set` can change
+    /// them afterwards.
+    pub fn override_settings(&mut self, config: &HashMap<String, bool>, cli: &[String]) {
+        self.settings.extend(config.iter().map(|(name, value)| (name.clone(), *value)));
+        for arg in cli {
+            match condition::parse_setting_override(arg) {
+                Ok((name, value)) => {
+                    self.settings.insert(name, value);
+                }
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+    }
+
+    /// Set a script-facing parameter. Toggles and feature flags are on for
+    /// any non-zero value.
+    pub fn set_param(&mut self, name: &str, value: f32) -> Result<(), String> {
+        if let Some(flag) = name.strip_prefix("setting.").filter(|flag| !flag.is_empty()) {
+            self.settings.insert(flag.to_string(), value != 0.0);
+            return Ok(());
+        }
+        match name {
+            "bloom" => self.bloom_enabled = value != 0.0,
+            "point_lights" => self.point_lights_enabled = value != 0.0,
+            "spot_lights" => self.spot_lights_enabled = value != 0.0,
+            "emission" => self.emission_enabled = value != 0.0,
+            "torch_flicker" => self.torch_flicker_enabled = value != 0.0,
+            "light_intensity" => self.light_intensity_mult = value.max(0.0),
+            "ambient" => self.ambient_override = value.max(0.0),
+            "exposure" => self.exposure = Some(value.max(0.0)),
+            "contrast" => self.contrast = Some(value.max(0.0)),
+            "saturation" => self.saturation = Some(value.max(0.0)),
+            "foliage_density" => self.foliage_density = value.clamp(0.0, 1.0),
+            _ => {
+                return Err(format!(
+                    "unknown render parameter '{}' (expected setting.<flag> or one of: {})",
+                    name,
+                    Self::SCRIPT_PARAMS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Compiled pipeline types
 // ---------------------------------------------------------------------------
@@ -58,16 +212,29 @@ pub struct CompiledPipeline {
     pub passes: Vec<CompiledPass>,
     pub pass_order: Vec<usize>,
     pub light_buffer: wgpu::Buffer,
+    /// Point light storage and the per-cluster light lists built before lighting.
+    pub light_clusters: LightClusters,
     pub light_bind_group_layout: wgpu::BindGroupLayout,
     pub light_bind_group: wgpu::BindGroup,
     pub gbuffer_sampler: wgpu::Sampler,
+    /// Input samplers of the bloom, tonemap and FXAA passes (linear clamp
+    /// unless the pass names one), kept for bind group rebuilds on resize.
+    pub bloom_sampler: wgpu::Sampler,
+    pub tonemap_sampler: wgpu::Sampler,
+    pub fxaa_sampler: wgpu::Sampler,
+    /// Named samplers from the pipeline's `samplers:` section.
+    pub samplers: HashMap<String, wgpu::Sampler>,
     pub gbuffer_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub gbuffer_bind_group: Option<wgpu::BindGroup>,
     pub tonemap_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub tonemap_bind_group: Option<wgpu::BindGroup>,
+    /// Tonemap exposure/contrast/saturation and LUT (None without a tonemap pass).
+    pub color_grading: Option<ColorGrading>,
     /// Bloom pass bind group (reads HDR buffer).
     pub bloom_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub bloom_bind_group: Option<wgpu::BindGroup>,
+    /// Downsample/upsample chain run after the bloom pass.
+    pub bloom_chain: Option<BloomChain>,
     /// Bind group layout for splat data (storage buffers).
     pub splat_data_bind_group_layout: Option<wgpu::BindGroupLayout>,
     /// Bind group layout + bind group for splat compositing in lighting pass.
@@ -76,12 +243,42 @@ pub struct CompiledPipeline {
     /// FXAA pass bind group (reads LDR buffer).
     pub fxaa_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub fxaa_bind_group: Option<wgpu::BindGroup>,
+    /// SSAO pass bind group (reads G-buffer normal + depth).
+    pub ssao_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    pub ssao_bind_group: Option<wgpu::BindGroup>,
+    /// Bound as the lighting pass's AO texture when there is no ssao resource.
+    pub ao_fallback_view: wgpu::TextureView,
+    /// Temporal anti-aliasing resolve, if the pipeline has a `taa` pass.
+    pub taa: Option<TaaPass>,
+    /// Compute culling and indirect draws for rasterize passes, when the
+    /// pipeline asks for it and the device supports it.
+    pub gpu_culling: Option<GpuCulling>,
+    /// Fallbacks applied because the device couldn't run the YAML as written.
+    pub downgrades: Downgrades,
+    /// Skybox cubemaps bound to the lighting and skybox passes (black
+    /// stand-ins until `set_environment`), their sampler and uniforms.
+    pub environment: EnvironmentMaps,
+    pub environment_sampler: wgpu::Sampler,
+    pub environment_buffer: wgpu::Buffer,
+    /// Skybox source and intensity the bound maps came from.
+    pub environment_source: Option<(SkyboxSource, f32)>,
+    /// Skybox pass bind group (sky cube + environment uniforms).
+    pub skybox_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    pub skybox_bind_group: Option<wgpu::BindGroup>,
+    /// Built-in water pass, if the pipeline has one.
+    pub water: Option<WaterPass>,
+    /// Built-in particle pass, if the pipeline has one.
+    pub particles: Option<ParticlePass>,
+    /// Reflected bindings and parameters of `postprocess` passes, by pass name.
+    pub post_passes: HashMap<String, PostProcessPass>,
     /// Shadow map resources.
     pub shadow_uniform_buffer: Option<wgpu::Buffer>,
     pub shadow_bind_group_layout: Option<wgpu::BindGroupLayout>,
     pub shadow_bind_group: Option<wgpu::BindGroup>,
     /// Shadow map sampler (comparison) for lighting pass.
     pub shadow_sampler: Option<wgpu::Sampler>,
+    /// Cube shadow maps of shadow-casting point lights.
+    pub point_shadows: PointShadows,
     /// Skin matrix storage buffer for skeletal animation (shared, updated per-entity).
     pub skin_buffer: Option<wgpu::Buffer>,
     pub skin_bind_group_layout: Option<wgpu::BindGroupLayout>,
@@ -98,6 +295,85 @@ pub struct CompiledPass {
     pub depth_target: Option<String>,
     pub wgsl_source: String,
     pub shader_path: PathBuf,
+    /// Files the shader `#include`s, for hot reload.
+    pub includes: Vec<PathBuf>,
+    /// MSAA sample count (1 unless this is a multisampled rasterize pass).
+    pub sample_count: u32,
+    /// Copies the multisampled depth target into its resource after the pass.
+    pub depth_resolve: Option<DepthResolve>,
+    /// Load/clear behavior keyed by target resource name; missing targets clear.
+    pub target_ops: HashMap<String, TargetOpsDef>,
+    /// `enabled_if` conditions, all checked every frame.
+    pub enabled_if: Vec<PassCondition>,
+}
+
+impl CompiledPass {
+    /// Whether the pass runs this frame: its `enabled_if` conditions all hold.
+    pub fn enabled(&self, debug: &RenderDebugState, world: &hecs::World) -> bool {
+        self.enabled_if.iter().all(|condition| condition.holds(debug, world))
+    }
+
+    /// Load op for a color target, clearing to `default` unless the pass YAML
+    /// says otherwise.
+    pub fn color_load_op(&self, target: &str, default: wgpu::Color) -> wgpu::LoadOp<wgpu::Color> {
+        self.target_ops
+            .get(target)
+            .map_or(wgpu::LoadOp::Clear(default), |ops| ops.color_load_op(default))
+    }
+
+    /// Load op for a depth target (clears to 1.0 by default).
+    pub fn depth_load_op(&self, target: &str) -> wgpu::LoadOp<f32> {
+        self.target_ops
+            .get(target)
+            .map_or(wgpu::LoadOp::Clear(1.0), TargetOpsDef::depth_load_op)
+    }
+}
+
+/// Fullscreen pass writing sample 0 of a multisampled depth target into the
+/// single-sample resource later passes read (wgpu can't resolve depth).
+pub struct DepthResolve {
+    pub pipeline: wgpu::RenderPipeline,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+/// Resources read and written by the TAA pass.
+pub struct TaaInputs {
+    /// Current (jittered) frame.
+    pub color: String,
+    /// Depth used to reproject into the previous frame.
+    pub depth: String,
+    /// Last frame's resolved image.
+    pub history: String,
+    /// Second target of the pass, copied into `history` after it runs (a
+    /// pass can't sample and render to the same texture).
+    pub resolve: String,
+}
+
+/// The TAA pass's bind group state, rebuilt on resize.
+pub struct TaaPass {
+    pub inputs: TaaInputs,
+    pub sampler: wgpu::Sampler,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+/// Cube shadow maps for point lights with `casts_shadows`, stored as six
+/// layers per light in one depth array. Rendered by the shadow pass after
+/// its directional map; without a shadow pass the array is a 1x1 stand-in
+/// and `pipeline` is None.
+pub struct PointShadows {
+    pub texture: wgpu::Texture,
+    /// All layers, bound to the lighting pass.
+    pub array_view: wgpu::TextureView,
+    /// One render target per cube face.
+    pub face_views: Vec<wgpu::TextureView>,
+    /// Face view-projection matrices, one per `POINT_SHADOW_FACE_STRIDE`.
+    pub face_buffer: wgpu::Buffer,
+    pub face_bind_group: wgpu::BindGroup,
+    /// `PointShadowUniforms` for the lighting pass.
+    pub uniform_buffer: wgpu::Buffer,
+    pub pipeline: Option<wgpu::RenderPipeline>,
 }
 
 // ---------------------------------------------------------------------------
@@ -227,6 +503,56 @@ passes:
         assert!(pos_light < pos_tone, "lighting must precede tonemap");
     }
 
+    #[test]
+    fn test_dag_orders_shared_outputs_by_file_order() {
+        // The skybox and lighting both write hdr_buffer; tonemap is listed
+        // first but must see both writes
+        let yaml = r#"
+version: 1
+passes:
+  - name: tonemap_pass
+    type: fullscreen
+    shader: tonemap.slang
+    inputs:
+      hdr: hdr_buffer
+    outputs:
+      color: swapchain
+  - name: geometry_pass
+    type: rasterize
+    shader: gbuffer.slang
+    outputs:
+      depth: gbuffer_depth
+  - name: lighting_pass
+    type: fullscreen
+    shader: deferred_light.slang
+    inputs:
+      gbuffer_depth: gbuffer_depth
+    outputs:
+      color: hdr_buffer
+  - name: probe_pass
+    type: fullscreen
+    shader: probe.slang
+    inputs:
+      hdr: hdr_buffer
+    outputs:
+      color: probe_buffer
+  - name: skybox_pass
+    type: skybox
+    inputs:
+      depth: gbuffer_depth
+    outputs:
+      color: hdr_buffer
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let order = build_dag(&pipeline.passes).unwrap();
+        let pos = |i: usize| order.iter().position(|&x| x == i).unwrap();
+        assert!(pos(1) < pos(2), "geometry before lighting");
+        assert!(pos(2) < pos(4), "lighting before skybox (file order)");
+        assert!(pos(4) < pos(0), "tonemap reads the last write");
+        // A reader between two writers sees the first and runs before the second
+        assert!(pos(2) < pos(3) && pos(3) < pos(4));
+    }
+
     #[test]
     fn test_dag_cycle_detection() {
         // Create a cycle: A outputs x, B reads x and outputs y, A reads y
@@ -244,6 +570,14 @@ passes:
                 sort: None,
                 cull: None,
                 dispatch: None,
+                targets: HashMap::new(),
+                samplers: HashMap::new(),
+                grading: None,
+                bloom: None,
+                params: HashMap::new(),
+                defines: HashMap::new(),
+                output_color_space: None,
+                enabled_if: None,
             },
             PassDef {
                 name: "pass_b".to_string(),
@@ -258,6 +592,14 @@ passes:
                 sort: None,
                 cull: None,
                 dispatch: None,
+                targets: HashMap::new(),
+                samplers: HashMap::new(),
+                grading: None,
+                bloom: None,
+                params: HashMap::new(),
+                defines: HashMap::new(),
+                output_color_space: None,
+                enabled_if: None,
             },
         ];
 
@@ -269,6 +611,218 @@ passes:
         }
     }
 
+    #[test]
+    fn test_pass_sample_count() {
+        let yaml = r#"
+version: 1
+settings:
+  samples: 4
+resources:
+  - name: albedo
+    type: texture_2d
+    format: rgba8
+  - name: depth
+    type: texture_2d
+    format: depth32f
+  - name: overlay
+    type: texture_2d
+    format: rgba8
+    samples: 1
+  - name: odd
+    type: texture_2d
+    format: rgba8
+    samples: 3
+passes:
+  - name: geometry_pass
+    type: rasterize
+    shader: gbuffer.slang
+    outputs: { color: albedo, depth: depth }
+  - name: overlay_pass
+    type: rasterize
+    shader: overlay.slang
+    outputs: { color: overlay }
+  - name: lighting_pass
+    type: fullscreen
+    shader: deferred_light.slang
+    outputs: { color: albedo }
+  - name: bad_pass
+    type: rasterize
+    shader: bad.slang
+    outputs: { color: odd }
+  - name: mixed_pass
+    type: rasterize
+    shader: mixed.slang
+    outputs: { color: overlay, normal: odd }
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let count = |i: usize| pass_sample_count(&pipeline, &pipeline.passes[i]);
+        assert_eq!(count(0).unwrap(), 4); // settings.samples
+        assert_eq!(count(1).unwrap(), 1); // resource override
+        assert_eq!(count(2).unwrap(), 1); // fullscreen passes never multisample
+        assert!(count(3).is_err());
+        assert!(count(4).is_err());
+        assert_eq!(PipelineSettings::default().samples, 1);
+    }
+
+    #[test]
+    fn test_pass_target_ops() {
+        let yaml = r#"
+version: 1
+passes:
+  - name: sky_pass
+    type: rasterize
+    shader: gbuffer.slang
+    outputs: { color: hdr_buffer, depth: scene_depth }
+    targets:
+      color: { clear_color: [0.4, 0.6, 0.9, 1.0] }
+      depth: { load: load }
+  - name: ui_pass
+    type: fullscreen
+    shader: ui.slang
+    outputs: { color: swapchain }
+    targets:
+      color: { load: load }
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let sky = &pipeline.passes[0].targets;
+        assert_eq!(
+            sky["color"].color_load_op(wgpu::Color::BLACK),
+            wgpu::LoadOp::Clear(wgpu::Color { r: 0.4, g: 0.6, b: 0.9, a: 1.0 })
+        );
+        assert_eq!(sky["depth"].depth_load_op(), wgpu::LoadOp::Load);
+        assert_eq!(pipeline.passes[1].targets["color"].color_load_op(wgpu::Color::BLACK), wgpu::LoadOp::Load);
+        assert_eq!(TargetOpsDef::default().depth_load_op(), wgpu::LoadOp::Clear(1.0));
+
+        let bad = "version: 1\npasses:\n  - { name: p, type: fullscreen, shader: s, targets: { color: { load: keep } } }\n";
+        assert!(serde_yaml::from_str::<PipelineFile>(bad).is_err());
+    }
+
+    #[test]
+    fn test_pass_samplers() {
+        let yaml = r#"
+version: 1
+samplers:
+  - name: pixel_art
+    filter: nearest
+  - name: trilinear_aniso
+    address_mode: repeat
+    anisotropy: 8
+  - name: soft_shadow
+    compare: less_equal
+passes:
+  - name: lighting_pass
+    type: fullscreen
+    shader: deferred_light.slang
+    outputs: { color: hdr_buffer }
+    samplers: { input: pixel_art, shadow: soft_shadow }
+  - name: fxaa_pass
+    type: fullscreen
+    shader: fxaa.slang
+    outputs: { color: swapchain }
+    samplers: { shadow: pixel_art }
+  - name: geometry_pass
+    type: rasterize
+    shader: gbuffer.slang
+    outputs: { color: albedo }
+    samplers: { input: pixel_art }
+  - name: bloom_pass
+    type: fullscreen
+    shader: bloom.slang
+    outputs: { color: bloom_buffer }
+    samplers: { input: missing }
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let sampler = |i: usize, slot: &str| pass_sampler(&pipeline, &pipeline.passes[i], slot);
+        assert_eq!(sampler(0, "input").unwrap().unwrap().name, "pixel_art");
+        assert_eq!(sampler(0, "shadow").unwrap().unwrap().name, "soft_shadow");
+        assert!(sampler(1, "shadow").is_err()); // shadow slot needs a compare sampler
+        assert!(sampler(2, "input").is_err()); // rasterize passes use material samplers
+        assert!(sampler(3, "input").is_err());
+
+        let aniso = pipeline.samplers[1].descriptor().unwrap();
+        assert_eq!(aniso.anisotropy_clamp, 8);
+        assert_eq!(aniso.address_mode_u, wgpu::AddressMode::Repeat);
+        assert_eq!(pipeline.samplers[2].descriptor().unwrap().compare, Some(wgpu::CompareFunction::LessEqual));
+        let nearest_aniso = SamplerDef { anisotropy: 4, ..pipeline.samplers[0].clone() };
+        assert!(nearest_aniso.descriptor().is_err());
+    }
+
+    #[test]
+    fn test_pass_bloom_settings() {
+        let yaml = r#"
+version: 1
+passes:
+  - name: bloom_pass
+    type: fullscreen
+    shader: bloom.slang
+    outputs: { color: bloom_buffer }
+    bloom: { threshold: 1.0, intensity: 0.4 }
+  - name: tonemap_pass
+    type: fullscreen
+    shader: tonemap.slang
+    outputs: { color: swapchain }
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let bloom = pipeline.passes[0].bloom.unwrap();
+        assert_eq!(bloom.threshold, 1.0);
+        assert_eq!(bloom.intensity, 0.4);
+        assert_eq!(bloom.knee, BloomSettings::default().knee);
+        assert_eq!(bloom.mips, BloomSettings::default().mips);
+        assert!(pipeline.passes[1].bloom.is_none());
+    }
+
+    #[test]
+    fn test_pass_params() {
+        let yaml = r#"
+version: 1
+passes:
+  - name: vignette_pass
+    type: postprocess
+    shader: vignette.wgsl
+    inputs: { scene: ldr_buffer }
+    outputs: { color: swapchain }
+    params: { strength: 0.6, tint: [1.0, 0.9, 0.8] }
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let params = &pipeline.passes[0].params;
+        assert_eq!(params["strength"], ParamValue::Scalar(0.6));
+        assert_eq!(params["tint"].components(), vec![1.0, 0.9, 0.8]);
+    }
+
+    #[test]
+    fn test_pass_defines() {
+        let yaml = r#"
+version: 1
+passes:
+  - name: gbuffer_alpha_test
+    type: rasterize
+    shader: shaders/passes/gbuffer.slang
+    outputs: { albedo: gbuffer_albedo }
+    defines: { ALPHA_TEST: true, NORMAL_MAP: false, SAMPLES: 16, CUTOFF: 0.5, MODE: PCF }
+"#;
+        let pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        let pairs = define_pairs(&pipeline.passes[0].defines);
+        let expected = [("ALPHA_TEST", "1"), ("CUTOFF", "0.5"), ("MODE", "PCF"), ("NORMAL_MAP", "0"), ("SAMPLES", "16")];
+        assert_eq!(pairs, expected.map(|(k, v)| (k.to_string(), v.to_string())));
+    }
+
+    #[test]
+    fn test_point_shadow_faces_follow_dominant_axis() {
+        let light = glam::Vec3::new(1.0, 2.0, 3.0);
+        let faces = point_shadow_face_view_proj(light, 10.0);
+        let dirs = [glam::Vec3::X, -glam::Vec3::X, glam::Vec3::Y, -glam::Vec3::Y, glam::Vec3::Z, -glam::Vec3::Z];
+        for (face, dir) in dirs.iter().enumerate() {
+            // Straight ahead lands in the face's center, inside the depth range
+            let ndc = faces[face].project_point3(light + *dir * 5.0);
+            assert!(ndc.x.abs() < 1e-4 && ndc.y.abs() < 1e-4, "face {} center: {:?}", face, ndc);
+            assert!(ndc.z > 0.0 && ndc.z < 1.0);
+        }
+        // A point mostly along -Y (with some X and Z) stays inside face 3
+        let ndc = faces[3].project_point3(light + glam::Vec3::new(0.9, -2.0, -0.9));
+        assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0);
+        assert_eq!(PointShadowUniforms::default().light_slots[7], [-1; 4]);
+    }
+
     #[test]
     fn test_format_from_string() {
         assert_eq!(
@@ -286,6 +840,20 @@ passes:
         assert!(format_from_string("unknown").is_err());
     }
 
+    #[test]
+    fn test_resource_color_space() {
+        let def = |yaml: &str| -> ResourceDef { serde_yaml::from_str(yaml).unwrap() };
+        assert_eq!(
+            def("{ name: ldr, type: texture_2d, format: rgba8, color_space: srgb }").texture_format().unwrap(),
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        );
+        assert_eq!(
+            def("{ name: ldr, type: texture_2d, format: rgba8 }").texture_format().unwrap(),
+            wgpu::TextureFormat::Rgba8Unorm
+        );
+        assert!(def("{ name: hdr, type: texture_2d, format: rgba16f, color_space: srgb }").texture_format().is_err());
+    }
+
     #[test]
     fn test_parse_resource_size() {
         match parse_resource_size("viewport") {
@@ -313,6 +881,102 @@ passes:
         assert_eq!(PassType::from_str("compute"), Some(PassType::Compute));
         assert_eq!(PassType::from_str("splat"), Some(PassType::Splat));
         assert_eq!(PassType::from_str("shadow"), Some(PassType::Shadow));
+        assert_eq!(PassType::from_str("ssao"), Some(PassType::Ssao));
+        assert_eq!(PassType::from_str("taa"), Some(PassType::Taa));
+        assert_eq!(PassType::from_str("skybox"), Some(PassType::Skybox));
+        assert_eq!(PassType::from_str("water"), Some(PassType::Water));
+        assert_eq!(PassType::from_str("particles"), Some(PassType::Particles));
+        assert_eq!(PassType::from_str("postprocess"), Some(PassType::PostProcess));
         assert_eq!(PassType::from_str("invalid"), None);
     }
+
+    #[test]
+    fn test_ssao_auto_wiring() {
+        let yaml = r#"
+version: 1
+resources:
+  - name: ssao
+    type: texture_2d
+    format: rgba8
+    size: viewport/2
+passes:
+  - name: lighting_pass
+    type: fullscreen
+    shader: deferred_light.slang
+    inputs:
+      gbuffer_normal: gbuffer_normal
+      gbuffer_depth: gbuffer_depth
+    outputs:
+      color: hdr_buffer
+
+  - name: geometry_pass
+    type: rasterize
+    shader: gbuffer.slang
+    outputs:
+      normal: gbuffer_normal
+      depth: gbuffer_depth
+"#;
+
+        let mut pipeline: PipelineFile = serde_yaml::from_str(yaml).unwrap();
+        wire_ssao(&mut pipeline);
+        assert_eq!(pipeline.passes.len(), 3);
+        let ssao = &pipeline.passes[2];
+        assert_eq!(ssao.pass_type, "ssao");
+        assert!(ssao.shader.is_empty());
+        assert_eq!(ssao.outputs.get("color").map(String::as_str), Some(SSAO_RESOURCE));
+        assert_eq!(pipeline.passes[0].inputs.get("ssao").map(String::as_str), Some(SSAO_RESOURCE));
+
+        // Lighting now waits for SSAO, which waits for the G-buffer
+        let order = build_dag(&pipeline.passes).unwrap();
+        let pos = |i: usize| order.iter().position(|&x| x == i).unwrap();
+        assert!(pos(1) < pos(2), "geometry must precede ssao");
+        assert!(pos(2) < pos(0), "ssao must precede lighting");
+
+        // Wiring twice (or with a declared SSAO pass) adds nothing
+        wire_ssao(&mut pipeline);
+        assert_eq!(pipeline.passes.len(), 3);
+
+        // No ssao resource, no pass
+        let mut plain: PipelineFile = serde_yaml::from_str(&yaml.replace("name: ssao", "name: other")).unwrap();
+        wire_ssao(&mut plain);
+        assert_eq!(plain.passes.len(), 2);
+        assert!(!plain.passes[0].inputs.contains_key("ssao"));
+    }
+
+    #[test]
+    fn test_render_debug_set_param() {
+        let mut debug = RenderDebugState::default();
+        debug.set_param("bloom", 0.0).unwrap();
+        assert!(!debug.bloom_enabled);
+        debug.set_param("light_intensity", 2.5).unwrap();
+        assert_eq!(debug.light_intensity_mult, 2.5);
+        debug.set_param("ambient", -1.0).unwrap();
+        assert_eq!(debug.ambient_override, 0.0);
+        debug.set_param("exposure", 1.5).unwrap();
+        assert_eq!(debug.exposure, Some(1.5));
+        debug.set_param("foliage_density", 3.0).unwrap();
+        assert_eq!(debug.foliage_density, 1.0);
+        assert!(debug.set_param("fog", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_force_lod0_draws_all_foliage() {
+        let mut debug = RenderDebugState::default();
+        debug.set_param("foliage_density", 0.25).unwrap();
+        assert_eq!(debug.foliage_fraction(), 0.25);
+        debug.lod_lock.force_lod0 = true;
+        assert_eq!(debug.foliage_fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_spot_lights_toggle_separately() {
+        let mut debug = RenderDebugState::default();
+        debug.set_param("spot_lights", 0.0).unwrap();
+        assert!(!debug.spot_lights_enabled);
+        assert!(debug.point_lights_enabled);
+        debug.set_param("point_lights", 0.0).unwrap();
+        debug.set_param("spot_lights", 1.0).unwrap();
+        assert!(debug.spot_lights_enabled);
+        assert!(!debug.point_lights_enabled);
+    }
 }
@@ -0,0 +1,231 @@
+//! Built-in particle pass: draws the particle system's live particles as
+//! camera-facing, instanced quads over the lit scene.
+//!
+//! Alpha-blended particles are drawn back-to-front first, then additive ones
+//! with a second pipeline. Depth is tested in the shader against the depth
+//! input, and particles fade out as they near the surface behind them (soft
+//! particles) instead of cutting into it.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::components::ParticleBlend;
+use crate::particles::ParticleInstance;
+
+use super::resource::GpuResource;
+
+/// Particles drawn per frame; further ones are skipped with a warning.
+pub const MAX_PARTICLES: usize = 65536;
+
+/// Resources the particle pass reads and writes.
+pub struct ParticleInputs {
+    /// Lit scene color the particles are blended into.
+    pub color: String,
+    pub depth: String,
+}
+
+/// The particle pass's instance buffer and depth binding (rebuilt on resize).
+pub struct ParticlePass {
+    pub inputs: ParticleInputs,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: Option<wgpu::BindGroup>,
+    /// Draws the additive particles after the pass's alpha pipeline.
+    pub additive_pipeline: Option<wgpu::RenderPipeline>,
+    instance_buffer: wgpu::Buffer,
+    /// Instances uploaded for this frame, and how many of them (the first
+    /// ones) are alpha-blended.
+    counts: Cell<(u32, u32)>,
+    warned_capacity: Cell<bool>,
+}
+
+impl ParticlePass {
+    pub fn new(device: &wgpu::Device, inputs: ParticleInputs) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Depth Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Instances"),
+            size: (MAX_PARTICLES * std::mem::size_of::<ParticleInstance>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            inputs,
+            bind_group_layout,
+            bind_group: None,
+            additive_pipeline: None,
+            instance_buffer,
+            counts: Cell::new((0, 0)),
+            warned_capacity: Cell::new(false),
+        }
+    }
+
+    /// Rebind the depth input after it was recreated.
+    pub fn rebuild(&mut self, device: &wgpu::Device, resources: &HashMap<String, GpuResource>) {
+        let Some(depth) = resources.get(&self.inputs.depth) else {
+            self.bind_group = None;
+            return;
+        };
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Depth Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&depth.view),
+            }],
+        }));
+    }
+
+    /// Upload the frame's particles, as returned by
+    /// `ParticleSystem::collect_instances`. Call once a frame before drawing.
+    pub fn upload(&self, queue: &wgpu::Queue, instances: &[ParticleInstance], alpha_count: usize) {
+        let count = instances.len().min(MAX_PARTICLES);
+        if count < instances.len() && !self.warned_capacity.replace(true) {
+            tracing::warn!("More than {} particles; drawing the first ones", MAX_PARTICLES);
+        }
+        if count > 0 {
+            queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances[..count]));
+        }
+        self.counts.set((count as u32, alpha_count.min(count) as u32));
+    }
+
+    /// Draw the uploaded particles into the color input.
+    pub fn record(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::RenderPipeline,
+        pass_name: &str,
+        resources: &HashMap<String, GpuResource>,
+        camera_bind_group: &wgpu::BindGroup,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+    ) {
+        let (count, alpha_count) = self.counts.get();
+        let (Some(color), Some(bind_group)) = (resources.get(&self.inputs.color), &self.bind_group) else {
+            return;
+        };
+        if count == 0 {
+            return;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(pass_name),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes,
+            occlusion_query_set: None,
+        });
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        // 6 vertices per quad, one instance per particle
+        if alpha_count > 0 {
+            render_pass.set_pipeline(pipeline);
+            render_pass.draw(0..6, 0..alpha_count);
+        }
+        if let Some(additive) = self.additive_pipeline.as_ref().filter(|_| count > alpha_count) {
+            render_pass.set_pipeline(additive);
+            render_pass.draw(0..6, alpha_count..count);
+        }
+    }
+}
+
+/// Create the particle pipeline writing `format` with `blend`.
+pub fn create_particle_pipeline(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    format: wgpu::TextureFormat,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+    particles: &ParticlePass,
+    blend: ParticleBlend,
+) -> wgpu::RenderPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Particle Shader"),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Particle Pipeline Layout"),
+        bind_group_layouts: &[camera_bind_group_layout, &particles.bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let blend = match blend {
+        ParticleBlend::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+        // Keep the target's alpha, add the particle's color weighted by its own
+        ParticleBlend::Additive => wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+        },
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Particle Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[ParticleInstance::desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particle_instances_match_the_shader() {
+        let layout = ParticleInstance::desc();
+        assert_eq!(layout.array_stride, 48);
+        assert_eq!(layout.attributes[2].offset, 16);
+        assert_eq!(layout.attributes[3].offset, 32);
+
+        let source = crate::shader::get_particle_wgsl();
+        if let Err(e) = crate::shader_diagnostic::check_wgsl(&source, std::path::Path::new("particles.wgsl"), &[]) {
+            panic!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,644 @@
+//! User-defined post-process passes (`type: postprocess`).
+//!
+//! The pass's WGSL is reflected with naga instead of being matched to a
+//! built-in by name. Every binding is filled from what the shader declares:
+//! textures by the input slot or resource they are named after, samplers
+//! with the pass's input sampler, a uniform named `camera` with the camera
+//! buffer, and any other uniform as a block of parameters that start at the
+//! pass YAML's `params` and can be changed from Lua.
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use super::def::{FilterKind, PassDef, PipelineError, SamplerDef};
+use super::resource::GpuResource;
+use super::CompiledPipeline;
+
+/// Scalar type of a uniform parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Float,
+    Sint,
+    Uint,
+}
+
+/// A scalar or vector inside a parameter block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformMember {
+    pub name: String,
+    /// Byte offset in the block.
+    pub offset: u32,
+    pub kind: ParamKind,
+    /// 1 for scalars, 2 to 4 for vectors.
+    pub components: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingKind {
+    Texture {
+        /// Declared as `texture_depth_2d`.
+        depth: bool,
+        /// Sample type of a `texture_2d<T>`; ignored for depth textures.
+        kind: ParamKind,
+    },
+    Sampler {
+        comparison: bool,
+    },
+    /// A uniform buffer. Members of types other than scalars and vectors are
+    /// zeroed and not exposed as parameters.
+    Uniform {
+        size: u32,
+        members: Vec<UniformMember>,
+    },
+}
+
+/// One resource binding declared by a shader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReflectedBinding {
+    /// Variable name, without the suffix SLANG appends.
+    pub name: String,
+    pub group: u32,
+    pub binding: u32,
+    pub kind: BindingKind,
+}
+
+/// Strip the `_<digits>` suffix SLANG appends to identifiers in its WGSL.
+fn source_name(name: &str) -> &str {
+    match name.rsplit_once('_') {
+        Some((base, digits)) if !base.is_empty() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            base
+        }
+        _ => name,
+    }
+}
+
+fn param_kind(kind: naga::ScalarKind) -> Option<ParamKind> {
+    match kind {
+        naga::ScalarKind::Float => Some(ParamKind::Float),
+        naga::ScalarKind::Sint => Some(ParamKind::Sint),
+        naga::ScalarKind::Uint => Some(ParamKind::Uint),
+        _ => None,
+    }
+}
+
+/// Kind and component count of a scalar or 32-bit vector type.
+fn member_type(inner: &naga::TypeInner) -> Option<(ParamKind, u32)> {
+    match *inner {
+        naga::TypeInner::Scalar(scalar) if scalar.width == 4 => Some((param_kind(scalar.kind)?, 1)),
+        naga::TypeInner::Vector { size, scalar } if scalar.width == 4 => {
+            Some((param_kind(scalar.kind)?, size as u32))
+        }
+        _ => None,
+    }
+}
+
+/// List the resource bindings of a WGSL shader, ordered by group and binding.
+pub fn reflect_bindings(wgsl: &str) -> Result<Vec<ReflectedBinding>, String> {
+    let module = naga::front::wgsl::parse_str(wgsl).map_err(|e| e.emit_to_string(wgsl))?;
+    let mut bindings = Vec::new();
+    for (_, var) in module.global_variables.iter() {
+        let Some(slot) = &var.binding else {
+            continue;
+        };
+        let name = source_name(var.name.as_deref().unwrap_or_default()).to_string();
+        let ty = &module.types[var.ty];
+        let kind = match (var.space, &ty.inner) {
+            (naga::AddressSpace::Handle, naga::TypeInner::Image { dim, arrayed, class }) => {
+                if *dim != naga::ImageDimension::D2 || *arrayed {
+                    return Err(format!("'{}': only 2D textures can be bound", name));
+                }
+                match *class {
+                    naga::ImageClass::Sampled { kind, multi: false } => BindingKind::Texture {
+                        depth: false,
+                        kind: param_kind(kind).ok_or_else(|| format!("'{}': unsupported sample type", name))?,
+                    },
+                    naga::ImageClass::Depth { multi: false } => BindingKind::Texture {
+                        depth: true,
+                        kind: ParamKind::Float,
+                    },
+                    _ => return Err(format!("'{}': multisampled and storage textures can't be bound", name)),
+                }
+            }
+            (naga::AddressSpace::Handle, naga::TypeInner::Sampler { comparison }) => BindingKind::Sampler {
+                comparison: *comparison,
+            },
+            (naga::AddressSpace::Uniform, inner) => {
+                let members = match inner {
+                    naga::TypeInner::Struct { members, .. } => members
+                        .iter()
+                        .filter_map(|member| {
+                            let (kind, components) = member_type(&module.types[member.ty].inner)?;
+                            Some(UniformMember {
+                                name: source_name(member.name.as_deref()?).to_string(),
+                                offset: member.offset,
+                                kind,
+                                components,
+                            })
+                        })
+                        .collect(),
+                    other => member_type(other)
+                        .map(|(kind, components)| UniformMember {
+                            name: name.clone(),
+                            offset: 0,
+                            kind,
+                            components,
+                        })
+                        .into_iter()
+                        .collect(),
+                };
+                BindingKind::Uniform {
+                    size: inner.size(module.to_ctx()),
+                    members,
+                }
+            }
+            _ => return Err(format!("'{}': only textures, samplers and uniforms can be bound", name)),
+        };
+        bindings.push(ReflectedBinding {
+            name,
+            group: slot.group,
+            binding: slot.binding,
+            kind,
+        });
+    }
+    bindings.sort_by_key(|b| (b.group, b.binding));
+    Ok(bindings)
+}
+
+/// What fills a binding.
+enum Source {
+    /// Resource name.
+    Texture(String),
+    Sampler,
+    ComparisonSampler,
+    Camera,
+    /// Index into `blocks`.
+    Params(usize),
+}
+
+struct ParamBlock {
+    buffer: wgpu::Buffer,
+    members: Vec<UniformMember>,
+}
+
+/// A compiled `postprocess` pass's bindings and parameter buffers.
+pub struct PostProcessPass {
+    pub bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+    bind_groups: Vec<wgpu::BindGroup>,
+    sources: Vec<(u32, u32, Source)>,
+    blocks: Vec<ParamBlock>,
+    sampler: wgpu::Sampler,
+    comparison_sampler: wgpu::Sampler,
+    camera_buffer: wgpu::Buffer,
+}
+
+impl PostProcessPass {
+    /// Reflect `wgsl` and resolve each binding against the pass's inputs.
+    /// `input_sampler` is the pass's `input` sampler from the pipeline YAML.
+    pub fn new(
+        device: &wgpu::Device,
+        pass_def: &PassDef,
+        wgsl: &str,
+        resources: &HashMap<String, GpuResource>,
+        input_sampler: Option<(&SamplerDef, &wgpu::Sampler)>,
+        camera_buffer: &wgpu::Buffer,
+    ) -> Result<Self, PipelineError> {
+        let fail = |msg: String| PipelineError::ShaderError(format!("Pass '{}': {}", pass_def.name, msg));
+        let reflected = reflect_bindings(wgsl).map_err(fail)?;
+
+        // Textures first: the sampler type depends on whether all are filterable
+        let mut texture_types = HashMap::new();
+        for binding in &reflected {
+            let BindingKind::Texture { depth, kind } = binding.kind else {
+                continue;
+            };
+            let resource_name = pass_def
+                .inputs
+                .get(&binding.name)
+                .or_else(|| pass_def.inputs.values().find(|r| **r == binding.name))
+                .ok_or_else(|| fail(format!("texture '{}' is not one of the pass's inputs", binding.name)))?;
+            let resource = resources
+                .get(resource_name)
+                .ok_or_else(|| fail(format!("input '{}' is not a pipeline resource", resource_name)))?;
+            let sample_type = match (depth, kind) {
+                (true, _) => wgpu::TextureSampleType::Depth,
+                (false, ParamKind::Sint) => wgpu::TextureSampleType::Sint,
+                (false, ParamKind::Uint) => wgpu::TextureSampleType::Uint,
+                (false, ParamKind::Float) => wgpu::TextureSampleType::Float {
+                    filterable: !resource.format.is_depth_stencil_format()
+                        && resource
+                            .format
+                            .sample_type(None, Some(device.features()))
+                            .is_some_and(|t| t == wgpu::TextureSampleType::Float { filterable: true }),
+                },
+            };
+            texture_types.insert((binding.group, binding.binding), (resource_name.clone(), sample_type));
+        }
+        let filtering = texture_types
+            .values()
+            .all(|(_, t)| *t != wgpu::TextureSampleType::Float { filterable: false });
+        let sampler = match input_sampler {
+            Some((def, _)) if !filtering && (def.filter != FilterKind::Nearest || def.anisotropy > 1) => {
+                return Err(fail(format!(
+                    "sampler '{}' must use nearest filtering for unfilterable inputs",
+                    def.name
+                )));
+            }
+            Some((_, sampler)) => sampler.clone(),
+            None => device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("Post-Process Sampler"),
+                mag_filter: if filtering { wgpu::FilterMode::Linear } else { wgpu::FilterMode::Nearest },
+                min_filter: if filtering { wgpu::FilterMode::Linear } else { wgpu::FilterMode::Nearest },
+                ..Default::default()
+            }),
+        };
+        let comparison_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post-Process Comparison Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let mut sources = Vec::new();
+        let mut entries: Vec<Vec<wgpu::BindGroupLayoutEntry>> = Vec::new();
+        let mut blocks = Vec::new();
+        let mut declared: HashMap<&str, &str> = HashMap::new();
+        for binding in &reflected {
+            let (ty, source) = match &binding.kind {
+                BindingKind::Texture { .. } => {
+                    let (resource_name, sample_type) = texture_types[&(binding.group, binding.binding)].clone();
+                    let ty = wgpu::BindingType::Texture {
+                        sample_type,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    };
+                    (ty, Source::Texture(resource_name))
+                }
+                BindingKind::Sampler { comparison: true } => (
+                    wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    Source::ComparisonSampler,
+                ),
+                BindingKind::Sampler { comparison: false } => {
+                    let ty = if filtering {
+                        wgpu::SamplerBindingType::Filtering
+                    } else {
+                        wgpu::SamplerBindingType::NonFiltering
+                    };
+                    (wgpu::BindingType::Sampler(ty), Source::Sampler)
+                }
+                BindingKind::Uniform { size, members } => {
+                    let source = if binding.name == "camera" {
+                        Source::Camera
+                    } else {
+                        for member in members {
+                            if let Some(other) = declared.insert(member.name.as_str(), binding.name.as_str()) {
+                                return Err(fail(format!(
+                                    "parameter '{}' is declared in both '{}' and '{}'",
+                                    member.name, other, binding.name
+                                )));
+                            }
+                        }
+                        let mut data = vec![0u8; (*size as usize).next_multiple_of(16)];
+                        for member in members {
+                            if let Some(value) = pass_def.params.get(&member.name) {
+                                write_member(&mut data[member.offset as usize..], member, &value.components())
+                                    .map_err(|e| fail(format!("params.{}: {}", member.name, e)))?;
+                            }
+                        }
+                        blocks.push(ParamBlock {
+                            buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                                label: Some(&format!("{} {} Uniform Buffer", pass_def.name, binding.name)),
+                                contents: &data,
+                                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                            }),
+                            members: members.clone(),
+                        });
+                        Source::Params(blocks.len() - 1)
+                    };
+                    let ty = wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    };
+                    (ty, source)
+                }
+            };
+            let group = binding.group as usize;
+            if entries.len() <= group {
+                entries.resize_with(group + 1, Vec::new);
+            }
+            entries[group].push(wgpu::BindGroupLayoutEntry {
+                binding: binding.binding,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty,
+                count: None,
+            });
+            sources.push((binding.group, binding.binding, source));
+        }
+        if let Some(name) = pass_def.params.keys().find(|name| !declared.contains_key(name.as_str())) {
+            return Err(fail(format!("params.{}: the shader declares no such parameter", name)));
+        }
+
+        let bind_group_layouts = entries
+            .iter()
+            .enumerate()
+            .map(|(group, entries)| {
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(&format!("{} Group {} Layout", pass_def.name, group)),
+                    entries,
+                })
+            })
+            .collect();
+
+        let mut pass = Self {
+            bind_group_layouts,
+            bind_groups: Vec::new(),
+            sources,
+            blocks,
+            sampler,
+            comparison_sampler,
+            camera_buffer: camera_buffer.clone(),
+        };
+        pass.rebuild(device, resources);
+        Ok(pass)
+    }
+
+    /// Recreate the bind groups from the current resource views (after a resize).
+    pub fn rebuild(&mut self, device: &wgpu::Device, resources: &HashMap<String, GpuResource>) {
+        let mut bind_groups = Vec::with_capacity(self.bind_group_layouts.len());
+        for (group, layout) in self.bind_group_layouts.iter().enumerate() {
+            let mut entries = Vec::new();
+            for (_, binding, source) in self.sources.iter().filter(|(g, _, _)| *g as usize == group) {
+                let resource = match source {
+                    Source::Texture(name) => match resources.get(name) {
+                        Some(r) => wgpu::BindingResource::TextureView(&r.view),
+                        None => {
+                            tracing::warn!("Post-process input '{}' is gone, keeping the old bindings", name);
+                            return;
+                        }
+                    },
+                    Source::Sampler => wgpu::BindingResource::Sampler(&self.sampler),
+                    Source::ComparisonSampler => wgpu::BindingResource::Sampler(&self.comparison_sampler),
+                    Source::Camera => self.camera_buffer.as_entire_binding(),
+                    Source::Params(index) => self.blocks[*index].buffer.as_entire_binding(),
+                };
+                entries.push(wgpu::BindGroupEntry {
+                    binding: *binding,
+                    resource,
+                });
+            }
+            bind_groups.push(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post-Process Bind Group"),
+                layout,
+                entries: &entries,
+            }));
+        }
+        self.bind_groups = bind_groups;
+    }
+
+    /// Names of the parameters scripts can set.
+    pub fn param_names(&self) -> Vec<String> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.members.iter().map(|m| m.name.clone()))
+            .collect()
+    }
+
+    /// Write one parameter; `value` needs one number per component.
+    pub fn set_param(&self, queue: &wgpu::Queue, name: &str, value: &[f32]) -> Result<(), String> {
+        let (block, member) = self
+            .blocks
+            .iter()
+            .find_map(|block| block.members.iter().find(|m| m.name == name).map(|m| (block, m)))
+            .ok_or_else(|| format!("unknown parameter '{}'", name))?;
+        let mut data = vec![0u8; member.components as usize * 4];
+        write_member(&mut data, member, value)?;
+        queue.write_buffer(&block.buffer, member.offset as u64, &data);
+        Ok(())
+    }
+
+    pub fn bind(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        for (group, bind_group) in self.bind_groups.iter().enumerate() {
+            render_pass.set_bind_group(group as u32, bind_group, &[]);
+        }
+    }
+}
+
+/// Encode `value` into `out` as the member's scalar type.
+fn write_member(out: &mut [u8], member: &UniformMember, value: &[f32]) -> Result<(), String> {
+    if value.len() != member.components as usize {
+        return Err(format!(
+            "'{}' takes {} value(s), got {}",
+            member.name,
+            member.components,
+            value.len()
+        ));
+    }
+    for (chunk, v) in out.chunks_exact_mut(4).zip(value) {
+        let bytes = match member.kind {
+            ParamKind::Float => v.to_le_bytes(),
+            ParamKind::Sint => (*v as i32).to_le_bytes(),
+            ParamKind::Uint => (v.max(0.0) as u32).to_le_bytes(),
+        };
+        chunk.copy_from_slice(&bytes);
+    }
+    Ok(())
+}
+
+/// Fullscreen-triangle pipeline writing `target_formats` (no blending).
+pub fn create_post_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    wgsl_source: &str,
+    pass: &PostProcessPass,
+    target_formats: &[wgpu::TextureFormat],
+) -> wgpu::RenderPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+    let layouts: Vec<&wgpu::BindGroupLayout> = pass.bind_group_layouts.iter().collect();
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(label),
+        bind_group_layouts: &layouts,
+        push_constant_ranges: &[],
+    });
+    let targets: Vec<Option<wgpu::ColorTargetState>> = target_formats
+        .iter()
+        .map(|&format| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        })
+        .collect();
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &targets,
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// Post-process parameters scripts may set, and the values they set.
+/// Values are kept so they survive a pipeline recompile (shader hot reload).
+#[derive(Debug, Default)]
+pub struct PassParams {
+    declared: HashMap<String, Vec<String>>,
+    values: HashMap<(String, String), Vec<f32>>,
+    pending: Vec<(String, String)>,
+}
+
+impl PassParams {
+    /// Take the parameter names of a freshly compiled pipeline's passes and
+    /// queue every stored value that still applies.
+    pub fn declare(&mut self, compiled: &CompiledPipeline) {
+        self.declared = compiled
+            .post_passes
+            .iter()
+            .map(|(pass, post)| (pass.clone(), post.param_names()))
+            .collect();
+        self.values
+            .retain(|(pass, name), _| self.declared.get(pass).is_some_and(|names| names.contains(name)));
+        self.pending = self.values.keys().cloned().collect();
+    }
+
+    pub fn names(&self, pass: &str) -> Option<&[String]> {
+        self.declared.get(pass).map(Vec::as_slice)
+    }
+
+    pub fn set(&mut self, pass: &str, name: &str, value: Vec<f32>) -> Result<(), String> {
+        let names = self.names(pass).ok_or_else(|| format!("no postprocess pass '{}'", pass))?;
+        if !names.iter().any(|n| n == name) {
+            return Err(format!("pass '{}' has no parameter '{}' (has: {})", pass, name, names.join(", ")));
+        }
+        let key = (pass.to_string(), name.to_string());
+        if !self.pending.contains(&key) {
+            self.pending.push(key.clone());
+        }
+        self.values.insert(key, value);
+        Ok(())
+    }
+
+    /// Write the values set since the last call into the pipeline's buffers.
+    pub fn apply(&mut self, queue: &wgpu::Queue, compiled: &CompiledPipeline) {
+        for key in self.pending.drain(..) {
+            let (Some(post), Some(value)) = (compiled.post_passes.get(&key.0), self.values.get(&key)) else {
+                continue;
+            };
+            if let Err(e) = post.set_param(queue, &key.1, value) {
+                tracing::warn!("render.set_param('{}', '{}'): {}", key.0, key.1, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = r#"
+struct Params {
+    strength: f32,
+    tint: vec3<f32>,
+    steps: i32,
+    transform: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var scene: texture_2d<f32>;
+@group(0) @binding(1) var scene_sampler: sampler;
+@group(0) @binding(2) var depth_0: texture_depth_2d;
+@group(1) @binding(0) var<uniform> params_0: Params;
+@group(1) @binding(1) var<uniform> radius: f32;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) p: vec4<f32>) -> @location(0) vec4<f32> {
+    let c = textureSample(scene, scene_sampler, p.xy);
+    let d = textureLoad(depth_0, vec2<i32>(p.xy), 0);
+    return vec4<f32>(c.rgb * params_0.tint * params_0.strength * radius * d, 1.0);
+}
+"#;
+
+    #[test]
+    fn test_reflect_bindings() {
+        let bindings = reflect_bindings(SHADER).unwrap();
+        let names: Vec<_> = bindings.iter().map(|b| (b.name.as_str(), b.group, b.binding)).collect();
+        assert_eq!(
+            names,
+            [("scene", 0, 0), ("scene_sampler", 0, 1), ("depth", 0, 2), ("params", 1, 0), ("radius", 1, 1)]
+        );
+        assert_eq!(bindings[0].kind, BindingKind::Texture { depth: false, kind: ParamKind::Float });
+        assert_eq!(bindings[1].kind, BindingKind::Sampler { comparison: false });
+        assert_eq!(bindings[2].kind, BindingKind::Texture { depth: true, kind: ParamKind::Float });
+
+        let BindingKind::Uniform { size, members } = &bindings[3].kind else {
+            panic!("params is not a uniform");
+        };
+        assert_eq!(*size, 96);
+        // The matrix isn't a tweakable parameter
+        let members: Vec<_> = members.iter().map(|m| (m.name.as_str(), m.offset, m.kind, m.components)).collect();
+        assert_eq!(
+            members,
+            [("strength", 0, ParamKind::Float, 1), ("tint", 16, ParamKind::Float, 3), ("steps", 28, ParamKind::Sint, 1)]
+        );
+
+        // A bare uniform is one parameter named after the variable
+        let BindingKind::Uniform { members, .. } = &bindings[4].kind else {
+            panic!("radius is not a uniform");
+        };
+        assert_eq!(members[0].name, "radius");
+        assert_eq!(members[0].components, 1);
+    }
+
+    #[test]
+    fn test_reflect_rejects_storage_buffers() {
+        let shader = "@group(0) @binding(0) var<storage, read> data: array<f32>;\n\
+                      @fragment fn fs_main() -> @location(0) vec4<f32> { return vec4<f32>(data[0]); }";
+        assert!(reflect_bindings(shader).is_err());
+        assert!(reflect_bindings("fn broken( {").is_err());
+    }
+
+    #[test]
+    fn test_write_member() {
+        let member = UniformMember {
+            name: "steps".to_string(),
+            offset: 0,
+            kind: ParamKind::Sint,
+            components: 2,
+        };
+        let mut out = [0u8; 8];
+        write_member(&mut out, &member, &[3.0, -2.0]).unwrap();
+        assert_eq!(out, [3i32.to_le_bytes(), (-2i32).to_le_bytes()].concat()[..]);
+        assert!(write_member(&mut out, &member, &[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_source_name() {
+        assert_eq!(source_name("params_0"), "params");
+        assert_eq!(source_name("bloom_buffer"), "bloom_buffer");
+        assert_eq!(source_name("_0"), "_0");
+    }
+}
@@ -0,0 +1,404 @@
+//! `#include` and `#define` for WGSL pass shaders.
+//!
+//! WGSL has no preprocessor, so pass shaders loaded from `.wgsl` files go
+//! through this one. SLANG has its own; for SLANG shaders only the include
+//! list is collected, so hot reload can follow the included files.
+//!
+//! - `#include "path"` pastes a file in, resolved against the including
+//!   file's directory, then each include directory. A file is pasted at most
+//!   once per shader, so shared structs can be included from several places.
+//! - `#define NAME value` replaces NAME as a whole word in the lines after it
+//!   (included files too). Function-like macros are not supported. A pass's
+//!   `defines:` are defined before the first line.
+//! - `#ifdef NAME`, `#ifndef NAME`, `#if EXPR`, `#elif EXPR`, `#else` and
+//!   `#endif` select lines. EXPR is a number, a define, `!EXPR` or
+//!   `defined(NAME)`; an undefined name is 0. A block must close in the file
+//!   that opens it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A shader with its includes pasted in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preprocessed {
+    pub source: String,
+    /// Files pasted in, in first-include order (not the shader itself).
+    pub includes: Vec<PathBuf>,
+    /// File and 1-based line each line of `source` came from, for errors.
+    pub origins: Vec<(PathBuf, usize)>,
+}
+
+/// Preprocess the WGSL file at `path` with `defines` predefined.
+pub fn preprocess_file(
+    path: &Path,
+    include_dirs: &[PathBuf],
+    defines: &[(String, String)],
+) -> Result<Preprocessed, String> {
+    preprocess_with(path, include_dirs, defines, &mut |p| std::fs::read_to_string(p))
+}
+
+/// Preprocess with a custom file loader (used by tests).
+pub fn preprocess_with(
+    path: &Path,
+    include_dirs: &[PathBuf],
+    defines: &[(String, String)],
+    load: &mut dyn FnMut(&Path) -> std::io::Result<String>,
+) -> Result<Preprocessed, String> {
+    let source = load(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+    let mut state = State {
+        include_dirs,
+        load,
+        defines: defines.iter().cloned().collect(),
+        seen: vec![path.to_path_buf()],
+        out: String::with_capacity(source.len()),
+        origins: Vec::new(),
+    };
+    state.expand(path, &source)?;
+    let mut includes = state.seen;
+    includes.remove(0);
+    Ok(Preprocessed {
+        source: state.out,
+        includes,
+        origins: state.origins,
+    })
+}
+
+/// Files a shader includes, directly or not, for watching. Unresolvable
+/// includes are skipped: this serves SLANG shaders, whose compiler reports them.
+pub fn collect_includes(path: &Path, include_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(file) = stack.pop() {
+        let Ok(source) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        for line in source.lines() {
+            let Some(Ok(name)) = include_directive(line) else {
+                continue;
+            };
+            if let Some(resolved) = resolve(&file, name, include_dirs, &mut |p| p.is_file()) {
+                if resolved != path && !found.contains(&resolved) {
+                    found.push(resolved.clone());
+                    stack.push(resolved);
+                }
+            }
+        }
+    }
+    found
+}
+
+struct State<'a> {
+    include_dirs: &'a [PathBuf],
+    load: &'a mut dyn FnMut(&Path) -> std::io::Result<String>,
+    defines: HashMap<String, String>,
+    /// The shader and every file pasted so far.
+    seen: Vec<PathBuf>,
+    out: String,
+    origins: Vec<(PathBuf, usize)>,
+}
+
+/// An open `#if` block.
+struct Block {
+    /// Whether the enclosing lines are kept.
+    parent_active: bool,
+    /// Whether this branch's lines are kept.
+    active: bool,
+    /// Whether an earlier branch was taken (for `#elif` / `#else`).
+    taken: bool,
+}
+
+impl State<'_> {
+    fn expand(&mut self, path: &Path, source: &str) -> Result<(), String> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let at = || format!("{}:{}", path.display(), index + 1);
+            let active = blocks.last().is_none_or(|b| b.active);
+            if let Some((directive, rest)) = conditional_directive(line) {
+                match directive {
+                    "#ifdef" | "#ifndef" | "#if" => {
+                        let condition = match directive {
+                            "#ifdef" => self.defines.contains_key(rest),
+                            "#ifndef" => !self.defines.contains_key(rest),
+                            _ => eval_condition(rest, &self.defines).map_err(|e| format!("{}: {}", at(), e))?,
+                        };
+                        blocks.push(Block {
+                            parent_active: active,
+                            active: active && condition,
+                            taken: condition,
+                        });
+                    }
+                    "#elif" | "#else" => {
+                        let block = blocks
+                            .last_mut()
+                            .ok_or_else(|| format!("{}: {} without #if", at(), directive))?;
+                        let condition = directive == "#else"
+                            || eval_condition(rest, &self.defines).map_err(|e| format!("{}: {}", at(), e))?;
+                        block.active = block.parent_active && !block.taken && condition;
+                        block.taken |= condition;
+                    }
+                    _ => {
+                        blocks.pop().ok_or_else(|| format!("{}: #endif without #if", at()))?;
+                    }
+                }
+                self.blank_line(path, index);
+                continue;
+            }
+            if !active {
+                self.blank_line(path, index);
+                continue;
+            }
+            if let Some(name) = include_directive(line) {
+                let name = name.map_err(|e| format!("{}: {}", at(), e))?;
+                let load = &mut *self.load;
+                let resolved = resolve(path, name, self.include_dirs, &mut |p| load(p).is_ok())
+                    .ok_or_else(|| format!("{}: can't find include \"{}\"", at(), name))?;
+                if self.seen.contains(&resolved) {
+                    continue;
+                }
+                let included = (self.load)(&resolved)
+                    .map_err(|e| format!("{}: can't read {}: {}", at(), resolved.display(), e))?;
+                self.seen.push(resolved.clone());
+                self.expand(&resolved, &included)?;
+            } else if let Some(rest) = line.trim_start().strip_prefix("#define") {
+                let (name, value) = parse_define(rest).map_err(|e| format!("{}: {}", at(), e))?;
+                self.defines.insert(name.to_string(), value.to_string());
+                self.blank_line(path, index);
+            } else {
+                self.out.push_str(&substitute(line, &self.defines));
+                self.out.push('\n');
+                self.origins.push((path.to_path_buf(), index + 1));
+            }
+        }
+        if !blocks.is_empty() {
+            return Err(format!("{}: unterminated #if", path.display()));
+        }
+        Ok(())
+    }
+
+    /// Stand-in for a directive or skipped line, keeping line numbers of the rest of the file.
+    fn blank_line(&mut self, path: &Path, index: usize) {
+        self.out.push('\n');
+        self.origins.push((path.to_path_buf(), index + 1));
+    }
+}
+
+/// `(directive, argument)` for `#if`-family lines.
+fn conditional_directive(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    let end = line.find(char::is_whitespace).unwrap_or(line.len());
+    let (directive, rest) = line.split_at(end);
+    matches!(directive, "#ifdef" | "#ifndef" | "#if" | "#elif" | "#else" | "#endif").then(|| (directive, rest.trim()))
+}
+
+/// Evaluate an `#if` expression: a number, a define, `!EXPR` or `defined(NAME)`.
+fn eval_condition(expr: &str, defines: &HashMap<String, String>) -> Result<bool, String> {
+    let expr = expr.trim();
+    if expr.is_empty() {
+        return Err("#if needs an expression".to_string());
+    }
+    if let Some(rest) = expr.strip_prefix('!') {
+        return eval_condition(rest, defines).map(|b| !b);
+    }
+    if let Some(name) = expr
+        .strip_prefix("defined")
+        .and_then(|r| r.trim().strip_prefix('('))
+        .and_then(|r| r.strip_suffix(')'))
+    {
+        return Ok(defines.contains_key(name.trim()));
+    }
+    let value = defines.get(expr).map_or(expr, String::as_str).trim();
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        v => match v.parse::<f64>() {
+            Ok(number) => Ok(number != 0.0),
+            // Undefined names (and empty defines) are 0, as in C
+            Err(_) if v.chars().all(is_ident_char) => Ok(false),
+            Err(_) => Err(format!("unsupported #if expression '{}'", expr)),
+        },
+    }
+}
+
+/// `Some(Ok(name))` for an `#include "name"` line, `Some(Err)` for a malformed one.
+fn include_directive(line: &str) -> Option<Result<&str, String>> {
+    let rest = line.trim_start().strip_prefix("#include")?;
+    let rest = rest.trim();
+    Some(
+        rest.strip_prefix('"')
+            .and_then(|r| r.strip_suffix('"'))
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| format!("expected #include \"file\", got #include {}", rest)),
+    )
+}
+
+fn parse_define(rest: &str) -> Result<(&str, &str), String> {
+    let rest = rest.trim();
+    let name_end = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+    let (name, value) = rest.split_at(name_end);
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("expected #define NAME value, got #define {}", rest));
+    }
+    if value.starts_with('(') {
+        return Err(format!("#define {}: function-like macros are not supported", name));
+    }
+    Ok((name, value.trim()))
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Replace whole-word uses of each define, leaving `//` comments alone.
+fn substitute(line: &str, defines: &HashMap<String, String>) -> String {
+    if defines.is_empty() {
+        return line.to_string();
+    }
+    let (code, comment) = line.find("//").map_or((line, ""), |i| line.split_at(i));
+    let mut out = String::with_capacity(line.len());
+    let mut word_start = None;
+    for (i, c) in code.char_indices().chain(std::iter::once((code.len(), ' '))) {
+        if is_ident_char(c) {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            let word = &code[start..i];
+            out.push_str(defines.get(word).map_or(word, String::as_str));
+        }
+        if i < code.len() {
+            out.push(c);
+        }
+    }
+    out.push_str(comment);
+    out
+}
+
+/// Find an included file next to `from`, then in each include directory.
+fn resolve(
+    from: &Path,
+    name: &str,
+    include_dirs: &[PathBuf],
+    exists: &mut dyn FnMut(&Path) -> bool,
+) -> Option<PathBuf> {
+    let base = from.parent().unwrap_or(Path::new("."));
+    std::iter::once(base)
+        .chain(include_dirs.iter().map(PathBuf::as_path))
+        .map(|dir| normalize(&dir.join(name)))
+        .find(|candidate| exists(candidate))
+}
+
+/// Drop `.` and fold `..` components, so one file always has one path.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir if out.file_name().is_some() => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(files: &[(&str, &str)], include_dirs: &[&str]) -> Result<Preprocessed, String> {
+        let files: HashMap<PathBuf, String> =
+            files.iter().map(|(p, s)| (PathBuf::from(p), s.to_string())).collect();
+        let dirs: Vec<PathBuf> = include_dirs.iter().map(PathBuf::from).collect();
+        let mut load = |p: &Path| {
+            files
+                .get(p)
+                .cloned()
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
+        };
+        preprocess_with(Path::new("shaders/passes/main.wgsl"), &dirs, &[], &mut load)
+    }
+
+    #[test]
+    fn test_include_pastes_each_file_once() {
+        let result = run(
+            &[
+                ("shaders/passes/main.wgsl", "#include \"brdf.wgsl\"\n#include \"../common/types.wgsl\"\nfn main() {}"),
+                ("shaders/passes/brdf.wgsl", "#include \"../common/types.wgsl\"\nfn brdf() {}"),
+                ("shaders/common/types.wgsl", "struct Light { color: vec3<f32> }"),
+            ],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(result.source, "struct Light { color: vec3<f32> }\nfn brdf() {}\nfn main() {}\n");
+        assert_eq!(
+            result.includes,
+            [PathBuf::from("shaders/passes/brdf.wgsl"), PathBuf::from("shaders/common/types.wgsl")]
+        );
+        assert_eq!(
+            result.origins,
+            [
+                (PathBuf::from("shaders/common/types.wgsl"), 1),
+                (PathBuf::from("shaders/passes/brdf.wgsl"), 2),
+                (PathBuf::from("shaders/passes/main.wgsl"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_include_searches_include_dirs() {
+        let result = run(
+            &[
+                ("shaders/passes/main.wgsl", "#include \"common/pbr.wgsl\""),
+                ("shaders/common/pbr.wgsl", "const PI: f32 = 3.14159;"),
+            ],
+            &["shaders"],
+        )
+        .unwrap();
+        assert_eq!(result.source, "const PI: f32 = 3.14159;\n");
+
+        let err = run(&[("shaders/passes/main.wgsl", "\n#include \"missing.wgsl\"")], &["shaders"]).unwrap_err();
+        assert!(err.contains("main.wgsl:2"), "{}", err);
+        assert!(run(&[("shaders/passes/main.wgsl", "#include missing.wgsl")], &[]).is_err());
+    }
+
+    #[test]
+    fn test_define_substitutes_whole_words() {
+        let result = run(
+            &[(
+                "shaders/passes/main.wgsl",
+                "#define SAMPLES 16\nlet n = SAMPLES + SAMPLES_MAX; // SAMPLES stays",
+            )],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(result.source, "\nlet n = 16 + SAMPLES_MAX; // SAMPLES stays\n");
+        assert!(run(&[("shaders/passes/main.wgsl", "#define SQ(x) x * x")], &[]).is_err());
+        assert!(run(&[("shaders/passes/main.wgsl", "#define 1X 2")], &[]).is_err());
+    }
+
+    #[test]
+    fn test_conditionals_and_pass_defines() {
+        let source = "#ifdef NORMAL_MAP\nnormal\n#else\nflat\n#endif\n#if SAMPLES\nlet n = SAMPLES;\n#elif defined(FAST)\nfast\n#endif\n#ifndef SKINNED\n#include \"missing.wgsl\"\n#endif";
+        let mut load = |_: &Path| -> std::io::Result<String> { Ok(source.to_string()) };
+        let path = Path::new("main.wgsl");
+        let defines = [("NORMAL_MAP".to_string(), "1".to_string()), ("SAMPLES".to_string(), "16".to_string()), ("SKINNED".to_string(), "0".to_string())];
+        let result = preprocess_with(path, &[], &defines, &mut load).unwrap();
+        assert_eq!(result.source, "\nnormal\n\n\n\n\nlet n = 16;\n\n\n\n\n\n\n");
+        assert_eq!(result.origins.len(), 13);
+
+        let result = preprocess_with(path, &[], &[("FAST".to_string(), String::new()), ("SKINNED".to_string(), String::new())], &mut load).unwrap();
+        assert_eq!(result.source, "\n\n\nflat\n\n\n\n\nfast\n\n\n\n\n");
+
+        let mut unterminated = |_: &Path| -> std::io::Result<String> { Ok("#if 1\nx".to_string()) };
+        assert!(preprocess_with(path, &[], &[], &mut unterminated).unwrap_err().contains("unterminated"));
+        let mut stray = |_: &Path| -> std::io::Result<String> { Ok("#endif".to_string()) };
+        assert!(preprocess_with(path, &[], &[], &mut stray).is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Path::new("a/b/../c/./d.wgsl")), PathBuf::from("a/c/d.wgsl"));
+        assert_eq!(normalize(Path::new("../x.wgsl")), PathBuf::from("../x.wgsl"));
+    }
+}
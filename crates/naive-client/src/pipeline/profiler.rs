@@ -0,0 +1,187 @@
+//! Per-pass GPU timing using timestamp queries.
+//!
+//! Each executed pass writes a begin/end timestamp into a query set. The
+//! results are resolved into a buffer, copied to a mappable readback buffer and
+//! read without stalling: timings lag the frame they describe by a frame or two.
+//! While a readback is still in flight, new frames are simply not profiled.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Upper bound on passes timed per frame.
+pub const MAX_PROFILED_PASSES: u32 = 32;
+
+const TIMESTAMP_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+const READBACK_IDLE: u8 = 0;
+const READBACK_MAPPING: u8 = 1;
+const READBACK_READY: u8 = 2;
+
+/// GPU time spent in one pass.
+#[derive(Debug, Clone)]
+pub struct PassTiming {
+    pub name: String,
+    pub gpu_ms: f32,
+}
+
+/// Timestamp-query profiler for the compiled pipeline's passes.
+pub struct GpuPassProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period_ns: f32,
+    /// Whether the current frame is being recorded.
+    recording: bool,
+    /// Passes recorded in the current frame, in query order.
+    frame_passes: Vec<String>,
+    /// Passes whose timestamps are in the readback buffer.
+    readback_passes: Vec<String>,
+    readback_state: Arc<AtomicU8>,
+    timings: Vec<PassTiming>,
+}
+
+impl GpuPassProfiler {
+    /// Create a profiler, or `None` if the device lacks timestamp queries.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let count = MAX_PROFILED_PASSES * 2;
+        let size = count as u64 * TIMESTAMP_SIZE;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamp Queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timestamp Resolve"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timestamp Readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period_ns: queue.get_timestamp_period(),
+            recording: false,
+            frame_passes: Vec::new(),
+            readback_passes: Vec::new(),
+            readback_state: Arc::new(AtomicU8::new(READBACK_IDLE)),
+            timings: Vec::new(),
+        })
+    }
+
+    /// Collect finished readbacks and start recording a new frame if the
+    /// readback buffer is free.
+    pub fn begin_frame(&mut self, device: &wgpu::Device) {
+        let _ = device.poll(wgpu::Maintain::Poll);
+        if self.readback_state.load(Ordering::Acquire) == READBACK_READY {
+            self.read_timings();
+        }
+        self.frame_passes.clear();
+        self.recording = self.readback_state.load(Ordering::Acquire) == READBACK_IDLE;
+    }
+
+    /// Timestamp writes for the next pass, or `None` if this frame is not
+    /// being profiled or the pass limit is reached.
+    pub fn pass_timestamps(&mut self, pass_name: &str) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        if !self.recording || self.frame_passes.len() as u32 >= MAX_PROFILED_PASSES {
+            return None;
+        }
+        let index = self.frame_passes.len() as u32;
+        self.frame_passes.push(pass_name.to_string());
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: Some(index * 2 + 1),
+        })
+    }
+
+    /// Resolve this frame's queries into the readback buffer. Call once after
+    /// all passes are recorded, before the encoder is submitted.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if !self.recording || self.frame_passes.is_empty() {
+            self.recording = false;
+            return;
+        }
+        let count = self.frame_passes.len() as u32 * 2;
+        let size = count as u64 * TIMESTAMP_SIZE;
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, size);
+        self.readback_passes = std::mem::take(&mut self.frame_passes);
+    }
+
+    /// Start mapping the readback buffer. Call after the frame's submit.
+    pub fn after_submit(&mut self) {
+        if !self.recording || self.readback_passes.is_empty() {
+            return;
+        }
+        self.recording = false;
+        let size = self.readback_passes.len() as u64 * 2 * TIMESTAMP_SIZE;
+        let state = Arc::clone(&self.readback_state);
+        state.store(READBACK_MAPPING, Ordering::Release);
+        self.readback_buffer.slice(..size).map_async(wgpu::MapMode::Read, move |result| {
+            let next = if result.is_ok() { READBACK_READY } else { READBACK_IDLE };
+            state.store(next, Ordering::Release);
+        });
+    }
+
+    /// Most recent per-pass timings, in execution order.
+    pub fn timings(&self) -> &[PassTiming] {
+        &self.timings
+    }
+
+    /// Sum of the most recent pass timings.
+    pub fn total_ms(&self) -> f32 {
+        self.timings.iter().map(|t| t.gpu_ms).sum()
+    }
+
+    fn read_timings(&mut self) {
+        let size = self.readback_passes.len() as u64 * 2 * TIMESTAMP_SIZE;
+        {
+            let data = self.readback_buffer.slice(..size).get_mapped_range();
+            let ticks: &[u64] = bytemuck::cast_slice(&data);
+            self.timings = pass_timings(&self.readback_passes, ticks, self.period_ns);
+        }
+        self.readback_buffer.unmap();
+        self.readback_state.store(READBACK_IDLE, Ordering::Release);
+    }
+}
+
+/// Convert begin/end tick pairs into per-pass milliseconds.
+fn pass_timings(names: &[String], ticks: &[u64], period_ns: f32) -> Vec<PassTiming> {
+    names
+        .iter()
+        .zip(ticks.chunks_exact(2))
+        .map(|(name, pair)| PassTiming {
+            name: name.clone(),
+            gpu_ms: pair[1].saturating_sub(pair[0]) as f32 * period_ns / 1_000_000.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pass_timings_from_ticks() {
+        let names = vec!["geometry".to_string(), "bloom_pass".to_string()];
+        // Second pair is out of order (counter reset) and clamps to zero
+        let ticks = [1_000, 2_501_000, 9_000_000, 8_000_000];
+        let timings = pass_timings(&names, &ticks, 1.0);
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].name, "geometry");
+        assert!((timings[0].gpu_ms - 2.5).abs() < 1e-4);
+        assert_eq!(timings[1].gpu_ms, 0.0);
+    }
+}
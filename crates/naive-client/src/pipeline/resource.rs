@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::def::{PipelineError, ResourceDef};
+use super::def::{PipelineError, ResourceDef, SamplerDef};
 
 // ---------------------------------------------------------------------------
 // GPU resource types
@@ -13,6 +13,18 @@ pub enum PassType {
     Compute,
     Splat,
     Shadow,
+    /// Built-in screen-space ambient occlusion from the G-buffer.
+    Ssao,
+    /// Built-in temporal anti-aliasing resolve with a history resource.
+    Taa,
+    /// Built-in skybox background, drawn where the depth input is empty.
+    Skybox,
+    /// Built-in water surfaces over the lit scene, reading its depth.
+    Water,
+    /// Built-in particles over the lit scene, soft against its depth.
+    Particles,
+    /// User shader over fullscreen inputs, bound by reflecting its WGSL.
+    PostProcess,
 }
 
 impl PassType {
@@ -23,6 +35,12 @@ impl PassType {
             "compute" => Some(Self::Compute),
             "splat" => Some(Self::Splat),
             "shadow" => Some(Self::Shadow),
+            "ssao" => Some(Self::Ssao),
+            "taa" => Some(Self::Taa),
+            "skybox" => Some(Self::Skybox),
+            "water" => Some(Self::Water),
+            "particles" => Some(Self::Particles),
+            "postprocess" => Some(Self::PostProcess),
             _ => None,
         }
     }
@@ -82,6 +100,13 @@ pub fn parse_resource_size(s: &str) -> ResourceSize {
 // GPU resource allocation
 // ---------------------------------------------------------------------------
 
+/// Usage of every pipeline resource. Copies move the TAA resolve into its
+/// history resource.
+const RESOURCE_USAGE: wgpu::TextureUsages = wgpu::TextureUsages::RENDER_ATTACHMENT
+    .union(wgpu::TextureUsages::TEXTURE_BINDING)
+    .union(wgpu::TextureUsages::COPY_SRC)
+    .union(wgpu::TextureUsages::COPY_DST);
+
 /// A GPU texture resource allocated by the pipeline.
 pub struct GpuResource {
     pub texture: wgpu::Texture,
@@ -89,19 +114,101 @@ pub struct GpuResource {
     pub format: wgpu::TextureFormat,
     pub size: ResourceSize,
     pub name: String,
+    /// MSAA sample count of the rasterize passes writing this resource.
+    pub sample_count: u32,
+    /// Multisampled render target when `sample_count > 1`. Color is resolved
+    /// into `texture`; depth is copied by the pass's depth resolve.
+    pub msaa_texture: Option<wgpu::Texture>,
+    pub msaa_view: Option<wgpu::TextureView>,
+    /// Resource whose textures this one shares (see `alias`).
+    pub alias_of: Option<String>,
+}
+
+impl GpuResource {
+    /// The view a pass renders into: the multisampled one when MSAA is on.
+    pub fn render_view(&self) -> &wgpu::TextureView {
+        self.msaa_view.as_ref().unwrap_or(&self.view)
+    }
+
+    /// Resource `name` on this resource's textures.
+    fn alias(&self, name: &str) -> GpuResource {
+        GpuResource {
+            texture: self.texture.clone(),
+            view: self.view.clone(),
+            format: self.format,
+            size: self.size,
+            name: name.to_string(),
+            sample_count: self.sample_count,
+            msaa_texture: self.msaa_texture.clone(),
+            msaa_view: self.msaa_view.clone(),
+            alias_of: Some(self.name.clone()),
+        }
+    }
+
+    /// Approximate memory of the textures, MSAA target included.
+    fn texture_bytes(&self) -> u64 {
+        let size = self.texture.size();
+        let pixel = self.format.block_copy_size(None).unwrap_or(4) as u64;
+        let pixels = size.width as u64 * size.height as u64;
+        pixels * pixel * (1 + self.msaa_texture.as_ref().map_or(0, |_| self.sample_count as u64))
+    }
 }
 
-/// Allocate all pipeline resources as GPU textures.
+/// Create the multisampled companion of a resource, or None for `sample_count <= 1`.
+fn create_msaa_texture(
+    device: &wgpu::Device,
+    name: &str,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    sample_count: u32,
+) -> Option<(wgpu::Texture, wgpu::TextureView)> {
+    if sample_count <= 1 {
+        return None;
+    }
+    let mut usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+    if format.is_depth_stencil_format() {
+        // Read by the depth resolve pass
+        usage |= wgpu::TextureUsages::TEXTURE_BINDING;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(&format!("{} (MSAA)", name)),
+        size: wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    Some((texture, view))
+}
+
+/// Allocate all pipeline resources as GPU textures. Resources listed in
+/// `sample_counts` with a count above 1 also get a multisampled render target,
+/// and those in `format_overrides` use that format instead of their own.
+/// Resources in `aliases` share the textures of the resource they map to.
 pub fn allocate_resources(
     device: &wgpu::Device,
     resource_defs: &[ResourceDef],
+    sample_counts: &HashMap<String, u32>,
+    format_overrides: &HashMap<String, wgpu::TextureFormat>,
+    aliases: &HashMap<String, String>,
     viewport_width: u32,
     viewport_height: u32,
 ) -> Result<HashMap<String, GpuResource>, PipelineError> {
     let mut resources = HashMap::new();
 
-    for def in resource_defs {
-        let format = format_from_string(&def.format)?;
+    for def in resource_defs.iter().filter(|def| !aliases.contains_key(&def.name)) {
+        let format = match format_overrides.get(&def.name) {
+            Some(&format) => format,
+            None => def.texture_format()?,
+        };
         let size = parse_resource_size(&def.size);
         let (width, height) = match size {
             ResourceSize::Viewport => (viewport_width, viewport_height),
@@ -109,9 +216,6 @@ pub fn allocate_resources(
             ResourceSize::Fixed(w, h) => (w, h),
         };
 
-        let usage =
-            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
-
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some(&def.name),
             size: wgpu::Extent3d {
@@ -123,13 +227,19 @@ pub fn allocate_resources(
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
-            usage,
+            usage: RESOURCE_USAGE,
             view_formats: &[],
         });
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sample_count = sample_counts.get(&def.name).copied().unwrap_or(1);
+        let (msaa_texture, msaa_view) =
+            create_msaa_texture(device, &def.name, format, width, height, sample_count).unzip();
 
-        tracing::debug!("Allocated pipeline resource '{}': {:?} {}x{}", def.name, format, width, height);
+        tracing::debug!(
+            "Allocated pipeline resource '{}': {:?} {}x{} ({}x MSAA)",
+            def.name, format, width, height, sample_count
+        );
 
         resources.insert(
             def.name.clone(),
@@ -139,13 +249,51 @@ pub fn allocate_resources(
                 format,
                 size,
                 name: def.name.clone(),
+                sample_count,
+                msaa_texture,
+                msaa_view,
+                alias_of: None,
             },
         );
     }
 
+    let mut saved_bytes = 0;
+    for def in resource_defs {
+        let Some(owner) = aliases.get(&def.name) else { continue };
+        let alias = resources[owner].alias(&def.name);
+        saved_bytes += alias.texture_bytes();
+        tracing::debug!("Pipeline resource '{}' shares the textures of '{}'", def.name, owner);
+        resources.insert(def.name.clone(), alias);
+    }
+    if !aliases.is_empty() {
+        tracing::info!(
+            "Pipeline resources: {} share textures with others, saving {:.1} MB",
+            aliases.len(),
+            saved_bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+
     Ok(resources)
 }
 
+/// Create the pipeline's named samplers.
+pub fn create_samplers(
+    device: &wgpu::Device,
+    defs: &[SamplerDef],
+) -> Result<HashMap<String, wgpu::Sampler>, PipelineError> {
+    let mut samplers = HashMap::new();
+    for def in defs {
+        let sampler = device.create_sampler(&def.descriptor()?);
+        if samplers.insert(def.name.clone(), sampler).is_some() {
+            return Err(PipelineError::InvalidFormat(format!(
+                "Sampler '{}' is defined twice",
+                def.name
+            )));
+        }
+    }
+    Ok(samplers)
+}
+
 /// Recreate all viewport-sized resources after a window resize.
 pub fn resize_resources(
     device: &wgpu::Device,
@@ -153,36 +301,76 @@ pub fn resize_resources(
     new_width: u32,
     new_height: u32,
 ) {
-    for resource in resources.values_mut() {
+    for resource in resources.values_mut().filter(|r| r.alias_of.is_none()) {
         let (w, h) = match resource.size {
             ResourceSize::Viewport => (new_width, new_height),
             ResourceSize::ViewportDiv(d) => (new_width / d, new_height / d),
             ResourceSize::Fixed(_, _) => continue,
         };
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some(&resource.name),
-            size: wgpu::Extent3d {
-                width: w.max(1),
-                height: h.max(1),
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: resource.format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                | wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
-        });
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        resource.texture = texture;
-        resource.view = view;
-        tracing::debug!(
-            "Resized pipeline resource '{}': {}x{}",
-            resource.name,
-            w,
-            h
-        );
+        reallocate(device, resource, w, h);
+    }
+    refresh_aliases(resources);
+}
+
+/// Recreate a fixed-size resource at `size` texels square, or at the size
+/// the pipeline declares when None (the shadow map under `auto_quality`).
+/// Returns false when there is no such fixed-size resource.
+pub fn resize_fixed_resource(
+    device: &wgpu::Device,
+    resources: &mut HashMap<String, GpuResource>,
+    name: &str,
+    size: Option<u32>,
+) -> bool {
+    let Some(resource) = resources.get_mut(name).filter(|r| r.alias_of.is_none()) else {
+        return false;
+    };
+    let ResourceSize::Fixed(declared_w, declared_h) = resource.size else {
+        return false;
+    };
+    let (w, h) = size.map_or((declared_w, declared_h), |s| (s, s));
+    reallocate(device, resource, w, h);
+    refresh_aliases(resources);
+    true
+}
+
+/// Replace a resource's textures with new ones of `width` x `height`.
+fn reallocate(device: &wgpu::Device, resource: &mut GpuResource, width: u32, height: u32) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(&resource.name),
+        size: wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: resource.format,
+        usage: RESOURCE_USAGE,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    resource.texture = texture;
+    resource.view = view;
+    (resource.msaa_texture, resource.msaa_view) =
+        create_msaa_texture(device, &resource.name, resource.format, width, height, resource.sample_count).unzip();
+    tracing::debug!(
+        "Resized pipeline resource '{}': {}x{}",
+        resource.name,
+        width,
+        height
+    );
+}
+
+/// Point aliases at their owners' current textures.
+fn refresh_aliases(resources: &mut HashMap<String, GpuResource>) {
+    let aliased: Vec<(String, String)> = resources
+        .values()
+        .filter_map(|r| Some((r.name.clone(), r.alias_of.clone()?)))
+        .collect();
+    for (name, owner) in aliased {
+        let alias = resources[&owner].alias(&name);
+        resources.insert(name, alias);
     }
 }
 
@@ -190,7 +378,9 @@ pub fn resize_resources(
 // Light uniforms for deferred lighting
 // ---------------------------------------------------------------------------
 
-/// Per-point-light data sent to the GPU.
+/// Per-light data sent to the GPU. Spot lights share the point light list:
+/// the cone factor is `saturate(dot(-to_light, direction) * spot_scale +
+/// spot_offset)` squared, which `point` keeps at 1 everywhere.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct PointLightUniform {
@@ -198,6 +388,49 @@ pub struct PointLightUniform {
     pub range: f32,
     pub color: [f32; 3],
     pub intensity: f32,
+    pub direction: [f32; 3],
+    pub spot_scale: f32,
+    pub spot_offset: f32,
+    pub _pad: [f32; 3],
+}
+
+impl PointLightUniform {
+    /// An omnidirectional light.
+    pub fn point(position: glam::Vec3, range: f32, color: glam::Vec3, intensity: f32) -> Self {
+        Self {
+            position: position.to_array(),
+            range,
+            color: color.to_array(),
+            intensity,
+            direction: [0.0, -1.0, 0.0],
+            spot_scale: 0.0,
+            spot_offset: 1.0,
+            _pad: [0.0; 3],
+        }
+    }
+
+    /// A light shining into a cone around `direction` (world space,
+    /// normalized), full strength inside `inner_angle` and fading to zero at
+    /// `outer_angle` (half-angles in degrees).
+    pub fn spot(
+        position: glam::Vec3,
+        range: f32,
+        color: glam::Vec3,
+        intensity: f32,
+        direction: glam::Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        let cos_inner = inner_angle.to_radians().cos();
+        let cos_outer = outer_angle.to_radians().cos();
+        let spot_scale = 1.0 / (cos_inner - cos_outer).max(1e-4);
+        Self {
+            direction: direction.to_array(),
+            spot_scale,
+            spot_offset: -cos_outer * spot_scale,
+            ..Self::point(position, range, color, intensity)
+        }
+    }
 }
 
 /// Shadow pass uniforms (light view-projection matrix).
@@ -207,9 +440,71 @@ pub struct ShadowUniforms {
     pub light_view_projection: [[f32; 4]; 4],
 }
 
-/// Light data buffer header + array.
-pub const MAX_LIGHTS: usize = 32;
+/// Point lights in the light storage buffer (fewer if the device's storage
+/// buffer bindings are smaller, see `downgrade::light_capacity`).
+pub const MAX_LIGHTS: usize = 1024;
+
+/// Light clusters across the screen, down it and through the depth range.
+/// Depth slices are spaced exponentially between the near and far planes.
+pub const CLUSTER_GRID: [u32; 3] = [16, 9, 24];
+/// `u32`s per cluster in the cluster buffer: a light count, then up to
+/// `CLUSTER_STRIDE - 1` light indices.
+pub const CLUSTER_STRIDE: u32 = 64;
+
+/// Point lights that can cast shadows in one frame (first come, first served).
+pub const MAX_POINT_SHADOWS: usize = 4;
+/// Face size of each point light's cube shadow map.
+pub const POINT_SHADOW_SIZE: u32 = 512;
+/// Stride of the per-face view-projection matrices in the point shadow
+/// pass's dynamic uniform buffer (the minimum uniform offset alignment).
+pub const POINT_SHADOW_FACE_STRIDE: u64 = 256;
+/// Near plane of the cube shadow face projections.
+const POINT_SHADOW_NEAR: f32 = 0.05;
+
+/// Point light shadow data for the lighting pass. The cube faces of shadow
+/// slot `s` are layers `s * 6 .. s * 6 + 6` of the point shadow array, in
+/// +X, -X, +Y, -Y, +Z, -Z order.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointShadowUniforms {
+    pub face_view_proj: [[[f32; 4]; 4]; MAX_POINT_SHADOWS * 6],
+    /// Shadow slot of each point light, -1 for none (four lights per vec4).
+    pub light_slots: [[i32; 4]; MAX_LIGHTS / 4],
+}
+
+impl Default for PointShadowUniforms {
+    fn default() -> Self {
+        Self {
+            face_view_proj: [[[0.0; 4]; 4]; MAX_POINT_SHADOWS * 6],
+            light_slots: [[-1; 4]; MAX_LIGHTS / 4],
+        }
+    }
+}
+
+/// View-projection matrices of the six cube faces around a point light, in
+/// +X, -X, +Y, -Y, +Z, -Z order. Each is a 90 degree frustum reaching to
+/// the light's range, so the face a point falls on is its dominant axis.
+pub fn point_shadow_face_view_proj(position: glam::Vec3, range: f32) -> [glam::Mat4; 6] {
+    use glam::Vec3;
+    let projection = glam::Mat4::perspective_rh(
+        std::f32::consts::FRAC_PI_2,
+        1.0,
+        POINT_SHADOW_NEAR,
+        range.max(POINT_SHADOW_NEAR * 2.0),
+    );
+    let faces = [
+        (Vec3::X, Vec3::NEG_Y),
+        (Vec3::NEG_X, Vec3::NEG_Y),
+        (Vec3::Y, Vec3::Z),
+        (Vec3::NEG_Y, Vec3::NEG_Z),
+        (Vec3::Z, Vec3::NEG_Y),
+        (Vec3::NEG_Z, Vec3::NEG_Y),
+    ];
+    faces.map(|(dir, up)| projection * glam::Mat4::look_at_rh(position, position + dir, up))
+}
 
+/// Lighting pass header. The point lights themselves live in a storage
+/// buffer (`LightClusters::light_buffer`), `light_count` of them.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct LightingUniforms {
@@ -223,8 +518,6 @@ pub struct LightingUniforms {
     pub _pad2: f32,
     // Shadow light VP matrix (offset 64)
     pub light_vp: [[f32; 4]; 4],
-    // Point lights (offset 128)
-    pub lights: [PointLightUniform; MAX_LIGHTS],
 }
 
 impl Default for LightingUniforms {
@@ -238,12 +531,6 @@ impl Default for LightingUniforms {
             dir_light_color: [1.0, 1.0, 1.0],
             _pad2: 0.0,
             light_vp: [[0.0; 4]; 4],
-            lights: [PointLightUniform {
-                position: [0.0; 3],
-                range: 0.0,
-                color: [0.0; 3],
-                intensity: 0.0,
-            }; MAX_LIGHTS],
         }
     }
 }
@@ -0,0 +1,82 @@
+//! Switching between the project's pipeline files at runtime.
+//!
+//! A project may keep several pipelines in `pipelines/` (`render_low.yaml`,
+//! `render_high.yaml`, ...). `pipeline.switch(name)` from Lua and the
+//! `switch_pipeline` socket command compile the named file and swap it in
+//! place of the running pipeline; if it fails to compile, the running one
+//! stays.
+
+use std::path::Path;
+
+/// Folder of the project's pipeline files.
+pub const PIPELINE_DIR: &str = "pipelines";
+
+/// Which pipeline file is compiled and which one was asked for.
+#[derive(Debug, Default)]
+pub struct PipelineSwitch {
+    /// Project-relative path of the compiled pipeline.
+    pub current: Option<String>,
+    /// File chosen by a switch, used in place of `--pipeline` from then on.
+    pub selected: Option<String>,
+    /// A switch requested by a script, made before the next frame.
+    pub pending: Option<String>,
+}
+
+/// The project-relative file of a pipeline: a bare name means
+/// `pipelines/<name>.yaml`, a path ending in `.yaml` is used as is.
+pub fn pipeline_file(name: &str) -> String {
+    if name.ends_with(".yaml") || name.ends_with(".yml") {
+        name.to_string()
+    } else {
+        format!("{}/{}.yaml", PIPELINE_DIR, name)
+    }
+}
+
+/// The name of a pipeline file (its file stem).
+pub fn pipeline_name(file: &str) -> String {
+    Path::new(file)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file.to_string())
+}
+
+/// Names of the pipelines in the project's `pipelines/` folder, sorted.
+pub fn list_pipelines(project_root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(project_root.join(PIPELINE_DIR)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_file_and_name() {
+        assert_eq!(pipeline_file("render_low"), "pipelines/render_low.yaml");
+        assert_eq!(pipeline_file("extra/cinematic.yaml"), "extra/cinematic.yaml");
+        assert_eq!(pipeline_name("pipelines/render_low.yaml"), "render_low");
+        assert_eq!(pipeline_name(&pipeline_file("render_high")), "render_high");
+    }
+
+    #[test]
+    fn test_list_pipelines() {
+        let root = std::env::temp_dir().join(format!("naive_pipeline_switch_{}", std::process::id()));
+        let dir = root.join(PIPELINE_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["render_low.yaml", "render.yaml", "render_high.yml", "notes.txt"] {
+            std::fs::write(dir.join(file), "passes: []\n").unwrap();
+        }
+        assert_eq!(list_pipelines(&root), ["render", "render_high", "render_low"]);
+        assert!(list_pipelines(&root.join("missing")).is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
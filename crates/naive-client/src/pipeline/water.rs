@@ -0,0 +1,366 @@
+//! Built-in water pass: draws every `Water` surface over the lit scene.
+//!
+//! The pass copies its color target before drawing, so surfaces can refract
+//! and reflect the scene behind them, and reads the depth input for the
+//! water's thickness. Depth is tested in the shader against that input, which
+//! keeps it bindable as a texture.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use glam::Mat4;
+
+use crate::components::{Hidden, Transform, Water, WaterReflection};
+use crate::environment::EnvironmentMaps;
+use crate::mesh::{MeshCache, Vertex3D};
+use crate::world::SceneWorld;
+
+use super::resource::GpuResource;
+
+/// Surfaces drawn per frame; further ones are skipped with a warning.
+pub const MAX_WATER_SURFACES: usize = 16;
+
+/// Stride of one surface's uniforms (dynamic offset alignment).
+const WATER_UNIFORM_STRIDE: u64 = 256;
+
+/// Per-surface uniforms, bound by dynamic offset.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WaterUniforms {
+    pub model: [[f32; 4]; 4],
+    /// RGB + depth fade.
+    pub shallow: [f32; 4],
+    /// RGB + shore fade.
+    pub deep: [f32; 4],
+    /// Scale, speed, strength, time.
+    pub waves: [f32; 4],
+    /// Refraction, reflection mode (0 none, 1 sky, 2 screen-space), unused x2.
+    pub params: [f32; 4],
+}
+
+impl WaterUniforms {
+    pub fn new(water: &Water, model: Mat4, time: f32) -> Self {
+        let mode = match water.reflection {
+            WaterReflection::None => 0.0,
+            WaterReflection::Sky => 1.0,
+            WaterReflection::ScreenSpace => 2.0,
+        };
+        let [sr, sg, sb] = water.shallow_color;
+        let [dr, dg, db] = water.deep_color;
+        Self {
+            model: model.to_cols_array_2d(),
+            shallow: [sr, sg, sb, water.depth_fade],
+            deep: [dr, dg, db, water.shore_fade],
+            waves: [water.wave_scale, water.wave_speed, water.wave_strength, time],
+            params: [water.refraction, mode, 0.0, 0.0],
+        }
+    }
+}
+
+/// Resources read by the water pass.
+pub struct WaterInputs {
+    /// Scene color, copied before drawing; also the pass's output.
+    pub color: String,
+    pub depth: String,
+}
+
+/// The water pass's bind groups and scene copy, rebuilt on resize.
+pub struct WaterPass {
+    pub inputs: WaterInputs,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub uniform_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    scene_copy: Option<wgpu::Texture>,
+    bind_group: Option<wgpu::BindGroup>,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    /// Clock for the wave animation.
+    start: instant::Instant,
+    warned_capacity: Cell<bool>,
+}
+
+impl WaterPass {
+    pub fn new(device: &wgpu::Device, inputs: WaterInputs) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Water Scene Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                super::compiler::cube_texture_layout_entry(3),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Water Uniform Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<WaterUniforms>() as u64),
+                },
+                count: None,
+            }],
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Water Uniforms"),
+            size: WATER_UNIFORM_STRIDE * MAX_WATER_SURFACES as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Water Uniform Bind Group"),
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &uniform_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<WaterUniforms>() as u64),
+                }),
+            }],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Water Scene Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            inputs,
+            bind_group_layout,
+            uniform_bind_group_layout,
+            sampler,
+            scene_copy: None,
+            bind_group: None,
+            uniform_buffer,
+            uniform_bind_group,
+            start: instant::Instant::now(),
+            warned_capacity: Cell::new(false),
+        }
+    }
+
+    /// Recreate the scene copy for the color input's size and rebind it with
+    /// the depth input and the environment maps.
+    pub fn rebuild(
+        &mut self,
+        device: &wgpu::Device,
+        resources: &HashMap<String, GpuResource>,
+        environment: &EnvironmentMaps,
+        environment_sampler: &wgpu::Sampler,
+        environment_buffer: &wgpu::Buffer,
+    ) {
+        let (Some(color), Some(depth)) = (resources.get(&self.inputs.color), resources.get(&self.inputs.depth))
+        else {
+            self.scene_copy = None;
+            self.bind_group = None;
+            return;
+        };
+        let copy = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Water Scene Copy"),
+            size: color.texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: color.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let copy_view = copy.create_view(&wgpu::TextureViewDescriptor::default());
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Water Scene Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&copy_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&depth.view) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&environment.sky) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(environment_sampler) },
+                wgpu::BindGroupEntry { binding: 5, resource: environment_buffer.as_entire_binding() },
+            ],
+        }));
+        self.scene_copy = Some(copy);
+    }
+
+    /// Copy the scene and draw the visible surfaces into the color input.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        pipeline: &wgpu::RenderPipeline,
+        pass_name: &str,
+        resources: &HashMap<String, GpuResource>,
+        camera_bind_group: &wgpu::BindGroup,
+        scene_world: &SceneWorld,
+        mesh_cache: &MeshCache,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites>,
+    ) {
+        let (Some(color), Some(copy), Some(bind_group)) =
+            (resources.get(&self.inputs.color), &self.scene_copy, &self.bind_group)
+        else {
+            return;
+        };
+
+        let time = self.start.elapsed().as_secs_f32();
+        let mut surfaces = Vec::new();
+        for (entity, (transform, water)) in scene_world.world.query::<(&Transform, &Water)>().iter() {
+            let Some(mesh) = water.mesh else { continue };
+            if scene_world.world.get::<&Hidden>(entity).is_ok() {
+                continue;
+            }
+            if surfaces.len() == MAX_WATER_SURFACES {
+                if !self.warned_capacity.replace(true) {
+                    tracing::warn!("More than {} water surfaces; drawing the first ones", MAX_WATER_SURFACES);
+                }
+                break;
+            }
+            let uniforms = WaterUniforms::new(water, transform.world_matrix, time);
+            queue.write_buffer(
+                &self.uniform_buffer,
+                surfaces.len() as u64 * WATER_UNIFORM_STRIDE,
+                bytemuck::cast_slice(&[uniforms]),
+            );
+            surfaces.push(mesh);
+        }
+        if surfaces.is_empty() {
+            return;
+        }
+
+        encoder.copy_texture_to_texture(
+            color.texture.as_image_copy(),
+            copy.as_image_copy(),
+            color.texture.size(),
+        );
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(pass_name),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, bind_group, &[]);
+        for (i, mesh) in surfaces.into_iter().enumerate() {
+            let gpu_mesh = mesh_cache.get(mesh);
+            render_pass.set_bind_group(2, &self.uniform_bind_group, &[(i as u64 * WATER_UNIFORM_STRIDE) as u32]);
+            render_pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice());
+            render_pass.set_index_buffer(gpu_mesh.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
+        }
+    }
+}
+
+/// Create the water surface pipeline writing `format`.
+pub fn create_water_pipeline(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    format: wgpu::TextureFormat,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+    water: &WaterPass,
+) -> wgpu::RenderPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Water Shader"),
+        source: wgpu::ShaderSource::Wgsl(wgsl_source.into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Water Pipeline Layout"),
+        bind_group_layouts: &[
+            camera_bind_group_layout,
+            &water.bind_group_layout,
+            &water.uniform_bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Water Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            buffers: &[Vertex3D::desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        // Seen from above and below
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_water_uniforms_fit_stride() {
+        assert!(std::mem::size_of::<WaterUniforms>() as u64 <= WATER_UNIFORM_STRIDE);
+        assert_eq!(std::mem::size_of::<WaterUniforms>() % 16, 0);
+    }
+}
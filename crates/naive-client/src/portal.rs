@@ -0,0 +1,624 @@
+//! Portals and mirrors: render the scene from a linked or reflected camera
+//! into an offscreen texture and composite it onto the portal's surface.
+//!
+//! A portal's surface is its mesh; the portal plane is the entity's local XZ
+//! plane and the surface is seen from the +Y side. A linked portal shows the
+//! world behind its target (seen from the target's +Y side), a portal without
+//! a target is a mirror. Portals visible inside a portal view are drawn with
+//! the next deeper view, up to `max_recursion` levels.
+//!
+//! Views are rendered with the forward shader before the frame's pipeline and
+//! composited by a render hook after the lighting pass, depth-tested against
+//! the G-buffer depth so geometry in front of the portal still occludes it.
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use glam::{Mat4, Vec3, Vec4};
+use wgpu::util::DeviceExt;
+
+use crate::camera::CameraState;
+use crate::components::{Hidden, MeshHandle, MeshRenderer, Portal, Transform};
+use crate::material::MaterialCache;
+use crate::mesh::{MeshCache, TextureResources, Vertex3D};
+use crate::pipeline::{CompiledPipeline, PassType};
+use crate::renderer::DrawUniformPool;
+use crate::world::SceneWorld;
+
+/// How far the clip plane is pushed past the exit surface, so the surface's
+/// own geometry doesn't show up in its view.
+const CLIP_PLANE_OFFSET: f32 = 0.001;
+
+const PORTAL_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// The portal plane of a world matrix: a point on it and its +Y normal.
+pub fn portal_plane(world: &Mat4) -> (Vec3, Vec3) {
+    let point = world.w_axis.truncate();
+    let normal = world.transform_vector3(Vec3::Y).normalize_or(Vec3::Y);
+    (point, normal)
+}
+
+/// Maps the space in front of `source` to the space behind `target`: a point
+/// entering the source surface comes out of the target. The half turn about
+/// local Z turns "into the source" (-Y) into "out of the target" (+Y).
+pub fn linked_portal_matrix(source: &Mat4, target: &Mat4) -> Mat4 {
+    *target * Mat4::from_rotation_z(PI) * source.inverse()
+}
+
+/// Reflection across the plane through `point` with unit `normal`.
+pub fn mirror_matrix(point: Vec3, normal: Vec3) -> Mat4 {
+    let n = normal;
+    let d = 2.0 * point.dot(n);
+    Mat4::from_cols(
+        Vec4::new(1.0 - 2.0 * n.x * n.x, -2.0 * n.x * n.y, -2.0 * n.x * n.z, 0.0),
+        Vec4::new(-2.0 * n.y * n.x, 1.0 - 2.0 * n.y * n.y, -2.0 * n.y * n.z, 0.0),
+        Vec4::new(-2.0 * n.z * n.x, -2.0 * n.z * n.y, 1.0 - 2.0 * n.z * n.z, 0.0),
+        (n * d).extend(1.0),
+    )
+}
+
+/// Replace the near plane of a [0, 1]-depth projection with `clip_plane`
+/// (view space, visible side positive), keeping the far plane as tight as
+/// possible (Lengyel's oblique frustum). The camera must be on the negative
+/// side of the plane.
+pub fn oblique_projection(projection: Mat4, clip_plane: Vec4) -> Mat4 {
+    let far_corner = projection.inverse()
+        * Vec4::new(clip_plane.x.signum(), clip_plane.y.signum(), 1.0, 1.0);
+    let w_row = projection.row(3);
+    let scale = w_row.dot(far_corner) / clip_plane.dot(far_corner);
+    let c = clip_plane * scale;
+    let mut oblique = projection;
+    oblique.x_axis.z = c.x;
+    oblique.y_axis.z = c.y;
+    oblique.z_axis.z = c.z;
+    oblique.w_axis.z = c.w;
+    oblique
+}
+
+/// World-space plane through `point` with `normal`, pushed along the normal
+/// by `offset`, as (n, -n·p).
+fn plane_vec4(point: Vec3, normal: Vec3, offset: f32) -> Vec4 {
+    normal.extend(-normal.dot(point) - offset)
+}
+
+/// The lighting pass of a compiled pipeline: the first fullscreen pass that
+/// isn't bloom, tonemap or FXAA and writes an offscreen target.
+pub fn composite_pass(compiled: &CompiledPipeline) -> Option<(&str, wgpu::TextureFormat)> {
+    compiled.pass_order.iter().map(|&i| &compiled.passes[i]).find_map(|pass| {
+        if pass.pass_type != PassType::Fullscreen
+            || ["bloom", "tonemap", "fxaa"].iter().any(|n| pass.name.contains(n))
+        {
+            return None;
+        }
+        let target = pass.color_targets.first()?;
+        let resource = compiled.resources.get(target)?;
+        Some((pass.name.as_str(), resource.format))
+    })
+}
+
+/// One recursion level of a portal: its camera and render targets.
+struct PortalLevel {
+    camera: CameraState,
+    color_view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+    /// Samples `color_view` when drawn onto the portal surface.
+    bind_group: wgpu::BindGroup,
+}
+
+struct PortalState {
+    size: (u32, u32),
+    model_buffer: wgpu::Buffer,
+    levels: Vec<PortalLevel>,
+    mesh: MeshHandle,
+    /// Level 1 was rendered this frame and should be composited.
+    visible: bool,
+}
+
+/// Renders and composites every `Portal` of the scene.
+pub struct PortalRenderer {
+    pass_name: String,
+    format: wgpu::TextureFormat,
+    scene_pipeline: wgpu::RenderPipeline,
+    mirrored_scene_pipeline: wgpu::RenderPipeline,
+    surface_pipeline: wgpu::RenderPipeline,
+    surface_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    portals: HashMap<hecs::Entity, PortalState>,
+    warned_depth: bool,
+}
+
+impl PortalRenderer {
+    /// `pass_name` and `format` are the lighting pass and its target format,
+    /// from [`composite_pass`].
+    pub fn new(
+        device: &wgpu::Device,
+        pass_name: &str,
+        format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        draw_pool: &DrawUniformPool,
+        texture_resources: Option<&TextureResources>,
+    ) -> Self {
+        let forward_wgsl = crate::shader::get_mesh_forward_wgsl();
+        let scene_pipeline_for = |front_face| {
+            crate::renderer::create_forward_pipeline_oriented(
+                device,
+                &forward_wgsl,
+                format,
+                camera_bind_group_layout,
+                &draw_pool.bind_group_layout,
+                texture_resources.map(|t| &t.bind_group_layout),
+                front_face,
+            )
+        };
+        let scene_pipeline = scene_pipeline_for(wgpu::FrontFace::Ccw);
+        let mirrored_scene_pipeline = scene_pipeline_for(wgpu::FrontFace::Cw);
+
+        let surface_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Portal Surface Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Portal Surface Shader"),
+            source: wgpu::ShaderSource::Wgsl(PORTAL_SURFACE_WGSL.into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Portal Surface Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &surface_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let surface_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Portal Surface Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex3D::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // Mirrored views see the surface with flipped winding.
+                cull_mode: None,
+                ..Default::default()
+            },
+            // Read-only depth: the surface was already written by the scene
+            // geometry, so equal depth passes and nearer geometry occludes it.
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: PORTAL_DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Portal Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            pass_name: pass_name.to_string(),
+            format,
+            scene_pipeline,
+            mirrored_scene_pipeline,
+            surface_pipeline,
+            surface_bind_group_layout,
+            sampler,
+            portals: HashMap::new(),
+            warned_depth: false,
+        }
+    }
+
+    /// Whether this renderer was built for the given lighting pass and format.
+    pub fn matches(&self, pass_name: &str, format: wgpu::TextureFormat) -> bool {
+        self.pass_name == pass_name && self.format == format
+    }
+
+    pub fn pass_name(&self) -> &str {
+        &self.pass_name
+    }
+
+    /// Render every visible portal's views, deepest level first. Uses the
+    /// frame's draw uniforms, which the pipeline uploads before this submit.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_views(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        scene_world: &SceneWorld,
+        camera_state: &CameraState,
+        draw_pool: &DrawUniformPool,
+        mesh_cache: &MeshCache,
+        material_cache: &MaterialCache,
+        texture_resources: Option<&TextureResources>,
+    ) {
+        let main = &camera_state.uniform;
+        let view = Mat4::from_cols_array_2d(&main.view);
+        // Unjitter: portal views are not accumulated by TAA.
+        let mut projection = Mat4::from_cols_array_2d(&main.projection);
+        projection.z_axis.x -= main.jitter[0];
+        projection.z_axis.y -= main.jitter[1];
+        let camera_position = Vec3::from_array(main.position);
+        let viewport = (main.viewport_size[0] as u32, main.viewport_size[1] as u32);
+
+        let mut present = Vec::new();
+        for (entity, (transform, portal, mesh_renderer)) in scene_world
+            .world
+            .query::<(&Transform, &Portal, &MeshRenderer)>()
+            .iter()
+        {
+            if scene_world.world.get::<&Hidden>(entity).is_ok() {
+                continue;
+            }
+            present.push(entity);
+
+            let source = transform.world_matrix;
+            let (mirror, portal_matrix, exit_plane) = match &portal.target {
+                None => {
+                    let (point, normal) = portal_plane(&source);
+                    (true, mirror_matrix(point, normal), plane_vec4(point, normal, CLIP_PLANE_OFFSET))
+                }
+                Some(id) => {
+                    let Some(target) = scene_world
+                        .entity_registry
+                        .get(id)
+                        .and_then(|&e| scene_world.world.get::<&Transform>(e).ok())
+                        .map(|t| t.world_matrix)
+                    else {
+                        continue;
+                    };
+                    let (point, normal) = portal_plane(&target);
+                    (
+                        false,
+                        linked_portal_matrix(&source, &target),
+                        plane_vec4(point, normal, CLIP_PLANE_OFFSET),
+                    )
+                }
+            };
+
+            let depth = if mirror { 1 } else { portal.max_recursion.max(1) };
+            let size = (
+                ((viewport.0 as f32 * portal.resolution) as u32).max(1),
+                ((viewport.1 as f32 * portal.resolution) as u32).max(1),
+            );
+            let state = self.portals.entry(entity).or_insert_with(|| PortalState {
+                size: (0, 0),
+                model_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Portal Model Buffer"),
+                    contents: bytemuck::cast_slice(&Mat4::IDENTITY.to_cols_array()),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                }),
+                levels: Vec::new(),
+                mesh: mesh_renderer.mesh_handle,
+                visible: false,
+            });
+            state.mesh = mesh_renderer.mesh_handle;
+            queue.write_buffer(&state.model_buffer, 0, bytemuck::cast_slice(&source.to_cols_array()));
+            if state.size != size || state.levels.len() != depth as usize {
+                state.levels = (0..depth)
+                    .map(|_| {
+                        create_level(
+                            device,
+                            self.format,
+                            size,
+                            &self.surface_bind_group_layout,
+                            &self.sampler,
+                            &state.model_buffer,
+                        )
+                    })
+                    .collect();
+                state.size = size;
+            }
+
+            // Surfaces are one-sided: nothing to show from behind.
+            let (source_point, source_normal) = portal_plane(&source);
+            state.visible = source_normal.dot(camera_position - source_point) > 0.0;
+            if !state.visible {
+                continue;
+            }
+
+            let pipeline = if mirror { &self.mirrored_scene_pipeline } else { &self.scene_pipeline };
+            let inverse_portal = portal_matrix.inverse();
+            for level in (1..=depth as usize).rev() {
+                let mut level_view = view;
+                let mut level_position = camera_position;
+                for _ in 0..level {
+                    level_view *= inverse_portal;
+                    level_position = portal_matrix.transform_point3(level_position);
+                }
+                let clip_plane = level_view.inverse().transpose() * exit_plane;
+                // The exit plane must be in front of the virtual camera to clip with it.
+                let level_projection = if clip_plane.w < 0.0 {
+                    oblique_projection(projection, clip_plane)
+                } else {
+                    projection
+                };
+
+                let current = &mut state.levels[level - 1];
+                current.camera.set_matrices(
+                    queue,
+                    level_view,
+                    level_projection,
+                    level_position,
+                    main.near_plane,
+                    main.far_plane,
+                    size.0,
+                    size.1,
+                );
+
+                let (current, deeper) = state.levels.split_at(level);
+                let current = &current[level - 1];
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Portal View Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &current.color_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: &current.depth_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: wgpu::StoreOp::Store,
+                        }),
+                        stencil_ops: None,
+                    }),
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, &current.camera.bind_group, &[]);
+                crate::renderer::draw_scene_meshes(
+                    &mut render_pass,
+                    draw_pool,
+                    mesh_cache,
+                    material_cache,
+                    texture_resources,
+                );
+
+                // The portal seen through itself shows the next level down.
+                if let Some(next) = deeper.first() {
+                    render_pass.set_pipeline(&self.surface_pipeline);
+                    render_pass.set_bind_group(0, &current.camera.bind_group, &[]);
+                    render_pass.set_bind_group(1, &next.bind_group, &[]);
+                    draw_mesh(&mut render_pass, mesh_cache, state.mesh);
+                }
+            }
+        }
+
+        self.portals.retain(|entity, _| present.contains(entity));
+    }
+
+    /// Draw each visible portal's first level onto its surface in the main
+    /// view. Called from a hook after the lighting pass.
+    pub fn composite(&mut self, ctx: &mut crate::pipeline::RenderHookContext<'_>) {
+        if self.portals.values().all(|p| !p.visible) {
+            return;
+        }
+        let Some(target) = ctx.pass.color_targets.first().and_then(|t| ctx.view(t)) else {
+            return;
+        };
+        let depth = match ctx.compiled.resources.get("gbuffer_depth") {
+            Some(depth) if depth.format == PORTAL_DEPTH_FORMAT => &depth.view,
+            _ => {
+                if !self.warned_depth {
+                    tracing::warn!("Portals need a depth32f 'gbuffer_depth' resource; not compositing");
+                    self.warned_depth = true;
+                }
+                return;
+            }
+        };
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Portal Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth,
+                depth_ops: None,
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.surface_pipeline);
+        render_pass.set_bind_group(0, &ctx.camera_state.bind_group, &[]);
+        for state in self.portals.values().filter(|p| p.visible) {
+            render_pass.set_bind_group(1, &state.levels[0].bind_group, &[]);
+            draw_mesh(&mut render_pass, ctx.mesh_cache, state.mesh);
+        }
+    }
+}
+
+fn draw_mesh(render_pass: &mut wgpu::RenderPass<'_>, mesh_cache: &MeshCache, mesh: MeshHandle) {
+    let gpu_mesh = mesh_cache.get(mesh);
+    render_pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice());
+    render_pass.set_index_buffer(gpu_mesh.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+    render_pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
+}
+
+fn create_level(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    model_buffer: &wgpu::Buffer,
+) -> PortalLevel {
+    let extent = wgpu::Extent3d { width: size.0, height: size.1, depth_or_array_layers: 1 };
+    let color = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Portal Color"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let depth = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Portal Depth"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: PORTAL_DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Portal Surface Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&color_view),
+            },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            wgpu::BindGroupEntry { binding: 2, resource: model_buffer.as_entire_binding() },
+        ],
+    });
+    PortalLevel { camera: CameraState::new(device), color_view, depth_view, bind_group }
+}
+
+/// Draws a portal surface with the view behind it. The view texture covers
+/// the same screen as the camera rendering the surface, so it is sampled at
+/// the fragment's screen position.
+const PORTAL_SURFACE_WGSL: &str = r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+};
+
+struct PortalUniforms {
+    model: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var portal_view: texture_2d<f32>;
+@group(1) @binding(1) var portal_sampler: sampler;
+@group(1) @binding(2) var<uniform> portal: PortalUniforms;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return camera.view_projection * portal.model * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = frag_coord.xy / camera.viewport_size;
+    return vec4<f32>(textureSampleLevel(portal_view, portal_sampler, uv, 0.0).rgb, 1.0);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+
+    fn approx(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn test_linked_portal_maps_source_to_target() {
+        let source = Mat4::from_translation(Vec3::new(0.0, 0.0, -5.0));
+        let target = Mat4::from_rotation_translation(
+            Quat::from_rotation_x(PI / 2.0),
+            Vec3::new(10.0, 2.0, 0.0),
+        );
+        let m = linked_portal_matrix(&source, &target);
+        assert!(approx(m.transform_point3(Vec3::new(0.0, 0.0, -5.0)), Vec3::new(10.0, 2.0, 0.0)));
+
+        // Looking into the source (-Y) means looking out of the target (+Y in its frame).
+        let (_, target_normal) = portal_plane(&target);
+        assert!(approx(m.transform_vector3(Vec3::NEG_Y), target_normal));
+    }
+
+    #[test]
+    fn test_mirror_matrix_reflects_across_plane() {
+        let m = mirror_matrix(Vec3::new(0.0, 0.0, 2.0), Vec3::Z);
+        assert!(approx(m.transform_point3(Vec3::new(1.0, 1.0, 5.0)), Vec3::new(1.0, 1.0, -1.0)));
+        assert!(approx(m.transform_point3(Vec3::new(3.0, 0.0, 2.0)), Vec3::new(3.0, 0.0, 2.0)));
+        assert!(m.determinant() < 0.0);
+    }
+
+    #[test]
+    fn test_oblique_projection_clips_at_plane() {
+        let projection = Mat4::perspective_rh(1.0, 1.5, 0.1, 100.0);
+        // View-space plane z = -4 facing away from the camera (visible side z < -4).
+        let plane = Vec4::new(0.0, 0.0, -1.0, -4.0);
+        let oblique = oblique_projection(projection, plane);
+
+        let depth = |z: f32| {
+            let clip = oblique * Vec4::new(0.3, -0.2, z, 1.0);
+            clip.z / clip.w
+        };
+        assert!(depth(-4.0).abs() < 1e-4);
+        assert!(depth(-3.0) < 0.0);
+        assert!(depth(-10.0) > 0.0 && depth(-10.0) <= 1.0);
+    }
+}
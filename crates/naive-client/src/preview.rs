@@ -0,0 +1,75 @@
+//! `naive preview` — render one frame of a scene offscreen and save it as a PNG.
+//!
+//! Loads the scene without a window, compiles the project's render pipeline
+//! against an offscreen color target, renders a single frame from the chosen
+//! camera and reads the result back. Scripts and physics are not run, so the
+//! thumbnail shows the scene exactly as authored in YAML.
+
+use std::path::Path;
+
+use crate::components::{Camera, CameraRole, Transform};
+use crate::headless::HeadlessRenderer;
+use crate::world::SceneWorld;
+
+/// Options for a single preview render.
+#[derive(Debug, Clone)]
+pub struct PreviewOptions {
+    /// Scene YAML, relative to the project root.
+    pub scene: String,
+    /// Output PNG path.
+    pub out: std::path::PathBuf,
+    /// Entity ID of the camera to render from (defaults to the main camera).
+    pub camera: Option<String>,
+    /// Render pipeline YAML, relative to the project root (defaults to pipelines/render.yaml).
+    pub pipeline: Option<String>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Render `options.scene` offscreen and write the frame to `options.out`.
+pub fn render_preview(project_root: &Path, options: &PreviewOptions) -> Result<(), String> {
+    let scene_path = project_root.join(&options.scene);
+    let scene = crate::scene::load_scene(&scene_path)
+        .map_err(|e| format!("Failed to load scene '{}': {}", scene_path.display(), e))?;
+
+    let mut renderer = HeadlessRenderer::new(project_root, options.pipeline.as_deref(), options.width, options.height)?;
+
+    let mut scene_world = SceneWorld::new();
+    renderer.spawn_scene(&mut scene_world, &scene, None);
+
+    let (camera, cam_transform) = find_camera(&scene_world, options.camera.as_deref())?;
+    renderer.render(&scene_world, &camera, &cam_transform)?;
+    renderer.save_png(&options.out)?;
+
+    tracing::info!("Preview written to {:?} ({}x{})", options.out, renderer.width, renderer.height);
+    Ok(())
+}
+
+/// Find the camera to render from: the entity named `camera_id`, or the main camera.
+fn find_camera(scene_world: &SceneWorld, camera_id: Option<&str>) -> Result<(Camera, Transform), String> {
+    if let Some(id) = camera_id {
+        let entity = *scene_world
+            .entity_registry
+            .get(id)
+            .ok_or_else(|| format!("Camera entity '{}' not found in scene", id))?;
+        let transform = scene_world
+            .world
+            .get::<&Transform>(entity)
+            .map(|t| (*t).clone())
+            .map_err(|_| format!("Entity '{}' has no transform", id))?;
+        let camera = scene_world
+            .world
+            .get::<&Camera>(entity)
+            .map(|c| (*c).clone())
+            .unwrap_or_default();
+        return Ok((camera, transform));
+    }
+
+    scene_world
+        .world
+        .query::<(&Transform, &Camera)>()
+        .iter()
+        .find(|(_, (_, camera))| camera.role == CameraRole::Main)
+        .map(|(_, (transform, camera))| (camera.clone(), transform.clone()))
+        .ok_or_else(|| "Scene has no main camera; pass --camera <entity_id>".to_string())
+}
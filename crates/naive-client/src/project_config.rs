@@ -2,8 +2,15 @@
 //!
 //! Reads a game project's `naive.yaml` and converts it to `CliArgs`
 //! via the bridge pattern — zero changes to engine.rs required.
+//! A running engine also reloads the file on save and applies the settings
+//! `diff_config` marks as live.
+//!
+//! Top-level `windows:`, `macos:`, `linux:` and `web:` sections hold
+//! per-platform overrides of any other setting; the one for the platform
+//! being run (or built) is merged over the rest at load.
 
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::cli::{CliArgs, OutputMode};
@@ -22,6 +29,86 @@ pub struct NaiveConfig {
     pub build: BuildConfig,
     #[serde(default)]
     pub dev_log: DevLogConfig,
+    #[serde(default)]
+    pub scripting: ScriptingConfig,
+    #[serde(default)]
+    pub window: WindowConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// How the screen is shared between `player<N>` cameras.
+    #[serde(default)]
+    pub split_screen: crate::split_screen::SplitScreenConfig,
+    /// Render quality preset (default: high).
+    pub quality: Option<QualityPreset>,
+    /// Frame-rate driven render scale, shadow and particle levels.
+    #[serde(default)]
+    pub auto_quality: crate::auto_quality::AutoQualityConfig,
+    /// Overrides of the render pipeline's feature flags (`settings.flags`).
+    #[serde(default)]
+    pub render_settings: HashMap<String, bool>,
+    #[serde(default)]
+    pub watch: WatchConfig,
+    /// GPU backend, adapter, surface format and present mode.
+    #[serde(default)]
+    pub graphics: crate::gpu_options::GraphicsOptions,
+    /// Audio output device and buffer size.
+    #[serde(default)]
+    pub audio: crate::audio::AudioSettings,
+    /// Steamworks app id (used by builds with the `steam` feature).
+    #[serde(default)]
+    pub steam: crate::steam::SteamConfig,
+    /// Opt-in playtest analytics from `analytics.track`.
+    #[serde(default)]
+    pub analytics: crate::analytics::AnalyticsConfig,
+    /// Scene lint rules applied by `naive check`.
+    #[serde(default)]
+    pub lint: crate::check::LintConfig,
+    /// Values for the scripts' read-only `config` table.
+    #[serde(default)]
+    pub config: serde_yaml::Mapping,
+    #[serde(default)]
+    pub input: InputConfig,
+    /// Snapshots of unsaved live scene edits.
+    #[serde(default)]
+    pub autosave: AutosaveConfig,
+}
+
+/// Input settings (`input:` in naive.yaml).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct InputConfig {
+    /// Bindings file, relative to the project root (default:
+    /// `input/bindings.yaml`).
+    pub bindings: Option<String>,
+}
+
+impl InputConfig {
+    pub fn bindings_path(&self) -> &str {
+        self.bindings.as_deref().unwrap_or(crate::input::BINDINGS_PATH)
+    }
+}
+
+/// Auto-save settings (`autosave:` in naive.yaml).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AutosaveConfig {
+    /// Snapshot the scene to `.naive/autosave/` while socket edits are unsaved.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Seconds between an unsaved edit and the snapshot that covers it.
+    #[serde(default = "default_autosave_interval")]
+    pub interval_secs: u64,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: default_autosave_interval(),
+        }
+    }
+}
+
+fn default_autosave_interval() -> u64 {
+    60
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -33,6 +120,118 @@ pub struct TestConfig {
 #[derive(Debug, Default, Deserialize)]
 pub struct BuildConfig {
     pub targets: Option<Vec<String>>,
+    /// How scripts ship (`--bytecode` on the command line forces bytecode).
+    #[serde(default)]
+    pub scripts: ScriptOutput,
+    /// Keep debug info in bytecode, so script errors still name the file
+    /// and line.
+    #[serde(default)]
+    pub keep_debug_info: bool,
+}
+
+/// Script format of a build (`build.scripts`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptOutput {
+    /// Copy the `.lua` files as they are.
+    #[default]
+    Source,
+    /// Compile them into one `logic.bundle` of Lua bytecode.
+    Bytecode,
+}
+
+/// Lua VM limits (`scripting:` in naive.yaml). Unset limits use the defaults
+/// in `script_memory`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+pub struct ScriptingConfig {
+    /// VM memory cap in megabytes (0 = unlimited).
+    pub memory_limit_mb: Option<u64>,
+    /// Milliseconds per frame spent on incremental garbage collection.
+    pub gc_budget_ms: Option<f32>,
+    /// Lua API level the scripts were written against; functions deprecated
+    /// after it keep working behind shims (default: 1, every shim).
+    pub api_level: Option<u32>,
+}
+
+/// Window settings (`window:` in naive.yaml).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct WindowConfig {
+    pub title: Option<String>,
+    /// What the game does while rendering is suspended.
+    #[serde(default)]
+    pub background: BackgroundMode,
+    /// Also suspend rendering when the window loses focus (it always is
+    /// while minimized or hidden).
+    #[serde(default)]
+    pub suspend_unfocused: bool,
+}
+
+/// UI overlay settings (`ui:` in naive.yaml).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct UiConfig {
+    /// How text is drawn: `bitmap` (default), `sdf` or `subpixel`.
+    #[serde(default)]
+    pub text: crate::font::TextRendering,
+}
+
+/// Behavior while the window is suspended (`window.background`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundMode {
+    /// Stop the simulation until the window is back.
+    #[default]
+    Pause,
+    /// Keep simulating (scripts, physics, audio) without rendering.
+    Simulate,
+}
+
+/// File watcher settings (`watch:` in naive.yaml).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WatchConfig {
+    /// Hot-reload assets on save. naive.yaml and input bindings are always
+    /// watched, so this can be switched back on without a restart.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Wait until no file has changed for this long before reloading, so an
+    /// editor that saves in several writes triggers one reload.
+    #[serde(default)]
+    pub debounce_ms: u64,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_ms: 0,
+        }
+    }
+}
+
+/// Render quality preset (`quality:` in naive.yaml), ordered low to high.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityPreset {
+    /// No bloom, emission or torch flicker; a quarter of the foliage.
+    Low,
+    /// Bloom and emission, no torch flicker; half of the foliage.
+    Medium,
+    #[default]
+    High,
+}
+
+impl QualityPreset {
+    /// Set the render toggles this preset controls.
+    pub fn apply(self, debug: &mut crate::pipeline::RenderDebugState) {
+        debug.quality = self;
+        debug.bloom_enabled = self != QualityPreset::Low;
+        debug.emission_enabled = self != QualityPreset::Low;
+        debug.torch_flicker_enabled = self == QualityPreset::High;
+        debug.foliage_density = match self {
+            QualityPreset::Low => 0.25,
+            QualityPreset::Medium => 0.5,
+            QualityPreset::High => 1.0,
+        };
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +255,85 @@ fn default_engine() -> String {
     "naive-runtime".to_string()
 }
 
+fn default_true() -> bool {
+    true
+}
+
+/// Settings that differ between two loads of naive.yaml, split by whether a
+/// running engine can apply them.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigChanges {
+    /// Applied without a restart.
+    pub live: Vec<&'static str>,
+    /// Only take effect on the next launch.
+    pub restart: Vec<&'static str>,
+}
+
+/// Compare two configs for hot-reload. Settings only read by other `naive`
+/// subcommands (test, build, lint, dev_log) are picked up on their next run and are
+/// not reported.
+pub fn diff_config(old: &NaiveConfig, new: &NaiveConfig) -> ConfigChanges {
+    let mut changes = ConfigChanges::default();
+    if old.window.title != new.window.title {
+        changes.live.push("window.title");
+    }
+    if old.window.background != new.window.background || old.window.suspend_unfocused != new.window.suspend_unfocused {
+        changes.live.push("window.background");
+    }
+    if old.quality != new.quality {
+        changes.live.push("quality");
+    }
+    if old.auto_quality != new.auto_quality {
+        changes.live.push("auto_quality");
+    }
+    if old.render_settings != new.render_settings {
+        changes.live.push("render_settings");
+    }
+    if old.watch != new.watch {
+        changes.live.push("watch");
+    }
+    if old.audio != new.audio {
+        changes.live.push("audio");
+    }
+    if old.config != new.config {
+        changes.live.push("config");
+    }
+    if old.input != new.input {
+        changes.live.push("input");
+    }
+    if old.autosave != new.autosave {
+        changes.live.push("autosave");
+    }
+    if old.split_screen != new.split_screen {
+        changes.live.push("split_screen");
+    }
+    if old.default_scene != new.default_scene {
+        changes.restart.push("default_scene");
+    }
+    if old.default_pipeline != new.default_pipeline {
+        changes.restart.push("default_pipeline");
+    }
+    if old.scripting != new.scripting {
+        changes.restart.push("scripting");
+    }
+    if old.ui != new.ui {
+        changes.restart.push("ui");
+    }
+    if old.graphics != new.graphics {
+        changes.restart.push("graphics");
+    }
+    if old.engine != new.engine {
+        changes.restart.push("engine");
+    }
+    if old.steam != new.steam {
+        changes.restart.push("steam");
+    }
+    if old.analytics != new.analytics {
+        changes.restart.push("analytics");
+    }
+    changes
+}
+
 #[derive(Debug)]
 pub enum ConfigError {
     NotFound,
@@ -87,11 +365,67 @@ pub fn find_config(start_dir: &Path) -> Option<PathBuf> {
     }
 }
 
-/// Load and parse a `naive.yaml` file.
+/// Platforms that can have an overlay section in naive.yaml.
+pub const PLATFORMS: &[&str] = &["windows", "macos", "linux", "web"];
+
+/// The platform this engine runs on, as named in naive.yaml.
+pub fn current_platform() -> &'static str {
+    if cfg!(target_arch = "wasm32") {
+        "web"
+    } else if cfg!(target_os = "macos") {
+        "macos"
+    } else if cfg!(target_os = "windows") {
+        "windows"
+    } else {
+        "linux"
+    }
+}
+
+/// Load and parse a `naive.yaml` file for the current platform.
 pub fn load_config(path: &Path) -> Result<NaiveConfig, ConfigError> {
+    load_config_for(path, current_platform())
+}
+
+/// Load and parse a `naive.yaml` file with `platform`'s overlay applied.
+pub fn load_config_for(path: &Path, platform: &str) -> Result<NaiveConfig, ConfigError> {
     let contents = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
-    let config: NaiveConfig = serde_yaml::from_str(&contents).map_err(ConfigError::Parse)?;
-    Ok(config)
+    parse_config(&contents, platform).map_err(ConfigError::Parse)
+}
+
+/// Parse naive.yaml contents, merging `platform`'s section over the base
+/// settings and dropping the other platforms' sections.
+pub fn parse_config(contents: &str, platform: &str) -> Result<NaiveConfig, serde_yaml::Error> {
+    let mut value: serde_yaml::Value = serde_yaml::from_str(contents)?;
+    if let serde_yaml::Value::Mapping(root) = &mut value {
+        let mut overlay = None;
+        for name in PLATFORMS {
+            let section = root.remove(*name);
+            if *name == platform {
+                overlay = section;
+            }
+        }
+        if let Some(overlay) = overlay {
+            merge_yaml(&mut value, overlay);
+        }
+    }
+    serde_yaml::from_value(value)
+}
+
+/// Merge `overlay` into `base`: mappings key by key, anything else replaced.
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
 }
 
 /// Convert a NaiveConfig into CliArgs for the engine (bridge pattern).
@@ -104,7 +438,20 @@ pub fn to_cli_args(config: &NaiveConfig, project_root: &Path) -> CliArgs {
         project: project_root.to_string_lossy().to_string(),
         socket: "/tmp/naive-runtime.sock".to_string(),
         hud: false,
+        headless: false,
+        frames: crate::cli::DEFAULT_HEADLESS_FRAMES,
+        screenshot_after: None,
+        screenshot_path: crate::cli::DEFAULT_SCREENSHOT_PATH.to_string(),
+        lua_memory_limit: config.scripting.memory_limit_mb,
+        lua_gc_budget: config.scripting.gc_budget_ms,
+        force_lod0: false,
+        no_culling: false,
+        config: Vec::new(),
+        settings: Vec::new(),
+        graphics: config.graphics.clone(),
         editor_mode: false,
+        recover: false,
+        color_audit: false,
     }
 }
 
@@ -148,3 +495,67 @@ pub fn discover_test_files(config: &NaiveConfig, project_root: &Path) -> Vec<Pat
     files.sort();
     files
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> NaiveConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_diff_config_splits_live_and_restart_settings() {
+        let old = parse("name: g\nversion: '0.1'\ndefault_scene: scenes/a.yaml\n");
+        assert!(old.watch.enabled);
+        assert_eq!(diff_config(&old, &old), ConfigChanges::default());
+
+        let new = parse(
+            "name: g\nversion: '0.1'\ndefault_scene: scenes/b.yaml\nquality: low\n\
+             window:\n  title: My Game\nwatch:\n  debounce_ms: 200\nscripting:\n  memory_limit_mb: 64\n\
+             audio:\n  buffer_size: 256\n",
+        );
+        let changes = diff_config(&old, &new);
+        assert_eq!(changes.live, vec!["window.title", "quality", "watch", "audio"]);
+        assert_eq!(changes.restart, vec!["default_scene", "scripting"]);
+
+        let mut debug = crate::pipeline::RenderDebugState::default();
+        new.quality.unwrap().apply(&mut debug);
+        assert!(!debug.bloom_enabled && !debug.torch_flicker_enabled);
+        assert_eq!(debug.foliage_density, 0.25);
+    }
+
+    #[test]
+    fn test_platform_overlay_merges_over_base() {
+        let yaml = "\
+name: g
+version: '0.1'
+default_pipeline: pipelines/render.yaml
+quality: high
+window:
+  title: Game
+  suspend_unfocused: true
+web:
+  default_pipeline: pipelines/web.yaml
+  quality: low
+  window:
+    title: Web Game
+  input:
+    bindings: input/web.yaml
+windows:
+  quality: medium
+";
+        let web = parse_config(yaml, "web").unwrap();
+        assert_eq!(web.default_pipeline.as_deref(), Some("pipelines/web.yaml"));
+        assert_eq!(web.quality, Some(QualityPreset::Low));
+        assert_eq!(web.window.title.as_deref(), Some("Web Game"));
+        assert!(web.window.suspend_unfocused);
+        assert_eq!(web.input.bindings_path(), "input/web.yaml");
+
+        let linux = parse_config(yaml, "linux").unwrap();
+        assert_eq!(linux.default_pipeline.as_deref(), Some("pipelines/render.yaml"));
+        assert_eq!(linux.quality, Some(QualityPreset::High));
+        assert_eq!(linux.input.bindings_path(), crate::input::BINDINGS_PATH);
+        assert_eq!(parse_config(yaml, "windows").unwrap().quality, Some(QualityPreset::Medium));
+    }
+}
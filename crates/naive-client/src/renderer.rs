@@ -4,7 +4,7 @@ use wgpu::util::DeviceExt;
 use winit::window::Window;
 
 use crate::camera::CameraState;
-use crate::components::{Hidden, MeshRenderer, Transform};
+use crate::components::{Hidden, MaterialHandle, MeshHandle, MeshRenderer, Transform};
 use crate::material::MaterialCache;
 use crate::mesh::{MeshCache, Vertex3D};
 use crate::world::SceneWorld;
@@ -67,14 +67,33 @@ pub struct DrawUniforms {
 pub const DRAW_UNIFORM_SIZE: u64 = 256;
 const INITIAL_CAPACITY: u32 = 256;
 const GROWTH_FACTOR: u32 = 2;
+/// Frames the draw uniform ring holds. Each frame writes its own region, so
+/// the uniforms of the frames before it are left alone while the GPU may
+/// still be drawing them.
+pub const DRAW_RING_FRAMES: u32 = 3;
 
-/// Manages per-entity draw uniforms with dynamic offsets.
-/// Buffer grows dynamically when more entities are needed.
+/// Per-draw uniforms, bound with dynamic offsets into a ring of
+/// `DRAW_RING_FRAMES` regions of `capacity` slots. `begin_frame` moves on to
+/// the next region and is the only place the ring grows, so a frame never
+/// sees its buffer replaced halfway. Draws past the capacity are dropped and
+/// counted rather than written out of bounds.
+///
+/// Slots are filled in a CPU staging copy of the region and `upload` sends
+/// them in one write, instead of one driver call per draw.
 pub struct DrawUniformPool {
     pub buffer: wgpu::Buffer,
     pub bind_group_layout: wgpu::BindGroupLayout,
     pub bind_group: wgpu::BindGroup,
+    /// Draw slots per frame.
     pub capacity: u32,
+    /// Ring region of the current frame.
+    frame: u32,
+    /// Draws dropped this frame for lack of a slot.
+    overflow: std::cell::Cell<u32>,
+    /// The frame's visible meshes, in draw order.
+    draws: Vec<FrameDraw>,
+    /// This frame's slots, uploaded together by `upload`.
+    staging: std::cell::RefCell<Vec<DrawUniforms>>,
 }
 
 impl DrawUniformPool {
@@ -95,83 +114,190 @@ impl DrawUniformPool {
             });
 
         let capacity = INITIAL_CAPACITY;
-        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Draw Uniform Buffer"),
-            size: DRAW_UNIFORM_SIZE * capacity as u64,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Draw Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                    buffer: &buffer,
-                    offset: 0,
-                    size: wgpu::BufferSize::new(DRAW_UNIFORM_SIZE),
-                }),
-            }],
-        });
+        let (buffer, bind_group) = create_draw_ring(device, &bind_group_layout, capacity);
 
         DrawUniformPool {
             buffer,
             bind_group_layout,
             bind_group,
             capacity,
+            frame: 0,
+            overflow: std::cell::Cell::new(0),
+            draws: Vec::new(),
+            staging: Default::default(),
         }
     }
 
-    /// Ensure the buffer can hold at least `needed` entities.
-    /// Returns true if the buffer was rebuilt (bind_group changed).
-    pub fn ensure_capacity(&mut self, device: &wgpu::Device, needed: u32) -> bool {
+    /// Start a frame that draws `draws` (see `collect_frame_draws`): move
+    /// to the next ring region, growing the ring first if it is too small.
+    /// Returns true if the ring was rebuilt (bind_group changed).
+    pub fn begin_frame(&mut self, device: &wgpu::Device, draws: Vec<FrameDraw>) -> bool {
+        let dropped = self.overflow.replace(0);
+        if dropped > 0 {
+            tracing::warn!("DrawUniformPool: {} draws didn't fit last frame ({} slots)", dropped, self.capacity);
+        }
+        self.frame = (self.frame + 1) % DRAW_RING_FRAMES;
+        self.draws = draws;
+        let needed = self.slots();
+        self.staging.borrow_mut().resize(needed as usize, bytemuck::Zeroable::zeroed());
         if needed <= self.capacity {
             return false;
         }
 
-        let mut new_capacity = self.capacity;
-        while new_capacity < needed {
-            new_capacity *= GROWTH_FACTOR;
-        }
-
+        let new_capacity = grown_capacity(self.capacity, needed);
         tracing::info!(
-            "Growing DrawUniformPool: {} -> {} entities",
+            "Growing DrawUniformPool: {} -> {} draws per frame",
             self.capacity,
             new_capacity
         );
+        let (buffer, bind_group) = create_draw_ring(device, &self.bind_group_layout, new_capacity);
+        self.buffer = buffer;
+        self.bind_group = bind_group;
+        self.capacity = new_capacity;
+        true
+    }
 
-        self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Draw Uniform Buffer"),
-            size: DRAW_UNIFORM_SIZE * new_capacity as u64,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+    /// The frame's visible meshes. Every loop that uploads or draws mesh
+    /// uniforms walks this list rather than querying the world again.
+    pub fn draws(&self) -> &[FrameDraw] {
+        &self.draws
+    }
 
-        self.bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Draw Bind Group"),
-            layout: &self.bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                    buffer: &self.buffer,
-                    offset: 0,
-                    size: wgpu::BufferSize::new(DRAW_UNIFORM_SIZE),
-                }),
-            }],
-        });
+    /// Draw slots the frame uses.
+    pub fn slots(&self) -> u32 {
+        self.draws.last().map_or(0, |d| d.first_slot + d.slots)
+    }
 
-        self.capacity = new_capacity;
-        true
+    /// Dynamic offset of this frame's `draw_index` slot, None past the capacity.
+    pub fn dynamic_offset(&self, draw_index: u32) -> Option<u32> {
+        ring_offset(self.frame, self.capacity, draw_index).map(|offset| offset as u32)
+    }
+
+    /// Fill this frame's `draw_index` slot; `upload` sends it to the GPU.
+    /// A slot past the capacity is counted and reported by the next
+    /// `begin_frame`.
+    pub fn write(&self, draw_index: u32, uniforms: &DrawUniforms) {
+        let mut staging = self.staging.borrow_mut();
+        match staging.get_mut(draw_index as usize) {
+            Some(slot) => *slot = *uniforms,
+            None => self.overflow.set(self.overflow.get() + 1),
+        }
+    }
+
+    /// Upload the frame's filled slots to its ring region in one write.
+    /// Call once all of them are written, before the frame's draws are
+    /// submitted.
+    pub fn upload(&self, queue: &wgpu::Queue) {
+        let staging = self.staging.borrow();
+        if let Some(offset) = ring_offset(self.frame, self.capacity, 0).filter(|_| !staging.is_empty()) {
+            queue.write_buffer(&self.buffer, offset, bytemuck::cast_slice(&staging[..]));
+        }
+    }
+}
+
+/// The ring buffer for `capacity` draws per frame and its bind group.
+fn create_draw_ring(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    capacity: u32,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Draw Uniform Ring"),
+        size: DRAW_UNIFORM_SIZE * capacity as u64 * DRAW_RING_FRAMES as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Draw Bind Group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &buffer,
+                offset: 0,
+                size: wgpu::BufferSize::new(DRAW_UNIFORM_SIZE),
+            }),
+        }],
+    });
+    (buffer, bind_group)
+}
+
+/// Byte offset of slot `draw_index` in ring region `frame`.
+fn ring_offset(frame: u32, capacity: u32, draw_index: u32) -> Option<u64> {
+    if draw_index >= capacity {
+        return None;
+    }
+    Some((frame as u64 * capacity as u64 + draw_index as u64) * DRAW_UNIFORM_SIZE)
+}
+
+/// Capacity after growing `capacity` until it holds `needed` draws.
+fn grown_capacity(capacity: u32, needed: u32) -> u32 {
+    let mut capacity = capacity.max(1);
+    while capacity < needed {
+        capacity *= GROWTH_FACTOR;
+    }
+    capacity
+}
+
+/// A visible mesh of the frame and its run of draw uniform slots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameDraw {
+    pub entity: hecs::Entity,
+    pub mesh: MeshHandle,
+    pub material: MaterialHandle,
+    /// First slot; the mesh uses `slots` from here (one per submesh).
+    pub first_slot: u32,
+    pub slots: u32,
+}
+
+/// The visible meshes in the order this frame uploads and draws them, each
+/// with its slots. Collected once per frame so that the upload, shadow and
+/// rasterize loops agree on slot numbers even if the world's iteration order
+/// would change between them. Meshes without a transform aren't drawn, so
+/// they get no slots.
+pub fn collect_frame_draws(scene_world: &SceneWorld, mesh_cache: &MeshCache) -> Vec<FrameDraw> {
+    let mut draws: Vec<FrameDraw> = scene_world
+        .world
+        .query::<(&MeshRenderer, &Transform)>()
+        .iter()
+        .filter(|(e, _)| scene_world.world.get::<&Hidden>(*e).is_err())
+        .map(|(entity, (mr, _))| FrameDraw {
+            entity,
+            mesh: mr.mesh_handle,
+            material: mr.material_handle,
+            first_slot: 0,
+            slots: mesh_cache.get(mr.mesh_handle).draw_slots(),
+        })
+        .collect();
+    sort_draws(&mut draws);
+    draws
+}
+
+/// Order draws by material, then mesh, and number their slots in that order.
+/// Consecutive draws then share texture bind groups and vertex buffers, which
+/// the rasterize passes only set when they change; the world's query order
+/// would switch them on almost every draw. The sort is stable, so draws of
+/// the same material and mesh keep their query order.
+fn sort_draws(draws: &mut [FrameDraw]) {
+    draws.sort_by_key(|d| (d.material.0, d.mesh.0));
+    let mut next_slot = 0;
+    for draw in draws {
+        draw.first_slot = next_slot;
+        next_slot += draw.slots;
     }
 }
 
 // --- GPU State ---
 
-/// GPU state created after the window is available.
+/// GPU state created after the window is available (or offscreen when headless).
 pub struct GpuState {
-    pub window: Arc<Window>,
-    pub surface: wgpu::Surface<'static>,
+    /// `None` when rendering headless
+    pub window: Option<Arc<Window>>,
+    /// Kept to recreate the surface after repeated swapchain losses.
+    pub instance: wgpu::Instance,
+    /// Queried for texture format capabilities when compiling pipelines.
+    pub adapter: wgpu::Adapter,
+    pub target: FrameTarget,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
@@ -182,6 +308,78 @@ pub struct GpuState {
     pub depth_view: wgpu::TextureView,
 }
 
+/// Where frames are drawn: the window's surface, or an offscreen texture.
+pub enum FrameTarget {
+    Surface(wgpu::Surface<'static>),
+    Offscreen(wgpu::Texture),
+}
+
+/// A frame's color target. `present` shows swapchain images; offscreen frames
+/// stay in the target texture for readback.
+pub struct FrameOutput {
+    pub texture: wgpu::Texture,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+}
+
+impl FrameOutput {
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+impl GpuState {
+    /// Acquire the next frame's color target.
+    pub fn get_current_texture(&self) -> Result<FrameOutput, wgpu::SurfaceError> {
+        match &self.target {
+            FrameTarget::Surface(surface) => {
+                let surface_texture = surface.get_current_texture()?;
+                Ok(FrameOutput {
+                    texture: surface_texture.texture.clone(),
+                    surface_texture: Some(surface_texture),
+                })
+            }
+            FrameTarget::Offscreen(texture) => Ok(FrameOutput {
+                texture: texture.clone(),
+                surface_texture: None,
+            }),
+        }
+    }
+
+    /// Reconfigure the surface with `config` (no-op offscreen).
+    pub fn configure_surface(&self) {
+        if let FrameTarget::Surface(surface) = &self.target {
+            surface.configure(&self.device, &self.config);
+        }
+    }
+
+    /// Offscreen color target matching the surface, for frames that must be
+    /// read back when the surface lacks `COPY_SRC`.
+    pub fn create_capture_texture(&self) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Screenshot Capture Target"),
+            size: wgpu::Extent3d {
+                width: self.config.width,
+                height: self.config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    pub fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
+
 pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
 /// Create a depth texture for the given dimensions.
@@ -208,30 +406,44 @@ pub fn create_depth_texture(
     (texture, view)
 }
 
-/// Initialize the wgpu device, surface, and create the initial render pipeline.
-pub async fn init_gpu(window: Arc<Window>, initial_wgsl: &str) -> GpuState {
-    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::all(),
+/// Create the wgpu instance used for both windowed and headless devices.
+pub fn create_instance(graphics: &crate::gpu_options::GraphicsOptions) -> wgpu::Instance {
+    wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: graphics.backends(),
         ..Default::default()
-    });
+    })
+}
 
-    let surface = instance
-        .create_surface(Arc::clone(&window))
-        .expect("Failed to create surface");
+/// Optional features the device is opened with, where the adapter offers them:
+/// timestamp queries for per-pass GPU timings, the adapter's own texture
+/// format capabilities (including filterable 32-bit float) so pipelines keep
+/// their formats instead of downgrading to what WebGPU guarantees, indirect
+/// first instance, which marks native indirect draws for GPU culling, and
+/// dual-source blending, which draws subpixel UI text.
+pub fn device_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+    adapter.features()
+        & (wgpu::Features::TIMESTAMP_QUERY
+            | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+            | wgpu::Features::FLOAT32_FILTERABLE
+            | wgpu::Features::INDIRECT_FIRST_INSTANCE
+            | wgpu::Features::DUAL_SOURCE_BLENDING)
+}
 
-    let adapter = instance
-        .request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        })
+/// Pick an adapter (one that can present to `surface`, if given) and open the device.
+pub async fn request_device(
+    instance: &wgpu::Instance,
+    graphics: &crate::gpu_options::GraphicsOptions,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), String> {
+    let adapter = crate::gpu_options::request_adapter(instance, graphics, surface)
         .await
-        .expect("Failed to find suitable GPU adapter");
+        .ok_or_else(|| "No suitable GPU adapter found".to_string())?;
 
     let adapter_info = adapter.get_info();
     tracing::info!(
-        "GPU adapter: {} ({:?})",
+        "GPU adapter: {} ({:?}, {:?})",
         adapter_info.name,
+        adapter_info.device_type,
         adapter_info.backend
     );
 
@@ -239,32 +451,58 @@ pub async fn init_gpu(window: Arc<Window>, initial_wgsl: &str) -> GpuState {
         .request_device(
             &wgpu::DeviceDescriptor {
                 label: Some("nAIVE Device"),
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
+                required_features: device_features(&adapter),
+                // Whatever the adapter offers; pipelines and splats downgrade to fit
+                required_limits: adapter.limits(),
                 memory_hints: wgpu::MemoryHints::default(),
             },
             None,
         )
         .await
-        .expect("Failed to create device");
+        .map_err(|e| format!("Failed to create device: {}", e))?;
+
+    Ok((adapter, device, queue))
+}
+
+/// Initialize the wgpu device, surface, and create the initial render pipeline.
+/// Fails when no adapter or device is available, as after a driver reset.
+pub async fn init_gpu(
+    window: Arc<Window>,
+    initial_wgsl: &str,
+    graphics: &crate::gpu_options::GraphicsOptions,
+) -> Result<GpuState, String> {
+    let instance = create_instance(graphics);
+
+    let surface = instance
+        .create_surface(Arc::clone(&window))
+        .map_err(|e| format!("Failed to create surface: {}", e))?;
+
+    let (adapter, device, queue) = request_device(&instance, graphics, Some(&surface))
+        .await?;
 
     let size = window.inner_size();
     let surface_caps = surface.get_capabilities(&adapter);
-    let surface_format = surface_caps
-        .formats
-        .iter()
-        .find(|f| f.is_srgb())
-        .copied()
-        .unwrap_or(surface_caps.formats[0]);
+    let surface_format = crate::gpu_options::pick_surface_format(
+        &surface_caps.formats,
+        graphics.surface_format.unwrap_or_default(),
+    );
+    let present_mode = crate::gpu_options::pick_present_mode(
+        &surface_caps.present_modes,
+        graphics.present_mode.unwrap_or_default(),
+    );
 
-    tracing::info!("Surface format: {:?}", surface_format);
+    tracing::info!("Surface format: {:?}, present mode: {:?}", surface_format, present_mode);
 
+    // COPY_SRC lets screenshots read the presented frame back; without it they
+    // render offscreen and COPY_DST copies the result into the surface
+    let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+        | (surface_caps.usages & (wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST));
     let config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        usage,
         format: surface_format,
         width: size.width.max(1),
         height: size.height.max(1),
-        present_mode: wgpu::PresentMode::Fifo,
+        present_mode,
         alpha_mode: surface_caps.alpha_modes[0],
         view_formats: vec![],
         desired_maximum_frame_latency: 2,
@@ -282,9 +520,11 @@ pub async fn init_gpu(window: Arc<Window>, initial_wgsl: &str) -> GpuState {
     let (depth_texture, depth_view) =
         create_depth_texture(&device, config.width, config.height);
 
-    GpuState {
-        window,
-        surface,
+    Ok(GpuState {
+        window: Some(window),
+        instance,
+        adapter,
+        target: FrameTarget::Surface(surface),
         device,
         queue,
         config,
@@ -292,7 +532,87 @@ pub async fn init_gpu(window: Arc<Window>, initial_wgsl: &str) -> GpuState {
         vertex_buffer,
         depth_texture,
         depth_view,
-    }
+    })
+}
+
+/// Initialize the device with an offscreen color target instead of a window
+/// surface. `config` describes the target so the rest of the engine treats it
+/// like a surface.
+pub async fn init_headless_gpu(
+    width: u32,
+    height: u32,
+    initial_wgsl: &str,
+    graphics: &crate::gpu_options::GraphicsOptions,
+) -> Result<GpuState, String> {
+    let instance = create_instance(graphics);
+    let (adapter, device, queue) = request_device(&instance, graphics, None).await?;
+
+    let format = crate::headless::HEADLESS_FORMAT;
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        format,
+        width: width.max(1),
+        height: height.max(1),
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+        view_formats: vec![],
+        desired_maximum_frame_latency: 2,
+    };
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Headless Target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: config.usage,
+        view_formats: &[],
+    });
+
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Triangle Vertex Buffer"),
+        contents: bytemuck::cast_slice(TRIANGLE_VERTICES),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    let render_pipeline = create_render_pipeline(&device, initial_wgsl, format);
+
+    let (depth_texture, depth_view) =
+        create_depth_texture(&device, config.width, config.height);
+
+    Ok(GpuState {
+        window: None,
+        instance,
+        adapter,
+        target: FrameTarget::Offscreen(target),
+        device,
+        queue,
+        config,
+        render_pipeline: Some(render_pipeline),
+        vertex_buffer,
+        depth_texture,
+        depth_view,
+    })
+}
+
+/// Replace the window's surface with a new one, for drivers that keep
+/// reporting it lost after a reconfigure. The configuration is reused.
+pub fn recreate_surface(gpu: &mut GpuState) -> Result<(), String> {
+    let Some(window) = &gpu.window else {
+        return Err("No window surface to recreate".to_string());
+    };
+    let surface = gpu
+        .instance
+        .create_surface(Arc::clone(window))
+        .map_err(|e| format!("Failed to recreate surface: {}", e))?;
+    surface.configure(&gpu.device, &gpu.config);
+    gpu.target = FrameTarget::Surface(surface);
+    tracing::info!("Recreated window surface");
+    Ok(())
 }
 
 /// Create the Phase 1 triangle render pipeline from WGSL source.
@@ -355,6 +675,28 @@ pub fn create_forward_pipeline(
     camera_bind_group_layout: &wgpu::BindGroupLayout,
     draw_bind_group_layout: &wgpu::BindGroupLayout,
     texture_bind_group_layout: Option<&wgpu::BindGroupLayout>,
+) -> wgpu::RenderPipeline {
+    create_forward_pipeline_oriented(
+        device,
+        wgsl_source,
+        format,
+        camera_bind_group_layout,
+        draw_bind_group_layout,
+        texture_bind_group_layout,
+        wgpu::FrontFace::Ccw,
+    )
+}
+
+/// Forward pipeline with a chosen front face. Views through a mirror flip the
+/// winding of everything they draw, so they cull with `FrontFace::Cw`.
+pub fn create_forward_pipeline_oriented(
+    device: &wgpu::Device,
+    wgsl_source: &str,
+    format: wgpu::TextureFormat,
+    camera_bind_group_layout: &wgpu::BindGroupLayout,
+    draw_bind_group_layout: &wgpu::BindGroupLayout,
+    texture_bind_group_layout: Option<&wgpu::BindGroupLayout>,
+    front_face: wgpu::FrontFace,
 ) -> wgpu::RenderPipeline {
     let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Forward Shader"),
@@ -395,7 +737,7 @@ pub fn create_forward_pipeline(
         primitive: wgpu::PrimitiveState {
             topology: wgpu::PrimitiveTopology::TriangleList,
             strip_index_format: None,
-            front_face: wgpu::FrontFace::Ccw,
+            front_face,
             cull_mode: Some(wgpu::Face::Back),
             polygon_mode: wgpu::PolygonMode::Fill,
             unclipped_depth: false,
@@ -416,10 +758,10 @@ pub fn create_forward_pipeline(
 
 /// Render one frame with the Phase 1 triangle (fallback when no scene loaded).
 pub fn render(gpu: &GpuState) {
-    let output = match gpu.surface.get_current_texture() {
+    let output = match gpu.get_current_texture() {
         Ok(t) => t,
         Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-            gpu.surface.configure(&gpu.device, &gpu.config);
+            gpu.configure_surface();
             return;
         }
         Err(e) => {
@@ -472,8 +814,9 @@ pub fn render(gpu: &GpuState) {
 
 /// Render one frame with 3D scene content.
 /// Render the 3D scene to the provided view and encoder (does not acquire/present swapchain).
+#[allow(clippy::too_many_arguments)]
 pub fn render_scene_to_view(
-    gpu: &GpuState,
+    queue: &wgpu::Queue,
     scene_world: &SceneWorld,
     camera_state: &CameraState,
     draw_pool: &DrawUniformPool,
@@ -481,42 +824,61 @@ pub fn render_scene_to_view(
     material_cache: &MaterialCache,
     forward_pipeline: &wgpu::RenderPipeline,
     view: &wgpu::TextureView,
+    depth_view: &wgpu::TextureView,
     encoder: &mut wgpu::CommandEncoder,
     texture_resources: Option<&crate::mesh::TextureResources>,
 ) {
-    // Write per-draw uniforms (skip hidden entities before incrementing draw_index)
-    let mut draw_index = 0u32;
-    for (entity, (transform, mesh_renderer)) in
-        scene_world.world.query::<(&Transform, &MeshRenderer)>().iter()
-    {
-        if scene_world.world.get::<&Hidden>(entity).is_ok() {
+    // Write the draw uniforms of the frame's visible meshes
+    for item in draw_pool.draws() {
+        let Ok(transform) = scene_world.world.get::<&Transform>(item.entity) else {
             continue;
-        }
-        let material = material_cache.get(mesh_renderer.material_handle);
+        };
+        let material = material_cache.get(item.material);
         let model_matrix = transform.world_matrix;
         let normal_matrix = model_matrix.inverse().transpose();
-        let gpu_mesh = mesh_cache.get(mesh_renderer.mesh_handle);
-        let has_texture = if gpu_mesh.texture_bind_group.is_some() { 1.0f32 } else { 0.0f32 };
-
-        let draw_uniform = DrawUniforms {
-            model_matrix: model_matrix.to_cols_array_2d(),
-            normal_matrix: normal_matrix.to_cols_array_2d(),
-            base_color: material.uniform.base_color,
-            roughness: material.uniform.roughness,
-            metallic: material.uniform.metallic,
-            has_texture,
-            has_skin: 0.0,
-            emission: material.uniform.emission,
-            _padding: [0.0; 20],
-        };
+        let gpu_mesh = mesh_cache.get(item.mesh);
 
-        gpu.queue.write_buffer(
-            &draw_pool.buffer,
-            draw_index as u64 * DRAW_UNIFORM_SIZE,
-            bytemuck::cast_slice(&[draw_uniform]),
-        );
-        draw_index += 1;
+        // One uniform per submesh; glTF material factors fill in whatever the
+        // entity material file leaves unset
+        let explicit = material.explicit;
+        for slot in 0..gpu_mesh.draw_slots() {
+            let draw = gpu_mesh.draw_slot(slot);
+            let has_texture = if material.albedo_texture.is_some() || draw.texture_bind_group.is_some() { 1.0f32 } else { 0.0f32 };
+            let (base_color, roughness, metallic, emission) = match draw.material {
+                Some(m) => (
+                    std::array::from_fn(|i| material.uniform.base_color[i] * m.base_color[i]),
+                    if explicit.roughness { material.uniform.roughness } else { m.roughness },
+                    if explicit.metallic { material.uniform.metallic } else { m.metallic },
+                    if explicit.emission {
+                        material.uniform.emission
+                    } else {
+                        [m.emission[0], m.emission[1], m.emission[2], 0.0]
+                    },
+                ),
+                None => (
+                    material.uniform.base_color,
+                    material.uniform.roughness,
+                    material.uniform.metallic,
+                    material.uniform.emission,
+                ),
+            };
+
+            let draw_uniform = DrawUniforms {
+                model_matrix: model_matrix.to_cols_array_2d(),
+                normal_matrix: normal_matrix.to_cols_array_2d(),
+                base_color,
+                roughness,
+                metallic,
+                has_texture,
+                has_skin: 0.0,
+                emission,
+                _padding: [0.0; 20],
+            };
+
+            draw_pool.write(item.first_slot + slot, &draw_uniform);
+        }
     }
+    draw_pool.upload(queue);
 
     {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -535,7 +897,7 @@ pub fn render_scene_to_view(
                 },
             })],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &gpu.depth_view,
+                view: depth_view,
                 depth_ops: Some(wgpu::Operations {
                     load: wgpu::LoadOp::Clear(1.0),
                     store: wgpu::StoreOp::Store,
@@ -548,33 +910,117 @@ pub fn render_scene_to_view(
 
         render_pass.set_pipeline(forward_pipeline);
         render_pass.set_bind_group(0, &camera_state.bind_group, &[]);
+        draw_scene_meshes(&mut render_pass, draw_pool, mesh_cache, material_cache, texture_resources);
+    }
+}
 
-        let mut draw_index = 0u32;
-        for (entity, (_, mesh_renderer)) in
-            scene_world.world.query::<(&Transform, &MeshRenderer)>().iter()
-        {
-            if scene_world.world.get::<&Hidden>(entity).is_ok() {
-                continue;
-            }
-            let gpu_mesh = mesh_cache.get(mesh_renderer.mesh_handle);
-            let dynamic_offset = draw_index * DRAW_UNIFORM_SIZE as u32;
+/// Draw every visible mesh with the bound forward-style pipeline (camera at
+/// group 0), using the draw uniforms already uploaded this frame.
+pub fn draw_scene_meshes(
+    render_pass: &mut wgpu::RenderPass<'_>,
+    draw_pool: &DrawUniformPool,
+    mesh_cache: &MeshCache,
+    material_cache: &MaterialCache,
+    texture_resources: Option<&crate::mesh::TextureResources>,
+) {
+    let mut bound = BoundDrawState::default();
+    for item in draw_pool.draws() {
+        let gpu_mesh = mesh_cache.get(item.mesh);
+        bound.bind_mesh(render_pass, item.mesh, gpu_mesh);
 
+        for slot in 0..gpu_mesh.draw_slots() {
+            let draw = gpu_mesh.draw_slot(slot);
+            let Some(dynamic_offset) = draw_pool.dynamic_offset(item.first_slot + slot) else {
+                continue;
+            };
             render_pass.set_bind_group(1, &draw_pool.bind_group, &[dynamic_offset]);
 
-            // Bind texture at group(2): use mesh's texture or fallback to white
+            // Bind texture maps at group(2): material maps > submesh/mesh maps > fallbacks
             if let Some(tex_res) = texture_resources {
-                let tex_bg = gpu_mesh.texture_bind_group.as_ref()
+                let tex_bg = material_cache.get(item.material).bind_group.as_ref()
+                    .or(draw.texture_bind_group)
                     .unwrap_or(&tex_res.default_bind_group);
-                render_pass.set_bind_group(2, tex_bg, &[]);
+                bound.bind_textures(render_pass, 2, tex_bg);
             }
 
-            render_pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(
-                gpu_mesh.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint32,
-            );
-            render_pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
-            draw_index += 1;
+            render_pass.draw_indexed(draw.indices, 0, 0..1);
+        }
+    }
+}
+
+/// The vertex buffers and texture bind group a render pass last had set, so
+/// consecutive draws sharing them (`collect_frame_draws` sorts them that way)
+/// skip the calls.
+#[derive(Default)]
+pub struct BoundDrawState<'a> {
+    mesh: Option<MeshHandle>,
+    textures: Option<&'a wgpu::BindGroup>,
+    /// Pipeline, bind group and buffer changes made on the pass.
+    pub changes: u32,
+}
+
+impl<'a> BoundDrawState<'a> {
+    /// Set the vertex and index buffers of `mesh` unless they are already.
+    pub fn bind_mesh(&mut self, render_pass: &mut wgpu::RenderPass<'_>, handle: MeshHandle, mesh: &crate::mesh::GpuMesh) {
+        if self.mesh == Some(handle) {
+            return;
+        }
+        render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice());
+        render_pass.set_index_buffer(mesh.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+        self.mesh = Some(handle);
+        self.changes += 2;
+    }
+
+    /// Set the texture bind group at `group` unless it is already.
+    pub fn bind_textures(&mut self, render_pass: &mut wgpu::RenderPass<'_>, group: u32, bind_group: &'a wgpu::BindGroup) {
+        if self.textures.is_some_and(|bound| std::ptr::eq(bound, bind_group)) {
+            return;
         }
+        render_pass.set_bind_group(group, bind_group, &[]);
+        self.textures = Some(bind_group);
+        self.changes += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_ring_offsets() {
+        assert_eq!(ring_offset(0, 4, 0), Some(0));
+        assert_eq!(ring_offset(0, 4, 3), Some(3 * DRAW_UNIFORM_SIZE));
+        // Each frame starts past the previous frame's slots
+        assert_eq!(ring_offset(1, 4, 0), Some(4 * DRAW_UNIFORM_SIZE));
+        assert_eq!(ring_offset(DRAW_RING_FRAMES - 1, 4, 3), Some((4 * DRAW_RING_FRAMES as u64 - 1) * DRAW_UNIFORM_SIZE));
+        assert_eq!(ring_offset(1, 4, 4), None);
+    }
+
+    #[test]
+    fn test_draws_sorted_by_material_then_mesh() {
+        let mut world = hecs::World::new();
+        let mut draw = |material, mesh, slots| FrameDraw {
+            entity: world.spawn(()),
+            mesh: MeshHandle(mesh),
+            material: MaterialHandle(material),
+            first_slot: 0,
+            slots,
+        };
+        let mut draws = vec![draw(1, 0, 1), draw(0, 2, 3), draw(1, 0, 1), draw(0, 1, 2)];
+        let first = draws[0].entity;
+        sort_draws(&mut draws);
+
+        let order: Vec<(usize, usize, u32)> = draws.iter().map(|d| (d.material.0, d.mesh.0, d.first_slot)).collect();
+        assert_eq!(order, vec![(0, 1, 0), (0, 2, 2), (1, 0, 5), (1, 0, 6)]);
+        // Ties keep their order
+        assert_eq!(draws[2].entity, first);
+    }
+
+    #[test]
+    fn test_draw_ring_growth() {
+        assert_eq!(grown_capacity(256, 100), 256);
+        assert_eq!(grown_capacity(256, 257), 512);
+        assert_eq!(grown_capacity(256, 1500), 2048);
+        assert_eq!(grown_capacity(0, 3), 4);
     }
 }
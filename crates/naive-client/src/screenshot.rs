@@ -0,0 +1,177 @@
+//! Screenshot capture: copy a rendered texture to a staging buffer and write a PNG.
+//!
+//! Requests come from Lua (`capture_screenshot(path)`), the command socket
+//! (`capture_screenshot`) and `--screenshot-after N`. They are queued and served
+//! at the end of the frame, after the UI pass, so the PNG matches what was
+//! presented. The headless renderer reads its offscreen target the same way.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Queued screenshot output paths, served at the end of the frame.
+pub type ScreenshotQueue = Vec<PathBuf>;
+
+/// Resolve a requested screenshot path against the project root. Scripts may
+/// only write inside the project (`allow_absolute = false`); socket and CLI
+/// callers may also pass absolute paths.
+pub fn resolve_output_path(project_root: &Path, path: &str, allow_absolute: bool) -> Result<PathBuf, String> {
+    let requested = Path::new(path);
+    if path.is_empty() {
+        return Err("screenshot path is empty".to_string());
+    }
+    if requested.is_absolute() {
+        return if allow_absolute {
+            Ok(requested.to_path_buf())
+        } else {
+            Err(format!("screenshot path '{}' must be relative to the project", path))
+        };
+    }
+    if requested.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(format!("screenshot path '{}' must not contain '..'", path));
+    }
+    Ok(project_root.join(requested))
+}
+
+/// Copy `texture` into a staging buffer and read it back as tightly packed
+/// RGBA8 pixels. Blocks until the GPU has finished the copy. The texture needs
+/// `COPY_SRC` usage and an 8-bit RGBA or BGRA format.
+pub fn read_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> Result<Vec<u8>, String> {
+    if !is_readable_format(format) {
+        return Err(format!("Cannot capture {:?} textures (expected 8-bit RGBA/BGRA)", format));
+    }
+    let padded_row = padded_bytes_per_row(width);
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Screenshot Staging Buffer"),
+        size: padded_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Screenshot Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &staging,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = staging.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    rx.recv()
+        .map_err(|_| "GPU readback was cancelled".to_string())?
+        .map_err(|e| format!("Failed to map staging buffer: {}", e))?;
+
+    let mut pixels = unpad_rows(&slice.get_mapped_range(), width, height, padded_row);
+    staging.unmap();
+    convert_to_rgba8(&mut pixels, format);
+    Ok(pixels)
+}
+
+/// Write tightly packed RGBA8 pixels as a PNG, creating parent directories.
+pub fn write_png(out: &Path, pixels: &[u8], width: u32, height: u32) -> Result<(), String> {
+    if let Some(parent) = out.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+    }
+    image::save_buffer(out, pixels, width, height, image::ColorType::Rgba8)
+        .map_err(|e| format!("Failed to write '{}': {}", out.display(), e))
+}
+
+/// Whether `format` can be read back as 8-bit RGBA.
+fn is_readable_format(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+    matches!(format, Rgba8Unorm | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb)
+}
+
+/// Swizzle BGRA pixels to RGBA in place; RGBA pixels are left as is. sRGB
+/// formats already hold gamma-encoded bytes, which is what PNG expects.
+fn convert_to_rgba8(pixels: &mut [u8], format: wgpu::TextureFormat) {
+    if matches!(format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb) {
+        for px in pixels.chunks_exact_mut(4) {
+            px.swap(0, 2);
+        }
+    }
+}
+
+/// Bytes per row of a `width`-pixel RGBA8 image, padded for texture-to-buffer copies.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let unpadded = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    unpadded.div_ceil(align) * align
+}
+
+/// Strip per-row padding from a readback buffer into tightly packed RGBA8 pixels.
+fn unpad_rows(data: &[u8], width: u32, height: u32, padded_row: u32) -> Vec<u8> {
+    let row_bytes = (width * 4) as usize;
+    let mut pixels = Vec::with_capacity(row_bytes * height as usize);
+    for row in data.chunks(padded_row as usize).take(height as usize) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padded_bytes_per_row() {
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+        assert_eq!(padded_bytes_per_row(1), 256);
+    }
+
+    #[test]
+    fn test_unpad_rows() {
+        // 2x2 image padded to 256 bytes per row
+        let mut data = vec![0u8; 512];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data[256..264].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+        let pixels = unpad_rows(&data, 2, 2, 256);
+        assert_eq!(pixels, (1..=16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_convert_bgra_to_rgba() {
+        let mut pixels = vec![10, 20, 30, 255, 1, 2, 3, 4];
+        convert_to_rgba8(&mut pixels, wgpu::TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(pixels, vec![30, 20, 10, 255, 3, 2, 1, 4]);
+        convert_to_rgba8(&mut pixels, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(pixels, vec![30, 20, 10, 255, 3, 2, 1, 4]);
+        assert!(!is_readable_format(wgpu::TextureFormat::Rgba16Float));
+    }
+
+    #[test]
+    fn test_resolve_output_path() {
+        let root = Path::new("/game");
+        assert_eq!(resolve_output_path(root, "shots/a.png", false).unwrap(), PathBuf::from("/game/shots/a.png"));
+        assert!(resolve_output_path(root, "../a.png", false).is_err());
+        assert!(resolve_output_path(root, "/tmp/a.png", false).is_err());
+        assert_eq!(resolve_output_path(root, "/tmp/a.png", true).unwrap(), PathBuf::from("/tmp/a.png"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Precompiled scripts for shipped builds.
+//!
+//! `naive build --bytecode` (or `build.scripts: bytecode` in naive.yaml)
+//! compiles every `.lua` file of the project to Lua bytecode and writes them
+//! into one `logic.bundle` archive next to naive.yaml, instead of copying the
+//! sources. The runtime looks scripts up in the bundle before the filesystem.
+//!
+//! Bytecode only runs on the VM that produced it, so the bundle header
+//! records the Lua and mlua versions it was built with and a runtime with
+//! different ones refuses it rather than crashing on it. The layout of the
+//! file itself is versioned too; bytecode can't be migrated, so a bundle in
+//! another layout is refused the same way.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use mlua::prelude::*;
+
+/// File name of the bundle, relative to the project root.
+pub const BUNDLE_FILE: &str = "logic.bundle";
+
+const MAGIC: &[u8; 8] = b"NAIVELBC";
+
+/// Layout version of the bundle file, written after the magic.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// mlua version the engine is built against; keep in sync with Cargo.toml.
+const MLUA_VERSION: &str = "0.10";
+
+/// Identifies the VM bytecode is valid for, e.g. "Lua 5.4 mlua 0.10 64-bit".
+pub fn bytecode_tag(lua: &Lua) -> String {
+    let version: String = lua.globals().get("_VERSION").unwrap_or_else(|_| "Lua".to_string());
+    format!("{} mlua {} {}-bit", version, MLUA_VERSION, usize::BITS)
+}
+
+/// Script path as scenes reference it: forward slashes, no leading "./".
+pub fn bundle_key(path: &Path) -> String {
+    let key = path.to_string_lossy().replace('\\', "/");
+    key.strip_prefix("./").unwrap_or(&key).to_string()
+}
+
+/// Compiled scripts keyed by their project-relative path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptBundle {
+    pub tag: String,
+    pub chunks: BTreeMap<String, Vec<u8>>,
+}
+
+impl ScriptBundle {
+    /// Compile `(path, source)` pairs. `strip` drops debug info, so errors
+    /// lose their file names and line numbers.
+    pub fn compile<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+        strip: bool,
+    ) -> Result<Self, String> {
+        let lua = Lua::new();
+        let mut chunks = BTreeMap::new();
+        for (path, source) in sources {
+            let function = lua
+                .load(source)
+                .set_name(path)
+                .into_function()
+                .map_err(|e| format!("Failed to compile {}: {}", path, e))?;
+            chunks.insert(path.to_string(), function.dump(strip));
+        }
+        Ok(Self { tag: bytecode_tag(&lua), chunks })
+    }
+
+    /// Bytecode for the script at `path`, if bundled.
+    pub fn get(&self, path: &Path) -> Option<&[u8]> {
+        self.chunks.get(&bundle_key(path)).map(Vec::as_slice)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&BUNDLE_FORMAT_VERSION.to_le_bytes());
+        write_block(&mut out, self.tag.as_bytes());
+        out.extend_from_slice(&(self.chunks.len() as u32).to_le_bytes());
+        for (path, code) in &self.chunks {
+            write_block(&mut out, path.as_bytes());
+            write_block(&mut out, code);
+        }
+        out
+    }
+
+    /// Parse a bundle, rejecting one built for a VM other than `expected_tag`.
+    pub fn from_bytes(bytes: &[u8], expected_tag: &str) -> Result<Self, String> {
+        let mut rest = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or("not a script bundle")?;
+        let version = read_u32(&mut rest)?;
+        if version != BUNDLE_FORMAT_VERSION {
+            return Err(format!(
+                "script bundle has format version {}, this runtime reads version {}; rebuild the game",
+                version, BUNDLE_FORMAT_VERSION
+            ));
+        }
+        let tag = String::from_utf8(read_block(&mut rest)?.to_vec())
+            .map_err(|_| "corrupt script bundle")?;
+        if tag != expected_tag {
+            return Err(format!(
+                "script bundle was built for {}, this runtime is {}; rebuild the game",
+                tag, expected_tag
+            ));
+        }
+        let count = read_u32(&mut rest)?;
+        let mut chunks = BTreeMap::new();
+        for _ in 0..count {
+            let path = String::from_utf8(read_block(&mut rest)?.to_vec())
+                .map_err(|_| "corrupt script bundle")?;
+            let code = read_block(&mut rest)?.to_vec();
+            chunks.insert(path, code);
+        }
+        Ok(Self { tag, chunks })
+    }
+
+    /// Load `project_root/logic.bundle` if the project has one.
+    pub fn load(project_root: &Path, lua: &Lua) -> Result<Option<Self>, String> {
+        let path = project_root.join(BUNDLE_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_bytes(&bytes, &bytecode_tag(lua))
+            .map(Some)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+fn write_block(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+}
+
+fn read_u32(rest: &mut &[u8]) -> Result<u32, String> {
+    if rest.len() < 4 {
+        return Err("truncated script bundle".to_string());
+    }
+    let (head, tail) = rest.split_at(4);
+    *rest = tail;
+    Ok(u32::from_le_bytes([head[0], head[1], head[2], head[3]]))
+}
+
+fn read_block<'a>(rest: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    let len = read_u32(rest)? as usize;
+    if rest.len() < len {
+        return Err("truncated script bundle".to_string());
+    }
+    let (block, tail) = rest.split_at(len);
+    *rest = tail;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip_runs_bytecode() {
+        let bundle = ScriptBundle::compile(
+            [("logic/add.lua", "function add(a, b) return a + b end")],
+            true,
+        )
+        .unwrap();
+        let lua = Lua::new();
+        let loaded = ScriptBundle::from_bytes(&bundle.to_bytes(), &bytecode_tag(&lua)).unwrap();
+        assert_eq!(loaded, bundle);
+
+        let code = loaded.get(Path::new("./logic/add.lua")).unwrap();
+        assert!(!code.starts_with(b"function"));
+        lua.load(code).exec().unwrap();
+        let sum: i64 = lua.load("return add(2, 3)").eval().unwrap();
+        assert_eq!(sum, 5);
+    }
+
+    #[test]
+    fn test_bundle_rejects_other_vm_and_bad_input() {
+        let bundle = ScriptBundle::compile([("logic/a.lua", "return 1")], false).unwrap();
+        let bytes = bundle.to_bytes();
+        let err = ScriptBundle::from_bytes(&bytes, "Lua 5.1 mlua 0.9 64-bit").unwrap_err();
+        assert!(err.contains("rebuild"), "{}", err);
+        assert!(ScriptBundle::from_bytes(&bytes[..bytes.len() - 1], &bundle.tag).is_err());
+        assert!(ScriptBundle::from_bytes(b"print('hi')", &bundle.tag).is_err());
+
+        let mut future = bytes.clone();
+        future[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(BUNDLE_FORMAT_VERSION + 1).to_le_bytes());
+        let err = ScriptBundle::from_bytes(&future, &bundle.tag).unwrap_err();
+        assert!(err.contains("format version 2"), "{}", err);
+
+        let err = ScriptBundle::compile([("logic/bad.lua", "function (")], true).unwrap_err();
+        assert!(err.contains("logic/bad.lua"), "{}", err);
+    }
+}
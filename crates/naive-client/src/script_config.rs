@@ -0,0 +1,177 @@
+//! The read-only `config` table scripts see.
+//!
+//! Values come from three layers, later ones winning key by key: `config:` in
+//! naive.yaml, `settings.config:` in the current scene, and `--config
+//! key=value` on the command line. Nested mappings merge recursively, and
+//! dotted CLI keys (`--config debug.show_paths=true`) address nested values.
+//! Scripts read it as a plain table; writing to it raises an error.
+
+use mlua::prelude::*;
+use serde_yaml::{Mapping, Value};
+
+/// Parse a `key=value` override. The value is read as YAML, so numbers and
+/// booleans keep their types; anything unparsable is a string.
+pub fn parse_override(arg: &str) -> Result<(String, Value), String> {
+    let (key, raw) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("--config '{}': expected key=value", arg))?;
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(format!("--config '{}': invalid key", arg));
+    }
+    let value = match serde_yaml::from_str::<Value>(raw) {
+        Ok(v @ (Value::Bool(_) | Value::Number(_) | Value::String(_) | Value::Null)) => v,
+        _ => Value::String(raw.to_string()),
+    };
+    Ok((key.to_string(), value))
+}
+
+/// Set `value` at a dotted `key`, creating (or replacing non-mapping values
+/// with) intermediate mappings.
+fn set_path(root: &mut Mapping, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            let head = Value::String(head.to_string());
+            if !matches!(root.get(&head), Some(Value::Mapping(_))) {
+                root.insert(head.clone(), Value::Mapping(Mapping::new()));
+            }
+            if let Some(Value::Mapping(child)) = root.get_mut(&head) {
+                set_path(child, rest, value);
+            }
+        }
+        None => {
+            root.insert(Value::String(key.to_string()), value);
+        }
+    }
+}
+
+/// Merge `over` into `base`: mappings merge recursively, other values replace.
+fn merge(base: &mut Mapping, over: &Mapping) {
+    for (key, value) in over {
+        match (base.get_mut(key), value) {
+            (Some(Value::Mapping(base_child)), Value::Mapping(over_child)) => merge(base_child, over_child),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Layer the project config, the scene config and CLI overrides.
+pub fn resolve(project: Option<&Mapping>, scene: Option<&Mapping>, overrides: &[String]) -> Result<Mapping, String> {
+    let mut config = Mapping::new();
+    for layer in [project, scene].into_iter().flatten() {
+        merge(&mut config, layer);
+    }
+    for arg in overrides {
+        let (key, value) = parse_override(arg)?;
+        set_path(&mut config, &key, value);
+    }
+    Ok(config)
+}
+
+/// Convert a YAML value to Lua, wrapping mappings and sequences in read-only
+/// proxies.
+fn to_lua(lua: &Lua, value: &Value) -> LuaResult<LuaValue> {
+    Ok(match value {
+        Value::Null => LuaNil,
+        Value::Bool(b) => LuaValue::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => LuaValue::Integer(i),
+            None => LuaValue::Number(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => LuaValue::String(lua.create_string(s)?),
+        Value::Sequence(items) => {
+            let data = lua.create_table()?;
+            for (i, item) in items.iter().enumerate() {
+                data.set(i + 1, to_lua(lua, item)?)?;
+            }
+            LuaValue::Table(read_only(lua, data)?)
+        }
+        Value::Mapping(map) => LuaValue::Table(mapping_to_lua(lua, map)?),
+        Value::Tagged(tagged) => to_lua(lua, &tagged.value)?,
+    })
+}
+
+fn mapping_to_lua(lua: &Lua, map: &Mapping) -> LuaResult<LuaTable> {
+    let data = lua.create_table()?;
+    for (key, value) in map {
+        let key = match key {
+            Value::String(s) => LuaValue::String(lua.create_string(s)?),
+            Value::Number(_) | Value::Bool(_) => to_lua(lua, key)?,
+            _ => continue,
+        };
+        data.set(key, to_lua(lua, value)?)?;
+    }
+    read_only(lua, data)
+}
+
+/// An empty proxy that reads from `data`, iterates it with `pairs`/`#` and
+/// rejects writes.
+fn read_only(lua: &Lua, data: LuaTable) -> LuaResult<LuaTable> {
+    let proxy = lua.create_table()?;
+    let meta = lua.create_table()?;
+    meta.set("__index", data.clone())?;
+    meta.set(
+        "__newindex",
+        lua.create_function(|_, (_, key): (LuaTable, LuaValue)| -> LuaResult<()> {
+            let key = key.to_string().unwrap_or_else(|_| "?".to_string());
+            Err(LuaError::RuntimeError(format!("config is read-only (tried to set '{}')", key)))
+        })?,
+    )?;
+    let next: LuaFunction = lua.globals().get("next")?;
+    let pairs_data = data.clone();
+    meta.set(
+        "__pairs",
+        lua.create_function(move |_, _: LuaTable| Ok((next.clone(), pairs_data.clone(), LuaNil)))?,
+    )?;
+    meta.set("__len", lua.create_function(move |_, _: LuaTable| Ok(data.raw_len()))?)?;
+    meta.set("__metatable", false)?;
+    proxy.set_metatable(Some(meta));
+    Ok(proxy)
+}
+
+/// Build the read-only `config` table.
+pub fn to_lua_table(lua: &Lua, config: &Mapping) -> LuaResult<LuaTable> {
+    mapping_to_lua(lua, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_merge_and_cli_overrides_win() {
+        let project: Mapping = serde_yaml::from_str("difficulty: normal\ndebug: { show_paths: false, fps: true }").unwrap();
+        let scene: Mapping = serde_yaml::from_str("difficulty: hard\nenemies: 12").unwrap();
+        let overrides = vec!["debug.show_paths=true".to_string(), "enemies=3".to_string(), "name=a=b".to_string()];
+        let config = resolve(Some(&project), Some(&scene), &overrides).unwrap();
+
+        let expected: Mapping = serde_yaml::from_str(
+            "difficulty: hard\ndebug: { show_paths: true, fps: true }\nenemies: 3\nname: a=b",
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+        assert!(resolve(None, None, &["novalue".to_string()]).is_err());
+        assert!(resolve(None, None, &["a..b=1".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_lua_table_is_read_only() {
+        let lua = Lua::new();
+        let config: Mapping = serde_yaml::from_str("lives: 3\nflags: { god_mode: true }\nwaves: [5, 8]").unwrap();
+        lua.globals().set("config", to_lua_table(&lua, &config).unwrap()).unwrap();
+
+        let (lives, god, waves, second): (i64, bool, i64, i64) = lua
+            .load("return config.lives, config.flags.god_mode, #config.waves, config.waves[2]")
+            .eval()
+            .unwrap();
+        assert_eq!((lives, god, waves, second), (3, true, 2, 8));
+        let keys: i64 = lua.load("local n = 0 for _ in pairs(config) do n = n + 1 end return n").eval().unwrap();
+        assert_eq!(keys, 3);
+
+        let err = lua.load("config.flags.god_mode = false").exec().unwrap_err();
+        assert!(err.to_string().contains("read-only"));
+        assert!(lua.load("setmetatable(config, nil)").exec().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Lua memory cap, frame-budgeted garbage collection and per-script allocation stats.
+//!
+//! The VM is capped at `limit` bytes: an allocation past the cap fails with a
+//! memory error in the script that made it, after Lua's emergency collection,
+//! instead of growing the process without bound. Once configured, Lua's
+//! automatic collector is stopped and the engine runs incremental GC steps at
+//! the end of each frame for at most `gc_budget`, so collection cost lands in
+//! one predictable place. Bytes allocated inside each guarded hook call are
+//! attributed to the calling entity (see `script_watchdog::guarded`), which
+//! makes a script that allocates every frame easy to find.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use mlua::Lua;
+
+/// Default VM memory cap in megabytes.
+pub const DEFAULT_MEMORY_LIMIT_MB: u64 = 256;
+/// Default time per frame spent on incremental GC, in milliseconds.
+pub const DEFAULT_GC_BUDGET_MS: f32 = 1.0;
+/// Fraction of the cap at which memory is reported as high.
+pub const HIGH_MEMORY_FRACTION: f64 = 0.75;
+/// Fraction of the cap at which a full collection runs regardless of budget.
+const FULL_COLLECT_FRACTION: f64 = 0.9;
+/// Work per incremental GC step, as if this many kilobytes had been allocated.
+const GC_STEP_KB: i32 = 64;
+/// Weight of the current frame in the smoothed bytes-per-frame rate.
+const RATE_SMOOTHING: f32 = 0.1;
+
+/// Allocation stats for one entity's script.
+#[derive(Debug, Clone, Default)]
+pub struct EntityMemoryStats {
+    /// Bytes allocated so far this frame.
+    pub frame_bytes: u64,
+    /// Smoothed bytes allocated per frame.
+    pub bytes_per_frame: f32,
+    /// Bytes allocated since the script was loaded.
+    pub total_bytes: u64,
+}
+
+/// What the end-of-frame collection did.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GcReport {
+    pub ms: f32,
+    /// A collection cycle finished this frame.
+    pub cycle_finished: bool,
+    /// Memory was near the cap, so a full collection ran.
+    pub full_collect: bool,
+}
+
+/// VM memory cap, GC scheduling and per-entity allocation stats.
+pub struct ScriptMemory {
+    /// Cap in bytes (0 = unlimited).
+    pub limit: usize,
+    pub gc_budget: Duration,
+    /// Whether the engine drives the collector (automatic GC stopped).
+    pub manual_gc: bool,
+    /// Time spent collecting at the end of the last frame.
+    pub last_gc_ms: f32,
+    /// Completed collection cycles.
+    pub gc_cycles: u64,
+    /// VM memory right after the last completed cycle. Steady growth here
+    /// means something keeps its allocations alive.
+    pub live_bytes: usize,
+    entities: HashMap<hecs::Entity, EntityMemoryStats>,
+}
+
+impl ScriptMemory {
+    pub fn new() -> Self {
+        Self {
+            limit: 0,
+            gc_budget: Duration::from_secs_f32(DEFAULT_GC_BUDGET_MS / 1000.0),
+            manual_gc: false,
+            last_gc_ms: 0.0,
+            gc_cycles: 0,
+            live_bytes: 0,
+            entities: HashMap::new(),
+        }
+    }
+
+    /// Apply a cap of `limit_mb` (0 = unlimited) and take over GC scheduling
+    /// with `gc_budget_ms` per frame.
+    pub fn configure(&mut self, lua: &Lua, limit_mb: u64, gc_budget_ms: f32) -> Result<(), String> {
+        let limit = (limit_mb as usize).saturating_mul(1024 * 1024);
+        lua.set_memory_limit(limit).map_err(|e| e.to_string())?;
+        lua.gc_stop();
+        self.limit = limit;
+        self.gc_budget = Duration::from_secs_f32(gc_budget_ms.max(0.0) / 1000.0);
+        self.manual_gc = true;
+        self.live_bytes = lua.used_memory();
+        Ok(())
+    }
+
+    /// Attribute `bytes` allocated during a hook call to `entity`.
+    pub fn record(&mut self, entity: hecs::Entity, bytes: u64) {
+        let stats = self.entities.entry(entity).or_default();
+        stats.frame_bytes += bytes;
+        stats.total_bytes += bytes;
+    }
+
+    pub fn reset_entity(&mut self, entity: hecs::Entity) {
+        self.entities.remove(&entity);
+    }
+
+    pub fn entity_stats(&self) -> impl Iterator<Item = (hecs::Entity, &EntityMemoryStats)> + '_ {
+        self.entities.iter().map(|(&e, s)| (e, s))
+    }
+
+    /// Whether `used` bytes is close enough to the cap to warn about.
+    pub fn is_high(&self, used: usize) -> bool {
+        self.limit > 0 && used as f64 >= self.limit as f64 * HIGH_MEMORY_FRACTION
+    }
+
+    /// Fold this frame's allocations into the per-entity rates and run
+    /// incremental GC within the frame budget.
+    pub fn end_frame(&mut self, lua: &Lua) -> GcReport {
+        for stats in self.entities.values_mut() {
+            stats.bytes_per_frame += (stats.frame_bytes as f32 - stats.bytes_per_frame) * RATE_SMOOTHING;
+            stats.frame_bytes = 0;
+        }
+        if !self.manual_gc {
+            return GcReport::default();
+        }
+
+        let start = Instant::now();
+        let mut report = GcReport::default();
+        while start.elapsed() < self.gc_budget {
+            match lua.gc_step_kbytes(GC_STEP_KB) {
+                Ok(true) => {
+                    report.cycle_finished = true;
+                    break;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!("Lua GC step failed: {}", e);
+                    break;
+                }
+            }
+        }
+        // Allocation outpaced the budget: pay for a full cycle now rather
+        // than fail allocations later
+        if self.limit > 0 && lua.used_memory() as f64 >= self.limit as f64 * FULL_COLLECT_FRACTION {
+            if let Err(e) = lua.gc_collect() {
+                tracing::warn!("Lua full collection failed: {}", e);
+            }
+            report.full_collect = true;
+            report.cycle_finished = true;
+        }
+        if report.cycle_finished {
+            self.gc_cycles += 1;
+            self.live_bytes = lua.used_memory();
+        }
+        report.ms = start.elapsed().as_secs_f32() * 1000.0;
+        self.last_gc_ms = report.ms;
+        report
+    }
+}
+
+impl Default for ScriptMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script_watchdog::{guarded, install, ScriptWatchdog, SharedScriptWatchdog};
+
+    #[test]
+    fn test_allocations_are_attributed_capped_and_collected() {
+        let lua = Lua::new();
+        let watchdog: SharedScriptWatchdog = std::rc::Rc::new(std::cell::RefCell::new(ScriptWatchdog::new()));
+        install(&lua, watchdog.clone());
+        watchdog.borrow_mut().memory.configure(&lua, 4, 1000.0).unwrap();
+
+        let mut world = hecs::World::new();
+        let entity = world.spawn(());
+        let churn: mlua::Function = lua
+            .load("return function() local t = {} for i = 1, 10000 do t[i] = {i} end end")
+            .eval()
+            .unwrap();
+        guarded(&watchdog, Some(entity), "update", || churn.call::<()>(())).unwrap();
+        let allocated = watchdog.borrow().memory.entity_stats().next().unwrap().1.frame_bytes;
+        assert!(allocated > 100_000, "expected churn to be attributed, got {} bytes", allocated);
+
+        // The garbage is gone once the frame's collection finishes a cycle
+        let before = lua.used_memory();
+        let report = watchdog.borrow_mut().memory.end_frame(&lua);
+        assert!(report.cycle_finished);
+        assert!(lua.used_memory() < before);
+
+        // Holding on to more than the cap fails with a memory error
+        let hoard: mlua::Function = lua
+            .load("return function() local t = {} for i = 1, 1e7 do t[i] = {i} end return t end")
+            .eval()
+            .unwrap();
+        let err = guarded(&watchdog, Some(entity), "update", || hoard.call::<mlua::Table>(())).unwrap_err();
+        assert!(matches!(err, mlua::Error::MemoryError(_)), "unexpected error: {}", err);
+    }
+}
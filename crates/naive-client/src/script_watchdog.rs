@@ -0,0 +1,289 @@
+//! Per-frame instruction and time budgets for Lua scripts.
+//!
+//! A Lua count hook fires every `CHECK_INTERVAL` VM instructions while a guarded
+//! call runs. When an entity's usage this frame goes over budget, the hook
+//! raises an error that unwinds the offending script, so a `while true do end`
+//! in `update()` costs one frame instead of hanging the engine. Entities that
+//! blow the budget `max_violations` times are disabled until their script is
+//! reloaded. Violations are queued as `WatchdogEvent`s for the engine to
+//! surface on the HUD, the event bus and the command socket. Guarded calls
+//! also measure the bytes they allocate for `script_memory`.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use mlua::prelude::*;
+
+/// VM instructions between two budget checks.
+pub const CHECK_INTERVAL: u32 = 1000;
+/// Default instruction budget per entity per frame.
+pub const DEFAULT_MAX_INSTRUCTIONS: u64 = 10_000_000;
+/// Default wall-clock budget per entity per frame, in milliseconds.
+pub const DEFAULT_MAX_TIME_MS: f32 = 100.0;
+/// Default number of violations before a script is disabled.
+pub const DEFAULT_MAX_VIOLATIONS: u32 = 3;
+
+/// Which budget a script ran over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetKind {
+    Instructions,
+    Time,
+}
+
+impl BudgetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetKind::Instructions => "instructions",
+            BudgetKind::Time => "time",
+        }
+    }
+}
+
+/// A script that was aborted for exceeding its budget.
+#[derive(Debug, Clone)]
+pub struct WatchdogEvent {
+    /// Entity whose hook was aborted (`None` for entity-less callbacks).
+    pub entity: Option<hecs::Entity>,
+    pub hook: String,
+    pub kind: BudgetKind,
+    /// Violations so far for this entity.
+    pub violations: u32,
+    /// Whether the entity's script is now disabled.
+    pub disabled: bool,
+}
+
+/// A guarded call in progress.
+struct ActiveCall {
+    entity: Option<hecs::Entity>,
+    started: Instant,
+    instructions: u64,
+    /// Set once the hook has aborted this call; later checks keep erroring so
+    /// a `pcall` inside the runaway loop cannot swallow the abort.
+    aborted: Option<BudgetKind>,
+    /// VM memory when the call started.
+    start_memory: usize,
+    /// Bytes allocated by nested guarded calls, attributed to their own entities.
+    child_bytes: u64,
+}
+
+/// Budget settings plus per-frame usage and violation counts.
+pub struct ScriptWatchdog {
+    pub max_instructions: u64,
+    pub max_time: Duration,
+    pub max_violations: u32,
+    /// Memory cap, GC scheduling and per-entity allocation stats.
+    pub memory: crate::script_memory::ScriptMemory,
+    /// The guarded VM, for memory readings (weak: the VM owns the hook that owns us).
+    lua: Option<WeakLua>,
+    calls: Vec<ActiveCall>,
+    /// (instructions, time) used this frame by each entity, from finished calls.
+    frame_usage: HashMap<hecs::Entity, (u64, Duration)>,
+    violations: HashMap<hecs::Entity, u32>,
+    disabled: HashSet<hecs::Entity>,
+    events: Vec<WatchdogEvent>,
+}
+
+impl ScriptWatchdog {
+    pub fn new() -> Self {
+        Self {
+            max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+            max_time: Duration::from_secs_f32(DEFAULT_MAX_TIME_MS / 1000.0),
+            max_violations: DEFAULT_MAX_VIOLATIONS,
+            memory: crate::script_memory::ScriptMemory::new(),
+            lua: None,
+            calls: Vec::new(),
+            frame_usage: HashMap::new(),
+            violations: HashMap::new(),
+            disabled: HashSet::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Reset per-frame usage. Call once at the start of each frame.
+    pub fn begin_frame(&mut self) {
+        self.frame_usage.clear();
+    }
+
+    pub fn is_disabled(&self, entity: hecs::Entity) -> bool {
+        self.disabled.contains(&entity)
+    }
+
+    /// Forget violations for an entity (its script was reloaded or it was destroyed).
+    pub fn reset_entity(&mut self, entity: hecs::Entity) {
+        self.violations.remove(&entity);
+        self.disabled.remove(&entity);
+        self.frame_usage.remove(&entity);
+        self.memory.reset_entity(entity);
+    }
+
+    /// Violation counts per entity, with whether each is disabled.
+    pub fn violations(&self) -> impl Iterator<Item = (hecs::Entity, u32, bool)> + '_ {
+        self.violations.iter().map(|(&e, &n)| (e, n, self.disabled.contains(&e)))
+    }
+
+    pub fn disabled_count(&self) -> usize {
+        self.disabled.len()
+    }
+
+    pub fn take_events(&mut self) -> Vec<WatchdogEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Current VM memory, or 0 if no VM is attached.
+    fn used_memory(&self) -> usize {
+        self.lua.as_ref().and_then(|l| l.try_upgrade()).map(|l| l.used_memory()).unwrap_or(0)
+    }
+
+    fn begin_call(&mut self, entity: Option<hecs::Entity>) {
+        let start_memory = self.used_memory();
+        self.calls.push(ActiveCall {
+            entity,
+            started: Instant::now(),
+            instructions: 0,
+            aborted: None,
+            start_memory,
+            child_bytes: 0,
+        });
+    }
+
+    /// Finish the innermost call. Returns the budget it was aborted for, if any.
+    fn end_call(&mut self) -> Option<BudgetKind> {
+        let call = self.calls.pop()?;
+        // A collection during the call can make this negative; count it as nothing
+        let bytes = self.used_memory().saturating_sub(call.start_memory) as u64;
+        if let Some(parent) = self.calls.last_mut() {
+            parent.child_bytes += bytes;
+        }
+        if let Some(entity) = call.entity {
+            let usage = self.frame_usage.entry(entity).or_default();
+            usage.0 += call.instructions;
+            usage.1 += call.started.elapsed();
+            self.memory.record(entity, bytes.saturating_sub(call.child_bytes));
+        }
+        call.aborted
+    }
+
+    /// Count-hook check: account for `CHECK_INTERVAL` instructions and return
+    /// the exceeded budget, if any.
+    fn check(&mut self) -> Option<BudgetKind> {
+        let (max_instructions, max_time) = (self.max_instructions, self.max_time);
+        let call = self.calls.last_mut()?;
+        if call.aborted.is_some() {
+            return call.aborted;
+        }
+        call.instructions += CHECK_INTERVAL as u64;
+        let (prev_instructions, prev_time) = call
+            .entity
+            .and_then(|e| self.frame_usage.get(&e).copied())
+            .unwrap_or_default();
+        let exceeded = if prev_instructions + call.instructions > max_instructions {
+            Some(BudgetKind::Instructions)
+        } else if prev_time + call.started.elapsed() > max_time {
+            Some(BudgetKind::Time)
+        } else {
+            None
+        };
+        call.aborted = exceeded;
+        exceeded
+    }
+
+    /// Record a violation and queue its event.
+    fn record_violation(&mut self, entity: Option<hecs::Entity>, hook: &str, kind: BudgetKind) {
+        let (violations, disabled) = match entity {
+            Some(entity) => {
+                let count = self.violations.entry(entity).or_insert(0);
+                *count += 1;
+                let count = *count;
+                if count >= self.max_violations {
+                    self.disabled.insert(entity);
+                }
+                (count, self.disabled.contains(&entity))
+            }
+            None => (1, false),
+        };
+        self.events.push(WatchdogEvent { entity, hook: hook.to_string(), kind, violations, disabled });
+    }
+}
+
+impl Default for ScriptWatchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type SharedScriptWatchdog = std::rc::Rc<std::cell::RefCell<ScriptWatchdog>>;
+
+/// Install the count hook that enforces the watchdog's budgets on `lua`.
+pub fn install(lua: &Lua, watchdog: SharedScriptWatchdog) {
+    watchdog.borrow_mut().lua = Some(lua.weak());
+    let triggers = LuaHookTriggers::new().every_nth_instruction(CHECK_INTERVAL);
+    lua.set_hook(triggers, move |_, _| {
+        // Hooks can fire while Rust code holds the watchdog (e.g. nested calls
+        // set up from a Lua API function); skip that check rather than panic.
+        let exceeded = match watchdog.try_borrow_mut() {
+            Ok(mut w) => w.check(),
+            Err(_) => None,
+        };
+        match exceeded {
+            Some(kind) => Err(LuaError::RuntimeError(format!(
+                "script exceeded its per-frame {} budget and was aborted",
+                kind.as_str()
+            ))),
+            None => Ok(LuaVmState::Continue),
+        }
+    });
+}
+
+/// Run `f` as a guarded call for `entity`. If the watchdog aborted it, the
+/// violation is recorded before the error is returned.
+pub fn guarded<R>(
+    watchdog: &SharedScriptWatchdog,
+    entity: Option<hecs::Entity>,
+    hook: &str,
+    f: impl FnOnce() -> LuaResult<R>,
+) -> LuaResult<R> {
+    watchdog.borrow_mut().begin_call(entity);
+    let result = f();
+    let mut w = watchdog.borrow_mut();
+    if let Some(kind) = w.end_call() {
+        w.record_violation(entity, hook, kind);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runaway_loop_is_aborted_then_disabled() {
+        let lua = Lua::new();
+        let watchdog: SharedScriptWatchdog = std::rc::Rc::new(std::cell::RefCell::new(ScriptWatchdog::new()));
+        watchdog.borrow_mut().max_instructions = 100_000;
+        watchdog.borrow_mut().max_violations = 2;
+        install(&lua, watchdog.clone());
+
+        let mut world = hecs::World::new();
+        let entity = world.spawn(());
+        let spin: LuaFunction = lua.load("return function() while true do pcall(function() end) end end").eval().unwrap();
+        let quick: LuaFunction = lua.load("return function() local x = 0 for i = 1, 100 do x = x + i end return x end").eval().unwrap();
+
+        // A cheap call stays within budget
+        let sum: i64 = guarded(&watchdog, Some(entity), "update", || quick.call(())).unwrap();
+        assert_eq!(sum, 5050);
+
+        for expected in 1..=2 {
+            watchdog.borrow_mut().begin_frame();
+            let err = guarded(&watchdog, Some(entity), "update", || spin.call::<()>(())).unwrap_err();
+            assert!(err.to_string().contains("instructions budget"));
+            let events = watchdog.borrow_mut().take_events();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].violations, expected);
+            assert_eq!(events[0].kind, BudgetKind::Instructions);
+        }
+        assert!(watchdog.borrow().is_disabled(entity));
+
+        watchdog.borrow_mut().reset_entity(entity);
+        assert!(!watchdog.borrow().is_disabled(entity));
+    }
+}
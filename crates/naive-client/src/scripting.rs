@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
@@ -27,6 +27,31 @@ pub type SharedCameraShakeState = Rc<RefCell<CameraShakeState>>;
 pub type SharedEventBus = Rc<RefCell<EventBus>>;
 pub type SharedAudioSystem = Rc<RefCell<AudioSystem>>;
 pub type SharedParticleSystem = Rc<RefCell<crate::particles::ParticleSystem>>;
+pub type SharedMessageQueue = Rc<RefCell<crate::messaging::MessageQueue>>;
+pub type SharedGameClock = Rc<RefCell<crate::time_scale::GameClock>>;
+pub type SharedScreenshotQueue = Rc<RefCell<crate::screenshot::ScreenshotQueue>>;
+/// Render parameter changes from `render.set`, applied by the engine before the next frame.
+pub type SharedRenderTweaks = Rc<RefCell<Vec<(String, f32)>>>;
+/// Post-process pass parameters from `render.set_param`, written into the pipeline before the next frame.
+pub type SharedPassParams = Rc<RefCell<crate::pipeline::PassParams>>;
+/// The compiled pipeline file and the switch requested by `pipeline.switch`.
+pub type SharedPipelineSwitch = Rc<RefCell<crate::pipeline::PipelineSwitch>>;
+/// Curve assets behind the `curve` API, cached until a file in `curves/` changes.
+pub type SharedCurves = Rc<RefCell<crate::curves::CurveLibrary>>;
+/// Flock group parameters from `crowd.set_params` / `crowd.set_goal`.
+pub type SharedCrowd = Rc<RefCell<crate::crowd::CrowdSystem>>;
+/// Gameplay noises from `noise.emit`, `audio.play_sfx` and the engine.
+pub type SharedNoise = Rc<RefCell<crate::noise::NoiseChannel>>;
+/// The project's mods, for the `mods` API.
+pub type SharedMods = Rc<RefCell<crate::mods::ModSet>>;
+/// Cutscene playback from `sequence.play`, advanced by the engine each frame.
+pub type SharedSequences = Rc<RefCell<crate::sequence::SequencePlayer>>;
+/// Camera path playback from `camera.play_path`, advanced by the engine each frame.
+pub type SharedCameraPaths = Rc<RefCell<crate::camera_path::CameraPathPlayer>>;
+/// The Steam connection behind the `steam` API.
+pub type SharedSteam = Rc<RefCell<crate::steam::SteamSession>>;
+/// The playtest event sink behind the `analytics` API.
+pub type SharedAnalytics = Rc<RefCell<crate::analytics::Analytics>>;
 
 /// Script component attached to entities.
 #[derive(Debug, Clone)]
@@ -61,6 +86,14 @@ pub struct ScriptRuntime {
     pub entity_envs: HashMap<hecs::Entity, LuaRegistryKey>,
     /// Cached script sources for hot-reload comparison.
     pub script_sources: HashMap<PathBuf, String>,
+    /// String id of the entity whose hook is running, used as the `msg` sender.
+    pub current_entity: Rc<RefCell<Option<String>>>,
+    /// Per-frame instruction/time budgets enforced on every hook call.
+    pub watchdog: crate::script_watchdog::SharedScriptWatchdog,
+    /// Precompiled scripts of a shipped build, looked up before the files.
+    pub bundle: Option<crate::script_bundle::ScriptBundle>,
+    /// Mod folders searched before the project for script files.
+    pub overlay: crate::mods::ModOverlay,
 }
 
 impl ScriptRuntime {
@@ -73,11 +106,28 @@ impl ScriptRuntime {
         lua.globals().set("loadfile", LuaNil).unwrap_or(());
         lua.globals().set("dofile", LuaNil).unwrap_or(());
 
+        let watchdog = Rc::new(RefCell::new(crate::script_watchdog::ScriptWatchdog::new()));
+        crate::script_watchdog::install(&lua, watchdog.clone());
+
         Self {
             lua,
             entity_envs: HashMap::new(),
             script_sources: HashMap::new(),
+            current_entity: Rc::new(RefCell::new(None)),
+            watchdog,
+            bundle: None,
+            overlay: Default::default(),
+        }
+    }
+
+    /// Use the project's `logic.bundle` if it has one. Returns whether a
+    /// bundle was loaded.
+    pub fn load_bundle(&mut self, project_root: &Path) -> Result<bool, String> {
+        self.bundle = crate::script_bundle::ScriptBundle::load(project_root, &self.lua)?;
+        if let Some(bundle) = &self.bundle {
+            tracing::info!("Loaded script bundle ({} scripts)", bundle.chunks.len());
         }
+        Ok(self.bundle.is_some())
     }
 
     /// Load and initialize a script for an entity.
@@ -87,11 +137,16 @@ impl ScriptRuntime {
         project_root: &Path,
         source: &Path,
     ) -> Result<(), String> {
-        let full_path = project_root.join(source);
-        let code = std::fs::read_to_string(&full_path)
-            .map_err(|e| format!("Failed to read script {:?}: {}", full_path, e))?;
-
-        self.script_sources.insert(source.to_path_buf(), code.clone());
+        let code = match self.bundle.as_ref().and_then(|b| b.get(source)) {
+            Some(bytecode) => bytecode.to_vec(),
+            None => {
+                let full_path = self.overlay.resolve(project_root, source);
+                let code = std::fs::read_to_string(&full_path)
+                    .map_err(|e| format!("Failed to read script {:?}: {}", full_path, e))?;
+                self.script_sources.insert(source.to_path_buf(), code.clone());
+                code.into_bytes()
+            }
+        };
 
         // Create per-entity environment table
         let env = self.lua.create_table().map_err(|e| e.to_string())?;
@@ -110,11 +165,13 @@ impl ScriptRuntime {
         let entity_table = self.lua.create_table().map_err(|e| e.to_string())?;
         env.set("self", entity_table).map_err(|e| e.to_string())?;
 
+        // A fresh script gets a clean watchdog record
+        self.watchdog.borrow_mut().reset_entity(entity);
+
         // Load and execute the script in the environment
-        let chunk = self.lua.load(&code).set_name(source.to_string_lossy());
-        chunk
-            .set_environment(env.clone())
-            .exec()
+        let chunk = self.lua.load(code.as_slice()).set_name(source.to_string_lossy());
+        let chunk = chunk.set_environment(env.clone());
+        crate::script_watchdog::guarded(&self.watchdog, Some(entity), "load", || chunk.exec())
             .map_err(|e| format!("Script error in {:?}: {}", source, e))?;
 
         // Store the environment
@@ -155,6 +212,11 @@ impl ScriptRuntime {
         self.call_hook(entity, "on_trigger_enter", other_entity_id.to_string());
     }
 
+    /// Call the `on_trigger_stay` hook with the seconds since the last stay call.
+    pub fn call_on_trigger_stay(&self, entity: hecs::Entity, other_entity_id: &str, elapsed: f32) {
+        self.call_hook(entity, "on_trigger_stay", (other_entity_id.to_string(), elapsed));
+    }
+
     /// Call the `on_trigger_exit` hook.
     pub fn call_on_trigger_exit(&self, entity: hecs::Entity, other_entity_id: &str) {
         self.call_hook(entity, "on_trigger_exit", other_entity_id.to_string());
@@ -170,21 +232,115 @@ impl ScriptRuntime {
         self.call_hook(entity, "on_death", ());
     }
 
+    /// Call the `on_message` hook with `(name, data, sender, request_id)` and
+    /// return the handler's result (its reply, for requests).
+    pub fn call_on_message(&self, entity: hecs::Entity, message: &crate::messaging::Message) -> Result<LuaValue, String> {
+        let env = self.entity_env(entity)
+            .ok_or_else(|| format!("'{}' has no script", message.target))?;
+        let func: LuaFunction = env.get("on_message")
+            .map_err(|_| format!("'{}' has no on_message handler", message.target))?;
+        let data: LuaValue = match &message.data {
+            Some(key) => self.lua.registry_value(key).map_err(|e| e.to_string())?,
+            None => LuaNil,
+        };
+        if self.watchdog.borrow().is_disabled(entity) {
+            return Err(format!("'{}' script is disabled by the watchdog", message.target));
+        }
+        let string_id: Option<String> = env.get("_entity_string_id").ok();
+        self.with_current_entity(string_id, || {
+            crate::script_watchdog::guarded(&self.watchdog, Some(entity), "on_message", || {
+                func.call::<LuaValue>((message.name.clone(), data, message.sender.clone(), message.request_id))
+            })
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Run a `msg.request` callback with `(reply, nil)` or `(nil, error)`.
+    pub fn call_request_callback(&self, request: &crate::messaging::PendingRequest, reply: crate::messaging::Reply) {
+        let func: LuaFunction = match self.lua.registry_value(&request.callback) {
+            Ok(f) => f,
+            Err(_) => return,
+        };
+        let args: (LuaValue, Option<String>) = match reply {
+            crate::messaging::Reply::Value(Some(key)) => (self.lua.registry_value(&key).unwrap_or(LuaNil), None),
+            crate::messaging::Reply::Value(None) => (LuaNil, None),
+            crate::messaging::Reply::Error(e) => (LuaNil, Some(e)),
+        };
+        let call = || crate::script_watchdog::guarded(&self.watchdog, None, "msg.request callback", || func.call::<()>(args));
+        if let Err(e) = self.with_current_entity(request.sender.clone(), call) {
+            tracing::error!("Script error in msg.request callback ('{}' -> '{}'): {}", request.name, request.target, e);
+        }
+    }
+
+    /// Cap VM memory at `limit_mb` (0 = unlimited) and move garbage
+    /// collection to `collect_garbage`, `gc_budget_ms` per frame. Unset
+    /// limits use `DEFAULT_MEMORY_LIMIT_MB` and `DEFAULT_GC_BUDGET_MS`.
+    pub fn configure_memory(&self, limit_mb: Option<u64>, gc_budget_ms: Option<f32>) -> Result<(), String> {
+        self.watchdog.borrow_mut().memory.configure(
+            &self.lua,
+            limit_mb.unwrap_or(crate::script_memory::DEFAULT_MEMORY_LIMIT_MB),
+            gc_budget_ms.unwrap_or(crate::script_memory::DEFAULT_GC_BUDGET_MS),
+        )
+    }
+
+    /// End-of-frame incremental GC within the configured budget.
+    pub fn collect_garbage(&self) -> crate::script_memory::GcReport {
+        self.watchdog.borrow_mut().memory.end_frame(&self.lua)
+    }
+
+    /// Table entries reachable from an entity's `self` state, up to `cap`.
+    /// A count that keeps climbing usually means the script is leaking.
+    pub fn state_entries(&self, entity: hecs::Entity, cap: usize) -> Option<usize> {
+        let state: LuaTable = self.entity_env(entity)?.get("self").ok()?;
+        let mut visited = std::collections::HashSet::new();
+        let mut stack = vec![state];
+        let mut count = 0;
+        while let Some(table) = stack.pop() {
+            if !visited.insert(table.to_pointer()) {
+                continue;
+            }
+            for (_, value) in table.pairs::<LuaValue, LuaValue>().flatten() {
+                count += 1;
+                if count >= cap {
+                    return Some(cap);
+                }
+                if let LuaValue::Table(child) = value {
+                    stack.push(child);
+                }
+            }
+        }
+        Some(count)
+    }
+
+    fn entity_env(&self, entity: hecs::Entity) -> Option<LuaTable> {
+        let key = self.entity_envs.get(&entity)?;
+        self.lua.registry_value(key).ok()
+    }
+
+    /// Run `f` with `current_entity` set, restoring the previous value afterwards.
+    fn with_current_entity<R>(&self, string_id: Option<String>, f: impl FnOnce() -> R) -> R {
+        let previous = self.current_entity.replace(string_id);
+        let result = f();
+        *self.current_entity.borrow_mut() = previous;
+        result
+    }
+
     /// Internal: call a named function in an entity's environment.
     fn call_hook<A: IntoLuaMulti>(&self, entity: hecs::Entity, name: &str, args: A) {
-        let key = match self.entity_envs.get(&entity) {
-            Some(k) => k,
+        if self.watchdog.borrow().is_disabled(entity) {
+            return;
+        }
+        let env = match self.entity_env(entity) {
+            Some(t) => t,
             None => return,
         };
-        let env: LuaTable = match self.lua.registry_value(key) {
-            Ok(t) => t,
-            Err(_) => return,
-        };
         let func: LuaFunction = match env.get(name) {
             Ok(f) => f,
             Err(_) => return, // Hook not defined, that's fine
         };
-        if let Err(e) = func.call::<()>(args) {
+        let string_id: Option<String> = env.get("_entity_string_id").ok();
+        let call = || crate::script_watchdog::guarded(&self.watchdog, Some(entity), name, || func.call::<()>(args));
+        if let Err(e) = self.with_current_entity(string_id, call) {
             tracing::error!("Script error in {:?}.{}: {}", entity, name, e);
         }
     }
@@ -293,9 +449,12 @@ impl ScriptRuntime {
         let globals = self.lua.globals();
         let physics_table = self.lua.create_table().map_err(|e| e.to_string())?;
 
-        let pw = physics.clone();
-        let raycast_fn = self.lua.create_function(move |_, (ox, oy, oz, dx, dy, dz, max_dist): (f32, f32, f32, f32, f32, f32, f32)| {
-            match pw.borrow().raycast(Vec3::new(ox, oy, oz), Vec3::new(dx, dy, dz), max_dist) {
+        // physics.raycast(ox, oy, oz, dx, dy, dz, max_dist, filter?) -> (hit, distance, nx, ny, nz)
+        // filter: { exclude = id|{ids}, include_tags = tag|{tags}, exclude_tags = tag|{tags}, triggers = bool }
+        let pw = physics.clone(); let sw = scene_world.clone();
+        let raycast_fn = self.lua.create_function(move |_, (ox, oy, oz, dx, dy, dz, max_dist, filter): (f32, f32, f32, f32, f32, f32, f32, Option<LuaTable>)| {
+            let filter = lua_query_filter(&sw.borrow(), filter.as_ref())?;
+            match pw.borrow().raycast(Vec3::new(ox, oy, oz), Vec3::new(dx, dy, dz), max_dist, &filter) {
                 Some((_entity, distance, normal)) => {
                     Ok((true, distance, normal.x, normal.y, normal.z))
                 }
@@ -304,16 +463,17 @@ impl ScriptRuntime {
         }).map_err(|e| e.to_string())?;
         physics_table.set("raycast", raycast_fn).map_err(|e| e.to_string())?;
 
-        // physics.hitscan(ox, oy, oz, dx, dy, dz, range) -> (hit, entity_id, distance, hx, hy, hz, nx, ny, nz)
+        // physics.hitscan(ox, oy, oz, dx, dy, dz, range, filter?) -> (hit, entity_id, distance, hx, hy, hz, nx, ny, nz)
         let pw = physics.clone(); let sw = scene_world.clone();
-        let hitscan_fn = self.lua.create_function(move |_, (ox, oy, oz, dx, dy, dz, range): (f32, f32, f32, f32, f32, f32, f32)| {
+        let hitscan_fn = self.lua.create_function(move |_, (ox, oy, oz, dx, dy, dz, range, filter): (f32, f32, f32, f32, f32, f32, f32, Option<LuaTable>)| {
             let pw = pw.borrow();
             let sw = sw.borrow();
+            let filter = lua_query_filter(&sw, filter.as_ref())?;
             match pw.raycast_detailed(
                 Vec3::new(ox, oy, oz),
                 Vec3::new(dx, dy, dz),
                 range,
-                None,
+                &filter,
             ) {
                 Some((entity, distance, hit_point, normal)) => {
                     let entity_id = sw.entity_registry
@@ -414,6 +574,31 @@ impl ScriptRuntime {
         }).map_err(|e| e.to_string())?;
         physics_table.set("set_friction", set_friction_fn).map_err(|e| e.to_string())?;
 
+        // physics.volume_contents(trigger_id) -> { entity_id, ... } currently inside the trigger
+        let pw = physics.clone(); let sw = scene_world.clone();
+        let volume_contents_fn = self.lua.create_function(move |_, id: String| {
+            let pw = pw.borrow();
+            let sw = sw.borrow();
+            let trigger = match sw.entity_registry.get(&id) {
+                Some(&entity) => entity,
+                None => return Ok(Vec::new()),
+            };
+            let contents: Vec<String> = pw.volume_contents(trigger)
+                .into_iter()
+                .filter_map(|entity| sw.entity_registry.iter().find(|(_, &e)| e == entity).map(|(id, _)| id.clone()))
+                .collect();
+            Ok(contents)
+        }).map_err(|e| e.to_string())?;
+        physics_table.set("volume_contents", volume_contents_fn).map_err(|e| e.to_string())?;
+
+        // physics.set_trigger_stay_interval(seconds) — how often on_trigger_stay fires
+        let pw = physics.clone();
+        let set_stay_interval_fn = self.lua.create_function(move |_, seconds: f32| {
+            pw.borrow_mut().trigger_stay_interval = seconds.max(0.0);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        physics_table.set("set_trigger_stay_interval", set_stay_interval_fn).map_err(|e| e.to_string())?;
+
         globals.set("physics", physics_table).map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -617,6 +802,51 @@ impl ScriptRuntime {
         }).map_err(|e| e.to_string())?;
         entity_table.set("set_base_color", set_base_color_fn).map_err(|e| e.to_string())?;
 
+        // entity.get_splat_opacity(id) -> opacity, or nil without a splat
+        let sw = scene_world.clone();
+        let get_splat_opacity_fn = self.lua.create_function(move |_, id: String| {
+            let sw = sw.borrow();
+            if let Some(&entity) = sw.entity_registry.get(&id) {
+                if let Ok(splat) = sw.world.get::<&crate::components::GaussianSplat>(entity) {
+                    return Ok(Some(splat.opacity));
+                }
+            }
+            Ok(None)
+        }).map_err(|e| e.to_string())?;
+        entity_table.set("get_splat_opacity", get_splat_opacity_fn).map_err(|e| e.to_string())?;
+
+        // entity.set_splat_opacity(id, opacity) -- 0 to 1, multiplies every splat's
+        let sw = scene_world.clone();
+        let set_splat_opacity_fn = self.lua.create_function(move |_, (id, opacity): (String, f32)| {
+            let sw = sw.borrow_mut();
+            if let Some(&entity) = sw.entity_registry.get(&id) {
+                if let Ok(mut splat) = sw.world.get::<&mut crate::components::GaussianSplat>(entity) {
+                    splat.opacity = opacity.clamp(0.0, 1.0);
+                }
+            }
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        entity_table.set("set_splat_opacity", set_splat_opacity_fn).map_err(|e| e.to_string())?;
+
+        // entity.set_splat_crop(id, {{shape=, center=, size=, invert=}, ...})
+        // -- same fields as `crop:` in scene YAML; an empty list uncrops
+        let sw = scene_world.clone();
+        let set_splat_crop_fn = self.lua.create_function(move |lua, (id, crops): (String, LuaTable)| {
+            let defs: Vec<crate::scene::SplatCropDef> = lua
+                .from_value(LuaValue::Table(crops))
+                .map_err(|e| mlua::Error::runtime(format!("set_splat_crop: invalid crop: {}", e)))?;
+            let crops = crate::world::splat_crops_from_defs(&defs)
+                .map_err(|e| mlua::Error::runtime(format!("set_splat_crop: {}", e)))?;
+            let sw = sw.borrow_mut();
+            if let Some(&entity) = sw.entity_registry.get(&id) {
+                if let Ok(mut splat) = sw.world.get::<&mut crate::components::GaussianSplat>(entity) {
+                    splat.crops = crops;
+                }
+            }
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        entity_table.set("set_splat_crop", set_splat_crop_fn).map_err(|e| e.to_string())?;
+
         // entity.get_health(id) -> current, max
         let sw = scene_world.clone();
         let get_health_fn = self.lua.create_function(move |_, id: String| {
@@ -812,30 +1042,72 @@ impl ScriptRuntime {
         let globals = self.lua.globals();
         let events_table = self.lua.create_table().map_err(|e| e.to_string())?;
 
-        // events.emit(event_type, data_table)
+        // events.emit(event_type, data_table) — validated against events/schema.yaml
         let bus = event_bus.clone();
-        let emit_fn = self.lua.create_function(move |_, (event_type, data): (String, LuaTable)| {
+        let emit_fn = self.lua.create_function(move |lua, (event_type, data): (String, Option<LuaTable>)| {
+            let map = lua_table_to_event_data(data);
             let mut bus = bus.borrow_mut();
-            let mut map = HashMap::new();
-            for pair in data.pairs::<String, LuaValue>() {
-                if let Ok((key, val)) = pair {
-                    let json_val = match val {
-                        LuaValue::Integer(i) => serde_json::Value::Number(serde_json::Number::from(i)),
-                        LuaValue::Number(n) => serde_json::Number::from_f64(n)
-                            .map(serde_json::Value::Number)
-                            .unwrap_or(serde_json::Value::Null),
-                        LuaValue::String(s) => serde_json::Value::String(s.to_string_lossy().to_string()),
-                        LuaValue::Boolean(b) => serde_json::Value::Bool(b),
-                        _ => serde_json::Value::Null,
-                    };
-                    map.insert(key, json_val);
-                }
+            if let Err(e) = bus.validate(&event_type, &map) {
+                return Err(LuaError::RuntimeError(format!("events.emit: {}{}", e, lua_caller_location(lua))));
             }
             bus.emit(&event_type, map);
             Ok(())
         }).map_err(|e| e.to_string())?;
         events_table.set("emit", emit_fn).map_err(|e| e.to_string())?;
 
+        // events.emitter(event_type) -> function(data_table)
+        // Typed emitter bound to one schema entry; unknown event types fail at creation.
+        let bus = event_bus.clone();
+        let emitter_fn = self.lua.create_function(move |lua, event_type: String| {
+            {
+                let bus = bus.borrow();
+                if let Some(schema) = bus.schema() {
+                    if !schema.events.contains_key(&event_type) {
+                        return Err(LuaError::RuntimeError(format!(
+                            "events.emitter: unknown event type '{}'{}",
+                            event_type,
+                            lua_caller_location(lua)
+                        )));
+                    }
+                }
+            }
+            let bus = bus.clone();
+            lua.create_function(move |lua, data: Option<LuaTable>| {
+                let map = lua_table_to_event_data(data);
+                let mut bus = bus.borrow_mut();
+                if let Err(e) = bus.validate(&event_type, &map) {
+                    return Err(LuaError::RuntimeError(format!("{}{}", e, lua_caller_location(lua))));
+                }
+                bus.emit(&event_type, map);
+                Ok(())
+            })
+        }).map_err(|e| e.to_string())?;
+        events_table.set("emitter", emitter_fn).map_err(|e| e.to_string())?;
+
+        // events.schema() -> { [event_type] = { description, fields = { {name, type}, ... } } }
+        let bus = event_bus.clone();
+        let schema_fn = self.lua.create_function(move |lua, ()| {
+            let result = lua.create_table()?;
+            let bus = bus.borrow();
+            if let Some(schema) = bus.schema() {
+                for (event_type, event_schema) in &schema.events {
+                    let entry = lua.create_table()?;
+                    entry.set("description", event_schema.description.clone())?;
+                    let fields = lua.create_table()?;
+                    for (i, field) in event_schema.fields.iter().enumerate() {
+                        let f = lua.create_table()?;
+                        f.set("name", field.name.clone())?;
+                        f.set("type", field.field_type.as_str())?;
+                        fields.set(i + 1, f)?;
+                    }
+                    entry.set("fields", fields)?;
+                    result.set(event_type.clone(), entry)?;
+                }
+            }
+            Ok(result)
+        }).map_err(|e| e.to_string())?;
+        events_table.set("schema", schema_fn).map_err(|e| e.to_string())?;
+
         // events.on(event_type, callback) -> listener_id
         let listeners = lua_listeners.clone();
         let next_id = next_listener_id.clone();
@@ -887,15 +1159,105 @@ impl ScriptRuntime {
         Ok(())
     }
 
+    /// Register the `msg` API for directed entity messages.
+    pub fn register_message_api(&self, queue: SharedMessageQueue) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let msg_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // msg.send(target_id, name, data?) — delivered to the target's on_message hook
+        let q = queue.clone();
+        let current = self.current_entity.clone();
+        let send_fn = self.lua.create_function(move |lua, (target, name, data): (String, String, LuaValue)| {
+            let data = match data {
+                LuaNil => None,
+                v => Some(lua.create_registry_value(v)?),
+            };
+            q.borrow_mut().send(crate::messaging::Message {
+                target,
+                sender: current.borrow().clone(),
+                name,
+                data,
+                request_id: None,
+            });
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        msg_table.set("send", send_fn).map_err(|e| e.to_string())?;
+
+        // msg.request(target_id, name, data, callback, timeout?) -> request_id
+        // callback(reply, err): err is nil on success, "timeout" or a delivery error otherwise.
+        let q = queue.clone();
+        let current = self.current_entity.clone();
+        let request_fn = self.lua.create_function(
+            move |lua, (target, name, data, callback, timeout): (String, String, LuaValue, LuaFunction, Option<f32>)| {
+                let sender = current.borrow().clone();
+                let data = match data {
+                    LuaNil => None,
+                    v => Some(lua.create_registry_value(v)?),
+                };
+                let mut q = q.borrow_mut();
+                let request_id = q.open_request(crate::messaging::PendingRequest {
+                    sender: sender.clone(),
+                    target: target.clone(),
+                    name: name.clone(),
+                    callback: lua.create_registry_value(callback)?,
+                    remaining: timeout.unwrap_or(crate::messaging::DEFAULT_REQUEST_TIMEOUT),
+                });
+                q.send(crate::messaging::Message {
+                    target,
+                    sender,
+                    name,
+                    data,
+                    request_id: Some(request_id),
+                });
+                Ok(request_id)
+            },
+        ).map_err(|e| e.to_string())?;
+        msg_table.set("request", request_fn).map_err(|e| e.to_string())?;
+
+        // msg.reply(request_id, data) -> bool — answer a request after on_message returned
+        let q = queue.clone();
+        let reply_fn = self.lua.create_function(move |lua, (request_id, data): (u64, LuaValue)| {
+            let data = match data {
+                LuaNil => None,
+                v => Some(lua.create_registry_value(v)?),
+            };
+            Ok(q.borrow_mut().reply(request_id, crate::messaging::Reply::Value(data)))
+        }).map_err(|e| e.to_string())?;
+        msg_table.set("reply", reply_fn).map_err(|e| e.to_string())?;
+
+        globals.set("msg", msg_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     /// Register audio API functions that control the audio system from Lua.
-    pub fn register_audio_api(&self, audio_system: SharedAudioSystem, project_root: PathBuf) -> Result<(), String> {
+    pub fn register_audio_api(
+        &self,
+        audio_system: SharedAudioSystem,
+        project_root: PathBuf,
+        noise: SharedNoise,
+        curves: SharedCurves,
+    ) -> Result<(), String> {
         let globals = self.lua.globals();
         let audio_table = self.lua.create_table().map_err(|e| e.to_string())?;
 
-        // audio.play_sfx(id, path, volume)
+        // audio.play_sfx(id, path, volume [, {x, y, z, loudness, tag, source}])
+        // With the table, AI also hears it as a noise (see noise.emit).
         let root1 = project_root.clone();
         let audio = audio_system.clone();
-        let play_sfx_fn = self.lua.create_function(move |_, (id, path, volume): (String, String, f32)| {
+        let play_sfx_fn = self.lua.create_function(move |_, (id, path, volume, heard): (String, String, f32, Option<LuaTable>)| {
+            if let Some(heard) = heard {
+                let position = glam::Vec3::new(
+                    heard.get::<f32>("x").unwrap_or(0.0),
+                    heard.get::<f32>("y").unwrap_or(0.0),
+                    heard.get::<f32>("z").unwrap_or(0.0),
+                );
+                let loudness: f32 = heard.get("loudness").map_err(|_| {
+                    LuaError::RuntimeError("audio.play_sfx: the noise table needs a loudness".into())
+                })?;
+                let tag = heard.get::<String>("tag").unwrap_or_else(|_| "sound".to_string());
+                let source: Option<String> = heard.get("source")?;
+                noise.borrow_mut().emit(crate::noise::NoiseEvent::new(position, loudness, tag).with_source(source));
+            }
             let mut audio = audio.borrow_mut();
             if let Err(e) = audio.play_sfx(&id, &root1, &path, volume) {
                 tracing::error!("[Lua] audio.play_sfx error: {}", e);
@@ -934,6 +1296,78 @@ impl ScriptRuntime {
         }).map_err(|e| e.to_string())?;
         audio_table.set("stop_music", stop_music_fn).map_err(|e| e.to_string())?;
 
+        // audio.fade(id, volume, seconds [, curve])  -- curve: name in curves/
+        let root3 = project_root.clone();
+        let lib = curves.clone();
+        let audio = audio_system.clone();
+        let fade_fn = self.lua.create_function(move |_, (id, volume, seconds, curve): (String, f32, f32, Option<String>)| {
+            let curve = match curve {
+                Some(name) => Some(lib.borrow_mut().get(&root3, &name).cloned().map_err(|e| {
+                    LuaError::RuntimeError(format!("audio.fade: {}", e))
+                })?),
+                None => None,
+            };
+            audio.borrow_mut().fade(Some(&id), volume, seconds, curve);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        audio_table.set("fade", fade_fn).map_err(|e| e.to_string())?;
+
+        // audio.fade_music(volume, seconds [, curve])
+        let root4 = project_root.clone();
+        let audio = audio_system.clone();
+        let fade_music_fn = self.lua.create_function(move |_, (volume, seconds, curve): (f32, f32, Option<String>)| {
+            let curve = match curve {
+                Some(name) => Some(curves.borrow_mut().get(&root4, &name).cloned().map_err(|e| {
+                    LuaError::RuntimeError(format!("audio.fade_music: {}", e))
+                })?),
+                None => None,
+            };
+            audio.borrow_mut().fade(None, volume, seconds, curve);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        audio_table.set("fade_music", fade_music_fn).map_err(|e| e.to_string())?;
+
+        // audio.list_devices() -> { name, ... }
+        let list_devices_fn = self.lua.create_function(|_, ()| Ok(crate::audio::output_devices()))
+            .map_err(|e| e.to_string())?;
+        audio_table.set("list_devices", list_devices_fn).map_err(|e| e.to_string())?;
+
+        // audio.get_device() -> name of the device playing, or nil
+        let audio = audio_system.clone();
+        let get_device_fn = self.lua.create_function(move |_, ()| {
+            Ok(audio.borrow().device_name().map(str::to_string))
+        }).map_err(|e| e.to_string())?;
+        audio_table.set("get_device", get_device_fn).map_err(|e| e.to_string())?;
+
+        // audio.set_device(name) -- nil follows the system default
+        let audio = audio_system.clone();
+        let set_device_fn = self.lua.create_function(move |_, device: Option<String>| {
+            let mut audio = audio.borrow_mut();
+            let settings = crate::audio::AudioSettings { device, ..audio.settings().clone() };
+            audio.configure(settings);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        audio_table.set("set_device", set_device_fn).map_err(|e| e.to_string())?;
+
+        // audio.set_buffer_size(frames) -- nil uses the driver default
+        let audio = audio_system.clone();
+        let set_buffer_size_fn = self.lua.create_function(move |_, frames: Option<u32>| {
+            if frames == Some(0) {
+                return Err(LuaError::RuntimeError("audio.set_buffer_size: frames must be positive".into()));
+            }
+            let mut audio = audio.borrow_mut();
+            let settings = crate::audio::AudioSettings { buffer_size: frames, ..audio.settings().clone() };
+            audio.configure(settings);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        audio_table.set("set_buffer_size", set_buffer_size_fn).map_err(|e| e.to_string())?;
+
+        // audio.get_latency() -> milliseconds per buffer, or nil with the driver default
+        let audio = audio_system.clone();
+        let get_latency_fn = self.lua.create_function(move |_, ()| Ok(audio.borrow().latency_ms()))
+            .map_err(|e| e.to_string())?;
+        audio_table.set("get_latency", get_latency_fn).map_err(|e| e.to_string())?;
+
         globals.set("audio", audio_table).map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -967,8 +1401,15 @@ impl ScriptRuntime {
         // config_table: { id, mesh, material, position={x,y,z}, scale={x,y,z},
         //   collider={shape, half_extents, radius, is_trigger, restitution, friction},
         //   rigid_body="dynamic"|"fixed"|"kinematic", script="logic/foo.lua" }
+        // or, with `components`, a full scene entity:
+        //   { id, tags={...}, extends="template_id", components={ transform=..., collider=..., ... } }
         let cmd = cmd_queue.clone();
-        let spawn_ex_fn = self.lua.create_function(move |_, tbl: LuaTable| {
+        let spawn_ex_fn = self.lua.create_function(move |lua, tbl: LuaTable| {
+            if tbl.contains_key("components")? {
+                let entity_def = entity_def_from_lua(lua, tbl)?;
+                cmd.borrow_mut().entity_spawns.push(entity_def);
+                return Ok(());
+            }
             let id: String = tbl.get("id").map_err(|e| mlua::Error::runtime(format!("spawn_ex: missing id: {}", e)))?;
             let mesh: String = tbl.get("mesh").unwrap_or_else(|_| "procedural:cube".to_string());
             let material: String = tbl.get("material").unwrap_or_else(|_| "procedural:default".to_string());
@@ -1013,8 +1454,8 @@ impl ScriptRuntime {
         }).map_err(|e| e.to_string())?;
         entity_table.set("spawn_ex", spawn_ex_fn).map_err(|e| e.to_string())?;
 
-        // entity.set_texture(id, slot, path) — swap albedo or normal texture at runtime
-        // slot: "albedo" or "normal"
+        // entity.set_texture(id, slot, path) — swap one of the material's texture maps at runtime
+        // slot: "albedo", "normal", "metallic_roughness" or "emissive"
         let cmd = cmd_queue.clone();
         let set_tex_fn = self.lua.create_function(move |_, (id, slot, path): (String, String, String)| {
             let mut cmd = cmd.borrow_mut();
@@ -1027,6 +1468,20 @@ impl ScriptRuntime {
         }).map_err(|e| e.to_string())?;
         entity_table.set("set_texture", set_tex_fn).map_err(|e| e.to_string())?;
 
+        // physics.set_collider(id, {shape="capsule", radius=0.3, half_height=0.6})
+        // Rebuilds the entity's collider on its existing body (applied next frame).
+        // Shape fields match the scene `collider` component; is_trigger, restitution
+        // and friction keep their current values when omitted.
+        if let Ok(physics_table) = globals.get::<LuaTable>("physics") {
+            let cmd = cmd_queue.clone();
+            let set_collider_fn = self.lua.create_function(move |lua, (id, tbl): (String, LuaTable)| {
+                let update = collider_update_from_lua(lua, id, tbl)?;
+                cmd.borrow_mut().collider_updates.push(update);
+                Ok(())
+            }).map_err(|e| e.to_string())?;
+            physics_table.set("set_collider", set_collider_fn).map_err(|e| e.to_string())?;
+        }
+
         // entity.destroy(id)
         let cmd = cmd_queue.clone();
         let destroy_fn = self.lua.create_function(move |_, id: String| {
@@ -1243,73 +1698,48 @@ impl ScriptRuntime {
         let globals = self.lua.globals();
         let mesh_table = self.lua.create_table().map_err(|e| e.to_string())?;
 
-        // mesh.create(name, vertices_table, indices_table, uvs_table)
+        // mesh.create(name, vertices, indices, uvs?, normals?)
         // vertices: {{x,y,z}, {x,y,z}, ...}
-        // indices: {0, 1, 2, ...}
-        // uvs: {{u,v}, {u,v}, ...} (must match vertex count)
+        // indices: {0, 1, 2, ...} (triangle list)
+        // uvs: {{u,v}, ...}, normals: {{x,y,z}, ...} (one per vertex)
+        // Creating an existing name replaces its geometry (re-uploaded next frame).
         let cmd = cmd_queue.clone();
-        let create_fn = self.lua.create_function(move |_, (name, verts_tbl, indices_tbl, uvs_tbl): (String, LuaTable, LuaTable, LuaTable)| {
-            let mut positions = Vec::new();
-            let mut normals = Vec::new();
-            let mut uvs = Vec::new();
-            let mut indices = Vec::new();
-
-            // Parse vertices
+        let create_fn = self.lua.create_function(move |_, (name, verts_tbl, indices_tbl, uvs_tbl, normals_tbl): (String, LuaTable, LuaTable, Option<LuaTable>, Option<LuaTable>)| {
+            let bad = |what: &str, e: LuaError| LuaError::runtime(format!("mesh.create: bad {}: {}", what, e));
+            let mut data = crate::mesh::MeshData::default();
             for v in verts_tbl.sequence_values::<LuaTable>() {
-                let v = v.map_err(|e| mlua::Error::runtime(format!("mesh.create: bad vertex: {}", e)))?;
-                positions.push([
+                let v = v.map_err(|e| bad("vertex", e))?;
+                data.positions.push([
                     v.get::<f32>(1).unwrap_or(0.0),
                     v.get::<f32>(2).unwrap_or(0.0),
                     v.get::<f32>(3).unwrap_or(0.0),
                 ]);
-                // Default normals pointing up
-                normals.push([0.0, 1.0, 0.0]);
             }
-
-            // Parse indices
             for idx in indices_tbl.sequence_values::<u32>() {
-                indices.push(idx.map_err(|e| mlua::Error::runtime(format!("mesh.create: bad index: {}", e)))?);
+                data.indices.push(idx.map_err(|e| bad("index", e))?);
             }
-
-            // Parse UVs
-            for uv in uvs_tbl.sequence_values::<LuaTable>() {
-                let uv = uv.map_err(|e| mlua::Error::runtime(format!("mesh.create: bad uv: {}", e)))?;
-                uvs.push([
-                    uv.get::<f32>(1).unwrap_or(0.0),
-                    uv.get::<f32>(2).unwrap_or(0.0),
-                ]);
+            if let Some(uvs_tbl) = uvs_tbl {
+                for uv in uvs_tbl.sequence_values::<LuaTable>() {
+                    let uv = uv.map_err(|e| bad("uv", e))?;
+                    data.uvs.push([uv.get::<f32>(1).unwrap_or(0.0), uv.get::<f32>(2).unwrap_or(0.0)]);
+                }
             }
-
-            // Pad UVs if fewer than positions
-            while uvs.len() < positions.len() {
-                uvs.push([0.0, 0.0]);
-            }
-
-            // Compute flat normals from triangles
-            let mut computed_normals = vec![glam::Vec3::ZERO; positions.len()];
-            for tri in indices.chunks(3) {
-                if tri.len() == 3 {
-                    let (i0, i1, i2) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
-                    if i0 < positions.len() && i1 < positions.len() && i2 < positions.len() {
-                        let v0 = glam::Vec3::from(positions[i0]);
-                        let v1 = glam::Vec3::from(positions[i1]);
-                        let v2 = glam::Vec3::from(positions[i2]);
-                        let face_normal = (v1 - v0).cross(v2 - v0);
-                        computed_normals[i0] += face_normal;
-                        computed_normals[i1] += face_normal;
-                        computed_normals[i2] += face_normal;
-                    }
+            if let Some(normals_tbl) = normals_tbl {
+                for n in normals_tbl.sequence_values::<LuaTable>() {
+                    let n = n.map_err(|e| bad("normal", e))?;
+                    data.normals.push([
+                        n.get::<f32>(1).unwrap_or(0.0),
+                        n.get::<f32>(2).unwrap_or(1.0),
+                        n.get::<f32>(3).unwrap_or(0.0),
+                    ]);
                 }
             }
-            normals = computed_normals.iter().map(|n| {
-                let n = n.normalize_or_zero();
-                if n == glam::Vec3::ZERO { [0.0, 1.0, 0.0] } else { n.to_array() }
-            }).collect();
+            data.validate().map_err(|e| LuaError::runtime(format!("mesh.create '{}': {}", name, e)))?;
 
             let mut cmd = cmd.borrow_mut();
-            cmd.mesh_creates.push(crate::world::MeshCreateCommand {
-                name, positions, normals, uvs, indices,
-            });
+            // A later create of the same name in this frame wins
+            cmd.mesh_creates.retain(|c| c.name != name);
+            cmd.mesh_creates.push(crate::world::MeshCreateCommand { name, data });
             Ok(())
         }).map_err(|e| e.to_string())?;
         mesh_table.set("create", create_fn).map_err(|e| e.to_string())?;
@@ -1400,11 +1830,45 @@ impl ScriptRuntime {
         Ok(())
     }
 
+    /// Register camera path playback (camera.play_path) for paths in `camera_paths/`.
+    pub fn register_camera_path_api(&self, camera_paths: SharedCameraPaths) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let camera_table: LuaTable = globals.get("camera").map_err(|e| e.to_string())?;
+
+        // camera.play_path(name, duration?) -- errors if the file is missing or invalid
+        let p = camera_paths.clone();
+        let play_fn = self.lua.create_function(move |_, (name, duration): (String, Option<f32>)| {
+            p.borrow_mut().play(&name, duration).map_err(LuaError::RuntimeError)
+        }).map_err(|e| e.to_string())?;
+        camera_table.set("play_path", play_fn).map_err(|e| e.to_string())?;
+
+        // camera.stop_path() -- hands the camera back now
+        let p = camera_paths.clone();
+        let stop_fn = self.lua.create_function(move |_, ()| {
+            p.borrow_mut().stop();
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        camera_table.set("stop_path", stop_fn).map_err(|e| e.to_string())?;
+
+        // camera.path_playing() -> name, seconds (nil when idle)
+        let playing_fn = self.lua.create_function(move |_, ()| {
+            let player = camera_paths.borrow();
+            Ok(match player.current() {
+                Some((name, time)) => (Some(name.to_string()), Some(time)),
+                None => (None, None),
+            })
+        }).map_err(|e| e.to_string())?;
+        camera_table.set("path_playing", playing_fn).map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
     /// Register particle system API (entity emitter control + one-shot bursts).
     pub fn register_particle_api(
         &self,
         scene_world: SharedSceneWorld,
         particle_system: SharedParticleSystem,
+        project_root: PathBuf,
     ) -> Result<(), String> {
         let globals = self.lua.globals();
         let entity_table: LuaTable = globals.get("entity").map_err(|e| e.to_string())?;
@@ -1451,44 +1915,72 @@ impl ScriptRuntime {
         // particles table for non-entity operations
         let particles_table = self.lua.create_table().map_err(|e| e.to_string())?;
 
-        // particles.spawn_burst(x, y, z, count, config_table)
+        // particles.spawn_burst(x, y, z, count, config_table | preset_name)
+        // preset_name loads effects/<name>.yaml
         let ps = particle_system.clone();
-        let spawn_burst_fn = self.lua.create_function(move |_, (x, y, z, count, config_tbl): (f32, f32, f32, u32, LuaTable)| {
+        let spawn_burst_fn = self.lua.create_function(move |_, (x, y, z, count, config): (f32, f32, f32, u32, LuaValue)| {
             let mut ps = ps.borrow_mut();
-            let config = crate::components::ParticleConfig {
-                max_particles: count * 2,
-                spawn_rate: 0.0,
-                lifetime: [
-                    config_tbl.get::<f32>("lifetime_min").unwrap_or(0.5),
-                    config_tbl.get::<f32>("lifetime_max").unwrap_or(1.5),
-                ],
-                initial_speed: [
-                    config_tbl.get::<f32>("speed_min").unwrap_or(1.0),
-                    config_tbl.get::<f32>("speed_max").unwrap_or(3.0),
-                ],
-                direction: glam::Vec3::new(
-                    config_tbl.get::<f32>("dir_x").unwrap_or(0.0),
-                    config_tbl.get::<f32>("dir_y").unwrap_or(1.0),
-                    config_tbl.get::<f32>("dir_z").unwrap_or(0.0),
-                ),
-                spread: config_tbl.get::<f32>("spread").unwrap_or(360.0),
-                size: [
-                    config_tbl.get::<f32>("size_start").unwrap_or(0.2),
-                    config_tbl.get::<f32>("size_end").unwrap_or(0.05),
-                ],
-                color_start: [
-                    config_tbl.get::<f32>("r").unwrap_or(1.0),
-                    config_tbl.get::<f32>("g").unwrap_or(1.0),
-                    config_tbl.get::<f32>("b").unwrap_or(1.0),
-                    config_tbl.get::<f32>("a").unwrap_or(1.0),
-                ],
-                color_end: [
-                    config_tbl.get::<f32>("r_end").unwrap_or(1.0),
-                    config_tbl.get::<f32>("g_end").unwrap_or(1.0),
-                    config_tbl.get::<f32>("b_end").unwrap_or(1.0),
-                    0.0,
-                ],
-                gravity_scale: config_tbl.get::<f32>("gravity_scale").unwrap_or(0.0),
+            let config = match config {
+                LuaValue::String(name) => ps
+                    .preset(&project_root, &name.to_str()?)
+                    .map_err(|e| LuaError::RuntimeError(format!("particles.spawn_burst: {}", e)))?,
+                LuaValue::Table(config_tbl) => {
+                    let r = config_tbl.get::<f32>("r").unwrap_or(1.0);
+                    let g = config_tbl.get::<f32>("g").unwrap_or(1.0);
+                    let b = config_tbl.get::<f32>("b").unwrap_or(1.0);
+                    crate::components::ParticleConfig {
+                        max_particles: count * 2,
+                        spawn_rate: 0.0,
+                        lifetime: [
+                            config_tbl.get::<f32>("lifetime_min").unwrap_or(0.5),
+                            config_tbl.get::<f32>("lifetime_max").unwrap_or(1.5),
+                        ],
+                        initial_speed: [
+                            config_tbl.get::<f32>("speed_min").unwrap_or(1.0),
+                            config_tbl.get::<f32>("speed_max").unwrap_or(3.0),
+                        ],
+                        direction: glam::Vec3::new(
+                            config_tbl.get::<f32>("dir_x").unwrap_or(0.0),
+                            config_tbl.get::<f32>("dir_y").unwrap_or(1.0),
+                            config_tbl.get::<f32>("dir_z").unwrap_or(0.0),
+                        ),
+                        spread: config_tbl.get::<f32>("spread").unwrap_or(360.0),
+                        size: [
+                            config_tbl.get::<f32>("size_start").unwrap_or(0.2),
+                            config_tbl.get::<f32>("size_end").unwrap_or(0.05),
+                        ],
+                        color_start: [r, g, b, config_tbl.get::<f32>("a").unwrap_or(1.0)],
+                        // Without end colors the burst keeps its color and fades out
+                        color_end: [
+                            config_tbl.get::<f32>("r_end").unwrap_or(r),
+                            config_tbl.get::<f32>("g_end").unwrap_or(g),
+                            config_tbl.get::<f32>("b_end").unwrap_or(b),
+                            0.0,
+                        ],
+                        color_over_life: Vec::new(),
+                        size_over_life: Vec::new(),
+                        size_curve: config_tbl.get::<String>("size_curve").ok().and_then(|name| {
+                            let def = crate::curve::CurveDef::Named(name);
+                            crate::curves::resolve_field(&project_root, Some(&def), "particles.spawn_burst")
+                        }),
+                        alpha_curve: config_tbl.get::<String>("alpha_curve").ok().and_then(|name| {
+                            let def = crate::curve::CurveDef::Named(name);
+                            crate::curves::resolve_field(&project_root, Some(&def), "particles.spawn_burst")
+                        }),
+                        gravity_scale: config_tbl.get::<f32>("gravity_scale").unwrap_or(1.0),
+                        blend: config_tbl
+                            .get::<String>("blend")
+                            .map(|name| crate::world::particle_blend_from_name(&name))
+                            .unwrap_or_default(),
+                        softness: config_tbl.get::<f32>("softness").unwrap_or(0.25).max(0.0),
+                        collision: config_tbl
+                            .get::<String>("collision")
+                            .map(|name| crate::world::particle_collision_from_name(&name))
+                            .unwrap_or_default(),
+                        bounce: config_tbl.get::<f32>("bounce").unwrap_or(0.4).clamp(0.0, 1.0),
+                    }
+                }
+                _ => return Err(LuaError::RuntimeError("particles.spawn_burst: expected a config table or a preset name".into())),
             };
             ps.spawn_burst(glam::Vec3::new(x, y, z), count, &config);
             Ok(())
@@ -1584,7 +2076,12 @@ impl ScriptRuntime {
         project_root: &Path,
         source: &Path,
     ) -> Result<bool, String> {
-        let full_path = project_root.join(source);
+        // Bundled scripts are fixed for the life of a shipped build
+        if self.bundle.as_ref().is_some_and(|b| b.get(source).is_some()) {
+            return Ok(false);
+        }
+
+        let full_path = self.overlay.resolve(project_root, source);
         let new_code = std::fs::read_to_string(&full_path)
             .map_err(|e| format!("Failed to read script {:?}: {}", full_path, e))?;
 
@@ -1732,6 +2229,1121 @@ impl ScriptRuntime {
         globals.set("animation", anim_table).map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    /// Register `capture_screenshot(path)`. The PNG is written at the end of the
+    /// current frame; paths are relative to the project root.
+    pub fn register_screenshot_api(&self, queue: SharedScreenshotQueue, project_root: PathBuf) -> Result<(), String> {
+        let globals = self.lua.globals();
+
+        // capture_screenshot(path) -> resolved path
+        let capture_fn = self.lua.create_function(move |_, path: String| {
+            let out = crate::screenshot::resolve_output_path(&project_root, &path, false)
+                .map_err(|e| LuaError::RuntimeError(format!("capture_screenshot: {}", e)))?;
+            queue.borrow_mut().push(out.clone());
+            Ok(out.to_string_lossy().into_owned())
+        }).map_err(|e| e.to_string())?;
+        globals.set("capture_screenshot", capture_fn).map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Register `render.set(name, value)` for tweaking lighting and post-process
+    /// parameters (see `RenderDebugState::SCRIPT_PARAMS`) and pipeline feature
+    /// flags (`setting.<name>`). Booleans map to 1/0.
+    /// `render.set_param(pass, name, value)` and `render.params(pass)` reach the
+    /// uniforms declared by `postprocess` pass shaders.
+    pub fn register_render_api(&self, tweaks: SharedRenderTweaks, pass_params: SharedPassParams) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let render_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // render.set(name, value)
+        let set_fn = self.lua.create_function(move |_, (name, value): (String, LuaValue)| {
+            if !crate::pipeline::RenderDebugState::is_script_param(&name) {
+                return Err(LuaError::RuntimeError(format!(
+                    "render.set: unknown parameter '{}' (expected setting.<flag> or one of: {})",
+                    name,
+                    crate::pipeline::RenderDebugState::SCRIPT_PARAMS.join(", ")
+                )));
+            }
+            let value = match value {
+                LuaValue::Boolean(b) => if b { 1.0 } else { 0.0 },
+                LuaValue::Integer(i) => i as f32,
+                LuaValue::Number(n) => n as f32,
+                other => {
+                    return Err(LuaError::RuntimeError(format!(
+                        "render.set: expected number or boolean for '{}', got {}",
+                        name,
+                        other.type_name()
+                    )))
+                }
+            };
+            tweaks.borrow_mut().push((name, value));
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        render_table.set("set", set_fn).map_err(|e| e.to_string())?;
+
+        // render.set_param(pass, name, value)  -- value is a number or {x, y, ...}
+        let pp = pass_params.clone();
+        let set_param_fn = self.lua.create_function(move |_, (pass, name, value): (String, String, LuaValue)| {
+            let value = match value {
+                LuaValue::Integer(i) => vec![i as f32],
+                LuaValue::Number(n) => vec![n as f32],
+                LuaValue::Table(t) => t
+                    .sequence_values::<f32>()
+                    .collect::<LuaResult<Vec<f32>>>()?,
+                other => {
+                    return Err(LuaError::RuntimeError(format!(
+                        "render.set_param: expected number or table for '{}', got {}",
+                        name,
+                        other.type_name()
+                    )))
+                }
+            };
+            pp.borrow_mut()
+                .set(&pass, &name, value)
+                .map_err(|e| LuaError::RuntimeError(format!("render.set_param: {}", e)))
+        }).map_err(|e| e.to_string())?;
+        render_table.set("set_param", set_param_fn).map_err(|e| e.to_string())?;
+
+        // render.params(pass) -> list of parameter names, or nil
+        let params_fn = self.lua.create_function(move |_, pass: String| {
+            Ok(pass_params.borrow().names(&pass).map(<[String]>::to_vec))
+        }).map_err(|e| e.to_string())?;
+        render_table.set("params", params_fn).map_err(|e| e.to_string())?;
+
+        globals.set("render", render_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register `pipeline.switch(name)`, which swaps in another of the
+    /// project's pipeline files before the next frame, plus `pipeline.current()`
+    /// and `pipeline.list()`.
+    pub fn register_pipeline_api(&self, switch: SharedPipelineSwitch, project_root: PathBuf) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let pipeline_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // pipeline.switch(name)  -- "render_low" or a project-relative .yaml path
+        let sw = switch.clone();
+        let root = project_root.clone();
+        let switch_fn = self.lua.create_function(move |_, name: String| {
+            let file = crate::pipeline::switch::pipeline_file(&name);
+            if !root.join(&file).is_file() {
+                return Err(LuaError::RuntimeError(format!(
+                    "pipeline.switch: no pipeline '{}' ({} not found; available: {})",
+                    name,
+                    file,
+                    crate::pipeline::switch::list_pipelines(&root).join(", ")
+                )));
+            }
+            sw.borrow_mut().pending = Some(file);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        pipeline_table.set("switch", switch_fn).map_err(|e| e.to_string())?;
+
+        // pipeline.current() -> name, or nil without a pipeline
+        let current_fn = self.lua.create_function(move |_, ()| {
+            Ok(switch.borrow().current.as_deref().map(crate::pipeline::switch::pipeline_name))
+        }).map_err(|e| e.to_string())?;
+        pipeline_table.set("current", current_fn).map_err(|e| e.to_string())?;
+
+        // pipeline.list() -> names of the files in pipelines/
+        let list_fn = self.lua.create_function(move |_, ()| {
+            Ok(crate::pipeline::switch::list_pipelines(&project_root))
+        }).map_err(|e| e.to_string())?;
+        pipeline_table.set("list", list_fn).map_err(|e| e.to_string())?;
+
+        globals.set("pipeline", pipeline_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the `curve` API: evaluate the project's curve assets.
+    pub fn register_curve_api(&self, curves: SharedCurves, project_root: PathBuf) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let curve_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // curve.eval(name, t) -> value at time t (in the curve's own units)
+        let lib = curves.clone();
+        let root = project_root.clone();
+        let eval_fn = self.lua.create_function(move |_, (name, t): (String, f32)| {
+            let mut lib = lib.borrow_mut();
+            let curve = lib.get(&root, &name).map_err(|e| LuaError::RuntimeError(format!("curve.eval: {}", e)))?;
+            Ok(curve.eval(t))
+        }).map_err(|e| e.to_string())?;
+        curve_table.set("eval", eval_fn).map_err(|e| e.to_string())?;
+
+        // curve.eval_normalized(name, t) -> value at t in 0..1 of the key range
+        let lib = curves.clone();
+        let root = project_root.clone();
+        let eval_normalized_fn = self.lua.create_function(move |_, (name, t): (String, f32)| {
+            let mut lib = lib.borrow_mut();
+            let curve = lib.get(&root, &name)
+                .map_err(|e| LuaError::RuntimeError(format!("curve.eval_normalized: {}", e)))?;
+            Ok(curve.eval_normalized(t))
+        }).map_err(|e| e.to_string())?;
+        curve_table.set("eval_normalized", eval_normalized_fn).map_err(|e| e.to_string())?;
+
+        // curve.duration(name) -> seconds between the first and last keys
+        let lib = curves.clone();
+        let duration_fn = self.lua.create_function(move |_, name: String| {
+            let mut lib = lib.borrow_mut();
+            let curve = lib.get(&project_root, &name)
+                .map_err(|e| LuaError::RuntimeError(format!("curve.duration: {}", e)))?;
+            Ok(curve.duration())
+        }).map_err(|e| e.to_string())?;
+        curve_table.set("duration", duration_fn).map_err(|e| e.to_string())?;
+
+        // curve.define(name, { keys = {{t, v}, ...}, interpolation = "smooth", wrap = "loop" })
+        let define_fn = self.lua.create_function(move |lua, (name, tbl): (String, LuaTable)| {
+            let curve: crate::curve::Curve = lua.from_value(LuaValue::Table(tbl))
+                .map_err(|e| LuaError::RuntimeError(format!("curve.define: {}", e)))?;
+            curve.validate().map_err(|e| LuaError::RuntimeError(format!("curve.define: {}", e)))?;
+            let curve = crate::curve::Curve::new(curve.keys, curve.interpolation).with_wrap(curve.wrap);
+            curves.borrow_mut().define(&name, curve);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        curve_table.set("define", define_fn).map_err(|e| e.to_string())?;
+
+        globals.set("curve", curve_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the `crowd` API: per-group flocking parameters and goals.
+    pub fn register_crowd_api(&self, crowd: SharedCrowd) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let crowd_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // crowd.set_params(group, {separation = 2, cohesion = 0.5, ...})
+        let c = crowd.clone();
+        let set_params_fn = self.lua.create_function(move |_, (group, params): (String, LuaTable)| {
+            let mut crowd = c.borrow_mut();
+            let group_params = crowd.group_mut(&group);
+            for pair in params.pairs::<String, f32>() {
+                let (name, value) = pair?;
+                group_params
+                    .set(&name, value)
+                    .map_err(|e| LuaError::RuntimeError(format!("crowd.set_params: {}", e)))?;
+            }
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        crowd_table.set("set_params", set_params_fn).map_err(|e| e.to_string())?;
+
+        // crowd.params(group) -> {separation = ..., ...}
+        let c = crowd.clone();
+        let params_fn = self.lua.create_function(move |lua, group: String| {
+            let params = c.borrow().group(&group);
+            let table = lua.create_table()?;
+            for name in crate::crowd::FlockParams::NAMES {
+                table.set(*name, params.get(name))?;
+            }
+            Ok(table)
+        }).map_err(|e| e.to_string())?;
+        crowd_table.set("params", params_fn).map_err(|e| e.to_string())?;
+
+        // crowd.set_goal(group, x, y, z) or crowd.set_goal(group, entity_id)
+        let c = crowd.clone();
+        let set_goal_fn = self.lua.create_function(move |_, (group, target, y, z): (String, LuaValue, Option<f32>, Option<f32>)| {
+            let point = |x: f32| crate::crowd::FlockGoal::Point(glam::Vec3::new(x, y.unwrap_or(0.0), z.unwrap_or(0.0)));
+            let goal = match target {
+                LuaValue::String(id) => crate::crowd::FlockGoal::Entity(id.to_str()?.to_string()),
+                LuaValue::Integer(i) => point(i as f32),
+                LuaValue::Number(n) => point(n as f32),
+                other => {
+                    return Err(LuaError::RuntimeError(format!(
+                        "crowd.set_goal: expected x, y, z or an entity id, got {}",
+                        other.type_name()
+                    )))
+                }
+            };
+            c.borrow_mut().group_mut(&group).goal = Some(goal);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        crowd_table.set("set_goal", set_goal_fn).map_err(|e| e.to_string())?;
+
+        // crowd.clear_goal(group)
+        let clear_goal_fn = self.lua.create_function(move |_, group: String| {
+            crowd.borrow_mut().group_mut(&group).goal = None;
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        crowd_table.set("clear_goal", clear_goal_fn).map_err(|e| e.to_string())?;
+
+        globals.set("crowd", crowd_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the `noise` API: emit gameplay noises and ask what a
+    /// listener has heard. Noises also arrive as `sound.noise` events.
+    pub fn register_noise_api(&self, noise: SharedNoise) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let noise_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // noise.emit(x, y, z, loudness, tag [, source_id])
+        let n = noise.clone();
+        let emit_fn = self.lua.create_function(move |_, (x, y, z, loudness, tag, source): (f32, f32, f32, f32, String, Option<String>)| {
+            n.borrow_mut().emit(crate::noise::NoiseEvent::new(glam::Vec3::new(x, y, z), loudness, tag).with_source(source));
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        noise_table.set("emit", emit_fn).map_err(|e| e.to_string())?;
+
+        // noise.heard(x, y, z [, hearing]) -> {{x, y, z, loudness, tag, source, distance, age}, ...}, nearest first
+        let heard_fn = self.lua.create_function(move |lua, (x, y, z, hearing): (f32, f32, f32, Option<f32>)| {
+            let channel = noise.borrow();
+            let heard = lua.create_table()?;
+            for (i, h) in channel.heard(glam::Vec3::new(x, y, z), hearing.unwrap_or(1.0)).iter().enumerate() {
+                let entry = lua.create_table()?;
+                entry.set("x", h.event.position.x)?;
+                entry.set("y", h.event.position.y)?;
+                entry.set("z", h.event.position.z)?;
+                entry.set("loudness", h.event.loudness)?;
+                entry.set("tag", h.event.tag.as_str())?;
+                entry.set("source", h.event.source.as_deref())?;
+                entry.set("distance", h.distance)?;
+                entry.set("age", h.age)?;
+                heard.set(i + 1, entry)?;
+            }
+            Ok(heard)
+        }).map_err(|e| e.to_string())?;
+        noise_table.set("heard", heard_fn).map_err(|e| e.to_string())?;
+
+        globals.set("noise", noise_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the mods API for in-game mod menus. Changes apply on the
+    /// next launch.
+    pub fn register_mods_api(&self, mods: SharedMods) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let mods_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // mods.list() -> {{id, name, version, description, priority, enabled, mounted}, ...} in mount order
+        let m = mods.clone();
+        let list_fn = self.lua.create_function(move |lua, ()| {
+            let list = lua.create_table()?;
+            for (i, info) in m.borrow().mods.iter().enumerate() {
+                let entry = lua.create_table()?;
+                entry.set("id", info.id.as_str())?;
+                entry.set("name", info.name.as_str())?;
+                entry.set("version", info.version.as_str())?;
+                entry.set("description", info.description.as_str())?;
+                entry.set("priority", info.priority)?;
+                entry.set("enabled", info.enabled)?;
+                entry.set("mounted", info.mounted)?;
+                list.set(i + 1, entry)?;
+            }
+            Ok(list)
+        }).map_err(|e| e.to_string())?;
+        mods_table.set("list", list_fn).map_err(|e| e.to_string())?;
+
+        // mods.enable(id [, enabled = true])
+        let m = mods.clone();
+        let enable_fn = self.lua.create_function(move |_, (id, enabled): (String, Option<bool>)| {
+            m.borrow_mut()
+                .set_enabled(&id, enabled.unwrap_or(true))
+                .map_err(LuaError::RuntimeError)
+        }).map_err(|e| e.to_string())?;
+        mods_table.set("enable", enable_fn).map_err(|e| e.to_string())?;
+
+        // mods.conflicts() -> {{path, winner, shadowed = {id, ...}}, ...}
+        let conflicts_fn = self.lua.create_function(move |lua, ()| {
+            let list = lua.create_table()?;
+            for (i, conflict) in mods.borrow().conflicts.iter().enumerate() {
+                let entry = lua.create_table()?;
+                entry.set("path", conflict.path.as_str())?;
+                entry.set("winner", conflict.winner.as_str())?;
+                entry.set("shadowed", conflict.shadowed.clone())?;
+                list.set(i + 1, entry)?;
+            }
+            Ok(list)
+        }).map_err(|e| e.to_string())?;
+        mods_table.set("conflicts", conflicts_fn).map_err(|e| e.to_string())?;
+
+        globals.set("mods", mods_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the `steam` API. Every call works without Steam: unlocks and
+    /// cloud writes return false and reads return nil.
+    pub fn register_steam_api(&self, steam: SharedSteam) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let steam_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // steam.available() -> bool
+        let s = steam.clone();
+        let available_fn = self.lua.create_function(move |_, ()| Ok(s.borrow().available()))
+            .map_err(|e| e.to_string())?;
+        steam_table.set("available", available_fn).map_err(|e| e.to_string())?;
+
+        // steam.unlock(name) -> bool
+        let s = steam.clone();
+        let unlock_fn = self.lua.create_function(move |_, name: String| {
+            s.borrow().unlock(&name).map_err(|e| LuaError::RuntimeError(format!("steam.unlock: {}", e)))
+        }).map_err(|e| e.to_string())?;
+        steam_table.set("unlock", unlock_fn).map_err(|e| e.to_string())?;
+
+        // steam.is_unlocked(name) -> bool
+        let s = steam.clone();
+        let is_unlocked_fn = self.lua.create_function(move |_, name: String| Ok(s.borrow().is_unlocked(&name)))
+            .map_err(|e| e.to_string())?;
+        steam_table.set("is_unlocked", is_unlocked_fn).map_err(|e| e.to_string())?;
+
+        // steam.clear_achievement(name)
+        let s = steam.clone();
+        let clear_fn = self.lua.create_function(move |_, name: String| {
+            s.borrow().clear_achievement(&name).map_err(|e| LuaError::RuntimeError(format!("steam.clear_achievement: {}", e)))
+        }).map_err(|e| e.to_string())?;
+        steam_table.set("clear_achievement", clear_fn).map_err(|e| e.to_string())?;
+
+        // steam.set_presence(key, value) -> bool; a nil value removes the key
+        let s = steam.clone();
+        let set_presence_fn = self.lua.create_function(move |_, (key, value): (String, Option<String>)| {
+            Ok(s.borrow().set_presence(&key, value.as_deref()))
+        }).map_err(|e| e.to_string())?;
+        steam_table.set("set_presence", set_presence_fn).map_err(|e| e.to_string())?;
+
+        // steam.clear_presence()
+        let s = steam.clone();
+        let clear_presence_fn = self.lua.create_function(move |_, ()| {
+            s.borrow().clear_presence();
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        steam_table.set("clear_presence", clear_presence_fn).map_err(|e| e.to_string())?;
+
+        // steam.cloud_write(name, data) -> bool
+        let s = steam.clone();
+        let cloud_write_fn = self.lua.create_function(move |_, (name, data): (String, LuaString)| {
+            s.borrow()
+                .cloud_write(&name, &data.as_bytes())
+                .map_err(|e| LuaError::RuntimeError(format!("steam.cloud_write: {}", e)))
+        }).map_err(|e| e.to_string())?;
+        steam_table.set("cloud_write", cloud_write_fn).map_err(|e| e.to_string())?;
+
+        // steam.cloud_read(name) -> string or nil
+        let s = steam.clone();
+        let cloud_read_fn = self.lua.create_function(move |lua, name: String| {
+            let data = s.borrow()
+                .cloud_read(&name)
+                .map_err(|e| LuaError::RuntimeError(format!("steam.cloud_read: {}", e)))?;
+            data.map(|bytes| lua.create_string(&bytes)).transpose()
+        }).map_err(|e| e.to_string())?;
+        steam_table.set("cloud_read", cloud_read_fn).map_err(|e| e.to_string())?;
+
+        // steam.overlay_active() -> bool
+        let overlay_fn = self.lua.create_function(move |_, ()| Ok(steam.borrow().overlay_active()))
+            .map_err(|e| e.to_string())?;
+        steam_table.set("overlay_active", overlay_fn).map_err(|e| e.to_string())?;
+
+        globals.set("steam", steam_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the `analytics` API. Tracking does nothing unless naive.yaml
+    /// enables analytics.
+    pub fn register_analytics_api(&self, analytics: SharedAnalytics) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let analytics_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // analytics.track(name, data?) -> bool, false when tracking is off
+        let a = analytics.clone();
+        let track_fn = self.lua.create_function(move |_, (name, data): (String, Option<LuaTable>)| {
+            a.borrow_mut()
+                .track(&name, lua_table_to_event_data(data))
+                .map_err(|e| LuaError::RuntimeError(format!("analytics.track: {}", e)))
+        }).map_err(|e| e.to_string())?;
+        analytics_table.set("track", track_fn).map_err(|e| e.to_string())?;
+
+        // analytics.enabled() -> bool
+        let a = analytics.clone();
+        let enabled_fn = self.lua.create_function(move |_, ()| Ok(a.borrow().enabled()))
+            .map_err(|e| e.to_string())?;
+        analytics_table.set("enabled", enabled_fn).map_err(|e| e.to_string())?;
+
+        // analytics.set_enabled(bool) -- e.g. from a privacy setting
+        let a = analytics.clone();
+        let set_enabled_fn = self.lua.create_function(move |_, enabled: bool| {
+            a.borrow_mut().set_enabled(enabled);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        analytics_table.set("set_enabled", set_enabled_fn).map_err(|e| e.to_string())?;
+
+        // analytics.flush() -- send pending events now
+        let flush_fn = self.lua.create_function(move |_, ()| {
+            analytics.borrow_mut().flush(false);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        analytics_table.set("flush", flush_fn).map_err(|e| e.to_string())?;
+
+        globals.set("analytics", analytics_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register the `sequence` API for cutscenes in `sequences/`.
+    pub fn register_sequence_api(&self, sequences: SharedSequences) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let sequence_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // sequence.play(name) -- errors if the file is missing or invalid
+        let s = sequences.clone();
+        let play_fn = self.lua.create_function(move |_, name: String| {
+            s.borrow_mut().play(&name).map_err(LuaError::RuntimeError)
+        }).map_err(|e| e.to_string())?;
+        sequence_table.set("play", play_fn).map_err(|e| e.to_string())?;
+
+        // sequence.skip() -> bool, false when nothing skippable is playing
+        let s = sequences.clone();
+        let skip_fn = self.lua.create_function(move |_, ()| Ok(s.borrow_mut().skip())).map_err(|e| e.to_string())?;
+        sequence_table.set("skip", skip_fn).map_err(|e| e.to_string())?;
+
+        // sequence.stop() -- ends playback without applying the remaining cues
+        let s = sequences.clone();
+        let stop_fn = self.lua.create_function(move |_, ()| {
+            s.borrow_mut().stop();
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        sequence_table.set("stop", stop_fn).map_err(|e| e.to_string())?;
+
+        // sequence.is_playing() -> bool
+        let s = sequences.clone();
+        let is_playing_fn = self.lua.create_function(move |_, ()| Ok(s.borrow().is_playing())).map_err(|e| e.to_string())?;
+        sequence_table.set("is_playing", is_playing_fn).map_err(|e| e.to_string())?;
+
+        // sequence.current() -> name, seconds (nil when idle)
+        let current_fn = self.lua.create_function(move |_, ()| {
+            let player = sequences.borrow();
+            Ok(match player.current() {
+                Some((name, time)) => (Some(name.to_string()), Some(time)),
+                None => (None, None),
+            })
+        }).map_err(|e| e.to_string())?;
+        sequence_table.set("current", current_fn).map_err(|e| e.to_string())?;
+
+        globals.set("sequence", sequence_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register (or replace) the read-only global `config` table. See `script_config`.
+    pub fn register_config_api(&self, config: &serde_yaml::Mapping) -> Result<(), String> {
+        let table = crate::script_config::to_lua_table(&self.lua, config).map_err(|e| e.to_string())?;
+        self.lua.globals().set("config", table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Register time scaling API (global slow motion + per-entity scales).
+    pub fn register_time_api(&self, clock: SharedGameClock, scene_world: SharedSceneWorld) -> Result<(), String> {
+        let globals = self.lua.globals();
+        let time_table = self.lua.create_table().map_err(|e| e.to_string())?;
+
+        // time.set_scale(scale)  -- 1 = normal, 0.2 = slow motion, 0 = frozen
+        let c = clock.clone();
+        let set_scale_fn = self.lua.create_function(move |_, scale: f32| {
+            c.borrow_mut().set_scale(scale);
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        time_table.set("set_scale", set_scale_fn).map_err(|e| e.to_string())?;
+
+        // time.get_scale() -> number
+        let c = clock.clone();
+        let get_scale_fn = self.lua.create_function(move |_, ()| {
+            Ok(c.borrow().scale)
+        }).map_err(|e| e.to_string())?;
+        time_table.set("get_scale", get_scale_fn).map_err(|e| e.to_string())?;
+
+        // time.set_physics_scaled(enabled)  -- false keeps physics in real time
+        let c = clock.clone();
+        let physics_fn = self.lua.create_function(move |_, enabled: bool| {
+            c.borrow_mut().scale_physics = enabled;
+            Ok(())
+        }).map_err(|e| e.to_string())?;
+        time_table.set("set_physics_scaled", physics_fn).map_err(|e| e.to_string())?;
+
+        // time.unscaled_dt() -> number  -- real frame time, for UI during slow motion
+        let c = clock.clone();
+        let unscaled_fn = self.lua.create_function(move |_, ()| {
+            Ok(c.borrow().unscaled_dt)
+        }).map_err(|e| e.to_string())?;
+        time_table.set("unscaled_dt", unscaled_fn).map_err(|e| e.to_string())?;
+
+        // time.elapsed() -> number  -- scaled seconds since the scene loaded
+        let c = clock.clone();
+        let elapsed_fn = self.lua.create_function(move |_, ()| {
+            Ok(c.borrow().elapsed)
+        }).map_err(|e| e.to_string())?;
+        time_table.set("elapsed", elapsed_fn).map_err(|e| e.to_string())?;
+
+        // time.set_entity_scale(entity_id, scale)  -- scale 1 removes the override
+        let sw = scene_world.clone();
+        let set_entity_fn = self.lua.create_function(move |_, (id, scale): (String, f32)| {
+            let mut sw = sw.borrow_mut();
+            let entity = match sw.entity_registry.get(&id) {
+                Some(&e) => e,
+                None => return Ok(false),
+            };
+            let scale = crate::time_scale::clamp_scale(scale);
+            if scale == 1.0 {
+                let _ = sw.world.remove_one::<crate::components::TimeScale>(entity);
+            } else {
+                let _ = sw.world.insert_one(entity, crate::components::TimeScale(scale));
+            }
+            Ok(true)
+        }).map_err(|e| e.to_string())?;
+        time_table.set("set_entity_scale", set_entity_fn).map_err(|e| e.to_string())?;
+
+        // time.get_entity_scale(entity_id) -> number  -- the entity's own scale, excluding the global one
+        let sw = scene_world.clone();
+        let get_entity_fn = self.lua.create_function(move |_, id: String| {
+            let sw = sw.borrow();
+            let scale = sw.entity_registry.get(&id)
+                .and_then(|&e| sw.world.get::<&crate::components::TimeScale>(e).ok().map(|t| t.0))
+                .unwrap_or(1.0);
+            Ok(scale)
+        }).map_err(|e| e.to_string())?;
+        time_table.set("get_entity_scale", get_entity_fn).map_err(|e| e.to_string())?;
+
+        globals.set("time", time_table).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Install the shims of functions deprecated after `api_level`, the level
+    /// the project's scripts were written against. Call after every other
+    /// API is registered; each shim warns once, with its migration hint, the
+    /// first time a script calls it.
+    pub fn register_api_shims(&self, api_level: u32, deprecations: &[LuaDeprecation]) -> Result<(), String> {
+        if api_level > LUA_API_LEVEL {
+            tracing::warn!(
+                "scripting.api_level {} is newer than this engine's Lua API (level {})",
+                api_level,
+                LUA_API_LEVEL
+            );
+        }
+        let globals = self.lua.globals();
+        for deprecation in deprecations.iter().filter(|d| d.level > api_level) {
+            let path = deprecation.path();
+            let shim: LuaFunction = self
+                .lua
+                .load(deprecation.shim)
+                .set_name(format!("=shim {}", path))
+                .eval()
+                .map_err(|e| format!("Shim for {}: {}", path, e))?;
+            let (level, hint) = (deprecation.level, deprecation.hint);
+            let warned = Cell::new(false);
+            let name = path.clone();
+            let shim_fn = self.lua.create_function(move |lua, args: LuaMultiValue| {
+                if !warned.replace(true) {
+                    tracing::warn!(
+                        "{} is deprecated since Lua API level {}{}: {}",
+                        name,
+                        level,
+                        lua_caller_location(lua),
+                        hint
+                    );
+                }
+                shim.call::<LuaMultiValue>(args)
+            }).map_err(|e| e.to_string())?;
+
+            if deprecation.table.is_empty() {
+                globals.set(deprecation.name, shim_fn).map_err(|e| e.to_string())?;
+                continue;
+            }
+            let table = match globals.get::<Option<LuaTable>>(deprecation.table).map_err(|e| e.to_string())? {
+                Some(table) => table,
+                None => {
+                    let table = self.lua.create_table().map_err(|e| e.to_string())?;
+                    globals.set(deprecation.table, &table).map_err(|e| e.to_string())?;
+                    table
+                }
+            };
+            table.set(deprecation.name, shim_fn).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// A script API function as listed by `naive docs`. Types follow the Lua
+/// language server (`string`, `number`, `integer`, `boolean`, `table`,
+/// `function`, `any`); a parameter name ending in `?` is optional.
+pub struct LuaApiFn {
+    /// Global table holding the function; empty for a global function.
+    pub table: &'static str,
+    pub name: &'static str,
+    /// `(name, type)` pairs.
+    pub params: &'static [(&'static str, &'static str)],
+    /// `(name, type)` pairs, in return order.
+    pub returns: &'static [(&'static str, &'static str)],
+    pub doc: &'static str,
+}
+
+impl LuaApiFn {
+    /// Qualified name, e.g. `entity.get_position`.
+    pub fn path(&self) -> String {
+        qualified_name(self.table, self.name)
+    }
+}
+
+fn qualified_name(table: &str, name: &str) -> String {
+    if table.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", table, name)
+    }
+}
+
+const fn api(
+    table: &'static str,
+    name: &'static str,
+    params: &'static [(&'static str, &'static str)],
+    returns: &'static [(&'static str, &'static str)],
+    doc: &'static str,
+) -> LuaApiFn {
+    LuaApiFn { table, name, params, returns, doc }
+}
+
+/// Every function the `register_*_api` methods install, in documentation
+/// order. `test_lua_api_declarations_match_registrations` fails when a
+/// binding is added or removed without updating this table.
+pub const LUA_API: &[LuaApiFn] = &[
+    api("", "log", &[("msg", "string")], &[], "Write a message to the engine log."),
+    api("", "print", &[("...", "any")], &[], "Write the values to the engine log, tab-separated."),
+    api("", "capture_screenshot", &[("path", "string")], &[("path", "string")],
+        "Save the current frame as a PNG at the end of the frame. The path is relative to the project root; returns the resolved path."),
+    api("math", "lerp", &[("a", "number"), ("b", "number"), ("t", "number")], &[("value", "number")],
+        "Linear interpolation from `a` to `b`."),
+    api("math", "clamp", &[("value", "number"), ("min", "number"), ("max", "number")], &[("value", "number")],
+        "Clamp `value` to `[min, max]`."),
+
+    api("input", "pressed", &[("action", "string")], &[("held", "boolean")],
+        "Whether an action from input/bindings.yaml is held."),
+    api("input", "just_pressed", &[("action", "string")], &[("pressed", "boolean")],
+        "Whether an action was pressed this frame."),
+    api("input", "any_just_pressed", &[], &[("pressed", "boolean")],
+        "Whether any bound action was pressed this frame."),
+    api("input", "mouse_delta", &[], &[("dx", "number"), ("dy", "number")],
+        "Mouse movement since the last frame."),
+    api("input", "scroll_delta", &[], &[("dx", "number"), ("dy", "number")],
+        "Scroll wheel movement this frame."),
+    api("input", "mouse_position", &[], &[("sx", "number"), ("sy", "number")],
+        "Cursor position in screen pixels."),
+
+    api("physics", "raycast",
+        &[("ox", "number"), ("oy", "number"), ("oz", "number"), ("dx", "number"), ("dy", "number"), ("dz", "number"), ("max_dist", "number"), ("filter?", "table")],
+        &[("hit", "boolean"), ("distance", "number"), ("nx", "number"), ("ny", "number"), ("nz", "number")],
+        "Cast a ray. `filter` takes `exclude` (id or ids), `include_tags`, `exclude_tags` and `triggers`."),
+    api("physics", "hitscan",
+        &[("ox", "number"), ("oy", "number"), ("oz", "number"), ("dx", "number"), ("dy", "number"), ("dz", "number"), ("range", "number"), ("filter?", "table")],
+        &[("hit", "boolean"), ("entity_id", "string"), ("distance", "number"), ("hx", "number"), ("hy", "number"), ("hz", "number"), ("nx", "number"), ("ny", "number"), ("nz", "number")],
+        "Cast a ray and report the entity hit, the hit point and the surface normal. Takes the same filter as `physics.raycast`."),
+    api("physics", "apply_impulse", &[("id", "string"), ("fx", "number"), ("fy", "number"), ("fz", "number")], &[],
+        "Apply an impulse to an entity's rigid body."),
+    api("physics", "apply_force", &[("id", "string"), ("fx", "number"), ("fy", "number"), ("fz", "number")], &[],
+        "Apply a force to an entity's rigid body for the next step."),
+    api("physics", "set_velocity", &[("id", "string"), ("vx", "number"), ("vy", "number"), ("vz", "number")], &[],
+        "Set an entity's linear velocity."),
+    api("physics", "get_velocity", &[("id", "string")], &[("vx", "number"), ("vy", "number"), ("vz", "number")],
+        "An entity's linear velocity; zero without a rigid body."),
+    api("physics", "set_restitution", &[("id", "string"), ("value", "number")], &[],
+        "Set the bounciness of an entity's colliders."),
+    api("physics", "set_friction", &[("id", "string"), ("value", "number")], &[],
+        "Set the friction of an entity's colliders."),
+    api("physics", "volume_contents", &[("trigger_id", "string")], &[("ids", "string[]")],
+        "Entities currently inside a trigger volume."),
+    api("physics", "set_trigger_stay_interval", &[("seconds", "number")], &[],
+        "How often `on_trigger_stay` fires."),
+    api("physics", "set_collider", &[("id", "string"), ("collider", "table")], &[],
+        "Rebuild an entity's collider on its existing body, next frame. Fields match the scene `collider` component."),
+
+    api("entity", "exists", &[("id", "string")], &[("exists", "boolean")], "Whether an entity with this id exists."),
+    api("entity", "get_position", &[("id", "string")], &[("x", "number"), ("y", "number"), ("z", "number")],
+        "An entity's position."),
+    api("entity", "set_position", &[("id", "string"), ("x", "number"), ("y", "number"), ("z", "number")], &[],
+        "Move an entity."),
+    api("entity", "get_rotation", &[("id", "string")], &[("pitch", "number"), ("yaw", "number"), ("roll", "number")],
+        "An entity's rotation in degrees."),
+    api("entity", "set_rotation", &[("id", "string"), ("pitch", "number"), ("yaw", "number"), ("roll", "number")], &[],
+        "Rotate an entity (degrees). Kinematic bodies follow."),
+    api("entity", "get_scale", &[("id", "string")], &[("sx", "number"), ("sy", "number"), ("sz", "number")],
+        "An entity's scale."),
+    api("entity", "set_scale", &[("id", "string"), ("sx", "number"), ("sy", "number"), ("sz", "number")], &[],
+        "Scale an entity."),
+    api("entity", "set_visible", &[("id", "string"), ("visible", "boolean")], &[], "Show or hide an entity."),
+    api("entity", "set_light", &[("id", "string"), ("intensity", "number")], &[], "Set a light's intensity."),
+    api("entity", "set_light_color", &[("id", "string"), ("r", "number"), ("g", "number"), ("b", "number")], &[],
+        "Set a light's color."),
+    api("entity", "set_emission", &[("id", "string"), ("r", "number"), ("g", "number"), ("b", "number")], &[],
+        "Override the emissive color of an entity's material."),
+    api("entity", "set_roughness", &[("id", "string"), ("value", "number")], &[],
+        "Override the roughness of an entity's material."),
+    api("entity", "set_metallic", &[("id", "string"), ("value", "number")], &[],
+        "Override the metallic value of an entity's material."),
+    api("entity", "set_base_color", &[("id", "string"), ("r", "number"), ("g", "number"), ("b", "number")], &[],
+        "Override the base color of an entity's material."),
+    api("entity", "set_texture", &[("id", "string"), ("slot", "string"), ("path", "string")], &[],
+        "Swap one texture map of an entity's material: `albedo`, `normal`, `metallic_roughness` or `emissive`."),
+    api("entity", "get_splat_opacity", &[("id", "string")], &[("opacity", "number?")],
+        "A splat cloud's opacity, or nil without a splat."),
+    api("entity", "set_splat_opacity", &[("id", "string"), ("opacity", "number")], &[],
+        "Multiply every splat's opacity (0 to 1)."),
+    api("entity", "set_splat_crop", &[("id", "string"), ("crops", "table")], &[],
+        "Replace a splat cloud's crop volumes; fields match `crop:` in scene YAML. An empty list uncrops."),
+    api("entity", "get_health", &[("id", "string")], &[("current", "number"), ("max", "number")], "An entity's health."),
+    api("entity", "set_health", &[("id", "string"), ("current", "number"), ("max", "number")], &[], "Set an entity's health."),
+    api("entity", "damage", &[("id", "string"), ("amount", "number")], &[("current", "number")],
+        "Subtract health; returns what is left."),
+    api("entity", "heal", &[("id", "string"), ("amount", "number")], &[("current", "number")],
+        "Add health up to the maximum; returns the new value."),
+    api("entity", "is_alive", &[("id", "string")], &[("alive", "boolean")], "Whether an entity's health is above zero; entities without health count as alive."),
+    api("entity", "has_tag", &[("id", "string"), ("tag", "string")], &[("has", "boolean")], "Whether an entity has a tag."),
+    api("entity", "add_tag", &[("id", "string"), ("tag", "string")], &[], "Tag an entity."),
+    api("entity", "remove_tag", &[("id", "string"), ("tag", "string")], &[], "Remove a tag from an entity."),
+    api("entity", "get_tag", &[("id", "string")], &[("tag", "string?")], "An entity's first tag, or nil."),
+    api("entity", "get_tags", &[("id", "string")], &[("tags", "string[]")], "All of an entity's tags."),
+    api("entity", "spawn",
+        &[("id", "string"), ("mesh", "string"), ("material", "string"), ("x", "number"), ("y", "number"), ("z", "number"), ("sx", "number"), ("sy", "number"), ("sz", "number")],
+        &[], "Spawn a mesh entity at the end of the frame."),
+    api("entity", "spawn_ex", &[("config", "table")], &[],
+        "Spawn with an optional collider, rigid body and script, or a full scene entity when `components` is given."),
+    api("entity", "spawn_projectile",
+        &[("owner_id", "string"), ("mesh", "string"), ("material", "string"), ("ox", "number"), ("oy", "number"), ("oz", "number"), ("dx", "number"), ("dy", "number"), ("dz", "number"), ("speed", "number"), ("damage", "number"), ("lifetime", "number"), ("gravity", "boolean")],
+        &[], "Spawn a projectile that damages what it hits and is destroyed on impact."),
+    api("entity", "spawn_dynamic",
+        &[("mesh", "string"), ("material", "string"), ("x", "number"), ("y", "number"), ("z", "number"), ("vx", "number"), ("vy", "number"), ("vz", "number"), ("radius", "number"), ("mass", "number"), ("restitution", "number"), ("friction", "number"), ("lifetime", "number")],
+        &[], "Spawn a dynamic rigid body that bounces and persists."),
+    api("entity", "destroy", &[("id", "string")], &[], "Destroy an entity at the end of the frame."),
+    api("entity", "destroy_by_prefix", &[("prefix", "string")], &[], "Destroy every entity whose id starts with `prefix`."),
+    api("entity", "pool_create", &[("name", "string"), ("mesh", "string"), ("material", "string"), ("count", "integer")], &[],
+        "Create a pool of `count` hidden entities."),
+    api("entity", "pool_acquire", &[("name", "string")], &[("id", "string?")],
+        "Take an entity from a pool, spawning one when it is empty; nil for an unknown pool."),
+    api("entity", "pool_release", &[("id", "string")], &[], "Hide an entity and return it to its pool."),
+    api("entity", "pool_size", &[("name", "string")], &[("total", "integer"), ("available", "integer")],
+        "A pool's size and how many of its entities are free."),
+    api("entity", "set_emitter_enabled", &[("id", "string"), ("enabled", "boolean")], &[],
+        "Start or stop an entity's particle emitter."),
+    api("entity", "set_emitter_rate", &[("id", "string"), ("rate", "number")], &[],
+        "Set an emitter's particles per second."),
+    api("entity", "burst", &[("id", "string"), ("count", "integer")], &[],
+        "Spawn `count` particles from an entity's emitter now."),
+
+    api("scene", "find_by_tag", &[("tag", "string")], &[("ids", "string[]")], "Entities with a tag."),
+    api("scene", "find_by_tags", &[("...", "string")], &[("ids", "string[]")], "Entities with all of the given tags."),
+    api("scene", "load", &[("path", "string")], &[], "Switch to another scene at the end of the frame."),
+
+    api("events", "emit", &[("event_type", "string"), ("data?", "table")], &[],
+        "Emit an event, validated against events/schema.yaml."),
+    api("events", "emitter", &[("event_type", "string")], &[("emit", "function")],
+        "A function emitting one event type; unknown types fail here instead of at emit time."),
+    api("events", "schema", &[], &[("schema", "table")], "The event types declared in events/schema.yaml and their fields."),
+    api("events", "on", &[("event_type", "string"), ("callback", "function")], &[("listener_id", "integer")],
+        "Call `callback(data)` for every event of a type."),
+    api("events", "off", &[("listener_id", "integer")], &[], "Remove a listener added with `events.on`."),
+
+    api("msg", "send", &[("target_id", "string"), ("name", "string"), ("data?", "any")], &[],
+        "Send a message to an entity's `on_message` hook."),
+    api("msg", "request", &[("target_id", "string"), ("name", "string"), ("data", "any"), ("callback", "function"), ("timeout?", "number")],
+        &[("request_id", "integer")],
+        "Send a message expecting a reply. `callback(reply, err)` gets `err` as nil, \"timeout\" or a delivery error."),
+    api("msg", "reply", &[("request_id", "integer"), ("data", "any")], &[("sent", "boolean")],
+        "Answer a request after `on_message` returned."),
+
+    api("audio", "play_sfx", &[("id", "string"), ("path", "string"), ("volume", "number"), ("heard?", "table")], &[],
+        "Play a sound effect. With `{x, y, z, loudness, tag, source}` AI also hears it as a noise."),
+    api("audio", "play_music", &[("path", "string"), ("volume", "number"), ("fade_in", "number")], &[],
+        "Play a music track, fading in over `fade_in` seconds."),
+    api("audio", "stop_sound", &[("id", "string"), ("fade_out", "number")], &[], "Stop a sound effect."),
+    api("audio", "stop_music", &[("fade_out", "number")], &[], "Stop the music."),
+    api("audio", "fade", &[("id", "string"), ("volume", "number"), ("seconds", "number"), ("curve?", "string")], &[],
+        "Fade a sound effect's volume, shaped by a curve from `curves/` (linear without one)."),
+    api("audio", "fade_music", &[("volume", "number"), ("seconds", "number"), ("curve?", "string")], &[],
+        "Fade the music's volume, shaped by a curve from `curves/`."),
+    api("audio", "list_devices", &[], &[("names", "string[]")], "Audio output devices."),
+    api("audio", "get_device", &[], &[("name", "string?")], "The device playing, or nil."),
+    api("audio", "set_device", &[("name?", "string")], &[], "Switch output device; nil follows the system default."),
+    api("audio", "set_buffer_size", &[("frames?", "integer")], &[], "Set the output buffer size; nil uses the driver default."),
+    api("audio", "get_latency", &[], &[("ms", "number?")], "Milliseconds per buffer, or nil with the driver default."),
+
+    api("mesh", "create", &[("name", "string"), ("vertices", "table"), ("indices", "table"), ("uvs?", "table"), ("normals?", "table")], &[],
+        "Create a mesh that entities can use as `runtime:<name>`, or replace the geometry of an existing one. Vertices and normals are `{x, y, z}`, UVs `{u, v}`; without normals smooth ones are computed."),
+
+    api("camera", "world_to_screen", &[("x", "number"), ("y", "number"), ("z", "number")],
+        &[("sx", "number"), ("sy", "number"), ("visible", "boolean")],
+        "Project a world point to screen pixels."),
+    api("camera", "screen_to_ray", &[("sx", "number"), ("sy", "number")],
+        &[("ox", "number"), ("oy", "number"), ("oz", "number"), ("dx", "number"), ("dy", "number"), ("dz", "number")],
+        "The world ray through a screen pixel."),
+    api("camera", "shake", &[("intensity", "number"), ("duration", "number")], &[], "Shake the camera."),
+    api("camera", "play_path", &[("name", "string"), ("duration?", "number")], &[],
+        "Fly the main camera along a path from `camera_paths/`, stretched to `duration` seconds."),
+    api("camera", "stop_path", &[], &[], "Stop the playing camera path and hand the camera back."),
+    api("camera", "path_playing", &[], &[("name", "string?"), ("seconds", "number?")], "The playing camera path and its time, or nil."),
+
+    api("particles", "spawn_burst", &[("x", "number"), ("y", "number"), ("z", "number"), ("count", "integer"), ("config", "table|string")], &[],
+        "Spawn a one-shot burst of particles without an emitter entity, from a config table or the name of an `effects/` preset."),
+
+    api("ui", "text", &[("x", "number"), ("y", "number"), ("text", "string"), ("size", "number"), ("r", "number"), ("g", "number"), ("b", "number"), ("a", "number")], &[],
+        "Draw text this frame."),
+    api("ui", "rect", &[("x", "number"), ("y", "number"), ("w", "number"), ("h", "number"), ("r", "number"), ("g", "number"), ("b", "number"), ("a", "number")], &[],
+        "Draw a filled rectangle this frame."),
+    api("ui", "flash", &[("r", "number"), ("g", "number"), ("b", "number"), ("a", "number"), ("duration", "number")], &[],
+        "Flash the screen with a color that fades out."),
+    api("ui", "text_width", &[("text", "string"), ("font_size", "number")], &[("pixels", "number")], "Width of a string in pixels."),
+    api("ui", "screen_width", &[], &[("pixels", "number")], "Screen width in pixels."),
+    api("ui", "screen_height", &[], &[("pixels", "number")], "Screen height in pixels."),
+
+    api("animation", "play", &[("id", "string"), ("state", "string")], &[], "Play an animation state on an entity."),
+    api("animation", "stop", &[("id", "string")], &[], "Stop an entity's animation."),
+    api("animation", "set_speed", &[("id", "string"), ("speed", "number")], &[], "Set an entity's playback speed."),
+    api("animation", "set_looping", &[("id", "string"), ("looping", "boolean")], &[], "Loop the current animation or not."),
+    api("animation", "get_state", &[("id", "string")], &[("state", "string")], "The playing clip's name, or \"none\"."),
+    api("animation", "get_time", &[("id", "string")], &[("seconds", "number")], "Playback time of the current clip."),
+
+    api("render", "set", &[("name", "string"), ("value", "number|boolean")], &[],
+        "Set a lighting or post-process parameter before the next frame."),
+    api("render", "set_param", &[("pass", "string"), ("name", "string"), ("value", "number|table")], &[],
+        "Set a uniform declared by a `postprocess` pass shader."),
+    api("render", "params", &[("pass", "string")], &[("names", "string[]?")], "A pass's parameter names, or nil."),
+
+    api("curve", "eval", &[("name", "string"), ("t", "number")], &[("value", "number")],
+        "Value of `curves/<name>.yaml` (or a defined curve) at time t."),
+    api("curve", "eval_normalized", &[("name", "string"), ("t", "number")], &[("value", "number")],
+        "Value at t from 0 to 1 across the curve's keys."),
+    api("curve", "duration", &[("name", "string")], &[("seconds", "number")], "Time between the curve's first and last keys."),
+    api("curve", "define", &[("name", "string"), ("curve", "table")], &[],
+        "Define a curve at runtime: `{ keys = {{t, v}, ...}, interpolation = \"smooth\", wrap = \"loop\" }`."),
+    api("pipeline", "switch", &[("name", "string")], &[],
+        "Compile another pipeline file (`pipelines/<name>.yaml`) and swap it in before the next frame."),
+    api("pipeline", "current", &[], &[("name", "string?")], "Name of the running pipeline, or nil."),
+    api("pipeline", "list", &[], &[("names", "string[]")], "Names of the pipeline files in `pipelines/`."),
+
+    api("crowd", "set_params", &[("group", "string"), ("params", "table")], &[], "Set a flock group's parameters."),
+    api("crowd", "params", &[("group", "string")], &[("params", "table")], "A flock group's parameters."),
+    api("crowd", "set_goal", &[("group", "string"), ("target", "number|string"), ("y?", "number"), ("z?", "number")], &[],
+        "Steer a group toward a point or an entity id."),
+    api("crowd", "clear_goal", &[("group", "string")], &[], "Remove a group's goal."),
+
+    api("noise", "emit", &[("x", "number"), ("y", "number"), ("z", "number"), ("loudness", "number"), ("tag", "string"), ("source_id?", "string")], &[],
+        "Make a gameplay noise AI can hear; also emitted as a `sound.noise` event."),
+    api("noise", "heard", &[("x", "number"), ("y", "number"), ("z", "number"), ("hearing?", "number")], &[("noises", "table")],
+        "Noises a listener at this point hears, nearest first."),
+
+    api("mods", "list", &[], &[("mods", "table")], "Installed mods in mount order."),
+    api("mods", "enable", &[("id", "string"), ("enabled?", "boolean")], &[], "Enable or disable a mod from the next launch."),
+    api("mods", "conflicts", &[], &[("conflicts", "table")], "Files provided by more than one mod and which one wins."),
+
+    api("steam", "available", &[], &[("available", "boolean")], "Whether Steam is running."),
+    api("steam", "unlock", &[("name", "string")], &[("ok", "boolean")], "Unlock an achievement."),
+    api("steam", "is_unlocked", &[("name", "string")], &[("unlocked", "boolean")], "Whether an achievement is unlocked."),
+    api("steam", "clear_achievement", &[("name", "string")], &[], "Lock an achievement again."),
+    api("steam", "set_presence", &[("key", "string"), ("value?", "string")], &[("ok", "boolean")],
+        "Set a rich presence key; nil removes it."),
+    api("steam", "clear_presence", &[], &[], "Remove all rich presence keys."),
+    api("steam", "cloud_write", &[("name", "string"), ("data", "string")], &[("ok", "boolean")], "Write a Steam Cloud file."),
+    api("steam", "cloud_read", &[("name", "string")], &[("data", "string?")], "Read a Steam Cloud file, or nil."),
+    api("steam", "overlay_active", &[], &[("active", "boolean")], "Whether the Steam overlay is open."),
+
+    api("analytics", "track", &[("name", "string"), ("data?", "table")], &[("tracked", "boolean")],
+        "Record a playtest event; false when tracking is off."),
+    api("analytics", "enabled", &[], &[("enabled", "boolean")], "Whether tracking is on."),
+    api("analytics", "set_enabled", &[("enabled", "boolean")], &[], "Turn tracking on or off, e.g. from a privacy setting."),
+    api("analytics", "flush", &[], &[], "Send pending events now."),
+
+    api("sequence", "play", &[("name", "string")], &[], "Play a cutscene from `sequences/`."),
+    api("sequence", "skip", &[], &[("skipped", "boolean")], "Skip the playing cutscene if it allows it."),
+    api("sequence", "stop", &[], &[], "End playback without applying the remaining cues."),
+    api("sequence", "is_playing", &[], &[("playing", "boolean")], "Whether a cutscene is playing."),
+    api("sequence", "current", &[], &[("name", "string?"), ("seconds", "number?")], "The playing cutscene and its time, or nil."),
+
+    api("time", "set_scale", &[("scale", "number")], &[], "Global time scale: 1 is normal, 0 freezes the game."),
+    api("time", "get_scale", &[], &[("scale", "number")], "The global time scale."),
+    api("time", "set_physics_scaled", &[("enabled", "boolean")], &[], "Whether physics follows the time scale."),
+    api("time", "unscaled_dt", &[], &[("dt", "number")], "Real frame time, e.g. for UI during slow motion."),
+    api("time", "elapsed", &[], &[("seconds", "number")], "Scaled seconds since the scene loaded."),
+    api("time", "set_entity_scale", &[("id", "string"), ("scale", "number")], &[],
+        "An entity's own time scale; 1 removes the override."),
+    api("time", "get_entity_scale", &[("id", "string")], &[("scale", "number")],
+        "An entity's own time scale, excluding the global one."),
+];
+
+/// Current level of the script API. Bump it with every change that needs an
+/// entry in `LUA_DEPRECATIONS`, so projects that migrated can declare the new
+/// level in `scripting.api_level` and stop getting the shims.
+pub const LUA_API_LEVEL: u32 = 1;
+
+/// A function removed from the script API, kept working for projects on an
+/// older API level by a shim written against the current API.
+pub struct LuaDeprecation {
+    /// Global table that held the function; empty for a global function.
+    pub table: &'static str,
+    pub name: &'static str,
+    /// First API level without the function.
+    pub level: u32,
+    /// Migration hint for the warning, e.g. "use `entity.get_tags` instead".
+    pub hint: &'static str,
+    /// Lua expression evaluating to the old function.
+    pub shim: &'static str,
+}
+
+impl LuaDeprecation {
+    /// Qualified name, e.g. `entity.get_position`.
+    pub fn path(&self) -> String {
+        qualified_name(self.table, self.name)
+    }
+}
+
+/// Deprecated functions, oldest first.
+pub const LUA_DEPRECATIONS: &[LuaDeprecation] = &[];
+
+/// Convert a Lua event payload table into JSON values (non-scalar values become null).
+fn lua_table_to_event_data(data: Option<LuaTable>) -> HashMap<String, serde_json::Value> {
+    let mut map = HashMap::new();
+    let Some(data) = data else {
+        return map;
+    };
+    for (key, val) in data.pairs::<String, LuaValue>().flatten() {
+        let json_val = match val {
+            LuaValue::Integer(i) => serde_json::Value::Number(serde_json::Number::from(i)),
+            LuaValue::Number(n) => serde_json::Number::from_f64(n)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            LuaValue::String(s) => serde_json::Value::String(s.to_string_lossy().to_string()),
+            LuaValue::Boolean(b) => serde_json::Value::Bool(b),
+            _ => serde_json::Value::Null,
+        };
+        map.insert(key, json_val);
+    }
+    map
+}
+
+/// " (at file.lua:12)" for the Lua code that called the current Rust function, if known.
+fn lua_caller_location(lua: &Lua) -> String {
+    lua.inspect_stack(1)
+        .and_then(|frame| {
+            let line = frame.curr_line();
+            let src = frame.source().short_src.map(|s| s.to_string())?;
+            Some(if line > 0 { format!(" (at {}:{})", src, line) } else { format!(" (at {})", src) })
+        })
+        .unwrap_or_default()
+}
+
+/// Read a Lua value that may be a single string or an array of strings.
+fn lua_string_list(value: LuaValue) -> LuaResult<Vec<String>> {
+    match value {
+        LuaNil => Ok(Vec::new()),
+        LuaValue::String(s) => Ok(vec![s.to_str()?.to_string()]),
+        LuaValue::Table(t) => t.sequence_values::<String>().collect(),
+        other => Err(LuaError::RuntimeError(format!(
+            "expected a string or list of strings, got {}",
+            other.type_name()
+        ))),
+    }
+}
+
+/// Resolve a Lua physics query filter table into entities.
+/// Unknown ids are ignored so excluding an already-destroyed entity is harmless.
+fn lua_query_filter(sw: &SceneWorld, filter: Option<&LuaTable>) -> LuaResult<crate::physics::EntityQueryFilter> {
+    let mut result = crate::physics::EntityQueryFilter::default();
+    let Some(filter) = filter else {
+        return Ok(result);
+    };
+
+    for id in lua_string_list(filter.get("exclude")?)? {
+        if let Some(&entity) = sw.entity_registry.get(&id) {
+            result.exclude.insert(entity);
+        }
+    }
+
+    let include_tags = lua_string_list(filter.get("include_tags")?)?;
+    let exclude_tags = lua_string_list(filter.get("exclude_tags")?)?;
+    if !include_tags.is_empty() || !exclude_tags.is_empty() {
+        let mut included = std::collections::HashSet::new();
+        for (entity, tags) in sw.world.query::<&Tags>().iter() {
+            if tags.0.iter().any(|t| exclude_tags.contains(t)) {
+                result.exclude.insert(entity);
+            }
+            if tags.0.iter().any(|t| include_tags.contains(t)) {
+                included.insert(entity);
+            }
+        }
+        if !include_tags.is_empty() {
+            result.include_only = Some(included);
+        }
+    }
+
+    result.exclude_triggers = !filter.get::<Option<bool>>("triggers")?.unwrap_or(true);
+    Ok(result)
+}
+
+/// Build the Lua table passed to `events.on` listeners.
+/// When the event type has a schema entry, `data` is strict: reading an undeclared
+/// field raises an error instead of silently returning nil.
+pub fn event_to_lua_table(lua: &Lua, event: &crate::events::GameEvent, bus: &EventBus) -> LuaResult<LuaTable> {
+    let tbl = lua.create_table()?;
+    tbl.set("type", event.event_type.clone())?;
+    let data_tbl = lua.create_table()?;
+    for (k, v) in &event.data {
+        match v {
+            serde_json::Value::Number(n) => {
+                if let Some(f) = n.as_f64() {
+                    data_tbl.set(k.as_str(), f)?;
+                }
+            }
+            serde_json::Value::String(s) => data_tbl.set(k.as_str(), s.as_str())?,
+            serde_json::Value::Bool(b) => data_tbl.set(k.as_str(), *b)?,
+            _ => {}
+        }
+    }
+
+    if let Some(event_schema) = bus.schema().and_then(|s| s.events.get(&event.event_type)) {
+        let declared: Vec<String> = event_schema.fields.iter().map(|f| f.name.clone()).collect();
+        let event_type = event.event_type.clone();
+        let meta = lua.create_table()?;
+        meta.set("__index", lua.create_function(move |lua, (_, key): (LuaTable, LuaValue)| {
+            let key = match key {
+                LuaValue::String(s) => s.to_string_lossy().to_string(),
+                _ => return Ok(LuaNil),
+            };
+            if declared.contains(&key) {
+                Ok(LuaNil)
+            } else {
+                Err(LuaError::RuntimeError(format!(
+                    "event '{}' has no field '{}'{}",
+                    event_type,
+                    key,
+                    lua_caller_location(lua)
+                )))
+            }
+        })?)?;
+        data_tbl.set_metatable(Some(meta));
+    }
+
+    tbl.set("data", data_tbl)?;
+    Ok(tbl)
+}
+
+/// Parse a Lua entity table with the same deserializer as scene YAML entities.
+pub fn entity_def_from_lua(lua: &Lua, tbl: LuaTable) -> LuaResult<crate::scene::EntityDef> {
+    lua.from_value(LuaValue::Table(tbl))
+        .map_err(|e| mlua::Error::runtime(format!("spawn_ex: invalid entity: {}", e)))
+}
+
+/// Parse a `physics.set_collider` table. The shape uses the scene `collider`
+/// deserializer; the material fields are only overridden when present.
+pub fn collider_update_from_lua(
+    lua: &Lua,
+    entity_id: String,
+    tbl: LuaTable,
+) -> LuaResult<crate::world::ColliderUpdateCommand> {
+    let collider: crate::scene::ColliderDef = lua
+        .from_value(LuaValue::Table(tbl.clone()))
+        .map_err(|e| mlua::Error::runtime(format!("set_collider: invalid collider: {}", e)))?;
+    if !matches!(collider.shape.as_str(), "box" | "sphere" | "capsule" | "trimesh") {
+        return Err(mlua::Error::runtime(format!(
+            "set_collider: unknown shape '{}' (expected box, sphere, capsule or trimesh)",
+            collider.shape
+        )));
+    }
+    Ok(crate::world::ColliderUpdateCommand {
+        entity_id,
+        collider,
+        is_trigger: tbl.get("is_trigger")?,
+        restitution: tbl.get("restitution")?,
+        friction: tbl.get("friction")?,
+    })
 }
 
 #[cfg(test)]
@@ -1791,4 +3403,124 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    #[test]
+    fn test_entity_def_from_lua_uses_scene_parsing() {
+        let lua = Lua::new();
+        let tbl: LuaTable = lua.load(r#"return {
+            id = "grunt_1", tags = {"enemy"},
+            components = {
+                transform = { position = {1, 0, 2.5} },
+                point_light = { color = {1, 0, 0} },
+                collider = { shape = "capsule", radius = 0.4 },
+                rigid_body = { type = "dynamic", mass = 2 },
+                health = { max = 50 },
+                script = { source = "logic/grunt.lua" },
+            },
+        }"#).eval().unwrap();
+        let def = entity_def_from_lua(&lua, tbl).unwrap();
+        assert_eq!(def.tags, vec!["enemy"]);
+        let c = &def.components;
+        assert_eq!(c.transform.as_ref().unwrap().position, [1.0, 0.0, 2.5]);
+        assert_eq!(c.transform.as_ref().unwrap().scale, [1.0, 1.0, 1.0]);
+        assert_eq!(c.point_light.as_ref().unwrap().range, 10.0);
+        assert_eq!(c.rigid_body.as_ref().unwrap().body_type, "dynamic");
+        assert_eq!(c.health.as_ref().unwrap().max, 50.0);
+        assert_eq!(c.script.as_ref().unwrap().source, "logic/grunt.lua");
+
+        // Same validation as scene YAML: health needs `max`
+        let bad: LuaTable = lua.load(r#"return { id = "x", components = { health = { current = 5 } } }"#).eval().unwrap();
+        let err = entity_def_from_lua(&lua, bad).unwrap_err();
+        assert!(err.to_string().contains("max"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_collider_update_from_lua() {
+        let lua = Lua::new();
+        let tbl: LuaTable = lua.load(r#"return { shape = "capsule", radius = 0.3, half_height = 0.6, friction = 0.2 }"#).eval().unwrap();
+        let update = collider_update_from_lua(&lua, "player".to_string(), tbl).unwrap();
+        assert_eq!(update.entity_id, "player");
+        assert!(matches!(
+            crate::world::parse_collider_shape(&update.collider),
+            crate::physics::PhysicsShape::Capsule { half_height, radius } if half_height == 0.6 && radius == 0.3
+        ));
+        assert_eq!(update.friction, Some(0.2));
+        assert_eq!((update.is_trigger, update.restitution), (None, None));
+
+        let bad: LuaTable = lua.load(r#"return { shape = "cylinder" }"#).eval().unwrap();
+        assert!(collider_update_from_lua(&lua, "x".to_string(), bad).is_err());
+    }
+
+    /// Every `<table>.set("name", <x>_fn)` before the tests must be declared
+    /// in `LUA_API`, and every declaration must be registered.
+    #[test]
+    fn test_lua_api_declarations_match_registrations() {
+        let source = include_str!("scripting.rs");
+        let source = &source[..source.find("#[cfg(test)]").unwrap()];
+
+        // Table variables: `globals.set("input", input_table)`
+        let mut tables: HashMap<&str, &str> = HashMap::new();
+        let mut calls = Vec::new();
+        for line in source.lines() {
+            let line = line.trim();
+            let Some(dot) = line.find(".set(\"") else { continue };
+            let receiver = &line[..dot];
+            let rest = &line[dot + 6..];
+            let Some(quote) = rest.find('"') else { continue };
+            let name = &rest[..quote];
+            let value = rest[quote + 1..].trim_start_matches(", ");
+            let value = &value[..value.find(')').unwrap_or(value.len())];
+            if receiver == "globals" && value.ends_with("_table") {
+                tables.insert(value, name);
+            } else if value.ends_with("_fn") {
+                calls.push((receiver, name));
+            }
+        }
+        let registered: std::collections::BTreeSet<String> = calls
+            .into_iter()
+            .map(|(receiver, name)| match receiver {
+                "globals" => name.to_string(),
+                var => {
+                    let table = tables.get(var).copied().unwrap_or_else(|| var.trim_end_matches("_table"));
+                    format!("{}.{}", table, name)
+                }
+            })
+            .collect();
+        let declared: std::collections::BTreeSet<String> = LUA_API.iter().map(|f| f.path()).collect();
+        assert_eq!(declared.len(), LUA_API.len(), "duplicate LUA_API entry");
+
+        let undeclared: Vec<_> = registered.difference(&declared).collect();
+        let unregistered: Vec<_> = declared.difference(&registered).collect();
+        assert!(undeclared.is_empty(), "registered but missing from LUA_API: {:?}", undeclared);
+        assert!(unregistered.is_empty(), "in LUA_API but never registered: {:?}", unregistered);
+    }
+
+    #[test]
+    fn test_api_shims_follow_project_level() {
+        for deprecation in LUA_DEPRECATIONS {
+            assert!(deprecation.level <= LUA_API_LEVEL, "{} deprecated at a future level", deprecation.path());
+            assert!(!LUA_API.iter().any(|f| f.path() == deprecation.path()), "{} is still in LUA_API", deprecation.path());
+        }
+        let runtime = ScriptRuntime::new();
+        runtime.register_api_shims(1, LUA_DEPRECATIONS).unwrap();
+
+        let renamed = [LuaDeprecation {
+            table: "demo",
+            name: "twice",
+            level: 2,
+            hint: "use `demo.scale(x, 2)` instead",
+            shim: "function(x) return demo.scale(x, 2) end",
+        }];
+        let runtime = ScriptRuntime::new();
+        runtime.lua.load("demo = { scale = function(x, k) return x * k end }").exec().unwrap();
+        runtime.register_api_shims(1, &renamed).unwrap();
+        let doubled: f64 = runtime.lua.load("return demo.twice(3) + demo.twice(1)").eval().unwrap();
+        assert_eq!(doubled, 8.0);
+
+        // Projects that migrated don't get the shim
+        let runtime = ScriptRuntime::new();
+        runtime.register_api_shims(2, &renamed).unwrap();
+        let missing: bool = runtime.lua.load("return demo == nil or demo.twice == nil").eval().unwrap();
+        assert!(missing);
+    }
 }
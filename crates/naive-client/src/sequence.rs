@@ -0,0 +1,560 @@
+//! Cutscene sequencer.
+//!
+//! A sequence is a timeline in `sequences/<name>.yaml` with tracks for the
+//! camera path, animation cues, audio, events, subtitles and letterbox bars.
+//! `sequence.play("intro")` starts one. While it plays it owns the main
+//! camera (and by default the player's controls), and the engine applies each
+//! cue as playback passes it. A skippable sequence can be cut short with
+//! `sequence.skip()`: the remaining events still fire and every entity gets
+//! its last animation cue, so the game ends up in the same state as after
+//! watching it through.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use glam::{Quat, Vec3};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::engine_events::{payload, SEQUENCE_FINISHED, SEQUENCE_STARTED};
+use crate::format::Format;
+use crate::mods::ModOverlay;
+
+/// Directory holding the sequences, relative to the project root.
+pub const SEQUENCES_DIR: &str = "sequences";
+
+/// Sequence files (`sequences/*.yaml`).
+pub const SEQUENCE_FORMAT: Format = Format { name: "sequence", current: 1, migrations: &[] };
+
+/// Letterbox bars never cover more than this fraction of the screen, each.
+pub const MAX_LETTERBOX: f32 = 0.4;
+
+/// A sequence file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Sequence {
+    /// Seconds; defaults to the end of the last key or cue.
+    #[serde(default)]
+    pub duration: Option<f32>,
+    #[serde(default = "default_true")]
+    pub skippable: bool,
+    /// Ignore the player's movement and look input while playing.
+    #[serde(default = "default_true")]
+    pub lock_player: bool,
+    #[serde(default)]
+    pub camera: Vec<CameraKey>,
+    #[serde(default)]
+    pub animations: Vec<AnimationCue>,
+    #[serde(default)]
+    pub audio: Vec<AudioCue>,
+    #[serde(default)]
+    pub events: Vec<EventCue>,
+    #[serde(default)]
+    pub subtitles: Vec<SubtitleCue>,
+    #[serde(default)]
+    pub letterbox: Vec<LetterboxKey>,
+}
+
+/// A point the camera passes through, looking at `look_at`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CameraKey {
+    pub time: f32,
+    pub position: [f32; 3],
+    pub look_at: [f32; 3],
+    /// Field of view in degrees (the camera's own when unset).
+    #[serde(default)]
+    pub fov: Option<f32>,
+}
+
+/// Switch an entity's animation state.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AnimationCue {
+    pub time: f32,
+    pub entity: String,
+    pub state: String,
+}
+
+/// Play a sound effect, or music when `music` is set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AudioCue {
+    pub time: f32,
+    pub sound: String,
+    #[serde(default)]
+    pub music: bool,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Music fade-in seconds.
+    #[serde(default)]
+    pub fade: f32,
+}
+
+/// Emit a game event on the event bus.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EventCue {
+    pub time: f32,
+    pub name: String,
+    #[serde(default)]
+    pub data: HashMap<String, Value>,
+}
+
+/// A line of text shown from `time` for `duration` seconds.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SubtitleCue {
+    pub time: f32,
+    pub duration: f32,
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<String>,
+}
+
+/// Letterbox bar height (fraction of the screen height, each) at `time`;
+/// eased linearly between keys.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LetterboxKey {
+    pub time: f32,
+    pub amount: f32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+impl Sequence {
+    /// Parse and check a sequence file, sorting every track by time.
+    pub fn parse(yaml: &str) -> Result<Self, String> {
+        let mut sequence: Sequence = match SEQUENCE_FORMAT.migrate(yaml)? {
+            Some(doc) => serde_yaml::from_value(doc),
+            None => serde_yaml::from_str(yaml),
+        }
+        .map_err(|e| e.to_string())?;
+
+        let times = sequence
+            .camera
+            .iter()
+            .map(|k| k.time)
+            .chain(sequence.animations.iter().map(|c| c.time))
+            .chain(sequence.audio.iter().map(|c| c.time))
+            .chain(sequence.events.iter().map(|c| c.time))
+            .chain(sequence.subtitles.iter().map(|c| c.time))
+            .chain(sequence.letterbox.iter().map(|k| k.time));
+        for time in times.chain(sequence.duration) {
+            if !(time.is_finite() && time >= 0.0) {
+                return Err(format!("times must be zero or more seconds, got {}", time));
+            }
+        }
+
+        let by_time = |a: f32, b: f32| a.total_cmp(&b);
+        sequence.camera.sort_by(|a, b| by_time(a.time, b.time));
+        sequence.animations.sort_by(|a, b| by_time(a.time, b.time));
+        sequence.audio.sort_by(|a, b| by_time(a.time, b.time));
+        sequence.events.sort_by(|a, b| by_time(a.time, b.time));
+        sequence.subtitles.sort_by(|a, b| by_time(a.time, b.time));
+        sequence.letterbox.sort_by(|a, b| by_time(a.time, b.time));
+        for key in &mut sequence.letterbox {
+            key.amount = key.amount.clamp(0.0, MAX_LETTERBOX);
+        }
+        Ok(sequence)
+    }
+
+    /// Length in seconds.
+    pub fn length(&self) -> f32 {
+        self.duration.unwrap_or_else(|| {
+            self.camera
+                .iter()
+                .map(|k| k.time)
+                .chain(self.animations.iter().map(|c| c.time))
+                .chain(self.audio.iter().map(|c| c.time))
+                .chain(self.events.iter().map(|c| c.time))
+                .chain(self.subtitles.iter().map(|c| c.time + c.duration))
+                .chain(self.letterbox.iter().map(|k| k.time))
+                .fold(0.0, f32::max)
+        })
+    }
+
+    /// Camera on the path at `time`: a Catmull-Rom curve through the key
+    /// positions, with the look-at point and field of view eased linearly.
+    /// None without camera keys.
+    pub fn camera_at(&self, time: f32) -> Option<CameraPose> {
+        let keys = &self.camera;
+        let last = keys.len().checked_sub(1)?;
+        let i = keys.iter().rposition(|k| k.time <= time).unwrap_or(0).min(last);
+        let j = (i + 1).min(last);
+        let span = keys[j].time - keys[i].time;
+        let t = if span > 0.0 { ((time - keys[i].time) / span).clamp(0.0, 1.0) } else { 0.0 };
+
+        let p = |index: usize| Vec3::from(keys[index].position);
+        let position = catmull_rom(p(i.saturating_sub(1)), p(i), p(j), p((j + 1).min(last)), t);
+        let look_at = Vec3::from(keys[i].look_at).lerp(Vec3::from(keys[j].look_at), t);
+        let fov = match (keys[i].fov, keys[j].fov) {
+            (Some(a), Some(b)) => Some(a + (b - a) * t),
+            (a, b) => a.or(b),
+        };
+        Some(CameraPose { position, rotation: look_rotation(look_at - position), fov })
+    }
+
+    /// Subtitle showing at `time`, the latest one if several overlap.
+    pub fn subtitle_at(&self, time: f32) -> Option<&SubtitleCue> {
+        self.subtitles.iter().rev().find(|s| time >= s.time && time < s.time + s.duration)
+    }
+
+    /// Letterbox bar height at `time` (0 without keys).
+    pub fn letterbox_at(&self, time: f32) -> f32 {
+        let keys = &self.letterbox;
+        let Some(i) = keys.iter().rposition(|k| k.time <= time) else {
+            return keys.first().map_or(0.0, |k| k.amount);
+        };
+        match keys.get(i + 1) {
+            Some(next) if next.time > keys[i].time => {
+                let t = (time - keys[i].time) / (next.time - keys[i].time);
+                keys[i].amount + (next.amount - keys[i].amount) * t
+            }
+            _ => keys[i].amount,
+        }
+    }
+}
+
+/// Camera transform a sequence asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Degrees; None keeps the camera's own.
+    pub fov: Option<f32>,
+}
+
+pub(crate) fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Rotation of a camera (looking down -Z) facing `forward`, without roll.
+pub(crate) fn look_rotation(forward: Vec3) -> Quat {
+    let forward = forward.normalize_or(Vec3::NEG_Z);
+    let yaw = (-forward.x).atan2(-forward.z);
+    let pitch = forward.y.clamp(-1.0, 1.0).asin();
+    Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch)
+}
+
+/// Something the engine does for a playing sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceAction {
+    Animate { entity: String, state: String },
+    Sound { id: String, path: String, volume: f32 },
+    Music { path: String, volume: f32, fade: f32 },
+    StopSound { id: String },
+    /// A game event, or `sequence.started` / `sequence.finished`.
+    Event { name: String, data: HashMap<String, Value> },
+}
+
+/// The sequence playing now and how far along it is.
+#[derive(Debug, Clone)]
+struct Playback {
+    name: String,
+    sequence: Rc<Sequence>,
+    time: f32,
+    /// Cues of each one-shot track already applied.
+    next_animation: usize,
+    next_audio: usize,
+    next_event: usize,
+}
+
+/// Loads sequences and plays one at a time.
+#[derive(Debug, Default)]
+pub struct SequencePlayer {
+    root: PathBuf,
+    /// Mods may replace or add sequences.
+    pub overlay: ModOverlay,
+    cache: HashMap<String, Rc<Sequence>>,
+    active: Option<Playback>,
+    pending: Vec<SequenceAction>,
+}
+
+impl SequencePlayer {
+    pub fn new(project_root: &Path) -> Self {
+        Self { root: project_root.to_path_buf(), ..Default::default() }
+    }
+
+    /// `sequences/<name>.yaml`, parsed once and cached.
+    pub fn load(&mut self, name: &str) -> Result<Rc<Sequence>, String> {
+        if let Some(sequence) = self.cache.get(name) {
+            return Ok(sequence.clone());
+        }
+        let relative = Path::new(SEQUENCES_DIR).join(format!("{}.yaml", name));
+        let path = self.overlay.resolve(&self.root, &relative);
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let sequence = Rc::new(Sequence::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))?);
+        self.cache.insert(name.to_string(), sequence.clone());
+        Ok(sequence)
+    }
+
+    /// Load and start `name`, replacing whatever is playing.
+    pub fn play(&mut self, name: &str) -> Result<(), String> {
+        let sequence = self.load(name)?;
+        self.start(name, sequence);
+        Ok(())
+    }
+
+    /// Start an already loaded sequence.
+    pub fn start(&mut self, name: &str, sequence: Rc<Sequence>) {
+        self.stop();
+        self.active = Some(Playback {
+            name: name.to_string(),
+            sequence,
+            time: 0.0,
+            next_animation: 0,
+            next_audio: 0,
+            next_event: 0,
+        });
+        self.pending.push(SequenceAction::Event {
+            name: SEQUENCE_STARTED.to_string(),
+            data: payload(json!({ "name": name })),
+        });
+    }
+
+    /// Jump to the end of a skippable sequence. Remaining events fire and
+    /// entities take their last animation cue; sounds and subtitles don't
+    /// play. False when nothing skippable is playing.
+    pub fn skip(&mut self) -> bool {
+        let Some(mut playback) = self.active.take_if(|p| p.sequence.skippable) else {
+            return false;
+        };
+        let sequence = playback.sequence.clone();
+
+        // Only the last cue per entity matters once the cutscene is over
+        let mut last_states: Vec<&AnimationCue> = Vec::new();
+        for cue in &sequence.animations[playback.next_animation..] {
+            last_states.retain(|c| c.entity != cue.entity);
+            last_states.push(cue);
+        }
+        self.pending.extend(last_states.into_iter().map(|cue| SequenceAction::Animate {
+            entity: cue.entity.clone(),
+            state: cue.state.clone(),
+        }));
+        self.pending.extend(sequence.events[playback.next_event..].iter().map(|cue| SequenceAction::Event {
+            name: cue.name.clone(),
+            data: cue.data.clone(),
+        }));
+        playback.next_animation = sequence.animations.len();
+        playback.next_event = sequence.events.len();
+        self.finish(playback, true);
+        true
+    }
+
+    /// End the sequence now without applying its remaining cues.
+    pub fn stop(&mut self) {
+        if let Some(playback) = self.active.take() {
+            self.finish(playback, true);
+        }
+    }
+
+    /// Forget the playing sequence without any actions (scene change) and
+    /// drop cached files so edits are picked up.
+    pub fn reset(&mut self) {
+        self.active = None;
+        self.pending.clear();
+        self.cache.clear();
+    }
+
+    fn finish(&mut self, playback: Playback, skipped: bool) {
+        // Cut off the effects this sequence started; its music keeps playing
+        let sounds = playback.sequence.audio[..playback.next_audio]
+            .iter()
+            .enumerate()
+            .filter(|(_, cue)| !cue.music);
+        self.pending.extend(sounds.map(|(i, _)| SequenceAction::StopSound { id: sound_id(&playback.name, i) }));
+        self.pending.push(SequenceAction::Event {
+            name: SEQUENCE_FINISHED.to_string(),
+            data: payload(json!({ "name": playback.name, "skipped": skipped })),
+        });
+    }
+
+    /// Advance by `dt` seconds and return what to apply this frame.
+    pub fn update(&mut self, dt: f32) -> Vec<SequenceAction> {
+        let Some(playback) = &mut self.active else {
+            return std::mem::take(&mut self.pending);
+        };
+        playback.time += dt.max(0.0);
+        let time = playback.time;
+        let sequence = playback.sequence.clone();
+
+        for cue in sequence.animations[playback.next_animation..].iter().take_while(|c| c.time <= time) {
+            self.pending.push(SequenceAction::Animate { entity: cue.entity.clone(), state: cue.state.clone() });
+            playback.next_animation += 1;
+        }
+        for cue in sequence.audio[playback.next_audio..].iter().take_while(|c| c.time <= time) {
+            self.pending.push(if cue.music {
+                SequenceAction::Music { path: cue.sound.clone(), volume: cue.volume, fade: cue.fade }
+            } else {
+                SequenceAction::Sound {
+                    id: sound_id(&playback.name, playback.next_audio),
+                    path: cue.sound.clone(),
+                    volume: cue.volume,
+                }
+            });
+            playback.next_audio += 1;
+        }
+        for cue in sequence.events[playback.next_event..].iter().take_while(|c| c.time <= time) {
+            self.pending.push(SequenceAction::Event { name: cue.name.clone(), data: cue.data.clone() });
+            playback.next_event += 1;
+        }
+
+        if time >= sequence.length() {
+            if let Some(playback) = self.active.take() {
+                // Played to the end: its sounds may ring out
+                self.pending.push(SequenceAction::Event {
+                    name: SEQUENCE_FINISHED.to_string(),
+                    data: payload(json!({ "name": playback.name, "skipped": false })),
+                });
+            }
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Name of the playing sequence and seconds into it.
+    pub fn current(&self) -> Option<(&str, f32)> {
+        self.active.as_ref().map(|p| (p.name.as_str(), p.time))
+    }
+
+    /// Whether the player's controls are locked by the playing sequence.
+    pub fn locks_player(&self) -> bool {
+        self.active.as_ref().is_some_and(|p| p.sequence.lock_player)
+    }
+
+    pub fn camera(&self) -> Option<CameraPose> {
+        self.active.as_ref().and_then(|p| p.sequence.camera_at(p.time))
+    }
+
+    pub fn subtitle(&self) -> Option<&SubtitleCue> {
+        self.active.as_ref().and_then(|p| p.sequence.subtitle_at(p.time))
+    }
+
+    pub fn letterbox(&self) -> f32 {
+        self.active.as_ref().map_or(0.0, |p| p.sequence.letterbox_at(p.time))
+    }
+}
+
+/// Audio id of a sequence's sound effect, so it can be stopped on skip.
+fn sound_id(sequence: &str, cue: usize) -> String {
+    format!("sequence:{}:{}", sequence, cue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTRO: &str = r#"
+skippable: true
+camera:
+  - { time: 4, position: [10, 2, 0], look_at: [0, 1, 0], fov: 50 }
+  - { time: 0, position: [0, 2, 10], look_at: [0, 1, 0], fov: 70 }
+animations:
+  - { time: 1, entity: guard, state: walk }
+  - { time: 3, entity: guard, state: idle }
+audio:
+  - { time: 0, sound: audio/intro.ogg, music: true, volume: 0.8, fade: 1.0 }
+  - { time: 2, sound: audio/door.ogg }
+events:
+  - { time: 2.5, name: intro.door_opened, data: { door: front } }
+subtitles:
+  - { time: 0.5, duration: 2, text: "Where am I?", speaker: Mara }
+letterbox:
+  - { time: 0, amount: 0 }
+  - { time: 1, amount: 0.1 }
+"#;
+
+    fn names(actions: &[SequenceAction]) -> Vec<String> {
+        actions
+            .iter()
+            .map(|a| match a {
+                SequenceAction::Animate { entity, state } => format!("{}:{}", entity, state),
+                SequenceAction::Sound { path, .. } => format!("sfx:{}", path),
+                SequenceAction::Music { path, .. } => format!("music:{}", path),
+                SequenceAction::StopSound { id } => format!("stop:{}", id),
+                SequenceAction::Event { name, .. } => name.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_sorts_tracks_and_measures_length() {
+        let sequence = Sequence::parse(INTRO).unwrap();
+        assert_eq!(sequence.camera[0].time, 0.0);
+        assert_eq!(sequence.length(), 4.0);
+        assert_eq!(sequence.audio[1].volume, 1.0);
+        assert!(sequence.lock_player);
+        assert!(Sequence::parse("events: [{ time: -1, name: x }]").is_err());
+        assert!(Sequence::parse("version: 9\n").unwrap_err().contains("update the engine"));
+    }
+
+    #[test]
+    fn test_camera_path_subtitles_and_letterbox() {
+        let sequence = Sequence::parse(INTRO).unwrap();
+        let start = sequence.camera_at(0.0).unwrap();
+        assert!((start.position - Vec3::new(0.0, 2.0, 10.0)).length() < 1e-5);
+        assert_eq!(start.fov, Some(70.0));
+        // Facing the look-at point: -Z rotated toward the origin
+        let forward = start.rotation * Vec3::NEG_Z;
+        assert!((forward - (Vec3::new(0.0, -1.0, -10.0)).normalize()).length() < 1e-4);
+
+        let middle = sequence.camera_at(2.0).unwrap();
+        assert_eq!(middle.fov, Some(60.0));
+        assert!((middle.position - Vec3::new(5.0, 2.0, 5.0)).length() < 1e-4);
+        // Held at the last key afterwards
+        assert!((sequence.camera_at(9.0).unwrap().position - Vec3::new(10.0, 2.0, 0.0)).length() < 1e-5);
+
+        assert_eq!(sequence.subtitle_at(1.0).unwrap().text, "Where am I?");
+        assert!(sequence.subtitle_at(2.5).is_none());
+        assert!((sequence.letterbox_at(0.5) - 0.05).abs() < 1e-6);
+        assert_eq!(sequence.letterbox_at(3.0), 0.1);
+    }
+
+    #[test]
+    fn test_playback_fires_cues_in_order() {
+        let mut player = SequencePlayer::default();
+        player.start("intro", Rc::new(Sequence::parse(INTRO).unwrap()));
+        assert!(player.locks_player());
+        assert_eq!(names(&player.update(0.1)), ["sequence.started", "music:audio/intro.ogg"]);
+        assert_eq!(names(&player.update(1.0)), ["guard:walk"]);
+        assert_eq!(names(&player.update(1.5)), ["sfx:audio/door.ogg", "intro.door_opened"]);
+        assert!(player.update(0.1).is_empty());
+        assert_eq!(player.current().map(|(name, _)| name), Some("intro"));
+
+        assert_eq!(names(&player.update(2.0)), ["guard:idle", "sequence.finished"]);
+        assert!(!player.is_playing() && player.camera().is_none());
+    }
+
+    #[test]
+    fn test_skip_applies_outcome_without_the_show() {
+        let mut player = SequencePlayer::default();
+        player.start("intro", Rc::new(Sequence::parse(INTRO).unwrap()));
+        player.update(2.1);
+        assert!(player.skip());
+        let actions = player.update(0.1);
+        assert_eq!(
+            names(&actions),
+            ["guard:idle", "intro.door_opened", "stop:sequence:intro:1", "sequence.finished"]
+        );
+        let SequenceAction::Event { data, .. } = &actions[3] else { panic!() };
+        assert_eq!(data["skipped"], true);
+        assert!(!player.skip());
+
+        let mut locked = Sequence::parse(INTRO).unwrap();
+        locked.skippable = false;
+        player.start("locked", Rc::new(locked));
+        assert!(!player.skip());
+        assert!(player.is_playing());
+    }
+}
@@ -77,9 +77,19 @@ pub fn compile_slang_to_wgsl_public(path: &Path) -> Result<String, ShaderError>
     compile_slang_to_wgsl(path)
 }
 
+/// SLANG-to-WGSL compilation with preprocessor defines (`(name, value)`),
+/// for shader variants.
+pub fn compile_slang_to_wgsl_with_defines(path: &Path, defines: &[(String, String)]) -> Result<String, ShaderError> {
+    compile_slang_variant(path, defines)
+}
+
+fn compile_slang_to_wgsl(path: &Path) -> Result<String, ShaderError> {
+    compile_slang_variant(path, &[])
+}
+
 /// Actual SLANG-to-WGSL compilation using shader-slang crate.
 #[cfg(feature = "slang")]
-fn compile_slang_to_wgsl(path: &Path) -> Result<String, ShaderError> {
+fn compile_slang_variant(path: &Path, defines: &[(String, String)]) -> Result<String, ShaderError> {
     use shader_slang as slang;
     use std::ffi::CString;
 
@@ -106,14 +116,26 @@ fn compile_slang_to_wgsl(path: &Path) -> Result<String, ShaderError> {
     let modules_path_c = CString::new(modules_path.as_str()).map_err(|e| {
         ShaderError::SlangCompilationFailed(format!("Invalid search path: {:?}", e))
     })?;
-    let search_paths_ptrs = [search_path_c.as_ptr(), modules_path_c.as_ptr()];
+    // And the shaders directory, where `#include "common/brdf.slang"` resolves
+    let shaders_path = path
+        .parent()
+        .and_then(|p| p.parent())
+        .unwrap_or(Path::new("."))
+        .to_string_lossy()
+        .to_string();
+    let shaders_path_c = CString::new(shaders_path.as_str()).map_err(|e| {
+        ShaderError::SlangCompilationFailed(format!("Invalid search path: {:?}", e))
+    })?;
+    let search_paths_ptrs = [search_path_c.as_ptr(), modules_path_c.as_ptr(), shaders_path_c.as_ptr()];
 
     let target_desc = slang::TargetDesc::default()
         .format(slang::CompileTarget::Wgsl);
     let targets = [target_desc];
 
-    let options = slang::CompilerOptions::default()
-        .matrix_layout_column(true);
+    let options = defines.iter().fold(
+        slang::CompilerOptions::default().matrix_layout_column(true),
+        |options, (name, value)| options.macro_define(name, value),
+    );
 
     let session_desc = slang::SessionDesc::default()
         .targets(&targets)
@@ -131,12 +153,9 @@ fn compile_slang_to_wgsl(path: &Path) -> Result<String, ShaderError> {
         .and_then(|s| s.to_str())
         .ok_or_else(|| ShaderError::SlangCompilationFailed("Invalid file name".to_string()))?;
 
-    let module = session.load_module(file_name).map_err(|e| {
-        ShaderError::SlangCompilationFailed(format!(
-            "Failed to load module '{}': {:?}",
-            file_name, e
-        ))
-    })?;
+    let module = session
+        .load_module(file_name)
+        .map_err(|e| slang_failure(&format!("Failed to load module '{}'", file_name), &e))?;
 
     let vertex_entry = module.find_entry_point_by_name("vs_main").ok_or_else(|| {
         ShaderError::SlangCompilationFailed("Vertex entry point 'vs_main' not found".to_string())
@@ -163,9 +182,9 @@ fn compile_slang_to_wgsl(path: &Path) -> Result<String, ShaderError> {
             ShaderError::SlangCompilationFailed(format!("Failed to compose program: {:?}", e))
         })?;
 
-    let linked = program.link().map_err(|e| {
-        ShaderError::SlangCompilationFailed(format!("Failed to link program: {:?}", e))
-    })?;
+    let linked = program
+        .link()
+        .map_err(|e| slang_failure("Failed to link program", &e))?;
 
     // Get the compiled WGSL for the whole target (target index 0)
     let code = linked.target_code(0).map_err(|e| {
@@ -181,6 +200,17 @@ fn compile_slang_to_wgsl(path: &Path) -> Result<String, ShaderError> {
     Ok(fixup_slang_wgsl(&wgsl))
 }
 
+/// A SLANG error, led by the file, line and snippet of the first error in
+/// its diagnostics when they name one.
+#[cfg(feature = "slang")]
+fn slang_failure(context: &str, error: &impl std::fmt::Debug) -> ShaderError {
+    let output = format!("{:?}", error);
+    ShaderError::SlangCompilationFailed(match crate::shader_diagnostic::from_slang(&output) {
+        Some(diagnostic) => format!("{}:\n{}", context, diagnostic),
+        None => format!("{}: {}", context, output),
+    })
+}
+
 /// Fix known SLANG WGSL backend issues:
 /// 1. Depth textures emitted as `texture_2d<f32>` instead of `texture_depth_2d`
 /// 2. `textureLoad(depth_tex, ...).x` should be `textureLoad(depth_tex, ...)` (returns f32)
@@ -287,7 +317,7 @@ fn fixup_slang_wgsl(wgsl: &str) -> String {
 
 /// Fallback when SLANG feature is disabled.
 #[cfg(not(feature = "slang"))]
-fn compile_slang_to_wgsl(_path: &Path) -> Result<String, ShaderError> {
+fn compile_slang_variant(_path: &Path, _defines: &[(String, String)]) -> Result<String, ShaderError> {
     Err(ShaderError::SlangCompilationFailed(
         "SLANG support not compiled in (feature 'slang' disabled)".to_string(),
     ))
@@ -448,12 +478,9 @@ pub fn compile_shader(
         .and_then(|s| s.to_str())
         .ok_or_else(|| ShaderError::SlangCompilationFailed("Invalid file name".to_string()))?;
 
-    let module = session.load_module(file_name).map_err(|e| {
-        ShaderError::SlangCompilationFailed(format!(
-            "Failed to load module '{}': {:?}",
-            file_name, e
-        ))
-    })?;
+    let module = session
+        .load_module(file_name)
+        .map_err(|e| slang_failure(&format!("Failed to load module '{}'", file_name), &e))?;
 
     // Find all requested entry points
     use slang::Downcast;
@@ -477,9 +504,9 @@ pub fn compile_shader(
             ShaderError::SlangCompilationFailed(format!("Failed to compose program: {:?}", e))
         })?;
 
-    let linked = program.link().map_err(|e| {
-        ShaderError::SlangCompilationFailed(format!("Failed to link program: {:?}", e))
-    })?;
+    let linked = program
+        .link()
+        .map_err(|e| slang_failure("Failed to link program", &e))?;
 
     // Extract reflection data
     let reflection = crate::reflect::reflect_shader(&linked)?;
@@ -573,6 +600,9 @@ struct SkinUniforms {
 @group(1) @binding(0) var<uniform> draw: DrawUniforms;
 @group(2) @binding(0) var albedo_texture: texture_2d<f32>;
 @group(2) @binding(1) var albedo_sampler: sampler;
+@group(2) @binding(2) var normal_texture: texture_2d<f32>; // tangent space, flat (0.5, 0.5, 1) when unset
+@group(2) @binding(3) var metallic_roughness_texture: texture_2d<f32>; // g = roughness, b = metallic
+@group(2) @binding(4) var emissive_texture: texture_2d<f32>;
 @group(3) @binding(0) var<storage, read> skin: SkinUniforms;
 
 struct VertexInput {
@@ -582,6 +612,7 @@ struct VertexInput {
     @location(3) color: vec4<f32>,
     @location(4) joint_indices: vec4<u32>,
     @location(5) joint_weights: vec4<f32>,
+    @location(6) tangent: vec4<f32>,
 };
 
 struct VertexOutput {
@@ -590,6 +621,7 @@ struct VertexOutput {
     @location(1) world_pos: vec3<f32>,
     @location(2) tex_coords: vec2<f32>,
     @location(3) vertex_color: vec4<f32>,
+    @location(4) world_tangent: vec4<f32>, // xyz = tangent, w = bitangent sign
 };
 
 struct GBufferOutput {
@@ -621,6 +653,7 @@ fn vs_main(model: VertexInput) -> VertexOutput {
     let skin_matrix = apply_skinning(model.position, model.normal, model.joint_indices, model.joint_weights);
     let skinned_pos = skin_matrix * vec4<f32>(model.position, 1.0);
     let skinned_normal = (skin_matrix * vec4<f32>(model.normal, 0.0)).xyz;
+    let skinned_tangent = (skin_matrix * vec4<f32>(model.tangent.xyz, 0.0)).xyz;
 
     let world_pos = draw.model_matrix * skinned_pos;
     out.clip_position = camera.view_projection * world_pos;
@@ -628,6 +661,7 @@ fn vs_main(model: VertexInput) -> VertexOutput {
     out.world_pos = world_pos.xyz;
     out.tex_coords = model.tex_coords;
     out.vertex_color = model.color;
+    out.world_tangent = vec4<f32>((draw.model_matrix * vec4<f32>(skinned_tangent, 0.0)).xyz, model.tangent.w);
     return out;
 }
 
@@ -639,9 +673,25 @@ fn fs_main(in: VertexOutput) -> GBufferOutput {
         let tex_color = textureSample(albedo_texture, albedo_sampler, in.tex_coords);
         albedo = albedo * tex_color.rgb;
     }
-    out.albedo = vec4<f32>(albedo, draw.roughness);
-    out.normal = vec4<f32>(in.world_normal * 0.5 + 0.5, draw.metallic);
-    out.emission = vec4<f32>(draw.emission.rgb, 0.0);
+    // Unset maps are bound to white fallbacks, leaving the factors unchanged
+    let metallic_roughness = textureSample(metallic_roughness_texture, albedo_sampler, in.tex_coords);
+    let emissive = textureSample(emissive_texture, albedo_sampler, in.tex_coords).rgb;
+
+    // Tangent-space normal map; re-orthogonalize the interpolated TBN and
+    // keep the vertex normal where the tangent is degenerate
+    let tangent_normal = textureSample(normal_texture, albedo_sampler, in.tex_coords).xyz * 2.0 - 1.0;
+    let n = normalize(in.world_normal);
+    let t_raw = in.world_tangent.xyz - n * dot(n, in.world_tangent.xyz);
+    var world_normal = n;
+    if (dot(t_raw, t_raw) > 1e-8) {
+        let t = normalize(t_raw);
+        let b = cross(n, t) * in.world_tangent.w;
+        world_normal = normalize(mat3x3<f32>(t, b, n) * tangent_normal);
+    }
+
+    out.albedo = vec4<f32>(albedo, draw.roughness * metallic_roughness.g);
+    out.normal = vec4<f32>(world_normal * 0.5 + 0.5, draw.metallic * metallic_roughness.b);
+    out.emission = vec4<f32>(draw.emission.rgb * emissive, 0.0);
     return out;
 }
 "#
@@ -670,6 +720,12 @@ struct PointLight {
     range: f32,
     color: vec3<f32>,
     intensity: f32,
+    direction: vec3<f32>,
+    spot_scale: f32,
+    spot_offset: f32,
+    _pad_a: f32,
+    _pad_b: f32,
+    _pad_c: f32,
 };
 
 struct LightingUniforms {
@@ -682,7 +738,20 @@ struct LightingUniforms {
     dir_light_color: vec3<f32>,
     _pad_c: f32,
     light_vp: mat4x4<f32>,
-    lights: array<PointLight, 32>,
+};
+
+struct PointShadowUniforms {
+    // Cube faces (+X, -X, +Y, -Y, +Z, -Z) of each shadow slot
+    face_view_proj: array<mat4x4<f32>, 24>,
+    // Shadow slot per point light, -1 for none (4 lights per vec4)
+    light_slots: array<vec4<i32>, 256>,
+};
+
+struct EnvironmentUniforms {
+    intensity: f32,
+    specular_mips: f32,
+    enabled: u32,
+    _pad: u32,
 };
 
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
@@ -692,10 +761,23 @@ struct LightingUniforms {
 @group(1) @binding(2) var gbuffer_depth: texture_depth_2d;
 @group(1) @binding(3) var gbuffer_sampler: sampler;
 @group(1) @binding(4) var gbuffer_emission: texture_2d<f32>;
+@group(1) @binding(5) var ssao_texture: texture_2d<f32>;
 
 @group(2) @binding(0) var<uniform> lighting: LightingUniforms;
 @group(2) @binding(1) var shadow_map: texture_depth_2d;
 @group(2) @binding(2) var shadow_sampler: sampler_comparison;
+@group(2) @binding(3) var point_shadow_maps: texture_depth_2d_array;
+@group(2) @binding(4) var<uniform> point_shadows: PointShadowUniforms;
+@group(2) @binding(5) var specular_map: texture_cube<f32>;
+@group(2) @binding(6) var irradiance_map: texture_cube<f32>;
+@group(2) @binding(7) var environment_sampler: sampler;
+@group(2) @binding(8) var<uniform> environment: EnvironmentUniforms;
+@group(2) @binding(9) var<storage, read> point_lights: array<PointLight>;
+@group(2) @binding(10) var<storage, read> light_clusters: array<u32>;
+
+// Must match CLUSTER_GRID and CLUSTER_STRIDE in pipeline/resource.rs
+const CLUSTER_GRID: vec3<u32> = vec3<u32>(16u, 9u, 24u);
+const CLUSTER_STRIDE: u32 = 64u;
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -717,6 +799,26 @@ fn reconstruct_world_pos(uv: vec2<f32>, depth: f32) -> vec3<f32> {
     return world_h.xyz / world_h.w;
 }
 
+// Ambient occlusion at a pixel: a 4x4 box matching the SSAO pass's rotation
+// tile, read at the AO texture's own resolution. Pipelines without an ssao
+// resource bind a 1x1 placeholder, which means no occlusion.
+fn sample_ssao(pixel: vec2<i32>) -> f32 {
+    let ao_dims = vec2<i32>(textureDimensions(ssao_texture));
+    if ao_dims.x == 1i && ao_dims.y == 1i {
+        return 1.0;
+    }
+    let full_dims = vec2<i32>(textureDimensions(gbuffer_depth));
+    let center = pixel * ao_dims / full_dims;
+    var ao = 0.0;
+    for (var y = -2i; y < 2i; y = y + 1i) {
+        for (var x = -2i; x < 2i; x = x + 1i) {
+            let tap = clamp(center + vec2<i32>(x, y), vec2<i32>(0i), ao_dims - 1i);
+            ao = ao + textureLoad(ssao_texture, tap, 0).r;
+        }
+    }
+    return ao / 16.0;
+}
+
 fn sample_shadow_pcf(world_pos: vec3<f32>) -> f32 {
     let light_clip = lighting.light_vp * vec4<f32>(world_pos, 1.0);
     let light_ndc = light_clip.xyz / light_clip.w;
@@ -740,6 +842,51 @@ fn sample_shadow_pcf(world_pos: vec3<f32>) -> f32 {
     return shadow / 25.0;
 }
 
+// Cube shadow of point light `index`: pick the face by the dominant axis from
+// the light, project with that face's matrix and filter 3x3 taps.
+fn sample_point_shadow(index: u32, light_pos: vec3<f32>, world_pos: vec3<f32>) -> f32 {
+    let slot = point_shadows.light_slots[index / 4u][index % 4u];
+    if slot < 0 {
+        return 1.0;
+    }
+    let d = world_pos - light_pos;
+    let a = abs(d);
+    var face = 0u;
+    if a.x >= a.y && a.x >= a.z {
+        face = select(1u, 0u, d.x > 0.0);
+    } else if a.y >= a.z {
+        face = select(3u, 2u, d.y > 0.0);
+    } else {
+        face = select(5u, 4u, d.z > 0.0);
+    }
+    let layer = u32(slot) * 6u + face;
+    let clip = point_shadows.face_view_proj[layer] * vec4<f32>(world_pos, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5);
+
+    let texel_size = 1.0 / f32(textureDimensions(point_shadow_maps).x);
+    var shadow = 0.0;
+    for (var y = -1i; y <= 1i; y = y + 1i) {
+        for (var x = -1i; x <= 1i; x = x + 1i) {
+            let tap = clamp(uv + vec2<f32>(f32(x), f32(y)) * texel_size, vec2<f32>(0.0), vec2<f32>(1.0));
+            shadow = shadow + textureSampleCompareLevel(point_shadow_maps, shadow_sampler, tap, layer, ndc.z - 0.0001);
+        }
+    }
+    return shadow / 9.0;
+}
+
+// Start of the light list of the cluster holding `world_pos` at `pixel`:
+// a count, then that many indices into `point_lights`.
+fn cluster_base(pixel: vec2<f32>, world_pos: vec3<f32>) -> u32 {
+    let dims = vec2<f32>(textureDimensions(gbuffer_depth));
+    let tile = min(vec2<u32>(pixel / dims * vec2<f32>(CLUSTER_GRID.xy)), CLUSTER_GRID.xy - 1u);
+    let depth = max(-(camera.view * vec4<f32>(world_pos, 1.0)).z, camera.near_plane);
+    let slices = f32(CLUSTER_GRID.z);
+    let slice = log(depth / camera.near_plane) / log(camera.far_plane / camera.near_plane) * slices;
+    let z = u32(clamp(slice, 0.0, slices - 1.0));
+    return ((z * CLUSTER_GRID.y + tile.y) * CLUSTER_GRID.x + tile.x) * CLUSTER_STRIDE;
+}
+
 // Cook-Torrance BRDF helper functions
 fn distribution_ggx(NdotH: f32, roughness: f32) -> f32 {
     let a = roughness * roughness;
@@ -761,6 +908,29 @@ fn fresnel_schlick(cos_theta: f32, F0: vec3<f32>) -> vec3<f32> {
     return F0 + (1.0 - F0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
 }
 
+// Ambient light: the skybox's irradiance plus its prefiltered radiance
+// weighted by an analytic fit of the split-sum BRDF (Karis), or a dim
+// constant without a skybox.
+fn ambient_light(normal: vec3<f32>, view_dir: vec3<f32>, NdotV: f32, diffuse_color: vec3<f32>, F0: vec3<f32>, roughness: f32) -> vec3<f32> {
+    if environment.enabled == 0u {
+        return diffuse_color * vec3<f32>(0.02, 0.02, 0.025);
+    }
+    let irradiance = textureSampleLevel(irradiance_map, environment_sampler, normal, 0.0).rgb;
+    let reflected = reflect(-view_dir, normal);
+    let lod = roughness * (environment.specular_mips - 1.0);
+    let prefiltered = textureSampleLevel(specular_map, environment_sampler, reflected, lod).rgb;
+
+    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+    let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+    let r = roughness * c0 + c1;
+    let a004 = min(r.x * r.x, exp2(-9.28 * NdotV)) * r.x + r.y;
+    let ab = vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+    let env_brdf = F0 * ab.x + ab.y;
+
+    let diffuse = (vec3<f32>(1.0) - env_brdf) * diffuse_color * irradiance;
+    return (diffuse + prefiltered * env_brdf) * environment.intensity;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let tex_coords = vec2<i32>(in.position.xy);
@@ -788,14 +958,17 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let F0 = mix(vec3<f32>(0.04, 0.04, 0.04), albedo, metallic);
     let diffuse_color = albedo * (1.0 - metallic);
 
-    // Ambient
-    var color = diffuse_color * vec3<f32>(0.02, 0.02, 0.025);
-
     let NdotV = max(dot(normal, view_dir), 0.001);
 
-    // Accumulate point lights with Cook-Torrance BRDF
-    for (var i = 0u; i < lighting.light_count; i = i + 1u) {
-        let light = lighting.lights[i];
+    // Ambient
+    var color = ambient_light(normal, view_dir, NdotV, diffuse_color, F0, roughness) * sample_ssao(tex_coords);
+
+    // Accumulate the point lights of this pixel's cluster with Cook-Torrance BRDF
+    let cluster = cluster_base(in.position.xy, world_pos);
+    let cluster_light_count = light_clusters[cluster];
+    for (var k = 0u; k < cluster_light_count; k = k + 1u) {
+        let i = light_clusters[cluster + 1u + k];
+        let light = point_lights[i];
         let to_light = light.position - world_pos;
         let dist = length(to_light);
 
@@ -813,7 +986,9 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         // Attenuation: inverse-square with smooth range falloff
         let dist_atten = 1.0 / (1.0 + dist * dist);
         let range_factor = saturate(1.0 - pow(dist / light.range, 4.0));
-        let attenuation = light.intensity * dist_atten * range_factor;
+        // Spot cone falloff (1 for point lights)
+        let spot = saturate(dot(-light_dir, light.direction) * light.spot_scale + light.spot_offset);
+        let attenuation = light.intensity * dist_atten * range_factor * spot * spot;
 
         // Cook-Torrance specular BRDF
         let D = distribution_ggx(NdotH, roughness);
@@ -828,7 +1003,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         let kD = (vec3<f32>(1.0) - F) * (1.0 - metallic);
         let diffuse = kD * diffuse_color / 3.14159265;
 
-        color = color + (diffuse + specular) * light.color * NdotL * attenuation;
+        // Offset along the normal by about a shadow texel to avoid acne
+        let shadow_pos = world_pos + normal * (dist * 0.006 + 0.01);
+        let shadow = sample_point_shadow(i, light.position, shadow_pos);
+        color = color + (diffuse + specular) * light.color * NdotL * attenuation * shadow;
     }
 
     // Directional light with Cook-Torrance BRDF + shadows
@@ -887,20 +1065,37 @@ struct CameraUniform {
     _pad2: vec3<f32>,
 };
 
+// Quantized splat: f16 scale and rotation, unorm8 color and opacity
 struct GaussianSplat {
     position: vec3<f32>,
-    opacity: f32,
-    scale: vec3<f32>,
-    _pad0: f32,
-    rotation: vec4<f32>,
-    sh_dc: vec3<f32>,
-    _pad1: f32,
+    color: u32,
+    scale: vec2<u32>,
+    rotation: vec2<u32>,
 };
 
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
 
 @group(1) @binding(0) var<storage, read> splats: array<GaussianSplat>;
 @group(1) @binding(1) var<storage, read> sorted_indices: array<u32>;
+// SH bands 1 and 2: 12 words (24 f16) per splat, or one zeroed entry
+@group(1) @binding(2) var<storage, read> sh_coeffs: array<u32>;
+
+// center.w: 1 for an ellipsoid; half_extents.w: 1 to remove what is inside
+struct SplatCrop {
+    center: vec4<f32>,
+    half_extents: vec4<f32>,
+};
+
+// Per entity: local-to-world transform, opacity and crop volumes
+struct SplatParams {
+    model: mat4x4<f32>,
+    opacity: f32,
+    crop_count: u32,
+    _pad: vec2<u32>,
+    crops: array<SplatCrop, 4>,
+};
+
+@group(1) @binding(3) var<uniform> params: SplatParams;
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
@@ -909,6 +1104,58 @@ struct VertexOutput {
     @location(2) opacity: f32,
 };
 
+// Whether the crop volumes remove a splat at local position p: outside
+// every kept volume (if there are any), or inside a removed one
+fn is_cropped(p: vec3<f32>) -> bool {
+    var keeps = 0u;
+    var kept = false;
+    for (var i = 0u; i < params.crop_count; i++) {
+        let crop = params.crops[i];
+        let d = (p - crop.center.xyz) / max(crop.half_extents.xyz, vec3<f32>(1e-6));
+        var inside = all(abs(d) <= vec3<f32>(1.0));
+        if crop.center.w > 0.5 {
+            inside = dot(d, d) <= 1.0;
+        }
+        if crop.half_extents.w > 0.5 {
+            if inside {
+                return true;
+            }
+        } else {
+            keeps += 1u;
+            kept = kept || inside;
+        }
+    }
+    return keeps > 0u && !kept;
+}
+
+// View-dependent color from SH bands 1 and 2, added to the DC color
+fn sh_color(idx: u32, dir: vec3<f32>) -> vec3<f32> {
+    let base = idx * 12u;
+    if base + 12u > arrayLength(&sh_coeffs) {
+        return vec3<f32>(0.0);
+    }
+    var v: array<f32, 24>;
+    for (var i = 0u; i < 12u; i++) {
+        let pair = unpack2x16float(sh_coeffs[base + i]);
+        v[i * 2u] = pair.x;
+        v[i * 2u + 1u] = pair.y;
+    }
+    var c: array<vec3<f32>, 8>;
+    for (var i = 0u; i < 8u; i++) {
+        c[i] = vec3<f32>(v[i * 3u], v[i * 3u + 1u], v[i * 3u + 2u]);
+    }
+
+    let x = dir.x; let y = dir.y; let z = dir.z;
+    var result = 0.4886025 * (-y * c[0] + z * c[1] - x * c[2]);
+    let xx = x * x; let yy = y * y; let zz = z * z;
+    result += 1.0925484 * x * y * c[3]
+        - 1.0925484 * y * z * c[4]
+        + 0.3153916 * (2.0 * zz - xx - yy) * c[5]
+        - 1.0925484 * x * z * c[6]
+        + 0.5462742 * (xx - yy) * c[7];
+    return result;
+}
+
 // Build a rotation matrix from a quaternion
 fn quat_to_mat3(q: vec4<f32>) -> mat3x3<f32> {
     let x = q.x; let y = q.y; let z = q.z; let w = q.w;
@@ -946,22 +1193,35 @@ fn vs_main(
         vec2<f32>(-1.0,  1.0),
     );
 
+    // Cropped splats collapse behind the far plane
+    if is_cropped(splat.position) {
+        out.clip_position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        return out;
+    }
+
     let uv = quad_pos[vertex_index];
     out.uv = uv;
-    out.color = splat.sh_dc;
-    out.opacity = splat.opacity;
+    let color = unpack4x8unorm(splat.color);
+    // SH are evaluated in the cloud's own frame
+    let model3 = mat3x3<f32>(params.model[0].xyz, params.model[1].xyz, params.model[2].xyz);
+    let world_center = (params.model * vec4<f32>(splat.position, 1.0)).xyz;
+    let view_dir = normalize((world_center - camera.position) * model3);
+    out.color = max(color.rgb + sh_color(splat_idx, view_dir), vec3<f32>(0.0));
+    out.opacity = color.a * params.opacity;
+    let scale = unpack2x16float(splat.scale.x);
+    let rotation = vec4<f32>(unpack2x16float(splat.rotation.x), unpack2x16float(splat.rotation.y));
 
     // Compute the 3D covariance axes from rotation and scale
-    let rot_mat = quat_to_mat3(splat.rotation);
-    let scaled_x = rot_mat[0] * splat.scale.x;
-    let scaled_y = rot_mat[1] * splat.scale.y;
+    let rot_mat = quat_to_mat3(rotation);
+    let scaled_x = rot_mat[0] * scale.x;
+    let scaled_y = rot_mat[1] * scale.y;
 
-    // Billboard: offset the splat center in world space along the covariance axes
+    // Billboard: offset the splat center in the cloud's space along the covariance axes
     // Use 2x scale for the quad extent (covers ~95% of Gaussian at 2 sigma)
-    let world_offset = scaled_x * uv.x * 2.0 + scaled_y * uv.y * 2.0;
-    let world_pos = splat.position + world_offset;
+    let local_offset = scaled_x * uv.x * 2.0 + scaled_y * uv.y * 2.0;
+    let world_pos = params.model * vec4<f32>(splat.position + local_offset, 1.0);
 
-    out.clip_position = camera.view_projection * vec4<f32>(world_pos, 1.0);
+    out.clip_position = camera.view_projection * world_pos;
 
     return out;
 }
@@ -1008,6 +1268,12 @@ struct PointLight {
     range: f32,
     color: vec3<f32>,
     intensity: f32,
+    direction: vec3<f32>,
+    spot_scale: f32,
+    spot_offset: f32,
+    _pad_a: f32,
+    _pad_b: f32,
+    _pad_c: f32,
 };
 
 struct LightingUniforms {
@@ -1020,7 +1286,20 @@ struct LightingUniforms {
     dir_light_color: vec3<f32>,
     _pad_c: f32,
     light_vp: mat4x4<f32>,
-    lights: array<PointLight, 32>,
+};
+
+struct PointShadowUniforms {
+    // Cube faces (+X, -X, +Y, -Y, +Z, -Z) of each shadow slot
+    face_view_proj: array<mat4x4<f32>, 24>,
+    // Shadow slot per point light, -1 for none (4 lights per vec4)
+    light_slots: array<vec4<i32>, 256>,
+};
+
+struct EnvironmentUniforms {
+    intensity: f32,
+    specular_mips: f32,
+    enabled: u32,
+    _pad: u32,
 };
 
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
@@ -1030,10 +1309,23 @@ struct LightingUniforms {
 @group(1) @binding(2) var gbuffer_depth: texture_depth_2d;
 @group(1) @binding(3) var gbuffer_sampler: sampler;
 @group(1) @binding(4) var gbuffer_emission: texture_2d<f32>;
+@group(1) @binding(5) var ssao_texture: texture_2d<f32>;
 
 @group(2) @binding(0) var<uniform> lighting: LightingUniforms;
 @group(2) @binding(1) var shadow_map: texture_depth_2d;
 @group(2) @binding(2) var shadow_sampler: sampler_comparison;
+@group(2) @binding(3) var point_shadow_maps: texture_depth_2d_array;
+@group(2) @binding(4) var<uniform> point_shadows: PointShadowUniforms;
+@group(2) @binding(5) var specular_map: texture_cube<f32>;
+@group(2) @binding(6) var irradiance_map: texture_cube<f32>;
+@group(2) @binding(7) var environment_sampler: sampler;
+@group(2) @binding(8) var<uniform> environment: EnvironmentUniforms;
+@group(2) @binding(9) var<storage, read> point_lights: array<PointLight>;
+@group(2) @binding(10) var<storage, read> light_clusters: array<u32>;
+
+// Must match CLUSTER_GRID and CLUSTER_STRIDE in pipeline/resource.rs
+const CLUSTER_GRID: vec3<u32> = vec3<u32>(16u, 9u, 24u);
+const CLUSTER_STRIDE: u32 = 64u;
 
 @group(3) @binding(0) var splat_color_tex: texture_2d<f32>;
 @group(3) @binding(1) var splat_depth_tex: texture_depth_2d;
@@ -1058,6 +1350,26 @@ fn reconstruct_world_pos(uv: vec2<f32>, depth: f32) -> vec3<f32> {
     return world_h.xyz / world_h.w;
 }
 
+// Ambient occlusion at a pixel: a 4x4 box matching the SSAO pass's rotation
+// tile, read at the AO texture's own resolution. Pipelines without an ssao
+// resource bind a 1x1 placeholder, which means no occlusion.
+fn sample_ssao(pixel: vec2<i32>) -> f32 {
+    let ao_dims = vec2<i32>(textureDimensions(ssao_texture));
+    if ao_dims.x == 1i && ao_dims.y == 1i {
+        return 1.0;
+    }
+    let full_dims = vec2<i32>(textureDimensions(gbuffer_depth));
+    let center = pixel * ao_dims / full_dims;
+    var ao = 0.0;
+    for (var y = -2i; y < 2i; y = y + 1i) {
+        for (var x = -2i; x < 2i; x = x + 1i) {
+            let tap = clamp(center + vec2<i32>(x, y), vec2<i32>(0i), ao_dims - 1i);
+            ao = ao + textureLoad(ssao_texture, tap, 0).r;
+        }
+    }
+    return ao / 16.0;
+}
+
 fn sample_shadow_pcf(world_pos: vec3<f32>) -> f32 {
     let light_clip = lighting.light_vp * vec4<f32>(world_pos, 1.0);
     let light_ndc = light_clip.xyz / light_clip.w;
@@ -1081,6 +1393,51 @@ fn sample_shadow_pcf(world_pos: vec3<f32>) -> f32 {
     return shadow / 25.0;
 }
 
+// Cube shadow of point light `index`: pick the face by the dominant axis from
+// the light, project with that face's matrix and filter 3x3 taps.
+fn sample_point_shadow(index: u32, light_pos: vec3<f32>, world_pos: vec3<f32>) -> f32 {
+    let slot = point_shadows.light_slots[index / 4u][index % 4u];
+    if slot < 0 {
+        return 1.0;
+    }
+    let d = world_pos - light_pos;
+    let a = abs(d);
+    var face = 0u;
+    if a.x >= a.y && a.x >= a.z {
+        face = select(1u, 0u, d.x > 0.0);
+    } else if a.y >= a.z {
+        face = select(3u, 2u, d.y > 0.0);
+    } else {
+        face = select(5u, 4u, d.z > 0.0);
+    }
+    let layer = u32(slot) * 6u + face;
+    let clip = point_shadows.face_view_proj[layer] * vec4<f32>(world_pos, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5);
+
+    let texel_size = 1.0 / f32(textureDimensions(point_shadow_maps).x);
+    var shadow = 0.0;
+    for (var y = -1i; y <= 1i; y = y + 1i) {
+        for (var x = -1i; x <= 1i; x = x + 1i) {
+            let tap = clamp(uv + vec2<f32>(f32(x), f32(y)) * texel_size, vec2<f32>(0.0), vec2<f32>(1.0));
+            shadow = shadow + textureSampleCompareLevel(point_shadow_maps, shadow_sampler, tap, layer, ndc.z - 0.0001);
+        }
+    }
+    return shadow / 9.0;
+}
+
+// Start of the light list of the cluster holding `world_pos` at `pixel`:
+// a count, then that many indices into `point_lights`.
+fn cluster_base(pixel: vec2<f32>, world_pos: vec3<f32>) -> u32 {
+    let dims = vec2<f32>(textureDimensions(gbuffer_depth));
+    let tile = min(vec2<u32>(pixel / dims * vec2<f32>(CLUSTER_GRID.xy)), CLUSTER_GRID.xy - 1u);
+    let depth = max(-(camera.view * vec4<f32>(world_pos, 1.0)).z, camera.near_plane);
+    let slices = f32(CLUSTER_GRID.z);
+    let slice = log(depth / camera.near_plane) / log(camera.far_plane / camera.near_plane) * slices;
+    let z = u32(clamp(slice, 0.0, slices - 1.0));
+    return ((z * CLUSTER_GRID.y + tile.y) * CLUSTER_GRID.x + tile.x) * CLUSTER_STRIDE;
+}
+
 // Cook-Torrance BRDF helper functions
 fn distribution_ggx(NdotH: f32, roughness: f32) -> f32 {
     let a = roughness * roughness;
@@ -1102,6 +1459,29 @@ fn fresnel_schlick(cos_theta: f32, F0: vec3<f32>) -> vec3<f32> {
     return F0 + (1.0 - F0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
 }
 
+// Ambient light: the skybox's irradiance plus its prefiltered radiance
+// weighted by an analytic fit of the split-sum BRDF (Karis), or a dim
+// constant without a skybox.
+fn ambient_light(normal: vec3<f32>, view_dir: vec3<f32>, NdotV: f32, diffuse_color: vec3<f32>, F0: vec3<f32>, roughness: f32) -> vec3<f32> {
+    if environment.enabled == 0u {
+        return diffuse_color * vec3<f32>(0.02, 0.02, 0.025);
+    }
+    let irradiance = textureSampleLevel(irradiance_map, environment_sampler, normal, 0.0).rgb;
+    let reflected = reflect(-view_dir, normal);
+    let lod = roughness * (environment.specular_mips - 1.0);
+    let prefiltered = textureSampleLevel(specular_map, environment_sampler, reflected, lod).rgb;
+
+    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+    let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+    let r = roughness * c0 + c1;
+    let a004 = min(r.x * r.x, exp2(-9.28 * NdotV)) * r.x + r.y;
+    let ab = vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+    let env_brdf = F0 * ab.x + ab.y;
+
+    let diffuse = (vec3<f32>(1.0) - env_brdf) * diffuse_color * irradiance;
+    return (diffuse + prefiltered * env_brdf) * environment.intensity;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let tex_coords = vec2<i32>(in.position.xy);
@@ -1132,10 +1512,13 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let NdotV = max(dot(normal, view_dir), 0.001);
 
     // Compute mesh lighting with Cook-Torrance BRDF
-    var mesh_color = diffuse_color * vec3<f32>(0.02, 0.02, 0.025);
+    var mesh_color = ambient_light(normal, view_dir, NdotV, diffuse_color, F0, roughness) * sample_ssao(tex_coords);
 
-    for (var i = 0u; i < lighting.light_count; i = i + 1u) {
-        let light = lighting.lights[i];
+    let cluster = cluster_base(in.position.xy, world_pos);
+    let cluster_light_count = light_clusters[cluster];
+    for (var k = 0u; k < cluster_light_count; k = k + 1u) {
+        let i = light_clusters[cluster + 1u + k];
+        let light = point_lights[i];
         let to_light = light.position - world_pos;
         let dist = length(to_light);
 
@@ -1152,7 +1535,9 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
 
         let dist_atten = 1.0 / (1.0 + dist * dist);
         let range_factor = saturate(1.0 - pow(dist / light.range, 4.0));
-        let attenuation = light.intensity * dist_atten * range_factor;
+        // Spot cone falloff (1 for point lights)
+        let spot = saturate(dot(-light_dir, light.direction) * light.spot_scale + light.spot_offset);
+        let attenuation = light.intensity * dist_atten * range_factor * spot * spot;
 
         // Cook-Torrance specular BRDF
         let D = distribution_ggx(NdotH, roughness);
@@ -1166,7 +1551,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         let kD = (vec3<f32>(1.0) - F) * (1.0 - metallic);
         let diffuse = kD * diffuse_color / 3.14159265;
 
-        mesh_color = mesh_color + (diffuse + specular) * light.color * NdotL * attenuation;
+        // Offset along the normal by about a shadow texel to avoid acne
+        let shadow_pos = world_pos + normal * (dist * 0.006 + 0.01);
+        let shadow = sample_point_shadow(i, light.position, shadow_pos);
+        mesh_color = mesh_color + (diffuse + specular) * light.color * NdotL * attenuation * shadow;
     }
 
     // Directional light with Cook-Torrance BRDF + shadows
@@ -1205,11 +1593,12 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let mesh_valid = mesh_depth < 1.0;
     let splat_valid = splat_color.a > 0.004;
 
-    // Composite: prefer closer of mesh vs splat, blend if overlapping
+    // Composite: prefer closer of mesh vs splat, blend if overlapping. Over
+    // empty pixels the splat keeps its coverage so the skybox goes behind it.
     if splat_valid && (!mesh_valid || splat_depth < mesh_depth) {
         let bg = select(vec3<f32>(0.0), mesh_color, mesh_valid);
         let blended = splat_color.rgb + bg * (1.0 - splat_color.a);
-        return vec4<f32>(blended, 1.0);
+        return vec4<f32>(blended, select(splat_color.a, 1.0, mesh_valid));
     } else if mesh_valid {
         return vec4<f32>(mesh_color, 1.0);
     }
@@ -1220,81 +1609,301 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     .to_string()
 }
 
-/// Hardcoded WGSL fallback for the tone mapping pass.
-/// WGSL fallback for bloom extraction pass (threshold + 13-tap tent downsample).
-pub fn get_bloom_wgsl() -> String {
+/// Built-in WGSL for the light clustering compute step run before the
+/// lighting pass. One invocation per cluster bounds the cluster in view space
+/// and lists the point lights whose range reaches it.
+pub fn get_light_cluster_wgsl() -> String {
     r#"
-@group(0) @binding(0) var hdr_texture: texture_2d<f32>;
-@group(0) @binding(1) var hdr_sampler: sampler;
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    _pad2: vec4<f32>,
+    inv_view_projection: mat4x4<f32>,
+};
 
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
-    @location(0) uv: vec2<f32>,
+struct PointLight {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    direction: vec3<f32>,
+    spot_scale: f32,
+    spot_offset: f32,
+    _pad_a: f32,
+    _pad_b: f32,
+    _pad_c: f32,
 };
 
-@vertex
-fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
-    var out: VertexOutput;
-    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
-    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
-    out.uv = vec2<f32>(uv.x, 1.0 - uv.y);
-    return out;
+struct LightingUniforms {
+    light_count: u32,
+    has_directional: u32,
+    _pad_a: vec2<u32>,
+    _pad_b: vec4<u32>,
+    dir_light_direction: vec3<f32>,
+    dir_light_intensity: f32,
+    dir_light_color: vec3<f32>,
+    _pad_c: f32,
+    light_vp: mat4x4<f32>,
+};
+
+// Must match CLUSTER_GRID and CLUSTER_STRIDE in pipeline/resource.rs
+const CLUSTER_GRID: vec3<u32> = vec3<u32>(16u, 9u, 24u);
+const CLUSTER_STRIDE: u32 = 64u;
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var<uniform> lighting: LightingUniforms;
+@group(1) @binding(1) var<storage, read> point_lights: array<PointLight>;
+@group(1) @binding(2) var<storage, read_write> light_clusters: array<u32>;
+
+// View depth where depth slice `slice` starts (exponential spacing)
+fn slice_depth(slice: u32) -> f32 {
+    let t = f32(slice) / f32(CLUSTER_GRID.z);
+    return camera.near_plane * pow(camera.far_plane / camera.near_plane, t);
 }
 
-fn luminance(c: vec3<f32>) -> f32 {
-    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+// View-space point at `ndc` and view depth `depth`, for perspective and
+// orthographic projections alike
+fn view_point(ndc: vec2<f32>, depth: f32) -> vec3<f32> {
+    let p = camera.projection;
+    let z = -depth;
+    let w = p[2][3] * z + p[3][3];
+    let x = (ndc.x * w - p[2][0] * z - p[3][0]) / p[0][0];
+    let y = (ndc.y * w - p[2][1] * z - p[3][1]) / p[1][1];
+    return vec3<f32>(x, y, z);
 }
 
-fn threshold_color(color: vec3<f32>, threshold: f32, knee: f32) -> vec3<f32> {
-    let lum = luminance(color);
-    var soft = lum - threshold + knee;
-    soft = clamp(soft, 0.0, 2.0 * knee);
-    soft = soft * soft / (4.0 * knee + 0.0001);
-    let contribution = max(soft, lum - threshold) / max(lum, 0.0001);
-    return color * max(contribution, 0.0);
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let cluster = id.x;
+    if cluster >= CLUSTER_GRID.x * CLUSTER_GRID.y * CLUSTER_GRID.z {
+        return;
+    }
+    let tx = cluster % CLUSTER_GRID.x;
+    let ty = (cluster / CLUSTER_GRID.x) % CLUSTER_GRID.y;
+    let tz = cluster / (CLUSTER_GRID.x * CLUSTER_GRID.y);
+
+    // Tile rows run top to bottom, like pixel rows
+    let tiles = vec2<f32>(CLUSTER_GRID.xy);
+    let ndc_min = vec2<f32>(f32(tx) / tiles.x * 2.0 - 1.0, 1.0 - f32(ty + 1u) / tiles.y * 2.0);
+    let ndc_max = vec2<f32>(f32(tx + 1u) / tiles.x * 2.0 - 1.0, 1.0 - f32(ty) / tiles.y * 2.0);
+    let near = slice_depth(tz);
+    let far = slice_depth(tz + 1u);
+
+    var box_min = vec3<f32>(1e30);
+    var box_max = vec3<f32>(-1e30);
+    for (var c = 0u; c < 8u; c = c + 1u) {
+        let ndc = vec2<f32>(
+            select(ndc_min.x, ndc_max.x, (c & 1u) != 0u),
+            select(ndc_min.y, ndc_max.y, (c & 2u) != 0u),
+        );
+        let corner = view_point(ndc, select(near, far, (c & 4u) != 0u));
+        box_min = min(box_min, corner);
+        box_max = max(box_max, corner);
+    }
+
+    // Sphere against box: the closest point of the box within the light's range
+    let base = cluster * CLUSTER_STRIDE;
+    var count = 0u;
+    for (var i = 0u; i < lighting.light_count && count < CLUSTER_STRIDE - 1u; i = i + 1u) {
+        let light = point_lights[i];
+        let center = (camera.view * vec4<f32>(light.position, 1.0)).xyz;
+        let offset = center - clamp(center, box_min, box_max);
+        if dot(offset, offset) <= light.range * light.range {
+            light_clusters[base + 1u + count] = i;
+            count = count + 1u;
+        }
+    }
+    light_clusters[base] = count;
+}
+"#
+    .to_string()
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let dims = vec2<f32>(textureDimensions(hdr_texture));
-    let texel = 1.0 / dims;
-    let uv = in.uv;
+/// Built-in WGSL for GPU-driven culling. One invocation per draw slot tests
+/// the slot's world bounds against the camera frustum and writes its
+/// `DrawIndexedIndirect` command, drawing zero instances when culled.
+pub fn get_gpu_cull_wgsl() -> String {
+    r#"
+struct CullParams {
+    planes: array<vec4<f32>, 6>,
+    count: u32,
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+};
 
-    // 13-tap tent filter (Jimenez 2014)
-    let a = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-2.0, -2.0) * texel).rgb;
-    let b = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 0.0, -2.0) * texel).rgb;
-    let c = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 2.0, -2.0) * texel).rgb;
-    let d = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-1.0, -1.0) * texel).rgb;
-    let e = textureSample(hdr_texture, hdr_sampler, uv).rgb;
-    let f = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 1.0, -1.0) * texel).rgb;
-    let g = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-2.0,  0.0) * texel).rgb;
-    let h = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 2.0,  0.0) * texel).rgb;
-    let i = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-1.0,  1.0) * texel).rgb;
-    let j = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 1.0,  1.0) * texel).rgb;
-    let k = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-2.0,  2.0) * texel).rgb;
-    let l = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 0.0,  2.0) * texel).rgb;
-    let m = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 2.0,  2.0) * texel).rgb;
+struct CullItem {
+    min: vec3<f32>,
+    index_count: u32,
+    max: vec3<f32>,
+    first_index: u32,
+    always_visible: u32,
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+};
 
-    // Weighted downsample
-    var result = e * 0.125;
-    result += (d + f + i + j) * 0.125;
-    result += (a + c + k + m) * 0.03125;
-    result += (b + g + h + l) * 0.0625;
+@group(0) @binding(0) var<uniform> params: CullParams;
+@group(0) @binding(1) var<storage, read> items: array<CullItem>;
+@group(0) @binding(2) var<storage, read_write> commands: array<u32>;
 
-    // Soft threshold
-    result = threshold_color(result, 0.5, 0.3);
+// Same test as Frustum::intersects: the box corner furthest along each
+// inward plane normal must be on the inside
+fn visible(item: CullItem) -> bool {
+    if item.always_visible != 0u {
+        return true;
+    }
+    for (var i = 0u; i < 6u; i = i + 1u) {
+        let plane = params.planes[i];
+        let corner = select(item.min, item.max, plane.xyz >= vec3<f32>(0.0));
+        if dot(plane.xyz, corner) + plane.w < 0.0 {
+            return false;
+        }
+    }
+    return true;
+}
 
-    return vec4<f32>(result, 1.0);
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    let item = items[index];
+    // index_count, instance_count, first_index, base_vertex, first_instance
+    let base = index * 5u;
+    commands[base] = item.index_count;
+    commands[base + 1u] = select(0u, 1u, visible(item));
+    commands[base + 2u] = item.first_index;
+    commands[base + 3u] = 0u;
+    commands[base + 4u] = 0u;
 }
 "#
     .to_string()
 }
 
-pub fn get_tonemap_wgsl() -> String {
+/// Built-in WGSL for the GPU splat sort (see `splat_sort`). `cs_keys` writes
+/// a depth key and index per splat, padding the rest with keys that sort
+/// last; `cs_sort` runs one compare-and-swap step of a bitonic sort.
+pub fn get_splat_sort_wgsl() -> String {
     r#"
-@group(0) @binding(0) var hdr_texture: texture_2d<f32>;
-@group(0) @binding(1) var hdr_sampler: sampler;
-@group(0) @binding(2) var bloom_texture: texture_2d<f32>;
+struct SortParams {
+    view_z: vec4<f32>,
+    segment_capacity: u32,
+    capacity: u32,
+    _pad0: u32,
+    _pad1: u32,
+};
+
+struct SortStep {
+    k: u32,
+    j: u32,
+    _pad0: u32,
+    _pad1: u32,
+};
+
+// Quantized splat: f16 scale and rotation, unorm8 color and opacity
+struct GaussianSplat {
+    position: vec3<f32>,
+    color: u32,
+    scale: vec2<u32>,
+    rotation: vec2<u32>,
+};
+
+@group(0) @binding(0) var<uniform> params: SortParams;
+@group(0) @binding(1) var<storage, read> splats: array<GaussianSplat>;
+@group(0) @binding(2) var<storage, read_write> keys: array<u32>;
+@group(0) @binding(3) var<storage, read_write> indices: array<u32>;
+// First splat and splats to sort of each cell's segment
+@group(0) @binding(4) var<storage, read> segments: array<vec2<u32>>;
+@group(1) @binding(0) var<uniform> sort_step: SortStep;
+
+// Large sorts spread their workgroups over y
+fn invocation_index(id: vec3<u32>, groups: vec3<u32>) -> u32 {
+    return id.y * groups.x * 256u + id.x;
+}
+
+// Camera-space depth as a key that orders like the float (farthest first)
+fn depth_key(z: f32) -> u32 {
+    let bits = bitcast<u32>(z);
+    return bits ^ select(0x80000000u, 0xffffffffu, (bits >> 31u) == 1u);
+}
+
+@compute @workgroup_size(256)
+fn cs_keys(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = invocation_index(id, groups);
+    if i >= params.capacity {
+        return;
+    }
+    let segment = segments[i / params.segment_capacity];
+    let local = i % params.segment_capacity;
+    if local < segment.y {
+        let index = segment.x + local;
+        let z = dot(params.view_z, vec4<f32>(splats[index].position, 1.0));
+        keys[i] = depth_key(z);
+        indices[i] = index;
+    } else {
+        keys[i] = 0xffffffffu;
+        indices[i] = 0u;
+    }
+}
+
+@compute @workgroup_size(256)
+fn cs_sort(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = invocation_index(id, groups);
+    let l = i ^ sort_step.j;
+    if i >= params.capacity || l <= i {
+        return;
+    }
+    let a = keys[i];
+    let b = keys[l];
+    // Segments sort on their own, each ending in ascending order
+    let ascending = (i & sort_step.k) == 0u || sort_step.k >= params.segment_capacity;
+    if select(a < b, a > b, ascending) {
+        keys[i] = b;
+        keys[l] = a;
+        let index = indices[i];
+        indices[i] = indices[l];
+        indices[l] = index;
+    }
+}
+"#
+    .to_string()
+}
+
+/// WGSL for the built-in SSAO pass: 16 hemisphere samples around each
+/// G-buffer position, compared against the depth buffer. The sample kernel is
+/// rotated over a 4x4 pixel tile that the lighting pass averages away.
+pub fn get_ssao_wgsl() -> String {
+    r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    _pad2: vec4<f32>,
+    inv_view_projection: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var gbuffer_normal: texture_2d<f32>;
+@group(1) @binding(1) var gbuffer_depth: texture_depth_2d;
+
+const SAMPLE_COUNT: u32 = 16u;
+const RADIUS: f32 = 0.5;
+const BIAS: f32 = 0.025;
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -1310,15 +1919,692 @@ fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     return out;
 }
 
-fn aces_tonemap(x: vec3<f32>) -> vec3<f32> {
-    let a = 2.51;
-    let b = 0.03;
-    let c = 2.43;
-    let d = 0.59;
+fn texel_at(uv: vec2<f32>, dims: vec2<u32>) -> vec2<i32> {
+    return clamp(vec2<i32>(uv * vec2<f32>(dims)), vec2<i32>(0i), vec2<i32>(dims) - 1i);
+}
+
+fn depth_at(uv: vec2<f32>) -> f32 {
+    return textureLoad(gbuffer_depth, texel_at(uv, textureDimensions(gbuffer_depth)), 0);
+}
+
+fn reconstruct_world_pos(uv: vec2<f32>, depth: f32) -> vec3<f32> {
+    let clip = vec4<f32>(uv.x * 2.0 - 1.0, (1.0 - uv.y) * 2.0 - 1.0, depth, 1.0);
+    let world_h = camera.inv_view_projection * clip;
+    return world_h.xyz / world_h.w;
+}
+
+fn view_depth(world_pos: vec3<f32>) -> f32 {
+    return -(camera.view * vec4<f32>(world_pos, 1.0)).z;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let depth = depth_at(in.uv);
+    if depth >= 1.0 {
+        return vec4<f32>(1.0);
+    }
+    let normal_metal = textureLoad(gbuffer_normal, texel_at(in.uv, textureDimensions(gbuffer_normal)), 0);
+    let normal = normalize(normal_metal.rgb * 2.0 - 1.0);
+    let world_pos = reconstruct_world_pos(in.uv, depth);
+    let center_depth = view_depth(world_pos);
+
+    // Tangent frame around the normal, rotated per pixel of a 4x4 tile
+    let tile = vec2<u32>(in.position.xy) % 4u;
+    let angle = f32(tile.y * 4u + tile.x) * (6.2831853 / 16.0);
+    let up = select(vec3<f32>(0.0, 1.0, 0.0), vec3<f32>(1.0, 0.0, 0.0), abs(normal.y) > 0.9);
+    let t0 = normalize(cross(up, normal));
+    let b0 = cross(normal, t0);
+    let tangent = t0 * cos(angle) + b0 * sin(angle);
+    let bitangent = cross(normal, tangent);
+
+    var occlusion = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i = i + 1u) {
+        // Spiral over the hemisphere, denser close to the surface point
+        let t = (f32(i) + 0.5) / f32(SAMPLE_COUNT);
+        let phi = f32(i) * 2.3999632;
+        let sin_theta = sqrt(1.0 - t * t);
+        let dir = tangent * (cos(phi) * sin_theta) + bitangent * (sin(phi) * sin_theta) + normal * t;
+        let scale = mix(0.1, 1.0, t * t);
+        let sample_pos = world_pos + dir * RADIUS * scale;
+
+        let clip = camera.view_projection * vec4<f32>(sample_pos, 1.0);
+        let ndc = clip.xyz / clip.w;
+        let uv = vec2<f32>(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5);
+        if uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 {
+            continue;
+        }
+
+        let scene_depth = view_depth(reconstruct_world_pos(uv, depth_at(uv)));
+        let range = smoothstep(0.0, 1.0, RADIUS / max(abs(center_depth - scene_depth), 0.0001));
+        if scene_depth <= view_depth(sample_pos) - BIAS {
+            occlusion = occlusion + range;
+        }
+    }
+
+    let ao = 1.0 - occlusion / f32(SAMPLE_COUNT);
+    return vec4<f32>(ao, ao, ao, 1.0);
+}
+"#
+    .to_string()
+}
+
+/// WGSL for the built-in TAA resolve pass. Reprojects each pixel into the
+/// previous frame from its depth and the camera's previous view-projection,
+/// clamps the history sample to the current 3x3 neighborhood, and blends.
+/// Writes the result twice: to the pass output and to the history resolve
+/// target that is copied into the history resource afterwards.
+pub fn get_taa_wgsl() -> String {
+    r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    jitter: vec2<f32>,
+    _pad2: vec2<f32>,
+    inv_view_projection: mat4x4<f32>,
+    prev_view_projection: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var color_texture: texture_2d<f32>;
+@group(1) @binding(1) var depth_texture: texture_depth_2d;
+@group(1) @binding(2) var history_texture: texture_2d<f32>;
+@group(1) @binding(3) var history_sampler: sampler;
+
+// Weight of the current frame; the rest comes from the clamped history.
+const CURRENT_WEIGHT: f32 = 0.1;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct TaaOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) history: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> TaaOutput {
+    let dims = vec2<i32>(textureDimensions(color_texture));
+    let pixel = clamp(vec2<i32>(in.position.xy), vec2<i32>(0i), dims - 1i);
+    let current = textureLoad(color_texture, pixel, 0);
+
+    // Neighborhood bounds reject history that no longer matches the scene
+    var lo = current.rgb;
+    var hi = current.rgb;
+    for (var y = -1i; y <= 1i; y++) {
+        for (var x = -1i; x <= 1i; x++) {
+            let p = clamp(pixel + vec2<i32>(x, y), vec2<i32>(0i), dims - 1i);
+            let c = textureLoad(color_texture, p, 0).rgb;
+            lo = min(lo, c);
+            hi = max(hi, c);
+        }
+    }
+
+    // Motion vector from depth: this pixel's world position in last frame's clip space
+    let depth_dims = vec2<i32>(textureDimensions(depth_texture));
+    let depth = textureLoad(depth_texture, clamp(pixel, vec2<i32>(0i), depth_dims - 1i), 0);
+    let uv = (vec2<f32>(pixel) + 0.5) / vec2<f32>(dims);
+    let ndc = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let world = camera.inv_view_projection * ndc;
+    let prev_clip = camera.prev_view_projection * vec4<f32>(world.xyz / world.w, 1.0);
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    let prev_uv = vec2<f32>(prev_ndc.x * 0.5 + 0.5, 0.5 - prev_ndc.y * 0.5);
+
+    var result = current.rgb;
+    let on_screen = all(prev_uv >= vec2<f32>(0.0)) && all(prev_uv <= vec2<f32>(1.0));
+    if (on_screen && prev_clip.w > 0.0) {
+        let history = textureSampleLevel(history_texture, history_sampler, prev_uv, 0.0).rgb;
+        result = mix(clamp(history, lo, hi), current.rgb, CURRENT_WEIGHT);
+    }
+
+    let color = vec4<f32>(result, current.a);
+    return TaaOutput(color, color);
+}
+"#
+    .to_string()
+}
+
+/// WGSL for the built-in skybox pass: the sky cube seen through each pixel,
+/// drawn at the far plane (only where the depth test finds nothing closer).
+pub fn get_skybox_wgsl() -> String {
+    r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    jitter: vec2<f32>,
+    _pad2: vec2<f32>,
+    inv_view_projection: mat4x4<f32>,
+    prev_view_projection: mat4x4<f32>,
+};
+
+struct EnvironmentUniforms {
+    intensity: f32,
+    specular_mips: f32,
+    enabled: u32,
+    _pad: u32,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var sky_map: texture_cube<f32>;
+@group(1) @binding(1) var sky_sampler: sampler;
+@group(1) @binding(2) var<uniform> environment: EnvironmentUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.ndc = uv * 2.0 - 1.0;
+    out.position = vec4<f32>(out.ndc, 1.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if environment.enabled == 0u {
+        discard;
+    }
+    let far = camera.inv_view_projection * vec4<f32>(in.ndc, 1.0, 1.0);
+    let dir = normalize(far.xyz / far.w - camera.position);
+    let sky = textureSampleLevel(sky_map, sky_sampler, dir, 0.0).rgb;
+    return vec4<f32>(sky * environment.intensity, 1.0);
+}
+"#
+    .to_string()
+}
+
+/// Built-in water surface shader: animated normals, refraction of the scene
+/// color copy, depth-based absorption and shoreline fade, and screen-space
+/// (or sky) reflections. Depth is tested by hand against the sampled depth.
+pub fn get_water_wgsl() -> String {
+    r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    jitter: vec2<f32>,
+    _pad2: vec2<f32>,
+    inv_view_projection: mat4x4<f32>,
+    prev_view_projection: mat4x4<f32>,
+};
+
+struct EnvironmentUniforms {
+    intensity: f32,
+    specular_mips: f32,
+    enabled: u32,
+    _pad: u32,
+};
+
+struct WaterUniforms {
+    model: mat4x4<f32>,
+    shallow: vec4<f32>,   // rgb, depth_fade
+    deep: vec4<f32>,      // rgb, shore_fade
+    waves: vec4<f32>,     // scale, speed, strength, time
+    params: vec4<f32>,    // refraction, reflection (0 none, 1 sky, 2 ssr)
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var scene_color: texture_2d<f32>;
+@group(1) @binding(1) var scene_sampler: sampler;
+@group(1) @binding(2) var scene_depth: texture_depth_2d;
+@group(1) @binding(3) var sky_map: texture_cube<f32>;
+@group(1) @binding(4) var sky_sampler: sampler;
+@group(1) @binding(5) var<uniform> environment: EnvironmentUniforms;
+
+@group(2) @binding(0) var<uniform> water: WaterUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) normal: vec3<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    let world = water.model * vec4<f32>(position, 1.0);
+    out.position = camera.view_projection * world;
+    out.world_position = world.xyz;
+    out.normal = normalize((water.model * vec4<f32>(normal, 0.0)).xyz);
+    return out;
+}
+
+// Slope of one travelling sine wave (deep-water speed for its wavelength).
+fn wave(p: vec2<f32>, dir: vec2<f32>, wavelength: f32) -> vec2<f32> {
+    let k = 6.2831853 / (wavelength * water.waves.x);
+    let speed = sqrt(9.81 / k) * water.waves.y;
+    let phase = k * (dot(dir, p) - speed * water.waves.w);
+    return dir * cos(phase) * 0.25;
+}
+
+fn wave_slope(p: vec2<f32>) -> vec2<f32> {
+    return wave(p, vec2<f32>(1.0, 0.0), 4.0)
+        + wave(p, vec2<f32>(0.6, 0.8), 2.3)
+        + wave(p, vec2<f32>(-0.7, 0.7), 1.3)
+        + wave(p, vec2<f32>(0.2, -0.98), 0.7);
+}
+
+// View depth of a depth buffer value, for perspective and orthographic
+// projections alike
+fn linear_depth(z: f32) -> f32 {
+    let p = camera.projection;
+    return (z * p[3][3] - p[3][2]) / (z * p[2][3] - p[2][2]);
+}
+
+fn load_depth(uv: vec2<f32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(scene_depth));
+    let pixel = clamp(vec2<i32>(uv * vec2<f32>(size)), vec2<i32>(0), size - 1);
+    return textureLoad(scene_depth, pixel, 0);
+}
+
+fn screen_uv(world: vec3<f32>) -> vec3<f32> {
+    let clip = camera.view_projection * vec4<f32>(world, 1.0);
+    let ndc = clip.xyz / clip.w;
+    return vec3<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5, ndc.z);
+}
+
+fn sky(dir: vec3<f32>) -> vec3<f32> {
+    if environment.enabled == 0u {
+        // Flat horizon-to-zenith gradient without a skybox
+        return mix(vec3<f32>(0.6, 0.7, 0.8), vec3<f32>(0.25, 0.4, 0.65), clamp(dir.y, 0.0, 1.0));
+    }
+    return textureSampleLevel(sky_map, sky_sampler, dir, 0.0).rgb * environment.intensity;
+}
+
+// March the reflected ray through the depth buffer; w = 1 on a hit.
+fn trace_reflection(origin: vec3<f32>, dir: vec3<f32>) -> vec4<f32> {
+    var t = 0.2;
+    for (var i = 0; i < 40; i++) {
+        let p = origin + dir * t;
+        let s = screen_uv(p);
+        if s.x < 0.0 || s.x > 1.0 || s.y < 0.0 || s.y > 1.0 || s.z < 0.0 || s.z > 1.0 {
+            break;
+        }
+        let d = load_depth(s.xy);
+        if d < 1.0 && s.z > d {
+            let gap = linear_depth(s.z) - linear_depth(d);
+            if gap < max(0.3, t * 0.1) {
+                let edge = min(min(s.x, 1.0 - s.x), min(s.y, 1.0 - s.y));
+                let fade = clamp(edge * 10.0, 0.0, 1.0);
+                return vec4<f32>(textureSampleLevel(scene_color, scene_sampler, s.xy, 0.0).rgb, fade);
+            }
+        }
+        t = t * 1.12 + 0.05;
+    }
+    return vec4<f32>(0.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = in.position.xy / vec2<f32>(textureDimensions(scene_color));
+    let scene_z = load_depth(uv);
+    if in.position.z >= scene_z {
+        discard;
+    }
+
+    let view_dir = normalize(camera.position - in.world_position);
+    let slope = wave_slope(in.world_position.xz) * water.waves.z;
+    var n = normalize(in.normal + vec3<f32>(-slope.x, 0.0, -slope.y));
+    if dot(n, view_dir) < 0.0 {
+        n = -n;
+    }
+
+    // Water between the surface and whatever is behind it
+    let ndc = vec2<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    let behind = camera.inv_view_projection * vec4<f32>(ndc, scene_z, 1.0);
+    let thickness = select(distance(behind.xyz / behind.w, in.world_position), 1.0e4, scene_z >= 1.0);
+
+    // Refraction, unless the offset lands on something in front of the water
+    var refract_uv = uv + n.xz * water.params.x * clamp(thickness, 0.0, 1.0);
+    if load_depth(refract_uv) <= in.position.z {
+        refract_uv = uv;
+    }
+    let refracted = textureSampleLevel(scene_color, scene_sampler, refract_uv, 0.0).rgb;
+
+    let absorb = 1.0 - exp(-thickness / water.shallow.w);
+    let tint = mix(water.shallow.rgb, water.deep.rgb, absorb);
+    let body = mix(refracted * mix(vec3<f32>(1.0), tint, 0.5), tint, absorb);
+
+    var color = body;
+    let mode = u32(water.params.y + 0.5);
+    if mode > 0u {
+        let reflect_dir = reflect(-view_dir, n);
+        var reflection = sky(reflect_dir);
+        if mode == 2u {
+            let hit = trace_reflection(in.world_position, reflect_dir);
+            reflection = mix(reflection, hit.rgb, hit.w);
+        }
+        let fresnel = 0.02 + 0.98 * pow(1.0 - clamp(dot(n, view_dir), 0.0, 1.0), 5.0);
+        color = mix(body, reflection, fresnel);
+    }
+
+    // Fade in from the shoreline
+    let shore = clamp(thickness / water.deep.w, 0.0, 1.0);
+    let original = textureSampleLevel(scene_color, scene_sampler, uv, 0.0).rgb;
+    return vec4<f32>(mix(original, color, shore), 1.0);
+}
+"#
+    .to_string()
+}
+
+/// Built-in particle shader: camera-facing quads with round, soft-edged
+/// sprites that fade out near the scene depth behind them.
+pub fn get_particle_wgsl() -> String {
+    r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    jitter: vec2<f32>,
+    _pad2: vec2<f32>,
+    inv_view_projection: mat4x4<f32>,
+    prev_view_projection: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var scene_depth: texture_depth_2d;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) corner: vec2<f32>,
+    @location(2) view_depth: f32,
+    @location(3) softness: f32,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) center: vec3<f32>,
+    @location(1) size: f32,
+    @location(2) color: vec4<f32>,
+    @location(3) softness: f32,
+) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+    // The camera's right and up axes are the first two rows of the view matrix
+    let right = vec3<f32>(camera.view[0].x, camera.view[1].x, camera.view[2].x);
+    let up = vec3<f32>(camera.view[0].y, camera.view[1].y, camera.view[2].y);
+    let world = center + (right * corner.x + up * corner.y) * (size * 0.5);
+
+    var out: VertexOutput;
+    out.position = camera.view_projection * vec4<f32>(world, 1.0);
+    out.color = color;
+    out.corner = corner;
+    out.view_depth = -(camera.view * vec4<f32>(world, 1.0)).z;
+    out.softness = softness;
+    return out;
+}
+
+// View depth of a depth buffer value, for perspective and orthographic
+// projections alike
+fn linear_depth(z: f32) -> f32 {
+    let p = camera.projection;
+    return (z * p[3][3] - p[3][2]) / (z * p[2][3] - p[2][2]);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(scene_depth));
+    let pixel = clamp(vec2<i32>(in.position.xy), vec2<i32>(0), size - 1);
+    let scene_z = textureLoad(scene_depth, pixel, 0);
+    if in.position.z >= scene_z {
+        discard;
+    }
+
+    // Round sprite with a soft rim
+    var alpha = in.color.a * clamp(1.0 - dot(in.corner, in.corner), 0.0, 1.0);
+    // Soft particles: fade out as the surface behind comes within `softness`
+    if in.softness > 0.0 && scene_z < 1.0 {
+        let gap = linear_depth(scene_z) - in.view_depth;
+        alpha = alpha * clamp(gap / in.softness, 0.0, 1.0);
+    }
+    if alpha <= 0.001 {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, alpha);
+}
+"#
+    .to_string()
+}
+
+/// WGSL fallback for bloom extraction pass (threshold + 13-tap tent downsample).
+pub fn get_bloom_wgsl() -> String {
+    r#"
+@group(0) @binding(0) var hdr_texture: texture_2d<f32>;
+@group(0) @binding(1) var hdr_sampler: sampler;
+
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    _pad: f32,
+};
+@group(0) @binding(2) var<uniform> bloom: BloomParams;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+fn luminance(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn threshold_color(color: vec3<f32>, threshold: f32, knee: f32) -> vec3<f32> {
+    let lum = luminance(color);
+    var soft = lum - threshold + knee;
+    soft = clamp(soft, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 0.0001);
+    let contribution = max(soft, lum - threshold) / max(lum, 0.0001);
+    return color * max(contribution, 0.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dims = vec2<f32>(textureDimensions(hdr_texture));
+    let texel = 1.0 / dims;
+    let uv = in.uv;
+
+    // 13-tap tent filter (Jimenez 2014)
+    let a = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-2.0, -2.0) * texel).rgb;
+    let b = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 0.0, -2.0) * texel).rgb;
+    let c = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 2.0, -2.0) * texel).rgb;
+    let d = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-1.0, -1.0) * texel).rgb;
+    let e = textureSample(hdr_texture, hdr_sampler, uv).rgb;
+    let f = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 1.0, -1.0) * texel).rgb;
+    let g = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-2.0,  0.0) * texel).rgb;
+    let h = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 2.0,  0.0) * texel).rgb;
+    let i = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-1.0,  1.0) * texel).rgb;
+    let j = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 1.0,  1.0) * texel).rgb;
+    let k = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>(-2.0,  2.0) * texel).rgb;
+    let l = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 0.0,  2.0) * texel).rgb;
+    let m = textureSample(hdr_texture, hdr_sampler, uv + vec2<f32>( 2.0,  2.0) * texel).rgb;
+
+    // Weighted downsample
+    var result = e * 0.125;
+    result += (d + f + i + j) * 0.125;
+    result += (a + c + k + m) * 0.03125;
+    result += (b + g + h + l) * 0.0625;
+
+    // Soft threshold
+    result = threshold_color(result, bloom.threshold, bloom.knee);
+
+    return vec4<f32>(result, 1.0);
+}
+"#
+    .to_string()
+}
+
+/// Built-in bloom mip chain: `fs_downsample` (13-tap tent) halves the
+/// previous level, `fs_upsample` (9-tap tent) is blended onto the next larger
+/// one on the way back up.
+pub fn get_bloom_chain_wgsl() -> String {
+    r#"
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+fn tap(uv: vec2<f32>, offset: vec2<f32>, texel: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source_texture, source_sampler, uv + offset * texel, 0.0).rgb;
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let uv = in.uv;
+
+    var result = tap(uv, vec2<f32>(0.0, 0.0), texel) * 0.125;
+    result += (tap(uv, vec2<f32>(-1.0, -1.0), texel) + tap(uv, vec2<f32>(1.0, -1.0), texel)
+        + tap(uv, vec2<f32>(-1.0, 1.0), texel) + tap(uv, vec2<f32>(1.0, 1.0), texel)) * 0.125;
+    result += (tap(uv, vec2<f32>(-2.0, -2.0), texel) + tap(uv, vec2<f32>(2.0, -2.0), texel)
+        + tap(uv, vec2<f32>(-2.0, 2.0), texel) + tap(uv, vec2<f32>(2.0, 2.0), texel)) * 0.03125;
+    result += (tap(uv, vec2<f32>(0.0, -2.0), texel) + tap(uv, vec2<f32>(-2.0, 0.0), texel)
+        + tap(uv, vec2<f32>(2.0, 0.0), texel) + tap(uv, vec2<f32>(0.0, 2.0), texel)) * 0.0625;
+    return vec4<f32>(result, 1.0);
+}
+
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let uv = in.uv;
+
+    var result = tap(uv, vec2<f32>(0.0, 0.0), texel) * 4.0;
+    result += (tap(uv, vec2<f32>(0.0, -1.0), texel) + tap(uv, vec2<f32>(-1.0, 0.0), texel)
+        + tap(uv, vec2<f32>(1.0, 0.0), texel) + tap(uv, vec2<f32>(0.0, 1.0), texel)) * 2.0;
+    result += tap(uv, vec2<f32>(-1.0, -1.0), texel) + tap(uv, vec2<f32>(1.0, -1.0), texel)
+        + tap(uv, vec2<f32>(-1.0, 1.0), texel) + tap(uv, vec2<f32>(1.0, 1.0), texel);
+    return vec4<f32>(result / 16.0, 1.0);
+}
+"#
+    .to_string()
+}
+
+pub fn get_tonemap_wgsl() -> String {
+    r#"
+@group(0) @binding(0) var hdr_texture: texture_2d<f32>;
+@group(0) @binding(1) var hdr_sampler: sampler;
+@group(0) @binding(2) var bloom_texture: texture_2d<f32>;
+@group(0) @binding(3) var grading_lut: texture_3d<f32>;
+
+struct ColorGrading {
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    lut_size: f32,  // 0 = no LUT
+};
+@group(0) @binding(4) var<uniform> grading: ColorGrading;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+fn aces_tonemap(x: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
     let e = 0.14;
     return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
 }
 
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+// Contrast around mid-grey, saturation around luminance, then the LUT (in sRGB space)
+fn color_grade(color: vec3<f32>) -> vec3<f32> {
+    var c = (color - 0.18) * grading.contrast + 0.18;
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = clamp(mix(vec3<f32>(luma), c, grading.saturation), vec3<f32>(0.0), vec3<f32>(1.0));
+    if (grading.lut_size > 0.0) {
+        let scale = (grading.lut_size - 1.0) / grading.lut_size;
+        let offset = 0.5 / grading.lut_size;
+        let coords = linear_to_srgb(c) * scale + offset;
+        c = srgb_to_linear(textureSampleLevel(grading_lut, hdr_sampler, coords, 0.0).rgb);
+    }
+    return c;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let uv = in.uv;
@@ -1333,13 +2619,12 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let b = textureSample(hdr_texture, hdr_sampler, uv + ca_offset).b;
     var hdr_color = vec3<f32>(r, g, b);
 
-    // Add bloom (bilinear-filtered from half-res gives natural soft glow)
+    // Add bloom (already scaled by the bloom pass's intensity)
     let bloom = textureSample(bloom_texture, hdr_sampler, uv).rgb;
-    let bloom_strength = 0.7;
-    hdr_color += bloom * bloom_strength;
+    hdr_color += bloom;
 
-    // ACES tonemap
-    let sdr_color_tm = aces_tonemap(hdr_color);
+    // ACES tonemap, then grading
+    let sdr_color_tm = color_grade(aces_tonemap(hdr_color * grading.exposure));
 
     // Vignette: smooth darkening at edges
     let vignette = 1.0 - smoothstep(0.4, 0.9, dist_from_center);
@@ -1604,3 +2889,23 @@ fn fs_main() {
 "#
     .to_string()
 }
+
+/// Depth resolve for MSAA rasterize passes: copies sample 0 of the
+/// multisampled depth target into the single-sample depth resource.
+pub fn get_depth_resolve_wgsl() -> String {
+    r#"
+@group(0) @binding(0) var msaa_depth: texture_depth_multisampled_2d;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @builtin(frag_depth) f32 {
+    return textureLoad(msaa_depth, vec2<i32>(position.xy), 0);
+}
+"#
+    .to_string()
+}
@@ -0,0 +1,227 @@
+//! Shader compile errors pointing at the user's source.
+//!
+//! WGSL is checked with naga before it reaches wgpu, and SLANG diagnostics
+//! are parsed out of the compiler's message, so a broken shader reports the
+//! original file, line and a caret-annotated snippet instead of an opaque
+//! error or a silent fallback.
+
+use std::path::{Path, PathBuf};
+
+/// A compile error located in a shader source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderDiagnostic {
+    pub file: PathBuf,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, when the compiler reports one.
+    pub column: Option<usize>,
+    /// Characters to underline from `column`.
+    pub length: usize,
+    pub message: String,
+    /// The offending line, for the snippet.
+    pub source_line: Option<String>,
+}
+
+impl std::fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        write!(f, ": error: {}", self.message)?;
+        let Some(source_line) = &self.source_line else {
+            return Ok(());
+        };
+        let gutter = " ".repeat(self.line.to_string().len());
+        write!(f, "\n{} |\n{} | {}", gutter, self.line, source_line)?;
+        if let Some(column) = self.column {
+            // Copy tabs from the source so the caret lines up
+            let indent: String = source_line
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            write!(f, "\n{} | {}{}", gutter, indent, "^".repeat(self.length.max(1)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse and validate WGSL. `origins` maps each line of `source` to the file
+/// and line it came from (see `pipeline::preprocess`); when empty, lines are
+/// reported against `file` as-is.
+pub fn check_wgsl(source: &str, file: &Path, origins: &[(PathBuf, usize)]) -> Result<(), ShaderDiagnostic> {
+    let (location, message) = match naga::front::wgsl::parse_str(source) {
+        Ok(module) => {
+            let mut validator = naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::all(),
+            );
+            match validator.validate(&module) {
+                Ok(_) => return Ok(()),
+                Err(e) => (e.location(source), error_chain(e.as_inner())),
+            }
+        }
+        Err(e) => (e.location(source), e.message().to_string()),
+    };
+
+    let Some(location) = location else {
+        return Err(ShaderDiagnostic {
+            file: file.to_path_buf(),
+            line: 0,
+            column: None,
+            length: 0,
+            message,
+            source_line: None,
+        });
+    };
+    let line = location.line_number as usize;
+    let source_line = source.lines().nth(line.saturating_sub(1)).map(str::to_string);
+    let (file, line) = origins
+        .get(line.saturating_sub(1))
+        .cloned()
+        .unwrap_or_else(|| (file.to_path_buf(), line));
+    let column = location.line_position as usize;
+    let length = source_line.as_ref().map_or(1, |l| {
+        (location.length as usize).min(l.len().saturating_sub(column - 1)).max(1)
+    });
+    Err(ShaderDiagnostic {
+        file,
+        line,
+        column: Some(column),
+        length,
+        message,
+        source_line,
+    })
+}
+
+/// An error and its causes, joined with ": ".
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// Locate the first error in SLANG compiler output, which looks like
+///
+/// ```text
+/// shaders/passes/bloom.slang(12): error 30015: undefined identifier 'glow'.
+///     float3 c = glow * 2.0;
+///                ^~~~
+/// ```
+///
+/// The snippet is taken from the output when present, else read from disk.
+pub fn from_slang(output: &str) -> Option<ShaderDiagnostic> {
+    let lines: Vec<&str> = output.lines().collect();
+    let (index, (file, line, message)) = lines
+        .iter()
+        .enumerate()
+        .find_map(|(i, l)| parse_slang_line(l).map(|d| (i, d)))?;
+
+    // SLANG echoes the line and underlines the error with `^~~~`
+    let echoed = lines.get(index + 1).zip(lines.get(index + 2)).filter(|(_, caret)| {
+        caret.contains('^') && caret.chars().all(|c| c.is_whitespace() || c == '^' || c == '~')
+    });
+    let (source_line, column, length) = match echoed {
+        Some((source, caret)) => {
+            let start = caret.find('^').unwrap_or(0);
+            let length = caret[start..].trim_end().len();
+            (Some(source.to_string()), Some(start + 1), length)
+        }
+        None => {
+            let source = std::fs::read_to_string(&file)
+                .ok()
+                .and_then(|s| s.lines().nth(line.saturating_sub(1)).map(str::to_string));
+            (source, None, 0)
+        }
+    };
+    Some(ShaderDiagnostic {
+        file,
+        line,
+        column,
+        length,
+        message,
+        source_line,
+    })
+}
+
+/// `path(line): error N: message` or `path(line, column): error ...`.
+fn parse_slang_line(line: &str) -> Option<(PathBuf, usize, String)> {
+    let marker = line.find("): error")?;
+    let open = line[..marker].rfind('(')?;
+    let position = &line[open + 1..marker];
+    let line_number = position.split(',').next()?.trim().parse::<usize>().ok()?;
+    let file = line[..open].trim();
+    if file.is_empty() {
+        return None;
+    }
+    // Drop "error 30015:" down to the message itself
+    let rest = line[marker + "): error".len()..].trim_start();
+    let message = match rest.split_once(':') {
+        Some((code, message)) if code.trim().chars().all(|c| c.is_ascii_digit()) => message.trim(),
+        _ => rest.trim_start_matches(':').trim(),
+    };
+    Some((PathBuf::from(file), line_number, message.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgsl_error_maps_to_original_file() {
+        let source = "struct Light { color: vec3<f32> }\nfn shade() -> f32 {\n    return glow;\n}\n";
+        let origins = vec![
+            (PathBuf::from("shaders/common/types.wgsl"), 1),
+            (PathBuf::from("shaders/passes/bloom.wgsl"), 4),
+            (PathBuf::from("shaders/passes/bloom.wgsl"), 5),
+            (PathBuf::from("shaders/passes/bloom.wgsl"), 6),
+        ];
+        let diagnostic = check_wgsl(source, Path::new("shaders/passes/bloom.wgsl"), &origins).unwrap_err();
+        assert_eq!(diagnostic.file, PathBuf::from("shaders/passes/bloom.wgsl"));
+        assert_eq!(diagnostic.line, 5);
+        assert_eq!(diagnostic.column, Some(12));
+        assert_eq!(diagnostic.source_line.as_deref(), Some("    return glow;"));
+        assert!(diagnostic.message.contains("glow"), "{}", diagnostic.message);
+
+        assert!(check_wgsl("fn shade() -> f32 { return 1.0; }", Path::new("ok.wgsl"), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_display_snippet() {
+        let diagnostic = ShaderDiagnostic {
+            file: PathBuf::from("shaders/passes/bloom.wgsl"),
+            line: 12,
+            column: Some(12),
+            length: 4,
+            message: "no definition in scope for identifier: 'glow'".to_string(),
+            source_line: Some("    return glow;".to_string()),
+        };
+        assert_eq!(
+            diagnostic.to_string(),
+            "shaders/passes/bloom.wgsl:12:12: error: no definition in scope for identifier: 'glow'\n   |\n12 |     return glow;\n   |            ^^^^"
+        );
+    }
+
+    #[test]
+    fn test_from_slang() {
+        let output = "Failed to load module 'bloom'\n\
+                      shaders/passes/bloom.slang(7): error 30015: undefined identifier 'glow'.\n\
+                      \x20   float3 c = glow * 2.0;\n\
+                      \x20              ^~~~\n";
+        let diagnostic = from_slang(output).unwrap();
+        assert_eq!(diagnostic.file, PathBuf::from("shaders/passes/bloom.slang"));
+        assert_eq!(diagnostic.line, 7);
+        assert_eq!(diagnostic.column, Some(16));
+        assert_eq!(diagnostic.length, 4);
+        assert_eq!(diagnostic.message, "undefined identifier 'glow'.");
+        assert_eq!(diagnostic.source_line.as_deref(), Some("    float3 c = glow * 2.0;"));
+
+        assert!(from_slang("Failed to create SLANG session").is_none());
+    }
+}
@@ -1,18 +1,28 @@
-//! Gaussian splat loading, caching, and CPU sorting.
+//! Gaussian splat loading, caching, and depth sorting.
 //!
 //! Loads .ply files in standard 3DGS format (position, scale, rotation,
-//! opacity, spherical harmonics) and uploads to GPU storage buffers.
-//! Provides per-frame CPU depth sorting for correct alpha blending.
-
-use std::collections::HashMap;
+//! opacity, spherical harmonics), the compressed `.splat`, `.ksplat` and
+//! `.spz` formats, or `.nsplat` files from `naive splat optimize` (see
+//! `splat_formats`), and uploads them quantized to GPU storage buffers.
+//! Clouds are split into cells of nearby splats. Each frame the cells outside
+//! the camera frustum are skipped, distant cells draw only their most
+//! prominent splats, and the rest are depth sorted for correct alpha
+//! blending, on the GPU where the device can (see `splat_sort`) and on the
+//! CPU otherwise.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use linked_hash_map::LinkedHashMap;
 
-use glam::{Mat4, Vec3};
-use wgpu::util::DeviceExt;
-
-use crate::components::SplatHandle;
+use glam::{BVec3, Mat4, Vec3};
+use crate::camera::CameraUniform;
+use crate::components::{GaussianSplat, SplatCrop, SplatCropShape, SplatHandle, Transform};
+use crate::culling::{Aabb, Frustum};
+use crate::gpu_memory::{BufferArenas, BufferClass, SubBuffer};
+use crate::pipeline::LodLock;
+use crate::splat_sort::GpuSplatSort;
 
 #[derive(Debug)]
 pub enum SplatError {
@@ -20,6 +30,8 @@ pub enum SplatError {
     PlyError(String),
     NoVertices,
     MissingProperty(String),
+    /// A compressed file that doesn't match its format.
+    Corrupt(String),
 }
 
 impl std::fmt::Display for SplatError {
@@ -29,39 +41,434 @@ impl std::fmt::Display for SplatError {
             Self::PlyError(msg) => write!(f, "PLY parse error: {}", msg),
             Self::NoVertices => write!(f, "PLY file contains no vertices"),
             Self::MissingProperty(name) => write!(f, "PLY missing property: {}", name),
+            Self::Corrupt(msg) => write!(f, "Invalid splat file: {}", msg),
         }
     }
 }
 
-/// GPU-side splat data. Packed to 64 bytes for efficient storage buffer access.
+/// GPU-side splat data, quantized to 32 bytes: the position at full
+/// precision, scale and rotation as half floats, color and opacity as 8 bits.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct GaussianSplatGpu {
     pub position: [f32; 3],
+    /// Linear color (rgb) and opacity (a) as unorm8, red in the low byte.
+    pub color: u32,
+    /// Scale x, y, z as f16 pairs (the fourth half is unused).
+    pub scale: [u32; 2],
+    /// Unit quaternion as f16 pairs, in PLY `rot_0..rot_3` order.
+    pub rotation: [u32; 2],
+}
+
+/// Two f32s as f16 bits, the first in the low half (WGSL `unpack2x16float`).
+fn pack_half2(a: f32, b: f32) -> u32 {
+    half::f16::from_f32(a).to_bits() as u32 | (half::f16::from_f32(b).to_bits() as u32) << 16
+}
+
+fn unpack_half2(packed: u32) -> [f32; 2] {
+    [
+        half::f16::from_bits(packed as u16).to_f32(),
+        half::f16::from_bits((packed >> 16) as u16).to_f32(),
+    ]
+}
+
+impl GaussianSplatGpu {
+    pub fn new(position: [f32; 3], scale: [f32; 3], rotation: [f32; 4], opacity: f32, color: [f32; 3]) -> Self {
+        let unorm8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u32;
+        Self {
+            position,
+            color: unorm8(color[0]) | unorm8(color[1]) << 8 | unorm8(color[2]) << 16 | unorm8(opacity) << 24,
+            scale: [pack_half2(scale[0], scale[1]), pack_half2(scale[2], 0.0)],
+            rotation: [pack_half2(rotation[0], rotation[1]), pack_half2(rotation[2], rotation[3])],
+        }
+    }
+
+    /// Scale, rotation, opacity and color as the shader reads them.
+    pub fn unpack(&self) -> ([f32; 3], [f32; 4], f32, [f32; 3]) {
+        let [sx, sy] = unpack_half2(self.scale[0]);
+        let [sz, _] = unpack_half2(self.scale[1]);
+        let [r0, r1] = unpack_half2(self.rotation[0]);
+        let [r2, r3] = unpack_half2(self.rotation[1]);
+        let channel = |i: u32| ((self.color >> (i * 8)) & 0xff) as f32 / 255.0;
+        ([sx, sy, sz], [r0, r1, r2, r3], channel(3), [channel(0), channel(1), channel(2)])
+    }
+}
+
+/// Spherical harmonic coefficients in bands 1 and 2 (3 + 5), each rgb.
+pub const SH_COEFFICIENTS: usize = 8;
+
+/// Bands 1 and 2 of one splat's spherical harmonics: the 24 values of
+/// `SH_COEFFICIENTS` rgb triplets, coefficient-major, as f16 pairs. Kept in
+/// a buffer beside the splats so clouds without them don't pay for them.
+pub type SplatSh = [u32; 12];
+
+/// Pack SH coefficients, `get(coefficient, channel)` for coefficients 0..8
+/// (band 1 first, in the usual `f_rest` order).
+pub fn pack_sh(get: impl Fn(usize, usize) -> f32) -> SplatSh {
+    let values: Vec<f32> = (0..SH_COEFFICIENTS * 3).map(|i| get(i / 3, i % 3)).collect();
+    std::array::from_fn(|i| pack_half2(values[i * 2], values[i * 2 + 1]))
+}
+
+/// SH coefficients as the shader reads them.
+pub fn unpack_sh(sh: &SplatSh) -> [[f32; 3]; SH_COEFFICIENTS] {
+    let values: Vec<f32> = sh.iter().flat_map(|&packed| unpack_half2(packed)).collect();
+    std::array::from_fn(|i| [values[i * 3], values[i * 3 + 1], values[i * 3 + 2]])
+}
+
+/// A decoded splat file.
+#[derive(Debug, Default)]
+pub struct SplatData {
+    pub splats: Vec<GaussianSplatGpu>,
+    /// SH bands 1 and 2 per splat; empty when the file has none.
+    pub sh: Vec<SplatSh>,
+}
+
+/// Most crop volumes of one splat entity.
+pub const MAX_SPLAT_CROPS: usize = 4;
+
+/// Bytes between entities' `SplatParams` in the params buffer, the largest
+/// uniform offset alignment devices ask for.
+pub const SPLAT_PARAMS_STRIDE: u64 = 256;
+
+/// Per-entity splat uniforms: where the cloud is drawn, how opaque, and what
+/// it is cropped to.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SplatParams {
+    pub model: [[f32; 4]; 4],
     pub opacity: f32,
-    pub scale: [f32; 3],
-    pub _pad0: f32,
-    pub rotation: [f32; 4],
-    pub sh_dc: [f32; 3],
-    pub _pad1: f32,
+    pub crop_count: u32,
+    pub _pad: [u32; 2],
+    /// Per crop: center and shape (1 = ellipsoid), half extents and invert.
+    pub crops: [[[f32; 4]; 2]; MAX_SPLAT_CROPS],
+}
+
+impl SplatParams {
+    pub fn new(model: &Mat4, splat: &GaussianSplat) -> Self {
+        let mut crops = [[[0.0; 4]; 2]; MAX_SPLAT_CROPS];
+        for (slot, crop) in crops.iter_mut().zip(&splat.crops) {
+            let shape = if crop.shape == SplatCropShape::Ellipsoid { 1.0 } else { 0.0 };
+            *slot = [crop.center.extend(shape).to_array(), crop.half_extents.extend(crop.invert as u32 as f32).to_array()];
+        }
+        Self {
+            model: model.to_cols_array_2d(),
+            opacity: splat.opacity.clamp(0.0, 1.0),
+            crop_count: splat.crops.len().min(MAX_SPLAT_CROPS) as u32,
+            _pad: [0; 2],
+            crops,
+        }
+    }
+}
+
+/// Layout of a chunk's bind group in the splat render pass: splat data,
+/// sorted indices, SH and the entity's `SplatParams` at a dynamic offset.
+pub fn create_splat_data_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Splat Data Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<SplatParams>() as u64),
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+/// A params buffer with room for `slots` entities.
+fn create_params_buffer(device: &wgpu::Device, slots: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Splat Params"),
+        size: slots.max(4) as u64 * SPLAT_PARAMS_STRIDE,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// A chunk's render bind group: its splat, index and SH ranges, and the
+/// params buffer read at each entity's dynamic offset.
+fn create_chunk_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    params_buffer: &wgpu::Buffer,
+    chunk: &SplatChunk,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Splat Data Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: chunk.splat_buffer.binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: chunk.sorted_index_buffer.binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: chunk.sh_buffer.binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: params_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<SplatParams>() as u64),
+                }),
+            },
+        ],
+    })
+}
+
+/// Whether `crops` remove every splat centered in `bounds`.
+fn crops_exclude(crops: &[SplatCrop], bounds: &Aabb) -> bool {
+    let corners = (0..8).map(|i| Vec3::select(BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0), bounds.max, bounds.min));
+    if crops.iter().any(|crop| crop.invert && corners.clone().all(|corner| crop.contains(corner))) {
+        return true;
+    }
+    let mut kept = crops.iter().filter(|crop| !crop.invert).peekable();
+    kept.peek().is_some()
+        && kept.all(|crop| {
+            let (min, max) = (crop.center - crop.half_extents, crop.center + crop.half_extents);
+            bounds.max.cmplt(min).any() || bounds.min.cmpgt(max).any()
+        })
+}
+
+/// Most splats in one cell, the unit splats are culled, subsampled and
+/// sorted in.
+pub const SPLAT_CELL_SIZE: usize = 16384;
+
+/// A compact group of a cloud's splats. Its splats are stored most prominent
+/// first, so any prefix of the cell is a coarser version of it.
+#[derive(Debug, Clone, Copy)]
+pub struct SplatCell {
+    /// Chunk holding the cell's splats.
+    pub chunk: usize,
+    /// The cell's segment of the chunk's sorted indices.
+    pub segment: u32,
+    /// First of the cell's splats in the chunk.
+    pub first: u32,
+    pub count: u32,
+    /// Bounds of the splats' quads, for frustum culling.
+    pub bounds: Aabb,
+}
+
+/// Node of the k-d tree over a cloud's cells.
+#[derive(Debug, Clone, Copy)]
+enum SplatNode {
+    Cell(usize),
+    /// Splats at or past `at` along `axis` are under `above`.
+    Split { axis: usize, at: f32, below: usize, above: usize },
+}
+
+/// A cell to draw this frame and how many of its splats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellDraw {
+    pub cell: usize,
+    pub count: u32,
+}
+
+/// Part of a splat cloud that fits in one storage buffer binding.
+pub struct SplatChunk {
+    /// Storage range holding the chunk's splat data.
+    pub splat_buffer: SubBuffer,
+    /// `SplatSh` per splat, or one zeroed entry when the cloud has none.
+    pub sh_buffer: SubBuffer,
+    /// Storage range of sorted u32 indices into `splat_buffer` (updated each
+    /// frame), one segment of `segment_capacity` per cell.
+    pub sorted_index_buffer: SubBuffer,
+    /// Depth keys the GPU sort orders, when the chunk is sorted on the GPU.
+    pub sort_key_buffer: Option<SubBuffer>,
+    /// First splat and splats to sort of each segment, written each frame
+    /// when the chunk is sorted on the GPU.
+    pub segment_buffer: Option<SubBuffer>,
+    /// Number of splats in this chunk.
+    pub splat_count: u32,
+    /// Number of cells (and index segments) in this chunk.
+    pub segments: u32,
+    /// Indices per segment: the largest cell, padded to
+    /// `splat_sort::sort_capacity` when sorted on the GPU.
+    pub segment_capacity: u32,
+    /// CPU-side positions for depth sorting (empty when sorted on the GPU).
+    pub cpu_positions: Vec<[f32; 3]>,
+    /// Render bind group over the chunk's ranges and the cache's params
+    /// buffer, rebuilt when either moves.
+    pub bind_group: Option<wgpu::BindGroup>,
 }
 
 /// A loaded GPU splat cloud.
+#[derive(Default)]
 pub struct GpuSplat {
-    /// Storage buffer containing all splat data.
-    pub splat_buffer: wgpu::Buffer,
-    /// Buffer of sorted u32 indices (updated each frame).
-    pub sorted_index_buffer: wgpu::Buffer,
+    /// One chunk unless the cloud is bigger than the device's storage buffer
+    /// binding limit; then its cells are spread over several.
+    pub chunks: Vec<SplatChunk>,
+    pub cells: Vec<SplatCell>,
+    /// k-d tree over `cells`, root first.
+    nodes: Vec<SplatNode>,
     /// Number of splats in this cloud.
     pub splat_count: u32,
-    /// CPU-side positions for depth sorting.
-    pub cpu_positions: Vec<[f32; 3]>,
+}
+
+impl GpuSplat {
+    /// The cells to draw for `view`, farthest first, with the splats to draw
+    /// of each. Cells outside the frustum or cropped away are left out.
+    /// `view` is in the cloud's local space (see `SplatView::local`). Walking
+    /// the k-d tree
+    /// far side first orders the cells exactly; splats are sorted within
+    /// each cell.
+    pub fn plan(&self, view: &SplatView, splat: &GaussianSplat) -> Vec<CellDraw> {
+        let mut draws = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node) = stack.pop() {
+            match self.nodes[node] {
+                SplatNode::Cell(index) => {
+                    let cell = &self.cells[index];
+                    if cell.count == 0
+                        || view.frustum.as_ref().is_some_and(|f| !f.intersects(&cell.bounds))
+                        || crops_exclude(&splat.crops, &cell.bounds)
+                    {
+                        continue;
+                    }
+                    let detail = if view.full_detail {
+                        1.0
+                    } else {
+                        let closest = view.position.clamp(cell.bounds.min, cell.bounds.max);
+                        let distance = closest.distance(view.position) * view.lod_scale;
+                        lod_detail(distance, splat.lod_distance, splat.min_detail)
+                    };
+                    let count = ((cell.count as f32 * detail).ceil() as u32).clamp(1, cell.count);
+                    draws.push(CellDraw { cell: index, count });
+                }
+                SplatNode::Split { axis, at, below, above } => {
+                    // The near side goes on the stack first, to be drawn last
+                    if view.position[axis] < at {
+                        stack.extend([below, above]);
+                    } else {
+                        stack.extend([above, below]);
+                    }
+                }
+            }
+        }
+        draws
+    }
+}
+
+/// The camera a frame's splats are culled, subsampled and sorted for.
+pub struct SplatView {
+    pub view_matrix: Mat4,
+    pub view_projection: Mat4,
+    pub position: Vec3,
+    /// None under `--no-culling`.
+    pub frustum: Option<Frustum>,
+    /// Every splat at any distance (`--force-lod0`).
+    pub full_detail: bool,
+    /// World units per unit of the view's space, for LOD distances.
+    pub lod_scale: f32,
+}
+
+impl SplatView {
+    pub fn new(camera: &CameraUniform, lod_lock: LodLock) -> Self {
+        let view_projection = Mat4::from_cols_array_2d(&camera.view_projection);
+        Self {
+            view_matrix: Mat4::from_cols_array_2d(&camera.view),
+            view_projection,
+            position: Vec3::from(camera.position),
+            frustum: (!lod_lock.disable_culling).then(|| Frustum::from_view_projection(&view_projection)),
+            full_detail: lod_lock.force_lod0,
+            lod_scale: 1.0,
+        }
+    }
+
+    /// The view in the local space of a cloud drawn with `model`.
+    pub fn local(&self, model: &Mat4) -> Self {
+        let view_projection = self.view_projection * *model;
+        let scale = model.to_scale_rotation_translation().0.abs().max_element();
+        Self {
+            view_matrix: self.view_matrix * *model,
+            view_projection,
+            position: model.inverse().transform_point3(self.position),
+            frustum: self.frustum.map(|_| Frustum::from_view_projection(&view_projection)),
+            full_detail: self.full_detail,
+            lod_scale: self.lod_scale * scale,
+        }
+    }
+}
+
+/// Share of a cell's splats drawn `distance` from the camera: all of them
+/// within `lod_distance`, then falling with the square of the distance (as
+/// the cell's screen area does), but never below `min_detail`.
+pub fn lod_detail(distance: f32, lod_distance: Option<f32>, min_detail: f32) -> f32 {
+    match lod_distance {
+        Some(near) if near > 0.0 && distance > near => (near / distance).powi(2).max(min_detail.clamp(0.0, 1.0)),
+        _ => 1.0,
+    }
+}
+
+/// Splats per chunk on a device: as many as one storage buffer binding (and
+/// buffer) holds, of splat data or, `with_sh`, of SH coefficients.
+pub fn splats_per_chunk(limits: &wgpu::Limits, with_sh: bool) -> usize {
+    let max_bytes = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+    let splat_bytes = if with_sh {
+        std::mem::size_of::<SplatSh>().max(std::mem::size_of::<GaussianSplatGpu>())
+    } else {
+        std::mem::size_of::<GaussianSplatGpu>()
+    };
+    (max_bytes / splat_bytes as u64).max(1) as usize
 }
 
 /// Cache of loaded splat clouds, keyed by file path.
 pub struct SplatCache {
     splats: Vec<GpuSplat>,
     path_to_handle: HashMap<PathBuf, SplatHandle>,
+    /// Where splat data and sort indices live (see `gpu_memory`).
+    pub arenas: BufferArenas,
+    /// Compute sort for clouds loaded after `enable_gpu_sort`.
+    gpu_sort: Option<GpuSplatSort>,
+    /// `SplatParams` of the entities drawn this frame, `SPLAT_PARAMS_STRIDE`
+    /// apart.
+    params_buffer: Option<wgpu::Buffer>,
+    params_slots: HashMap<hecs::Entity, u32>,
+    /// Layout of the chunks' render bind groups.
+    render_layout: Option<wgpu::BindGroupLayout>,
 }
 
 impl SplatCache {
@@ -69,12 +476,33 @@ impl SplatCache {
         Self {
             splats: Vec::new(),
             path_to_handle: HashMap::new(),
+            arenas: BufferArenas::default(),
+            gpu_sort: None,
+            params_buffer: None,
+            params_slots: HashMap::new(),
+            render_layout: None,
         }
     }
 
+    /// Sort clouds loaded from now on with compute shaders, if the device
+    /// can run them. Returns whether it can.
+    pub fn enable_gpu_sort(&mut self, device: &wgpu::Device) -> bool {
+        self.gpu_sort = GpuSplatSort::new(device);
+        if self.gpu_sort.is_none() {
+            tracing::warn!("Splat downgrade: the device can't run the GPU splat sort; sorting on the CPU");
+        }
+        self.gpu_sort.is_some()
+    }
+
+    /// Whether splats are sorted on the GPU.
+    pub fn gpu_sorting(&self) -> bool {
+        self.gpu_sort.is_some()
+    }
+
     pub fn get_or_load(
         &mut self,
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         project_root: &Path,
         splat_path: &str,
     ) -> Result<SplatHandle, SplatError> {
@@ -83,12 +511,19 @@ impl SplatCache {
             return Ok(handle);
         }
 
-        let gpu_splat = load_ply(device, project_root, splat_path)?;
+        let data = load_splat_file(project_root, splat_path)?;
+        let mut gpu_splat = upload_splats(device, queue, &self.arenas, splat_path, data, self.gpu_sort.is_some());
+        let layout = self.render_layout.get_or_insert_with(|| create_splat_data_layout(device));
+        let params_buffer = self.params_buffer.get_or_insert_with(|| create_params_buffer(device, 4));
+        for chunk in &mut gpu_splat.chunks {
+            chunk.bind_group = Some(create_chunk_bind_group(device, layout, params_buffer, chunk));
+        }
         let handle = SplatHandle(self.splats.len());
         tracing::info!(
-            "Loaded splat: {} ({} gaussians)",
+            "Loaded splat: {} ({} gaussians in {} cells)",
             splat_path,
-            gpu_splat.splat_count
+            gpu_splat.splat_count,
+            gpu_splat.cells.len()
         );
         self.splats.push(gpu_splat);
         self.path_to_handle.insert(key, handle);
@@ -99,42 +534,77 @@ impl SplatCache {
         &self.splats[handle.0]
     }
 
-    /// Sort splats back-to-front for correct alpha blending.
-    /// Updates the sorted_index_buffer on GPU.
-    pub fn sort_splats(
-        &self,
-        handle: SplatHandle,
-        view_matrix: &Mat4,
-        queue: &wgpu::Queue,
-    ) {
-        let gpu_splat = &self.splats[handle.0];
-        let count = gpu_splat.splat_count as usize;
-        if count == 0 {
+    /// Upload the transform, opacity and crops of the frame's splat
+    /// entities. Call once a frame before the splat passes; each view sorts
+    /// the clouds for its own camera with `sort_splats`.
+    pub fn prepare_frame(&mut self, world: &hecs::World, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.params_slots.clear();
+        let mut params = Vec::new();
+        for (entity, (splat, transform)) in world.query::<(&GaussianSplat, &Transform)>().iter() {
+            self.params_slots.insert(entity, params.len() as u32);
+            params.push(SplatParams::new(&transform.world_matrix, splat));
+        }
+        if params.is_empty() {
             return;
         }
 
-        // Compute camera-space Z for each splat
-        let mut indexed_depths: Vec<(u32, f32)> = gpu_splat
-            .cpu_positions
-            .iter()
-            .enumerate()
-            .map(|(i, pos)| {
-                let world_pos = Vec3::from(*pos);
-                let view_pos = view_matrix.transform_point3(world_pos);
-                (i as u32, view_pos.z)
-            })
-            .collect();
+        let needed = params.len() as u64 * SPLAT_PARAMS_STRIDE;
+        if self.params_buffer.as_ref().is_none_or(|buffer| buffer.size() < needed) {
+            self.params_buffer = Some(create_params_buffer(device, params.len().next_power_of_two()));
+            self.rebind_chunks(device);
+        }
+        let mut bytes = vec![0u8; needed as usize];
+        for (slot, p) in bytes.chunks_exact_mut(SPLAT_PARAMS_STRIDE as usize).zip(&params) {
+            slot[..std::mem::size_of::<SplatParams>()].copy_from_slice(bytemuck::bytes_of(p));
+        }
+        if let Some(buffer) = &self.params_buffer {
+            queue.write_buffer(buffer, 0, &bytes);
+        }
+    }
 
-        // Sort back-to-front (most negative Z = farthest in right-handed view space)
-        indexed_depths.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    /// An entity's dynamic offset into the params buffer its chunks' bind
+    /// groups read, if the entity was prepared this frame.
+    pub fn params_offset(&self, entity: hecs::Entity) -> Option<u32> {
+        let slot = *self.params_slots.get(&entity)?;
+        Some(slot * SPLAT_PARAMS_STRIDE as u32)
+    }
 
-        // Upload sorted indices to GPU
-        let sorted_indices: Vec<u32> = indexed_depths.iter().map(|(i, _)| *i).collect();
-        queue.write_buffer(
-            &gpu_splat.sorted_index_buffer,
-            0,
-            bytemuck::cast_slice(&sorted_indices),
-        );
+    /// Rebuild every chunk's bind group, after the params buffer or the
+    /// chunks' storage ranges moved.
+    fn rebind_chunks(&mut self, device: &wgpu::Device) {
+        let (Some(layout), Some(params_buffer)) = (&self.render_layout, &self.params_buffer) else {
+            return;
+        };
+        for chunk in self.splats.iter_mut().flat_map(|s| s.chunks.iter_mut()) {
+            let bind_group = create_chunk_bind_group(device, layout, params_buffer, chunk);
+            chunk.bind_group = Some(bind_group);
+        }
+    }
+
+    /// Sort the splats `view` draws of an entity's cloud back-to-front for
+    /// correct alpha blending. Updates the sorted_index_buffer on GPU, with a
+    /// compute pass recorded into `encoder` for chunks that have sort keys and
+    /// a CPU sort and upload for the rest.
+    pub fn sort_splats(
+        &self,
+        splat: &GaussianSplat,
+        view: &SplatView,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let gpu_splat = &self.splats[splat.splat_handle.0];
+        let draws = gpu_splat.plan(view, splat);
+        if let Some(gpu_sort) = &self.gpu_sort {
+            gpu_sort.sort(device, queue, encoder, gpu_splat, &draws, &view.view_matrix);
+        }
+        for draw in &draws {
+            let cell = &gpu_splat.cells[draw.cell];
+            let chunk = &gpu_splat.chunks[cell.chunk];
+            if chunk.sort_key_buffer.is_none() {
+                sort_cell(chunk, cell, draw.count, &view.view_matrix, queue);
+            }
+        }
     }
 
     /// Invalidate a cached splat (for hot-reload).
@@ -147,12 +617,261 @@ impl SplatCache {
         }
     }
 
+    /// Free the GPU data of splat clouds outside `keep`, on a scene change.
+    /// A released handle draws nothing and its file loads afresh on the next
+    /// `get_or_load`. Returns how many clouds were released.
+    pub fn release_unused(&mut self, keep: &HashSet<SplatHandle>) -> usize {
+        self.path_to_handle.retain(|_, handle| keep.contains(handle));
+        let mut released = 0;
+        for (i, splat) in self.splats.iter_mut().enumerate() {
+            if !keep.contains(&SplatHandle(i)) && !splat.chunks.is_empty() {
+                *splat = GpuSplat::default();
+                released += 1;
+            }
+        }
+        released
+    }
+
+    /// Compact the storage arena if it has become fragmented. Returns the
+    /// bytes copied.
+    pub fn compact(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> u64 {
+        if !self.arenas.needs_compaction(BufferClass::Storage) {
+            return 0;
+        }
+        let live = self.splats.iter_mut()
+            .flat_map(|s| s.chunks.iter_mut())
+            .flat_map(|c| {
+                [
+                    Some(&mut c.splat_buffer),
+                    Some(&mut c.sh_buffer),
+                    Some(&mut c.sorted_index_buffer),
+                    c.sort_key_buffer.as_mut(),
+                    c.segment_buffer.as_mut(),
+                ]
+            })
+            .flatten();
+        let moved = self.arenas.compact(device, queue, BufferClass::Storage, live);
+        if moved > 0 {
+            self.rebind_chunks(device);
+        }
+        moved
+    }
+
     /// Check if any splats are loaded.
     pub fn has_splats(&self) -> bool {
         !self.splats.is_empty()
     }
 }
 
+/// Sort the first `count` splats of a cell back-to-front and upload the
+/// indices to its segment.
+fn sort_cell(chunk: &SplatChunk, cell: &SplatCell, count: u32, view_matrix: &Mat4, queue: &wgpu::Queue) {
+    let first = cell.first as usize;
+    let positions = &chunk.cpu_positions[first..first + count as usize];
+
+    // Upload sorted indices to GPU
+    let sorted_indices = depth_order(positions, cell.first, view_matrix);
+    let segment_offset = (cell.segment * chunk.segment_capacity) as u64 * std::mem::size_of::<u32>() as u64;
+    queue.write_buffer(
+        chunk.sorted_index_buffer.buffer(),
+        chunk.sorted_index_buffer.offset() + segment_offset,
+        bytemuck::cast_slice(&sorted_indices),
+    );
+}
+
+/// Indices (from `first`) of `positions` ordered back-to-front for
+/// `view_matrix`.
+pub(crate) fn depth_order(positions: &[[f32; 3]], first: u32, view_matrix: &Mat4) -> Vec<u32> {
+    // Compute camera-space Z for each splat
+    let mut indexed_depths: Vec<(u32, f32)> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, pos)| {
+            let view_pos = view_matrix.transform_point3(Vec3::from(*pos));
+            (first + i as u32, view_pos.z)
+        })
+        .collect();
+
+    // Sort back-to-front (most negative Z = farthest in right-handed view space)
+    indexed_depths.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    indexed_depths.iter().map(|(i, _)| *i).collect()
+}
+
+/// Order splats into cells of at most `cell_size` by splitting them at the
+/// median of their longest axis, recursively. Rearranges `order` (indices
+/// into `splats`) so each cell is a run of it, and returns the k-d tree
+/// (root first) and each cell's range of `order`, in tree order.
+fn build_cells(order: &mut [u32], splats: &[GaussianSplatGpu], cell_size: usize) -> (Vec<SplatNode>, Vec<Range<usize>>) {
+    let mut nodes = Vec::new();
+    let mut cells = Vec::new();
+    split_cells(order, splats, 0, cell_size.max(1), &mut nodes, &mut cells);
+    (nodes, cells)
+}
+
+fn split_cells(
+    order: &mut [u32],
+    splats: &[GaussianSplatGpu],
+    first: usize,
+    cell_size: usize,
+    nodes: &mut Vec<SplatNode>,
+    cells: &mut Vec<Range<usize>>,
+) -> usize {
+    let node = nodes.len();
+    if order.len() <= cell_size {
+        nodes.push(SplatNode::Cell(cells.len()));
+        cells.push(first..first + order.len());
+        return node;
+    }
+
+    let position = |i: &u32| splats[*i as usize].position;
+    let bounds = Aabb::from_points(order.iter().map(position));
+    let extent = bounds.max - bounds.min;
+    let axis = if extent.x >= extent.y && extent.x >= extent.z {
+        0
+    } else if extent.y >= extent.z {
+        1
+    } else {
+        2
+    };
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by(mid, |a, b| position(a)[axis].total_cmp(&position(b)[axis]));
+    let at = position(&order[mid])[axis];
+
+    // Children are filled in once they have indices
+    nodes.push(SplatNode::Cell(usize::MAX));
+    let (lower, upper) = order.split_at_mut(mid);
+    let below = split_cells(lower, splats, first, cell_size, nodes, cells);
+    let above = split_cells(upper, splats, first + mid, cell_size, nodes, cells);
+    nodes[node] = SplatNode::Split { axis, at, below, above };
+    node
+}
+
+/// How much of the screen a splat covers: opacity times the area of its quad.
+pub(crate) fn prominence(splat: &GaussianSplatGpu) -> f32 {
+    let (scale, _, opacity, _) = splat.unpack();
+    opacity * (scale[0] * scale[1]).abs()
+}
+
+/// Bounds of the splats' quads (two standard deviations, as drawn).
+fn quad_bounds(splats: &[GaussianSplatGpu]) -> Aabb {
+    let mut bounds = Aabb::from_points(splats.iter().map(|s| s.position));
+    for splat in splats {
+        let (scale, ..) = splat.unpack();
+        let radius = Vec3::splat(2.0 * scale[0].max(scale[1]).max(scale[2]));
+        let position = Vec3::from(splat.position);
+        bounds.min = bounds.min.min(position - radius);
+        bounds.max = bounds.max.max(position + radius);
+    }
+    bounds
+}
+
+/// Upload splat data into the storage arena: split into cells, and the cells
+/// into chunks that fit the device's storage buffer limits. `gpu_sort` adds
+/// the padded index, key and segment ranges the compute sort works in.
+fn upload_splats(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    arenas: &BufferArenas,
+    label: &str,
+    data: SplatData,
+    gpu_sort: bool,
+) -> GpuSplat {
+    let splat_count = data.splats.len() as u32;
+    let has_sh = !data.sh.is_empty();
+    let per_chunk = splats_per_chunk(&device.limits(), has_sh);
+    let mut order: Vec<u32> = (0..splat_count).collect();
+    let (nodes, ranges) = build_cells(&mut order, &data.splats, SPLAT_CELL_SIZE.min(per_chunk));
+    for range in &ranges {
+        order[range.clone()].sort_by_cached_key(|&i| std::cmp::Reverse(prominence(&data.splats[i as usize]).to_bits()));
+    }
+    let splats: Vec<GaussianSplatGpu> = order.iter().map(|&i| data.splats[i as usize]).collect();
+    let sh: Vec<SplatSh> = if has_sh { order.iter().map(|&i| data.sh[i as usize]).collect() } else { Vec::new() };
+
+    // Consecutive cells share a chunk while they fit in one
+    let mut chunk_cells: Vec<Range<usize>> = Vec::new();
+    for (i, range) in ranges.iter().enumerate() {
+        match chunk_cells.last_mut() {
+            Some(cells) if range.end - ranges[cells.start].start <= per_chunk => cells.end = i + 1,
+            _ => chunk_cells.push(i..i + 1),
+        }
+    }
+    if chunk_cells.len() > 1 {
+        tracing::warn!(
+            "Splat downgrade: {} has {} gaussians, more than one storage buffer holds on this device ({}); drawing it in {} chunks",
+            label,
+            splats.len(),
+            per_chunk,
+            chunk_cells.len()
+        );
+    }
+
+    let mut cells = Vec::with_capacity(ranges.len());
+    let chunks = chunk_cells
+        .into_iter()
+        .enumerate()
+        .map(|(chunk_index, cell_range)| {
+            let cell_ranges = &ranges[cell_range];
+            let base = cell_ranges[0].start;
+            let end = cell_ranges[cell_ranges.len() - 1].end;
+            let data = &splats[base..end];
+            let largest = cell_ranges.iter().map(|r| r.len() as u32).max().unwrap_or(1);
+            let segment_capacity = if gpu_sort { crate::splat_sort::sort_capacity(largest) } else { largest.max(1) };
+            let segments = cell_ranges.len() as u32;
+
+            let mut initial_indices = vec![0u32; (segments * segment_capacity) as usize];
+            for (segment, range) in cell_ranges.iter().enumerate() {
+                let first = (range.start - base) as u32;
+                cells.push(SplatCell {
+                    chunk: chunk_index,
+                    segment: segment as u32,
+                    first,
+                    count: range.len() as u32,
+                    bounds: quad_bounds(&splats[range.clone()]),
+                });
+                // Sorted indices start out sequential
+                let indices = &mut initial_indices[segment * segment_capacity as usize..][..range.len()];
+                for (i, index) in indices.iter_mut().enumerate() {
+                    *index = first + i as u32;
+                }
+            }
+
+            let splat_buffer = arenas.upload(device, queue, BufferClass::Storage, bytemuck::cast_slice(data));
+            let sh_buffer = if has_sh {
+                arenas.upload(device, queue, BufferClass::Storage, bytemuck::cast_slice(&sh[base..end]))
+            } else {
+                arenas.upload(device, queue, BufferClass::Storage, bytemuck::cast_slice(&[SplatSh::default()]))
+            };
+            let sorted_index_buffer = arenas.upload(device, queue, BufferClass::Storage, bytemuck::cast_slice(&initial_indices));
+            let sort_key_buffer = gpu_sort.then(|| {
+                arenas.alloc(device, BufferClass::Storage, initial_indices.len() as u64 * std::mem::size_of::<u32>() as u64)
+            });
+            let segment_buffer = gpu_sort.then(|| {
+                arenas.alloc(device, BufferClass::Storage, segments as u64 * std::mem::size_of::<[u32; 2]>() as u64)
+            });
+            let cpu_positions: Vec<[f32; 3]> = if gpu_sort {
+                Vec::new()
+            } else {
+                data.iter().map(|s| s.position).collect()
+            };
+
+            SplatChunk {
+                splat_buffer,
+                sh_buffer,
+                sorted_index_buffer,
+                sort_key_buffer,
+                segment_buffer,
+                splat_count: data.len() as u32,
+                segments,
+                segment_capacity,
+                cpu_positions,
+                bind_group: None,
+            }
+        })
+        .collect();
+
+    GpuSplat { chunks, cells, nodes, splat_count }
+}
+
 /// Sigmoid activation: 1 / (1 + exp(-x))
 fn sigmoid(x: f32) -> f32 {
     1.0 / (1.0 + (-x).exp())
@@ -160,16 +879,15 @@ fn sigmoid(x: f32) -> f32 {
 
 /// SH DC coefficient to linear color: c * C0 + 0.5
 /// where C0 = 0.28209479 (Y_0^0 normalization constant)
-fn sh_dc_to_color(c: f32) -> f32 {
+pub(crate) fn sh_dc_to_color(c: f32) -> f32 {
     (c * 0.28209479 + 0.5).clamp(0.0, 1.0)
 }
 
-/// Load a PLY file in standard 3DGS format and upload to GPU.
-fn load_ply(
-    device: &wgpu::Device,
+/// Load a splat file, by extension: a compressed format or PLY.
+fn load_splat_file(
     project_root: &Path,
     splat_path: &str,
-) -> Result<GpuSplat, SplatError> {
+) -> Result<SplatData, SplatError> {
     let full_path = project_root.join(splat_path);
 
     if !full_path.exists() {
@@ -178,11 +896,38 @@ fn load_ply(
             "Splat file not found: {:?}, using procedural splat cloud",
             full_path
         );
-        return Ok(create_procedural_splats(device));
+        return Ok(SplatData { splats: create_procedural_splats(), sh: Vec::new() });
     }
+    read_splat_file(&full_path)
+}
+
+/// Decode a splat file that must exist, by extension.
+pub fn read_splat_file(full_path: &Path) -> Result<SplatData, SplatError> {
+    let extension = full_path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    if !crate::splat_formats::is_compressed(extension) {
+        return load_ply(full_path);
+    }
+    let bytes = std::fs::read(full_path).map_err(|e| SplatError::IoError(e.to_string()))?;
+    crate::splat_formats::decode(extension, &bytes)
+}
+
+/// Gaussians in a splat file and the bytes they and their SH take on the
+/// GPU. Unlike loading, a missing file is an error rather than the
+/// procedural stand-in.
+pub fn measure_splats(project_root: &Path, splat_path: &str) -> Result<(usize, u64), SplatError> {
+    if !project_root.join(splat_path).exists() {
+        return Err(SplatError::IoError(format!("{} not found", splat_path)));
+    }
+    let data = load_splat_file(project_root, splat_path)?;
+    let bytes = data.splats.len() * std::mem::size_of::<GaussianSplatGpu>() + data.sh.len() * std::mem::size_of::<SplatSh>();
+    Ok((data.splats.len(), bytes as u64))
+}
 
+/// Load a PLY file in standard 3DGS format. Bands 1 and 2 of the `f_rest_*`
+/// SH coefficients are kept; higher bands are dropped.
+fn load_ply(full_path: &Path) -> Result<SplatData, SplatError> {
     let file =
-        std::fs::File::open(&full_path).map_err(|e| SplatError::IoError(e.to_string()))?;
+        std::fs::File::open(full_path).map_err(|e| SplatError::IoError(e.to_string()))?;
     let mut reader = std::io::BufReader::new(file);
 
     let parser = ply_rs::parser::Parser::<ply_rs::ply::DefaultElement>::new();
@@ -201,7 +946,10 @@ fn load_ply(
 
     let count = vertices.len();
     let mut gpu_data = Vec::with_capacity(count);
-    let mut cpu_positions = Vec::with_capacity(count);
+    // f_rest holds all of red's coefficients, then green's, then blue's
+    let rest_count = vertices[0].keys().filter(|k| k.starts_with("f_rest_")).count();
+    let rest_per_channel = rest_count / 3;
+    let mut sh = Vec::with_capacity(if rest_per_channel > 0 { count } else { 0 });
 
     for vertex in vertices {
         let x = get_float_property(vertex, "x")?;
@@ -232,40 +980,18 @@ fn load_ply(
             sh_dc_to_color(f_dc_2),
         ];
 
-        // Normalize quaternion
-        let q_len = (rot_0 * rot_0 + rot_1 * rot_1 + rot_2 * rot_2 + rot_3 * rot_3).sqrt();
-        let rotation = if q_len > 0.0001 {
-            [rot_0 / q_len, rot_1 / q_len, rot_2 / q_len, rot_3 / q_len]
-        } else {
-            [1.0, 0.0, 0.0, 0.0]
-        };
+        let rotation = crate::splat_formats::normalize_rotation([rot_0, rot_1, rot_2, rot_3]);
 
-        cpu_positions.push([x, y, z]);
-
-        gpu_data.push(GaussianSplatGpu {
-            position: [x, y, z],
-            opacity,
-            scale,
-            _pad0: 0.0,
-            rotation,
-            sh_dc,
-            _pad1: 0.0,
-        });
-    }
-
-    let splat_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some(&format!("Splat Data: {}", splat_path)),
-        contents: bytemuck::cast_slice(&gpu_data),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
-
-    // Create sorted index buffer (initially sequential)
-    let initial_indices: Vec<u32> = (0..count as u32).collect();
-    let sorted_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some(&format!("Splat Sorted Indices: {}", splat_path)),
-        contents: bytemuck::cast_slice(&initial_indices),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
+        gpu_data.push(GaussianSplatGpu::new([x, y, z], scale, rotation, opacity, sh_dc));
+        if rest_per_channel > 0 {
+            sh.push(pack_sh(|i, c| {
+                if i >= rest_per_channel {
+                    return 0.0;
+                }
+                get_float_property(vertex, &format!("f_rest_{}", c * rest_per_channel + i)).unwrap_or(0.0)
+            }));
+        }
+    }
 
     tracing::info!(
         "Parsed PLY: {} gaussians from {:?}",
@@ -273,12 +999,7 @@ fn load_ply(
         full_path.file_name().unwrap_or_default()
     );
 
-    Ok(GpuSplat {
-        splat_buffer,
-        sorted_index_buffer,
-        splat_count: count as u32,
-        cpu_positions,
-    })
+    Ok(SplatData { splats: gpu_data, sh })
 }
 
 /// Extract a float property from a PLY element, handling both Float and Double types.
@@ -298,11 +1019,10 @@ fn get_float_property(
 }
 
 /// Create a galaxy/nebula spiral procedural splat cloud.
-fn create_procedural_splats(device: &wgpu::Device) -> GpuSplat {
+fn create_procedural_splats() -> Vec<GaussianSplatGpu> {
     use std::f32::consts::PI;
 
     let mut gpu_data = Vec::new();
-    let mut cpu_positions = Vec::new();
 
     // Simple LCG pseudo-random for deterministic results without rand crate
     let mut seed: u32 = 42;
@@ -335,7 +1055,6 @@ fn create_procedural_splats(device: &wgpu::Device) -> GpuSplat {
             let z = radius * angle.sin() + dz;
 
             let pos = [x, y, z];
-            cpu_positions.push(pos);
 
             // Color: warm core fading to cool blue/purple at edges
             let core_mix = (1.0 - t).powf(1.5);
@@ -346,15 +1065,13 @@ fn create_procedural_splats(device: &wgpu::Device) -> GpuSplat {
             let opacity = (0.6 - t * 0.3).max(0.15) + next_rand() * 0.1;
             let scale_val = 0.04 + t * 0.06 + next_rand() * 0.02;
 
-            gpu_data.push(GaussianSplatGpu {
-                position: pos,
+            gpu_data.push(GaussianSplatGpu::new(
+                pos,
+                [scale_val, scale_val * 0.5, scale_val],
+                [1.0, 0.0, 0.0, 0.0],
                 opacity,
-                scale: [scale_val, scale_val * 0.5, scale_val],
-                _pad0: 0.0,
-                rotation: [1.0, 0.0, 0.0, 0.0],
-                sh_dc: [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)],
-                _pad1: 0.0,
-            });
+                [r, g, b],
+            ));
         }
     }
 
@@ -369,46 +1086,25 @@ fn create_procedural_splats(device: &wgpu::Device) -> GpuSplat {
         let z = r_dist * theta.sin() * phi.cos();
 
         let pos = [x, y, z];
-        cpu_positions.push(pos);
 
         // Hot white/yellow core
         let r = 1.0;
         let g = 0.85 + next_rand() * 0.15;
         let b = 0.5 + next_rand() * 0.3;
 
-        gpu_data.push(GaussianSplatGpu {
-            position: pos,
-            opacity: 0.5 + next_rand() * 0.3,
-            scale: [0.06, 0.03, 0.06],
-            _pad0: 0.0,
-            rotation: [1.0, 0.0, 0.0, 0.0],
-            sh_dc: [r, g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)],
-            _pad1: 0.0,
-        });
+        gpu_data.push(GaussianSplatGpu::new(
+            pos,
+            [0.06, 0.03, 0.06],
+            [1.0, 0.0, 0.0, 0.0],
+            0.5 + next_rand() * 0.3,
+            [r, g, b],
+        ));
     }
 
     let count = gpu_data.len();
     assert_eq!(count, total);
 
-    let splat_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Procedural Galaxy Splat Data"),
-        contents: bytemuck::cast_slice(&gpu_data),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
-
-    let initial_indices: Vec<u32> = (0..count as u32).collect();
-    let sorted_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Procedural Galaxy Splat Sorted Indices"),
-        contents: bytemuck::cast_slice(&initial_indices),
-        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-    });
-
-    GpuSplat {
-        splat_buffer,
-        sorted_index_buffer,
-        splat_count: count as u32,
-        cpu_positions,
-    }
+    gpu_data
 }
 
 #[cfg(test)]
@@ -439,8 +1135,128 @@ mod tests {
     }
 
     #[test]
-    fn test_gpu_splat_size() {
-        // Verify the struct is 64 bytes as expected
-        assert_eq!(std::mem::size_of::<GaussianSplatGpu>(), 64);
+    fn test_cells_cull_and_draw_back_to_front() {
+        let splats: Vec<GaussianSplatGpu> = (0..10)
+            .map(|i| {
+                // Spread along z, listed out of order
+                let z = ((i * 7) % 10) as f32;
+                GaussianSplatGpu::new([0.0, 0.0, z], [0.01; 3], [1.0, 0.0, 0.0, 0.0], 1.0, [1.0; 3])
+            })
+            .collect();
+        let mut order: Vec<u32> = (0..10).collect();
+        let (nodes, ranges) = build_cells(&mut order, &splats, 4);
+        let splats: Vec<GaussianSplatGpu> = order.iter().map(|&i| splats[i as usize]).collect();
+        assert_eq!(ranges.iter().map(|r| r.len()).collect::<Vec<_>>(), vec![2, 3, 2, 3]);
+        // Each cell covers its own stretch of the longest axis
+        let depths = |r: &Range<usize>| splats[r.clone()].iter().map(|s| s.position[2] as u32).collect::<HashSet<_>>();
+        assert_eq!(depths(&ranges[1]), HashSet::from([2, 3, 4]));
+        assert_eq!(depths(&ranges[3]), HashSet::from([7, 8, 9]));
+
+        let cells = ranges
+            .iter()
+            .enumerate()
+            .map(|(i, r)| SplatCell {
+                chunk: 0,
+                segment: i as u32,
+                first: r.start as u32,
+                count: r.len() as u32,
+                bounds: quad_bounds(&splats[r.clone()]),
+            })
+            .collect();
+        let cloud = GpuSplat { chunks: Vec::new(), cells, nodes, splat_count: 10 };
+        let mut splat = GaussianSplat {
+            splat_handle: SplatHandle(0),
+            lod_distance: Some(2.0),
+            min_detail: 0.1,
+            opacity: 1.0,
+            crops: Vec::new(),
+        };
+        let look = |eye: Vec3, target: Vec3, far: f32| {
+            let view_matrix = Mat4::look_at_rh(eye, target, Vec3::Y);
+            let view_projection = Mat4::perspective_rh(1.0, 1.0, 0.1, far) * view_matrix;
+            SplatView {
+                view_matrix,
+                view_projection,
+                position: eye,
+                frustum: Some(Frustum::from_view_projection(&view_projection)),
+                full_detail: true,
+                lod_scale: 1.0,
+            }
+        };
+        let view_from = |eye: Vec3, far: f32| look(eye, Vec3::new(0.0, 0.0, 4.5), far);
+        let order = |draws: Vec<CellDraw>| draws.iter().map(|d| d.cell).collect::<Vec<_>>();
+
+        assert_eq!(order(cloud.plan(&view_from(Vec3::new(0.0, 0.0, 20.0), 100.0), &splat)), vec![0, 1, 2, 3]);
+        assert_eq!(order(cloud.plan(&view_from(Vec3::new(0.0, 0.0, -10.0), 100.0), &splat)), vec![3, 2, 1, 0]);
+        // The far plane at 15.5 leaves out the cells below z = 4.5
+        let mut near_view = view_from(Vec3::new(0.0, 0.0, 20.0), 15.5);
+        assert_eq!(order(cloud.plan(&near_view, &splat)), vec![2, 3]);
+
+        // Subsampled down to min_detail of each cell, at least one splat
+        near_view.full_detail = false;
+        let counts: Vec<u32> = cloud.plan(&near_view, &splat).iter().map(|d| d.count).collect();
+        assert_eq!(counts, vec![1, 1]);
+        assert_eq!(lod_detail(1.0, Some(2.0), 0.1), 1.0);
+        assert_eq!(lod_detail(4.0, Some(2.0), 0.1), 0.25);
+        assert_eq!(lod_detail(100.0, Some(2.0), 0.1), 0.1);
+        assert_eq!(lod_detail(100.0, None, 0.1), 1.0);
+
+        // Moved 20 m along z, the cloud is culled in its own space
+        let moved = Mat4::from_translation(Vec3::new(0.0, 0.0, 20.0));
+        let world_view = look(Vec3::new(0.0, 0.0, 40.0), Vec3::new(0.0, 0.0, 24.5), 15.5);
+        assert_eq!(order(cloud.plan(&world_view.local(&moved), &splat)), vec![2, 3]);
+
+        // Kept inside z -1..3, less an ellipsoid around the first cell
+        let full_view = view_from(Vec3::new(0.0, 0.0, 20.0), 100.0);
+        let keep = SplatCrop {
+            shape: SplatCropShape::Box,
+            center: Vec3::new(0.0, 0.0, 1.0),
+            half_extents: Vec3::new(1.0, 1.0, 2.0),
+            invert: false,
+        };
+        splat.crops = vec![keep];
+        assert_eq!(order(cloud.plan(&full_view, &splat)), vec![0, 1]);
+        let remove = SplatCrop {
+            shape: SplatCropShape::Ellipsoid,
+            center: Vec3::new(0.0, 0.0, 0.5),
+            half_extents: Vec3::ONE,
+            invert: true,
+        };
+        splat.crops.push(remove);
+        assert_eq!(order(cloud.plan(&full_view, &splat)), vec![1]);
+        assert!(!crate::components::splat_cropped(&splat.crops, Vec3::new(0.0, 0.0, 2.5)));
+        assert!(crate::components::splat_cropped(&splat.crops, Vec3::new(0.0, 0.0, 5.0)));
+        assert!(crate::components::splat_cropped(&splat.crops, Vec3::new(0.0, 0.0, 0.5)));
+        let params = SplatParams::new(&moved, &splat);
+        assert_eq!((params.crop_count, params.crops[1][0][3], params.crops[1][1][3]), (2, 1.0, 1.0));
+        assert!(std::mem::size_of::<SplatParams>() as u64 <= SPLAT_PARAMS_STRIDE);
+
+        let limits = wgpu::Limits { max_storage_buffer_binding_size: 32 * 1000, ..Default::default() };
+        assert_eq!(splats_per_chunk(&limits, false), 1000);
+        assert_eq!(splats_per_chunk(&limits, true), 666);
+    }
+
+    #[test]
+    fn test_gpu_splat_quantization() {
+        // Verify the struct is 32 bytes as expected
+        assert_eq!(std::mem::size_of::<GaussianSplatGpu>(), 32);
+        let splat = GaussianSplatGpu::new([1.0, -2.0, 3.5], [0.01, 0.5, 2.0], [0.6, 0.8, 0.0, 0.0], 0.75, [1.0, 0.5, 0.0]);
+        let (scale, rotation, opacity, color) = splat.unpack();
+        assert_eq!(splat.position, [1.0, -2.0, 3.5]);
+        assert!(scale.iter().zip([0.01, 0.5, 2.0]).all(|(a, b)| (a - b).abs() < b * 0.001));
+        assert!(rotation.iter().zip([0.6, 0.8, 0.0, 0.0]).all(|(a, b)| (a - b).abs() < 0.001));
+        assert!((opacity - 0.75).abs() < 0.5 / 255.0 + f32::EPSILON);
+        assert_eq!((color[0], color[2]), (1.0, 0.0));
+
+        // SH survives the f16 round trip coefficient by coefficient
+        assert_eq!(std::mem::size_of::<SplatSh>(), 48);
+        let sh = unpack_sh(&pack_sh(|i, c| i as f32 * 0.25 - c as f32 * 0.5));
+        assert_eq!(sh[0], [0.0, -0.5, -1.0]);
+        assert_eq!(sh[7], [1.75, 1.25, 0.75]);
+
+        let source = crate::shader::get_splat_render_wgsl();
+        if let Err(e) = crate::shader_diagnostic::check_wgsl(&source, std::path::Path::new("splat.wgsl"), &[]) {
+            panic!("{}", e);
+        }
     }
 }
@@ -0,0 +1,567 @@
+//! Compressed Gaussian splat formats: `.splat`, `.ksplat` and `.spz`, and the
+//! engine's own `.nsplat` written by `naive splat optimize`.
+//!
+//! Each decoder turns a whole file into GPU splats without the per-property
+//! lookups of the PLY parser. Positions come out in the PLY convention
+//! (right, down, forward) and rotations in PLY `rot_0..rot_3` order, so the
+//! formats can be swapped for one another in a scene. Spherical harmonics
+//! in bands 1 and 2 are kept where the format stores them; band 3 is
+//! dropped.
+
+use std::io::Read;
+
+use crate::splat::{pack_sh, sh_dc_to_color, GaussianSplatGpu, SplatData, SplatError, SplatSh, SH_COEFFICIENTS};
+
+/// Bytes of one `.splat` record.
+const SPLAT_RECORD_BYTES: usize = 32;
+
+/// Bytes of the `.ksplat` file header and of each section header.
+const KSPLAT_HEADER_BYTES: usize = 4096;
+const KSPLAT_SECTION_HEADER_BYTES: usize = 1024;
+
+/// `NGSP`, little-endian, at the start of an unzipped `.spz`.
+const SPZ_MAGIC: u32 = 0x5053_474e;
+const SPZ_HEADER_BYTES: usize = 16;
+/// Scale of the quantized SH DC term in `.spz` colors.
+const SPZ_COLOR_SCALE: f32 = 0.15;
+/// Band 1 and 2 SH coefficients that flip sign when `.spz` y and z do.
+const SPZ_SH_FLIPS: [usize; 4] = [0, 1, 3, 6];
+
+/// `NSPL`, little-endian, at the start of a `.nsplat`.
+const NSPLAT_MAGIC: u32 = 0x4c50_534e;
+const NSPLAT_VERSION: u32 = 1;
+const NSPLAT_HEADER_BYTES: usize = 16;
+/// `.nsplat` header flag: SH bands 1 and 2 follow the splats.
+const NSPLAT_HAS_SH: u32 = 1;
+
+/// Whether files with this extension are read here rather than as PLY.
+pub fn is_compressed(extension: &str) -> bool {
+    matches!(extension.to_ascii_lowercase().as_str(), "splat" | "ksplat" | "spz" | "nsplat")
+}
+
+/// Decode a compressed splat file by extension.
+pub fn decode(extension: &str, bytes: &[u8]) -> Result<SplatData, SplatError> {
+    match extension.to_ascii_lowercase().as_str() {
+        "splat" => decode_splat(bytes),
+        "ksplat" => decode_ksplat(bytes),
+        "spz" => decode_spz(bytes),
+        "nsplat" => decode_nsplat(bytes),
+        other => Err(corrupt(format!("unknown splat format .{}", other))),
+    }
+}
+
+fn corrupt(message: impl Into<String>) -> SplatError {
+    SplatError::Corrupt(message.into())
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_bits(read_u32(bytes, offset))
+}
+
+fn read_f16(bytes: &[u8], offset: usize) -> f32 {
+    half::f16::from_bits(read_u16(bytes, offset)).to_f32()
+}
+
+fn read_vec3(bytes: &[u8], offset: usize) -> [f32; 3] {
+    [read_f32(bytes, offset), read_f32(bytes, offset + 4), read_f32(bytes, offset + 8)]
+}
+
+/// Unit quaternion, or identity when `q` is degenerate.
+pub fn normalize_rotation(q: [f32; 4]) -> [f32; 4] {
+    let len = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if len > 0.0001 {
+        q.map(|c| c / len)
+    } else {
+        [1.0, 0.0, 0.0, 0.0]
+    }
+}
+
+/// `.splat`: 32-byte records of position and linear scale (f32), RGBA color
+/// (u8) and a rotation quantized to u8 around 128. There is no SH.
+pub fn decode_splat(bytes: &[u8]) -> Result<SplatData, SplatError> {
+    if bytes.is_empty() {
+        return Err(SplatError::NoVertices);
+    }
+    if !bytes.len().is_multiple_of(SPLAT_RECORD_BYTES) {
+        return Err(corrupt(format!(".splat size {} is not a multiple of {} bytes", bytes.len(), SPLAT_RECORD_BYTES)));
+    }
+
+    let splats = bytes
+        .chunks_exact(SPLAT_RECORD_BYTES)
+        .map(|record| {
+            let color = &record[24..28];
+            let rotation = [28, 29, 30, 31].map(|i| (record[i] as f32 - 128.0) / 128.0);
+            GaussianSplatGpu::new(
+                read_vec3(record, 0),
+                read_vec3(record, 12),
+                normalize_rotation(rotation),
+                color[3] as f32 / 255.0,
+                [color[0], color[1], color[2]].map(|c| c as f32 / 255.0),
+            )
+        })
+        .collect();
+    Ok(SplatData { splats, sh: Vec::new() })
+}
+
+/// `.ksplat` (GaussianSplats3D): sections of bucketed splats at one of three
+/// compression levels. Level 0 stores f32 data; levels 1 and 2 store
+/// positions as u16 offsets from a bucket center and scale and rotation as
+/// f16. SH coefficients follow the color as f32, f16 or (level 2) u8 over
+/// the header's coefficient range.
+pub fn decode_ksplat(bytes: &[u8]) -> Result<SplatData, SplatError> {
+    if bytes.len() < KSPLAT_HEADER_BYTES {
+        return Err(corrupt(".ksplat header is truncated"));
+    }
+    let (major, minor) = (bytes[0], bytes[1]);
+    if major != 0 || minor < 1 {
+        return Err(corrupt(format!(".ksplat version {}.{} is not supported", major, minor)));
+    }
+    let max_section_count = read_u32(bytes, 4) as usize;
+    let splat_count = read_u32(bytes, 16) as usize;
+    let compression_level = read_u16(bytes, 20);
+    let (center_bytes, scale_bytes, rotation_bytes, sh_bytes, default_range) = match compression_level {
+        0 => (12, 12, 16, 4, 1),
+        1 => (6, 6, 8, 2, 32767),
+        2 => (6, 6, 8, 1, 32767),
+        level => return Err(corrupt(format!(".ksplat compression level {} is not supported", level))),
+    };
+    let color_offset = center_bytes + scale_bytes + rotation_bytes;
+    let sh_range = match (read_f32(bytes, 36), read_f32(bytes, 40)) {
+        (min, max) if min == 0.0 && max == 0.0 => (-1.5, 1.5),
+        range => range,
+    };
+    let read_sh = |offset: usize| match compression_level {
+        0 => read_f32(bytes, offset),
+        1 => read_f16(bytes, offset),
+        _ => sh_range.0 + bytes[offset] as f32 / 255.0 * (sh_range.1 - sh_range.0),
+    };
+
+    let headers_end = KSPLAT_HEADER_BYTES + max_section_count * KSPLAT_SECTION_HEADER_BYTES;
+    if bytes.len() < headers_end {
+        return Err(corrupt(".ksplat section headers are truncated"));
+    }
+
+    let mut splats = Vec::with_capacity(splat_count.min(bytes.len() / (color_offset + 4)));
+    let mut sh: Vec<SplatSh> = Vec::with_capacity(splats.capacity());
+    let mut any_sh = false;
+    let mut section_base = headers_end;
+    for section in 0..max_section_count {
+        let header = KSPLAT_HEADER_BYTES + section * KSPLAT_SECTION_HEADER_BYTES;
+        let section_splats = read_u32(bytes, header) as usize;
+        let max_section_splats = read_u32(bytes, header + 4) as usize;
+        let bucket_size = read_u32(bytes, header + 8).max(1) as usize;
+        let bucket_count = read_u32(bytes, header + 12) as usize;
+        let bucket_block_size = read_f32(bytes, header + 16);
+        let bucket_storage_bytes = read_u16(bytes, header + 20) as usize;
+        let scale_range = match read_u32(bytes, header + 24) {
+            0 => default_range,
+            range => range,
+        };
+        let full_buckets = read_u32(bytes, header + 32) as usize;
+        let partial_buckets = read_u32(bytes, header + 36) as usize;
+        let sh_coefficients = match read_u16(bytes, header + 40) {
+            0 => 0,
+            1 => 9,
+            2 => 24,
+            _ => 45,
+        };
+
+        let bytes_per_splat = color_offset + 4 + sh_coefficients * sh_bytes;
+        let bucket_meta_bytes = partial_buckets * 4;
+        let bucket_bytes = bucket_storage_bytes * bucket_count + bucket_meta_bytes;
+        let data_base = section_base + bucket_bytes;
+        if bytes.len() < data_base + bytes_per_splat * section_splats {
+            return Err(corrupt(format!(".ksplat section {} is truncated", section)));
+        }
+
+        let bucket_center = |bucket: usize| read_vec3(bytes, section_base + bucket_meta_bytes + bucket * bucket_storage_bytes);
+        let scale_factor = bucket_block_size / 2.0 / scale_range as f32;
+
+        // Full buckets hold bucket_size splats; partially filled ones,
+        // after them, list their own lengths
+        let bucket_len = |bucket: usize| match bucket.checked_sub(full_buckets) {
+            Some(partial) if partial < partial_buckets => read_u32(bytes, section_base + partial * 4) as usize,
+            _ => bucket_size,
+        };
+        let mut bucket = 0;
+        let mut bucket_end = bucket_len(0);
+        for i in 0..section_splats {
+            while compression_level > 0 && i >= bucket_end && bucket + 1 < bucket_count {
+                bucket += 1;
+                bucket_end += bucket_len(bucket);
+            }
+
+            let base = data_base + i * bytes_per_splat;
+            let (position, scale, rotation) = if compression_level == 0 {
+                let rotation = [0, 4, 8, 12].map(|o| read_f32(bytes, base + 24 + o));
+                (read_vec3(bytes, base), read_vec3(bytes, base + 12), rotation)
+            } else {
+                let center = bucket_center(bucket);
+                let position = [0, 1, 2].map(|axis| {
+                    (read_u16(bytes, base + axis * 2) as f32 - scale_range as f32) * scale_factor + center[axis]
+                });
+                let scale = [0, 2, 4].map(|o| read_f16(bytes, base + 6 + o));
+                let rotation = [0, 2, 4, 6].map(|o| read_f16(bytes, base + 12 + o));
+                (position, scale, rotation)
+            };
+            let color = &bytes[base + color_offset..base + color_offset + 4];
+            splats.push(GaussianSplatGpu::new(
+                position,
+                scale,
+                normalize_rotation(rotation),
+                color[3] as f32 / 255.0,
+                [color[0], color[1], color[2]].map(|c| c as f32 / 255.0),
+            ));
+            // rgb triplets per coefficient, band 1 first
+            let sh_base = base + color_offset + 4;
+            sh.push(pack_sh(|i, c| {
+                if i * 3 >= sh_coefficients {
+                    return 0.0;
+                }
+                read_sh(sh_base + (i * 3 + c) * sh_bytes)
+            }));
+        }
+        any_sh |= sh_coefficients > 0;
+
+        section_base = data_base + bytes_per_splat * max_section_splats;
+    }
+
+    if splats.is_empty() {
+        return Err(SplatError::NoVertices);
+    }
+    if !any_sh {
+        sh.clear();
+    }
+    Ok(SplatData { splats, sh })
+}
+
+/// `.spz` (Niantic): a gzipped stream of 24-bit fixed-point positions, then
+/// per-splat u8 alphas, colors, log scales and rotations, each in its own
+/// block. Version 2 stores rotations as three u8 components, version 3 as
+/// the smallest three in 32 bits. Positions are stored right, up, back and
+/// flipped to the PLY convention, along with the SH coefficients (u8 around
+/// 128) that follow the rotations.
+pub fn decode_spz(compressed: &[u8]) -> Result<SplatData, SplatError> {
+    let mut bytes = Vec::new();
+    flate2::read::GzDecoder::new(compressed)
+        .read_to_end(&mut bytes)
+        .map_err(|e| SplatError::IoError(format!("spz decompression failed: {}", e)))?;
+    if bytes.len() < SPZ_HEADER_BYTES || read_u32(&bytes, 0) != SPZ_MAGIC {
+        return Err(corrupt("not an spz file"));
+    }
+    let version = read_u32(&bytes, 4);
+    if !(2..=3).contains(&version) {
+        return Err(corrupt(format!("spz version {} is not supported", version)));
+    }
+    let count = read_u32(&bytes, 8) as usize;
+    let sh_dim = match bytes[12] {
+        0 => 0,
+        1 => 3,
+        2 => 8,
+        3 => 15,
+        degree => return Err(corrupt(format!("spz SH degree {} is not supported", degree))),
+    };
+    let fraction = (1u32 << bytes[13]) as f32;
+    let rotation_bytes = if version >= 3 { 4 } else { 3 };
+    if count == 0 {
+        return Err(SplatError::NoVertices);
+    }
+
+    let positions = SPZ_HEADER_BYTES;
+    let alphas = positions + count * 9;
+    let colors = alphas + count;
+    let scales = colors + count * 3;
+    let rotations = scales + count * 3;
+    let coefficients = rotations + count * rotation_bytes;
+    let end = coefficients + count * sh_dim * 3;
+    if bytes.len() < end {
+        return Err(corrupt(format!("spz data is truncated ({} of {} bytes)", bytes.len(), end)));
+    }
+
+    let splats = (0..count)
+        .map(|i| {
+            let fixed = |offset: usize| {
+                let b = &bytes[offset..offset + 3];
+                // Sign-extend the 24-bit value
+                ((b[0] as i32 | (b[1] as i32) << 8 | (b[2] as i32) << 16) << 8 >> 8) as f32 / fraction
+            };
+            let p = positions + i * 9;
+            let position = [fixed(p), -fixed(p + 3), -fixed(p + 6)];
+            let color = [0, 1, 2].map(|c| {
+                sh_dc_to_color((bytes[colors + i * 3 + c] as f32 / 255.0 - 0.5) / SPZ_COLOR_SCALE)
+            });
+            let scale = [0, 1, 2].map(|c| (bytes[scales + i * 3 + c] as f32 / 16.0 - 10.0).exp());
+            let r = &bytes[rotations + i * rotation_bytes..rotations + (i + 1) * rotation_bytes];
+            let [x, y, z, w] = if version >= 3 { smallest_three(r) } else { xyz_rotation(r) };
+            GaussianSplatGpu::new(
+                position,
+                scale,
+                normalize_rotation([w, x, -y, -z]),
+                bytes[alphas + i] as f32 / 255.0,
+                color,
+            )
+        })
+        .collect();
+    let sh = if sh_dim == 0 {
+        Vec::new()
+    } else {
+        (0..count)
+            .map(|i| {
+                let base = coefficients + i * sh_dim * 3;
+                pack_sh(|coefficient, c| {
+                    if coefficient >= sh_dim.min(SH_COEFFICIENTS) {
+                        return 0.0;
+                    }
+                    let value = (bytes[base + coefficient * 3 + c] as f32 - 128.0) / 128.0;
+                    if SPZ_SH_FLIPS.contains(&coefficient) { -value } else { value }
+                })
+            })
+            .collect()
+    };
+    Ok(SplatData { splats, sh })
+}
+
+/// `.nsplat`: a 16-byte header (magic, version, splat count, flags), then the
+/// splats exactly as uploaded (`GaussianSplatGpu`) and, with the SH flag,
+/// one `SplatSh` per splat.
+pub fn encode_nsplat(data: &SplatData) -> Vec<u8> {
+    let flags = if data.sh.is_empty() { 0 } else { NSPLAT_HAS_SH };
+    let mut bytes = Vec::with_capacity(
+        NSPLAT_HEADER_BYTES
+            + std::mem::size_of_val(data.splats.as_slice())
+            + std::mem::size_of_val(data.sh.as_slice()),
+    );
+    for v in [NSPLAT_MAGIC, NSPLAT_VERSION, data.splats.len() as u32, flags] {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    bytes.extend_from_slice(bytemuck::cast_slice(&data.splats));
+    bytes.extend_from_slice(bytemuck::cast_slice(&data.sh));
+    bytes
+}
+
+/// Read a `.nsplat` back; the records need no conversion.
+pub fn decode_nsplat(bytes: &[u8]) -> Result<SplatData, SplatError> {
+    if bytes.len() < NSPLAT_HEADER_BYTES || read_u32(bytes, 0) != NSPLAT_MAGIC {
+        return Err(corrupt("not an .nsplat file"));
+    }
+    let version = read_u32(bytes, 4);
+    if version != NSPLAT_VERSION {
+        return Err(corrupt(format!(".nsplat version {} is not supported", version)));
+    }
+    let count = read_u32(bytes, 8) as usize;
+    if count == 0 {
+        return Err(SplatError::NoVertices);
+    }
+    let has_sh = read_u32(bytes, 12) & NSPLAT_HAS_SH != 0;
+    let splat_bytes = count * std::mem::size_of::<GaussianSplatGpu>();
+    let sh_bytes = if has_sh { count * std::mem::size_of::<SplatSh>() } else { 0 };
+    if bytes.len() != NSPLAT_HEADER_BYTES + splat_bytes + sh_bytes {
+        return Err(corrupt(format!(".nsplat size {} does not match its {} splats", bytes.len(), count)));
+    }
+
+    let (splat_data, sh_data) = bytes[NSPLAT_HEADER_BYTES..].split_at(splat_bytes);
+    let splats = splat_data
+        .chunks_exact(std::mem::size_of::<GaussianSplatGpu>())
+        .map(bytemuck::pod_read_unaligned)
+        .collect();
+    let sh = sh_data
+        .chunks_exact(std::mem::size_of::<SplatSh>())
+        .map(bytemuck::pod_read_unaligned)
+        .collect();
+    Ok(SplatData { splats, sh })
+}
+
+/// Version 2 rotation: x, y, z in [-1, 1] as u8, w non-negative.
+fn xyz_rotation(r: &[u8]) -> [f32; 4] {
+    let [x, y, z] = [0, 1, 2].map(|i| r[i] as f32 / 127.5 - 1.0);
+    [x, y, z, (1.0 - x * x - y * y - z * z).max(0.0).sqrt()]
+}
+
+/// Version 3 rotation: the index of the largest of x, y, z, w in the top two
+/// bits, then the other three as 9-bit magnitudes with a sign bit, the last
+/// component in the lowest bits.
+fn smallest_three(r: &[u8]) -> [f32; 4] {
+    const MASK: u32 = (1 << 9) - 1;
+    let mut packed = read_u32(r, 0);
+    let largest = (packed >> 30) as usize;
+    let mut q = [0.0f32; 4];
+    let mut sum_squares = 0.0;
+    for i in (0..4).rev().filter(|&i| i != largest) {
+        let magnitude = std::f32::consts::FRAC_1_SQRT_2 * (packed & MASK) as f32 / MASK as f32;
+        q[i] = if (packed >> 9) & 1 == 1 { -magnitude } else { magnitude };
+        sum_squares += q[i] * q[i];
+        packed >>= 10;
+    }
+    q[largest] = (1.0 - sum_squares).max(0.0).sqrt();
+    q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn splat_record(position: [f32; 3], scale: [f32; 3], color: [u8; 4], rotation: [u8; 4]) -> Vec<u8> {
+        let mut record = Vec::new();
+        for v in position.iter().chain(&scale) {
+            record.extend_from_slice(&v.to_le_bytes());
+        }
+        record.extend_from_slice(&color);
+        record.extend_from_slice(&rotation);
+        record
+    }
+
+    #[test]
+    fn test_decode_splat_records() {
+        let mut bytes = splat_record([1.0, 2.0, 3.0], [0.5, 0.25, 0.125], [255, 0, 128, 51], [255, 128, 128, 128]);
+        bytes.extend(splat_record([-1.0, 0.0, 0.0], [1.0; 3], [0; 4], [128; 4]));
+        let SplatData { splats, sh } = decode_splat(&bytes).unwrap();
+        assert!(sh.is_empty());
+        assert_eq!(splats.len(), 2);
+        assert_eq!(splats[0].position, [1.0, 2.0, 3.0]);
+        let (scale, rotation, opacity, color) = splats[0].unpack();
+        assert_eq!(scale, [0.5, 0.25, 0.125]);
+        assert_eq!(rotation, [1.0, 0.0, 0.0, 0.0]);
+        assert!((opacity - 0.2).abs() < 0.01);
+        assert!((color[0] - 1.0).abs() < 0.01 && color[1] == 0.0);
+        // A zero rotation falls back to identity
+        assert_eq!(splats[1].unpack().1, [1.0, 0.0, 0.0, 0.0]);
+
+        assert!(matches!(decode_splat(&bytes[..40]), Err(SplatError::Corrupt(_))));
+        assert!(matches!(decode_splat(&[]), Err(SplatError::NoVertices)));
+    }
+
+    #[test]
+    fn test_decode_spz_v2_and_v3() {
+        fn spz(version: u32, rotation: &[u8], sh: &[u8]) -> Vec<u8> {
+            let mut raw = Vec::new();
+            raw.extend_from_slice(&SPZ_MAGIC.to_le_bytes());
+            raw.extend_from_slice(&version.to_le_bytes());
+            raw.extend_from_slice(&1u32.to_le_bytes());
+            raw.extend_from_slice(&[(sh.len() / 9) as u8, 12, 0, 0]);
+            // x = 1.5, y = -2.0, z = 0.25 with 12 fractional bits
+            for v in [1.5f32, -2.0, 0.25] {
+                let fixed = (v * 4096.0) as i32;
+                raw.extend_from_slice(&fixed.to_le_bytes()[..3]);
+            }
+            raw.push(255);
+            raw.extend_from_slice(&[128, 128, 128]);
+            raw.extend_from_slice(&[160, 160, 160]);
+            raw.extend_from_slice(rotation);
+            raw.extend_from_slice(sh);
+
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&raw).unwrap();
+            encoder.finish().unwrap()
+        }
+
+        // Identity: x, y, z at zero (127.5 rounds to 128 -> ~0.004)
+        let v2 = decode_spz(&spz(2, &[128, 128, 128], &[])).unwrap().splats;
+        assert_eq!(v2.len(), 1);
+        assert_eq!(v2[0].position, [1.5, 2.0, -0.25]);
+        let (scale, rotation, opacity, color) = v2[0].unpack();
+        assert!((scale[0] - 1.0).abs() < 0.01);
+        assert!((rotation[0] - 1.0).abs() < 0.01);
+        assert_eq!(opacity, 1.0);
+        assert!((color[0] - 0.5).abs() < 0.01);
+
+        // Version 3: w (index 3) largest, x, y, z zero
+        let v3 = decode_spz(&spz(3, &(3u32 << 30).to_le_bytes(), &[])).unwrap();
+        assert_eq!(v3.splats[0].unpack().1, [1.0, 0.0, 0.0, 0.0]);
+        assert!(v3.sh.is_empty());
+
+        // Degree 1: the y and z coefficients flip with the axes
+        let sh = decode_spz(&spz(3, &(3u32 << 30).to_le_bytes(), &[192; 9])).unwrap().sh;
+        let coefficients = crate::splat::unpack_sh(&sh[0]);
+        assert_eq!(coefficients[0], [-0.5; 3]);
+        assert_eq!(coefficients[1], [-0.5; 3]);
+        assert_eq!(coefficients[2], [0.5; 3]);
+        assert_eq!(coefficients[3], [0.0; 3]);
+
+        assert!(matches!(decode_spz(&spz(1, &[128; 3], &[])), Err(SplatError::Corrupt(_))));
+    }
+
+    #[test]
+    fn test_nsplat_round_trip() {
+        let splats = vec![
+            GaussianSplatGpu::new([1.0, 2.0, 3.0], [0.5; 3], [1.0, 0.0, 0.0, 0.0], 0.5, [1.0, 0.0, 0.25]),
+            GaussianSplatGpu::new([-4.0, 0.0, 8.0], [0.1; 3], [0.0, 1.0, 0.0, 0.0], 1.0, [0.0; 3]),
+        ];
+        let sh = vec![pack_sh(|i, c| i as f32 - c as f32), pack_sh(|_, _| 0.5)];
+        let with_sh = SplatData { splats: splats.clone(), sh: sh.clone() };
+        let bytes = encode_nsplat(&with_sh);
+        assert_eq!(bytes.len(), NSPLAT_HEADER_BYTES + 2 * 32 + 2 * 48);
+        let decoded = decode("nsplat", &bytes).unwrap();
+        assert_eq!(bytemuck::cast_slice::<_, u8>(&decoded.splats), bytemuck::cast_slice::<_, u8>(&splats));
+        assert_eq!(decoded.sh, sh);
+
+        let flat = encode_nsplat(&SplatData { splats, sh: Vec::new() });
+        assert!(decode_nsplat(&flat).unwrap().sh.is_empty());
+        assert!(matches!(decode_nsplat(&flat[..flat.len() - 1]), Err(SplatError::Corrupt(_))));
+        assert!(matches!(decode_nsplat(&bytes[4..]), Err(SplatError::Corrupt(_))));
+    }
+
+    #[test]
+    fn test_smallest_three_restores_largest_component() {
+        // y largest; x at half the 9-bit range and negative (w, z, x are
+        // packed from the lowest bits up), z and w zero
+        let packed = 1u32 << 30 | (255 | 1 << 9) << 20;
+        let q = smallest_three(&packed.to_le_bytes());
+        let x = -0.5 * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((q[0] - x).abs() < 0.01);
+        assert!((q[1] - (1.0 - x * x).sqrt()).abs() < 0.01);
+        assert_eq!((q[2], q[3]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_decode_ksplat_compressed_buckets() {
+        // One section, level 1, one full bucket of 2 splats centered at (10, 0, 0)
+        let mut bytes = vec![0u8; KSPLAT_HEADER_BYTES + KSPLAT_SECTION_HEADER_BYTES];
+        bytes[1] = 1;
+        bytes[4..8].copy_from_slice(&1u32.to_le_bytes());
+        bytes[8..12].copy_from_slice(&1u32.to_le_bytes());
+        bytes[16..20].copy_from_slice(&2u32.to_le_bytes());
+        bytes[20..22].copy_from_slice(&1u16.to_le_bytes());
+        let header = KSPLAT_HEADER_BYTES;
+        let put_u32 = |bytes: &mut Vec<u8>, offset: usize, v: u32| bytes[offset..offset + 4].copy_from_slice(&v.to_le_bytes());
+        put_u32(&mut bytes, header, 2);
+        put_u32(&mut bytes, header + 4, 2);
+        put_u32(&mut bytes, header + 8, 256);
+        put_u32(&mut bytes, header + 12, 1);
+        bytes[header + 16..header + 20].copy_from_slice(&2.0f32.to_le_bytes());
+        bytes[header + 20..header + 22].copy_from_slice(&12u16.to_le_bytes());
+        put_u32(&mut bytes, header + 32, 1);
+
+        for v in [10.0f32, 0.0, 0.0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        for offset in [32767u16, 65534] {
+            for _ in 0..3 {
+                bytes.extend_from_slice(&offset.to_le_bytes());
+            }
+            for v in [0.5f32, 0.5, 0.5, 1.0, 0.0, 0.0, 0.0] {
+                bytes.extend_from_slice(&half::f16::from_f32(v).to_bits().to_le_bytes());
+            }
+            bytes.extend_from_slice(&[255, 255, 255, 255]);
+        }
+
+        let SplatData { splats, sh } = decode_ksplat(&bytes).unwrap();
+        assert_eq!(splats.len(), 2);
+        assert!(sh.is_empty());
+        assert_eq!(splats[0].position, [10.0, 0.0, 0.0]);
+        // One block half-size (1.0) past the center
+        assert!((splats[1].position[0] - 11.0).abs() < 0.001);
+        assert_eq!(splats[1].unpack().0, [0.5; 3]);
+
+        bytes[1] = 0;
+        assert!(matches!(decode_ksplat(&bytes), Err(SplatError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,197 @@
+//! `naive splat optimize` — prepare splat captures for the engine offline.
+//!
+//! Drops gaussians too transparent to see, optionally keeps only the most
+//! prominent share of the rest, and reorders them along a Morton curve so
+//! splats that are close in space are close in the file. The result is
+//! written as `.nsplat` (see `splat_formats`), which `SplatCache` loads
+//! without decoding, and whose cells then gather from nearby memory.
+
+use std::path::{Path, PathBuf};
+
+use crate::splat::{prominence, read_splat_file, SplatData, SplatError};
+
+/// Extension of the optimized files.
+pub const OPTIMIZED_EXTENSION: &str = "nsplat";
+
+/// Order of the splats in an optimized file.
+#[derive(clap::ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SplatOrder {
+    /// Keep the source file's order.
+    None,
+    /// Sort along a Z-order curve through the cloud's bounds.
+    #[default]
+    Morton,
+}
+
+/// What `optimize` removes and how it orders what is left.
+#[derive(Debug, Clone, Copy)]
+pub struct OptimizeOptions {
+    /// Drop splats less opaque than this.
+    pub prune_alpha: f32,
+    /// Share of the remaining splats to keep, most prominent first (1 keeps all).
+    pub decimate: f32,
+    pub order: SplatOrder,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            prune_alpha: 0.0,
+            decimate: 1.0,
+            order: SplatOrder::Morton,
+        }
+    }
+}
+
+impl OptimizeOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.prune_alpha) {
+            return Err(format!("--prune-alpha must be in 0..1, got {}", self.prune_alpha));
+        }
+        if !(self.decimate > 0.0 && self.decimate <= 1.0) {
+            return Err(format!("--decimate must be above 0 and at most 1, got {}", self.decimate));
+        }
+        Ok(())
+    }
+}
+
+/// Splat counts through one optimization.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizeReport {
+    pub input: usize,
+    /// Dropped by `prune_alpha`.
+    pub pruned: usize,
+    /// Dropped by `decimate`.
+    pub decimated: usize,
+    pub output: usize,
+}
+
+impl std::fmt::Display for OptimizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {} gaussians ({} pruned, {} decimated)",
+            self.input, self.output, self.pruned, self.decimated
+        )
+    }
+}
+
+/// Where `optimize_file` writes when no output is given: beside the input,
+/// as `.nsplat`.
+pub fn default_output(input: &Path) -> PathBuf {
+    input.with_extension(OPTIMIZED_EXTENSION)
+}
+
+/// Prune, decimate and reorder a cloud. Spherical harmonics follow their
+/// splats.
+pub fn optimize(data: SplatData, options: &OptimizeOptions) -> (SplatData, OptimizeReport) {
+    let input = data.splats.len();
+    let mut keep: Vec<u32> = (0..input as u32)
+        .filter(|&i| data.splats[i as usize].unpack().2 >= options.prune_alpha)
+        .collect();
+    let pruned = input - keep.len();
+
+    let target = ((keep.len() as f32 * options.decimate).ceil() as usize).min(keep.len());
+    if target < keep.len() {
+        keep.sort_by_cached_key(|&i| std::cmp::Reverse(prominence(&data.splats[i as usize]).to_bits()));
+        keep.truncate(target);
+        keep.sort_unstable();
+    }
+    let decimated = input - pruned - keep.len();
+
+    if options.order == SplatOrder::Morton {
+        let codes = morton_codes(&data, &keep);
+        let mut order: Vec<usize> = (0..keep.len()).collect();
+        order.sort_by_key(|&i| codes[i]);
+        keep = order.into_iter().map(|i| keep[i]).collect();
+    }
+
+    let splats = keep.iter().map(|&i| data.splats[i as usize]).collect();
+    let sh = if data.sh.is_empty() { Vec::new() } else { keep.iter().map(|&i| data.sh[i as usize]).collect() };
+    let report = OptimizeReport { input, pruned, decimated, output: keep.len() };
+    (SplatData { splats, sh }, report)
+}
+
+/// Read a splat file in any supported format, optimize it and write it to
+/// `output` as `.nsplat`.
+pub fn optimize_file(input: &Path, output: &Path, options: &OptimizeOptions) -> Result<OptimizeReport, SplatError> {
+    let data = read_splat_file(input)?;
+    let (optimized, report) = optimize(data, options);
+    if optimized.splats.is_empty() {
+        return Err(SplatError::NoVertices);
+    }
+    let bytes = crate::splat_formats::encode_nsplat(&optimized);
+    std::fs::write(output, bytes).map_err(|e| SplatError::IoError(e.to_string()))?;
+    Ok(report)
+}
+
+/// Z-order code of each kept splat's position, 10 bits per axis over the
+/// kept splats' bounds.
+fn morton_codes(data: &SplatData, keep: &[u32]) -> Vec<u32> {
+    let positions = keep.iter().map(|&i| data.splats[i as usize].position);
+    let bounds = crate::culling::Aabb::from_points(positions.clone());
+    let extent = (bounds.max - bounds.min).max(glam::Vec3::splat(1e-6));
+    positions
+        .map(|p| {
+            let cell = ((glam::Vec3::from(p) - bounds.min) / extent * 1023.0)
+                .clamp(glam::Vec3::ZERO, glam::Vec3::splat(1023.0));
+            spread_bits(cell.x as u32) | spread_bits(cell.y as u32) << 1 | spread_bits(cell.z as u32) << 2
+        })
+        .collect()
+}
+
+/// The low 10 bits of `v`, two zero bits after each.
+fn spread_bits(v: u32) -> u32 {
+    let mut v = v & 0x3ff;
+    v = (v | v << 16) & 0x0300_00ff;
+    v = (v | v << 8) & 0x0300_f00f;
+    v = (v | v << 4) & 0x030c_30c3;
+    (v | v << 2) & 0x0924_9249
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::splat::{pack_sh, GaussianSplatGpu};
+
+    fn splat(position: [f32; 3], scale: f32, opacity: f32) -> GaussianSplatGpu {
+        GaussianSplatGpu::new(position, [scale; 3], [1.0, 0.0, 0.0, 0.0], opacity, [1.0; 3])
+    }
+
+    #[test]
+    fn test_prune_decimate_and_reorder() {
+        let splats = vec![
+            splat([1.0, 1.0, 1.0], 0.1, 1.0),
+            splat([0.0, 0.0, 0.0], 0.1, 0.01),
+            splat([0.0, 0.0, 0.0], 0.2, 1.0),
+            splat([1.0, 0.0, 0.0], 0.05, 1.0),
+            splat([0.0, 1.0, 0.0], 0.3, 1.0),
+        ];
+        let sh = (0..5).map(|n| pack_sh(move |_, _| n as f32)).collect();
+        let options = OptimizeOptions { prune_alpha: 0.02, decimate: 0.75, order: SplatOrder::Morton };
+        assert!(options.validate().is_ok());
+        let (out, report) = optimize(SplatData { splats, sh }, &options);
+        assert_eq!(report, OptimizeReport { input: 5, pruned: 1, decimated: 1, output: 3 });
+        // The faint splat and the smallest are gone; the rest in Z order
+        let positions: Vec<[f32; 3]> = out.splats.iter().map(|s| s.position).collect();
+        assert_eq!(positions, vec![[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 1.0]]);
+        let first_sh = |i: usize| crate::splat::unpack_sh(&out.sh[i])[0][0];
+        assert_eq!((first_sh(0), first_sh(1), first_sh(2)), (2.0, 4.0, 0.0));
+
+        let keep_order = OptimizeOptions { order: SplatOrder::None, ..Default::default() };
+        let (same, report) = optimize(SplatData { splats: out.splats.clone(), sh: Vec::new() }, &keep_order);
+        assert_eq!((report.output, same.sh.len()), (3, 0));
+        assert_eq!(same.splats[2].position, [1.0, 1.0, 1.0]);
+
+        assert!(OptimizeOptions { decimate: 0.0, ..Default::default() }.validate().is_err());
+        assert!(OptimizeOptions { prune_alpha: 1.0, ..Default::default() }.validate().is_err());
+        assert_eq!(default_output(Path::new("splats/garden.ply")), PathBuf::from("splats/garden.nsplat"));
+    }
+
+    #[test]
+    fn test_spread_bits() {
+        assert_eq!(spread_bits(0b111), 0b001_001_001);
+        assert_eq!(spread_bits(0x3ff).count_ones(), 10);
+        assert_eq!(spread_bits(0x400), 0);
+    }
+}
@@ -0,0 +1,374 @@
+//! GPU depth sorting for Gaussian splats.
+//!
+//! Each frame a compute step writes one depth key per splat the frame draws,
+//! then a bitonic sort orders the keys and their splat indices back-to-front
+//! in place, so the sorted index buffer the splat pass reads never leaves the
+//! GPU. A chunk's indices are split into one segment per cell, each padded
+//! to a power of two and sorted on its own; the padding, and the splats of
+//! culled cells or left out by LOD, sort last and are never drawn. Devices
+//! without compute storage buffers keep the CPU sort in `splat`.
+
+use glam::Mat4;
+use wgpu::util::DeviceExt;
+
+use crate::splat::{CellDraw, GpuSplat, SplatChunk};
+
+/// Invocations per workgroup of the sort shaders.
+pub const SPLAT_SORT_WORKGROUP_SIZE: u32 = 256;
+
+/// Largest sort, as a power of two (2^31 keys).
+const MAX_SORT_LOG2: u32 = 31;
+
+/// Byte stride of the per-chunk params and the sort steps, a multiple of
+/// every device's uniform offset alignment.
+const UNIFORM_STRIDE: u64 = 256;
+
+/// Whether the device can sort splats on the GPU: the sort shader binds
+/// four storage buffers in the compute stage, which the GL backend lacks.
+pub fn gpu_sort_supported(limits: &wgpu::Limits) -> bool {
+    limits.max_compute_workgroup_size_x >= SPLAT_SORT_WORKGROUP_SIZE
+        && limits.max_compute_invocations_per_workgroup >= SPLAT_SORT_WORKGROUP_SIZE
+        && limits.max_storage_buffers_per_shader_stage >= 4
+}
+
+/// Keys (and indices) a segment of `splat_count` splats sorts.
+pub fn sort_capacity(splat_count: u32) -> u32 {
+    splat_count.max(1).next_power_of_two()
+}
+
+/// Camera-space depth as a key that orders like the float: farthest (most
+/// negative z) first. Mirrors `depth_key` in the shader.
+pub fn depth_key(z: f32) -> u32 {
+    let bits = z.to_bits();
+    bits ^ if bits >> 31 == 1 { 0xffff_ffff } else { 0x8000_0000 }
+}
+
+/// The (k, j) compare distances of a bitonic sort of 2^`log2` keys, in
+/// order. The steps of a shorter sort are a prefix of a longer one's, so one
+/// table serves every chunk.
+fn bitonic_steps(log2: u32) -> Vec<(u32, u32)> {
+    let mut steps = Vec::new();
+    for stage in 1..=log2 {
+        let k = 1u32 << stage;
+        let mut j = k / 2;
+        while j > 0 {
+            steps.push((k, j));
+            j /= 2;
+        }
+    }
+    steps
+}
+
+/// Number of steps that sort `capacity` (a power of two) keys.
+fn step_count(capacity: u32) -> usize {
+    let log2 = capacity.trailing_zeros() as usize;
+    log2 * (log2 + 1) / 2
+}
+
+/// View row and sizes for one chunk.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct SortParams {
+    /// Third row of the view matrix: camera-space z of a point.
+    view_z: [f32; 4],
+    segment_capacity: u32,
+    /// Keys over all segments.
+    capacity: u32,
+    _pad: [u32; 2],
+}
+
+/// One compare distance of the bitonic sort.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct SortStep {
+    k: u32,
+    j: u32,
+    _pad: [u32; 2],
+}
+
+/// The compute pipelines and step table of the splat sort.
+pub struct GpuSplatSort {
+    keys_pipeline: wgpu::ComputePipeline,
+    sort_pipeline: wgpu::ComputePipeline,
+    chunk_layout: wgpu::BindGroupLayout,
+    step_bind_group: wgpu::BindGroup,
+    max_workgroups: u32,
+}
+
+impl GpuSplatSort {
+    /// None when the device can't run the sort (see `gpu_sort_supported`).
+    pub fn new(device: &wgpu::Device) -> Option<Self> {
+        let limits = device.limits();
+        if !gpu_sort_supported(&limits) {
+            return None;
+        }
+
+        let storage_entry = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let uniform_entry = |has_dynamic_offset: bool| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let chunk_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Splat Sort Chunk Bind Group Layout"),
+            entries: &[
+                uniform_entry(false),
+                storage_entry(1, true),
+                storage_entry(2, false),
+                storage_entry(3, false),
+                storage_entry(4, true),
+            ],
+        });
+        let step_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Splat Sort Step Bind Group Layout"),
+            entries: &[uniform_entry(true)],
+        });
+
+        // Every step at its own aligned offset, selected per dispatch
+        let steps = bitonic_steps(MAX_SORT_LOG2);
+        let mut step_bytes = vec![0u8; steps.len() * UNIFORM_STRIDE as usize];
+        for (i, &(k, j)) in steps.iter().enumerate() {
+            let offset = i * UNIFORM_STRIDE as usize;
+            let step = SortStep { k, j, _pad: [0; 2] };
+            step_bytes[offset..offset + std::mem::size_of::<SortStep>()].copy_from_slice(bytemuck::bytes_of(&step));
+        }
+        let step_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Splat Sort Step Buffer"),
+            contents: &step_bytes,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let step_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Splat Sort Step Bind Group"),
+            layout: &step_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &step_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<SortStep>() as u64),
+                }),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Splat Sort Shader"),
+            source: wgpu::ShaderSource::Wgsl(crate::shader::get_splat_sort_wgsl().into()),
+        });
+        let pipeline = |label: &str, layouts: &[&wgpu::BindGroupLayout], entry_point: &str| {
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: layouts,
+                push_constant_ranges: &[],
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let keys_pipeline = pipeline("Splat Sort Keys Pipeline", &[&chunk_layout], "cs_keys");
+        let sort_pipeline = pipeline("Splat Sort Pipeline", &[&chunk_layout, &step_layout], "cs_sort");
+
+        Some(Self {
+            keys_pipeline,
+            sort_pipeline,
+            chunk_layout,
+            step_bind_group,
+            max_workgroups: limits.max_compute_workgroups_per_dimension.max(1),
+        })
+    }
+
+    /// Record the sort of the splats of `draws` back-to-front for
+    /// `view_matrix` into `encoder`. Chunks loaded without sort keys, or with
+    /// no cells to draw, are skipped. Each call gets its own params buffer, so
+    /// clouds recorded into one encoder don't overwrite each other's view
+    /// before it runs.
+    pub fn sort(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        splat: &GpuSplat,
+        draws: &[CellDraw],
+        view_matrix: &Mat4,
+    ) {
+        // First splat and splats to sort of every segment; undrawn cells sort none
+        let mut segments: Vec<Vec<[u32; 2]>> = splat.chunks.iter().map(|c| vec![[0, 0]; c.segments as usize]).collect();
+        for draw in draws {
+            let cell = &splat.cells[draw.cell];
+            segments[cell.chunk][cell.segment as usize] = [cell.first, draw.count];
+        }
+        let chunks: Vec<(&SplatChunk, &[[u32; 2]])> = splat
+            .chunks
+            .iter()
+            .zip(&segments)
+            .filter(|(c, s)| c.sort_key_buffer.is_some() && s.iter().any(|s| s[1] > 0))
+            .map(|(c, s)| (c, s.as_slice()))
+            .collect();
+        if chunks.is_empty() {
+            return;
+        }
+
+        // One `UNIFORM_STRIDE` slot per chunk
+        let view_z = view_matrix.row(2).to_array();
+        let mut param_bytes = vec![0u8; chunks.len() * UNIFORM_STRIDE as usize];
+        for (slot, (chunk, _)) in chunks.iter().enumerate() {
+            let offset = slot * UNIFORM_STRIDE as usize;
+            let chunk_params = SortParams {
+                view_z,
+                segment_capacity: chunk.segment_capacity,
+                capacity: chunk.segments * chunk.segment_capacity,
+                _pad: [0; 2],
+            };
+            param_bytes[offset..offset + std::mem::size_of::<SortParams>()]
+                .copy_from_slice(bytemuck::bytes_of(&chunk_params));
+        }
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Splat Sort Params Buffer"),
+            contents: &param_bytes,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("splat_sort"),
+            timestamp_writes: None,
+        });
+        for (slot, (chunk, chunk_segments)) in chunks.iter().enumerate() {
+            let (Some(keys), Some(segment_buffer)) = (&chunk.sort_key_buffer, &chunk.segment_buffer) else {
+                continue;
+            };
+            queue.write_buffer(segment_buffer.buffer(), segment_buffer.offset(), bytemuck::cast_slice(chunk_segments));
+            let capacity = chunk.segments * chunk.segment_capacity;
+            let offset = slot as u64 * UNIFORM_STRIDE;
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Splat Sort Chunk Bind Group"),
+                layout: &self.chunk_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: &params,
+                            offset,
+                            size: wgpu::BufferSize::new(std::mem::size_of::<SortParams>() as u64),
+                        }),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: chunk.splat_buffer.binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: keys.binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: chunk.sorted_index_buffer.binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: segment_buffer.binding(),
+                    },
+                ],
+            });
+
+            let (groups_x, groups_y) = dispatch_size(capacity, self.max_workgroups);
+            pass.set_pipeline(&self.keys_pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+
+            pass.set_pipeline(&self.sort_pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            for step in 0..step_count(chunk.segment_capacity) {
+                let step_offset = (step as u64 * UNIFORM_STRIDE) as u32;
+                pass.set_bind_group(1, &self.step_bind_group, &[step_offset]);
+                pass.dispatch_workgroups(groups_x, groups_y, 1);
+            }
+        }
+    }
+}
+
+/// Workgroups covering `invocations`, spread over two dimensions when one
+/// would exceed the device limit.
+fn dispatch_size(invocations: u32, max_per_dimension: u32) -> (u32, u32) {
+    let groups = invocations.div_ceil(SPLAT_SORT_WORKGROUP_SIZE).max(1);
+    let x = groups.min(max_per_dimension);
+    (x, groups.div_ceil(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_bitonic_steps_sort_segments_back_to_front() {
+        let depths = [-3.0f32, 2.5, -0.0, -10.0, 7.0, 0.25, -3.0, 1.0, -1.0];
+        // Two cells: splats 0..5 (LOD keeps 4) and 5..9 (all 4)
+        let segments = [[0u32, 4], [5, 4]];
+        let segment_capacity = sort_capacity(5);
+        assert_eq!(segment_capacity, 8);
+        let capacity = segment_capacity * segments.len() as u32;
+
+        // The shader's keys pass, then its compare-and-swap for every step
+        let mut keys = vec![u32::MAX; capacity as usize];
+        let mut indices = vec![0u32; capacity as usize];
+        for i in 0..capacity {
+            let [first, count] = segments[(i / segment_capacity) as usize];
+            let local = i % segment_capacity;
+            if local < count {
+                keys[i as usize] = depth_key(depths[(first + local) as usize]);
+                indices[i as usize] = first + local;
+            }
+        }
+        let steps = bitonic_steps(MAX_SORT_LOG2);
+        for &(k, j) in &steps[..step_count(segment_capacity)] {
+            for i in 0..capacity {
+                let l = i ^ j;
+                if l <= i {
+                    continue;
+                }
+                let (a, b) = (keys[i as usize], keys[l as usize]);
+                let ascending = i & k == 0 || k >= segment_capacity;
+                let swap = if ascending { a > b } else { a < b };
+                if swap {
+                    keys.swap(i as usize, l as usize);
+                    indices.swap(i as usize, l as usize);
+                }
+            }
+        }
+
+        let sorted = |segment: usize| -> Vec<f32> {
+            let start = segment * segment_capacity as usize;
+            indices[start..start + 4].iter().map(|&i| depths[i as usize]).collect()
+        };
+        assert_eq!(sorted(0), vec![-10.0, -3.0, -0.0, 2.5]);
+        assert_eq!(sorted(1), vec![-3.0, -1.0, 0.25, 1.0]);
+        assert_eq!(step_count(1), 0);
+        assert_eq!(steps.len(), step_count(1 << MAX_SORT_LOG2));
+        assert_eq!(dispatch_size(1 << 26, 65535), (65535, 5));
+
+        let source = crate::shader::get_splat_sort_wgsl();
+        if let Err(e) = crate::shader_diagnostic::check_wgsl(&source, Path::new("splat_sort.wgsl"), &[]) {
+            panic!("{}", e);
+        }
+        assert_eq!(std::mem::size_of::<SortParams>(), 32);
+    }
+}
@@ -0,0 +1,184 @@
+//! Split-screen rendering for local co-op.
+//!
+//! Cameras with the role `player1`, `player2`, ... each get their own
+//! [`CameraState`]. With two or more of them, the engine runs the pipeline
+//! once per player and its passes that write the swapchain draw into that
+//! player's [`Viewport`] only. With fewer, the main camera renders as usual.
+
+use serde::Deserialize;
+
+use crate::camera::CameraState;
+
+/// Most players the screen is split between.
+pub const MAX_PLAYERS: usize = 4;
+
+/// A region of the render target, in pixels from its top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Width over height.
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+}
+
+/// How two players share the screen; three or four always use quarters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitLayout {
+    /// Side by side.
+    #[default]
+    Vertical,
+    /// One above the other.
+    Horizontal,
+}
+
+/// Split-screen settings (`split_screen:` in naive.yaml).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct SplitScreenConfig {
+    #[serde(default)]
+    pub layout: SplitLayout,
+}
+
+/// One local player's view: the camera entity it follows and its own
+/// camera uniforms (jitter and reprojection history included).
+pub struct PlayerView {
+    /// Player number from the camera's role (`player2` is 2).
+    pub player: u32,
+    pub entity: hecs::Entity,
+    pub camera_state: CameraState,
+}
+
+/// The viewports of `count` players on a `width` x `height` target, in
+/// player order. Three players leave the bottom-right quarter empty.
+pub fn layout_viewports(count: usize, width: u32, height: u32, layout: SplitLayout) -> Vec<Viewport> {
+    let (half_w, half_h) = (width / 2, height / 2);
+    match count {
+        0 => Vec::new(),
+        1 => vec![Viewport { x: 0, y: 0, width, height }],
+        2 => match layout {
+            SplitLayout::Vertical => vec![
+                Viewport { x: 0, y: 0, width: half_w, height },
+                Viewport { x: half_w, y: 0, width: width - half_w, height },
+            ],
+            SplitLayout::Horizontal => vec![
+                Viewport { x: 0, y: 0, width, height: half_h },
+                Viewport { x: 0, y: half_h, width, height: height - half_h },
+            ],
+        },
+        _ => (0..count.min(MAX_PLAYERS))
+            .map(|i| {
+                let (col, row) = ((i % 2) as u32, (i / 2) as u32);
+                Viewport {
+                    x: col * half_w,
+                    y: row * half_h,
+                    width: if col == 0 { half_w } else { width - half_w },
+                    height: if row == 0 { half_h } else { height - half_h },
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Clear the whole target before the views draw into their regions (each
+/// view loads what is already there).
+pub fn clear_target(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    let _clear = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("split_screen_clear"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+}
+
+/// Restrict a pass drawing into the swapchain to a view's region.
+pub fn apply_viewport(render_pass: &mut wgpu::RenderPass<'_>, viewport: Viewport) {
+    render_pass.set_viewport(
+        viewport.x as f32,
+        viewport.y as f32,
+        viewport.width as f32,
+        viewport.height as f32,
+        0.0,
+        1.0,
+    );
+    render_pass.set_scissor_rect(viewport.x, viewport.y, viewport.width, viewport.height);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{CameraRole, ParticleConfig};
+    use crate::particles::ParticleSystem;
+    use glam::{Mat4, Vec3};
+
+    #[test]
+    fn test_layout_covers_target() {
+        let two = layout_viewports(2, 1281, 720, SplitLayout::Vertical);
+        assert_eq!(two[0], Viewport { x: 0, y: 0, width: 640, height: 720 });
+        assert_eq!(two[1], Viewport { x: 640, y: 0, width: 641, height: 720 });
+
+        let stacked = layout_viewports(2, 1280, 720, SplitLayout::Horizontal);
+        assert_eq!(stacked[1], Viewport { x: 0, y: 360, width: 1280, height: 360 });
+        assert!((stacked[0].aspect() - 1280.0 / 360.0).abs() < 1e-4);
+
+        let four = layout_viewports(4, 1280, 720, SplitLayout::Vertical);
+        let area: u32 = four.iter().map(|v| v.width * v.height).sum();
+        assert_eq!(area, 1280 * 720);
+        assert_eq!(four[3], Viewport { x: 640, y: 360, width: 640, height: 360 });
+        assert_eq!(layout_viewports(3, 1280, 720, SplitLayout::Vertical).len(), 3);
+        assert_eq!(layout_viewports(6, 1280, 720, SplitLayout::Vertical).len(), MAX_PLAYERS);
+    }
+
+    #[test]
+    fn test_player_roles() {
+        assert_eq!(CameraRole::from_name("player2"), CameraRole::Player(2));
+        assert_eq!(CameraRole::from_name("main"), CameraRole::Main);
+        assert_eq!(CameraRole::from_name("player0"), CameraRole::Other("player0".into()));
+        assert_eq!(CameraRole::from_name("minimap"), CameraRole::Other("minimap".into()));
+        assert_eq!(CameraRole::Player(3).name(), "player3");
+    }
+
+    #[test]
+    fn test_opposite_views_sort_differently() {
+        // Two players on either side of the same particles and splats
+        let eyes = [Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, -10.0)];
+        let points = [[0.0, 0.0, -1.0], [0.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+
+        let config = ParticleConfig { lifetime: [10.0, 10.0], initial_speed: [0.0, 0.0], ..Default::default() };
+        let mut particles = ParticleSystem::new();
+        for point in points {
+            particles.spawn_burst(Vec3::from(point), 1, &config);
+        }
+        let particle_depths: Vec<Vec<f32>> = eyes
+            .iter()
+            .map(|eye| {
+                let (instances, alpha_count) = particles.collect_instances(*eye);
+                assert_eq!(alpha_count, 3);
+                instances.iter().map(|p| p.position[2]).collect()
+            })
+            .collect();
+        assert_eq!(particle_depths[0], vec![-1.0, 0.0, 1.0]);
+        assert_eq!(particle_depths[1], vec![1.0, 0.0, -1.0]);
+
+        let splat_orders: Vec<Vec<u32>> = eyes
+            .iter()
+            .map(|eye| crate::splat::depth_order(&points, 0, &Mat4::look_at_rh(*eye, Vec3::ZERO, Vec3::Y)))
+            .collect();
+        assert_eq!(splat_orders[0], vec![0, 1, 2]);
+        assert_eq!(splat_orders[1], vec![2, 1, 0]);
+    }
+}
@@ -0,0 +1,231 @@
+//! Optional Steamworks integration, built with `--features steam`.
+//!
+//! With `steam.app_id` in naive.yaml and the Steam client running, the engine
+//! connects at startup. Scripts unlock achievements, set rich presence and
+//! read and write Steam Cloud files through the `steam` table, and the
+//! overlay keeps the game drawing while it holds focus (see
+//! `WindowActivity::overlay`). Without the feature, the app id or a running
+//! Steam client, the same calls do nothing, so scripts run unchanged outside
+//! Steam.
+
+use serde::Deserialize;
+
+/// Longest Steam Cloud file name.
+const MAX_CLOUD_NAME: usize = 255;
+
+/// Steam settings (`steam:` in naive.yaml).
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct SteamConfig {
+    /// The game's Steam app id; without one the engine doesn't connect.
+    pub app_id: Option<u32>,
+}
+
+/// Check a Steam Cloud file name: a relative path of at most 255 bytes that
+/// stays inside the game's cloud folder.
+pub fn check_cloud_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_CLOUD_NAME {
+        return Err(format!("cloud file names are 1 to {} bytes long", MAX_CLOUD_NAME));
+    }
+    if name.starts_with(['/', '\\']) || name.split(['/', '\\']).any(|part| part == "..") {
+        return Err(format!("cloud file '{}' must stay inside the game's cloud folder", name));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "steam")]
+struct Connection {
+    client: steamworks::Client,
+    single: steamworks::SingleClient,
+    overlay: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// Keeps the overlay callback registered.
+    _overlay_callback: steamworks::CallbackHandle,
+}
+
+/// Builds without Steam never connect.
+#[cfg(not(feature = "steam"))]
+type Connection = std::convert::Infallible;
+
+/// The Steam connection, if there is one.
+#[derive(Default)]
+pub struct SteamSession {
+    connection: Option<Connection>,
+    overlay_active: bool,
+}
+
+impl SteamSession {
+    /// Connect to the running Steam client for `config.app_id`. Failing to
+    /// connect is logged and leaves the session unavailable.
+    #[cfg(feature = "steam")]
+    pub fn connect(config: &SteamConfig) -> Self {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let Some(app_id) = config.app_id else {
+            return Self::default();
+        };
+        let (client, single) = match steamworks::Client::init_app(app_id) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("Steam unavailable (app {}): {}", app_id, e);
+                return Self::default();
+            }
+        };
+        client.user_stats().request_current_stats();
+        let overlay = Arc::new(AtomicBool::new(false));
+        let flag = overlay.clone();
+        let overlay_callback = client.register_callback(move |event: steamworks::GameOverlayActivated| {
+            flag.store(event.active, Ordering::Relaxed);
+        });
+        tracing::info!("Connected to Steam (app {})", app_id);
+        Self {
+            connection: Some(Connection { client, single, overlay, _overlay_callback: overlay_callback }),
+            overlay_active: false,
+        }
+    }
+
+    #[cfg(not(feature = "steam"))]
+    pub fn connect(config: &SteamConfig) -> Self {
+        if let Some(app_id) = config.app_id {
+            tracing::warn!("steam.app_id is {} but this build has no Steam support (build with --features steam)", app_id);
+        }
+        Self::default()
+    }
+
+    /// Whether the game is connected to Steam.
+    pub fn available(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Whether the Steam overlay is open.
+    pub fn overlay_active(&self) -> bool {
+        self.overlay_active
+    }
+
+    /// Run Steam callbacks; call once a frame. Returns the overlay state when
+    /// it opened or closed since the last call.
+    pub fn poll(&mut self) -> Option<bool> {
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            connection.single.run_callbacks();
+            let active = connection.overlay.load(std::sync::atomic::Ordering::Relaxed);
+            if active != self.overlay_active {
+                self.overlay_active = active;
+                return Some(active);
+            }
+        }
+        None
+    }
+
+    /// Unlock an achievement by its API name and store it. Returns false
+    /// without Steam; an achievement the app doesn't define is an error.
+    pub fn unlock(&self, name: &str) -> Result<bool, String> {
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            let stats = connection.client.user_stats();
+            stats.achievement(name).set().map_err(|_| format!("unknown achievement '{}'", name))?;
+            stats.store_stats().map_err(|_| "couldn't store Steam stats".to_string())?;
+            return Ok(true);
+        }
+        let _ = name;
+        Ok(false)
+    }
+
+    /// Whether an achievement is unlocked (false without Steam).
+    pub fn is_unlocked(&self, name: &str) -> bool {
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            return connection.client.user_stats().achievement(name).get().unwrap_or(false);
+        }
+        let _ = name;
+        false
+    }
+
+    /// Lock an achievement again, for testing unlocks.
+    pub fn clear_achievement(&self, name: &str) -> Result<(), String> {
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            let stats = connection.client.user_stats();
+            stats.achievement(name).clear().map_err(|_| format!("unknown achievement '{}'", name))?;
+            stats.store_stats().map_err(|_| "couldn't store Steam stats".to_string())?;
+        }
+        let _ = name;
+        Ok(())
+    }
+
+    /// Set a rich presence key, or remove it with None. Returns whether Steam
+    /// took it.
+    pub fn set_presence(&self, key: &str, value: Option<&str>) -> bool {
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            return connection.client.friends().set_rich_presence(key, value);
+        }
+        let _ = (key, value);
+        false
+    }
+
+    /// Remove every rich presence key.
+    pub fn clear_presence(&self) {
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            connection.client.friends().clear_rich_presence();
+        }
+    }
+
+    /// Write a Steam Cloud file. Returns false without Steam or with cloud
+    /// saves switched off for the account or the app.
+    pub fn cloud_write(&self, name: &str, data: &[u8]) -> Result<bool, String> {
+        check_cloud_name(name)?;
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            use std::io::Write;
+            let storage = connection.client.remote_storage();
+            if !storage.is_cloud_enabled_for_account() || !storage.is_cloud_enabled_for_app() {
+                return Ok(false);
+            }
+            let mut writer = storage.file(name).write();
+            writer.write_all(data).map_err(|e| format!("cloud file '{}': {}", name, e))?;
+            return Ok(true);
+        }
+        let _ = data;
+        Ok(false)
+    }
+
+    /// Read a Steam Cloud file; None without Steam or if it doesn't exist.
+    pub fn cloud_read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        check_cloud_name(name)?;
+        #[cfg(feature = "steam")]
+        if let Some(connection) = &self.connection {
+            use std::io::Read;
+            let file = connection.client.remote_storage().file(name);
+            if !file.exists() {
+                return Ok(None);
+            }
+            let mut data = Vec::new();
+            file.read().read_to_end(&mut data).map_err(|e| format!("cloud file '{}': {}", name, e))?;
+            return Ok(Some(data));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloud_names_stay_in_the_cloud_folder() {
+        assert!(check_cloud_name("save1.json").is_ok());
+        assert!(check_cloud_name("slots/save1.json").is_ok());
+        assert!(check_cloud_name("").is_err());
+        assert!(check_cloud_name("/etc/passwd").is_err());
+        assert!(check_cloud_name("slots/../../x").is_err());
+        assert!(check_cloud_name(&"a".repeat(256)).is_err());
+
+        // Unconfigured sessions answer every call without Steam
+        let session = SteamSession::connect(&SteamConfig::default());
+        assert!(!session.available());
+        assert_eq!(session.unlock("FIRST_BLOOD"), Ok(false));
+        assert_eq!(session.cloud_read("save1.json"), Ok(None));
+        assert!(session.cloud_write("../save1.json", b"{}").is_err());
+    }
+}
@@ -0,0 +1,813 @@
+//! Terrain: a heightmap stretched over an entity's local XZ plane.
+//!
+//! The heightmap is resampled onto a regular grid split into square chunks.
+//! Each chunk is meshed once per level of detail, every level taking every
+//! other grid point of the one before, and each frame a chunk draws the level
+//! for its distance to the camera. Chunks at different levels don't share
+//! their edge vertices, so every chunk hangs a skirt below its border to hide
+//! the cracks. The chunks are drawn into the G-buffer by a render hook after
+//! the geometry pass, blending up to four tiled layers by the splat map's
+//! channels. The same full-detail grid becomes the entity's static
+//! heightfield collider (see `world::spawn_terrain_collider`).
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::components::{Hidden, Terrain, Transform};
+use crate::culling::{Aabb, Frustum};
+use crate::mesh::{Vertex3D, DEFAULT_TANGENT};
+use crate::physics::PhysicsShape;
+use crate::pipeline::RenderHookContext;
+use crate::texture_cache::create_texture_view_from_rgba;
+
+/// Layers a splat map can weight (one per RGBA channel).
+pub const MAX_LAYERS: usize = 4;
+/// Finest chunk grid, in quads per side.
+pub const MAX_CHUNK_RESOLUTION: u32 = 256;
+/// Finest whole-terrain grid, in quads per side.
+pub const MAX_GRID_RESOLUTION: u32 = 4096;
+
+/// Grayscale heightmap, 0..1 per texel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    pub width: u32,
+    pub height: u32,
+    pub values: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Load a project-relative image as its luminance, keeping 16-bit precision.
+    pub fn load(project_root: &Path, path: &str) -> Result<Self, String> {
+        let image = image::open(project_root.join(path))
+            .map_err(|e| format!("heightmap '{}': {}", path, e))?
+            .to_luma16();
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            values: image.pixels().map(|p| p.0[0] as f32 / 65535.0).collect(),
+        })
+    }
+
+    /// Bilinear sample at (u, v) in 0..1, with the corner texels on the
+    /// corners; v runs along local +Z.
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let x = u.clamp(0.0, 1.0) * (self.width - 1) as f32;
+        let y = v.clamp(0.0, 1.0) * (self.height - 1) as f32;
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let at = |x: u32, y: u32| self.values[(y * self.width + x) as usize];
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * fx;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * fx;
+        top + (bottom - top) * fy
+    }
+}
+
+/// The terrain's full-detail grid of local heights, `side` points per side,
+/// rows running along local +Z.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightGrid {
+    pub side: u32,
+    pub heights: Vec<f32>,
+    size: [f32; 2],
+}
+
+impl HeightGrid {
+    pub fn new(terrain: &Terrain, heightmap: &Heightmap) -> Self {
+        let side = terrain.chunks * terrain.chunk_resolution + 1;
+        let last = (side - 1) as f32;
+        let heights = (0..side)
+            .flat_map(|z| (0..side).map(move |x| (x, z)))
+            .map(|(x, z)| heightmap.sample(x as f32 / last, z as f32 / last) * terrain.height)
+            .collect();
+        Self { side, heights, size: terrain.size }
+    }
+
+    pub fn height(&self, x: u32, z: u32) -> f32 {
+        self.heights[(z * self.side + x) as usize]
+    }
+
+    /// Local position of grid point (x, z); the grid is centered on the entity.
+    pub fn position(&self, x: u32, z: u32) -> Vec3 {
+        let last = (self.side - 1) as f32;
+        Vec3::new(
+            (x as f32 / last - 0.5) * self.size[0],
+            self.height(x, z),
+            (z as f32 / last - 0.5) * self.size[1],
+        )
+    }
+
+    /// Local normal at grid point (x, z), from the slope to its neighbours.
+    pub fn normal(&self, x: u32, z: u32) -> Vec3 {
+        let last = self.side - 1;
+        let (x0, x1) = (x.saturating_sub(1), (x + 1).min(last));
+        let (z0, z1) = (z.saturating_sub(1), (z + 1).min(last));
+        let cell = [self.size[0] / last as f32, self.size[1] / last as f32];
+        let dx = (self.height(x1, z) - self.height(x0, z)) / ((x1 - x0) as f32 * cell[0]);
+        let dz = (self.height(x, z1) - self.height(x, z0)) / ((z1 - z0) as f32 * cell[1]);
+        Vec3::new(-dx, 1.0, -dz).normalize()
+    }
+
+    /// A heightfield collider matching the grid under a transform of `scale`.
+    pub fn collider_shape(&self, scale: Vec3) -> PhysicsShape {
+        PhysicsShape::Heightfield {
+            heights: self.heights.clone(),
+            rows: self.side as usize,
+            cols: self.side as usize,
+            scale: Vec3::new(self.size[0] * scale.x, scale.y, self.size[1] * scale.z),
+        }
+    }
+}
+
+/// Detail levels of a terrain's chunks: one per LOD distance plus full
+/// detail, stopping where a chunk would be a single quad.
+pub fn level_count(terrain: &Terrain) -> u32 {
+    (terrain.lod_distances.len() as u32).min(terrain.chunk_resolution.trailing_zeros()) + 1
+}
+
+/// Level of a chunk whose nearest point is `distance` from the camera.
+pub fn chunk_level(lod_distances: &[f32], distance: f32, levels: u32) -> u32 {
+    (lod_distances.iter().filter(|&&d| distance > d).count() as u32).min(levels - 1)
+}
+
+/// Mesh of chunk (`cx`, `cz`) at `level`: every `2^level`-th point of its
+/// `resolution` x `resolution` quads of the grid, plus a skirt hanging below
+/// the border (drawn from both sides) as deep as the chunk's height range.
+pub fn chunk_mesh(grid: &HeightGrid, resolution: u32, cx: u32, cz: u32, level: u32) -> (Vec<Vertex3D>, Vec<u32>) {
+    let step = (1 << level).min(resolution);
+    let n = resolution / step;
+    let (x0, z0) = (cx * resolution, cz * resolution);
+    let last = (grid.side - 1) as f32;
+
+    let mut vertices = Vec::with_capacity(((n + 1) * (n + 1) + 4 * (n + 1)) as usize);
+    for j in 0..=n {
+        for i in 0..=n {
+            let (x, z) = (x0 + i * step, z0 + j * step);
+            vertices.push(Vertex3D {
+                position: grid.position(x, z).to_array(),
+                normal: grid.normal(x, z).to_array(),
+                tex_coords: [x as f32 / last, z as f32 / last],
+                color: [1.0; 4],
+                joint_indices: [0; 4],
+                joint_weights: [1.0, 0.0, 0.0, 0.0],
+                tangent: DEFAULT_TANGENT,
+            });
+        }
+    }
+
+    let v = |i: u32, j: u32| j * (n + 1) + i;
+    let mut indices = Vec::with_capacity((n * n * 6 + 4 * n * 12) as usize);
+    for j in 0..n {
+        for i in 0..n {
+            indices.extend([v(i, j), v(i, j + 1), v(i + 1, j), v(i + 1, j), v(i, j + 1), v(i + 1, j + 1)]);
+        }
+    }
+
+    let (min, max) = vertices
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), vertex| (min.min(vertex.position[1]), max.max(vertex.position[1])));
+    let cell = grid.size[0].min(grid.size[1]) / last * step as f32;
+    let skirt = (max - min).max(cell * 0.25);
+    let edges: [Vec<u32>; 4] = [
+        (0..=n).map(|i| v(i, 0)).collect(),
+        (0..=n).map(|i| v(i, n)).collect(),
+        (0..=n).map(|j| v(0, j)).collect(),
+        (0..=n).map(|j| v(n, j)).collect(),
+    ];
+    for edge in edges {
+        let base = vertices.len() as u32;
+        for &top in &edge {
+            let mut bottom = vertices[top as usize];
+            bottom.position[1] -= skirt;
+            vertices.push(bottom);
+        }
+        for (k, pair) in edge.windows(2).enumerate() {
+            let (a, b) = (pair[0], pair[1]);
+            let (c, d) = (base + k as u32, base + k as u32 + 1);
+            indices.extend([a, c, b, b, c, d, a, b, c, b, d, c]);
+        }
+    }
+    (vertices, indices)
+}
+
+/// Per-terrain uniforms (group 1, binding 0).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TerrainUniforms {
+    model: [[f32; 4]; 4],
+    normal_matrix: [[f32; 4]; 4],
+    /// RGB tint + roughness per layer.
+    layer_colors: [[f32; 4]; 4],
+    /// Texture repeats across the terrain along local X, per layer.
+    tile_u: [f32; 4],
+    /// Texture repeats across the terrain along local Z, per layer.
+    tile_v: [f32; 4],
+    /// Layer count, unused x3.
+    params: [f32; 4],
+}
+
+impl TerrainUniforms {
+    fn new(terrain: &Terrain, model: &Mat4) -> Self {
+        let mut layer_colors = [[0.0; 4]; 4];
+        let (mut tile_u, mut tile_v) = ([1.0; 4], [1.0; 4]);
+        for (i, layer) in terrain.layers.iter().take(MAX_LAYERS).enumerate() {
+            let [r, g, b] = layer.color;
+            layer_colors[i] = [r, g, b, layer.roughness];
+            tile_u[i] = terrain.size[0] / layer.tile_size;
+            tile_v[i] = terrain.size[1] / layer.tile_size;
+        }
+        Self {
+            model: model.to_cols_array_2d(),
+            normal_matrix: model.inverse().transpose().to_cols_array_2d(),
+            layer_colors,
+            tile_u,
+            tile_v,
+            params: [terrain.layers.len().min(MAX_LAYERS) as f32, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// One level of one chunk.
+struct ChunkLevel {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+struct Chunk {
+    /// Local bounds, skirt included.
+    local_bounds: Aabb,
+    bounds: Aabb,
+    levels: Vec<ChunkLevel>,
+}
+
+/// One entity's chunks.
+struct TerrainBatch {
+    /// Component the chunks were built from.
+    terrain: Terrain,
+    matrix: Mat4,
+    /// Empty when the heightmap couldn't be loaded.
+    chunks: Vec<Chunk>,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Builds and draws every `Terrain` of the scene into the G-buffer.
+pub struct TerrainRenderer {
+    pass_name: String,
+    formats: Vec<wgpu::TextureFormat>,
+    depth_format: wgpu::TextureFormat,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    splat_sampler: wgpu::Sampler,
+    layer_sampler: wgpu::Sampler,
+    /// Stands in for layers without a texture.
+    white: wgpu::TextureView,
+    /// Stands in for a missing splat map: all layer 0.
+    default_splat: wgpu::TextureView,
+    batches: HashMap<hecs::Entity, TerrainBatch>,
+    /// Loaded heightmaps by path (None when loading failed).
+    heightmaps: HashMap<String, Option<Heightmap>>,
+    /// Loaded textures by path and color space (None when loading failed).
+    textures: HashMap<(String, bool), Option<wgpu::TextureView>>,
+    /// Skip chunks outside the view (off under `--no-culling`).
+    culling: bool,
+    /// Draw every chunk at full detail (`--force-lod0`).
+    force_lod0: bool,
+}
+
+impl TerrainRenderer {
+    /// `pass_name` and the formats are the geometry pass's, from
+    /// [`crate::foliage::geometry_pass`].
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pass_name: &str,
+        formats: &[wgpu::TextureFormat],
+        depth_format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let sampler_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Terrain Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1),
+                texture_entry(2),
+                texture_entry(3),
+                texture_entry(4),
+                texture_entry(5),
+                sampler_entry(6),
+                sampler_entry(7),
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Terrain Shader"),
+            source: wgpu::ShaderSource::Wgsl(TERRAIN_WGSL.into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Terrain Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let targets: Vec<_> = formats
+            .iter()
+            .map(|&format| {
+                Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+            })
+            .collect();
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Terrain Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex3D::desc()],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &targets,
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = |label, address_mode| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some(label),
+                address_mode_u: address_mode,
+                address_mode_v: address_mode,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            })
+        };
+        Self {
+            pass_name: pass_name.to_string(),
+            formats: formats.to_vec(),
+            depth_format,
+            pipeline,
+            bind_group_layout,
+            splat_sampler: sampler("Terrain Splat Sampler", wgpu::AddressMode::ClampToEdge),
+            layer_sampler: sampler("Terrain Layer Sampler", wgpu::AddressMode::Repeat),
+            white: create_texture_view_from_rgba(device, queue, &[255; 4], 1, 1, true, "Terrain White"),
+            default_splat: create_texture_view_from_rgba(device, queue, &[255, 0, 0, 0], 1, 1, false, "Terrain Default Splat"),
+            batches: HashMap::new(),
+            heightmaps: HashMap::new(),
+            textures: HashMap::new(),
+            culling: true,
+            force_lod0: false,
+        }
+    }
+
+    /// Whether this renderer was built for the given geometry pass and formats.
+    pub fn matches(&self, pass_name: &str, formats: &[wgpu::TextureFormat], depth_format: wgpu::TextureFormat) -> bool {
+        self.pass_name == pass_name && self.formats == formats && self.depth_format == depth_format
+    }
+
+    pub fn set_culling(&mut self, culling: bool) {
+        self.culling = culling;
+    }
+
+    pub fn set_force_lod0(&mut self, force_lod0: bool) {
+        self.force_lod0 = force_lod0;
+    }
+
+    /// Build the chunks of new or changed terrains, follow moved ones and drop
+    /// those of removed entities.
+    pub fn sync(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, project_root: &Path, world: &hecs::World) {
+        self.batches.retain(|&entity, _| world.get::<&Terrain>(entity).is_ok());
+
+        for (entity, (transform, terrain)) in world.query::<(&Transform, &Terrain)>().iter() {
+            let matrix = transform.world_matrix;
+            if let Some(batch) = self.batches.get_mut(&entity).filter(|b| b.terrain == *terrain) {
+                if batch.matrix != matrix {
+                    batch.matrix = matrix;
+                    for chunk in &mut batch.chunks {
+                        chunk.bounds = chunk.local_bounds.transformed(&matrix);
+                    }
+                    queue.write_buffer(&batch.uniform_buffer, 0, bytemuck::cast_slice(&[TerrainUniforms::new(terrain, &matrix)]));
+                }
+                continue;
+            }
+            let batch = self.build(device, queue, project_root, terrain, matrix);
+            self.batches.insert(entity, batch);
+        }
+    }
+
+    fn build(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, project_root: &Path, terrain: &Terrain, matrix: Mat4) -> TerrainBatch {
+        let heightmap = self
+            .heightmaps
+            .entry(terrain.heightmap.clone())
+            .or_insert_with(|| {
+                Heightmap::load(project_root, &terrain.heightmap)
+                    .inspect_err(|e| tracing::warn!("Terrain: {}", e))
+                    .ok()
+            })
+            .as_ref();
+
+        let mut chunks = Vec::new();
+        if let Some(heightmap) = heightmap {
+            let grid = HeightGrid::new(terrain, heightmap);
+            let levels = level_count(terrain);
+            for cz in 0..terrain.chunks {
+                for cx in 0..terrain.chunks {
+                    let mut local_bounds = None;
+                    let levels = (0..levels)
+                        .map(|level| {
+                            let (vertices, indices) = chunk_mesh(&grid, terrain.chunk_resolution, cx, cz, level);
+                            if level == 0 {
+                                local_bounds = Some(Aabb::from_points(vertices.iter().map(|v| v.position)));
+                            }
+                            ChunkLevel {
+                                vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                                    label: Some("Terrain Chunk Vertices"),
+                                    contents: bytemuck::cast_slice(&vertices),
+                                    usage: wgpu::BufferUsages::VERTEX,
+                                }),
+                                index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                                    label: Some("Terrain Chunk Indices"),
+                                    contents: bytemuck::cast_slice(&indices),
+                                    usage: wgpu::BufferUsages::INDEX,
+                                }),
+                                index_count: indices.len() as u32,
+                            }
+                        })
+                        .collect();
+                    let local_bounds = local_bounds.unwrap_or(Aabb { min: Vec3::ZERO, max: Vec3::ZERO });
+                    chunks.push(Chunk { local_bounds, bounds: local_bounds.transformed(&matrix), levels });
+                }
+            }
+            tracing::debug!("Built {} terrain chunks at {} levels", chunks.len(), levels);
+        }
+
+        let splat_key = terrain.splat_map.clone().map(|path| (path, false));
+        let layer_keys: Vec<Option<(String, bool)>> = (0..MAX_LAYERS)
+            .map(|i| terrain.layers.get(i).and_then(|layer| layer.texture.clone()).map(|path| (path, true)))
+            .collect();
+        for key in splat_key.iter().chain(layer_keys.iter().flatten()) {
+            self.textures.entry(key.clone()).or_insert_with(|| {
+                let (path, srgb) = key;
+                image::open(project_root.join(path))
+                    .inspect_err(|e| tracing::warn!("Terrain: texture '{}': {}", path, e))
+                    .ok()
+                    .map(|image| {
+                        let image = image.to_rgba8();
+                        create_texture_view_from_rgba(device, queue, image.as_raw(), image.width(), image.height(), *srgb, &format!("Terrain: {}", path))
+                    })
+            });
+        }
+        let view = |key: &Option<(String, bool)>, fallback| {
+            key.as_ref().and_then(|key| self.textures.get(key)?.as_ref()).unwrap_or(fallback)
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Uniforms"),
+            contents: bytemuck::cast_slice(&[TerrainUniforms::new(terrain, &matrix)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let texture = |binding, texture_view| wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::TextureView(texture_view),
+        };
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Terrain Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                texture(1, view(&splat_key, &self.default_splat)),
+                texture(2, view(&layer_keys[0], &self.white)),
+                texture(3, view(&layer_keys[1], &self.white)),
+                texture(4, view(&layer_keys[2], &self.white)),
+                texture(5, view(&layer_keys[3], &self.white)),
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: wgpu::BindingResource::Sampler(&self.splat_sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: wgpu::BindingResource::Sampler(&self.layer_sampler),
+                },
+            ],
+        });
+
+        TerrainBatch {
+            terrain: terrain.clone(),
+            matrix,
+            chunks,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    /// Draw the visible chunks, each at the level for its distance, into the
+    /// geometry pass's targets. Runs as a hook after that pass.
+    pub fn draw(&mut self, ctx: &mut RenderHookContext<'_>) {
+        let resources = &ctx.compiled.resources;
+        let views: Option<Vec<_>> = ctx.pass.color_targets.iter().map(|name| resources.get(name)).collect();
+        let depth = ctx.pass.depth_target.as_ref().and_then(|name| resources.get(name));
+        let (Some(views), Some(depth)) = (views, depth) else {
+            return;
+        };
+
+        let camera = &ctx.camera_state.uniform;
+        let camera_position = Vec3::from_array(camera.position);
+        let frustum = Frustum::from_view_projection(&Mat4::from_cols_array_2d(&camera.view_projection));
+        let mut visible = Vec::new();
+        for (entity, batch) in &self.batches {
+            if ctx.scene_world.world.get::<&Hidden>(*entity).is_ok() {
+                continue;
+            }
+            let chunks: Vec<&ChunkLevel> = batch
+                .chunks
+                .iter()
+                .filter(|chunk| !self.culling || frustum.intersects(&chunk.bounds))
+                .map(|chunk| {
+                    let level = if self.force_lod0 {
+                        0
+                    } else {
+                        let distance = camera_position.clamp(chunk.bounds.min, chunk.bounds.max).distance(camera_position);
+                        chunk_level(&batch.terrain.lod_distances, distance, chunk.levels.len() as u32)
+                    };
+                    &chunk.levels[level as usize]
+                })
+                .collect();
+            if !chunks.is_empty() {
+                visible.push((batch, chunks));
+            }
+        }
+        if visible.is_empty() {
+            return;
+        }
+
+        let color_attachments: Vec<_> = views
+            .iter()
+            .map(|resource| {
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &resource.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            })
+            .collect();
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("terrain"),
+            color_attachments: &color_attachments,
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &ctx.camera_state.bind_group, &[]);
+        for (batch, chunks) in visible {
+            render_pass.set_bind_group(1, &batch.bind_group, &[]);
+            for level in chunks {
+                render_pass.set_vertex_buffer(0, level.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(level.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..level.index_count, 0, 0..1);
+            }
+        }
+    }
+}
+
+/// G-buffer shader blending the layers by the splat map. Channels past the
+/// layer count are ignored and the rest renormalized; where they are all
+/// zero, layer 0 shows.
+const TERRAIN_WGSL: &str = r#"
+struct CameraUniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    position: vec3<f32>,
+    near_plane: f32,
+    far_plane: f32,
+    _pad1: f32,
+    viewport_size: vec2<f32>,
+    jitter: vec2<f32>,
+    _pad2: vec2<f32>,
+    inv_view_projection: mat4x4<f32>,
+    prev_view_projection: mat4x4<f32>,
+};
+
+struct TerrainUniforms {
+    model: mat4x4<f32>,
+    normal_matrix: mat4x4<f32>,
+    layer_colors: array<vec4<f32>, 4>, // rgb, roughness
+    tile_u: vec4<f32>,
+    tile_v: vec4<f32>,
+    params: vec4<f32>,                 // layer count
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> terrain: TerrainUniforms;
+@group(1) @binding(1) var splat_map: texture_2d<f32>;
+@group(1) @binding(2) var layer0: texture_2d<f32>;
+@group(1) @binding(3) var layer1: texture_2d<f32>;
+@group(1) @binding(4) var layer2: texture_2d<f32>;
+@group(1) @binding(5) var layer3: texture_2d<f32>;
+@group(1) @binding(6) var splat_sampler: sampler;
+@group(1) @binding(7) var layer_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) tex_coords: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_normal: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct GBufferOutput {
+    @location(0) albedo: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+    @location(2) emission: vec4<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let world = terrain.model * vec4<f32>(model.position, 1.0);
+    out.clip_position = camera.view_projection * world;
+    out.world_normal = normalize((terrain.normal_matrix * vec4<f32>(model.normal, 0.0)).xyz);
+    out.uv = model.tex_coords;
+    return out;
+}
+
+fn layer_uv(uv: vec2<f32>, i: i32) -> vec2<f32> {
+    return uv * vec2<f32>(terrain.tile_u[i], terrain.tile_v[i]);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> GBufferOutput {
+    var weights = textureSample(splat_map, splat_sampler, in.uv);
+    weights = weights * step(vec4<f32>(0.5, 1.5, 2.5, 3.5), vec4<f32>(terrain.params.x));
+    let total = dot(weights, vec4<f32>(1.0));
+    weights = select(weights / max(total, 1e-4), vec4<f32>(1.0, 0.0, 0.0, 0.0), total < 1e-4);
+
+    let c0 = textureSample(layer0, layer_sampler, layer_uv(in.uv, 0)).rgb * terrain.layer_colors[0].rgb;
+    let c1 = textureSample(layer1, layer_sampler, layer_uv(in.uv, 1)).rgb * terrain.layer_colors[1].rgb;
+    let c2 = textureSample(layer2, layer_sampler, layer_uv(in.uv, 2)).rgb * terrain.layer_colors[2].rgb;
+    let c3 = textureSample(layer3, layer_sampler, layer_uv(in.uv, 3)).rgb * terrain.layer_colors[3].rgb;
+    let albedo = c0 * weights.x + c1 * weights.y + c2 * weights.z + c3 * weights.w;
+    let roughness = dot(weights, vec4<f32>(
+        terrain.layer_colors[0].a,
+        terrain.layer_colors[1].a,
+        terrain.layer_colors[2].a,
+        terrain.layer_colors[3].a,
+    ));
+
+    var out: GBufferOutput;
+    out.albedo = vec4<f32>(albedo, roughness);
+    out.normal = vec4<f32>(normalize(in.world_normal) * 0.5 + 0.5, 0.0);
+    out.emission = vec4<f32>(0.0);
+    return out;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::TerrainLayer;
+
+    fn terrain(chunks: u32, chunk_resolution: u32) -> Terrain {
+        Terrain {
+            heightmap: "height.png".into(),
+            size: [64.0, 32.0],
+            height: 10.0,
+            chunks,
+            chunk_resolution,
+            lod_distances: vec![20.0, 40.0, 80.0],
+            splat_map: None,
+            layers: vec![TerrainLayer { texture: None, color: [1.0; 3], tile_size: 4.0, roughness: 0.9 }],
+            collider: true,
+            friction: 0.5,
+        }
+    }
+
+    /// A ramp rising along +X, from 0 to 1.
+    fn ramp() -> Heightmap {
+        Heightmap { width: 3, height: 2, values: vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0] }
+    }
+
+    #[test]
+    fn test_heightmap_bilinear_sample() {
+        let map = ramp();
+        assert_eq!(map.sample(0.0, 0.0), 0.0);
+        assert_eq!(map.sample(0.25, 0.7), 0.25);
+        assert_eq!(map.sample(1.0, 1.0), 1.0);
+        assert_eq!(map.sample(2.0, -1.0), 1.0);
+    }
+
+    #[test]
+    fn test_height_grid_spans_terrain() {
+        let grid = HeightGrid::new(&terrain(2, 4), &ramp());
+        assert_eq!(grid.side, 9);
+        assert_eq!(grid.position(0, 0), Vec3::new(-32.0, 0.0, -16.0));
+        assert_eq!(grid.position(8, 8), Vec3::new(32.0, 10.0, 16.0));
+        // Slope of 10 m over 64 m along X
+        let normal = grid.normal(4, 4);
+        assert!((normal - Vec3::new(-10.0 / 64.0, 1.0, 0.0).normalize()).length() < 1e-5);
+
+        let PhysicsShape::Heightfield { heights, rows, cols, scale } = grid.collider_shape(Vec3::new(2.0, 1.0, 1.0)) else {
+            panic!("not a heightfield");
+        };
+        assert_eq!((rows, cols, heights.len()), (9, 9, 81));
+        assert_eq!(scale, Vec3::new(128.0, 1.0, 32.0));
+    }
+
+    #[test]
+    fn test_chunk_mesh_levels_and_skirt() {
+        let grid = HeightGrid::new(&terrain(2, 4), &ramp());
+        let (vertices, indices) = chunk_mesh(&grid, 4, 1, 0, 0);
+        // 5x5 grid + 4 skirt edges of 5
+        assert_eq!(vertices.len(), 25 + 20);
+        assert_eq!(indices.len(), 4 * 4 * 6 + 4 * 4 * 12);
+        assert_eq!(vertices[0].position, [0.0, 5.0, -16.0]);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+        // The skirt hangs as deep as the chunk's 5 m height range
+        assert_eq!(vertices[25].position, [0.0, 0.0, -16.0]);
+
+        // Each level halves the grid, down to a single quad
+        let (vertices, indices) = chunk_mesh(&grid, 4, 1, 0, 1);
+        assert_eq!(vertices.len(), 9 + 12);
+        assert_eq!(indices.len(), 2 * 2 * 6 + 4 * 2 * 12);
+        let (vertices, _) = chunk_mesh(&grid, 4, 1, 0, 5);
+        assert_eq!(vertices.len(), 4 + 8);
+    }
+
+    #[test]
+    fn test_chunk_level_selection() {
+        let terrain = terrain(2, 4);
+        // Three distances, but a 4-quad chunk only halves twice
+        assert_eq!(level_count(&terrain), 3);
+        assert_eq!(chunk_level(&terrain.lod_distances, 10.0, 3), 0);
+        assert_eq!(chunk_level(&terrain.lod_distances, 30.0, 3), 1);
+        assert_eq!(chunk_level(&terrain.lod_distances, 1000.0, 3), 2);
+    }
+}
@@ -1,7 +1,8 @@
 //! Phase 10: Headless test runner for automated gameplay testing.
 //!
 //! Runs Lua test scripts that inject input, advance game time, and assert
-//! that game events occurred. No GPU or window required.
+//! that game events occurred. No GPU or window required; with `--headless`
+//! every frame is also rendered offscreen through the compiled pipeline.
 
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -28,7 +29,29 @@ pub struct TestResult {
     pub game_time: f32,
 }
 
-/// Headless test runner. Owns all game systems except GPU/rendering.
+/// Offscreen rendering settings for `naive test --headless`.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Render pipeline YAML, relative to the project root (defaults to pipelines/render.yaml).
+    pub pipeline: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    /// LOD and culling locks (`--force-lod0`, `--no-culling`).
+    pub lod_lock: crate::pipeline::LodLock,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            pipeline: None,
+            width: crate::headless::DEFAULT_HEADLESS_WIDTH,
+            height: crate::headless::DEFAULT_HEADLESS_HEIGHT,
+            lod_lock: Default::default(),
+        }
+    }
+}
+
+/// Headless test runner. Owns all game systems; rendering is optional.
 /// Fields shared with Lua closures use Rc<RefCell<T>> for safe interior mutability.
 pub struct TestRunner {
     pub project_root: PathBuf,
@@ -44,11 +67,23 @@ pub struct TestRunner {
     lua_event_listeners: Rc<RefCell<HashMap<String, Vec<mlua::RegistryKey>>>>,
     next_lua_listener_id: Rc<RefCell<u64>>,
     lua_listener_id_map: Rc<RefCell<HashMap<u64, (String, usize)>>>,
+    message_queue: Rc<RefCell<crate::messaging::MessageQueue>>,
+    game_clock: crate::scripting::SharedGameClock,
+    screenshot_queue: crate::scripting::SharedScreenshotQueue,
+    /// Lua memory cap and GC budget applied when a scene loads.
+    pub scripting: crate::project_config::ScriptingConfig,
+    /// Offscreen renderer, when rendering is enabled.
+    pub renderer: Option<crate::headless::HeadlessRenderer>,
+    /// First GPU error hit while rendering a frame.
+    pub render_error: Option<String>,
 }
 
 impl TestRunner {
     pub fn new(project_root: &Path) -> Self {
-        let bindings = crate::input::load_bindings(project_root);
+        let bindings_path = crate::project_config::load_config(&project_root.join("naive.yaml"))
+            .map(|c| c.input.bindings_path().to_string())
+            .unwrap_or_else(|_| crate::input::BINDINGS_PATH.to_string());
+        let bindings = crate::input::load_bindings(&project_root.join(bindings_path));
         Self {
             project_root: project_root.to_path_buf(),
             scene_world: Rc::new(RefCell::new(SceneWorld::new())),
@@ -63,29 +98,54 @@ impl TestRunner {
             lua_event_listeners: Rc::new(RefCell::new(HashMap::new())),
             next_lua_listener_id: Rc::new(RefCell::new(0)),
             lua_listener_id_map: Rc::new(RefCell::new(HashMap::new())),
+            message_queue: Rc::new(RefCell::new(crate::messaging::MessageQueue::new())),
+            game_clock: Rc::new(RefCell::new(crate::time_scale::GameClock::new())),
+            screenshot_queue: Rc::new(RefCell::new(Vec::new())),
+            scripting: Default::default(),
+            renderer: None,
+            render_error: None,
         }
     }
 
+    /// Render every frame offscreen through the project's pipeline. Call before
+    /// `load_scene` so entities are spawned with GPU resources.
+    pub fn enable_rendering(&mut self, options: &RenderOptions) -> Result<(), String> {
+        let mut renderer = crate::headless::HeadlessRenderer::new(
+            &self.project_root,
+            options.pipeline.as_deref(),
+            options.width,
+            options.height,
+        )?;
+        renderer.render_debug.lod_lock = options.lod_lock;
+        self.renderer = Some(renderer);
+        Ok(())
+    }
+
     /// Load a scene by path (relative to project root).
     pub fn load_scene(&mut self, scene_rel: &str) -> Result<(), String> {
         let scene_path = self.project_root.join(scene_rel);
         let scene = crate::scene::load_scene(&scene_path)
             .map_err(|e| format!("Failed to load scene: {:?}", e))?;
 
-        // Set gravity from scene settings
-        let gravity = glam::Vec3::from(scene.settings.gravity);
-        *self.physics_world.borrow_mut() = PhysicsWorld::new(gravity);
+        // Set gravity and the physics rate from scene settings
+        let mut physics_world = PhysicsWorld::new(glam::Vec3::from(scene.settings.gravity));
+        physics_world.timestep = crate::physics::FixedTimestep::new(scene.settings.physics_rate);
+        *self.physics_world.borrow_mut() = physics_world;
 
-        // Spawn entities headlessly (no GPU)
+        // Spawn entities (headlessly unless rendering is enabled)
         *self.scene_world.borrow_mut() = SceneWorld::new();
         {
             let mut sw = self.scene_world.borrow_mut();
             let mut pw = self.physics_world.borrow_mut();
-            crate::world::spawn_all_entities_headless(&mut *sw, &scene, &mut *pw);
+            match self.renderer.as_mut() {
+                Some(renderer) => renderer.spawn_scene(&mut sw, &scene, Some(&mut pw)),
+                None => crate::world::spawn_all_entities_headless(&mut *sw, &scene, &self.project_root, &mut *pw),
+            }
         }
 
         // Initialize scripting
         self.script_runtime = ScriptRuntime::new();
+        self.script_runtime.configure_memory(self.scripting.memory_limit_mb, self.scripting.gc_budget_ms)?;
         if let Err(e) = self.script_runtime.register_api() {
             return Err(format!("Failed to register script API: {}", e));
         }
@@ -108,6 +168,30 @@ impl TestRunner {
                 self.lua_listener_id_map.clone(),
             )
             .map_err(|e| format!("Event API: {}", e))?;
+        self.message_queue.borrow_mut().clear();
+        self.script_runtime
+            .register_message_api(self.message_queue.clone())
+            .map_err(|e| format!("Message API: {}", e))?;
+        *self.game_clock.borrow_mut() = crate::time_scale::GameClock::new();
+        self.script_runtime
+            .register_time_api(self.game_clock.clone(), self.scene_world.clone())
+            .map_err(|e| format!("Time API: {}", e))?;
+        self.script_runtime
+            .register_screenshot_api(self.screenshot_queue.clone(), self.project_root.clone())
+            .map_err(|e| format!("Screenshot API: {}", e))?;
+        let project_config = crate::project_config::load_config(&self.project_root.join("naive.yaml")).ok();
+        let config = crate::script_config::resolve(
+            project_config.as_ref().map(|c| &c.config),
+            Some(&scene.settings.config),
+            &[],
+        )?;
+        self.script_runtime
+            .register_config_api(&config)
+            .map_err(|e| format!("Config API: {}", e))?;
+        let api_level = project_config.as_ref().map_or(1, |c| c.scripting.api_level.unwrap_or(1));
+        self.script_runtime
+            .register_api_shims(api_level, crate::scripting::LUA_DEPRECATIONS)
+            .map_err(|e| format!("API shims: {}", e))?;
 
         // Load event schema
         self.event_bus.borrow_mut().load_schema(&self.project_root);
@@ -167,9 +251,10 @@ impl TestRunner {
         }
 
         // Emit lifecycle event
-        self.event_bus
-            .borrow_mut()
-            .emit("lifecycle.scene_loaded", std::collections::HashMap::new());
+        self.event_bus.borrow_mut().emit(
+            crate::engine_events::SCENE_LOADED,
+            crate::engine_events::payload(serde_json::json!({"scene": scene_rel})),
+        );
         self.event_bus.borrow_mut().flush();
 
         tracing::info!("Test runner: scene loaded");
@@ -178,7 +263,8 @@ impl TestRunner {
 
     /// Advance the simulation by one frame.
     pub fn step_frame(&mut self) {
-        let dt = self.delta_time;
+        let dt = self.game_clock.borrow_mut().advance(self.delta_time);
+        self.script_runtime.watchdog.borrow_mut().begin_frame();
 
         // Apply synthetic inputs
         self.input_state.borrow_mut().begin_frame();
@@ -188,23 +274,31 @@ impl TestRunner {
 
         // FPS controller update
         self.update_fps_controller(dt);
+        self.dispatch_trigger_events();
 
         // Update all scripts (collect first to release world borrow before Lua runs)
-        let scripted: Vec<hecs::Entity> = {
+        let scripted: Vec<(hecs::Entity, f32)> = {
             let sw = self.scene_world.borrow();
             let mut query = sw.world.query::<&Script>();
             query.iter()
-                .map(|(e, _)| e)
+                .map(|(e, _)| (e, crate::time_scale::entity_dt(&sw.world, e, dt)))
                 .collect()
         };
-        for entity in scripted {
-            self.script_runtime.call_update(entity, dt);
+        for (entity, entity_dt) in scripted {
+            self.script_runtime.call_update(entity, entity_dt);
         }
+        crate::messaging::dispatch_messages(&self.script_runtime, &self.message_queue, &self.scene_world, dt);
 
         // Tick event bus and tweens
         self.event_bus.borrow_mut().tick(dt as f64);
         self.event_bus.borrow_mut().flush();
-        let _tween_results = self.tween_system.update(dt);
+        self.script_runtime.collect_garbage();
+        {
+            let sw = self.scene_world.borrow();
+            let _tween_results = self.tween_system.update_scaled(dt, |e| {
+                crate::time_scale::entity_dt(&sw.world, e, 1.0)
+            });
+        }
 
         // Update transforms
         {
@@ -212,10 +306,75 @@ impl TestRunner {
             crate::transform::update_transforms(&mut sw.world);
         }
 
-        self.total_time += dt;
+        self.render_frame();
+
+        self.total_time += self.delta_time;
         self.frame_count += 1;
     }
 
+    /// Call the trigger hooks for this frame's trigger events, as the engine does.
+    fn dispatch_trigger_events(&self) {
+        // Trigger hooks may damage or move entities, so release borrows first
+        let trigger_calls: Vec<_> = {
+            let sw = self.scene_world.borrow();
+            let pw = self.physics_world.borrow();
+            let entity_to_id: HashMap<hecs::Entity, &str> = sw
+                .entity_registry
+                .iter()
+                .map(|(id, &e)| (e, id.as_str()))
+                .collect();
+            pw.trigger_events
+                .iter()
+                .flat_map(|event| {
+                    let trigger_id = entity_to_id.get(&event.trigger_entity).copied().unwrap_or("unknown").to_string();
+                    let other_id = entity_to_id.get(&event.other_entity).copied().unwrap_or("unknown").to_string();
+                    [(event.trigger_entity, other_id, event.phase), (event.other_entity, trigger_id, event.phase)]
+                })
+                .collect()
+        };
+        for (entity, other_id, phase) in trigger_calls {
+            match phase {
+                crate::physics::TriggerPhase::Enter => self.script_runtime.call_on_trigger_enter(entity, &other_id),
+                crate::physics::TriggerPhase::Stay { elapsed } => {
+                    self.script_runtime.call_on_trigger_stay(entity, &other_id, elapsed)
+                }
+                crate::physics::TriggerPhase::Exit => self.script_runtime.call_on_trigger_exit(entity, &other_id),
+            }
+        }
+    }
+
+    /// Render the current frame offscreen if rendering is enabled and serve
+    /// queued screenshots from it. Scenes without a camera are simulated but
+    /// not rendered.
+    fn render_frame(&mut self) {
+        let requested = std::mem::take(&mut *self.screenshot_queue.borrow_mut());
+        let renderer = match self.renderer.as_mut() {
+            Some(r) => r,
+            None => {
+                if !requested.is_empty() {
+                    tracing::warn!("capture_screenshot ignored: tests render only with --headless");
+                }
+                return;
+            }
+        };
+        let sw = self.scene_world.borrow();
+        let (camera, cam_transform) = match crate::headless::frame_camera(&sw) {
+            Some(view) => view,
+            None => return,
+        };
+        if let Err(e) = renderer.render(&sw, &camera, &cam_transform) {
+            tracing::error!("Frame {}: {}", self.frame_count, e);
+            self.render_error.get_or_insert(e);
+            return;
+        }
+        for out in requested {
+            match renderer.save_png(&out) {
+                Ok(()) => tracing::info!("Screenshot saved to {}", out.display()),
+                Err(e) => tracing::error!("Screenshot failed: {}", e),
+            }
+        }
+    }
+
     /// Advance multiple frames.
     pub fn step_frames(&mut self, count: u64) {
         for _ in 0..count {
@@ -287,6 +446,7 @@ impl TestRunner {
                         cc.jump_impulse,
                         cc.grounded,
                         cc.velocity,
+                        cc.push(),
                         rb.handle,
                         col.handle,
                     )
@@ -304,6 +464,7 @@ impl TestRunner {
             jump_impulse,
             grounded,
             velocity,
+            push,
             rb_handle,
             col_handle,
         ) in player_data
@@ -347,7 +508,7 @@ impl TestRunner {
             let (_effective, new_grounded) =
                 self.physics_world
                     .borrow_mut()
-                    .move_character(rb_handle, col_handle, desired, dt);
+                    .move_character(rb_handle, col_handle, desired, dt, push);
 
             // Update player + character controller
             if let Ok(mut player) = self.scene_world.borrow_mut().world.get::<&mut Player>(entity) {
@@ -367,7 +528,7 @@ impl TestRunner {
             }
         }
 
-        self.physics_world.borrow_mut().step(dt);
+        self.physics_world.borrow_mut().advance(dt);
         {
             let pw = self.physics_world.borrow();
             let mut sw = self.scene_world.borrow_mut();
@@ -381,7 +542,14 @@ impl TestRunner {
 // ---------------------------------------------------------------------------
 
 /// Run all test functions in a Lua test file. Returns results for each test.
-pub fn run_test_file(project_root: &Path, test_file: &Path) -> Vec<TestResult> {
+/// With `render`, each frame is also rendered offscreen and GPU errors fail the test.
+/// `scripting` sets the Lua memory cap and GC budget of each test's VM.
+pub fn run_test_file(
+    project_root: &Path,
+    test_file: &Path,
+    render: Option<&RenderOptions>,
+    scripting: &crate::project_config::ScriptingConfig,
+) -> Vec<TestResult> {
     let test_source = match std::fs::read_to_string(test_file) {
         Ok(s) => s,
         Err(e) => {
@@ -433,7 +601,7 @@ pub fn run_test_file(project_root: &Path, test_file: &Path) -> Vec<TestResult> {
     let mut results = Vec::new();
 
     for test_name in &test_names {
-        let result = run_single_test(project_root, &test_source, test_name);
+        let result = run_single_test(project_root, &test_source, test_name, render, scripting);
         let status = if result.passed { "OK" } else { "FAIL" };
         println!(
             "  {} {} ({:.1}s game time)",
@@ -449,11 +617,29 @@ pub fn run_test_file(project_root: &Path, test_file: &Path) -> Vec<TestResult> {
 }
 
 /// Run a single test function in an isolated TestRunner.
-fn run_single_test(project_root: &Path, test_source: &str, test_name: &str) -> TestResult {
+fn run_single_test(
+    project_root: &Path,
+    test_source: &str,
+    test_name: &str,
+    render: Option<&RenderOptions>,
+    scripting: &crate::project_config::ScriptingConfig,
+) -> TestResult {
     // Each test gets a fresh TestRunner, wrapped in Rc<RefCell<>> for safe sharing with Lua closures
     let runner = Rc::new(RefCell::new(TestRunner::new(project_root)));
+    runner.borrow_mut().scripting = *scripting;
     let start_time = std::time::Instant::now();
 
+    if let Some(options) = render {
+        if let Err(e) = runner.borrow_mut().enable_rendering(options) {
+            return TestResult {
+                name: test_name.to_string(),
+                passed: false,
+                error: Some(format!("Headless renderer: {}", e)),
+                game_time: 0.0,
+            };
+        }
+    }
+
     // Create the test Lua VM with the test API
     let test_lua = Lua::new();
 
@@ -489,6 +675,13 @@ fn run_single_test(project_root: &Path, test_source: &str, test_name: &str) -> T
     let game_time = runner.borrow().total_time;
     let _elapsed = start_time.elapsed();
 
+    // GPU errors fail an otherwise passing test
+    let render_error = runner.borrow_mut().render_error.take();
+    let result = match (result, render_error) {
+        (Ok(()), Some(e)) => Err(LuaError::RuntimeError(e)),
+        (result, _) => result,
+    };
+
     match result {
         Ok(()) => TestResult {
             name: test_name.to_string(),
@@ -2,23 +2,27 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 
-/// Handle to a cached texture bind group.
+/// Handle to a cached texture.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TextureHandle(pub usize);
 
-/// Cache of loaded textures from disk (PNG, JPG, WEBP).
-/// Each entry is a wgpu bind group (texture view + sampler) matching the
-/// texture bind group layout (group 2).
+/// Cache of loaded textures from disk (PNG, JPG, WEBP), keyed by path and
+/// color space: color maps (albedo, emissive) are sampled as sRGB, data maps
+/// (normal, metallic-roughness) as linear. Materials combine the cached views
+/// into their group 2 bind group (see `TextureResources::create_bind_group`).
 pub struct TextureCache {
-    bind_groups: Vec<wgpu::BindGroup>,
-    path_to_handle: HashMap<PathBuf, TextureHandle>,
+    views: Vec<wgpu::TextureView>,
+    path_to_handle: HashMap<(PathBuf, bool), TextureHandle>,
+    /// Mod folders searched before the project for texture files.
+    pub overlay: crate::mods::ModOverlay,
 }
 
 impl TextureCache {
     pub fn new() -> Self {
         Self {
-            bind_groups: Vec::new(),
+            views: Vec::new(),
             path_to_handle: HashMap::new(),
+            overlay: Default::default(),
         }
     }
 
@@ -27,16 +31,16 @@ impl TextureCache {
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        layout: &wgpu::BindGroupLayout,
         project_root: &Path,
         texture_path: &str,
+        srgb: bool,
     ) -> Result<TextureHandle, String> {
-        let key = PathBuf::from(texture_path);
+        let key = (PathBuf::from(texture_path), srgb);
         if let Some(&handle) = self.path_to_handle.get(&key) {
             return Ok(handle);
         }
 
-        let full_path = project_root.join(texture_path);
+        let full_path = self.overlay.resolve(project_root, texture_path);
         let img = image::open(&full_path)
             .map_err(|e| format!("Failed to load texture '{}': {}", full_path.display(), e))?
             .to_rgba8();
@@ -44,41 +48,54 @@ impl TextureCache {
         let (width, height) = img.dimensions();
         let pixels = img.into_raw();
 
-        let bind_group = create_texture_bind_group_from_rgba(
-            device, queue, layout, &pixels, width, height,
+        let view = create_texture_view_from_rgba(
+            device, queue, &pixels, width, height, srgb,
             &format!("Texture: {}", texture_path),
         );
 
-        let handle = TextureHandle(self.bind_groups.len());
-        self.bind_groups.push(bind_group);
+        let handle = TextureHandle(self.views.len());
+        self.views.push(view);
         self.path_to_handle.insert(key, handle);
         tracing::info!("Loaded texture: {} ({}x{})", texture_path, width, height);
         Ok(handle)
     }
 
-    /// Get the bind group for a texture handle.
-    pub fn get(&self, handle: TextureHandle) -> &wgpu::BindGroup {
-        &self.bind_groups[handle.0]
+    /// Paths of the loaded textures, each with whether it is sampled as sRGB.
+    pub fn keys(&self) -> impl Iterator<Item = (PathBuf, bool)> + '_ {
+        self.path_to_handle.keys().cloned()
     }
+
+    /// Get the texture view for a texture handle.
+    pub fn get(&self, handle: TextureHandle) -> &wgpu::TextureView {
+        &self.views[handle.0]
+    }
+}
+
+/// Bytes an image file takes once the cache uploads it (RGBA8, one mip),
+/// read from the file's header.
+pub fn texture_bytes(path: &Path) -> Result<u64, String> {
+    let (width, height) = image::image_dimensions(path)
+        .map_err(|e| format!("Failed to read texture '{}': {}", path.display(), e))?;
+    Ok(width as u64 * height as u64 * 4)
 }
 
-/// Create a texture bind group from raw RGBA8 pixel data.
-pub fn create_texture_bind_group_from_rgba(
+/// Upload raw RGBA8 pixel data as a texture, sampled as sRGB or linear.
+pub fn create_texture_view_from_rgba(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
-    layout: &wgpu::BindGroupLayout,
     pixels: &[u8],
     width: u32,
     height: u32,
+    srgb: bool,
     label: &str,
-) -> wgpu::BindGroup {
+) -> wgpu::TextureView {
     let texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some(label),
         size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
         mip_level_count: 1,
         sample_count: 1,
         dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        format: if srgb { wgpu::TextureFormat::Rgba8UnormSrgb } else { wgpu::TextureFormat::Rgba8Unorm },
         usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
         view_formats: &[],
     });
@@ -99,22 +116,5 @@ pub fn create_texture_bind_group_from_rgba(
         wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
     );
 
-    let view = texture.create_view(&Default::default());
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        mipmap_filter: wgpu::FilterMode::Linear,
-        address_mode_u: wgpu::AddressMode::Repeat,
-        address_mode_v: wgpu::AddressMode::Repeat,
-        ..Default::default()
-    });
-
-    device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some(&format!("{} BG", label)),
-        layout,
-        entries: &[
-            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
-            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
-        ],
-    })
+    texture.create_view(&Default::default())
 }
@@ -0,0 +1,191 @@
+//! Secondary windows for tooling: an entity inspector and a frame profiler,
+//! opened from the command socket next to the game window.
+//!
+//! Each tool window has its own surface and UI renderer on the engine's
+//! device. winit routes its events here by window id; it redraws whenever the
+//! game window does, from the state the engine hands it as text lines.
+
+use std::sync::Arc;
+
+use winit::event_loop::ActiveEventLoop;
+use winit::window::Window;
+
+use crate::font::BitmapFont;
+use crate::renderer::GpuState;
+use crate::ui::UiRenderer;
+
+const BACKGROUND: wgpu::Color = wgpu::Color { r: 0.06, g: 0.06, b: 0.08, a: 1.0 };
+const TEXT_SIZE: f32 = 14.0;
+const LINE_HEIGHT: f32 = 18.0;
+const MARGIN: f32 = 10.0;
+
+/// What a tool window shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolWindowKind {
+    /// Entities of the scene with their positions.
+    Inspector,
+    /// Frame time, draw stats and per-pass GPU timings.
+    Profiler,
+}
+
+impl ToolWindowKind {
+    pub const ALL: [ToolWindowKind; 2] = [ToolWindowKind::Inspector, ToolWindowKind::Profiler];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| format!("unknown window '{}' (expected inspector or profiler)", name))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToolWindowKind::Inspector => "inspector",
+            ToolWindowKind::Profiler => "profiler",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ToolWindowKind::Inspector => "Inspector",
+            ToolWindowKind::Profiler => "Profiler",
+        }
+    }
+}
+
+/// One line of tool window text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolLine {
+    pub text: String,
+    pub color: [f32; 4],
+}
+
+impl ToolLine {
+    pub fn new(text: impl Into<String>, color: [f32; 4]) -> Self {
+        Self { text: text.into(), color }
+    }
+}
+
+/// The lines that fit in `height` pixels; when some don't, the last visible
+/// line says how many were cut.
+pub fn fit_lines(lines: &[ToolLine], height: f32) -> Vec<ToolLine> {
+    let rows = ((height - 2.0 * MARGIN) / LINE_HEIGHT).floor().max(1.0) as usize;
+    if lines.len() <= rows {
+        return lines.to_vec();
+    }
+    let shown = rows - 1;
+    let mut fitted = lines[..shown].to_vec();
+    fitted.push(ToolLine::new(format!("... {} more", lines.len() - shown), [0.7, 0.7, 0.7, 1.0]));
+    fitted
+}
+
+pub struct ToolWindow {
+    pub kind: ToolWindowKind,
+    pub window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    ui: UiRenderer,
+}
+
+impl ToolWindow {
+    /// Open a window on the game window's device, with its surface format.
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        gpu: &GpuState,
+        font: &BitmapFont,
+        kind: ToolWindowKind,
+        title: &str,
+    ) -> Result<Self, String> {
+        let attrs = Window::default_attributes()
+            .with_title(title)
+            .with_inner_size(winit::dpi::LogicalSize::new(420, 560));
+        let window = Arc::new(event_loop.create_window(attrs).map_err(|e| e.to_string())?);
+        let surface = gpu.instance.create_surface(Arc::clone(&window)).map_err(|e| e.to_string())?;
+
+        let size = window.inner_size();
+        let config = wgpu::SurfaceConfiguration {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            // Fifo is the one mode every surface supports
+            present_mode: wgpu::PresentMode::Fifo,
+            ..gpu.config.clone()
+        };
+        surface.configure(&gpu.device, &config);
+        let ui = UiRenderer::new(&gpu.device, config.format, font);
+
+        Ok(Self { kind, window, surface, config, ui })
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.config.width = width;
+            self.config.height = height;
+            self.surface.configure(device, &self.config);
+        }
+    }
+
+    /// Draw `lines` top to bottom over a cleared background.
+    pub fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, font: &BitmapFont, lines: &[ToolLine]) {
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(device, &self.config);
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("{} window: {}", self.kind.title(), e);
+                return;
+            }
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Tool Window Encoder"),
+        });
+        {
+            let _clear = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Tool Window Clear"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(BACKGROUND),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                ..Default::default()
+            });
+        }
+
+        let (width, height) = (self.config.width, self.config.height);
+        let mut y = MARGIN;
+        for line in fit_lines(lines, height as f32) {
+            self.ui.draw_text(MARGIN, y, &line.text, TEXT_SIZE, line.color, font);
+            y += LINE_HEIGHT;
+        }
+        self.ui.render(device, queue, &mut encoder, &view, font, width, height, 0.0);
+
+        queue.submit(Some(encoder.finish()));
+        frame.present();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_windows() {
+        assert_eq!(ToolWindowKind::parse("profiler").unwrap(), ToolWindowKind::Profiler);
+        assert!(ToolWindowKind::parse("camera").unwrap_err().contains("inspector or profiler"));
+
+        let lines: Vec<ToolLine> = (0..10).map(|i| ToolLine::new(format!("line {}", i), [1.0; 4])).collect();
+        // Room for all ten
+        assert_eq!(fit_lines(&lines, 2.0 * MARGIN + 10.0 * LINE_HEIGHT), lines);
+        // Room for four: three lines and a count of the rest
+        let fitted = fit_lines(&lines, 2.0 * MARGIN + 4.5 * LINE_HEIGHT);
+        assert_eq!(fitted.len(), 4);
+        assert_eq!(fitted[2].text, "line 2");
+        assert_eq!(fitted[3].text, "... 7 more");
+    }
+}
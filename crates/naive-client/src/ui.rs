@@ -1,7 +1,7 @@
 /// Immediate-mode 2D overlay renderer for text, rectangles, and screen effects.
 /// Draws on top of the 3D scene using LoadOp::Load to preserve the existing framebuffer.
 
-use crate::font::{self, BitmapFont};
+use crate::font::{self, BitmapFont, TextRendering};
 
 // ── Vertex ──────────────────────────────────────────────────────────
 
@@ -92,6 +92,41 @@ struct VOut {
 }
 "#;
 
+/// Distance field text: alpha 0.5 is the outline, antialiased over one
+/// screen pixel whatever the text size.
+const SDF_FS_WGSL: &str = r#"
+@fragment fn fs(v: VOut) -> @location(0) vec4<f32> {
+    let d = textureSample(font_tex, font_smp, v.uv).a - 0.5;
+    let coverage = clamp(d / max(fwidth(d), 1e-4) + 0.5, 0.0, 1.0);
+    return vec4<f32>(v.col.rgb, v.col.a * coverage);
+}
+"#;
+
+/// Distance field text with a coverage per RGB subpixel (horizontal stripes
+/// of a third of a pixel), blended per channel through the second source.
+const SUBPIXEL_FS_WGSL: &str = r#"
+struct FOut {
+    @location(0) @blend_src(0) color: vec4<f32>,
+    @location(0) @blend_src(1) coverage: vec4<f32>,
+};
+
+@fragment fn fs(v: VOut) -> FOut {
+    let third = dpdx(v.uv) / 3.0;
+    let d = vec3<f32>(
+        textureSample(font_tex, font_smp, v.uv - third).a,
+        textureSample(font_tex, font_smp, v.uv).a,
+        textureSample(font_tex, font_smp, v.uv + third).a,
+    ) - vec3<f32>(0.5);
+    let width = max(fwidth(d.g), 1e-4);
+    let coverage = clamp(d / width + vec3<f32>(0.5), vec3<f32>(0.0), vec3<f32>(1.0)) * v.col.a;
+    let alpha = max(coverage.r, max(coverage.g, coverage.b));
+    var o: FOut;
+    o.color = vec4<f32>(v.col.rgb * coverage, alpha);
+    o.coverage = vec4<f32>(coverage, alpha);
+    return o;
+}
+"#;
+
 // ── Constants ───────────────────────────────────────────────────────
 
 const MAX_QUADS: usize = 4096;
@@ -118,6 +153,42 @@ pub struct UiRenderer {
     flash_duration: f32,
 }
 
+/// Per-channel blend for subpixel text: dst * (1 - coverage) + premultiplied color.
+fn subpixel_blend_state() -> wgpu::BlendState {
+    wgpu::BlendState {
+        color: wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::OneMinusSrc1,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha: wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+    }
+}
+
+/// Fragment shader and blend state of the text pipeline for the font's
+/// rendering mode. Subpixel text falls back to plain distance field text
+/// without dual-source blending.
+fn text_shader(device: &wgpu::Device, rendering: TextRendering) -> (String, wgpu::BlendState) {
+    let vertex = &TEXTURED_WGSL[..TEXTURED_WGSL.find("@fragment").unwrap_or(TEXTURED_WGSL.len())];
+    match rendering {
+        TextRendering::Bitmap => (TEXTURED_WGSL.to_string(), alpha_blend_state()),
+        TextRendering::Subpixel if device.features().contains(wgpu::Features::DUAL_SOURCE_BLENDING) => (
+            format!("enable dual_source_blending;\n{}{}", vertex, SUBPIXEL_FS_WGSL),
+            subpixel_blend_state(),
+        ),
+        TextRendering::Subpixel | TextRendering::Sdf => {
+            if rendering == TextRendering::Subpixel {
+                tracing::info!("No dual-source blending on this GPU; subpixel text is drawn as sdf");
+            }
+            (format!("{}{}", vertex, SDF_FS_WGSL), alpha_blend_state())
+        }
+    }
+}
+
 fn alpha_blend_state() -> wgpu::BlendState {
     wgpu::BlendState {
         color: wgpu::BlendComponent {
@@ -213,9 +284,10 @@ impl UiRenderer {
 
         // Textured pipeline (group 0 = projection, group 1 = font atlas)
         let textured_pipeline = {
+            let (source, blend) = text_shader(device, font.rendering);
             let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: Some("UI Textured Shader"),
-                source: wgpu::ShaderSource::Wgsl(TEXTURED_WGSL.into()),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
             });
             let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("UI Textured PL"),
@@ -236,7 +308,7 @@ impl UiRenderer {
                     entry_point: Some("fs"),
                     targets: &[Some(wgpu::ColorTargetState {
                         format: surface_format,
-                        blend: Some(alpha_blend_state()),
+                        blend: Some(blend),
                         write_mask: wgpu::ColorWrites::ALL,
                     })],
                     compilation_options: Default::default(),
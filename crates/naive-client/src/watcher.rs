@@ -13,6 +13,12 @@ pub enum WatchEvent {
     PipelineChanged(PathBuf),
     SplatChanged(PathBuf),
     ScriptChanged(PathBuf),
+    /// The project's naive.yaml.
+    ProjectConfigChanged(PathBuf),
+    /// A YAML file in `input/`; the engine only reloads its bindings file.
+    BindingsChanged(PathBuf),
+    /// A curve asset in `curves/`.
+    CurveChanged(PathBuf),
 }
 
 /// Creates a file watcher on the project directory and returns a receiver
@@ -37,7 +43,22 @@ pub fn start_watching_all(
                                     let _ = tx.send(WatchEvent::ShaderChanged(path.clone()));
                                 }
                                 "yaml" | "yml" => {
-                                    if path_str.contains("scenes") {
+                                    let file_name = path.file_name().unwrap_or_default();
+                                    let parent_name = path
+                                        .parent()
+                                        .and_then(|p| p.file_name())
+                                        .unwrap_or_default();
+                                    if file_name == "naive.yaml" {
+                                        tracing::info!("Project config changed: {:?}", path);
+                                        let _ = tx
+                                            .send(WatchEvent::ProjectConfigChanged(path.clone()));
+                                    } else if parent_name == "curves" {
+                                        tracing::info!("Curve file changed: {:?}", path);
+                                        let _ = tx.send(WatchEvent::CurveChanged(path.clone()));
+                                    } else if parent_name == "input" {
+                                        tracing::info!("Input bindings changed: {:?}", path);
+                                        let _ = tx.send(WatchEvent::BindingsChanged(path.clone()));
+                                    } else if path_str.contains("scenes") {
                                         tracing::info!("Scene file changed: {:?}", path);
                                         let _ = tx.send(WatchEvent::SceneChanged(path.clone()));
                                     } else if path_str.contains("materials") {
@@ -50,7 +71,7 @@ pub fn start_watching_all(
                                             tx.send(WatchEvent::PipelineChanged(path.clone()));
                                     }
                                 }
-                                "ply" => {
+                                "ply" | "splat" | "ksplat" | "spz" | "nsplat" => {
                                     tracing::info!("Splat file changed: {:?}", path);
                                     let _ = tx.send(WatchEvent::SplatChanged(path.clone()));
                                 }
@@ -70,22 +91,59 @@ pub fn start_watching_all(
             }
         })?;
 
-    // Watch shaders, scenes, materials, and pipelines directories
-    let dirs = [
+    for dir in &watched_dirs(project_root) {
+        if dir.exists() {
+            watcher.watch(dir, RecursiveMode::Recursive)?;
+            tracing::info!("File watcher started on: {:?}", dir);
+        }
+    }
+
+    // naive.yaml sits in the project root; a non-recursive watch catches it
+    // without seeing every file in the project
+    if project_root.join("naive.yaml").exists() {
+        watcher.watch(project_root, RecursiveMode::NonRecursive)?;
+    }
+
+    Ok((watcher, rx))
+}
+
+/// Directories watched recursively: shaders, scenes, materials, splats,
+/// pipelines, scripts, input bindings and curves.
+fn watched_dirs(project_root: &Path) -> [PathBuf; 8] {
+    [
         project_root.join("shaders"),
         project_root.join("scenes"),
         project_root.join("assets/materials"),
         project_root.join("assets/splats"),
         project_root.join("pipelines"),
         project_root.join("logic"),
-    ];
+        project_root.join("input"),
+        project_root.join(crate::curves::CURVES_DIR),
+    ]
+}
 
-    for dir in &dirs {
-        if dir.exists() {
-            watcher.watch(dir, RecursiveMode::Recursive)?;
-            tracing::info!("File watcher started on: {:?}", dir);
+/// Also watch the directories of shader includes that live outside the
+/// watched directories. `extra` holds the directories added so far.
+pub fn watch_includes<'a>(
+    watcher: &mut RecommendedWatcher,
+    project_root: &Path,
+    includes: impl IntoIterator<Item = &'a PathBuf>,
+    extra: &mut std::collections::HashSet<PathBuf>,
+) {
+    let dirs = watched_dirs(project_root);
+    for include in includes {
+        let Some(dir) = include.parent() else {
+            continue;
+        };
+        if dirs.iter().any(|d| include.starts_with(d)) || extra.contains(dir) {
+            continue;
+        }
+        match watcher.watch(dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                tracing::info!("File watcher started on shader include directory: {:?}", dir);
+                extra.insert(dir.to_path_buf());
+            }
+            Err(e) => tracing::warn!("Can't watch shader include directory {:?}: {:?}", dir, e),
         }
     }
-
-    Ok((watcher, rx))
 }
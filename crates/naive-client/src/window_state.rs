@@ -0,0 +1,140 @@
+//! Window visibility and swapchain health.
+//!
+//! `WindowActivity` tracks whether the window is minimized, hidden or
+//! unfocused, which decides when the engine suspends rendering (see
+//! `window.background` in naive.yaml). `SurfaceRecovery` picks the reaction
+//! to swapchain acquisition errors: reconfigure, recreate the surface after
+//! repeated losses, and keep the log quiet while a driver keeps failing.
+
+use winit::event::WindowEvent;
+
+/// Consecutive Lost/Outdated frames before the surface is recreated instead
+/// of reconfigured.
+const RECREATE_AFTER: u32 = 3;
+/// While failures continue, log one warning per this many frames.
+const LOG_EVERY: u32 = 120;
+
+/// Window state that can suspend rendering.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowActivity {
+    pub minimized: bool,
+    pub occluded: bool,
+    pub focused: bool,
+    /// The Steam overlay is open. It draws into the game's frames and takes
+    /// focus, so rendering carries on until the window is minimized.
+    pub overlay: bool,
+}
+
+impl Default for WindowActivity {
+    fn default() -> Self {
+        Self { minimized: false, occluded: false, focused: true, overlay: false }
+    }
+}
+
+impl WindowActivity {
+    /// Update from a window event. Returns true if anything changed.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        let before = *self;
+        match event {
+            WindowEvent::Resized(size) => self.minimized = size.width == 0 || size.height == 0,
+            WindowEvent::Occluded(occluded) => self.occluded = *occluded,
+            WindowEvent::Focused(focused) => self.focused = *focused,
+            _ => {}
+        }
+        *self != before
+    }
+
+    /// Why rendering is suspended, or None while the window is visible
+    /// (and focused, when `suspend_unfocused` is set).
+    pub fn suspend_reason(&self, suspend_unfocused: bool) -> Option<&'static str> {
+        if self.minimized {
+            Some("minimized")
+        } else if self.overlay {
+            None
+        } else if self.occluded {
+            Some("occluded")
+        } else if suspend_unfocused && !self.focused {
+            Some("unfocused")
+        } else {
+            None
+        }
+    }
+}
+
+/// What to do after `get_current_texture` fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceAction {
+    /// Skip this frame and try again next frame.
+    Skip,
+    /// Reconfigure the existing surface.
+    Reconfigure,
+    /// Create a new surface for the window.
+    Recreate,
+}
+
+/// Counts consecutive swapchain failures.
+#[derive(Debug, Default)]
+pub struct SurfaceRecovery {
+    failures: u32,
+}
+
+impl SurfaceRecovery {
+    /// A frame was acquired.
+    pub fn succeeded(&mut self) {
+        if self.failures > 0 {
+            tracing::info!("Surface recovered after {} failed frame(s)", self.failures);
+            self.failures = 0;
+        }
+    }
+
+    /// A frame could not be acquired.
+    pub fn failed(&mut self, error: &wgpu::SurfaceError) -> SurfaceAction {
+        self.failures += 1;
+        if self.failures == 1 || self.failures.is_multiple_of(LOG_EVERY) {
+            tracing::warn!("Surface error ({} frame(s) in a row): {:?}", self.failures, error);
+        }
+        match error {
+            wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated if self.failures >= RECREATE_AFTER => {
+                SurfaceAction::Recreate
+            }
+            wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => SurfaceAction::Reconfigure,
+            _ => SurfaceAction::Skip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_activity_suspend_reasons() {
+        let mut activity = WindowActivity::default();
+        assert_eq!(activity.suspend_reason(true), None);
+
+        assert!(activity.handle_event(&WindowEvent::Focused(false)));
+        assert!(!activity.handle_event(&WindowEvent::Focused(false)));
+        assert_eq!(activity.suspend_reason(false), None);
+        assert_eq!(activity.suspend_reason(true), Some("unfocused"));
+
+        activity.handle_event(&WindowEvent::Resized(winit::dpi::PhysicalSize::new(0, 0)));
+        assert_eq!(activity.suspend_reason(false), Some("minimized"));
+        activity.handle_event(&WindowEvent::Resized(winit::dpi::PhysicalSize::new(1280, 720)));
+        activity.handle_event(&WindowEvent::Occluded(true));
+        assert_eq!(activity.suspend_reason(false), Some("occluded"));
+
+        // The Steam overlay keeps an unfocused window drawing
+        activity.overlay = true;
+        assert_eq!(activity.suspend_reason(true), None);
+    }
+
+    #[test]
+    fn test_surface_recovery_escalates_to_recreate() {
+        let mut recovery = SurfaceRecovery::default();
+        assert_eq!(recovery.failed(&wgpu::SurfaceError::Timeout), SurfaceAction::Skip);
+        assert_eq!(recovery.failed(&wgpu::SurfaceError::Lost), SurfaceAction::Reconfigure);
+        assert_eq!(recovery.failed(&wgpu::SurfaceError::Outdated), SurfaceAction::Recreate);
+        recovery.succeeded();
+        assert_eq!(recovery.failed(&wgpu::SurfaceError::Lost), SurfaceAction::Reconfigure);
+    }
+}
@@ -19,6 +19,8 @@ pub enum PoolOp {
 #[derive(Default)]
 pub struct EntityCommandQueue {
     pub spawns: Vec<SpawnCommand>,
+    /// Full scene-format entity definitions (`entity.spawn_ex` with `components`).
+    pub entity_spawns: Vec<EntityDef>,
     pub destroys: Vec<String>,
     pub scale_updates: Vec<(String, [f32; 3])>,
     pub visibility_updates: Vec<(String, bool)>,
@@ -30,22 +32,30 @@ pub struct EntityCommandQueue {
     pub pending_scene_load: Option<String>,
     pub texture_swaps: Vec<TextureSwapCommand>,
     pub mesh_creates: Vec<MeshCreateCommand>,
+    pub collider_updates: Vec<ColliderUpdateCommand>,
 }
 
-/// Deferred texture swap: change an entity's albedo or normal map at runtime.
+/// Deferred collider rebuild (`physics.set_collider`). Trigger flag,
+/// restitution and friction keep their current values when None.
+pub struct ColliderUpdateCommand {
+    pub entity_id: String,
+    pub collider: crate::scene::ColliderDef,
+    pub is_trigger: Option<bool>,
+    pub restitution: Option<f32>,
+    pub friction: Option<f32>,
+}
+
+/// Deferred texture swap: change one of an entity material's texture maps at runtime.
 pub struct TextureSwapCommand {
     pub entity_id: String,
     pub slot: String,
     pub texture_path: String,
 }
 
-/// Deferred mesh creation from Lua vertex/index data.
+/// Deferred runtime mesh creation or update (`mesh.create`).
 pub struct MeshCreateCommand {
     pub name: String,
-    pub positions: Vec<[f32; 3]>,
-    pub normals: Vec<[f32; 3]>,
-    pub uvs: Vec<[f32; 2]>,
-    pub indices: Vec<u32>,
+    pub data: crate::mesh::MeshData,
 }
 
 pub struct SpawnCommand {
@@ -104,6 +114,7 @@ impl EntityCommandQueue {
 
     pub fn clear(&mut self) {
         self.spawns.clear();
+        self.entity_spawns.clear();
         self.destroys.clear();
         self.scale_updates.clear();
         self.visibility_updates.clear();
@@ -113,6 +124,7 @@ impl EntityCommandQueue {
         self.pending_scene_load = None;
         self.texture_swaps.clear();
         self.mesh_creates.clear();
+        self.collider_updates.clear();
     }
 }
 
@@ -172,6 +184,62 @@ pub fn spawn_runtime_entity(
     true
 }
 
+/// Spawn a runtime entity from a full scene-format definition, so it gets
+/// every component a scene entity can have. `extends` resolves against the
+/// entities of the current scene. A `script` component is attached as an
+/// uninitialized `Script`; loading it is up to the caller, which owns the
+/// script runtime.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_entity_from_def(
+    scene_world: &mut SceneWorld,
+    entity_def: &EntityDef,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    project_root: &Path,
+    mesh_cache: &mut MeshCache,
+    material_cache: &mut MaterialCache,
+    splat_cache: &mut SplatCache,
+    physics_world: Option<&mut PhysicsWorld>,
+    texture_resources: Option<&crate::mesh::TextureResources>,
+    texture_cache: Option<&mut crate::texture_cache::TextureCache>,
+) -> Result<hecs::Entity, String> {
+    if scene_world.entity_registry.contains_key(&entity_def.id) {
+        return Err(format!("Entity '{}' already exists", entity_def.id));
+    }
+    let resolved = match &entity_def.extends {
+        Some(parent_id) => {
+            let parent = scene_world
+                .current_scene
+                .as_ref()
+                .and_then(|scene| scene.entities.iter().find(|e| &e.id == parent_id))
+                .ok_or_else(|| format!("Entity '{}' extends unknown entity '{}'", entity_def.id, parent_id))?;
+            crate::scene::merge_entity(parent, entity_def)
+        }
+        None => entity_def.clone(),
+    };
+    for name in resolved.components.extra.keys() {
+        tracing::warn!("Entity '{}': ignoring unknown component '{}'", resolved.id, name);
+    }
+
+    spawn_entity(
+        scene_world, &resolved, device, queue, project_root, mesh_cache, material_cache, splat_cache,
+        physics_world, texture_resources, texture_cache,
+    );
+    let entity = scene_world
+        .entity_registry
+        .get(&resolved.id)
+        .copied()
+        .ok_or_else(|| format!("Failed to spawn entity '{}' (see log)", resolved.id))?;
+    if let Some(script_def) = &resolved.components.script {
+        let script = crate::scripting::Script {
+            source: std::path::PathBuf::from(&script_def.source),
+            initialized: false,
+        };
+        let _ = scene_world.world.insert_one(entity, script);
+    }
+    Ok(entity)
+}
+
 /// Destroy a runtime entity by its string ID.
 pub fn destroy_runtime_entity(scene_world: &mut SceneWorld, id: &str) -> bool {
     if let Some(entity) = scene_world.entity_registry.remove(id) {
@@ -266,14 +334,20 @@ fn spawn_entity(
 
     // Handle gaussian splat entities
     if let Some(gs) = &entity_def.components.gaussian_splat {
-        let splat_handle = match splat_cache.get_or_load(device, project_root, &gs.source) {
+        let splat_handle = match splat_cache.get_or_load(device, queue, project_root, &gs.source) {
             Ok(h) => h,
             Err(e) => {
                 tracing::error!("Failed to load splat '{}' for entity '{}': {}", gs.source, entity_def.id, e);
                 return;
             }
         };
-        let gaussian_splat = GaussianSplat { splat_handle };
+        let gaussian_splat = GaussianSplat {
+            splat_handle,
+            lod_distance: gs.lod_distance,
+            min_detail: gs.min_detail,
+            opacity: gs.opacity,
+            crops: scene_splat_crops(&entity_def.id, &gs.crop),
+        };
         let entity = scene_world.world.spawn((entity_id, tags, transform, gaussian_splat));
         scene_world.entity_registry.insert(entity_def.id.clone(), entity);
         return;
@@ -289,8 +363,7 @@ fn spawn_entity(
                 return;
             }
         };
-        let tex_layout = texture_resources.map(|tr| &tr.bind_group_layout);
-        let material_handle = match material_cache.get_or_load(device, queue, project_root, &mr.material, texture_cache, tex_layout) {
+        let material_handle = match material_cache.get_or_load(device, queue, project_root, &mr.material, texture_cache, texture_resources) {
             Ok(h) => h,
             Err(e) => {
                 tracing::error!("Failed to load material '{}' for entity '{}': {}", mr.material, entity_def.id, e);
@@ -303,17 +376,7 @@ fn spawn_entity(
         };
 
         if let Some(cam) = &entity_def.components.camera {
-            let camera = Camera {
-                fov_degrees: cam.fov,
-                near: cam.near,
-                far: cam.far,
-                role: if cam.role == "main" {
-                    CameraRole::Main
-                } else {
-                    CameraRole::Other(cam.role.clone())
-                },
-                aspect_ratio: 16.0 / 9.0,
-            };
+            let camera = camera_from_def(&entity_def.id, cam);
             scene_world
                 .world
                 .spawn((entity_id, tags, transform, mesh_renderer, camera))
@@ -322,6 +385,7 @@ fn spawn_entity(
                 color: glam::Vec3::from(pl.color),
                 intensity: pl.intensity,
                 range: pl.range,
+                casts_shadows: pl.casts_shadows,
             };
             scene_world
                 .world
@@ -332,17 +396,7 @@ fn spawn_entity(
                 .spawn((entity_id, tags, transform, mesh_renderer))
         }
     } else if let Some(cam) = &entity_def.components.camera {
-        let camera = Camera {
-            fov_degrees: cam.fov,
-            near: cam.near,
-            far: cam.far,
-            role: if cam.role == "main" {
-                CameraRole::Main
-            } else {
-                CameraRole::Other(cam.role.clone())
-            },
-            aspect_ratio: 16.0 / 9.0,
-        };
+        let camera = camera_from_def(&entity_def.id, cam);
         scene_world
             .world
             .spawn((entity_id, tags, transform, camera))
@@ -351,6 +405,7 @@ fn spawn_entity(
             color: glam::Vec3::from(pl.color),
             intensity: pl.intensity,
             range: pl.range,
+            casts_shadows: pl.casts_shadows,
         };
         scene_world
             .world
@@ -375,6 +430,35 @@ fn spawn_entity(
         .entity_registry
         .insert(entity_def.id.clone(), entity);
 
+    // Attach MeshLod component if the mesh renderer has LOD levels
+    if let Some(mr) = entity_def.components.mesh_renderer.as_ref().filter(|mr| mr.lod.is_some()) {
+        let base = scene_world.world.get::<&MeshRenderer>(entity).map(|r| r.mesh_handle).ok();
+        match base {
+            Some(base) if mesh_cache.has_skin(base) => {
+                tracing::warn!("Entity '{}': skinned meshes don't support lod levels", entity_def.id);
+            }
+            Some(base) => {
+                let bounds = mesh_cache.get(base).bounds;
+                let lod = crate::lod::MeshLod::from_def(mr.lod.as_ref().unwrap(), base, bounds, |level| {
+                    let loaded = match (&level.mesh, level.ratio) {
+                        (Some(mesh), _) => mesh_cache.get_or_load(device, queue, project_root, mesh, texture_resources),
+                        (None, ratio) => mesh_cache.get_or_load_decimated(
+                            device, queue, project_root, &mr.mesh, ratio.unwrap_or(1.0), texture_resources,
+                        ),
+                    };
+                    loaded.map_err(|e| e.to_string())
+                });
+                match lod {
+                    Ok(lod) => {
+                        let _ = scene_world.world.insert_one(entity, lod);
+                    }
+                    Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+                }
+            }
+            None => {}
+        }
+    }
+
     // Attach Health component if defined
     if let Some(health_def) = &entity_def.components.health {
         let health = crate::components::Health {
@@ -394,21 +478,99 @@ fn spawn_entity(
         let _ = scene_world.world.insert_one(entity, collision_damage);
     }
 
+    // Attach TimeScale component if defined
+    if let Some(ts_def) = &entity_def.components.time_scale {
+        let scale = crate::components::TimeScale(crate::time_scale::clamp_scale(ts_def.scale));
+        let _ = scene_world.world.insert_one(entity, scale);
+    }
+
+    // Attach Lifetime component if defined
+    if let Some(lt_def) = &entity_def.components.lifetime {
+        match crate::lifetime::Lifetime::from_def(lt_def) {
+            Ok(lifetime) => {
+                let _ = scene_world.world.insert_one(entity, lifetime);
+            }
+            Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+        }
+    }
+
+    // Attach FlockAgent component if defined
+    if let Some(flock_def) = &entity_def.components.flock {
+        let _ = scene_world.world.insert_one(entity, crate::crowd::FlockAgent::from_def(flock_def));
+    }
+
+    // Attach SpotLight component if defined
+    if let Some(spot_def) = &entity_def.components.spot_light {
+        let _ = scene_world.world.insert_one(entity, spot_light_from_def(spot_def));
+    }
+
+    // Attach LightFlicker component if the point light flickers
+    if let Some(pl) = &entity_def.components.point_light {
+        if let Some(flicker) = light_flicker_from_def(project_root, &entity_def.id, pl) {
+            let _ = scene_world.world.insert_one(entity, flicker);
+        }
+    }
+
+    // Attach Skybox component if defined
+    if let Some(sky_def) = &entity_def.components.skybox {
+        match skybox_from_def(sky_def) {
+            Ok(skybox) => {
+                let _ = scene_world.world.insert_one(entity, skybox);
+            }
+            Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+        }
+    }
+
+    // Attach Portal component if defined
+    if let Some(portal_def) = &entity_def.components.portal {
+        let _ = scene_world.world.insert_one(entity, portal_from_def(portal_def));
+    }
+
+    // Attach Water component if defined
+    if let Some(water_def) = &entity_def.components.water {
+        let mesh = match mesh_cache.get_or_load(device, queue, project_root, &water_def.mesh, None) {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                tracing::warn!("Entity '{}': water mesh '{}': {}", entity_def.id, water_def.mesh, e);
+                None
+            }
+        };
+        let _ = scene_world.world.insert_one(entity, water_from_def(water_def, mesh));
+    }
+
+    // Attach Foliage component if defined
+    if let Some(foliage_def) = &entity_def.components.foliage {
+        let mesh = match mesh_cache.get_or_load(device, queue, project_root, &foliage_def.mesh, None) {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                tracing::warn!("Entity '{}': foliage mesh '{}': {}", entity_def.id, foliage_def.mesh, e);
+                None
+            }
+        };
+        let _ = scene_world.world.insert_one(entity, foliage_from_def(foliage_def, mesh));
+    }
+
+    // Attach Terrain component if defined
+    if let Some(terrain_def) = &entity_def.components.terrain {
+        match terrain_from_def(terrain_def) {
+            Ok(terrain) => {
+                let _ = scene_world.world.insert_one(entity, terrain);
+            }
+            Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+        }
+    }
+
     // Attach ParticleEmitter component if defined
     if let Some(pe_def) = &entity_def.components.particle_emitter {
+        let config = match &pe_def.preset {
+            Some(name) => crate::particles::load_preset(project_root, name).unwrap_or_else(|e| {
+                tracing::warn!("Entity '{}': {}", entity_def.id, e);
+                particle_config_from_def(project_root, pe_def)
+            }),
+            None => particle_config_from_def(project_root, pe_def),
+        };
         let emitter = crate::components::ParticleEmitter {
-            config: crate::components::ParticleConfig {
-                max_particles: pe_def.max_particles,
-                spawn_rate: pe_def.spawn_rate,
-                lifetime: pe_def.lifetime,
-                initial_speed: pe_def.initial_speed,
-                direction: glam::Vec3::from(pe_def.direction),
-                spread: pe_def.spread,
-                size: pe_def.size,
-                color_start: pe_def.color_start,
-                color_end: pe_def.color_end,
-                gravity_scale: pe_def.gravity_scale,
-            },
+            config,
             enabled: pe_def.enabled,
         };
         let _ = scene_world.world.insert_one(entity, emitter);
@@ -423,8 +585,13 @@ fn spawn_entity(
                 height_offset: cam_def.height_offset,
                 pitch_min: pitch_limits[0].to_radians(),
                 pitch_max: pitch_limits[1].to_radians(),
+                position_damping: cam_def.position_damping.max(0.0),
+                rotation_damping: cam_def.rotation_damping.max(0.0),
+                look_ahead: cam_def.look_ahead.max(0.0),
+                look_ahead_max: cam_def.look_ahead_max.max(0.0),
             };
-            let _ = scene_world.world.insert_one(entity, camera_mode);
+            let follow = crate::camera_follow::CameraFollow::default();
+            let _ = scene_world.world.insert(entity, (camera_mode, follow));
         }
     }
 
@@ -464,6 +631,8 @@ fn spawn_entity(
                 sprint_multiplier: cc_def.sprint_multiplier,
                 jump_impulse: cc_def.jump_impulse,
                 step_height: cc_def.step_height,
+                push_force: cc_def.push_force,
+                mass: cc_def.mass,
                 ..Default::default()
             };
             let player = Player {
@@ -474,8 +643,24 @@ fn spawn_entity(
             };
             let _ = scene_world.world.insert(entity, (rb_comp, col_comp, cc_comp, player));
         } else if let Some(col_def) = &entity_def.components.collider {
-            let shape = parse_collider_shape(col_def);
+            let mut shape = parse_collider_shape(col_def);
             let is_trigger = col_def.is_trigger;
+            // Resolve trimesh from the entity's own mesh
+            if matches!(shape, PhysicsShape::Trimesh { .. }) {
+                let scale = entity_def.components.transform.as_ref()
+                    .map(|t| glam::Vec3::from(t.scale))
+                    .unwrap_or(glam::Vec3::ONE);
+                let mesh_handle = scene_world.world.get::<&MeshRenderer>(entity).ok().map(|mr| mr.mesh_handle);
+                if let Some(handle) = mesh_handle {
+                    if let Some((vertices, indices)) = mesh_cache.get_physics_trimesh(handle, scale) {
+                        shape = PhysicsShape::Trimesh { vertices, indices };
+                    } else {
+                        tracing::warn!("Trimesh collider for '{}': no mesh data, falling back to box", entity_def.id);
+                        let he = col_def.half_extents.unwrap_or([0.5, 0.5, 0.5]);
+                        shape = PhysicsShape::Box { half_extents: glam::Vec3::from(he) };
+                    }
+                }
+            }
 
             let body_type = entity_def
                 .components
@@ -483,6 +668,12 @@ fn spawn_entity(
                 .as_ref()
                 .map(|rb| rb.body_type.as_str())
                 .unwrap_or("static");
+            let interpolate = entity_def
+                .components
+                .rigid_body
+                .as_ref()
+                .map(|rb| rb.interpolate)
+                .unwrap_or(true);
 
             let restitution = col_def.restitution;
             let friction = col_def.friction;
@@ -503,6 +694,7 @@ fn spawn_entity(
                         .unwrap_or(false);
                     let (rb_handle, col_handle) =
                         pw.add_dynamic_body(entity, pos, rot, shape.clone(), mass, restitution, friction, ccd);
+                    pw.set_interpolated(rb_handle, interpolate);
                     let rb_comp = physics::RigidBody {
                         handle: rb_handle,
                         body_type: physics::PhysicsBodyType::Dynamic,
@@ -517,6 +709,7 @@ fn spawn_entity(
                 "kinematic" => {
                     let (rb_handle, col_handle) =
                         pw.add_kinematic_body(entity, pos, rot, shape.clone(), is_trigger, restitution, friction);
+                    pw.set_interpolated(rb_handle, interpolate);
                     let rb_comp = physics::RigidBody {
                         handle: rb_handle,
                         body_type: physics::PhysicsBodyType::Kinematic,
@@ -544,9 +737,316 @@ fn spawn_entity(
                 }
             }
         }
+        spawn_terrain_collider(scene_world, entity, entity_def, project_root, pw);
+    }
+}
+
+/// Build a Skybox from its scene definition: exactly one of `texture` and
+/// `cubemap` (with six faces) must be set.
+pub fn skybox_from_def(def: &crate::scene::SkyboxDef) -> Result<Skybox, String> {
+    let source = match (&def.texture, &def.cubemap) {
+        (Some(texture), None) => SkyboxSource::Equirect(texture.clone()),
+        (None, Some(faces)) => {
+            let faces: [String; 6] = faces.clone().try_into().map_err(|faces: Vec<String>| {
+                format!("skybox cubemap needs 6 faces (+X -X +Y -Y +Z -Z), got {}", faces.len())
+            })?;
+            SkyboxSource::Cubemap(faces)
+        }
+        (Some(_), Some(_)) => return Err("skybox sets both texture and cubemap".to_string()),
+        (None, None) => return Err("skybox needs a texture or a cubemap".to_string()),
+    };
+    Ok(Skybox { source, intensity: def.intensity.max(0.0) })
+}
+
+/// Build a Portal from its scene definition, clamping the view size and recursion.
+pub fn portal_from_def(def: &crate::scene::PortalDef) -> Portal {
+    Portal {
+        target: def.target.clone(),
+        resolution: def.resolution.clamp(0.1, 1.0),
+        max_recursion: if def.target.is_some() { def.max_recursion.clamp(1, 8) } else { 1 },
     }
 }
 
+/// Build a SpotLight component from its scene definition. The outer angle
+/// stays below 90 degrees and the inner angle within the outer one.
+pub fn spot_light_from_def(def: &crate::scene::SpotLightDef) -> SpotLight {
+    let outer_angle = def.outer_angle.clamp(0.1, 89.0);
+    SpotLight {
+        color: glam::Vec3::from(def.color),
+        intensity: def.intensity,
+        range: def.range,
+        direction: glam::Vec3::from(def.direction).try_normalize().unwrap_or(glam::Vec3::NEG_Y),
+        inner_angle: def.inner_angle.clamp(0.0, outer_angle),
+        outer_angle,
+    }
+}
+
+/// Build splat crop volumes from their definitions. Unknown shapes, and more
+/// volumes than the splat shader takes, are errors.
+pub fn splat_crops_from_defs(defs: &[crate::scene::SplatCropDef]) -> Result<Vec<SplatCrop>, String> {
+    if defs.len() > crate::splat::MAX_SPLAT_CROPS {
+        return Err(format!("{} crop volumes, at most {}", defs.len(), crate::splat::MAX_SPLAT_CROPS));
+    }
+    defs.iter()
+        .map(|def| {
+            let shape = match def.shape.as_str() {
+                "box" => SplatCropShape::Box,
+                "ellipsoid" => SplatCropShape::Ellipsoid,
+                other => return Err(format!("unknown crop shape '{}' (expected box or ellipsoid)", other)),
+            };
+            Ok(SplatCrop {
+                shape,
+                center: glam::Vec3::from(def.center),
+                half_extents: glam::Vec3::from(def.size).abs() * 0.5,
+                invert: def.invert,
+            })
+        })
+        .collect()
+}
+
+/// Crop volumes of a scene's splat entity; invalid ones leave it uncropped.
+fn scene_splat_crops(entity_id: &str, defs: &[crate::scene::SplatCropDef]) -> Vec<SplatCrop> {
+    splat_crops_from_defs(defs).unwrap_or_else(|e| {
+        tracing::warn!("Splat '{}': {}; drawing it uncropped", entity_id, e);
+        Vec::new()
+    })
+}
+
+/// Build a Water component from its scene definition. Unknown reflection
+/// modes fall back to screen-space.
+pub fn water_from_def(def: &crate::scene::WaterDef, mesh: Option<MeshHandle>) -> Water {
+    let reflection = match def.reflection.as_str() {
+        "sky" => WaterReflection::Sky,
+        "none" => WaterReflection::None,
+        "ssr" => WaterReflection::ScreenSpace,
+        other => {
+            tracing::warn!("Unknown water reflection '{}', using 'ssr'", other);
+            WaterReflection::ScreenSpace
+        }
+    };
+    Water {
+        mesh,
+        shallow_color: def.shallow_color,
+        deep_color: def.deep_color,
+        depth_fade: def.depth_fade.max(0.01),
+        shore_fade: def.shore_fade.max(0.01),
+        wave_scale: def.wave_scale.max(0.01),
+        wave_speed: def.wave_speed,
+        wave_strength: def.wave_strength.max(0.0),
+        refraction: def.refraction.max(0.0),
+        reflection,
+        buoyancy: def.buoyancy.max(0.0),
+        drag: def.drag.max(0.0),
+        depth: def.depth.max(0.0),
+    }
+}
+
+/// The flicker of a point light, with its curve resolved and looping.
+pub fn light_flicker_from_def(
+    project_root: &Path,
+    entity_id: &str,
+    def: &crate::scene::PointLightDef,
+) -> Option<crate::components::LightFlicker> {
+    let flicker = def.flicker.as_ref()?;
+    let what = format!("Entity '{}' flicker", entity_id);
+    let curve = crate::curves::resolve_field(project_root, Some(&flicker.curve), &what)?;
+    Some(crate::components::LightFlicker {
+        curve: crate::curves::looping(curve),
+        source: flicker.curve.clone(),
+        speed: flicker.speed,
+        base_intensity: def.intensity,
+        time: 0.0,
+    })
+}
+
+/// Build a particle emitter config from a scene emitter or preset
+/// definition, sorting the over-life curves by time and resolving curve
+/// assets against `project_root`.
+pub fn particle_config_from_def(project_root: &Path, def: &crate::scene::ParticleEmitterDef) -> ParticleConfig {
+    let mut color_over_life = def.color_over_life.clone();
+    color_over_life.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    let mut size_over_life = def.size_over_life.clone();
+    size_over_life.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    ParticleConfig {
+        max_particles: def.max_particles,
+        spawn_rate: def.spawn_rate,
+        lifetime: def.lifetime,
+        initial_speed: def.initial_speed,
+        direction: glam::Vec3::from(def.direction),
+        spread: def.spread,
+        size: def.size,
+        color_start: def.color_start,
+        color_end: def.color_end,
+        color_over_life,
+        size_over_life,
+        size_curve: crate::curves::resolve_field(project_root, def.size_curve.as_ref(), "Particle size_curve"),
+        alpha_curve: crate::curves::resolve_field(project_root, def.alpha_curve.as_ref(), "Particle alpha_curve"),
+        gravity_scale: def.gravity_scale,
+        blend: particle_blend_from_name(&def.blend),
+        softness: def.softness.max(0.0),
+        collision: particle_collision_from_name(&def.collision),
+        bounce: def.bounce.clamp(0.0, 1.0),
+    }
+}
+
+/// Build a Camera component from its scene definition. An unknown
+/// projection warns and falls back to perspective.
+pub fn camera_from_def(entity_id: &str, cam: &crate::scene::CameraDef) -> Camera {
+    let projection = Projection::from_name(&cam.projection, cam.size).unwrap_or_else(|| {
+        tracing::warn!("Entity '{}': unknown camera projection '{}', using 'perspective'", entity_id, cam.projection);
+        Projection::Perspective
+    });
+    Camera {
+        fov_degrees: cam.fov,
+        near: cam.near,
+        far: cam.far,
+        role: CameraRole::from_name(&cam.role),
+        aspect_ratio: 16.0 / 9.0,
+        projection,
+    }
+}
+
+/// Parse a particle collision mode. Unknown modes warn and collide with nothing.
+pub fn particle_collision_from_name(name: &str) -> ParticleCollision {
+    match name {
+        "none" => ParticleCollision::None,
+        "bounce" => ParticleCollision::Bounce,
+        "kill" => ParticleCollision::Kill,
+        other => {
+            tracing::warn!("Unknown particle collision '{}', using 'none'", other);
+            ParticleCollision::None
+        }
+    }
+}
+
+/// Parse a particle blend mode. Unknown modes warn and blend by alpha.
+pub fn particle_blend_from_name(name: &str) -> ParticleBlend {
+    match name {
+        "alpha" => ParticleBlend::Alpha,
+        "additive" => ParticleBlend::Additive,
+        other => {
+            tracing::warn!("Unknown particle blend '{}', using 'alpha'", other);
+            ParticleBlend::Alpha
+        }
+    }
+}
+
+/// Build a Foliage component from its scene definition, keeping the scale
+/// range ordered and the fade distances increasing.
+pub fn foliage_from_def(def: &crate::scene::FoliageDef, mesh: Option<MeshHandle>) -> Foliage {
+    let min_scale = def.scale[0].min(def.scale[1]).max(0.0);
+    let fade_end = def.fade_end.max(0.0);
+    Foliage {
+        mesh,
+        density: def.density.max(0.0),
+        density_map: def.density_map.clone(),
+        max_instances: def.max_instances,
+        seed: def.seed,
+        snap_to_ground: def.snap_to_ground,
+        surface: def.surface.clone(),
+        scale: [min_scale, def.scale[0].max(def.scale[1]).max(min_scale)],
+        base_color: def.base_color,
+        tip_color: def.tip_color,
+        wind_strength: def.wind_strength.max(0.0),
+        wind_speed: def.wind_speed,
+        wind_direction: def.wind_direction,
+        fade_start: def.fade_start.clamp(0.0, fade_end),
+        fade_end,
+    }
+}
+
+/// Build a Terrain component from its scene definition. Fails without a
+/// heightmap, with more layers than a splat map weights or with a grid too
+/// fine to build; the chunk resolution is rounded up to a power of two, the
+/// LOD distances are sorted and a terrain without layers gets a gray one.
+pub fn terrain_from_def(def: &crate::scene::TerrainDef) -> Result<Terrain, String> {
+    use crate::terrain::{MAX_CHUNK_RESOLUTION, MAX_GRID_RESOLUTION, MAX_LAYERS};
+
+    if def.heightmap.is_empty() {
+        return Err("terrain needs a heightmap".to_string());
+    }
+    if def.layers.len() > MAX_LAYERS {
+        return Err(format!("terrain has {} layers, at most {} are blended", def.layers.len(), MAX_LAYERS));
+    }
+    let chunks = def.chunks.max(1);
+    let chunk_resolution = def.chunk_resolution.clamp(2, MAX_CHUNK_RESOLUTION).next_power_of_two();
+    if chunks.saturating_mul(chunk_resolution) > MAX_GRID_RESOLUTION {
+        return Err(format!(
+            "terrain grid of {} chunks x {} quads is finer than {} quads per side",
+            chunks, chunk_resolution, MAX_GRID_RESOLUTION
+        ));
+    }
+    let mut lod_distances: Vec<f32> = def.lod_distances.iter().copied().filter(|d| *d > 0.0).collect();
+    lod_distances.sort_by(f32::total_cmp);
+    let layers = if def.layers.is_empty() {
+        vec![TerrainLayer { texture: None, color: [0.5, 0.5, 0.5], tile_size: 4.0, roughness: 0.9 }]
+    } else {
+        def.layers
+            .iter()
+            .map(|layer| TerrainLayer {
+                texture: layer.texture.clone(),
+                color: layer.color,
+                tile_size: layer.tile_size.max(0.01),
+                roughness: layer.roughness.clamp(0.0, 1.0),
+            })
+            .collect()
+    };
+    Ok(Terrain {
+        heightmap: def.heightmap.clone(),
+        size: [def.size[0].max(0.01), def.size[1].max(0.01)],
+        height: def.height,
+        chunks,
+        chunk_resolution,
+        lod_distances,
+        splat_map: def.splat_map.clone(),
+        layers,
+        collider: def.collider,
+        friction: def.friction.max(0.0),
+    })
+}
+
+/// Give a terrain entity a static heightfield body built from its
+/// full-detail grid, unless it turned `collider` off or has a collider or
+/// character controller of its own.
+fn spawn_terrain_collider(
+    scene_world: &mut SceneWorld,
+    entity: hecs::Entity,
+    entity_def: &EntityDef,
+    project_root: &Path,
+    physics_world: &mut PhysicsWorld,
+) {
+    if entity_def.components.collider.is_some() || entity_def.components.character_controller.is_some() {
+        return;
+    }
+    let Some(terrain) = scene_world.world.get::<&Terrain>(entity).ok().filter(|t| t.collider).map(|t| (*t).clone()) else {
+        return;
+    };
+    let heightmap = match crate::terrain::Heightmap::load(project_root, &terrain.heightmap) {
+        Ok(heightmap) => heightmap,
+        Err(e) => {
+            tracing::warn!("Entity '{}': terrain collider: {}", entity_def.id, e);
+            return;
+        }
+    };
+    let (pos, rot, scale) = match &entity_def.components.transform {
+        Some(t) => (glam::Vec3::from(t.position), euler_degrees_to_quat(t.rotation), glam::Vec3::from(t.scale)),
+        None => (glam::Vec3::ZERO, glam::Quat::IDENTITY, glam::Vec3::ONE),
+    };
+    let shape = crate::terrain::HeightGrid::new(&terrain, &heightmap).collider_shape(scale);
+    let (rb_handle, col_handle) =
+        physics_world.add_static_body(entity, pos, rot, shape.clone(), false, 0.0, terrain.friction);
+    let rb_comp = physics::RigidBody {
+        handle: rb_handle,
+        body_type: physics::PhysicsBodyType::Static,
+    };
+    let col_comp = physics::Collider {
+        handle: col_handle,
+        shape,
+        is_trigger: false,
+    };
+    let _ = scene_world.world.insert(entity, (rb_comp, col_comp));
+}
+
 /// Parse a shape from a collider definition.
 /// For trimesh shapes, returns a Trimesh with empty data — caller must resolve with mesh cache.
 pub fn parse_collider_shape(col_def: &crate::scene::ColliderDef) -> PhysicsShape {
@@ -571,6 +1071,44 @@ pub fn parse_collider_shape(col_def: &crate::scene::ColliderDef) -> PhysicsShape
     }
 }
 
+/// Rebuild an entity's collider with a new shape and update its `Collider`
+/// component. Trimesh shapes are built from the entity's mesh at its current scale.
+pub fn set_entity_collider(
+    scene_world: &mut SceneWorld,
+    cmd: &ColliderUpdateCommand,
+    mesh_cache: &MeshCache,
+    physics_world: &mut PhysicsWorld,
+) -> Result<(), String> {
+    let &entity = scene_world
+        .entity_registry
+        .get(&cmd.entity_id)
+        .ok_or_else(|| format!("unknown entity '{}'", cmd.entity_id))?;
+    let old = scene_world
+        .world
+        .get::<&physics::Collider>(entity)
+        .map(|c| c.handle)
+        .map_err(|_| format!("entity '{}' has no collider", cmd.entity_id))?;
+
+    let mut shape = parse_collider_shape(&cmd.collider);
+    if matches!(shape, PhysicsShape::Trimesh { .. }) {
+        let scale = scene_world.world.get::<&Transform>(entity).map(|t| t.scale).unwrap_or(glam::Vec3::ONE);
+        let mesh_handle = scene_world.world.get::<&MeshRenderer>(entity).map(|mr| mr.mesh_handle).ok();
+        let (vertices, indices) = mesh_handle
+            .and_then(|handle| mesh_cache.get_physics_trimesh(handle, scale))
+            .ok_or_else(|| format!("trimesh collider for '{}': no mesh data", cmd.entity_id))?;
+        shape = PhysicsShape::Trimesh { vertices, indices };
+    }
+
+    let handle = physics_world
+        .replace_collider(old, &shape, cmd.is_trigger, cmd.restitution, cmd.friction)
+        .ok_or_else(|| format!("entity '{}' has no physics body", cmd.entity_id))?;
+    let is_trigger = physics_world.collider_set[handle].is_sensor();
+    let _ = scene_world
+        .world
+        .insert_one(entity, physics::Collider { handle, shape, is_trigger });
+    Ok(())
+}
+
 /// Spawn a projectile entity at runtime with physics.
 #[allow(clippy::too_many_arguments)]
 pub fn spawn_projectile_entity(
@@ -784,10 +1322,11 @@ pub fn euler_degrees_to_quat(euler: [f32; 3]) -> glam::Quat {
 pub fn spawn_all_entities_headless(
     scene_world: &mut SceneWorld,
     scene: &SceneFile,
+    project_root: &Path,
     physics_world: &mut PhysicsWorld,
 ) {
     for entity_def in &scene.entities {
-        spawn_entity_headless(scene_world, entity_def, physics_world);
+        spawn_entity_headless(scene_world, entity_def, project_root, physics_world);
     }
     scene_world.current_scene = Some(scene.clone());
     tracing::info!(
@@ -801,6 +1340,7 @@ pub fn spawn_all_entities_headless(
 fn spawn_entity_headless(
     scene_world: &mut SceneWorld,
     entity_def: &EntityDef,
+    project_root: &Path,
     physics_world: &mut PhysicsWorld,
 ) {
     let entity_id = EntityId(entity_def.id.clone());
@@ -826,22 +1366,13 @@ fn spawn_entity_headless(
 
     // Spawn entity with non-GPU components
     let entity = if let Some(cam) = &entity_def.components.camera {
-        let camera = Camera {
-            fov_degrees: cam.fov,
-            near: cam.near,
-            far: cam.far,
-            role: if cam.role == "main" {
-                CameraRole::Main
-            } else {
-                CameraRole::Other(cam.role.clone())
-            },
-            aspect_ratio: 16.0 / 9.0,
-        };
+        let camera = camera_from_def(&entity_def.id, cam);
         if let Some(pl) = &entity_def.components.point_light {
             let point_light = PointLight {
                 color: glam::Vec3::from(pl.color),
                 intensity: pl.intensity,
                 range: pl.range,
+                casts_shadows: pl.casts_shadows,
             };
             scene_world.world.spawn((entity_id, tags, transform, camera, point_light))
         } else {
@@ -852,6 +1383,7 @@ fn spawn_entity_headless(
             color: glam::Vec3::from(pl.color),
             intensity: pl.intensity,
             range: pl.range,
+            casts_shadows: pl.casts_shadows,
         };
         scene_world.world.spawn((entity_id, tags, transform, point_light))
     } else if let Some(dl) = &entity_def.components.directional_light {
@@ -887,6 +1419,74 @@ fn spawn_entity_headless(
         let _ = scene_world.world.insert_one(entity, collision_damage);
     }
 
+    // Attach TimeScale component if defined
+    if let Some(ts_def) = &entity_def.components.time_scale {
+        let scale = crate::components::TimeScale(crate::time_scale::clamp_scale(ts_def.scale));
+        let _ = scene_world.world.insert_one(entity, scale);
+    }
+
+    // Attach Lifetime component if defined
+    if let Some(lt_def) = &entity_def.components.lifetime {
+        match crate::lifetime::Lifetime::from_def(lt_def) {
+            Ok(lifetime) => {
+                let _ = scene_world.world.insert_one(entity, lifetime);
+            }
+            Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+        }
+    }
+
+    // Attach FlockAgent component if defined
+    if let Some(flock_def) = &entity_def.components.flock {
+        let _ = scene_world.world.insert_one(entity, crate::crowd::FlockAgent::from_def(flock_def));
+    }
+
+    // Attach SpotLight component if defined
+    if let Some(spot_def) = &entity_def.components.spot_light {
+        let _ = scene_world.world.insert_one(entity, spot_light_from_def(spot_def));
+    }
+
+    // Attach LightFlicker component if the point light flickers
+    if let Some(pl) = &entity_def.components.point_light {
+        if let Some(flicker) = light_flicker_from_def(project_root, &entity_def.id, pl) {
+            let _ = scene_world.world.insert_one(entity, flicker);
+        }
+    }
+
+    // Attach Skybox component if defined
+    if let Some(sky_def) = &entity_def.components.skybox {
+        match skybox_from_def(sky_def) {
+            Ok(skybox) => {
+                let _ = scene_world.world.insert_one(entity, skybox);
+            }
+            Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+        }
+    }
+
+    // Attach Portal component if defined
+    if let Some(portal_def) = &entity_def.components.portal {
+        let _ = scene_world.world.insert_one(entity, portal_from_def(portal_def));
+    }
+
+    // Attach Water component if defined (no surface mesh without a GPU)
+    if let Some(water_def) = &entity_def.components.water {
+        let _ = scene_world.world.insert_one(entity, water_from_def(water_def, None));
+    }
+
+    // Attach Foliage component if defined (not drawn without a GPU)
+    if let Some(foliage_def) = &entity_def.components.foliage {
+        let _ = scene_world.world.insert_one(entity, foliage_from_def(foliage_def, None));
+    }
+
+    // Attach Terrain component if defined (not drawn without a GPU, but collides)
+    if let Some(terrain_def) = &entity_def.components.terrain {
+        match terrain_from_def(terrain_def) {
+            Ok(terrain) => {
+                let _ = scene_world.world.insert_one(entity, terrain);
+            }
+            Err(e) => tracing::warn!("Entity '{}': {}", entity_def.id, e),
+        }
+    }
+
     // Spawn physics components
     let pos = if let Some(t) = &entity_def.components.transform {
         glam::Vec3::from(t.position)
@@ -921,6 +1521,8 @@ fn spawn_entity_headless(
             sprint_multiplier: cc_def.sprint_multiplier,
             jump_impulse: cc_def.jump_impulse,
             step_height: cc_def.step_height,
+            push_force: cc_def.push_force,
+            mass: cc_def.mass,
             ..Default::default()
         };
         let player = Player {
@@ -940,6 +1542,12 @@ fn spawn_entity_headless(
             .as_ref()
             .map(|rb| rb.body_type.as_str())
             .unwrap_or("static");
+        let interpolate = entity_def
+            .components
+            .rigid_body
+            .as_ref()
+            .map(|rb| rb.interpolate)
+            .unwrap_or(true);
 
         match body_type {
             "dynamic" => {
@@ -957,6 +1565,7 @@ fn spawn_entity_headless(
                     .unwrap_or(false);
                 let (rb_handle, col_handle) =
                     physics_world.add_dynamic_body(entity, pos, rot, shape.clone(), mass, restitution, friction, ccd);
+                physics_world.set_interpolated(rb_handle, interpolate);
                 let rb_comp = physics::RigidBody {
                     handle: rb_handle,
                     body_type: physics::PhysicsBodyType::Dynamic,
@@ -968,6 +1577,21 @@ fn spawn_entity_headless(
                 };
                 let _ = scene_world.world.insert(entity, (rb_comp, col_comp));
             }
+            "kinematic" => {
+                let (rb_handle, col_handle) =
+                    physics_world.add_kinematic_body(entity, pos, rot, shape.clone(), is_trigger, restitution, friction);
+                physics_world.set_interpolated(rb_handle, interpolate);
+                let rb_comp = physics::RigidBody {
+                    handle: rb_handle,
+                    body_type: physics::PhysicsBodyType::Kinematic,
+                };
+                let col_comp = physics::Collider {
+                    handle: col_handle,
+                    shape,
+                    is_trigger,
+                };
+                let _ = scene_world.world.insert(entity, (rb_comp, col_comp));
+            }
             _ => {
                 let (rb_handle, col_handle) =
                     physics_world.add_static_body(entity, pos, rot, shape.clone(), is_trigger, restitution, friction);
@@ -984,6 +1608,7 @@ fn spawn_entity_headless(
             }
         }
     }
+    spawn_terrain_collider(scene_world, entity, entity_def, project_root, physics_world);
 }
 
 /// Reconcile a scene update: diff old vs new, spawn/despawn/patch entities.
@@ -1082,6 +1707,15 @@ pub fn reconcile_scene(
     scene_world.current_scene = Some(new_scene.clone());
 }
 
+/// LOD levels swap the mesh each frame, so changing them (or the mesh they
+/// belong to) respawns the entity.
+fn lod_changed(old_def: &EntityDef, new_def: &EntityDef) -> bool {
+    match (&old_def.components.mesh_renderer, &new_def.components.mesh_renderer) {
+        (Some(old), Some(new)) => old.lod != new.lod || (new.lod.is_some() && old.mesh != new.mesh),
+        _ => false,
+    }
+}
+
 /// Patch an existing entity's components in-place.
 /// Returns `true` if the entity needs a full destroy+respawn (structural change).
 #[allow(clippy::too_many_arguments)]
@@ -1101,8 +1735,11 @@ fn patch_entity(
         old_def.components.rigid_body.is_some() != new_def.components.rigid_body.is_some()
         || old_def.components.collider.is_some() != new_def.components.collider.is_some()
         || old_def.components.character_controller.is_some() != new_def.components.character_controller.is_some()
-        || old_def.components.gaussian_splat.is_some() != new_def.components.gaussian_splat.is_some()
+        || old_def.components.gaussian_splat.as_ref().map(|s| &s.source)
+            != new_def.components.gaussian_splat.as_ref().map(|s| &s.source)
         || old_def.components.mesh_renderer.is_some() != new_def.components.mesh_renderer.is_some()
+        || old_def.components.terrain != new_def.components.terrain
+        || lod_changed(old_def, new_def)
         || old_def.components.script.is_some() != new_def.components.script.is_some();
 
     if structural_change {
@@ -1121,12 +1758,24 @@ fn patch_entity(
         }
     }
 
+    // Patch splat detail, opacity and crops (a new source respawns)
+    if let Some(gs) = &new_def.components.gaussian_splat {
+        if let Ok(mut splat) = world.get::<&mut GaussianSplat>(entity) {
+            splat.lod_distance = gs.lod_distance;
+            splat.min_detail = gs.min_detail;
+            splat.opacity = gs.opacity;
+            splat.crops = scene_splat_crops(&new_def.id, &gs.crop);
+        }
+    }
+
     // Patch camera
     if let Some(cam) = &new_def.components.camera {
         if let Ok(mut camera) = world.get::<&mut Camera>(entity) {
-            camera.fov_degrees = cam.fov;
-            camera.near = cam.near;
-            camera.far = cam.far;
+            let patched = camera_from_def(&new_def.id, cam);
+            camera.fov_degrees = patched.fov_degrees;
+            camera.near = patched.near;
+            camera.far = patched.far;
+            camera.projection = patched.projection;
         }
     }
 
@@ -1137,6 +1786,109 @@ fn patch_entity(
             point_light.intensity = pl.intensity;
             point_light.range = pl.range;
         }
+        match light_flicker_from_def(project_root, &new_def.id, pl) {
+            Some(flicker) => {
+                let _ = world.insert_one(entity, flicker);
+            }
+            None => {
+                let _ = world.remove_one::<crate::components::LightFlicker>(entity);
+            }
+        }
+    }
+
+    // Patch spot light
+    if old_def.components.spot_light != new_def.components.spot_light {
+        match &new_def.components.spot_light {
+            Some(def) => {
+                let _ = world.insert_one(entity, spot_light_from_def(def));
+            }
+            None => {
+                let _ = world.remove_one::<SpotLight>(entity);
+            }
+        }
+    }
+
+    // Patch skybox (the renderer reloads its maps when the source changes)
+    if old_def.components.skybox != new_def.components.skybox {
+        match new_def.components.skybox.as_ref().map(skybox_from_def) {
+            Some(Ok(skybox)) => {
+                let _ = world.insert_one(entity, skybox);
+            }
+            Some(Err(e)) => tracing::warn!("Entity '{}': {}", new_def.id, e),
+            None => {
+                let _ = world.remove_one::<Skybox>(entity);
+            }
+        }
+    }
+
+    // Patch portal
+    if old_def.components.portal != new_def.components.portal {
+        match &new_def.components.portal {
+            Some(def) => {
+                let _ = world.insert_one(entity, portal_from_def(def));
+            }
+            None => {
+                let _ = world.remove_one::<Portal>(entity);
+            }
+        }
+    }
+
+    // Patch water
+    if old_def.components.water != new_def.components.water {
+        match &new_def.components.water {
+            Some(def) => {
+                let mesh = mesh_cache.get_or_load(device, queue, project_root, &def.mesh, None).ok();
+                let _ = world.insert_one(entity, water_from_def(def, mesh));
+            }
+            None => {
+                let _ = world.remove_one::<Water>(entity);
+            }
+        }
+    }
+
+    // Patch foliage
+    if old_def.components.foliage != new_def.components.foliage {
+        match &new_def.components.foliage {
+            Some(def) => {
+                let mesh = mesh_cache.get_or_load(device, queue, project_root, &def.mesh, None).ok();
+                let _ = world.insert_one(entity, foliage_from_def(def, mesh));
+            }
+            None => {
+                let _ = world.remove_one::<Foliage>(entity);
+            }
+        }
+    }
+
+    // Patch lifetime (the age carries over, so a tweak doesn't restart the clock)
+    if old_def.components.lifetime != new_def.components.lifetime {
+        match new_def.components.lifetime.as_ref().map(crate::lifetime::Lifetime::from_def) {
+            Some(Ok(mut lifetime)) => {
+                if let Ok(old) = world.get::<&crate::lifetime::Lifetime>(entity) {
+                    lifetime.age = old.age;
+                }
+                let _ = world.insert_one(entity, lifetime);
+            }
+            Some(Err(e)) => tracing::warn!("Entity '{}': {}", new_def.id, e),
+            None => {
+                let _ = world.remove_one::<crate::lifetime::Lifetime>(entity);
+            }
+        }
+    }
+
+    // Patch flock (keeping the agent's current velocity)
+    if old_def.components.flock != new_def.components.flock {
+        match &new_def.components.flock {
+            Some(def) => {
+                let mut agent = crate::crowd::FlockAgent::from_def(def);
+                if let Ok(old) = world.get::<&crate::crowd::FlockAgent>(entity) {
+                    agent.velocity = old.velocity;
+                }
+                let _ = world.insert_one(entity, agent);
+            }
+            None => {
+                let _ = world.remove_one::<crate::crowd::FlockAgent>(entity);
+            }
+        }
     }
 
     // Patch mesh renderer (mesh and/or material changes)
@@ -1,5 +1,7 @@
 use glam::{Mat4, Quat, Vec3};
 
+use crate::curve::{Curve, CurveDef};
+
 /// Transform component. Present on every entity.
 #[derive(Debug, Clone)]
 pub struct Transform {
@@ -47,24 +49,151 @@ pub struct SplatHandle(pub usize);
 #[derive(Debug, Clone)]
 pub struct GaussianSplat {
     pub splat_handle: SplatHandle,
+    /// Distance past which splats are subsampled (None: never).
+    pub lod_distance: Option<f32>,
+    /// Smallest share of splats drawn far away.
+    pub min_detail: f32,
+    /// Multiplier on every splat's opacity.
+    pub opacity: f32,
+    /// Volumes the cloud is cropped to, in its local space.
+    pub crops: Vec<SplatCrop>,
+}
+
+/// A crop volume of a splat cloud. Splats outside every kept volume (when
+/// there are any) or inside a removed one are not drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplatCrop {
+    pub shape: SplatCropShape,
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    /// Remove the splats inside instead of keeping them.
+    pub invert: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplatCropShape {
+    Box,
+    Ellipsoid,
+}
+
+impl SplatCrop {
+    /// Whether a local-space point is inside the volume.
+    pub fn contains(&self, point: Vec3) -> bool {
+        let d = (point - self.center) / self.half_extents.max(Vec3::splat(1e-6));
+        match self.shape {
+            SplatCropShape::Box => d.abs().max_element() <= 1.0,
+            SplatCropShape::Ellipsoid => d.length_squared() <= 1.0,
+        }
+    }
+}
+
+/// Whether `crops` remove a local-space point.
+pub fn splat_cropped(crops: &[SplatCrop], point: Vec3) -> bool {
+    let mut kept = None;
+    for crop in crops {
+        let inside = crop.contains(point);
+        if crop.invert {
+            if inside {
+                return true;
+            }
+        } else {
+            kept = Some(kept.unwrap_or(false) || inside);
+        }
+    }
+    kept == Some(false)
 }
 
 /// Camera component.
 #[derive(Debug, Clone)]
 pub struct Camera {
+    /// Vertical field of view of a perspective camera.
     pub fov_degrees: f32,
     pub near: f32,
     pub far: f32,
     pub role: CameraRole,
     pub aspect_ratio: f32,
+    pub projection: Projection,
+}
+
+/// How a camera projects the scene onto the screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Projection {
+    /// Things shrink with distance, over the camera's `fov_degrees`.
+    #[default]
+    Perspective,
+    /// Parallel lines stay parallel and size doesn't change with distance
+    /// (top-down, isometric and 2D views). `size` is the height of the view
+    /// in world units; the width follows the aspect ratio.
+    Orthographic { size: f32 },
+}
+
+impl Projection {
+    /// Parse a scene projection name; `size` is used by `orthographic`.
+    pub fn from_name(name: &str, size: f32) -> Option<Self> {
+        match name {
+            "perspective" => Some(Projection::Perspective),
+            "orthographic" => Some(Projection::Orthographic { size }),
+            _ => None,
+        }
+    }
+
+    /// The projection's name in a scene file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective => "perspective",
+            Projection::Orthographic { .. } => "orthographic",
+        }
+    }
+}
+
+impl Camera {
+    /// Projection matrix (depth 0 at `near`, 1 at `far`) for a view of the
+    /// given width over height.
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        match self.projection {
+            Projection::Perspective => {
+                Mat4::perspective_rh(self.fov_degrees.to_radians(), aspect, self.near, self.far)
+            }
+            Projection::Orthographic { size } => {
+                let half_height = size.max(1e-4) * 0.5;
+                let half_width = half_height * aspect;
+                Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, self.near, self.far)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CameraRole {
     Main,
+    /// A local player's view (`player1`, `player2`, ...); two or more
+    /// split the screen between them.
+    Player(u32),
     Other(String),
 }
 
+impl CameraRole {
+    /// Parse a scene role: `main`, `player<N>` (N from 1) or any other name.
+    pub fn from_name(name: &str) -> Self {
+        if name == "main" {
+            return CameraRole::Main;
+        }
+        match name.strip_prefix("player").and_then(|n| n.parse::<u32>().ok()) {
+            Some(n) if n >= 1 => CameraRole::Player(n),
+            _ => CameraRole::Other(name.to_string()),
+        }
+    }
+
+    /// The role's name in a scene file.
+    pub fn name(&self) -> String {
+        match self {
+            CameraRole::Main => "main".to_string(),
+            CameraRole::Player(n) => format!("player{}", n),
+            CameraRole::Other(name) => name.clone(),
+        }
+    }
+}
+
 impl Default for Camera {
     fn default() -> Self {
         Self {
@@ -73,6 +202,7 @@ impl Default for Camera {
             far: 100.0,
             role: CameraRole::Main,
             aspect_ratio: 16.0 / 9.0,
+            projection: Projection::Perspective,
         }
     }
 }
@@ -83,6 +213,33 @@ pub struct PointLight {
     pub color: Vec3,
     pub intensity: f32,
     pub range: f32,
+    pub casts_shadows: bool,
+}
+
+/// Drives a point light's intensity from a looping curve.
+#[derive(Debug, Clone)]
+pub struct LightFlicker {
+    pub curve: Curve,
+    /// The scene's curve field, written back when the scene is saved.
+    pub source: CurveDef,
+    pub speed: f32,
+    /// Intensity the curve's value multiplies.
+    pub base_intensity: f32,
+    pub time: f32,
+}
+
+/// Spot light component: a point light limited to a cone.
+#[derive(Debug, Clone)]
+pub struct SpotLight {
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+    /// Cone axis in the entity's local space.
+    pub direction: Vec3,
+    /// Half-angle in degrees where the falloff starts.
+    pub inner_angle: f32,
+    /// Half-angle in degrees where the light reaches zero.
+    pub outer_angle: f32,
 }
 
 /// Directional light component (sun-like, infinite distance).
@@ -105,6 +262,127 @@ impl Default for DirectionalLight {
     }
 }
 
+/// Scene background and image-based lighting source. The deferred pipeline
+/// draws it behind the geometry and lights surfaces with maps prefiltered
+/// from it; one per scene (the first one found wins).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skybox {
+    pub source: SkyboxSource,
+    /// Multiplier on the sky color and the ambient/specular light it casts.
+    pub intensity: f32,
+}
+
+/// Where a skybox's radiance comes from, as project-relative image paths.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SkyboxSource {
+    /// One equirectangular (latitude-longitude) image, usually `.hdr` or `.exr`.
+    Equirect(String),
+    /// Six faces in +X, -X, +Y, -Y, +Z, -Z order.
+    Cubemap([String; 6]),
+}
+
+/// Window onto another place: the entity's mesh (a surface in its local XZ
+/// plane, facing +Y, like `procedural:plane`) shows the scene as seen through
+/// the linked portal, or mirrored across its own plane when `target` is None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portal {
+    /// Entity id of the linked portal.
+    pub target: Option<String>,
+    /// View size as a fraction of the window, 0.1 to 1.
+    pub resolution: f32,
+    /// Nested views rendered when the portal sees itself (mirrors use 1).
+    pub max_recursion: u32,
+}
+
+/// Water surface, drawn by the pipeline's `water` pass (not the geometry
+/// pass) over the lit scene. Bodies inside its volume, the unit square of
+/// its local XZ plane scaled by the transform and `depth` deep, float.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Water {
+    /// Surface mesh (None when it couldn't be loaded, or without a GPU).
+    pub mesh: Option<MeshHandle>,
+    pub shallow_color: [f32; 3],
+    pub deep_color: [f32; 3],
+    pub depth_fade: f32,
+    pub shore_fade: f32,
+    pub wave_scale: f32,
+    pub wave_speed: f32,
+    pub wave_strength: f32,
+    pub refraction: f32,
+    pub reflection: WaterReflection,
+    pub buoyancy: f32,
+    pub drag: f32,
+    pub depth: f32,
+}
+
+/// What a water surface reflects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaterReflection {
+    /// Ray-marched against the depth buffer, the sky where the ray leaves the screen.
+    ScreenSpace,
+    Sky,
+    None,
+}
+
+/// Grass or other small meshes scattered over the unit square of the
+/// entity's local XZ plane, drawn instanced with wind sway. The scatter is
+/// rebuilt whenever this component or the entity's transform changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Foliage {
+    /// Instanced mesh (None when it couldn't be loaded, or without a GPU).
+    pub mesh: Option<MeshHandle>,
+    /// Instances per square meter before the quality preset's scaling.
+    pub density: f32,
+    /// Project-relative grayscale image scaling density over the square.
+    pub density_map: Option<String>,
+    pub max_instances: u32,
+    pub seed: u64,
+    pub snap_to_ground: bool,
+    /// Entity id whose colliders instances snap onto (any collider when None).
+    pub surface: Option<String>,
+    pub scale: [f32; 2],
+    pub base_color: [f32; 3],
+    pub tip_color: [f32; 3],
+    pub wind_strength: f32,
+    pub wind_speed: f32,
+    pub wind_direction: [f32; 2],
+    pub fade_start: f32,
+    pub fade_end: f32,
+}
+
+/// Heightmap terrain over `size` of the entity's local XZ plane, centered on
+/// the entity, drawn in chunks with distance LOD and splat-blended layers.
+/// The chunks are rebuilt whenever this component or the entity's transform
+/// changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terrain {
+    /// Project-relative grayscale heightmap; white is `height`.
+    pub heightmap: String,
+    pub size: [f32; 2],
+    pub height: f32,
+    pub chunks: u32,
+    /// Grid quads per chunk side at full detail, a power of two.
+    pub chunk_resolution: u32,
+    /// Camera distances where chunks drop a level, increasing.
+    pub lod_distances: Vec<f32>,
+    /// Project-relative RGBA image weighting layers 0-3.
+    pub splat_map: Option<String>,
+    /// One to four layers.
+    pub layers: Vec<TerrainLayer>,
+    pub collider: bool,
+    pub friction: f32,
+}
+
+/// A terrain surface layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainLayer {
+    pub texture: Option<String>,
+    pub color: [f32; 3],
+    /// Meters covered by one texture repeat.
+    pub tile_size: f32,
+    pub roughness: f32,
+}
+
 /// First-person player marker component.
 #[derive(Debug, Clone)]
 pub struct Player {
@@ -146,6 +424,10 @@ pub struct MaterialOverride {
 /// Marker component: entity is hidden from rendering.
 pub struct Hidden;
 
+/// Per-entity time multiplier applied on top of the global time scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeScale(pub f32);
+
 /// Handle into the animation system's skeleton store.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SkeletonHandle(pub usize);
@@ -196,6 +478,14 @@ pub enum CameraMode {
         height_offset: f32,
         pitch_min: f32, // radians
         pitch_max: f32, // radians
+        /// Seconds to ease toward the orbit position (0 snaps).
+        position_damping: f32,
+        /// Seconds to ease toward the rotation looking at the player (0 snaps).
+        rotation_damping: f32,
+        /// Seconds of the player's movement the orbit point leads by.
+        look_ahead: f32,
+        /// Farthest the orbit point leads, in meters.
+        look_ahead_max: f32,
     },
 }
 
@@ -218,7 +508,90 @@ pub struct ParticleConfig {
     pub size: [f32; 2],
     pub color_start: [f32; 4],
     pub color_end: [f32; 4],
+    /// `[t, r, g, b, a]` keys sorted by t; overrides the start/end colors.
+    pub color_over_life: Vec<[f32; 5]>,
+    /// `[t, size]` keys sorted by t; overrides `size`.
+    pub size_over_life: Vec<[f32; 2]>,
+    /// Size over the particle's life, played over the curve's key range;
+    /// overrides `size` and `size_over_life`.
+    pub size_curve: Option<Curve>,
+    /// Alpha over the particle's life, played over the curve's key range;
+    /// replaces the alpha of the color keys.
+    pub alpha_curve: Option<Curve>,
     pub gravity_scale: f32,
+    pub blend: ParticleBlend,
+    /// Fade distance against the surface behind (soft particles); 0 = hard.
+    pub softness: f32,
+    /// What particles do when they hit a physics collider.
+    pub collision: ParticleCollision,
+    /// Share of the speed into a surface kept on a bounce.
+    pub bounce: f32,
+}
+
+/// How particles react to physics colliders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParticleCollision {
+    /// Pass through everything (no physics queries).
+    #[default]
+    None,
+    /// Bounce off surfaces, then settle on them.
+    Bounce,
+    /// Die on contact.
+    Kill,
+}
+
+impl ParticleConfig {
+    /// Color at normalized age `t` (0 = born, 1 = expired).
+    pub fn color_at(&self, t: f32) -> [f32; 4] {
+        let mut color: [f32; 4] = if self.color_over_life.is_empty() {
+            std::array::from_fn(|i| self.color_start[i] + (self.color_end[i] - self.color_start[i]) * t)
+        } else {
+            let keys = &self.color_over_life;
+            let (a, b, f) = curve_segment(keys.len(), |i| keys[i][0], t);
+            std::array::from_fn(|c| keys[a][c + 1] + (keys[b][c + 1] - keys[a][c + 1]) * f)
+        };
+        if let Some(curve) = &self.alpha_curve {
+            color[3] = curve.eval_normalized(t);
+        }
+        color
+    }
+
+    /// Size at normalized age `t`.
+    pub fn size_at(&self, t: f32) -> f32 {
+        if let Some(curve) = &self.size_curve {
+            return curve.eval_normalized(t);
+        }
+        if self.size_over_life.is_empty() {
+            return self.size[0] + (self.size[1] - self.size[0]) * t;
+        }
+        let keys = &self.size_over_life;
+        let (a, b, f) = curve_segment(keys.len(), |i| keys[i][0], t);
+        keys[a][1] + (keys[b][1] - keys[a][1]) * f
+    }
+}
+
+/// The two keys of a sorted, non-empty curve around `t` and the blend
+/// factor between them. Outside the keys the nearest one holds.
+fn curve_segment(len: usize, key_t: impl Fn(usize) -> f32, t: f32) -> (usize, usize, f32) {
+    let next = (0..len).find(|&i| key_t(i) > t).unwrap_or(len);
+    if next == 0 {
+        return (0, 0, 0.0);
+    }
+    if next == len {
+        return (len - 1, len - 1, 0.0);
+    }
+    let (t0, t1) = (key_t(next - 1), key_t(next));
+    (next - 1, next, (t - t0) / (t1 - t0))
+}
+
+/// How particles combine with the scene behind them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParticleBlend {
+    /// Blended by alpha, drawn back-to-front (smoke, dust).
+    #[default]
+    Alpha,
+    /// Added to the scene, in any order (fire, sparks, magic).
+    Additive,
 }
 
 impl Default for ParticleConfig {
@@ -233,7 +606,15 @@ impl Default for ParticleConfig {
             size: [0.2, 0.05],
             color_start: [1.0, 1.0, 1.0, 1.0],
             color_end: [1.0, 1.0, 1.0, 0.0],
+            color_over_life: Vec::new(),
+            size_over_life: Vec::new(),
+            size_curve: None,
+            alpha_curve: None,
             gravity_scale: 0.0,
+            blend: ParticleBlend::Alpha,
+            softness: 0.25,
+            collision: ParticleCollision::None,
+            bounce: 0.4,
         }
     }
 }
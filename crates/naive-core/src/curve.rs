@@ -0,0 +1,254 @@
+//! Keyframe curves: reusable tuning data for tweens, particles, lights and audio.
+//!
+//! A curve is a list of `[time, value]` keys plus how to interpolate between
+//! them and what to do outside their range. Curves live in `curves/<name>.yaml`
+//! and are referenced by name, or written inline wherever a curve is accepted.
+//!
+//! ```yaml
+//! # curves/recoil.yaml
+//! keys: [[0.0, 0.0], [0.05, 1.0], [0.3, 0.0]]
+//! interpolation: smooth
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// How values between two keys are computed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Straight line between keys.
+    #[default]
+    Linear,
+    /// Hold each key's value until the next key.
+    Step,
+    /// Cubic Hermite through the keys, flat at the ends.
+    Smooth,
+}
+
+/// What the curve returns for times outside its keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Wrap {
+    /// Hold the first/last value.
+    #[default]
+    Clamp,
+    /// Repeat from the first key.
+    Loop,
+    /// Play forward then backward.
+    PingPong,
+}
+
+/// A keyframed curve.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Curve {
+    /// `[time, value]` pairs, sorted by time.
+    pub keys: Vec<[f32; 2]>,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    #[serde(default)]
+    pub wrap: Wrap,
+}
+
+/// A curve field in a scene or config: the name of a file in `curves/`, or
+/// the curve itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CurveDef {
+    Named(String),
+    Inline(Curve),
+}
+
+impl Curve {
+    /// A curve through the given keys, sorted by time.
+    pub fn new(keys: Vec<[f32; 2]>, interpolation: Interpolation) -> Self {
+        let mut curve = Self {
+            keys,
+            interpolation,
+            wrap: Wrap::Clamp,
+        };
+        curve.sort_keys();
+        curve
+    }
+
+    /// The same curve with another wrap mode.
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Parse a curve from YAML, checking and sorting its keys.
+    pub fn from_yaml(source: &str) -> Result<Self, String> {
+        let mut curve: Curve = serde_yaml::from_str(source).map_err(|e| e.to_string())?;
+        curve.validate()?;
+        curve.sort_keys();
+        Ok(curve)
+    }
+
+    /// Check the curve has keys and they are all finite.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keys.is_empty() {
+            return Err("curve has no keys".to_string());
+        }
+        if self.keys.iter().flatten().any(|v| !v.is_finite()) {
+            return Err("curve keys must be finite numbers".to_string());
+        }
+        Ok(())
+    }
+
+    fn sort_keys(&mut self) {
+        self.keys.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    }
+
+    /// Time of the first key.
+    pub fn start(&self) -> f32 {
+        self.keys.first().map_or(0.0, |k| k[0])
+    }
+
+    /// Time of the last key.
+    pub fn end(&self) -> f32 {
+        self.keys.last().map_or(0.0, |k| k[0])
+    }
+
+    /// Time between the first and last keys.
+    pub fn duration(&self) -> f32 {
+        self.end() - self.start()
+    }
+
+    /// Value of the curve at time `t`.
+    pub fn eval(&self, t: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return 0.0;
+        };
+        let t = self.wrap_time(t);
+        if t <= first[0] {
+            return first[1];
+        }
+        if t >= last[0] {
+            return last[1];
+        }
+        // First key strictly after t; t is inside (keys[i-1], keys[i]].
+        let i = self.keys.partition_point(|k| k[0] <= t);
+        let (a, b) = (self.keys[i - 1], self.keys[i]);
+        let span = b[0] - a[0];
+        if span <= 0.0 {
+            return b[1];
+        }
+        let s = (t - a[0]) / span;
+        match self.interpolation {
+            Interpolation::Linear => a[1] + (b[1] - a[1]) * s,
+            Interpolation::Step => a[1],
+            Interpolation::Smooth => {
+                let m0 = self.tangent(i - 1) * span;
+                let m1 = self.tangent(i) * span;
+                let s2 = s * s;
+                let s3 = s2 * s;
+                (2.0 * s3 - 3.0 * s2 + 1.0) * a[1]
+                    + (s3 - 2.0 * s2 + s) * m0
+                    + (-2.0 * s3 + 3.0 * s2) * b[1]
+                    + (s3 - s2) * m1
+            }
+        }
+    }
+
+    /// Value at `t` in 0..1 of the curve's time range, for curves keyed in
+    /// their own units but played over a lifetime or duration.
+    pub fn eval_normalized(&self, t: f32) -> f32 {
+        self.eval(self.start() + t * self.duration())
+    }
+
+    /// Slope at key `i` (finite difference of its neighbours, flat at the ends).
+    fn tangent(&self, i: usize) -> f32 {
+        if i == 0 || i + 1 >= self.keys.len() {
+            return 0.0;
+        }
+        let (prev, next) = (self.keys[i - 1], self.keys[i + 1]);
+        let dt = next[0] - prev[0];
+        if dt <= 0.0 {
+            0.0
+        } else {
+            (next[1] - prev[1]) / dt
+        }
+    }
+
+    fn wrap_time(&self, t: f32) -> f32 {
+        let (start, duration) = (self.start(), self.duration());
+        if duration <= 0.0 {
+            return t;
+        }
+        match self.wrap {
+            Wrap::Clamp => t,
+            Wrap::Loop => start + (t - start).rem_euclid(duration),
+            Wrap::PingPong => {
+                let local = (t - start).rem_euclid(duration * 2.0);
+                start + if local > duration { duration * 2.0 - local } else { local }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_linear_and_step() {
+        let linear = Curve::new(vec![[1.0, 10.0], [0.0, 0.0], [2.0, 0.0]], Interpolation::Linear);
+        assert_eq!(linear.keys[0], [0.0, 0.0]);
+        assert!(approx(linear.eval(0.5), 5.0));
+        assert!(approx(linear.eval(1.5), 5.0));
+        assert!(approx(linear.eval(-1.0), 0.0));
+        assert!(approx(linear.eval(3.0), 0.0));
+        assert!(approx(linear.duration(), 2.0));
+
+        let step = Curve::new(vec![[0.0, 1.0], [1.0, 2.0]], Interpolation::Step);
+        assert!(approx(step.eval(0.99), 1.0));
+        assert!(approx(step.eval(1.0), 2.0));
+    }
+
+    #[test]
+    fn test_smooth_passes_through_keys() {
+        let curve = Curve::new(vec![[0.0, 0.0], [0.5, 1.0], [1.0, 0.0]], Interpolation::Smooth);
+        assert!(approx(curve.eval(0.0), 0.0));
+        assert!(approx(curve.eval(0.5), 1.0));
+        assert!(approx(curve.eval(1.0), 0.0));
+        // Flat at the ends: barely moves near the first key.
+        assert!(curve.eval(0.01) < 0.01);
+        assert!(approx(curve.eval(0.25), curve.eval(0.75)));
+    }
+
+    #[test]
+    fn test_wrap_modes() {
+        let mut curve = Curve::new(vec![[0.0, 0.0], [1.0, 1.0]], Interpolation::Linear);
+        curve.wrap = Wrap::Loop;
+        assert!(approx(curve.eval(1.25), 0.25));
+        assert!(approx(curve.eval(-0.25), 0.75));
+        curve.wrap = Wrap::PingPong;
+        assert!(approx(curve.eval(1.25), 0.75));
+        assert!(approx(curve.eval(2.25), 0.25));
+    }
+
+    #[test]
+    fn test_eval_normalized() {
+        let curve = Curve::new(vec![[10.0, 0.0], [20.0, 4.0]], Interpolation::Linear);
+        assert!(approx(curve.eval_normalized(0.5), 2.0));
+        assert!(approx(curve.eval_normalized(1.0), 4.0));
+    }
+
+    #[test]
+    fn test_from_yaml_and_curve_def() {
+        let curve = Curve::from_yaml("keys: [[1, 0], [0, 1]]\ninterpolation: step\nwrap: loop\n").unwrap();
+        assert_eq!(curve.keys, vec![[0.0, 1.0], [1.0, 0.0]]);
+        assert_eq!(curve.interpolation, Interpolation::Step);
+        assert_eq!(curve.wrap, Wrap::Loop);
+        assert!(Curve::from_yaml("keys: []").is_err());
+
+        let named: CurveDef = serde_yaml::from_str("recoil").unwrap();
+        assert_eq!(named, CurveDef::Named("recoil".to_string()));
+        let inline: CurveDef = serde_yaml::from_str("keys: [[0, 1], [1, 0]]").unwrap();
+        assert!(matches!(inline, CurveDef::Inline(c) if c.keys.len() == 2));
+    }
+}
@@ -12,20 +12,141 @@ pub struct GameEvent {
 }
 
 /// Event schema loaded from events/schema.yaml for validation.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventSchema {
     #[serde(default)]
     pub events: HashMap<String, EventFieldSchema>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventFieldSchema {
     #[serde(default)]
-    pub fields: Vec<String>,
+    pub fields: Vec<EventField>,
     #[serde(default)]
     pub description: String,
 }
 
+/// Expected type of an event field. Untyped fields (`- amount`) accept anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFieldType {
+    #[default]
+    Any,
+    Number,
+    String,
+    Bool,
+}
+
+impl EventFieldType {
+    /// Whether a JSON payload value satisfies this type.
+    pub fn accepts(&self, value: &serde_json::Value) -> bool {
+        match self {
+            EventFieldType::Any => true,
+            EventFieldType::Number => value.is_number(),
+            EventFieldType::String => value.is_string(),
+            EventFieldType::Bool => value.is_boolean(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventFieldType::Any => "any",
+            EventFieldType::Number => "number",
+            EventFieldType::String => "string",
+            EventFieldType::Bool => "bool",
+        }
+    }
+}
+
+/// A single event field: written as `- amount` or `- amount: number` in YAML.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: EventFieldType,
+}
+
+impl<'de> Deserialize<'de> for EventField {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawField {
+            Name(String),
+            Typed(HashMap<String, EventFieldType>),
+        }
+
+        match RawField::deserialize(deserializer)? {
+            RawField::Name(name) => Ok(EventField { name, field_type: EventFieldType::Any }),
+            RawField::Typed(map) if map.len() == 1 => {
+                let (name, field_type) = map.into_iter().next().unwrap();
+                Ok(EventField { name, field_type })
+            }
+            RawField::Typed(_) => Err(serde::de::Error::custom(
+                "event field must be a name or a single `name: type` entry",
+            )),
+        }
+    }
+}
+
+impl EventFieldSchema {
+    /// Look up a declared field by name.
+    pub fn field(&self, name: &str) -> Option<&EventField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+impl EventSchema {
+    /// Check an event payload against its schema entry.
+    /// Unknown fields and type mismatches are errors; events without a schema entry pass.
+    pub fn validate(
+        &self,
+        event_type: &str,
+        data: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), String> {
+        let Some(event_schema) = self.events.get(event_type) else {
+            return Ok(());
+        };
+        let mut keys: Vec<&String> = data.keys().collect();
+        keys.sort();
+        for key in keys {
+            let value = &data[key];
+            match event_schema.field(key) {
+                None => {
+                    let expected: Vec<&str> =
+                        event_schema.fields.iter().map(|f| f.name.as_str()).collect();
+                    return Err(format!(
+                        "event '{}' has no field '{}' (expected: {})",
+                        event_type,
+                        key,
+                        if expected.is_empty() { "no fields".to_string() } else { expected.join(", ") }
+                    ));
+                }
+                Some(field) if !field.field_type.accepts(value) => {
+                    return Err(format!(
+                        "event '{}' field '{}' expects {}, got {}",
+                        event_type,
+                        key,
+                        field.field_type.as_str(),
+                        json_type_name(value)
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "nil",
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => "table",
+    }
+}
+
 /// Central event bus with ring buffer logging.
 pub struct EventBus {
     /// Listeners keyed by event type. Each listener gets an ID.
@@ -78,6 +199,23 @@ impl EventBus {
         }
     }
 
+    /// The loaded event schema, if any.
+    pub fn schema(&self) -> Option<&EventSchema> {
+        self.schema.as_ref()
+    }
+
+    /// Validate an event payload against the loaded schema (no-op without a schema).
+    pub fn validate(
+        &self,
+        event_type: &str,
+        data: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), String> {
+        match &self.schema {
+            Some(schema) => schema.validate(event_type, data),
+            None => Ok(()),
+        }
+    }
+
     /// Enable file logging.
     pub fn enable_file_logging(&mut self, path: std::path::PathBuf) {
         self.log_file = Some(path);
@@ -89,11 +227,11 @@ impl EventBus {
         if let Some(schema) = &self.schema {
             if let Some(event_schema) = schema.events.get(event_type) {
                 for required_field in &event_schema.fields {
-                    if !data.contains_key(required_field) {
+                    if !data.contains_key(&required_field.name) {
                         tracing::warn!(
                             "Event '{}' missing required field '{}' per schema",
                             event_type,
-                            required_field
+                            required_field.name
                         );
                     }
                 }
@@ -248,4 +386,36 @@ mod tests {
         bus.flush();
         assert_eq!(*received.lock().unwrap(), 1); // Still 1, listener was removed
     }
+
+    #[test]
+    fn test_schema_typed_fields() {
+        let yaml = r#"
+events:
+  player.damaged:
+    fields:
+      - amount: number
+      - source
+"#;
+        let schema: EventSchema = serde_yaml::from_str(yaml).unwrap();
+        let event = &schema.events["player.damaged"];
+        assert_eq!(event.field("amount").unwrap().field_type, EventFieldType::Number);
+        assert_eq!(event.field("source").unwrap().field_type, EventFieldType::Any);
+
+        let mut data = HashMap::new();
+        data.insert("amount".to_string(), serde_json::json!(5));
+        data.insert("source".to_string(), serde_json::json!("lava"));
+        assert!(schema.validate("player.damaged", &data).is_ok());
+
+        data.insert("amount".to_string(), serde_json::json!("five"));
+        let err = schema.validate("player.damaged", &data).unwrap_err();
+        assert!(err.contains("expects number"));
+
+        data.remove("amount");
+        data.insert("amout".to_string(), serde_json::json!(5));
+        let err = schema.validate("player.damaged", &data).unwrap_err();
+        assert!(err.contains("no field 'amout'"));
+
+        // Events without a schema entry are unrestricted
+        assert!(schema.validate("custom.event", &data).is_ok());
+    }
 }
@@ -1,6 +1,8 @@
 pub mod animation;
 pub mod components;
+pub mod curve;
 pub mod events;
+pub mod format;
 pub mod scene;
 pub mod transform;
 pub mod tween;
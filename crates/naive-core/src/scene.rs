@@ -2,12 +2,17 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::curve::CurveDef;
+use crate::format::SCENE_FORMAT;
+
 #[derive(Debug)]
 pub enum SceneError {
     IoError(std::io::Error),
     ParseError(serde_yaml::Error),
     InheritanceCycle(String),
     MissingParent(String),
+    /// A scene from a newer engine, or one that couldn't be migrated.
+    Format(String),
 }
 
 impl std::fmt::Display for SceneError {
@@ -17,6 +22,7 @@ impl std::fmt::Display for SceneError {
             Self::ParseError(e) => write!(f, "YAML parse error: {}", e),
             Self::InheritanceCycle(id) => write!(f, "Inheritance cycle detected at entity '{}'", id),
             Self::MissingParent(id) => write!(f, "Entity extends missing parent '{}'", id),
+            Self::Format(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -25,6 +31,9 @@ impl std::fmt::Display for SceneError {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SceneFile {
+    /// Format version (`SCENE_FORMAT`); older scenes are migrated on load.
+    #[serde(default = "current_scene_version")]
+    pub version: u32,
     pub name: String,
     #[serde(default)]
     pub settings: SceneSettings,
@@ -40,6 +49,49 @@ pub struct SceneSettings {
     pub fog: Option<FogSettings>,
     #[serde(default = "default_gravity")]
     pub gravity: [f32; 3],
+    /// Fixed physics steps per second; 0 steps once per frame.
+    #[serde(default)]
+    pub physics_rate: f32,
+    /// Values for the scripts' read-only `config` table, layered over the
+    /// project's `config:`.
+    #[serde(default, skip_serializing_if = "serde_yaml::Mapping::is_empty")]
+    pub config: serde_yaml::Mapping,
+    /// Replaces the pipeline's tonemap grading while this scene is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_grading: Option<ColorGradingSettings>,
+}
+
+/// Color grading applied by the tonemap pass (`grading:` on the pass, or
+/// `settings.color_grading` in a scene).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ColorGradingSettings {
+    /// 3D LUT, relative to the project: a `.cube` file or a strip PNG
+    /// (N*N wide, N high, red across each tile, blue across tiles, green down).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lut: Option<String>,
+    /// Linear multiplier on the HDR color before tonemapping.
+    #[serde(default = "default_one")]
+    pub exposure: f32,
+    /// Contrast around mid-grey after tonemapping (1 = unchanged).
+    #[serde(default = "default_one")]
+    pub contrast: f32,
+    /// Saturation after tonemapping (0 = greyscale, 1 = unchanged).
+    #[serde(default = "default_one")]
+    pub saturation: f32,
+}
+
+impl Default for ColorGradingSettings {
+    fn default() -> Self {
+        Self { lut: None, exposure: 1.0, contrast: 1.0, saturation: 1.0 }
+    }
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn current_scene_version() -> u32 {
+    SCENE_FORMAT.current
 }
 
 fn default_ambient() -> [f32; 3] {
@@ -84,6 +136,18 @@ pub struct ComponentMap {
     #[serde(default)]
     pub directional_light: Option<DirectionalLightDef>,
     #[serde(default)]
+    pub spot_light: Option<SpotLightDef>,
+    #[serde(default)]
+    pub skybox: Option<SkyboxDef>,
+    #[serde(default)]
+    pub portal: Option<PortalDef>,
+    #[serde(default)]
+    pub water: Option<WaterDef>,
+    #[serde(default)]
+    pub foliage: Option<FoliageDef>,
+    #[serde(default)]
+    pub terrain: Option<TerrainDef>,
+    #[serde(default)]
     pub gaussian_splat: Option<GaussianSplatDef>,
     #[serde(default)]
     pub rigid_body: Option<RigidBodyDef>,
@@ -101,6 +165,12 @@ pub struct ComponentMap {
     pub particle_emitter: Option<ParticleEmitterDef>,
     #[serde(default)]
     pub animator: Option<AnimatorDef>,
+    #[serde(default)]
+    pub time_scale: Option<TimeScaleDef>,
+    #[serde(default)]
+    pub lifetime: Option<LifetimeDef>,
+    #[serde(default)]
+    pub flock: Option<FlockDef>,
     /// Absorbs unknown component types for forward compatibility.
     #[serde(flatten)]
     pub extra: HashMap<String, serde_yaml::Value>,
@@ -128,12 +198,46 @@ pub struct MeshRendererDef {
     pub cast_shadows: bool,
     #[serde(default = "default_true")]
     pub receive_shadows: bool,
+    /// Lower-detail meshes swapped in with distance or size on screen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lod: Option<LodDef>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+/// Level-of-detail levels of a mesh renderer, from most to least detailed.
+/// `mesh` is level 0.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LodDef {
+    pub levels: Vec<LodLevelDef>,
+    /// How far past a threshold the entity must come back before a more
+    /// detailed level returns, as a fraction of the threshold.
+    #[serde(default = "default_lod_hysteresis")]
+    pub hysteresis: f32,
+}
+
+/// One lower-detail level: an authored `mesh`, or the base mesh decimated to
+/// `ratio` of its triangles at load. Used beyond `distance` meters from the
+/// camera, or once the mesh covers less than `screen_size` of the screen
+/// height.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LodLevelDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_size: Option<f32>,
+}
+
+fn default_lod_hysteresis() -> f32 {
+    0.1
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CameraDef {
     #[serde(default = "default_fov")]
@@ -152,6 +256,24 @@ pub struct CameraDef {
     pub height_offset: f32,
     #[serde(default)]
     pub pitch_limits: Option<[f32; 2]>,
+    /// Third person: seconds to ease toward the orbit position (0 snaps).
+    #[serde(default)]
+    pub position_damping: f32,
+    /// Third person: seconds to ease the rotation toward the player (0 snaps).
+    #[serde(default)]
+    pub rotation_damping: f32,
+    /// Third person: seconds of the player's movement the camera leads by.
+    #[serde(default)]
+    pub look_ahead: f32,
+    /// Third person: farthest the camera leads, in meters.
+    #[serde(default = "default_look_ahead_max")]
+    pub look_ahead_max: f32,
+    /// `perspective` or `orthographic`.
+    #[serde(default = "default_projection")]
+    pub projection: String,
+    /// Height of an orthographic camera's view in world units.
+    #[serde(default = "default_ortho_size")]
+    pub size: f32,
 }
 
 fn default_fov() -> f32 {
@@ -175,6 +297,15 @@ fn default_camera_distance() -> f32 {
 fn default_camera_height_offset() -> f32 {
     1.5
 }
+fn default_look_ahead_max() -> f32 {
+    2.0
+}
+fn default_projection() -> String {
+    "perspective".to_string()
+}
+fn default_ortho_size() -> f32 {
+    10.0
+}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PointLightDef {
@@ -184,6 +315,21 @@ pub struct PointLightDef {
     pub intensity: f32,
     #[serde(default = "default_range")]
     pub range: f32,
+    /// Render a cube shadow map for this light (a few per frame at most).
+    #[serde(default)]
+    pub casts_shadows: bool,
+    /// Scale `intensity` by a looping curve (torches, failing neon).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flicker: Option<LightFlickerDef>,
+}
+
+/// Light flicker: `intensity` is multiplied by the curve's value, played in
+/// a loop at `speed` times its own time.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LightFlickerDef {
+    pub curve: CurveDef,
+    #[serde(default = "default_flicker_speed")]
+    pub speed: f32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -198,6 +344,282 @@ pub struct DirectionalLightDef {
     pub shadow_extent: f32,
 }
 
+/// Spot light: a point light limited to a cone around `direction` (in the
+/// entity's local space, so it turns with the transform). Angles are the
+/// half-angles in degrees where the falloff starts and where it reaches zero.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SpotLightDef {
+    #[serde(default = "default_white")]
+    pub color: [f32; 3],
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+    #[serde(default = "default_range")]
+    pub range: f32,
+    #[serde(default = "default_spot_direction")]
+    pub direction: [f32; 3],
+    #[serde(default = "default_spot_inner_angle")]
+    pub inner_angle: f32,
+    #[serde(default = "default_spot_outer_angle")]
+    pub outer_angle: f32,
+}
+
+/// Skybox component: either `texture` (equirectangular) or `cubemap` (six
+/// faces, +X -X +Y -Y +Z -Z).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SkyboxDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cubemap: Option<Vec<String>>,
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+}
+
+/// Portal component: the entity's mesh shows the view through `target`
+/// (another portal's entity id), or a reflection when `target` is unset.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PortalDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Portal view size as a fraction of the window.
+    #[serde(default = "default_portal_resolution")]
+    pub resolution: f32,
+    /// How many nested views of this portal are visible through itself.
+    #[serde(default = "default_portal_recursion")]
+    pub max_recursion: u32,
+}
+
+/// Water component: a surface drawn by the pipeline's `water` pass that also
+/// floats dynamic bodies. `mesh` is the surface (a unit plane by default,
+/// sized by the transform's scale).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WaterDef {
+    #[serde(default = "default_water_mesh")]
+    pub mesh: String,
+    #[serde(default = "default_water_shallow")]
+    pub shallow_color: [f32; 3],
+    #[serde(default = "default_water_deep")]
+    pub deep_color: [f32; 3],
+    /// Meters of water before the color is fully `deep_color`.
+    #[serde(default = "default_water_depth_fade")]
+    pub depth_fade: f32,
+    /// Meters of water over which the edge fades in at the shoreline.
+    #[serde(default = "default_water_shore_fade")]
+    pub shore_fade: f32,
+    /// Wavelength multiplier of the animated normals.
+    #[serde(default = "default_one")]
+    pub wave_scale: f32,
+    #[serde(default = "default_one")]
+    pub wave_speed: f32,
+    /// How strongly the waves bend normals (0 = flat).
+    #[serde(default = "default_water_wave_strength")]
+    pub wave_strength: f32,
+    /// Screen-space distortion of the refracted scene.
+    #[serde(default = "default_water_refraction")]
+    pub refraction: f32,
+    /// `ssr` (screen-space, sky where it misses), `sky` or `none`.
+    #[serde(default = "default_water_reflection")]
+    pub reflection: String,
+    /// Upward force on a fully submerged body, as a multiple of its weight.
+    #[serde(default = "default_water_buoyancy")]
+    pub buoyancy: f32,
+    /// Velocity damping of submerged bodies, per second.
+    #[serde(default = "default_one")]
+    pub drag: f32,
+    /// Depth of the buoyancy volume below the surface.
+    #[serde(default = "default_water_volume_depth")]
+    pub depth: f32,
+}
+
+fn default_water_mesh() -> String {
+    "procedural:plane".to_string()
+}
+fn default_water_shallow() -> [f32; 3] {
+    [0.1, 0.45, 0.5]
+}
+fn default_water_deep() -> [f32; 3] {
+    [0.02, 0.08, 0.15]
+}
+fn default_water_depth_fade() -> f32 {
+    4.0
+}
+fn default_water_shore_fade() -> f32 {
+    0.3
+}
+fn default_water_wave_strength() -> f32 {
+    0.3
+}
+fn default_water_refraction() -> f32 {
+    0.03
+}
+fn default_water_reflection() -> String {
+    "ssr".to_string()
+}
+fn default_water_buoyancy() -> f32 {
+    1.5
+}
+fn default_water_volume_depth() -> f32 {
+    10.0
+}
+
+/// Foliage component: instances of `mesh` scattered over the unit square of
+/// the entity's local XZ plane (sized by the transform's scale) and dropped
+/// onto the colliders below, drawn instanced after the geometry pass.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FoliageDef {
+    #[serde(default = "default_foliage_mesh")]
+    pub mesh: String,
+    /// Instances per square meter at the `high` quality preset.
+    #[serde(default = "default_foliage_density")]
+    pub density: f32,
+    /// Grayscale image stretched over the square; brightness scales density.
+    #[serde(default)]
+    pub density_map: Option<String>,
+    #[serde(default = "default_foliage_max_instances")]
+    pub max_instances: u32,
+    #[serde(default)]
+    pub seed: u64,
+    /// Drop instances onto the first collider below the square, discarding
+    /// those with nothing under them.
+    #[serde(default = "default_true")]
+    pub snap_to_ground: bool,
+    /// Only snap onto this entity's colliders.
+    #[serde(default)]
+    pub surface: Option<String>,
+    /// Random uniform scale range per instance.
+    #[serde(default = "default_foliage_scale")]
+    pub scale: [f32; 2],
+    #[serde(default = "default_foliage_base_color")]
+    pub base_color: [f32; 3],
+    #[serde(default = "default_foliage_tip_color")]
+    pub tip_color: [f32; 3],
+    #[serde(default = "default_foliage_wind_strength")]
+    pub wind_strength: f32,
+    #[serde(default = "default_one")]
+    pub wind_speed: f32,
+    /// Wind direction in the world XZ plane.
+    #[serde(default = "default_foliage_wind_direction")]
+    pub wind_direction: [f32; 2],
+    /// Camera distance where instances start shrinking away.
+    #[serde(default = "default_foliage_fade_start")]
+    pub fade_start: f32,
+    /// Camera distance past which instances aren't drawn.
+    #[serde(default = "default_foliage_fade_end")]
+    pub fade_end: f32,
+}
+
+fn default_foliage_mesh() -> String {
+    "procedural:grass".to_string()
+}
+fn default_foliage_density() -> f32 {
+    20.0
+}
+fn default_foliage_max_instances() -> u32 {
+    65536
+}
+fn default_foliage_scale() -> [f32; 2] {
+    [0.3, 0.6]
+}
+fn default_foliage_base_color() -> [f32; 3] {
+    [0.05, 0.15, 0.03]
+}
+fn default_foliage_tip_color() -> [f32; 3] {
+    [0.3, 0.5, 0.12]
+}
+fn default_foliage_wind_strength() -> f32 {
+    0.15
+}
+fn default_foliage_wind_direction() -> [f32; 2] {
+    [1.0, 0.0]
+}
+fn default_foliage_fade_start() -> f32 {
+    30.0
+}
+fn default_foliage_fade_end() -> f32 {
+    40.0
+}
+
+/// Terrain component: a heightmap stretched over `size` of the entity's
+/// local XZ plane (centered on the entity), drawn in chunks that coarsen with
+/// distance and shaded by blending up to four layers with a splat map. A
+/// static heightfield collider is built from the same grid.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TerrainDef {
+    /// Grayscale image (8 or 16 bit); black is height 0, white is `height`.
+    pub heightmap: String,
+    /// Extent along local X and Z.
+    #[serde(default = "default_terrain_size")]
+    pub size: [f32; 2],
+    #[serde(default = "default_terrain_height")]
+    pub height: f32,
+    /// Chunks per side; each is culled and switches detail on its own.
+    #[serde(default = "default_terrain_chunks")]
+    pub chunks: u32,
+    /// Grid quads per chunk side at full detail (rounded up to a power of two).
+    #[serde(default = "default_terrain_chunk_resolution")]
+    pub chunk_resolution: u32,
+    /// Camera distances past which a chunk drops a level, halving its grid.
+    #[serde(default = "default_terrain_lod_distances")]
+    pub lod_distances: Vec<f32>,
+    /// RGBA image whose channels weight layers 0-3; without one, layer 0 covers everything.
+    #[serde(default)]
+    pub splat_map: Option<String>,
+    /// Up to four surface layers.
+    #[serde(default)]
+    pub layers: Vec<TerrainLayerDef>,
+    /// Build a static heightfield collider (skipped when the entity has a `collider`).
+    #[serde(default = "default_true")]
+    pub collider: bool,
+    #[serde(default = "default_friction")]
+    pub friction: f32,
+}
+
+/// One splat-blended terrain layer: a tiled texture tinted by `color`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TerrainLayerDef {
+    #[serde(default)]
+    pub texture: Option<String>,
+    #[serde(default = "default_terrain_layer_color")]
+    pub color: [f32; 3],
+    /// Meters covered by one repeat of the texture.
+    #[serde(default = "default_terrain_tile_size")]
+    pub tile_size: f32,
+    #[serde(default = "default_terrain_roughness")]
+    pub roughness: f32,
+}
+
+fn default_terrain_size() -> [f32; 2] {
+    [256.0, 256.0]
+}
+fn default_terrain_height() -> f32 {
+    32.0
+}
+fn default_terrain_chunks() -> u32 {
+    8
+}
+fn default_terrain_chunk_resolution() -> u32 {
+    32
+}
+fn default_terrain_lod_distances() -> Vec<f32> {
+    vec![64.0, 128.0, 256.0]
+}
+fn default_terrain_layer_color() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+fn default_terrain_tile_size() -> f32 {
+    4.0
+}
+fn default_terrain_roughness() -> f32 {
+    0.9
+}
+
+fn default_portal_resolution() -> f32 {
+    0.5
+}
+fn default_portal_recursion() -> u32 {
+    2
+}
+
 fn default_dir_direction() -> [f32; 3] {
     [0.3, -1.0, 0.5]
 }
@@ -208,6 +630,43 @@ fn default_shadow_extent() -> f32 {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GaussianSplatDef {
     pub source: String,
+    /// Camera distance past which the cloud is subsampled, drawing a share
+    /// of its splats that falls with the square of the distance. Without
+    /// one every splat is drawn.
+    #[serde(default)]
+    pub lod_distance: Option<f32>,
+    /// Smallest share of splats drawn, however far away.
+    #[serde(default = "default_splat_min_detail")]
+    pub min_detail: f32,
+    /// Multiplier on every splat's opacity.
+    #[serde(default = "default_one")]
+    pub opacity: f32,
+    /// Crop volumes in the cloud's local space, to trim stray splats.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crop: Vec<SplatCropDef>,
+}
+
+/// A `gaussian_splat.crop` volume: splats outside every kept volume, or
+/// inside one with `invert`, are not drawn.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SplatCropDef {
+    /// "box" or "ellipsoid".
+    #[serde(default = "default_splat_crop_shape")]
+    pub shape: String,
+    #[serde(default)]
+    pub center: [f32; 3],
+    /// Full size along each local axis.
+    pub size: [f32; 3],
+    #[serde(default)]
+    pub invert: bool,
+}
+
+fn default_splat_min_detail() -> f32 {
+    0.1
+}
+
+fn default_splat_crop_shape() -> String {
+    "box".to_string()
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -218,6 +677,10 @@ pub struct RigidBodyDef {
     pub mass: f32,
     #[serde(default)]
     pub ccd: bool,
+    /// Ease the transform between fixed physics steps instead of snapping
+    /// to the latest one (only with `settings.physics_rate`).
+    #[serde(default = "default_true")]
+    pub interpolate: bool,
 }
 
 fn default_body_type() -> String {
@@ -270,6 +733,11 @@ pub struct CharacterControllerDef {
     pub height: f32,
     #[serde(default = "default_player_radius")]
     pub radius: f32,
+    /// Maximum force applied to dynamic bodies the character walks into.
+    #[serde(default = "default_push_force")]
+    pub push_force: f32,
+    #[serde(default = "default_character_mass")]
+    pub mass: f32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -293,9 +761,14 @@ pub struct ScriptDef {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ParticleEmitterDef {
+    /// Name of an `effects/<name>.yaml` preset. The preset supplies every
+    /// parameter; only `enabled` is taken from here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
     #[serde(default = "default_max_particles")]
     pub max_particles: u32,
-    #[serde(default = "default_spawn_rate")]
+    /// Particles per second.
+    #[serde(default = "default_spawn_rate", alias = "rate")]
     pub spawn_rate: f32,
     #[serde(default = "default_lifetime")]
     pub lifetime: [f32; 2],
@@ -311,12 +784,80 @@ pub struct ParticleEmitterDef {
     pub color_start: [f32; 4],
     #[serde(default = "default_color_transparent")]
     pub color_end: [f32; 4],
+    /// `[t, r, g, b, a]` keys over the particle's life (t from 0 to 1);
+    /// replaces `color_start`/`color_end` when given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub color_over_life: Vec<[f32; 5]>,
+    /// `[t, size]` keys over the particle's life; replaces `size` when given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub size_over_life: Vec<[f32; 2]>,
+    /// Size over the particle's life from a curve (a `curves/` name or an
+    /// inline curve); replaces `size` and `size_over_life`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_curve: Option<CurveDef>,
+    /// Alpha over the particle's life from a curve.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha_curve: Option<CurveDef>,
     #[serde(default)]
     pub gravity_scale: f32,
+    /// `alpha` (default) or `additive`.
+    #[serde(default = "default_particle_blend")]
+    pub blend: String,
+    /// Distance in meters over which particles fade out before touching
+    /// the surface behind them; 0 for hard edges.
+    #[serde(default = "default_particle_softness")]
+    pub softness: f32,
+    /// `none` (default), `bounce` or `kill`: what particles do when they
+    /// hit a physics collider.
+    #[serde(default = "default_particle_collision")]
+    pub collision: String,
+    /// Share of the speed into a surface kept on a bounce.
+    #[serde(default = "default_particle_bounce")]
+    pub bounce: f32,
     #[serde(default = "default_true")]
     pub enabled: bool,
 }
 
+/// Per-entity time scale (slow motion / bullet time).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TimeScaleDef {
+    #[serde(default = "default_time_scale")]
+    pub scale: f32,
+}
+
+/// Lifetime component: despawns the entity once any of its rules fires.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct LifetimeDef {
+    /// Seconds of (entity-scaled) game time before despawning.
+    #[serde(default)]
+    pub seconds: Option<f32>,
+    /// Condition expression, e.g. `"position.y < -50 or health <= 0"`.
+    #[serde(default)]
+    pub condition: Option<String>,
+    /// Despawn when farther than this from every player.
+    #[serde(default)]
+    pub despawn_distance: Option<f32>,
+}
+
+/// Flock component: boids steering with the other agents of its group.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FlockDef {
+    /// Agents only flock with their own group; weights are set per group.
+    #[serde(default = "default_flock_group")]
+    pub group: String,
+    /// Overrides the group's max speed for this agent.
+    #[serde(default)]
+    pub max_speed: Option<f32>,
+    /// Steer in the XZ plane only (ground creatures).
+    #[serde(default = "default_true")]
+    pub planar: bool,
+    /// Turn the entity to face where it's heading.
+    #[serde(default = "default_true")]
+    pub face_velocity: bool,
+}
+
+fn default_flock_group() -> String { "default".to_string() }
+
 /// Animator component: enables skeletal animation on a skinned mesh.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AnimatorDef {
@@ -333,6 +874,7 @@ pub struct AnimatorDef {
 
 fn default_anim_state() -> String { "idle".to_string() }
 fn default_anim_speed() -> f32 { 1.0 }
+fn default_time_scale() -> f32 { 1.0 }
 
 fn default_max_particles() -> u32 { 100 }
 fn default_spawn_rate() -> f32 { 10.0 }
@@ -343,6 +885,10 @@ fn default_spread() -> f32 { 30.0 }
 fn default_particle_size() -> [f32; 2] { [0.2, 0.05] }
 fn default_color_white() -> [f32; 4] { [1.0, 1.0, 1.0, 1.0] }
 fn default_color_transparent() -> [f32; 4] { [1.0, 1.0, 1.0, 0.0] }
+fn default_particle_blend() -> String { "alpha".to_string() }
+fn default_particle_softness() -> f32 { 0.25 }
+fn default_particle_collision() -> String { "none".to_string() }
+fn default_particle_bounce() -> f32 { 0.4 }
 
 fn default_move_speed() -> f32 { 5.0 }
 fn default_sprint_multiplier() -> f32 { 1.8 }
@@ -350,6 +896,8 @@ fn default_jump_impulse() -> f32 { 7.0 }
 fn default_step_height() -> f32 { 0.3 }
 fn default_player_height() -> f32 { 1.8 }
 fn default_player_radius() -> f32 { 0.3 }
+fn default_push_force() -> f32 { 400.0 }
+fn default_character_mass() -> f32 { 80.0 }
 
 fn default_white() -> [f32; 3] {
     [1.0, 1.0, 1.0]
@@ -357,22 +905,45 @@ fn default_white() -> [f32; 3] {
 fn default_intensity() -> f32 {
     1.0
 }
+fn default_flicker_speed() -> f32 {
+    1.0
+}
+
 fn default_range() -> f32 {
     10.0
 }
+fn default_spot_direction() -> [f32; 3] {
+    [0.0, -1.0, 0.0]
+}
+fn default_spot_inner_angle() -> f32 {
+    20.0
+}
+fn default_spot_outer_angle() -> f32 {
+    30.0
+}
 
-/// Load and parse a scene YAML file, resolving entity inheritance.
-/// Parse a scene from a YAML string.
+/// Parse a scene from a YAML string, migrating an older format.
 pub fn parse_scene(yaml: &str) -> Result<SceneFile, String> {
-    let mut scene: SceneFile =
-        serde_yaml::from_str(yaml).map_err(|e| format!("YAML parse error: {}", e))?;
+    let mut scene: SceneFile = match SCENE_FORMAT.migrate(yaml)? {
+        Some(doc) => serde_yaml::from_value(doc),
+        None => serde_yaml::from_str(yaml),
+    }
+    .map_err(|e| format!("YAML parse error: {}", e))?;
     scene.entities = resolve_inheritance(&scene.entities).map_err(|e| format!("{}", e))?;
     Ok(scene)
 }
 
+/// Load and parse a scene YAML file, migrating an older format and
+/// resolving entity inheritance.
 pub fn load_scene(path: &Path) -> Result<SceneFile, SceneError> {
     let contents = std::fs::read_to_string(path).map_err(SceneError::IoError)?;
-    let mut scene: SceneFile = serde_yaml::from_str(&contents).map_err(SceneError::ParseError)?;
+    let migrated = SCENE_FORMAT
+        .migrate(&contents)
+        .map_err(|e| SceneError::Format(format!("{}: {}", path.display(), e)))?;
+    let mut scene: SceneFile = match migrated {
+        Some(doc) => serde_yaml::from_value(doc).map_err(SceneError::ParseError)?,
+        None => serde_yaml::from_str(&contents).map_err(SceneError::ParseError)?,
+    };
     scene.entities = resolve_inheritance(&scene.entities)?;
     Ok(scene)
 }
@@ -407,7 +978,7 @@ fn resolve_inheritance(entities: &[EntityDef]) -> Result<Vec<EntityDef>, SceneEr
 }
 
 /// Merge parent entity components into child. Child fields win.
-fn merge_entity(parent: &EntityDef, child: &EntityDef) -> EntityDef {
+pub fn merge_entity(parent: &EntityDef, child: &EntityDef) -> EntityDef {
     let mut merged = child.clone();
     merged.extends = None; // resolved
 
@@ -427,6 +998,24 @@ fn merge_entity(parent: &EntityDef, child: &EntityDef) -> EntityDef {
     if merged.components.directional_light.is_none() {
         merged.components.directional_light = parent.components.directional_light.clone();
     }
+    if merged.components.spot_light.is_none() {
+        merged.components.spot_light = parent.components.spot_light.clone();
+    }
+    if merged.components.skybox.is_none() {
+        merged.components.skybox = parent.components.skybox.clone();
+    }
+    if merged.components.portal.is_none() {
+        merged.components.portal = parent.components.portal.clone();
+    }
+    if merged.components.water.is_none() {
+        merged.components.water = parent.components.water.clone();
+    }
+    if merged.components.foliage.is_none() {
+        merged.components.foliage = parent.components.foliage.clone();
+    }
+    if merged.components.terrain.is_none() {
+        merged.components.terrain = parent.components.terrain.clone();
+    }
     if merged.components.gaussian_splat.is_none() {
         merged.components.gaussian_splat = parent.components.gaussian_splat.clone();
     }
@@ -451,6 +1040,15 @@ fn merge_entity(parent: &EntityDef, child: &EntityDef) -> EntityDef {
     if merged.components.particle_emitter.is_none() {
         merged.components.particle_emitter = parent.components.particle_emitter.clone();
     }
+    if merged.components.time_scale.is_none() {
+        merged.components.time_scale = parent.components.time_scale.clone();
+    }
+    if merged.components.lifetime.is_none() {
+        merged.components.lifetime = parent.components.lifetime.clone();
+    }
+    if merged.components.flock.is_none() {
+        merged.components.flock = parent.components.flock.clone();
+    }
 
     // Merge extra components from parent that child doesn't have
     for (key, value) in &parent.components.extra {
@@ -511,6 +1109,61 @@ entities:
         assert!(scene.entities[2].components.point_light.is_some());
     }
 
+    #[test]
+    fn test_spot_light_defaults() {
+        let yaml = r#"
+name: "Spot"
+entities:
+  - id: lamp
+    components:
+      spot_light:
+        intensity: 4.0
+        outer_angle: 45
+"#;
+        let scene: SceneFile = serde_yaml::from_str(yaml).unwrap();
+        let spot = scene.entities[0].components.spot_light.as_ref().unwrap();
+        assert_eq!(spot.intensity, 4.0);
+        assert_eq!(spot.direction, [0.0, -1.0, 0.0]);
+        assert_eq!(spot.inner_angle, 20.0);
+        assert_eq!(spot.outer_angle, 45.0);
+    }
+
+    #[test]
+    fn test_scene_format_version() {
+        let scene = parse_scene("name: Old\n").unwrap();
+        assert_eq!(scene.version, SCENE_FORMAT.current);
+        assert!(serde_yaml::to_string(&scene).unwrap().starts_with("version: 1\n"));
+
+        let err = parse_scene("version: 99\nname: Future\n").unwrap_err();
+        assert!(err.contains("update the engine"), "{}", err);
+    }
+
+    #[test]
+    fn test_mesh_lod_levels() {
+        let yaml = r#"
+name: "Rocks"
+entities:
+  - id: rock
+    components:
+      mesh_renderer:
+        mesh: assets/meshes/rock.glb
+        material: assets/materials/rock.yaml
+        lod:
+          levels:
+            - { mesh: assets/meshes/rock_lod1.glb, distance: 25 }
+            - { ratio: 0.1, screen_size: 0.02 }
+"#;
+        let scene: SceneFile = serde_yaml::from_str(yaml).unwrap();
+        let mr = scene.entities[0].components.mesh_renderer.as_ref().unwrap();
+        let lod = mr.lod.as_ref().unwrap();
+        assert_eq!(lod.hysteresis, 0.1);
+        assert_eq!(lod.levels[0].mesh.as_deref(), Some("assets/meshes/rock_lod1.glb"));
+        assert_eq!(lod.levels[0].distance, Some(25.0));
+        assert_eq!(lod.levels[1].ratio, Some(0.1));
+        assert_eq!(lod.levels[1].screen_size, Some(0.02));
+        assert!(!serde_yaml::to_string(mr).unwrap().contains("ratio: null"));
+    }
+
     #[test]
     fn test_inheritance() {
         let yaml = r#"
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::curve::Curve;
+
 /// Easing functions for tweens.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Easing {
@@ -64,6 +66,8 @@ pub struct Tween {
     pub duration: f32,
     pub elapsed: f32,
     pub easing: Easing,
+    /// Shape of the tween over its duration, used instead of `easing` when set.
+    pub curve: Option<Curve>,
     pub on_complete: Option<String>, // Event to emit when done
 }
 
@@ -84,10 +88,17 @@ impl Tween {
             duration,
             elapsed: 0.0,
             easing,
+            curve: None,
             on_complete: None,
         }
     }
 
+    /// Shape the tween with a curve, evaluated over 0..1 of its key range.
+    pub fn with_curve(mut self, curve: Curve) -> Self {
+        self.curve = Some(curve);
+        self
+    }
+
     /// Update the tween, returning the current interpolated value.
     /// Returns None if the tween is complete.
     pub fn update(&mut self, dt: f32) -> Option<f32> {
@@ -96,7 +107,10 @@ impl Tween {
             return None; // Complete
         }
         let t = self.elapsed / self.duration;
-        let eased = self.easing.apply(t);
+        let eased = match &self.curve {
+            Some(curve) => curve.eval_normalized(t),
+            None => self.easing.apply(t),
+        };
         Some(self.from + (self.to - self.from) * eased)
     }
 
@@ -138,11 +152,20 @@ impl TweenSystem {
 
     /// Update all tweens. Returns completed tween events.
     pub fn update(&mut self, dt: f32) -> Vec<(hecs::Entity, String, f32, Option<String>)> {
+        self.update_scaled(dt, |_| 1.0)
+    }
+
+    /// Update all tweens, advancing each by `dt` times its entity's time scale.
+    pub fn update_scaled(
+        &mut self,
+        dt: f32,
+        entity_scale: impl Fn(hecs::Entity) -> f32,
+    ) -> Vec<(hecs::Entity, String, f32, Option<String>)> {
         let mut results = Vec::new();
         let mut completed_indices = Vec::new();
 
         for (i, tween) in self.tweens.iter_mut().enumerate() {
-            match tween.update(dt) {
+            match tween.update(dt * entity_scale(tween.entity)) {
                 Some(value) => {
                     results.push((tween.entity, tween.property.clone(), value, None));
                 }
@@ -217,6 +240,20 @@ mod tests {
         assert!(val.is_none()); // Complete
     }
 
+    #[test]
+    fn test_tween_curve() {
+        use crate::curve::Interpolation;
+        let world = hecs::World::new();
+        let entity = world.reserve_entity();
+        let curve = Curve::new(vec![[0.0, 0.0], [0.5, 1.0], [1.0, 0.0]], Interpolation::Linear);
+        let mut tween = Tween::new(entity, "y", 0.0, 2.0, 2.0, Easing::Linear).with_curve(curve);
+
+        let val = tween.update(1.0).unwrap();
+        assert!((val - 2.0).abs() < 0.01);
+        let val = tween.update(0.5).unwrap();
+        assert!((val - 1.0).abs() < 0.01);
+    }
+
     #[test]
     fn test_tween_system() {
         let world = hecs::World::new();
@@ -234,4 +271,19 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(system.active_count(), 0); // Completed
     }
+
+    #[test]
+    fn test_tween_system_entity_scale() {
+        let world = hecs::World::new();
+        let slowed = world.reserve_entity();
+        let normal = world.reserve_entity();
+        let mut system = TweenSystem::new();
+        system.add(Tween::new(slowed, "x", 0.0, 10.0, 1.0, Easing::Linear));
+        system.add(Tween::new(normal, "x", 0.0, 10.0, 1.0, Easing::Linear));
+
+        let results = system.update_scaled(0.5, |e| if e == slowed { 0.5 } else { 1.0 });
+        let value_of = |entity| results.iter().find(|r| r.0 == entity).unwrap().2;
+        assert!((value_of(slowed) - 2.5).abs() < 0.01);
+        assert!((value_of(normal) - 5.0).abs() < 0.01);
+    }
 }
@@ -96,6 +96,7 @@ fn handle_tools_call(id: Option<Value>, request: &Value, socket_path: &str) -> V
             copy_field(args, &mut c, "entity_id");
             copy_field(args, &mut c, "components");
             copy_field(args, &mut c, "tags");
+            copy_field(args, &mut c, "extends");
             c
         }
         "naive_destroy_entity" => {
@@ -115,6 +116,11 @@ fn handle_tools_call(id: Option<Value>, request: &Value, socket_path: &str) -> V
             copy_field(args, &mut c, "limit");
             c
         }
+        "naive_get_event_schema" => {
+            let mut c = json!({"cmd": "get_event_schema"});
+            copy_field(args, &mut c, "event_type");
+            c
+        }
         "naive_inject_input" => {
             let mut c = json!({"cmd": "inject_input"});
             copy_field(args, &mut c, "action");
@@ -124,6 +130,14 @@ fn handle_tools_call(id: Option<Value>, request: &Value, socket_path: &str) -> V
             copy_field(args, &mut c, "dy");
             c
         }
+        "naive_get_bindings" => json!({"cmd": "get_bindings"}),
+        "naive_rebind" => {
+            let mut c = json!({"cmd": "rebind"});
+            copy_field(args, &mut c, "action");
+            copy_field(args, &mut c, "triggers");
+            c
+        }
+        "naive_save_bindings" => json!({"cmd": "save_bindings"}),
         "naive_runtime_control" => {
             let mut c = json!({"cmd": "runtime_control"});
             copy_field(args, &mut c, "action");
@@ -144,6 +158,30 @@ fn handle_tools_call(id: Option<Value>, request: &Value, socket_path: &str) -> V
             c
         }
         "naive_editor_status" => json!({"cmd": "editor_status"}),
+        "naive_get_gpu_timings" => json!({"cmd": "get_gpu_timings"}),
+        "naive_get_gpu_memory" => json!({"cmd": "get_gpu_memory"}),
+        "naive_get_script_watchdog" => json!({"cmd": "get_script_watchdog"}),
+        "naive_get_script_memory" => json!({"cmd": "get_script_memory"}),
+        "naive_capture_screenshot" => {
+            let mut c = json!({"cmd": "capture_screenshot"});
+            copy_field(args, &mut c, "path");
+            c
+        }
+        "naive_open_window" => {
+            let mut c = json!({"cmd": "open_window"});
+            copy_field(args, &mut c, "kind");
+            c
+        }
+        "naive_close_window" => {
+            let mut c = json!({"cmd": "close_window"});
+            copy_field(args, &mut c, "kind");
+            c
+        }
+        "naive_switch_pipeline" => {
+            let mut c = json!({"cmd": "switch_pipeline"});
+            copy_field(args, &mut c, "name");
+            c
+        }
         "naive_run_lua" => {
             let mut c = json!({"cmd": "run_lua"});
             copy_field(args, &mut c, "code");
@@ -364,13 +402,14 @@ fn tool_definitions() -> Vec<Value> {
         }),
         json!({
             "name": "naive_spawn_entity",
-            "description": "Spawn a new entity with given components. Supports mesh_renderer for 3D objects (meshes: 'procedural:cube', 'procedural:sphere', or path like 'assets/meshes/model.glb'; materials: 'procedural:default' or path like 'assets/materials/red.yaml'). Supports physics via rigid_body and collider components — spawned objects will fall, bounce, and collide. Also supports transform, point_light, directional_light, and camera components.",
+            "description": "Spawn a new entity with given components. Supports mesh_renderer for 3D objects (meshes: 'procedural:cube', 'procedural:sphere', or path like 'assets/meshes/model.glb'; materials: 'procedural:default' or path like 'assets/materials/red.yaml'). Supports physics via rigid_body and collider components — spawned objects will fall, bounce, and collide. Components use the scene YAML format, so anything a scene entity can have works: transform, point_light, directional_light, camera, character_controller, script, health, collision_damage, particle_emitter, time_scale.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "entity_id": {"type": "string", "description": "Unique ID for the new entity"},
-                    "components": {"type": "object", "description": "Components: transform {position, rotation, scale}, mesh_renderer {mesh, material}, rigid_body {type: 'dynamic'|'static', mass, ccd}, collider {shape: 'box'|'sphere'|'capsule', radius, half_extents: [x,y,z], half_height, restitution, friction, is_trigger}, point_light {color, intensity, range}, camera {fov, near, far, role}"},
-                    "tags": {"type": "array", "items": {"type": "string"}, "description": "Tags for the entity"}
+                    "components": {"type": "object", "description": "Components: transform {position, rotation, scale}, mesh_renderer {mesh, material}, rigid_body {type: 'dynamic'|'static', mass, ccd}, collider {shape: 'box'|'sphere'|'capsule', radius, half_extents: [x,y,z], half_height, restitution, friction, is_trigger}, point_light {color, intensity, range}, camera {fov, near, far, role}, script {source}, health {max, current}, ..."},
+                    "tags": {"type": "array", "items": {"type": "string"}, "description": "Tags for the entity"},
+                    "extends": {"type": "string", "description": "ID of a scene entity to inherit components from; given components override it"}
                 },
                 "required": ["entity_id"]
             }
@@ -410,6 +449,17 @@ fn tool_definitions() -> Vec<Value> {
                 "required": []
             }
         }),
+        json!({
+            "name": "naive_get_event_schema",
+            "description": "Get the event schema (event types, fields and field types) from events/schema.yaml",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "event_type": {"type": "string", "description": "Only return this event type's schema"}
+                },
+                "required": []
+            }
+        }),
         json!({
             "name": "naive_inject_input",
             "description": "Inject synthetic input (keyboard/mouse) into the running game",
@@ -425,6 +475,36 @@ fn tool_definitions() -> Vec<Value> {
                 "required": ["action"]
             }
         }),
+        json!({
+            "name": "naive_get_bindings",
+            "description": "List input actions with their bound keys/mouse buttons, the axes, and any key bound to more than one action",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_rebind",
+            "description": "Rebind an input action live. Returns the conflicts the new binding creates. Lasts until bindings.yaml is reloaded unless saved with naive_save_bindings.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "action": {"type": "string", "description": "Action name (e.g. jump)"},
+                    "triggers": {"type": "array", "items": {"type": "string"}, "description": "Key names (W, Space, ShiftLeft, ...) or mouse buttons (Left, Right, Middle); empty unbinds the action"}
+                },
+                "required": ["action", "triggers"]
+            }
+        }),
+        json!({
+            "name": "naive_save_bindings",
+            "description": "Write the live input bindings to input/bindings.yaml",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
         json!({
             "name": "naive_runtime_control",
             "description": "Control game runtime: pause, resume, or get status",
@@ -479,6 +559,86 @@ fn tool_definitions() -> Vec<Value> {
                 "required": []
             }
         }),
+        json!({
+            "name": "naive_get_gpu_timings",
+            "description": "Get per-pass GPU timings (ms) for the render pipeline, from timestamp queries. Use to find which pass (bloom, lighting, splats) eats the frame budget.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_get_gpu_memory",
+            "description": "Get GPU buffer arena use per class (vertex, index, storage): blocks, capacity, bytes used, allocations and fragmentation, plus compactions run on scene changes. Use to check memory growth over many scene loads.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_get_script_watchdog",
+            "description": "Get Lua script budget status: per-frame instruction/time limits and which entities' scripts were aborted or disabled for running over them (e.g. an infinite loop in update()).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_get_script_memory",
+            "description": "Get Lua memory usage: VM bytes in use against the memory cap, live bytes after the last GC cycle, GC time per frame, and per-entity allocation rates and state table sizes, heaviest allocators first. Use to find scripts that leak or allocate every frame.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_capture_screenshot",
+            "description": "Save the next rendered frame (3D scene plus UI) as a PNG. Relative paths resolve against the project root. Use to visually check a scene after changes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "Output PNG path (default: screenshots/screenshot.png)"}
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_open_window",
+            "description": "Open a tool window next to the game window: 'inspector' lists the scene's entities with their positions, 'profiler' shows frame time, draw stats and per-pass GPU timings. Focuses the window if it is already open.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "kind": {"type": "string", "enum": ["inspector", "profiler"], "description": "Which window to open (default: inspector)"}
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_close_window",
+            "description": "Close a tool window opened with naive_open_window.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "kind": {"type": "string", "enum": ["inspector", "profiler"], "description": "Which window to close (default: inspector)"}
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "naive_switch_pipeline",
+            "description": "Compile another of the project's render pipelines and swap it in without restarting, e.g. 'render_low' for pipelines/render_low.yaml. If it fails to compile the running pipeline stays and the error is returned. The reply lists the available pipelines.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string", "description": "Pipeline name in pipelines/, or a project-relative .yaml path"}
+                },
+                "required": ["name"]
+            }
+        }),
         json!({
             "name": "naive_run_lua",
             "description": "Execute Lua code in the running engine with full API access. Available APIs: entity.spawn_dynamic(), entity.get_position(), entity.set_position(), physics.apply_impulse(), physics.set_velocity(), physics.set_gravity(), particles.spawn_burst(), camera.shake(), scene.find_by_tag(), events.emit(), audio.play(). Use for batch operations, physics manipulation, particle effects, and anything not covered by other tools.",
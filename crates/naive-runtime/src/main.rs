@@ -25,6 +25,15 @@ fn main() {
         // naive run [--scene X]
         Some(naive_client::cli::Command::Run { scene }) => {
             let hud = args.hud;
+            let (headless, frames) = (args.is_headless(), args.frames);
+            let (screenshot_after, screenshot_path) = (args.screenshot_after, args.screenshot_path.clone());
+            let (lua_memory_limit, lua_gc_budget) = (args.lua_memory_limit, args.lua_gc_budget);
+            let config_overrides = args.config.clone();
+            let setting_overrides = args.settings.clone();
+            let (force_lod0, no_culling) = (args.force_lod0, args.no_culling);
+            let recover = args.recover;
+            let color_audit = args.color_audit;
+            let graphics = args.graphics.clone();
             let cwd = std::env::current_dir().expect("Failed to get current directory");
             let args = match naive_client::project_config::find_config(&cwd) {
                 Some(config_path) => {
@@ -43,6 +52,20 @@ fn main() {
                         cli_args.scene = scene.clone();
                     }
                     cli_args.hud = hud;
+                    cli_args.headless = headless;
+                    cli_args.frames = frames;
+                    cli_args.screenshot_after = screenshot_after;
+                    cli_args.screenshot_path = screenshot_path;
+                    // Explicit Lua limits override `scripting:`
+                    cli_args.lua_memory_limit = lua_memory_limit.or(cli_args.lua_memory_limit);
+                    cli_args.lua_gc_budget = lua_gc_budget.or(cli_args.lua_gc_budget);
+                    cli_args.config = config_overrides;
+                    cli_args.settings = setting_overrides;
+                    cli_args.force_lod0 = force_lod0;
+                    cli_args.no_culling = no_culling;
+                    cli_args.recover = recover;
+                    cli_args.color_audit = color_audit;
+                    cli_args.graphics = graphics.or(&cli_args.graphics);
                     cli_args
                 }
                 None => {
@@ -58,15 +81,26 @@ fn main() {
 
         // naive test [test_file]
         Some(naive_client::cli::Command::Test { test_file }) => {
+            let render = args.is_headless().then(|| naive_client::test_runner::RenderOptions {
+                pipeline: args.pipeline.clone(),
+                lod_lock: args.lod_lock(),
+                ..Default::default()
+            });
             match test_file {
                 Some(file) => {
                     let cwd = std::env::current_dir().expect("Failed to get current directory");
-                    let project_root = match naive_client::project_config::find_config(&cwd) {
+                    let config_path = naive_client::project_config::find_config(&cwd);
+                    let project_root = match &config_path {
                         Some(config_path) => config_path.parent().unwrap().to_path_buf(),
                         None => std::path::PathBuf::from(&args.project),
                     };
+                    let configured = config_path
+                        .and_then(|p| naive_client::project_config::load_config(&p).ok())
+                        .map(|config| config.scripting)
+                        .unwrap_or_default();
+                    let scripting = args.scripting_limits(&configured);
                     let test_path = project_root.join(file);
-                    run_single_test(&project_root, &test_path);
+                    run_single_test(&project_root, &test_path, render.as_ref(), &scripting);
                 }
                 None => {
                     let cwd = std::env::current_dir().expect("Failed to get current directory");
@@ -85,6 +119,11 @@ fn main() {
                             std::process::exit(1);
                         }
                     };
+                    let render = render.map(|mut r| {
+                        r.pipeline = r.pipeline.or(config.default_pipeline.clone());
+                        r
+                    });
+                    let scripting = args.scripting_limits(&config.scripting);
                     let test_files = naive_client::project_config::discover_test_files(&config, project_root);
                     if test_files.is_empty() {
                         println!("No test files found.");
@@ -95,7 +134,7 @@ fn main() {
                     let mut total_failed = 0;
                     for test_path in &test_files {
                         println!("--- {} ---", test_path.display());
-                        let results = naive_client::test_runner::run_test_file(project_root, test_path);
+                        let results = naive_client::test_runner::run_test_file(project_root, test_path, render.as_ref(), &scripting);
                         let passed = results.iter().filter(|r| r.passed).count();
                         let failed = results.len() - passed;
                         total_passed += passed;
@@ -112,8 +151,8 @@ fn main() {
             return;
         }
 
-        // naive build [--target X]
-        Some(naive_client::cli::Command::Build { target }) => {
+        // naive build [--target X] [--bytecode]
+        Some(naive_client::cli::Command::Build { target, bytecode }) => {
             let cwd = std::env::current_dir().expect("Failed to get current directory");
             let config_path = match naive_client::project_config::find_config(&cwd) {
                 Some(p) => p,
@@ -123,14 +162,15 @@ fn main() {
                 }
             };
             let project_root = config_path.parent().unwrap();
-            let config = match naive_client::project_config::load_config(&config_path) {
+            let platform = target.as_deref().unwrap_or(naive_client::project_config::current_platform());
+            let config = match naive_client::project_config::load_config_for(&config_path, platform) {
                 Ok(c) => c,
                 Err(e) => {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
                 }
             };
-            if let Err(e) = naive_client::build::bundle_project(&config, project_root, target.as_deref()) {
+            if let Err(e) = naive_client::build::bundle_project(&config, project_root, target.as_deref(), *bytecode) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -204,6 +244,9 @@ fn main() {
                     // In edit mode, only use --scene flag, not project default_scene.
                     // A blank canvas is the default when no --scene is given.
                     cli_args.scene = scene.clone();
+                    cli_args.recover = args.recover;
+                    cli_args.color_audit = args.color_audit;
+                    cli_args.graphics = args.graphics.or(&cli_args.graphics);
                     cli_args
                 }
                 None => {
@@ -304,11 +347,150 @@ fn main() {
             return;
         }
 
+        // naive preview <scene> [--out X] [--camera Y]
+        Some(naive_client::cli::Command::Preview { scene, out, camera, width, height }) => {
+            let cwd = std::env::current_dir().expect("Failed to get current directory");
+            let (project_root, pipeline) = match naive_client::project_config::find_config(&cwd) {
+                Some(config_path) => {
+                    let project_root = config_path.parent().unwrap().to_path_buf();
+                    let pipeline = naive_client::project_config::load_config(&config_path)
+                        .ok()
+                        .and_then(|c| c.default_pipeline);
+                    (project_root, pipeline)
+                }
+                None => (std::path::PathBuf::from(&args.project), None),
+            };
+            let options = naive_client::preview::PreviewOptions {
+                scene: scene.clone(),
+                out: cwd.join(out),
+                camera: camera.clone(),
+                pipeline: args.pipeline.clone().or(pipeline),
+                width: *width,
+                height: *height,
+            };
+            if let Err(e) = naive_client::preview::render_preview(&project_root, &options) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            println!("Preview saved to {}", options.out.display());
+            return;
+        }
+
+        // naive check [--strict] [--budgets]
+        Some(naive_client::cli::Command::Check { strict, budgets }) => {
+            let cwd = std::env::current_dir().expect("Failed to get current directory");
+            let config_path = match naive_client::project_config::find_config(&cwd) {
+                Some(p) => p,
+                None => {
+                    eprintln!("Error: No naive.yaml found. Run from a project directory.");
+                    std::process::exit(1);
+                }
+            };
+            let project_root = config_path.parent().unwrap();
+            let config = match naive_client::project_config::load_config(&config_path) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let report = naive_client::check::check_project(&config, project_root, *budgets);
+            for (scene, budget) in &report.budgets {
+                println!("{}: {}", scene, budget);
+            }
+            for finding in &report.findings {
+                println!("{}", finding);
+            }
+            println!("Checked {} scene(s): {} error(s), {} warning(s).",
+                report.scenes, report.errors(), report.warnings());
+            if report.errors() > 0 || (*strict && report.warnings() > 0) {
+                std::process::exit(1);
+            }
+            return;
+        }
+
+        // naive docs [--out DIR]
+        Some(naive_client::cli::Command::Docs { out }) => {
+            let out_dir = std::env::current_dir().expect("Failed to get current directory").join(out);
+            match naive_client::lua_docs::write_docs(&out_dir) {
+                Ok(files) => {
+                    for file in files {
+                        println!("Wrote {}", file.display());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+
+        // naive splat optimize <inputs...> [--out X] [--decimate F] [--prune-alpha A] [--reorder R]
+        Some(naive_client::cli::Command::Splat {
+            action: naive_client::cli::SplatCommand::Optimize { inputs, out, decimate, prune_alpha, reorder },
+        }) => {
+            let options = naive_client::splat_optimize::OptimizeOptions {
+                prune_alpha: *prune_alpha,
+                decimate: *decimate,
+                order: *reorder,
+            };
+            if let Err(e) = options.validate() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            if out.is_some() && inputs.len() > 1 {
+                eprintln!("Error: --out can only be used with a single input.");
+                std::process::exit(1);
+            }
+            let mut failed = 0;
+            for input in inputs {
+                let input = std::path::Path::new(input);
+                let output = match out {
+                    Some(o) => std::path::PathBuf::from(o),
+                    None => naive_client::splat_optimize::default_output(input),
+                };
+                match naive_client::splat_optimize::optimize_file(input, &output, &options) {
+                    Ok(report) => println!("{} -> {}: {}", input.display(), output.display(), report),
+                    Err(e) => {
+                        eprintln!("{}: {}", input.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                std::process::exit(1);
+            }
+            return;
+        }
+
+        // naive doctor
+        Some(naive_client::cli::Command::Doctor) => {
+            let cwd = std::env::current_dir().expect("Failed to get current directory");
+            let config_graphics = match naive_client::project_config::find_config(&cwd) {
+                Some(config_path) => match naive_client::project_config::load_config(&config_path) {
+                    Ok(c) => {
+                        println!("Project: {} v{} ({})\n", c.name, c.version, config_path.display());
+                        c.graphics
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+                None => Default::default(),
+            };
+            let graphics = args.graphics.or(&config_graphics);
+            print!("{}", naive_client::gpu_options::doctor_report(&graphics));
+            return;
+        }
+
         // naive demo [selector] / naive demos [selector]
         Some(naive_client::cli::Command::Demo { selector })
         | Some(naive_client::cli::Command::Demos { selector }) => {
             match naive_client::demos::run_demo(selector.as_deref()) {
-                Some(demo_args) => {
+                Some(mut demo_args) => {
+                    demo_args.graphics = args.graphics.clone();
                     run_engine(demo_args);
                 }
                 None => {
@@ -338,6 +520,18 @@ fn main() {
                     tracing::info!("Auto-detected project: {} v{}", config.name, config.version);
                     let mut cli_args = naive_client::project_config::to_cli_args(&config, project_root);
                     cli_args.hud = args.hud;
+                    cli_args.headless = args.is_headless();
+                    cli_args.frames = args.frames;
+                    cli_args.screenshot_after = args.screenshot_after;
+                    cli_args.screenshot_path = args.screenshot_path.clone();
+                    cli_args.lua_memory_limit = args.lua_memory_limit.or(cli_args.lua_memory_limit);
+                    cli_args.lua_gc_budget = args.lua_gc_budget.or(cli_args.lua_gc_budget);
+                    cli_args.force_lod0 = args.force_lod0;
+                    cli_args.no_culling = args.no_culling;
+                    cli_args.settings = args.settings.clone();
+                    cli_args.recover = args.recover;
+                    cli_args.color_audit = args.color_audit;
+                    cli_args.graphics = args.graphics.or(&cli_args.graphics);
                     run_engine(cli_args);
                     return;
                 }
@@ -349,13 +543,18 @@ fn main() {
     }
 }
 
-fn run_single_test(project_root: &std::path::Path, test_path: &std::path::Path) {
+fn run_single_test(
+    project_root: &std::path::Path,
+    test_path: &std::path::Path,
+    render: Option<&naive_client::test_runner::RenderOptions>,
+    scripting: &naive_client::project_config::ScriptingConfig,
+) {
     if !test_path.exists() {
         eprintln!("Test file not found: {}", test_path.display());
         std::process::exit(1);
     }
 
-    let results = naive_client::test_runner::run_test_file(project_root, test_path);
+    let results = naive_client::test_runner::run_test_file(project_root, test_path, render, scripting);
 
     let total = results.len();
     let passed = results.iter().filter(|r| r.passed).count();
@@ -372,6 +571,11 @@ fn run_single_test(project_root: &std::path::Path, test_path: &std::path::Path)
 }
 
 fn run_engine(args: CliArgs) {
+    if args.is_headless() {
+        run_headless(args);
+        return;
+    }
+
     let event_loop =
         winit::event_loop::EventLoop::new().expect("Failed to create event loop");
     event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
@@ -382,3 +586,25 @@ fn run_engine(args: CliArgs) {
         .run_app(&mut engine)
         .expect("Event loop error");
 }
+
+/// Run the engine for `--frames` frames into an offscreen target, without creating a window.
+fn run_headless(args: CliArgs) {
+    if args.scene.is_none() {
+        eprintln!("Error: --headless needs a scene (--scene or default_scene in naive.yaml).");
+        std::process::exit(1);
+    }
+    let frames = args.frames;
+    let mut engine = naive_client::engine::Engine::new(args);
+    match engine.run_headless(frames) {
+        Ok(report) => {
+            println!(
+                "Headless run complete: {} frames, {:.2}s game time, {:.2} ms/frame",
+                report.frames, report.game_time, report.avg_frame_ms
+            );
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}